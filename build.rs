@@ -6,6 +6,11 @@ fn main() {
     // Tell cargo to rerun this build script if it changes
     println!("cargo:rerun-if-changed=build.rs");
 
+    // Frontend-only builds (e.g. for wasm32) neither use LLVM nor link the C++ standard library
+    if env::var_os("CARGO_FEATURE_FULL").is_none() {
+        return;
+    }
+
     // Print helpful message about LLVM requirement
     println!("cargo:warning=Yuni Language Compiler requires LLVM 18 to be installed.");
     println!("cargo:warning=On macOS: brew install llvm@18");
@@ -1,10 +1,24 @@
 //! 借用チェッカー
 
+use crate::assertion::AssertKind;
 use crate::ast::{Expression, Pattern, Span, Statement, Type};
+use crate::console::OutputKind;
 
 use super::lifetime::{LifetimeContext, UsageKind};
 use super::symbol::{AnalysisError, AnalysisResult, BorrowInfo, BorrowKind, Scope};
 
+/// 借用規則の違反の扱い（`--borrow-check`）
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum BorrowCheckMode {
+    /// 借用チェックを行わない
+    Off,
+    /// 違反を警告として報告する（解析は成功する）
+    Warn,
+    /// 違反をエラーとして報告する
+    #[default]
+    Error,
+}
+
 /// 借用チェッカー
 pub struct BorrowChecker<'a> {
     /// ライフタイムコンテキスト
@@ -16,6 +30,8 @@ pub struct BorrowChecker<'a> {
 }
 impl<'a> BorrowChecker<'a> {
     pub fn new(lifetime_ctx: &'a mut LifetimeContext, current_scope: &'a mut Scope) -> Self {
+        // 借用チェッカーは関数ごとに作るため、前の関数の借用の記録を引き継がない
+        lifetime_ctx.clear_borrows();
         Self {
             lifetime_ctx,
             current_scope,
@@ -68,10 +84,11 @@ impl<'a> BorrowChecker<'a> {
     fn check_variable_access(&mut self, name: &str, span: &Span) -> AnalysisResult<()> {
         if let Some(symbol) = self.current_scope.lookup(name) {
             // 移動済みの変数へのアクセスをチェック
-            if symbol.is_moved {
+            if let Some(moved_at) = symbol.moved_at {
                 return Err(AnalysisError::UseAfterMove {
                     name: name.to_string(),
                     span: *span,
+                    moved_at,
                 });
             }
             
@@ -90,10 +107,11 @@ impl<'a> BorrowChecker<'a> {
         if let Expression::Identifier(id) = expr {
             if let Some(symbol) = self.current_scope.lookup(&id.name) {
                 // 移動済みの変数を借用できない
-                if symbol.is_moved {
-                    return Err(AnalysisError::MoveWhileBorrowed {
+                if let Some(moved_at) = symbol.moved_at {
+                    return Err(AnalysisError::UseAfterMove {
                         name: id.name.to_string(),
                         span: *span,
+                        moved_at,
                     });
                 }
                 
@@ -104,23 +122,17 @@ impl<'a> BorrowChecker<'a> {
                         return Err(AnalysisError::MultipleMutableBorrows {
                             name: id.name.to_string(),
                             span: *span,
+                            borrowed_at: existing_borrow.span,
                         });
                     }
-                    // 既存が可変借用の場合、新しい借用（共有・可変問わず）は不可
-                    if existing_borrow.kind == BorrowKind::Mutable {
-                        return Err(AnalysisError::MultipleMutableBorrows {
+                    // 可変借用と共有借用は同時に存在できない（共有借用同士は許可）
+                    if is_mutable || existing_borrow.kind == BorrowKind::Mutable {
+                        return Err(AnalysisError::MutableBorrowConflict {
                             name: id.name.to_string(),
                             span: *span,
+                            borrowed_at: existing_borrow.span,
                         });
                     }
-                    // 新しい借用が可変の場合、既存の共有借用があってもエラー
-                    if is_mutable {
-                        return Err(AnalysisError::MultipleMutableBorrows {
-                            name: id.name.to_string(),
-                            span: *span,
-                        });
-                    }
-                    // 共有借用同士は許可（何もしない）
                 }
                 
                 // 新しい借用を記録
@@ -139,6 +151,7 @@ impl<'a> BorrowChecker<'a> {
                     BorrowInfo {
                         kind: borrow_kind,
                         lifetime,
+                        span: *span,
                     },
                 )?;
             }
@@ -176,14 +189,16 @@ impl<'a> BorrowChecker<'a> {
                         return Err(AnalysisError::ImmutableVariable {
                             name: id.name.to_string(),
                             span: *span,
+                            declared_at: symbol.span,
                         });
                     }
                     
                     // 借用中の変数への代入をチェック
-                    if symbol.borrow_info.is_some() {
-                        return Err(AnalysisError::MoveWhileBorrowed {
+                    if let Some(borrow) = &symbol.borrow_info {
+                        return Err(AnalysisError::AssignWhileBorrowed {
                             name: id.name.to_string(),
                             span: *span,
+                            borrowed_at: borrow.span,
                         });
                     }
                     
@@ -215,8 +230,10 @@ impl<'a> BorrowChecker<'a> {
         self.check_expr(callee)?;
         
         // 呼び出される関数名を取得
-        let is_println = match callee {
-            Expression::Identifier(id) => id.name == "println",
+        let borrows_args = match callee {
+            Expression::Identifier(id) => {
+                OutputKind::from_name(&id.name).is_some() || AssertKind::from_name(&id.name).is_some()
+            }
             _ => false,
         };
         
@@ -224,8 +241,8 @@ impl<'a> BorrowChecker<'a> {
         for arg in args {
             self.check_expr(arg)?;
             
-            // printlnの場合は引数を移動させない（参照として扱う）
-            if !is_println {
+            // printlnとアサーションの場合は引数を移動させない（参照として扱う）
+            if !borrows_args {
                 // 引数が移動を伴うかチェック
                 if self.is_move_expr(arg) {
                     self.handle_move(arg)?;
@@ -244,6 +261,7 @@ impl<'a> BorrowChecker<'a> {
                     return Err(AnalysisError::ImmutableVariable {
                         name: format!("{}.{}", id.name, field),
                         span: *span,
+                        declared_at: symbol.span,
                     });
                 }
                 
@@ -286,6 +304,8 @@ impl<'a> BorrowChecker<'a> {
             Type::Bool => true,
             // 参照型もコピー可能（参照自体がコピーされる）
             Type::Reference(_, _) => true,
+            // 解析に失敗した値は移動を追跡しない
+            Type::Error => true,
             // その他の型（文字列、配列、構造体など）は移動
             _ => false,
         }
@@ -295,22 +315,24 @@ impl<'a> BorrowChecker<'a> {
     fn handle_move(&mut self, expr: &Expression) -> AnalysisResult<()> {
         if let Expression::Identifier(id) = expr {
             if let Some(symbol) = self.current_scope.lookup(&id.name) {
-                if symbol.is_moved {
+                if let Some(moved_at) = symbol.moved_at {
                     return Err(AnalysisError::UseAfterMove {
                         name: id.name.to_string(),
                         span: id.span,
+                        moved_at,
                     });
                 }
                 
                 // 借用中の変数は移動できない
-                if symbol.borrow_info.is_some() {
+                if let Some(borrow) = &symbol.borrow_info {
                     return Err(AnalysisError::MoveWhileBorrowed {
                         name: id.name.to_string(),
                         span: id.span,
+                        borrowed_at: borrow.span,
                     });
                 }
                 
-                self.current_scope.mark_moved(&id.name)?;
+                self.current_scope.mark_moved(&id.name, id.span)?;
                 
                 self.lifetime_ctx.record_usage(
                     id.name.to_string(),
@@ -452,6 +474,12 @@ impl<'a> BorrowChecker<'a> {
                     self.check_statement(stmt)?;
                 }
             }
+            Statement::Defer(defer_stmt) => {
+                for stmt in &defer_stmt.body.statements {
+                    self.check_statement(stmt)?;
+                }
+            }
+            Statement::Function(_) => {}
         }
         Ok(())
     }
@@ -494,7 +522,14 @@ impl<'a> BorrowChecker<'a> {
                 }
                 Ok(())
             }
-            Pattern::Literal(_) | Pattern::Wildcard => {
+            Pattern::Binding(_, pattern) => self.register_pattern(pattern),
+            Pattern::Or(patterns) => {
+                for p in patterns {
+                    self.register_pattern(p)?;
+                }
+                Ok(())
+            }
+            Pattern::Literal(_) | Pattern::Range { .. } | Pattern::Wildcard => {
                 // リテラルパターンとワイルドカードパターンは変数を導入しない
                 Ok(())
             }
@@ -0,0 +1,171 @@
+//! 組み込み型のメソッド
+//!
+//! 文字列・Vec・HashMap・数値型はユーザー定義の型情報を持たないため、これらの型の
+//! メソッドのシグネチャをこの表で定義します。セマンティック解析はユーザー定義の型情報が
+//! 見つからないときにこの表を引き、コード生成とインタプリタは同じ表で戻り値の型を決めます。
+//!
+//! 引数の型はレシーバーと同じ型（`a.min(b)` の `b` は `a` と同じ型）か、
+//! コレクションの要素の型（`v.push(x)` の `x` は `v` の要素の型）か、
+//! 要素を比較する関数（`v.sort_by(f)` の `f` は `fn(T, T) -> i32`）か、
+//! 文字列（`s.find(",")` の `","` は `str`）か、長さ（`v.resize(n, x)` の `n` は `i64`）です。
+//!
+//! `sort` と `binary_search` は、コンパイラが順序を知っている要素の型（整数・浮動小数点数・
+//! 文字列）のVecだけにあります。
+
+use crate::ast::{FunctionType, Type};
+use super::numeric::integer_layout;
+
+/// 組み込みメソッドの引数の型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BuiltinParam {
+    /// レシーバーと同じ型
+    Receiver,
+    /// Vecの要素の型
+    Element,
+    /// Vecの要素を2つ受け取り、負・0・正で大小を返す関数（`fn(T, T) -> i32`）
+    Comparator,
+    /// 文字列（`str`。`String` と `&String` も渡せる）
+    Str,
+    /// 長さ（`i64`）
+    Length,
+}
+
+/// 組み込みメソッドの戻り値の型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BuiltinReturn {
+    Void,
+    Bool,
+    I64,
+    String,
+    Str,
+    /// 新しく確保したStringを要素とするVec（`Vec<String>`）
+    StringVec,
+    /// レシーバーと同じ型
+    Receiver,
+}
+
+/// 組み込みメソッドのシグネチャ
+#[derive(Debug, PartialEq, Eq)]
+pub struct BuiltinMethod {
+    pub name: &'static str,
+    pub params: &'static [BuiltinParam],
+    pub returns: BuiltinReturn,
+}
+
+const LEN: BuiltinMethod = BuiltinMethod { name: "len", params: &[], returns: BuiltinReturn::I64 };
+const PUSH: BuiltinMethod = BuiltinMethod { name: "push", params: &[BuiltinParam::Element], returns: BuiltinReturn::Void };
+const TO_STRING: BuiltinMethod = BuiltinMethod { name: "to_string", params: &[], returns: BuiltinReturn::String };
+const AS_STR: BuiltinMethod = BuiltinMethod { name: "as_str", params: &[], returns: BuiltinReturn::Str };
+const ABS: BuiltinMethod = BuiltinMethod { name: "abs", params: &[], returns: BuiltinReturn::Receiver };
+const MIN: BuiltinMethod = BuiltinMethod { name: "min", params: &[BuiltinParam::Receiver], returns: BuiltinReturn::Receiver };
+const MAX: BuiltinMethod = BuiltinMethod { name: "max", params: &[BuiltinParam::Receiver], returns: BuiltinReturn::Receiver };
+const SORT: BuiltinMethod = BuiltinMethod { name: "sort", params: &[], returns: BuiltinReturn::Void };
+const SORT_BY: BuiltinMethod = BuiltinMethod { name: "sort_by", params: &[BuiltinParam::Comparator], returns: BuiltinReturn::Void };
+const BINARY_SEARCH: BuiltinMethod = BuiltinMethod { name: "binary_search", params: &[BuiltinParam::Element], returns: BuiltinReturn::I64 };
+const STARTS_WITH: BuiltinMethod = BuiltinMethod { name: "starts_with", params: &[BuiltinParam::Str], returns: BuiltinReturn::Bool };
+const FIND: BuiltinMethod = BuiltinMethod { name: "find", params: &[BuiltinParam::Str], returns: BuiltinReturn::I64 };
+const SPLIT: BuiltinMethod = BuiltinMethod { name: "split", params: &[BuiltinParam::Str], returns: BuiltinReturn::StringVec };
+const RESIZE: BuiltinMethod = BuiltinMethod { name: "resize", params: &[BuiltinParam::Length, BuiltinParam::Element], returns: BuiltinReturn::Void };
+const TRIM: BuiltinMethod = BuiltinMethod { name: "trim", params: &[], returns: BuiltinReturn::String };
+
+/// HashMapのメソッド
+const COLLECTION_METHODS: &[BuiltinMethod] = &[LEN];
+/// Stringのメソッド（`as_str` はコピーせずに同じ文字列データを指すstrを返す）
+///
+/// `find` はバイト単位の位置（なければ-1）を返し、`split` と `trim` は結果の文字列を新しく確保します。
+const STRING_METHODS: &[BuiltinMethod] = &[LEN, AS_STR, TO_STRING, STARTS_WITH, FIND, SPLIT, TRIM];
+/// strのメソッド（`to_string` は文字列データを新しいStringに複製する）
+const STR_METHODS: &[BuiltinMethod] = &[LEN, TO_STRING, STARTS_WITH, FIND, SPLIT, TRIM];
+/// Vecのメソッド（`resize` は縮める場合は末尾の要素を取り除き、伸ばす場合は2つ目の引数の値で埋める）
+const VEC_METHODS: &[BuiltinMethod] = &[LEN, PUSH, RESIZE, SORT_BY];
+/// 順序のある要素の型のVecのメソッド
+const ORDERED_VEC_METHODS: &[BuiltinMethod] = &[LEN, PUSH, RESIZE, SORT, SORT_BY, BINARY_SEARCH];
+/// 符号付き整数と浮動小数点数のメソッド
+const SIGNED_METHODS: &[BuiltinMethod] = &[TO_STRING, ABS, MIN, MAX];
+/// 符号なし整数のメソッド（絶対値は値そのもののため `abs` はない）
+const UNSIGNED_METHODS: &[BuiltinMethod] = &[TO_STRING, MIN, MAX];
+
+/// 型の組み込みメソッドの一覧（参照型は参照先の型のメソッド）
+pub fn builtin_methods(ty: &Type) -> &'static [BuiltinMethod] {
+    match receiver_value_type(ty) {
+        Type::String => STRING_METHODS,
+        Type::Str => STR_METHODS,
+        Type::Generic(name, args) if name == "Vec" && args.len() == 1 && has_builtin_ordering(&args[0]) => ORDERED_VEC_METHODS,
+        Type::Generic(name, args) if name == "Vec" && args.len() == 1 => VEC_METHODS,
+        Type::Generic(name, args) if name == "HashMap" && args.len() == 2 => COLLECTION_METHODS,
+        Type::F8 | Type::F16 | Type::F32 | Type::F64 => SIGNED_METHODS,
+        ty => match integer_layout(ty) {
+            Some((_, true)) => SIGNED_METHODS,
+            Some((_, false)) => UNSIGNED_METHODS,
+            None => &[],
+        },
+    }
+}
+
+/// 型の組み込みメソッドを名前で探す
+pub fn builtin_method(ty: &Type, name: &str) -> Option<&'static BuiltinMethod> {
+    builtin_methods(ty).iter().find(|method| method.name == name)
+}
+
+/// コンパイラが順序を知っている型（整数・浮動小数点数・文字列）か
+///
+/// 浮動小数点数はNaNを最大として全順序で比較します。
+pub fn has_builtin_ordering(ty: &Type) -> bool {
+    matches!(ty, Type::String | Type::Str | Type::F8 | Type::F16 | Type::F32 | Type::F64) || integer_layout(ty).is_some()
+}
+
+/// `in` の左辺に置く値の型（Vecは要素、HashMapはキー、文字列は部分文字列）
+///
+/// Vecの要素は組み込みの順序を持つ型とboolに限り、`sort` と同じ比較で等しいかを判定します。
+/// 参照を通したコンテナにも使えます。
+pub fn membership_needle_type(container: &Type) -> Option<Type> {
+    match receiver_value_type(container) {
+        Type::Generic(name, args) if name == "Vec" && args.len() == 1 => {
+            (has_builtin_ordering(&args[0]) || args[0] == Type::Bool).then(|| args[0].clone())
+        }
+        Type::Generic(name, args) if name == "HashMap" && args.len() == 2 => Some(args[0].clone()),
+        ty @ (Type::String | Type::Str) => Some(ty.clone()),
+        _ => None,
+    }
+}
+
+/// 参照を外したレシーバーの型
+pub fn receiver_value_type(ty: &Type) -> &Type {
+    match ty {
+        Type::Reference(inner, _) => receiver_value_type(inner),
+        ty => ty,
+    }
+}
+
+impl BuiltinMethod {
+    /// レシーバーの型に対する引数の型
+    pub fn param_types(&self, receiver: &Type) -> Vec<Type> {
+        let receiver = receiver_value_type(receiver);
+        self.params
+            .iter()
+            .map(|param| match (param, receiver) {
+                (BuiltinParam::Element, Type::Generic(_, args)) if !args.is_empty() => args[0].clone(),
+                (BuiltinParam::Comparator, Type::Generic(_, args)) if !args.is_empty() => Type::Function(FunctionType {
+                    params: vec![args[0].clone(), args[0].clone()],
+                    return_type: Box::new(Type::I32),
+                }),
+                (BuiltinParam::Str, _) => Type::Str,
+                (BuiltinParam::Length, _) => Type::I64,
+                _ => receiver.clone(),
+            })
+            .collect()
+    }
+
+    /// レシーバーの型に対する戻り値の型
+    pub fn return_type(&self, receiver: &Type) -> Type {
+        match self.returns {
+            BuiltinReturn::Void => Type::Void,
+            BuiltinReturn::Bool => Type::Bool,
+            BuiltinReturn::I64 => Type::I64,
+            BuiltinReturn::String => Type::String,
+            BuiltinReturn::Str => Type::Str,
+            BuiltinReturn::StringVec => Type::Generic("Vec".to_string(), vec![Type::String]),
+            BuiltinReturn::Receiver => receiver_value_type(receiver).clone(),
+        }
+    }
+}
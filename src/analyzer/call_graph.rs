@@ -0,0 +1,384 @@
+//! 関数の呼び出しグラフ
+//!
+//! 関数とメソッドの本体にある呼び出しから、呼び出し元と呼び出し先の関係を作ります。
+//! メソッドは `型::名前` で表し、呼び出し先は型テーブルに記録したレシーバーの型で決めます。
+//! レシーバーの型が決まらない呼び出しは同じ名前のメソッドすべてを、関数を値として参照する
+//! 式（関数ポインタ）は参照先を呼び出しうるものとして、保守的に辺を張ります。
+//!
+//! セマンティック解析は `inline(always)` の再帰の検査にこのグラフを使い、
+//! `yunilang compile --emit=callgraph` はDOT形式で出力します。コード生成の前には
+//! [`prune_unreachable`] がmainと公開された関数から到達できない関数を取り除きます。
+
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write;
+
+use crate::ast::*;
+use crate::analyzer::type_table::TypeTable;
+
+/// 呼び出しの種類
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CallKind {
+    /// 関数の呼び出し、またはレシーバーの型が決まったメソッドの呼び出し
+    Direct,
+    /// レシーバーの型が決まらないメソッドの呼び出し（同じ名前のメソッドそれぞれへの辺）
+    Dynamic,
+    /// 関数を値として参照している（関数ポインタとして呼び出されうる）
+    AddressTaken,
+}
+
+/// 呼び出しの関係（辺）
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CallEdge {
+    pub caller: String,
+    pub callee: String,
+    pub kind: CallKind,
+    /// 呼び出し（参照）している式の位置
+    pub span: Span,
+}
+
+/// プログラムの呼び出しグラフ
+#[derive(Debug, Clone, Default)]
+pub struct CallGraph {
+    /// 関数と `型::名前` で表したメソッド（プログラム中の順）
+    pub nodes: Vec<String>,
+    /// 呼び出しの関係（本体の中に現れる順）
+    pub edges: Vec<CallEdge>,
+    /// 呼び出されなくても残す関数（mainと公開された関数、アドレスを取られた関数）
+    pub roots: Vec<String>,
+}
+
+impl CallGraph {
+    /// プログラムの関数とメソッドの本体から呼び出しグラフを作る
+    pub fn build(program: &Program, type_table: &TypeTable) -> Self {
+        let mut graph = CallGraph::default();
+        let mut methods: HashMap<&str, Vec<String>> = HashMap::new();
+        for item in &program.items {
+            let Some(key) = item_key(item) else { continue };
+            match item {
+                Item::Function(func) if func.name == "main" || func.is_public => graph.roots.push(key.clone()),
+                Item::Method(method) => {
+                    methods.entry(method.name.as_str()).or_default().push(key.clone());
+                    if method.is_public {
+                        graph.roots.push(key.clone());
+                    }
+                }
+                _ => {}
+            }
+            graph.nodes.push(key);
+        }
+
+        let nodes: HashSet<&str> = graph.nodes.iter().map(String::as_str).collect();
+        for item in &program.items {
+            let (Some(caller), Some(body)) = (item_key(item), item_body(item)) else { continue };
+            let mut collector = CallCollector { type_table, nodes: &nodes, methods: &methods, calls: Vec::new() };
+            collector.visit_block(body);
+            for (callee, kind, span) in collector.calls {
+                graph.edges.push(CallEdge { caller: caller.clone(), callee, kind, span });
+            }
+        }
+
+        let taken: Vec<String> = graph.edges.iter()
+            .filter(|edge| edge.kind == CallKind::AddressTaken)
+            .map(|edge| edge.callee.clone())
+            .collect();
+        for callee in taken {
+            if !graph.roots.contains(&callee) {
+                graph.roots.push(callee);
+            }
+        }
+        graph
+    }
+
+    /// 関数・メソッドの本体にある呼び出し
+    pub fn calls_from<'a>(&'a self, caller: &'a str) -> impl Iterator<Item = &'a CallEdge> + 'a {
+        self.edges.iter().filter(move |edge| edge.caller == caller)
+    }
+
+    /// `roots` から呼び出しをたどって到達できる関数とメソッド
+    pub fn reachable(&self) -> HashSet<&str> {
+        let mut reached: HashSet<&str> = HashSet::new();
+        let mut pending: Vec<&str> = self.roots.iter().map(String::as_str).collect();
+        while let Some(current) = pending.pop() {
+            if reached.insert(current) {
+                pending.extend(self.calls_from(current).map(|edge| edge.callee.as_str()));
+            }
+        }
+        reached
+    }
+
+    /// DOT形式（Graphvizで描画できる）に変換
+    ///
+    /// 同じ呼び出し元と呼び出し先の辺は種類ごとに1本にまとめ、関数ポインタは破線、
+    /// レシーバーの型が決まらないメソッド呼び出しは点線で表します。`roots` から到達できない
+    /// 関数は灰色で表示します。
+    pub fn to_dot(&self) -> String {
+        let reachable = self.reachable();
+        let mut dot = String::from("digraph callgraph {\n");
+        for node in &self.nodes {
+            if reachable.contains(node.as_str()) {
+                let _ = writeln!(dot, "    {};", dot_id(node));
+            } else {
+                let _ = writeln!(dot, "    {} [color=gray, fontcolor=gray];", dot_id(node));
+            }
+        }
+        let mut written = HashSet::new();
+        for edge in &self.edges {
+            if !written.insert((&edge.caller, &edge.callee, edge.kind)) {
+                continue;
+            }
+            let style = match edge.kind {
+                CallKind::Direct => "",
+                CallKind::Dynamic => " [style=dotted]",
+                CallKind::AddressTaken => " [style=dashed]",
+            };
+            let _ = writeln!(dot, "    {} -> {}{};", dot_id(&edge.caller), dot_id(&edge.callee), style);
+        }
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+/// mainと公開された関数から到達できない関数とメソッドをプログラムから取り除く
+///
+/// アドレスを取られた関数は、関数ポインタを通して呼び出されうるため残します。
+/// 取り除いた関数とメソッドの名前を返します。
+pub fn prune_unreachable(program: &mut Program, type_table: &TypeTable) -> Vec<String> {
+    let graph = CallGraph::build(program, type_table);
+    let reachable = graph.reachable();
+    let mut removed = Vec::new();
+    program.items.retain(|item| match item_key(item) {
+        Some(key) if !reachable.contains(key.as_str()) => {
+            removed.push(key);
+            false
+        }
+        _ => true,
+    });
+    removed
+}
+
+/// 呼び出しグラフでのアイテムの名前（本体を持つ関数とメソッドのみ）
+fn item_key(item: &Item) -> Option<String> {
+    match item {
+        Item::Function(func) => Some(func.name.clone()),
+        Item::Method(method) => method_key(&method.receiver.ty, &method.name),
+        Item::TypeDef(_) | Item::ExternFunction(_) => None,
+    }
+}
+
+/// アイテムの本体
+fn item_body(item: &Item) -> Option<&Block> {
+    match item {
+        Item::Function(func) => Some(&func.body),
+        Item::Method(method) => Some(&method.body),
+        Item::TypeDef(_) | Item::ExternFunction(_) => None,
+    }
+}
+
+/// メソッドの呼び出しグラフでの名前（`型::名前`）
+pub(crate) fn method_key(receiver: &Type, method: &str) -> Option<String> {
+    match receiver {
+        Type::Reference(inner, _) => method_key(inner, method),
+        Type::UserDefined(name) | Type::Generic(name, _) => Some(format!("{}::{}", name, method)),
+        _ => None,
+    }
+}
+
+/// DOTの識別子（引用符で囲む）
+fn dot_id(name: &str) -> String {
+    format!("\"{}\"", name.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// 本体の中の関数・メソッドの呼び出しと関数の参照を集める
+struct CallCollector<'a> {
+    type_table: &'a TypeTable,
+    /// プログラム中の関数とメソッド（組み込み関数やローカル変数の呼び出しは含めない）
+    nodes: &'a HashSet<&'a str>,
+    /// メソッド名 → そのメソッドを持つ型の `型::名前`
+    methods: &'a HashMap<&'a str, Vec<String>>,
+    calls: Vec<(String, CallKind, Span)>,
+}
+
+impl CallCollector<'_> {
+    /// 名前がプログラム中の関数であれば辺を追加する
+    fn push_function(&mut self, name: &str, kind: CallKind, span: Span) {
+        if self.nodes.contains(name) {
+            self.calls.push((name.to_string(), kind, span));
+        }
+    }
+
+    fn visit_block(&mut self, block: &Block) {
+        for stmt in &block.statements {
+            self.visit_statement(stmt);
+        }
+    }
+
+    fn visit_statement(&mut self, stmt: &Statement) {
+        match stmt {
+            Statement::Let(let_stmt) => {
+                if let Some(init) = &let_stmt.init {
+                    self.visit_expr(init);
+                }
+            }
+            Statement::Assignment(assign) => {
+                self.visit_expr(&assign.target);
+                self.visit_expr(&assign.value);
+            }
+            Statement::Return(ret) => {
+                if let Some(value) = &ret.value {
+                    self.visit_expr(value);
+                }
+            }
+            Statement::If(if_stmt) => self.visit_if(if_stmt),
+            Statement::While(while_stmt) => {
+                self.visit_expr(&while_stmt.condition);
+                self.visit_block(&while_stmt.body);
+            }
+            Statement::For(for_stmt) => {
+                if let Some(init) = &for_stmt.init {
+                    self.visit_statement(init);
+                }
+                if let Some(condition) = &for_stmt.condition {
+                    self.visit_expr(condition);
+                }
+                if let Some(update) = &for_stmt.update {
+                    self.visit_expr(update);
+                }
+                self.visit_block(&for_stmt.body);
+            }
+            Statement::Expression(expr) => self.visit_expr(expr),
+            Statement::Block(block) => self.visit_block(block),
+            Statement::Defer(defer_stmt) => self.visit_block(&defer_stmt.body),
+            // 取り出す前のローカル関数の呼び出しは、囲んでいる関数からの呼び出しとして数える
+            Statement::Function(func) => self.visit_block(&func.body),
+        }
+    }
+
+    fn visit_if(&mut self, if_stmt: &IfStatement) {
+        self.visit_expr(&if_stmt.condition);
+        self.visit_block(&if_stmt.then_branch);
+        match &if_stmt.else_branch {
+            Some(ElseBranch::Block(block)) => self.visit_block(block),
+            Some(ElseBranch::If(else_if)) => self.visit_if(else_if),
+            None => {}
+        }
+    }
+
+    fn visit_exprs(&mut self, exprs: &[Expression]) {
+        for expr in exprs {
+            self.visit_expr(expr);
+        }
+    }
+
+    fn visit_expr(&mut self, expr: &Expression) {
+        match expr {
+            Expression::Call(call) => {
+                match call.callee.as_ref() {
+                    Expression::Identifier(ident) => self.push_function(&ident.name, CallKind::Direct, call.span),
+                    Expression::Path(path) if path.segments.len() == 1 => {
+                        self.push_function(&path.segments[0], CallKind::Direct, call.span)
+                    }
+                    callee => self.visit_expr(callee),
+                }
+                self.visit_exprs(&call.args);
+            }
+            Expression::MethodCall(call) => {
+                let receiver = self.type_table.get(&call.object)
+                    .or_else(|| self.type_table.get_generic(&call.object));
+                match receiver.and_then(|ty| method_key(ty, &call.method)) {
+                    Some(key) if self.nodes.contains(key.as_str()) => {
+                        self.calls.push((key, CallKind::Direct, call.span));
+                    }
+                    _ => {
+                        for key in self.methods.get(call.method.as_str()).into_iter().flatten() {
+                            self.calls.push((key.clone(), CallKind::Dynamic, call.span));
+                        }
+                    }
+                }
+                self.visit_expr(&call.object);
+                self.visit_exprs(&call.args);
+            }
+            // 呼び出し以外で関数の名前を参照している場合は関数ポインタとして扱う
+            Expression::Identifier(ident) => self.push_function(&ident.name, CallKind::AddressTaken, ident.span),
+            Expression::Path(path) if path.segments.len() == 1 => {
+                self.push_function(&path.segments[0], CallKind::AddressTaken, path.span)
+            }
+            Expression::Assignment(assign) => {
+                self.visit_expr(&assign.target);
+                self.visit_expr(&assign.value);
+            }
+            Expression::Binary(binary) => {
+                self.visit_expr(&binary.left);
+                self.visit_expr(&binary.right);
+            }
+            Expression::Unary(unary) => self.visit_expr(&unary.expr),
+            Expression::Cast(cast) => self.visit_expr(&cast.expr),
+            Expression::Field(field) => self.visit_expr(&field.object),
+            Expression::Index(index) => {
+                self.visit_expr(&index.object);
+                self.visit_expr(&index.index);
+            }
+            Expression::Reference(reference) => self.visit_expr(&reference.expr),
+            Expression::Dereference(deref) => self.visit_expr(&deref.expr),
+            Expression::TemplateString(template) => {
+                for part in &template.parts {
+                    if let TemplateStringPart::Interpolation(expr) = part {
+                        self.visit_expr(expr);
+                    }
+                }
+            }
+            Expression::StructLit(lit) => {
+                for field in &lit.fields {
+                    self.visit_expr(&field.value);
+                }
+            }
+            Expression::EnumVariant(variant) => match &variant.fields {
+                EnumVariantFields::Tuple(args) => self.visit_exprs(args),
+                EnumVariantFields::Struct(fields) => {
+                    for field in fields {
+                        self.visit_expr(&field.value);
+                    }
+                }
+                EnumVariantFields::Unit => {}
+            },
+            Expression::Array(array) => self.visit_exprs(&array.elements),
+            Expression::Tuple(tuple) => self.visit_exprs(&tuple.elements),
+            Expression::ListLiteral(list) => self.visit_exprs(&list.elements),
+            Expression::MapLiteral(map) => {
+                for (key, value) in &map.pairs {
+                    self.visit_expr(key);
+                    self.visit_expr(value);
+                }
+            }
+            Expression::Match(match_expr) => {
+                self.visit_expr(&match_expr.expr);
+                for arm in &match_expr.arms {
+                    if let Some(guard) = &arm.guard {
+                        self.visit_expr(guard);
+                    }
+                    self.visit_expr(&arm.expr);
+                }
+            }
+            Expression::If(if_expr) => {
+                self.visit_expr(&if_expr.condition);
+                self.visit_expr(&if_expr.then_branch);
+                if let Some(else_branch) = &if_expr.else_branch {
+                    self.visit_expr(else_branch);
+                }
+            }
+            Expression::Block(block) => {
+                for stmt in &block.statements {
+                    self.visit_statement(stmt);
+                }
+                if let Some(last) = &block.last_expr {
+                    self.visit_expr(last);
+                }
+            }
+            Expression::Integer(_)
+            | Expression::Float(_)
+            | Expression::String(_)
+            | Expression::Bytes(_)
+            | Expression::Boolean(_)
+            | Expression::Path(_) => {}
+        }
+    }
+}
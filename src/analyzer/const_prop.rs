@@ -0,0 +1,208 @@
+//! 定数の伝播
+//!
+//! 定数で初期化した変数の値を直線的なコードに沿って追跡し、常に偽になる `if` の条件や
+//! matchのガードを見つけます。警告のための最善努力の解析で、誤検知を避けるために
+//! 次の場合は変数の値を追跡しません。
+//!
+//! - 変数への代入（分岐の中の代入も含め、以降は値がわからないものとする）
+//! - `&mut` での借用（借用先を通して変更できるため）
+//! - ループと `defer` に入るとき（前の反復や後で実行されるまでの変更を見ないため、
+//!   可変な変数をすべて追跡しない）
+//!
+//! 評価できるのは整数とboolの定数・追跡している変数・算術演算・比較・論理演算だけです。
+//! 算術演算は結果の型の範囲に収まる場合だけ評価し、オーバーフローする演算は評価しません。
+
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::ast::*;
+use crate::error::ConstantSource;
+
+/// 評価した定数の値
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ConstValue {
+    Int(i128),
+    Bool(bool),
+}
+
+impl fmt::Display for ConstValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConstValue::Int(value) => write!(f, "{}", value),
+            ConstValue::Bool(value) => write!(f, "{}", value),
+        }
+    }
+}
+
+/// 定数の値と、その値を決めた変数
+#[derive(Debug, Clone)]
+pub(crate) struct Constant {
+    pub value: ConstValue,
+    /// 値の根拠となった変数の宣言（リテラルだけで決まる場合は空）
+    pub sources: Vec<ConstantSource>,
+}
+
+impl Constant {
+    fn literal(value: ConstValue) -> Self {
+        Self { value, sources: Vec::new() }
+    }
+
+    /// 二つの定数から計算した値（根拠は両方の変数）
+    fn combine(value: ConstValue, left: Constant, right: Constant) -> Self {
+        let mut sources = left.sources;
+        for source in right.sources {
+            if !sources.iter().any(|known| known.span == source.span) {
+                sources.push(source);
+            }
+        }
+        Self { value, sources }
+    }
+}
+
+/// 変数に束縛された定数
+#[derive(Debug, Clone)]
+struct Binding {
+    constant: Constant,
+    is_mutable: bool,
+    /// 変数の宣言の位置（シンボルテーブルの変数と同じものか確かめるため）
+    declared_at: Span,
+}
+
+/// スコープごとの変数の定数値
+///
+/// 値のわからない変数も `None` として記録し、外側のスコープの同名の変数を隠します。
+#[derive(Debug)]
+pub(crate) struct ConstEnv {
+    scopes: Vec<HashMap<String, Option<Binding>>>,
+}
+
+impl Default for ConstEnv {
+    fn default() -> Self {
+        Self { scopes: vec![HashMap::new()] }
+    }
+}
+
+impl ConstEnv {
+    /// 新しいスコープに入る（`depth` は入った後のシンボルテーブルのスコープの数）
+    ///
+    /// エラーで抜けたスコープが残っていても、シンボルテーブルと同じ深さに揃えます。
+    pub fn enter_scope(&mut self, depth: usize) {
+        self.scopes.truncate(depth.saturating_sub(1).max(1));
+        self.scopes.resize_with(depth.max(1), HashMap::new);
+    }
+
+    /// スコープを抜ける（`depth` は抜けた後のシンボルテーブルのスコープの数）
+    pub fn exit_scope(&mut self, depth: usize) {
+        self.scopes.resize_with(depth.max(1), HashMap::new);
+    }
+
+    /// 変数を宣言する（値がわからない場合は `None`）
+    pub fn define(&mut self, name: &str, declared_at: Span, constant: Option<Constant>, is_mutable: bool) {
+        let binding = constant.map(|constant| Binding { constant, is_mutable, declared_at });
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.insert(name.to_string(), binding);
+        }
+    }
+
+    /// 変数の値を追跡しないようにする
+    pub fn invalidate(&mut self, name: &str) {
+        if let Some(binding) = self.scopes.iter_mut().rev().find_map(|scope| scope.get_mut(name)) {
+            *binding = None;
+        }
+    }
+
+    /// 可変な変数をすべて追跡しないようにする
+    pub fn invalidate_mutable(&mut self) {
+        for binding in self.scopes.iter_mut().flat_map(|scope| scope.values_mut()) {
+            if binding.as_ref().is_some_and(|binding| binding.is_mutable) {
+                *binding = None;
+            }
+        }
+    }
+
+    /// `declared_at` で宣言された変数の値
+    pub fn lookup(&self, name: &str, declared_at: Span) -> Option<&Constant> {
+        let binding = self.scopes.iter().rev().find_map(|scope| scope.get(name))?.as_ref()?;
+        (binding.declared_at == declared_at).then_some(&binding.constant)
+    }
+}
+
+/// 式の評価に使う変数の値と型
+pub(crate) trait ConstContext {
+    /// 変数の値（値を追跡していない場合は `None`）
+    fn constant(&self, name: &str) -> Option<Constant>;
+    /// 整数の式の型で表せる値の範囲
+    fn range_of(&self, expr: &Expression) -> Option<(i128, i128)>;
+}
+
+/// 式を定数として評価する
+pub(crate) fn evaluate(expr: &Expression, ctx: &impl ConstContext) -> Option<Constant> {
+    match expr {
+        Expression::Integer(lit) => Some(Constant::literal(ConstValue::Int(lit.value))),
+        Expression::Boolean(lit) => Some(Constant::literal(ConstValue::Bool(lit.value))),
+        Expression::Identifier(ident) => ctx.constant(&ident.name),
+        Expression::Unary(unary) => {
+            let operand = evaluate(&unary.expr, ctx)?;
+            let value = match (&unary.op, operand.value) {
+                (UnaryOp::Not, ConstValue::Bool(value)) => ConstValue::Bool(!value),
+                (UnaryOp::Negate, ConstValue::Int(value)) => ConstValue::Int(in_range(value.checked_neg()?, expr, ctx)?),
+                _ => return None,
+            };
+            Some(Constant { value, sources: operand.sources })
+        }
+        Expression::Binary(binary) => evaluate_binary(expr, binary, ctx),
+        _ => None,
+    }
+}
+
+fn evaluate_binary(expr: &Expression, binary: &BinaryExpr, ctx: &impl ConstContext) -> Option<Constant> {
+    let left = evaluate(&binary.left, ctx);
+    // 論理演算は片方だけで値が決まる場合がある（右辺に副作用があっても分岐の結果は変わらない）
+    if let BinaryOp::And | BinaryOp::Or = binary.op {
+        let decisive = matches!(binary.op, BinaryOp::Or);
+        if let Some(left @ Constant { value: ConstValue::Bool(value), .. }) = &left {
+            if *value == decisive {
+                return Some(left.clone());
+            }
+        }
+        let right = evaluate(&binary.right, ctx)?;
+        return match (left, right.value) {
+            (_, ConstValue::Bool(value)) if value == decisive => Some(right),
+            (Some(left @ Constant { value: ConstValue::Bool(_), .. }), ConstValue::Bool(value)) => {
+                Some(Constant::combine(ConstValue::Bool(value), left, right))
+            }
+            _ => None,
+        };
+    }
+
+    let (left, right) = (left?, evaluate(&binary.right, ctx)?);
+    let value = match (left.value, right.value) {
+        (ConstValue::Int(l), ConstValue::Int(r)) => match binary.op {
+            BinaryOp::Add => ConstValue::Int(in_range(l.checked_add(r)?, expr, ctx)?),
+            BinaryOp::Subtract => ConstValue::Int(in_range(l.checked_sub(r)?, expr, ctx)?),
+            BinaryOp::Multiply => ConstValue::Int(in_range(l.checked_mul(r)?, expr, ctx)?),
+            BinaryOp::Divide => ConstValue::Int(in_range(l.checked_div(r)?, expr, ctx)?),
+            BinaryOp::Modulo => ConstValue::Int(in_range(l.checked_rem(r)?, expr, ctx)?),
+            BinaryOp::Lt => ConstValue::Bool(l < r),
+            BinaryOp::Gt => ConstValue::Bool(l > r),
+            BinaryOp::Le => ConstValue::Bool(l <= r),
+            BinaryOp::Ge => ConstValue::Bool(l >= r),
+            BinaryOp::Eq => ConstValue::Bool(l == r),
+            BinaryOp::Ne => ConstValue::Bool(l != r),
+            _ => return None,
+        },
+        (ConstValue::Bool(l), ConstValue::Bool(r)) => match binary.op {
+            BinaryOp::Eq => ConstValue::Bool(l == r),
+            BinaryOp::Ne => ConstValue::Bool(l != r),
+            _ => return None,
+        },
+        _ => return None,
+    };
+    Some(Constant::combine(value, left, right))
+}
+
+/// 演算結果が式の型の範囲に収まる場合だけ値を返す
+fn in_range(value: i128, expr: &Expression, ctx: &impl ConstContext) -> Option<i128> {
+    let (min, max) = ctx.range_of(expr)?;
+    (min..=max).contains(&value).then_some(value)
+}
@@ -0,0 +1,55 @@
+//! 発散する式の判定
+//!
+//! 必ずreturnで関数から抜け、値を生まない式（`{ return 1; }` など）を見つけます。
+//! if式とmatch式は発散する分岐を型の決定から除くため、`if c { return 1; } else { 2 }` は
+//! else節の型（`i32`）になります。判定は構文だけで行い、分岐の条件の値は見ません。
+
+use crate::ast::*;
+
+/// 式の評価が必ず関数から戻るか
+pub(crate) fn expression_diverges(expr: &Expression) -> bool {
+    match expr {
+        Expression::Block(block_expr) => {
+            block_expr.statements.iter().any(statement_diverges)
+                || block_expr.last_expr.as_deref().is_some_and(expression_diverges)
+        }
+        Expression::If(if_expr) => {
+            expression_diverges(&if_expr.then_branch)
+                && if_expr.else_branch.as_deref().is_some_and(expression_diverges)
+        }
+        Expression::Match(match_expr) => {
+            !match_expr.arms.is_empty() && match_expr.arms.iter().all(|arm| expression_diverges(&arm.expr))
+        }
+        _ => false,
+    }
+}
+
+/// 文の実行が必ず関数から戻るか
+pub(crate) fn statement_diverges(stmt: &Statement) -> bool {
+    match stmt {
+        Statement::Return(_) => true,
+        Statement::If(if_stmt) => if_statement_diverges(if_stmt),
+        Statement::Block(block) => block_diverges(block),
+        Statement::Expression(expr) => expression_diverges(expr),
+        Statement::Let(let_stmt) => let_stmt.init.as_ref().is_some_and(expression_diverges),
+        // ループは条件が偽なら本体を実行せず、deferはブロックの終了時に実行される
+        Statement::Assignment(_)
+        | Statement::While(_)
+        | Statement::For(_)
+        | Statement::Defer(_)
+        | Statement::Function(_) => false,
+    }
+}
+
+fn block_diverges(block: &Block) -> bool {
+    block.statements.iter().any(statement_diverges)
+}
+
+fn if_statement_diverges(if_stmt: &IfStatement) -> bool {
+    block_diverges(&if_stmt.then_branch)
+        && match &if_stmt.else_branch {
+            Some(ElseBranch::Block(block)) => block_diverges(block),
+            Some(ElseBranch::If(nested_if)) => if_statement_diverges(nested_if),
+            None => false,
+        }
+}
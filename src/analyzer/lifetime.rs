@@ -193,11 +193,18 @@ impl LifetimeContext {
         }
     }
     
+    /// 借用と使用の記録を空にする（関数ごとに借用チェックを始める前に呼ぶ）
+    pub fn clear_borrows(&mut self) {
+        self.variable_borrows.clear();
+        self.variable_usage.clear();
+    }
+
     /// 変数の借用を記録
-    pub fn record_borrow(&mut self, var_name: String, kind: BorrowKind, lifetime: LifetimeId, _span: Span) {
+    pub fn record_borrow(&mut self, var_name: String, kind: BorrowKind, lifetime: LifetimeId, span: Span) {
         let borrow_info = BorrowInfo {
             kind,
             lifetime,
+            span,
         };
         
         self.variable_borrows.entry(var_name).or_default().push(borrow_info);
@@ -223,21 +230,29 @@ impl LifetimeContext {
                 .filter(|b| b.kind == BorrowKind::Mutable)
                 .collect();
             
-            if mutable_borrows.len() > 1 {
+            if let [first, second, ..] = mutable_borrows.as_slice() {
                 return Err(AnalysisError::MultipleMutableBorrows {
                     name: var_name.clone(),
-                    span: Span::dummy(),
+                    span: second.span,
+                    borrowed_at: first.span,
                 });
             }
             
             // 可変借用と不変借用は同時に存在できない
-            let has_mutable = borrows.iter().any(|b| b.kind == BorrowKind::Mutable);
-            let has_immutable = borrows.iter().any(|b| b.kind == BorrowKind::Shared);
+            let mutable = borrows.iter().find(|b| b.kind == BorrowKind::Mutable);
+            let shared = borrows.iter().find(|b| b.kind == BorrowKind::Shared);
             
-            if has_mutable && has_immutable {
+            if let (Some(mutable), Some(shared)) = (mutable, shared) {
+                // 後に借用した側を違反した箇所として報告する
+                let (earlier, later) = if mutable.span.start < shared.span.start {
+                    (mutable, shared)
+                } else {
+                    (shared, mutable)
+                };
                 return Err(AnalysisError::MutableBorrowConflict {
                     name: var_name.clone(),
-                    span: Span::dummy(),
+                    span: later.span,
+                    borrowed_at: earlier.span,
                 });
             }
         }
@@ -0,0 +1,178 @@
+//! リント（警告の種類）と、リントごとの報告の水準
+//!
+//! 警告として報告する診断にはそれぞれ変わらない名前を付け、コマンドラインの `--allow`・`--warn`・`--deny`
+//! で無視する・警告する・エラーにするを選べるようにします。`warnings` はすべてのリントをまとめて指す名前で、
+//! 個別に指定していないリントの水準になります。何も指定しなければ、すべてのリントは警告です。
+
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::error::{AnalyzerError, CodeSizeError, ParserError, YuniError, YuniResult};
+
+/// すべてのリントをまとめて指す名前
+pub const WARNINGS_GROUP: &str = "warnings";
+
+/// 警告として報告する診断の種類
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Lint {
+    /// 使われていないインポート
+    UnusedImport,
+    /// ユーザー定義に隠されたインポート
+    ShadowedImport,
+    /// 標準ライブラリの名前を隠すユーザー定義
+    ShadowedPrelude,
+    /// まだ解決できない選択インポート
+    UnresolvedImport,
+    /// アイテムの直前にないドキュメントコメント
+    MisplacedDocComment,
+    /// returnのない無条件ループ
+    InfiniteLoop,
+    /// 条件の変数を本体で変更しないループ
+    LoopConditionNotModified,
+    /// 定数のキャストで値が変わる
+    LossyConstantCast,
+    /// 常に偽の条件で実行されない分岐・matchのアーム
+    UnreachableBranch,
+    /// 型の範囲から結果が決まる比較
+    ConstantComparison,
+    /// bool値と `true`・`false` のリテラルの比較
+    BoolComparison,
+    /// `--borrow-check=warn` で警告にした借用規則の違反
+    BorrowViolation,
+    /// しきい値を超えた生成コードの規模
+    CodeSize,
+}
+
+impl Lint {
+    /// すべてのリント（名前の一覧の表示順）
+    pub const ALL: [Lint; 13] = [
+        Lint::UnusedImport,
+        Lint::ShadowedImport,
+        Lint::ShadowedPrelude,
+        Lint::UnresolvedImport,
+        Lint::MisplacedDocComment,
+        Lint::InfiniteLoop,
+        Lint::LoopConditionNotModified,
+        Lint::LossyConstantCast,
+        Lint::UnreachableBranch,
+        Lint::ConstantComparison,
+        Lint::BoolComparison,
+        Lint::BorrowViolation,
+        Lint::CodeSize,
+    ];
+
+    /// コマンドラインで指定する名前
+    pub fn name(self) -> &'static str {
+        match self {
+            Lint::UnusedImport => "unused-import",
+            Lint::ShadowedImport => "shadowed-import",
+            Lint::ShadowedPrelude => "shadowed-prelude",
+            Lint::UnresolvedImport => "unresolved-import",
+            Lint::MisplacedDocComment => "misplaced-doc-comment",
+            Lint::InfiniteLoop => "infinite-loop",
+            Lint::LoopConditionNotModified => "loop-condition-not-modified",
+            Lint::LossyConstantCast => "lossy-constant-cast",
+            Lint::UnreachableBranch => "unreachable-branch",
+            Lint::ConstantComparison => "constant-comparison",
+            Lint::BoolComparison => "bool-comparison",
+            Lint::BorrowViolation => "borrow-violation",
+            Lint::CodeSize => "code-size",
+        }
+    }
+
+    /// 名前からリントを探す
+    pub fn from_name(name: &str) -> Option<Lint> {
+        Self::ALL.into_iter().find(|lint| lint.name() == name)
+    }
+
+    /// 警告として報告する診断のリント（リントでない診断はNone）
+    pub fn of(warning: &YuniError) -> Option<Lint> {
+        match warning {
+            YuniError::Analyzer(warning) => Self::of_analysis(warning),
+            YuniError::Parser(ParserError::MisplacedDocComment { .. }) => Some(Lint::MisplacedDocComment),
+            YuniError::CodeSize(CodeSizeError::TooManyInstantiations { .. } | CodeSizeError::ModuleTooLarge { .. }) => {
+                Some(Lint::CodeSize)
+            }
+            _ => None,
+        }
+    }
+
+    /// 名前解決・セマンティック解析の警告のリント
+    pub fn of_analysis(warning: &AnalyzerError) -> Option<Lint> {
+        match warning {
+            AnalyzerError::UnusedImport { .. } => Some(Lint::UnusedImport),
+            AnalyzerError::ShadowedImport { .. } => Some(Lint::ShadowedImport),
+            AnalyzerError::ShadowsPrelude { .. } => Some(Lint::ShadowedPrelude),
+            AnalyzerError::UnresolvedImportSymbols { .. } => Some(Lint::UnresolvedImport),
+            AnalyzerError::InfiniteLoop { .. } => Some(Lint::InfiniteLoop),
+            AnalyzerError::LoopConditionNotModified { .. } => Some(Lint::LoopConditionNotModified),
+            AnalyzerError::LossyConstantCast { .. } => Some(Lint::LossyConstantCast),
+            AnalyzerError::BranchNeverTaken { .. } | AnalyzerError::MatchArmNeverTaken { .. } => {
+                Some(Lint::UnreachableBranch)
+            }
+            AnalyzerError::ComparisonAlwaysConstant { .. } => Some(Lint::ConstantComparison),
+            AnalyzerError::RedundantBoolComparison { .. } => Some(Lint::BoolComparison),
+            AnalyzerError::UseAfterMove { .. }
+            | AnalyzerError::MoveWhileBorrowed { .. }
+            | AnalyzerError::AssignWhileBorrowed { .. }
+            | AnalyzerError::MultipleMutableBorrows { .. }
+            | AnalyzerError::MutableBorrowConflict { .. } => Some(Lint::BorrowViolation),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Lint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// リントの報告の水準
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LintLevel {
+    /// 報告しない
+    Allow,
+    /// 警告として報告する（コンパイルは続ける）
+    #[default]
+    Warn,
+    /// エラーとして報告する（コンパイルは失敗する）
+    Deny,
+}
+
+/// リントごとの報告の水準の表
+///
+/// 個別に指定したリントの水準は、指定した順序に関係なく `warnings` の水準より優先します。
+/// リントに分類されない警告（入力ファイルの拡張子の警告など）は `warnings` の水準に従います。
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LintLevels {
+    /// 個別に指定していないリントの水準
+    warnings: LintLevel,
+    /// 個別に指定したリントの水準
+    levels: HashMap<Lint, LintLevel>,
+}
+
+impl LintLevels {
+    /// 名前 `name` のリント（または `warnings`）の水準を設定
+    ///
+    /// 名前が不明な場合は、指定できる名前の一覧を含むエラーを返します。
+    pub fn set(&mut self, name: &str, level: LintLevel) -> YuniResult<()> {
+        if name == WARNINGS_GROUP {
+            self.warnings = level;
+            return Ok(());
+        }
+        let lint = Lint::from_name(name).ok_or_else(|| {
+            let valid: Vec<&str> = std::iter::once(WARNINGS_GROUP)
+                .chain(Lint::ALL.iter().map(|lint| lint.name()))
+                .collect();
+            YuniError::Other(format!("Unknown lint `{}`; valid lints are: {}", name, valid.join(", ")))
+        })?;
+        self.levels.insert(lint, level);
+        Ok(())
+    }
+
+    /// リント `lint` の水準（Noneはリントに分類されない警告）
+    pub fn level(&self, lint: Option<Lint>) -> LintLevel {
+        lint.and_then(|lint| self.levels.get(&lint).copied()).unwrap_or(self.warnings)
+    }
+}
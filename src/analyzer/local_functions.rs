@@ -0,0 +1,436 @@
+//! ローカル関数の巻き上げ
+//!
+//! 関数・メソッドの本体で定義した関数（`Statement::Function`）を、囲んでいる関数の
+//! 名前を前に付けた一意な名前（`outer::helper`）のトップレベルの関数として取り出します。
+//! ローカル関数は定義した位置より後の同じブロックの文・入れ子のブロック・自身の本体
+//! （再帰呼び出し）からだけ参照でき、参照は取り出した関数の名前に書き換えます。
+//! 同じ名前のトップレベルの関数よりローカル関数を優先します。
+//!
+//! ローカル関数はクロージャではないため、外側の関数の変数を捕捉できません。
+//! 本体から外側の変数を参照した箇所は [`LocalCapture`] として返し、解析がエラーにします。
+//! 巻き上げは解析・単相化・コード生成の入口で行い、以降のパスはローカル関数を扱いません。
+
+use std::collections::{HashMap, HashSet};
+
+use crate::ast::*;
+
+use super::call_graph::method_key;
+
+/// ローカル関数の本体から外側の関数の変数を参照した箇所
+#[derive(Debug, Clone, PartialEq)]
+pub struct LocalCapture {
+    /// 参照した変数名
+    pub name: String,
+    /// 参照したローカル関数のソース上の名前
+    pub function: String,
+    pub span: Span,
+}
+
+/// プログラムがローカル関数を含むか
+pub fn contains_local_functions(program: &Program) -> bool {
+    program.items.iter().any(|item| match item {
+        Item::Function(func) => block_contains_local_function(&func.body),
+        Item::Method(method) => block_contains_local_function(&method.body),
+        Item::TypeDef(_) | Item::ExternFunction(_) => false,
+    })
+}
+
+/// ローカル関数をトップレベルの関数として取り出し、外側の変数を参照した箇所を返す
+pub fn hoist_local_functions(program: &mut Program) -> Vec<LocalCapture> {
+    if !contains_local_functions(program) {
+        return Vec::new();
+    }
+
+    let used_names = program
+        .items
+        .iter()
+        .filter_map(|item| match item {
+            Item::Function(func) => Some(func.name.clone()),
+            Item::ExternFunction(func) => Some(func.name.clone()),
+            Item::Method(method) => method_key(&method.receiver.ty, &method.name),
+            Item::TypeDef(_) => None,
+        })
+        .collect();
+    let mut hoister = Hoister { used_names, ..Hoister::default() };
+
+    let items = std::mem::take(&mut program.items);
+    for mut item in items {
+        match &mut item {
+            Item::Function(func) => {
+                hoister.enclosing = func.name.clone();
+                hoister.hoist_body(&func.params, &mut func.body, None);
+            }
+            Item::Method(method) => {
+                hoister.enclosing = method_key(&method.receiver.ty, &method.name)
+                    .unwrap_or_else(|| method.name.clone());
+                let receiver = method.receiver.name.as_deref().unwrap_or("self");
+                hoister.hoist_body(&method.params, &mut method.body, Some(receiver));
+            }
+            Item::TypeDef(_) | Item::ExternFunction(_) => {}
+        }
+        program.items.push(item);
+        program.items.extend(hoister.hoisted.drain(..).map(Item::Function));
+    }
+    hoister.captures
+}
+
+/// 巻き上げの途中のスコープ
+#[derive(Default)]
+struct LocalScope {
+    variables: HashSet<String>,
+    /// ソース上の名前 → 取り出した関数の名前
+    functions: HashMap<String, String>,
+    /// ローカル関数の本体のスコープであれば、その関数のソース上の名前
+    boundary: Option<String>,
+}
+
+#[derive(Default)]
+struct Hoister {
+    scopes: Vec<LocalScope>,
+    /// 解析中のローカル関数を囲む関数の名前（取り出した名前の接頭辞）
+    enclosing: String,
+    /// 取り出した関数（囲んでいるアイテムの直後に置く）
+    hoisted: Vec<FunctionDecl>,
+    /// トップレベルのアイテムと取り出した関数の名前
+    used_names: HashSet<String>,
+    captures: Vec<LocalCapture>,
+}
+
+impl Hoister {
+    /// 関数の本体を走査する（引数とレシーバーは本体のスコープの変数）
+    fn hoist_body(&mut self, params: &[Param], body: &mut Block, receiver: Option<&str>) {
+        let mut scope = LocalScope::default();
+        scope.variables.extend(params.iter().map(|param| param.name.clone()));
+        scope.variables.extend(receiver.map(str::to_string));
+        self.scopes.push(scope);
+        self.hoist_statements(&mut body.statements);
+        self.scopes.pop();
+    }
+
+    /// ほかの名前と重ならない、取り出した関数の名前
+    fn unique_name(&mut self, name: &str) -> String {
+        let base = format!("{}::{}", self.enclosing, name);
+        let mut unique = base.clone();
+        let mut suffix = 2;
+        while self.used_names.contains(&unique) {
+            unique = format!("{}#{}", base, suffix);
+            suffix += 1;
+        }
+        self.used_names.insert(unique.clone());
+        unique
+    }
+
+    fn current_scope(&mut self) -> &mut LocalScope {
+        self.scopes.last_mut().expect("hoisting always runs inside a function body")
+    }
+
+    fn with_scope(&mut self, f: impl FnOnce(&mut Self)) {
+        self.scopes.push(LocalScope::default());
+        f(self);
+        self.scopes.pop();
+    }
+
+    fn hoist_block(&mut self, block: &mut Block) {
+        self.with_scope(|this| this.hoist_statements(&mut block.statements));
+    }
+
+    /// 文の並びを走査し、ローカル関数の定義を取り除く
+    fn hoist_statements(&mut self, statements: &mut Vec<Statement>) {
+        for stmt in std::mem::take(statements) {
+            match stmt {
+                Statement::Function(func) => self.hoist_function(func),
+                mut stmt => {
+                    self.hoist_statement(&mut stmt);
+                    statements.push(stmt);
+                }
+            }
+        }
+    }
+
+    /// ローカル関数を取り出す
+    ///
+    /// 名前は本体より先に登録し、本体からの再帰呼び出しも取り出した名前に書き換えます。
+    fn hoist_function(&mut self, mut func: FunctionDecl) {
+        let source_name = std::mem::take(&mut func.name);
+        let unique = self.unique_name(&source_name);
+        self.current_scope().functions.insert(source_name.clone(), unique.clone());
+
+        let mut scope = LocalScope { boundary: Some(source_name), ..LocalScope::default() };
+        scope.variables.extend(func.params.iter().map(|param| param.name.clone()));
+        self.scopes.push(scope);
+        let enclosing = std::mem::replace(&mut self.enclosing, unique.clone());
+        self.hoist_statements(&mut func.body.statements);
+        self.enclosing = enclosing;
+        self.scopes.pop();
+
+        func.name = unique;
+        func.is_public = false;
+        self.hoisted.push(func);
+    }
+
+    fn hoist_statement(&mut self, stmt: &mut Statement) {
+        match stmt {
+            Statement::Let(let_stmt) => {
+                if let Some(init) = &mut let_stmt.init {
+                    self.hoist_expr(init);
+                }
+                self.bind_pattern(&let_stmt.pattern);
+            }
+            Statement::Assignment(assign) => {
+                self.hoist_expr(&mut assign.target);
+                self.hoist_expr(&mut assign.value);
+            }
+            Statement::Return(ret) => {
+                if let Some(value) = &mut ret.value {
+                    self.hoist_expr(value);
+                }
+            }
+            Statement::If(if_stmt) => self.hoist_if(if_stmt),
+            Statement::While(while_stmt) => {
+                self.hoist_expr(&mut while_stmt.condition);
+                self.hoist_block(&mut while_stmt.body);
+            }
+            Statement::For(for_stmt) => self.with_scope(|this| {
+                if let Some(init) = &mut for_stmt.init {
+                    this.hoist_statement(init);
+                }
+                if let Some(condition) = &mut for_stmt.condition {
+                    this.hoist_expr(condition);
+                }
+                if let Some(update) = &mut for_stmt.update {
+                    this.hoist_expr(update);
+                }
+                this.hoist_block(&mut for_stmt.body);
+            }),
+            Statement::Expression(expr) => self.hoist_expr(expr),
+            Statement::Block(block) => self.hoist_block(block),
+            Statement::Defer(defer_stmt) => self.hoist_block(&mut defer_stmt.body),
+            // `hoist_statements` が取り除く（for文の初期化式には現れない）
+            Statement::Function(_) => {}
+        }
+    }
+
+    fn hoist_if(&mut self, if_stmt: &mut IfStatement) {
+        self.hoist_expr(&mut if_stmt.condition);
+        self.hoist_block(&mut if_stmt.then_branch);
+        match &mut if_stmt.else_branch {
+            Some(ElseBranch::Block(block)) => self.hoist_block(block),
+            Some(ElseBranch::If(else_if)) => self.hoist_if(else_if),
+            None => {}
+        }
+    }
+
+    fn bind_pattern(&mut self, pattern: &Pattern) {
+        match pattern {
+            Pattern::Identifier(name, _) => {
+                self.current_scope().variables.insert(name.clone());
+            }
+            Pattern::Binding(name, pattern) => {
+                self.current_scope().variables.insert(name.clone());
+                self.bind_pattern(pattern);
+            }
+            Pattern::Tuple(patterns) | Pattern::Or(patterns) => {
+                patterns.iter().for_each(|pattern| self.bind_pattern(pattern))
+            }
+            Pattern::Struct(_, fields)
+            | Pattern::EnumVariant { fields: EnumVariantPatternFields::Struct(fields), .. } => {
+                fields.iter().for_each(|(_, pattern)| self.bind_pattern(pattern))
+            }
+            Pattern::EnumVariant { fields: EnumVariantPatternFields::Tuple(patterns), .. } => {
+                patterns.iter().for_each(|pattern| self.bind_pattern(pattern))
+            }
+            Pattern::EnumVariant { fields: EnumVariantPatternFields::Unit, .. }
+            | Pattern::Literal(_)
+            | Pattern::Range { .. }
+            | Pattern::Wildcard => {}
+        }
+    }
+
+    /// 識別子をスコープで探し、ローカル関数であれば名前を書き換える
+    ///
+    /// ローカル関数の本体の外側の変数であれば、捕捉として記録します。
+    fn resolve_identifier(&mut self, ident: &mut Identifier) {
+        let mut crossed: Option<&str> = None;
+        for scope in self.scopes.iter().rev() {
+            if scope.variables.contains(&ident.name) {
+                if let Some(function) = crossed {
+                    self.captures.push(LocalCapture {
+                        name: ident.name.clone(),
+                        function: function.to_string(),
+                        span: ident.span,
+                    });
+                }
+                return;
+            }
+            if let Some(unique) = scope.functions.get(&ident.name) {
+                ident.name = unique.clone();
+                return;
+            }
+            if let Some(boundary) = &scope.boundary {
+                crossed.get_or_insert(boundary.as_str());
+            }
+        }
+    }
+
+    fn hoist_exprs<'e>(&mut self, exprs: impl IntoIterator<Item = &'e mut Expression>) {
+        for expr in exprs {
+            self.hoist_expr(expr);
+        }
+    }
+
+    fn hoist_expr(&mut self, expr: &mut Expression) {
+        match expr {
+            Expression::Identifier(ident) => self.resolve_identifier(ident),
+            Expression::Call(call) => self.hoist_exprs(std::iter::once(call.callee.as_mut()).chain(&mut call.args)),
+            Expression::MethodCall(call) => {
+                self.hoist_exprs(std::iter::once(call.object.as_mut()).chain(&mut call.args))
+            }
+            Expression::Binary(binary) => self.hoist_exprs([binary.left.as_mut(), binary.right.as_mut()]),
+            Expression::Unary(unary) => self.hoist_expr(&mut unary.expr),
+            Expression::Index(index) => self.hoist_exprs([index.object.as_mut(), index.index.as_mut()]),
+            Expression::Field(field) => self.hoist_expr(&mut field.object),
+            Expression::Reference(ref_expr) => self.hoist_expr(&mut ref_expr.expr),
+            Expression::Dereference(deref_expr) => self.hoist_expr(&mut deref_expr.expr),
+            Expression::Cast(cast) => self.hoist_expr(&mut cast.expr),
+            Expression::Assignment(assign) => self.hoist_exprs([assign.target.as_mut(), assign.value.as_mut()]),
+            Expression::StructLit(struct_lit) => {
+                self.hoist_exprs(struct_lit.fields.iter_mut().map(|field| &mut field.value))
+            }
+            Expression::EnumVariant(enum_variant) => match &mut enum_variant.fields {
+                EnumVariantFields::Tuple(args) => self.hoist_exprs(args),
+                EnumVariantFields::Struct(fields) => self.hoist_exprs(fields.iter_mut().map(|field| &mut field.value)),
+                EnumVariantFields::Unit => {}
+            },
+            Expression::Array(array) => self.hoist_exprs(&mut array.elements),
+            Expression::Tuple(tuple) => self.hoist_exprs(&mut tuple.elements),
+            Expression::ListLiteral(list) => self.hoist_exprs(&mut list.elements),
+            Expression::MapLiteral(map) => self.hoist_exprs(map.pairs.iter_mut().flat_map(|(key, value)| [key, value])),
+            Expression::TemplateString(template) => {
+                self.hoist_exprs(template.parts.iter_mut().filter_map(|part| match part {
+                    TemplateStringPart::Interpolation(expr) => Some(expr),
+                    TemplateStringPart::Text(_) => None,
+                }))
+            }
+            Expression::If(if_expr) => {
+                self.hoist_exprs([if_expr.condition.as_mut(), if_expr.then_branch.as_mut()]);
+                if let Some(else_branch) = &mut if_expr.else_branch {
+                    self.hoist_expr(else_branch);
+                }
+            }
+            Expression::Match(match_expr) => {
+                self.hoist_expr(&mut match_expr.expr);
+                for arm in &mut match_expr.arms {
+                    self.with_scope(|this| {
+                        this.bind_pattern(&arm.pattern);
+                        if let Some(guard) = &mut arm.guard {
+                            this.hoist_expr(guard);
+                        }
+                        this.hoist_expr(&mut arm.expr);
+                    });
+                }
+            }
+            Expression::Block(block_expr) => self.with_scope(|this| {
+                this.hoist_statements(&mut block_expr.statements);
+                if let Some(last_expr) = &mut block_expr.last_expr {
+                    this.hoist_expr(last_expr);
+                }
+            }),
+            Expression::Integer(_)
+            | Expression::Float(_)
+            | Expression::String(_)
+            | Expression::Bytes(_)
+            | Expression::Boolean(_)
+            | Expression::Path(_) => {}
+        }
+    }
+}
+
+fn block_contains_local_function(block: &Block) -> bool {
+    block.statements.iter().any(statement_contains_local_function)
+}
+
+fn statement_contains_local_function(stmt: &Statement) -> bool {
+    match stmt {
+        Statement::Function(_) => true,
+        Statement::Let(let_stmt) => let_stmt.init.as_ref().is_some_and(expression_contains_local_function),
+        Statement::Assignment(assign) => {
+            expression_contains_local_function(&assign.target) || expression_contains_local_function(&assign.value)
+        }
+        Statement::Return(ret) => ret.value.as_ref().is_some_and(expression_contains_local_function),
+        Statement::If(if_stmt) => if_contains_local_function(if_stmt),
+        Statement::While(while_stmt) => {
+            expression_contains_local_function(&while_stmt.condition) || block_contains_local_function(&while_stmt.body)
+        }
+        Statement::For(for_stmt) => {
+            for_stmt.init.as_deref().is_some_and(statement_contains_local_function)
+                || for_stmt.condition.as_ref().is_some_and(expression_contains_local_function)
+                || for_stmt.update.as_ref().is_some_and(expression_contains_local_function)
+                || block_contains_local_function(&for_stmt.body)
+        }
+        Statement::Expression(expr) => expression_contains_local_function(expr),
+        Statement::Block(block) => block_contains_local_function(block),
+        Statement::Defer(defer_stmt) => block_contains_local_function(&defer_stmt.body),
+    }
+}
+
+fn if_contains_local_function(if_stmt: &IfStatement) -> bool {
+    expression_contains_local_function(&if_stmt.condition)
+        || block_contains_local_function(&if_stmt.then_branch)
+        || match &if_stmt.else_branch {
+            Some(ElseBranch::Block(block)) => block_contains_local_function(block),
+            Some(ElseBranch::If(else_if)) => if_contains_local_function(else_if),
+            None => false,
+        }
+}
+
+/// 式の中のブロック式にローカル関数の定義があるか
+fn expression_contains_local_function(expr: &Expression) -> bool {
+    match expr {
+        Expression::Block(block_expr) => {
+            block_expr.statements.iter().any(statement_contains_local_function)
+                || block_expr.last_expr.as_deref().is_some_and(expression_contains_local_function)
+        }
+        Expression::Call(call) => any_contains(std::iter::once(call.callee.as_ref()).chain(&call.args)),
+        Expression::MethodCall(call) => any_contains(std::iter::once(call.object.as_ref()).chain(&call.args)),
+        Expression::Binary(binary) => any_contains([binary.left.as_ref(), binary.right.as_ref()]),
+        Expression::Unary(unary) => expression_contains_local_function(&unary.expr),
+        Expression::Index(index) => any_contains([index.object.as_ref(), index.index.as_ref()]),
+        Expression::Field(field) => expression_contains_local_function(&field.object),
+        Expression::Reference(ref_expr) => expression_contains_local_function(&ref_expr.expr),
+        Expression::Dereference(deref_expr) => expression_contains_local_function(&deref_expr.expr),
+        Expression::Cast(cast) => expression_contains_local_function(&cast.expr),
+        Expression::Assignment(assign) => any_contains([assign.target.as_ref(), assign.value.as_ref()]),
+        Expression::StructLit(struct_lit) => any_contains(struct_lit.fields.iter().map(|field| &field.value)),
+        Expression::EnumVariant(enum_variant) => match &enum_variant.fields {
+            EnumVariantFields::Tuple(args) => any_contains(args.iter()),
+            EnumVariantFields::Struct(fields) => any_contains(fields.iter().map(|field| &field.value)),
+            EnumVariantFields::Unit => false,
+        },
+        Expression::Array(array) => any_contains(array.elements.iter()),
+        Expression::Tuple(tuple) => any_contains(tuple.elements.iter()),
+        Expression::ListLiteral(list) => any_contains(list.elements.iter()),
+        Expression::MapLiteral(map) => any_contains(map.pairs.iter().flat_map(|(key, value)| [key, value])),
+        Expression::TemplateString(template) => any_contains(template.parts.iter().filter_map(|part| match part {
+            TemplateStringPart::Interpolation(expr) => Some(expr),
+            TemplateStringPart::Text(_) => None,
+        })),
+        Expression::If(if_expr) => {
+            any_contains([if_expr.condition.as_ref(), if_expr.then_branch.as_ref()].into_iter().chain(if_expr.else_branch.as_deref()))
+        }
+        Expression::Match(match_expr) => {
+            expression_contains_local_function(&match_expr.expr)
+                || any_contains(match_expr.arms.iter().flat_map(|arm| arm.guard.iter().chain([&arm.expr])))
+        }
+        Expression::Integer(_)
+        | Expression::Float(_)
+        | Expression::String(_)
+        | Expression::Bytes(_)
+        | Expression::Boolean(_)
+        | Expression::Identifier(_)
+        | Expression::Path(_) => false,
+    }
+}
+
+fn any_contains<'e>(exprs: impl IntoIterator<Item = &'e Expression>) -> bool {
+    exprs.into_iter().any(expression_contains_local_function)
+}
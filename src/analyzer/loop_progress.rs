@@ -0,0 +1,302 @@
+//! ループの進行の検査
+//!
+//! 条件の変数をループ本体で変更し忘れた `while` / `for` ループを見つけます。
+//! 警告のための最善努力の解析で、誤検知を避けるために次の場合は変更されたものとみなします。
+//!
+//! - 変数（またはそのフィールド・要素）への代入
+//! - `&mut` で借用している（関数に渡して変更できる）
+//! - 変数をレシーバーとしたメソッド呼び出し
+//! - 参照型の変数を関数の引数に渡している
+//!
+//! 参照外しを通した代入は別名の変数を変更できるため、これを含むループは検査しません。
+//! 条件が関数呼び出しや参照外しを含む場合も、反復ごとに値が変わり得るため検査しません。
+//! 本体にreturnがあるループは条件に関わらず終了できるため、警告の対象外です。
+
+use std::collections::HashSet;
+
+use crate::ast::*;
+
+/// ループ本体で変更されない条件の変数を返す（警告しない場合はNone）
+///
+/// `is_reference` は変数が参照型かどうかを返します。
+/// `update` はfor文の更新式で、本体と同じく変更として扱います。
+pub(crate) fn unmodified_condition_variables(
+    condition: &Expression,
+    body: &Block,
+    update: Option<&Expression>,
+    is_reference: impl Fn(&str) -> bool,
+) -> Option<Vec<String>> {
+    let mut reads = ConditionReads::default();
+    reads.visit_expr(condition);
+    if reads.opaque || reads.variables.is_empty() {
+        return None;
+    }
+
+    let mut effects = BodyEffects::default();
+    effects.visit_block(body);
+    if let Some(update) = update {
+        effects.visit_expr(update);
+    }
+    if effects.opaque || effects.exits {
+        return None;
+    }
+
+    let modified = |name: &str| {
+        effects.modified.contains(name) || (effects.passed.contains(name) && is_reference(name))
+    };
+    if reads.variables.iter().any(|name| modified(name)) {
+        return None;
+    }
+    Some(reads.variables)
+}
+
+/// 本体からループを抜けるreturnがないかどうか（条件のないループの検査用）
+pub(crate) fn never_exits(body: &Block) -> bool {
+    let mut effects = BodyEffects::default();
+    effects.visit_block(body);
+    !effects.exits
+}
+
+/// 条件が読む変数
+#[derive(Default)]
+struct ConditionReads {
+    /// 出現順の変数名（重複なし）
+    variables: Vec<String>,
+    /// 呼び出しや参照外しを含み、変数だけでは値が決まらない
+    opaque: bool,
+}
+
+impl ConditionReads {
+    fn visit_expr(&mut self, expr: &Expression) {
+        match expr {
+            Expression::Identifier(ident) if !self.variables.contains(&ident.name) => {
+                self.variables.push(ident.name.clone());
+            }
+            Expression::Call(_) | Expression::MethodCall(_) | Expression::Dereference(_) => self.opaque = true,
+            // 条件の中のブロックや代入は珍しいため、解析せずに検査を諦める
+            Expression::Block(_) | Expression::Assignment(_) | Expression::Match(_) | Expression::If(_) => {
+                self.opaque = true
+            }
+            Expression::Binary(binary) => {
+                self.visit_expr(&binary.left);
+                self.visit_expr(&binary.right);
+            }
+            Expression::Unary(unary) => self.visit_expr(&unary.expr),
+            Expression::Cast(cast) => self.visit_expr(&cast.expr),
+            Expression::Field(field) => self.visit_expr(&field.object),
+            Expression::Index(index) => {
+                self.visit_expr(&index.object);
+                self.visit_expr(&index.index);
+            }
+            Expression::Reference(reference) => self.visit_expr(&reference.expr),
+            _ => {}
+        }
+    }
+}
+
+/// ループ本体の副作用
+#[derive(Default)]
+struct BodyEffects {
+    /// 代入・可変借用・メソッド呼び出しで変更され得る変数
+    modified: HashSet<String>,
+    /// 関数やメソッドの引数にそのまま渡した変数
+    passed: HashSet<String>,
+    /// 参照外しを通した代入を含む
+    opaque: bool,
+    /// returnを含む
+    exits: bool,
+}
+
+impl BodyEffects {
+    fn visit_block(&mut self, block: &Block) {
+        for stmt in &block.statements {
+            self.visit_statement(stmt);
+        }
+    }
+
+    fn visit_statement(&mut self, stmt: &Statement) {
+        match stmt {
+            Statement::Let(let_stmt) => {
+                if let Some(init) = &let_stmt.init {
+                    self.visit_expr(init);
+                }
+            }
+            Statement::Assignment(assign) => {
+                self.assign_to(&assign.target);
+                self.visit_expr(&assign.value);
+            }
+            Statement::Return(ret) => {
+                self.exits = true;
+                if let Some(value) = &ret.value {
+                    self.visit_expr(value);
+                }
+            }
+            Statement::If(if_stmt) => self.visit_if(if_stmt),
+            Statement::While(while_stmt) => {
+                self.visit_expr(&while_stmt.condition);
+                self.visit_block(&while_stmt.body);
+            }
+            Statement::For(for_stmt) => {
+                if let Some(init) = &for_stmt.init {
+                    self.visit_statement(init);
+                }
+                if let Some(condition) = &for_stmt.condition {
+                    self.visit_expr(condition);
+                }
+                if let Some(update) = &for_stmt.update {
+                    self.visit_expr(update);
+                }
+                self.visit_block(&for_stmt.body);
+            }
+            Statement::Expression(expr) => self.visit_expr(expr),
+            Statement::Block(block) => self.visit_block(block),
+            Statement::Defer(defer_stmt) => self.visit_block(&defer_stmt.body),
+            Statement::Function(_) => {}
+        }
+    }
+
+    fn visit_if(&mut self, if_stmt: &IfStatement) {
+        self.visit_expr(&if_stmt.condition);
+        self.visit_block(&if_stmt.then_branch);
+        match &if_stmt.else_branch {
+            Some(ElseBranch::Block(block)) => self.visit_block(block),
+            Some(ElseBranch::If(else_if)) => self.visit_if(else_if),
+            None => {}
+        }
+    }
+
+    /// 代入先の変数を変更されたものとして記録する
+    fn assign_to(&mut self, target: &Expression) {
+        match root_variable(target) {
+            Some(name) => {
+                self.modified.insert(name.to_string());
+            }
+            None => self.opaque = true,
+        }
+        // 添字など代入先に含まれる式も評価される
+        self.visit_expr(target);
+    }
+
+    fn visit_args(&mut self, args: &[Expression]) {
+        for arg in args {
+            if let Expression::Identifier(ident) = arg {
+                self.passed.insert(ident.name.clone());
+            }
+            self.visit_expr(arg);
+        }
+    }
+
+    fn visit_expr(&mut self, expr: &Expression) {
+        match expr {
+            Expression::Assignment(assign) => {
+                self.assign_to(&assign.target);
+                self.visit_expr(&assign.value);
+            }
+            Expression::Reference(reference) => {
+                if reference.is_mut {
+                    match root_variable(&reference.expr) {
+                        Some(name) => {
+                            self.modified.insert(name.to_string());
+                        }
+                        None => self.opaque = true,
+                    }
+                }
+                self.visit_expr(&reference.expr);
+            }
+            Expression::MethodCall(call) => {
+                if let Some(name) = root_variable(&call.object) {
+                    self.modified.insert(name.to_string());
+                }
+                self.visit_expr(&call.object);
+                self.visit_args(&call.args);
+            }
+            Expression::Call(call) => {
+                self.visit_expr(&call.callee);
+                self.visit_args(&call.args);
+            }
+            Expression::Binary(binary) => {
+                self.visit_expr(&binary.left);
+                self.visit_expr(&binary.right);
+            }
+            Expression::Unary(unary) => self.visit_expr(&unary.expr),
+            Expression::Cast(cast) => self.visit_expr(&cast.expr),
+            Expression::Field(field) => self.visit_expr(&field.object),
+            Expression::Index(index) => {
+                self.visit_expr(&index.object);
+                self.visit_expr(&index.index);
+            }
+            Expression::Dereference(deref) => self.visit_expr(&deref.expr),
+            Expression::TemplateString(template) => {
+                for part in &template.parts {
+                    if let TemplateStringPart::Interpolation(expr) = part {
+                        self.visit_expr(expr);
+                    }
+                }
+            }
+            Expression::StructLit(lit) => {
+                for field in &lit.fields {
+                    self.visit_expr(&field.value);
+                }
+            }
+            Expression::EnumVariant(variant) => match &variant.fields {
+                EnumVariantFields::Tuple(args) => self.visit_args(args),
+                EnumVariantFields::Struct(fields) => {
+                    for field in fields {
+                        self.visit_expr(&field.value);
+                    }
+                }
+                EnumVariantFields::Unit => {}
+            },
+            Expression::Array(array) => self.visit_args(&array.elements),
+            Expression::Tuple(tuple) => self.visit_args(&tuple.elements),
+            Expression::ListLiteral(list) => self.visit_args(&list.elements),
+            Expression::MapLiteral(map) => {
+                for (key, value) in &map.pairs {
+                    self.visit_expr(key);
+                    self.visit_expr(value);
+                }
+            }
+            Expression::Match(match_expr) => {
+                self.visit_expr(&match_expr.expr);
+                for arm in &match_expr.arms {
+                    if let Some(guard) = &arm.guard {
+                        self.visit_expr(guard);
+                    }
+                    self.visit_expr(&arm.expr);
+                }
+            }
+            Expression::If(if_expr) => {
+                self.visit_expr(&if_expr.condition);
+                self.visit_expr(&if_expr.then_branch);
+                if let Some(else_branch) = &if_expr.else_branch {
+                    self.visit_expr(else_branch);
+                }
+            }
+            Expression::Block(block) => {
+                for stmt in &block.statements {
+                    self.visit_statement(stmt);
+                }
+                if let Some(last) = &block.last_expr {
+                    self.visit_expr(last);
+                }
+            }
+            Expression::Integer(_)
+            | Expression::Float(_)
+            | Expression::String(_)
+            | Expression::Bytes(_)
+            | Expression::Boolean(_)
+            | Expression::Identifier(_)
+            | Expression::Path(_) => {}
+        }
+    }
+}
+
+/// 代入や借用の対象の根元の変数（参照外しを通す場合はNone）
+fn root_variable(expr: &Expression) -> Option<&str> {
+    match expr {
+        Expression::Identifier(ident) => Some(&ident.name),
+        Expression::Field(field) => root_variable(&field.object),
+        Expression::Index(index) => root_variable(&index.object),
+        _ => None,
+    }
+}
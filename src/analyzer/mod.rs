@@ -3,15 +3,36 @@
 //! このモジュールは型チェック、名前解決、ライフタイム解析、
 //! その他のセマンティック検証を行います。
 
-pub mod semantic_analyzer;
+pub(crate) mod semantic_analyzer;
 mod borrow_checker;
+pub(crate) mod call_graph;
+pub(crate) mod builtin_methods;
+mod const_prop;
+mod divergence;
 mod lifetime;
-pub mod monomorphization;
+mod lint;
+pub(crate) mod local_functions;
+mod loop_progress;
+pub(crate) mod monomorphization;
+pub(crate) mod numeric;
+pub(crate) mod prelude;
 mod symbol;
+pub(crate) mod tail_position;
 mod type_checker;
 mod type_env;
 mod type_inference;
+pub(crate) mod type_table;
 
 // 公開API
 pub use semantic_analyzer::SemanticAnalyzer;
-pub use monomorphization::monomorphize_program;
\ No newline at end of file
+pub use borrow_checker::BorrowCheckMode;
+pub use call_graph::{prune_unreachable, CallEdge, CallGraph, CallKind};
+pub use lint::{Lint, LintLevel, LintLevels};
+pub use monomorphization::{
+    monomorphize_program, monomorphize_program_with_stats, monomorphize_program_with_types,
+    InstantiationOrigin, MonomorphizationStats, SourceMap,
+};
+pub use prelude::{resolve_names, NameResolution};
+pub use tail_position::mark_tail_calls;
+pub use type_checker::display_type;
+pub use type_table::TypeTable;
\ No newline at end of file
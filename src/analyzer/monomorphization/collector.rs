@@ -3,7 +3,9 @@
 use std::collections::HashMap;
 use crate::ast::*;
 use crate::error::YuniResult;
-use super::{Monomorphizer, InstantiationType};
+use super::{Monomorphizer, InstantiationRequest, InstantiationType, MonomorphizedFunction, MonomorphizedStruct};
+use super::mangling::{mangle_function_name, mangle_struct_name};
+use super::type_inference::contains_type_variable;
 
 impl Monomorphizer {
     /// ジェネリック定義を収集
@@ -75,7 +77,8 @@ impl Monomorphizer {
                     self.collect_instantiations_in_expr(init, type_params)?;
                 }
                 if let Some(ty) = &let_stmt.ty {
-                    self.collect_instantiations_in_type(ty, type_params)?;
+                    self.collect_instantiations_in_type(ty, let_stmt.span)?;
+                    self.resolve_struct_lit_from_annotation(ty, let_stmt.init.as_ref());
                 }
             }
             Statement::Assignment(assign) => {
@@ -91,11 +94,7 @@ impl Monomorphizer {
                 }
             }
             Statement::If(if_stmt) => {
-                self.collect_instantiations_in_expr(&if_stmt.condition, type_params)?;
-                self.collect_instantiations_in_block(&if_stmt.then_branch, type_params)?;
-                if let Some(ElseBranch::Block(else_block)) = &if_stmt.else_branch {
-                    self.collect_instantiations_in_block(else_block, type_params)?;
-                }
+                self.collect_instantiations_in_if(if_stmt, type_params)?;
             }
             Statement::While(while_stmt) => {
                 self.collect_instantiations_in_expr(&while_stmt.condition, type_params)?;
@@ -116,10 +115,25 @@ impl Monomorphizer {
             Statement::Block(block) => {
                 self.collect_instantiations_in_block(block, type_params)?;
             }
+            Statement::Defer(defer_stmt) => {
+                self.collect_instantiations_in_block(&defer_stmt.body, type_params)?;
+            }
+            Statement::Function(_) => {}
         }
         Ok(())
     }
     
+    /// if文（else ifの連鎖を含む）でのジェネリックの使用箇所を検出
+    fn collect_instantiations_in_if(&mut self, if_stmt: &IfStatement, type_params: &HashMap<String, Type>) -> YuniResult<()> {
+        self.collect_instantiations_in_expr(&if_stmt.condition, type_params)?;
+        self.collect_instantiations_in_block(&if_stmt.then_branch, type_params)?;
+        match &if_stmt.else_branch {
+            Some(ElseBranch::Block(else_block)) => self.collect_instantiations_in_block(else_block, type_params),
+            Some(ElseBranch::If(else_if)) => self.collect_instantiations_in_if(else_if, type_params),
+            None => Ok(()),
+        }
+    }
+    
     /// 式内でのジェネリックの使用箇所を検出
     pub(super) fn collect_instantiations_in_expr(&mut self, expr: &Expression, type_params: &HashMap<String, Type>) -> YuniResult<()> {
         match expr {
@@ -130,9 +144,15 @@ impl Monomorphizer {
                     if self.generic_functions.contains_key(&ident.name) {
                         // TODO: 型推論結果から実際の型引数を取得
                         // 現在は単純化のため、引数から推論
-                        let type_args = self.infer_type_args_from_call(&ident.name, &call.args)?;
+                        let type_args = self.infer_type_args_from_call(&ident.name, call, &call.args, type_params)?;
                         if !type_args.is_empty() {
-                            self.queue_instantiation(&ident.name, type_args, InstantiationType::Function);
+                            // 置換パスはここで決めたインスタンスをそのまま使う
+                            self.resolved_calls.insert(call.span, MonomorphizedFunction {
+                                original_name: ident.name.clone(),
+                                mangled_name: mangle_function_name(&ident.name, &type_args),
+                                type_args: type_args.clone(),
+                            });
+                            self.queue_instantiation(&ident.name, type_args, InstantiationType::Function, call.span);
                         }
                     }
                 }
@@ -147,9 +167,14 @@ impl Monomorphizer {
                 if let Some(name) = &struct_lit.name {
                     if self.generic_structs.contains_key(name) {
                         // TODO: フィールドの型から型引数を推論
-                        let type_args = self.infer_type_args_from_struct_lit(struct_lit)?;
+                        let type_args = self.infer_type_args_from_struct_lit(struct_lit, type_params)?;
                         if !type_args.is_empty() {
-                            self.queue_instantiation(name, type_args, InstantiationType::Struct);
+                            self.resolved_struct_lits.insert(struct_lit.span, MonomorphizedStruct {
+                                original_name: name.clone(),
+                                mangled_name: mangle_struct_name(name, &type_args),
+                                type_args: type_args.clone(),
+                            });
+                            self.queue_instantiation(name, type_args, InstantiationType::Struct, struct_lit.span);
                         }
                     }
                 }
@@ -181,40 +206,143 @@ impl Monomorphizer {
             Expression::Match(match_expr) => {
                 self.collect_instantiations_in_expr(&match_expr.expr, type_params)?;
                 for arm in &match_expr.arms {
+                    if let Some(guard) = &arm.guard {
+                        self.collect_instantiations_in_expr(guard, type_params)?;
+                    }
                     self.collect_instantiations_in_expr(&arm.expr, type_params)?;
                 }
             }
-            // 他の式タイプも必要に応じて処理
-            _ => {}
+            Expression::Unary(unary) => {
+                self.collect_instantiations_in_expr(&unary.expr, type_params)?;
+            }
+            Expression::MethodCall(method_call) => {
+                self.collect_instantiations_in_expr(&method_call.object, type_params)?;
+                for arg in &method_call.args {
+                    self.collect_instantiations_in_expr(arg, type_params)?;
+                }
+            }
+            Expression::Index(index) => {
+                self.collect_instantiations_in_expr(&index.object, type_params)?;
+                self.collect_instantiations_in_expr(&index.index, type_params)?;
+            }
+            Expression::Field(field) => {
+                self.collect_instantiations_in_expr(&field.object, type_params)?;
+            }
+            Expression::Reference(ref_expr) => {
+                self.collect_instantiations_in_expr(&ref_expr.expr, type_params)?;
+            }
+            Expression::Dereference(deref_expr) => {
+                self.collect_instantiations_in_expr(&deref_expr.expr, type_params)?;
+            }
+            Expression::Cast(cast) => {
+                self.collect_instantiations_in_expr(&cast.expr, type_params)?;
+            }
+            Expression::Assignment(assign) => {
+                self.collect_instantiations_in_expr(&assign.target, type_params)?;
+                self.collect_instantiations_in_expr(&assign.value, type_params)?;
+            }
+            Expression::TemplateString(template) => {
+                for part in &template.parts {
+                    if let TemplateStringPart::Interpolation(expr) = part {
+                        self.collect_instantiations_in_expr(expr, type_params)?;
+                    }
+                }
+            }
+            Expression::EnumVariant(enum_variant) => match &enum_variant.fields {
+                EnumVariantFields::Tuple(args) => {
+                    for arg in args {
+                        self.collect_instantiations_in_expr(arg, type_params)?;
+                    }
+                }
+                EnumVariantFields::Struct(fields) => {
+                    for field in fields {
+                        self.collect_instantiations_in_expr(&field.value, type_params)?;
+                    }
+                }
+                EnumVariantFields::Unit => {}
+            },
+            Expression::Array(array) => {
+                for elem in &array.elements {
+                    self.collect_instantiations_in_expr(elem, type_params)?;
+                }
+            }
+            Expression::Tuple(tuple) => {
+                for elem in &tuple.elements {
+                    self.collect_instantiations_in_expr(elem, type_params)?;
+                }
+            }
+            Expression::ListLiteral(list) => {
+                for elem in &list.elements {
+                    self.collect_instantiations_in_expr(elem, type_params)?;
+                }
+            }
+            Expression::MapLiteral(map) => {
+                for (key, value) in &map.pairs {
+                    self.collect_instantiations_in_expr(key, type_params)?;
+                    self.collect_instantiations_in_expr(value, type_params)?;
+                }
+            }
+            Expression::Integer(_)
+            | Expression::Float(_)
+            | Expression::String(_)
+            | Expression::Bytes(_)
+            | Expression::Boolean(_)
+            | Expression::Identifier(_)
+            | Expression::Path(_) => {}
         }
         Ok(())
     }
     
+    /// フィールドから型引数を推論できなかった構造体リテラルに、変数の型注釈の型引数を使う
+    ///
+    /// `let v: Vec<i32> = Vec { data: [], len: 0 };` のように、リテラルだけでは型引数が決まらない場合です。
+    fn resolve_struct_lit_from_annotation(&mut self, ty: &Type, init: Option<&Expression>) {
+        let (Type::Generic(name, args), Some(Expression::StructLit(struct_lit))) = (ty, init) else {
+            return;
+        };
+        if struct_lit.name.as_ref() != Some(name)
+            || !self.generic_structs.contains_key(name)
+            || args.iter().any(contains_type_variable)
+        {
+            return;
+        }
+        self.resolved_struct_lits.entry(struct_lit.span).or_insert_with(|| MonomorphizedStruct {
+            original_name: name.clone(),
+            mangled_name: mangle_struct_name(name, args),
+            type_args: args.clone(),
+        });
+    }
+
     /// 型内でのジェネリックの使用箇所を検出
-    fn collect_instantiations_in_type(&mut self, ty: &Type, _type_params: &HashMap<String, Type>) -> YuniResult<()> {
+    ///
+    /// 型変数を含む型はまだ具体化されていないため、インスタンス化しません。
+    /// 型はスパンを持たないため、`span` にはその型を書いた宣言や文のスパンを渡します。
+    pub(super) fn collect_instantiations_in_type(&mut self, ty: &Type, span: Span) -> YuniResult<()> {
         match ty {
             Type::Generic(name, args) => {
                 // ジェネリック型の使用を検出
-                if self.generic_structs.contains_key(name) {
-                    self.queue_instantiation(name, args.clone(), InstantiationType::Struct);
-                } else if self.generic_enums.contains_key(name) {
-                    self.queue_instantiation(name, args.clone(), InstantiationType::Enum);
+                if !args.iter().any(contains_type_variable) {
+                    if self.generic_structs.contains_key(name) {
+                        self.queue_instantiation(name, args.clone(), InstantiationType::Struct, span);
+                    } else if self.generic_enums.contains_key(name) {
+                        self.queue_instantiation(name, args.clone(), InstantiationType::Enum, span);
+                    }
                 }
                 
                 // 型引数も再帰的に処理
                 for arg in args {
-                    self.collect_instantiations_in_type(arg, _type_params)?;
+                    self.collect_instantiations_in_type(arg, span)?;
                 }
             }
             Type::Array(elem) => {
-                self.collect_instantiations_in_type(elem, _type_params)?;
+                self.collect_instantiations_in_type(elem, span)?;
             }
             Type::Reference(inner, _) => {
-                self.collect_instantiations_in_type(inner, _type_params)?;
+                self.collect_instantiations_in_type(inner, span)?;
             }
             Type::Tuple(elems) => {
                 for elem in elems {
-                    self.collect_instantiations_in_type(elem, _type_params)?;
+                    self.collect_instantiations_in_type(elem, span)?;
                 }
             }
             _ => {}
@@ -223,7 +351,9 @@ impl Monomorphizer {
     }
     
     /// インスタンス化をキューに追加
-    pub(super) fn queue_instantiation(&mut self, name: &str, type_args: Vec<Type>, inst_type: InstantiationType) {
+    ///
+    /// `span` はインスタンス化を要求した箇所です。同じインスタンスが複数回要求された場合は最初の箇所を記録します。
+    pub(super) fn queue_instantiation(&mut self, name: &str, type_args: Vec<Type>, inst_type: InstantiationType, span: Span) {
         let key = (name.to_string(), type_args.clone());
         
         // 既に単相化済みかチェック
@@ -232,8 +362,17 @@ impl Monomorphizer {
             InstantiationType::Struct | InstantiationType::Enum => self.monomorphized_structs.contains(&key),
         };
         
-        if !already_monomorphized {
-            self.instantiation_queue.push((name.to_string(), type_args, inst_type));
+        if already_monomorphized {
+            log::trace!(target: "yuni::mono", "{} already queued", describe_instance(name, &key.1));
+        } else {
+            log::debug!(target: "yuni::mono", "queued instantiation {} ({:?})", describe_instance(name, &key.1), inst_type);
+            self.instantiation_queue.push(InstantiationRequest {
+                name: name.to_string(),
+                type_args,
+                kind: inst_type,
+                requested_by: self.current_chain.clone(),
+                requested_at: span,
+            });
             
             // キューに追加したことを記録
             match inst_type {
@@ -246,4 +385,10 @@ impl Monomorphizer {
             }
         }
     }
-}
\ No newline at end of file
+}
+
+/// ログに出すインスタンスの表示（`max<f64>` の形式）
+fn describe_instance(name: &str, type_args: &[Type]) -> String {
+    let args: Vec<String> = type_args.iter().map(print_type).collect();
+    format!("{}<{}>", name, args.join(", "))
+}
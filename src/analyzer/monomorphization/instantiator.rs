@@ -3,32 +3,67 @@
 use std::collections::HashMap;
 use crate::ast::*;
 use crate::error::YuniResult;
-use super::{Monomorphizer, InstantiationType};
+use super::{Monomorphizer, InstantiationOrigin, InstantiationRequest, InstantiationType};
 
 impl Monomorphizer {
     /// インスタンス化処理
-    pub(super) fn process_instantiation(&mut self, name: &str, type_args: &[Type], inst_type: InstantiationType) -> YuniResult<()> {
-        match inst_type {
+    pub(super) fn process_instantiation(&mut self, request: InstantiationRequest) -> YuniResult<()> {
+        let Some(type_params) = self.generic_type_params(&request) else {
+            return Ok(());
+        };
+        let origin = InstantiationOrigin {
+            generic_name: request.name.clone(),
+            type_params,
+            type_args: request.type_args.clone(),
+            requested_at: request.requested_at,
+        };
+        
+        // インスタンスの中で見つかったインスタンス化は、このインスタンスが要求したものとして記録する
+        let mut chain = request.requested_by.clone();
+        chain.push((request.name.clone(), request.type_args.clone()));
+        self.current_chain = chain;
+        let result = self.instantiate(&request);
+        self.current_chain.clear();
+        
+        let (item_name, item) = result.map_err(|error| origin.wrap_error(error))?;
+        log::debug!(target: "yuni::mono", "instantiated {} as {}", origin, item_name);
+        self.generated_items.push(item);
+        self.source_map.insert(item_name.clone(), origin);
+        self.record_instantiation(&request, &item_name)
+    }
+    
+    /// 要求されたジェネリック定義の型パラメータ名（定義が見つからなければ `None`）
+    fn generic_type_params(&self, request: &InstantiationRequest) -> Option<Vec<String>> {
+        let name = request.name.as_str();
+        let params = match request.kind {
+            InstantiationType::Function => &self.generic_functions.get(name)?.type_params,
+            InstantiationType::Struct => &self.generic_structs.get(name)?.type_params,
+            InstantiationType::Enum => &self.generic_enums.get(name)?.type_params,
+        };
+        Some(params.iter().map(|p| p.name.clone()).collect())
+    }
+    
+    /// 要求されたジェネリック定義を具体化し、生成したアイテムとその名前を返す
+    fn instantiate(&mut self, request: &InstantiationRequest) -> YuniResult<(String, Item)> {
+        let name = request.name.as_str();
+        let type_args = request.type_args.as_slice();
+        match request.kind {
             InstantiationType::Function => {
-                if let Some(func) = self.generic_functions.get(name).cloned() {
-                    let monomorphized = self.monomorphize_function(func, type_args)?;
-                    self.generated_items.push(Item::Function(monomorphized));
-                }
+                let func = self.generic_functions[name].clone();
+                let func = self.monomorphize_function(func, type_args)?;
+                Ok((func.name.clone(), Item::Function(func)))
             }
             InstantiationType::Struct => {
-                if let Some(struct_def) = self.generic_structs.get(name).cloned() {
-                    let monomorphized = self.monomorphize_struct(struct_def, type_args)?;
-                    self.generated_items.push(Item::TypeDef(TypeDef::Struct(monomorphized)));
-                }
+                let struct_def = self.generic_structs[name].clone();
+                let struct_def = self.monomorphize_struct(struct_def, type_args)?;
+                Ok((struct_def.name.clone(), Item::TypeDef(TypeDef::Struct(struct_def))))
             }
             InstantiationType::Enum => {
-                if let Some(enum_def) = self.generic_enums.get(name).cloned() {
-                    let monomorphized = self.monomorphize_enum(enum_def, type_args)?;
-                    self.generated_items.push(Item::TypeDef(TypeDef::Enum(monomorphized)));
-                }
+                let enum_def = self.generic_enums[name].clone();
+                let enum_def = self.monomorphize_enum(enum_def, type_args)?;
+                Ok((enum_def.name.clone(), Item::TypeDef(TypeDef::Enum(enum_def))))
             }
         }
-        Ok(())
     }
     
     /// 関数を単相化
@@ -50,11 +85,13 @@ impl Monomorphizer {
         // パラメータの型を置換
         for param in &mut func.params {
             param.ty = self.substitute_type(&param.ty, &type_map);
+            self.collect_instantiations_in_type(&param.ty, param.span)?;
         }
         
         // 戻り値の型を置換
         if let Some(ret_ty) = &mut func.return_type {
             *ret_ty = Box::new(self.substitute_type(ret_ty, &type_map));
+            self.collect_instantiations_in_type(ret_ty, func.span)?;
         }
         
         // 関数本体を置換
@@ -82,6 +119,7 @@ impl Monomorphizer {
         // フィールドの型を置換
         for field in &mut struct_def.fields {
             field.ty = self.substitute_type(&field.ty, &type_map);
+            self.collect_instantiations_in_type(&field.ty, field.span)?;
         }
         
         Ok(struct_def)
@@ -107,6 +145,7 @@ impl Monomorphizer {
         for variant in &mut enum_def.variants {
             for field in &mut variant.fields {
                 field.ty = self.substitute_type(&field.ty, &type_map);
+                self.collect_instantiations_in_type(&field.ty, field.span)?;
             }
         }
         
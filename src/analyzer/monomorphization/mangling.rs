@@ -63,6 +63,9 @@ pub fn type_to_string(ty: &Type) -> String {
         
         // 型変数
         Type::Variable(name) => format!("var_{}", name),
+
+        // 解析に失敗した式の型（エラーがあれば単相化しないため、通常は現れない）
+        Type::Error => "error".to_string(),
         
         // ジェネリック型
         Type::Generic(name, args) => {
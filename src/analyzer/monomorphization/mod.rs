@@ -4,7 +4,10 @@
 
 use std::collections::{HashMap, HashSet};
 use crate::ast::*;
-use crate::error::YuniResult;
+use crate::error::{CodeSizeError, YuniError, YuniResult};
+use super::local_functions::hoist_local_functions;
+use super::tail_position::mark_tail_calls;
+use super::type_table::TypeTable;
 
 // サブモジュール
 mod types;
@@ -14,6 +17,8 @@ mod instantiator;
 mod substitution;
 mod replacement;
 mod mangling;
+mod validation;
+mod variants;
 
 // 再エクスポート
 pub use types::*;
@@ -34,9 +39,25 @@ pub struct Monomorphizer {
     /// ジェネリック列挙型の定義（名前 -> 列挙型定義）
     pub(crate) generic_enums: HashMap<String, EnumDef>,
     /// 処理すべきインスタンス化のキュー
-    pub(crate) instantiation_queue: Vec<(String, Vec<Type>, InstantiationType)>,
+    pub(crate) instantiation_queue: Vec<InstantiationRequest>,
+    /// 処理中のインスタンスに至る要求の連鎖（ジェネリックでない関数を走査している間は空）
+    pub(crate) current_chain: Vec<(String, Vec<Type>)>,
+    /// 生成できるインスタンスの上限
+    pub(crate) max_instantiations: Option<usize>,
+    /// 生成したインスタンスの集計
+    pub(crate) stats: MonomorphizationStats,
+    /// 生成したアイテムから元のジェネリック定義への対応表
+    pub(crate) source_map: SourceMap,
     /// 生成された単相化アイテム
     pub(crate) generated_items: Vec<Item>,
+    /// 呼び出し箇所ごとに決めたジェネリック関数のインスタンス
+    ///
+    /// 置換パスは型引数を推論し直さず、この決定に従って呼び出し先を置き換えます。
+    pub(crate) resolved_calls: HashMap<Span, MonomorphizedFunction>,
+    /// 構造体リテラルごとに決めたジェネリック構造体のインスタンス
+    pub(crate) resolved_struct_lits: HashMap<Span, MonomorphizedStruct>,
+    /// セマンティック解析で決定した式の型
+    pub(crate) type_table: TypeTable,
 }
 
 impl Monomorphizer {
@@ -50,12 +71,53 @@ impl Monomorphizer {
             generic_structs: HashMap::new(),
             generic_enums: HashMap::new(),
             instantiation_queue: Vec::new(),
+            current_chain: Vec::new(),
+            max_instantiations: None,
+            stats: MonomorphizationStats::default(),
+            source_map: SourceMap::default(),
             generated_items: Vec::new(),
+            resolved_calls: HashMap::new(),
+            resolved_struct_lits: HashMap::new(),
+            type_table: TypeTable::new(),
         }
     }
+
+    /// セマンティック解析の型テーブルを使う単相化エンジンを作成
+    ///
+    /// 型引数の推論では、テーブルに記録された引数の型が優先されます。
+    pub fn with_type_table(program: Program, type_table: TypeTable) -> Self {
+        Self {
+            type_table,
+            ..Self::new(program)
+        }
+    }
+    
+    /// 生成できるインスタンスの数に上限を設ける
+    ///
+    /// 上限を超えた時点で、インスタンスを多く生成したジェネリック定義を挙げたエラーで中断します。
+    pub fn with_max_instantiations(mut self, max: Option<usize>) -> Self {
+        self.max_instantiations = max;
+        self
+    }
     
-    /// プログラムを単相化
-    pub fn monomorphize(mut self) -> YuniResult<Program> {
+    /// プログラムを単相化し、生成したアイテムの由来の対応表も返す
+    #[allow(dead_code)]
+    pub fn monomorphize(self) -> YuniResult<(Program, SourceMap)> {
+        self.monomorphize_with_stats()
+            .map(|(program, source_map, _)| (program, source_map))
+    }
+    
+    /// プログラムを単相化し、生成したアイテムの由来の対応表とインスタンスの集計も返す
+    pub fn monomorphize_with_stats(mut self) -> YuniResult<(Program, SourceMap, MonomorphizationStats)> {
+        // ローカル関数をトップレベルの関数に取り出す（外側の変数の参照は解析でエラーにしている）
+        hoist_local_functions(&mut self.original_program);
+
+        // ステップ0: 列挙型名を省略したバリアントを通常のバリアントに置き換え
+        self.resolve_contextual_variants();
+        
+        // 末尾位置の呼び出しに印を付ける（インスタンスは印の付いた本体から作られる）
+        mark_tail_calls(&mut self.original_program);
+        
         // ステップ1: ジェネリック定義を収集
         self.collect_generic_definitions();
         
@@ -63,8 +125,8 @@ impl Monomorphizer {
         self.collect_instantiations(&self.original_program.clone())?;
         
         // ステップ3: キューを処理して必要な単相化バージョンを生成
-        while let Some((name, type_args, inst_type)) = self.instantiation_queue.pop() {
-            self.process_instantiation(&name, &type_args, inst_type)?;
+        while let Some(request) = self.instantiation_queue.pop() {
+            self.process_instantiation(request)?;
         }
         
         // ステップ4: 単相化されたプログラムを構築
@@ -87,12 +149,57 @@ impl Monomorphizer {
         // ステップ5: すべてのジェネリック呼び出しを単相化バージョンに置き換え
         self.replace_generic_calls(&mut result_program)?;
         
-        Ok(result_program)
+        // ステップ6: 置き換えた呼び出し先がすべて生成されていることを確認
+        self.validate_instances(&result_program)?;
+        
+        Ok((result_program, self.source_map, self.stats))
+    }
+    
+    /// 生成したインスタンスを集計し、上限を超えていればエラーにする
+    pub(crate) fn record_instantiation(&mut self, request: &InstantiationRequest, item_name: &str) -> YuniResult<()> {
+        let root = request.root().to_string();
+        self.stats.instantiations += 1;
+        *self.stats.per_root.entry(root.clone()).or_insert(0) += 1;
+        self.stats.item_roots.insert(item_name.to_string(), root);
+        
+        match self.max_instantiations {
+            Some(limit) if self.stats.instantiations > limit => {
+                Err(YuniError::CodeSize(CodeSizeError::InstantiationLimitExceeded {
+                    limit,
+                    roots: self.stats.top_roots(REPORTED_ROOTS),
+                }))
+            }
+            _ => Ok(()),
+        }
     }
 }
 
+/// 規模の診断で内訳を挙げる起点の数
+pub const REPORTED_ROOTS: usize = 5;
+
 /// プログラムを単相化するエントリポイント
-pub fn monomorphize_program(program: Program) -> YuniResult<Program> {
+///
+/// 単相化したプログラムと、生成したアイテムから元のジェネリック定義への対応表を返します。
+#[allow(dead_code)]
+pub fn monomorphize_program(program: Program) -> YuniResult<(Program, SourceMap)> {
     let monomorphizer = Monomorphizer::new(program);
     monomorphizer.monomorphize()
+}
+
+/// セマンティック解析の型テーブルを使ってプログラムを単相化するエントリポイント
+#[allow(dead_code)]
+pub fn monomorphize_program_with_types(program: Program, type_table: TypeTable) -> YuniResult<(Program, SourceMap)> {
+    let monomorphizer = Monomorphizer::with_type_table(program, type_table);
+    monomorphizer.monomorphize()
+}
+
+/// インスタンス数の上限を設けて単相化し、生成したインスタンスの集計も返すエントリポイント
+pub fn monomorphize_program_with_stats(
+    program: Program,
+    type_table: TypeTable,
+    max_instantiations: Option<usize>,
+) -> YuniResult<(Program, SourceMap, MonomorphizationStats)> {
+    Monomorphizer::with_type_table(program, type_table)
+        .with_max_instantiations(max_instantiations)
+        .monomorphize_with_stats()
 }
\ No newline at end of file
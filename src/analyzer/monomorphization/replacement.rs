@@ -1,4 +1,8 @@
 //! ジェネリック呼び出しの置換処理
+//!
+//! 呼び出し先は型引数を推論し直さず、収集パスが呼び出し箇所ごとに記録した
+//! インスタンスに置き換えます。推論が箇所によって食い違っても、生成したインスタンスと
+//! 呼び出し先が一致します。
 
 use crate::ast::*;
 use crate::error::YuniResult;
@@ -118,6 +122,13 @@ impl Monomorphizer {
             Statement::Block(block) => {
                 Ok(Statement::Block(self.replace_calls_in_block(block)?))
             }
+            Statement::Defer(defer_stmt) => {
+                Ok(Statement::Defer(DeferStatement {
+                    body: self.replace_calls_in_block(&defer_stmt.body)?,
+                    span: defer_stmt.span,
+                }))
+            }
+            Statement::Function(func) => Ok(Statement::Function(func.clone())),
         }
     }
     
@@ -125,63 +136,42 @@ impl Monomorphizer {
     fn replace_calls_in_expr(&self, expr: &Expression) -> YuniResult<Expression> {
         match expr {
             Expression::Call(call) => {
-                if let Expression::Identifier(ident) = &*call.callee {
-                    // ジェネリック関数の呼び出しかチェック
-                    if self.generic_functions.contains_key(&ident.name) {
-                        // 型引数を推論
-                        let type_args = self.infer_type_args_from_call(&ident.name, &call.args)?;
-                        if !type_args.is_empty() {
-                            // マングルされた名前に置き換え
-                            let mangled_name = crate::analyzer::monomorphization::mangling::mangle_function_name(&ident.name, &type_args);
-                            return Ok(Expression::Call(CallExpr {
-                                callee: Box::new(Expression::Identifier(Identifier {
-                                    name: mangled_name,
-                                    span: ident.span,
-                                })),
-                                args: call.args.clone(),
-                                span: call.span,
-                                is_tail: call.is_tail,
-                            }));
-                        }
-                    }
-                }
-                
                 // 引数も再帰的に処理
                 let mut new_args = Vec::new();
                 for arg in &call.args {
                     new_args.push(self.replace_calls_in_expr(arg)?);
                 }
+                
+                // ジェネリック関数の呼び出しは、収集パスで決めたインスタンスに置き換え
+                let mut new_callee = call.callee.clone();
+                let mut type_args = call.type_args.clone();
+                if let Expression::Identifier(ident) = &*call.callee {
+                    let resolved = self.resolved_calls.get(&call.span)
+                        .filter(|resolved| resolved.original_name == ident.name);
+                    if let Some(resolved) = resolved {
+                        new_callee = Box::new(Expression::Identifier(Identifier {
+                            name: resolved.mangled_name.clone(),
+                            span: ident.span,
+                        }));
+                        type_args = Vec::new();
+                    }
+                }
                 Ok(Expression::Call(CallExpr {
-                    callee: call.callee.clone(),
+                    callee: new_callee,
+                    type_args,
                     args: new_args,
                     span: call.span,
                     is_tail: call.is_tail,
                 }))
             }
             Expression::StructLit(struct_lit) => {
-                // ジェネリック構造体のインスタンス化かチェック
-                if let Some(name) = &struct_lit.name {
-                    if self.generic_structs.contains_key(name) {
-                        // 型引数を推論
-                        let type_args = self.infer_type_args_from_struct_lit(struct_lit)?;
-                        if !type_args.is_empty() {
-                            // マングルされた名前に置き換え
-                            let mangled_name = crate::analyzer::monomorphization::mangling::mangle_struct_name(name, &type_args);
-                        let mut new_fields = Vec::new();
-                        for field in &struct_lit.fields {
-                            new_fields.push(StructFieldInit {
-                                name: field.name.clone(),
-                                value: self.replace_calls_in_expr(&field.value)?,
-                            });
-                        }
-                            return Ok(Expression::StructLit(StructLiteral {
-                                name: Some(mangled_name),
-                                fields: new_fields,
-                                span: struct_lit.span,
-                            }));
-                        }
-                    }
-                }
+                // ジェネリック構造体のリテラルは、収集パスで決めたインスタンスに置き換え
+                let resolved = self.resolved_struct_lits.get(&struct_lit.span)
+                    .filter(|resolved| struct_lit.name.as_ref() == Some(&resolved.original_name));
+                let (new_name, type_args) = match resolved {
+                    Some(resolved) => (Some(resolved.mangled_name.clone()), Vec::new()),
+                    None => (struct_lit.name.clone(), struct_lit.type_args.clone()),
+                };
                 
                 // フィールドの値も再帰的に処理
                 let mut new_fields = Vec::new();
@@ -192,7 +182,8 @@ impl Monomorphizer {
                     });
                 }
                 Ok(Expression::StructLit(StructLiteral {
-                    name: struct_lit.name.clone(),
+                    name: new_name,
+                    type_args,
                     fields: new_fields,
                     span: struct_lit.span,
                 }))
@@ -262,6 +253,7 @@ impl Monomorphizer {
                             .map(|guard| self.replace_calls_in_expr(guard))
                             .transpose()?,
                         expr: self.replace_calls_in_expr(&arm.expr)?,
+                        span: arm.span,
                     });
                 }
                 Ok(Expression::Match(MatchExpr {
@@ -310,6 +302,7 @@ impl Monomorphizer {
                     method: method.method.clone(),
                     args: new_args,
                     span: method.span,
+                    is_tail: method.is_tail,
                 }))
             }
             Expression::Cast(cast) => {
@@ -360,6 +353,56 @@ impl Monomorphizer {
                     span: map.span,
                 }))
             }
+            Expression::Assignment(assign) => {
+                Ok(Expression::Assignment(AssignmentExpr {
+                    target: Box::new(self.replace_calls_in_expr(&assign.target)?),
+                    value: Box::new(self.replace_calls_in_expr(&assign.value)?),
+                    span: assign.span,
+                }))
+            }
+            Expression::TemplateString(template) => {
+                let mut new_parts = Vec::new();
+                for part in &template.parts {
+                    new_parts.push(match part {
+                        TemplateStringPart::Text(text) => TemplateStringPart::Text(text.clone()),
+                        TemplateStringPart::Interpolation(expr) => {
+                            TemplateStringPart::Interpolation(self.replace_calls_in_expr(expr)?)
+                        }
+                    });
+                }
+                Ok(Expression::TemplateString(TemplateStringLit {
+                    parts: new_parts,
+                    span: template.span,
+                }))
+            }
+            Expression::EnumVariant(enum_variant) => {
+                let new_fields = match &enum_variant.fields {
+                    EnumVariantFields::Tuple(args) => {
+                        let mut new_args = Vec::new();
+                        for arg in args {
+                            new_args.push(self.replace_calls_in_expr(arg)?);
+                        }
+                        EnumVariantFields::Tuple(new_args)
+                    }
+                    EnumVariantFields::Struct(fields) => {
+                        let mut new_fields = Vec::new();
+                        for field in fields {
+                            new_fields.push(StructFieldInit {
+                                name: field.name.clone(),
+                                value: self.replace_calls_in_expr(&field.value)?,
+                            });
+                        }
+                        EnumVariantFields::Struct(new_fields)
+                    }
+                    EnumVariantFields::Unit => EnumVariantFields::Unit,
+                };
+                Ok(Expression::EnumVariant(EnumVariantExpr {
+                    enum_name: enum_variant.enum_name.clone(),
+                    variant: enum_variant.variant.clone(),
+                    fields: new_fields,
+                    span: enum_variant.span,
+                }))
+            }
             // リテラルや識別子などはそのまま
             _ => Ok(expr.clone()),
         }
@@ -16,6 +16,12 @@ impl Monomorphizer {
             Type::UserDefined(name) if type_map.contains_key(name) => {
                 type_map.get(name).cloned().unwrap()
             }
+            Type::Generic(name, args) => {
+                let substituted_args: Vec<Type> = args.iter()
+                    .map(|arg| self.substitute_type(arg, type_map))
+                    .collect();
+                Type::Generic(name.clone(), substituted_args)
+            }
             Type::Array(elem) => {
                 Type::Array(Box::new(self.substitute_type(elem, type_map)))
             }
@@ -59,6 +65,10 @@ impl Monomorphizer {
         match stmt {
             Statement::Let(let_stmt) => {
                 let new_ty = let_stmt.ty.as_ref().map(|ty| self.substitute_type(ty, type_map));
+                // 具体化された型注釈のジェネリック型をインスタンス化
+                if let Some(ty) = &new_ty {
+                    self.collect_instantiations_in_type(ty, let_stmt.span)?;
+                }
                 let new_init = let_stmt.init.as_ref()
                     .map(|init| self.substitute_expr(init, type_map))
                     .transpose()?;
@@ -145,6 +155,13 @@ impl Monomorphizer {
             Statement::Block(block) => {
                 Ok(Statement::Block(self.substitute_block(block, type_map)?))
             }
+            Statement::Defer(defer_stmt) => {
+                Ok(Statement::Defer(DeferStatement {
+                    body: self.substitute_block(&defer_stmt.body, type_map)?,
+                    span: defer_stmt.span,
+                }))
+            }
+            Statement::Function(func) => Ok(Statement::Function(func.clone())),
         }
     }
     
@@ -162,10 +179,10 @@ impl Monomorphizer {
                 if let Expression::Identifier(ident) = &new_callee {
                     if self.generic_functions.contains_key(&ident.name) {
                         // 型引数を推論
-                        let type_args = self.infer_type_args_from_call(&ident.name, &new_args)?;
+                        let type_args = self.infer_type_args_from_call(&ident.name, call, &new_args, type_map)?;
                         if !type_args.is_empty() {
                             // インスタンス化をキューに追加
-                            self.queue_instantiation(&ident.name, type_args.clone(), InstantiationType::Function);
+                            self.queue_instantiation(&ident.name, type_args.clone(), InstantiationType::Function, call.span);
                             // マングルされた名前に置き換え
                             let mangled_name = crate::analyzer::monomorphization::mangling::mangle_function_name(&ident.name, &type_args);
                             return Ok(Expression::Call(CallExpr {
@@ -173,6 +190,7 @@ impl Monomorphizer {
                                     name: mangled_name,
                                     span: ident.span,
                                 })),
+                                type_args: Vec::new(),
                                 args: new_args,
                                 span: call.span,
                                 is_tail: call.is_tail,
@@ -183,6 +201,7 @@ impl Monomorphizer {
                 
                 Ok(Expression::Call(CallExpr {
                     callee: Box::new(new_callee),
+                    type_args: call.type_args.iter().map(|ty| self.substitute_type(ty, type_map)).collect(),
                     args: new_args,
                     span: call.span,
                     is_tail: call.is_tail,
@@ -199,21 +218,24 @@ impl Monomorphizer {
                 
                 // ジェネリック構造体の場合、単相化された名前に置き換える
                 let mut new_name = struct_lit.name.clone();
+                let mut new_type_args: Vec<Type> = struct_lit.type_args.iter().map(|ty| self.substitute_type(ty, type_map)).collect();
                 if let Some(name) = &struct_lit.name {
                     if self.generic_structs.contains_key(name) {
                         // 型引数を推論
-                        let type_args = self.infer_type_args_from_struct_lit(struct_lit)?;
+                        let type_args = self.infer_type_args_from_struct_lit(struct_lit, type_map)?;
                         if !type_args.is_empty() {
                             // インスタンス化をキューに追加
-                            self.queue_instantiation(name, type_args.clone(), InstantiationType::Struct);
+                            self.queue_instantiation(name, type_args.clone(), InstantiationType::Struct, struct_lit.span);
                             // マングルされた名前に置き換え
                             new_name = Some(crate::analyzer::monomorphization::mangling::mangle_struct_name(name, &type_args));
+                            new_type_args = Vec::new();
                         }
                     }
                 }
                 
                 Ok(Expression::StructLit(StructLiteral {
                     name: new_name,
+                    type_args: new_type_args,
                     fields: new_fields,
                     span: struct_lit.span,
                 }))
@@ -275,6 +297,7 @@ impl Monomorphizer {
                             .map(|guard| self.substitute_expr(guard, type_map))
                             .transpose()?,
                         expr: self.substitute_expr(&arm.expr, type_map)?,
+                        span: arm.span,
                     });
                 }
                 Ok(Expression::Match(MatchExpr {
@@ -331,6 +354,7 @@ impl Monomorphizer {
                     method: method.method.clone(),
                     args: new_args,
                     span: method.span,
+                    is_tail: method.is_tail,
                 }))
             }
             Expression::Cast(cast) => {
@@ -1,13 +1,25 @@
 //! 型推論ヘルパー関数
 
 use std::collections::HashMap;
+use crate::analyzer::numeric::literal_type;
 use crate::ast::*;
 use crate::error::YuniResult;
 use super::Monomorphizer;
 
 impl Monomorphizer {
     /// 関数呼び出しから型引数を推論（簡略版）
-    pub(super) fn infer_type_args_from_call(&self, func_name: &str, args: &[Expression]) -> YuniResult<Vec<Type>> {
+    ///
+    /// `type_map` は呼び出しを含むアイテム自身の型パラメータの置換です。
+    /// 引数の型にはこの置換だけを適用し、呼び出し先の型パラメータとは
+    /// 名前が同じでも別のものとして扱います。
+    /// 引数の型がまだ型変数を含む場合（ジェネリック関数の本体を単相化前に
+    /// 走査している場合など）は、インスタンス化できないため空のベクタを返します。
+    /// 呼び出しに型引数を明示した場合（`make::<i64>()`）は、推論せずにその型引数を使います。
+    pub(super) fn infer_type_args_from_call(&self, func_name: &str, call: &CallExpr, args: &[Expression], type_map: &HashMap<String, Type>) -> YuniResult<Vec<Type>> {
+        if !call.type_args.is_empty() {
+            return Ok(self.explicit_type_args(&call.type_args, type_map));
+        }
+
         // TODO: 実際の型推論実装
         // 現在は単純に引数の型から推論
         
@@ -20,7 +32,7 @@ impl Monomorphizer {
             for (i, arg) in args.iter().enumerate() {
                 if i < generic_func.params.len() {
                     let param_type = &generic_func.params[i].ty;
-                    if let Some(arg_type) = self.infer_expr_type(arg) {
+                    if let Some(arg_type) = self.infer_expr_type_in_scope(arg, type_map) {
                         self.unify_types(param_type, &arg_type, &mut type_param_map);
                     }
                 }
@@ -36,6 +48,9 @@ impl Monomorphizer {
                 }
             }
             
+            if type_args.iter().any(contains_type_variable) {
+                return Ok(vec![]);
+            }
             Ok(type_args)
         } else {
             // ジェネリック関数でない場合は空のベクタを返す
@@ -51,6 +66,22 @@ impl Monomorphizer {
                 // 型変数の場合は型を記録
                 type_map.insert(name.clone(), arg_type.clone());
             }
+            (Type::Array(param_elem), Type::Array(arg_elem)) => {
+                self.unify_types(param_elem, arg_elem, type_map);
+            }
+            (Type::Reference(param_inner, _), Type::Reference(arg_inner, _)) => {
+                self.unify_types(param_inner, arg_inner, type_map);
+            }
+            (Type::Tuple(param_elems), Type::Tuple(arg_elems)) => {
+                for (param_elem, arg_elem) in param_elems.iter().zip(arg_elems) {
+                    self.unify_types(param_elem, arg_elem, type_map);
+                }
+            }
+            (Type::Generic(param_name, param_args), Type::Generic(arg_name, arg_args)) if param_name == arg_name => {
+                for (param_arg, arg_arg) in param_args.iter().zip(arg_args) {
+                    self.unify_types(param_arg, arg_arg, type_map);
+                }
+            }
             _ => {
                 // その他の場合は何もしない
             }
@@ -58,45 +89,72 @@ impl Monomorphizer {
     }
     
     /// 構造体リテラルから型引数を推論
-    pub(super) fn infer_type_args_from_struct_lit(&self, struct_lit: &StructLiteral) -> YuniResult<Vec<Type>> {
-        // TODO: 実際の型推論実装
-        // 現在はフィールドの値から簡易的に推論
-        let mut type_args = Vec::new();
-        
+    ///
+    /// フィールドの値の型には、リテラルを含むアイテム自身の型パラメータの置換を適用し、
+    /// 構造体定義のフィールドの型と統一して型引数を求めます。
+    pub(super) fn infer_type_args_from_struct_lit(&self, struct_lit: &StructLiteral, type_map: &HashMap<String, Type>) -> YuniResult<Vec<Type>> {
+        let Some(struct_def) = struct_lit.name.as_ref().and_then(|name| self.generic_structs.get(name)) else {
+            return Ok(vec![]);
+        };
+        if !struct_lit.type_args.is_empty() {
+            return Ok(self.explicit_type_args(&struct_lit.type_args, type_map));
+        }
+
+        let mut type_param_map: HashMap<String, Type> = HashMap::new();
         for field in &struct_lit.fields {
-            if let Some(ty) = self.infer_expr_type(&field.value) {
-                // 重複を避ける
-                if !type_args.contains(&ty) {
-                    type_args.push(ty);
-                }
+            let field_def = struct_def.fields.iter().find(|f| f.name == field.name);
+            if let (Some(field_def), Some(value_type)) = (field_def, self.infer_expr_type_in_scope(&field.value, type_map)) {
+                self.unify_types(&field_def.ty, &value_type, &mut type_param_map);
             }
         }
-        
-        Ok(type_args)
+
+        // すべての型パラメータが具体的な型に決まった場合だけインスタンス化できる
+        let type_args: Option<Vec<Type>> = struct_def.type_params.iter()
+            .map(|param| type_param_map.get(&param.name).cloned())
+            .collect();
+        match type_args {
+            Some(type_args) if !type_args.iter().any(contains_type_variable) => Ok(type_args),
+            _ => Ok(vec![]),
+        }
+    }
+
+    /// 明示した型引数にアイテム自身の型パラメータの置換を適用する
+    ///
+    /// 置換しても型変数が残る場合（ジェネリック関数の本体を単相化前に走査している場合など）は、
+    /// インスタンス化できないため空のベクタを返します。
+    fn explicit_type_args(&self, type_args: &[Type], type_map: &HashMap<String, Type>) -> Vec<Type> {
+        let type_args: Vec<Type> = type_args.iter().map(|ty| self.substitute_type(ty, type_map)).collect();
+        if type_args.iter().any(contains_type_variable) {
+            return vec![];
+        }
+        type_args
+    }
+
+    /// アイテムの型パラメータの置換を適用した式の型を推論
+    ///
+    /// セマンティック解析で記録された型がアイテム自身の型パラメータを含む場合は、
+    /// 置換を適用した型を返します。それ以外の型変数を含む型は使いません。
+    fn infer_expr_type_in_scope(&self, expr: &Expression, type_map: &HashMap<String, Type>) -> Option<Type> {
+        match self.type_table.get(expr).or_else(|| self.type_table.get_generic(expr)) {
+            Some(ty) if !matches!(ty, Type::Void) && !has_type_variable(ty, &|name| !type_map.contains_key(name)) => {
+                Some(self.substitute_type(ty, type_map))
+            }
+            _ => self.infer_expr_type(expr),
+        }
     }
     
     /// 式の型を推論（簡略版）
+    ///
+    /// セマンティック解析で型が決まっている式はその型を使用します。
     pub(super) fn infer_expr_type(&self, expr: &Expression) -> Option<Type> {
+        if let Some(ty) = self.type_table.resolved(expr) {
+            return Some(ty.clone());
+        }
+
         match expr {
-            Expression::Integer(int_lit) => {
-                match int_lit.suffix.as_deref() {
-                    Some("i8") => Some(Type::I8),
-                    Some("i16") => Some(Type::I16),
-                    Some("i64") => Some(Type::I64),
-                    Some("u8") => Some(Type::U8),
-                    Some("u16") => Some(Type::U16),
-                    Some("u32") => Some(Type::U32),
-                    Some("u64") => Some(Type::U64),
-                    _ => Some(Type::I32), // デフォルト
-                }
-            }
-            Expression::Float(float_lit) => {
-                match float_lit.suffix.as_deref() {
-                    Some("f32") => Some(Type::F32),
-                    _ => Some(Type::F64), // デフォルト
-                }
-            }
-            Expression::String(_) => Some(Type::String),
+            Expression::Integer(_) | Expression::Float(_) => literal_type(expr, None),
+            Expression::String(_) => Some(Type::Str),
+            Expression::Bytes(_) => Some(Type::Generic("Vec".to_string(), vec![Type::U8])),
             Expression::Boolean(_) => Some(Type::Bool),
             Expression::StructLit(struct_lit) => {
                 // 構造体リテラルの型を推論
@@ -149,4 +207,23 @@ impl Monomorphizer {
             _ => None,
         }
     }
-}
\ No newline at end of file
+}
+/// 型変数を含む型かどうか
+pub(super) fn contains_type_variable(ty: &Type) -> bool {
+    has_type_variable(ty, &|_| true)
+}
+
+/// 条件を満たす名前の型変数を含む型かどうか
+fn has_type_variable(ty: &Type, pred: &impl Fn(&str) -> bool) -> bool {
+    match ty {
+        Type::Variable(name) => pred(name),
+        Type::Generic(_, args) => args.iter().any(|arg| has_type_variable(arg, pred)),
+        Type::Array(elem) | Type::Reference(elem, _) => has_type_variable(elem, pred),
+        Type::Tuple(elems) => elems.iter().any(|elem| has_type_variable(elem, pred)),
+        Type::Function(func) => {
+            func.params.iter().any(|param| has_type_variable(param, pred))
+                || has_type_variable(&func.return_type, pred)
+        }
+        _ => false,
+    }
+}
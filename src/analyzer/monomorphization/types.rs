@@ -1,6 +1,9 @@
 //! 単相化に関する型定義
 
-use crate::ast::Type;
+use std::collections::HashMap;
+use std::fmt;
+use crate::ast::{print_type, Span, Type};
+use crate::error::YuniError;
 
 /// 単相化された関数の情報
 #[derive(Debug, Clone, PartialEq)]
@@ -30,4 +33,141 @@ pub enum InstantiationType {
     Function,
     Struct,
     Enum,
-}
\ No newline at end of file
+}
+
+/// インスタンス化の要求
+#[derive(Debug, Clone, PartialEq)]
+pub struct InstantiationRequest {
+    /// ジェネリック定義の名前
+    pub name: String,
+    /// 型引数
+    pub type_args: Vec<Type>,
+    /// インスタンス化の種類
+    pub kind: InstantiationType,
+    /// このインスタンス化を要求したインスタンスの連鎖（外側から順に、定義名と型引数）
+    ///
+    /// ジェネリックでない関数から直接要求された場合は空になります。
+    pub requested_by: Vec<(String, Vec<Type>)>,
+    /// このインスタンス化を最初に要求した箇所
+    pub requested_at: Span,
+}
+
+impl InstantiationRequest {
+    /// 連鎖の起点となったジェネリック定義の名前
+    pub fn root(&self) -> &str {
+        self.requested_by
+            .first()
+            .map_or(self.name.as_str(), |(name, _)| name.as_str())
+    }
+}
+
+/// 単相化で生成したインスタンスの集計
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MonomorphizationStats {
+    /// 生成したインスタンスの数
+    pub instantiations: usize,
+    /// 起点となったジェネリック定義ごとの生成数
+    pub per_root: HashMap<String, usize>,
+    /// 生成したアイテムの名前から、起点となったジェネリック定義の名前への対応
+    pub item_roots: HashMap<String, String>,
+}
+
+impl MonomorphizationStats {
+    /// 生成数の多い順に起点を最大 `n` 個並べる（同数の場合は名前順）
+    pub fn top_roots(&self, n: usize) -> Vec<(String, usize)> {
+        let mut roots: Vec<(String, usize)> = self
+            .per_root
+            .iter()
+            .map(|(name, count)| (name.clone(), *count))
+            .collect();
+        roots.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        roots.truncate(n);
+        roots
+    }
+
+    /// アイテムの起点となったジェネリック定義の名前
+    ///
+    /// 単相化で生成していないアイテムはそのアイテム自身が起点になります。
+    pub fn root_of<'a>(&'a self, item: &'a str) -> &'a str {
+        self.item_roots.get(item).map_or(item, String::as_str)
+    }
+}
+
+/// 単相化で生成したアイテムの元になったジェネリック定義
+#[derive(Debug, Clone, PartialEq)]
+pub struct InstantiationOrigin {
+    /// 元のジェネリック定義の名前
+    pub generic_name: String,
+    /// 元の定義の型パラメータ名
+    pub type_params: Vec<String>,
+    /// 具体的な型引数
+    pub type_args: Vec<Type>,
+    /// インスタンス化を最初に要求した箇所
+    pub requested_at: Span,
+}
+
+impl InstantiationOrigin {
+    /// このインスタンスの中で発生したエラーに、元の定義と要求箇所を付ける
+    pub fn wrap_error(&self, error: YuniError) -> YuniError {
+        YuniError::Instantiation {
+            error: Box::new(error),
+            instance: self.to_string(),
+            requested_at: self.requested_at,
+        }
+    }
+}
+
+impl fmt::Display for InstantiationOrigin {
+    /// `max<T=f64>` の形式で表示する
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let args: Vec<String> = self
+            .type_params
+            .iter()
+            .zip(&self.type_args)
+            .map(|(param, arg)| format!("{}={}", param, print_type(arg)))
+            .collect();
+        write!(f, "{}<{}>", self.generic_name, args.join(", "))
+    }
+}
+
+/// 単相化で生成したアイテムから元のジェネリック定義への対応表
+///
+/// 生成したアイテムのスパンはジェネリック定義のものを複製しているため、
+/// 診断やデバッグ情報ではこの表を使ってマングルされた名前ではなく
+/// 元の定義と型引数、要求した箇所を示します。
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SourceMap {
+    origins: HashMap<String, InstantiationOrigin>,
+}
+
+impl SourceMap {
+    /// 生成したアイテムの由来を記録
+    pub fn insert(&mut self, item: String, origin: InstantiationOrigin) {
+        self.origins.insert(item, origin);
+    }
+
+    /// 生成したアイテムの由来（単相化で生成していないアイテムは `None`）
+    pub fn origin(&self, item: &str) -> Option<&InstantiationOrigin> {
+        self.origins.get(item)
+    }
+
+    /// ソース上の名前（デバッグ情報などで使う `max<T=f64>` 形式）
+    ///
+    /// 単相化で生成していないアイテムはその名前をそのまま返します。
+    #[allow(dead_code)]
+    pub fn display_name(&self, item: &str) -> String {
+        self.origin(item).map_or_else(|| item.to_string(), ToString::to_string)
+    }
+
+    /// 記録したアイテムの数
+    #[allow(dead_code)]
+    pub fn len(&self) -> usize {
+        self.origins.len()
+    }
+
+    /// 記録したアイテムがないかどうか
+    #[allow(dead_code)]
+    pub fn is_empty(&self) -> bool {
+        self.origins.is_empty()
+    }
+}
@@ -0,0 +1,204 @@
+//! 単相化結果の検証
+//!
+//! 単相化したプログラムの呼び出し先と構造体リテラルの型が、すべて出力に含まれることを確認します。
+//! ジェネリック定義は出力から取り除かれるため、置き換え漏れや生成漏れがあるとコード生成で
+//! 未定義になります。その前にコンパイラの内部エラーとして報告します。
+
+use std::collections::HashSet;
+use crate::ast::*;
+use crate::error::{AnalyzerError, YuniError, YuniResult};
+use super::Monomorphizer;
+use super::mangling::{mangle_function_name, mangle_struct_name};
+
+impl Monomorphizer {
+    /// 単相化したプログラムが生成していないインスタンスを参照していないか検証
+    pub(super) fn validate_instances(&self, program: &Program) -> YuniResult<()> {
+        let mut generated = HashSet::new();
+        for item in &self.generated_items {
+            match item {
+                Item::Function(func) => generated.insert(func.name.as_str()),
+                Item::TypeDef(TypeDef::Struct(s)) => generated.insert(s.name.as_str()),
+                Item::TypeDef(TypeDef::Enum(e)) => generated.insert(e.name.as_str()),
+                _ => false,
+            };
+        }
+        let requested_functions: HashSet<String> = self.monomorphized_functions.iter()
+            .map(|(name, type_args)| mangle_function_name(name, type_args))
+            .collect();
+        let requested_structs: HashSet<String> = self.monomorphized_structs.iter()
+            .map(|(name, type_args)| mangle_struct_name(name, type_args))
+            .collect();
+
+        let validator = InstanceValidator {
+            monomorphizer: self,
+            generated,
+            requested_functions,
+            requested_structs,
+        };
+        for item in &program.items {
+            match item {
+                Item::Function(func) => validator.check_block(&func.body)?,
+                Item::Method(method) => validator.check_block(&method.body)?,
+                Item::TypeDef(_) | Item::ExternFunction(_) => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+struct InstanceValidator<'a> {
+    monomorphizer: &'a Monomorphizer,
+    /// 生成したインスタンスの名前
+    generated: HashSet<&'a str>,
+    /// 要求されたジェネリック関数のインスタンスのマングル名
+    requested_functions: HashSet<String>,
+    /// 要求されたジェネリック構造体・列挙型のインスタンスのマングル名
+    requested_structs: HashSet<String>,
+}
+
+impl InstanceValidator<'_> {
+    /// 呼び出し先がジェネリック定義のまま残っていないか、生成していないインスタンスでないか
+    fn check_callee(&self, name: &str, span: Span) -> YuniResult<()> {
+        if self.monomorphizer.generic_functions.contains_key(name) {
+            return Err(internal_error(format!("ジェネリック関数 {} の呼び出しをインスタンスに置き換えられませんでした", name), span));
+        }
+        if self.requested_functions.contains(name) && !self.generated.contains(name) {
+            return Err(internal_error(format!("呼び出し先のインスタンス {} が生成されていません", name), span));
+        }
+        Ok(())
+    }
+
+    /// 構造体リテラルの型がジェネリック定義のまま残っていないか、生成していないインスタンスでないか
+    fn check_struct_name(&self, name: &str, span: Span) -> YuniResult<()> {
+        if self.monomorphizer.generic_structs.contains_key(name) {
+            return Err(internal_error(format!("ジェネリック構造体 {} のリテラルをインスタンスに置き換えられませんでした", name), span));
+        }
+        if self.requested_structs.contains(name) && !self.generated.contains(name) {
+            return Err(internal_error(format!("構造体のインスタンス {} が生成されていません", name), span));
+        }
+        Ok(())
+    }
+
+    fn check_block(&self, block: &Block) -> YuniResult<()> {
+        block.statements.iter().try_for_each(|stmt| self.check_statement(stmt))
+    }
+
+    fn check_statement(&self, stmt: &Statement) -> YuniResult<()> {
+        match stmt {
+            Statement::Let(let_stmt) => let_stmt.init.as_ref().map_or(Ok(()), |init| self.check_expr(init)),
+            Statement::Assignment(assign) => {
+                self.check_expr(&assign.target)?;
+                self.check_expr(&assign.value)
+            }
+            Statement::Return(ret) => ret.value.as_ref().map_or(Ok(()), |value| self.check_expr(value)),
+            Statement::If(if_stmt) => self.check_if(if_stmt),
+            Statement::While(while_stmt) => {
+                self.check_expr(&while_stmt.condition)?;
+                self.check_block(&while_stmt.body)
+            }
+            Statement::For(for_stmt) => {
+                if let Some(init) = &for_stmt.init {
+                    self.check_statement(init)?;
+                }
+                if let Some(condition) = &for_stmt.condition {
+                    self.check_expr(condition)?;
+                }
+                if let Some(update) = &for_stmt.update {
+                    self.check_expr(update)?;
+                }
+                self.check_block(&for_stmt.body)
+            }
+            Statement::Expression(expr) => self.check_expr(expr),
+            Statement::Block(block) => self.check_block(block),
+            Statement::Defer(defer_stmt) => self.check_block(&defer_stmt.body),
+            Statement::Function(_) => Ok(()),
+        }
+    }
+
+    fn check_if(&self, if_stmt: &IfStatement) -> YuniResult<()> {
+        self.check_expr(&if_stmt.condition)?;
+        self.check_block(&if_stmt.then_branch)?;
+        match &if_stmt.else_branch {
+            Some(ElseBranch::Block(block)) => self.check_block(block),
+            Some(ElseBranch::If(else_if)) => self.check_if(else_if),
+            None => Ok(()),
+        }
+    }
+
+    fn check_exprs<'e>(&self, exprs: impl IntoIterator<Item = &'e Expression>) -> YuniResult<()> {
+        exprs.into_iter().try_for_each(|expr| self.check_expr(expr))
+    }
+
+    fn check_expr(&self, expr: &Expression) -> YuniResult<()> {
+        match expr {
+            Expression::Call(call) => {
+                if let Expression::Identifier(ident) = call.callee.as_ref() {
+                    self.check_callee(&ident.name, call.span)?;
+                } else {
+                    self.check_expr(&call.callee)?;
+                }
+                self.check_exprs(&call.args)
+            }
+            Expression::StructLit(struct_lit) => {
+                if let Some(name) = &struct_lit.name {
+                    self.check_struct_name(name, struct_lit.span)?;
+                }
+                self.check_exprs(struct_lit.fields.iter().map(|field| &field.value))
+            }
+            Expression::MethodCall(method_call) => {
+                self.check_expr(&method_call.object)?;
+                self.check_exprs(&method_call.args)
+            }
+            Expression::Binary(binary) => self.check_exprs([binary.left.as_ref(), binary.right.as_ref()]),
+            Expression::Unary(unary) => self.check_expr(&unary.expr),
+            Expression::Index(index) => self.check_exprs([index.object.as_ref(), index.index.as_ref()]),
+            Expression::Field(field) => self.check_expr(&field.object),
+            Expression::Reference(ref_expr) => self.check_expr(&ref_expr.expr),
+            Expression::Dereference(deref_expr) => self.check_expr(&deref_expr.expr),
+            Expression::Cast(cast) => self.check_expr(&cast.expr),
+            Expression::Assignment(assign) => self.check_exprs([assign.target.as_ref(), assign.value.as_ref()]),
+            Expression::TemplateString(template) => {
+                self.check_exprs(template.parts.iter().filter_map(|part| match part {
+                    TemplateStringPart::Interpolation(expr) => Some(expr),
+                    TemplateStringPart::Text(_) => None,
+                }))
+            }
+            Expression::EnumVariant(enum_variant) => match &enum_variant.fields {
+                EnumVariantFields::Tuple(args) => self.check_exprs(args),
+                EnumVariantFields::Struct(fields) => self.check_exprs(fields.iter().map(|field| &field.value)),
+                EnumVariantFields::Unit => Ok(()),
+            },
+            Expression::Array(array) => self.check_exprs(&array.elements),
+            Expression::Tuple(tuple) => self.check_exprs(&tuple.elements),
+            Expression::ListLiteral(list) => self.check_exprs(&list.elements),
+            Expression::MapLiteral(map) => self.check_exprs(map.pairs.iter().flat_map(|(key, value)| [key, value])),
+            Expression::If(if_expr) => {
+                self.check_exprs([if_expr.condition.as_ref(), if_expr.then_branch.as_ref()])?;
+                self.check_exprs(if_expr.else_branch.as_deref())
+            }
+            Expression::Match(match_expr) => {
+                self.check_expr(&match_expr.expr)?;
+                for arm in &match_expr.arms {
+                    self.check_exprs(arm.guard.as_ref())?;
+                    self.check_expr(&arm.expr)?;
+                }
+                Ok(())
+            }
+            Expression::Block(block_expr) => {
+                block_expr.statements.iter().try_for_each(|stmt| self.check_statement(stmt))?;
+                self.check_exprs(block_expr.last_expr.as_deref())
+            }
+            Expression::Integer(_)
+            | Expression::Float(_)
+            | Expression::String(_)
+            | Expression::Bytes(_)
+            | Expression::Boolean(_)
+            | Expression::Identifier(_)
+            | Expression::Path(_) => Ok(()),
+        }
+    }
+}
+
+fn internal_error(message: String, span: Span) -> YuniError {
+    YuniError::Analyzer(AnalyzerError::Internal { message, span })
+}
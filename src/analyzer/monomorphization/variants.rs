@@ -0,0 +1,315 @@
+//! 列挙型名を省略したバリアントと、構造体名のない構造体リテラルの置き換え
+//!
+//! セマンティック解析は、期待される型から `Red` を `Color::Red` と解決して型テーブルに
+//! 記録します。構造体名のない構造体リテラル（`{ x: 1, y: 2 }`）も、期待される型の構造体として
+//! 型を記録します。単相化とコード生成が通常のバリアント式・パターンと構造体名のある
+//! 構造体リテラルだけを扱えばよいよう、単相化の最初にプログラムを書き換えます。
+
+use std::collections::HashMap;
+use crate::ast::*;
+use crate::analyzer::type_table::TypeTable;
+use super::Monomorphizer;
+
+impl Monomorphizer {
+    /// 列挙型名を省略したバリアントを通常のバリアント式・パターンに置き換え
+    pub(super) fn resolve_contextual_variants(&mut self) {
+        let mut resolver = VariantResolver::new(&self.original_program, &mut self.type_table);
+        for item in &mut self.original_program.items {
+            match item {
+                Item::Function(func) => resolver.resolve_block(&mut func.body),
+                Item::Method(method) => resolver.resolve_block(&mut method.body),
+                Item::TypeDef(_) | Item::ExternFunction(_) => {}
+            }
+        }
+    }
+}
+
+/// 型テーブルの解決結果と型定義をもとにプログラムを書き換える
+struct VariantResolver<'a> {
+    type_table: &'a mut TypeTable,
+    enums: HashMap<String, EnumDef>,
+    structs: HashMap<String, StructDef>,
+}
+
+impl<'a> VariantResolver<'a> {
+    fn new(program: &Program, type_table: &'a mut TypeTable) -> Self {
+        let mut enums = HashMap::new();
+        let mut structs = HashMap::new();
+        for item in &program.items {
+            match item {
+                Item::TypeDef(TypeDef::Enum(e)) => {
+                    enums.insert(e.name.clone(), e.clone());
+                }
+                Item::TypeDef(TypeDef::Struct(s)) => {
+                    structs.insert(s.name.clone(), s.clone());
+                }
+                _ => {}
+            }
+        }
+        Self { type_table, enums, structs }
+    }
+
+    fn resolve_block(&mut self, block: &mut Block) {
+        for stmt in &mut block.statements {
+            self.resolve_statement(stmt);
+        }
+    }
+
+    fn resolve_statement(&mut self, stmt: &mut Statement) {
+        match stmt {
+            Statement::Let(let_stmt) => {
+                if let Some(init) = &mut let_stmt.init {
+                    self.resolve_expr(init);
+                }
+            }
+            Statement::Assignment(assign) => {
+                self.resolve_expr(&mut assign.target);
+                self.resolve_expr(&mut assign.value);
+            }
+            Statement::Return(ret) => {
+                if let Some(value) = &mut ret.value {
+                    self.resolve_expr(value);
+                }
+            }
+            Statement::If(if_stmt) => self.resolve_if_statement(if_stmt),
+            Statement::While(while_stmt) => {
+                self.resolve_expr(&mut while_stmt.condition);
+                self.resolve_block(&mut while_stmt.body);
+            }
+            Statement::For(for_stmt) => {
+                if let Some(init) = &mut for_stmt.init {
+                    self.resolve_statement(init);
+                }
+                if let Some(condition) = &mut for_stmt.condition {
+                    self.resolve_expr(condition);
+                }
+                if let Some(update) = &mut for_stmt.update {
+                    self.resolve_expr(update);
+                }
+                self.resolve_block(&mut for_stmt.body);
+            }
+            Statement::Expression(expr) => self.resolve_expr(expr),
+            Statement::Block(block) => self.resolve_block(block),
+            Statement::Defer(defer_stmt) => self.resolve_block(&mut defer_stmt.body),
+            Statement::Function(_) => {}
+        }
+    }
+
+    fn resolve_if_statement(&mut self, if_stmt: &mut IfStatement) {
+        self.resolve_expr(&mut if_stmt.condition);
+        self.resolve_block(&mut if_stmt.then_branch);
+        match &mut if_stmt.else_branch {
+            Some(ElseBranch::Block(block)) => self.resolve_block(block),
+            Some(ElseBranch::If(else_if)) => self.resolve_if_statement(else_if),
+            None => {}
+        }
+    }
+
+    fn resolve_expr(&mut self, expr: &mut Expression) {
+        let resolved = self
+            .type_table
+            .variant(expr)
+            .map(|(enum_name, variant)| (enum_name.to_string(), variant.to_string()));
+        if let Some((enum_name, variant)) = resolved {
+            // 置き換えた式からも解析で決まった型を引けるようにする
+            let ty = Type::UserDefined(enum_name.clone());
+            let variant_expr = Expression::EnumVariant(EnumVariantExpr {
+                enum_name,
+                variant,
+                fields: EnumVariantFields::Unit,
+                span: expr.span(),
+            });
+            self.type_table.record(&variant_expr, &ty);
+            *expr = variant_expr;
+            return;
+        }
+
+        let anonymous_struct_type = match expr {
+            Expression::StructLit(StructLiteral { name: None, .. }) => {
+                self.type_table.get(expr).or_else(|| self.type_table.get_generic(expr)).cloned()
+            }
+            _ => None,
+        };
+
+        match expr {
+            Expression::Integer(_)
+            | Expression::Float(_)
+            | Expression::String(_)
+            | Expression::Bytes(_)
+            | Expression::Boolean(_)
+            | Expression::Identifier(_)
+            | Expression::Path(_) => {}
+            Expression::TemplateString(template) => {
+                for part in &mut template.parts {
+                    if let TemplateStringPart::Interpolation(expr) = part {
+                        self.resolve_expr(expr);
+                    }
+                }
+            }
+            Expression::Binary(binary) => {
+                self.resolve_expr(&mut binary.left);
+                self.resolve_expr(&mut binary.right);
+            }
+            Expression::Unary(unary) => self.resolve_expr(&mut unary.expr),
+            Expression::Call(call) => {
+                self.resolve_expr(&mut call.callee);
+                call.args.iter_mut().for_each(|arg| self.resolve_expr(arg));
+            }
+            Expression::MethodCall(method_call) => {
+                self.resolve_expr(&mut method_call.object);
+                method_call.args.iter_mut().for_each(|arg| self.resolve_expr(arg));
+            }
+            Expression::Index(index) => {
+                self.resolve_expr(&mut index.object);
+                self.resolve_expr(&mut index.index);
+            }
+            Expression::Field(field) => self.resolve_expr(&mut field.object),
+            Expression::Reference(ref_expr) => self.resolve_expr(&mut ref_expr.expr),
+            Expression::Dereference(deref_expr) => self.resolve_expr(&mut deref_expr.expr),
+            Expression::StructLit(struct_lit) => {
+                // 構造体名のないリテラルには、解析で決まった構造体の型から構造体名と型引数を補う
+                match anonymous_struct_type {
+                    Some(Type::UserDefined(name)) => struct_lit.name = Some(name),
+                    Some(Type::Generic(name, type_args)) => {
+                        struct_lit.name = Some(name);
+                        struct_lit.type_args = type_args;
+                    }
+                    _ => {}
+                }
+                for field in &mut struct_lit.fields {
+                    self.resolve_expr(&mut field.value);
+                }
+            }
+            Expression::EnumVariant(enum_variant) => match &mut enum_variant.fields {
+                EnumVariantFields::Tuple(args) => args.iter_mut().for_each(|arg| self.resolve_expr(arg)),
+                EnumVariantFields::Struct(fields) => {
+                    for field in fields {
+                        self.resolve_expr(&mut field.value);
+                    }
+                }
+                EnumVariantFields::Unit => {}
+            },
+            Expression::Array(array) => array.elements.iter_mut().for_each(|e| self.resolve_expr(e)),
+            Expression::Tuple(tuple) => tuple.elements.iter_mut().for_each(|e| self.resolve_expr(e)),
+            Expression::Cast(cast) => self.resolve_expr(&mut cast.expr),
+            Expression::Assignment(assign) => {
+                self.resolve_expr(&mut assign.target);
+                self.resolve_expr(&mut assign.value);
+            }
+            Expression::Match(match_expr) => {
+                let scrutinee_type = self
+                    .type_table
+                    .get(&match_expr.expr)
+                    .or_else(|| self.type_table.get_generic(&match_expr.expr))
+                    .cloned();
+                self.resolve_expr(&mut match_expr.expr);
+                for arm in &mut match_expr.arms {
+                    if let Some(ty) = &scrutinee_type {
+                        self.resolve_pattern(&mut arm.pattern, ty);
+                    }
+                    if let Some(guard) = &mut arm.guard {
+                        self.resolve_expr(guard);
+                    }
+                    self.resolve_expr(&mut arm.expr);
+                }
+            }
+            Expression::If(if_expr) => {
+                self.resolve_expr(&mut if_expr.condition);
+                self.resolve_expr(&mut if_expr.then_branch);
+                if let Some(else_branch) = &mut if_expr.else_branch {
+                    self.resolve_expr(else_branch);
+                }
+            }
+            Expression::Block(block_expr) => {
+                for stmt in &mut block_expr.statements {
+                    self.resolve_statement(stmt);
+                }
+                if let Some(last_expr) = &mut block_expr.last_expr {
+                    self.resolve_expr(last_expr);
+                }
+            }
+            Expression::ListLiteral(list) => list.elements.iter_mut().for_each(|e| self.resolve_expr(e)),
+            Expression::MapLiteral(map) => {
+                for (key, value) in &mut map.pairs {
+                    self.resolve_expr(key);
+                    self.resolve_expr(value);
+                }
+            }
+        }
+    }
+
+    /// パターンを対象の型に従って書き換え
+    ///
+    /// 解析と同じ規則で、対象の列挙型にあるバリアントと同名の識別子パターンは
+    /// バリアントのパターンに、列挙型名のないバリアントのパターンは対象の列挙型のものにします。
+    fn resolve_pattern(&self, pattern: &mut Pattern, ty: &Type) {
+        let enum_def = match ty {
+            Type::UserDefined(name) => self.enums.get(name),
+            _ => None,
+        };
+
+        match pattern {
+            Pattern::Identifier(name, false) => {
+                let is_variant = enum_def.is_some_and(|e| {
+                    e.variants.iter().any(|v| v.name == *name && v.fields.is_empty())
+                });
+                if let (true, Some(enum_def)) = (is_variant, enum_def) {
+                    *pattern = Pattern::EnumVariant {
+                        enum_name: enum_def.name.clone(),
+                        variant: name.clone(),
+                        fields: EnumVariantPatternFields::Unit,
+                    };
+                }
+            }
+            Pattern::EnumVariant { enum_name, variant, fields } => {
+                let Some(enum_def) = enum_def else {
+                    return;
+                };
+                if enum_name.is_empty() {
+                    *enum_name = enum_def.name.clone();
+                }
+                let Some(variant_def) = enum_def.variants.iter().find(|v| v.name == *variant) else {
+                    return;
+                };
+                match fields {
+                    EnumVariantPatternFields::Tuple(patterns) => {
+                        for (pattern, field) in patterns.iter_mut().zip(&variant_def.fields) {
+                            self.resolve_pattern(pattern, &field.ty);
+                        }
+                    }
+                    EnumVariantPatternFields::Struct(field_patterns) => {
+                        self.resolve_field_patterns(field_patterns, &variant_def.fields);
+                    }
+                    EnumVariantPatternFields::Unit => {}
+                }
+            }
+            Pattern::Tuple(patterns) => {
+                if let Type::Tuple(types) = ty {
+                    for (pattern, ty) in patterns.iter_mut().zip(types) {
+                        self.resolve_pattern(pattern, ty);
+                    }
+                }
+            }
+            Pattern::Struct(name, field_patterns) => {
+                if let Some(struct_def) = self.structs.get(name) {
+                    self.resolve_field_patterns(field_patterns, &struct_def.fields);
+                }
+            }
+            Pattern::Binding(_, pattern) => self.resolve_pattern(pattern, ty),
+            Pattern::Or(patterns) => {
+                for pattern in patterns {
+                    self.resolve_pattern(pattern, ty);
+                }
+            }
+            Pattern::Identifier(_, true) | Pattern::Literal(_) | Pattern::Range { .. } | Pattern::Wildcard => {}
+        }
+    }
+
+    fn resolve_field_patterns(&self, field_patterns: &mut [(String, Pattern)], fields: &[Field]) {
+        for (field_name, pattern) in field_patterns {
+            if let Some(field) = fields.iter().find(|f| f.name == *field_name) {
+                self.resolve_pattern(pattern, &field.ty);
+            }
+        }
+    }
+}
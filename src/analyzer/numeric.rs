@@ -0,0 +1,250 @@
+//! 数値型の昇格規則
+//!
+//! 二項演算（算術・比較・ビット演算）のオペランドの型をどの型に揃えるかを定義します。
+//! セマンティック解析とコード生成の両方がこのモジュールの規則を使用するため、
+//! 解析で受理された式についてコード生成が型を推測する必要はありません。
+//!
+//! 規則:
+//! - 同じ型同士はそのまま
+//! - 符号が同じ整数同士は、ビット幅の大きい方の型に揃える（`i32 + i64` → `i64`）
+//! - 符号付き整数と符号なし整数の混在は暗黙に変換しない（明示的なキャストが必要）
+//! - 浮動小数点同士は精度の高い方の型に揃える（`f32 + f64` → `f64`）
+//! - 整数と浮動小数点の混在は暗黙に変換しない（明示的なキャストが必要）
+//! - 接尾辞のない整数リテラルは、もう一方のオペランドの整数型を採用する
+//!
+//! 数値リテラルの型の決め方（`literal_type`）と、数値型の関連定数（`i64::MAX` など）も
+//! ここで定義します。
+
+use crate::ast::{Expression, FloatLit, IntegerLit, Type};
+
+/// 数値型の昇格に失敗した理由
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PromotionError {
+    /// 符号付き整数と符号なし整数の混在
+    MixedSignedness,
+    /// 整数と浮動小数点の混在
+    IntegerFloatMix,
+    /// 数値型ではないオペランド
+    NotNumeric,
+}
+
+/// 整数型のビット幅と符号の有無を取得
+pub fn integer_layout(ty: &Type) -> Option<(u32, bool)> {
+    match ty {
+        Type::I8 => Some((8, true)),
+        Type::I16 => Some((16, true)),
+        Type::I32 => Some((32, true)),
+        Type::I64 => Some((64, true)),
+        Type::I128 => Some((128, true)),
+        Type::I256 => Some((256, true)),
+        Type::U8 => Some((8, false)),
+        Type::U16 => Some((16, false)),
+        Type::U32 => Some((32, false)),
+        Type::U64 => Some((64, false)),
+        Type::U128 => Some((128, false)),
+        Type::U256 => Some((256, false)),
+        _ => None,
+    }
+}
+
+/// 128ビットまでの整数型で表せる値の範囲（最小値, 最大値）
+///
+/// u128の最大値はi128に収まらないため、i128の最大値で打ち切ります。
+pub fn integer_range(ty: &Type) -> Option<(i128, i128)> {
+    match integer_layout(ty)? {
+        (bits, _) if bits > 128 => None,
+        (128, true) => Some((i128::MIN, i128::MAX)),
+        (128, false) => Some((0, i128::MAX)),
+        (bits, true) => Some((-(1i128 << (bits - 1)), (1i128 << (bits - 1)) - 1)),
+        (bits, false) => Some((0, (1i128 << bits) - 1)),
+    }
+}
+
+/// 浮動小数点数を切り捨てて整数型に変換するとき、値が失われない範囲（最小値以上, 上限未満）
+///
+/// 境界はいずれも2の冪のため、浮動小数点数で正確に表せます。
+pub fn float_to_integer_bounds(ty: &Type) -> Option<(f64, f64)> {
+    match integer_layout(ty)? {
+        (bits, _) if bits > 128 => None,
+        (bits, true) => Some((-(2f64.powi(bits as i32 - 1)), 2f64.powi(bits as i32 - 1))),
+        (bits, false) => Some((0.0, 2f64.powi(bits as i32))),
+    }
+}
+
+/// 浮動小数点型のビット幅を取得
+fn float_width(ty: &Type) -> Option<u32> {
+    match ty {
+        Type::F8 => Some(8),
+        Type::F16 => Some(16),
+        Type::F32 => Some(32),
+        Type::F64 => Some(64),
+        _ => None,
+    }
+}
+
+/// 二つの数値型を演算に使う共通の型に昇格する
+pub fn promote_numeric(left: &Type, right: &Type) -> Result<Type, PromotionError> {
+    if left == right && (integer_layout(left).is_some() || float_width(left).is_some()) {
+        return Ok(left.clone());
+    }
+
+    match (integer_layout(left), integer_layout(right), float_width(left), float_width(right)) {
+        (Some((left_bits, left_signed)), Some((right_bits, right_signed)), _, _) => {
+            if left_signed != right_signed {
+                Err(PromotionError::MixedSignedness)
+            } else if left_bits >= right_bits {
+                Ok(left.clone())
+            } else {
+                Ok(right.clone())
+            }
+        }
+        (_, _, Some(left_bits), Some(right_bits)) => {
+            if left_bits >= right_bits {
+                Ok(left.clone())
+            } else {
+                Ok(right.clone())
+            }
+        }
+        (Some(_), _, _, Some(_)) | (_, Some(_), Some(_), _) => Err(PromotionError::IntegerFloatMix),
+        _ => Err(PromotionError::NotNumeric),
+    }
+}
+
+/// 数値リテラルの型
+///
+/// 接尾辞があればその型を使います。接尾辞がなければ期待される型を採用し
+/// （整数リテラルは整数型と `f32`・`f64`、浮動小数点リテラルは `f32`・`f64`）、
+/// 期待される型がなければ整数リテラルは `i32`、浮動小数点リテラルは `f64` になります。
+/// セマンティック解析・単相化・コード生成・インタプリタはすべてこの規則で型を決めるため、
+/// 構造体のフィールドや引数の期待される型が伝わる限り、同じリテラルが異なる型になることはありません。
+pub fn literal_type(expr: &Expression, expected: Option<&Type>) -> Option<Type> {
+    match expr {
+        Expression::Integer(lit) => Some(integer_literal_type(lit, expected)),
+        Expression::Float(lit) => Some(float_literal_type(lit, expected)),
+        _ => None,
+    }
+}
+
+/// 整数リテラルの型（`literal_type` を参照）
+pub fn integer_literal_type(lit: &IntegerLit, expected: Option<&Type>) -> Type {
+    match lit.suffix.as_deref() {
+        Some(suffix) => integer_suffix_type(suffix).unwrap_or(Type::I32),
+        None => match expected {
+            Some(ty @ (Type::I8 | Type::I16 | Type::I32 | Type::I64 | Type::I128
+                | Type::U8 | Type::U16 | Type::U32 | Type::U64 | Type::U128
+                | Type::F32 | Type::F64)) => ty.clone(),
+            _ => Type::I32,
+        },
+    }
+}
+
+/// 浮動小数点リテラルの型（`literal_type` を参照）
+pub fn float_literal_type(lit: &FloatLit, expected: Option<&Type>) -> Type {
+    match (lit.suffix.as_deref(), expected) {
+        (Some("f32"), _) | (None, Some(Type::F32)) => Type::F32,
+        _ => Type::F64,
+    }
+}
+
+/// 整数リテラルの接尾辞の型
+pub fn integer_suffix_type(suffix: &str) -> Option<Type> {
+    match suffix {
+        "i8" => Some(Type::I8),
+        "i16" => Some(Type::I16),
+        "i32" => Some(Type::I32),
+        "i64" => Some(Type::I64),
+        "i128" => Some(Type::I128),
+        "u8" => Some(Type::U8),
+        "u16" => Some(Type::U16),
+        "u32" => Some(Type::U32),
+        "u64" => Some(Type::U64),
+        "u128" => Some(Type::U128),
+        _ => None,
+    }
+}
+
+/// 接尾辞のない整数リテラルかどうか
+///
+/// このリテラルはもう一方のオペランドの整数型を採用します。
+pub fn is_untyped_integer_literal(expr: &Expression) -> bool {
+    matches!(expr, Expression::Integer(lit) if lit.suffix.is_none())
+}
+
+/// 数値型の関連定数の値
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NumericConstant {
+    /// 整数型の定数（u128の最大値はビットパターンのままi128に格納します）
+    Integer(i128),
+    /// 浮動小数点型の定数
+    Float(f64),
+}
+
+/// 整数型の関連定数の名前
+const INTEGER_CONSTANTS: &[&str] = &["MIN", "MAX"];
+
+/// 浮動小数点型の関連定数の名前
+const FLOAT_CONSTANTS: &[&str] = &["MIN", "MAX", "INFINITY", "NEG_INFINITY", "NAN", "EPSILON"];
+
+/// `Type::CONST` の型の部分に書ける組み込み型の名前から型を取得
+///
+/// 数値型のほか、関連定数を持たないことを報告するために `bool`・`str`・`String` も含めます。
+pub fn builtin_type_by_name(name: &str) -> Option<Type> {
+    match name {
+        "i256" => Some(Type::I256),
+        "u256" => Some(Type::U256),
+        "f8" => Some(Type::F8),
+        "f16" => Some(Type::F16),
+        "f32" => Some(Type::F32),
+        "f64" => Some(Type::F64),
+        "bool" => Some(Type::Bool),
+        "str" => Some(Type::Str),
+        "String" => Some(Type::String),
+        _ => integer_suffix_type(name),
+    }
+}
+
+/// 型が持つ関連定数の名前
+///
+/// 関連定数を持つのは128ビットまでの整数型と `f32`・`f64` だけで、
+/// ほかの型は空になります。
+pub fn associated_constant_names(ty: &Type) -> &'static [&'static str] {
+    if integer_range(ty).is_some() {
+        INTEGER_CONSTANTS
+    } else if matches!(ty, Type::F32 | Type::F64) {
+        FLOAT_CONSTANTS
+    } else {
+        &[]
+    }
+}
+
+/// 型の関連定数の値
+///
+/// 値はコンパイル時に決まるため、セマンティック解析・コード生成・インタプリタは
+/// いずれもこの値を定数として扱います。`f32` の定数は `f32` の値を `f64` に広げたものです。
+pub fn associated_constant(ty: &Type, name: &str) -> Option<NumericConstant> {
+    let constant = match (ty, name) {
+        (Type::U128, "MAX") => NumericConstant::Integer(u128::MAX as i128),
+        (Type::F32, _) => NumericConstant::Float(match name {
+            "MIN" => f32::MIN,
+            "MAX" => f32::MAX,
+            "INFINITY" => f32::INFINITY,
+            "NEG_INFINITY" => f32::NEG_INFINITY,
+            "NAN" => f32::NAN,
+            "EPSILON" => f32::EPSILON,
+            _ => return None,
+        } as f64),
+        (Type::F64, _) => NumericConstant::Float(match name {
+            "MIN" => f64::MIN,
+            "MAX" => f64::MAX,
+            "INFINITY" => f64::INFINITY,
+            "NEG_INFINITY" => f64::NEG_INFINITY,
+            "NAN" => f64::NAN,
+            "EPSILON" => f64::EPSILON,
+            _ => return None,
+        }),
+        (_, "MIN") => NumericConstant::Integer(integer_range(ty)?.0),
+        (_, "MAX") => NumericConstant::Integer(integer_range(ty)?.1),
+        _ => return None,
+    };
+    Some(constant)
+}
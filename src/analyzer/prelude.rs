@@ -0,0 +1,659 @@
+//! 標準ライブラリのプレリュードと名前解決
+//!
+//! VecやHashMap、printlnなどのビルトインは合成された `std` モジュールに属し、
+//! ファイルがno_preludeを宣言しない限りプレリュードとしてインポートなしで使えます。
+//! 名前はユーザー定義 > インポート > プレリュードの順に解決し、ソース上の名前を
+//! 正規の名前に書き換えます。以降のパスはビルトインを正規の名前だけで判別します。
+//!
+//! ビルトインと同じ名前のユーザー定義は警告付きでビルトインを隠し、
+//! ビルトインと区別するため `パッケージ名.名前` に改名されます。
+//!
+//! 同じ名前で引数の数が異なる関数は、引数の数でオーバーロードできます。それぞれの定義は
+//! `名前/引数の数`（`add/2`）に改名され、呼び出しは引数の数が一致する定義に解決されます。
+//! 引数の数が同じ定義は改名しても名前が重なるため、重複した定義としてエラーになります。
+//!
+//! 標準ライブラリのインポートは、名前がそのインポートを通して解決されたかを記録し、
+//! 一度も使われなかったもの（選択インポートは要素ごと）とモジュールのインポートの要素を
+//! 隠すユーザー定義を警告します。
+
+use std::collections::{HashMap, HashSet};
+
+use crate::ast::*;
+use crate::error::AnalyzerError;
+
+/// プレリュードの要素
+pub struct PreludeItem {
+    /// 正規の名前（ソース上の既定の名前でもある）
+    pub name: &'static str,
+    /// 標準ライブラリ内のパス（`import` で指定する）
+    pub path: &'static str,
+}
+
+/// プレリュードに含まれるビルトインの型と関数
+pub const PRELUDE: &[PreludeItem] = &[
+    PreludeItem { name: "Vec", path: "std/collections/Vec" },
+    PreludeItem { name: "HashMap", path: "std/collections/HashMap" },
+    PreludeItem { name: "StringBuilder", path: "std/string/StringBuilder" },
+    PreludeItem { name: "parse_int", path: "std/string/parse_int" },
+    PreludeItem { name: "parse_float", path: "std/string/parse_float" },
+    PreludeItem { name: "Option", path: "std/option/Option" },
+    PreludeItem { name: "Result", path: "std/result/Result" },
+    PreludeItem { name: "print", path: "std/io/print" },
+    PreludeItem { name: "println", path: "std/io/println" },
+    PreludeItem { name: "eprintln", path: "std/io/eprintln" },
+    PreludeItem { name: "read_line", path: "std/io/read_line" },
+    PreludeItem { name: "read_bytes", path: "std/fs/read_bytes" },
+    PreludeItem { name: "write_bytes", path: "std/fs/write_bytes" },
+    PreludeItem { name: "sqrt", path: "std/math/sqrt" },
+    PreludeItem { name: "time_nanos", path: "std/time/time_nanos" },
+    PreludeItem { name: "time_unix_millis", path: "std/time/time_unix_millis" },
+    PreludeItem { name: "sleep_millis", path: "std/time/sleep_millis" },
+    PreludeItem { name: "random_seed", path: "std/random/random_seed" },
+    PreludeItem { name: "random_u64", path: "std/random/random_u64" },
+    PreludeItem { name: "assert", path: "std/assert/assert" },
+    PreludeItem { name: "assert_eq", path: "std/assert/assert_eq" },
+    PreludeItem { name: "debug_assert", path: "std/assert/debug_assert" },
+    PreludeItem { name: "debug_assert_eq", path: "std/assert/debug_assert_eq" },
+];
+
+/// 名前解決で見つかった警告とエラー
+#[derive(Debug, Default)]
+pub struct NameResolution {
+    pub warnings: Vec<AnalyzerError>,
+    pub errors: Vec<AnalyzerError>,
+}
+
+/// プログラム中の型名・関数名を正規の名前に書き換える
+pub fn resolve_names(program: &mut Program) -> NameResolution {
+    let mut resolver = NameResolver::new(program);
+    for item in &mut program.items {
+        resolver.resolve_item(item);
+    }
+    resolver.report_unused_imports();
+    resolver.result
+}
+
+fn prelude_item(name: &str) -> Option<&'static PreludeItem> {
+    PRELUDE.iter().find(|item| item.name == name)
+}
+
+/// 引数の数が異なる定義が複数ある関数と、その引数の数
+///
+/// エントリポイントの `main` はオーバーロードできません。
+fn overloaded_functions(program: &Program) -> HashMap<String, Vec<usize>> {
+    let mut arities: HashMap<String, Vec<usize>> = HashMap::new();
+    for item in &program.items {
+        if let Item::Function(func) = item {
+            if func.name != "main" {
+                arities.entry(func.name.clone()).or_default().push(func.params.len());
+            }
+        }
+    }
+    arities.retain(|_, arities| {
+        arities.sort_unstable();
+        arities.dedup();
+        arities.len() > 1
+    });
+    arities
+}
+
+/// 名前の表記 `std.collections` を `std/collections` にそろえる
+pub fn normalize_path(path: &str) -> String {
+    path.replace('.', "/")
+}
+
+/// そろえたインポートのパスが標準ライブラリを指すか
+///
+/// 標準ライブラリはコンパイラに組み込まれているため、ファイルとしては解決しません。
+pub fn is_std_path(path: &str) -> bool {
+    path == "std" || path.starts_with("std/")
+}
+
+/// モジュール（`std/collections`）に直接含まれる要素
+fn module_members(path: &str) -> Vec<&'static PreludeItem> {
+    let module_prefix = format!("{}/", path);
+    PRELUDE
+        .iter()
+        .filter(|item| item.path.strip_prefix(&module_prefix).is_some_and(|rest| !rest.contains('/')))
+        .collect()
+}
+
+/// オーバーロードした関数の、引数の数ごとの名前（`add/2`）
+pub fn overload_name(name: &str, arity: usize) -> String {
+    format!("{}/{}", name, arity)
+}
+
+/// 名前を結び付けた標準ライブラリのインポート（選択インポートは要素ごと）
+struct ImportBinding {
+    /// 警告に示す名前（モジュールのインポートはパス）
+    name: String,
+    /// インポート文（選択インポートは要素）の位置
+    span: Span,
+    used: bool,
+}
+
+struct NameResolver {
+    /// ソース上の名前から正規の名前への対応
+    names: HashMap<String, String>,
+    /// 名前を指定してインポートした名前とそのインポートの位置
+    imported: HashMap<String, Span>,
+    /// インポートで結び付けた名前と、そのインポートの `bindings` の添字
+    import_bindings: HashMap<String, usize>,
+    bindings: Vec<ImportBinding>,
+    /// オーバーロードした関数の名前と、定義された引数の数（昇順）
+    overloads: HashMap<String, Vec<usize>>,
+    /// ブロックごとの、そこまでに定義したローカル関数の名前（トップレベルの名前より優先する）
+    local_functions: Vec<HashSet<String>>,
+    result: NameResolution,
+}
+
+impl NameResolver {
+    fn new(program: &Program) -> Self {
+        let mut resolver = Self {
+            names: HashMap::new(),
+            imported: HashMap::new(),
+            import_bindings: HashMap::new(),
+            bindings: Vec::new(),
+            overloads: overloaded_functions(program),
+            local_functions: Vec::new(),
+            result: NameResolution::default(),
+        };
+
+        // プレリュード（最も優先度が低い）
+        if !program.package.no_prelude {
+            for item in PRELUDE {
+                resolver.names.insert(item.name.to_string(), item.name.to_string());
+            }
+        }
+
+        // インポート
+        for import in &program.imports {
+            resolver.bind_import(import);
+        }
+
+        // ユーザー定義（最も優先度が高い）
+        for item in &program.items {
+            let (name, span) = match item {
+                Item::Function(func) => (&func.name, func.span),
+                Item::ExternFunction(func) => (&func.name, func.span),
+                Item::TypeDef(TypeDef::Struct(s)) => (&s.name, s.span),
+                Item::TypeDef(TypeDef::Enum(e)) => (&e.name, e.span),
+                Item::TypeDef(TypeDef::Alias(a)) => (&a.name, a.span),
+                Item::Method(_) => continue,
+            };
+            // 定義で隠したインポートは、この名前では使われない
+            let binding = resolver.import_bindings.remove(name);
+            if let Some(&import_span) = resolver.imported.get(name) {
+                // 衝突はエラーで報告するため、未使用のインポートとしては報告しない
+                if let Some(binding) = binding {
+                    resolver.bindings[binding].used = true;
+                }
+                resolver.result.errors.push(AnalyzerError::ImportConflictsWithDefinition {
+                    name: name.clone(),
+                    span,
+                    import_span,
+                });
+            } else if let Some(binding) = binding {
+                resolver.result.warnings.push(AnalyzerError::ShadowedImport {
+                    name: name.clone(),
+                    path: resolver.bindings[binding].name.clone(),
+                    span,
+                    import_span: resolver.bindings[binding].span,
+                });
+            } else if let Some(shadowed) = resolver.names.get(name).and_then(|canonical| prelude_item(canonical)) {
+                resolver.result.warnings.push(AnalyzerError::ShadowsPrelude {
+                    name: name.clone(),
+                    path: shadowed.path.to_string(),
+                    span,
+                });
+            }
+            let canonical = if prelude_item(name).is_some() {
+                format!("{}.{}", program.package.name, name)
+            } else {
+                name.clone()
+            };
+            resolver.names.insert(name.clone(), canonical);
+        }
+
+        resolver
+    }
+
+    /// 標準ライブラリのインポートを名前に結び付ける
+    ///
+    /// 要素（`std/collections/HashMap`）は別名か要素名で、モジュール（`std/collections`）は
+    /// 含まれる要素をそれぞれの名前で結び付けます。要素を選択したインポート
+    /// （`std/collections { HashMap as Map }`）は選択した要素だけを結び付けます。
+    /// 標準ライブラリ以外のインポートは対象外です。
+    fn bind_import(&mut self, import: &Import) {
+        let path = normalize_path(&import.path);
+        if !is_std_path(&path) {
+            if !import.symbols.is_empty() {
+                // ファイルをまたぐ名前解決ができるまでは要素の存在を確認できない
+                self.result.warnings.push(AnalyzerError::UnresolvedImportSymbols {
+                    path: import.path.clone(),
+                    span: import.span,
+                });
+                for symbol in &import.symbols {
+                    self.record_import(symbol.local_name(), symbol.span);
+                }
+            }
+            return;
+        }
+
+        if let Some(item) = PRELUDE.iter().find(|item| item.path == path) {
+            if !import.symbols.is_empty() {
+                self.result.errors.push(AnalyzerError::InvalidOperation {
+                    message: format!("{} はモジュールではないため要素を選択できません", import.path),
+                    span: import.span,
+                });
+                return;
+            }
+            let alias = import.alias.as_deref().unwrap_or(item.name);
+            if self.record_import(alias, import.span) {
+                let binding = self.add_binding(alias, import.span);
+                self.bind(alias, item.name, binding);
+            }
+            return;
+        }
+
+        let members = module_members(&path);
+        if members.is_empty() {
+            self.result.errors.push(AnalyzerError::UnknownStdImport {
+                path: import.path.clone(),
+                span: import.span,
+            });
+        } else if !import.symbols.is_empty() {
+            for symbol in &import.symbols {
+                match members.iter().find(|item| item.name == symbol.name) {
+                    Some(item) => {
+                        if self.record_import(symbol.local_name(), symbol.span) {
+                            let binding = self.add_binding(symbol.local_name(), symbol.span);
+                            self.bind(symbol.local_name(), item.name, binding);
+                        }
+                    }
+                    None => self.result.errors.push(AnalyzerError::UnknownImportSymbol {
+                        name: symbol.name.clone(),
+                        path: import.path.clone(),
+                        available: members.iter().map(|item| item.name.to_string()).collect(),
+                        span: symbol.span,
+                    }),
+                }
+            }
+        } else if import.alias.is_some() {
+            self.result.errors.push(AnalyzerError::InvalidOperation {
+                message: format!("モジュール {} には別名を付けられません。要素ごとにインポートしてください", import.path),
+                span: import.span,
+            });
+        } else {
+            let binding = self.add_binding(&import.path, import.span);
+            for item in members {
+                self.bind(item.name, item.name, binding);
+            }
+        }
+    }
+
+    /// インポートの使用を記録する領域を追加する
+    fn add_binding(&mut self, name: &str, span: Span) -> usize {
+        self.bindings.push(ImportBinding { name: name.to_string(), span, used: false });
+        self.bindings.len() - 1
+    }
+
+    /// インポートした名前を正規の名前に結び付ける
+    fn bind(&mut self, name: &str, canonical: &str, binding: usize) {
+        self.names.insert(name.to_string(), canonical.to_string());
+        self.import_bindings.insert(name.to_string(), binding);
+    }
+
+    /// 一度も使われなかったインポートを警告する
+    fn report_unused_imports(&mut self) {
+        for binding in &self.bindings {
+            if !binding.used {
+                self.result.warnings.push(AnalyzerError::UnusedImport {
+                    name: binding.name.clone(),
+                    span: binding.span,
+                });
+            }
+        }
+    }
+
+    /// 名前を指定したインポートを記録する（同じ名前を既にインポートしていればエラー）
+    fn record_import(&mut self, name: &str, span: Span) -> bool {
+        if let Some(&previous) = self.imported.get(name) {
+            self.result.errors.push(AnalyzerError::DuplicateImport { name: name.to_string(), span, previous });
+            return false;
+        }
+        self.imported.insert(name.to_string(), span);
+        true
+    }
+
+    /// 名前を正規の名前に書き換える
+    fn resolve_name(&mut self, name: &mut String, span: Span) {
+        if let Some(canonical) = self.names.get(name.as_str()) {
+            if let Some(&binding) = self.import_bindings.get(name.as_str()) {
+                self.bindings[binding].used = true;
+            }
+            *name = canonical.clone();
+        } else if let Some(item) = prelude_item(name) {
+            // no_preludeのファイルでインポートしていないビルトイン
+            self.result.errors.push(AnalyzerError::PreludeDisabled {
+                name: name.clone(),
+                path: item.path.to_string(),
+                span,
+            });
+        }
+    }
+
+    /// 関数名を正規の名前に書き換え、オーバーロードした関数は引数の数の定義を選ぶ
+    fn resolve_function_name(&mut self, name: &mut String, arity: usize, span: Span) {
+        if self.local_functions.iter().any(|scope| scope.contains(name.as_str())) {
+            return;
+        }
+        let Some(arities) = self.overloads.get(name.as_str()) else {
+            self.resolve_name(name, span);
+            return;
+        };
+        if !arities.contains(&arity) {
+            self.result.errors.push(AnalyzerError::NoOverloadForArity {
+                name: name.clone(),
+                arity,
+                arities: arities.clone(),
+                span,
+            });
+            return;
+        }
+        self.resolve_name(name, span);
+        *name = overload_name(name, arity);
+    }
+
+    fn resolve_type(&mut self, ty: &mut Type, span: Span) {
+        match ty {
+            Type::UserDefined(name) => self.resolve_name(name, span),
+            Type::Generic(name, args) => {
+                self.resolve_name(name, span);
+                for arg in args {
+                    self.resolve_type(arg, span);
+                }
+            }
+            Type::Reference(inner, _) | Type::Array(inner) => self.resolve_type(inner, span),
+            Type::Tuple(elements) => {
+                for element in elements {
+                    self.resolve_type(element, span);
+                }
+            }
+            Type::Function(fn_type) => {
+                for param in &mut fn_type.params {
+                    self.resolve_type(param, span);
+                }
+                self.resolve_type(&mut fn_type.return_type, span);
+            }
+            _ => {}
+        }
+    }
+
+    fn resolve_item(&mut self, item: &mut Item) {
+        match item {
+            Item::Function(func) => {
+                self.resolve_function_name(&mut func.name, func.params.len(), func.span);
+                self.resolve_signature(&mut func.params, &mut func.return_type, func.span);
+                self.resolve_block(&mut func.body);
+            }
+            Item::Method(method) => {
+                self.resolve_type(&mut method.receiver.ty, method.receiver.span);
+                self.resolve_signature(&mut method.params, &mut method.return_type, method.span);
+                self.resolve_block(&mut method.body);
+            }
+            Item::TypeDef(TypeDef::Struct(struct_def)) => {
+                self.resolve_name(&mut struct_def.name, struct_def.span);
+                for field in &mut struct_def.fields {
+                    self.resolve_type(&mut field.ty, field.span);
+                }
+            }
+            Item::TypeDef(TypeDef::Enum(enum_def)) => {
+                self.resolve_name(&mut enum_def.name, enum_def.span);
+                for field in enum_def.variants.iter_mut().flat_map(|variant| &mut variant.fields) {
+                    self.resolve_type(&mut field.ty, field.span);
+                }
+            }
+            Item::TypeDef(TypeDef::Alias(alias)) => {
+                self.resolve_name(&mut alias.name, alias.span);
+                self.resolve_type(&mut alias.underlying_type, alias.span);
+            }
+            Item::ExternFunction(func) => {
+                self.resolve_name(&mut func.name, func.span);
+                self.resolve_signature(&mut func.params, &mut func.return_type, func.span);
+            }
+        }
+    }
+
+    fn resolve_signature(&mut self, params: &mut [Param], return_type: &mut Option<Box<Type>>, span: Span) {
+        for param in params {
+            self.resolve_type(&mut param.ty, param.span);
+        }
+        if let Some(return_type) = return_type {
+            self.resolve_type(return_type, span);
+        }
+    }
+
+    fn resolve_block(&mut self, block: &mut Block) {
+        self.local_functions.push(HashSet::new());
+        for stmt in &mut block.statements {
+            self.resolve_statement(stmt);
+        }
+        self.local_functions.pop();
+    }
+
+    fn resolve_statement(&mut self, stmt: &mut Statement) {
+        match stmt {
+            Statement::Let(let_stmt) => {
+                self.resolve_pattern(&mut let_stmt.pattern, let_stmt.span);
+                if let Some(ty) = &mut let_stmt.ty {
+                    self.resolve_type(ty, let_stmt.span);
+                }
+                if let Some(init) = &mut let_stmt.init {
+                    self.resolve_expr(init);
+                }
+            }
+            Statement::Assignment(assign) => {
+                self.resolve_expr(&mut assign.target);
+                self.resolve_expr(&mut assign.value);
+            }
+            Statement::Return(ret) => {
+                if let Some(value) = &mut ret.value {
+                    self.resolve_expr(value);
+                }
+            }
+            Statement::If(if_stmt) => self.resolve_if(if_stmt),
+            Statement::While(while_stmt) => {
+                self.resolve_expr(&mut while_stmt.condition);
+                self.resolve_block(&mut while_stmt.body);
+            }
+            Statement::For(for_stmt) => {
+                if let Some(init) = &mut for_stmt.init {
+                    self.resolve_statement(init);
+                }
+                if let Some(condition) = &mut for_stmt.condition {
+                    self.resolve_expr(condition);
+                }
+                if let Some(update) = &mut for_stmt.update {
+                    self.resolve_expr(update);
+                }
+                self.resolve_block(&mut for_stmt.body);
+            }
+            Statement::Expression(expr) => self.resolve_expr(expr),
+            Statement::Block(block) => self.resolve_block(block),
+            Statement::Defer(defer_stmt) => self.resolve_block(&mut defer_stmt.body),
+            Statement::Function(func) => {
+                // ローカル関数の名前は書き換えず、本体からの再帰呼び出しにも使えるよう先に登録する
+                if let Some(scope) = self.local_functions.last_mut() {
+                    scope.insert(func.name.clone());
+                }
+                self.resolve_signature(&mut func.params, &mut func.return_type, func.span);
+                self.resolve_block(&mut func.body);
+            }
+        }
+    }
+
+    fn resolve_if(&mut self, if_stmt: &mut IfStatement) {
+        self.resolve_expr(&mut if_stmt.condition);
+        self.resolve_block(&mut if_stmt.then_branch);
+        match &mut if_stmt.else_branch {
+            Some(ElseBranch::Block(block)) => self.resolve_block(block),
+            Some(ElseBranch::If(else_if)) => self.resolve_if(else_if),
+            None => {}
+        }
+    }
+
+    fn resolve_pattern(&mut self, pattern: &mut Pattern, span: Span) {
+        match pattern {
+            Pattern::Struct(name, fields) => {
+                self.resolve_name(name, span);
+                for (_, field) in fields {
+                    self.resolve_pattern(field, span);
+                }
+            }
+            Pattern::EnumVariant { enum_name, fields, .. } => {
+                self.resolve_name(enum_name, span);
+                match fields {
+                    EnumVariantPatternFields::Tuple(patterns) => {
+                        for field in patterns {
+                            self.resolve_pattern(field, span);
+                        }
+                    }
+                    EnumVariantPatternFields::Struct(fields) => {
+                        for (_, field) in fields {
+                            self.resolve_pattern(field, span);
+                        }
+                    }
+                    EnumVariantPatternFields::Unit => {}
+                }
+            }
+            Pattern::Tuple(patterns) => {
+                for element in patterns {
+                    self.resolve_pattern(element, span);
+                }
+            }
+            Pattern::Binding(_, pattern) => self.resolve_pattern(pattern, span),
+            Pattern::Or(patterns) => {
+                for alternative in patterns {
+                    self.resolve_pattern(alternative, span);
+                }
+            }
+            Pattern::Identifier(..) | Pattern::Literal(_) | Pattern::Range { .. } | Pattern::Wildcard => {}
+        }
+    }
+
+    fn resolve_exprs<'e>(&mut self, exprs: impl IntoIterator<Item = &'e mut Expression>) {
+        for expr in exprs {
+            self.resolve_expr(expr);
+        }
+    }
+
+    fn resolve_expr(&mut self, expr: &mut Expression) {
+        match expr {
+            Expression::Call(call) => {
+                // 関数名として使われた識別子だけを解決する（変数は対象外）
+                if let Expression::Identifier(ident) = call.callee.as_mut() {
+                    self.resolve_function_name(&mut ident.name, call.args.len(), ident.span);
+                } else {
+                    self.resolve_expr(&mut call.callee);
+                }
+                self.resolve_exprs(&mut call.args);
+            }
+            Expression::StructLit(struct_lit) => {
+                if let Some(name) = &mut struct_lit.name {
+                    self.resolve_name(name, struct_lit.span);
+                }
+                self.resolve_exprs(struct_lit.fields.iter_mut().map(|field| &mut field.value));
+            }
+            Expression::EnumVariant(enum_variant) => {
+                self.resolve_name(&mut enum_variant.enum_name, enum_variant.span);
+                match &mut enum_variant.fields {
+                    EnumVariantFields::Tuple(args) => self.resolve_exprs(args),
+                    EnumVariantFields::Struct(fields) => {
+                        self.resolve_exprs(fields.iter_mut().map(|field| &mut field.value))
+                    }
+                    EnumVariantFields::Unit => {}
+                }
+            }
+            Expression::Path(path) => {
+                // `Type::member` の型の部分
+                if path.segments.len() > 1 {
+                    self.resolve_name(&mut path.segments[0], path.span);
+                }
+            }
+            Expression::Cast(cast) => {
+                self.resolve_type(&mut cast.ty, cast.span);
+                self.resolve_expr(&mut cast.expr);
+            }
+            Expression::ListLiteral(list) => {
+                if let Some((name, type_args)) = &mut list.type_name {
+                    self.resolve_name(name, list.span);
+                    for arg in type_args {
+                        self.resolve_type(arg, list.span);
+                    }
+                }
+                self.resolve_exprs(&mut list.elements);
+            }
+            Expression::MapLiteral(map) => {
+                if let Some((name, type_args)) = &mut map.type_name {
+                    self.resolve_name(name, map.span);
+                    for arg in type_args {
+                        self.resolve_type(arg, map.span);
+                    }
+                }
+                self.resolve_exprs(map.pairs.iter_mut().flat_map(|(key, value)| [key, value]));
+            }
+            Expression::Match(match_expr) => {
+                self.resolve_expr(&mut match_expr.expr);
+                for arm in &mut match_expr.arms {
+                    self.resolve_pattern(&mut arm.pattern, match_expr.span);
+                    if let Some(guard) = &mut arm.guard {
+                        self.resolve_expr(guard);
+                    }
+                    self.resolve_expr(&mut arm.expr);
+                }
+            }
+            Expression::Block(block_expr) => {
+                self.local_functions.push(HashSet::new());
+                for stmt in &mut block_expr.statements {
+                    self.resolve_statement(stmt);
+                }
+                if let Some(last_expr) = &mut block_expr.last_expr {
+                    self.resolve_expr(last_expr);
+                }
+                self.local_functions.pop();
+            }
+            Expression::MethodCall(method_call) => {
+                self.resolve_expr(&mut method_call.object);
+                self.resolve_exprs(&mut method_call.args);
+            }
+            Expression::Binary(binary) => self.resolve_exprs([binary.left.as_mut(), binary.right.as_mut()]),
+            Expression::Unary(unary) => self.resolve_expr(&mut unary.expr),
+            Expression::Index(index) => self.resolve_exprs([index.object.as_mut(), index.index.as_mut()]),
+            Expression::Field(field) => self.resolve_expr(&mut field.object),
+            Expression::Reference(ref_expr) => self.resolve_expr(&mut ref_expr.expr),
+            Expression::Dereference(deref_expr) => self.resolve_expr(&mut deref_expr.expr),
+            Expression::Assignment(assign) => self.resolve_exprs([assign.target.as_mut(), assign.value.as_mut()]),
+            Expression::TemplateString(template) => {
+                self.resolve_exprs(template.parts.iter_mut().filter_map(|part| match part {
+                    TemplateStringPart::Interpolation(expr) => Some(expr),
+                    TemplateStringPart::Text(_) => None,
+                }))
+            }
+            Expression::Array(array) => self.resolve_exprs(&mut array.elements),
+            Expression::Tuple(tuple) => self.resolve_exprs(&mut tuple.elements),
+            Expression::If(if_expr) => {
+                self.resolve_exprs([if_expr.condition.as_mut(), if_expr.then_branch.as_mut()]);
+                if let Some(else_branch) = &mut if_expr.else_branch {
+                    self.resolve_expr(else_branch);
+                }
+            }
+            Expression::Integer(_)
+            | Expression::Float(_)
+            | Expression::String(_)
+            | Expression::Bytes(_)
+            | Expression::Boolean(_)
+            | Expression::Identifier(_) => {}
+        }
+    }
+}
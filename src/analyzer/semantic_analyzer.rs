@@ -3,15 +3,27 @@
 use crate::ast::*;
 use std::collections::HashMap;
 
+use super::borrow_checker::BorrowCheckMode;
+use super::call_graph::CallGraph;
+use super::const_prop::ConstEnv;
 use super::lifetime::LifetimeContext;
+use super::local_functions::{contains_local_functions, hoist_local_functions, LocalCapture};
+use super::lint::{Lint, LintLevel, LintLevels};
 use super::symbol::{AnalysisError, AnalysisResult, Scope};
 use super::type_checker::TypeChecker;
 use super::type_env::TypeEnvironment;
+use super::type_table::TypeTable;
 
 // サブモジュール
+mod assertions;
+mod borrow_sources;
+mod calls;
 mod complex_expressions;
+mod console;
+mod constants;
 mod declarations;
 mod expressions;
+mod inline;
 mod scope;
 mod statements;
 mod validation;
@@ -26,12 +38,28 @@ pub struct SemanticAnalyzer {
     pub imports: HashMap<String, String>,
     /// 現在の関数の戻り値型（return文のチェック用）
     pub current_return_type: Option<Type>,
+    /// 解析中のdeferブロックのネストの深さ（deferからのreturnを禁止するため）
+    pub defer_depth: usize,
     /// 現在の関数のライフタイムコンテキスト
     pub lifetime_context: LifetimeContext,
     /// 型パラメータ環境
     pub type_env: TypeEnvironment,
+    /// 解析中の関数がジェネリックかどうか（型テーブルへの記録先を切り替えるため）
+    pub in_generic_body: bool,
+    /// 解析で決定した式の型
+    pub type_table: TypeTable,
+    /// 関数とメソッドの呼び出しグラフ（本体の解析の後に作る）
+    pub call_graph: CallGraph,
+    /// 借用規則の違反の扱い
+    pub borrow_check: BorrowCheckMode,
+    /// リントごとの警告の報告の水準
+    pub lints: LintLevels,
+    /// 定数で初期化した変数の値（常に偽になる条件の警告用）
+    pub(crate) constants: ConstEnv,
     /// 収集されたエラー
     pub errors: Vec<AnalysisError>,
+    /// 収集された警告（解析の成否には影響しない。`deny` のリントは `errors` に入る）
+    pub warnings: Vec<AnalysisError>,
 }
 
 impl Default for SemanticAnalyzer {
@@ -47,26 +75,126 @@ impl SemanticAnalyzer {
             type_checker: TypeChecker::new(),
             imports: HashMap::new(),
             current_return_type: None,
+            defer_depth: 0,
             lifetime_context: LifetimeContext::new(),
             type_env: TypeEnvironment::new(),
+            in_generic_body: false,
+            type_table: TypeTable::new(),
+            call_graph: CallGraph::default(),
+            borrow_check: BorrowCheckMode::default(),
+            lints: LintLevels::default(),
+            constants: ConstEnv::default(),
             errors: Vec::new(),
+            warnings: Vec::new(),
+        }
+    }
+
+    /// 借用規則の違反の扱いを設定
+    ///
+    /// 設定しない場合は違反をエラーとして報告します。
+    pub fn with_borrow_check(mut self, mode: BorrowCheckMode) -> Self {
+        self.borrow_check = mode;
+        self
+    }
+
+    /// リントごとの警告の報告の水準を設定
+    ///
+    /// 設定しない場合はすべての警告を報告し、解析は失敗しません。
+    pub fn with_lints(mut self, lints: LintLevels) -> Self {
+        self.lints = lints;
+        self
+    }
+
+    /// 警告を報告
+    ///
+    /// 解析のすべての警告はここを通し、リントの水準に従って捨てる・警告として記録する・
+    /// エラーとして記録するのいずれかにします。
+    pub(crate) fn warn(&mut self, warning: AnalysisError) {
+        match self.lints.level(Lint::of_analysis(&warning)) {
+            LintLevel::Allow => {}
+            LintLevel::Warn => self.warnings.push(warning),
+            LintLevel::Deny => self.errors.push(warning),
         }
     }
 
     pub fn analyze(&mut self, program: &Program) -> AnalysisResult<()> {
+        // ローカル関数はトップレベルの関数に取り出してから解析する
+        if contains_local_functions(program) {
+            let mut hoisted = program.clone();
+            let captures = hoist_local_functions(&mut hoisted);
+            self.analyze_program(&hoisted);
+            self.report_captures(captures);
+        } else {
+            self.analyze_program(program);
+        }
+
+        log::debug!(
+            target: "yuni::analyzer",
+            "analysis finished with {} errors and {} warnings",
+            self.errors.len(),
+            self.warnings.len(),
+        );
+
+        if self.errors.is_empty() {
+            Ok(())
+        } else {
+            // 最初のエラーを返すが、Spanがdummyの場合は実際のエラー箇所が分からないので
+            // より詳細なエラーメッセージを構築
+            Err(self.errors[0].clone())
+        }
+    }
+
+    /// ローカル関数から外側の変数を参照した箇所をエラーにする
+    ///
+    /// 取り出した関数の中では外側の変数は未定義になるため、その箇所の未定義の名前のエラーを置き換えます。
+    fn report_captures(&mut self, captures: Vec<LocalCapture>) {
+        for capture in captures {
+            let error = AnalysisError::LocalFunctionCapture {
+                name: capture.name.clone(),
+                function: capture.function.clone(),
+                span: capture.span,
+            };
+            let undefined = self.errors.iter().position(|error| match error {
+                AnalysisError::UndefinedVariable { name, span } | AnalysisError::UndefinedFunction { name, span } => {
+                    *name == capture.name && span.start <= capture.span.start && capture.span.end <= span.end
+                }
+                _ => false,
+            });
+            match undefined {
+                Some(index) => self.errors[index] = error,
+                None => self.errors.push(error),
+            }
+        }
+    }
+
+    fn analyze_program(&mut self, program: &Program) {
         // インポートを処理
         for import in &program.imports {
             self.process_import(import);
         }
 
-        // 第一パス: 型定義と関数シグネチャを収集
+        // 第一パス: すべての型定義の名前を登録してから中身を検証する
+        // （宣言の順序に関係なく、型・関数・メソッドから後で定義される型を参照できるようにするため）
         for item in &program.items {
-            match item {
-                Item::TypeDef(type_def) => {
-                    if let Err(e) = self.collect_type_definition(type_def) {
-                        self.errors.push(e);
-                    }
+            if let Item::TypeDef(type_def) = item {
+                if let Err(e) = self.declare_type_definition(type_def) {
+                    self.errors.push(e);
+                }
+            }
+        }
+        for item in &program.items {
+            if let Item::TypeDef(type_def) = item {
+                if let Err(e) = self.validate_type_definition(type_def) {
+                    self.errors.push(e);
                 }
+            }
+        }
+        self.check_recursive_types(program);
+
+        // 第一パス（続き）: 関数とメソッドのシグネチャを収集
+        for item in &program.items {
+            match item {
+                Item::TypeDef(_) => {}
                 Item::Function(func) => {
                     if let Err(e) = self.collect_function_signature(func) {
                         self.errors.push(e);
@@ -77,6 +205,11 @@ impl SemanticAnalyzer {
                         self.errors.push(e);
                     }
                 }
+                Item::ExternFunction(func) => {
+                    if let Err(e) = self.collect_extern_function_signature(func) {
+                        self.errors.push(e);
+                    }
+                }
             }
         }
 
@@ -84,11 +217,13 @@ impl SemanticAnalyzer {
         for item in &program.items {
             match item {
                 Item::Function(func) => {
+                    self.in_generic_body = !func.type_params.is_empty();
                     if let Err(e) = self.analyze_function(func) {
                         self.errors.push(e);
                     }
                 }
                 Item::Method(method) => {
+                    self.in_generic_body = !method.type_params.is_empty();
                     if let Err(e) = self.analyze_method(method) {
                         self.errors.push(e);
                     }
@@ -96,13 +231,8 @@ impl SemanticAnalyzer {
                 _ => {}
             }
         }
-
-        if self.errors.is_empty() {
-            Ok(())
-        } else {
-            // 最初のエラーを返すが、Spanがdummyの場合は実際のエラー箇所が分からないので
-            // より詳細なエラーメッセージを構築
-            Err(self.errors[0].clone())
-        }
+        self.in_generic_body = false;
+        self.call_graph = CallGraph::build(program, &self.type_table);
+        self.check_recursive_inline(program);
     }
 }
\ No newline at end of file
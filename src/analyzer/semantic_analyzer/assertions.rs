@@ -0,0 +1,71 @@
+//! アサーションの組み込み関数の解析
+//!
+//! `assert` と `debug_assert` の条件はbool、`assert_eq` と `debug_assert_eq` の2つの引数は
+//! `==` で比較できる同じ型（数値同士は昇格規則に従う）である必要があります。
+
+use crate::assertion::AssertKind;
+use crate::ast::*;
+use crate::analyzer::numeric::is_untyped_integer_literal;
+use crate::analyzer::symbol::{AnalysisError, AnalysisResult};
+use crate::error::Callee;
+use super::SemanticAnalyzer;
+use super::calls::ValueUse;
+
+impl SemanticAnalyzer {
+    /// アサーションの呼び出しを解析
+    pub(super) fn analyze_assert_call(&mut self, kind: AssertKind, call: &CallExpr) -> AnalysisResult<Type> {
+        if call.args.len() != kind.arity() {
+            return Err(AnalysisError::ArgumentCountMismatch {
+                expected: kind.arity(),
+                found: call.args.len(),
+                span: call.span,
+                callee: Some(Box::new(Callee {
+                    signature: kind.signature(),
+                    param_spans: Vec::new(),
+                })),
+            });
+        }
+
+        if !kind.is_eq() {
+            let condition = &call.args[0];
+            let condition_type = self.analyze_value(condition, Some(&Type::Bool), ValueUse::Argument)?;
+            if !matches!(condition_type, Type::Bool | Type::Error) {
+                return Err(AnalysisError::TypeMismatch {
+                    expected: "bool".to_string(),
+                    found: self.type_checker.type_to_string(&condition_type),
+                    span: condition.span(),
+                });
+            }
+            return Ok(Type::Void);
+        }
+
+        // 接尾辞のない整数リテラルは二項演算と同様にもう一方の引数の型を採用する
+        let (left, right) = (&call.args[0], &call.args[1]);
+        let (left_type, right_type) = if is_untyped_integer_literal(left) {
+            let right_type = self.analyze_value(right, None, ValueUse::Argument)?;
+            let left_type = self.analyze_value(left, Some(&right_type), ValueUse::Argument)?;
+            (left_type, right_type)
+        } else {
+            let left_type = self.analyze_value(left, None, ValueUse::Argument)?;
+            let right_type = self.analyze_value(right, Some(&left_type), ValueUse::Argument)?;
+            (left_type, right_type)
+        };
+        for (arg, ty) in [(left, &left_type), (right, &right_type)] {
+            if !self.is_assert_comparable(ty) {
+                return Err(AnalysisError::AssertNotComparable {
+                    name: kind.name().to_string(),
+                    ty: self.type_checker.type_to_string(ty),
+                    span: arg.span(),
+                });
+            }
+        }
+        self.type_checker.binary_op_result_type(&BinaryOp::Eq, &left_type, &right_type, call.span)?;
+        Ok(Type::Void)
+    }
+
+    /// 失敗時のメッセージに値を表示できる型か（数値・bool・文字列）
+    fn is_assert_comparable(&self, ty: &Type) -> bool {
+        let ty = self.type_checker.resolve_type_alias(ty);
+        self.type_checker.is_numeric_type(&ty) || self.type_checker.is_string_type(&ty) || ty == Type::Bool
+    }
+}
@@ -0,0 +1,710 @@
+//! 参照の借用元の検査
+//!
+//! 関数の本体を走査し、参照を持つ値がどの引数・ローカル変数から借用されているか（借用元）を追跡します。
+//!
+//! - 戻り値の参照の借用元は、lives句の `return` に指定した引数でなければなりません。
+//!   参照を持つ引数が1つだけの場合は、lives句を省略するとその引数が借用元になります。
+//! - ローカル変数（値渡しの引数を含む）と一時的な値への参照は返せません。
+//! - ブロックの終わりで破棄された変数を借用している変数は、その後使えません。
+//!
+//! 関数・メソッド呼び出しの結果の借用元は、呼び出された関数のlives句から求めます。
+//! 型のわからない値や、シグネチャのわからない呼び出しの結果は検査しません。
+
+use crate::ast::*;
+use std::rc::Rc;
+use crate::analyzer::symbol::{AnalysisError, FunctionSignature, TypeKind};
+use super::SemanticAnalyzer;
+
+/// 参照の借用元
+#[derive(Debug, Clone, PartialEq)]
+enum Origin {
+    /// 参照を持つ引数（呼び出し元が有効であることを保証する）
+    Param(String),
+    /// ローカル変数
+    Local { name: String, declared_at: Span },
+    /// 一時的な値
+    Temporary(Span),
+    /// 借用元がわからない
+    Unknown,
+}
+
+/// 戻り値の借用元の指定
+enum ReturnSources {
+    /// lives句の `return` に指定された引数
+    Declared(Vec<String>),
+    /// 参照を持つ唯一の引数（lives句を省略した場合）
+    Inferred(String),
+    /// 参照を持つ引数が複数あるのにlives句で指定されていない
+    Missing(Vec<String>),
+    /// 参照を持つ引数がない
+    NoReferenceParams,
+}
+
+/// 本体を解析する関数の情報
+pub(super) struct BorrowSourcesInput<'a> {
+    pub name: &'a str,
+    /// レシーバーを含む引数（名前・型・宣言の位置）
+    pub params: Vec<(String, Type, Span)>,
+    pub return_type: &'a Type,
+    pub lives_clause: Option<&'a LivesClause>,
+    pub body: &'a Block,
+    /// 本体を除いたシグネチャの位置
+    pub signature_span: Span,
+}
+
+impl SemanticAnalyzer {
+    /// 関数本体の参照の借用元を検査し、エラーを `self.errors` に追加する
+    pub(super) fn check_borrow_sources(&mut self, input: BorrowSourcesInput) {
+        let errors = BorrowSources::new(self, &input.params).check(&input);
+        self.errors.extend(errors);
+    }
+
+    /// 型が参照を含むかどうか（構造体・Enumのフィールドもたどる）
+    fn contains_reference(&self, ty: &Type) -> bool {
+        self.contains_reference_in(ty, &mut Vec::new())
+    }
+
+    fn contains_reference_in(&self, ty: &Type, visiting: &mut Vec<String>) -> bool {
+        match ty {
+            Type::Reference(_, _) => true,
+            Type::Array(inner) => self.contains_reference_in(inner, visiting),
+            Type::Tuple(types) | Type::Generic(_, types) => {
+                types.iter().any(|ty| self.contains_reference_in(ty, visiting))
+            }
+            Type::UserDefined(name) => {
+                if visiting.contains(name) {
+                    return false;
+                }
+                let Some(info) = self.type_checker.get_type_info(name) else {
+                    return false;
+                };
+                visiting.push(name.clone());
+                let result = match &info.kind {
+                    TypeKind::Struct(fields) => {
+                        fields.iter().any(|field| self.contains_reference_in(&field.ty, visiting))
+                    }
+                    TypeKind::Enum(variants) => variants
+                        .iter()
+                        .flat_map(|variant| &variant.fields)
+                        .any(|field| self.contains_reference_in(&field.ty, visiting)),
+                    TypeKind::Alias(ty) => self.contains_reference_in(ty, visiting),
+                    TypeKind::Builtin => false,
+                };
+                visiting.pop();
+                result
+            }
+            _ => false,
+        }
+    }
+
+    /// 戻り値の借用元の指定を求める
+    ///
+    /// `params` はレシーバーを含む引数の名前と型です。
+    fn return_sources<'p>(
+        &self,
+        lives_clause: Option<&LivesClause>,
+        params: impl Iterator<Item = (&'p str, &'p Type)>,
+    ) -> ReturnSources {
+        let declared: Vec<String> = lives_clause
+            .into_iter()
+            .flat_map(|clause| &clause.constraints)
+            .filter(|constraint| constraint.is_return())
+            .flat_map(|constraint| constraint.sources.iter().cloned())
+            .collect();
+        if !declared.is_empty() {
+            return ReturnSources::Declared(declared);
+        }
+
+        let mut references: Vec<String> = params
+            .filter(|(_, ty)| self.contains_reference(ty))
+            .map(|(name, _)| name.to_string())
+            .collect();
+        match references.len() {
+            0 => ReturnSources::NoReferenceParams,
+            1 => ReturnSources::Inferred(references.remove(0)),
+            _ => ReturnSources::Missing(references),
+        }
+    }
+}
+
+/// スコープ内の変数
+struct Binding {
+    name: String,
+    declared_at: Span,
+    /// 型（わからない場合はNone）
+    ty: Option<Type>,
+    /// 値が持つ参照の借用元
+    origins: Vec<Origin>,
+    /// 借用元のうち既に破棄された変数（名前と宣言の位置）
+    dropped: Option<(String, Span)>,
+}
+
+struct BorrowSources<'a> {
+    analyzer: &'a SemanticAnalyzer,
+    /// 引数のスコープから始まるスコープのスタック
+    scopes: Vec<Vec<Binding>>,
+    /// return文の値の位置と借用元
+    returns: Vec<(Span, Vec<Origin>)>,
+    errors: Vec<AnalysisError>,
+}
+
+impl<'a> BorrowSources<'a> {
+    fn new(analyzer: &'a SemanticAnalyzer, params: &[(String, Type, Span)]) -> Self {
+        let params = params
+            .iter()
+            .map(|(name, ty, span)| Binding {
+                name: name.clone(),
+                declared_at: *span,
+                ty: Some(ty.clone()),
+                origins: if analyzer.contains_reference(ty) {
+                    vec![Origin::Param(name.clone())]
+                } else {
+                    Vec::new()
+                },
+                dropped: None,
+            })
+            .collect();
+        Self {
+            analyzer,
+            scopes: vec![params],
+            returns: Vec::new(),
+            errors: Vec::new(),
+        }
+    }
+
+    fn check(mut self, input: &BorrowSourcesInput) -> Vec<AnalysisError> {
+        let returns_reference = self.analyzer.contains_reference(input.return_type);
+        self.check_lives_clause(input, returns_reference);
+
+        self.visit_block(input.body);
+        if !returns_reference {
+            return self.errors;
+        }
+
+        let sources = self.analyzer.return_sources(
+            input.lives_clause,
+            input.params.iter().map(|(name, ty, _)| (name.as_str(), ty)),
+        );
+        let mut borrowed_params: Vec<String> = Vec::new();
+        for (span, origins) in std::mem::take(&mut self.returns) {
+            for origin in origins {
+                match origin {
+                    Origin::Local { name, declared_at } => {
+                        self.errors.push(AnalysisError::ReturnsLocalReference { name, span, declared_at });
+                    }
+                    Origin::Temporary(span) => self.errors.push(AnalysisError::TemporaryReference { span }),
+                    Origin::Param(param) => {
+                        let allowed = match &sources {
+                            ReturnSources::Declared(names) => names.clone(),
+                            ReturnSources::Inferred(name) => vec![name.clone()],
+                            ReturnSources::Missing(_) | ReturnSources::NoReferenceParams => {
+                                if !borrowed_params.contains(&param) {
+                                    borrowed_params.push(param);
+                                }
+                                continue;
+                            }
+                        };
+                        if !allowed.contains(&param) {
+                            let suggestion = lives_suggestion(allowed.iter().chain([&param]));
+                            self.errors.push(AnalysisError::UnlistedReturnSource { param, suggestion, span });
+                        }
+                    }
+                    Origin::Unknown => {}
+                }
+            }
+        }
+
+        if let ReturnSources::Missing(params) = &sources {
+            // 本体から借用元がわかる場合はそれを、わからない場合は参照を持つ引数すべてを提案する
+            let suggested = params.iter().filter(|param| borrowed_params.contains(param)).collect::<Vec<_>>();
+            let suggestion = if suggested.is_empty() {
+                lives_suggestion(params.iter())
+            } else {
+                lives_suggestion(suggested.into_iter())
+            };
+            self.errors.push(AnalysisError::MissingLivesClause {
+                name: input.name.to_string(),
+                suggestion,
+                span: input.signature_span,
+            });
+        }
+        self.errors
+    }
+
+    /// lives句の `return` に指定された名前が参照を持つ引数かどうかを検査
+    fn check_lives_clause(&mut self, input: &BorrowSourcesInput, returns_reference: bool) {
+        let Some(clause) = input.lives_clause else {
+            return;
+        };
+        for constraint in clause.constraints.iter().filter(|constraint| constraint.is_return()) {
+            if !returns_reference {
+                self.errors.push(AnalysisError::LifetimeError {
+                    message: format!(
+                        "戻り値の型 {} は参照を含まないため、借用元を指定できません",
+                        self.analyzer.type_checker.type_to_string(input.return_type)
+                    ),
+                    span: constraint.span,
+                });
+                continue;
+            }
+            for source in &constraint.sources {
+                let is_reference_param = input
+                    .params
+                    .iter()
+                    .any(|(name, ty, _)| name == source && self.analyzer.contains_reference(ty));
+                if !is_reference_param {
+                    self.errors.push(AnalysisError::LifetimeError {
+                        message: format!("lives句の {} は参照を持つ引数ではありません", source),
+                        span: constraint.span,
+                    });
+                }
+            }
+        }
+    }
+
+    fn expr_type(&self, expr: &Expression) -> Option<Type> {
+        let table = &self.analyzer.type_table;
+        table
+            .get(expr)
+            .or_else(|| table.get_generic(expr))
+            .filter(|ty| !matches!(ty, Type::Void))
+            .map(|ty| self.analyzer.type_checker.resolve_type_alias(ty))
+    }
+
+    fn lookup(&mut self, name: &str) -> Option<&mut Binding> {
+        self.scopes
+            .iter_mut()
+            .rev()
+            .find_map(|scope| scope.iter_mut().rev().find(|binding| binding.name == name))
+    }
+
+    fn enter_scope(&mut self) {
+        self.scopes.push(Vec::new());
+    }
+
+    /// スコープを抜け、破棄される変数を借用している外側の変数に印を付ける
+    fn exit_scope(&mut self) {
+        let Some(dropped) = self.scopes.pop() else {
+            return;
+        };
+        for binding in self.scopes.iter_mut().flatten() {
+            if binding.dropped.is_some() {
+                continue;
+            }
+            binding.dropped = dropped
+                .iter()
+                .find(|local| {
+                    binding.origins.iter().any(|origin| {
+                        matches!(origin, Origin::Local { name, declared_at }
+                            if *name == local.name && *declared_at == local.declared_at)
+                    })
+                })
+                .map(|local| (local.name.clone(), local.declared_at));
+        }
+    }
+
+    fn declare(&mut self, name: &str, declared_at: Span, ty: Option<Type>, origins: Vec<Origin>) {
+        let binding = Binding {
+            name: name.to_string(),
+            declared_at,
+            ty,
+            origins,
+            dropped: None,
+        };
+        self.scopes.last_mut().expect("scope stack is never empty").push(binding);
+    }
+
+    fn visit_block(&mut self, block: &Block) {
+        self.enter_scope();
+        for stmt in &block.statements {
+            self.visit_statement(stmt);
+        }
+        self.exit_scope();
+    }
+
+    fn visit_statement(&mut self, stmt: &Statement) {
+        match stmt {
+            Statement::Let(let_stmt) => {
+                let mut origins = let_stmt.init.as_ref().map(|init| self.eval(init)).unwrap_or_default();
+                let ty = let_stmt
+                    .ty
+                    .as_ref()
+                    .map(|ty| self.analyzer.type_checker.resolve_type_alias(ty))
+                    .or_else(|| let_stmt.init.as_ref().and_then(|init| self.expr_type(init)));
+                if ty.as_ref().is_some_and(|ty| !self.analyzer.contains_reference(ty)) {
+                    origins.clear();
+                }
+                // 分解した変数の型はわからないため、値全体の借用元を引き継ぐ
+                let single = matches!(let_stmt.pattern, Pattern::Identifier(_, _));
+                for name in pattern_bindings(&let_stmt.pattern) {
+                    let ty = if single { ty.clone() } else { None };
+                    self.declare(name, let_stmt.span, ty, origins.clone());
+                }
+            }
+            Statement::Assignment(assign) => self.assign(&assign.target, &assign.value),
+            Statement::Return(ret) => {
+                if let Some(value) = &ret.value {
+                    let origins = self.eval(value);
+                    self.returns.push((value.span(), origins));
+                }
+            }
+            Statement::If(if_stmt) => self.visit_if(if_stmt),
+            Statement::While(while_stmt) => {
+                self.eval(&while_stmt.condition);
+                self.visit_block(&while_stmt.body);
+            }
+            Statement::For(for_stmt) => {
+                self.enter_scope();
+                if let Some(init) = &for_stmt.init {
+                    self.visit_statement(init);
+                }
+                if let Some(condition) = &for_stmt.condition {
+                    self.eval(condition);
+                }
+                self.visit_block(&for_stmt.body);
+                if let Some(update) = &for_stmt.update {
+                    self.eval(update);
+                }
+                self.exit_scope();
+            }
+            Statement::Expression(expr) => {
+                self.eval(expr);
+            }
+            Statement::Block(block) => self.visit_block(block),
+            Statement::Defer(defer) => self.visit_block(&defer.body),
+            Statement::Function(_) => {}
+        }
+    }
+
+    fn visit_if(&mut self, if_stmt: &IfStatement) {
+        self.eval(&if_stmt.condition);
+        self.visit_block(&if_stmt.then_branch);
+        match &if_stmt.else_branch {
+            Some(ElseBranch::Block(block)) => self.visit_block(block),
+            Some(ElseBranch::If(else_if)) => self.visit_if(else_if),
+            None => {}
+        }
+    }
+
+    /// 代入先の変数の借用元を更新する
+    ///
+    /// 変数全体への代入は借用元を置き換え、フィールドや要素への代入は借用元を追加します。
+    fn assign(&mut self, target: &Expression, value: &Expression) {
+        let origins = self.eval(value);
+        match target {
+            Expression::Identifier(ident) => {
+                if let Some(binding) = self.lookup(&ident.name) {
+                    binding.origins = origins;
+                    binding.dropped = None;
+                }
+            }
+            Expression::Field(_) | Expression::Index(_) => {
+                let mut root = target;
+                while let Expression::Field(FieldExpr { object, .. }) | Expression::Index(IndexExpr { object, .. }) = root {
+                    if let Expression::Index(index) = root {
+                        self.eval(&index.index);
+                    }
+                    root = object;
+                }
+                if let Expression::Identifier(ident) = root {
+                    self.read(ident);
+                    if let Some(binding) = self.lookup(&ident.name) {
+                        union(&mut binding.origins, origins);
+                    }
+                } else {
+                    self.eval(root);
+                }
+            }
+            _ => {
+                self.eval(target);
+            }
+        }
+    }
+
+    /// 変数を読み、借用元が破棄されていればエラーにする
+    fn read(&mut self, ident: &Identifier) -> Option<&Binding> {
+        let binding = self.lookup(&ident.name)?;
+        if let Some((borrowed, dropped_at)) = binding.dropped.take() {
+            let error = AnalysisError::BorrowedValueDropped {
+                name: ident.name.clone(),
+                borrowed,
+                span: ident.span,
+                dropped_at,
+            };
+            self.errors.push(error);
+        }
+        self.lookup(&ident.name).map(|binding| &*binding)
+    }
+
+    /// 式の値が持つ参照の借用元を求める
+    fn eval(&mut self, expr: &Expression) -> Vec<Origin> {
+        let origins = match expr {
+            Expression::Integer(_)
+            | Expression::Float(_)
+            | Expression::String(_)
+            | Expression::Bytes(_)
+            | Expression::Boolean(_)
+            | Expression::Path(_) => Vec::new(),
+            Expression::TemplateString(template) => {
+                for part in &template.parts {
+                    if let TemplateStringPart::Interpolation(expr) = part {
+                        self.eval(expr);
+                    }
+                }
+                Vec::new()
+            }
+            Expression::Identifier(ident) => match self.read(ident) {
+                Some(binding) if binding.origins.is_empty() && binding.ty.is_none() => vec![Origin::Unknown],
+                Some(binding) => binding.origins.clone(),
+                None => vec![Origin::Unknown],
+            },
+            Expression::Binary(binary) => {
+                self.eval(&binary.left);
+                self.eval(&binary.right);
+                Vec::new()
+            }
+            Expression::Unary(unary) => {
+                self.eval(&unary.expr);
+                Vec::new()
+            }
+            Expression::Cast(cast) => {
+                self.eval(&cast.expr);
+                Vec::new()
+            }
+            Expression::Call(call) => {
+                let signature = match call.callee.as_ref() {
+                    Expression::Identifier(ident) => {
+                        self.analyzer.type_checker.get_function_signature(&ident.name).map(Rc::as_ref)
+                    }
+                    _ => None,
+                };
+                let args: Vec<Vec<Origin>> = call.args.iter().map(|arg| self.eval(arg)).collect();
+                self.call_result(signature, None, args)
+            }
+            Expression::MethodCall(call) => {
+                let signature = self.method_signature(&call.object, &call.method);
+                // `&self` のメソッドは値のレシーバーを自動的に借用する
+                let receiver = match signature.and_then(|signature| signature.receiver_type.as_ref()) {
+                    Some(Type::Reference(_, _)) => self.borrow(&call.object),
+                    _ => self.eval(&call.object),
+                };
+                let args: Vec<Vec<Origin>> = call.args.iter().map(|arg| self.eval(arg)).collect();
+                self.call_result(signature, Some(receiver), args)
+            }
+            Expression::Index(index) => {
+                self.eval(&index.index);
+                self.eval(&index.object)
+            }
+            Expression::Field(field) => self.eval(&field.object),
+            Expression::Reference(reference) => self.borrow(&reference.expr),
+            Expression::Dereference(deref) => self.eval(&deref.expr),
+            Expression::StructLit(lit) => self.eval_all(lit.fields.iter().map(|field| &field.value)),
+            Expression::EnumVariant(variant) => match &variant.fields {
+                EnumVariantFields::Tuple(values) => self.eval_all(values.iter()),
+                EnumVariantFields::Struct(fields) => self.eval_all(fields.iter().map(|field| &field.value)),
+                EnumVariantFields::Unit => Vec::new(),
+            },
+            Expression::Array(array) => self.eval_all(array.elements.iter()),
+            Expression::Tuple(tuple) => self.eval_all(tuple.elements.iter()),
+            Expression::ListLiteral(list) => self.eval_all(list.elements.iter()),
+            Expression::MapLiteral(map) => {
+                self.eval_all(map.pairs.iter().flat_map(|(key, value)| [key, value]))
+            }
+            Expression::Assignment(assign) => {
+                self.assign(&assign.target, &assign.value);
+                Vec::new()
+            }
+            Expression::Match(match_expr) => {
+                let scrutinee = self.eval(&match_expr.expr);
+                let mut origins = Vec::new();
+                for arm in &match_expr.arms {
+                    self.enter_scope();
+                    for name in pattern_bindings(&arm.pattern) {
+                        self.declare(name, match_expr.expr.span(), None, scrutinee.clone());
+                    }
+                    if let Some(guard) = &arm.guard {
+                        self.eval(guard);
+                    }
+                    let arm_origins = self.eval(&arm.expr);
+                    union(&mut origins, arm_origins);
+                    self.exit_scope();
+                }
+                origins
+            }
+            Expression::If(if_expr) => {
+                self.eval(&if_expr.condition);
+                let mut origins = self.eval(&if_expr.then_branch);
+                if let Some(else_branch) = &if_expr.else_branch {
+                    let else_origins = self.eval(else_branch);
+                    union(&mut origins, else_origins);
+                }
+                origins
+            }
+            Expression::Block(block) => {
+                self.enter_scope();
+                for stmt in &block.statements {
+                    self.visit_statement(stmt);
+                }
+                let origins = block.last_expr.as_ref().map(|expr| self.eval(expr)).unwrap_or_default();
+                self.exit_scope();
+                origins
+            }
+        };
+
+        // 参照を含まない型の値は借用元を持たない
+        match self.expr_type(expr) {
+            Some(ty) if !self.analyzer.contains_reference(&ty) => Vec::new(),
+            _ => origins,
+        }
+    }
+
+    fn eval_all<'e>(&mut self, exprs: impl Iterator<Item = &'e Expression>) -> Vec<Origin> {
+        let mut origins = Vec::new();
+        for expr in exprs {
+            let expr_origins = self.eval(expr);
+            union(&mut origins, expr_origins);
+        }
+        origins
+    }
+
+    /// `&place` の借用元を求める
+    fn borrow(&mut self, place: &Expression) -> Vec<Origin> {
+        match place {
+            Expression::Identifier(ident) => match self.read(ident) {
+                // 参照を通した借用は、参照の借用元を借用する
+                Some(Binding { ty: Some(Type::Reference(_, _)), origins, .. }) => origins.clone(),
+                Some(Binding { ty: Some(_), name, declared_at, .. }) => vec![Origin::Local {
+                    name: name.clone(),
+                    declared_at: *declared_at,
+                }],
+                Some(Binding { ty: None, origins, .. }) if !origins.is_empty() => origins.clone(),
+                _ => vec![Origin::Unknown],
+            },
+            Expression::Field(field) => self.borrow(&field.object),
+            Expression::Index(index) => {
+                self.eval(&index.index);
+                self.borrow(&index.object)
+            }
+            Expression::Dereference(deref) => self.eval(&deref.expr),
+            // リテラルはプログラム全体で有効
+            Expression::Integer(_)
+            | Expression::Float(_)
+            | Expression::String(_)
+            | Expression::Bytes(_)
+            | Expression::Boolean(_)
+            | Expression::Path(_) => Vec::new(),
+            _ => {
+                let origins = self.eval(place);
+                match self.expr_type(place) {
+                    Some(Type::Reference(_, _)) => origins,
+                    Some(_) => vec![Origin::Temporary(place.span())],
+                    None => vec![Origin::Unknown],
+                }
+            }
+        }
+    }
+
+    fn method_signature(&self, object: &Expression, method: &str) -> Option<&'a FunctionSignature> {
+        let ty = self.expr_type(object)?;
+        let ty = match ty {
+            Type::Reference(inner, _) => *inner,
+            ty => ty,
+        };
+        let type_name = match &ty {
+            Type::UserDefined(name) | Type::Generic(name, _) => name,
+            _ => return None,
+        };
+        self.analyzer.type_checker.get_method_signature(type_name, method).map(Rc::as_ref)
+    }
+
+    /// 呼び出しの結果の借用元を求める
+    ///
+    /// lives句の `return` に指定された引数（省略時は参照を持つ唯一の引数）の借用元を引き継ぎます。
+    /// メソッドのlives句で引数にない名前はレシーバーを指します。
+    fn call_result(
+        &self,
+        signature: Option<&FunctionSignature>,
+        receiver: Option<Vec<Origin>>,
+        args: Vec<Vec<Origin>>,
+    ) -> Vec<Origin> {
+        let Some(signature) = signature else {
+            return vec![Origin::Unknown];
+        };
+        if !self.analyzer.contains_reference(&signature.return_type) {
+            return Vec::new();
+        }
+
+        let receiver_param = signature.receiver_type.as_ref().map(|ty| ("", ty));
+        let params = receiver_param
+            .into_iter()
+            .chain(signature.params.iter().map(|(name, ty)| (name.as_str(), ty)));
+        let sources = match self.analyzer.return_sources(signature.lives_clause.as_ref(), params) {
+            ReturnSources::Declared(names) | ReturnSources::Missing(names) => names,
+            ReturnSources::Inferred(name) => vec![name],
+            ReturnSources::NoReferenceParams => return Vec::new(),
+        };
+
+        let mut origins = Vec::new();
+        for source in sources {
+            let source_origins = match signature.params.iter().position(|(name, _)| *name == source) {
+                Some(index) => args.get(index),
+                None => receiver.as_ref(),
+            };
+            match source_origins {
+                Some(source_origins) => union(&mut origins, source_origins.clone()),
+                None => union(&mut origins, vec![Origin::Unknown]),
+            }
+        }
+        origins
+    }
+}
+
+/// `lives return: a + b` の形の提案
+fn lives_suggestion<'s>(params: impl Iterator<Item = &'s String>) -> String {
+    let params: Vec<&str> = params.map(String::as_str).collect();
+    format!("lives {}: {}", LivesConstraint::RETURN_TARGET, params.join(" + "))
+}
+
+/// 借用元を重複なく追加する
+fn union(origins: &mut Vec<Origin>, other: Vec<Origin>) {
+    for origin in other {
+        if !origins.contains(&origin) {
+            origins.push(origin);
+        }
+    }
+}
+
+/// パターンが束縛する変数名
+fn pattern_bindings(pattern: &Pattern) -> Vec<&str> {
+    let mut names = Vec::new();
+    collect_bindings(pattern, &mut names);
+    names
+}
+
+fn collect_bindings<'p>(pattern: &'p Pattern, names: &mut Vec<&'p str>) {
+    match pattern {
+        Pattern::Identifier(name, _) => names.push(name),
+        Pattern::Tuple(patterns) => patterns.iter().for_each(|pattern| collect_bindings(pattern, names)),
+        Pattern::Struct(_, fields) => fields.iter().for_each(|(_, pattern)| collect_bindings(pattern, names)),
+        Pattern::EnumVariant { fields, .. } => match fields {
+            EnumVariantPatternFields::Tuple(patterns) => {
+                patterns.iter().for_each(|pattern| collect_bindings(pattern, names))
+            }
+            EnumVariantPatternFields::Struct(fields) => {
+                fields.iter().for_each(|(_, pattern)| collect_bindings(pattern, names))
+            }
+            EnumVariantPatternFields::Unit => {}
+        },
+        Pattern::Binding(name, pattern) => {
+            names.push(name);
+            collect_bindings(pattern, names);
+        }
+        // どの選択肢も同じ変数を束縛するため、最初の選択肢の変数を数える
+        Pattern::Or(patterns) => {
+            if let Some(first) = patterns.first() {
+                collect_bindings(first, names);
+            }
+        }
+        Pattern::Literal(_) | Pattern::Range { .. } | Pattern::Wildcard => {}
+    }
+}
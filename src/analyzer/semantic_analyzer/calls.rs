@@ -0,0 +1,150 @@
+//! 呼び出しの引数のエラー
+//!
+//! 引数の数や型が一致しない場合に、呼び出された関数のシグネチャと引数の宣言を示すエラーを作ります。
+//! 値を返さない関数の呼び出しを値として使った場合も、シグネチャを示す専用のエラーにします。
+
+use crate::ast::*;
+use std::rc::Rc;
+use crate::analyzer::numeric::is_untyped_integer_literal;
+use crate::analyzer::symbol::{AnalysisError, AnalysisResult, FunctionSignature};
+use crate::error::Callee;
+use super::SemanticAnalyzer;
+
+/// 式の値を使う位置
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum ValueUse {
+    Let,
+    Condition,
+    Argument,
+    Operand,
+    Assignment,
+    Return,
+}
+
+impl ValueUse {
+    fn describe(self) -> &'static str {
+        match self {
+            ValueUse::Let => "let文の初期化式",
+            ValueUse::Condition => "条件",
+            ValueUse::Argument => "引数",
+            ValueUse::Operand => "演算子のオペランド",
+            ValueUse::Assignment => "代入する値",
+            ValueUse::Return => "戻り値",
+        }
+    }
+}
+
+impl SemanticAnalyzer {
+    /// 値として使う式を解析する
+    ///
+    /// 値を返さない関数・メソッドの呼び出しは、型の不一致ではなく専用のエラーにします。
+    /// 式文や戻り値型のない関数の最後の文としての呼び出しは、この関数を通らないため許可されます。
+    pub(super) fn analyze_value(
+        &mut self,
+        expr: &Expression,
+        expected_type: Option<&Type>,
+        usage: ValueUse,
+    ) -> AnalysisResult<Type> {
+        let ty = self.analyze_expression_with_type(expr, expected_type)?;
+        if ty != Type::Void {
+            return Ok(ty);
+        }
+        let (name, signature) = match expr {
+            Expression::Call(call) => match call.callee.as_ref() {
+                Expression::Identifier(ident) => {
+                    (&ident.name, self.type_checker.get_function_signature(&ident.name).map(Rc::as_ref))
+                }
+                _ => return Ok(ty),
+            },
+            Expression::MethodCall(method_call) => {
+                let object_type = match self.type_table.get(&method_call.object) {
+                    Some(Type::Reference(inner, _)) => Some(inner.as_ref()),
+                    object_type => object_type,
+                };
+                let signature = match object_type {
+                    Some(Type::UserDefined(type_name) | Type::Generic(type_name, _)) => {
+                        self.type_checker.get_method_signature(type_name, &method_call.method).map(Rc::as_ref)
+                    }
+                    _ => None,
+                };
+                (&method_call.method, signature)
+            }
+            _ => return Ok(ty),
+        };
+        Err(AnalysisError::VoidValue {
+            name: name.clone(),
+            usage: usage.describe().to_string(),
+            span: expr.span(),
+            callee: signature.map(Self::callee),
+        })
+    }
+
+    /// 引数の数が一致しないエラー
+    pub(super) fn argument_count_error(&self, signature: &FunctionSignature, found: usize, span: Span) -> AnalysisError {
+        AnalysisError::ArgumentCountMismatch {
+            expected: signature.params.len(),
+            found,
+            span,
+            callee: Some(Self::callee(signature)),
+        }
+    }
+
+    /// 引数の型が一致するか（暗黙的な初期化子変換と、接尾辞のない整数リテラル・文字列リテラルを含む）
+    fn argument_fits(&self, arg: &Expression, arg_type: &Type, expected: &Type) -> bool {
+        let expected = self.type_checker.resolve_type_alias(expected);
+        let arg_type = self.type_checker.resolve_type_alias(arg_type);
+        self.type_checker.types_compatible(&expected, &arg_type)
+            || self.can_convert_initializer_to_type(arg, &arg_type, &expected)
+            || (is_untyped_integer_literal(arg) && self.type_checker.is_integer_type(&expected))
+            || (matches!(arg, Expression::String(_)) && expected == Type::String)
+    }
+
+    /// `index` 番目の引数の型が一致しないエラー
+    ///
+    /// `arg_types` は `index` 番目までの引数の型です。残りの引数も解析し、
+    /// 2つの引数を入れ替えると型が一致する場合はその位置をエラーに含めます。
+    pub(super) fn argument_type_error(
+        &mut self,
+        signature: &FunctionSignature,
+        args: &[Expression],
+        mut arg_types: Vec<Type>,
+        index: usize,
+    ) -> AnalysisError {
+        // 呼び出しは既にエラーのため、残りの引数のエラーは報告しない
+        for arg in &args[arg_types.len()..] {
+            let ty = self.analyze_expression(arg).unwrap_or(Type::Void);
+            arg_types.push(ty);
+        }
+
+        let fits = |this: &Self, arg: usize, param: usize| {
+            this.argument_fits(&args[arg], &arg_types[arg], &signature.params[param].1)
+        };
+        let swap_with = (0..args.len())
+            .filter(|&other| other != index)
+            .find(|&other| {
+                fits(self, index, other)
+                    && fits(self, other, index)
+                    && (0..args.len())
+                        .filter(|&i| i != index && i != other)
+                        .all(|i| fits(self, i, i))
+            });
+
+        let (param, expected) = &signature.params[index];
+        AnalysisError::ArgumentTypeMismatch {
+            index,
+            param: param.clone(),
+            expected: self.type_checker.type_to_string(expected),
+            found: self.type_checker.type_to_string(&arg_types[index]),
+            span: args[index].span(),
+            callee: Self::callee(signature),
+            swap_with,
+        }
+    }
+
+    fn callee(signature: &FunctionSignature) -> Box<Callee> {
+        Box::new(Callee {
+            signature: signature.display(),
+            param_spans: signature.param_spans.clone(),
+        })
+    }
+}
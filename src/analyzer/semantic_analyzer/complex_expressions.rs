@@ -1,14 +1,22 @@
 //! 複雑な式（match、enum variant、メソッド呼び出しなど）の解析
 
 use crate::ast::*;
+use std::rc::Rc;
+use crate::analyzer::const_prop::Constant;
+use crate::analyzer::divergence::expression_diverges;
+use crate::analyzer::builtin_methods::{builtin_method, builtin_methods, receiver_value_type};
 use crate::analyzer::symbol::{AnalysisError, AnalysisResult, Symbol, TypeInfo, TypeKind};
 use super::SemanticAnalyzer;
+use super::calls::ValueUse;
 
 impl SemanticAnalyzer {
     /// match式の解析
-    pub fn analyze_match_expression(&mut self, match_expr: &MatchExpr) -> AnalysisResult<Type> {
-        // match対象の式を解析
-        let expr_type = self.analyze_expression(&match_expr.expr)?;
+    pub fn analyze_match_expression(&mut self, match_expr: &MatchExpr, expected_type: Option<&Type>) -> AnalysisResult<Type> {
+        // match対象の式を解析（誤りは記録し、アームの解析を続ける）
+        let expr_type = self.analyze_expression(&match_expr.expr).unwrap_or_else(|e| {
+            self.errors.push(e);
+            Type::Error
+        });
         
         if match_expr.arms.is_empty() {
             return Err(AnalysisError::TypeMismatch {
@@ -18,35 +26,91 @@ impl SemanticAnalyzer {
             });
         }
         
-        // 最初のarmの型を基準とする
-        let first_arm = &match_expr.arms[0];
-        self.analyze_pattern(&first_arm.pattern, &expr_type)?;
-        let expected_type = self.analyze_expression(&first_arm.expr)?;
+        let scrutinee = self.match_scrutinee_constant(&match_expr.expr);
         
-        // 残りのarmの型を確認
-        for arm in &match_expr.arms[1..] {
-            self.analyze_pattern(&arm.pattern, &expr_type)?;
-            let arm_type = self.analyze_expression(&arm.expr)?;
-            if !self.type_checker.types_compatible(&expected_type, &arm_type) {
-                return Err(AnalysisError::TypeMismatch {
-                    expected: format!("{:?}", expected_type),
-                    found: format!("{:?}", arm_type),
-                    span: match_expr.span,
+        // 最初の値を生むarmの型を基準とする（returnで抜けるarmは値を生まないため型の決定に使わない）
+        // 期待される型がなければ、決まったarmの型を以降のarmの期待される型にする
+        let mut result_type: Option<Type> = None;
+        for arm in &match_expr.arms {
+            let arm_expected = expected_type.or(result_type.as_ref()).cloned();
+            // アームの誤りは記録し、ほかのアームの解析を続ける
+            let arm_type = self
+                .analyze_match_arm(arm, &expr_type, scrutinee.as_ref(), arm_expected.as_ref())
+                .unwrap_or_else(|e| {
+                    self.errors.push(e);
+                    Type::Error
                 });
+            if expression_diverges(&arm.expr) {
+                continue;
+            }
+            match &result_type {
+                None => result_type = Some(arm_type),
+                Some(first_type) if !self.type_checker.types_compatible(first_type, &arm_type) => {
+                    return Err(AnalysisError::TypeMismatch {
+                        expected: self.type_checker.type_to_string(first_type),
+                        found: self.type_checker.type_to_string(&arm_type),
+                        span: match_expr.span,
+                    });
+                }
+                Some(_) => {}
             }
         }
         
-        // 網羅性チェック
+        // 重複したリテラルのアームと網羅性のチェック
+        self.check_duplicate_match_arms(match_expr)?;
         self.check_match_exhaustiveness(match_expr, &expr_type)?;
         
-        Ok(expected_type)
+        Ok(result_type.unwrap_or(Type::Void))
+    }
+    
+    /// match armの解析
+    ///
+    /// パターンで束縛した変数はそのarmのガードと式からのみ参照できるよう、
+    /// arm毎にスコープを作る。
+    /// match対象が定数の場合は、常に偽になるガードを警告する。
+    fn analyze_match_arm(
+        &mut self,
+        arm: &MatchArm,
+        expr_type: &Type,
+        scrutinee: Option<&Constant>,
+        expected_type: Option<&Type>,
+    ) -> AnalysisResult<Type> {
+        self.enter_scope();
+        let result = self.analyze_match_arm_body(arm, expr_type, scrutinee, expected_type);
+        self.exit_scope();
+        result
+    }
+    
+    fn analyze_match_arm_body(
+        &mut self,
+        arm: &MatchArm,
+        expr_type: &Type,
+        scrutinee: Option<&Constant>,
+        expected_type: Option<&Type>,
+    ) -> AnalysisResult<Type> {
+        self.check_literal_pattern_type(&arm.pattern, expr_type, arm.span)?;
+        self.analyze_pattern(&arm.pattern, expr_type, arm.span)?;
+        if let Some(guard) = &arm.guard {
+            let guard_type = self.analyze_expression(guard)?;
+            if !matches!(guard_type, Type::Bool | Type::Error) {
+                return Err(AnalysisError::TypeMismatch {
+                    expected: "bool".to_string(),
+                    found: self.type_checker.type_to_string(&guard_type),
+                    span: self.get_expression_span(guard),
+                });
+            }
+            if let Some(scrutinee) = scrutinee {
+                self.check_never_taken_guard(arm, scrutinee);
+            }
+        }
+        self.analyze_expression_with_type(&arm.expr, expected_type)
     }
     
     /// enum variant式の解析
     pub fn analyze_enum_variant_expression(&mut self, enum_variant: &EnumVariantExpr) -> AnalysisResult<Type> {
-        // enum型が定義されているかチェック（借用を避けるためにクローンする）
+        // enum型が定義されているかチェック（型情報は共有しているため参照カウントを増やすだけで済む）
         let enum_def = if let Some(enum_def) = self.lookup_type(&enum_variant.enum_name) {
-            enum_def.clone()
+            Rc::clone(enum_def)
         } else {
             return Err(AnalysisError::UndefinedType {
                 name: enum_variant.enum_name.clone(),
@@ -69,10 +133,11 @@ impl SemanticAnalyzer {
                                     expected: fields.len(),
                                     found: args.len(),
                                     span: enum_variant.span,
+                                    callee: None,
                                 });
                             }
                             for (arg, field) in args.iter().zip(fields.iter()) {
-                                let arg_type = self.analyze_expression(arg)?;
+                                let arg_type = self.analyze_expression_with_type(arg, Some(&field.ty))?;
                                 if !self.type_checker.types_compatible(&field.ty, &arg_type) {
                                     return Err(AnalysisError::TypeMismatch {
                                         expected: format!("{:?}", field.ty),
@@ -86,7 +151,7 @@ impl SemanticAnalyzer {
                         (crate::ast::EnumVariantFields::Struct(field_inits), fields) => {
                             for field_init in field_inits {
                                 if let Some(field) = fields.iter().find(|f| f.name == field_init.name) {
-                                    let value_type = self.analyze_expression(&field_init.value)?;
+                                    let value_type = self.analyze_expression_with_type(&field_init.value, Some(&field.ty))?;
                                     if !self.type_checker.types_compatible(&field.ty, &value_type) {
                                         return Err(AnalysisError::TypeMismatch {
                                             expected: format!("{:?}", field.ty),
@@ -127,118 +192,498 @@ impl SemanticAnalyzer {
     }
     
     /// パターンの解析
-    pub fn analyze_pattern(&mut self, pattern: &Pattern, expected_type: &Type) -> AnalysisResult<()> {
+    ///
+    /// パターン中の識別子を、対応する部分の型で現在のスコープに束縛する。
+    /// `span` はパターンを含むアームの位置で、orパターンの束縛の不一致の報告に使う。
+    pub fn analyze_pattern(&mut self, pattern: &Pattern, expected_type: &Type, span: Span) -> AnalysisResult<()> {
+        let mut bindings = Vec::new();
+        self.collect_pattern_bindings(pattern, expected_type, span, &mut bindings)?;
+        for symbol in bindings {
+            self.scope_stack.last_mut().unwrap().define(symbol)?;
+        }
+        Ok(())
+    }
+
+    /// パターンが束縛する変数を、対応する部分の型とともに集める
+    fn collect_pattern_bindings(
+        &self,
+        pattern: &Pattern,
+        expected_type: &Type,
+        span: Span,
+        bindings: &mut Vec<Symbol>,
+    ) -> AnalysisResult<()> {
+        // 解析に失敗した値の分解は検査せず、変数だけを束縛して後続の解析を続ける
+        if matches!(expected_type, Type::Error) {
+            if let Some(subpatterns) = destructured_subpatterns(pattern) {
+                for pattern in subpatterns {
+                    self.collect_pattern_bindings(pattern, &Type::Error, span, bindings)?;
+                }
+                return Ok(());
+            }
+        }
         match pattern {
-            Pattern::Identifier(name, _is_mut) => {
-                // パターン変数をスコープに追加
-                let symbol = Symbol {
-                    name: name.clone(),
-                    ty: expected_type.clone(),
-                    is_mutable: false,
-                    span: crate::ast::Span::dummy(), // TODO: 適切なspan
-                    borrow_info: None,
-                    is_moved: false,
-                    lifetime: None,
-                };
-                self.scope_stack.last_mut().unwrap().define(symbol)?;
+            Pattern::Identifier(name, is_mut) => {
+                // 対象の列挙型にある同名のバリアントは、変数の束縛ではなくバリアントのパターンとする
+                if !*is_mut && self.contextual_enum(expected_type, name).is_some() {
+                    return Ok(());
+                }
+
+                bindings.push(pattern_symbol(name, expected_type, *is_mut, span));
                 Ok(())
             }
-            Pattern::EnumVariant { enum_name, variant: _, fields: _ } => {
+            Pattern::Binding(name, pattern) => {
+                bindings.push(pattern_symbol(name, expected_type, false, span));
+                self.collect_pattern_bindings(pattern, expected_type, span, bindings)
+            }
+            Pattern::Or(patterns) => {
+                // どの選択肢に一致してもアームの式から同じ変数を使えるよう、
+                // すべての選択肢が同じ名前の変数を同じ型で束縛する必要がある
+                let mut alternatives = Vec::new();
+                for pattern in patterns {
+                    let mut alternative = Vec::new();
+                    self.collect_pattern_bindings(pattern, expected_type, span, &mut alternative)?;
+                    alternatives.push(alternative);
+                }
+                let names = mismatched_bindings(&alternatives);
+                if !names.is_empty() {
+                    return Err(AnalysisError::OrPatternBindingMismatch { names, span });
+                }
+                bindings.extend(alternatives.into_iter().next().unwrap_or_default());
+                Ok(())
+            }
+            Pattern::EnumVariant { enum_name, variant, fields } => {
                 // enum型が存在することを確認
-                if let Type::UserDefined(type_name) = expected_type {
-                    if type_name != enum_name {
+                let type_name = match expected_type {
+                    Type::UserDefined(type_name) => type_name,
+                    _ => {
                         return Err(AnalysisError::TypeMismatch {
                             expected: enum_name.clone(),
-                            found: type_name.clone(),
-                            span: crate::ast::Span::dummy(), // TODO: 適切なspan
+                            found: self.type_checker.type_to_string(expected_type),
+                            span,
                         });
                     }
-                    // TODO: variant とフィールドの詳細チェック
-                    Ok(())
-                } else {
-                    Err(AnalysisError::TypeMismatch {
+                };
+                // 列挙型名を省略したパターンは対象の型の列挙型とする
+                if !enum_name.is_empty() && type_name != enum_name {
+                    return Err(AnalysisError::TypeMismatch {
                         expected: enum_name.clone(),
-                        found: format!("{:?}", expected_type),
-                        span: crate::ast::Span::dummy(), // TODO: 適切なspan
-                    })
+                        found: type_name.clone(),
+                        span,
+                    });
+                }
+                let enum_name = type_name;
+                
+                let variant_fields = self.lookup_variant_fields(enum_name, variant, span)?;
+                match fields {
+                    EnumVariantPatternFields::Unit => Ok(()),
+                    EnumVariantPatternFields::Tuple(patterns) => {
+                        if patterns.len() != variant_fields.len() {
+                            return Err(AnalysisError::ArgumentCountMismatch {
+                                expected: variant_fields.len(),
+                                found: patterns.len(),
+                                span,
+                                callee: None,
+                            });
+                        }
+                        for (pattern, field) in patterns.iter().zip(variant_fields.iter()) {
+                            self.collect_pattern_bindings(pattern, &field.ty, span, bindings)?;
+                        }
+                        Ok(())
+                    }
+                    EnumVariantPatternFields::Struct(field_patterns) => {
+                        for (field_name, pattern) in field_patterns {
+                            let field = variant_fields.iter().find(|f| &f.name == field_name).ok_or_else(|| {
+                                AnalysisError::UnknownVariantPatternField {
+                                    variant: format!("{}::{}", enum_name, variant),
+                                    field: field_name.clone(),
+                                    available: variant_fields.iter().map(|f| f.name.clone()).collect(),
+                                    span,
+                                }
+                            })?;
+                            self.collect_pattern_bindings(pattern, &field.ty, span, bindings)?;
+                        }
+                        Ok(())
+                    }
                 }
             }
-            _ => {
-                // 他のパターンは後で実装
+            Pattern::Tuple(patterns) => {
+                let ty = self.destructured_type(expected_type);
+                let Type::Tuple(element_types) = &ty else {
+                    return Err(AnalysisError::TypeMismatch {
+                        expected: format!("{}個の要素のタプル", patterns.len()),
+                        found: self.type_checker.type_to_string(&ty),
+                        span,
+                    });
+                };
+                if element_types.len() != patterns.len() {
+                    return Err(AnalysisError::TuplePatternArityMismatch {
+                        ty: self.type_checker.type_to_string(&ty),
+                        expected: element_types.len(),
+                        found: patterns.len(),
+                        span,
+                    });
+                }
+                for (pattern, ty) in patterns.iter().zip(element_types.iter()) {
+                    self.collect_pattern_bindings(pattern, ty, span, bindings)?;
+                }
                 Ok(())
             }
+            Pattern::Struct(struct_name, field_patterns) => {
+                let ty = self.destructured_type(expected_type);
+                let fields = match &ty {
+                    Type::UserDefined(name) | Type::Generic(name, _) if name == struct_name => {
+                        self.type_checker.get_type_info(name).and_then(|info| match &info.kind {
+                            TypeKind::Struct(fields) => Some(fields.iter().map(|f| f.name.clone()).collect::<Vec<_>>()),
+                            _ => None,
+                        })
+                    }
+                    _ => None,
+                };
+                let Some(fields) = fields else {
+                    return Err(AnalysisError::TypeMismatch {
+                        expected: struct_name.clone(),
+                        found: self.type_checker.type_to_string(&ty),
+                        span,
+                    });
+                };
+                for (field_name, pattern) in field_patterns {
+                    if !fields.contains(field_name) {
+                        return Err(AnalysisError::UnknownPatternField {
+                            ty: struct_name.clone(),
+                            field: field_name.clone(),
+                            available: fields,
+                            span,
+                        });
+                    }
+                    let field_type = self.type_checker.get_field_type(&ty, field_name, span)?;
+                    self.collect_pattern_bindings(pattern, &field_type, span, bindings)?;
+                }
+                Ok(())
+            }
+            Pattern::Literal(_) | Pattern::Range { .. } | Pattern::Wildcard => Ok(()),
+        }
+    }
+
+    /// タプルと構造体のパターンで分解する型（エイリアスを解決し、参照を辿る）
+    fn destructured_type(&self, ty: &Type) -> Type {
+        let mut ty = self.type_checker.resolve_type_alias(ty);
+        while let Type::Reference(inner, _) = ty {
+            ty = self.type_checker.resolve_type_alias(&inner);
+        }
+        ty
+    }
+
+    /// リテラルと範囲のパターンがmatch対象の型と比較できるかを確認
+    fn check_literal_pattern_type(&self, pattern: &Pattern, expr_type: &Type, span: Span) -> AnalysisResult<()> {
+        if let Pattern::Binding(..) | Pattern::Or(_) = pattern {
+            return pattern
+                .alternatives()
+                .into_iter()
+                .try_for_each(|alternative| self.check_literal_pattern_type(alternative, expr_type, span));
+        }
+        let mut ty = self.type_checker.resolve_type_alias(expr_type);
+        while let Type::Reference(inner, _) = ty {
+            ty = *inner;
+        }
+        let ty = &ty;
+        let (expected, compatible) = match pattern {
+            Pattern::Literal(LiteralPattern::Integer(_)) | Pattern::Range { .. } => {
+                ("整数", self.type_checker.is_integer_type(ty))
+            }
+            Pattern::Literal(LiteralPattern::Float(_)) => ("浮動小数点数", self.type_checker.is_float_type(ty)),
+            Pattern::Literal(LiteralPattern::String(_)) => ("文字列", self.type_checker.is_string_type(ty)),
+            Pattern::Literal(LiteralPattern::Bool(_)) => ("bool", matches!(ty, Type::Bool)),
+            _ => return Ok(()),
+        };
+        // 型変数は具体的な型が決まっていないため、インスタンス化したときに確認する
+        if compatible || matches!(ty, Type::Variable(_) | Type::Error) {
+            return Ok(());
         }
+        Err(AnalysisError::TypeMismatch {
+            expected: self.type_checker.type_to_string(ty),
+            found: format!("{}のパターン", expected),
+            span,
+        })
+    }
+
+    /// ガードのないリテラルと範囲のアームが、前のアームと同じ値を処理していないかを確認
+    ///
+    /// orパターンは選択肢ごとに確認する。
+    fn check_duplicate_match_arms(&self, match_expr: &MatchExpr) -> AnalysisResult<()> {
+        let mut seen: Vec<(&Pattern, Span)> = Vec::new();
+        for arm in match_expr.arms.iter().filter(|arm| arm.guard.is_none()) {
+            let alternatives = arm.pattern.alternatives();
+            for &pattern in &alternatives {
+                let previous = seen.iter().find(|(previous, _)| match (previous, pattern) {
+                    (Pattern::Literal(LiteralPattern::Float(_)), _) => false,
+                    (Pattern::Literal(a), Pattern::Literal(b)) => a == b,
+                    (Pattern::Range { .. }, Pattern::Literal(LiteralPattern::Integer(value))) => {
+                        range_contains(previous, *value)
+                    }
+                    (Pattern::Range { .. }, Pattern::Range { start, end, inclusive }) => {
+                        let last = if *inclusive { *end } else { end - 1 };
+                        range_contains(previous, *start) && range_contains(previous, last)
+                    }
+                    _ => false,
+                });
+                if let Some((_, previous)) = previous {
+                    return Err(AnalysisError::DuplicateMatchArm {
+                        pattern: print_pattern(pattern),
+                        span: arm.span,
+                        previous: *previous,
+                    });
+                }
+            }
+            for pattern in alternatives {
+                if matches!(pattern, Pattern::Literal(_) | Pattern::Range { .. }) {
+                    seen.push((pattern, arm.span));
+                }
+            }
+        }
+        Ok(())
+    }
+    
+    /// 列挙型名を省略したバリアントの列挙型を期待される型から決定
+    ///
+    /// 期待される型が、`variant` という名前のフィールドを持たないバリアントを含む
+    /// 列挙型であれば、その列挙型名を返します。
+    pub(super) fn contextual_enum(&self, expected_type: &Type, variant: &str) -> Option<String> {
+        let Type::UserDefined(enum_name) = expected_type else {
+            return None;
+        };
+        match self.lookup_type(enum_name).map(|info| &info.kind) {
+            Some(TypeKind::Enum(variants))
+                if variants.iter().any(|v| v.name == variant && v.fields.is_empty()) =>
+            {
+                Some(enum_name.clone())
+            }
+            _ => None,
+        }
+    }
+    
+    /// enumのバリアントのフィールド定義を取得
+    ///
+    /// `span` はパターンを含むアームまたはlet文の位置です。
+    fn lookup_variant_fields(&self, enum_name: &str, variant: &str, span: Span) -> AnalysisResult<Vec<Field>> {
+        let variants = match self.lookup_type(enum_name).map(|info| &info.kind) {
+            Some(TypeKind::Enum(variants)) => variants,
+            _ => {
+                return Err(AnalysisError::UndefinedType {
+                    name: enum_name.to_string(),
+                    span,
+                });
+            }
+        };
+        variants
+            .iter()
+            .find(|v| v.name == variant)
+            .map(|v| v.fields.clone())
+            .ok_or_else(|| AnalysisError::UnknownPatternVariant {
+                ty: enum_name.to_string(),
+                variant: variant.to_string(),
+                available: variants.iter().map(|v| v.name.clone()).collect(),
+                span,
+            })
     }
     
     /// メソッド呼び出し式の解析
     pub fn analyze_method_call_expression(&mut self, method_call: &MethodCallExpr) -> AnalysisResult<Type> {
         // オブジェクトの型を取得
         let object_type = self.analyze_expression(&method_call.object)?;
+        if object_type == Type::Error {
+            for arg in &method_call.args {
+                self.analyze_expression(arg)?;
+            }
+            return Ok(Type::Error);
+        }
         
-        // メソッドが定義されているかチェック（借用を避けるためにクローンする）
-        let type_info = if let Some(type_info) = self.lookup_type_info(&object_type) {
-            type_info.clone()
-        } else {
-            return Err(AnalysisError::MethodNotFound {
-                method: method_call.method.clone(),
-                ty: format!("{:?}", object_type),
-                span: method_call.span,
-            });
+        if self.is_string_builder_type(&object_type) {
+            return self.analyze_string_builder_method(method_call, &object_type);
+        }
+        
+        // メソッドが定義されているかチェック（シグネチャは共有しているため参照カウントを増やすだけで済む）
+        // ユーザー定義の型情報がない型は組み込みメソッドの表を引く
+        let Some(type_info) = self.lookup_type_info(&object_type) else {
+            return self.analyze_builtin_method(method_call, &object_type);
         };
         
-        if let Some(method_sig) = type_info.methods.get(&method_call.method) {
+        if let Some(method_sig) = type_info.methods.get(&method_call.method).cloned() {
             // 引数数のチェック
             if method_call.args.len() != method_sig.params.len() {
-                return Err(AnalysisError::ArgumentCountMismatch {
-                    expected: method_sig.params.len(),
-                    found: method_call.args.len(),
-                    span: method_call.span,
-                });
+                return Err(self.argument_count_error(&method_sig, method_call.args.len(), method_call.span));
             }
             
             // 各引数の型チェック
+            let mut arg_types = Vec::with_capacity(method_call.args.len());
             for (i, arg) in method_call.args.iter().enumerate() {
-                let arg_type = self.analyze_expression(arg)?;
                 let expected_type = &method_sig.params[i].1;
-                self.type_checker.check_type_compatibility(expected_type, &arg_type, method_call.span)?;
+                let arg_type = self.analyze_value(arg, Some(expected_type), ValueUse::Argument)?;
+                arg_types.push(arg_type);
+                if self.type_checker.check_type_compatibility(expected_type, &arg_types[i], method_call.span).is_err() {
+                    return Err(self.argument_type_error(&method_sig, &method_call.args, arg_types, i));
+                }
             }
             
             Ok(method_sig.return_type.clone())
         } else {
+            let mut available: Vec<String> = type_info.methods.keys().cloned().collect();
+            available.sort();
             Err(AnalysisError::MethodNotFound {
                 method: method_call.method.clone(),
                 ty: format!("{:?}", object_type),
+                available,
                 span: method_call.span,
             })
         }
     }
+
+    /// 組み込み型（文字列・Vec・HashMap・数値型）のメソッド呼び出しの解析
+    fn analyze_builtin_method(&mut self, method_call: &MethodCallExpr, object_type: &Type) -> AnalysisResult<Type> {
+        let Some(method) = builtin_method(object_type, &method_call.method) else {
+            return Err(AnalysisError::MethodNotFound {
+                method: method_call.method.clone(),
+                ty: self.type_checker.type_to_string(receiver_value_type(object_type)),
+                available: builtin_methods(object_type).iter().map(|method| method.name.to_string()).collect(),
+                span: method_call.span,
+            });
+        };
+
+        let param_types = method.param_types(object_type);
+        if method_call.args.len() != param_types.len() {
+            return Err(AnalysisError::ArgumentCountMismatch {
+                expected: param_types.len(),
+                found: method_call.args.len(),
+                span: method_call.span,
+                callee: None,
+            });
+        }
+
+        for (arg, expected_type) in method_call.args.iter().zip(&param_types) {
+            let arg_type = match expected_type {
+                Type::Function(function_type) => self.analyze_function_argument(arg, function_type)?,
+                _ => self.analyze_value(arg, Some(expected_type), ValueUse::Argument)?,
+            };
+            let arg_span = self.get_expression_span(arg);
+            if self.type_checker.check_type_compatibility(expected_type, &arg_type, arg_span).is_err() {
+                return Err(AnalysisError::TypeMismatch {
+                    expected: self.type_checker.type_to_string(expected_type),
+                    found: self.type_checker.type_to_string(&arg_type),
+                    span: arg_span,
+                });
+            }
+        }
+
+        Ok(method.return_type(object_type))
+    }
+    
+    /// 関数を受け取る引数（`v.sort_by(f)` の `f`）の解析
+    ///
+    /// 関数を値として扱う式はまだないため、引数にはジェネリックでないトップレベルの関数の
+    /// 名前を書きます。戻り値はその関数の型で、期待される関数の型との比較は呼び出し側で行います。
+    fn analyze_function_argument(&mut self, arg: &Expression, expected: &FunctionType) -> AnalysisResult<Type> {
+        let signature = match arg {
+            Expression::Identifier(ident) if self.lookup_variable(&ident.name).is_none() => self
+                .type_checker
+                .get_function_signature(&ident.name)
+                .filter(|signature| signature.type_params.is_empty() && !signature.is_method)
+                .cloned(),
+            _ => None,
+        };
+        let Some(signature) = signature else {
+            return Err(AnalysisError::InvalidOperation {
+                message: format!(
+                    "{} を受け取る引数には関数の名前を指定してください",
+                    self.type_checker.type_to_string(&Type::Function(expected.clone()))
+                ),
+                span: self.get_expression_span(arg),
+            });
+        };
+        Ok(Type::Function(FunctionType {
+            params: signature.params.iter().map(|(_, ty)| ty.clone()).collect(),
+            return_type: Box::new(signature.return_type.clone()),
+        }))
+    }
+
+    /// 組み込みのStringBuilder（またはその参照）かどうか
+    fn is_string_builder_type(&self, ty: &Type) -> bool {
+        match ty {
+            Type::UserDefined(name) => name == "StringBuilder",
+            Type::Reference(inner, _) => self.is_string_builder_type(inner),
+            _ => false,
+        }
+    }
+
+    /// StringBuilderのメソッド呼び出しの解析
+    ///
+    /// `append`はテンプレート文字列の補間と同じく、文字列に変換できる型の値を受け取ります。
+    fn analyze_string_builder_method(&mut self, method_call: &MethodCallExpr, object_type: &Type) -> AnalysisResult<Type> {
+        let (param_count, return_type) = match method_call.method.as_str() {
+            "append" => (1, Type::Void),
+            "len" => (0, Type::I64),
+            "to_string" => (0, Type::String),
+            _ => {
+                return Err(AnalysisError::MethodNotFound {
+                    method: method_call.method.clone(),
+                    ty: self.type_checker.type_to_string(object_type),
+                    available: ["append", "len", "to_string"].map(String::from).to_vec(),
+                    span: method_call.span,
+                });
+            }
+        };
+
+        if method_call.args.len() != param_count {
+            return Err(AnalysisError::ArgumentCountMismatch {
+                expected: param_count,
+                found: method_call.args.len(),
+                span: method_call.span,
+                callee: None,
+            });
+        }
+
+        for arg in &method_call.args {
+            let arg_type = self.analyze_value(arg, None, ValueUse::Argument)?;
+            self.check_displayable(&arg_type, self.get_expression_span(arg))?;
+        }
+
+        Ok(return_type)
+    }
     
     /// 型情報を取得（型名から）
-    pub fn lookup_type_info(&self, ty: &Type) -> Option<&TypeInfo> {
+    ///
+    /// 参照型の場合は参照先の型情報を返します。
+    pub fn lookup_type_info(&self, ty: &Type) -> Option<&Rc<TypeInfo>> {
         match ty {
             Type::UserDefined(name) => self.lookup_type(name),
+            Type::Reference(inner, _) => self.lookup_type_info(inner),
             _ => None,
         }
     }
     
     /// if式の解析
-    pub fn analyze_if_expression(&mut self, if_expr: &IfExpr) -> AnalysisResult<Type> {
+    pub fn analyze_if_expression(&mut self, if_expr: &IfExpr, expected_type: Option<&Type>) -> AnalysisResult<Type> {
         // 条件式をbool型として解析
-        let condition_type = self.analyze_expression(&if_expr.condition)?;
-        if !matches!(condition_type, Type::Bool) {
-            return Err(AnalysisError::TypeMismatch {
-                expected: "bool".to_string(),
-                found: self.type_checker.type_to_string(&condition_type),
-                span: self.get_expression_span(&if_expr.condition),
-            });
-        }
+        self.analyze_condition(&if_expr.condition);
+        self.check_never_taken_branch(&if_expr.condition);
         
         // then節の解析
-        let then_type = self.analyze_expression(&if_expr.then_branch)?;
+        let then_type = self.analyze_expression_with_type(&if_expr.then_branch, expected_type)?;
         
         // else節の解析（存在する場合）
+        // 期待される型がなければ、値を生むthen節の型をelse節の期待される型にする
         if let Some(else_branch) = &if_expr.else_branch {
-            let else_type = self.analyze_expression(else_branch)?;
+            let else_expected = match expected_type {
+                Some(ty) => Some(ty),
+                None if !expression_diverges(&if_expr.then_branch) && then_type != Type::Void => Some(&then_type),
+                None => None,
+            };
+            let else_type = self.analyze_expression_with_type(else_branch, else_expected)?;
+            // returnで抜ける分岐は値を生まないため、if式はもう一方の分岐の型になる
+            if expression_diverges(&if_expr.then_branch) {
+                return Ok(else_type);
+            }
+            if expression_diverges(else_branch) {
+                return Ok(then_type);
+            }
             // 両方のブランチの型が一致するかチェック
             if !self.type_checker.types_compatible(&then_type, &else_type) {
                 return Err(AnalysisError::TypeMismatch {
@@ -262,17 +707,22 @@ impl SemanticAnalyzer {
     }
     
     /// ブロック式の解析
-    pub fn analyze_block_expression(&mut self, block_expr: &BlockExpr) -> AnalysisResult<Type> {
+    pub fn analyze_block_expression(&mut self, block_expr: &BlockExpr, expected_type: Option<&Type>) -> AnalysisResult<Type> {
         self.enter_scope();
         
-        // 文を順次解析
+        // 文を順次解析（文の誤りは記録し、後続の文の解析を続ける）
         for stmt in &block_expr.statements {
-            self.analyze_statement(stmt)?;
+            if let Err(e) = self.analyze_statement(stmt) {
+                self.errors.push(e);
+            }
         }
         
         // 最後の式の型を返す
         let result_type = if let Some(last_expr) = &block_expr.last_expr {
-            self.analyze_expression(last_expr)?
+            self.analyze_expression_with_type(last_expr, expected_type).unwrap_or_else(|e| {
+                self.errors.push(e);
+                Type::Error
+            })
         } else {
             Type::Void
         };
@@ -283,10 +733,21 @@ impl SemanticAnalyzer {
     
     /// match式の網羅性をチェック
     pub fn check_match_exhaustiveness(&self, match_expr: &MatchExpr, expr_type: &Type) -> AnalysisResult<()> {
+        // ガードのあるアームはガードが偽の場合にマッチしないため、網羅性には数えない。
+        // orパターンは選択肢のいずれかに一致するため、選択肢をそれぞれ1つのアームとして数える
+        let unguarded: Vec<&Pattern> = match_expr
+            .arms
+            .iter()
+            .filter(|arm| arm.guard.is_none())
+            .flat_map(|arm| arm.pattern.alternatives())
+            .collect();
+        
         // ワイルドカードパターンまたは識別子パターンがある場合は網羅的
-        for arm in &match_expr.arms {
-            match &arm.pattern {
+        for pattern in &unguarded {
+            match pattern {
                 Pattern::Wildcard => return Ok(()),
+                // 列挙型名を省略したバリアントは変数の束縛ではない
+                Pattern::Identifier(name, false) if self.contextual_enum(expr_type, name).is_some() => {}
                 Pattern::Identifier(_, _) => return Ok(()),
                 _ => {}
             }
@@ -299,8 +760,8 @@ impl SemanticAnalyzer {
                 let mut has_true = false;
                 let mut has_false = false;
                 
-                for arm in &match_expr.arms {
-                    if let Pattern::Literal(LiteralPattern::Bool(value)) = &arm.pattern {
+                for pattern in &unguarded {
+                    if let Pattern::Literal(LiteralPattern::Bool(value)) = pattern {
                         if *value {
                             has_true = true;
                         } else {
@@ -322,11 +783,17 @@ impl SemanticAnalyzer {
                         use std::collections::HashSet;
                         let mut covered_variants = HashSet::new();
                         
-                        for arm in &match_expr.arms {
-                            if let Pattern::EnumVariant { enum_name, variant, .. } = &arm.pattern {
-                                if enum_name == type_name {
+                        for pattern in &unguarded {
+                            match pattern {
+                                Pattern::EnumVariant { enum_name, variant, .. }
+                                    if enum_name == type_name || enum_name.is_empty() =>
+                                {
                                     covered_variants.insert(variant.clone());
                                 }
+                                Pattern::Identifier(name, false) => {
+                                    covered_variants.insert(name.clone());
+                                }
+                                _ => {}
                             }
                         }
                         
@@ -341,6 +808,8 @@ impl SemanticAnalyzer {
                     }
                 }
             }
+            // 解析に失敗した式は型が分からないため検査しない
+            Type::Error => {}
             _ => {
                 // その他の型の場合、ワイルドカードパターンか識別子パターンが必要
                 // （すでに上でチェック済み）
@@ -352,4 +821,54 @@ impl SemanticAnalyzer {
         
         Ok(())
     }
-}
\ No newline at end of file
+}
+
+/// 値を分解するパターン（タプル・構造体・バリアント）の部分パターン
+fn destructured_subpatterns(pattern: &Pattern) -> Option<Vec<&Pattern>> {
+    match pattern {
+        Pattern::Tuple(patterns) => Some(patterns.iter().collect()),
+        Pattern::Struct(_, fields) => Some(fields.iter().map(|(_, pattern)| pattern).collect()),
+        Pattern::EnumVariant { fields, .. } => match fields {
+            EnumVariantPatternFields::Unit => Some(Vec::new()),
+            EnumVariantPatternFields::Tuple(patterns) => Some(patterns.iter().collect()),
+            EnumVariantPatternFields::Struct(fields) => Some(fields.iter().map(|(_, pattern)| pattern).collect()),
+        },
+        _ => None,
+    }
+}
+
+/// パターンが束縛する変数のシンボル
+fn pattern_symbol(name: &str, ty: &Type, is_mutable: bool, span: Span) -> Symbol {
+    Symbol {
+        name: name.to_string(),
+        ty: ty.clone(),
+        is_mutable,
+        span,
+        borrow_info: None,
+        moved_at: None,
+        lifetime: None,
+    }
+}
+
+/// orパターンの選択肢の間で名前か型が一致しない束縛（最初に現れた順）
+fn mismatched_bindings(alternatives: &[Vec<Symbol>]) -> Vec<String> {
+    let mut names: Vec<String> = Vec::new();
+    for symbol in alternatives.iter().flatten() {
+        let bound_everywhere = alternatives
+            .iter()
+            .all(|bindings| bindings.iter().any(|other| other.name == symbol.name && other.ty == symbol.ty));
+        if !bound_everywhere && !names.contains(&symbol.name) {
+            names.push(symbol.name.clone());
+        }
+    }
+    names
+}
+
+/// 範囲パターンが値を含むか
+fn range_contains(pattern: &Pattern, value: i64) -> bool {
+    match pattern {
+        Pattern::Range { start, end, inclusive: true } => (*start..=*end).contains(&value),
+        Pattern::Range { start, end, inclusive: false } => (*start..*end).contains(&value),
+        _ => false,
+    }
+}
@@ -0,0 +1,34 @@
+//! コンソール出力の組み込み関数の解析
+//!
+//! `print`・`println`・`eprintln` は出力する文字列を1つ取ります。複数の引数や文字列以外の値は
+//! テンプレート文字列に脱糖して出力するため、補間と同じく文字列に変換できる型の値だけを受け付けます。
+
+use crate::ast::*;
+use crate::analyzer::symbol::{AnalysisError, AnalysisResult};
+use crate::console::OutputKind;
+use crate::error::Callee;
+use super::SemanticAnalyzer;
+use super::calls::ValueUse;
+
+impl SemanticAnalyzer {
+    /// コンソール出力の呼び出しを解析
+    pub(super) fn analyze_output_call(&mut self, kind: OutputKind, call: &CallExpr) -> AnalysisResult<Type> {
+        // 改行を出力しない `print` は、引数がないと何も出力しない
+        if call.args.is_empty() && !kind.allows_no_message() {
+            return Err(AnalysisError::ArgumentCountMismatch {
+                expected: 1,
+                found: 0,
+                span: call.span,
+                callee: Some(Box::new(Callee {
+                    signature: kind.signature(),
+                    param_spans: Vec::new(),
+                })),
+            });
+        }
+        for arg in &call.args {
+            let ty = self.analyze_value(arg, None, ValueUse::Argument)?;
+            self.check_displayable(&ty, arg.span())?;
+        }
+        Ok(Type::Void)
+    }
+}
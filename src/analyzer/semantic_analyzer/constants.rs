@@ -0,0 +1,230 @@
+//! 定数の伝播による常に偽になる条件・型の範囲により結果の変わらない比較・マップリテラルのキーの重複の検出
+
+use std::fmt;
+
+use crate::ast::*;
+use crate::analyzer::const_prop::{evaluate, ConstContext, ConstValue, Constant};
+use crate::analyzer::numeric::integer_range;
+use crate::analyzer::symbol::{AnalysisError, AnalysisResult};
+use crate::error::ConstantSource;
+use super::SemanticAnalyzer;
+
+impl ConstContext for SemanticAnalyzer {
+    fn constant(&self, name: &str) -> Option<Constant> {
+        let symbol = self.lookup_variable(name)?;
+        self.constants.lookup(name, symbol.span).cloned()
+    }
+
+    fn range_of(&self, expr: &Expression) -> Option<(i128, i128)> {
+        self.type_table.get(expr).and_then(integer_range)
+    }
+}
+
+/// パターンの変数にmatch対象の値を束縛して、ガードを評価するための文脈
+struct PatternBinding<'a> {
+    analyzer: &'a SemanticAnalyzer,
+    name: &'a str,
+    constant: &'a Constant,
+}
+
+impl ConstContext for PatternBinding<'_> {
+    fn constant(&self, name: &str) -> Option<Constant> {
+        if name == self.name {
+            Some(self.constant.clone())
+        } else {
+            self.analyzer.constant(name)
+        }
+    }
+
+    fn range_of(&self, expr: &Expression) -> Option<(i128, i128)> {
+        self.analyzer.range_of(expr)
+    }
+}
+
+/// マップリテラルのキーとして比較できる定数
+#[derive(Debug, PartialEq)]
+enum MapKey<'a> {
+    Value(ConstValue),
+    Str(&'a str),
+}
+
+impl fmt::Display for MapKey<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MapKey::Value(value) => write!(f, "{}", value),
+            MapKey::Str(value) => write!(f, "{:?}", value),
+        }
+    }
+}
+
+impl SemanticAnalyzer {
+    /// let文で宣言した変数の値を記録する（値のわからない変数も外側の同名の変数を隠す）
+    pub(super) fn record_constant(&mut self, let_stmt: &LetStatement) {
+        let Pattern::Identifier(name, is_mutable) = &let_stmt.pattern else {
+            return;
+        };
+        let constant = let_stmt.init.as_ref().and_then(|init| evaluate(init, self)).map(|constant| {
+            let source = ConstantSource { name: name.clone(), value: constant.value.to_string(), span: let_stmt.span };
+            Constant { value: constant.value, sources: vec![source] }
+        });
+        self.constants.define(name, let_stmt.span, constant, *is_mutable);
+    }
+
+    /// 代入や `&mut` の借用で変更される変数の値を追跡しないようにする
+    pub(super) fn invalidate_constant(&mut self, target: &Expression) {
+        if let Expression::Identifier(ident) = target {
+            self.constants.invalidate(&ident.name);
+        }
+    }
+
+    /// 変数の値から常に偽になるとわかる `if` の条件を警告する
+    pub(super) fn check_never_taken_branch(&mut self, condition: &Expression) {
+        if let Some(sources) = always_false(condition, self) {
+            self.warn(AnalysisError::BranchNeverTaken {
+                condition: print_expression(condition),
+                span: self.get_expression_span(condition),
+                sources,
+            });
+        }
+    }
+
+    /// match対象の値から常に偽になるとわかるガードを警告する
+    ///
+    /// パターンが束縛する変数にはmatch対象の値を入れて評価します。
+    /// パターンがmatch対象の値に一致しないアームは、ガードに関わらず実行されないため対象外です。
+    pub(super) fn check_never_taken_guard(&mut self, arm: &MatchArm, scrutinee: &Constant) {
+        let Some(guard) = &arm.guard else {
+            return;
+        };
+        let sources = match (&arm.pattern, scrutinee.value) {
+            (Pattern::Wildcard, _) => always_false(guard, self),
+            (Pattern::Identifier(name, _), _) => {
+                always_false(guard, &PatternBinding { analyzer: self, name, constant: scrutinee })
+            }
+            (Pattern::Literal(LiteralPattern::Integer(literal)), ConstValue::Int(value)) if i128::from(*literal) == value => {
+                always_false(guard, self)
+            }
+            (Pattern::Literal(LiteralPattern::Bool(literal)), ConstValue::Bool(value)) if *literal == value => {
+                always_false(guard, self)
+            }
+            (Pattern::Range { start, end, inclusive }, ConstValue::Int(value)) => {
+                let (start, end) = (i128::from(*start), i128::from(*end));
+                let contains = if *inclusive { (start..=end).contains(&value) } else { (start..end).contains(&value) };
+                if contains { always_false(guard, self) } else { None }
+            }
+            _ => None,
+        };
+        if let Some(sources) = sources {
+            self.warn(AnalysisError::MatchArmNeverTaken {
+                guard: print_expression(guard),
+                span: self.get_expression_span(guard),
+                sources,
+            });
+        }
+    }
+
+    /// 整数型のオペランドと定数の比較のうち、オペランドの型の範囲から結果が決まるものを警告する
+    ///
+    /// 符号なし整数は0以下の定数との比較（`x < 0` や `x >= 0`）と範囲外の定数との比較を、
+    /// 符号付き整数は範囲外の定数との比較だけを対象にします。
+    /// オペランドの型は比較の昇格の前の型のため、`x as i32 < 0` のようにキャストすると警告されません。
+    /// 両辺が定数の比較は `check_never_taken_branch` の対象です。
+    pub(super) fn check_constant_comparison(&mut self, binary: &BinaryExpr, left_type: &Type, right_type: &Type) {
+        let (operand_type, constant, op) = match (evaluate(&binary.left, self), evaluate(&binary.right, self)) {
+            (None, Some(constant)) => (left_type, constant, binary.op.clone()),
+            (Some(constant), None) => (right_type, constant, flip_comparison(&binary.op)),
+            _ => return,
+        };
+        let ConstValue::Int(value) = constant.value else {
+            return;
+        };
+        let operand_type = self.type_checker.resolve_type_alias(operand_type);
+        let Some((min, max)) = integer_range(&operand_type) else {
+            return;
+        };
+        if (min..=max).contains(&value) && !(min == 0 && value == 0) {
+            return;
+        }
+        let result = match op {
+            BinaryOp::Lt if value <= min => false,
+            BinaryOp::Lt if value > max => true,
+            BinaryOp::Le if value < min => false,
+            BinaryOp::Le if value >= max => true,
+            BinaryOp::Gt if value >= max => false,
+            BinaryOp::Gt if value < min => true,
+            BinaryOp::Ge if value > max => false,
+            BinaryOp::Ge if value <= min => true,
+            BinaryOp::Eq => false,
+            BinaryOp::Ne => true,
+            _ => return,
+        };
+        self.warn(AnalysisError::ComparisonAlwaysConstant {
+            comparison: print_expression(&Expression::Binary(binary.clone())),
+            result,
+            ty: self.type_checker.type_to_string(&operand_type),
+            range: format!("{}..={}", min, max),
+            span: binary.span,
+        });
+    }
+
+    /// マップリテラルで同じ定数のキーを2回以上指定していないかを確認
+    ///
+    /// 文字列リテラルと、リテラルだけから計算できる整数・boolの式（`1 + 1` など）のキーを比較します。
+    /// 変数を含むキーは、値を追跡していても対象外です。
+    pub(super) fn check_duplicate_map_keys(&self, map: &MapLiteral) -> AnalysisResult<()> {
+        let mut seen: Vec<(MapKey, Span)> = Vec::new();
+        for (key, _) in &map.pairs {
+            let constant = match key {
+                Expression::String(lit) => MapKey::Str(&lit.value),
+                key => match evaluate(key, self) {
+                    Some(Constant { value, sources }) if sources.is_empty() => MapKey::Value(value),
+                    _ => continue,
+                },
+            };
+            let span = self.get_expression_span(key);
+            if let Some((_, previous)) = seen.iter().find(|(seen_key, _)| *seen_key == constant) {
+                return Err(AnalysisError::DuplicateMapKey {
+                    key: constant.to_string(),
+                    span,
+                    previous: *previous,
+                });
+            }
+            seen.push((constant, span));
+        }
+        Ok(())
+    }
+
+    /// match対象の値（リテラルでは、リテラル自身を値の根拠とする）
+    pub(super) fn match_scrutinee_constant(&self, scrutinee: &Expression) -> Option<Constant> {
+        let mut constant = evaluate(scrutinee, self)?;
+        if constant.sources.is_empty() {
+            constant.sources.push(ConstantSource {
+                name: "match対象".to_string(),
+                value: constant.value.to_string(),
+                span: self.get_expression_span(scrutinee),
+            });
+        }
+        Some(constant)
+    }
+}
+
+/// 左右のオペランドを入れ替えた比較演算子（`c < x` は `x > c`）
+fn flip_comparison(op: &BinaryOp) -> BinaryOp {
+    match op {
+        BinaryOp::Lt => BinaryOp::Gt,
+        BinaryOp::Gt => BinaryOp::Lt,
+        BinaryOp::Le => BinaryOp::Ge,
+        BinaryOp::Ge => BinaryOp::Le,
+        op => op.clone(),
+    }
+}
+
+/// 条件が変数の値から常に偽になる場合、その根拠を返す
+///
+/// リテラルだけで偽になる条件（`if false`）は意図的なものとして警告しません。
+fn always_false(condition: &Expression, ctx: &impl ConstContext) -> Option<Vec<ConstantSource>> {
+    match evaluate(condition, ctx)? {
+        Constant { value: ConstValue::Bool(false), sources } if !sources.is_empty() => Some(sources),
+        _ => None,
+    }
+}
@@ -2,239 +2,389 @@
 
 use crate::ast::*;
 use std::collections::HashMap;
+use std::rc::Rc;
 
 use crate::analyzer::symbol::{AnalysisError, AnalysisResult, FunctionSignature, TypeInfo, TypeKind};
 use super::SemanticAnalyzer;
 
 impl SemanticAnalyzer {
-    /// 型定義を収集
-    pub fn collect_type_definition(&mut self, type_def: &TypeDef) -> AnalysisResult<()> {
-        match type_def {
-            TypeDef::Struct(struct_def) => self.collect_struct_definition(struct_def),
-            TypeDef::Enum(enum_def) => self.collect_enum_definition(enum_def),
-            TypeDef::Alias(type_alias) => self.collect_type_alias(type_alias),
-        }
+    /// 型定義の名前と中身を登録（第一段階）
+    ///
+    /// すべての型定義を先に登録しておくことで、フィールドの型や関数のシグネチャは
+    /// 宣言の順序に関係なく後で定義される型を参照できます。
+    /// 中身の検証は全ての型を登録した後に `validate_type_definition` で行います。
+    pub fn declare_type_definition(&mut self, type_def: &TypeDef) -> AnalysisResult<()> {
+        let type_info = Rc::new(match type_def {
+            TypeDef::Struct(struct_def) => TypeInfo {
+                name: struct_def.name.clone(),
+                type_params: struct_def.type_params.clone(),
+                kind: TypeKind::Struct(struct_def.fields.clone()),
+                methods: HashMap::new(),
+                span: struct_def.span,
+            },
+            TypeDef::Enum(enum_def) => TypeInfo {
+                name: enum_def.name.clone(),
+                type_params: enum_def.type_params.clone(),
+                kind: TypeKind::Enum(enum_def.variants.clone()),
+                methods: HashMap::new(),
+                span: enum_def.span,
+            },
+            TypeDef::Alias(type_alias) => TypeInfo {
+                name: type_alias.name.clone(),
+                type_params: type_alias.type_params.clone(),
+                kind: TypeKind::Alias(Box::new(type_alias.underlying_type.clone())),
+                methods: HashMap::new(),
+                span: type_alias.span,
+            },
+        });
+
+        // type_checkerとscopeの両方に登録（同じ型情報を共有する）
+        self.type_checker.register_type(Rc::clone(&type_info))?;
+        self.scope_stack.last_mut().unwrap().define_type(type_info)
     }
 
-    /// 構造体定義を収集
-    pub fn collect_struct_definition(&mut self, struct_def: &StructDef) -> AnalysisResult<()> {
-        // 型パラメータを環境に登録
-        self.type_env.enter_scope();
-        if let Err(e) = self.type_env.register_type_params(&struct_def.type_params) {
-            return match e {
-                crate::error::YuniError::Analyzer(ae) => Err(ae),
-                _ => Err(AnalysisError::InvalidOperation {
-                    message: format!("Unexpected error in type parameter registration: {:?}", e),
-                    span: struct_def.span,
-                }),
-            };
-        }
-        
-        // フィールドの型を検証
-        for field in &struct_def.fields {
-            self.type_checker.validate_type(&field.ty, field.span)?;
+    /// 型定義のフィールド・バリアント・基底型を検証（第二段階）
+    pub fn validate_type_definition(&mut self, type_def: &TypeDef) -> AnalysisResult<()> {
+        match type_def {
+            TypeDef::Struct(struct_def) => self.with_type_params(&struct_def.type_params, struct_def.span, |this| {
+                for field in &struct_def.fields {
+                    this.type_checker.validate_type(&field.ty, field.span)?;
+                }
+                Ok(())
+            }),
+            TypeDef::Enum(enum_def) => self.with_type_params(&enum_def.type_params, enum_def.span, |this| {
+                for variant in &enum_def.variants {
+                    for field in &variant.fields {
+                        this.type_checker.validate_type(&field.ty, field.span)?;
+                    }
+                }
+                Ok(())
+            }),
+            TypeDef::Alias(type_alias) => self.with_type_params(&type_alias.type_params, type_alias.span, |this| {
+                this.type_checker.validate_type(&type_alias.underlying_type, type_alias.span)
+            }),
         }
-
-        let type_info = TypeInfo {
-            name: struct_def.name.clone(),
-            type_params: struct_def.type_params.clone(),
-            kind: TypeKind::Struct(struct_def.fields.clone()),
-            methods: HashMap::new(),
-            span: struct_def.span,
-        };
-
-        // type_checkerとscopeの両方に登録
-        self.type_checker.register_type(type_info.clone())?;
-        self.scope_stack.last_mut().unwrap().define_type(type_info)?;
-        
-        // 型パラメータのスコープを終了
-        self.type_env.exit_scope();
-        Ok(())
     }
 
-    /// Enum定義を収集
-    pub fn collect_enum_definition(&mut self, enum_def: &EnumDef) -> AnalysisResult<()> {
-        // 型パラメータを環境に登録
-        self.type_env.enter_scope();
-        if let Err(e) = self.type_env.register_type_params(&enum_def.type_params) {
-            return match e {
-                crate::error::YuniError::Analyzer(ae) => Err(ae),
-                _ => Err(AnalysisError::InvalidOperation {
-                    message: format!("Unexpected error in type parameter registration: {:?}", e),
-                    span: enum_def.span,
-                }),
+    /// 自身を含む型定義を検出
+    ///
+    /// 構造体・列挙型が値として（参照やVecなどを介さずに）自身を含むと大きさが決まらず、
+    /// 型エイリアスが自身を参照すると展開が終わりません。宣言の順序に関係なく相互に参照できるため、
+    /// 循環は登録と検証の後にまとめて調べます。
+    pub fn check_recursive_types(&mut self, program: &Program) {
+        let type_defs: HashMap<&str, &TypeDef> = program
+            .items
+            .iter()
+            .filter_map(|item| match item {
+                Item::TypeDef(type_def) => Some((type_def_name(type_def), type_def)),
+                _ => None,
+            })
+            .collect();
+
+        for item in &program.items {
+            let Item::TypeDef(type_def) = item else {
+                continue;
             };
-        }
-        
-        // バリアントのフィールド型を検証
-        for variant in &enum_def.variants {
-            for field in &variant.fields {
-                self.type_checker.validate_type(&field.ty, field.span)?;
+            let name = type_def_name(type_def);
+            let mut visited = Vec::new();
+            let mut pending = contained_types(type_def);
+            while let Some(next) = pending.pop() {
+                if next == name {
+                    self.errors.push(AnalysisError::RecursiveType {
+                        name: name.to_string(),
+                        span: type_def_span(type_def),
+                    });
+                    break;
+                }
+                if visited.contains(&next) {
+                    continue;
+                }
+                visited.push(next);
+                if let Some(def) = type_defs.get(next) {
+                    pending.extend(contained_types(def));
+                }
             }
         }
+    }
 
-        let type_info = TypeInfo {
-            name: enum_def.name.clone(),
-            type_params: enum_def.type_params.clone(),
-            kind: TypeKind::Enum(enum_def.variants.clone()),
-            methods: HashMap::new(),
-            span: enum_def.span,
+    /// 型パラメータを登録した環境で検証を実行する
+    fn with_type_params<T>(
+        &mut self,
+        type_params: &[TypeParam],
+        span: Span,
+        validate: impl FnOnce(&mut Self) -> AnalysisResult<T>,
+    ) -> AnalysisResult<T> {
+        self.type_env.enter_scope();
+        let result = match self.type_env.register_type_params(type_params) {
+            Ok(()) => validate(self),
+            Err(crate::error::YuniError::Analyzer(ae)) => Err(ae),
+            Err(e) => Err(AnalysisError::InvalidOperation {
+                message: format!("Unexpected error in type parameter registration: {:?}", e),
+                span,
+            }),
         };
-
-        // type_checkerとscopeの両方に登録
-        self.type_checker.register_type(type_info.clone())?;
-        self.scope_stack.last_mut().unwrap().define_type(type_info)?;
-        
         // 型パラメータのスコープを終了
         self.type_env.exit_scope();
-        Ok(())
+        result
     }
 
-    /// 型エイリアスを収集
-    pub fn collect_type_alias(&mut self, type_alias: &TypeAlias) -> AnalysisResult<()> {
-        // 型パラメータを環境に登録
-        self.type_env.enter_scope();
-        if let Err(e) = self.type_env.register_type_params(&type_alias.type_params) {
-            return match e {
-                crate::error::YuniError::Analyzer(ae) => Err(ae),
-                _ => Err(AnalysisError::InvalidOperation {
-                    message: format!("Unexpected error in type parameter registration: {:?}", e),
-                    span: type_alias.span,
-                }),
-            };
+    /// 引数・戻り値として宣言された型（定義されていない型を含む場合は `Type::Error`）
+    pub(super) fn declared_type(&self, ty: &Type, span: Span) -> Type {
+        match self.type_checker.validate_type(ty, span) {
+            Ok(()) => ty.clone(),
+            Err(_) => Type::Error,
         }
-        
-        // 基底型を検証
-        self.type_checker.validate_type(&type_alias.underlying_type, type_alias.span)?;
-
-        let type_info = TypeInfo {
-            name: type_alias.name.clone(),
-            type_params: type_alias.type_params.clone(),
-            kind: TypeKind::Alias(Box::new(type_alias.underlying_type.clone())),
-            methods: HashMap::new(),
-            span: type_alias.span,
-        };
+    }
 
-        // type_checkerとscopeの両方に登録
-        self.type_checker.register_type(type_info.clone())?;
-        self.scope_stack.last_mut().unwrap().define_type(type_info)?;
-        
-        // 型パラメータのスコープを終了
-        self.type_env.exit_scope();
-        Ok(())
+    /// 引数・戻り値の型を検証し、誤りを記録して宣言された型を返す
+    fn checked_signature_type(&mut self, ty: &Type, span: Span) -> Type {
+        if let Err(e) = self.type_checker.validate_type(ty, span) {
+            self.errors.push(e);
+        }
+        self.declared_type(ty, span)
     }
 
     /// 関数シグネチャを収集
+    ///
+    /// 引数・戻り値の型の誤りは記録し、その型を `Type::Error` にしたシグネチャを登録します。
+    /// 呼び出し側は未定義の関数のエラーを重ねて報告せず、ほかの引数の検査を続けられます。
     pub fn collect_function_signature(&mut self, func: &FunctionDecl) -> AnalysisResult<()> {
-        // 型パラメータを環境に登録
-        self.type_env.enter_scope();
-        if let Err(e) = self.type_env.register_type_params(&func.type_params) {
-            return match e {
-                crate::error::YuniError::Analyzer(ae) => Err(ae),
-                _ => Err(AnalysisError::InvalidOperation {
-                    message: format!("Unexpected error in type parameter registration: {:?}", e),
-                    span: func.span,
-                }),
-            };
-        }
-        
-        // パラメータの型を検証
-        for param in &func.params {
-            self.type_checker.validate_type(&param.ty, param.span)?;
-        }
-        
-        // 戻り値型を検証
+        let signature = self.with_type_params(&func.type_params, func.span, |this| {
+            // パラメータの型を検証
+            let params = func.params
+                .iter()
+                .map(|p| (p.name.clone(), this.checked_signature_type(&p.ty, p.span)))
+                .collect();
+
+            // 戻り値型を検証
+            let return_type = func.return_type.as_ref()
+                .map(|t| t.as_ref().clone())
+                .unwrap_or(Type::Void);
+            let return_type = this.checked_signature_type(&return_type, func.span);
+
+            Ok(FunctionSignature {
+                name: func.name.clone(),
+                type_params: func.type_params.clone(),
+                params,
+                return_type,
+                lives_clause: func.lives_clause.clone(),
+                is_method: false,
+                receiver_type: None,
+                span: func.span,
+                param_spans: func.params.iter().map(|p| p.span).collect(),
+            })
+        })?;
+
+        // TypeCheckerに関数シグネチャを登録
+        self.type_checker.register_function(signature)
+    }
+
+    /// 外部関数のシグネチャを収集
+    ///
+    /// 引数と戻り値の型はCと値をそのまま受け渡せる型（FFI安全な型）に限ります。
+    /// 使えない型はエラーとして記録し、呼び出し側の解析を続けるためシグネチャは登録します。
+    pub fn collect_extern_function_signature(&mut self, func: &ExternFunctionDecl) -> AnalysisResult<()> {
         let return_type = func.return_type.as_ref()
             .map(|t| t.as_ref().clone())
             .unwrap_or(Type::Void);
+        for param in &func.params {
+            self.type_checker.validate_type(&param.ty, param.span)?;
+            self.check_ffi_safe(&func.name, &param.ty, param.span);
+        }
         self.type_checker.validate_type(&return_type, func.span)?;
+        if return_type != Type::Void {
+            self.check_ffi_safe(&func.name, &return_type, func.span);
+        }
 
-        let signature = FunctionSignature {
+        self.type_checker.register_function(FunctionSignature {
             name: func.name.clone(),
-            type_params: func.type_params.clone(),
+            type_params: Vec::new(),
             params: func.params.iter().map(|p| (p.name.clone(), p.ty.clone())).collect(),
             return_type,
-            lives_clause: func.lives_clause.clone(),
+            lives_clause: None,
             is_method: false,
             receiver_type: None,
             span: func.span,
-        };
+            param_spans: func.params.iter().map(|p| p.span).collect(),
+        })
+    }
 
-        // グローバルスコープに関数を登録
-        // TypeCheckerに関数シグネチャを登録
-        self.type_checker.register_function(signature)?;
-        
-        // 型パラメータのスコープを終了
-        self.type_env.exit_scope();
-        Ok(())
+    /// 外部関数の引数・戻り値の型がFFI安全でなければエラーを記録する
+    fn check_ffi_safe(&mut self, name: &str, ty: &Type, span: Span) {
+        if !is_ffi_safe(&self.type_checker.resolve_type_alias(ty)) {
+            self.errors.push(AnalysisError::NonFfiSafeType {
+                name: name.to_string(),
+                ty: self.type_checker.type_to_string(ty),
+                span,
+            });
+        }
     }
 
     /// メソッドシグネチャを収集
+    ///
+    /// 引数・戻り値の型の誤りは関数と同じく記録し、`Type::Error` にして登録します。
     pub fn collect_method_signature(&mut self, method: &MethodDecl) -> AnalysisResult<()> {
-        // 型パラメータを環境に登録
-        self.type_env.enter_scope();
-        if let Err(e) = self.type_env.register_type_params(&method.type_params) {
-            return match e {
-                crate::error::YuniError::Analyzer(ae) => Err(ae),
-                _ => Err(AnalysisError::InvalidOperation {
-                    message: format!("Unexpected error in type parameter registration: {:?}", e),
+        let (receiver_name, signature) = self.with_type_params(&method.type_params, method.span, |this| {
+            // レシーバー型が定義されているか確認
+            this.type_checker.validate_type(&method.receiver.ty, method.span)?;
+
+            // パラメータの型を検証
+            let params = method.params
+                .iter()
+                .map(|p| (p.name.clone(), this.checked_signature_type(&p.ty, p.span)))
+                .collect();
+
+            // 戻り値型を検証
+            let return_type = method.return_type.as_ref()
+                .map(|t| t.as_ref().clone())
+                .unwrap_or(Type::Void);
+            let return_type = this.checked_signature_type(&return_type, method.span);
+
+            // メソッドを対応する型に登録
+            let receiver_name = match &method.receiver.ty {
+                Type::UserDefined(name) => name.clone(),
+                Type::Reference(inner, _) => {
+                    // 参照型の場合、内部の型を確認
+                    match inner.as_ref() {
+                        Type::UserDefined(name) => name.clone(),
+                        _ => return Err(AnalysisError::TypeMismatch {
+                            expected: "user-defined type or reference to user-defined type".to_string(),
+                            found: format!("{:?}", method.receiver.ty),
+                            span: method.span,
+                        }),
+                    }
+                }
+                _ => return Err(AnalysisError::TypeMismatch {
+                    expected: "user-defined type or reference to user-defined type".to_string(),
+                    found: format!("{:?}", method.receiver.ty),
                     span: method.span,
                 }),
             };
-        }
-        
-        // レシーバー型が定義されているか確認
-        self.type_checker.validate_type(&method.receiver.ty, method.span)?;
 
-        // パラメータの型を検証
-        for param in &method.params {
-            self.type_checker.validate_type(&param.ty, param.span)?;
+            // メソッドシグネチャを作成
+            let signature = FunctionSignature {
+                name: method.name.clone(),
+                type_params: method.type_params.clone(),
+                params,
+                return_type,
+                lives_clause: method.lives_clause.clone(),
+                is_method: true,
+                receiver_type: Some(method.receiver.ty.clone()),
+                span: method.span,
+                param_spans: method.params.iter().map(|p| p.span).collect(),
+            };
+            Ok((receiver_name, signature))
+        })?;
+
+        // 型情報はスコープと共有しているため、スコープから一度取り出してから登録する
+        // （共有したままではメソッドを追加するたびに型情報が複製される）
+        let scope = self
+            .scope_stack
+            .iter_mut()
+            .rev()
+            .find_map(|scope| scope.take_type(&receiver_name).map(|_| scope));
+
+        // TypeCheckerにメソッドを登録（重複はここで検出される）
+        let result = self.type_checker.register_method(&receiver_name, Rc::new(signature));
+
+        // 登録後の型情報をスコープに戻し、メソッド呼び出しの解決に使えるようにする
+        if let (Some(scope), Some(type_info)) = (scope, self.type_checker.get_type_info(&receiver_name)) {
+            scope.define_type(Rc::clone(type_info))?;
         }
+        result
+    }
+}
 
-        // 戻り値型を検証
-        let return_type = method.return_type.as_ref()
-            .map(|t| t.as_ref().clone())
-            .unwrap_or(Type::Void);
-        self.type_checker.validate_type(&return_type, method.span)?;
-
-        // メソッドを対応する型に登録
-        let receiver_name = match &method.receiver.ty {
-            Type::UserDefined(name) => name.clone(),
-            Type::Reference(inner, _) => {
-                // 参照型の場合、内部の型を確認
-                match inner.as_ref() {
-                    Type::UserDefined(name) => name.clone(),
-                    _ => return Err(AnalysisError::TypeMismatch {
-                        expected: "user-defined type or reference to user-defined type".to_string(),
-                        found: format!("{:?}", method.receiver.ty),
-                        span: method.span,
-                    }),
-                }
+fn type_def_name(type_def: &TypeDef) -> &str {
+    match type_def {
+        TypeDef::Struct(struct_def) => &struct_def.name,
+        TypeDef::Enum(enum_def) => &enum_def.name,
+        TypeDef::Alias(type_alias) => &type_alias.name,
+    }
+}
+
+fn type_def_span(type_def: &TypeDef) -> Span {
+    match type_def {
+        TypeDef::Struct(struct_def) => struct_def.span,
+        TypeDef::Enum(enum_def) => enum_def.span,
+        TypeDef::Alias(type_alias) => type_alias.span,
+    }
+}
+
+/// 型定義がその大きさ（エイリアスでは展開）のために必要とする型の名前
+fn contained_types(type_def: &TypeDef) -> Vec<&str> {
+    let mut names = Vec::new();
+    match type_def {
+        TypeDef::Struct(struct_def) => {
+            for field in &struct_def.fields {
+                collect_by_value(&field.ty, &mut names);
             }
-            _ => return Err(AnalysisError::TypeMismatch {
-                expected: "user-defined type or reference to user-defined type".to_string(),
-                found: format!("{:?}", method.receiver.ty),
-                span: method.span,
-            }),
-        };
+        }
+        TypeDef::Enum(enum_def) => {
+            for field in enum_def.variants.iter().flat_map(|variant| &variant.fields) {
+                collect_by_value(&field.ty, &mut names);
+            }
+        }
+        TypeDef::Alias(type_alias) => collect_mentioned(&type_alias.underlying_type, &mut names),
+    }
+    names
+}
 
-        // メソッドシグネチャを作成
-        let signature = FunctionSignature {
-            name: method.name.clone(),
-            type_params: method.type_params.clone(),
-            params: method.params.iter().map(|p| (p.name.clone(), p.ty.clone())).collect(),
-            return_type,
-            lives_clause: method.lives_clause.clone(),
-            is_method: true,
-            receiver_type: Some(method.receiver.ty.clone()),
-            span: method.span,
-        };
+/// 値として含む型（参照やジェネリック型の中は間接的に持つため含めない）
+fn collect_by_value<'a>(ty: &'a Type, names: &mut Vec<&'a str>) {
+    match ty {
+        Type::UserDefined(name) => names.push(name),
+        Type::Tuple(elements) => {
+            for element in elements {
+                collect_by_value(element, names);
+            }
+        }
+        _ => {}
+    }
+}
 
-        // TypeCheckerにメソッドを登録
-        self.type_checker.register_method(&receiver_name, signature)?;
-        
-        // 型パラメータのスコープを終了
-        self.type_env.exit_scope();
-        Ok(())
+/// 型の中に現れるすべての型の名前
+fn collect_mentioned<'a>(ty: &'a Type, names: &mut Vec<&'a str>) {
+    match ty {
+        Type::UserDefined(name) => names.push(name),
+        Type::Generic(name, args) => {
+            names.push(name);
+            for arg in args {
+                collect_mentioned(arg, names);
+            }
+        }
+        Type::Reference(inner, _) | Type::Array(inner) => collect_mentioned(inner, names),
+        Type::Tuple(elements) => {
+            for element in elements {
+                collect_mentioned(element, names);
+            }
+        }
+        Type::Function(fn_type) => {
+            for param in &fn_type.params {
+                collect_mentioned(param, names);
+            }
+            collect_mentioned(&fn_type.return_type, names);
+        }
+        _ => {}
     }
-}
\ No newline at end of file
+}
+
+/// Cの関数と値をそのまま受け渡せる型か（Stringは文字列へのポインタとして渡す）
+fn is_ffi_safe(ty: &Type) -> bool {
+    matches!(
+        ty,
+        Type::I8
+            | Type::I16
+            | Type::I32
+            | Type::I64
+            | Type::U8
+            | Type::U16
+            | Type::U32
+            | Type::U64
+            | Type::F32
+            | Type::F64
+            | Type::Bool
+            | Type::Str
+            | Type::String
+    )
+}
@@ -1,9 +1,18 @@
 //! 式の解析
 
+use crate::assertion::AssertKind;
 use crate::ast::*;
+use crate::console::OutputKind;
+use crate::analyzer::builtin_methods::{membership_needle_type, receiver_value_type};
 use crate::analyzer::symbol::{AnalysisError, AnalysisResult, TypeKind};
+use crate::analyzer::numeric::{
+    associated_constant, associated_constant_names, builtin_type_by_name, float_to_integer_bounds, integer_range,
+    is_untyped_integer_literal, literal_type,
+};
 use crate::analyzer::type_inference::TypeInference;
+use crate::analyzer::type_table::is_concrete;
 use super::SemanticAnalyzer;
+use super::calls::ValueUse;
 
 impl SemanticAnalyzer {
     /// 式の解析と型推論（期待される型のコンテキストなし）
@@ -12,113 +21,236 @@ impl SemanticAnalyzer {
     }
 
     /// 式の解析と型推論（期待される型のコンテキスト付き）
+    ///
+    /// 決定した型は型テーブルに記録され、単相化とコード生成で参照されます。
     pub fn analyze_expression_with_type(&mut self, expr: &Expression, expected_type: Option<&Type>) -> AnalysisResult<Type> {
-        match expr {
-            Expression::Integer(int_lit) => self.analyze_integer_literal(int_lit, expected_type),
-            Expression::Float(float_lit) => self.analyze_float_literal(float_lit),
-            Expression::String(_) => Ok(Type::String),
+        let ty = match expr {
+            Expression::Integer(_) | Expression::Float(_) => self.analyze_numeric_literal(expr, expected_type),
+            // 文字列リテラルは静的な領域の文字列データを指すstr（確保しない）
+            // Stringの位置に置いたリテラルだけは、文字列データを複製したStringになる
+            Expression::String(_) => Ok(match expected_type.map(|ty| self.type_checker.resolve_type_alias(ty)) {
+                Some(Type::String) => Type::String,
+                _ => Type::Str,
+            }),
+            Expression::Bytes(_) => Ok(Type::Generic("Vec".to_string(), vec![Type::U8])),
             Expression::Boolean(_) => Ok(Type::Bool),
-            Expression::Identifier(ident) => self.analyze_identifier(ident),
+            Expression::Identifier(ident) => self.analyze_identifier(expr, ident, expected_type),
             Expression::Binary(binary) => self.analyze_binary_expression(binary),
             Expression::Unary(unary) => self.analyze_unary_expression(unary),
             Expression::Call(call) => self.analyze_call_expression(call),
             Expression::Field(field) => self.analyze_field_expression(field),
-            Expression::StructLit(struct_lit) => self.analyze_struct_literal(struct_lit),
-            Expression::Array(array) => self.analyze_array_expression(array),
+            Expression::StructLit(struct_lit) => self.analyze_struct_literal(struct_lit, expected_type),
+            Expression::Array(array) => self.analyze_array_expression(array, expected_type),
             Expression::Cast(cast) => self.analyze_cast_expression(cast),
-            Expression::Match(match_expr) => self.analyze_match_expression(match_expr),
+            Expression::Match(match_expr) => self.analyze_match_expression(match_expr, expected_type),
             Expression::EnumVariant(enum_variant) => self.analyze_enum_variant_expression(enum_variant),
             Expression::MethodCall(method_call) => self.analyze_method_call_expression(method_call),
-            Expression::If(if_expr) => self.analyze_if_expression(if_expr),
-            Expression::Block(block_expr) => self.analyze_block_expression(block_expr),
+            Expression::If(if_expr) => self.analyze_if_expression(if_expr, expected_type),
+            Expression::Block(block_expr) => self.analyze_block_expression(block_expr, expected_type),
             Expression::TemplateString(template) => self.analyze_template_string(template),
-            Expression::Path(path_expr) => self.analyze_path_expression(path_expr),
+            Expression::Path(path_expr) => self.analyze_path_expression(expr, path_expr, expected_type),
             Expression::Index(index_expr) => self.analyze_index_expression(index_expr),
             Expression::Reference(ref_expr) => self.analyze_reference_expression(ref_expr),
             Expression::Dereference(deref_expr) => self.analyze_dereference_expression(deref_expr),
             Expression::Assignment(assign_expr) => self.analyze_assignment_expression(assign_expr),
-            Expression::Tuple(tuple_expr) => self.analyze_tuple_expression(tuple_expr),
+            Expression::Tuple(tuple_expr) => self.analyze_tuple_expression(tuple_expr, expected_type),
             Expression::ListLiteral(list) => self.analyze_list_literal(list, expected_type),
             Expression::MapLiteral(map) => self.analyze_map_literal(map, expected_type),
-        }
-    }
+        }?;
 
-    fn analyze_integer_literal(&self, int_lit: &IntegerLit, expected_type: Option<&Type>) -> AnalysisResult<Type> {
-        if let Some(suffix) = &int_lit.suffix {
-            match suffix.as_str() {
-                "i8" => Ok(Type::I8),
-                "i16" => Ok(Type::I16),
-                "i32" => Ok(Type::I32),
-                "i64" => Ok(Type::I64),
-                "i128" => Ok(Type::I128),
-                "u8" => Ok(Type::U8),
-                "u16" => Ok(Type::U16),
-                "u32" => Ok(Type::U32),
-                "u64" => Ok(Type::U64),
-                "u128" => Ok(Type::U128),
-                _ => Ok(Type::I32), // デフォルト
-            }
+        // ジェネリック関数の本体は単相化で複製されるため、型パラメータを含む型は分けて記録する
+        if self.in_generic_body {
+            self.type_table.record_generic(expr, &ty);
         } else {
-            // 期待される型が指定されている場合はそれを使用
-            if let Some(expected) = expected_type {
-                match expected {
-                    Type::I8 => Ok(Type::I8),
-                    Type::I16 => Ok(Type::I16),
-                    Type::I32 => Ok(Type::I32),
-                    Type::I64 => Ok(Type::I64),
-                    Type::I128 => Ok(Type::I128),
-                    Type::U8 => Ok(Type::U8),
-                    Type::U16 => Ok(Type::U16),
-                    Type::U32 => Ok(Type::U32),
-                    Type::U64 => Ok(Type::U64),
-                    Type::U128 => Ok(Type::U128),
-                    _ => Ok(Type::I32), // 整数型でない場合はデフォルト
-                }
-            } else {
-                Ok(Type::I32) // サフィックスがない場合のデフォルトはi32
-            }
+            self.type_table.record(expr, &ty);
         }
+        Ok(ty)
     }
 
-    fn analyze_float_literal(&self, float_lit: &FloatLit) -> AnalysisResult<Type> {
-        if let Some(suffix) = &float_lit.suffix {
-            match suffix.as_str() {
-                "f32" => Ok(Type::F32),
-                "f64" => Ok(Type::F64),
-                _ => Ok(Type::F64), // fallback
+    /// 数値リテラルの解析（型の決め方は `literal_type` を参照）
+    fn analyze_numeric_literal(&self, expr: &Expression, expected_type: Option<&Type>) -> AnalysisResult<Type> {
+        let expected_type = expected_type.map(|ty| self.type_checker.resolve_type_alias(ty));
+        let ty = literal_type(expr, expected_type.as_ref()).ok_or_else(|| AnalysisError::Internal {
+            message: "numeric literal expected".to_string(),
+            span: expr.span(),
+        })?;
+        // 接尾辞も期待される型もないリテラルは、演算のもう一方のオペランドに合わせて型が変わるため検査しない
+        if let Expression::Integer(lit) = expr {
+            if lit.suffix.is_some() || expected_type.as_ref() == Some(&ty) {
+                check_integer_literal_range(lit, &ty, &self.type_checker.type_to_string(&ty))?;
             }
-        } else {
-            Ok(Type::F64) // default when no suffix
         }
+        Ok(ty)
     }
 
-    fn analyze_identifier(&self, ident: &Identifier) -> AnalysisResult<Type> {
+    /// 識別子の解析
+    ///
+    /// 同名の変数がなければ、列挙型名を省略したバリアントとして解決を試みます。
+    fn analyze_identifier(&mut self, expr: &Expression, ident: &Identifier, expected_type: Option<&Type>) -> AnalysisResult<Type> {
         if let Some(symbol) = self.lookup_variable(&ident.name) {
-            Ok(symbol.ty.clone())
-        } else {
-            Err(AnalysisError::UndefinedVariable {
-                name: ident.name.clone(),
-                span: ident.span,
-            })
+            return Ok(symbol.ty.clone());
         }
+        self.analyze_bare_variant(expr, &ident.name, ident.span, expected_type)
+    }
+
+    /// 列挙型名を省略したバリアント（`Color::Red` に対する `Red`）の解析
+    ///
+    /// 期待される型の列挙型にその名前のバリアントがあれば、解決結果を型テーブルに記録します。
+    /// 記録された式は単相化の前に通常のバリアント式に置き換えられます。
+    fn analyze_bare_variant(&mut self, expr: &Expression, name: &str, span: Span, expected_type: Option<&Type>) -> AnalysisResult<Type> {
+        if let Some(enum_name) = expected_type.and_then(|ty| self.contextual_enum(ty, name)) {
+            self.type_table.record_variant(expr, &enum_name, name);
+            return Ok(Type::UserDefined(enum_name));
+        }
+
+        // 文脈から列挙型が決まらない場合、候補の列挙型があれば書き方を示す
+        let candidates = self.type_checker.enums_with_unit_variant(name);
+        if candidates.is_empty() {
+            return Err(AnalysisError::UndefinedVariable {
+                name: name.to_string(),
+                span,
+            });
+        }
+        let qualified: Vec<String> = candidates
+            .iter()
+            .map(|enum_name| format!("{}::{}", enum_name, name))
+            .collect();
+        Err(AnalysisError::InvalidOperation {
+            message: format!(
+                "バリアント {} の列挙型を文脈から決定できません。{} のように列挙型名を付けてください",
+                name,
+                qualified.join("、")
+            ),
+            span,
+        })
     }
 
     /// 二項演算式の解析
     pub fn analyze_binary_expression(&mut self, binary: &BinaryExpr) -> AnalysisResult<Type> {
-        let left_type = self.analyze_expression(&binary.left)?;
-        let right_type = self.analyze_expression(&binary.right)?;
+        if binary.op == BinaryOp::In {
+            return self.analyze_membership_expression(binary);
+        }
+
+        // 接尾辞のない整数リテラルはもう一方のオペランドの型を採用する
+        let (left_type, right_type) = if is_untyped_integer_literal(&binary.left) {
+            let right_type = self.analyze_value(&binary.right, None, ValueUse::Operand)?;
+            let left_type = self.analyze_value(&binary.left, Some(&right_type), ValueUse::Operand)?;
+            (left_type, right_type)
+        } else if is_untyped_integer_literal(&binary.right) {
+            let left_type = self.analyze_value(&binary.left, None, ValueUse::Operand)?;
+            let right_type = self.analyze_value(&binary.right, Some(&left_type), ValueUse::Operand)?;
+            (left_type, right_type)
+        } else {
+            (
+                self.analyze_value(&binary.left, None, ValueUse::Operand)?,
+                self.analyze_value(&binary.right, None, ValueUse::Operand)?,
+            )
+        };
         
-        self.type_checker.binary_op_result_type(&binary.op, &left_type, &right_type, binary.span)
+        let result_type = self.type_checker.binary_op_result_type(&binary.op, &left_type, &right_type, binary.span)?;
+        self.check_constant_comparison(binary, &left_type, &right_type);
+        self.check_redundant_bool_comparison(binary, &left_type, &right_type);
+        Ok(result_type)
+    }
+
+    /// bool値と `true`・`false` のリテラルの比較を警告し、比較しない書き方を提案する
+    ///
+    /// `flag == true` と `flag != false` は `flag`、`flag == false` と `flag != true` は `!flag` になります。
+    fn check_redundant_bool_comparison(&mut self, binary: &BinaryExpr, left_type: &Type, right_type: &Type) {
+        let negate = match binary.op {
+            BinaryOp::Eq => false,
+            BinaryOp::Ne => true,
+            _ => return,
+        };
+        let (operand, operand_type, literal) = match (&*binary.left, &*binary.right) {
+            (Expression::Boolean(_), Expression::Boolean(_)) => return,
+            (operand, Expression::Boolean(lit)) => (operand, left_type, lit.value),
+            (Expression::Boolean(lit), operand) => (operand, right_type, lit.value),
+            _ => return,
+        };
+        if self.type_checker.resolve_type_alias(operand_type) != Type::Bool {
+            return;
+        }
+        let suggestion = if literal != negate {
+            print_expression(operand)
+        } else {
+            print_expression(&Expression::Unary(UnaryExpr {
+                op: UnaryOp::Not,
+                expr: Box::new(operand.clone()),
+                span: binary.span,
+            }))
+        };
+        self.warn(AnalysisError::RedundantBoolComparison {
+            comparison: print_expression(&Expression::Binary(binary.clone())),
+            literal,
+            suggestion,
+            span: binary.span,
+        });
+    }
+
+    /// `in` 演算式の解析
+    ///
+    /// 右辺のコンテナから左辺に置く値の型（Vecの要素・HashMapのキー・部分文字列）を決め、
+    /// 左辺をその型として解析します。
+    fn analyze_membership_expression(&mut self, binary: &BinaryExpr) -> AnalysisResult<Type> {
+        let container_type = self.analyze_value(&binary.right, None, ValueUse::Operand)?;
+        let Some(needle_type) = membership_needle_type(&container_type) else {
+            return Err(AnalysisError::InvalidMembershipContainer {
+                ty: self.type_checker.type_to_string(&container_type),
+                span: binary.right.span(),
+            });
+        };
+
+        let left_type = self.analyze_value(&binary.left, Some(&needle_type), ValueUse::Operand)?;
+        let left_span = binary.left.span();
+        if self.type_checker.check_type_compatibility(&needle_type, &left_type, left_span).is_err() {
+            return Err(AnalysisError::TypeMismatch {
+                expected: self.type_checker.type_to_string(&needle_type),
+                found: self.type_checker.type_to_string(&left_type),
+                span: left_span,
+            });
+        }
+        Ok(Type::Bool)
     }
 
     /// 単項演算式の解析
+    ///
+    /// `!` はboolの論理否定、`~` は整数のビット反転です。整数に `!` を使った場合は
+    /// 0との比較かビット反転への書き換えを提案します。
     pub fn analyze_unary_expression(&mut self, unary: &UnaryExpr) -> AnalysisResult<Type> {
-        let operand_type = self.analyze_expression(&unary.expr)?;
+        let operand_type = self.analyze_value(&unary.expr, None, ValueUse::Operand)?;
+
+        if unary.op == UnaryOp::Not && self.type_checker.is_integer_type(&operand_type) {
+            let operand = print_expression(&unary.expr);
+            return Err(AnalysisError::LogicalNotOnInteger {
+                ty: self.type_checker.type_to_string(&operand_type),
+                suggestion: format!(
+                    "0かどうかを判定するには `{} == 0`、ビット反転には `~{}` を使ってください",
+                    operand, operand
+                ),
+                span: unary.span,
+            });
+        }
         
         self.type_checker.unary_op_result_type(&unary.op, &operand_type, unary.span)
     }
 
-    fn analyze_path_expression(&self, path_expr: &PathExpr) -> AnalysisResult<Type> {
+    fn analyze_path_expression(&mut self, expr: &Expression, path_expr: &PathExpr, expected_type: Option<&Type>) -> AnalysisResult<Type> {
+        // 1つのセグメントの場合は識別子と同じく扱う
+        if let [name] = path_expr.segments.as_slice() {
+            if let Some(symbol) = self.lookup_variable(name) {
+                return Ok(symbol.ty.clone());
+            }
+            return self.analyze_bare_variant(expr, name, path_expr.span, expected_type);
+        }
+
+        // 組み込み型の関連定数（`i64::MAX`）
+        if let [type_name, name] = path_expr.segments.as_slice() {
+            if let Some(ty) = builtin_type_by_name(type_name) {
+                return self.analyze_associated_constant(ty, name, path_expr.span);
+            }
+        }
+
         // パス式（Enum::Variantなど）の解析
         // 2つのセグメントの場合、Enum variantとして処理
         if path_expr.segments.len() == 2 {
@@ -134,13 +266,47 @@ impl SemanticAnalyzer {
         })
     }
 
+    /// 組み込み型の関連定数を解析し、その型を返す
+    ///
+    /// 値はコンパイル時に決まる定数で、型は `i64::MAX` なら `i64` になります。
+    fn analyze_associated_constant(&mut self, ty: Type, name: &str, span: Span) -> AnalysisResult<Type> {
+        if associated_constant(&ty, name).is_some() {
+            return Ok(ty);
+        }
+        Err(AnalysisError::UnknownAssociatedConstant {
+            ty: self.type_checker.type_to_string(&ty),
+            name: name.to_string(),
+            available: associated_constant_names(&ty).iter().map(|name| name.to_string()).collect(),
+            span,
+        })
+    }
+
     fn analyze_index_expression(&mut self, index_expr: &IndexExpr) -> AnalysisResult<Type> {
         // インデックスアクセスの解析
         let object_type = self.analyze_expression(&index_expr.object)?;
+
+        // HashMapの添字はキーの型として解析し、値の型を返す（参照を通しても使える）
+        if let Type::Generic(name, type_args) = receiver_value_type(&object_type) {
+            if name == "HashMap" && type_args.len() == 2 {
+                let (key_type, value_type) = (type_args[0].clone(), type_args[1].clone());
+                let index_type = self.analyze_value(&index_expr.index, Some(&key_type), ValueUse::Argument)?;
+                let index_span = index_expr.index.span();
+                if self.type_checker.check_type_compatibility(&key_type, &index_type, index_span).is_err() {
+                    return Err(AnalysisError::TypeMismatch {
+                        expected: self.type_checker.type_to_string(&key_type),
+                        found: self.type_checker.type_to_string(&index_type),
+                        span: index_span,
+                    });
+                }
+                return Ok(value_type);
+            }
+        }
+
         let index_type = self.analyze_expression(&index_expr.index)?;
         
         // 配列型またはVec型の場合、要素型を返す
         match object_type {
+            Type::Error => Ok(Type::Error),
             Type::Array(elem_type) => {
                 // インデックスが整数型であることを確認
                 if !self.type_checker.is_integer_type(&index_type) {
@@ -165,7 +331,7 @@ impl SemanticAnalyzer {
                 Ok(type_args[0].clone())
             }
             _ => Err(AnalysisError::TypeMismatch {
-                expected: "array or Vec type".to_string(),
+                expected: "array, Vec or HashMap type".to_string(),
                 found: self.type_checker.type_to_string(&object_type),
                 span: index_expr.span,
             }),
@@ -175,6 +341,9 @@ impl SemanticAnalyzer {
     fn analyze_reference_expression(&mut self, ref_expr: &ReferenceExpr) -> AnalysisResult<Type> {
         // 参照式の解析
         let inner_type = self.analyze_expression(&ref_expr.expr)?;
+        if ref_expr.is_mut {
+            self.invalidate_constant(&ref_expr.expr);
+        }
         Ok(Type::Reference(Box::new(inner_type), ref_expr.is_mut))
     }
 
@@ -183,6 +352,7 @@ impl SemanticAnalyzer {
         let ref_type = self.analyze_expression(&deref_expr.expr)?;
         match ref_type {
             Type::Reference(inner_type, _) => Ok(*inner_type),
+            Type::Error => Ok(Type::Error),
             _ => Err(AnalysisError::TypeMismatch {
                 expected: "reference type".to_string(),
                 found: self.type_checker.type_to_string(&ref_type),
@@ -192,9 +362,10 @@ impl SemanticAnalyzer {
     }
 
     fn analyze_assignment_expression(&mut self, assign_expr: &AssignmentExpr) -> AnalysisResult<Type> {
-        // 代入式の解析
+        // 代入式の解析（左辺の型を右辺の期待される型とする）
         let target_type = self.analyze_expression(&assign_expr.target)?;
-        let value_type = self.analyze_expression(&assign_expr.value)?;
+        let value_type = self.analyze_value(&assign_expr.value, Some(&target_type), ValueUse::Assignment)?;
+        self.invalidate_constant(&assign_expr.target);
         
         // 型の互換性チェック
         self.type_checker.check_type_compatibility(&target_type, &value_type, assign_expr.span)?;
@@ -203,11 +374,16 @@ impl SemanticAnalyzer {
         Ok(Type::Void)
     }
 
-    fn analyze_tuple_expression(&mut self, tuple_expr: &TupleExpr) -> AnalysisResult<Type> {
-        // タプル式の解析
+    /// タプル式の解析（期待される型がタプルなら、要素ごとの型を各要素の期待される型にする）
+    fn analyze_tuple_expression(&mut self, tuple_expr: &TupleExpr, expected_type: Option<&Type>) -> AnalysisResult<Type> {
+        let expected_elements = match expected_type.map(|ty| self.type_checker.resolve_type_alias(ty)) {
+            Some(Type::Tuple(types)) if types.len() == tuple_expr.elements.len() => Some(types),
+            _ => None,
+        };
         let mut element_types = Vec::new();
-        for elem in &tuple_expr.elements {
-            element_types.push(self.analyze_expression(elem)?);
+        for (i, elem) in tuple_expr.elements.iter().enumerate() {
+            let expected = expected_elements.as_ref().map(|types| &types[i]);
+            element_types.push(self.analyze_expression_with_type(elem, expected)?);
         }
         Ok(Type::Tuple(element_types))
     }
@@ -215,39 +391,75 @@ impl SemanticAnalyzer {
     fn analyze_cast_expression(&mut self, cast: &CastExpr) -> AnalysisResult<Type> {
         self.analyze_expression(&cast.expr)?;
         self.type_checker.validate_type(&cast.ty, cast.span)?;
+        self.check_constant_cast(cast);
         Ok(cast.ty.clone())
     }
 
+    /// 定数のオペランドが変換先の整数型の範囲外で、常に値が変わるキャストを警告する
+    fn check_constant_cast(&mut self, cast: &CastExpr) {
+        let target = self.type_checker.resolve_type_alias(&cast.ty);
+        let (negative, operand) = match cast.expr.as_ref() {
+            Expression::Unary(unary) if matches!(unary.op, UnaryOp::Negate) => (true, unary.expr.as_ref()),
+            operand => (false, operand),
+        };
+        let (in_range, value) = match operand {
+            Expression::Integer(lit) => {
+                let Some((min, max)) = integer_range(&target) else { return };
+                let value = if negative { -lit.value } else { lit.value };
+                ((min..=max).contains(&value), lit.text())
+            }
+            Expression::Float(lit) => {
+                let Some((min, max_exclusive)) = float_to_integer_bounds(&target) else { return };
+                let value = if negative { -lit.value } else { lit.value };
+                (value.trunc() >= min && value.trunc() < max_exclusive, lit.text())
+            }
+            _ => return,
+        };
+        // 警告では書かれた表記（`0x1FF` など）のまま示す
+        let value = if negative { format!("-{}", value) } else { value };
+        if !in_range {
+            self.warn(AnalysisError::LossyConstantCast {
+                value,
+                target: self.type_checker.type_to_string(&target),
+                span: cast.span,
+            });
+        }
+    }
+
+    /// 型変数のバインディングを適用し、具体的に決まった型であれば返す
+    fn concrete_type(&self, ty: &Type) -> Option<Type> {
+        Some(self.type_env.instantiate_type(ty)).filter(is_concrete)
+    }
+
     /// 関数呼び出し式の解析
     pub fn analyze_call_expression(&mut self, call: &CallExpr) -> AnalysisResult<Type> {
         if let Expression::Identifier(ident) = call.callee.as_ref() {
-            // println関数の特別な処理（任意の数の引数と型を受け入れる）
-            if ident.name == "println" {
-                // 引数がない場合は改行のみを出力
-                // 全ての引数の型を解析するが、型チェックはしない（任意の型を受け入れる）
-                for arg in &call.args {
-                    self.analyze_expression(arg)?;
-                }
-                return Ok(Type::Void);
+            if let Some(kind) = OutputKind::from_name(&ident.name) {
+                return self.analyze_output_call(kind, call);
+            }
+
+            if let Some(kind) = AssertKind::from_name(&ident.name) {
+                return self.analyze_assert_call(kind, call);
             }
             
             if let Some(func_sig) = self.type_checker.get_function_signature(&ident.name).cloned() {
+                self.check_type_arguments(&ident.name, &func_sig.type_params, &call.type_args, call.span)?;
+
                 // 引数数のチェック
                 if call.args.len() != func_sig.params.len() {
-                    return Err(AnalysisError::ArgumentCountMismatch {
-                        expected: func_sig.params.len(),
-                        found: call.args.len(),
-                        span: call.span,
-                    });
+                    return Err(self.argument_count_error(&func_sig, call.args.len(), call.span));
                 }
                 
                 // ジェネリック関数の場合、型推論を行う
                 if !func_sig.type_params.is_empty() {
+                    // 呼び出し元の同名の型パラメータと区別するため、型パラメータを付け替える
+                    let (type_params, renaming) = self.type_env.fresh_type_params(&func_sig.type_params);
+
                     // 型パラメータのスコープを開始
                     self.type_env.enter_scope();
                     
                     // 型パラメータを環境に登録
-                    if let Err(e) = self.type_env.register_type_params(&func_sig.type_params) {
+                    if let Err(e) = self.type_env.register_type_params(&type_params) {
                         return match e {
                             crate::error::YuniError::Analyzer(ae) => Err(ae),
                             _ => Err(AnalysisError::InvalidOperation {
@@ -257,19 +469,21 @@ impl SemanticAnalyzer {
                         };
                     }
                     
-                    // 各引数の型を収集
-                    let mut arg_types = Vec::new();
-                    for arg in &call.args {
-                        arg_types.push(self.analyze_expression(arg)?);
+                    // 明示した型引数は推論より優先する
+                    for (type_param, type_arg) in type_params.iter().zip(&call.type_args) {
+                        self.type_env.bind_type(type_param.name.clone(), type_arg.clone());
                     }
                     
-                    // 型推論エンジンを作成して型パラメータを推論
-                    let mut inference = TypeInference::new(&mut self.type_env);
-                    for (i, arg_type) in arg_types.iter().enumerate() {
-                        let expected_type = &func_sig.params[i].1;
+                    // 引数を順に解析し、型パラメータを推論
+                    for (i, arg) in call.args.iter().enumerate() {
+                        let expected_type = TypeInference::new(&mut self.type_env)
+                            .apply_substitutions(&func_sig.params[i].1, &renaming);
+                        // それまでの引数で型が決まっていれば、数値リテラルなどの期待される型にする
+                        let arg_type = self.analyze_value(arg, self.concrete_type(&expected_type).as_ref(), ValueUse::Argument)?;
                         
                         // 型を統一（型変数のバインディングを設定）
-                        if let Err(e) = inference.unify(expected_type, arg_type, call.span) {
+                        let mut inference = TypeInference::new(&mut self.type_env);
+                        if let Err(e) = inference.unify(&expected_type, &arg_type, call.span) {
                             self.type_env.exit_scope(); // スコープをクリーンアップ
                             return match e {
                                 crate::error::YuniError::Analyzer(ae) => Err(ae),
@@ -282,7 +496,14 @@ impl SemanticAnalyzer {
                     }
                     
                     // 推論された型で戻り値型を具体化
-                    let instantiated_return_type = self.type_env.instantiate_type(&func_sig.return_type);
+                    let return_type = TypeInference::new(&mut self.type_env).apply_substitutions(&func_sig.return_type, &renaming);
+                    let instantiated_return_type = self.type_env.instantiate_type(&return_type);
+                    log::debug!(
+                        target: "yuni::analyzer",
+                        "inferred call to generic {} returning {}",
+                        ident.name,
+                        self.type_checker.type_to_string(&instantiated_return_type),
+                    );
                     
                     // 型パラメータのスコープを終了
                     self.type_env.exit_scope();
@@ -290,23 +511,37 @@ impl SemanticAnalyzer {
                     Ok(instantiated_return_type)
                 } else {
                     // 非ジェネリック関数の場合、従来通りの処理
+                    let mut arg_types = Vec::with_capacity(call.args.len());
                     for (i, arg) in call.args.iter().enumerate() {
-                        let arg_type = self.analyze_expression(arg)?;
                         let expected_type = &func_sig.params[i].1;
+                        let arg_type = self.analyze_value(arg, Some(expected_type), ValueUse::Argument)?;
                         
                         // 暗黙的な初期化子変換をチェック
                         if !self.type_checker.types_compatible(expected_type, &arg_type) {
                             // 型が一致しない場合、初期化子の暗黙的変換を試みる
                             if self.can_convert_initializer_to_type(arg, &arg_type, expected_type) {
                                 // 変換可能な場合は続行（実際の変換はコード生成時に行う）
+                                log::debug!(
+                                    target: "yuni::analyzer",
+                                    "converting argument {} of {} from {} to {}",
+                                    i,
+                                    ident.name,
+                                    self.type_checker.type_to_string(&arg_type),
+                                    self.type_checker.type_to_string(expected_type),
+                                );
+                                arg_types.push(arg_type);
                                 continue;
                             }
                         }
                         
-                        self.type_checker.check_type_compatibility(expected_type, &arg_type, call.span)?;
+                        if self.type_checker.check_type_compatibility(expected_type, &arg_type, call.span).is_err() {
+                            arg_types.push(arg_type);
+                            return Err(self.argument_type_error(&func_sig, &call.args, arg_types, i));
+                        }
+                        arg_types.push(arg_type);
                     }
                     
-                    Ok(func_sig.return_type)
+                    Ok(func_sig.return_type.clone())
                 }
             } else {
                 Err(AnalysisError::UndefinedFunction {
@@ -320,42 +555,75 @@ impl SemanticAnalyzer {
         }
     }
 
+    /// 明示した型引数（`make::<i64>()`・`Pair<i32, f64> { ... }`）の数と型を確認
+    ///
+    /// 型引数を省略した場合は推論するため、何も確認しません。
+    fn check_type_arguments(&self, name: &str, type_params: &[TypeParam], type_args: &[Type], span: Span) -> AnalysisResult<()> {
+        if type_args.is_empty() {
+            return Ok(());
+        }
+        if type_params.is_empty() {
+            return Err(AnalysisError::UnexpectedTypeArguments { name: name.to_string(), span });
+        }
+        if type_args.len() != type_params.len() {
+            return Err(AnalysisError::TypeArgumentCountMismatch {
+                name: name.to_string(),
+                expected: type_params.len(),
+                found: type_args.len(),
+                span,
+            });
+        }
+        for type_arg in type_args {
+            self.type_checker.validate_type(type_arg, span)?;
+        }
+        Ok(())
+    }
+
     /// フィールドアクセス式の解析
     pub fn analyze_field_expression(&mut self, field: &FieldExpr) -> AnalysisResult<Type> {
         let object_type = self.analyze_expression(&field.object)?;
+        if object_type == Type::Error {
+            return Ok(Type::Error);
+        }
         self.type_checker.get_field_type(&object_type, &field.field, field.span)
     }
 
     /// 構造体リテラル式の解析
-    pub fn analyze_struct_literal(&mut self, struct_lit: &StructLiteral) -> AnalysisResult<Type> {
-        // 型名が指定されていない場合は、文脈から推論される必要がある
-        let struct_name = match &struct_lit.name {
-            Some(name) => name.clone(),
-            None => {
-                return Err(AnalysisError::InvalidOperation {
-                    message: "Type inference for anonymous struct literals not yet implemented".to_string(),
-                    span: struct_lit.span,
-                });
-            }
+    ///
+    /// 型名のない構造体リテラル（`{ x: 1.0, y: 2.0 }`）は、期待される型の構造体として解析します。
+    pub fn analyze_struct_literal(&mut self, struct_lit: &StructLiteral, expected_type: Option<&Type>) -> AnalysisResult<Type> {
+        let (struct_name, explicit_type_args) = match &struct_lit.name {
+            Some(name) => (name.clone(), struct_lit.type_args.clone()),
+            None => self.contextual_struct(struct_lit, expected_type)?,
         };
         let struct_span = struct_lit.span;
         
+        // 型情報は共有しているため、参照カウントを増やすだけで保持できる
         if let Some(type_info) = self.type_checker.get_type_info(&struct_name).cloned() {
             let fields = match &type_info.kind {
-                TypeKind::Struct(fields) => fields.clone(),
+                TypeKind::Struct(fields) => fields,
+                // 組み込みのStringBuilderは空のリテラルで作成する
+                TypeKind::Builtin if struct_name == "StringBuilder" && struct_lit.fields.is_empty() => {
+                    return Ok(Type::UserDefined(struct_name));
+                }
                 _ => return Err(AnalysisError::InvalidOperation {
                     message: format!("Type {} is not a struct", struct_name),
                     span: struct_span,
                 }),
             };
             
+            self.check_type_arguments(&struct_name, &type_info.type_params, &explicit_type_args, struct_span)?;
+
             // ジェネリック構造体の場合、型推論を行う
             if !type_info.type_params.is_empty() {
+                // 外側の同名の型パラメータと区別するため、型パラメータを付け替える
+                let (type_params, renaming) = self.type_env.fresh_type_params(&type_info.type_params);
+
                 // 型パラメータのスコープを開始
                 self.type_env.enter_scope();
                 
                 // 型パラメータを環境に登録
-                if let Err(e) = self.type_env.register_type_params(&type_info.type_params) {
+                if let Err(e) = self.type_env.register_type_params(&type_params) {
                     return match e {
                         crate::error::YuniError::Analyzer(ae) => Err(ae),
                         _ => Err(AnalysisError::InvalidOperation {
@@ -365,26 +633,28 @@ impl SemanticAnalyzer {
                     };
                 }
                 
-                // 各フィールドの値の型を収集
-                let mut field_value_types = Vec::new();
+                // 明示した型引数は推論より優先する
+                for (type_param, type_arg) in type_params.iter().zip(&explicit_type_args) {
+                    self.type_env.bind_type(type_param.name.clone(), type_arg.clone());
+                }
+                
+                // フィールドの値を順に解析し、型パラメータを推論
                 for field_init in &struct_lit.fields {
-                    if let Some(field_def) = fields.iter().find(|f| f.name == field_init.name) {
-                        let value_type = self.analyze_expression(&field_init.value)?;
-                        field_value_types.push((field_def.ty.clone(), value_type));
-                    } else {
+                    let Some(field_def) = fields.iter().find(|f| f.name == field_init.name) else {
                         self.type_env.exit_scope(); // スコープをクリーンアップ
                         return Err(AnalysisError::UndefinedVariable {
                             name: format!("{}.{}", struct_name, field_init.name),
                             span: struct_span,
                         });
-                    }
-                }
-                
-                // 型推論エンジンを作成して型パラメータを推論
-                let mut inference = TypeInference::new(&mut self.type_env);
-                for (field_type, value_type) in &field_value_types {
+                    };
+                    let field_type = TypeInference::new(&mut self.type_env)
+                        .apply_substitutions(&field_def.ty, &renaming);
+                    // 型パラメータを含まないフィールドや、それまでのフィールドで型が決まっていれば期待される型にする
+                    let value_type = self.analyze_expression_with_type(&field_init.value, self.concrete_type(&field_type).as_ref())?;
+
                     // 型を統一（型変数のバインディングを設定）
-                    if let Err(e) = inference.unify(field_type, value_type, struct_span) {
+                    let mut inference = TypeInference::new(&mut self.type_env);
+                    if let Err(e) = inference.unify(&field_type, &value_type, struct_span) {
                         self.type_env.exit_scope(); // スコープをクリーンアップ
                         return match e {
                             crate::error::YuniError::Analyzer(ae) => Err(ae),
@@ -398,9 +668,9 @@ impl SemanticAnalyzer {
                 
                 // 推論された型パラメータを収集
                 let mut type_args = Vec::new();
-                for type_param in &type_info.type_params {
-                    if let Some(binding) = self.type_env.get_binding(&type_param.name) {
-                        type_args.push(binding.clone());
+                for (type_param, fresh) in type_info.type_params.iter().zip(&type_params) {
+                    if let Some(binding) = self.type_env.get_binding(&fresh.name) {
+                        type_args.push(self.type_env.instantiate_type(binding));
                     } else {
                         self.type_env.exit_scope(); // スコープをクリーンアップ
                         return Err(AnalysisError::TypeInferenceError {
@@ -419,7 +689,7 @@ impl SemanticAnalyzer {
                 // 非ジェネリック構造体の場合、従来通りの処理
                 for field_init in &struct_lit.fields {
                     if let Some(field_def) = fields.iter().find(|f| f.name == field_init.name) {
-                        let value_type = self.analyze_expression(&field_init.value)?;
+                        let value_type = self.analyze_expression_with_type(&field_init.value, Some(&field_def.ty))?;
                         self.type_checker.check_type_compatibility(&field_def.ty, &value_type, struct_span)?;
                     } else {
                         return Err(AnalysisError::UndefinedVariable {
@@ -439,8 +709,30 @@ impl SemanticAnalyzer {
         }
     }
 
+    /// 型名のない構造体リテラルの構造体を、期待される型から決定
+    ///
+    /// 構造体名と、ジェネリック構造体の場合は期待される型の型引数を返します。
+    fn contextual_struct(&self, struct_lit: &StructLiteral, expected_type: Option<&Type>) -> AnalysisResult<(String, Vec<Type>)> {
+        let (name, type_args) = match expected_type.map(|ty| self.type_checker.resolve_type_alias(ty)) {
+            Some(Type::UserDefined(name)) => (name, Vec::new()),
+            Some(Type::Generic(name, type_args)) => (name, type_args),
+            _ => (String::new(), Vec::new()),
+        };
+        match self.type_checker.get_type_info(&name).map(|info| &info.kind) {
+            Some(TypeKind::Struct(_)) => Ok((name, type_args)),
+            _ => Err(AnalysisError::InvalidOperation {
+                message: "構造体名のない構造体リテラルの型を文脈から決定できません。\
+                          `Point { ... }` のように構造体名を付けるか、`let p: Point = { ... }` のように型を注釈してください"
+                    .to_string(),
+                span: struct_lit.span,
+            }),
+        }
+    }
+
     /// 配列式の解析
-    pub fn analyze_array_expression(&mut self, array: &ArrayExpr) -> AnalysisResult<Type> {
+    ///
+    /// 要素の期待される型は、期待される配列型の要素型か、なければ最初の要素の型です。
+    pub fn analyze_array_expression(&mut self, array: &ArrayExpr, expected_type: Option<&Type>) -> AnalysisResult<Type> {
         if array.elements.is_empty() {
             // 空配列の場合、型を推論できない
             return Err(AnalysisError::TypeInferenceError {
@@ -449,12 +741,18 @@ impl SemanticAnalyzer {
             });
         }
         
+        let expected_element = match expected_type.map(|ty| self.type_checker.resolve_type_alias(ty)) {
+            Some(Type::Array(element)) => Some(*element),
+            _ => None,
+        };
+        
         // 最初の要素の型を基準とする
-        let first_element_type = self.analyze_expression(&array.elements[0])?;
+        let first_element_type = self.analyze_expression_with_type(&array.elements[0], expected_element.as_ref())?;
+        let expected_element = expected_element.unwrap_or_else(|| first_element_type.clone());
         
         // 残りの要素の型が一致するかチェック
         for element in array.elements.iter().skip(1) {
-            let element_type = self.analyze_expression(element)?;
+            let element_type = self.analyze_expression_with_type(element, Some(&expected_element))?;
             if !self.type_checker.types_compatible(&first_element_type, &element_type) {
                 return Err(AnalysisError::TypeMismatch {
                     expected: self.type_checker.type_to_string(&first_element_type),
@@ -469,11 +767,11 @@ impl SemanticAnalyzer {
 
     /// テンプレート文字列の解析
     fn analyze_template_string(&mut self, template: &TemplateStringLit) -> AnalysisResult<Type> {
-        // 各補間式の型を解析
+        // 各補間式の型を解析し、文字列に変換できる型か確認
         for part in &template.parts {
             if let TemplateStringPart::Interpolation(expr) = part {
-                // 補間式の型を解析（任意の型を許可）
-                self.analyze_expression(expr)?;
+                let ty = self.analyze_expression(expr)?;
+                self.check_displayable(&ty, expr.span())?;
             }
         }
         
@@ -481,6 +779,40 @@ impl SemanticAnalyzer {
         Ok(Type::String)
     }
 
+    /// 値を文字列に補間できる型か確認
+    pub(super) fn check_displayable(&self, ty: &Type, span: Span) -> AnalysisResult<()> {
+        if self.is_displayable(ty) {
+            Ok(())
+        } else {
+            Err(AnalysisError::NotDisplayable {
+                ty: self.type_checker.type_to_string(ty),
+                span,
+            })
+        }
+    }
+
+    /// 文字列に補間できる型か
+    ///
+    /// 64ビット以下の整数・f32・f64・bool・文字列と、バリアントがフィールドを持たない列挙型
+    /// （`Color::Red` のように名前を表示）です。参照は参照先の型で判定します。
+    /// 型パラメータは単相化で具体的な型に決まるため、ここでは受け付けます。
+    /// 解析に失敗した式（`Type::Error`）も、誤りを重ねて報告しないよう受け付けます。
+    fn is_displayable(&self, ty: &Type) -> bool {
+        match self.type_checker.resolve_type_alias(ty) {
+            Type::I8 | Type::I16 | Type::I32 | Type::I64 |
+            Type::U8 | Type::U16 | Type::U32 | Type::U64 |
+            Type::F32 | Type::F64 | Type::Bool | Type::Str | Type::String |
+            Type::Variable(_) | Type::Error => true,
+            Type::Reference(inner, _) => self.is_displayable(&inner),
+            Type::UserDefined(name) if self.type_env.is_type_param(&name) => true,
+            Type::UserDefined(name) => matches!(
+                self.lookup_type(&name).map(|info| &info.kind),
+                Some(TypeKind::Enum(variants)) if variants.iter().all(|variant| variant.fields.is_empty())
+            ),
+            _ => false,
+        }
+    }
+
     /// 初期化式の解析
     fn analyze_list_literal(&mut self, list: &ListLiteral, expected_type: Option<&Type>) -> AnalysisResult<Type> {
         // 型名が指定されている場合
@@ -490,7 +822,7 @@ impl SemanticAnalyzer {
                 
                 // 各要素の型をチェック
                 for elem in &list.elements {
-                    let elem_type = self.analyze_expression(elem)?;
+                    let elem_type = self.analyze_expression_with_type(elem, Some(element_type))?;
                     self.type_checker.check_type_compatibility(element_type, &elem_type, elem.span())?;
                 }
                 
@@ -510,7 +842,7 @@ impl SemanticAnalyzer {
                     let element_type = &args[0];
                     
                     for elem in &list.elements {
-                        let elem_type = self.analyze_expression(elem)?;
+                        let elem_type = self.analyze_expression_with_type(elem, Some(element_type))?;
                         self.type_checker.check_type_compatibility(element_type, &elem_type, elem.span())?;
                     }
                     
@@ -524,7 +856,7 @@ impl SemanticAnalyzer {
             let first_type = self.analyze_expression(&list.elements[0])?;
             
             for elem in &list.elements[1..] {
-                let elem_type = self.analyze_expression(elem)?;
+                let elem_type = self.analyze_expression_with_type(elem, Some(&first_type))?;
                 self.type_checker.check_type_compatibility(&first_type, &elem_type, elem.span())?;
             }
             
@@ -538,7 +870,16 @@ impl SemanticAnalyzer {
         })
     }
     
+    /// マップリテラルの解析（定数のキーの重複はエラー）
     fn analyze_map_literal(&mut self, map: &MapLiteral, expected_type: Option<&Type>) -> AnalysisResult<Type> {
+        let map_type = self.analyze_map_pairs(map, expected_type)?;
+        // 定数の式の値は解析で決まった型の範囲で計算するため、キーの型を決めてから確認する
+        self.check_duplicate_map_keys(map)?;
+        Ok(map_type)
+    }
+
+    /// マップリテラルのキーと値の型を確認し、マップの型を返す
+    fn analyze_map_pairs(&mut self, map: &MapLiteral, expected_type: Option<&Type>) -> AnalysisResult<Type> {
         // 型名が指定されている場合
         if let Some((type_name, type_args)) = &map.type_name {
             if type_name == "HashMap" && type_args.len() == 2 {
@@ -547,8 +888,8 @@ impl SemanticAnalyzer {
                 
                 // 各ペアの型をチェック
                 for (key, value) in &map.pairs {
-                    let k_type = self.analyze_expression(key)?;
-                    let v_type = self.analyze_expression(value)?;
+                    let k_type = self.analyze_expression_with_type(key, Some(key_type))?;
+                    let v_type = self.analyze_expression_with_type(value, Some(value_type))?;
                     self.type_checker.check_type_compatibility(key_type, &k_type, key.span())?;
                     self.type_checker.check_type_compatibility(value_type, &v_type, value.span())?;
                 }
@@ -570,8 +911,8 @@ impl SemanticAnalyzer {
                     let value_type = &args[1];
                     
                     for (key, value) in &map.pairs {
-                        let k_type = self.analyze_expression(key)?;
-                        let v_type = self.analyze_expression(value)?;
+                        let k_type = self.analyze_expression_with_type(key, Some(key_type))?;
+                        let v_type = self.analyze_expression_with_type(value, Some(value_type))?;
                         self.type_checker.check_type_compatibility(key_type, &k_type, key.span())?;
                         self.type_checker.check_type_compatibility(value_type, &v_type, value.span())?;
                     }
@@ -588,8 +929,8 @@ impl SemanticAnalyzer {
             let value_type = self.analyze_expression(first_value)?;
             
             for (key, value) in &map.pairs[1..] {
-                let k_type = self.analyze_expression(key)?;
-                let v_type = self.analyze_expression(value)?;
+                let k_type = self.analyze_expression_with_type(key, Some(&key_type))?;
+                let v_type = self.analyze_expression_with_type(value, Some(&value_type))?;
                 self.type_checker.check_type_compatibility(&key_type, &k_type, key.span())?;
                 self.type_checker.check_type_compatibility(&value_type, &v_type, value.span())?;
             }
@@ -780,7 +1121,7 @@ impl SemanticAnalyzer {
 //     }
     
     /// 初期化子が指定された型に暗黙的に変換可能かをチェック
-    fn can_convert_initializer_to_type(&self, expr: &Expression, expr_type: &Type, target_type: &Type) -> bool {
+    pub(super) fn can_convert_initializer_to_type(&self, expr: &Expression, expr_type: &Type, target_type: &Type) -> bool {
         match expr {
             // 匿名構造体リテラルの場合
             Expression::StructLit(struct_lit) if struct_lit.name.is_none() => {
@@ -831,4 +1172,18 @@ impl SemanticAnalyzer {
             _ => false,
         }
     }
-}
\ No newline at end of file
+}
+
+/// 整数リテラルが型の範囲内かを検査する（エラーにはソース上の表記を示す）
+fn check_integer_literal_range(lit: &IntegerLit, ty: &Type, type_name: &str) -> AnalysisResult<()> {
+    match integer_range(ty) {
+        Some((min, max)) if !(min..=max).contains(&lit.value) => Err(AnalysisError::IntegerLiteralOutOfRange {
+            literal: lit.text(),
+            ty: type_name.to_string(),
+            min: min.to_string(),
+            max: max.to_string(),
+            span: lit.span,
+        }),
+        _ => Ok(()),
+    }
+}
@@ -0,0 +1,72 @@
+//! インライン展開の指定の検査
+//!
+//! `inline(always)` の関数は呼び出し元に必ず展開されるため、自身を（他の関数を経由して）
+//! 呼び出すと展開が終わりません。本体の解析が終わった後、呼び出しグラフの直接の呼び出しから
+//! 循環を探します。メソッド呼び出しの対象は型テーブルに記録したレシーバーの型で決めます。
+
+use std::collections::HashMap;
+
+use crate::ast::*;
+use crate::analyzer::call_graph::{method_key, CallEdge, CallGraph, CallKind};
+use crate::analyzer::symbol::AnalysisError;
+use super::SemanticAnalyzer;
+
+impl SemanticAnalyzer {
+    /// 再帰する関数・メソッドに `inline(always)` が指定されていないかを検査
+    pub fn check_recursive_inline(&mut self, program: &Program) {
+        let always: Vec<String> = program.items.iter()
+            .filter_map(|item| match item {
+                Item::Function(func) if func.inline == InlineHint::Always => Some(func.name.clone()),
+                Item::Method(method) if method.inline == InlineHint::Always => {
+                    method_key(&method.receiver.ty, &method.name)
+                }
+                _ => None,
+            })
+            .collect();
+
+        for name in always {
+            for edge in direct_calls(&self.call_graph, &name) {
+                if let Some(mut cycle) = call_path(&self.call_graph, &edge.callee, &name) {
+                    cycle.insert(0, name.clone());
+                    self.errors.push(AnalysisError::RecursiveInlineAlways {
+                        name: name.clone(),
+                        cycle,
+                        span: edge.span,
+                    });
+                    break;
+                }
+            }
+        }
+    }
+}
+
+/// 関数・メソッドの本体にある直接の呼び出し（関数ポインタを通した呼び出しは展開されない）
+fn direct_calls<'a>(graph: &'a CallGraph, caller: &'a str) -> impl Iterator<Item = &'a CallEdge> + 'a {
+    graph.calls_from(caller).filter(|edge| edge.kind == CallKind::Direct)
+}
+
+/// `from` から `to` までの呼び出しの経路（両端を含む。たどり着けなければNone）
+fn call_path(graph: &CallGraph, from: &str, to: &str) -> Option<Vec<String>> {
+    let mut previous: HashMap<&str, &str> = HashMap::new();
+    let mut pending = vec![from];
+    while let Some(current) = pending.pop() {
+        if current == to {
+            let mut path = vec![current.to_string()];
+            let mut node = current;
+            while let Some(&prev) = previous.get(node) {
+                path.push(prev.to_string());
+                node = prev;
+            }
+            path.reverse();
+            return Some(path);
+        }
+        for edge in direct_calls(graph, current) {
+            let callee = edge.callee.as_str();
+            if callee != from && !previous.contains_key(callee) {
+                previous.insert(callee, current);
+                pending.push(callee);
+            }
+        }
+    }
+    None
+}
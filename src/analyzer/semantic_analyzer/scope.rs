@@ -1,6 +1,7 @@
 //! スコープ管理とユーティリティ関数
 
 use crate::ast::*;
+use std::rc::Rc;
 use crate::analyzer::symbol::{Scope, Symbol, TypeInfo};
 use super::SemanticAnalyzer;
 
@@ -16,7 +17,7 @@ impl SemanticAnalyzer {
     }
     
     /// 型の検索
-    pub fn lookup_type(&self, name: &str) -> Option<&TypeInfo> {
+    pub fn lookup_type(&self, name: &str) -> Option<&Rc<TypeInfo>> {
         for scope in self.scope_stack.iter().rev() {
             if let Some(type_info) = scope.lookup_type(name) {
                 return Some(type_info);
@@ -28,6 +29,7 @@ impl SemanticAnalyzer {
     pub fn enter_scope(&mut self) {
         let new_scope = Scope::new();
         self.scope_stack.push(new_scope);
+        self.constants.enter_scope(self.scope_stack.len());
         self.lifetime_context.enter_scope();
     }
     
@@ -35,6 +37,7 @@ impl SemanticAnalyzer {
         if self.scope_stack.len() > 1 {
             self.scope_stack.pop();
         }
+        self.constants.exit_scope(self.scope_stack.len());
         self.lifetime_context.exit_scope();
     }
     
@@ -48,6 +51,8 @@ impl SemanticAnalyzer {
             Statement::For(s) => s.span,
             Statement::Expression(e) => self.get_expression_span(e),
             Statement::Block(b) => b.span,
+            Statement::Defer(s) => s.span,
+            Statement::Function(f) => f.span,
         }
     }
     
@@ -56,6 +61,7 @@ impl SemanticAnalyzer {
             Expression::Integer(i) => i.span,
             Expression::Float(f) => f.span,
             Expression::String(s) => s.span,
+            Expression::Bytes(b) => b.span,
             Expression::TemplateString(t) => t.span,
             Expression::Boolean(b) => b.span,
             Expression::Identifier(i) => i.span,
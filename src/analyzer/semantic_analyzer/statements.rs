@@ -1,8 +1,11 @@
 //! 文の解析
 
 use crate::ast::*;
+use crate::analyzer::divergence::expression_diverges;
+use crate::analyzer::loop_progress;
 use crate::analyzer::symbol::{AnalysisError, AnalysisResult, Symbol};
 use super::SemanticAnalyzer;
+use super::calls::ValueUse;
 
 impl SemanticAnalyzer {
     /// ブロックを解析
@@ -37,7 +40,8 @@ impl SemanticAnalyzer {
             Statement::For(for_stmt) => self.analyze_for_statement(for_stmt),
             Statement::Expression(expr) => {
                 self.analyze_expression(expr)?;
-                Ok(false)
+                // すべての分岐でreturnするif式・match式の後の文には到達しない
+                Ok(expression_diverges(expr))
             }
             Statement::Block(block) => {
                 self.enter_scope();
@@ -45,23 +49,46 @@ impl SemanticAnalyzer {
                 self.exit_scope();
                 Ok(returns)
             }
+            Statement::Defer(defer_stmt) => self.analyze_defer_statement(defer_stmt),
+            // ローカル関数は `analyze` がトップレベルの関数に取り出してから解析する
+            Statement::Function(_) => Ok(false),
         }
     }
 
     /// let文の解析
+    ///
+    /// 初期化式や型注釈の誤りは記録し、変数は型注釈の型（なければ `Type::Error`）で束縛します。
+    /// 後続の文がその変数を使っても、未定義の変数や型の不一致のエラーを重ねて報告しません。
     pub fn analyze_let_statement(&mut self, let_stmt: &LetStatement) -> AnalysisResult<bool> {
+        let inferred_type = match self.let_statement_type(let_stmt) {
+            Ok(ty) => ty,
+            Err(e) => {
+                self.errors.push(e);
+                let annotated_type = let_stmt.ty.as_ref()
+                    .filter(|ty| self.type_checker.validate_type(ty, let_stmt.span).is_ok());
+                let ty = annotated_type.cloned().unwrap_or(Type::Error);
+                self.bind_let_pattern(let_stmt, ty)?;
+                return Ok(false);
+            }
+        };
+        self.bind_let_pattern(let_stmt, inferred_type)?;
+        self.record_constant(let_stmt);
+        
+        Ok(false)
+    }
+
+    /// let文で束縛する変数の型を決める
+    fn let_statement_type(&mut self, let_stmt: &LetStatement) -> AnalysisResult<Type> {
         // 初期化式がある場合は型チェック
         let inferred_type = if let Some(ref init_expr) = let_stmt.init {
             // 型注釈がある場合はそれを期待される型として使用
-            
-            
             if let Some(ref annotated_type) = let_stmt.ty {
                 self.type_checker.validate_type(annotated_type, let_stmt.span)?;
-                let expr_type = self.analyze_expression_with_type(init_expr, Some(annotated_type))?;
+                let expr_type = self.analyze_value(init_expr, Some(annotated_type), ValueUse::Let)?;
                 self.type_checker.check_type_compatibility(annotated_type, &expr_type, let_stmt.span)?;
                 annotated_type.clone()
             } else {
-                self.analyze_expression(init_expr)?
+                self.analyze_value(init_expr, None, ValueUse::Let)?
             }
         } else if let Some(ref annotated_type) = let_stmt.ty {
             self.type_checker.validate_type(annotated_type, let_stmt.span)?;
@@ -72,23 +99,37 @@ impl SemanticAnalyzer {
                 span: let_stmt.span,
             });
         };
+        Ok(inferred_type)
+    }
 
-        // パターンの解析（今は簡単な識別子のみ対応）
-        if let Pattern::Identifier(name, is_mutable) = &let_stmt.pattern {
-            let symbol = Symbol {
-                name: name.clone(),
-                ty: inferred_type,
-                is_mutable: *is_mutable,
-                span: let_stmt.span,
-                borrow_info: None,
-                is_moved: false,
-                lifetime: None,
-            };
-            
-            self.scope_stack.last_mut().unwrap().define(symbol)?;
+    /// let文のパターンの変数を束縛する
+    fn bind_let_pattern(&mut self, let_stmt: &LetStatement, inferred_type: Type) -> AnalysisResult<()> {
+        // パターンの解析（タプルと構造体のパターンは要素とフィールドごとに変数を束縛する）
+        match &let_stmt.pattern {
+            Pattern::Identifier(name, is_mutable) => {
+                let symbol = Symbol {
+                    name: name.clone(),
+                    ty: inferred_type,
+                    is_mutable: *is_mutable,
+                    span: let_stmt.span,
+                    borrow_info: None,
+                    moved_at: None,
+                    lifetime: None,
+                };
+
+                self.scope_stack.last_mut().unwrap().define(symbol)?;
+            }
+            pattern => {
+                if let Some(refutable) = refutable_let_pattern(pattern) {
+                    return Err(AnalysisError::RefutableLetPattern {
+                        pattern: print_pattern(refutable),
+                        span: let_stmt.span,
+                    });
+                }
+                self.analyze_pattern(pattern, &inferred_type, let_stmt.span)?;
+            }
         }
-        
-        Ok(false)
+        Ok(())
     }
 
     /// 代入文の解析
@@ -96,12 +137,14 @@ impl SemanticAnalyzer {
         // 左辺の解析
         let target_type = self.analyze_expression(&assign.target)?;
         
-        // 右辺の解析
-        let value_type = self.analyze_expression(&assign.value)?;
+        // 右辺の解析（左辺の型を期待される型とする）
+        let value_type = self.analyze_value(&assign.value, Some(&target_type), ValueUse::Assignment)?;
         
         // 型の互換性チェック
         self.type_checker.check_type_compatibility(&target_type, &value_type, assign.span)?;
         
+        self.invalidate_constant(&assign.target);
+
         // 変更可能性のチェック
         if let Expression::Identifier(ident) = &assign.target {
             if let Some(symbol) = self.lookup_variable(&ident.name) {
@@ -109,6 +152,7 @@ impl SemanticAnalyzer {
                     return Err(AnalysisError::ImmutableVariable {
                         name: ident.name.clone(),
                         span: assign.span,
+                        declared_at: symbol.span,
                     });
                 }
             }
@@ -119,10 +163,17 @@ impl SemanticAnalyzer {
 
     /// return文の解析
     pub fn analyze_return_statement(&mut self, ret: &ReturnStatement) -> AnalysisResult<bool> {
+        if self.defer_depth > 0 {
+            return Err(AnalysisError::ReturnInDefer { span: ret.span });
+        }
+
         let expected_type = self.current_return_type.clone();
         let return_type = if let Some(ref expr) = ret.value {
             // 現在の関数の戻り値型を期待される型として渡す
-            self.analyze_expression_with_type(expr, expected_type.as_ref())?
+            match expected_type.as_ref() {
+                None | Some(Type::Void) => self.analyze_expression_with_type(expr, expected_type.as_ref())?,
+                Some(expected_type) => self.analyze_value(expr, Some(expected_type), ValueUse::Return)?,
+            }
         } else {
             Type::Void
         };
@@ -138,14 +189,8 @@ impl SemanticAnalyzer {
     /// if文の解析
     pub fn analyze_if_statement(&mut self, if_stmt: &IfStatement) -> AnalysisResult<bool> {
         // 条件式の型チェック
-        let condition_type = self.analyze_expression(&if_stmt.condition)?;
-        if !matches!(condition_type, Type::Bool) {
-            return Err(AnalysisError::TypeMismatch {
-                expected: "bool".to_string(),
-                found: self.type_checker.type_to_string(&condition_type),
-                span: self.get_expression_span(&if_stmt.condition),
-            });
-        }
+        self.analyze_condition(&if_stmt.condition);
+        self.check_never_taken_branch(&if_stmt.condition);
         
         // then節の解析
         let then_returns = self.analyze_block(&if_stmt.then_branch)?;
@@ -166,56 +211,144 @@ impl SemanticAnalyzer {
 
     /// while文の解析
     pub fn analyze_while_statement(&mut self, while_stmt: &WhileStatement) -> AnalysisResult<bool> {
+        // 条件と本体は反復ごとに変わる変数の値を読むため、変数の値を追跡しない
+        self.constants.invalidate_mutable();
+
         // 条件式の型チェック
-        let condition_type = self.analyze_expression(&while_stmt.condition)?;
-        if !matches!(condition_type, Type::Bool) {
-            return Err(AnalysisError::TypeMismatch {
-                expected: "bool".to_string(),
-                found: self.type_checker.type_to_string(&condition_type),
-                span: self.get_expression_span(&while_stmt.condition),
-            });
-        }
+        self.analyze_condition(&while_stmt.condition);
         
-        // ループ本体の解析
-        self.analyze_block(&while_stmt.body)?;
+        // ループ本体の解析（本体の変数は反復ごとのスコープに属する）
+        self.enter_scope();
+        let result = self.analyze_block(&while_stmt.body);
+        self.exit_scope();
+        result?;
+
+        if matches!(&while_stmt.condition, Expression::Boolean(lit) if lit.value) {
+            if loop_progress::never_exits(&while_stmt.body) {
+                self.warn(AnalysisError::InfiniteLoop {
+                    span: self.get_expression_span(&while_stmt.condition),
+                });
+            }
+        } else {
+            self.check_loop_progress(&while_stmt.condition, &while_stmt.body, None);
+        }
         
         // while文は必ずしもreturnしない（条件がfalseの場合実行されない可能性）
         Ok(false)
     }
 
     /// for文の解析
+    ///
+    /// init文の変数はループ全体のスコープに、本体の変数は反復ごとのスコープに属し、
+    /// どちらもループの後では見えません。update式からは本体の変数は見えません。
     pub fn analyze_for_statement(&mut self, for_stmt: &ForStatement) -> AnalysisResult<bool> {
         // 新しいスコープを作成（ループ変数用）
         self.enter_scope();
+        let result = self.analyze_for_parts(for_stmt);
+        self.exit_scope();
+        result?;
         
+        // for文は必ずしもreturnしない
+        Ok(false)
+    }
+
+    /// for文のスコープ内の解析
+    fn analyze_for_parts(&mut self, for_stmt: &ForStatement) -> AnalysisResult<()> {
         // init文の解析（存在する場合）
         if let Some(ref init) = for_stmt.init {
-            self.analyze_statement(init)?;
+            if let Err(e) = self.analyze_statement(init) {
+                self.errors.push(e);
+            }
         }
+        // 条件・本体・update式は反復ごとに変わる変数の値を読むため、変数の値を追跡しない
+        self.constants.invalidate_mutable();
         
         // 条件式の解析（存在する場合）
         if let Some(ref condition) = for_stmt.condition {
-            let condition_type = self.analyze_expression(condition)?;
-            if !matches!(condition_type, Type::Bool) {
-                return Err(AnalysisError::TypeMismatch {
-                    expected: "bool".to_string(),
-                    found: self.type_checker.type_to_string(&condition_type),
-                    span: self.get_expression_span(condition),
-                });
-            }
+            self.analyze_condition(condition);
         }
         
         // ループ本体の解析
-        self.analyze_block(&for_stmt.body)?;
+        self.enter_scope();
+        let result = self.analyze_block(&for_stmt.body);
+        self.exit_scope();
+        result?;
         
         // update式の解析（存在する場合）
         if let Some(ref update) = for_stmt.update {
             self.analyze_expression(update)?;
         }
+
+        // init文の変数が見えるうちに進行を検査する
+        match &for_stmt.condition {
+            Some(condition) => self.check_loop_progress(condition, &for_stmt.body, for_stmt.update.as_ref()),
+            None => {
+                if loop_progress::never_exits(&for_stmt.body) {
+                    self.warn(AnalysisError::InfiniteLoop { span: for_stmt.span });
+                }
+            }
+        }
         
+        Ok(())
+    }
+
+    /// if・while・forの条件を解析する
+    ///
+    /// 条件の誤りは記録するだけにして、分岐やループの本体の解析を続けます。
+    pub(super) fn analyze_condition(&mut self, condition: &Expression) {
+        let result = self.analyze_value(condition, None, ValueUse::Condition).and_then(|condition_type| {
+            if matches!(condition_type, Type::Bool | Type::Error) {
+                Ok(())
+            } else {
+                Err(AnalysisError::TypeMismatch {
+                    expected: "bool".to_string(),
+                    found: self.type_checker.type_to_string(&condition_type),
+                    span: self.get_expression_span(condition),
+                })
+            }
+        });
+        if let Err(e) = result {
+            self.errors.push(e);
+        }
+    }
+
+    /// 条件の変数を本体で変更しないループを警告する
+    fn check_loop_progress(&mut self, condition: &Expression, body: &Block, update: Option<&Expression>) {
+        let unmodified = loop_progress::unmodified_condition_variables(condition, body, update, |name| {
+            self.lookup_variable(name).is_some_and(|symbol| matches!(symbol.ty, Type::Reference(..)))
+        });
+        if let Some(variables) = unmodified {
+            self.warn(AnalysisError::LoopConditionNotModified {
+                variables,
+                span: self.get_expression_span(condition),
+            });
+        }
+    }
+
+    /// defer文の解析
+    ///
+    /// 本体は外側の変数を参照できますが、囲んでいる関数から抜けることはできません。
+    pub fn analyze_defer_statement(&mut self, defer_stmt: &DeferStatement) -> AnalysisResult<bool> {
+        // 本体はブロックの終了時に実行されるため、それまでに変わり得る変数の値を追跡しない
+        self.constants.invalidate_mutable();
+        self.defer_depth += 1;
+        self.enter_scope();
+        let result = self.analyze_block(&defer_stmt.body);
         self.exit_scope();
-        
-        // for文は必ずしもreturnしない
+        self.defer_depth -= 1;
+        result?;
+
+        // deferは囲んでいるブロックの終了時に実行されるため、returnとは扱わない
         Ok(false)
     }
-}
\ No newline at end of file
+}
+
+/// let文で変数に分解できない（値によっては一致しない）部分パターン
+fn refutable_let_pattern(pattern: &Pattern) -> Option<&Pattern> {
+    match pattern {
+        Pattern::Identifier(..) | Pattern::Wildcard => None,
+        Pattern::Tuple(patterns) => patterns.iter().find_map(refutable_let_pattern),
+        Pattern::Struct(_, fields) => fields.iter().find_map(|(_, pattern)| refutable_let_pattern(pattern)),
+        _ => Some(pattern),
+    }
+}
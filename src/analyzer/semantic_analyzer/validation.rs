@@ -3,7 +3,8 @@
 use crate::ast::*;
 use crate::analyzer::symbol::{AnalysisError, AnalysisResult, Symbol};
 use super::SemanticAnalyzer;
-use crate::analyzer::borrow_checker::BorrowChecker;
+use super::borrow_sources::BorrowSourcesInput;
+use crate::analyzer::borrow_checker::{BorrowCheckMode, BorrowChecker};
 
 impl SemanticAnalyzer {
     /// インポートを処理
@@ -14,6 +15,8 @@ impl SemanticAnalyzer {
 
     /// 関数の解析
     pub fn analyze_function(&mut self, func: &FunctionDecl) -> AnalysisResult<()> {
+        log::trace!(target: "yuni::analyzer", "analyzing function {}", func.name);
+
         // 新しいスコープを作成
         self.enter_scope();
         
@@ -61,13 +64,18 @@ impl SemanticAnalyzer {
 
         // パラメータをスコープに追加
         for param in &func.params {
+            // `_` は引数を受け取るだけで束縛しないため、何度でも書ける
+            if param.name == "_" {
+                continue;
+            }
+            // 型の誤りはシグネチャの収集で報告済みのため、ここでは `Type::Error` として続ける
             let symbol = Symbol {
                 name: param.name.clone(),
-                ty: param.ty.clone(),
-                is_mutable: false,
+                ty: self.declared_type(&param.ty, param.span),
+                is_mutable: param.is_mut,
                 span: param.span,
                 borrow_info: None,
-                is_moved: false,
+                moved_at: None,
                 lifetime: None,
             };
             self.scope_stack.last_mut().unwrap().define(symbol)?;
@@ -77,6 +85,7 @@ impl SemanticAnalyzer {
         let return_type = func.return_type.as_ref()
             .map(|t| t.as_ref().clone())
             .unwrap_or(Type::Void);
+        let return_type = self.declared_type(&return_type, func.span);
         self.current_return_type = Some(return_type.clone());
 
         // 関数本体を解析
@@ -91,19 +100,17 @@ impl SemanticAnalyzer {
         }
 
         // 借用チェック
-        {
-            let current_scope = self.scope_stack.last_mut().unwrap();
-            let mut borrow_checker = BorrowChecker::new(&mut self.lifetime_context, current_scope);
-            for stmt in &func.body.statements {
-                if let Err(e) = borrow_checker.check_statement(stmt) {
-                    self.errors.push(e);
-                }
-            }
-            // 借用チェックの最終検証
-            if let Err(e) = borrow_checker.check() {
-                self.errors.push(e);
-            }
-        }
+        self.check_function_borrows(&func.body, &func.attributes);
+
+        // 参照の借用元の検査
+        self.check_borrow_sources(BorrowSourcesInput {
+            name: &func.name,
+            params: func.params.iter().map(|p| (p.name.clone(), p.ty.clone(), p.span)).collect(),
+            return_type: &return_type,
+            lives_clause: func.lives_clause.as_ref(),
+            body: &func.body,
+            signature_span: Span::new(func.span.start, func.body.span.start),
+        });
 
         self.current_return_type = None;
         self.exit_scope();
@@ -116,6 +123,8 @@ impl SemanticAnalyzer {
 
     /// メソッドの解析
     pub fn analyze_method(&mut self, method: &MethodDecl) -> AnalysisResult<()> {
+        log::trace!(target: "yuni::analyzer", "analyzing method {}", method.name);
+
         // 新しいスコープを作成
         self.enter_scope();
         
@@ -168,20 +177,25 @@ impl SemanticAnalyzer {
             is_mutable: method.receiver.is_mut,
             span: method.span,
             borrow_info: None,
-            is_moved: false,
+            moved_at: None,
             lifetime: None,
         };
         self.scope_stack.last_mut().unwrap().define(self_symbol)?;
 
         // その他のパラメータをスコープに追加
         for param in &method.params {
+            // `_` は引数を受け取るだけで束縛しないため、何度でも書ける
+            if param.name == "_" {
+                continue;
+            }
+            // 型の誤りはシグネチャの収集で報告済みのため、ここでは `Type::Error` として続ける
             let symbol = Symbol {
                 name: param.name.clone(),
-                ty: param.ty.clone(),
-                is_mutable: false,
+                ty: self.declared_type(&param.ty, param.span),
+                is_mutable: param.is_mut,
                 span: param.span,
                 borrow_info: None,
-                is_moved: false,
+                moved_at: None,
                 lifetime: None,
             };
             self.scope_stack.last_mut().unwrap().define(symbol)?;
@@ -191,6 +205,7 @@ impl SemanticAnalyzer {
         let return_type = method.return_type.as_ref()
             .map(|t| t.as_ref().clone())
             .unwrap_or(Type::Void);
+        let return_type = self.declared_type(&return_type, method.span);
         self.current_return_type = Some(return_type.clone());
 
         // メソッド本体を解析
@@ -205,19 +220,24 @@ impl SemanticAnalyzer {
         }
 
         // 借用チェック
-        {
-            let current_scope = self.scope_stack.last_mut().unwrap();
-            let mut borrow_checker = BorrowChecker::new(&mut self.lifetime_context, current_scope);
-            for stmt in &method.body.statements {
-                if let Err(e) = borrow_checker.check_statement(stmt) {
-                    self.errors.push(e);
-                }
-            }
-            // 借用チェックの最終検証
-            if let Err(e) = borrow_checker.check() {
-                self.errors.push(e);
-            }
-        }
+        self.check_function_borrows(&method.body, &method.attributes);
+
+        // 参照の借用元の検査
+        let receiver = (
+            method.receiver.name.clone().unwrap_or_else(|| "self".to_string()),
+            method.receiver.ty.clone(),
+            method.receiver.span,
+        );
+        self.check_borrow_sources(BorrowSourcesInput {
+            name: &method.name,
+            params: std::iter::once(receiver)
+                .chain(method.params.iter().map(|p| (p.name.clone(), p.ty.clone(), p.span)))
+                .collect(),
+            return_type: &return_type,
+            lives_clause: method.lives_clause.as_ref(),
+            body: &method.body,
+            signature_span: Span::new(method.span.start, method.body.span.start),
+        });
 
         self.current_return_type = None;
         self.exit_scope();
@@ -227,4 +247,40 @@ impl SemanticAnalyzer {
 
         Ok(())
     }
+
+    /// 関数本体の借用チェック
+    ///
+    /// `#[unsafe_no_borrow_check]` を付けた関数と `BorrowCheckMode::Off` では行いません。
+    /// `BorrowCheckMode::Warn` では借用の競合と移動後の使用を警告として報告します。
+    fn check_function_borrows(&mut self, body: &Block, attributes: &[Attribute]) {
+        if self.borrow_check == BorrowCheckMode::Off || attributes.contains(&Attribute::UnsafeNoBorrowCheck) {
+            return;
+        }
+
+        let current_scope = self.scope_stack.last_mut().unwrap();
+        let mut borrow_checker = BorrowChecker::new(&mut self.lifetime_context, current_scope);
+        let mut errors: Vec<AnalysisError> = body
+            .statements
+            .iter()
+            .filter_map(|stmt| borrow_checker.check_statement(stmt).err())
+            .collect();
+        // 借用チェックの最終検証
+        errors.extend(borrow_checker.check().err());
+
+        for error in errors {
+            let is_borrow_violation = matches!(
+                error,
+                AnalysisError::UseAfterMove { .. }
+                    | AnalysisError::MoveWhileBorrowed { .. }
+                    | AnalysisError::AssignWhileBorrowed { .. }
+                    | AnalysisError::MultipleMutableBorrows { .. }
+                    | AnalysisError::MutableBorrowConflict { .. }
+            );
+            if is_borrow_violation && self.borrow_check == BorrowCheckMode::Warn {
+                self.warn(error);
+            } else {
+                self.errors.push(error);
+            }
+        }
+    }
 }
\ No newline at end of file
@@ -1,8 +1,10 @@
 //! シンボルテーブルおよび関連するデータ構造
 
 use crate::ast::{Field, LivesClause, Span, Type, TypeParam, Variant};
+use crate::analyzer::display_type;
 use crate::error::AnalyzerError;
 use std::collections::HashMap;
+use std::rc::Rc;
 
 pub type AnalysisError = AnalyzerError;
 pub type AnalysisResult<T> = Result<T, AnalysisError>;
@@ -17,8 +19,8 @@ pub struct Symbol {
     /// 変数が借用されているかどうか
     #[allow(dead_code)]
     pub borrow_info: Option<BorrowInfo>,
-    /// 変数が移動されたかどうか
-    pub is_moved: bool,
+    /// 変数を移動した位置（移動されていなければNone）
+    pub moved_at: Option<Span>,
     /// 変数のライフタイム（参照の場合）
     #[allow(dead_code)]
     pub lifetime: Option<LifetimeId>,
@@ -38,15 +40,42 @@ pub struct FunctionSignature {
     #[allow(dead_code)]
     pub receiver_type: Option<Type>,
     pub span: Span,
+    /// 各引数の宣言の位置（組み込み関数では空）
+    pub param_spans: Vec<Span>,
+}
+
+impl FunctionSignature {
+    /// 診断メッセージに表示するシグネチャ（`fn add(a: i32, b: i32): i32`）
+    pub fn display(&self) -> String {
+        let type_params = if self.type_params.is_empty() {
+            String::new()
+        } else {
+            let names: Vec<&str> = self.type_params.iter().map(|tp| tp.name.as_str()).collect();
+            format!("<{}>", names.join(", "))
+        };
+        let params: Vec<String> = self
+            .params
+            .iter()
+            .map(|(name, ty)| format!("{}: {}", name, display_type(ty)))
+            .collect();
+        let return_type = match &self.return_type {
+            Type::Void => String::new(),
+            ty => format!(": {}", display_type(ty)),
+        };
+        format!("fn {}{}({}){}", self.name, type_params, params.join(", "), return_type)
+    }
 }
 
 /// 型定義情報
+///
+/// 型情報とシグネチャはTypeCheckerとスコープで `Rc` として共有し、検索した側は
+/// 参照カウントを増やすだけで保持できます。
 #[derive(Debug, Clone)]
 pub struct TypeInfo {
     pub name: String,
     pub type_params: Vec<TypeParam>,
     pub kind: TypeKind,
-    pub methods: HashMap<String, FunctionSignature>,
+    pub methods: HashMap<String, Rc<FunctionSignature>>,
     pub span: Span,
 }
 
@@ -71,6 +100,8 @@ pub enum LifetimeId {
 pub struct BorrowInfo {
     pub kind: BorrowKind,
     pub lifetime: LifetimeId,
+    /// 借用した位置（借用の競合の診断に使う）
+    pub span: Span,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -83,7 +114,7 @@ pub enum BorrowKind {
 #[derive(Debug, Default)]
 pub struct Scope {
     symbols: HashMap<String, Symbol>,
-    types: HashMap<String, TypeInfo>,
+    types: HashMap<String, Rc<TypeInfo>>,
     parent: Option<Box<Scope>>,
 }
 
@@ -127,10 +158,10 @@ impl Scope {
         }
     }
 
-    pub fn mark_moved(&mut self, name: &str) -> AnalysisResult<()> {
+    pub fn mark_moved(&mut self, name: &str, span: Span) -> AnalysisResult<()> {
         match self.lookup_mut(name) {
             Some(symbol) => {
-                symbol.is_moved = true;
+                symbol.moved_at = Some(span);
                 Ok(())
             }
             None => Err(AnalysisError::UndefinedVariable {
@@ -153,7 +184,7 @@ impl Scope {
         }
     }
 
-    pub fn define_type(&mut self, type_info: TypeInfo) -> AnalysisResult<()> {
+    pub fn define_type(&mut self, type_info: Rc<TypeInfo>) -> AnalysisResult<()> {
         if self.types.contains_key(&type_info.name) {
             return Err(AnalysisError::DuplicateType {
                 name: type_info.name.clone(),
@@ -164,9 +195,17 @@ impl Scope {
         Ok(())
     }
 
-    pub fn lookup_type(&self, name: &str) -> Option<&TypeInfo> {
+    pub fn lookup_type(&self, name: &str) -> Option<&Rc<TypeInfo>> {
         self.types
             .get(name)
             .or_else(|| self.parent.as_ref().and_then(|parent| parent.lookup_type(name)))
     }
+
+    /// このスコープで定義した型情報を取り出す
+    ///
+    /// 共有している型情報を書き換える前に取り出しておくと、書き換えで複製されずに済みます。
+    /// 書き換えた後の型情報は `define_type` で戻します。
+    pub fn take_type(&mut self, name: &str) -> Option<Rc<TypeInfo>> {
+        self.types.remove(name)
+    }
 }
\ No newline at end of file
@@ -0,0 +1,235 @@
+//! 末尾位置の解析
+//!
+//! 関数・メソッドの本体を走査し、結果がそのまま戻り値になる呼び出しに
+//! `CallExpr::is_tail` と `MethodCallExpr::is_tail` の印を付けます。
+//! 末尾位置は `return` の値と関数本体の最後の文から、if・matchの各分岐と
+//! ブロック式の最後の式へ伝播します。コード生成はこの印を見て末尾呼び出しを出力します。
+
+use crate::ast::*;
+
+/// プログラム中の末尾位置にある呼び出しに印を付ける
+pub fn mark_tail_calls(program: &mut Program) {
+    for item in &mut program.items {
+        match item {
+            Item::Function(func) => mark_body(&mut func.body),
+            Item::Method(method) => mark_body(&mut method.body),
+            Item::TypeDef(_) | Item::ExternFunction(_) => {}
+        }
+    }
+}
+
+/// 関数本体に印を付ける
+///
+/// deferを含む関数では戻る直前にdeferの本体が実行されるため、末尾位置の呼び出しはありません。
+fn mark_body(body: &mut Block) {
+    let marker = TailMarker {
+        allows_tail: !block_contains_defer(body),
+    };
+    marker.mark_block(body, marker.allows_tail);
+}
+
+fn block_contains_defer(block: &Block) -> bool {
+    block.statements.iter().any(statement_contains_defer)
+}
+
+fn statement_contains_defer(stmt: &Statement) -> bool {
+    match stmt {
+        Statement::Defer(_) => true,
+        Statement::Block(block) => block_contains_defer(block),
+        Statement::If(if_stmt) => if_contains_defer(if_stmt),
+        Statement::While(while_stmt) => block_contains_defer(&while_stmt.body),
+        Statement::For(for_stmt) => block_contains_defer(&for_stmt.body),
+        Statement::Expression(expr) => expression_contains_defer(expr),
+        Statement::Let(let_stmt) => let_stmt.init.as_ref().is_some_and(expression_contains_defer),
+        Statement::Return(ret) => ret.value.as_ref().is_some_and(expression_contains_defer),
+        Statement::Assignment(assign) => expression_contains_defer(&assign.value),
+        // ローカル関数のdeferはその関数を抜けるときに実行される
+        Statement::Function(_) => false,
+    }
+}
+
+fn if_contains_defer(if_stmt: &IfStatement) -> bool {
+    block_contains_defer(&if_stmt.then_branch)
+        || match &if_stmt.else_branch {
+            Some(ElseBranch::Block(block)) => block_contains_defer(block),
+            Some(ElseBranch::If(else_if)) => if_contains_defer(else_if),
+            None => false,
+        }
+}
+
+/// ブロック式の中のdeferを探す（それ以外の式はdeferを含むブロックを持たない）
+fn expression_contains_defer(expr: &Expression) -> bool {
+    match expr {
+        Expression::Block(block_expr) => {
+            block_expr.statements.iter().any(statement_contains_defer)
+                || block_expr.last_expr.as_deref().is_some_and(expression_contains_defer)
+        }
+        Expression::If(if_expr) => {
+            expression_contains_defer(&if_expr.then_branch)
+                || if_expr.else_branch.as_deref().is_some_and(expression_contains_defer)
+        }
+        Expression::Match(match_expr) => match_expr.arms.iter().any(|arm| expression_contains_defer(&arm.expr)),
+        _ => false,
+    }
+}
+
+/// 末尾位置の印付け
+struct TailMarker {
+    /// 関数が末尾呼び出しを持てるか（deferを含まないか）
+    allows_tail: bool,
+}
+
+impl TailMarker {
+    /// ブロックに印を付ける（末尾位置は最後の文だけに伝播する）
+    fn mark_block(&self, block: &mut Block, tail: bool) {
+        let count = block.statements.len();
+        for (i, stmt) in block.statements.iter_mut().enumerate() {
+            self.mark_statement(stmt, tail && i + 1 == count);
+        }
+    }
+
+    fn mark_statement(&self, stmt: &mut Statement, tail: bool) {
+        match stmt {
+            Statement::Let(let_stmt) => {
+                if let Some(init) = &mut let_stmt.init {
+                    self.mark_expression(init, false);
+                }
+            }
+            Statement::Assignment(assign) => {
+                self.mark_expression(&mut assign.target, false);
+                self.mark_expression(&mut assign.value, false);
+            }
+            Statement::Return(ret) => {
+                // returnの値は、関数のどこにあっても末尾位置
+                if let Some(value) = &mut ret.value {
+                    self.mark_expression(value, self.allows_tail);
+                }
+            }
+            Statement::If(if_stmt) => self.mark_if_statement(if_stmt, tail),
+            Statement::While(while_stmt) => {
+                self.mark_expression(&mut while_stmt.condition, false);
+                self.mark_block(&mut while_stmt.body, false);
+            }
+            Statement::For(for_stmt) => {
+                if let Some(init) = &mut for_stmt.init {
+                    self.mark_statement(init, false);
+                }
+                if let Some(condition) = &mut for_stmt.condition {
+                    self.mark_expression(condition, false);
+                }
+                if let Some(update) = &mut for_stmt.update {
+                    self.mark_expression(update, false);
+                }
+                self.mark_block(&mut for_stmt.body, false);
+            }
+            Statement::Expression(expr) => self.mark_expression(expr, tail),
+            Statement::Block(block) => self.mark_block(block, tail),
+            Statement::Defer(defer_stmt) => self.mark_block(&mut defer_stmt.body, false),
+            Statement::Function(func) => mark_body(&mut func.body),
+        }
+    }
+
+    fn mark_if_statement(&self, if_stmt: &mut IfStatement, tail: bool) {
+        self.mark_expression(&mut if_stmt.condition, false);
+        self.mark_block(&mut if_stmt.then_branch, tail);
+        match &mut if_stmt.else_branch {
+            Some(ElseBranch::Block(block)) => self.mark_block(block, tail),
+            Some(ElseBranch::If(else_if)) => self.mark_if_statement(else_if, tail),
+            None => {}
+        }
+    }
+
+    fn mark_expression(&self, expr: &mut Expression, tail: bool) {
+        match expr {
+            Expression::Call(call) => {
+                call.is_tail = tail;
+                self.mark_expression(&mut call.callee, false);
+                call.args.iter_mut().for_each(|arg| self.mark_expression(arg, false));
+            }
+            Expression::MethodCall(method_call) => {
+                method_call.is_tail = tail;
+                self.mark_expression(&mut method_call.object, false);
+                method_call.args.iter_mut().for_each(|arg| self.mark_expression(arg, false));
+            }
+            Expression::If(if_expr) => {
+                self.mark_expression(&mut if_expr.condition, false);
+                self.mark_expression(&mut if_expr.then_branch, tail);
+                if let Some(else_branch) = &mut if_expr.else_branch {
+                    self.mark_expression(else_branch, tail);
+                }
+            }
+            Expression::Match(match_expr) => {
+                self.mark_expression(&mut match_expr.expr, false);
+                for arm in &mut match_expr.arms {
+                    if let Some(guard) = &mut arm.guard {
+                        self.mark_expression(guard, false);
+                    }
+                    self.mark_expression(&mut arm.expr, tail);
+                }
+            }
+            Expression::Block(block_expr) => {
+                for stmt in &mut block_expr.statements {
+                    self.mark_statement(stmt, false);
+                }
+                if let Some(last_expr) = &mut block_expr.last_expr {
+                    self.mark_expression(last_expr, tail);
+                }
+            }
+            Expression::Integer(_)
+            | Expression::Float(_)
+            | Expression::String(_)
+            | Expression::Bytes(_)
+            | Expression::Boolean(_)
+            | Expression::Identifier(_)
+            | Expression::Path(_) => {}
+            Expression::TemplateString(template) => {
+                for part in &mut template.parts {
+                    if let TemplateStringPart::Interpolation(expr) = part {
+                        self.mark_expression(expr, false);
+                    }
+                }
+            }
+            Expression::Binary(binary) => {
+                self.mark_expression(&mut binary.left, false);
+                self.mark_expression(&mut binary.right, false);
+            }
+            Expression::Unary(unary) => self.mark_expression(&mut unary.expr, false),
+            Expression::Index(index) => {
+                self.mark_expression(&mut index.object, false);
+                self.mark_expression(&mut index.index, false);
+            }
+            Expression::Field(field) => self.mark_expression(&mut field.object, false),
+            Expression::Reference(ref_expr) => self.mark_expression(&mut ref_expr.expr, false),
+            Expression::Dereference(deref_expr) => self.mark_expression(&mut deref_expr.expr, false),
+            Expression::StructLit(struct_lit) => {
+                for field in &mut struct_lit.fields {
+                    self.mark_expression(&mut field.value, false);
+                }
+            }
+            Expression::EnumVariant(enum_variant) => match &mut enum_variant.fields {
+                EnumVariantFields::Tuple(args) => args.iter_mut().for_each(|arg| self.mark_expression(arg, false)),
+                EnumVariantFields::Struct(fields) => {
+                    for field in fields {
+                        self.mark_expression(&mut field.value, false);
+                    }
+                }
+                EnumVariantFields::Unit => {}
+            },
+            Expression::Array(array) => array.elements.iter_mut().for_each(|e| self.mark_expression(e, false)),
+            Expression::Tuple(tuple) => tuple.elements.iter_mut().for_each(|e| self.mark_expression(e, false)),
+            // 型変換の結果は呼び出しの結果そのものではない
+            Expression::Cast(cast) => self.mark_expression(&mut cast.expr, false),
+            Expression::Assignment(assign) => {
+                self.mark_expression(&mut assign.target, false);
+                self.mark_expression(&mut assign.value, false);
+            }
+            Expression::ListLiteral(list) => list.elements.iter_mut().for_each(|e| self.mark_expression(e, false)),
+            Expression::MapLiteral(map) => {
+                for (key, value) in &mut map.pairs {
+                    self.mark_expression(key, false);
+                    self.mark_expression(value, false);
+                }
+            }
+        }
+    }
+}
@@ -2,15 +2,17 @@
 
 use crate::ast::*;
 use std::collections::HashMap;
+use std::rc::Rc;
 
+use super::numeric::{promote_numeric, PromotionError};
 use super::symbol::{AnalysisError, AnalysisResult, FunctionSignature, TypeInfo, TypeKind};
 
 /// 型チェッカー
 pub struct TypeChecker {
     /// 型定義のレジストリ
-    types: HashMap<String, TypeInfo>,
+    types: HashMap<String, Rc<TypeInfo>>,
     /// 関数シグネチャのレジストリ
-    functions: HashMap<String, FunctionSignature>,
+    functions: HashMap<String, Rc<FunctionSignature>>,
 }
 
 impl Default for TypeChecker {
@@ -47,13 +49,13 @@ impl TypeChecker {
         for type_name in builtin_types {
             self.types.insert(
                 type_name.to_string(),
-                TypeInfo {
+                Rc::new(TypeInfo {
                     name: type_name.to_string(),
                     type_params: Vec::new(),
                     kind: TypeKind::Builtin,
                     methods: HashMap::new(),
                     span: Span::dummy(),
-                },
+                }),
             );
         }
         
@@ -61,19 +63,19 @@ impl TypeChecker {
         // Vec<T>
         self.types.insert(
             "Vec".to_string(),
-            TypeInfo {
+            Rc::new(TypeInfo {
                 name: "Vec".to_string(),
                 type_params: vec![TypeParam { name: "T".to_string(), span: Span::dummy() }],
                 kind: TypeKind::Builtin,
                 methods: HashMap::new(),
                 span: Span::dummy(),
-            },
+            }),
         );
         
         // HashMap<K, V>
         self.types.insert(
             "HashMap".to_string(),
-            TypeInfo {
+            Rc::new(TypeInfo {
                 name: "HashMap".to_string(),
                 type_params: vec![
                     TypeParam { name: "K".to_string(), span: Span::dummy() },
@@ -82,25 +84,25 @@ impl TypeChecker {
                 kind: TypeKind::Builtin,
                 methods: HashMap::new(),
                 span: Span::dummy(),
-            },
+            }),
         );
         
         // Option<T>
         self.types.insert(
             "Option".to_string(),
-            TypeInfo {
+            Rc::new(TypeInfo {
                 name: "Option".to_string(),
                 type_params: vec![TypeParam { name: "T".to_string(), span: Span::dummy() }],
                 kind: TypeKind::Builtin,
                 methods: HashMap::new(),
                 span: Span::dummy(),
-            },
+            }),
         );
         
         // Result<T, E>
         self.types.insert(
             "Result".to_string(),
-            TypeInfo {
+            Rc::new(TypeInfo {
                 name: "Result".to_string(),
                 type_params: vec![
                     TypeParam { name: "T".to_string(), span: Span::dummy() },
@@ -109,41 +111,138 @@ impl TypeChecker {
                 kind: TypeKind::Builtin,
                 methods: HashMap::new(),
                 span: Span::dummy(),
-            },
+            }),
+        );
+        
+        // StringBuilder（メソッドはセマンティック解析で特別扱いする）
+        self.types.insert(
+            "StringBuilder".to_string(),
+            Rc::new(TypeInfo {
+                name: "StringBuilder".to_string(),
+                type_params: Vec::new(),
+                kind: TypeKind::Builtin,
+                methods: HashMap::new(),
+                span: Span::dummy(),
+            }),
         );
     }
     
     /// ビルトイン関数を登録
     fn register_builtin_functions(&mut self) {
-        // println関数
-        let println_sig = FunctionSignature {
-            name: "println".to_string(),
+        // コンソール出力の関数
+        for name in ["print", "println", "eprintln"] {
+            let output_sig = FunctionSignature {
+                name: name.to_string(),
+                type_params: Vec::new(),
+                params: vec![("message".to_string(), Type::String)],
+                return_type: Type::Void,
+                lives_clause: None,
+                is_method: false,
+                receiver_type: None,
+                span: Span::dummy(),
+                param_spans: Vec::new(),
+            };
+            self.functions.insert(name.to_string(), Rc::new(output_sig));
+        }
+
+        // sqrt関数
+        let sqrt_sig = FunctionSignature {
+            name: "sqrt".to_string(),
             type_params: Vec::new(),
-            params: vec![("value".to_string(), Type::String)],
-            return_type: Type::Void,
+            params: vec![("value".to_string(), Type::F64)],
+            return_type: Type::F64,
             lives_clause: None,
             is_method: false,
             receiver_type: None,
             span: Span::dummy(),
+            param_spans: Vec::new(),
         };
-        self.functions.insert("println".to_string(), println_sig);
+        self.functions.insert("sqrt".to_string(), Rc::new(sqrt_sig));
 
-        // sqrt関数
-        let sqrt_sig = FunctionSignature {
-            name: "sqrt".to_string(),
+        // read_line関数（標準入力から1行読み込む）
+        let read_line_sig = FunctionSignature {
+            name: "read_line".to_string(),
             type_params: Vec::new(),
-            params: vec![("value".to_string(), Type::F64)],
-            return_type: Type::F64,
+            params: Vec::new(),
+            return_type: Type::String,
             lives_clause: None,
             is_method: false,
             receiver_type: None,
             span: Span::dummy(),
+            param_spans: Vec::new(),
         };
-        self.functions.insert("sqrt".to_string(), sqrt_sig);
+        self.functions.insert("read_line".to_string(), Rc::new(read_line_sig));
+
+        // バイナリファイルの読み書き（NULを含むバイト列もそのまま扱う）
+        let bytes_type = Type::Generic("Vec".to_string(), vec![Type::U8]);
+        let write_bytes_sig = FunctionSignature {
+            name: "write_bytes".to_string(),
+            type_params: Vec::new(),
+            params: vec![("path".to_string(), Type::String), ("data".to_string(), bytes_type.clone())],
+            return_type: Type::Bool,
+            lives_clause: None,
+            is_method: false,
+            receiver_type: None,
+            span: Span::dummy(),
+            param_spans: Vec::new(),
+        };
+        self.functions.insert("write_bytes".to_string(), Rc::new(write_bytes_sig));
+
+        let read_bytes_sig = FunctionSignature {
+            name: "read_bytes".to_string(),
+            type_params: Vec::new(),
+            params: vec![("path".to_string(), Type::String)],
+            return_type: bytes_type,
+            lives_clause: None,
+            is_method: false,
+            receiver_type: None,
+            span: Span::dummy(),
+            param_spans: Vec::new(),
+        };
+        self.functions.insert("read_bytes".to_string(), Rc::new(read_bytes_sig));
+
+        // 文字列から数値への変換（Optionが列挙型になるまでは `(変換できたか, 値)` のタプルで返す）
+        for (name, value_type) in [("parse_int", Type::I64), ("parse_float", Type::F64)] {
+            let parse_sig = FunctionSignature {
+                name: name.to_string(),
+                type_params: Vec::new(),
+                params: vec![("text".to_string(), Type::Str)],
+                return_type: Type::Tuple(vec![Type::Bool, value_type]),
+                lives_clause: None,
+                is_method: false,
+                receiver_type: None,
+                span: Span::dummy(),
+                param_spans: Vec::new(),
+            };
+            self.functions.insert(name.to_string(), Rc::new(parse_sig));
+        }
+
+        // 時刻と乱数（乱数は同じシードから同じ系列になる）
+        let clock_functions = [
+            ("time_nanos", Vec::new(), Type::I64),
+            ("time_unix_millis", Vec::new(), Type::I64),
+            ("sleep_millis", vec![("ms".to_string(), Type::I64)], Type::Void),
+            ("random_seed", vec![("seed".to_string(), Type::U64)], Type::Void),
+            ("random_u64", Vec::new(), Type::U64),
+        ];
+        for (name, params, return_type) in clock_functions {
+            let clock_sig = FunctionSignature {
+                name: name.to_string(),
+                type_params: Vec::new(),
+                params,
+                return_type,
+                lives_clause: None,
+                is_method: false,
+                receiver_type: None,
+                span: Span::dummy(),
+                param_spans: Vec::new(),
+            };
+            self.functions.insert(name.to_string(), Rc::new(clock_sig));
+        }
     }
     
     /// 型定義を登録
-    pub fn register_type(&mut self, type_info: TypeInfo) -> AnalysisResult<()> {
+    pub fn register_type(&mut self, type_info: Rc<TypeInfo>) -> AnalysisResult<()> {
         if self.types.contains_key(&type_info.name) {
             return Err(AnalysisError::DuplicateType {
                 name: type_info.name.clone(),
@@ -167,7 +266,7 @@ impl TypeChecker {
                 span: func_sig.span,
             });
         }
-        self.functions.insert(func_sig.name.clone(), func_sig);
+        self.functions.insert(func_sig.name.clone(), Rc::new(func_sig));
         Ok(())
     }
     
@@ -239,52 +338,8 @@ impl TypeChecker {
     }
     
     /// 型の文字列表現を取得
-    #[allow(clippy::only_used_in_recursion)]
     pub fn type_to_string(&self, ty: &Type) -> String {
-        match ty {
-            Type::I8 => "i8".to_string(),
-            Type::I16 => "i16".to_string(),
-            Type::I32 => "i32".to_string(),
-            Type::I64 => "i64".to_string(),
-            Type::I128 => "i128".to_string(),
-            Type::I256 => "i256".to_string(),
-            Type::U8 => "u8".to_string(),
-            Type::U16 => "u16".to_string(),
-            Type::U32 => "u32".to_string(),
-            Type::U64 => "u64".to_string(),
-            Type::U128 => "u128".to_string(),
-            Type::U256 => "u256".to_string(),
-            Type::F8 => "f8".to_string(),
-            Type::F16 => "f16".to_string(),
-            Type::F32 => "f32".to_string(),
-            Type::F64 => "f64".to_string(),
-            Type::Bool => "bool".to_string(),
-            Type::Str => "str".to_string(),
-            Type::String => "String".to_string(),
-            Type::Void => "void".to_string(),
-            Type::UserDefined(name) => name.clone(),
-            Type::Reference(referent, is_mutable) => {
-                if *is_mutable {
-                    format!("&mut {}", self.type_to_string(referent))
-                } else {
-                    format!("&{}", self.type_to_string(referent))
-                }
-            }
-            Type::Array(element) => format!("[{}]", self.type_to_string(element)),
-            Type::Tuple(elements) => {
-                let elems: Vec<String> = elements.iter().map(|e| self.type_to_string(e)).collect();
-                format!("({})", elems.join(", "))
-            }
-            Type::Function(fn_type) => {
-                let param_strs: Vec<String> = fn_type.params.iter().map(|p| self.type_to_string(p)).collect();
-                format!("fn({}) -> {}", param_strs.join(", "), self.type_to_string(&fn_type.return_type))
-            }
-            Type::Variable(name) => name.clone(),
-            Type::Generic(name, args) => {
-                let arg_strs: Vec<String> = args.iter().map(|a| self.type_to_string(a)).collect();
-                format!("{}<{}>", name, arg_strs.join(", "))
-            }
-        }
+        display_type(ty)
     }
     
     /// 型が整数型かチェック
@@ -312,37 +367,50 @@ impl TypeChecker {
     
     /// 型エイリアスを解決
     pub fn resolve_type_alias(&self, ty: &Type) -> Type {
+        self.resolve_type_alias_in(ty, &mut Vec::new())
+    }
+
+    /// 展開中のエイリアスを記録しながら解決する
+    ///
+    /// 循環するエイリアス（解析エラーとして報告済み）は展開を止めてそのまま返します。
+    fn resolve_type_alias_in(&self, ty: &Type, expanding: &mut Vec<String>) -> Type {
         match ty {
             Type::UserDefined(name) => {
                 // 型情報を取得
                 if let Some(type_info) = self.types.get(name) {
                     if let TypeKind::Alias(underlying) = &type_info.kind {
+                        if expanding.contains(name) {
+                            return ty.clone();
+                        }
                         // エイリアスの場合は基底型を再帰的に解決
-                        return self.resolve_type_alias(underlying);
+                        expanding.push(name.clone());
+                        let resolved = self.resolve_type_alias_in(underlying, expanding);
+                        expanding.pop();
+                        return resolved;
                     }
                 }
                 // エイリアスでない場合はそのまま返す
                 ty.clone()
             }
             Type::Reference(inner, is_mut) => {
-                Type::Reference(Box::new(self.resolve_type_alias(inner)), *is_mut)
+                Type::Reference(Box::new(self.resolve_type_alias_in(inner, expanding)), *is_mut)
             }
             Type::Array(elem) => {
-                Type::Array(Box::new(self.resolve_type_alias(elem)))
+                Type::Array(Box::new(self.resolve_type_alias_in(elem, expanding)))
             }
             Type::Tuple(elems) => {
-                Type::Tuple(elems.iter().map(|e| self.resolve_type_alias(e)).collect())
+                Type::Tuple(elems.iter().map(|e| self.resolve_type_alias_in(e, expanding)).collect())
             }
             Type::Generic(name, args) => {
                 Type::Generic(
                     name.clone(),
-                    args.iter().map(|a| self.resolve_type_alias(a)).collect()
+                    args.iter().map(|a| self.resolve_type_alias_in(a, expanding)).collect()
                 )
             }
             Type::Function(fn_type) => {
                 Type::Function(FunctionType {
-                    params: fn_type.params.iter().map(|p| self.resolve_type_alias(p)).collect(),
-                    return_type: Box::new(self.resolve_type_alias(&fn_type.return_type)),
+                    params: fn_type.params.iter().map(|p| self.resolve_type_alias_in(p, expanding)).collect(),
+                    return_type: Box::new(self.resolve_type_alias_in(&fn_type.return_type, expanding)),
                 })
             }
             // プリミティブ型はそのまま返す
@@ -430,47 +498,46 @@ impl TypeChecker {
     
     /// 二項演算子の結果型を取得
     pub fn binary_op_result_type(&self, op: &BinaryOp, left: &Type, right: &Type, span: Span) -> AnalysisResult<Type> {
+        // 解析に失敗したオペランドの誤りは報告済みのため、結果の型だけを決める
+        if matches!(left, Type::Error) || matches!(right, Type::Error) {
+            return Ok(match op {
+                BinaryOp::Lt | BinaryOp::Gt | BinaryOp::Le | BinaryOp::Ge
+                | BinaryOp::Eq | BinaryOp::Ne | BinaryOp::And | BinaryOp::Or | BinaryOp::In => Type::Bool,
+                _ => Type::Error,
+            });
+        }
         match op {
             BinaryOp::Add => {
-                // 数値の加算
-                if self.types_compatible(left, right) && self.is_numeric_type(left) {
-                    Ok(left.clone())
-                }
                 // 文字列の連結
-                else if self.is_string_type(left) && self.is_string_type(right) {
+                if self.is_string_type(left) && self.is_string_type(right) {
                     Ok(Type::String) // 文字列連結の結果は常にString型
-                } else {
-                    Err(AnalysisError::TypeMismatch {
-                        expected: self.type_to_string(left),
-                        found: self.type_to_string(right),
-                        span,
-                    })
+                }
+                // 数値の加算
+                else {
+                    self.numeric_operand_type(left, right, span)
                 }
             }
             BinaryOp::Subtract | BinaryOp::Multiply | BinaryOp::Divide | BinaryOp::Modulo => {
-                if self.types_compatible(left, right) && self.is_numeric_type(left) {
-                    Ok(left.clone())
-                } else {
-                    Err(AnalysisError::TypeMismatch {
-                        expected: self.type_to_string(left),
-                        found: self.type_to_string(right),
-                        span,
-                    })
-                }
+                self.numeric_operand_type(left, right, span)
             }
             BinaryOp::Lt | BinaryOp::Gt | BinaryOp::Le | BinaryOp::Ge => {
-                if self.types_compatible(left, right) && self.is_numeric_type(left) {
-                    Ok(Type::Bool)
-                } else {
-                    Err(AnalysisError::TypeMismatch {
+                if !self.is_numeric_type(left) {
+                    return Err(AnalysisError::TypeMismatch {
                         expected: "numeric type".to_string(),
                         found: self.type_to_string(left),
                         span,
-                    })
+                    });
                 }
+                self.numeric_operand_type(left, right, span)?;
+                Ok(Type::Bool)
             }
             BinaryOp::Eq | BinaryOp::Ne => {
-                if self.types_compatible(left, right) {
+                // 数値同士の比較は算術演算と同じ昇格規則に従う
+                if self.is_numeric_type(left) && self.is_numeric_type(right) {
+                    self.numeric_operand_type(left, right, span)?;
+                    Ok(Type::Bool)
+                } else if self.types_compatible(left, right) || self.types_compatible(right, left) {
+                    // strとStringのように一方向にだけ置き換えられる型も、内容で比較できる
                     Ok(Type::Bool)
                 } else {
                     Err(AnalysisError::TypeMismatch {
@@ -495,10 +562,10 @@ impl TypeChecker {
                     })
                 }
             }
-            // ビット演算子は現在定義されていない
+            // ビット演算子
             _ => {
                 if self.is_integer_type(left) && self.is_integer_type(right) {
-                    Ok(left.clone())
+                    self.numeric_operand_type(left, right, span)
                 } else {
                     Err(AnalysisError::TypeMismatch {
                         expected: "integer type".to_string(),
@@ -513,9 +580,30 @@ impl TypeChecker {
             }
         }
     }
+
+    /// 数値演算のオペランドを揃える型を取得（昇格規則はnumericモジュールを参照）
+    pub fn numeric_operand_type(&self, left: &Type, right: &Type, span: Span) -> AnalysisResult<Type> {
+        match promote_numeric(left, right) {
+            Ok(ty) => Ok(ty),
+            Err(PromotionError::NotNumeric | PromotionError::IntegerFloatMix) => Err(AnalysisError::TypeMismatch {
+                expected: self.type_to_string(left),
+                found: self.type_to_string(right),
+                span,
+            }),
+            Err(PromotionError::MixedSignedness) => Err(AnalysisError::MixedNumericTypes {
+                left: self.type_to_string(left),
+                right: self.type_to_string(right),
+                suggestion: format!("右辺を `as {}` でキャストしてください", self.type_to_string(left)),
+                span,
+            }),
+        }
+    }
     
     /// 単項演算子の結果型を取得
     pub fn unary_op_result_type(&self, op: &UnaryOp, operand: &Type, span: Span) -> AnalysisResult<Type> {
+        if matches!(operand, Type::Error) {
+            return Ok(if *op == UnaryOp::Not { Type::Bool } else { Type::Error });
+        }
         match op {
             UnaryOp::Not => {
                 if matches!(operand, Type::Bool) {
@@ -528,6 +616,7 @@ impl TypeChecker {
                     })
                 }
             }
+            // 符号なし整数の符号反転は型の幅でラップアラウンドする（`-(3u8)` は253）
             UnaryOp::Negate => {
                 if self.is_numeric_type(operand) {
                     Ok(operand.clone())
@@ -539,8 +628,8 @@ impl TypeChecker {
                     })
                 }
             }
-            // ビット反転演算子は現在定義されていない
-            _ => {
+            // ビット反転は整数にのみ適用でき、結果は被演算子と同じ型になる
+            UnaryOp::BitNot => {
                 if self.is_integer_type(operand) {
                     Ok(operand.clone())
                 } else {
@@ -565,10 +654,14 @@ impl TypeChecker {
         match (expected, actual) {
             // 同じ型は互換
             (a, b) if a == b => true,
+
+            // 解析に失敗した式の誤りは報告済みのため、不一致を重ねて報告しない
+            (Type::Error, _) | (_, Type::Error) => true,
             
-            // 文字列型の互換性（Stringとstrは相互変換可能）
-            (Type::String, Type::Str) => true,
+            // Stringとその参照はstrとして渡せる（文字列データをコピーせずに指す）
+            // strからStringへは暗黙に変換しない（所有する文字列は `to_string()` で複製して作る）
             (Type::Str, Type::String) => true,
+            (Type::Str, Type::Reference(inner, _)) if matches!(**inner, Type::String | Type::Str) => true,
             
             // 参照型の互換性
             (Type::Reference(ref_a, mut_a),
@@ -601,17 +694,36 @@ impl TypeChecker {
     }
     
     /// 関数シグネチャを取得
-    pub fn get_function_signature(&self, name: &str) -> Option<&FunctionSignature> {
+    pub fn get_function_signature(&self, name: &str) -> Option<&Rc<FunctionSignature>> {
         self.functions.get(name)
     }
     
     /// 型情報を取得
-    pub fn get_type_info(&self, name: &str) -> Option<&TypeInfo> {
+    pub fn get_type_info(&self, name: &str) -> Option<&Rc<TypeInfo>> {
         self.types.get(name)
     }
-    
+
+    /// 指定した名前のフィールドを持たないバリアントを含む列挙型の名前を取得（名前順）
+    pub fn enums_with_unit_variant(&self, variant: &str) -> Vec<String> {
+        let mut names: Vec<String> = self
+            .types
+            .values()
+            .filter(|info| match &info.kind {
+                TypeKind::Enum(variants) => variants
+                    .iter()
+                    .any(|v| v.name == variant && v.fields.is_empty()),
+                _ => false,
+            })
+            .map(|info| info.name.clone())
+            .collect();
+        names.sort();
+        names
+    }
+
     /// メソッドを型に登録
-    pub fn register_method(&mut self, type_name: &str, method_sig: FunctionSignature) -> AnalysisResult<()> {
+    ///
+    /// 型情報を他と共有していると、追加の前に型情報が複製されます。
+    pub fn register_method(&mut self, type_name: &str, method_sig: Rc<FunctionSignature>) -> AnalysisResult<()> {
         // 型が存在するか確認
         if let Some(type_info) = self.types.get_mut(type_name) {
             // メソッド名の重複をチェック
@@ -623,7 +735,7 @@ impl TypeChecker {
             }
             
             // メソッドを登録
-            type_info.methods.insert(method_sig.name.clone(), method_sig);
+            Rc::make_mut(type_info).methods.insert(method_sig.name.clone(), method_sig);
             Ok(())
         } else {
             Err(AnalysisError::UndefinedType {
@@ -635,7 +747,7 @@ impl TypeChecker {
     
     /// メソッドシグネチャを取得
     #[allow(dead_code)]
-    pub fn get_method_signature(&self, type_name: &str, method_name: &str) -> Option<&FunctionSignature> {
+    pub fn get_method_signature(&self, type_name: &str, method_name: &str) -> Option<&Rc<FunctionSignature>> {
         self.types.get(type_name)
             .and_then(|type_info| type_info.methods.get(method_name))
     }
@@ -681,4 +793,53 @@ impl TypeChecker {
             _ => ty.clone(),
         }
     }
-}
\ No newline at end of file
+}
+
+/// 診断メッセージなどに表示する型の文字列表現
+pub fn display_type(ty: &Type) -> String {
+    match ty {
+        Type::I8 => "i8".to_string(),
+        Type::I16 => "i16".to_string(),
+        Type::I32 => "i32".to_string(),
+        Type::I64 => "i64".to_string(),
+        Type::I128 => "i128".to_string(),
+        Type::I256 => "i256".to_string(),
+        Type::U8 => "u8".to_string(),
+        Type::U16 => "u16".to_string(),
+        Type::U32 => "u32".to_string(),
+        Type::U64 => "u64".to_string(),
+        Type::U128 => "u128".to_string(),
+        Type::U256 => "u256".to_string(),
+        Type::F8 => "f8".to_string(),
+        Type::F16 => "f16".to_string(),
+        Type::F32 => "f32".to_string(),
+        Type::F64 => "f64".to_string(),
+        Type::Bool => "bool".to_string(),
+        Type::Str => "str".to_string(),
+        Type::String => "String".to_string(),
+        Type::Void => "void".to_string(),
+        Type::UserDefined(name) => name.clone(),
+        Type::Reference(referent, is_mutable) => {
+            if *is_mutable {
+                format!("&mut {}", display_type(referent))
+            } else {
+                format!("&{}", display_type(referent))
+            }
+        }
+        Type::Array(element) => format!("[{}]", display_type(element)),
+        Type::Tuple(elements) => {
+            let elems: Vec<String> = elements.iter().map(display_type).collect();
+            format!("({})", elems.join(", "))
+        }
+        Type::Function(fn_type) => {
+            let param_strs: Vec<String> = fn_type.params.iter().map(display_type).collect();
+            format!("fn({}) -> {}", param_strs.join(", "), display_type(&fn_type.return_type))
+        }
+        Type::Variable(name) => name.clone(),
+        Type::Generic(name, args) => {
+            let arg_strs: Vec<String> = args.iter().map(display_type).collect();
+            format!("{}<{}>", name, arg_strs.join(", "))
+        }
+        Type::Error => "{error}".to_string(),
+    }
+}
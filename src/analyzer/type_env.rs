@@ -14,6 +14,8 @@ pub struct TypeEnvironment {
     type_params: Vec<HashSet<String>>,
     /// 型変数の具体的な型へのマッピング（型推論後）
     type_bindings: HashMap<String, Type>,
+    /// 付け替えた型パラメータ名の通し番号
+    fresh_counter: usize,
 }
 
 impl TypeEnvironment {
@@ -22,6 +24,7 @@ impl TypeEnvironment {
         Self {
             type_params: vec![HashSet::new()],
             type_bindings: HashMap::new(),
+            fresh_counter: 0,
         }
     }
     
@@ -62,6 +65,36 @@ impl TypeEnvironment {
     pub fn is_type_param(&self, name: &str) -> bool {
         self.type_params.iter().any(|scope| scope.contains(name))
     }
+
+    /// 現在のスコープの型パラメータかチェック
+    ///
+    /// 外側のスコープの型パラメータ（解析中の関数自身の型パラメータなど）は
+    /// 具体的な型に束縛できません。
+    pub fn is_current_type_param(&self, name: &str) -> bool {
+        self.type_params.last().is_some_and(|scope| scope.contains(name))
+    }
+
+    /// 型パラメータを他と衝突しない名前に付け替え
+    ///
+    /// ジェネリック関数の中から同じ名前の型パラメータを持つジェネリック関数を
+    /// 呼び出しても両者を区別できるよう、呼び出しごとに新しい名前を割り当てます。
+    /// 付け替えた型パラメータと、元の型変数から新しい型変数への置換を返します。
+    pub fn fresh_type_params(&mut self, params: &[TypeParam]) -> (Vec<TypeParam>, HashMap<String, Type>) {
+        self.fresh_counter += 1;
+        let fresh: Vec<TypeParam> = params
+            .iter()
+            .map(|param| TypeParam {
+                name: format!("{}'{}", param.name, self.fresh_counter),
+                span: param.span,
+            })
+            .collect();
+        let renaming = params
+            .iter()
+            .zip(&fresh)
+            .map(|(param, fresh)| (param.name.clone(), Type::Variable(fresh.name.clone())))
+            .collect();
+        (fresh, renaming)
+    }
     
     /// 型変数にバインディングを設定
     pub fn bind_type(&mut self, type_var: String, ty: Type) {
@@ -205,4 +238,17 @@ mod tests {
         let instantiated = env.instantiate_type(&array_type);
         assert_eq!(instantiated, Type::Array(Box::new(Type::I32)));
     }
+
+    #[test]
+    fn test_fresh_type_params() {
+        let mut env = TypeEnvironment::new();
+        let params = vec![TypeParam { name: "T".to_string(), span: Span::dummy() }];
+
+        let (first, renaming) = env.fresh_type_params(&params);
+        let (second, _) = env.fresh_type_params(&params);
+
+        assert_ne!(first[0].name, "T");
+        assert_ne!(first[0].name, second[0].name);
+        assert_eq!(renaming["T"], Type::Variable(first[0].name.clone()));
+    }
 }
\ No newline at end of file
@@ -33,9 +33,26 @@ impl<'a> TypeInference<'a> {
                 if let Some(bound_type) = self.type_env.get_binding(name).cloned() {
                     // すでにバインディングがある場合、その型と統一
                     self.unify(&bound_type, actual_type, span)?;
-                } else if self.type_env.is_type_param(name) {
+                } else if self.type_env.is_current_type_param(name) {
                     // 型パラメータの場合、バインディングを設定
                     self.type_env.bind_type(name.clone(), actual_type.clone());
+                } else if self.type_env.is_type_param(name) {
+                    // 外側のスコープの型パラメータは束縛せず、自分自身とだけ一致する
+                    match actual_type {
+                        Type::Variable(other) if self.type_env.is_current_type_param(other) => {
+                            self.unify(actual_type, expected, span)?;
+                        }
+                        _ if actual_type == expected => {}
+                        _ => {
+                            return Err(crate::error::YuniError::Analyzer(
+                                AnalysisError::TypeMismatch {
+                                    expected: format!("{:?}", expected),
+                                    found: format!("{:?}", actual_type),
+                                    span,
+                                }
+                            ));
+                        }
+                    }
                 } else {
                     // 未定義の型変数
                     return Err(crate::error::YuniError::Analyzer(
@@ -244,7 +261,6 @@ impl<'a> TypeInference<'a> {
     
     /// 型に置換を適用
     #[allow(clippy::only_used_in_recursion)]
-    #[allow(dead_code)]
     pub fn apply_substitutions(&self, ty: &Type, substitutions: &HashMap<String, Type>) -> Type {
         match ty {
             Type::Variable(name) => {
@@ -324,6 +340,29 @@ mod tests {
         assert_eq!(env.get_binding("T"), Some(&Type::I32));
     }
     
+    #[test]
+    fn test_unify_outer_type_param_is_rigid() {
+        let mut env = TypeEnvironment::new();
+        let outer = vec![TypeParam { name: "T".to_string(), span: Span::dummy() }];
+        env.register_type_params(&outer).unwrap();
+        env.enter_scope();
+        let inner = vec![TypeParam { name: "U".to_string(), span: Span::dummy() }];
+        env.register_type_params(&inner).unwrap();
+
+        let mut inference = TypeInference::new(&mut env);
+        let t = Type::Variable("T".to_string());
+        let u = Type::Variable("U".to_string());
+
+        // 内側の U は外側の T に束縛でき、束縛後は T とだけ一致する
+        inference.unify(&u, &t, Span::dummy()).unwrap();
+        inference.unify(&u, &t, Span::dummy()).unwrap();
+        assert!(inference.unify(&u, &Type::I32, Span::dummy()).is_err());
+        assert!(inference.unify(&t, &Type::I32, Span::dummy()).is_err());
+
+        assert_eq!(env.get_binding("U"), Some(&t));
+        assert_eq!(env.get_binding("T"), None);
+    }
+
     #[test]
     fn test_infer_substitutions() {
         let mut env = TypeEnvironment::new();
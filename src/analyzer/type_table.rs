@@ -0,0 +1,105 @@
+//! 式の型テーブル
+//!
+//! セマンティック解析で決定した各式の型を、式のスパンをキーとして記録します。
+//! 単相化とコード生成はこのテーブルを参照するため、解析と異なる規則で
+//! 型を推測し直す必要はありません。
+
+use std::collections::HashMap;
+use std::mem::Discriminant;
+
+use crate::ast::{Expression, Type};
+
+/// 式を識別するキー（スパンと式の種類）
+///
+/// 同じスパンを持つ異なる種類の式（例: 括弧で囲まれた式）を区別するため、
+/// 式の種類もキーに含めます。
+type ExprKey = (usize, usize, Discriminant<Expression>);
+
+/// 解析済みの式の型を保持するテーブル
+#[derive(Debug, Clone, Default)]
+pub struct TypeTable {
+    types: HashMap<ExprKey, Type>,
+    /// ジェネリックなアイテムの本体にある式の型（アイテムの型パラメータを含みうる）
+    generic_types: HashMap<ExprKey, Type>,
+    /// 列挙型名を省略して書かれたバリアント（式 -> (列挙型名, バリアント名)）
+    variants: HashMap<ExprKey, (String, String)>,
+}
+
+impl TypeTable {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 式の型を記録
+    ///
+    /// スパンを持たない（合成された）式は識別できないため記録しません。
+    pub fn record(&mut self, expr: &Expression, ty: &Type) {
+        if let Some(key) = Self::key(expr) {
+            self.types.insert(key, ty.clone());
+        }
+    }
+
+    /// ジェネリックなアイテムの本体にある式の型を記録
+    ///
+    /// 本体は単相化で型引数ごとに複製されるため、コード生成が参照する型とは
+    /// 分けて記録します。単相化はアイテムの型パラメータを置換してから使います。
+    pub fn record_generic(&mut self, expr: &Expression, ty: &Type) {
+        if let Some(key) = Self::key(expr) {
+            self.generic_types.insert(key, ty.clone());
+        }
+    }
+
+    /// 列挙型名を省略して書かれたバリアントの解決結果を記録
+    ///
+    /// 単相化の前に、記録された識別子は通常のバリアント式に置き換えられます。
+    pub fn record_variant(&mut self, expr: &Expression, enum_name: &str, variant: &str) {
+        if let Some(key) = Self::key(expr) {
+            self.variants.insert(key, (enum_name.to_string(), variant.to_string()));
+        }
+    }
+
+    /// 記録された式の型を取得
+    pub fn get(&self, expr: &Expression) -> Option<&Type> {
+        Self::key(expr).and_then(|key| self.types.get(&key))
+    }
+
+    /// ジェネリックなアイテムの本体に記録された式の型を取得
+    pub fn get_generic(&self, expr: &Expression) -> Option<&Type> {
+        Self::key(expr).and_then(|key| self.generic_types.get(&key))
+    }
+
+    /// 列挙型名を省略して書かれたバリアントの解決結果を取得
+    pub fn variant(&self, expr: &Expression) -> Option<(&str, &str)> {
+        Self::key(expr)
+            .and_then(|key| self.variants.get(&key))
+            .map(|(enum_name, variant)| (enum_name.as_str(), variant.as_str()))
+    }
+
+    /// 具体的に決まった式の型を取得
+    ///
+    /// 型変数やジェネリック型を含む型、解析で型が決まらなかった式（`Void`）は
+    /// 単相化後のプログラムにそのまま使えないため返しません。
+    pub fn resolved(&self, expr: &Expression) -> Option<&Type> {
+        self.get(expr)
+            .filter(|ty| !matches!(ty, Type::Void) && is_concrete(ty))
+    }
+
+    fn key(expr: &Expression) -> Option<ExprKey> {
+        let span = expr.span();
+        if span.start == span.end {
+            return None;
+        }
+        Some((span.start, span.end, std::mem::discriminant(expr)))
+    }
+}
+
+/// 型変数とジェネリック型を含まない型かどうか
+pub fn is_concrete(ty: &Type) -> bool {
+    match ty {
+        Type::Variable(_) | Type::Generic(_, _) => false,
+        Type::Reference(inner, _) | Type::Array(inner) => is_concrete(inner),
+        Type::Tuple(types) => types.iter().all(is_concrete),
+        Type::Function(func) => func.params.iter().all(is_concrete) && is_concrete(&func.return_type),
+        _ => true,
+    }
+}
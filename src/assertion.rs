@@ -0,0 +1,139 @@
+//! アサーションの組み込み関数
+//!
+//! `assert(cond)` と `assert_eq(left, right)` は検査が失敗すると、引数の式のソースコードと
+//! 呼び出しの位置を示してパニックします。`debug_assert` と `debug_assert_eq` は同じ検査を
+//! デバッグ用のビルド（`-O0` または `--debug-assertions`）でだけ行い、それ以外では何も生成しません。
+//!
+//! 失敗時のメッセージはコンパイル時に分かる部分をここで組み立て、コード生成と
+//! インタプリタで同じ文言になるようにします。
+
+use std::path::Path;
+
+use crate::ast::{print_expression, Expression, Span};
+
+/// アサーションの組み込み関数の種類
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AssertKind {
+    Assert,
+    AssertEq,
+    DebugAssert,
+    DebugAssertEq,
+}
+
+impl AssertKind {
+    /// 組み込み関数の名前から種類を取得
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "assert" => Some(Self::Assert),
+            "assert_eq" => Some(Self::AssertEq),
+            "debug_assert" => Some(Self::DebugAssert),
+            "debug_assert_eq" => Some(Self::DebugAssertEq),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Assert => "assert",
+            Self::AssertEq => "assert_eq",
+            Self::DebugAssert => "debug_assert",
+            Self::DebugAssertEq => "debug_assert_eq",
+        }
+    }
+
+    /// 2つの値が等しいことを検査するか
+    pub fn is_eq(self) -> bool {
+        matches!(self, Self::AssertEq | Self::DebugAssertEq)
+    }
+
+    /// デバッグ用のビルドでだけ検査するか
+    pub fn is_debug(self) -> bool {
+        matches!(self, Self::DebugAssert | Self::DebugAssertEq)
+    }
+
+    /// 引数の数
+    pub fn arity(self) -> usize {
+        if self.is_eq() {
+            2
+        } else {
+            1
+        }
+    }
+
+    /// エラーの補足情報に表示するシグネチャ
+    pub fn signature(self) -> String {
+        if self.is_eq() {
+            format!("fn {}(left: T, right: T)", self.name())
+        } else {
+            format!("fn {}(condition: bool)", self.name())
+        }
+    }
+}
+
+/// 失敗時のメッセージに引用するソースコード
+#[derive(Debug, Clone)]
+pub struct SourceText {
+    /// メッセージに表示するファイル名（ディレクトリを除く）
+    file: String,
+    source: String,
+}
+
+impl SourceText {
+    pub fn new(path: &str, source: impl Into<String>) -> Self {
+        let file = Path::new(path)
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| path.to_string());
+        Self { file, source: source.into() }
+    }
+
+    /// スパンの開始位置（`foo.yuni:12:5`、行と列は1始まり）
+    pub fn location(&self, span: Span) -> Option<String> {
+        let line = self.line(span.start)?;
+        let line_start = self.source[..span.start].rfind('\n').map_or(0, |pos| pos + 1);
+        let column = self.source[line_start..span.start].chars().count() + 1;
+        Some(format!("{}:{}:{}", self.file, line, column))
+    }
+
+    /// スパンが指すソースコード
+    fn snippet(&self, span: Span) -> Option<&str> {
+        if span.start >= span.end {
+            return None;
+        }
+        self.source.get(span.start..span.end)
+    }
+
+    /// オフセットを含む行の番号（1始まり）
+    fn line(&self, offset: usize) -> Option<usize> {
+        let before = self.source.get(..offset)?;
+        Some(before.matches('\n').count() + 1)
+    }
+}
+
+/// 検査が失敗したときのメッセージ（`assertion failed: x > 0 (at foo.yuni:12)`）
+///
+/// ソースコードがない場合（JSON形式のASTの入力など）は、式をASTから出力し、位置を省きます。
+pub fn failure_message(kind: AssertKind, args: &[Expression], span: Span, source: Option<&SourceText>) -> String {
+    let text = |expr: &Expression| {
+        source
+            .and_then(|source| source.snippet(expr.span()))
+            .map(str::to_string)
+            .unwrap_or_else(|| print_expression(expr))
+    };
+    let expr = if kind.is_eq() {
+        format!("{} == {}", text(&args[0]), text(&args[1]))
+    } else {
+        text(&args[0])
+    };
+
+    let location = source.and_then(|source| Some((&source.file, source.line(span.start)?)));
+    match location {
+        Some((file, line)) => format!("assertion failed: {} (at {}:{})", expr, file, line),
+        None => format!("assertion failed: {}", expr),
+    }
+}
+
+/// `assert_eq` の失敗時のメッセージに実行時の値を加える
+pub fn with_values(message: &str, left: &str, right: &str) -> String {
+    format!("{}: left = {}, right = {}", message, left, right)
+}
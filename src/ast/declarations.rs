@@ -10,6 +10,33 @@ pub enum Item {
     Function(FunctionDecl),
     Method(MethodDecl),
     TypeDef(TypeDef),
+    ExternFunction(ExternFunctionDecl),
+}
+
+impl Item {
+    /// アイテムのドキュメントコメント
+    pub fn docs(&self) -> &[String] {
+        match self {
+            Item::Function(func) => &func.docs,
+            Item::Method(method) => &method.docs,
+            Item::ExternFunction(func) => &func.docs,
+            Item::TypeDef(TypeDef::Struct(struct_def)) => &struct_def.docs,
+            Item::TypeDef(TypeDef::Enum(enum_def)) => &enum_def.docs,
+            Item::TypeDef(TypeDef::Alias(alias)) => &alias.docs,
+        }
+    }
+
+    /// アイテムのドキュメントコメント（変更用）
+    pub fn docs_mut(&mut self) -> &mut Vec<String> {
+        match self {
+            Item::Function(func) => &mut func.docs,
+            Item::Method(method) => &mut method.docs,
+            Item::ExternFunction(func) => &mut func.docs,
+            Item::TypeDef(TypeDef::Struct(struct_def)) => &mut struct_def.docs,
+            Item::TypeDef(TypeDef::Enum(enum_def)) => &mut enum_def.docs,
+            Item::TypeDef(TypeDef::Alias(alias)) => &mut alias.docs,
+        }
+    }
 }
 
 /// 型定義（構造体、列挙型、または型エイリアス）
@@ -26,6 +53,9 @@ pub struct TypeAlias {
     pub name: String,
     pub type_params: Vec<TypeParam>,
     pub underlying_type: Type,
+    /// 直前のドキュメントコメント（`///`）の各行
+    #[serde(default)]
+    pub docs: Vec<String>,
     pub span: Span,
 }
 
@@ -35,6 +65,9 @@ pub struct StructDef {
     pub name: String,
     pub type_params: Vec<TypeParam>,
     pub fields: Vec<Field>,
+    /// 直前のドキュメントコメント（`///`）の各行
+    #[serde(default)]
+    pub docs: Vec<String>,
     pub span: Span,
 }
 
@@ -52,6 +85,9 @@ pub struct EnumDef {
     pub name: String,
     pub type_params: Vec<TypeParam>,
     pub variants: Vec<Variant>,
+    /// 直前のドキュメントコメント（`///`）の各行
+    #[serde(default)]
+    pub docs: Vec<String>,
     pub span: Span,
 }
 
@@ -63,23 +99,114 @@ pub struct Variant {
     pub span: Span,
 }
 
+/// インライン展開の指定
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum InlineHint {
+    /// 指定なし（展開するかは最適化に任せる）
+    #[default]
+    None,
+    /// `inline`: 展開を優先する（LLVMの `inlinehint`）
+    Hint,
+    /// `inline(always)`: 常に展開する（LLVMの `alwaysinline`）
+    Always,
+}
+
+impl InlineHint {
+    /// 宣言の先頭に書く修飾子（`fn` の前に置くため末尾に空白を含む。指定なしは空文字列）
+    pub fn prefix(self) -> &'static str {
+        match self {
+            InlineHint::None => "",
+            InlineHint::Hint => "inline ",
+            InlineHint::Always => "inline(always) ",
+        }
+    }
+}
+
+/// 関数・メソッドに付ける属性（`#[...]`）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Attribute {
+    /// `#[unsafe_no_borrow_check]`: この関数の借用チェックを行わない（型検査は行う）
+    UnsafeNoBorrowCheck,
+}
+
+impl Attribute {
+    /// 属性の名前（`#[...]` の中に書く名前）から属性を取得
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "unsafe_no_borrow_check" => Some(Attribute::UnsafeNoBorrowCheck),
+            _ => None,
+        }
+    }
+
+    /// `#[...]` の中に書く名前
+    pub fn name(self) -> &'static str {
+        match self {
+            Attribute::UnsafeNoBorrowCheck => "unsafe_no_borrow_check",
+        }
+    }
+}
+
 /// 関数宣言
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct FunctionDecl {
     pub is_public: bool,
+    /// `inline` / `inline(always)` の指定
+    #[serde(default)]
+    pub inline: InlineHint,
+    /// `#[...]` で付けた属性
+    #[serde(default)]
+    pub attributes: Vec<Attribute>,
     pub name: String,
     pub type_params: Vec<TypeParam>,
     pub params: Vec<Param>,
     pub return_type: Option<Box<Type>>,
     pub lives_clause: Option<LivesClause>,
     pub body: Block,
+    /// 直前のドキュメントコメント（`///`）の各行
+    #[serde(default)]
+    pub docs: Vec<String>,
     pub span: Span,
 }
 
+/// 外部関数の宣言（`extern fn sqrt(x: f64): f64;`）
+///
+/// 本体を持たず、Cの呼び出し規約でリンク時に解決される関数を宣言します。
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExternFunctionDecl {
+    pub is_public: bool,
+    /// 呼び出し規約（`extern "C"`。現在は `"C"` のみ）
+    pub abi: String,
+    /// リンクするライブラリ（`link("m")` の指定）
+    pub link: Option<String>,
+    pub name: String,
+    pub params: Vec<Param>,
+    pub return_type: Option<Box<Type>>,
+    /// 直前のドキュメントコメント（`///`）の各行
+    #[serde(default)]
+    pub docs: Vec<String>,
+    pub span: Span,
+}
+
+impl ExternFunctionDecl {
+    /// リンク時に参照するシンボル名
+    ///
+    /// プレリュードの名前を隠す宣言は名前解決で `main.sqrt` のように書き換わるため、
+    /// パッケージ名を除いた宣言時の名前を使います。
+    pub fn symbol(&self) -> &str {
+        self.name.rsplit('.').next().unwrap_or(&self.name)
+    }
+}
+
 /// メソッド宣言
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct MethodDecl {
     pub is_public: bool,
+    /// `inline` / `inline(always)` の指定
+    #[serde(default)]
+    pub inline: InlineHint,
+    /// `#[...]` で付けた属性
+    #[serde(default)]
+    pub attributes: Vec<Attribute>,
     pub name: String,
     pub type_params: Vec<TypeParam>,
     pub receiver: Receiver,
@@ -87,6 +214,9 @@ pub struct MethodDecl {
     pub return_type: Option<Box<Type>>,
     pub lives_clause: Option<LivesClause>,
     pub body: Block,
+    /// 直前のドキュメントコメント（`///`）の各行
+    #[serde(default)]
+    pub docs: Vec<String>,
     pub span: Span,
 }
 
@@ -95,6 +225,8 @@ pub struct MethodDecl {
 pub struct Param {
     pub name: String,
     pub ty: Type,
+    /// `mut` 修飾子の有無（引数の束縛を再代入可能にする）
+    pub is_mut: bool,
     pub span: Span,
 }
 
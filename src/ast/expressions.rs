@@ -10,6 +10,7 @@ pub enum Expression {
     Integer(IntegerLit),
     Float(FloatLit),
     String(StringLit),
+    Bytes(BytesLit),
     TemplateString(TemplateStringLit),
     Boolean(BooleanLit),
     Identifier(Identifier),
@@ -40,6 +41,9 @@ pub enum Expression {
 pub struct IntegerLit {
     pub value: i128,
     pub suffix: Option<String>,
+    /// ソース上の表記（`0xFF`・`1_000u32` など。接尾辞を含む）
+    #[serde(default)]
+    pub raw: String,
     pub span: Span,
 }
 
@@ -48,9 +52,38 @@ pub struct IntegerLit {
 pub struct FloatLit {
     pub value: f64,
     pub suffix: Option<String>,
+    /// ソース上の表記（`1_000.5f32` など。接尾辞を含む）
+    #[serde(default)]
+    pub raw: String,
     pub span: Span,
 }
 
+impl IntegerLit {
+    /// 診断やソースの出力に使う表記
+    ///
+    /// 表記を持たないリテラル（表記を記録する前のAST JSONなど）は値と接尾辞から組み立てます。
+    pub fn text(&self) -> String {
+        if !self.raw.is_empty() {
+            return self.raw.clone();
+        }
+        format!("{}{}", self.value, self.suffix.as_deref().unwrap_or(""))
+    }
+}
+
+impl FloatLit {
+    /// 診断やソースの出力に使う表記（表記がなければ、小数点を含む形で値から組み立てる）
+    pub fn text(&self) -> String {
+        if !self.raw.is_empty() {
+            return self.raw.clone();
+        }
+        let mut text = self.value.to_string();
+        if !text.contains('.') {
+            text.push_str(".0");
+        }
+        text + self.suffix.as_deref().unwrap_or("")
+    }
+}
+
 /// 文字列リテラル
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct StringLit {
@@ -58,6 +91,13 @@ pub struct StringLit {
     pub span: Span,
 }
 
+/// バイト列リテラル（`b"..."`。`Vec<u8>` 型の値になる）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BytesLit {
+    pub value: Vec<u8>,
+    pub span: Span,
+}
+
 /// テンプレート文字列リテラル
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct TemplateStringLit {
@@ -123,6 +163,8 @@ pub enum BinaryOp {
     BitXor,
     Shl,
     Shr,
+    /// 要素・キー・部分文字列を含むか（`x in v`）
+    In,
 }
 
 /// 単項演算式
@@ -145,6 +187,9 @@ pub enum UnaryOp {
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct CallExpr {
     pub callee: Box<Expression>,
+    /// 明示した型引数（`make::<i64>()` の `i64`）。指定がなければ空で、引数から推論する
+    #[serde(default)]
+    pub type_args: Vec<Type>,
     pub args: Vec<Expression>,
     pub span: Span,
     #[serde(default)]
@@ -158,6 +203,8 @@ pub struct MethodCallExpr {
     pub method: String,
     pub args: Vec<Expression>,
     pub span: Span,
+    #[serde(default)]
+    pub is_tail: bool,  // 末尾呼び出しかどうか
 }
 
 /// インデックスアクセス式
@@ -196,6 +243,9 @@ pub struct DereferenceExpr {
 pub struct StructLiteral {
     /// 型名。暗黙的変換の場合はNone
     pub name: Option<String>,
+    /// 明示した型引数（`Pair<i32, f64> { ... }` の `i32, f64`）。指定がなければ空で、フィールドから推論する
+    #[serde(default)]
+    pub type_args: Vec<Type>,
     pub fields: Vec<StructFieldInit>,
     pub span: Span,
 }
@@ -271,6 +321,9 @@ pub struct MatchArm {
     pub pattern: Pattern,
     pub guard: Option<Expression>,
     pub expr: Expression,
+    /// パターンとガードの位置
+    #[serde(default = "Span::dummy")]
+    pub span: Span,
 }
 
 /// if式
@@ -317,6 +370,7 @@ impl Expression {
             Expression::Integer(lit) => lit.span,
             Expression::Float(lit) => lit.span,
             Expression::String(lit) => lit.span,
+            Expression::Bytes(lit) => lit.span,
             Expression::TemplateString(lit) => lit.span,
             Expression::Boolean(lit) => lit.span,
             Expression::Identifier(id) => id.span,
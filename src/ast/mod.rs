@@ -5,6 +5,7 @@
 mod declarations;
 mod expressions;
 mod patterns;
+mod printer;
 mod program;
 mod span;
 mod statements;
@@ -14,19 +15,20 @@ mod types;
 pub use span::Span;
 
 // プログラム構造を再エクスポート
-pub use program::{Import, PackageDecl, Program};
+pub use program::{Import, ImportSymbol, PackageDecl, Program, AST_FORMAT_VERSION, AST_SPAN_ENCODING};
 
 // 型定義を再エクスポート
 pub use types::{FunctionType, LivesClause, LivesConstraint, Type, TypeParam};
 
 // 宣言を再エクスポート
 pub use declarations::{
-    EnumDef, Field, FunctionDecl, Item, MethodDecl, Param, Receiver, StructDef, TypeAlias, TypeDef, Variant,
+    Attribute, EnumDef, ExternFunctionDecl, Field, FunctionDecl, InlineHint, Item, MethodDecl, Param, Receiver, StructDef,
+    TypeAlias, TypeDef, Variant,
 };
 
 // 式を再エクスポート
 pub use expressions::{
-    ArrayExpr, AssignmentExpr, BinaryExpr, BinaryOp, BlockExpr, BooleanLit, CallExpr, CastExpr,
+    ArrayExpr, AssignmentExpr, BinaryExpr, BinaryOp, BlockExpr, BooleanLit, BytesLit, CallExpr, CastExpr,
     DereferenceExpr, EnumVariantExpr, EnumVariantFields, Expression, FieldExpr, FloatLit, Identifier, IfExpr, IndexExpr,
     IntegerLit, ListLiteral, MapLiteral, MatchArm, MatchExpr, MethodCallExpr, PathExpr, ReferenceExpr, StringLit,
     StructFieldInit, StructLiteral, TemplateStringLit, TemplateStringPart, TupleExpr, UnaryExpr,
@@ -35,9 +37,12 @@ pub use expressions::{
 
 // 文を再エクスポート
 pub use statements::{
-    AssignStatement, Block, ElseBranch, ForStatement, IfStatement, LetStatement, ReturnStatement,
+    AssignStatement, Block, DeferStatement, ElseBranch, ForStatement, IfStatement, LetStatement, ReturnStatement,
     Statement, WhileStatement,
 };
 
 // パターンを再エクスポート
-pub use patterns::{Pattern, EnumVariantPatternFields, LiteralPattern};
\ No newline at end of file
+pub use patterns::{Pattern, EnumVariantPatternFields, LiteralPattern};
+
+// ソースコードへの出力を再エクスポート
+pub use printer::{print_expression, print_pattern, print_program, print_type, print_type_def};
@@ -19,6 +19,14 @@ pub enum Pattern {
     
     /// リテラルパターン
     Literal(LiteralPattern),
+
+    /// 整数の範囲パターン（`1..=5`、`0..10`）
+    Range {
+        start: i64,
+        end: i64,
+        /// 終端を含むか（`..=`）
+        inclusive: bool,
+    },
     
     /// タプルパターン
     Tuple(Vec<Pattern>),
@@ -35,6 +43,26 @@ pub enum Pattern {
     
     /// ワイルドカードパターン（_）
     Wildcard,
+
+    /// 一致した値に名前を付けるパターン（`name @ pattern`）
+    Binding(String, Box<Pattern>),
+
+    /// いずれかの選択肢に一致するパターン（`pat1 | pat2`）
+    Or(Vec<Pattern>),
+}
+
+impl Pattern {
+    /// 最上位のorパターンを選択肢に展開し、`@` の束縛を外したパターン
+    ///
+    /// `n @ (1 | 2) | 3` は `1`、`2`、`3` になります。網羅性や重複の検査のように、
+    /// アームが一致する値だけを見る場合に使います。
+    pub fn alternatives(&self) -> Vec<&Pattern> {
+        match self {
+            Pattern::Binding(_, pattern) => pattern.alternatives(),
+            Pattern::Or(patterns) => patterns.iter().flat_map(Pattern::alternatives).collect(),
+            pattern => vec![pattern],
+        }
+    }
 }
 
 /// 列挙型バリアントパターンのフィールド定義
@@ -0,0 +1,996 @@
+//! ASTのプリティプリンタ
+//!
+//! ASTをYuniのソースコードとして出力します。
+//! パーサーが生成したASTを出力して再びパースすると、スパンを除いて
+//! 元と同じ構造のASTが得られます。そのため演算子の優先順位や、
+//! 文脈によって解釈が変わる構文（`{` で始まる式など）には必要なだけ括弧を補います。
+
+use super::*;
+
+/// プログラムをソースコードとして出力
+pub fn print_program(program: &Program) -> String {
+    let mut printer = Printer::default();
+    printer.program(program);
+    printer.out
+}
+
+/// 式をソースコードとして出力
+pub fn print_expression(expr: &Expression) -> String {
+    let mut printer = Printer::default();
+    printer.expr(expr);
+    printer.out
+}
+
+/// 型定義をソースコードとして出力
+pub fn print_type_def(type_def: &TypeDef) -> String {
+    let mut printer = Printer::default();
+    printer.type_def(type_def);
+    printer.out
+}
+
+/// パターンをソースコードとして出力
+pub fn print_pattern(pattern: &Pattern) -> String {
+    let mut printer = Printer::default();
+    printer.pattern(pattern);
+    printer.out
+}
+
+/// 型をソースコードとして出力
+pub fn print_type(ty: &Type) -> String {
+    match ty {
+        Type::I8 => "i8".to_string(),
+        Type::I16 => "i16".to_string(),
+        Type::I32 => "i32".to_string(),
+        Type::I64 => "i64".to_string(),
+        Type::I128 => "i128".to_string(),
+        Type::I256 => "i256".to_string(),
+        Type::U8 => "u8".to_string(),
+        Type::U16 => "u16".to_string(),
+        Type::U32 => "u32".to_string(),
+        Type::U64 => "u64".to_string(),
+        Type::U128 => "u128".to_string(),
+        Type::U256 => "u256".to_string(),
+        Type::F8 => "f8".to_string(),
+        Type::F16 => "f16".to_string(),
+        Type::F32 => "f32".to_string(),
+        Type::F64 => "f64".to_string(),
+        Type::Bool => "bool".to_string(),
+        Type::Str => "str".to_string(),
+        Type::String => "String".to_string(),
+        Type::Void => "void".to_string(),
+        Type::Reference(inner, true) => format!("&mut {}", print_type(inner)),
+        Type::Reference(inner, false) => format!("&{}", print_type(inner)),
+        Type::Array(element) => format!("[{}]", print_type(element)),
+        Type::Tuple(types) => format!("({})", join(types.iter().map(print_type))),
+        Type::Function(func) => {
+            let params = join(func.params.iter().map(print_type));
+            if *func.return_type == Type::Void {
+                format!("fn({})", params)
+            } else {
+                format!("fn({}) -> {}", params, print_type(&func.return_type))
+            }
+        }
+        Type::UserDefined(name) | Type::Variable(name) => name.clone(),
+        Type::Generic(name, args) => format!("{}{}", name, type_arguments(args)),
+        Type::Error => "_".to_string(),
+    }
+}
+
+/// 型引数リストを出力
+///
+/// `>>` はシフト演算子として字句解析されるため、入れ子の型引数の閉じ括弧は離して出力します。
+fn type_arguments(args: &[Type]) -> String {
+    let inner = join(args.iter().map(print_type));
+    if inner.ends_with('>') {
+        format!("<{} >", inner)
+    } else {
+        format!("<{}>", inner)
+    }
+}
+
+fn join(items: impl Iterator<Item = String>) -> String {
+    items.collect::<Vec<_>>().join(", ")
+}
+
+/// 二項演算子の記号
+fn binary_op_str(op: &BinaryOp) -> &'static str {
+    match op {
+        BinaryOp::Add => "+",
+        BinaryOp::Subtract => "-",
+        BinaryOp::Multiply => "*",
+        BinaryOp::Divide => "/",
+        BinaryOp::Modulo => "%",
+        BinaryOp::Lt => "<",
+        BinaryOp::Gt => ">",
+        BinaryOp::Le => "<=",
+        BinaryOp::Ge => ">=",
+        BinaryOp::Eq => "==",
+        BinaryOp::Ne => "!=",
+        BinaryOp::And => "&&",
+        BinaryOp::Or => "||",
+        BinaryOp::BitAnd => "&",
+        BinaryOp::BitOr => "|",
+        BinaryOp::BitXor => "^",
+        BinaryOp::Shl => "<<",
+        BinaryOp::Shr => ">>",
+        BinaryOp::In => "in",
+    }
+}
+
+/// 二項演算子の優先順位（パーサーの下降順と対応）
+fn binary_precedence(op: &BinaryOp) -> u8 {
+    match op {
+        BinaryOp::Or => 1,
+        BinaryOp::And => 2,
+        BinaryOp::BitOr => 3,
+        BinaryOp::BitXor => 4,
+        BinaryOp::BitAnd => 5,
+        BinaryOp::Eq | BinaryOp::Ne => 6,
+        BinaryOp::Lt | BinaryOp::Gt | BinaryOp::Le | BinaryOp::Ge | BinaryOp::In => 7,
+        BinaryOp::Shl | BinaryOp::Shr => 8,
+        BinaryOp::Add | BinaryOp::Subtract => 9,
+        BinaryOp::Multiply | BinaryOp::Divide | BinaryOp::Modulo => 10,
+    }
+}
+
+const CAST_PRECEDENCE: u8 = 11;
+const UNARY_PRECEDENCE: u8 = 12;
+const POSTFIX_PRECEDENCE: u8 = 13;
+
+/// 式の優先順位
+fn precedence(expr: &Expression) -> u8 {
+    match expr {
+        Expression::Assignment(_) => 0,
+        Expression::Binary(binary) => binary_precedence(&binary.op),
+        Expression::Cast(_) => CAST_PRECEDENCE,
+        Expression::Unary(_) | Expression::Reference(_) | Expression::Dereference(_) => UNARY_PRECEDENCE,
+        _ => POSTFIX_PRECEDENCE,
+    }
+}
+
+/// 出力したときに先頭となる部分式
+fn leftmost(expr: &Expression) -> &Expression {
+    match expr {
+        Expression::Binary(binary) => leftmost(&binary.left),
+        Expression::Cast(cast) => leftmost(&cast.expr),
+        Expression::Call(call) => leftmost(&call.callee),
+        Expression::MethodCall(method_call) => leftmost(&method_call.object),
+        Expression::Index(index) => leftmost(&index.object),
+        Expression::Field(field) => leftmost(&field.object),
+        Expression::Assignment(assign) => leftmost(&assign.target),
+        _ => expr,
+    }
+}
+
+/// 文の先頭に置くとブロックやif文として解析される式かどうか
+fn starts_like_statement(expr: &Expression) -> bool {
+    matches!(
+        leftmost(expr),
+        Expression::If(_)
+            | Expression::Block(_)
+            | Expression::StructLit(StructLiteral { name: None, .. })
+            | Expression::MapLiteral(MapLiteral { type_name: None, .. })
+    )
+}
+
+/// 型名（またはユニットバリアント）で終わる式かどうか
+///
+/// 直後に `{` が続くと初期化式、`<` が続くと型引数として解析されるため、
+/// if・while・matchの条件式や `<` の左辺がこれに当たる場合は括弧で囲みます。
+fn ends_with_type_name(expr: &Expression) -> bool {
+    match expr {
+        Expression::Identifier(id) => id.name.chars().next().is_some_and(char::is_uppercase),
+        Expression::EnumVariant(variant) => matches!(variant.fields, EnumVariantFields::Unit),
+        Expression::Binary(binary) => ends_with_type_name(&binary.right),
+        Expression::Unary(unary) => ends_with_type_name(&unary.expr),
+        Expression::Reference(ref_expr) => ends_with_type_name(&ref_expr.expr),
+        Expression::Dereference(deref) => ends_with_type_name(&deref.expr),
+        _ => false,
+    }
+}
+
+/// 文字列リテラルを出力
+///
+/// テンプレート文字列の補間式の中では、補間の終端判定が波括弧を数えるため
+/// 文字列中の波括弧もエスケープします。
+fn quote_string(value: &str, in_template: bool) -> String {
+    let mut out = String::from("\"");
+    for ch in value.chars() {
+        match ch {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            '\0' => out.push_str("\\0"),
+            '`' => out.push_str("\\x60"),
+            '{' | '}' if in_template => out.push_str(&format!("\\u{{{:x}}}", ch as u32)),
+            c if c.is_control() => out.push_str(&format!("\\u{{{:x}}}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// バイト列リテラルを出力（制御文字とASCII以外のバイトは `\xNN` で表す）
+fn quote_bytes(value: &[u8], in_template: bool) -> String {
+    let mut out = String::from("b\"");
+    for &byte in value {
+        match byte {
+            b'`' => out.push_str("\\x60"),
+            b'{' | b'}' if in_template => out.push_str(&format!("\\x{:02x}", byte)),
+            _ => out.push_str(&byte.escape_ascii().to_string()),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// 浮動小数点リテラルを出力（常に小数点を含める）
+fn float_literal(value: f64) -> String {
+    let text = value.to_string();
+    if text.contains('.') {
+        text
+    } else {
+        format!("{}.0", text)
+    }
+}
+
+/// ソースコードを組み立てるプリンタ
+#[derive(Default)]
+struct Printer {
+    out: String,
+    indent: usize,
+    /// テンプレート文字列の補間式を出力中かどうか
+    in_template: bool,
+}
+
+impl Printer {
+    fn write(&mut self, text: &str) {
+        self.out.push_str(text);
+    }
+
+    fn newline(&mut self) {
+        self.out.push('\n');
+        for _ in 0..self.indent {
+            self.out.push_str("    ");
+        }
+    }
+
+    /// 現在のインデントで式を文字列として出力
+    fn expr_string(&self, expr: &Expression) -> String {
+        let mut printer = Printer {
+            out: String::new(),
+            indent: self.indent,
+            in_template: self.in_template,
+        };
+        printer.expr(expr);
+        printer.out
+    }
+
+    // ==================== 宣言 ====================
+
+    fn program(&mut self, program: &Program) {
+        self.write(&format!("package {}", program.package.name));
+        if program.package.no_prelude {
+            self.write(" no_prelude");
+        }
+        self.newline();
+        for import in &program.imports {
+            self.newline();
+            self.write(&format!("import {}", quote_string(&import.path, false)));
+            if let Some(alias) = &import.alias {
+                self.write(&format!(" as {}", alias));
+            }
+            if !import.symbols.is_empty() {
+                let symbols: Vec<String> = import
+                    .symbols
+                    .iter()
+                    .map(|symbol| match &symbol.alias {
+                        Some(alias) => format!("{} as {}", symbol.name, alias),
+                        None => symbol.name.clone(),
+                    })
+                    .collect();
+                self.write(&format!(" {{ {} }}", symbols.join(", ")));
+            }
+        }
+        for item in &program.items {
+            self.newline();
+            self.newline();
+            self.item(item);
+        }
+        self.newline();
+    }
+
+    fn item(&mut self, item: &Item) {
+        self.docs(item.docs());
+        match item {
+            Item::Function(func) => self.function(func),
+            Item::Method(method) => {
+                self.attributes(&method.attributes);
+                if method.is_public {
+                    self.write("pub ");
+                }
+                self.write(method.inline.prefix());
+                self.write(&format!("impl fn {}{}(", method.name, type_params(&method.type_params)));
+                let receiver = &method.receiver;
+                if receiver.is_mut {
+                    self.write("mut ");
+                }
+                let receiver_name = receiver.name.as_deref().unwrap_or("self");
+                self.write(&format!("{}: {}", receiver_name, print_type(&receiver.ty)));
+                for p in &method.params {
+                    self.write(&format!(", {}", param(p)));
+                }
+                self.write(")");
+                self.signature_tail(&method.return_type, &method.lives_clause);
+                self.block(&method.body);
+            }
+            Item::TypeDef(type_def) => self.type_def(type_def),
+            Item::ExternFunction(func) => {
+                if func.is_public {
+                    self.write("pub ");
+                }
+                // 呼び出し規約は既定の "C" だけのため省略する
+                self.write("extern ");
+                if let Some(library) = &func.link {
+                    self.write(&format!("link({}) ", quote_string(library, false)));
+                }
+                self.write(&format!("fn {}(", func.name));
+                self.write(&join(func.params.iter().map(param)));
+                self.write(")");
+                if let Some(ty) = &func.return_type {
+                    self.write(&format!(": {}", print_type(ty)));
+                }
+                self.write(";");
+            }
+        }
+    }
+
+    fn docs(&mut self, docs: &[String]) {
+        for line in docs {
+            if line.is_empty() {
+                self.write("///");
+            } else {
+                self.write(&format!("/// {}", line));
+            }
+            self.newline();
+        }
+    }
+
+    fn function(&mut self, func: &FunctionDecl) {
+        self.attributes(&func.attributes);
+        if func.is_public {
+            self.write("pub ");
+        }
+        self.write(func.inline.prefix());
+        self.write(&format!("fn {}{}(", func.name, type_params(&func.type_params)));
+        self.write(&join(func.params.iter().map(param)));
+        self.write(")");
+        self.signature_tail(&func.return_type, &func.lives_clause);
+        self.block(&func.body);
+    }
+
+    fn type_def(&mut self, type_def: &TypeDef) {
+        match type_def {
+            TypeDef::Struct(struct_def) => {
+                self.write(&format!("type {}{} struct ", struct_def.name, type_params(&struct_def.type_params)));
+                self.fields(&struct_def.fields);
+            }
+            TypeDef::Enum(enum_def) => {
+                self.write(&format!("type {}{} enum {{", enum_def.name, type_params(&enum_def.type_params)));
+                self.indent += 1;
+                for variant in &enum_def.variants {
+                    self.newline();
+                    self.write(&variant.name);
+                    if !variant.fields.is_empty() {
+                        self.write(" ");
+                        self.fields(&variant.fields);
+                    }
+                    self.write(",");
+                }
+                self.indent -= 1;
+                self.newline();
+                self.write("}");
+            }
+            TypeDef::Alias(alias) => {
+                self.write(&format!(
+                    "type {}{} {}",
+                    alias.name,
+                    type_params(&alias.type_params),
+                    print_type(&alias.underlying_type)
+                ));
+            }
+        }
+    }
+
+    /// 属性を1行に1つずつ出力
+    fn attributes(&mut self, attributes: &[Attribute]) {
+        for attribute in attributes {
+            self.write(&format!("#[{}]", attribute.name()));
+            self.newline();
+        }
+    }
+
+    /// 戻り値型とlives句を出力
+    fn signature_tail(&mut self, return_type: &Option<Box<Type>>, lives_clause: &Option<LivesClause>) {
+        if let Some(ty) = return_type {
+            self.write(&format!(": {}", print_type(ty)));
+        }
+        if let Some(lives) = lives_clause {
+            let constraints = join(
+                lives
+                    .constraints
+                    .iter()
+                    .map(|c| format!("{}: {}", c.target, c.sources.join(" + "))),
+            );
+            self.write(&format!(" lives {}", constraints));
+        }
+        self.write(" ");
+    }
+
+    fn fields(&mut self, fields: &[Field]) {
+        self.write("{");
+        self.indent += 1;
+        for field in fields {
+            self.newline();
+            self.write(&format!("{}: {},", field.name, print_type(&field.ty)));
+        }
+        self.indent -= 1;
+        self.newline();
+        self.write("}");
+    }
+
+    // ==================== 文 ====================
+
+    /// 文のブロックを出力
+    fn block(&mut self, block: &Block) {
+        if block.statements.is_empty() {
+            self.write("{}");
+            return;
+        }
+        self.write("{");
+        self.indent += 1;
+        for stmt in &block.statements {
+            self.newline();
+            self.statement(stmt, false);
+        }
+        self.indent -= 1;
+        self.newline();
+        self.write("}");
+    }
+
+    /// 文を出力
+    ///
+    /// `in_block_expr` はブロック式の中の文であることを示します。
+    /// ブロック式では `if` や `{` で始まる文も式として解析されるため括弧は不要です。
+    fn statement(&mut self, stmt: &Statement, in_block_expr: bool) {
+        match stmt {
+            Statement::Let(let_stmt) => self.let_statement(let_stmt),
+            Statement::Assignment(assign) => {
+                self.statement_expr(&assign.target, in_block_expr);
+                self.write(" = ");
+                self.expr(&assign.value);
+                self.write(";");
+            }
+            Statement::Return(ret) => match &ret.value {
+                Some(value) => {
+                    self.write("return ");
+                    self.expr(value);
+                    self.write(";");
+                }
+                None => self.write("return;"),
+            },
+            Statement::If(if_stmt) => self.if_statement(if_stmt),
+            Statement::While(while_stmt) => {
+                self.write("while ");
+                self.condition(&while_stmt.condition);
+                self.write(" ");
+                self.block(&while_stmt.body);
+            }
+            Statement::For(for_stmt) => {
+                self.write("for ");
+                match for_stmt.init.as_deref() {
+                    Some(init) => self.statement(init, false),
+                    None => self.write(";"),
+                }
+                if let Some(condition) = &for_stmt.condition {
+                    self.write(" ");
+                    self.expr(condition);
+                }
+                self.write(";");
+                if let Some(update) = &for_stmt.update {
+                    self.write(" ");
+                    self.condition(update);
+                }
+                self.write(" ");
+                self.block(&for_stmt.body);
+            }
+            Statement::Expression(expr) => {
+                self.statement_expr(expr, in_block_expr);
+                self.write(";");
+            }
+            Statement::Block(block) => self.block(block),
+            Statement::Defer(defer_stmt) => {
+                self.write("defer ");
+                self.block(&defer_stmt.body);
+            }
+            Statement::Function(func) => {
+                self.docs(&func.docs);
+                self.function(func);
+            }
+        }
+    }
+
+    /// 文の先頭に置く式を出力
+    fn statement_expr(&mut self, expr: &Expression, in_block_expr: bool) {
+        if !in_block_expr && starts_like_statement(expr) {
+            self.parenthesized(expr);
+        } else {
+            self.expr(expr);
+        }
+    }
+
+    fn let_statement(&mut self, let_stmt: &LetStatement) {
+        self.write("let ");
+        self.pattern(&let_stmt.pattern);
+        if let Some(ty) = &let_stmt.ty {
+            self.write(&format!(": {}", print_type(ty)));
+        }
+        if let Some(init) = &let_stmt.init {
+            self.write(" = ");
+            self.expr(init);
+        }
+        self.write(";");
+    }
+
+    fn if_statement(&mut self, if_stmt: &IfStatement) {
+        self.write("if ");
+        self.condition(&if_stmt.condition);
+        self.write(" ");
+        self.block(&if_stmt.then_branch);
+        match &if_stmt.else_branch {
+            Some(ElseBranch::If(else_if)) => {
+                self.write(" else ");
+                self.if_statement(else_if);
+            }
+            Some(ElseBranch::Block(block)) => {
+                self.write(" else ");
+                self.block(block);
+            }
+            None => {}
+        }
+    }
+
+    /// 直後に `{` が続く式を出力
+    fn condition(&mut self, expr: &Expression) {
+        if ends_with_type_name(expr) {
+            self.parenthesized(expr);
+        } else {
+            self.expr(expr);
+        }
+    }
+
+    // ==================== パターン ====================
+
+    fn pattern(&mut self, pattern: &Pattern) {
+        match pattern {
+            Pattern::Identifier(name, true) => self.write(&format!("mut {}", name)),
+            Pattern::Identifier(name, false) => self.write(name),
+            Pattern::Literal(LiteralPattern::Integer(value)) => self.write(&value.to_string()),
+            Pattern::Literal(LiteralPattern::Float(value)) => self.write(&float_literal(*value)),
+            Pattern::Literal(LiteralPattern::String(value)) => {
+                self.write(&quote_string(value, self.in_template))
+            }
+            Pattern::Literal(LiteralPattern::Bool(value)) => self.write(&value.to_string()),
+            Pattern::Range { start, end, inclusive } => {
+                self.write(&format!("{}{}{}", start, if *inclusive { "..=" } else { ".." }, end))
+            }
+            Pattern::Tuple(patterns) => {
+                self.write("(");
+                self.pattern_list(patterns);
+                self.write(")");
+            }
+            Pattern::Struct(name, fields) => {
+                self.write(name);
+                self.write(" ");
+                self.pattern_fields(fields);
+            }
+            Pattern::EnumVariant { enum_name, variant, fields } => {
+                if enum_name.is_empty() {
+                    self.write(variant);
+                } else {
+                    self.write(&format!("{}::{}", enum_name, variant));
+                }
+                match fields {
+                    EnumVariantPatternFields::Tuple(patterns) => {
+                        self.write("(");
+                        self.pattern_list(patterns);
+                        self.write(")");
+                    }
+                    EnumVariantPatternFields::Struct(fields) => {
+                        self.write(" ");
+                        self.pattern_fields(fields);
+                    }
+                    EnumVariantPatternFields::Unit => {}
+                }
+            }
+            Pattern::Wildcard => self.write("_"),
+            Pattern::Binding(name, pattern) => {
+                self.write(&format!("{} @ ", name));
+                // orパターンに名前を付ける場合は括弧で選択肢をまとめる
+                if matches!(**pattern, Pattern::Or(_)) {
+                    self.write("(");
+                    self.pattern(pattern);
+                    self.write(")");
+                } else {
+                    self.pattern(pattern);
+                }
+            }
+            Pattern::Or(patterns) => {
+                for (i, pattern) in patterns.iter().enumerate() {
+                    if i > 0 {
+                        self.write(" | ");
+                    }
+                    self.pattern(pattern);
+                }
+            }
+        }
+    }
+
+    fn pattern_list(&mut self, patterns: &[Pattern]) {
+        for (i, pattern) in patterns.iter().enumerate() {
+            if i > 0 {
+                self.write(", ");
+            }
+            self.pattern(pattern);
+        }
+    }
+
+    fn pattern_fields(&mut self, fields: &[(String, Pattern)]) {
+        if fields.is_empty() {
+            self.write("{}");
+            return;
+        }
+        self.write("{ ");
+        for (i, (name, pattern)) in fields.iter().enumerate() {
+            if i > 0 {
+                self.write(", ");
+            }
+            self.write(&format!("{}: ", name));
+            self.pattern(pattern);
+        }
+        self.write(" }");
+    }
+
+    // ==================== 式 ====================
+
+    fn parenthesized(&mut self, expr: &Expression) {
+        self.write("(");
+        self.expr(expr);
+        self.write(")");
+    }
+
+    /// 必要な場合だけ括弧で囲んで部分式を出力
+    fn operand(&mut self, expr: &Expression, parenthesize: bool) {
+        if parenthesize {
+            self.parenthesized(expr);
+        } else {
+            self.expr(expr);
+        }
+    }
+
+    fn expr(&mut self, expr: &Expression) {
+        match expr {
+            // 16進数や区切りの `_` など、書かれた表記のまま出力する
+            Expression::Integer(lit) => self.write(&lit.text()),
+            Expression::Float(lit) => self.write(&lit.text()),
+            Expression::String(lit) => self.write(&quote_string(&lit.value, self.in_template)),
+            Expression::Bytes(lit) => self.write(&quote_bytes(&lit.value, self.in_template)),
+            Expression::TemplateString(lit) => self.template_string(lit),
+            Expression::Boolean(lit) => self.write(&lit.value.to_string()),
+            Expression::Identifier(id) => self.write(&id.name),
+            Expression::Path(path) => self.write(&path.segments.join("::")),
+            Expression::Binary(binary) => {
+                // 型名の直後の `<` は型引数と解釈されるため、キャストと型名で終わる左辺は囲む
+                let precedence = binary_precedence(&binary.op);
+                let left_parens = self::precedence(&binary.left) < precedence
+                    || matches!(*binary.left, Expression::Cast(_))
+                    || (binary.op == BinaryOp::Lt && ends_with_type_name(&binary.left));
+                let right_parens = self::precedence(&binary.right) <= precedence
+                    || matches!(*binary.right, Expression::Cast(_));
+                self.operand(&binary.left, left_parens);
+                self.write(&format!(" {} ", binary_op_str(&binary.op)));
+                self.operand(&binary.right, right_parens);
+            }
+            Expression::Unary(unary) => {
+                let op = match unary.op {
+                    UnaryOp::Not => "!",
+                    UnaryOp::Negate => "-",
+                    UnaryOp::BitNot => "~",
+                };
+                self.write(op);
+                // `-1` は負の整数リテラルとして字句解析されるため、数値の否定は囲む
+                let operand = self.expr_string(&unary.expr);
+                let parens = precedence(&unary.expr) < UNARY_PRECEDENCE
+                    || (unary.op == UnaryOp::Negate && operand.starts_with(|c: char| c.is_ascii_digit()));
+                self.wrap_text(&operand, parens);
+            }
+            Expression::Reference(ref_expr) => {
+                self.write(if ref_expr.is_mut { "&mut " } else { "&" });
+                // `&&` は論理積として字句解析されるため、参照の参照は離して出力する
+                let operand = self.expr_string(&ref_expr.expr);
+                if !ref_expr.is_mut && operand.starts_with('&') {
+                    self.write(" ");
+                }
+                self.wrap_text(&operand, precedence(&ref_expr.expr) < UNARY_PRECEDENCE);
+            }
+            Expression::Dereference(deref) => {
+                self.write("*");
+                self.operand(&deref.expr, precedence(&deref.expr) < UNARY_PRECEDENCE);
+            }
+            Expression::Cast(cast) => {
+                self.operand(&cast.expr, precedence(&cast.expr) < UNARY_PRECEDENCE);
+                self.write(&format!(" as {}", print_type(&cast.ty)));
+            }
+            Expression::Call(call) => {
+                // `E::V(...)` はタプルライクなバリアントとして解析されるため、ユニットバリアントは囲む
+                let parens = precedence(&call.callee) < POSTFIX_PRECEDENCE
+                    || matches!(&*call.callee, Expression::EnumVariant(v) if v.fields == EnumVariantFields::Unit);
+                self.operand(&call.callee, parens);
+                if !call.type_args.is_empty() {
+                    self.write(&format!("::{}", type_arguments(&call.type_args)));
+                }
+                self.arguments(&call.args);
+            }
+            Expression::MethodCall(method_call) => {
+                self.operand(&method_call.object, precedence(&method_call.object) < POSTFIX_PRECEDENCE);
+                self.write(&format!(".{}", method_call.method));
+                self.arguments(&method_call.args);
+            }
+            Expression::Index(index) => {
+                self.operand(&index.object, precedence(&index.object) < POSTFIX_PRECEDENCE);
+                self.write("[");
+                self.expr(&index.index);
+                self.write("]");
+            }
+            Expression::Field(field) => {
+                self.operand(&field.object, precedence(&field.object) < POSTFIX_PRECEDENCE);
+                self.write(&format!(".{}", field.field));
+            }
+            Expression::StructLit(struct_lit) => {
+                if let Some(name) = &struct_lit.name {
+                    self.write(name);
+                    if !struct_lit.type_args.is_empty() {
+                        self.write(&type_arguments(&struct_lit.type_args));
+                    }
+                    self.write(" ");
+                }
+                self.field_inits(&struct_lit.fields);
+            }
+            Expression::EnumVariant(variant) => {
+                self.write(&format!("{}::{}", variant.enum_name, variant.variant));
+                match &variant.fields {
+                    EnumVariantFields::Tuple(args) => self.arguments(args),
+                    EnumVariantFields::Struct(fields) => {
+                        self.write(" ");
+                        self.field_inits(fields);
+                    }
+                    EnumVariantFields::Unit => {}
+                }
+            }
+            Expression::Array(array) => self.list(&array.elements),
+            Expression::Tuple(tuple) => {
+                self.write("(");
+                self.expression_list(&tuple.elements);
+                if tuple.elements.len() == 1 {
+                    self.write(",");
+                }
+                self.write(")");
+            }
+            Expression::Assignment(assign) => {
+                self.expr(&assign.target);
+                self.write(" = ");
+                self.expr(&assign.value);
+            }
+            Expression::Match(match_expr) => self.match_expression(match_expr),
+            Expression::If(if_expr) => self.if_expression(if_expr),
+            Expression::Block(block_expr) => self.block_expression(block_expr),
+            Expression::ListLiteral(list) => {
+                if let Some((name, args)) = &list.type_name {
+                    self.write(&format!("{}{}", name, type_arguments(args)));
+                }
+                self.list(&list.elements);
+            }
+            Expression::MapLiteral(map) => {
+                if let Some((name, args)) = &map.type_name {
+                    self.write(name);
+                    if !args.is_empty() {
+                        self.write(&type_arguments(args));
+                    }
+                    self.write(" ");
+                }
+                if map.pairs.is_empty() {
+                    self.write("{}");
+                    return;
+                }
+                self.write("{ ");
+                for (i, (key, value)) in map.pairs.iter().enumerate() {
+                    if i > 0 {
+                        self.write(", ");
+                    }
+                    self.expr(key);
+                    self.write(": ");
+                    self.expr(value);
+                }
+                self.write(" }");
+            }
+        }
+    }
+
+    fn wrap_text(&mut self, text: &str, parenthesize: bool) {
+        if parenthesize {
+            self.write(&format!("({})", text));
+        } else {
+            self.write(text);
+        }
+    }
+
+    fn expression_list(&mut self, exprs: &[Expression]) {
+        for (i, expr) in exprs.iter().enumerate() {
+            if i > 0 {
+                self.write(", ");
+            }
+            self.expr(expr);
+        }
+    }
+
+    fn arguments(&mut self, args: &[Expression]) {
+        self.write("(");
+        self.expression_list(args);
+        self.write(")");
+    }
+
+    fn list(&mut self, elements: &[Expression]) {
+        self.write("[");
+        self.expression_list(elements);
+        self.write("]");
+    }
+
+    fn field_inits(&mut self, fields: &[StructFieldInit]) {
+        if fields.is_empty() {
+            self.write("{}");
+            return;
+        }
+        self.write("{ ");
+        for (i, field) in fields.iter().enumerate() {
+            if i > 0 {
+                self.write(", ");
+            }
+            self.write(&format!("{}: ", field.name));
+            self.expr(&field.value);
+        }
+        self.write(" }");
+    }
+
+    fn template_string(&mut self, lit: &TemplateStringLit) {
+        self.write("`");
+        for part in &lit.parts {
+            match part {
+                TemplateStringPart::Text(text) => {
+                    for ch in text.chars() {
+                        match ch {
+                            '\\' => self.write("\\\\"),
+                            '`' => self.write("\\`"),
+                            '$' => self.write("\\$"),
+                            '\n' => self.write("\\n"),
+                            '\r' => self.write("\\r"),
+                            '\t' => self.write("\\t"),
+                            c => self.out.push(c),
+                        }
+                    }
+                }
+                TemplateStringPart::Interpolation(expr) => {
+                    let mut printer = Printer {
+                        out: String::new(),
+                        indent: 0,
+                        in_template: true,
+                    };
+                    printer.expr(expr);
+                    self.write(&format!("${{{}}}", printer.out));
+                }
+            }
+        }
+        self.write("`");
+    }
+
+    /// ブロック式を出力（最後の式はセミコロンなし）
+    fn block_expression(&mut self, block: &BlockExpr) {
+        if block.statements.is_empty() && block.last_expr.is_none() {
+            self.write("{}");
+            return;
+        }
+        self.write("{");
+        self.indent += 1;
+        for stmt in &block.statements {
+            self.newline();
+            self.statement(stmt, true);
+        }
+        if let Some(last) = &block.last_expr {
+            self.newline();
+            self.expr(last);
+        }
+        self.indent -= 1;
+        self.newline();
+        self.write("}");
+    }
+
+    /// 分岐の本体をブロック式として出力
+    fn branch(&mut self, expr: &Expression) {
+        match expr {
+            Expression::Block(block) => self.block_expression(block),
+            other => {
+                self.write("{ ");
+                self.expr(other);
+                self.write(" }");
+            }
+        }
+    }
+
+    fn if_expression(&mut self, if_expr: &IfExpr) {
+        self.write("if ");
+        self.condition(&if_expr.condition);
+        self.write(" ");
+        self.branch(&if_expr.then_branch);
+        match if_expr.else_branch.as_deref() {
+            Some(Expression::If(else_if)) => {
+                self.write(" else ");
+                self.if_expression(else_if);
+            }
+            Some(other) => {
+                self.write(" else ");
+                self.branch(other);
+            }
+            None => {}
+        }
+    }
+
+    fn match_expression(&mut self, match_expr: &MatchExpr) {
+        self.write("match ");
+        self.condition(&match_expr.expr);
+        self.write(" {");
+        self.indent += 1;
+        for arm in &match_expr.arms {
+            self.newline();
+            self.pattern(&arm.pattern);
+            if let Some(guard) = &arm.guard {
+                self.write(" if ");
+                self.expr(guard);
+            }
+            self.write(" => ");
+            // アームの値が `{` で始まる場合はブロック式として解析されるため、ブロック以外は囲む
+            match &arm.expr {
+                Expression::Block(block) => self.block_expression(block),
+                other => self.statement_expr(other, false),
+            }
+            self.write(",");
+        }
+        self.indent -= 1;
+        self.newline();
+        self.write("}");
+    }
+}
+
+fn type_params(params: &[TypeParam]) -> String {
+    if params.is_empty() {
+        String::new()
+    } else {
+        format!("<{}>", join(params.iter().map(|p| p.name.clone())))
+    }
+}
+
+fn param(param: &Param) -> String {
+    let prefix = if param.is_mut { "mut " } else { "" };
+    format!("{}{}: {}", prefix, param.name, print_type(&param.ty))
+}
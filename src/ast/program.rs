@@ -3,6 +3,17 @@
 use serde::{Deserialize, Serialize};
 
 use super::{Item, Span};
+use crate::error::{YuniError, YuniResult};
+
+/// JSON形式のASTのバージョン
+///
+/// ASTの定義を互換性のない形で変更したら上げます。
+pub const AST_FORMAT_VERSION: u32 = 2;
+
+/// `--emit=ast-json` の出力のスパンの表し方
+///
+/// スパンの `start` と `end` は、ソースファイルの先頭からのUTF-8のバイト単位の位置です（`end` は含まない）。
+pub const AST_SPAN_ENCODING: &str = "utf8-byte-offsets";
 
 /// ASTのルートノード（完全なYuniプログラムを表す）
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -13,10 +24,79 @@ pub struct Program {
     pub span: Span,
 }
 
+/// `Program::to_json` の出力（プログラムの最上位にバージョンを加える）
+#[derive(Serialize)]
+struct VersionedProgram<'a> {
+    format_version: u32,
+    #[serde(flatten)]
+    program: &'a Program,
+}
+
+/// `Program::to_ast_json` の出力（バージョンに加えてソースファイルとスパンの表し方を示す）
+#[derive(Serialize)]
+struct AstDocument<'a> {
+    format_version: u32,
+    source_file: &'a str,
+    span_encoding: &'static str,
+    #[serde(flatten)]
+    program: &'a Program,
+}
+
+/// バージョンだけを先に読むための `Program::from_json` の入力
+#[derive(Deserialize)]
+struct FormatHeader {
+    format_version: Option<u32>,
+}
+
+impl Program {
+    /// バージョン付きのJSONに変換
+    pub fn to_json(&self) -> String {
+        let versioned = VersionedProgram {
+            format_version: AST_FORMAT_VERSION,
+            program: self,
+        };
+        serde_json::to_string_pretty(&versioned).expect("ASTはJSONに変換できる")
+    }
+
+    /// ツール向けのJSON文書に変換（`--emit=ast-json`）
+    ///
+    /// 構文解析したままのASTを、バージョン・ソースファイル名・スパンの表し方とともに出力します。
+    /// `from_json` で読み戻せます。
+    pub fn to_ast_json(&self, source_file: &str) -> String {
+        let document = AstDocument {
+            format_version: AST_FORMAT_VERSION,
+            source_file,
+            span_encoding: AST_SPAN_ENCODING,
+            program: self,
+        };
+        serde_json::to_string_pretty(&document).expect("ASTはJSONに変換できる")
+    }
+
+    /// `to_json` の出力から読み込む（バージョンが異なる場合はエラー）
+    pub fn from_json(json: &str) -> YuniResult<Self> {
+        let invalid = |e: serde_json::Error| YuniError::Other(format!("Invalid AST JSON: {}", e));
+        let header: FormatHeader = serde_json::from_str(json).map_err(invalid)?;
+        match header.format_version {
+            Some(AST_FORMAT_VERSION) => serde_json::from_str(json).map_err(invalid),
+            Some(found) => Err(YuniError::Other(format!(
+                "Unsupported AST format version {} (this compiler reads version {})",
+                found, AST_FORMAT_VERSION
+            ))),
+            None => Err(YuniError::Other(format!(
+                "AST JSON has no format_version field (this compiler reads version {})",
+                AST_FORMAT_VERSION
+            ))),
+        }
+    }
+}
+
 /// パッケージ宣言
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct PackageDecl {
     pub name: String,
+    /// 標準ライブラリのプレリュードを読み込まない（`package main no_prelude`）
+    #[serde(default)]
+    pub no_prelude: bool,
     pub span: Span,
 }
 
@@ -25,5 +105,23 @@ pub struct PackageDecl {
 pub struct Import {
     pub path: String,
     pub alias: Option<String>,
+    /// 選択してインポートする要素（空ならパス全体のインポート）
+    #[serde(default)]
+    pub symbols: Vec<ImportSymbol>,
+    pub span: Span,
+}
+
+/// 選択インポートの要素（`import "std/collections" { HashMap as Map }` の `HashMap as Map`）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ImportSymbol {
+    pub name: String,
+    pub alias: Option<String>,
     pub span: Span,
+}
+
+impl ImportSymbol {
+    /// ファイル内で使う名前（別名があれば別名）
+    pub fn local_name(&self) -> &str {
+        self.alias.as_deref().unwrap_or(&self.name)
+    }
 }
\ No newline at end of file
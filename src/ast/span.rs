@@ -3,7 +3,7 @@
 use serde::{Deserialize, Serialize};
 
 /// ソース位置情報を追跡するためのスパン情報
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct Span {
     pub start: usize,
     pub end: usize,
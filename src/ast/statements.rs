@@ -2,7 +2,7 @@
 
 use serde::{Deserialize, Serialize};
 
-use super::{Expression, Pattern, Span, Type};
+use super::{Expression, FunctionDecl, Pattern, Span, Type};
 
 /// 文
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -15,6 +15,9 @@ pub enum Statement {
     For(ForStatement),
     Expression(Expression),
     Block(Block),
+    Defer(DeferStatement),
+    /// 関数の中で定義した関数（ローカル関数）
+    Function(FunctionDecl),
 }
 
 /// let文
@@ -75,6 +78,15 @@ pub struct ForStatement {
     pub span: Span,
 }
 
+/// defer文
+///
+/// 囲んでいるブロックを抜けるときに、登録と逆順で本体を実行します。
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DeferStatement {
+    pub body: Block,
+    pub span: Span,
+}
+
 /// ブロック
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Block {
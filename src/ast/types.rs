@@ -43,6 +43,9 @@ pub enum Type {
     // ジェネリック型
     Variable(String), // 型変数（例：T, U）
     Generic(String, Vec<Type>), // ジェネリック型（例：Vec<T>, HashMap<K, V>）
+
+    // 解析に失敗した式の型（解析だけが使い、どの型とも互換として扱う）
+    Error,
 }
 
 /// 関数型
@@ -67,9 +70,21 @@ pub struct LivesClause {
 }
 
 /// ライフタイム制約
+///
+/// `lives return: a + b` は、戻り値の参照が引数 `a` か `b` から借用されることを表します。
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct LivesConstraint {
     pub target: String,
     pub sources: Vec<String>,
     pub span: Span,
 }
+
+impl LivesConstraint {
+    /// 戻り値を表すターゲット
+    pub const RETURN_TARGET: &'static str = "return";
+
+    /// 戻り値の借用元を指定する制約かどうか
+    pub fn is_return(&self) -> bool {
+        self.target == Self::RETURN_TARGET
+    }
+}
@@ -1,20 +1,29 @@
 //! メインコード生成器
 
+use crate::analyzer::local_functions::{contains_local_functions, hoist_local_functions};
+use crate::analyzer::{SourceMap, TypeTable};
+use crate::assertion::SourceText;
 use crate::ast::*;
 use crate::error::{CodegenError, YuniError, YuniResult};
 use inkwell::builder::Builder;
 use inkwell::context::Context as LLVMContext;
-use inkwell::module::Module;
+use inkwell::module::{FlagBehavior, Linkage, Module};
 use inkwell::passes::PassManager;
-use inkwell::targets::{Target, TargetMachine};
-use inkwell::types::{BasicTypeEnum, BasicType};
-use inkwell::values::{FunctionValue, PointerValue, IntValue, BasicValueEnum};
-use inkwell::{OptimizationLevel, AddressSpace};
-use std::collections::HashMap;
+use inkwell::targets::TargetData;
+use inkwell::types::{BasicTypeEnum, BasicType, StructType};
+use inkwell::values::{BasicMetadataValueEnum, FunctionValue, PointerValue, IntValue, BasicValueEnum};
+use inkwell::AddressSpace;
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
 
+use super::interner::{Interner, Symbol};
+use super::layout::{EnumLayout, FieldLayout, StructLayout, VariantLayout, VariantRef};
+use super::naming::{self, ValueNames};
 use super::runtime::RuntimeManager;
-use super::symbol_table::{ScopeManager, StructInfo};
-use super::tail_call::TailContext;
+use super::symbol_table::ScopeManager;
+use super::defer::DeferScope;
+use super::options::CodegenOptions;
+use super::stats::CodegenStats;
 use super::types::TypeManager;
 
 /// メインコード生成器構造体
@@ -22,6 +31,8 @@ pub struct CodeGenerator<'ctx> {
     pub context: &'ctx LLVMContext,
     pub module: Module<'ctx>,
     pub builder: Builder<'ctx>,
+    // エントリブロックにallocaを挿入するビルダー（関数をまたいで使い回す）
+    pub alloca_builder: Builder<'ctx>,
     pub pass_manager: PassManager<FunctionValue<'ctx>>,
 
     // マネージャー
@@ -33,80 +44,154 @@ pub struct CodeGenerator<'ctx> {
     pub functions: HashMap<String, FunctionValue<'ctx>>,
     // 関数の戻り値型情報
     pub function_types: HashMap<String, Type>,
+    // 関数の引数の型情報（メソッドはレシーバーを除く）
+    pub function_param_types: HashMap<String, Vec<Type>>,
+    // 外部関数の名前（`extern fn` で宣言した関数）
+    pub extern_functions: HashSet<String>,
+    // 外部関数の宣言で指定したリンクするライブラリ（宣言順、重複なし）
+    pub link_libraries: Vec<String>,
+    // 本体を別のモジュールで生成した関数の名前（宣言だけを生成する）
+    pub defined_elsewhere: HashSet<String>,
     
-    // 構造体のフィールド情報
-    pub struct_info: HashMap<String, StructInfo>,
+    // 構造体のレイアウト（型の宣言時に計算し、以降は読み取るだけ）
+    pub struct_layouts: HashMap<String, Rc<StructLayout<'ctx>>>,
     
     // 構造体のメソッド情報 (構造体名 -> Vec<(メソッド名, 関数名)>)
     pub struct_methods: HashMap<String, Vec<(String, String)>>,
     
-    // Enumのバリアント情報（名前 -> (Enum名, バリアントインデックス)）
-    pub enum_variants: HashMap<(String, String), u32>,
+    // Enum名・バリアント名の名前表（型の宣言時に登録する）
+    pub names: Interner,
+    // Enumのレイアウト（型の宣言時に計算し、以降は読み取るだけ）
+    pub enum_layouts: HashMap<Symbol, Rc<EnumLayout<'ctx>>>,
 
     // 現在コンパイル中の関数
     pub current_function: Option<FunctionValue<'ctx>>,
     // 現在の関数の戻り値型（型推論用）
     pub current_return_type: Option<Type>,
-    // 末尾呼び出しコンテキスト
-    pub tail_context: TailContext,
+    // ブロックごとに登録されたdefer（内側のブロックが末尾）
+    pub defer_scopes: Vec<DeferScope>,
+    // コンパイル中のループのネストの深さ（ループの基本ブロック名の区別用）
+    pub loop_depth: usize,
+    // 現在の関数の定数のグローバル変数の名前（関数ごとの連番）
+    pub value_names: ValueNames,
+    // セマンティック解析で決定した式の型
+    pub type_table: TypeTable,
+    // 単相化で生成したアイテムから元のジェネリック定義への対応表
+    pub source_map: SourceMap,
+    // ターゲットのデータレイアウト（型のサイズとアライメントの計算用）
+    pub target_data: TargetData,
+    // モジュールとオブジェクトファイルの生成設定
+    pub options: CodegenOptions,
+    // アサーションの失敗時のメッセージに引用するソースコード
+    pub source_text: Option<SourceText>,
+    // 関数ごとの作業用の状態の統計
+    pub stats: CodegenStats,
 }
 
 impl<'ctx> CodeGenerator<'ctx> {
     /// 型のサイズを取得（バイト単位）
+    ///
+    /// ターゲットのデータレイアウトに従い、末尾のパディングを含むサイズを返します。
+    /// このサイズはアライメントの倍数になるため、要素を隙間なく並べても整列が保たれます。
     pub fn get_size_of_type(&self, ty: BasicTypeEnum<'ctx>) -> u64 {
-        match ty {
-            BasicTypeEnum::IntType(int_type) => {
-                // ビット数をバイト数に変換
-                (int_type.get_bit_width() / 8) as u64
-            }
-            BasicTypeEnum::FloatType(float_type) => {
-                // FloatTypeのビット幅を取得
-                match float_type {
-                    t if t == self.context.f32_type() => 4,
-                    t if t == self.context.f64_type() => 8,
-                    t if t == self.context.f128_type() => 16,
-                    _ => 8, // デフォルト
-                }
-            }
-            BasicTypeEnum::PointerType(_) => {
-                // ポインタサイズ（64ビットシステムを仮定）
-                8
-            }
-            BasicTypeEnum::StructType(struct_type) => {
-                // 構造体の場合、各フィールドのサイズの合計（簡易実装）
-                // 実際にはアライメントを考慮する必要がある
-                let mut size = 0u64;
-                for i in 0..struct_type.count_fields() {
-                    if let Some(field_type) = struct_type.get_field_type_at_index(i) {
-                        size += self.get_size_of_type(field_type);
-                    }
-                }
-                size
-            }
-            BasicTypeEnum::ArrayType(array_type) => {
-                let element_size = self.get_size_of_type(array_type.get_element_type());
-                element_size * array_type.len() as u64
-            }
-            BasicTypeEnum::VectorType(vector_type) => {
-                let element_size = self.get_size_of_type(vector_type.get_element_type());
-                element_size * vector_type.get_size() as u64
-            }
-            BasicTypeEnum::ScalableVectorType(vector_type) => {
-                let element_size = self.get_size_of_type(vector_type.get_element_type());
-                element_size * vector_type.get_size() as u64
-            }
-        }
+        self.target_data.get_abi_size(&ty)
+    }
+
+    /// 型のアライメントを取得（バイト単位）
+    pub fn get_align_of_type(&self, ty: BasicTypeEnum<'ctx>) -> u64 {
+        self.target_data.get_abi_alignment(&ty) as u64
+    }
+
+    /// 構造体のフィールドの位置を取得
+    ///
+    /// 構造体型・フィールドのインデックス・フィールドのLLVMの型を返します。
+    /// レイアウトは型の宣言時に計算済みのため、ここでは参照するだけです。
+    pub fn struct_field_slot(
+        &self,
+        struct_name: &str,
+        field: &str,
+        span: Span,
+    ) -> YuniResult<(StructType<'ctx>, u32, BasicTypeEnum<'ctx>)> {
+        let layout = self.struct_layouts.get(struct_name)
+            .ok_or_else(|| YuniError::Codegen(CodegenError::Undefined {
+                name: struct_name.to_string(),
+                span,
+            }))?;
+        let (index, field_layout) = layout.field(field)
+            .ok_or_else(|| YuniError::Codegen(CodegenError::Undefined {
+                name: format!("{}.{}", struct_name, field),
+                span,
+            }))?;
+        Ok((layout.llvm_type, index, field_layout.llvm_type))
     }
 
-    pub fn new(context: &'ctx LLVMContext, module_name: &str) -> Self {
-        let module = context.create_module(module_name);
+    /// Enumのレイアウトを登録（Enum名とバリアント名を名前表に登録する）
+    pub fn register_enum_layout(&mut self, enum_name: &str, llvm_type: BasicTypeEnum<'ctx>, variants: Vec<VariantLayout<'ctx>>) {
+        let symbol = self.names.intern(enum_name);
+        let layout = EnumLayout::new(llvm_type, variants, &mut self.names);
+        self.enum_layouts.insert(symbol, Rc::new(layout));
+    }
+
+    /// 名前でEnumのレイアウトを取得
+    pub fn enum_layout(&self, enum_name: &str) -> Option<&Rc<EnumLayout<'ctx>>> {
+        self.enum_layouts.get(&self.names.get(enum_name)?)
+    }
+
+    /// Enum名とバリアント名をバリアントに解決
+    ///
+    /// 存在しないバリアントのエラーには、そのEnumの有効なバリアントの一覧を含めます。
+    pub fn resolve_variant(&self, enum_name: &str, variant: &str, span: Span) -> YuniResult<VariantRef<'ctx>> {
+        let layout = self.enum_layout(enum_name)
+            .ok_or_else(|| YuniError::Codegen(CodegenError::Undefined {
+                name: enum_name.to_string(),
+                span,
+            }))?;
+        let id = self.names.get(variant)
+            .and_then(|symbol| layout.variant_id(symbol))
+            .ok_or_else(|| YuniError::Codegen(CodegenError::UndefinedVariant {
+                enum_name: enum_name.to_string(),
+                variant: variant.to_string(),
+                valid: layout.variant_names(),
+                span,
+            }))?;
+        Ok(VariantRef { layout: Rc::clone(layout), id })
+    }
+
+    /// Enumのバリアントのフィールドを宣言順に並べた構造体型を取得
+    pub fn variant_payload_type(&self, fields: &[Field]) -> YuniResult<StructType<'ctx>> {
+        let field_types: Vec<BasicTypeEnum> = fields
+            .iter()
+            .map(|field| self.type_manager.ast_type_to_llvm(&field.ty))
+            .collect::<YuniResult<Vec<_>>>()?;
+        Ok(self.context.struct_type(&field_types, false))
+    }
+
+    pub fn new(context: &'ctx LLVMContext, options: impl Into<CodegenOptions>) -> Self {
+        let options = options.into();
+        let module = context.create_module(&options.source_path);
         let builder = context.create_builder();
         let pass_manager = PassManager::create(&module);
 
-        // ターゲット情報を設定
-        Target::initialize_all(&inkwell::targets::InitializationConfig::default());
-        let target_triple = TargetMachine::get_default_triple();
+        // モジュール情報とターゲットを設定
+        // ターゲットマシンを作成できない場合はLLVMのデフォルトのデータレイアウトを使用する
+        module.set_source_file_name(&options.source_path);
+        let target_machine = options.target_machine().ok();
+        let target_triple = target_machine
+            .as_ref()
+            .map(|machine| machine.get_triple())
+            .unwrap_or_else(|| options.triple());
         module.set_triple(&target_triple);
+        let target_data = target_machine
+            .map(|machine| machine.get_target_data())
+            .unwrap_or_else(|| TargetData::create(""));
+        module.set_data_layout(&target_data.get_data_layout());
+        if options.pic {
+            // clangが `-fPIE` で付けるものと同じモジュールフラグ（2はlarge PIC/PIE）
+            // 生成するオブジェクトファイルは常に実行ファイルにリンクするため、PIE Levelも付ける
+            let level = context.i32_type().const_int(2, false);
+            module.add_basic_value_flag("PIC Level", FlagBehavior::Error, level);
+            module.add_basic_value_flag("PIE Level", FlagBehavior::Error, level);
+        }
 
         // パスマネージャを初期化
         pass_manager.initialize();
@@ -121,34 +206,90 @@ impl<'ctx> CodeGenerator<'ctx> {
             context,
             module,
             builder,
+            alloca_builder: context.create_builder(),
             pass_manager,
             scope_manager: ScopeManager::new(),
             type_manager,
             runtime_manager,
             functions: HashMap::new(),
             function_types: HashMap::new(),
-            struct_info: HashMap::new(),
+            function_param_types: HashMap::new(),
+            extern_functions: HashSet::new(),
+            link_libraries: Vec::new(),
+            defined_elsewhere: HashSet::new(),
+            struct_layouts: HashMap::new(),
             struct_methods: HashMap::new(),
-            enum_variants: HashMap::new(),
+            names: Interner::new(),
+            enum_layouts: HashMap::new(),
             current_function: None,
             current_return_type: None,
-            tail_context: TailContext::new(),
+            defer_scopes: Vec::new(),
+            loop_depth: 0,
+            value_names: ValueNames::default(),
+            type_table: TypeTable::new(),
+            source_map: SourceMap::default(),
+            target_data,
+            options,
+            source_text: None,
+            stats: CodegenStats::default(),
         }
     }
+
+    /// 単相化で生成したアイテムの由来の対応表を設定
+    ///
+    /// 生成した関数の中で発生したエラーには、元のジェネリック定義と
+    /// インスタンス化を要求した箇所が付きます。
+    pub fn with_source_map(mut self, source_map: SourceMap) -> Self {
+        self.source_map = source_map;
+        self
+    }
+    
+    /// アサーションの失敗時のメッセージに引用するソースコードを設定
+    ///
+    /// 設定しない場合、メッセージの式はASTから出力し、位置を省きます。
+    pub fn with_source_text(mut self, source_text: SourceText) -> Self {
+        self.source_text = Some(source_text);
+        self
+    }
     
+    /// 本体を別のモジュールで生成した関数を設定
+    ///
+    /// 指定した関数は呼び出しのために宣言だけを生成し、本体はコンパイルしません。
+    /// REPLが定義ごとに作るモジュールから、以前のモジュールの関数を呼び出すために使います。
+    pub fn with_functions_defined_elsewhere(mut self, names: impl IntoIterator<Item = String>) -> Self {
+        self.defined_elsewhere = names.into_iter().collect();
+        self
+    }
+
     /// LLVMモジュールを取得
     #[allow(dead_code)]
     pub fn get_module(&self) -> &Module<'ctx> {
         &self.module
     }
 
+    /// セマンティック解析の型テーブルを使ってプログラム全体をコンパイル
+    ///
+    /// 式の型はテーブルから取得し、記録のない式に限り従来の推論を使用します。
+    pub fn compile_program_with_types(&mut self, program: &Program, type_table: TypeTable) -> YuniResult<()> {
+        self.type_table = type_table;
+        self.compile_program(program)
+    }
+
     /// プログラム全体をコンパイル
     pub fn compile_program(&mut self, program: &Program) -> YuniResult<()> {
+        // ローカル関数は一意な名前のトップレベルの関数として生成する
+        // （単相化を経たプログラムは取り出し済み）
+        if contains_local_functions(program) {
+            let mut hoisted = program.clone();
+            hoist_local_functions(&mut hoisted);
+            return self.compile_program(&hoisted);
+        }
+
         // 第一パス: すべての型を宣言
-        for item in &program.items {
-            if let Item::TypeDef(type_def) = item {
-                self.declare_type(type_def)?;
-            }
+        // 名前を先に宣言してから、値として含まれる型から順に本体を設定する
+        self.predeclare_types(program);
+        for type_def in type_declaration_order(program) {
+            self.declare_type(type_def)?;
         }
 
         // 第二パス: すべての関数を宣言
@@ -160,6 +301,9 @@ impl<'ctx> CodeGenerator<'ctx> {
                 Item::Method(method) => {
                     self.declare_method(method)?;
                 }
+                Item::ExternFunction(func) => {
+                    self.declare_extern_function(func)?;
+                }
                 _ => {}
             }
         }
@@ -167,50 +311,155 @@ impl<'ctx> CodeGenerator<'ctx> {
         // 第三パス: 関数本体をコンパイル
         for item in &program.items {
             match item {
+                Item::Function(func) if self.defined_elsewhere.contains(&func.name) => {}
                 Item::Function(func) => {
-                    self.compile_function(func)?;
+                    let result = self.compile_function(func);
+                    self.finish_function();
+                    result.map_err(|e| match self.source_map.origin(&func.name) {
+                        Some(origin) => origin.wrap_error(e),
+                        None => e,
+                    })?;
                 }
                 Item::Method(method) => {
-                    self.compile_method(method)?;
+                    let result = self.compile_method(method);
+                    self.finish_function();
+                    result?;
                 }
                 _ => {}
             }
         }
 
+        log::debug!(target: "yuni::codegen", "compiled {}", self.stats);
         Ok(())
     }
 
+    /// コード生成で保持したオブジェクトの数
+    pub fn stats(&self) -> CodegenStats {
+        self.stats
+    }
+
+    /// 関数のコンパイルを終え、関数ごとの作業用の状態を解放する
+    ///
+    /// エラーで途中で抜けた場合も呼び出し、次の関数に状態を持ち越さないようにします。
+    fn finish_function(&mut self) {
+        self.stats.functions += 1;
+        self.stats.peak_variables = self.stats.peak_variables.max(self.scope_manager.peak_symbols());
+        self.stats.peak_scopes = self.stats.peak_scopes.max(self.scope_manager.peak_depth());
+
+        self.scope_manager.reset();
+        self.defer_scopes = Vec::new();
+        self.loop_depth = 0;
+        self.value_names.leave_function();
+        self.current_function = None;
+        self.current_return_type = None;
+        self.builder.clear_insertion_position();
+        self.alloca_builder.clear_insertion_position();
+
+        self.stats.retained = self.scope_manager.symbol_count()
+            + self.scope_manager.depth()
+            - 1
+            + self.defer_scopes.len()
+            + self.value_names.kind_count();
+    }
+
+    /// すべての型の名前を本体なしで宣言
+    ///
+    /// 構造体とフィールドを持つ列挙型は名前付きの構造体型として先に作り、
+    /// 参照を介して自身や互いを含む型でも、Cコンパイラの前方宣言のようにフィールドの型を変換できるようにします。
+    fn predeclare_types(&mut self, program: &Program) {
+        for item in &program.items {
+            let Item::TypeDef(type_def) = item else {
+                continue;
+            };
+            match type_def {
+                TypeDef::Struct(struct_def) => {
+                    self.type_manager.declare_struct(&struct_def.name);
+                }
+                TypeDef::Enum(enum_def) if enum_def.variants.iter().all(|v| v.fields.is_empty()) => {
+                    self.type_manager.register_enum(enum_def.name.clone(), self.context.i32_type().into());
+                }
+                TypeDef::Enum(enum_def) => {
+                    self.type_manager.declare_enum(&enum_def.name);
+                }
+                TypeDef::Alias(type_alias) => {
+                    self.type_manager.register_type_alias(
+                        type_alias.name.clone(),
+                        type_alias.underlying_type.clone()
+                    );
+                }
+            }
+        }
+    }
+
     /// 型を宣言（構造体、列挙型、または型エイリアス）
     fn declare_type(&mut self, type_def: &TypeDef) -> YuniResult<()> {
         match type_def {
             TypeDef::Struct(struct_def) => {
-                let field_types: Vec<BasicTypeEnum> = struct_def
+                let fields: Vec<FieldLayout> = struct_def
                     .fields
                     .iter()
-                    .map(|field| self.type_manager.ast_type_to_llvm(&field.ty))
+                    .map(|field| {
+                        Ok(FieldLayout {
+                            name: field.name.clone(),
+                            ty: field.ty.clone(),
+                            llvm_type: self.type_manager.ast_type_to_llvm(&field.ty)?,
+                        })
+                    })
                     .collect::<YuniResult<Vec<_>>>()?;
+                let field_types: Vec<BasicTypeEnum> = fields.iter().map(|field| field.llvm_type).collect();
 
-                let struct_type = self.context.struct_type(&field_types, false);
-                self.type_manager.register_struct(struct_def.name.clone(), struct_type);
-                
-                // フィールド情報を保存
-                let mut struct_info = StructInfo::new();
-                for field in struct_def.fields.iter() {
-                    struct_info.add_field(field.name.clone(), field.ty.clone());
-                }
-                self.struct_info.insert(struct_def.name.clone(), struct_info);
+                let struct_type = match self.type_manager.get_struct(&struct_def.name) {
+                    Some(struct_type) => struct_type,
+                    None => self.type_manager.declare_struct(&struct_def.name),
+                };
+                struct_type.set_body(&field_types, false);
+
+                let size = self.get_size_of_type(struct_type.into());
+                let align = self.get_align_of_type(struct_type.into());
+                self.struct_layouts.insert(
+                    struct_def.name.clone(),
+                    Rc::new(StructLayout::new(struct_type, fields, size, align)),
+                );
             }
             TypeDef::Enum(enum_def) => {
-                // Enumは整数型として表現
-                // 各バリアントに0から順にインデックスを割り当てる
-                for (index, variant) in enum_def.variants.iter().enumerate() {
-                    let key = (enum_def.name.clone(), variant.name.clone());
-                    self.enum_variants.insert(key, index as u32);
-                }
+                // 各バリアントに0から順にタグを割り当てる
+                let variants: Vec<VariantLayout> = enum_def
+                    .variants
+                    .iter()
+                    .enumerate()
+                    .map(|(index, variant)| {
+                        Ok(VariantLayout {
+                            name: variant.name.clone(),
+                            tag: index as u32,
+                            fields: variant.fields.clone(),
+                            payload_type: self.variant_payload_type(&variant.fields)?,
+                        })
+                    })
+                    .collect::<YuniResult<Vec<_>>>()?;
                 
-                // Enum型をi32として登録
-                let enum_type = self.context.i32_type();
+                // フィールドを持たないEnumはタグ(i32)だけで表現する
+                // フィールドを持つEnumは { タグ(i32), 最も大きいバリアントのフィールドが収まる領域 } で表現する
+                let enum_type: BasicTypeEnum = if enum_def.variants.iter().all(|v| v.fields.is_empty()) {
+                    self.context.i32_type().into()
+                } else {
+                    let mut size = 0;
+                    let mut align = 1;
+                    for variant in &variants {
+                        size = size.max(self.get_size_of_type(variant.payload_type.into()));
+                        align = align.max(self.get_align_of_type(variant.payload_type.into()));
+                    }
+                    // 領域はアライメントの幅の整数の配列にして、どのバリアントのフィールドも整列させる
+                    let slot_type = self.context.custom_width_int_type((align * 8) as u32);
+                    let payload_area = slot_type.array_type(size.div_ceil(align) as u32);
+                    let enum_type = match self.type_manager.get_enum(&enum_def.name) {
+                        Some(BasicTypeEnum::StructType(enum_type)) => enum_type,
+                        _ => self.type_manager.declare_enum(&enum_def.name),
+                    };
+                    enum_type.set_body(&[self.context.i32_type().into(), payload_area.into()], false);
+                    enum_type.into()
+                };
                 self.type_manager.register_enum(enum_def.name.clone(), enum_type);
+                self.register_enum_layout(&enum_def.name, enum_type, variants);
             }
             TypeDef::Alias(type_alias) => {
                 // 型エイリアスをTypeManagerに登録
@@ -235,8 +484,47 @@ impl<'ctx> CodeGenerator<'ctx> {
         let fn_type = self.type_manager.create_function_type(&param_types, return_type, false)?;
 
         let function = self.module.add_function(&func.name, fn_type, None);
+        self.add_inline_attribute(function, self.function_inline_hint(func));
+        self.add_stack_probe_attribute(function);
+        self.functions.insert(func.name.clone(), function);
+        self.function_types.insert(func.name.clone(), return_type.clone());
+        self.function_param_types.insert(func.name.clone(), param_types);
+
+        Ok(())
+    }
+
+    /// 外部関数を宣言
+    ///
+    /// Cのシンボル名で本体のない関数を宣言します。ランタイムが既に宣言している関数
+    /// （`strlen` など）は、型が一致すればその宣言をそのまま使います。
+    fn declare_extern_function(&mut self, func: &ExternFunctionDecl) -> YuniResult<()> {
+        let param_types: Vec<Type> = func.params.iter().map(|param| param.ty.clone()).collect();
+        let return_type = func.return_type.as_deref().unwrap_or(&Type::Void);
+        let fn_type = self.type_manager.create_function_type(&param_types, return_type, false)?;
+
+        let function = match self.module.get_function(func.symbol()) {
+            Some(existing) if existing.get_type() == fn_type => existing,
+            Some(_) => {
+                return Err(YuniError::Codegen(CodegenError::InvalidType {
+                    message: format!(
+                        "Extern function {} does not match the existing declaration of {}",
+                        func.name,
+                        func.symbol()
+                    ),
+                    span: func.span,
+                }))
+            }
+            None => self.module.add_function(func.symbol(), fn_type, Some(Linkage::External)),
+        };
         self.functions.insert(func.name.clone(), function);
         self.function_types.insert(func.name.clone(), return_type.clone());
+        self.function_param_types.insert(func.name.clone(), param_types);
+        self.extern_functions.insert(func.name.clone());
+        if let Some(library) = &func.link {
+            if !self.link_libraries.contains(library) {
+                self.link_libraries.push(library.clone());
+            }
+        }
 
         Ok(())
     }
@@ -270,15 +558,15 @@ impl<'ctx> CodeGenerator<'ctx> {
         }
 
         let return_type = method.return_type.as_deref().unwrap_or(&Type::Void);
-        let fn_type = self.type_manager.create_function_type(
-            &method.params.iter().map(|p| p.ty.clone()).collect::<Vec<_>>(),
-            return_type,
-            false,
-        )?;
+        let method_param_types: Vec<Type> = method.params.iter().map(|p| p.ty.clone()).collect();
+        let fn_type = self.type_manager.create_function_type(&method_param_types, return_type, false)?;
 
         let function = self.module.add_function(&method_name, fn_type, None);
+        self.add_inline_attribute(function, method.inline);
+        self.add_stack_probe_attribute(function);
         self.functions.insert(method_name.clone(), function);
         self.function_types.insert(method_name.clone(), return_type.clone());
+        self.function_param_types.insert(method_name.clone(), method_param_types);
         
         // 構造体とメソッドの関連を保存
         self.struct_methods
@@ -291,6 +579,7 @@ impl<'ctx> CodeGenerator<'ctx> {
 
     /// 関数をコンパイル
     fn compile_function(&mut self, func: &FunctionDecl) -> YuniResult<()> {
+        log::trace!(target: "yuni::codegen", "compiling function {}", func.name);
         let function = *self.functions.get(&func.name)
             .ok_or_else(|| YuniError::Codegen(CodegenError::Internal { 
                 message: format!("Function {} not found", func.name) 
@@ -298,30 +587,23 @@ impl<'ctx> CodeGenerator<'ctx> {
 
         self.current_function = Some(function);
         self.current_return_type = func.return_type.as_ref().map(|t| (**t).clone());
-        
-        // 末尾位置解析を実行
-        self.tail_context.analyze_function(func);
+        self.value_names.enter_function(&func.name);
 
         // エントリブロックを作成
         let entry = self.context.append_basic_block(function, "entry");
         self.builder.position_at_end(entry);
 
+        // プログラムの入口ではまずランタイムを初期化
+        if func.name == "main" {
+            self.build_runtime_init()?;
+        }
+
         // 新しいスコープを作成
         self.scope_manager.push_scope();
 
         // パラメータをスコープに追加
         for (i, param) in func.params.iter().enumerate() {
-            let param_value = function
-                .get_nth_param(i as u32)
-                .ok_or_else(|| YuniError::Codegen(CodegenError::Internal { 
-                    message: format!("Parameter {} not found", i) 
-                }))?;
-
-            // パラメータ用のスタック領域を確保
-            let alloca = self.create_entry_block_alloca(&param.name, &param.ty)?;
-            self.builder.build_store(alloca, param_value)?;
-
-            self.add_variable(&param.name, alloca, param.ty.clone(), true)?;
+            self.bind_parameter(function, i as u32, &param.name, &param.ty, param.is_mut)?;
         }
 
         // 関数本体をコンパイル
@@ -339,19 +621,38 @@ impl<'ctx> CodeGenerator<'ctx> {
         if function.verify(true) {
             self.pass_manager.run_on(&function);
         } else {
-            // 検証失敗時にLLVM IRを出力してデバッグ
-            eprintln!("Function verification failed: {}", func.name);
-            function.print_to_stderr();
+            // 検証に失敗した関数のLLVM IRはデバッグ用にログに出す
+            log::debug!(target: "yuni::codegen", "invalid IR for {}:\n{}", func.name, function.print_to_string().to_string());
             return Err(YuniError::Codegen(CodegenError::Internal {
                 message: format!("Function verification failed: {}", func.name),
             }));
         }
 
-        self.current_function = None;
-        self.current_return_type = None;
         Ok(())
     }
 
+    /// `index` 番目の引数をパラメータ `name` としてスコープに追加
+    ///
+    /// 引数の値は名前を付けたスタック領域に格納し、ローカル変数と同じように扱います。
+    /// 参照の作成や `mut` のパラメータへの代入も変数と同じ処理で行え、
+    /// `-O1` 以上では最適化のパスがスタック領域をレジスタに戻します。
+    fn bind_parameter(&mut self, function: FunctionValue<'ctx>, index: u32, name: &str, ty: &Type, is_mut: bool) -> YuniResult<()> {
+        let param_value = function
+            .get_nth_param(index)
+            .ok_or_else(|| YuniError::Codegen(CodegenError::Internal {
+                message: format!("Parameter {} not found", name)
+            }))?;
+
+        param_value.set_name(&naming::argument_name(name));
+        // `_` のパラメータは参照できないため、スタック領域もスコープの変数も作らない
+        if name == "_" {
+            return Ok(());
+        }
+        let alloca = self.create_entry_block_alloca(&naming::address_name(name), ty)?;
+        self.builder.build_store(alloca, param_value)?;
+        self.add_variable(name, alloca, ty.clone(), is_mut)
+    }
+
     /// メソッドをコンパイル
     fn compile_method(&mut self, method: &MethodDecl) -> YuniResult<()> {
         let receiver_type_name = match &method.receiver.ty {
@@ -377,9 +678,11 @@ impl<'ctx> CodeGenerator<'ctx> {
             .ok_or_else(|| YuniError::Codegen(CodegenError::Internal { 
                 message: format!("Method {} not found", method_name) 
             }))?;
+        log::trace!(target: "yuni::codegen", "compiling method {}", method_name);
 
         self.current_function = Some(function);
         self.current_return_type = method.return_type.as_ref().map(|t| (**t).clone());
+        self.value_names.enter_function(&method_name);
 
         // エントリブロックを作成
         let entry = self.context.append_basic_block(function, "entry");
@@ -389,29 +692,12 @@ impl<'ctx> CodeGenerator<'ctx> {
         self.scope_manager.push_scope();
 
         // レシーバーをスコープに追加
-        let receiver_value = function
-            .get_nth_param(0)
-            .ok_or_else(|| YuniError::Codegen(CodegenError::Internal { 
-                message: "Receiver parameter not found".to_string() 
-            }))?;
-
-        let default_name = "self".to_string();
-        let receiver_name = method.receiver.name.as_ref().unwrap_or(&default_name);
-        let alloca = self.create_entry_block_alloca(receiver_name, &method.receiver.ty)?;
-        self.builder.build_store(alloca, receiver_value)?;
-        self.add_variable(receiver_name, alloca, method.receiver.ty.clone(), true)?;
+        let receiver_name = method.receiver.name.as_deref().unwrap_or("self");
+        self.bind_parameter(function, 0, receiver_name, &method.receiver.ty, method.receiver.is_mut)?;
 
         // その他のパラメータをスコープに追加
         for (i, param) in method.params.iter().enumerate() {
-            let param_value = function
-                .get_nth_param((i + 1) as u32)
-                .ok_or_else(|| YuniError::Codegen(CodegenError::Internal { 
-                    message: format!("Parameter {} not found", i) 
-                }))?;
-
-            let alloca = self.create_entry_block_alloca(&param.name, &param.ty)?;
-            self.builder.build_store(alloca, param_value)?;
-            self.add_variable(&param.name, alloca, param.ty.clone(), true)?;
+            self.bind_parameter(function, (i + 1) as u32, &param.name, &param.ty, param.is_mut)?;
         }
 
         // メソッド本体をコンパイル
@@ -434,8 +720,6 @@ impl<'ctx> CodeGenerator<'ctx> {
             }));
         }
 
-        self.current_function = None;
-        self.current_return_type = None;
         Ok(())
     }
 
@@ -443,6 +727,13 @@ impl<'ctx> CodeGenerator<'ctx> {
 
 
     
+    /// 関数の中の値（引数・基本ブロック・命令）の名前をすべて取り除く
+    ///
+    /// 名前の規則は `naming` モジュールを参照してください。
+    pub fn strip_value_names(&self) {
+        naming::strip_value_names(&self.module);
+    }
+
     /// LLVM IRをファイルに書き込む
     pub fn write_llvm_ir(&self, path: &std::path::Path) -> YuniResult<()> {
         self.module.print_to_file(path)
@@ -452,58 +743,72 @@ impl<'ctx> CodeGenerator<'ctx> {
     }
     
     /// オブジェクトファイルを生成
-    pub fn write_object_file(&self, path: &std::path::Path, opt_level: OptimizationLevel) -> YuniResult<()> {
-        use inkwell::targets::{Target, TargetMachine, RelocMode, CodeModel, FileType};
-        
-        Target::initialize_native(&inkwell::targets::InitializationConfig::default())
-            .map_err(|e| YuniError::Codegen(CodegenError::Internal {
-                message: format!("Failed to initialize native target: {}", e),
-            }))?;
-            
-        let target_triple = TargetMachine::get_default_triple();
-        let target = Target::from_triple(&target_triple)
-            .map_err(|e| YuniError::Codegen(CodegenError::Internal {
-                message: format!("Failed to get target: {}", e),
-            }))?;
-            
-        let target_machine = target
-            .create_target_machine(
-                &target_triple,
-                "generic",
-                "",
-                opt_level,
-                RelocMode::Default,
-                CodeModel::Default,
-            )
-            .ok_or_else(|| YuniError::Codegen(CodegenError::Internal {
-                message: "Failed to create target machine".to_string(),
-            }))?;
-            
-        target_machine
+    ///
+    /// モジュールと同じ設定（ターゲット・再配置モデル・最適化レベル）のターゲットマシンを使用します。
+    pub fn write_object_file(&self, path: &std::path::Path) -> YuniResult<()> {
+        use inkwell::targets::FileType;
+
+        self.assign_function_sections();
+        self.options
+            .target_machine()?
             .write_to_file(&self.module, FileType::Object, path)
             .map_err(|e| YuniError::Codegen(CodegenError::Internal {
                 message: format!("Failed to write object file: {}", e),
             }))
     }
-    
+
+    /// オブジェクトファイルの内容をメモリ上に生成
+    ///
+    /// `write_object_file` と同じターゲットマシンを使用します。
+    #[allow(dead_code)]
+    pub fn object_code(&self) -> YuniResult<Vec<u8>> {
+        use inkwell::targets::FileType;
+
+        self.assign_function_sections();
+        self.options
+            .target_machine()?
+            .write_to_memory_buffer(&self.module, FileType::Object)
+            .map(|buffer| buffer.as_slice().to_vec())
+            .map_err(|e| YuniError::Codegen(CodegenError::Internal {
+                message: format!("Failed to generate object code: {}", e),
+            }))
+    }
+
+    /// 定義した関数をそれぞれ `.text.<関数名>` セクションに置く
+    ///
+    /// llcでは `-function-sections` を渡しますが、LLVMのC APIのターゲットマシンには同じ設定がないため、
+    /// ターゲットマシンでオブジェクトファイルを生成する場合は関数ごとにセクションを指定します。
+    /// ELF以外のターゲットと `--no-gc-sections` の場合は何もしません。
+    fn assign_function_sections(&self) {
+        if !self.options.gc_sections || !self.options.is_elf() {
+            return;
+        }
+        for function in self.module.get_functions() {
+            let global = function.as_global_value();
+            if function.count_basic_blocks() > 0 && global.get_section().is_none() {
+                let name = function.get_name().to_string_lossy().into_owned();
+                global.set_section(Some(&format!(".text.{}", name)));
+            }
+        }
+    }
+
     // ========== Vecヘルパー関数 ==========
     
     /// 新しいVecを作成
+    ///
+    /// 要素のサイズとアライメントはターゲットのデータレイアウトから求めて
+    /// ランタイムに渡します。
     pub fn create_vec_new(&mut self, element_type: BasicTypeEnum<'ctx>) -> YuniResult<PointerValue<'ctx>> {
         let vec_new = self.runtime_manager.get_function("yuni_vec_new")
             .ok_or_else(|| YuniError::Codegen(CodegenError::Internal {
                 message: "yuni_vec_new not found".to_string(),
             }))?;
         
-        // 要素のサイズを取得
-        let element_size = element_type.size_of()
-            .ok_or_else(|| YuniError::Codegen(CodegenError::Internal {
-                message: "Cannot get size of element type".to_string(),
-            }))?;
-        
-        let size_value = self.builder.build_int_z_extend(element_size, self.context.i64_type(), "element_size")?;
+        let i64_type = self.context.i64_type();
+        let size_value = i64_type.const_int(self.get_size_of_type(element_type), false);
+        let align_value = i64_type.const_int(self.get_align_of_type(element_type), false);
         
-        let result = self.builder.build_call(vec_new, &[size_value.into()], "vec_new")?;
+        let result = self.builder.build_call(vec_new, &[size_value.into(), align_value.into()], "vec_new")?;
         Ok(result.try_as_basic_value().basic()
             .ok_or_else(|| YuniError::Codegen(CodegenError::Internal {
                 message: "vec_new returned void".to_string(),
@@ -577,10 +882,13 @@ impl<'ctx> CodeGenerator<'ctx> {
     // ========== HashMapヘルパー関数 ==========
     
     /// 新しいHashMapを作成
-    pub fn create_hashmap_new(&mut self, key_type: BasicTypeEnum<'ctx>, value_type: BasicTypeEnum<'ctx>) -> YuniResult<PointerValue<'ctx>> {
-        let hashmap_new = self.runtime_manager.get_function("yuni_hashmap_new")
+    ///
+    /// `string_keys` が真の場合、キーを文字列の内容で比較するHashMapを作成します。
+    pub fn create_hashmap_new(&mut self, key_type: BasicTypeEnum<'ctx>, value_type: BasicTypeEnum<'ctx>, string_keys: bool) -> YuniResult<PointerValue<'ctx>> {
+        let function_name = if string_keys { "yuni_hashmap_new_str" } else { "yuni_hashmap_new" };
+        let hashmap_new = self.runtime_manager.get_function(function_name)
             .ok_or_else(|| YuniError::Codegen(CodegenError::Internal {
-                message: "yuni_hashmap_new not found".to_string(),
+                message: format!("{} not found", function_name),
             }))?;
         
         // キーと値のサイズを取得
@@ -596,7 +904,12 @@ impl<'ctx> CodeGenerator<'ctx> {
         let key_size_value = self.builder.build_int_z_extend(key_size, self.context.i64_type(), "key_size")?;
         let value_size_value = self.builder.build_int_z_extend(value_size, self.context.i64_type(), "value_size")?;
         
-        let result = self.builder.build_call(hashmap_new, &[key_size_value.into(), value_size_value.into()], "hashmap_new")?;
+        let args: Vec<BasicMetadataValueEnum<'ctx>> = if string_keys {
+            vec![value_size_value.into()]
+        } else {
+            vec![key_size_value.into(), value_size_value.into()]
+        };
+        let result = self.builder.build_call(hashmap_new, &args, "hashmap_new")?;
         Ok(result.try_as_basic_value().basic()
             .ok_or_else(|| YuniError::Codegen(CodegenError::Internal {
                 message: "hashmap_new returned void".to_string(),
@@ -633,3 +946,84 @@ impl<'ctx> CodeGenerator<'ctx> {
         Ok(())
     }
 }
+
+/// 型定義を、定義の中で値として含む型が先になる順に並べる
+///
+/// ソース上では後で定義される型もフィールドに使えるため、LLVMの型の本体を設定する前に並べ替えます。
+/// 参照などポインタで表現する型は名前の宣言だけで変換できるため、順序には関係しません。
+/// 値として自身を含む型はセマンティック解析でエラーになるため、並べる順序に循環はありません。
+fn type_declaration_order(program: &Program) -> Vec<&TypeDef> {
+    let type_defs: HashMap<&str, &TypeDef> = program
+        .items
+        .iter()
+        .filter_map(|item| match item {
+            Item::TypeDef(type_def) => Some((type_def_name(type_def), type_def)),
+            _ => None,
+        })
+        .collect();
+
+    fn visit<'a>(
+        type_def: &'a TypeDef,
+        type_defs: &HashMap<&str, &'a TypeDef>,
+        visited: &mut Vec<&'a str>,
+        order: &mut Vec<&'a TypeDef>,
+    ) {
+        let name = type_def_name(type_def);
+        if visited.contains(&name) {
+            return;
+        }
+        visited.push(name);
+        let mut referenced = Vec::new();
+        match type_def {
+            TypeDef::Struct(struct_def) => {
+                for field in &struct_def.fields {
+                    collect_type_names(&field.ty, &mut referenced);
+                }
+            }
+            TypeDef::Enum(enum_def) => {
+                for field in enum_def.variants.iter().flat_map(|variant| &variant.fields) {
+                    collect_type_names(&field.ty, &mut referenced);
+                }
+            }
+            TypeDef::Alias(type_alias) => collect_type_names(&type_alias.underlying_type, &mut referenced),
+        }
+        for dependency in referenced {
+            if let Some(dependency) = type_defs.get(dependency) {
+                visit(dependency, type_defs, visited, order);
+            }
+        }
+        order.push(type_def);
+    }
+
+    let mut visited = Vec::new();
+    let mut order = Vec::new();
+    for item in &program.items {
+        if let Item::TypeDef(type_def) = item {
+            visit(type_def, &type_defs, &mut visited, &mut order);
+        }
+    }
+    order
+}
+
+fn type_def_name(type_def: &TypeDef) -> &str {
+    match type_def {
+        TypeDef::Struct(struct_def) => &struct_def.name,
+        TypeDef::Enum(enum_def) => &enum_def.name,
+        TypeDef::Alias(type_alias) => &type_alias.name,
+    }
+}
+
+/// LLVMの型の本体を設定するときに値として含むユーザー定義型の名前を集める
+///
+/// 参照・配列・関数・標準ライブラリのジェネリック型はポインタで表現するため、指す先の型は不要です。
+fn collect_type_names<'a>(ty: &'a Type, names: &mut Vec<&'a str>) {
+    match ty {
+        Type::UserDefined(name) => names.push(name),
+        Type::Tuple(elements) => {
+            for element in elements {
+                collect_type_names(element, names);
+            }
+        }
+        _ => {}
+    }
+}
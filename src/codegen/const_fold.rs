@@ -0,0 +1,114 @@
+//! 定数の畳み込み
+//!
+//! 単相化後のサイズ計算やジェネリック関数の中のリテラル演算など、セマンティック解析で
+//! 畳み込まれなかった定数の式をコード生成の時点で計算します。-O0（`yunilang run`）の
+//! 出力を小さくするためのもので、結果は実行時に計算した場合と一致させます。
+//! 整数演算は型の幅でラップアラウンドし、ゼロ除算と符号付き整数の最小値を-1で割る演算、
+//! 範囲外のシフト量のシフトは畳み込まずに実行時の処理に任せます。
+
+use crate::ast::BinaryOp;
+use inkwell::values::{BasicValueEnum, FloatValue, IntValue};
+
+use super::code_generator::CodeGenerator;
+
+impl<'ctx> CodeGenerator<'ctx> {
+    /// 両方のオペランドが定数の整数演算を計算する（畳み込めない場合はNone）
+    ///
+    /// 64ビットを超える整数は対象外です。
+    pub(crate) fn fold_int_binary(
+        &self,
+        op: &BinaryOp,
+        left: IntValue<'ctx>,
+        right: IntValue<'ctx>,
+        is_signed: bool,
+    ) -> Option<BasicValueEnum<'ctx>> {
+        let int_type = left.get_type();
+        let bits = int_type.get_bit_width();
+        if bits > 64 || right.get_type() != int_type {
+            return None;
+        }
+        // 符号付きは符号拡張、符号なし（boolを含む）はゼロ拡張した値で計算する
+        let (l, r) = if is_signed {
+            (left.get_sign_extended_constant()? as i128, right.get_sign_extended_constant()? as i128)
+        } else {
+            (left.get_zero_extended_constant()? as i128, right.get_zero_extended_constant()? as i128)
+        };
+
+        let value = match op {
+            BinaryOp::Add => l.wrapping_add(r),
+            BinaryOp::Subtract => l.wrapping_sub(r),
+            BinaryOp::Multiply => l.wrapping_mul(r),
+            BinaryOp::Divide | BinaryOp::Modulo => {
+                let signed_min = -(1i128 << (bits - 1));
+                if r == 0 || (is_signed && l == signed_min && r == -1) {
+                    return None;
+                }
+                // i128の除算は0方向への切り捨てで、sdiv・udiv・srem・uremと一致する
+                if matches!(op, BinaryOp::Divide) { l / r } else { l % r }
+            }
+            BinaryOp::Lt => return Some(self.const_bool(l < r)),
+            BinaryOp::Gt => return Some(self.const_bool(l > r)),
+            BinaryOp::Le => return Some(self.const_bool(l <= r)),
+            BinaryOp::Ge => return Some(self.const_bool(l >= r)),
+            BinaryOp::Eq => return Some(self.const_bool(l == r)),
+            BinaryOp::Ne => return Some(self.const_bool(l != r)),
+            BinaryOp::And | BinaryOp::BitAnd => l & r,
+            BinaryOp::Or | BinaryOp::BitOr => l | r,
+            BinaryOp::BitXor => l ^ r,
+            // 範囲外のシフト量は畳み込まずに実行時の検査に任せる
+            BinaryOp::Shl | BinaryOp::Shr if r < 0 || r >= bits as i128 => return None,
+            BinaryOp::Shl => l.wrapping_shl(r as u32),
+            // 符号付きは符号拡張した値のため、i128の右シフトがashr・lshrと一致する
+            BinaryOp::Shr => l >> r,
+            BinaryOp::In => return None,
+        };
+        // i128でのラップアラウンドは下位ビットを変えないため、型の幅に切り詰めれば実行時と同じ結果になる
+        let mask = if bits == 64 { u64::MAX } else { (1u64 << bits) - 1 };
+        Some(int_type.const_int(value as u64 & mask, false).into())
+    }
+
+    /// 両方のオペランドが定数の浮動小数点演算を計算する（畳み込めない場合はNone）
+    ///
+    /// f32の四則演算と剰余はf64で計算してから丸めても、f32で計算した結果と一致します。
+    pub(crate) fn fold_float_binary(
+        &self,
+        op: &BinaryOp,
+        left: FloatValue<'ctx>,
+        right: FloatValue<'ctx>,
+    ) -> Option<BasicValueEnum<'ctx>> {
+        if right.get_type() != left.get_type() {
+            return None;
+        }
+        let (l, _) = left.get_constant()?;
+        let (r, _) = right.get_constant()?;
+
+        let value = match op {
+            BinaryOp::Add => l + r,
+            BinaryOp::Subtract => l - r,
+            BinaryOp::Multiply => l * r,
+            BinaryOp::Divide => l / r,
+            BinaryOp::Modulo => l % r,
+            BinaryOp::Lt => return Some(self.const_bool(l < r)),
+            BinaryOp::Gt => return Some(self.const_bool(l > r)),
+            BinaryOp::Le => return Some(self.const_bool(l <= r)),
+            BinaryOp::Ge => return Some(self.const_bool(l >= r)),
+            BinaryOp::Eq => return Some(self.const_bool(l == r)),
+            // fcmp uneと同じく、どちらかがNaNならtrue
+            BinaryOp::Ne => return Some(self.const_bool(l != r)),
+            _ => return None,
+        };
+        Some(left.get_type().const_float(value).into())
+    }
+
+    /// 条件が定数のboolならその値を返す
+    pub(crate) fn constant_condition(condition: IntValue<'ctx>) -> Option<bool> {
+        if condition.get_type().get_bit_width() != 1 {
+            return None;
+        }
+        condition.get_zero_extended_constant().map(|value| value != 0)
+    }
+
+    fn const_bool(&self, value: bool) -> BasicValueEnum<'ctx> {
+        self.context.bool_type().const_int(value as u64, false).into()
+    }
+}
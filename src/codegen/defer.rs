@@ -0,0 +1,82 @@
+//! defer文のコード生成
+//!
+//! ブロックごとに登録されたdeferの本体をスタックで管理し、ブロックの終端と
+//! return文の直前に登録と逆順で展開します。
+//! 実行時エラー（パニック）の経路ではdeferは実行されません。
+
+use crate::ast::*;
+use crate::error::YuniResult;
+
+use super::code_generator::CodeGenerator;
+
+/// 1つのブロックで登録されたdefer
+#[derive(Default)]
+pub struct DeferScope {
+    /// deferの本体と、登録時の変数スコープの深さ
+    deferred: Vec<(Block, usize)>,
+}
+
+impl DeferScope {
+    /// 登録されたdeferがないか
+    pub fn is_empty(&self) -> bool {
+        self.deferred.is_empty()
+    }
+}
+
+impl<'ctx> CodeGenerator<'ctx> {
+    /// ブロックのdeferスコープを開始
+    pub fn enter_defer_scope(&mut self) {
+        self.defer_scopes.push(DeferScope::default());
+    }
+
+    /// ブロックのdeferスコープを終了
+    ///
+    /// ブロックの終端に到達する場合は、登録されたdeferを逆順に展開します。
+    /// return文で抜けた経路は `compile_return` で展開済みです。
+    pub fn leave_defer_scope(&mut self) -> YuniResult<()> {
+        let scope = self.defer_scopes.pop().unwrap_or_default();
+        if self.current_block_has_terminator() {
+            return Ok(());
+        }
+        for (body, depth) in scope.deferred.iter().rev() {
+            self.emit_deferred(body, *depth)?;
+        }
+        Ok(())
+    }
+
+    /// defer文をコンパイル
+    ///
+    /// 本体はここでは生成せず、囲んでいるブロックを抜けるときに生成します。
+    pub fn compile_defer_statement(&mut self, defer_stmt: &DeferStatement) -> YuniResult<()> {
+        let depth = self.scope_manager.depth();
+        if let Some(scope) = self.defer_scopes.last_mut() {
+            scope.deferred.push((defer_stmt.body.clone(), depth));
+        }
+        Ok(())
+    }
+
+    /// 関数から抜ける前に、登録済みのすべてのdeferを内側のブロックから順に展開
+    pub fn emit_all_defers(&mut self) -> YuniResult<()> {
+        let pending: Vec<(Block, usize)> = self
+            .defer_scopes
+            .iter()
+            .rev()
+            .flat_map(|scope| scope.deferred.iter().rev().cloned())
+            .collect();
+        for (body, depth) in &pending {
+            self.emit_deferred(body, *depth)?;
+        }
+        Ok(())
+    }
+
+    /// deferの本体を登録時の変数スコープで生成
+    ///
+    /// 登録後に内側のブロックで宣言された変数が本体の名前解決に影響しないよう、
+    /// それらのスコープを一時的に取り外します。
+    fn emit_deferred(&mut self, body: &Block, depth: usize) -> YuniResult<()> {
+        let detached = self.scope_manager.detach_scopes_above(depth);
+        let result = self.compile_block(body);
+        self.scope_manager.restore_scopes(detached);
+        result
+    }
+}
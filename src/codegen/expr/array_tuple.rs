@@ -7,6 +7,8 @@ use inkwell::types::{BasicType, BasicTypeEnum};
 use inkwell::AddressSpace;
 
 use crate::codegen::code_generator::CodeGenerator;
+use super::map_index::hashmap_types;
+use super::vec_index::vec_element_type;
 
 impl<'ctx> CodeGenerator<'ctx> {
     /// 配列式をコンパイル
@@ -108,13 +110,19 @@ impl<'ctx> CodeGenerator<'ctx> {
     }
 
     /// キャスト式をコンパイル
+    ///
+    /// `--checked-casts` の場合は、値が失われる整数・浮動小数点数から整数へのキャストの前に範囲を検査します。
     pub fn compile_cast_expr(&mut self, cast: &CastExpr) -> YuniResult<BasicValueEnum<'ctx>> {
         let value = self.compile_expression(&cast.expr)?;
         let target_type = self.type_manager.ast_type_to_llvm(&cast.ty)?;
+        let source_type = self.type_table.resolved(&cast.expr).cloned();
         
         match (value, target_type) {
             // 整数から整数へのキャスト
             (BasicValueEnum::IntValue(int_val), BasicTypeEnum::IntType(target_int_type)) => {
+                if let (true, Some(source_type)) = (self.options.checked_casts, &source_type) {
+                    self.check_int_cast(int_val, source_type, &cast.ty)?;
+                }
                 let source_bits = int_val.get_type().get_bit_width();
                 let target_bits = target_int_type.get_bit_width();
                 
@@ -147,6 +155,14 @@ impl<'ctx> CodeGenerator<'ctx> {
             
             // 浮動小数点から整数へのキャスト
             (BasicValueEnum::FloatValue(float_val), BasicTypeEnum::IntType(target_int_type)) => {
+                if self.options.checked_casts {
+                    let source_type = source_type.unwrap_or(if float_val.get_type() == self.context.f32_type() {
+                        Type::F32
+                    } else {
+                        Type::F64
+                    });
+                    self.check_float_cast(float_val, &source_type, &cast.ty)?;
+                }
                 if self.is_signed_type(target_int_type.get_bit_width()) {
                     Ok(self.builder.build_float_to_signed_int(float_val, target_int_type, "fptosi")?.into())
                 } else {
@@ -260,32 +276,13 @@ impl<'ctx> CodeGenerator<'ctx> {
             _ => unreachable!(),
         };
         
-        let struct_info = self.struct_info.get(&struct_name)
-            .ok_or_else(|| YuniError::Codegen(CodegenError::Undefined {
-                name: struct_name.clone(),
-                span: field_expr.span,
-            }))?;
-        
-        let field_index = struct_info.field_indices.get(&field_expr.field)
-            .ok_or_else(|| YuniError::Codegen(CodegenError::Undefined {
-                name: format!("{}.{}", struct_name, field_expr.field),
-                span: field_expr.span,
-            }))?;
-        
-        // フィールドポインタを取得
-        let struct_ty = Type::UserDefined(struct_name.clone());
-        let llvm_struct_type = self.type_manager.ast_type_to_llvm(&struct_ty)?;
-        let struct_type = match llvm_struct_type {
-            BasicTypeEnum::StructType(st) => st,
-            _ => return Err(YuniError::Codegen(CodegenError::Internal {
-                message: format!("構造体型 {} が見つかりません", struct_name),
-            })),
-        };
+        // フィールドの位置を取得
+        let (struct_type, field_index, _) = self.struct_field_slot(&struct_name, &field_expr.field, field_expr.span)?;
         
         let field_ptr = self.builder.build_struct_gep(
             struct_type,
             object_ptr,
-            *field_index,
+            field_index,
             &format!("{}_field_{}", struct_name, field_expr.field)
         )?;
         
@@ -294,10 +291,17 @@ impl<'ctx> CodeGenerator<'ctx> {
     }
     
     /// インデックスへの代入式をコンパイル（ヘルパー）
-    fn compile_index_assignment_expr(&mut self, _index_expr: &IndexExpr, _value: BasicValueEnum<'ctx>) -> YuniResult<()> {
+    fn compile_index_assignment_expr(&mut self, index_expr: &IndexExpr, value: BasicValueEnum<'ctx>) -> YuniResult<()> {
+        let object_type = self.expression_type(&index_expr.object)?;
+        if let Some((key_type, value_type)) = hashmap_types(&object_type) {
+            return self.compile_hashmap_index_assignment(index_expr, value, key_type, value_type);
+        }
+        if let Some(element_type) = vec_element_type(&object_type) {
+            return self.compile_vec_index_assignment(index_expr, value, element_type);
+        }
         Err(YuniError::Codegen(CodegenError::Unimplemented {
             feature: "インデックスアクセスへの代入はまだ実装されていません".to_string(),
-            span: Span::dummy(),
+            span: index_expr.span,
         }))
     }
     
@@ -0,0 +1,116 @@
+//! アサーションのコード生成
+//!
+//! 検査が失敗する分岐で、引数の式のソースコードと位置を含むメッセージ（文字列定数）を渡して
+//! パニックします。`assert_eq` は両辺の値を文字列に変換して一緒に渡します。
+//! `debug_assert` と `debug_assert_eq` は `debug_assertions` が無効であれば何も生成しません。
+
+use crate::assertion::{failure_message, AssertKind};
+use crate::analyzer::numeric::promote_numeric;
+use crate::ast::*;
+use crate::error::{CodegenError, YuniError, YuniResult};
+use inkwell::values::{BasicMetadataValueEnum, BasicValueEnum, IntValue};
+use inkwell::{FloatPredicate, IntPredicate};
+
+use crate::codegen::code_generator::CodeGenerator;
+
+impl<'ctx> CodeGenerator<'ctx> {
+    /// アサーションの呼び出しをコンパイル
+    pub(crate) fn compile_assert_call(&mut self, kind: AssertKind, call: &CallExpr) -> YuniResult<BasicValueEnum<'ctx>> {
+        let unit_value = self.context.i32_type().const_zero().into();
+        if kind.is_debug() && !self.options.debug_assertions {
+            return Ok(unit_value);
+        }
+
+        let (holds, operands) = if kind.is_eq() {
+            let (left_type, right_type) = self.operand_types(&call.args[0], &call.args[1])?;
+            let left = self.compile_binary_operand(&call.args[0], &left_type)?;
+            let right = self.compile_binary_operand(&call.args[1], &right_type)?;
+            let equal = self.build_values_equal(left, right, &left_type, &right_type, call.span)?;
+            (equal, Some(((left, left_type), (right, right_type))))
+        } else {
+            let condition = self.compile_expression(&call.args[0])?;
+            let BasicValueEnum::IntValue(condition) = condition else {
+                return Err(YuniError::Codegen(CodegenError::TypeError {
+                    expected: "bool".to_string(),
+                    actual: format!("{:?}", condition.get_type()),
+                    span: call.args[0].span(),
+                }));
+            };
+            (condition, None)
+        };
+
+        let function = self.current_function
+            .ok_or_else(|| YuniError::Codegen(CodegenError::Internal {
+                message: "No current function".to_string(),
+            }))?;
+        let fail_block = self.context.append_basic_block(function, "assert.fail");
+        let ok_block = self.context.append_basic_block(function, "assert.ok");
+        self.builder.build_conditional_branch(holds, ok_block, fail_block)?;
+
+        self.builder.position_at_end(fail_block);
+        let message = failure_message(kind, &call.args, call.span, self.source_text.as_ref());
+        let message = self.builder.build_global_string_ptr(&message, &self.value_names.global("assert.msg"))?.as_pointer_value();
+        let (panic_name, args): (&str, Vec<BasicMetadataValueEnum<'ctx>>) = match operands {
+            Some(((left, left_type), (right, right_type))) => {
+                let left = self.value_to_string(left, &left_type)?;
+                let right = self.value_to_string(right, &right_type)?;
+                ("yuni_assert_eq_failed", vec![message.into(), left.into(), right.into()])
+            }
+            None => ("yuni_panic", vec![message.into()]),
+        };
+        let panic_fn = self.runtime_manager.get_function(panic_name)
+            .ok_or_else(|| YuniError::Codegen(CodegenError::Internal {
+                message: format!("Runtime function {} not found", panic_name),
+            }))?;
+        self.builder.build_call(panic_fn, &args, "")?;
+        self.builder.build_unreachable()?;
+
+        self.builder.position_at_end(ok_block);
+        Ok(unit_value)
+    }
+
+    /// `assert_eq` の2つの値が等しいかを比較
+    fn build_values_equal(
+        &mut self,
+        left: BasicValueEnum<'ctx>,
+        right: BasicValueEnum<'ctx>,
+        left_type: &Type,
+        right_type: &Type,
+        span: Span,
+    ) -> YuniResult<IntValue<'ctx>> {
+        match (left, right) {
+            (BasicValueEnum::IntValue(left), BasicValueEnum::IntValue(right)) => {
+                let (left, right) = match promote_numeric(left_type, right_type) {
+                    // 昇格先の型の符号に従って幅の狭い方を拡張する
+                    Ok(ty) => {
+                        let signed = self.is_signed_integer_type(&ty);
+                        self.extend_int_operands(left, right, signed)?
+                    }
+                    Err(_) => self.coerce_int_types(left, right, span)?,
+                };
+                Ok(self.builder.build_int_compare(IntPredicate::EQ, left, right, "assert.eq")?)
+            }
+            (BasicValueEnum::FloatValue(left), BasicValueEnum::FloatValue(right)) => {
+                let (left, right) = self.coerce_float_types(left, right)?;
+                Ok(self.builder.build_float_compare(FloatPredicate::OEQ, left, right, "assert.eq")?)
+            }
+            (BasicValueEnum::PointerValue(left), BasicValueEnum::PointerValue(right)) => {
+                let string_eq_fn = self.runtime_manager.get_function("yuni_string_eq")
+                    .ok_or_else(|| YuniError::Codegen(CodegenError::Internal {
+                        message: "Runtime function yuni_string_eq not found".to_string(),
+                    }))?;
+                self.builder.build_call(string_eq_fn, &[left.into(), right.into()], "assert.eq")?
+                    .try_as_basic_value().basic()
+                    .map(|value| value.into_int_value())
+                    .ok_or_else(|| YuniError::Codegen(CodegenError::Internal {
+                        message: "yuni_string_eq should return a value".to_string(),
+                    }))
+            }
+            _ => Err(YuniError::Codegen(CodegenError::TypeError {
+                expected: "comparable values".to_string(),
+                actual: format!("{:?} and {:?}", left_type, right_type),
+                span,
+            })),
+        }
+    }
+}
@@ -1,5 +1,6 @@
 //! 識別子とパス式のコード生成
 
+use crate::analyzer::numeric::{associated_constant, builtin_type_by_name, NumericConstant};
 use crate::ast::*;
 use crate::error::{CodegenError, YuniError, YuniResult};
 use inkwell::values::BasicValueEnum;
@@ -39,6 +40,8 @@ impl<'ctx> CodeGenerator<'ctx> {
                 name: name.clone(),
                 span: path.span,
             });
+        } else if let Some(ty) = associated_constant_type(path) {
+            return self.compile_associated_constant(&ty, path);
         } else if path.segments.len() == 2 {
             // Enum::Variant のパターンを処理
             // これはEnumVariantExprとして処理されるべきだが、
@@ -57,4 +60,31 @@ impl<'ctx> CodeGenerator<'ctx> {
             span: path.span,
         }))
     }
-}
\ No newline at end of file
+
+    /// 組み込み型の関連定数（`i64::MAX`）を定数としてコンパイル
+    fn compile_associated_constant(&mut self, ty: &Type, path: &PathExpr) -> YuniResult<BasicValueEnum<'ctx>> {
+        let llvm_type = self.type_manager.ast_type_to_llvm(ty)?;
+        match associated_constant(ty, &path.segments[1]) {
+            Some(NumericConstant::Integer(value)) => {
+                // 128ビットの定数も表せるよう、2ワードで渡す（型の幅で切り詰められる）
+                let int_type = llvm_type.into_int_type();
+                let bits = value as u128;
+                Ok(int_type.const_int_arbitrary_precision(&[bits as u64, (bits >> 64) as u64]).into())
+            }
+            Some(NumericConstant::Float(value)) => Ok(llvm_type.into_float_type().const_float(value).into()),
+            None => Err(YuniError::Codegen(CodegenError::Undefined {
+                name: path.segments.join("::"),
+                span: path.span,
+            })),
+        }
+    }
+}
+
+/// パス式が組み込み型の関連定数（`i64::MAX`）であれば、その型を返す
+pub(super) fn associated_constant_type(path: &PathExpr) -> Option<Type> {
+    let [type_name, name] = path.segments.as_slice() else {
+        return None;
+    };
+    let ty = builtin_type_by_name(type_name)?;
+    associated_constant(&ty, name).map(|_| ty)
+}
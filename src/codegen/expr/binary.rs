@@ -2,7 +2,8 @@
 
 use crate::ast::*;
 use crate::error::{CodegenError, YuniError, YuniResult};
-use inkwell::values::BasicValueEnum;
+use crate::analyzer::numeric::{is_untyped_integer_literal, promote_numeric};
+use inkwell::values::{BasicValueEnum, IntValue};
 use inkwell::{FloatPredicate, IntPredicate};
 
 use crate::codegen::code_generator::CodeGenerator;
@@ -10,25 +11,38 @@ use crate::codegen::code_generator::CodeGenerator;
 impl<'ctx> CodeGenerator<'ctx> {
     /// 二項演算式をコンパイル
     pub fn compile_binary_expr(&mut self, binary: &BinaryExpr) -> YuniResult<BasicValueEnum<'ctx>> {
-        let left = self.compile_expression(&binary.left)?;
-        let right = self.compile_expression(&binary.right)?;
-        
-        // 型情報を取得して符号の有無を判定
-        let left_type = self.expression_type(&binary.left)?;
-        let _right_type = self.expression_type(&binary.right)?;
+        if binary.op == BinaryOp::In {
+            return self.compile_membership_expr(binary);
+        }
+
+        // オペランドの型を数値型の昇格規則で揃える
+        let (left_type, right_type) = self.binary_operand_types(binary)?;
+        let operand_type = promote_numeric(&left_type, &right_type).ok();
+
+        let left = self.compile_binary_operand(&binary.left, &left_type)?;
+        let right = self.compile_binary_operand(&binary.right, &right_type)?;
 
         match (&binary.op, left, right) {
             // 整数演算
             (op, BasicValueEnum::IntValue(left_int), BasicValueEnum::IntValue(right_int)) => {
-                // 型が異なる場合は型変換を行う
-                let (left_int, right_int) = if left_int.get_type() != right_int.get_type() {
-                    self.coerce_int_types(left_int, right_int, binary.span)?
-                } else {
-                    (left_int, right_int)
+                let (left_int, right_int) = match &operand_type {
+                    // 昇格先の型の符号に従って幅の狭い方を拡張する
+                    Some(ty) if left_int.get_type() != right_int.get_type() => {
+                        let signed = self.is_signed_integer_type(ty);
+                        self.extend_int_operands(left_int, right_int, signed)?
+                    }
+                    Some(_) => (left_int, right_int),
+                    // 昇格規則の対象外（bool同士など）は従来どおり型を揃える
+                    None if left_int.get_type() != right_int.get_type() => {
+                        self.coerce_int_types(left_int, right_int, binary.span)?
+                    }
+                    None => (left_int, right_int),
                 };
-                
-                // 左辺の型で符号を判定（型強制後は両辺同じ型になるはず）
-                let is_signed = self.is_signed_integer_type(&left_type);
+
+                let is_signed = self.is_signed_integer_type(operand_type.as_ref().unwrap_or(&left_type));
+                if let Some(folded) = self.fold_int_binary(op, left_int, right_int, is_signed) {
+                    return Ok(folded);
+                }
 
                 let result = match op {
                     BinaryOp::Add => self.builder.build_int_add(left_int, right_int, "add")?,
@@ -68,8 +82,20 @@ impl<'ctx> CodeGenerator<'ctx> {
                     BinaryOp::Ne => self.builder.build_int_compare(IntPredicate::NE, left_int, right_int, "ne")?,
                     BinaryOp::And => self.builder.build_and(left_int, right_int, "and")?,
                     BinaryOp::Or => self.builder.build_or(left_int, right_int, "or")?,
-                    // ビット演算子は現在定義されていない
-                    _ => return Err(YuniError::Codegen(CodegenError::InvalidType {
+                    BinaryOp::BitAnd => self.builder.build_and(left_int, right_int, "bitand")?,
+                    BinaryOp::BitOr => self.builder.build_or(left_int, right_int, "bitor")?,
+                    BinaryOp::BitXor => self.builder.build_xor(left_int, right_int, "bitxor")?,
+                    BinaryOp::Shl => {
+                        self.build_shift_amount_check(right_int, binary.span)?;
+                        self.builder.build_left_shift(left_int, right_int, "shl")?
+                    }
+                    BinaryOp::Shr => {
+                        self.build_shift_amount_check(right_int, binary.span)?;
+                        // 符号付きは算術シフト、符号なしは論理シフト
+                        self.builder.build_right_shift(left_int, right_int, is_signed, if is_signed { "ashr" } else { "lshr" })?
+                    }
+                    // `in` は入口でコンパイルしている
+                    BinaryOp::In => return Err(YuniError::Codegen(CodegenError::InvalidType {
                         message: format!("Unsupported binary operation: {:?}", op),
                         span: binary.span,
                     })),
@@ -85,7 +111,10 @@ impl<'ctx> CodeGenerator<'ctx> {
                 } else {
                     (left_float, right_float)
                 };
-                
+                if let Some(folded) = self.fold_float_binary(op, left_float, right_float) {
+                    return Ok(folded);
+                }
+
                 match op {
                     BinaryOp::Add => Ok(self.builder.build_float_add(left_float, right_float, "fadd")?.into()),
                     BinaryOp::Subtract => Ok(self.builder.build_float_sub(left_float, right_float, "fsub")?.into()),
@@ -97,14 +126,38 @@ impl<'ctx> CodeGenerator<'ctx> {
                     BinaryOp::Le => Ok(self.builder.build_float_compare(FloatPredicate::OLE, left_float, right_float, "fle")?.into()),
                     BinaryOp::Ge => Ok(self.builder.build_float_compare(FloatPredicate::OGE, left_float, right_float, "fge")?.into()),
                     BinaryOp::Eq => Ok(self.builder.build_float_compare(FloatPredicate::OEQ, left_float, right_float, "feq")?.into()),
-                    BinaryOp::Ne => Ok(self.builder.build_float_compare(FloatPredicate::ONE, left_float, right_float, "fne")?.into()),
+                    // IEEE 754に従い、NaNとの比較は `!=` だけがtrueになる（順序なしの不等価）
+                    BinaryOp::Ne => Ok(self.builder.build_float_compare(FloatPredicate::UNE, left_float, right_float, "fne")?.into()),
                     _ => Err(YuniError::Codegen(CodegenError::InvalidType {
                         message: format!("Invalid operation {:?} for float types", op),
                         span: binary.span,
                     })),
                 }
             }
-            
+
+            // 文字列の連結と比較（strとStringはどちらも文字列データへのポインタ）
+            (op, BasicValueEnum::PointerValue(left_ptr), BasicValueEnum::PointerValue(right_ptr))
+                if is_string(&left_type) && is_string(&right_type) =>
+            {
+                match op {
+                    BinaryOp::Add => self.call_runtime_function("yuni_string_concat", &[left_ptr.into(), right_ptr.into()]),
+                    BinaryOp::Eq | BinaryOp::Ne => {
+                        let equal = self
+                            .call_runtime_function("yuni_string_eq", &[left_ptr.into(), right_ptr.into()])?
+                            .into_int_value();
+                        if *op == BinaryOp::Eq {
+                            Ok(equal.into())
+                        } else {
+                            Ok(self.builder.build_not(equal, "str_ne")?.into())
+                        }
+                    }
+                    _ => Err(YuniError::Codegen(CodegenError::InvalidType {
+                        message: format!("Invalid operation {:?} for string types", op),
+                        span: binary.span,
+                    })),
+                }
+            }
+
             _ => Err(YuniError::Codegen(CodegenError::TypeError {
                 expected: "numeric types".to_string(),
                 actual: "non-numeric types".to_string(),
@@ -112,4 +165,81 @@ impl<'ctx> CodeGenerator<'ctx> {
             })),
         }
     }
-}
\ No newline at end of file
+
+    /// 二項演算のオペランドを1つコンパイル
+    ///
+    /// 接尾辞のない整数リテラルは昇格規則で決まった型で直接生成するため、拡張命令は不要です。
+    pub(super) fn compile_binary_operand(&mut self, expr: &Expression, ty: &Type) -> YuniResult<BasicValueEnum<'ctx>> {
+        if is_untyped_integer_literal(expr) {
+            self.compile_expression_with_type(expr, Some(ty))
+        } else {
+            self.compile_expression(expr)
+        }
+    }
+
+    /// シフト量が `0 <= amount < ビット幅` でなければパニックする分岐を生成する
+    ///
+    /// LLVMのシフト命令はビット幅以上のシフト量でpoisonになるため、インタプリタと同じく
+    /// 実行時エラーにします。符号なしで比較するため、負のシフト量も1回の比較で範囲外になります。
+    /// 範囲内の定数のシフト量には検査を生成しません。
+    fn build_shift_amount_check(&mut self, amount: IntValue<'ctx>, span: Span) -> YuniResult<()> {
+        let bits = amount.get_type().get_bit_width();
+        if amount.get_zero_extended_constant().is_some_and(|amount| amount < bits as u64) {
+            return Ok(());
+        }
+        let function = self.current_function
+            .ok_or_else(|| YuniError::Codegen(CodegenError::Internal {
+                message: "No current function".to_string(),
+            }))?;
+        let fail_block = self.context.append_basic_block(function, "shift.fail");
+        let ok_block = self.context.append_basic_block(function, "shift.ok");
+        let width = amount.get_type().const_int(bits as u64, false);
+        let in_range = self.builder.build_int_compare(IntPredicate::ULT, amount, width, "shift.in_range")?;
+        self.builder.build_conditional_branch(in_range, ok_block, fail_block)?;
+
+        self.builder.position_at_end(fail_block);
+        let message = match self.source_text.as_ref().and_then(|source| source.location(span)) {
+            Some(location) => format!("シフト量が範囲外です (at {})", location),
+            None => "シフト量が範囲外です".to_string(),
+        };
+        let message = self.builder.build_global_string_ptr(&message, &self.value_names.global("shift.msg"))?;
+        let panic_fn = self.runtime_manager.get_function("yuni_panic")
+            .ok_or_else(|| YuniError::Codegen(CodegenError::Internal {
+                message: "Runtime function yuni_panic not found".to_string(),
+            }))?;
+        self.builder.build_call(panic_fn, &[message.as_pointer_value().into()], "")?;
+        self.builder.build_unreachable()?;
+
+        self.builder.position_at_end(ok_block);
+        Ok(())
+    }
+
+    /// ビット幅の異なる整数オペランドを幅の広い方に拡張
+    pub(super) fn extend_int_operands(
+        &self,
+        left: IntValue<'ctx>,
+        right: IntValue<'ctx>,
+        signed: bool,
+    ) -> YuniResult<(IntValue<'ctx>, IntValue<'ctx>)> {
+        let target = if left.get_type().get_bit_width() >= right.get_type().get_bit_width() {
+            left.get_type()
+        } else {
+            right.get_type()
+        };
+        let extend = |value: IntValue<'ctx>| -> YuniResult<IntValue<'ctx>> {
+            if value.get_type() == target {
+                Ok(value)
+            } else if signed {
+                Ok(self.builder.build_int_s_extend(value, target, "sext")?)
+            } else {
+                Ok(self.builder.build_int_z_extend(value, target, "zext")?)
+            }
+        };
+        Ok((extend(left)?, extend(right)?))
+    }
+}
+
+/// 文字列の型（`str` または `String`）か
+fn is_string(ty: &Type) -> bool {
+    matches!(ty, Type::String | Type::Str)
+}
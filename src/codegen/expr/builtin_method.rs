@@ -0,0 +1,145 @@
+//! 組み込み型のメソッド呼び出しのコード生成
+//!
+//! `len` はランタイムの長さの関数を、`push`・`resize` はVecへの追加と長さの変更の関数を、
+//! `to_string` は数値の文字列変換の関数（文字列では文字列データの複製）を呼び出します。Stringの `as_str` は同じポインタをそのまま返します。
+//! 文字列の `starts_with`・`find`・`split`・`trim` はランタイムの同名の文字列関数を呼び出します。
+//! `sort`・`sort_by`・`binary_search` は `vec_sort` で比較関数を合成します。
+//! `abs`・`min`・`max` は、整数では比較とselectに、浮動小数点数ではLLVMの組み込み関数にします。
+
+use crate::analyzer::builtin_methods::BuiltinMethod;
+use crate::ast::*;
+use crate::error::{CodegenError, YuniError, YuniResult};
+use inkwell::intrinsics::Intrinsic;
+use inkwell::values::{BasicMetadataValueEnum, BasicValueEnum, FloatValue, IntValue};
+use inkwell::IntPredicate;
+
+use crate::codegen::code_generator::CodeGenerator;
+
+impl<'ctx> CodeGenerator<'ctx> {
+    /// 組み込みメソッドの呼び出しをコンパイル
+    pub(crate) fn compile_builtin_method(
+        &mut self,
+        method_call: &MethodCallExpr,
+        method: &BuiltinMethod,
+        object_value: BasicValueEnum<'ctx>,
+        object_type: &Type,
+    ) -> YuniResult<BasicValueEnum<'ctx>> {
+        // 参照経由の場合は参照先の値を読み出す
+        let mut receiver = object_value;
+        let mut ty = object_type;
+        while let Type::Reference(inner, _) = ty {
+            let inner_type = self.type_manager.ast_type_to_llvm(inner)?;
+            receiver = self.builder.build_load(inner_type, receiver.into_pointer_value(), "builtin_deref")?;
+            ty = inner;
+        }
+
+        if matches!(method.name, "sort" | "sort_by" | "binary_search") {
+            return self.compile_vec_sort_method(method_call, receiver.into_pointer_value(), ty);
+        }
+
+        // 引数はメソッドの仮引数の型（レシーバーと同じ型かVecの要素の型）
+        let param_types = method.param_types(ty);
+        let mut args = Vec::with_capacity(method_call.args.len());
+        for (arg, param_type) in method_call.args.iter().zip(&param_types) {
+            let value = self.compile_expression_in_context(arg, param_type)?;
+            let llvm_type = self.type_manager.ast_type_to_llvm(param_type)?;
+            args.push(self.coerce_to_type(value, llvm_type, arg.span())?);
+        }
+
+        match (method.name, receiver, args.as_slice()) {
+            ("len", BasicValueEnum::PointerValue(ptr), []) => {
+                let function_name = match ty {
+                    Type::Generic(name, _) if name == "Vec" => "yuni_vec_len",
+                    Type::Generic(_, _) => "yuni_hashmap_len",
+                    _ => "yuni_str_len",
+                };
+                self.call_runtime_function(function_name, &[ptr.into()])
+            }
+            ("push", BasicValueEnum::PointerValue(vec), [element]) => {
+                // 値を返さない組み込み関数と同じくi32の0を式の値とする
+                let element_type = element.get_type();
+                self.vec_push(vec, *element, element_type)?;
+                Ok(self.context.i32_type().const_zero().into())
+            }
+            ("resize", BasicValueEnum::PointerValue(vec), [BasicValueEnum::IntValue(len), fill]) => {
+                // 埋める値はpushと同じくメモリに置いてポインタで渡す
+                let fill_ptr = self.builder.build_alloca(fill.get_type(), "resize_fill")?;
+                self.builder.build_store(fill_ptr, *fill)?;
+                let resize = self.runtime_manager.get_function("yuni_vec_resize")
+                    .ok_or_else(|| YuniError::Codegen(CodegenError::Internal {
+                        message: "Runtime function yuni_vec_resize not found".to_string(),
+                    }))?;
+                self.builder.build_call(resize, &[vec.into(), (*len).into(), fill_ptr.into()], "")?;
+                Ok(self.context.i32_type().const_zero().into())
+            }
+            ("to_string", BasicValueEnum::IntValue(_) | BasicValueEnum::FloatValue(_), []) => self.value_to_string(receiver, ty),
+            ("to_string", BasicValueEnum::PointerValue(ptr), []) => {
+                let len = self.call_runtime_function("yuni_str_len", &[ptr.into()])?;
+                self.call_runtime_function("yuni_string_from_parts", &[ptr.into(), len.into()])
+            }
+            ("as_str", BasicValueEnum::PointerValue(ptr), []) => Ok(ptr.into()),
+            ("starts_with" | "find" | "split", BasicValueEnum::PointerValue(ptr), [BasicValueEnum::PointerValue(needle)]) => {
+                let function_name = format!("yuni_string_{}", method.name);
+                self.call_runtime_function(&function_name, &[ptr.into(), (*needle).into()])
+            }
+            ("trim", BasicValueEnum::PointerValue(ptr), []) => self.call_runtime_function("yuni_string_trim", &[ptr.into()]),
+            ("abs", BasicValueEnum::IntValue(value), []) => {
+                // 最小値の符号反転はラップして最小値のまま
+                let zero = value.get_type().const_zero();
+                let negated = self.builder.build_int_sub(zero, value, "abs_neg")?;
+                let is_negative = self.builder.build_int_compare(IntPredicate::SLT, value, zero, "abs_is_neg")?;
+                Ok(self.builder.build_select(is_negative, negated, value, "abs")?)
+            }
+            ("abs", BasicValueEnum::FloatValue(value), []) => self.call_float_intrinsic("llvm.fabs", &[value]),
+            ("min" | "max", BasicValueEnum::IntValue(value), [BasicValueEnum::IntValue(other)]) => {
+                self.select_int_extremum(method.name, ty, value, *other)
+            }
+            ("min", BasicValueEnum::FloatValue(value), [BasicValueEnum::FloatValue(other)]) => {
+                self.call_float_intrinsic("llvm.minnum", &[value, *other])
+            }
+            ("max", BasicValueEnum::FloatValue(value), [BasicValueEnum::FloatValue(other)]) => {
+                self.call_float_intrinsic("llvm.maxnum", &[value, *other])
+            }
+            _ => Err(YuniError::Codegen(CodegenError::InvalidType {
+                message: format!("Invalid receiver or arguments for builtin method '{}'", method.name),
+                span: method_call.span,
+            })),
+        }
+    }
+
+    /// 2つの整数の小さい方（`min`）または大きい方（`max`）を選ぶ
+    fn select_int_extremum(
+        &mut self,
+        method: &str,
+        ty: &Type,
+        value: IntValue<'ctx>,
+        other: IntValue<'ctx>,
+    ) -> YuniResult<BasicValueEnum<'ctx>> {
+        let predicate = match (method, self.is_unsigned_integer_type(ty)) {
+            ("min", false) => IntPredicate::SLT,
+            ("min", true) => IntPredicate::ULT,
+            (_, false) => IntPredicate::SGT,
+            (_, true) => IntPredicate::UGT,
+        };
+        let keep = self.builder.build_int_compare(predicate, value, other, &format!("{}_cmp", method))?;
+        Ok(self.builder.build_select(keep, value, other, method)?)
+    }
+
+    /// 浮動小数点数のLLVM組み込み関数を呼び出す
+    fn call_float_intrinsic(&mut self, name: &str, args: &[FloatValue<'ctx>]) -> YuniResult<BasicValueEnum<'ctx>> {
+        let float_type = args[0].get_type();
+        let intrinsic = Intrinsic::find(name)
+            .and_then(|intrinsic| intrinsic.get_declaration(&self.module, &[float_type.into()]))
+            .ok_or_else(|| YuniError::Codegen(CodegenError::Internal {
+                message: format!("{} intrinsic not found", name),
+            }))?;
+        let args: Vec<BasicMetadataValueEnum<'ctx>> = args.iter().map(|&arg| arg.into()).collect();
+        self.builder
+            .build_call(intrinsic, &args, name.trim_start_matches("llvm."))?
+            .try_as_basic_value()
+            .basic()
+            .ok_or_else(|| YuniError::Codegen(CodegenError::Internal {
+                message: format!("{} returned void", name),
+            }))
+    }
+}
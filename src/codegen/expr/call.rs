@@ -1,10 +1,17 @@
 //! 関数・メソッド呼び出しのコード生成
 
+use crate::analyzer::builtin_methods::builtin_method;
+use crate::assertion::AssertKind;
 use crate::ast::*;
+use crate::console::{output_message, OutputKind};
 use crate::error::{CodegenError, YuniError, YuniResult};
-use inkwell::values::BasicValueEnum;
+use inkwell::values::{BasicMetadataValueEnum, BasicValueEnum};
+use inkwell::AddressSpace;
 
 use crate::codegen::code_generator::CodeGenerator;
+use crate::codegen::naming;
+use super::map_index::hashmap_types;
+use super::vec_index::vec_element_type;
 
 impl<'ctx> CodeGenerator<'ctx> {
     /// 関数呼び出し式をコンパイル
@@ -30,9 +37,35 @@ impl<'ctx> CodeGenerator<'ctx> {
             }
         };
 
-        // printlnの特別な処理
-        if func_name == "println" {
-            return self.compile_println_call(&call.args, call.span);
+        if let Some(kind) = OutputKind::from_name(func_name) {
+            return self.compile_output_call(kind, call);
+        }
+
+        if let Some(kind) = AssertKind::from_name(func_name) {
+            return self.compile_assert_call(kind, call);
+        }
+
+        // read_lineはランタイム関数を直接呼び出す
+        if func_name == "read_line" {
+            let read_line_fn = self.runtime_manager.get_function("yuni_read_line")
+                .ok_or_else(|| YuniError::Codegen(CodegenError::Internal {
+                    message: "Runtime function yuni_read_line not found".to_string(),
+                }))?;
+            return self.builder.build_call(read_line_fn, &[], "read_line_result")?
+                .try_as_basic_value().basic()
+                .ok_or_else(|| YuniError::Codegen(CodegenError::Internal {
+                    message: "yuni_read_line should return a value".to_string(),
+                }));
+        }
+
+        if func_name == "read_bytes" || func_name == "write_bytes" {
+            return self.compile_bytes_io_call(func_name, call);
+        }
+        if func_name == "parse_int" || func_name == "parse_float" {
+            return self.compile_parse_number_call(func_name, call);
+        }
+        if let Some((runtime_name, _)) = clock_builtin(func_name) {
+            return self.compile_clock_call(runtime_name, call);
         }
 
         // 関数情報を取得（コピーして借用を解放）
@@ -48,9 +81,15 @@ impl<'ctx> CodeGenerator<'ctx> {
         // 通常の関数呼び出し
         // 引数を先にコンパイルし、必要に応じて型変換
         let mut args = Vec::new();
+        let mut compiled_args = Vec::new();
+        let declared_param_types = self.function_param_types.get(func_name).cloned().unwrap_or_default();
         
         for (i, arg) in call.args.iter().enumerate() {
-            let arg_value = self.compile_expression(arg)?;
+            let arg_value = match declared_param_types.get(i) {
+                Some(param_type) => self.compile_expression_in_context(arg, param_type)?,
+                None => self.compile_expression(arg)?,
+            };
+            compiled_args.push((arg, arg_value));
             
             // パラメータの型に合わせて変換
             if i < param_types.len() {
@@ -71,11 +110,16 @@ impl<'ctx> CodeGenerator<'ctx> {
         let call_site = self.builder.build_call(func, &args, "call_result")?;
         
         // 末尾呼び出し最適化を適用
-        if self.tail_context.is_tail_call(call) {
+        if call.is_tail && self.can_emit_tail_call(&compiled_args) {
             call_site.set_tail_call(true);
         }
         
         if let Some(value) = call_site.try_as_basic_value().basic() {
+            if self.extern_functions.contains(func_name)
+                && matches!(self.function_types.get(func_name), Some(Type::String | Type::Str))
+            {
+                return self.null_string_to_empty(value, call.span);
+            }
             Ok(value)
         } else {
             // void関数の場合、unit値を返す
@@ -83,100 +127,129 @@ impl<'ctx> CodeGenerator<'ctx> {
         }
     }
 
-    /// println呼び出しのコンパイル
-    fn compile_println_call(&mut self, args: &[Expression], _span: Span) -> YuniResult<BasicValueEnum<'ctx>> {
-        if args.is_empty() {
-            // 引数なしの場合は改行のみ
-            let newline_str = self.context.const_string(b"\n", true);
-            let global = self.module.add_global(newline_str.get_type(), None, "newline");
-            global.set_initializer(&newline_str);
-            global.set_constant(true);
+    /// 外部関数が返したNULLの文字列を空文字列に置き換える
+    ///
+    /// `getenv` のようにCの関数は値がないことをNULLで表すため、Yuniの文字列として使う前に
+    /// 空文字列にします。
+    fn null_string_to_empty(&mut self, value: BasicValueEnum<'ctx>, span: Span) -> YuniResult<BasicValueEnum<'ctx>> {
+        let is_null = self.builder.build_is_null(value.into_pointer_value(), "is_null")?;
+        let empty = self.compile_string_literal(&StringLit { value: String::new(), span })?;
+        Ok(self.builder.build_select(is_null, empty, value, "extern_str")?)
+    }
 
-            let printf_fn = self.runtime_manager.get_function("printf")
-                .ok_or_else(|| YuniError::Codegen(CodegenError::Internal {
-                    message: "printf function not found".to_string(),
-                }))?;
+    /// コンソール出力の呼び出しをコンパイル
+    ///
+    /// 出力する文字列はprintfの書式として解釈させず、ランタイムの出力関数に渡します。
+    fn compile_output_call(&mut self, kind: OutputKind, call: &CallExpr) -> YuniResult<BasicValueEnum<'ctx>> {
+        let message = match output_message(&call.args, call.span) {
+            Some(message) => self.compile_expression(&message)?,
+            None => self.compile_string_literal(&StringLit {
+                value: String::new(),
+                span: call.span,
+            })?,
+        };
 
-            let ptr = global.as_pointer_value();
-            self.builder.build_call(printf_fn, &[ptr.into()], "println_call")?;
-            return Ok(self.context.i32_type().const_zero().into());
-        }
+        let output_fn = self.runtime_manager.get_function(kind.runtime_function())
+            .ok_or_else(|| YuniError::Codegen(CodegenError::Internal {
+                message: format!("Runtime function {} not found", kind.runtime_function()),
+            }))?;
+        self.builder.build_call(output_fn, &[message.into()], &format!("{}_call", kind.name()))?;
 
-        // 最初の引数をコンパイルして、文字列に変換
-        let arg_value = self.compile_expression(&args[0])?;
-        let format_arg = self.value_to_string(arg_value)?;
-        
-        if args.len() == 1 {
-            // 引数が1つの場合
-            let printf_fn = self.runtime_manager.get_function("printf")
+        Ok(self.context.i32_type().const_zero().into())
+    }
+
+    /// バイナリファイルの読み書きの呼び出しをコンパイル
+    ///
+    /// `write_bytes` はVecのバッファのポインタと長さをランタイムに渡すため、NULを含むデータも書き出せます。
+    fn compile_bytes_io_call(&mut self, func_name: &str, call: &CallExpr) -> YuniResult<BasicValueEnum<'ctx>> {
+        let runtime_name = format!("yuni_{}", func_name);
+        let runtime_fn = self.runtime_manager.get_function(&runtime_name)
+            .ok_or_else(|| YuniError::Codegen(CodegenError::Internal {
+                message: format!("Runtime function {} not found", runtime_name),
+            }))?;
+
+        let path = self.compile_expression(&call.args[0])?;
+        let args: Vec<BasicMetadataValueEnum<'ctx>> = if func_name == "write_bytes" {
+            let data = self.compile_expression(&call.args[1])?.into_pointer_value();
+            let vec_data = self.runtime_manager.get_function("yuni_vec_data")
+                .ok_or_else(|| YuniError::Codegen(CodegenError::Internal {
+                    message: "Runtime function yuni_vec_data not found".to_string(),
+                }))?;
+            let buffer = self.builder.build_call(vec_data, &[data.into()], "bytes_data")?
+                .try_as_basic_value().basic()
                 .ok_or_else(|| YuniError::Codegen(CodegenError::Internal {
-                    message: "printf function not found".to_string(),
+                    message: "yuni_vec_data should return a value".to_string(),
                 }))?;
+            let len = self.vec_len(data)?;
+            vec![path.into(), buffer.into(), len.into()]
+        } else {
+            vec![path.into()]
+        };
 
-            // 改行を追加したフォーマット文字列を作成
-            let newline_format = "%s\n";
-            let format_str = self.context.const_string(newline_format.as_bytes(), true);
-            let format_global = self.module.add_global(format_str.get_type(), None, "printf_format");
-            format_global.set_initializer(&format_str);
-            format_global.set_constant(true);
+        self.builder.build_call(runtime_fn, &args, &format!("{}_result", func_name))?
+            .try_as_basic_value().basic()
+            .ok_or_else(|| YuniError::Codegen(CodegenError::Internal {
+                message: format!("{} should return a value", runtime_name),
+            }))
+    }
 
-            let format_ptr = format_global.as_pointer_value();
-            self.builder.build_call(printf_fn, &[format_ptr.into(), format_arg.into()], "println_call")?;
+    /// `parse_int`・`parse_float` の呼び出しをコンパイル
+    ///
+    /// ランタイム関数は変換した値を出力先のポインタに書き込み、変換できたかをboolで返します。
+    /// 結果は `(変換できたか, 値)` のタプルで、変換できなかった場合の値は0です。
+    fn compile_parse_number_call(&mut self, func_name: &str, call: &CallExpr) -> YuniResult<BasicValueEnum<'ctx>> {
+        let (runtime_name, value_type) = if func_name == "parse_int" {
+            ("yuni_parse_i64", Type::I64)
         } else {
-            // 複数の引数がある場合 - すべての値を文字列として連結して出力
-            let printf_fn = self.runtime_manager.get_function("printf")
-                .ok_or_else(|| YuniError::Codegen(CodegenError::Internal {
-                    message: "printf function not found".to_string(),
-                }))?;
+            ("yuni_parse_f64", Type::F64)
+        };
+        let runtime_fn = self.runtime_manager.get_function(runtime_name)
+            .ok_or_else(|| YuniError::Codegen(CodegenError::Internal {
+                message: format!("Runtime function {} not found", runtime_name),
+            }))?;
 
-            // すべての引数を文字列に変換して連結
-            let mut result_str = format_arg;
-            for arg in args.iter().skip(1) {
-                let arg_value = self.compile_expression(arg)?;
-                let arg_str = self.value_to_string(arg_value)?;
-                
-                // 文字列連結
-                let concat_fn = self.runtime_manager.get_function("yuni_string_concat")
-                    .ok_or_else(|| YuniError::Codegen(CodegenError::Internal {
-                        message: "yuni_string_concat function not found".to_string(),
-                    }))?;
-                
-                result_str = self.builder.build_call(
-                    concat_fn, 
-                    &[result_str.into(), arg_str.into()], 
-                    "concat_result"
-                )?.try_as_basic_value().basic().unwrap();
-            }
+        let text = self.compile_expression(&call.args[0])?;
+        let llvm_value_type = self.type_manager.ast_type_to_llvm(&value_type)?;
+        let out = self.builder.build_alloca(llvm_value_type, "parsed")?;
+        self.builder.build_store(out, self.type_manager.create_default_value(&value_type)?)?;
+        let parsed = self.builder.build_call(runtime_fn, &[text.into(), out.into()], "parse_ok")?
+            .try_as_basic_value().basic()
+            .ok_or_else(|| YuniError::Codegen(CodegenError::Internal {
+                message: format!("{} should return a value", runtime_name),
+            }))?;
+        let value = self.builder.build_load(llvm_value_type, out, "parsed_value")?;
 
-            // 改行を追加
-            let newline_str = self.context.const_string(b"\n", true);
-            let newline_global = self.module.add_global(newline_str.get_type(), None, "newline");
-            newline_global.set_initializer(&newline_str);
-            newline_global.set_constant(true);
-            let newline_ptr = newline_global.as_pointer_value();
-            
-            let concat_fn = self.runtime_manager.get_function("yuni_string_concat")
-                .ok_or_else(|| YuniError::Codegen(CodegenError::Internal {
-                    message: "yuni_string_concat function not found".to_string(),
-                }))?;
-            
-            result_str = self.builder.build_call(
-                concat_fn, 
-                &[result_str.into(), newline_ptr.into()], 
-                "concat_newline"
-            )?.try_as_basic_value().basic().unwrap();
-
-            // %s形式で出力
-            let format_str = self.context.const_string(b"%s", true);
-            let format_global = self.module.add_global(format_str.get_type(), None, "printf_format");
-            format_global.set_initializer(&format_str);
-            format_global.set_constant(true);
-
-            let format_ptr = format_global.as_pointer_value();
-            self.builder.build_call(printf_fn, &[format_ptr.into(), result_str.into()], "println_call")?;
+        let tuple_type = self.type_manager.ast_type_to_llvm(&Type::Tuple(vec![Type::Bool, value_type]))?.into_struct_type();
+        let tuple = self.builder.build_insert_value(tuple_type.get_undef(), parsed, 0, "parse_tuple")?.into_struct_value();
+        let tuple = self.builder.build_insert_value(tuple, value, 1, "parse_result")?.into_struct_value();
+        Ok(tuple.into())
+    }
+
+    /// 時刻と乱数のビルトイン関数の呼び出しをコンパイル
+    ///
+    /// 引数と戻り値はランタイム関数と同じ型のため、引数を変換してそのまま呼び出します。
+    fn compile_clock_call(&mut self, runtime_name: &str, call: &CallExpr) -> YuniResult<BasicValueEnum<'ctx>> {
+        let runtime_fn = self.runtime_manager.get_function(runtime_name)
+            .ok_or_else(|| YuniError::Codegen(CodegenError::Internal {
+                message: format!("Runtime function {} not found", runtime_name),
+            }))?;
+
+        let param_types = runtime_fn.get_type().get_param_types();
+        let mut args: Vec<BasicMetadataValueEnum<'ctx>> = Vec::new();
+        for (arg, param_type) in call.args.iter().zip(param_types) {
+            let value = self.compile_expression(arg)?;
+            let expected_type = param_type.try_into().map_err(|_| {
+                YuniError::Codegen(CodegenError::InvalidType {
+                    message: "Metadata parameters cannot accept runtime values".to_string(),
+                    span: arg.span(),
+                })
+            })?;
+            args.push(self.coerce_to_type(value, expected_type, arg.span())?.into());
         }
 
-        Ok(self.context.i32_type().const_zero().into())
+        let call_site = self.builder.build_call(runtime_fn, &args, "clock_result")?;
+        Ok(call_site.try_as_basic_value().basic()
+            .unwrap_or_else(|| self.context.i32_type().const_zero().into()))
     }
 
     /// メソッド呼び出し式をコンパイル
@@ -187,6 +260,14 @@ impl<'ctx> CodeGenerator<'ctx> {
         // オブジェクトの型を推論
         let object_type = self.expression_type(&method_call.object)?;
         
+        if is_string_builder_type(&object_type) {
+            return self.compile_string_builder_method(method_call, object_value, &object_type);
+        }
+        
+        if let Some(method) = builtin_method(&object_type, &method_call.method) {
+            return self.compile_builtin_method(method_call, method, object_value, &object_type);
+        }
+        
         // 構造体名を取得
         let struct_name = match &object_type {
             Type::UserDefined(name) => name.clone(),
@@ -231,16 +312,27 @@ impl<'ctx> CodeGenerator<'ctx> {
         
         // 引数を準備（最初の引数はレシーバー）
         let mut args = vec![object_value.into()];
+        let mut compiled_args = vec![(method_call.object.as_ref(), object_value)];
         
         // 残りの引数をコンパイル
-        for arg in &method_call.args {
-            let arg_value = self.compile_expression(arg)?;
+        let declared_param_types = self.function_param_types.get(mangled_name).cloned().unwrap_or_default();
+        for (i, arg) in method_call.args.iter().enumerate() {
+            let arg_value = match declared_param_types.get(i) {
+                Some(param_type) => self.compile_expression_in_context(arg, param_type)?,
+                None => self.compile_expression(arg)?,
+            };
             args.push(arg_value.into());
+            compiled_args.push((arg, arg_value));
         }
         
         // メソッドを呼び出し
         let call_result = self.builder.build_call(func, &args, "method_call_result")?;
         
+        // 末尾呼び出し最適化を適用
+        if method_call.is_tail && self.can_emit_tail_call(&compiled_args) {
+            call_result.set_tail_call(true);
+        }
+        
         if let Some(value) = call_result.try_as_basic_value().basic() {
             Ok(value)
         } else {
@@ -249,17 +341,73 @@ impl<'ctx> CodeGenerator<'ctx> {
         }
     }
 
+    /// StringBuilderのメソッド呼び出しをコンパイル
+    fn compile_string_builder_method(
+        &mut self,
+        method_call: &MethodCallExpr,
+        object_value: BasicValueEnum<'ctx>,
+        object_type: &Type,
+    ) -> YuniResult<BasicValueEnum<'ctx>> {
+        // 参照経由の場合はビルダーへのポインタを読み出す
+        let mut sb = object_value.into_pointer_value();
+        let mut ty = object_type;
+        while let Type::Reference(inner, _) = ty {
+            let ptr_type = self.context.ptr_type(AddressSpace::default());
+            sb = self.builder.build_load(ptr_type, sb, "sb_deref")?.into_pointer_value();
+            ty = inner;
+        }
+
+        match (method_call.method.as_str(), method_call.args.as_slice()) {
+            ("append", [arg]) => {
+                let value = self.compile_expression(arg)?;
+                let value_type = self.expression_type(arg)?;
+                self.append_to_string_builder(sb, value, &value_type)?;
+                Ok(self.context.i32_type().const_zero().into())
+            }
+            ("len", []) => self.call_runtime_function("yuni_sb_len", &[sb.into()]),
+            ("to_string", []) => self.call_runtime_function("yuni_sb_to_string", &[sb.into()]),
+            _ => Err(YuniError::Codegen(CodegenError::Undefined {
+                name: format!("Method '{}' not found for type 'StringBuilder'", method_call.method),
+                span: method_call.span,
+            })),
+        }
+    }
+
+    /// 値を返すランタイム関数を呼び出す
+    pub(crate) fn call_runtime_function(
+        &mut self,
+        function_name: &str,
+        args: &[BasicMetadataValueEnum<'ctx>],
+    ) -> YuniResult<BasicValueEnum<'ctx>> {
+        let function = self.runtime_manager.get_function(function_name)
+            .ok_or_else(|| YuniError::Codegen(CodegenError::Internal {
+                message: format!("Runtime function {} not found", function_name),
+            }))?;
+        self.builder.build_call(function, args, "runtime_call")?
+            .try_as_basic_value().basic()
+            .ok_or_else(|| YuniError::Codegen(CodegenError::Internal {
+                message: format!("{} should return a value", function_name),
+            }))
+    }
+
     /// インデックスアクセス式をコンパイル
     pub fn compile_index_expr(&mut self, index: &IndexExpr) -> YuniResult<BasicValueEnum<'ctx>> {
+        // HashMapはキーの型でインデックスをコンパイルする
+        let object_type = self.expression_type(&index.object)?;
+        if let Some((key_type, value_type)) = hashmap_types(&object_type) {
+            return self.compile_hashmap_index(index, key_type, value_type);
+        }
+        // Vecはインデックスの範囲を検査してから読み出す
+        if let Some(element_type) = vec_element_type(&object_type) {
+            return self.compile_vec_index(index, element_type);
+        }
+
         // オブジェクト（配列）の式をコンパイル
         let object_value = self.compile_expression(&index.object)?;
         
         // インデックスの式をコンパイル
         let index_value = self.compile_expression(&index.index)?;
         
-        // オブジェクトの型を推論
-        let object_type = self.expression_type(&index.object)?;
-        
         match &object_type {
             Type::Array(element_type) => {
                 // 配列のインデックスアクセス
@@ -298,22 +446,6 @@ impl<'ctx> CodeGenerator<'ctx> {
                     span: index.span,
                 }))
             }
-            Type::Generic(name, type_args) if name == "Vec" && type_args.len() == 1 => {
-                // Vecのインデックスアクセス
-                let element_type = &type_args[0];
-                let vec_ptr = object_value.into_pointer_value();
-                
-                // インデックスが整数型であることを確認
-                let index_int = index_value.into_int_value();
-                
-                // 要素のLLVM型を取得
-                let element_llvm_type = self.type_manager.ast_type_to_llvm(element_type)?;
-                
-                // vec_getヘルパー関数を使用
-                let value = self.vec_get(vec_ptr, index_int, element_llvm_type)?;
-                
-                Ok(value)
-            }
             _ => {
                 Err(YuniError::Codegen(CodegenError::InvalidType {
                     message: format!("Cannot index into type: {:?}", object_type),
@@ -352,18 +484,10 @@ impl<'ctx> CodeGenerator<'ctx> {
             }
         };
         
-        // 構造体情報を取得
-        let struct_info = self.struct_info.get(&struct_name)
-            .ok_or_else(|| YuniError::Codegen(CodegenError::Internal {
-                message: format!("Struct info not found for {}", struct_name),
-            }))?;
-        
-        // フィールドのインデックスを取得
-        let field_index = struct_info.get_field_index(&field.field)
-            .ok_or_else(|| YuniError::Codegen(CodegenError::Undefined {
-                name: format!("{}.{}", struct_name, field.field),
-                span: field.span,
-            }))?;
+        // フィールドの位置を取得
+        let (struct_type, field_index, llvm_field_type) =
+            self.struct_field_slot(&struct_name, &field.field, field.span)?;
+        let field_name = naming::field_name(&field.object, &field.field);
         
         // 構造体値からフィールドを抽出
         match object_value {
@@ -372,17 +496,12 @@ impl<'ctx> CodeGenerator<'ctx> {
                 let field_value = self.builder.build_extract_value(
                     struct_val,
                     field_index,
-                    &field.field
+                    &field_name
                 )?;
                 Ok(field_value)
             }
             BasicValueEnum::PointerValue(ptr_val) => {
                 // ポインタの場合はGEPを使用
-                let struct_type = self.type_manager.get_struct(&struct_name)
-                    .ok_or_else(|| YuniError::Codegen(CodegenError::Internal {
-                        message: format!("Struct type not found for {}", struct_name),
-                    }))?;
-                
                 let indices = [
                     self.context.i32_type().const_zero(),
                     self.context.i32_type().const_int(field_index as u64, false),
@@ -393,22 +512,15 @@ impl<'ctx> CodeGenerator<'ctx> {
                         struct_type,
                         ptr_val,
                         &indices,
-                        &format!("{}_ptr", field.field),
+                        &format!("{}.ptr", field_name),
                     )?
                 };
                 
-                // フィールドの型を取得
-                let field_type = struct_info.get_field_type(field_index as usize)
-                    .ok_or_else(|| YuniError::Codegen(CodegenError::Internal {
-                        message: format!("Field type not found for index {}", field_index),
-                    }))?;
-                let llvm_field_type = self.type_manager.ast_type_to_llvm(field_type)?;
-                
                 // フィールドの値をロード
                 let field_value = self.builder.build_load(
                     llvm_field_type,
                     field_ptr,
-                    &field.field
+                    &field_name
                 )?;
                 
                 Ok(field_value)
@@ -422,3 +534,24 @@ impl<'ctx> CodeGenerator<'ctx> {
         }
     }
 }
+
+/// 組み込みのStringBuilder（またはその参照）かどうか
+pub(crate) fn is_string_builder_type(ty: &Type) -> bool {
+    match ty {
+        Type::UserDefined(name) => name == "StringBuilder",
+        Type::Reference(inner, _) => is_string_builder_type(inner),
+        _ => false,
+    }
+}
+
+/// 時刻と乱数のビルトイン関数に対応するランタイム関数の名前と戻り値の型
+pub(crate) fn clock_builtin(name: &str) -> Option<(&'static str, Type)> {
+    match name {
+        "time_nanos" => Some(("yuni_time_nanos", Type::I64)),
+        "time_unix_millis" => Some(("yuni_time_unix_millis", Type::I64)),
+        "sleep_millis" => Some(("yuni_sleep_millis", Type::Void)),
+        "random_seed" => Some(("yuni_random_seed", Type::Void)),
+        "random_u64" => Some(("yuni_random_u64", Type::U64)),
+        _ => None,
+    }
+}
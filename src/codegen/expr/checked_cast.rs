@@ -0,0 +1,163 @@
+//! 検査付きキャストのコード生成
+//!
+//! `--checked-casts` を指定すると、値が失われる整数の縮小・符号の変更と、浮動小数点数から
+//! 整数へのキャストの前に値の範囲を検査し、範囲外（浮動小数点数ではNaNも）であれば
+//! 値と変換元・変換先の型を示してパニックします。
+//! 検査するのは128ビットまでの整数型で、それより広い整数型へのキャストは検査しません。
+
+use crate::analyzer::display_type;
+use crate::analyzer::numeric::{float_to_integer_bounds, integer_layout};
+use crate::ast::Type;
+use crate::error::{CodegenError, YuniError, YuniResult};
+use inkwell::intrinsics::Intrinsic;
+use inkwell::types::IntType;
+use inkwell::values::{BasicMetadataValueEnum, FloatValue, IntValue};
+use inkwell::{FloatPredicate, IntPredicate};
+
+use crate::codegen::code_generator::CodeGenerator;
+
+impl<'ctx> CodeGenerator<'ctx> {
+    /// 整数のキャストの前に、値が変換先の型の範囲内であることを検査する
+    pub(crate) fn check_int_cast(&mut self, value: IntValue<'ctx>, source: &Type, target: &Type) -> YuniResult<()> {
+        let (Some((source_bits, source_signed)), Some((target_bits, target_signed))) =
+            (integer_layout(source), integer_layout(target))
+        else {
+            return Ok(());
+        };
+        // 型テーブルの型と値の幅が一致しない場合（解析で型が決まらなかった式など）は検査しない
+        let int_type = value.get_type();
+        if source_bits > 128 || target_bits > 128 || int_type.get_bit_width() != source_bits {
+            return Ok(());
+        }
+
+        let mut in_range = None;
+        // 符号付きの値は、変換先の最小値を下回ることがある
+        if source_signed && (!target_signed || target_bits < source_bits) {
+            let min = if target_signed { -(1i128 << (target_bits - 1)) } else { 0 };
+            let min = Self::const_wide(int_type, min as u128, min < 0);
+            in_range = Some(self.builder.build_int_compare(IntPredicate::SGE, value, min, "cast.min")?);
+        }
+        // 変換先の最大値が変換元の最大値より小さければ、上回ることがある
+        let source_exp = if source_signed { source_bits - 1 } else { source_bits };
+        let target_exp = if target_signed { target_bits - 1 } else { target_bits };
+        if target_exp < source_exp {
+            let max = Self::const_wide(int_type, (1u128 << target_exp) - 1, false);
+            let predicate = if source_signed { IntPredicate::SLE } else { IntPredicate::ULE };
+            let below_max = self.builder.build_int_compare(predicate, value, max, "cast.max")?;
+            in_range = Some(match in_range {
+                Some(above_min) => self.builder.build_and(above_min, below_max, "cast.in_range")?,
+                None => below_max,
+            });
+        }
+        let Some(in_range) = in_range else {
+            return Ok(());
+        };
+
+        self.build_cast_check(in_range, source, target, |this| {
+            // 値を128ビットに拡張し、下位・上位の64ビットに分けて渡す
+            let i128_type = this.context.i128_type();
+            let wide = if source_bits == 128 {
+                value
+            } else if source_signed {
+                this.builder.build_int_s_extend(value, i128_type, "cast.value")?
+            } else {
+                this.builder.build_int_z_extend(value, i128_type, "cast.value")?
+            };
+            let i64_type = this.context.i64_type();
+            let low = this.builder.build_int_truncate(wide, i64_type, "cast.low")?;
+            let shifted = this.builder.build_right_shift(wide, i128_type.const_int(64, false), false, "cast.shift")?;
+            let high = this.builder.build_int_truncate(shifted, i64_type, "cast.high")?;
+            let is_signed = this.context.bool_type().const_int(source_signed as u64, false);
+            Ok(("yuni_panic_cast_int", vec![low.into(), high.into(), is_signed.into()]))
+        })
+    }
+
+    /// 浮動小数点数から整数へのキャストの前に、値がNaNでなく変換先の型の範囲内であることを検査する
+    ///
+    /// 整数へのキャストは0方向に切り捨てるため、切り捨てた値の範囲を検査します。
+    pub(crate) fn check_float_cast(&mut self, value: FloatValue<'ctx>, source: &Type, target: &Type) -> YuniResult<()> {
+        let Some((min, max_exclusive)) = float_to_integer_bounds(target) else {
+            return Ok(());
+        };
+
+        let float_type = value.get_type();
+        let trunc = Intrinsic::find("llvm.trunc")
+            .and_then(|intrinsic| intrinsic.get_declaration(&self.module, &[float_type.into()]))
+            .ok_or_else(|| YuniError::Codegen(CodegenError::Internal {
+                message: "llvm.trunc intrinsic not found".to_string(),
+            }))?;
+        let truncated = self.builder
+            .build_call(trunc, &[value.into()], "cast.trunc")?
+            .try_as_basic_value()
+            .basic()
+            .ok_or_else(|| YuniError::Codegen(CodegenError::Internal {
+                message: "llvm.trunc returned void".to_string(),
+            }))?
+            .into_float_value();
+
+        // 順序付きの比較はNaNで偽になる
+        let above_min = self.builder.build_float_compare(
+            FloatPredicate::OGE, truncated, float_type.const_float(min), "cast.min")?;
+        let below_max = self.builder.build_float_compare(
+            FloatPredicate::OLT, truncated, float_type.const_float(max_exclusive), "cast.max")?;
+        let in_range = self.builder.build_and(above_min, below_max, "cast.in_range")?;
+
+        self.build_cast_check(in_range, source, target, |this| {
+            let f64_type = this.context.f64_type();
+            let value = if float_type == f64_type {
+                value
+            } else {
+                this.builder.build_float_ext(value, f64_type, "cast.value")?
+            };
+            Ok(("yuni_panic_cast_float", vec![value.into()]))
+        })
+    }
+
+    /// 範囲外であればランタイムのパニック関数を呼ぶ分岐を生成する
+    ///
+    /// `panic_args` はパニック関数の名前と、型名の前に渡す引数を返します。
+    /// 条件が定数で常に範囲内の場合は分岐を生成しません。
+    fn build_cast_check(
+        &mut self,
+        in_range: IntValue<'ctx>,
+        source: &Type,
+        target: &Type,
+        panic_args: impl FnOnce(&mut Self) -> YuniResult<(&'static str, Vec<BasicMetadataValueEnum<'ctx>>)>,
+    ) -> YuniResult<()> {
+        if in_range.get_zero_extended_constant() == Some(1) {
+            return Ok(());
+        }
+        let function = self.current_function
+            .ok_or_else(|| YuniError::Codegen(CodegenError::Internal {
+                message: "No current function".to_string(),
+            }))?;
+        let fail_block = self.context.append_basic_block(function, "cast.fail");
+        let ok_block = self.context.append_basic_block(function, "cast.ok");
+        self.builder.build_conditional_branch(in_range, ok_block, fail_block)?;
+
+        self.builder.position_at_end(fail_block);
+        let (panic_name, mut args) = panic_args(self)?;
+        let panic_fn = self.runtime_manager.get_function(panic_name)
+            .ok_or_else(|| YuniError::Codegen(CodegenError::Internal {
+                message: format!("Runtime function {} not found", panic_name),
+            }))?;
+        let from = self.builder.build_global_string_ptr(&display_type(source), &self.value_names.global("cast.from"))?;
+        let to = self.builder.build_global_string_ptr(&display_type(target), &self.value_names.global("cast.to"))?;
+        args.push(from.as_pointer_value().into());
+        args.push(to.as_pointer_value().into());
+        self.builder.build_call(panic_fn, &args, "")?;
+        self.builder.build_unreachable()?;
+
+        self.builder.position_at_end(ok_block);
+        Ok(())
+    }
+
+    /// 128ビットまでの整数定数を作成（`negative` の場合は上位ビットを1で埋める）
+    fn const_wide(int_type: IntType<'ctx>, bits: u128, negative: bool) -> IntValue<'ctx> {
+        if int_type.get_bit_width() <= 64 {
+            int_type.const_int(bits as u64, negative)
+        } else {
+            int_type.const_int_arbitrary_precision(&[bits as u64, (bits >> 64) as u64])
+        }
+    }
+}
@@ -2,16 +2,35 @@
 
 use crate::ast::*;
 use crate::error::{CodegenError, YuniError, YuniResult};
-use inkwell::values::BasicValueEnum;
+use inkwell::basic_block::BasicBlock;
+use inkwell::values::{BasicValueEnum, IntValue, PointerValue};
 use inkwell::IntPredicate;
+use std::collections::HashSet;
+use std::rc::Rc;
 
 use crate::codegen::code_generator::CodeGenerator;
+use crate::codegen::layout::VariantRef;
+use crate::codegen::naming;
 
 impl<'ctx> CodeGenerator<'ctx> {
     /// match式をコンパイル
     pub fn compile_match_expr(&mut self, match_expr: &MatchExpr) -> YuniResult<BasicValueEnum<'ctx>> {
+        self.compile_match(match_expr, false)
+    }
+
+    /// match式の各アームで関数から戻る（末尾呼び出しのため、合流してから戻らない）
+    pub(crate) fn compile_match_return(&mut self, match_expr: &MatchExpr) -> YuniResult<()> {
+        self.compile_match(match_expr, true)?;
+        Ok(())
+    }
+
+    /// match式をコンパイル
+    ///
+    /// `returns` が真の場合は各アームの値をそのまま関数の戻り値とし、マージブロックは使いません。
+    fn compile_match(&mut self, match_expr: &MatchExpr, returns: bool) -> YuniResult<BasicValueEnum<'ctx>> {
         // マッチ対象の式をコンパイル
         let scrutinee = self.compile_expression(&match_expr.expr)?;
+        let scrutinee_type = self.expression_type(&match_expr.expr).ok();
         
         // 現在の関数を取得
         let function = self.builder.get_insert_block().unwrap().get_parent().unwrap();
@@ -32,18 +51,21 @@ impl<'ctx> CodeGenerator<'ctx> {
         // エンドブロック（すべてのパターンが失敗した場合）
         let end_block = self.context.append_basic_block(function, "match_end");
         
-        // エントリーポイントから最初のアームへジャンプ
+        // 合流する各アームの結果とブロック
+        let mut incoming: Vec<(BasicValueEnum<'ctx>, BasicBlock<'ctx>)> = Vec::new();
+        
+        // 整数と真偽値のリテラルで分岐するmatchはswitchで該当するアームへ直接ジャンプし、
+        // それ以外は最初のアームから順に照合する
         if !arm_blocks.is_empty() {
-            self.builder.build_unconditional_branch(arm_blocks[0])?;
+            if !self.build_match_switch(&match_expr.arms, scrutinee, scrutinee_type.as_ref(), &arm_blocks, end_block)? {
+                self.builder.build_unconditional_branch(arm_blocks[0])?;
+            }
         } else {
             // アームがない場合はマージブロックへ直接ジャンプ
             self.builder.build_unconditional_branch(merge_block)?;
+            incoming.push((self.context.i32_type().const_zero().into(), self.builder.get_insert_block().unwrap()));
         }
         
-        // 各アームの結果を格納
-        let mut arm_results = Vec::new();
-        let mut arm_result_blocks = Vec::new();
-        
         // 各アームを処理
         for (i, arm) in match_expr.arms.iter().enumerate() {
             self.builder.position_at_end(arm_blocks[i]);
@@ -51,12 +73,25 @@ impl<'ctx> CodeGenerator<'ctx> {
             // パターンマッチング
             let matches = self.compile_pattern_match(&arm.pattern, scrutinee, match_expr.span)?;
             
-            // パターンで導入された変数をバインド（ガード評価のため）
-            self.scope_manager.push_scope();
-            self.bind_pattern_variables(&arm.pattern, scrutinee)?;
+            // 次のアームまたはエンドブロックを決定
+            let next_block = if i + 1 < arm_blocks.len() {
+                arm_blocks[i + 1]
+            } else {
+                end_block
+            };
             
             // ガード条件がある場合
-            let final_condition = if let Some(guard) = &arm.guard {
+            if let Some(guard) = &arm.guard {
+                // 束縛する変数はパターンがマッチした場合にしか読めないため、
+                // ガードはパターンがマッチしてから評価する
+                let guard_block = self.context.append_basic_block(function, &format!("arm{}_guard", i));
+                self.builder.build_conditional_branch(matches, guard_block, next_block)?;
+                self.builder.position_at_end(guard_block);
+                
+                // パターンで導入された変数をバインド（ガード評価のため）
+                self.scope_manager.push_scope();
+                self.bind_pattern_variables(&arm.pattern, scrutinee, scrutinee_type.as_ref())?;
+                
                 let guard_value = self.compile_expression(guard)?;
                 let guard_bool = match guard_value {
                     BasicValueEnum::IntValue(int_val) => {
@@ -74,42 +109,38 @@ impl<'ctx> CodeGenerator<'ctx> {
                     })),
                 };
                 
-                // パターンマッチとガードの両方が真の場合のみマッチ
-                self.builder.build_and(matches, guard_bool, "match_with_guard")?
-            } else {
-                matches
-            };
-            
-            // ガード評価後、変数スコープを終了
-            self.scope_manager.pop_scope();
-            
-            // 次のアームまたはエンドブロックを決定
-            let next_block = if i + 1 < arm_blocks.len() {
-                arm_blocks[i + 1]
+                // ガード評価後、変数スコープを終了
+                self.scope_manager.pop_scope();
+                
+                // ガードが真の場合は結果ブロックへ、偽の場合は次のアームへ
+                self.builder.build_conditional_branch(guard_bool, result_blocks[i], next_block)?;
             } else {
-                end_block
-            };
-            
-            // マッチした場合は結果ブロックへ、しなかった場合は次のアームへ
-            self.builder.build_conditional_branch(final_condition, result_blocks[i], next_block)?;
+                // マッチした場合は結果ブロックへ、しなかった場合は次のアームへ
+                self.builder.build_conditional_branch(matches, result_blocks[i], next_block)?;
+            }
             
             // 結果ブロックでアームの式を評価
             self.builder.position_at_end(result_blocks[i]);
             
             // パターンで導入された変数を再度スコープに追加（今度はアームの式評価のため）
             self.scope_manager.push_scope();
-            self.bind_pattern_variables(&arm.pattern, scrutinee)?;
+            self.bind_pattern_variables(&arm.pattern, scrutinee, scrutinee_type.as_ref())?;
+            
+            if returns {
+                // アームの式の値で関数から戻る
+                self.compile_return_value(&arm.expr)?;
+                self.scope_manager.pop_scope();
+                continue;
+            }
             
             // アームの式を評価
             let result = self.compile_expression(&arm.expr)?;
-            arm_results.push(result);
             
             // スコープを終了
             self.scope_manager.pop_scope();
             
-            // マージブロックへジャンプ
-            self.builder.build_unconditional_branch(merge_block)?;
-            arm_result_blocks.push(self.builder.get_insert_block().unwrap());
+            // マージブロックへジャンプ（returnで抜けたアームは合流しない）
+            incoming.extend(self.branch_to_merge(result, merge_block)?);
         }
         
         // エンドブロック（すべてのパターンがマッチしなかった場合）
@@ -117,7 +148,7 @@ impl<'ctx> CodeGenerator<'ctx> {
         
         // パニックメッセージを生成
         let panic_msg = "パターンマッチが網羅的ではありません";
-        let panic_str = self.builder.build_global_string_ptr(panic_msg, "panic_msg")?.as_pointer_value();
+        let panic_str = self.builder.build_global_string_ptr(panic_msg, &self.value_names.global("panic_msg"))?.as_pointer_value();
         
         // yuni_panic関数を呼び出し
         let panic_fn = self.runtime_manager.get_function("yuni_panic")
@@ -129,43 +160,120 @@ impl<'ctx> CodeGenerator<'ctx> {
         self.builder.build_call(panic_fn, &[panic_str.into()], "panic_call")?;
         self.builder.build_unreachable()?;
         
-        // unit値を返す（実際には到達しないが、型システムのために必要）
-        let unit_value = self.context.i32_type().const_zero();
-        
-        // マージブロックでPHIノードを作成
-        self.builder.position_at_end(merge_block);
+        if returns {
+            // すべてのアームが戻るため、マージブロックには到達しない
+            self.builder.position_at_end(merge_block);
+            self.builder.build_unreachable()?;
+            return Ok(self.context.i32_type().const_zero().into());
+        }
         
-        if arm_results.is_empty() {
-            // アームがない場合はunit値を返す
-            Ok(unit_value.into())
-        } else {
-            // すべてのアームの結果が同じ型であることを確認
-            let result_type = arm_results[0].get_type();
-            let mut use_default = false;
-            for result in &arm_results[1..] {
-                if result.get_type() != result_type {
-                    // 型が異なる場合はunit値を返す
-                    use_default = true;
-                    break;
+        // マージブロックでPHIノードを作成（すべてのアームが戻る場合、ビルダーは終端したブロックに残る）
+        self.merge_branch_values(&incoming, merge_block, "match_result")
+    }
+    
+    /// パターンの値と比較するため、整数を64ビットに揃える
+    fn int_to_i64(&self, actual: IntValue<'ctx>, signed: bool) -> YuniResult<IntValue<'ctx>> {
+        let i64_type = self.context.i64_type();
+        Ok(match actual.get_type().get_bit_width().cmp(&64) {
+            std::cmp::Ordering::Less if signed => self.builder.build_int_s_extend(actual, i64_type, "sext")?,
+            std::cmp::Ordering::Less => self.builder.build_int_z_extend(actual, i64_type, "zext")?,
+            std::cmp::Ordering::Greater => self.builder.build_int_truncate(actual, i64_type, "trunc")?,
+            std::cmp::Ordering::Equal => actual,
+        })
+    }
+
+    /// 整数と真偽値のmatchをswitch命令で分岐する
+    ///
+    /// すべてのアームが整数・真偽値のリテラル、展開できる大きさの範囲、識別子、ワイルドカードの
+    /// いずれかの場合に限り、値ごとにその値にマッチしうる最初のアームへジャンプします。
+    /// 真偽値のmatchはswitchの代わりに、値で2つのアームへ分岐する条件分岐（`br`）にします。
+    /// ジャンプ先のアームはパターンとガードを改めて評価するため、ガードが偽の場合は
+    /// 通常どおり次のアームへ進みます。switchを生成しなかった場合は `false` を返します。
+    fn build_match_switch(
+        &mut self,
+        arms: &[MatchArm],
+        scrutinee: BasicValueEnum<'ctx>,
+        scrutinee_type: Option<&Type>,
+        arm_blocks: &[BasicBlock<'ctx>],
+        end_block: BasicBlock<'ctx>,
+    ) -> YuniResult<bool> {
+        const MAX_SWITCH_CASES: i64 = 256;
+
+        let BasicValueEnum::IntValue(value) = scrutinee else {
+            return Ok(false);
+        };
+        let mut case_count: i64 = 0;
+        for pattern in arms.iter().flat_map(|arm| arm.pattern.alternatives()) {
+            match pattern {
+                Pattern::Literal(LiteralPattern::Integer(_) | LiteralPattern::Bool(_)) => case_count += 1,
+                Pattern::Range { start, end, inclusive } => {
+                    case_count = case_count
+                        .saturating_add(end.saturating_sub(*start))
+                        .saturating_add(*inclusive as i64);
                 }
+                Pattern::Identifier(_, _) | Pattern::Wildcard => {}
+                _ => return Ok(false),
             }
-            
-            if use_default {
-                Ok(unit_value.into())
-            } else {
-                // PHIノードを作成
-                let phi = self.builder.build_phi(result_type, "match_result")?;
-                
-                // 各アームの結果を追加
-                for (result, block) in arm_results.iter().zip(arm_result_blocks.iter()) {
-                    phi.add_incoming(&[(result, *block)]);
+        }
+        if case_count == 0 || case_count > MAX_SWITCH_CASES {
+            return Ok(false);
+        }
+
+        // 型に収まらない値はどの値ともマッチしないため、caseにしない
+        let int_type = value.get_type();
+        let bit_width = int_type.get_bit_width();
+        let unsigned = bit_width == 1 || scrutinee_type.is_some_and(|ty| self.is_unsigned_integer_type(ty));
+        let fits = |v: i64| match (bit_width, unsigned) {
+            (64.., false) => true,
+            (64.., true) => v >= 0,
+            (_, true) => v >= 0 && v < (1i64 << bit_width),
+            (_, false) => v >= -(1i64 << (bit_width - 1)) && v < (1i64 << (bit_width - 1)),
+        };
+
+        // 値ごとに、その値にマッチしうる最初のアームへジャンプする（orパターンは選択肢ごとにcaseにする）。
+        // 識別子とワイルドカードのアームより後ろへは、どの値もそのアームを経由して進む
+        let mut seen = HashSet::new();
+        let mut cases = Vec::new();
+        let mut default_block = end_block;
+        for (arm, &block) in arms.iter().zip(arm_blocks) {
+            let mut values: Vec<i64> = Vec::new();
+            let mut matches_any = false;
+            for pattern in arm.pattern.alternatives() {
+                match pattern {
+                    Pattern::Literal(LiteralPattern::Integer(v)) => values.push(*v),
+                    Pattern::Literal(LiteralPattern::Bool(b)) => values.push(*b as i64),
+                    Pattern::Range { start, end, inclusive: true } => values.extend(*start..=*end),
+                    Pattern::Range { start, end, inclusive: false } => values.extend(*start..*end),
+                    _ => matches_any = true,
                 }
-                
-                Ok(phi.as_basic_value())
+            }
+            for v in values {
+                if fits(v) && seen.insert(v) {
+                    cases.push((int_type.const_int(v as u64, v < 0), block));
+                }
+            }
+            if matches_any {
+                default_block = block;
+                break;
             }
         }
+
+        // boolの値は2つしかないため、switchではなく条件分岐にする
+        if bit_width == 1 {
+            let target = |v: u64| {
+                cases
+                    .iter()
+                    .find(|(case, _)| case.get_zero_extended_constant() == Some(v))
+                    .map_or(default_block, |(_, block)| *block)
+            };
+            self.builder.build_conditional_branch(value, target(1), target(0))?;
+            return Ok(true);
+        }
+
+        self.builder.build_switch(value, default_block, &cases)?;
+        Ok(true)
     }
-    
+
     /// パターンマッチングをコンパイル
     fn compile_pattern_match(
         &mut self,
@@ -187,20 +295,7 @@ impl<'ctx> CodeGenerator<'ctx> {
                 match (lit, value) {
                     (LiteralPattern::Integer(expected), BasicValueEnum::IntValue(actual)) => {
                         let expected_val = self.context.i64_type().const_int(*expected as u64, expected < &0);
-                        // 実際の値が異なるビット幅の場合、適切にキャストする
-                        let actual_64 = match actual.get_type().get_bit_width().cmp(&64) {
-                            std::cmp::Ordering::Less => {
-                                if *expected < 0 {
-                                    self.builder.build_int_s_extend(actual, self.context.i64_type(), "sext")?
-                                } else {
-                                    self.builder.build_int_z_extend(actual, self.context.i64_type(), "zext")?
-                                }
-                            }
-                            std::cmp::Ordering::Greater => {
-                                self.builder.build_int_truncate(actual, self.context.i64_type(), "trunc")?
-                            }
-                            std::cmp::Ordering::Equal => actual,
-                        };
+                        let actual_64 = self.int_to_i64(actual, *expected < 0)?;
                         Ok(self.builder.build_int_compare(IntPredicate::EQ, actual_64, expected_val, "lit_match")?)
                     }
                     (LiteralPattern::Float(expected), BasicValueEnum::FloatValue(actual)) => {
@@ -213,7 +308,7 @@ impl<'ctx> CodeGenerator<'ctx> {
                     }
                     (LiteralPattern::String(expected), BasicValueEnum::PointerValue(actual)) => {
                         // 文字列比較のランタイム関数を使用
-                        let expected_str = self.builder.build_global_string_ptr(expected, "expected_str")?.as_pointer_value();
+                        let expected_str = self.builder.build_global_string_ptr(expected, &self.value_names.global("expected_str"))?.as_pointer_value();
                         
                         // yuni_string_eq関数を取得
                         let string_eq_fn = self.runtime_manager.get_function("yuni_string_eq")
@@ -246,98 +341,87 @@ impl<'ctx> CodeGenerator<'ctx> {
                     }
                 }
             }
-            Pattern::EnumVariant { enum_name, variant, fields } => {
-                // バリアントのインデックスを取得
-                let key = (enum_name.clone(), variant.clone());
-                let expected_index = self.enum_variants.get(&key)
-                    .ok_or_else(|| YuniError::Codegen(CodegenError::Undefined {
-                        name: format!("{}::{}", enum_name, variant),
+            Pattern::Range { start, end, inclusive } => {
+                // 範囲パターンは両端と比較する（負の値を含む範囲は符号付きで比較）
+                let BasicValueEnum::IntValue(actual) = value else {
+                    return Err(YuniError::Codegen(CodegenError::TypeError {
+                        expected: "integer value".to_string(),
+                        actual: format!("{:?}", value.get_type()),
                         span,
-                    }))?;
+                    }));
+                };
+                let signed = *start < 0;
+                let actual_64 = self.int_to_i64(actual, signed)?;
+                let i64_type = self.context.i64_type();
+                let (lower, upper) = if signed {
+                    (IntPredicate::SGE, if *inclusive { IntPredicate::SLE } else { IntPredicate::SLT })
+                } else {
+                    (IntPredicate::UGE, if *inclusive { IntPredicate::ULE } else { IntPredicate::ULT })
+                };
+                let start_val = i64_type.const_int(*start as u64, signed);
+                let end_val = i64_type.const_int(*end as u64, *end < 0);
+                let above = self.builder.build_int_compare(lower, actual_64, start_val, "range_start")?;
+                let below = self.builder.build_int_compare(upper, actual_64, end_val, "range_end")?;
+                Ok(self.builder.build_and(above, below, "range_match")?)
+            }
+            Pattern::EnumVariant { enum_name, variant, fields } => {
+                // バリアントを解決してタグを取得
+                let resolved = self.resolve_variant(enum_name, variant, span)?;
+                let expected = self.context.i32_type().const_int(resolved.variant().tag as u64, false);
                 
-                match fields {
-                    crate::ast::EnumVariantPatternFields::Unit => {
-                        // Unitバリアントの場合、値を直接比較
-                        if let BasicValueEnum::IntValue(int_val) = value {
-                            let expected = self.context.i32_type().const_int(*expected_index as u64, false);
-                            Ok(self.builder.build_int_compare(IntPredicate::EQ, int_val, expected, "enum_match")?)
-                        } else {
-                            Err(YuniError::Codegen(CodegenError::TypeError {
-                                expected: "enum value".to_string(),
-                                actual: format!("{:?}", value.get_type()),
-                                span,
-                            }))
-                        }
-                    }
-                    crate::ast::EnumVariantPatternFields::Tuple(patterns) => {
-                        // タプル形式のデータを持つバリアント
-                        if let BasicValueEnum::StructValue(struct_val) = value {
-                            // discriminantを抽出して比較
-                            let discriminant = self.builder.build_extract_value(struct_val, 0, "enum_discriminant")?
-                                .into_int_value();
-                            let expected = self.context.i32_type().const_int(*expected_index as u64, false);
-                            let discriminant_match = self.builder.build_int_compare(IntPredicate::EQ, discriminant, expected, "discriminant_match")?;
-                            
-                            // discriminantがマッチしない場合は早期リターン
-                            if patterns.is_empty() {
-                                return Ok(discriminant_match);
-                            }
-                            
-                            // データタプルを抽出
-                            let data_tuple = self.builder.build_extract_value(struct_val, 1, "enum_data")?;
-                            
-                            // 各フィールドのパターンマッチング
-                            let mut all_match = discriminant_match;
-                            for (i, pattern) in patterns.iter().enumerate() {
-                                let field_value = self.builder.build_extract_value(data_tuple.into_struct_value(), i as u32, &format!("field_{}", i))?;
-                                let field_match = self.compile_pattern_match(pattern, field_value, span)?;
-                                all_match = self.builder.build_and(all_match, field_match, &format!("field_match_{}", i))?;
-                            }
-                            
-                            Ok(all_match)
-                        } else {
-                            Err(YuniError::Codegen(CodegenError::TypeError {
-                                expected: "enum struct value".to_string(),
-                                actual: format!("{:?}", value.get_type()),
-                                span,
-                            }))
-                        }
+                let enum_val = match value {
+                    // フィールドを持たないEnumは値そのものがタグ
+                    BasicValueEnum::IntValue(int_val) => {
+                        return Ok(self.builder.build_int_compare(IntPredicate::EQ, int_val, expected, "enum_match")?);
                     }
-                    crate::ast::EnumVariantPatternFields::Struct(field_patterns) => {
-                        // 構造体形式のデータを持つバリアント
-                        if let BasicValueEnum::StructValue(struct_val) = value {
-                            // discriminantを抽出して比較
-                            let discriminant = self.builder.build_extract_value(struct_val, 0, "enum_discriminant")?
-                                .into_int_value();
-                            let expected = self.context.i32_type().const_int(*expected_index as u64, false);
-                            let discriminant_match = self.builder.build_int_compare(IntPredicate::EQ, discriminant, expected, "discriminant_match")?;
-                            
-                            // discriminantがマッチしない場合は早期リターン
-                            if field_patterns.is_empty() {
-                                return Ok(discriminant_match);
-                            }
-                            
-                            // データ構造体を抽出
-                            let data_struct = self.builder.build_extract_value(struct_val, 1, "enum_data")?;
-                            
-                            // 各フィールドのパターンマッチング
-                            let mut all_match = discriminant_match;
-                            for (i, (_, pattern)) in field_patterns.iter().enumerate() {
-                                let field_value = self.builder.build_extract_value(data_struct.into_struct_value(), i as u32, &format!("field_{}", i))?;
-                                let field_match = self.compile_pattern_match(pattern, field_value, span)?;
-                                all_match = self.builder.build_and(all_match, field_match, &format!("field_match_{}", i))?;
-                            }
-                            
-                            Ok(all_match)
-                        } else {
-                            Err(YuniError::Codegen(CodegenError::TypeError {
-                                expected: "enum struct value".to_string(),
-                                actual: format!("{:?}", value.get_type()),
-                                span,
-                            }))
-                        }
+                    BasicValueEnum::StructValue(struct_val) => struct_val,
+                    _ => {
+                        return Err(YuniError::Codegen(CodegenError::TypeError {
+                            expected: "enum value".to_string(),
+                            actual: format!("{:?}", value.get_type()),
+                            span,
+                        }));
                     }
+                };
+                
+                // タグを抽出して比較
+                let tag = self.builder.build_extract_value(enum_val, 0, "enum_tag")?
+                    .into_int_value();
+                let tag_match = self.builder.build_int_compare(IntPredicate::EQ, tag, expected, "tag_match")?;
+                
+                // 常にマッチするフィールドパターンしかなければタグの比較だけでよい
+                let sub_patterns: Vec<(u32, &Pattern)> = self.variant_sub_patterns(&resolved, fields, span)?
+                    .into_iter()
+                    .filter(|(_, pattern)| !matches!(pattern, Pattern::Identifier(_, _) | Pattern::Wildcard))
+                    .collect();
+                if sub_patterns.is_empty() {
+                    return Ok(tag_match);
+                }
+                
+                // フィールドは他のバリアントの値として読むと壊れているため、
+                // タグが一致した場合にだけフィールドのパターンを評価する
+                let function = self.builder.get_insert_block().unwrap().get_parent().unwrap();
+                let tag_block = self.builder.get_insert_block().unwrap();
+                let fields_block = self.context.append_basic_block(function, "enum_fields");
+                let done_block = self.context.append_basic_block(function, "enum_match_done");
+                self.builder.build_conditional_branch(tag_match, fields_block, done_block)?;
+                
+                self.builder.position_at_end(fields_block);
+                let payload = self.load_variant_payload(enum_val, enum_name, &resolved)?;
+                let mut all_match = self.context.bool_type().const_all_ones();
+                for (index, pattern) in sub_patterns {
+                    let field_value = self.builder.build_extract_value(payload, index, &format!("field_{}", index))?;
+                    let field_match = self.compile_pattern_match(pattern, field_value, span)?;
+                    all_match = self.builder.build_and(all_match, field_match, &format!("field_match_{}", index))?;
                 }
+                let fields_end_block = self.builder.get_insert_block().unwrap();
+                self.builder.build_unconditional_branch(done_block)?;
+                
+                self.builder.position_at_end(done_block);
+                let phi = self.builder.build_phi(self.context.bool_type(), "enum_match")?;
+                let no_match = self.context.bool_type().const_zero();
+                phi.add_incoming(&[(&no_match, tag_block), (&all_match, fields_end_block)]);
+                Ok(phi.as_basic_value().into_int_value())
             }
             Pattern::Tuple(patterns) => {
                 // タプル値であることを確認
@@ -368,24 +452,12 @@ impl<'ctx> CodeGenerator<'ctx> {
             Pattern::Struct(struct_name, field_patterns) => {
                 // 構造体値であることを確認
                 if let BasicValueEnum::StructValue(struct_val) = value {
-                    // 構造体情報を取得
-                    let struct_info = self.struct_info.get(struct_name)
-                        .ok_or_else(|| YuniError::Codegen(CodegenError::Undefined {
-                            name: struct_name.clone(),
-                            span,
-                        }))?
-                        .clone();
-                    
                     // すべてのフィールドがマッチするかチェック
                     let mut all_match = self.context.bool_type().const_all_ones();
                     
                     for (field_name, pattern) in field_patterns {
                         // フィールドのインデックスを取得
-                        let field_index = struct_info.get_field_index(field_name)
-                            .ok_or_else(|| YuniError::Codegen(CodegenError::Undefined {
-                                name: format!("{}.{}", struct_name, field_name),
-                                span,
-                            }))?;
+                        let (_, field_index, _) = self.struct_field_slot(struct_name, field_name, span)?;
                         
                         // フィールドの値を抽出
                         let field_value = self.builder.build_extract_value(struct_val, field_index, &format!("{}_value", field_name))?;
@@ -406,112 +478,257 @@ impl<'ctx> CodeGenerator<'ctx> {
                     }))
                 }
             }
+            Pattern::Binding(_, pattern) => self.compile_pattern_match(pattern, value, span),
+            Pattern::Or(patterns) => {
+                // 選択肢を順に照合し、いずれかがマッチすればマッチ
+                let mut any_match = self.context.bool_type().const_zero();
+                for (i, pattern) in patterns.iter().enumerate() {
+                    let alternative_match = self.compile_pattern_match(pattern, value, span)?;
+                    any_match = self.builder.build_or(any_match, alternative_match, &format!("or_match_{}", i))?;
+                }
+                Ok(any_match)
+            }
+        }
+    }
+    
+    /// バリアントパターンの各フィールドパターンを、フィールドの宣言順のインデックスと組にする
+    fn variant_sub_patterns<'p>(
+        &self,
+        resolved: &VariantRef<'ctx>,
+        fields: &'p EnumVariantPatternFields,
+        span: Span,
+    ) -> YuniResult<Vec<(u32, &'p Pattern)>> {
+        match fields {
+            EnumVariantPatternFields::Unit => Ok(Vec::new()),
+            EnumVariantPatternFields::Tuple(patterns) => {
+                Ok(patterns.iter().enumerate().map(|(i, pattern)| (i as u32, pattern)).collect())
+            }
+            EnumVariantPatternFields::Struct(field_patterns) => {
+                let variant_layout = resolved.variant();
+                field_patterns
+                    .iter()
+                    .map(|(field_name, pattern)| {
+                        variant_layout
+                            .field_index(field_name)
+                            .map(|index| (index, pattern))
+                            .ok_or_else(|| YuniError::Codegen(CodegenError::Undefined {
+                                name: format!("{}.{}", variant_layout.name, field_name),
+                                span,
+                            }))
+                    })
+                    .collect()
+            }
         }
     }
     
     /// パターンで導入された変数をバインド
+    ///
+    /// `ty` はマッチ対象の型です。分かっている場合は束縛する変数の型として使い、
+    /// 分からない場合はLLVMの値から推測します。
     fn bind_pattern_variables(
         &mut self,
         pattern: &Pattern,
         value: BasicValueEnum<'ctx>,
+        ty: Option<&Type>,
     ) -> YuniResult<()> {
         match pattern {
-            Pattern::Identifier(name, is_mut) => {
-                // 値を変数にバインド
-                let llvm_type = value.get_type();
-                let ptr = self.builder.build_alloca(llvm_type, name)?;
-                self.builder.build_store(ptr, value)?;
-                
-                // 型を推論
-                let ty = match value {
-                    BasicValueEnum::IntValue(int_val) => {
-                        match int_val.get_type().get_bit_width() {
-                            1 => Type::Bool,
-                            8 => Type::I8,
-                            16 => Type::I16,
-                            32 => Type::I32,
-                            64 => Type::I64,
-                            128 => Type::I128,
-                            _ => Type::I32,
-                        }
-                    }
-                    BasicValueEnum::FloatValue(float_val) => {
-                        if float_val.get_type() == self.context.f32_type() {
-                            Type::F32
-                        } else {
-                            Type::F64
-                        }
-                    }
-                    BasicValueEnum::PointerValue(_) => {
-                        // ポインタ型の場合は適切に処理（簡易実装）
-                        Type::String // 仮実装
-                    }
-                    _ => Type::I32, // デフォルト
-                };
-                
-                // スコープに登録
-                self.scope_manager.define_variable(name.clone(), ptr, ty, *is_mut);
-                Ok(())
-            }
-            Pattern::Literal(_) | Pattern::Wildcard => {
+            Pattern::Identifier(name, is_mut) => self.bind_variable(name, *is_mut, value, ty),
+            Pattern::Literal(_) | Pattern::Range { .. } | Pattern::Wildcard => {
                 // リテラルパターンとワイルドカードパターンは変数をバインドしない
                 Ok(())
             }
-            Pattern::EnumVariant { fields, .. } => {
-                match fields {
-                    crate::ast::EnumVariantPatternFields::Unit => {
-                        // Unitバリアントは変数をバインドしない
-                        Ok(())
-                    }
-                    crate::ast::EnumVariantPatternFields::Tuple(patterns) => {
-                        // データを持つEnumバリアントの場合、データ部分を抽出
-                        if let BasicValueEnum::StructValue(struct_val) = value {
-                            let data_tuple = self.builder.build_extract_value(struct_val, 1, "enum_data")?;
-                            
-                            // 各フィールドパターンの変数をバインド
-                            for (i, pattern) in patterns.iter().enumerate() {
-                                let field_value = self.builder.build_extract_value(data_tuple.into_struct_value(), i as u32, &format!("field_{}", i))?;
-                                self.bind_pattern_variables(pattern, field_value)?;
-                            }
-                        }
-                        Ok(())
-                    }
-                    crate::ast::EnumVariantPatternFields::Struct(field_patterns) => {
-                        // データを持つEnumバリアントの場合、データ部分を抽出
-                        if let BasicValueEnum::StructValue(struct_val) = value {
-                            let data_struct = self.builder.build_extract_value(struct_val, 1, "enum_data")?;
-                            
-                            // 各フィールドパターンの変数をバインド
-                            for (i, (_, pattern)) in field_patterns.iter().enumerate() {
-                                let field_value = self.builder.build_extract_value(data_struct.into_struct_value(), i as u32, &format!("field_{}", i))?;
-                                self.bind_pattern_variables(pattern, field_value)?;
-                            }
-                        }
-                        Ok(())
-                    }
+            Pattern::EnumVariant { enum_name, variant, fields } => {
+                // Unitバリアントとフィールドを持たないEnumは変数をバインドしない
+                let BasicValueEnum::StructValue(enum_val) = value else {
+                    return Ok(());
+                };
+                let resolved = self.resolve_variant(enum_name, variant, Span::dummy())?;
+                let sub_patterns = self.variant_sub_patterns(&resolved, fields, Span::dummy())?;
+                if sub_patterns.is_empty() {
+                    return Ok(());
+                }
+                
+                // 各フィールドパターンの変数を、フィールドの宣言された型でバインド
+                let payload = self.load_variant_payload(enum_val, enum_name, &resolved)?;
+                for (index, pattern) in sub_patterns {
+                    let field_value = self.builder.build_extract_value(payload, index, &format!("field_{}", index))?;
+                    let field_type = resolved.variant().fields.get(index as usize).map(|field| &field.ty);
+                    self.bind_pattern_variables(pattern, field_value, field_type)?;
                 }
+                Ok(())
             }
             Pattern::Tuple(patterns) => {
                 // タプルの各要素の変数をバインド
                 if let BasicValueEnum::StructValue(tuple_val) = value {
+                    let element_types = match ty {
+                        Some(Type::Tuple(types)) => Some(types),
+                        _ => None,
+                    };
                     for (i, pattern) in patterns.iter().enumerate() {
                         let element = self.builder.build_extract_value(tuple_val, i as u32, &format!("tuple_elem_{}", i))?;
-                        self.bind_pattern_variables(pattern, element)?;
+                        let element_type = element_types.and_then(|types| types.get(i));
+                        self.bind_pattern_variables(pattern, element, element_type)?;
                     }
                 }
                 Ok(())
             }
-            Pattern::Struct(_, field_patterns) => {
+            Pattern::Struct(struct_name, field_patterns) => {
                 // 構造体の各フィールドの変数をバインド
                 if let BasicValueEnum::StructValue(struct_val) = value {
-                    for (i, (_, pattern)) in field_patterns.iter().enumerate() {
-                        let field_value = self.builder.build_extract_value(struct_val, i as u32, &format!("field_{}", i))?;
-                        self.bind_pattern_variables(pattern, field_value)?;
+                    // フィールドの変数をバインドする間もフィールドの型を参照するため共有する
+                    let layout = Rc::clone(self.struct_layouts.get(struct_name)
+                        .ok_or_else(|| YuniError::Codegen(CodegenError::Undefined {
+                            name: struct_name.clone(),
+                            span: Span::dummy(),
+                        }))?);
+                    for (field_name, pattern) in field_patterns {
+                        let (field_index, field) = layout.field(field_name)
+                            .ok_or_else(|| YuniError::Codegen(CodegenError::Undefined {
+                                name: format!("{}.{}", struct_name, field_name),
+                                span: Span::dummy(),
+                            }))?;
+                        let field_value = self.builder.build_extract_value(struct_val, field_index, &format!("{}_value", field_name))?;
+                        let field_type = Some(&field.ty);
+                        self.bind_pattern_variables(pattern, field_value, field_type)?;
                     }
                 }
                 Ok(())
             }
+            Pattern::Binding(name, pattern) => {
+                // 一致した値全体を束縛してから、部分パターンの変数を束縛する
+                self.bind_variable(name, false, value, ty)?;
+                self.bind_pattern_variables(pattern, value, ty)
+            }
+            Pattern::Or(patterns) => self.bind_or_pattern_variables(patterns, value, ty),
+        }
+    }
+
+    /// 値を変数にバインド
+    ///
+    /// `ty` が分からない場合は変数の型をLLVMの値から推測します。
+    fn bind_variable(
+        &mut self,
+        name: &str,
+        is_mut: bool,
+        value: BasicValueEnum<'ctx>,
+        ty: Option<&Type>,
+    ) -> YuniResult<()> {
+        let llvm_type = value.get_type();
+        let ptr = self.builder.build_alloca(llvm_type, &naming::address_name(name))?;
+        self.builder.build_store(ptr, value)?;
+        
+        // 型が分からない場合は値から推論
+        let ty = match ty {
+            Some(ty) => ty.clone(),
+            None => match value {
+                BasicValueEnum::IntValue(int_val) => {
+                    match int_val.get_type().get_bit_width() {
+                        1 => Type::Bool,
+                        8 => Type::I8,
+                        16 => Type::I16,
+                        32 => Type::I32,
+                        64 => Type::I64,
+                        128 => Type::I128,
+                        _ => Type::I32,
+                    }
+                }
+                BasicValueEnum::FloatValue(float_val) => {
+                    if float_val.get_type() == self.context.f32_type() {
+                        Type::F32
+                    } else {
+                        Type::F64
+                    }
+                }
+                BasicValueEnum::PointerValue(_) => {
+                    // ポインタ型の場合は適切に処理（簡易実装）
+                    Type::String // 仮実装
+                }
+                _ => Type::I32, // デフォルト
+            },
+        };
+        
+        // スコープに登録
+        self.scope_manager.define_variable(name.to_string(), ptr, ty, is_mut);
+        Ok(())
+    }
+
+    /// orパターンで導入された変数をバインド
+    ///
+    /// どの選択肢にマッチしたかは実行時にしか分からないため、選択肢を順に照合し、
+    /// マッチした選択肢の変数の値を選択肢の間で共有するスタック領域に格納します。
+    /// マッチしなかった選択肢のフィールドは他のバリアントとして読むと壊れているため読みません。
+    fn bind_or_pattern_variables(
+        &mut self,
+        patterns: &[Pattern],
+        value: BasicValueEnum<'ctx>,
+        ty: Option<&Type>,
+    ) -> YuniResult<()> {
+        if !patterns.iter().any(binds_variables) {
+            return Ok(());
+        }
+        
+        let function = self.builder.get_insert_block().unwrap().get_parent().unwrap();
+        let done_block = self.context.append_basic_block(function, "or_bind_done");
+        let mut slots = Vec::new();
+        for (i, pattern) in patterns.iter().enumerate() {
+            // 最後の選択肢は、前の選択肢がすべてマッチしなかった場合に必ずマッチする
+            let next_block = if i + 1 < patterns.len() {
+                let matches = self.compile_pattern_match(pattern, value, Span::dummy())?;
+                let bind_block = self.context.append_basic_block(function, &format!("or_bind{}", i));
+                let next_block = self.context.append_basic_block(function, &format!("or_next{}", i));
+                self.builder.build_conditional_branch(matches, bind_block, next_block)?;
+                self.builder.position_at_end(bind_block);
+                Some(next_block)
+            } else {
+                None
+            };
+            self.store_alternative_bindings(pattern, value, ty, &mut slots)?;
+            self.builder.build_unconditional_branch(done_block)?;
+            if let Some(next_block) = next_block {
+                self.builder.position_at_end(next_block);
+            }
         }
+        
+        self.builder.position_at_end(done_block);
+        for (name, slot, ty, is_mut) in slots {
+            self.scope_manager.define_variable(name, slot, ty, is_mut);
+        }
+        Ok(())
+    }
+
+    /// orパターンの選択肢の変数をバインドし、その値を共有するスタック領域に格納する
+    ///
+    /// スタック領域は最初の選択肢の変数から、名前順に作ります。
+    fn store_alternative_bindings(
+        &mut self,
+        pattern: &Pattern,
+        value: BasicValueEnum<'ctx>,
+        ty: Option<&Type>,
+        slots: &mut Vec<(String, PointerValue<'ctx>, Type, bool)>,
+    ) -> YuniResult<()> {
+        self.scope_manager.push_scope();
+        let bound = self.bind_pattern_variables(pattern, value, ty);
+        let scope = self.scope_manager.pop_scope().unwrap_or_default();
+        bound?;
+        
+        if slots.is_empty() {
+            let mut names: Vec<&String> = scope.symbols.keys().collect();
+            names.sort();
+            for name in names {
+                let symbol = &scope.symbols[name];
+                let slot = self.create_entry_block_alloca(&naming::address_name(name), &symbol.ty)?;
+                slots.push((name.clone(), slot, symbol.ty.clone(), symbol.is_mutable));
+            }
+        }
+        for (name, slot, slot_type, _) in slots.iter() {
+            let symbol = scope.lookup(name).ok_or_else(|| YuniError::Codegen(CodegenError::Internal {
+                message: format!("Or pattern alternative does not bind {}", name),
+            }))?;
+            let llvm_type = self.type_manager.ast_type_to_llvm(slot_type)?;
+            let bound_value = self.builder.build_load(llvm_type, symbol.ptr, name)?;
+            self.builder.build_store(*slot, bound_value)?;
+        }
+        Ok(())
     }
 
     /// if式をコンパイル
@@ -536,6 +753,15 @@ impl<'ctx> CodeGenerator<'ctx> {
             })),
         };
 
+        // 条件が定数なら選ばれる分岐だけを生成する（merge ブロックとphiは不要）
+        if let Some(taken) = Self::constant_condition(condition_bool) {
+            return match (taken, &if_expr.else_branch) {
+                (true, _) => self.compile_expression(&if_expr.then_branch),
+                (false, Some(else_branch)) => self.compile_expression(else_branch),
+                (false, None) => Ok(self.context.i32_type().const_zero().into()),
+            };
+        }
+
         let function = self.builder.get_insert_block().unwrap().get_parent().unwrap();
         let then_bb = self.context.append_basic_block(function, "then");
         let else_bb = self.context.append_basic_block(function, "else");
@@ -544,11 +770,10 @@ impl<'ctx> CodeGenerator<'ctx> {
         // 条件分岐
         self.builder.build_conditional_branch(condition_bool, then_bb, else_bb)?;
 
-        // then ブロック
+        // then ブロック（returnで抜けた分岐は合流しない）
         self.builder.position_at_end(then_bb);
         let then_value = self.compile_expression(&if_expr.then_branch)?;
-        self.builder.build_unconditional_branch(merge_bb)?;
-        let then_bb = self.builder.get_insert_block().unwrap();
+        let then_result = self.branch_to_merge(then_value, merge_bb)?;
 
         // else ブロック
         self.builder.position_at_end(else_bb);
@@ -558,43 +783,103 @@ impl<'ctx> CodeGenerator<'ctx> {
             // else句がない場合はunit値
             self.context.i32_type().const_zero().into()
         };
+        let else_result = self.branch_to_merge(else_value, merge_bb)?;
+
+        let incoming: Vec<_> = then_result.into_iter().chain(else_result).collect();
+        self.merge_branch_values(&incoming, merge_bb, "if_result")
+    }
+
+    /// 分岐の終端から合流ブロックへジャンプし、合流する値とブロックを返す
+    ///
+    /// 分岐がreturnなどで既に終端している（発散した）場合は合流せず、`None` を返します。
+    fn branch_to_merge(
+        &mut self,
+        value: BasicValueEnum<'ctx>,
+        merge_bb: BasicBlock<'ctx>,
+    ) -> YuniResult<Option<(BasicValueEnum<'ctx>, BasicBlock<'ctx>)>> {
+        if self.current_block_has_terminator() {
+            return Ok(None);
+        }
         self.builder.build_unconditional_branch(merge_bb)?;
-        let else_bb = self.builder.get_insert_block().unwrap();
+        Ok(Some((value, self.builder.get_insert_block().unwrap())))
+    }
+
+    /// 合流ブロックで分岐の値をphiにまとめる
+    ///
+    /// 合流する分岐がなければ式全体が発散するため、合流ブロックを削除して
+    /// 終端したブロックにビルダーを残します（囲む文はそれを見て以降の生成をやめます）。
+    /// 値の型が分岐で異なる場合はunit値を返します。
+    fn merge_branch_values(
+        &mut self,
+        incoming: &[(BasicValueEnum<'ctx>, BasicBlock<'ctx>)],
+        merge_bb: BasicBlock<'ctx>,
+        name: &str,
+    ) -> YuniResult<BasicValueEnum<'ctx>> {
+        let unit_value: BasicValueEnum<'ctx> = self.context.i32_type().const_zero().into();
+        let Some((first_value, _)) = incoming.first() else {
+            // SAFETY: 合流ブロックへの分岐はなく、命令も生成していない
+            unsafe {
+                let _ = merge_bb.delete();
+            }
+            return Ok(unit_value);
+        };
 
-        // merge ブロック
         self.builder.position_at_end(merge_bb);
-        
-        // 両方のブランチで同じ型の値を返す必要がある
-        if then_value.get_type() == else_value.get_type() {
-            let phi = self.builder.build_phi(then_value.get_type(), "if_result")?;
-            phi.add_incoming(&[(&then_value, then_bb), (&else_value, else_bb)]);
-            Ok(phi.as_basic_value())
-        } else {
-            // 型が異なる場合はunit値を返す
-            Ok(self.context.i32_type().const_zero().into())
+        let result_type = first_value.get_type();
+        if incoming.iter().any(|(value, _)| value.get_type() != result_type) {
+            return Ok(unit_value);
+        }
+        let phi = self.builder.build_phi(result_type, name)?;
+        for (value, block) in incoming {
+            phi.add_incoming(&[(value, *block)]);
         }
+        Ok(phi.as_basic_value())
     }
 
     /// ブロック式をコンパイル
     pub fn compile_block_expr(&mut self, block_expr: &BlockExpr) -> YuniResult<BasicValueEnum<'ctx>> {
         // 新しいスコープを作成
         self.scope_manager.push_scope();
+        self.enter_defer_scope();
         
         let mut last_value: BasicValueEnum = self.context.i32_type().const_zero().into();
         
-        // ブロック内の文を順次コンパイル
+        // ブロック内の文を順次コンパイル（returnで抜けた後の文と式には到達しない）
         for stmt in &block_expr.statements {
             self.compile_statement(stmt)?;
+            if self.current_block_has_terminator() {
+                break;
+            }
         }
         
         // 最後の式がある場合はその値を返す
         if let Some(last_expr) = &block_expr.last_expr {
-            last_value = self.compile_expression(last_expr)?;
+            if !self.current_block_has_terminator() {
+                last_value = self.compile_expression(last_expr)?;
+            }
         }
         
+        // 最後の式を評価してからdeferを実行
+        self.leave_defer_scope()?;
+        
         // スコープを終了
         self.scope_manager.pop_scope();
         
         Ok(last_value)
     }
 }
+
+/// パターンが変数を束縛するか
+fn binds_variables(pattern: &Pattern) -> bool {
+    match pattern {
+        Pattern::Identifier(_, _) | Pattern::Binding(_, _) => true,
+        Pattern::Tuple(patterns) | Pattern::Or(patterns) => patterns.iter().any(binds_variables),
+        Pattern::Struct(_, fields) => fields.iter().any(|(_, pattern)| binds_variables(pattern)),
+        Pattern::EnumVariant { fields, .. } => match fields {
+            EnumVariantPatternFields::Tuple(patterns) => patterns.iter().any(binds_variables),
+            EnumVariantPatternFields::Struct(fields) => fields.iter().any(|(_, pattern)| binds_variables(pattern)),
+            EnumVariantPatternFields::Unit => false,
+        },
+        Pattern::Literal(_) | Pattern::Range { .. } | Pattern::Wildcard => false,
+    }
+}
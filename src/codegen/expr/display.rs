@@ -0,0 +1,175 @@
+//! 値の文字列への変換のコード生成
+//!
+//! テンプレート文字列の補間・`to_string`・`StringBuilder::append`・アサーションの失敗時の表示で使います。
+//! 変換の方法は値のLLVMの型ではなく式の型で決めます（u64の大きな値を負数と、
+//! 列挙型の値をタグの数値と取り違えないため）。セマンティック解析は補間できる型だけを受け付けます。
+//!
+//! - 整数: 符号付きはi64に符号拡張、符号なしはゼロ拡張して10進数にする
+//! - 浮動小数点数・bool・文字列: それぞれの型の書式で変換する
+//! - フィールドを持たないバリアントだけの列挙型: `Color::Red` のようにバリアントの名前にする
+//! - 参照: 参照先の値を変換する
+
+use crate::ast::*;
+use crate::error::{CodegenError, YuniError, YuniResult};
+use inkwell::module::Linkage;
+use inkwell::values::{BasicMetadataValueEnum, BasicValueEnum, IntValue, PointerValue};
+use inkwell::AddressSpace;
+
+use crate::codegen::code_generator::CodeGenerator;
+
+/// 文字列に変換する値の種類
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DisplayKind {
+    Bool,
+    Int,
+    UInt,
+    F32,
+    F64,
+    Str,
+}
+
+impl DisplayKind {
+    /// 値を新しい文字列に変換するランタイム関数（文字列はそのまま使う）
+    fn to_string_function(self) -> Option<&'static str> {
+        match self {
+            Self::Bool => Some("yuni_bool_to_string"),
+            Self::Int => Some("yuni_int_to_string"),
+            Self::UInt => Some("yuni_u64_to_string"),
+            Self::F32 => Some("yuni_f32_to_string"),
+            Self::F64 => Some("yuni_f64_to_string"),
+            Self::Str => None,
+        }
+    }
+
+    /// 値を文字列ビルダーに追加するランタイム関数
+    fn append_function(self) -> &'static str {
+        match self {
+            Self::Bool => "yuni_sb_append_bool",
+            Self::Int => "yuni_sb_append_int",
+            Self::UInt => "yuni_sb_append_uint",
+            Self::F32 => "yuni_sb_append_f32",
+            Self::F64 => "yuni_sb_append_float",
+            Self::Str => "yuni_sb_append_str",
+        }
+    }
+}
+
+impl<'ctx> CodeGenerator<'ctx> {
+    /// 型 `ty` の値を文字列に変換
+    pub fn value_to_string(&mut self, value: BasicValueEnum<'ctx>, ty: &Type) -> YuniResult<BasicValueEnum<'ctx>> {
+        let (kind, operand) = self.display_operand(value, ty)?;
+        let Some(function_name) = kind.to_string_function() else {
+            // 文字列はそのまま返す
+            return Ok(operand);
+        };
+        self.call_runtime_function(function_name, &[operand.into()])
+    }
+
+    /// 型 `ty` の値を文字列ビルダーに追加
+    ///
+    /// `value_to_string`と同じ書式になるよう、値の種類ごとに追加関数を選びます。
+    /// 中間の文字列を作らないため、連結を繰り返すより安価です。
+    pub fn append_to_string_builder(
+        &mut self,
+        sb: PointerValue<'ctx>,
+        value: BasicValueEnum<'ctx>,
+        ty: &Type,
+    ) -> YuniResult<()> {
+        let (kind, operand) = self.display_operand(value, ty)?;
+        let function_name = kind.append_function();
+        let append_fn = self.runtime_manager.get_function(function_name)
+            .ok_or_else(|| YuniError::Codegen(CodegenError::Internal {
+                message: format!("Runtime function {} not found", function_name),
+            }))?;
+        let args: [BasicMetadataValueEnum<'ctx>; 2] = [sb.into(), operand.into()];
+        self.builder.build_call(append_fn, &args, "")?;
+        Ok(())
+    }
+
+    /// 値の種類と、ランタイム関数に渡す形に変換した値を求める
+    fn display_operand(&mut self, value: BasicValueEnum<'ctx>, ty: &Type) -> YuniResult<(DisplayKind, BasicValueEnum<'ctx>)> {
+        let ty = self.type_manager.resolve_alias(ty).clone();
+        if let (Type::Reference(inner, _), BasicValueEnum::PointerValue(ptr)) = (&ty, value) {
+            let llvm_type = self.type_manager.ast_type_to_llvm(inner)?;
+            let value = self.builder.build_load(llvm_type, ptr, "display_deref")?;
+            return self.display_operand(value, inner);
+        }
+        if let (Type::UserDefined(name), BasicValueEnum::IntValue(tag)) = (&ty, value) {
+            if self.enum_layout(name).is_some() {
+                let variant_name = self.enum_variant_name(name, tag)?;
+                return Ok((DisplayKind::Str, variant_name.into()));
+            }
+        }
+
+        match value {
+            // boolは "true" / "false" に変換（符号拡張すると -1 になるため）
+            BasicValueEnum::IntValue(int_val) if int_val.get_type().get_bit_width() == 1 => {
+                Ok((DisplayKind::Bool, value))
+            }
+            BasicValueEnum::IntValue(int_val) => {
+                let unsigned = self.is_unsigned_integer_type(&ty);
+                let i64_type = self.context.i64_type();
+                let int_val = match int_val.get_type().get_bit_width().cmp(&64) {
+                    std::cmp::Ordering::Less if unsigned => self.builder.build_int_z_extend(int_val, i64_type, "zext_to_i64")?,
+                    std::cmp::Ordering::Less => self.builder.build_int_s_extend(int_val, i64_type, "sext_to_i64")?,
+                    // 補間できないi128などはアサーションの表示に限り下位64ビットを使う
+                    std::cmp::Ordering::Greater => self.builder.build_int_truncate(int_val, i64_type, "trunc_to_i64")?,
+                    std::cmp::Ordering::Equal => int_val,
+                };
+                let kind = if unsigned { DisplayKind::UInt } else { DisplayKind::Int };
+                Ok((kind, int_val.into()))
+            }
+            // f32はf64に広げると桁が増えるため専用の関数で変換する
+            BasicValueEnum::FloatValue(float_val) if float_val.get_type() == self.context.f32_type() => {
+                Ok((DisplayKind::F32, value))
+            }
+            BasicValueEnum::FloatValue(_) => Ok((DisplayKind::F64, value)),
+            BasicValueEnum::PointerValue(_) if matches!(ty, Type::String | Type::Str) => Ok((DisplayKind::Str, value)),
+            _ => Err(YuniError::Codegen(CodegenError::Unimplemented {
+                feature: format!("String conversion for type {:?}", ty),
+                span: Span::dummy(),
+            })),
+        }
+    }
+
+    /// 列挙型 `enum_name` のタグ `tag` のバリアントの名前（`Color::Red`）
+    ///
+    /// 列挙型ごとにバリアントの名前の表を定数として1つだけ置き、タグで引きます。
+    fn enum_variant_name(&mut self, enum_name: &str, tag: IntValue<'ctx>) -> YuniResult<PointerValue<'ctx>> {
+        let layout = self.enum_layout(enum_name).cloned()
+            .ok_or_else(|| YuniError::Codegen(CodegenError::Internal {
+                message: format!("Enum layout for {} not found", enum_name),
+            }))?;
+        let ptr_type = self.context.ptr_type(AddressSpace::default());
+        let table_type = ptr_type.array_type(layout.variants.len() as u32);
+        let table_name = format!("{}.variant_names", enum_name);
+        let table = match self.module.get_global(&table_name) {
+            Some(table) => table,
+            None => {
+                let names: Vec<PointerValue<'ctx>> = layout.variants
+                    .iter()
+                    .map(|variant| {
+                        let text = format!("{}::{}", enum_name, variant.name);
+                        let name_const = self.context.const_string(text.as_bytes(), true);
+                        let global = self.module.add_global(name_const.get_type(), None, &format!("{}.name", text));
+                        global.set_initializer(&name_const);
+                        global.set_constant(true);
+                        global.set_linkage(Linkage::Private);
+                        global.as_pointer_value()
+                    })
+                    .collect();
+                let table = self.module.add_global(table_type, None, &table_name);
+                table.set_initializer(&ptr_type.const_array(&names));
+                table.set_constant(true);
+                table.set_linkage(Linkage::Private);
+                table
+            }
+        };
+
+        let zero = self.context.i32_type().const_zero();
+        let entry = unsafe {
+            self.builder.build_in_bounds_gep(table_type, table.as_pointer_value(), &[zero, tag], "variant_name_ptr")?
+        };
+        Ok(self.builder.build_load(ptr_type, entry, "variant_name")?.into_pointer_value())
+    }
+}
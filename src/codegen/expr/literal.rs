@@ -1,5 +1,6 @@
 //! リテラル式のコード生成
 
+use crate::analyzer::numeric::{float_literal_type, integer_literal_type};
 use crate::ast::*;
 use crate::error::{CodegenError, YuniError, YuniResult};
 use inkwell::values::BasicValueEnum;
@@ -14,64 +15,42 @@ impl<'ctx> CodeGenerator<'ctx> {
     }
 
     /// 整数リテラルをコンパイル（期待される型のコンテキスト付き）
+    ///
+    /// 型はセマンティック解析と同じ規則（`literal_type`）で決めます。
+    /// 期待される型が浮動小数点型の場合は浮動小数点数の定数になります。
     pub fn compile_integer_literal_with_type(&self, lit: &IntegerLit, expected_type: Option<&Type>) -> YuniResult<BasicValueEnum<'ctx>> {
-        let int_type = if let Some(suffix) = &lit.suffix {
-            match suffix.as_str() {
-                "i8" => self.context.i8_type(),
-                "i16" => self.context.i16_type(),
-                "i32" => self.context.i32_type(),
-                "i64" => self.context.i64_type(),
-                "i128" => self.context.i128_type(),
-                "u8" => self.context.i8_type(),
-                "u16" => self.context.i16_type(),
-                "u32" => self.context.i32_type(),
-                "u64" => self.context.i64_type(),
-                "u128" => self.context.i128_type(),
-                _ => self.context.i32_type(), // デフォルト
-            }
-        } else {
-            // 期待される型が指定されている場合はそれを使用
-            if let Some(expected) = expected_type {
-                match expected {
-                    Type::I8 => self.context.i8_type(),
-                    Type::I16 => self.context.i16_type(),
-                    Type::I32 => self.context.i32_type(),
-                    Type::I64 => self.context.i64_type(),
-                    Type::I128 => self.context.i128_type(),
-                    Type::U8 => self.context.i8_type(),
-                    Type::U16 => self.context.i16_type(),
-                    Type::U32 => self.context.i32_type(),
-                    Type::U64 => self.context.i64_type(),
-                    Type::U128 => self.context.i128_type(),
-                    _ => self.context.i32_type(), // 整数型でない場合はデフォルト
-                }
-            } else {
-                self.context.i32_type() // デフォルトはi32（Rustと同じ）
-            }
+        let value = match integer_literal_type(lit, expected_type) {
+            Type::F32 => self.context.f32_type().const_float(lit.value as f64).into(),
+            Type::F64 => self.context.f64_type().const_float(lit.value as f64).into(),
+            Type::I8 | Type::U8 => self.context.i8_type().const_int(lit.value as u64, false).into(),
+            Type::I16 | Type::U16 => self.context.i16_type().const_int(lit.value as u64, false).into(),
+            Type::I64 | Type::U64 => self.context.i64_type().const_int(lit.value as u64, false).into(),
+            Type::I128 | Type::U128 => self.context.i128_type().const_int(lit.value as u64, false).into(),
+            _ => self.context.i32_type().const_int(lit.value as u64, false).into(),
         };
-
-        Ok(int_type.const_int(lit.value as u64, false).into())
+        Ok(value)
     }
 
-    /// 浮動小数点リテラルをコンパイル
+    /// 浮動小数点リテラルをコンパイル（期待される型のコンテキストなし）
+    #[allow(dead_code)]
     pub fn compile_float_literal(&self, lit: &FloatLit) -> YuniResult<BasicValueEnum<'ctx>> {
-        let float_type = if let Some(suffix) = &lit.suffix {
-            match suffix.as_str() {
-                "f32" => self.context.f32_type(),
-                "f64" => self.context.f64_type(),
-                _ => self.context.f64_type(), // デフォルト
-            }
-        } else {
-            self.context.f64_type() // デフォルトはf64
+        self.compile_float_literal_with_type(lit, None)
+    }
+
+    /// 浮動小数点リテラルをコンパイル（期待される型のコンテキスト付き）
+    pub fn compile_float_literal_with_type(&self, lit: &FloatLit, expected_type: Option<&Type>) -> YuniResult<BasicValueEnum<'ctx>> {
+        let float_type = match float_literal_type(lit, expected_type) {
+            Type::F32 => self.context.f32_type(),
+            _ => self.context.f64_type(),
         };
 
         Ok(float_type.const_float(lit.value).into())
     }
 
     /// 文字列リテラルをコンパイル
-    pub fn compile_string_literal(&self, lit: &StringLit) -> YuniResult<BasicValueEnum<'ctx>> {
+    pub fn compile_string_literal(&mut self, lit: &StringLit) -> YuniResult<BasicValueEnum<'ctx>> {
         let string_const = self.context.const_string(lit.value.as_bytes(), true);
-        let global = self.module.add_global(string_const.get_type(), None, "str");
+        let global = self.module.add_global(string_const.get_type(), None, &self.value_names.global("str"));
         global.set_initializer(&string_const);
         global.set_constant(true);
 
@@ -95,6 +74,46 @@ impl<'ctx> CodeGenerator<'ctx> {
         Ok(ptr.into())
     }
 
+    /// 文字列リテラルをコンパイル（期待される型のコンテキスト付き）
+    ///
+    /// `String` の位置に置いたリテラルは、静的な文字列データを `yuni_string_from_parts` で複製した
+    /// 所有する文字列になります。それ以外の位置では静的な文字列データを指す `str` のままです。
+    pub fn compile_string_literal_with_type(&mut self, lit: &StringLit, expected_type: Option<&Type>) -> YuniResult<BasicValueEnum<'ctx>> {
+        let data = self.compile_string_literal(lit)?;
+        if expected_type != Some(&Type::String) {
+            return Ok(data);
+        }
+        let len = self.context.i64_type().const_int(lit.value.len() as u64, false);
+        self.call_runtime_function("yuni_string_from_parts", &[data.into(), len.into()])
+    }
+
+    /// バイト列リテラルをコンパイル
+    ///
+    /// バイト列を定数のグローバル変数に置き、`yuni_vec_from_bytes` で一度に `Vec<u8>` へコピーします。
+    /// 長さを渡すため、NULを含むバイト列も途中で切れません。
+    pub fn compile_bytes_literal(&mut self, lit: &BytesLit) -> YuniResult<BasicValueEnum<'ctx>> {
+        let vec_from_bytes = self.runtime_manager.get_function("yuni_vec_from_bytes")
+            .ok_or_else(|| YuniError::Codegen(CodegenError::Internal {
+                message: "yuni_vec_from_bytes not found".to_string(),
+            }))?;
+
+        let bytes_const = self.context.const_string(&lit.value, false);
+        let global = self.module.add_global(bytes_const.get_type(), None, &self.value_names.global("bytes"));
+        global.set_initializer(&bytes_const);
+        global.set_constant(true);
+
+        let len = self.context.i64_type().const_int(lit.value.len() as u64, false);
+        let result = self.builder.build_call(
+            vec_from_bytes,
+            &[global.as_pointer_value().into(), len.into()],
+            "bytes_vec",
+        )?;
+        result.try_as_basic_value().basic()
+            .ok_or_else(|| YuniError::Codegen(CodegenError::Internal {
+                message: "yuni_vec_from_bytes returned void".to_string(),
+            }))
+    }
+
     /// ブール値リテラルをコンパイル
     pub fn compile_boolean_literal(&self, lit: &BooleanLit) -> YuniResult<BasicValueEnum<'ctx>> {
         Ok(self.context.bool_type().const_int(lit.value as u64, false).into())
@@ -109,6 +128,11 @@ impl<'ctx> CodeGenerator<'ctx> {
             });
         }
 
+        // 3要素以上は文字列ビルダーで組み立て、連結のたびに全体をコピーしないようにする
+        if lit.parts.len() > 2 {
+            return self.compile_template_string_with_builder(lit);
+        }
+
         let mut result: Option<BasicValueEnum> = None;
 
         for part in &lit.parts {
@@ -119,7 +143,8 @@ impl<'ctx> CodeGenerator<'ctx> {
                 })?,
                 TemplateStringPart::Interpolation(expr) => {
                     let value = self.compile_expression(expr)?;
-                    self.value_to_string(value)?
+                    let value_type = self.expression_type(expr)?;
+                    self.value_to_string(value, &value_type)?
                 }
             };
 
@@ -143,4 +168,42 @@ impl<'ctx> CodeGenerator<'ctx> {
             message: "Empty template string".to_string(),
         }))
     }
+
+    /// テンプレート文字列を文字列ビルダーで組み立て
+    fn compile_template_string_with_builder(&mut self, lit: &TemplateStringLit) -> YuniResult<BasicValueEnum<'ctx>> {
+        let sb_new_fn = self.runtime_manager.get_function("yuni_sb_new")
+            .ok_or_else(|| YuniError::Codegen(CodegenError::Internal {
+                message: "Runtime function yuni_sb_new not found".to_string(),
+            }))?;
+        let sb = self.builder.build_call(sb_new_fn, &[], "template_sb")?
+            .try_as_basic_value().basic()
+            .ok_or_else(|| YuniError::Codegen(CodegenError::Internal {
+                message: "yuni_sb_new should return a value".to_string(),
+            }))?
+            .into_pointer_value();
+
+        for part in &lit.parts {
+            let (value, value_type) = match part {
+                TemplateStringPart::Text(text) => {
+                    let value = self.compile_string_literal(&StringLit {
+                        value: text.clone(),
+                        span: lit.span,
+                    })?;
+                    (value, Type::Str)
+                }
+                TemplateStringPart::Interpolation(expr) => (self.compile_expression(expr)?, self.expression_type(expr)?),
+            };
+            self.append_to_string_builder(sb, value, &value_type)?;
+        }
+
+        let sb_finish_fn = self.runtime_manager.get_function("yuni_sb_finish")
+            .ok_or_else(|| YuniError::Codegen(CodegenError::Internal {
+                message: "Runtime function yuni_sb_finish not found".to_string(),
+            }))?;
+        self.builder.build_call(sb_finish_fn, &[sb.into()], "template_result")?
+            .try_as_basic_value().basic()
+            .ok_or_else(|| YuniError::Codegen(CodegenError::Internal {
+                message: "yuni_sb_finish should return a value".to_string(),
+            }))
+    }
 }
@@ -0,0 +1,116 @@
+//! HashMapのインデックスアクセスのコード生成
+//!
+//! `map[key]` の読み出しは `yuni_hashmap_get` で値へのポインタを取得して読み出し、キーが
+//! なければキーを示してパニックします。`map[key] = value` は `yuni_hashmap_insert` を呼び出し、
+//! 既にあるキーの値は上書きします。`&map[key]` は値へのポインタをそのまま返し、キーがなければ
+//! nullの参照になります（参照先を読み出すときに検査します）。
+
+use crate::ast::*;
+use crate::error::{CodegenError, YuniError, YuniResult};
+use inkwell::types::BasicTypeEnum;
+use inkwell::values::{BasicValueEnum, PointerValue};
+
+use crate::codegen::code_generator::CodeGenerator;
+
+/// HashMapの型（参照を含む）であれば、キーと値の型を返す
+pub(crate) fn hashmap_types(ty: &Type) -> Option<(&Type, &Type)> {
+    match ty {
+        Type::Reference(inner, _) => hashmap_types(inner),
+        Type::Generic(name, args) if name == "HashMap" && args.len() == 2 => Some((&args[0], &args[1])),
+        _ => None,
+    }
+}
+
+impl<'ctx> CodeGenerator<'ctx> {
+    /// `map[key]` の値を読み出す（キーがなければパニック）
+    pub(super) fn compile_hashmap_index(
+        &mut self,
+        index: &IndexExpr,
+        key_type: &Type,
+        value_type: &Type,
+    ) -> YuniResult<BasicValueEnum<'ctx>> {
+        let (value_ptr, key) = self.compile_hashmap_lookup(index, key_type)?;
+
+        let function = self.current_function
+            .ok_or_else(|| YuniError::Codegen(CodegenError::Internal {
+                message: "No current function".to_string(),
+            }))?;
+        let missing_block = self.context.append_basic_block(function, "map.missing");
+        let found_block = self.context.append_basic_block(function, "map.found");
+        let is_missing = self.builder.build_is_null(value_ptr, "key_missing")?;
+        self.builder.build_conditional_branch(is_missing, missing_block, found_block)?;
+
+        self.builder.position_at_end(missing_block);
+        let key_text = self.value_to_string(key, key_type)?;
+        let panic_fn = self.runtime_manager.get_function("yuni_panic_missing_key")
+            .ok_or_else(|| YuniError::Codegen(CodegenError::Internal {
+                message: "Runtime function yuni_panic_missing_key not found".to_string(),
+            }))?;
+        self.builder.build_call(panic_fn, &[key_text.into()], "")?;
+        self.builder.build_unreachable()?;
+
+        self.builder.position_at_end(found_block);
+        let value_llvm_type = self.type_manager.ast_type_to_llvm(value_type)?;
+        Ok(self.builder.build_load(value_llvm_type, value_ptr, "map_value")?)
+    }
+
+    /// `&map[key]` の値へのポインタを取得（キーがなければnull）
+    pub(super) fn compile_hashmap_reference(&mut self, index: &IndexExpr, key_type: &Type) -> YuniResult<PointerValue<'ctx>> {
+        Ok(self.compile_hashmap_lookup(index, key_type)?.0)
+    }
+
+    /// `map[key] = value` をコンパイル（既にあるキーの値は上書き）
+    pub(crate) fn compile_hashmap_index_assignment(
+        &mut self,
+        index: &IndexExpr,
+        value: BasicValueEnum<'ctx>,
+        key_type: &Type,
+        value_type: &Type,
+    ) -> YuniResult<()> {
+        let map_ptr = self.compile_hashmap_object(&index.object)?;
+        let (key, key_llvm_type) = self.compile_hashmap_key(&index.index, key_type)?;
+        let value_llvm_type = self.type_manager.ast_type_to_llvm(value_type)?;
+        let value = self.coerce_to_type(value, value_llvm_type, index.span)?;
+        self.hashmap_insert(map_ptr, key, value, key_llvm_type, value_llvm_type)
+    }
+
+    /// `yuni_hashmap_get` でキーの値へのポインタ（キーがなければnull）とキーの値を取得する
+    fn compile_hashmap_lookup(
+        &mut self,
+        index: &IndexExpr,
+        key_type: &Type,
+    ) -> YuniResult<(PointerValue<'ctx>, BasicValueEnum<'ctx>)> {
+        let map_ptr = self.compile_hashmap_object(&index.object)?;
+        let (key, key_llvm_type) = self.compile_hashmap_key(&index.index, key_type)?;
+        let key_ptr = self.builder.build_alloca(key_llvm_type, "map_key")?;
+        self.builder.build_store(key_ptr, key)?;
+        let value_ptr = self
+            .call_runtime_function("yuni_hashmap_get", &[map_ptr.into(), key_ptr.into()])?
+            .into_pointer_value();
+        Ok((value_ptr, key))
+    }
+
+    /// インデックスアクセスの対象のHashMapをコンパイルする（参照は参照先を読み出す）
+    fn compile_hashmap_object(&mut self, object: &Expression) -> YuniResult<PointerValue<'ctx>> {
+        let mut map = self.compile_expression(object)?;
+        let mut ty = self.expression_type(object)?;
+        while let Type::Reference(inner, _) = ty {
+            let inner_llvm_type = self.type_manager.ast_type_to_llvm(&inner)?;
+            map = self.builder.build_load(inner_llvm_type, map.into_pointer_value(), "map_deref")?;
+            ty = *inner;
+        }
+        Ok(map.into_pointer_value())
+    }
+
+    /// キーの式をキーの型でコンパイルする
+    fn compile_hashmap_key(
+        &mut self,
+        key: &Expression,
+        key_type: &Type,
+    ) -> YuniResult<(BasicValueEnum<'ctx>, BasicTypeEnum<'ctx>)> {
+        let key_llvm_type = self.type_manager.ast_type_to_llvm(key_type)?;
+        let key_value = self.compile_expression_in_context(key, key_type)?;
+        let key_value = self.coerce_to_type(key_value, key_llvm_type, key.span())?;
+        Ok((key_value, key_llvm_type))
+    }
+}
@@ -0,0 +1,53 @@
+//! `in` 演算子のコード生成
+//!
+//! Vecは要素の型の比較関数（`sort` と同じ `yuni.cmp.i64` など）を `yuni_vec_contains` に渡し、
+//! HashMapは `yuni_hashmap_contains_key`、文字列は `yuni_string_contains` を呼び出します。
+//! オペランドは他の二項演算と同じく左辺から評価します。
+
+use crate::analyzer::builtin_methods::membership_needle_type;
+use crate::analyzer::display_type;
+use crate::ast::*;
+use crate::error::{CodegenError, YuniError, YuniResult};
+use inkwell::values::BasicValueEnum;
+
+use crate::codegen::code_generator::CodeGenerator;
+
+impl<'ctx> CodeGenerator<'ctx> {
+    /// `needle in container` をコンパイル
+    pub(super) fn compile_membership_expr(&mut self, binary: &BinaryExpr) -> YuniResult<BasicValueEnum<'ctx>> {
+        let container_type = self.expression_type(&binary.right)?;
+        let needle_type = membership_needle_type(&container_type)
+            .ok_or_else(|| YuniError::Codegen(CodegenError::InvalidType {
+                message: format!("Operator 'in' is not supported for {}", display_type(&container_type)),
+                span: binary.right.span(),
+            }))?;
+        let needle_llvm_type = self.type_manager.ast_type_to_llvm(&needle_type)?;
+        let needle = self.compile_expression_in_context(&binary.left, &needle_type)?;
+        let needle = self.coerce_to_type(needle, needle_llvm_type, binary.left.span())?;
+
+        // 参照経由の場合は参照先のコンテナを読み出す
+        let mut container = self.compile_expression(&binary.right)?;
+        let mut ty = container_type;
+        while let Type::Reference(inner, _) = ty {
+            let inner_llvm_type = self.type_manager.ast_type_to_llvm(&inner)?;
+            container = self.builder.build_load(inner_llvm_type, container.into_pointer_value(), "in_deref")?;
+            ty = *inner;
+        }
+
+        if matches!(ty, Type::String | Type::Str) {
+            return self.call_runtime_function("yuni_string_contains", &[container.into(), needle.into()]);
+        }
+        let needle_ptr = self.builder.build_alloca(needle_llvm_type, "in_needle")?;
+        self.builder.build_store(needle_ptr, needle)?;
+        match &ty {
+            Type::Generic(name, _) if name == "Vec" => {
+                let compare = self.comparator(&needle_type, None)?;
+                self.call_runtime_function(
+                    "yuni_vec_contains",
+                    &[container.into(), needle_ptr.into(), compare.as_global_value().as_pointer_value().into()],
+                )
+            }
+            _ => self.call_runtime_function("yuni_hashmap_contains_key", &[container.into(), needle_ptr.into()]),
+        }
+    }
+}
@@ -6,28 +6,55 @@ mod unary;
 mod call;
 mod struct_enum;
 mod array_tuple;
+mod assertion;
+mod checked_cast;
+mod display;
+mod null_check;
 mod control;
 mod type_inference;
 mod basic;
+mod builtin_method;
+mod vec_sort;
+mod membership;
+pub(super) mod map_index;
+pub(super) mod vec_index;
 
 use crate::ast::*;
 use crate::error::YuniResult;
 use inkwell::values::BasicValueEnum;
+use inkwell::AddressSpace;
 
 use super::code_generator::CodeGenerator;
 
 impl<'ctx> CodeGenerator<'ctx> {
-    /// 式をコンパイル（期待される型のコンテキストなし）
+    /// 式をコンパイル（期待される型はセマンティック解析の型テーブルから取得）
     pub fn compile_expression(&mut self, expr: &Expression) -> YuniResult<BasicValueEnum<'ctx>> {
-        self.compile_expression_with_type(expr, None)
+        // セマンティック解析で決定した型を期待される型として使用
+        let expected_type = self.type_table.resolved(expr).cloned();
+        self.compile_expression_with_type(expr, expected_type.as_ref())
+    }
+
+    /// 文脈の型（構造体のフィールドや引数の宣言の型）のある位置の式をコンパイル
+    ///
+    /// 型テーブルに記録のない式（単相化で生成された式など）では、文脈の型を期待される型として使用します。
+    pub fn compile_expression_in_context(&mut self, expr: &Expression, context_type: &Type) -> YuniResult<BasicValueEnum<'ctx>> {
+        if self.is_borrowed_string(expr, context_type) {
+            return self.compile_string_view(expr);
+        }
+        let expected_type = self.type_table.resolved(expr).cloned().unwrap_or_else(|| context_type.clone());
+        self.compile_expression_with_type(expr, Some(&expected_type))
     }
 
     /// 式をコンパイル（期待される型のコンテキスト付き）
     pub fn compile_expression_with_type(&mut self, expr: &Expression, expected_type: Option<&Type>) -> YuniResult<BasicValueEnum<'ctx>> {
+        if expected_type.is_some_and(|ty| self.is_borrowed_string(expr, ty)) {
+            return self.compile_string_view(expr);
+        }
         match expr {
             Expression::Integer(lit) => self.compile_integer_literal_with_type(lit, expected_type),
-            Expression::Float(lit) => self.compile_float_literal(lit),
-            Expression::String(lit) => self.compile_string_literal(lit),
+            Expression::Float(lit) => self.compile_float_literal_with_type(lit, expected_type),
+            Expression::String(lit) => self.compile_string_literal_with_type(lit, expected_type),
+            Expression::Bytes(lit) => self.compile_bytes_literal(lit),
             Expression::TemplateString(lit) => self.compile_template_string(lit),
             Expression::Boolean(lit) => self.compile_boolean_literal(lit),
             Expression::Identifier(id) => self.compile_identifier(id),
@@ -40,7 +67,10 @@ impl<'ctx> CodeGenerator<'ctx> {
             Expression::Field(field) => self.compile_field_expr(field),
             Expression::Reference(ref_expr) => self.compile_reference_expr(ref_expr),
             Expression::Dereference(deref) => self.compile_dereference_expr(deref),
-            Expression::StructLit(struct_lit) => self.compile_struct_literal(struct_lit),
+            Expression::StructLit(struct_lit) => {
+                let literal_type = self.type_table.resolved(expr).cloned();
+                self.compile_struct_literal(struct_lit, literal_type.as_ref().or(expected_type))
+            }
             Expression::EnumVariant(enum_var) => self.compile_enum_variant(enum_var),
             Expression::Array(array) => self.compile_array_expr(array),
             Expression::Tuple(tuple) => self.compile_tuple_expr(tuple),
@@ -53,4 +83,19 @@ impl<'ctx> CodeGenerator<'ctx> {
             Expression::MapLiteral(map) => self.compile_map_literal(map),
         }
     }
-}
\ No newline at end of file
+
+    /// `str` の位置に置いた文字列への参照（`&String`・`&str`）の式か
+    fn is_borrowed_string(&self, expr: &Expression, expected_type: &Type) -> bool {
+        *expected_type == Type::Str
+            && matches!(self.type_table.resolved(expr), Some(Type::Reference(inner, _)) if matches!(**inner, Type::String | Type::Str))
+    }
+
+    /// 文字列への参照から参照先の文字列データを指すポインタ（`str`）を読み出す
+    ///
+    /// 文字列データはコピーしません。
+    fn compile_string_view(&mut self, expr: &Expression) -> YuniResult<BasicValueEnum<'ctx>> {
+        let reference = self.compile_expression(expr)?.into_pointer_value();
+        let ptr_type = self.context.ptr_type(AddressSpace::default());
+        Ok(self.builder.build_load(ptr_type, reference, "str_view")?)
+    }
+}
@@ -0,0 +1,50 @@
+//! nullの検査のコード生成
+//!
+//! 参照はランタイム関数（`yuni_hashmap_get` など）が返すnullを包むことがあり、そのまま読み出すと
+//! セグメンテーション違反になります。参照先を読み出す前にnullを検査し、nullであれば
+//! 位置を示してパニックします（`dereferenced null at foo.yuni:12:5`）。
+//! `--no-null-checks` を指定すると検査を生成しません。
+//!
+//! OptionもSomeの値へのポインタ（Noneはnull）で表します。Optionのパターン（`Some(x)`）の
+//! コード生成はまだないため、Someの値を取り出す処理では `None` を示してこの検査を使います。
+
+use crate::ast::Span;
+use crate::error::{CodegenError, YuniError, YuniResult};
+use inkwell::values::PointerValue;
+
+use crate::codegen::code_generator::CodeGenerator;
+
+impl<'ctx> CodeGenerator<'ctx> {
+    /// ポインタがnullであればパニックする分岐を生成する
+    ///
+    /// `subject` はメッセージに示す値の名前（参照は `null`、Optionは `None`）です。
+    pub(crate) fn build_null_check(&mut self, ptr: PointerValue<'ctx>, subject: &str, span: Span) -> YuniResult<()> {
+        if !self.options.null_checks {
+            return Ok(());
+        }
+        let function = self.current_function
+            .ok_or_else(|| YuniError::Codegen(CodegenError::Internal {
+                message: "No current function".to_string(),
+            }))?;
+        let null_block = self.context.append_basic_block(function, "null.fail");
+        let ok_block = self.context.append_basic_block(function, "null.ok");
+        let is_null = self.builder.build_is_null(ptr, "is_null")?;
+        self.builder.build_conditional_branch(is_null, null_block, ok_block)?;
+
+        self.builder.position_at_end(null_block);
+        let message = match self.source_text.as_ref().and_then(|source| source.location(span)) {
+            Some(location) => format!("dereferenced {} at {}", subject, location),
+            None => format!("dereferenced {}", subject),
+        };
+        let message = self.builder.build_global_string_ptr(&message, &self.value_names.global("null.msg"))?;
+        let panic_fn = self.runtime_manager.get_function("yuni_panic")
+            .ok_or_else(|| YuniError::Codegen(CodegenError::Internal {
+                message: "Runtime function yuni_panic not found".to_string(),
+            }))?;
+        self.builder.build_call(panic_fn, &[message.as_pointer_value().into()], "")?;
+        self.builder.build_unreachable()?;
+
+        self.builder.position_at_end(ok_block);
+        Ok(())
+    }
+}
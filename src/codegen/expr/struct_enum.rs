@@ -2,10 +2,14 @@
 
 use crate::ast::*;
 use crate::error::{CodegenError, YuniError, YuniResult};
-use inkwell::values::BasicValueEnum;
+use inkwell::types::BasicTypeEnum;
+use inkwell::values::{BasicValueEnum, StructValue};
 use inkwell::AddressSpace;
+use std::rc::Rc;
 
 use crate::codegen::code_generator::CodeGenerator;
+use crate::codegen::layout::VariantRef;
+use super::map_index::hashmap_types;
 
 impl<'ctx> CodeGenerator<'ctx> {
     /* // 古い初期化式の実装（削除予定）
@@ -59,19 +63,24 @@ impl<'ctx> CodeGenerator<'ctx> {
                     let element_type = &type_args[0];
                     let element_llvm_type = self.type_manager.ast_type_to_llvm(element_type)?;
                     let element_size = self.get_size_of_type(element_llvm_type);
+                    let element_align = self.get_align_of_type(element_llvm_type);
                     
                     // yuni_vec_new を呼び出してVecを作成
                     let vec_new_fn = self.runtime_manager.get_or_declare_function(
                         "yuni_vec_new",
                         self.context.ptr_type(AddressSpace::default()).fn_type(&[
-                            self.context.i64_type().into(),
+                            self.context.i64_type().into(), // element_size
+                            self.context.i64_type().into(), // element_align
                         ], false),
                         &self.module,
                     )?;
                     
                     let vec_ptr = self.builder.build_call(
                         vec_new_fn,
-                        &[self.context.i64_type().const_int(element_size, false).into()],
+                        &[
+                            self.context.i64_type().const_int(element_size, false).into(),
+                            self.context.i64_type().const_int(element_align, false).into(),
+                        ],
                         "vec_new",
                     )?.try_as_basic_value().left().unwrap();
                     
@@ -130,27 +139,11 @@ impl<'ctx> CodeGenerator<'ctx> {
                     let value_type = &type_args[1];
                     let key_llvm_type = self.type_manager.ast_type_to_llvm(key_type)?;
                     let value_llvm_type = self.type_manager.ast_type_to_llvm(value_type)?;
-                    let key_size = self.get_size_of_type(key_llvm_type);
-                    let value_size = self.get_size_of_type(value_llvm_type);
-                    
-                    // yuni_hashmap_new を呼び出してHashMapを作成
-                    let hashmap_new_fn = self.runtime_manager.get_or_declare_function(
-                        "yuni_hashmap_new",
-                        self.context.ptr_type(AddressSpace::default()).fn_type(&[
-                            self.context.i64_type().into(), // key_size
-                            self.context.i64_type().into(), // value_size
-                        ], false),
-                        &self.module,
-                    )?;
                     
-                    let hashmap_ptr = self.builder.build_call(
-                        hashmap_new_fn,
-                        &[
-                            self.context.i64_type().const_int(key_size, false).into(),
-                            self.context.i64_type().const_int(value_size, false).into(),
-                        ],
-                        "hashmap_new",
-                    )?.try_as_basic_value().left().unwrap();
+                    // 文字列のキーは内容で比較するHashMapを作成
+                    let hashmap_ptr: BasicValueEnum<'ctx> = self
+                        .create_hashmap_new(key_llvm_type, value_llvm_type, matches!(key_type, Type::String | Type::Str))?
+                        .into();
                     
                     // 各要素をHashMapに追加
                     if !init_expr.elements.is_empty() {
@@ -311,49 +304,43 @@ impl<'ctx> CodeGenerator<'ctx> {
     */
 
     /// 構造体リテラルをコンパイル
-    pub fn compile_struct_literal(&mut self, struct_lit: &StructLiteral) -> YuniResult<BasicValueEnum<'ctx>> {
-        // 型名が指定されていない場合はエラー（現在は型推論未実装）
-        let struct_name = struct_lit.name.as_ref()
-            .ok_or_else(|| YuniError::Codegen(CodegenError::InvalidType {
-                message: "Type inference for anonymous struct literals not yet implemented".to_string(),
-                span: struct_lit.span,
-            }))?;
+    ///
+    /// 構造体名のないリテラルは、`literal_type`（解析で決まった構造体の型）の構造体として作成します。
+    pub fn compile_struct_literal(&mut self, struct_lit: &StructLiteral, literal_type: Option<&Type>) -> YuniResult<BasicValueEnum<'ctx>> {
+        let struct_name = match (&struct_lit.name, literal_type) {
+            (Some(name), _) | (None, Some(Type::UserDefined(name))) => name,
+            (None, _) => {
+                return Err(YuniError::Codegen(CodegenError::InvalidType {
+                    message: "Cannot determine the struct type of an anonymous struct literal".to_string(),
+                    span: struct_lit.span,
+                }));
+            }
+        };
+
+        if struct_name == "StringBuilder" {
+            return self.call_runtime_function("yuni_sb_new", &[]);
+        }
         
-        // 構造体型を取得
-        let struct_type = self.type_manager.get_struct(struct_name)
+        // 構造体のレイアウトを取得（フィールドの式のコンパイル中も参照するため共有する）
+        let layout = Rc::clone(self.struct_layouts.get(struct_name)
             .ok_or_else(|| YuniError::Codegen(CodegenError::Undefined {
                 name: struct_name.clone(),
                 span: struct_lit.span,
-            }))?;
-
-        // 構造体情報を取得してクローン（借用チェッカーエラーを回避）
-        let struct_info = self.struct_info.get(struct_name)
-            .ok_or_else(|| YuniError::Codegen(CodegenError::Internal {
-                message: format!("Struct info not found for {}", struct_name),
-            }))?
-            .clone();
+            }))?);
 
-        // 各フィールドの値をコンパイル
+        // 各フィールドの値を宣言順にコンパイル
         let mut field_values = vec![];
-        for (index, field_type) in struct_info.field_types.iter().enumerate() {
-            // フィールド名を取得
-            let field_name = struct_info.field_indices.iter()
-                .find(|(_, &idx)| idx == index as u32)
-                .map(|(name, _)| name.clone())
-                .ok_or_else(|| YuniError::Codegen(CodegenError::Internal {
-                    message: format!("Field name not found for index {}", index),
-                }))?;
-
+        for field in &layout.fields {
             // 初期化されたフィールドを探す
             let field_init = struct_lit.fields.iter()
-                .find(|f| f.name == field_name);
+                .find(|f| f.name == field.name);
 
             let value = if let Some(init) = field_init {
                 // フィールドが明示的に初期化されている場合
-                self.compile_expression(&init.value)?
+                self.compile_expression_in_context(&init.value, &field.ty)?
             } else {
                 // フィールドが初期化されていない場合はデフォルト値を使用
-                self.type_manager.create_default_value(field_type)?
+                self.type_manager.create_default_value(&field.ty)?
             };
 
             field_values.push(value);
@@ -361,7 +348,7 @@ impl<'ctx> CodeGenerator<'ctx> {
 
         // 構造体値を作成
         // 動的な値を含む構造体の場合は、build_insert_valueを使用して構築
-        let struct_val = struct_type.get_undef();
+        let struct_val = layout.llvm_type.get_undef();
         let mut result = struct_val;
         
         for (i, field_value) in field_values.iter().enumerate() {
@@ -460,79 +447,76 @@ impl<'ctx> CodeGenerator<'ctx> {
         }
         // 通常のEnumの場合
         else {
-            // バリアントのインデックスを取得
-            let key = (enum_var.enum_name.clone(), enum_var.variant.clone());
-            let variant_index = self.enum_variants.get(&key)
-                .ok_or_else(|| YuniError::Codegen(CodegenError::Undefined {
-                    name: format!("{}::{}", enum_var.enum_name, enum_var.variant),
-                    span: enum_var.span,
-                }))?;
+            // バリアントを解決（フィールドの式のコンパイル中も参照するためレイアウトを共有する）
+            let resolved = self.resolve_variant(&enum_var.enum_name, &enum_var.variant, enum_var.span)?;
+            let layout = &resolved.layout;
+            let variant = resolved.variant();
+            let tag = self.context.i32_type().const_int(variant.tag as u64, false);
             
+            // フィールドを持たないEnumはタグだけで表現される
+            let enum_type = match layout.llvm_type {
+                BasicTypeEnum::StructType(enum_type) => enum_type,
+                _ => return Ok(tag.into()),
+            };
+            
+            // フィールドの値を宣言順にコンパイル
+            let mut field_values = vec![];
             match &enum_var.fields {
-            crate::ast::EnumVariantFields::Unit => {
-                // i32の定数として返す
-                Ok(self.context.i32_type().const_int(*variant_index as u64, false).into())
-            }
-            crate::ast::EnumVariantFields::Tuple(fields) => {
-                // タプル形式のデータを持つバリアント
-                // 構造: { discriminant: i32, data: tuple }
-                let discriminant = self.context.i32_type().const_int(*variant_index as u64, false);
-                
-                // フィールドの値をコンパイル
-                let mut field_values = vec![];
-                for field in fields {
-                    field_values.push(self.compile_expression(field)?);
+                crate::ast::EnumVariantFields::Unit => {}
+                crate::ast::EnumVariantFields::Tuple(args) => {
+                    for arg in args {
+                        field_values.push(self.compile_expression(arg)?);
+                    }
                 }
-                
-                // データタプルを作成
-                let data_tuple = self.context.const_struct(&field_values, false);
-                
-                // Enum構造体を作成 { discriminant, data }
-                let enum_struct = self.context.struct_type(&[
-                    discriminant.get_type().into(),
-                    data_tuple.get_type().into(),
-                ], false);
-                
-                let mut enum_value = enum_struct.get_undef();
-                enum_value = self.builder.build_insert_value(enum_value, discriminant, 0, "enum_discriminant")?
-                    .into_struct_value();
-                enum_value = self.builder.build_insert_value(enum_value, data_tuple, 1, "enum_data")?
-                    .into_struct_value();
-                
-                Ok(enum_value.into())
-            }
-            crate::ast::EnumVariantFields::Struct(fields) => {
-                // 構造体形式のデータを持つバリアント
-                // 構造: { discriminant: i32, data: struct }
-                let discriminant = self.context.i32_type().const_int(*variant_index as u64, false);
-                
-                // フィールドの値をコンパイル
-                let mut field_values = vec![];
-                for init in fields {
-                    field_values.push(self.compile_expression(&init.value)?);
+                crate::ast::EnumVariantFields::Struct(inits) => {
+                    for field in &variant.fields {
+                        let value = match inits.iter().find(|init| init.name == field.name) {
+                            Some(init) => self.compile_expression(&init.value)?,
+                            None => self.type_manager.create_default_value(&field.ty)?,
+                        };
+                        field_values.push(value);
+                    }
                 }
-                
-                // データ構造体を作成
-                let data_struct = self.context.const_struct(&field_values, false);
-                
-                // Enum構造体を作成 { discriminant, data }
-                let enum_struct = self.context.struct_type(&[
-                    discriminant.get_type().into(),
-                    data_struct.get_type().into(),
-                ], false);
-                
-                let mut enum_value = enum_struct.get_undef();
-                enum_value = self.builder.build_insert_value(enum_value, discriminant, 0, "enum_discriminant")?
-                    .into_struct_value();
-                enum_value = self.builder.build_insert_value(enum_value, data_struct, 1, "enum_data")?
-                    .into_struct_value();
-                
-                Ok(enum_value.into())
             }
-        }
+            
+            // タグとフィールドをメモリ上で組み立てて、Enumの値として読み出す
+            let enum_ty = Type::UserDefined(enum_var.enum_name.clone());
+            let enum_ptr = self.create_entry_block_alloca("enum_value", &enum_ty)?;
+            let tag_ptr = self.builder.build_struct_gep(enum_type, enum_ptr, 0, "enum_tag_ptr")?;
+            self.builder.build_store(tag_ptr, tag)?;
+            
+            let payload_ptr = self.builder.build_struct_gep(enum_type, enum_ptr, 1, "enum_payload_ptr")?;
+            for (i, value) in field_values.into_iter().enumerate() {
+                let field_ptr = self.builder.build_struct_gep(variant.payload_type, payload_ptr, i as u32, &format!("field_{}_ptr", i))?;
+                self.builder.build_store(field_ptr, value)?;
+            }
+            
+            Ok(self.builder.build_load(enum_type, enum_ptr, "enum_value")?)
         }
     }
 
+    /// Enumの値から指定したバリアントのフィールドを読み出す
+    ///
+    /// 戻り値はバリアントのフィールドを宣言順に並べた構造体です。
+    /// タグが一致していることは呼び出し側で確認しておく必要があります。
+    pub fn load_variant_payload(
+        &mut self,
+        enum_value: StructValue<'ctx>,
+        enum_name: &str,
+        variant: &VariantRef<'ctx>,
+    ) -> YuniResult<StructValue<'ctx>> {
+        let payload_type = variant.variant().payload_type;
+        
+        // 領域を読み替えるため、値を一度メモリに置いてからGEPで辿る
+        let enum_ptr = self.create_entry_block_alloca("enum_tmp", &Type::UserDefined(enum_name.to_string()))?;
+        self.builder.build_store(enum_ptr, enum_value)?;
+        let payload_ptr = self.builder.build_struct_gep(enum_value.get_type(), enum_ptr, 1, "enum_payload_ptr")?;
+        let payload = self.builder.build_load(payload_type, payload_ptr, "enum_payload")?
+            .into_struct_value();
+        
+        Ok(payload)
+    }
+
     /// 参照式をコンパイル
     pub fn compile_reference_expr(&mut self, ref_expr: &ReferenceExpr) -> YuniResult<BasicValueEnum<'ctx>> {
         // 参照式は内部式のアドレスを返す
@@ -588,6 +572,9 @@ impl<'ctx> CodeGenerator<'ctx> {
         // LLVMの型に変換
         let llvm_type = self.type_manager.ast_type_to_llvm(&inner_type)?;
         
+        // ランタイム関数が返したnullを読み出さないよう検査
+        self.build_null_check(ptr, "null", deref.span)?;
+        
         // ポインタから値をロード
         let value = self.builder.build_load(
             llvm_type,
@@ -627,18 +614,8 @@ impl<'ctx> CodeGenerator<'ctx> {
             }
         };
         
-        // 構造体情報を取得
-        let struct_info = self.struct_info.get(&struct_name)
-            .ok_or_else(|| YuniError::Codegen(CodegenError::Internal {
-                message: format!("Struct info not found for {}", struct_name),
-            }))?;
-        
-        // フィールドのインデックスを取得
-        let field_index = struct_info.get_field_index(&field.field)
-            .ok_or_else(|| YuniError::Codegen(CodegenError::Undefined {
-                name: format!("{}.{}", struct_name, field.field),
-                span: field.span,
-            }))?;
+        // フィールドの位置を取得
+        let (struct_type, field_index, _) = self.struct_field_slot(&struct_name, &field.field, field.span)?;
         
         // 構造体へのポインタを取得
         let struct_ptr = match object_value {
@@ -659,12 +636,6 @@ impl<'ctx> CodeGenerator<'ctx> {
             }
         };
         
-        // 構造体型を取得
-        let struct_type = self.type_manager.get_struct(&struct_name)
-            .ok_or_else(|| YuniError::Codegen(CodegenError::Internal {
-                message: format!("Struct type not found for {}", struct_name),
-            }))?;
-        
         // フィールドへのポインタを計算（GEP）
         let field_ptr = unsafe {
             self.builder.build_gep(
@@ -683,6 +654,12 @@ impl<'ctx> CodeGenerator<'ctx> {
     
     /// インデックスへの参照を取得
     fn compile_index_reference(&mut self, index: &IndexExpr) -> YuniResult<BasicValueEnum<'ctx>> {
+        // HashMapの値への参照（キーがなければnull）
+        if let Some((key_type, _)) = hashmap_types(&self.expression_type(&index.object)?) {
+            let key_type = key_type.clone();
+            return Ok(self.compile_hashmap_reference(index, &key_type)?.into());
+        }
+        
         // オブジェクト（配列）の式をコンパイル
         let object_value = self.compile_expression(&index.object)?;
         
@@ -756,7 +733,7 @@ impl<'ctx> CodeGenerator<'ctx> {
                 
                 // 各要素を追加
                 for elem in &list.elements {
-                    let value = self.compile_expression(elem)?;
+                    let value = self.compile_expression_in_context(elem, element_type)?;
                     self.vec_push(vec_ptr, value, llvm_element_type)?;
                 }
                 
@@ -807,12 +784,12 @@ impl<'ctx> CodeGenerator<'ctx> {
                 let llvm_value_type = self.type_manager.ast_type_to_llvm(value_type)?;
                 
                 // HashMapの作成
-                let map_ptr = self.create_hashmap_new(llvm_key_type, llvm_value_type)?;
+                let map_ptr = self.create_hashmap_new(llvm_key_type, llvm_value_type, matches!(key_type, Type::String | Type::Str))?;
                 
                 // 各ペアを挿入
                 for (key_expr, value_expr) in &map.pairs {
-                    let key = self.compile_expression(key_expr)?;
-                    let value = self.compile_expression(value_expr)?;
+                    let key = self.compile_expression_in_context(key_expr, key_type)?;
+                    let value = self.compile_expression_in_context(value_expr, value_type)?;
                     self.hashmap_insert(map_ptr, key, value, llvm_key_type, llvm_value_type)?;
                 }
                 
@@ -835,11 +812,12 @@ impl<'ctx> CodeGenerator<'ctx> {
                 let (first_key, first_value) = &map.pairs[0];
                 let key = self.compile_expression(first_key)?;
                 let value = self.compile_expression(first_value)?;
+                let string_keys = matches!(self.expression_type(first_key)?, Type::String | Type::Str);
                 let key_type = key.get_type();
                 let value_type = value.get_type();
                 
                 // HashMapの作成
-                let map_ptr = self.create_hashmap_new(key_type, value_type)?;
+                let map_ptr = self.create_hashmap_new(key_type, value_type, string_keys)?;
                 
                 // 最初のペアを挿入
                 self.hashmap_insert(map_ptr, key, value, key_type, value_type)?;
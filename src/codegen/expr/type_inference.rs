@@ -1,83 +1,20 @@
 //! 型推論と型変換のロジック
 
+use crate::analyzer::builtin_methods::builtin_method;
+use crate::analyzer::numeric::{float_literal_type, integer_layout, integer_literal_type, is_untyped_integer_literal, promote_numeric};
+use crate::assertion::AssertKind;
 use crate::ast::*;
+use crate::console::OutputKind;
 use crate::error::{CodegenError, YuniError, YuniResult};
 use inkwell::values::BasicValueEnum;
 use inkwell::types::BasicTypeEnum;
 
 use crate::codegen::code_generator::CodeGenerator;
+use super::basic::associated_constant_type;
+use super::call::{clock_builtin, is_string_builder_type};
+use super::map_index::hashmap_types;
 
 impl<'ctx> CodeGenerator<'ctx> {
-    /// 値を文字列に変換
-    pub fn value_to_string(&mut self, value: BasicValueEnum<'ctx>) -> YuniResult<BasicValueEnum<'ctx>> {
-        match value {
-            BasicValueEnum::IntValue(int_val) => {
-                // 整数を文字列に変換
-                let to_string_fn = self.runtime_manager.get_function("yuni_int_to_string")
-                    .ok_or_else(|| YuniError::Codegen(CodegenError::Internal {
-                        message: "Runtime function yuni_int_to_string not found".to_string(),
-                    }))?;
-                
-                // yuni_int_to_string は i64 を期待するので、必要に応じて拡張
-                let i64_type = self.context.i64_type();
-                let int_val_as_i64 = match int_val.get_type().get_bit_width().cmp(&64) {
-                    std::cmp::Ordering::Less => {
-                        // 符号付き拡張（i8, i16, i32 -> i64）
-                        self.builder.build_int_s_extend(int_val, i64_type, "sext_to_i64")?
-                    }
-                    std::cmp::Ordering::Greater => {
-                        // 切り詰め（i128 -> i64）
-                        self.builder.build_int_truncate(int_val, i64_type, "trunc_to_i64")?
-                    }
-                    std::cmp::Ordering::Equal => {
-                        // 既に i64
-                        int_val
-                    }
-                };
-                
-                let result = self.builder.build_call(
-                    to_string_fn,
-                    &[int_val_as_i64.into()],
-                    "int_to_string_result",
-                )?.try_as_basic_value().basic()
-                    .ok_or_else(|| YuniError::Codegen(CodegenError::Internal {
-                        message: "yuni_int_to_string should return a value".to_string(),
-                    }))?;
-                
-                Ok(result)
-            }
-            BasicValueEnum::FloatValue(float_val) => {
-                // 浮動小数点を文字列に変換
-                let to_string_fn = self.runtime_manager.get_function("yuni_float_to_string")
-                    .ok_or_else(|| YuniError::Codegen(CodegenError::Internal {
-                        message: "Runtime function yuni_float_to_string not found".to_string(),
-                    }))?;
-                
-                let result = self.builder.build_call(
-                    to_string_fn,
-                    &[float_val.into()],
-                    "float_to_string_result",
-                )?.try_as_basic_value().basic()
-                    .ok_or_else(|| YuniError::Codegen(CodegenError::Internal {
-                        message: "yuni_float_to_string should return a value".to_string(),
-                    }))?;
-                
-                Ok(result)
-            }
-            BasicValueEnum::PointerValue(_) => {
-                // ポインタ値（文字列を含む）はそのまま返す
-                Ok(value)
-            }
-            _ => {
-                // その他の型は未実装
-                Err(YuniError::Codegen(CodegenError::Unimplemented {
-                    feature: format!("String conversion for type {:?} not implemented", value.get_type()),
-                    span: Span::dummy(),
-                }))
-            }
-        }
-    }
-
     /// 値を指定された型に変換
     pub fn coerce_to_type(
         &self, 
@@ -139,6 +76,27 @@ impl<'ctx> CodeGenerator<'ctx> {
         }
     }
 
+    /// 二項演算の左右のオペランドの型を取得
+    ///
+    /// 接尾辞のない整数リテラルは、もう一方のオペランドの整数型を採用します。
+    pub fn binary_operand_types(&mut self, binary: &BinaryExpr) -> YuniResult<(Type, Type)> {
+        self.operand_types(&binary.left, &binary.right)
+    }
+
+    /// 2つの式をオペランドとして使うときの型を取得（`assert_eq` の引数にも使用）
+    pub fn operand_types(&mut self, left: &Expression, right: &Expression) -> YuniResult<(Type, Type)> {
+        let left_type = self.expression_type(left)?;
+        let right_type = self.expression_type(right)?;
+
+        if is_untyped_integer_literal(left) && integer_layout(&right_type).is_some() {
+            Ok((right_type.clone(), right_type))
+        } else if is_untyped_integer_literal(right) && integer_layout(&left_type).is_some() {
+            Ok((left_type.clone(), left_type))
+        } else {
+            Ok((left_type, right_type))
+        }
+    }
+
     /// 整数型の強制変換を行う
     /// 異なるビット幅の整数型を同じ型に変換する
     pub fn coerce_int_types(
@@ -228,40 +186,20 @@ impl<'ctx> CodeGenerator<'ctx> {
         }
     }
 
-    /// 式の型を推論する
+    /// 式の型を取得する
+    ///
+    /// セマンティック解析の型テーブルに記録された型を使用し、記録のない式
+    /// （単相化で生成された関数の本体など）に限り型を推論します。
     pub fn expression_type(&mut self, expr: &Expression) -> YuniResult<Type> {
+        if let Some(ty) = self.type_table.resolved(expr) {
+            return Ok(ty.clone());
+        }
+
         match expr {
-            Expression::Integer(lit) => {
-                if let Some(suffix) = &lit.suffix {
-                    match suffix.as_str() {
-                        "i8" => Ok(Type::I8),
-                        "i16" => Ok(Type::I16),
-                        "i32" => Ok(Type::I32),
-                        "i64" => Ok(Type::I64),
-                        "i128" => Ok(Type::I128),
-                        "u8" => Ok(Type::U8),
-                        "u16" => Ok(Type::U16),
-                        "u32" => Ok(Type::U32),
-                        "u64" => Ok(Type::U64),
-                        "u128" => Ok(Type::U128),
-                        _ => Ok(Type::I32), // デフォルト
-                    }
-                } else {
-                    Ok(Type::I32) // デフォルトはi32（Rustと同じ）
-                }
-            }
-            Expression::Float(lit) => {
-                if let Some(suffix) = &lit.suffix {
-                    match suffix.as_str() {
-                        "f32" => Ok(Type::F32),
-                        "f64" => Ok(Type::F64),
-                        _ => Ok(Type::F64), // デフォルト
-                    }
-                } else {
-                    Ok(Type::F64) // デフォルト
-                }
-            }
-            Expression::String(_) => Ok(Type::String),
+            Expression::Integer(lit) => Ok(integer_literal_type(lit, None)),
+            Expression::Float(lit) => Ok(float_literal_type(lit, None)),
+            Expression::String(_) => Ok(Type::Str),
+            Expression::Bytes(_) => Ok(Type::Generic("Vec".to_string(), vec![Type::U8])),
             Expression::Boolean(_) => Ok(Type::Bool),
             Expression::Identifier(id) => {
                 if let Some(symbol) = self.scope_manager.lookup(&id.name) {
@@ -292,6 +230,8 @@ impl<'ctx> CodeGenerator<'ctx> {
                             span: path.span,
                         }))
                     }
+                } else if let Some(ty) = associated_constant_type(path) {
+                    Ok(ty)
                 } else {
                     Err(YuniError::Codegen(CodegenError::Unimplemented {
                         feature: "Multi-segment path type inference not implemented".to_string(),
@@ -300,29 +240,20 @@ impl<'ctx> CodeGenerator<'ctx> {
                 }
             }
             Expression::Binary(binary) => {
-                let left_type = self.expression_type(&binary.left)?;
-                let right_type = self.expression_type(&binary.right)?;
+                let (left_type, right_type) = self.binary_operand_types(binary)?;
                 
                 match &binary.op {
-                    BinaryOp::Add | BinaryOp::Subtract | BinaryOp::Multiply | BinaryOp::Divide | BinaryOp::Modulo => {
-                        if left_type == right_type {
-                            Ok(left_type)
-                        } else {
-                            // 型の自動昇格をサポート（簡易実装）
-                            Ok(left_type)
-                        }
-                    }
                     BinaryOp::Lt | BinaryOp::Gt | BinaryOp::Le | BinaryOp::Ge | BinaryOp::Eq | BinaryOp::Ne |
-                    BinaryOp::And | BinaryOp::Or => Ok(Type::Bool),
-                    _ => Ok(left_type),
+                    BinaryOp::And | BinaryOp::Or | BinaryOp::In => Ok(Type::Bool),
+                    // 算術演算とビット演算は昇格後の型になる
+                    _ => Ok(promote_numeric(&left_type, &right_type).unwrap_or(left_type)),
                 }
             }
             Expression::Unary(unary) => {
                 let operand_type = self.expression_type(&unary.expr)?;
                 match &unary.op {
                     UnaryOp::Not => Ok(Type::Bool),
-                    UnaryOp::Negate => Ok(operand_type),
-                    _ => Ok(operand_type),
+                    UnaryOp::Negate | UnaryOp::BitNot => Ok(operand_type),
                 }
             }
             Expression::Call(call) => {
@@ -335,20 +266,32 @@ impl<'ctx> CodeGenerator<'ctx> {
                     })),
                 };
                 
-                // println の特別な処理
-                if func_name == "println" {
-                    return Ok(Type::I32); // printlnは実際にはi32(0)を返すので
+                // 値を返さない呼び出しは解析で値として使えないため、Voidのまま扱う
+                if OutputKind::from_name(func_name).is_some() || AssertKind::from_name(func_name).is_some() {
+                    return Ok(Type::Void);
+                }
+                if func_name == "read_line" {
+                    return Ok(Type::String);
+                }
+                if func_name == "read_bytes" {
+                    return Ok(Type::Generic("Vec".to_string(), vec![Type::U8]));
+                }
+                if func_name == "write_bytes" {
+                    return Ok(Type::Bool);
+                }
+                if func_name == "parse_int" {
+                    return Ok(Type::Tuple(vec![Type::Bool, Type::I64]));
+                }
+                if func_name == "parse_float" {
+                    return Ok(Type::Tuple(vec![Type::Bool, Type::F64]));
+                }
+                if let Some((_, return_type)) = clock_builtin(func_name) {
+                    return Ok(return_type);
                 }
                 
                 // 関数の戻り値型を取得
                 if let Some(return_type) = self.function_types.get(func_name) {
-                    // Void型の関数は実際にはunit値（i32(0)）を返すため、
-                    // 型推論ではI32として扱う
-                    if matches!(return_type, Type::Void) {
-                        Ok(Type::I32)
-                    } else {
-                        Ok(return_type.clone())
-                    }
+                    Ok(return_type.clone())
                 } else {
                     // 関数が見つからない場合はエラー
                     Err(YuniError::Codegen(CodegenError::Undefined {
@@ -384,11 +327,12 @@ impl<'ctx> CodeGenerator<'ctx> {
                 }
             }
             Expression::StructLit(struct_lit) => {
-                // 構造体リテラルの型は構造体名から決まる
+                // 構造体リテラルの型は構造体名（構造体名がなければ解析で決まった型）から決まる
                 struct_lit.name.as_ref()
                     .map(|name| Type::UserDefined(name.clone()))
+                    .or_else(|| self.type_table.resolved(expr).cloned())
                     .ok_or_else(|| YuniError::Codegen(CodegenError::Internal {
-                        message: "Anonymous struct literal type inference not implemented".to_string(),
+                        message: "Cannot determine the struct type of an anonymous struct literal".to_string(),
                     }))
             }
             Expression::Field(field_expr) => {
@@ -415,23 +359,17 @@ impl<'ctx> CodeGenerator<'ctx> {
                     }
                 };
                 
-                let struct_info = self.struct_info.get(&struct_name)
+                let (_, field) = self.struct_layouts.get(&struct_name)
                     .ok_or_else(|| YuniError::Codegen(CodegenError::Internal {
                         message: format!("Struct info not found for {}", struct_name),
-                    }))?;
-                
-                let field_index = struct_info.get_field_index(&field_expr.field)
+                    }))?
+                    .field(&field_expr.field)
                     .ok_or_else(|| YuniError::Codegen(CodegenError::Undefined {
                         name: format!("{}.{}", struct_name, field_expr.field),
                         span: field_expr.span,
                     }))?;
                 
-                let field_type = struct_info.get_field_type(field_index as usize)
-                    .ok_or_else(|| YuniError::Codegen(CodegenError::Internal {
-                        message: format!("Field type not found for index {}", field_index),
-                    }))?;
-                
-                Ok(field_type.clone())
+                Ok(field.ty.clone())
             }
             Expression::EnumVariant(enum_variant) => {
                 // Enumバリアントの型はEnum自体の型
@@ -474,8 +412,11 @@ impl<'ctx> CodeGenerator<'ctx> {
                 }
             }
             Expression::Index(index_expr) => {
-                // インデックスアクセスの型は配列の要素型
+                // インデックスアクセスの型は配列の要素型（HashMapでは値の型）
                 let object_type = self.expression_type(&index_expr.object)?;
+                if let Some((_, value_type)) = hashmap_types(&object_type) {
+                    return Ok(value_type.clone());
+                }
                 match object_type {
                     Type::Array(element_type) => Ok(*element_type),
                     Type::String => Ok(Type::U8), // 文字列の要素はu8（バイト）
@@ -492,6 +433,18 @@ impl<'ctx> CodeGenerator<'ctx> {
                 // メソッド呼び出しの型は、メソッドの戻り値型
                 let object_type = self.expression_type(&method_call.object)?;
                 
+                if is_string_builder_type(&object_type) {
+                    return Ok(match method_call.method.as_str() {
+                        "len" => Type::I64,
+                        "to_string" => Type::String,
+                        _ => Type::Void,
+                    });
+                }
+                
+                if let Some(method) = builtin_method(&object_type, &method_call.method) {
+                    return Ok(method.return_type(&object_type));
+                }
+                
                 // 構造体名を取得
                 let struct_name = match &object_type {
                     Type::UserDefined(name) => name.clone(),
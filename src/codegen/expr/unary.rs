@@ -12,16 +12,22 @@ impl<'ctx> CodeGenerator<'ctx> {
         let operand = self.compile_expression(&unary.expr)?;
 
         match (&unary.op, operand) {
-            (UnaryOp::Not, BasicValueEnum::IntValue(int_val)) => {
-                Ok(self.builder.build_not(int_val, "not")?.into())
+            // 論理否定はboolの値（i1）をtrueとのxorで反転する
+            (UnaryOp::Not, BasicValueEnum::IntValue(int_val)) if int_val.get_type().get_bit_width() == 1 => {
+                let true_val = self.context.bool_type().const_int(1, false);
+                Ok(self.builder.build_xor(int_val, true_val, "not")?.into())
             }
+            // ビット反転は整数のすべてのビットを反転する
+            (UnaryOp::BitNot, BasicValueEnum::IntValue(int_val)) => {
+                Ok(self.builder.build_not(int_val, "bitnot")?.into())
+            }
+            // 符号反転は型の幅でラップアラウンドする（nswを付けないため符号なし整数でも定義される）
             (UnaryOp::Negate, BasicValueEnum::IntValue(int_val)) => {
                 Ok(self.builder.build_int_neg(int_val, "neg")?.into())
             }
             (UnaryOp::Negate, BasicValueEnum::FloatValue(float_val)) => {
                 Ok(self.builder.build_float_neg(float_val, "fneg")?.into())
             }
-            // ビット反転演算子は現在定義されていない
             _ => Err(YuniError::Codegen(CodegenError::InvalidType {
                 message: format!("Invalid unary operation {:?}", unary.op),
                 span: unary.span,
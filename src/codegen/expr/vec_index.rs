@@ -0,0 +1,121 @@
+//! Vecのインデックスアクセスのコード生成
+//!
+//! `v[i]` の読み出しと `v[i] = x` の代入は、どちらもインデックスが `0 <= i < len` であることを
+//! 検査してから `yuni_vec_get` で要素へのポインタを取得します。範囲外であれば長さとインデックスと
+//! ソースコードの位置を示してパニックします。長さと同じインデックスへの代入も範囲外で、
+//! 要素を増やすには `push` か `resize` を使います。
+
+use crate::ast::*;
+use crate::error::{CodegenError, YuniError, YuniResult};
+use inkwell::values::{BasicValueEnum, IntValue, PointerValue};
+use inkwell::IntPredicate;
+
+use crate::codegen::code_generator::CodeGenerator;
+
+/// Vecの型（参照を含む）であれば、要素の型を返す
+pub(crate) fn vec_element_type(ty: &Type) -> Option<&Type> {
+    match ty {
+        Type::Reference(inner, _) => vec_element_type(inner),
+        Type::Generic(name, args) if name == "Vec" && args.len() == 1 => Some(&args[0]),
+        _ => None,
+    }
+}
+
+impl<'ctx> CodeGenerator<'ctx> {
+    /// `v[i]` の要素を読み出す（範囲外であればパニック）
+    pub(super) fn compile_vec_index(&mut self, index: &IndexExpr, element_type: &Type) -> YuniResult<BasicValueEnum<'ctx>> {
+        let element_ptr = self.compile_vec_element_ptr(index, false)?;
+        let element_llvm_type = self.type_manager.ast_type_to_llvm(element_type)?;
+        Ok(self.builder.build_load(element_llvm_type, element_ptr, "element_value")?)
+    }
+
+    /// `v[i] = value` をコンパイル（範囲外であればパニック）
+    pub(crate) fn compile_vec_index_assignment(
+        &mut self,
+        index: &IndexExpr,
+        value: BasicValueEnum<'ctx>,
+        element_type: &Type,
+    ) -> YuniResult<()> {
+        let element_ptr = self.compile_vec_element_ptr(index, true)?;
+        let element_llvm_type = self.type_manager.ast_type_to_llvm(element_type)?;
+        let value = self.coerce_to_type(value, element_llvm_type, index.span)?;
+        self.builder.build_store(element_ptr, value)?;
+        Ok(())
+    }
+
+    /// インデックスの範囲を検査し、要素へのポインタを取得する
+    fn compile_vec_element_ptr(&mut self, index: &IndexExpr, is_write: bool) -> YuniResult<PointerValue<'ctx>> {
+        let vec_ptr = self.compile_vec_object(&index.object)?;
+        let index_value = self.compile_expression(&index.index)?.into_int_value();
+        let index_type = self.expression_type(&index.index)?;
+        let index_i64 = self.index_to_i64(index_value, &index_type)?;
+
+        let len = self.vec_len(vec_ptr)?;
+        self.build_index_check(index_i64, len, index.span, is_write)?;
+        Ok(self
+            .call_runtime_function("yuni_vec_get", &[vec_ptr.into(), index_i64.into()])?
+            .into_pointer_value())
+    }
+
+    /// インデックスアクセスの対象のVecをコンパイルする（参照は参照先を読み出す）
+    fn compile_vec_object(&mut self, object: &Expression) -> YuniResult<PointerValue<'ctx>> {
+        let mut vec = self.compile_expression(object)?;
+        let mut ty = self.expression_type(object)?;
+        while let Type::Reference(inner, _) = ty {
+            let inner_llvm_type = self.type_manager.ast_type_to_llvm(&inner)?;
+            vec = self.builder.build_load(inner_llvm_type, vec.into_pointer_value(), "vec_deref")?;
+            ty = *inner;
+        }
+        Ok(vec.into_pointer_value())
+    }
+
+    /// インデックスをi64にする（符号付きの型は符号拡張するため、負のインデックスは範囲外になる）
+    fn index_to_i64(&mut self, index: IntValue<'ctx>, index_type: &Type) -> YuniResult<IntValue<'ctx>> {
+        let i64_type = self.context.i64_type();
+        let width = index.get_type().get_bit_width();
+        Ok(if width > 64 {
+            self.builder.build_int_truncate(index, i64_type, "index_i64")?
+        } else if width == 64 {
+            index
+        } else if self.is_unsigned_integer_type(index_type) {
+            self.builder.build_int_z_extend(index, i64_type, "index_i64")?
+        } else {
+            self.builder.build_int_s_extend(index, i64_type, "index_i64")?
+        })
+    }
+
+    /// インデックスが `0 <= index < len` でなければパニックする分岐を生成する
+    ///
+    /// 符号なしで比較するため、負のインデックスも1回の比較で範囲外になります。
+    fn build_index_check(&mut self, index: IntValue<'ctx>, len: IntValue<'ctx>, span: Span, is_write: bool) -> YuniResult<()> {
+        let function = self.current_function
+            .ok_or_else(|| YuniError::Codegen(CodegenError::Internal {
+                message: "No current function".to_string(),
+            }))?;
+        let fail_block = self.context.append_basic_block(function, "index.fail");
+        let ok_block = self.context.append_basic_block(function, "index.ok");
+        let in_bounds = self.builder.build_int_compare(IntPredicate::ULT, index, len, "index.in_bounds")?;
+        self.builder.build_conditional_branch(in_bounds, ok_block, fail_block)?;
+
+        self.builder.position_at_end(fail_block);
+        let location = self.source_text
+            .as_ref()
+            .and_then(|source| source.location(span))
+            .unwrap_or_default();
+        let location = self.builder.build_global_string_ptr(&location, &self.value_names.global("index.loc"))?;
+        let panic_fn = self.runtime_manager.get_function("yuni_panic_index")
+            .ok_or_else(|| YuniError::Codegen(CodegenError::Internal {
+                message: "Runtime function yuni_panic_index not found".to_string(),
+            }))?;
+        let is_write = self.context.bool_type().const_int(is_write as u64, false);
+        self.builder.build_call(
+            panic_fn,
+            &[index.into(), len.into(), is_write.into(), location.as_pointer_value().into()],
+            "",
+        )?;
+        self.builder.build_unreachable()?;
+
+        self.builder.position_at_end(ok_block);
+        Ok(())
+    }
+}
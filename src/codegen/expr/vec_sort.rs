@@ -0,0 +1,186 @@
+//! Vecの並べ替えと二分探索のコード生成
+//!
+//! `v.sort()` と `v.binary_search(key)` は要素の型ごとに比較関数（`yuni.cmp.i64` など）を、
+//! `v.sort_by(f)` は要素を読み出して `f` を呼び出す比較関数（`yuni.cmp_by.f`）を合成し、
+//! ランタイム関数 `yuni_vec_sort`・`yuni_vec_binary_search` に渡します。比較関数は要素への
+//! ポインタを2つ受け取り、負・0・正で大小を返します。
+//!
+//! 整数は符号の有無に従って比較し、浮動小数点数はNaNを最大とする全順序で、
+//! 文字列は `strcmp`（バイト列の順序）で比較します。
+
+use crate::analyzer::display_type;
+use crate::ast::*;
+use crate::error::{CodegenError, YuniError, YuniResult};
+use inkwell::module::Linkage;
+use inkwell::values::{BasicValueEnum, FunctionValue, IntValue, PointerValue};
+use inkwell::{AddressSpace, FloatPredicate, IntPredicate};
+
+use crate::codegen::code_generator::CodeGenerator;
+
+impl<'ctx> CodeGenerator<'ctx> {
+    /// `sort`・`sort_by`・`binary_search` の呼び出しをコンパイル
+    pub(super) fn compile_vec_sort_method(
+        &mut self,
+        method_call: &MethodCallExpr,
+        vec: PointerValue<'ctx>,
+        vec_type: &Type,
+    ) -> YuniResult<BasicValueEnum<'ctx>> {
+        let element_type = match vec_type {
+            Type::Generic(name, args) if name == "Vec" && args.len() == 1 => args[0].clone(),
+            _ => {
+                return Err(YuniError::Codegen(CodegenError::InvalidType {
+                    message: format!("Method '{}' requires a Vec receiver", method_call.method),
+                    span: method_call.span,
+                }))
+            }
+        };
+
+        match (method_call.method.as_str(), method_call.args.as_slice()) {
+            ("sort", []) => {
+                let compare = self.comparator(&element_type, None)?;
+                self.call_vec_sort(vec, compare)
+            }
+            ("sort_by", [Expression::Identifier(function)]) => {
+                let user_function = *self.functions.get(&function.name)
+                    .ok_or_else(|| YuniError::Codegen(CodegenError::Undefined {
+                        name: function.name.clone(),
+                        span: function.span,
+                    }))?;
+                let compare = self.comparator(&element_type, Some((&function.name, user_function)))?;
+                self.call_vec_sort(vec, compare)
+            }
+            ("binary_search", [key]) => {
+                let llvm_element_type = self.type_manager.ast_type_to_llvm(&element_type)?;
+                let key_value = self.compile_expression_in_context(key, &element_type)?;
+                let key_value = self.coerce_to_type(key_value, llvm_element_type, key.span())?;
+                let key_ptr = self.builder.build_alloca(llvm_element_type, "search_key")?;
+                self.builder.build_store(key_ptr, key_value)?;
+                let compare = self.comparator(&element_type, None)?;
+                self.call_runtime_function(
+                    "yuni_vec_binary_search",
+                    &[vec.into(), key_ptr.into(), compare.as_global_value().as_pointer_value().into()],
+                )
+            }
+            _ => Err(YuniError::Codegen(CodegenError::InvalidType {
+                message: format!("Invalid arguments for builtin method '{}'", method_call.method),
+                span: method_call.span,
+            })),
+        }
+    }
+
+    /// `yuni_vec_sort` を呼び出す
+    fn call_vec_sort(&mut self, vec: PointerValue<'ctx>, compare: FunctionValue<'ctx>) -> YuniResult<BasicValueEnum<'ctx>> {
+        let sort_fn = self.runtime_manager.get_function("yuni_vec_sort")
+            .ok_or_else(|| YuniError::Codegen(CodegenError::Internal {
+                message: "Runtime function yuni_vec_sort not found".to_string(),
+            }))?;
+        self.builder.build_call(sort_fn, &[vec.into(), compare.as_global_value().as_pointer_value().into()], "")?;
+        // 値を返さない組み込み関数と同じくi32の0を式の値とする
+        Ok(self.context.i32_type().const_zero().into())
+    }
+
+    /// 要素の比較関数を取得（まだなければ合成する）
+    ///
+    /// `user_function` を指定した場合はその関数で、指定しない場合は要素の型の組み込みの順序で比較します。
+    pub(super) fn comparator(
+        &mut self,
+        element_type: &Type,
+        user_function: Option<(&str, FunctionValue<'ctx>)>,
+    ) -> YuniResult<FunctionValue<'ctx>> {
+        let name = match user_function {
+            Some((function_name, _)) => format!("yuni.cmp_by.{}", function_name),
+            None => format!("yuni.cmp.{}", display_type(element_type)),
+        };
+        if let Some(function) = self.module.get_function(&name) {
+            return Ok(function);
+        }
+
+        let ptr_type = self.context.ptr_type(AddressSpace::default());
+        let fn_type = self.context.i32_type().fn_type(&[ptr_type.into(), ptr_type.into()], false);
+        let function = self.module.add_function(&name, fn_type, Some(Linkage::Internal));
+
+        // 呼び出し元のブロックの末尾に戻れるよう、比較関数の本体を作る前の位置を覚えておく
+        let caller_block = self.builder.get_insert_block();
+        self.builder.position_at_end(self.context.append_basic_block(function, "entry"));
+        let llvm_element_type = self.type_manager.ast_type_to_llvm(element_type)?;
+        let mut elements = Vec::with_capacity(2);
+        for (param, name) in function.get_param_iter().zip(["a", "b"]) {
+            elements.push(self.builder.build_load(llvm_element_type, param.into_pointer_value(), name)?);
+        }
+        let order = match user_function {
+            Some((function_name, user_function)) => self.builder
+                .build_call(user_function, &[elements[0].into(), elements[1].into()], "order")?
+                .try_as_basic_value()
+                .basic()
+                .ok_or_else(|| YuniError::Codegen(CodegenError::Internal {
+                    message: format!("Comparator {} should return a value", function_name),
+                }))?
+                .into_int_value(),
+            None => self.build_builtin_order(element_type, elements[0], elements[1])?,
+        };
+        self.builder.build_return(Some(&order))?;
+
+        if let Some(block) = caller_block {
+            self.builder.position_at_end(block);
+        }
+        Ok(function)
+    }
+
+    /// 組み込みの順序で2つの要素を比較し、-1・0・1（文字列は `strcmp` の結果）を返す
+    ///
+    /// boolは `in` で要素を探すときに使い、falseをtrueより小さいとします。
+    fn build_builtin_order(
+        &mut self,
+        element_type: &Type,
+        a: BasicValueEnum<'ctx>,
+        b: BasicValueEnum<'ctx>,
+    ) -> YuniResult<IntValue<'ctx>> {
+        match (a, b) {
+            (BasicValueEnum::IntValue(a), BasicValueEnum::IntValue(b)) => {
+                let (less, greater) = if self.is_unsigned_integer_type(element_type) || *element_type == Type::Bool {
+                    (IntPredicate::ULT, IntPredicate::UGT)
+                } else {
+                    (IntPredicate::SLT, IntPredicate::SGT)
+                };
+                let less = self.builder.build_int_compare(less, a, b, "lt")?;
+                let greater = self.builder.build_int_compare(greater, a, b, "gt")?;
+                self.build_order_difference(greater, less)
+            }
+            (BasicValueEnum::FloatValue(a), BasicValueEnum::FloatValue(b)) => {
+                // 順序のある比較の結果に、NaNを最大とする補正（NaN同士は等しい）を加える
+                let less = self.builder.build_float_compare(FloatPredicate::OLT, a, b, "lt")?;
+                let greater = self.builder.build_float_compare(FloatPredicate::OGT, a, b, "gt")?;
+                let a_nan = self.builder.build_float_compare(FloatPredicate::UNO, a, a, "a_nan")?;
+                let b_nan = self.builder.build_float_compare(FloatPredicate::UNO, b, b, "b_nan")?;
+                let order = self.build_order_difference(greater, less)?;
+                let nan_order = self.build_order_difference(a_nan, b_nan)?;
+                Ok(self.builder.build_int_add(order, nan_order, "order")?)
+            }
+            (BasicValueEnum::PointerValue(a), BasicValueEnum::PointerValue(b)) if matches!(element_type, Type::String | Type::Str) => {
+                let strcmp = self.runtime_manager.get_function("strcmp")
+                    .ok_or_else(|| YuniError::Codegen(CodegenError::Internal {
+                        message: "Runtime function strcmp not found".to_string(),
+                    }))?;
+                Ok(self.builder
+                    .build_call(strcmp, &[a.into(), b.into()], "order")?
+                    .try_as_basic_value()
+                    .basic()
+                    .ok_or_else(|| YuniError::Codegen(CodegenError::Internal {
+                        message: "strcmp should return a value".to_string(),
+                    }))?
+                    .into_int_value())
+            }
+            _ => Err(YuniError::Codegen(CodegenError::Internal {
+                message: format!("No builtin ordering for {}", display_type(element_type)),
+            })),
+        }
+    }
+
+    /// 2つの比較結果（i1）の差をi32で返す
+    fn build_order_difference(&mut self, greater: IntValue<'ctx>, less: IntValue<'ctx>) -> YuniResult<IntValue<'ctx>> {
+        let i32_type = self.context.i32_type();
+        let greater = self.builder.build_int_z_extend(greater, i32_type, "gt_i32")?;
+        let less = self.builder.build_int_z_extend(less, i32_type, "lt_i32")?;
+        Ok(self.builder.build_int_sub(greater, less, "order")?)
+    }
+}
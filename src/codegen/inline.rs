@@ -0,0 +1,94 @@
+//! インライン展開
+//!
+//! `inline` / `inline(always)` の指定をLLVMの関数属性 `inlinehint` / `alwaysinline` にします。
+//! 単相化で生成した関数は型ごとに複製されて小さいものが多いため、指定がなくても
+//! 本体の文が `SMALL_INSTANCE_STATEMENTS` 個以下であれば `inlinehint` を付けます。
+//!
+//! 生成したIRはllcに渡すだけでは関数をまたいだ最適化が行われないため、
+//! モジュール全体にインライン展開のパスを実行してから出力します。
+//! `-O1` 以上では、その前にパラメータとローカル変数のスタック領域をレジスタに戻します（mem2reg）。
+
+use crate::ast::*;
+use crate::error::{CodegenError, YuniError, YuniResult};
+use inkwell::attributes::{Attribute, AttributeLoc};
+use inkwell::passes::PassBuilderOptions;
+use inkwell::values::FunctionValue;
+use inkwell::OptimizationLevel;
+
+use super::code_generator::CodeGenerator;
+
+/// 指定がなくても `inlinehint` を付ける単相化した関数の本体の文の数の上限
+const SMALL_INSTANCE_STATEMENTS: usize = 8;
+
+impl<'ctx> CodeGenerator<'ctx> {
+    /// 関数の宣言にインライン展開の属性を付ける
+    pub(crate) fn add_inline_attribute(&self, function: FunctionValue<'ctx>, hint: InlineHint) {
+        let name = match hint {
+            InlineHint::None => return,
+            InlineHint::Hint => "inlinehint",
+            InlineHint::Always => "alwaysinline",
+        };
+        log::debug!(target: "yuni::codegen", "marking {} as {}", function.get_name().to_string_lossy(), name);
+        let kind = Attribute::get_named_enum_kind_id(name);
+        function.add_attribute(AttributeLoc::Function, self.context.create_enum_attribute(kind, 0));
+    }
+
+    /// 関数に付けるインライン展開の指定（単相化した小さい関数は指定がなくても展開を優先する）
+    pub(crate) fn function_inline_hint(&self, func: &FunctionDecl) -> InlineHint {
+        let is_instance = self.source_map.origin(&func.name).is_some();
+        if func.inline == InlineHint::None && is_instance && statement_count(&func.body) <= SMALL_INSTANCE_STATEMENTS {
+            InlineHint::Hint
+        } else {
+            func.inline
+        }
+    }
+
+    /// モジュール全体にインライン展開のパスを実行
+    ///
+    /// `-O0` では `inline(always)` の関数だけを展開し、`-O1` 以上ではLLVMのインライナーが
+    /// 属性と関数の大きさから展開する呼び出しを選びます。`-O1` 以上では展開の前にmem2regで
+    /// アドレスを取らないスタック領域をレジスタに戻すため、出力するIRにもパラメータのallocaは残りません。
+    /// それ以外の関数本体の最適化はllcに任せます。
+    pub fn run_inline_passes(&self) -> YuniResult<()> {
+        let passes = match self.options.opt_level {
+            OptimizationLevel::None => "always-inline",
+            _ => "function(mem2reg),cgscc(inline)",
+        };
+        log::debug!(target: "yuni::codegen", "running inline passes: {}", passes);
+        let machine = self.options.target_machine()?;
+        self.module
+            .run_passes(passes, &machine, PassBuilderOptions::create())
+            .map_err(|e| YuniError::Codegen(CodegenError::Internal {
+                message: format!("Failed to run inlining passes: {}", e),
+            }))
+    }
+}
+
+/// ブロックの文の数（入れ子のブロックの文も数える）
+fn statement_count(block: &Block) -> usize {
+    block.statements.iter().map(|stmt| 1 + nested_statement_count(stmt)).sum()
+}
+
+fn nested_statement_count(stmt: &Statement) -> usize {
+    match stmt {
+        Statement::If(if_stmt) => if_statement_count(if_stmt),
+        Statement::While(while_stmt) => statement_count(&while_stmt.body),
+        Statement::For(for_stmt) => statement_count(&for_stmt.body),
+        Statement::Block(block) => statement_count(block),
+        Statement::Defer(defer_stmt) => statement_count(&defer_stmt.body),
+        Statement::Let(_)
+        | Statement::Assignment(_)
+        | Statement::Return(_)
+        | Statement::Expression(_)
+        | Statement::Function(_) => 0,
+    }
+}
+
+fn if_statement_count(if_stmt: &IfStatement) -> usize {
+    statement_count(&if_stmt.then_branch)
+        + match &if_stmt.else_branch {
+            Some(ElseBranch::Block(block)) => statement_count(block),
+            Some(ElseBranch::If(else_if)) => 1 + if_statement_count(else_if),
+            None => 0,
+        }
+}
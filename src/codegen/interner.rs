@@ -0,0 +1,44 @@
+//! 名前の表
+//!
+//! Enum名・バリアント名を型の宣言時に数値のIDへ変換しておき、
+//! 式やパターンのコード生成では名前を一度だけIDに解決して、以降はIDで表を引きます。
+
+use std::collections::HashMap;
+
+/// 名前表に登録した名前のID
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Symbol(u32);
+
+/// 名前とIDの対応表（IDは登録順に0から）
+#[derive(Debug, Default)]
+pub struct Interner {
+    names: Vec<String>,
+    symbols: HashMap<String, Symbol>,
+}
+
+impl Interner {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 名前を登録してIDを返す（登録済みの名前は同じIDを返す）
+    pub fn intern(&mut self, name: &str) -> Symbol {
+        if let Some(&symbol) = self.symbols.get(name) {
+            return symbol;
+        }
+        let symbol = Symbol(self.names.len() as u32);
+        self.names.push(name.to_string());
+        self.symbols.insert(name.to_string(), symbol);
+        symbol
+    }
+
+    /// 登録済みの名前のIDを取得
+    pub fn get(&self, name: &str) -> Option<Symbol> {
+        self.symbols.get(name).copied()
+    }
+
+    /// IDから名前を取得
+    pub fn resolve(&self, symbol: Symbol) -> &str {
+        &self.names[symbol.0 as usize]
+    }
+}
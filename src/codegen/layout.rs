@@ -0,0 +1,136 @@
+//! 構造体とEnumのレイアウト
+//!
+//! 型の宣言時に一度だけ計算し、式のコード生成では読み取るだけにします。
+//! フィールド名・バリアント名からインデックスへの対応と、各フィールドのAST上の型・LLVMの型、
+//! 全体のサイズとアライメントをまとめて保持します。
+//! Enumのバリアントは名前表のIDで引き、宣言順のインデックス（タグと同じ値）で保持します。
+
+use super::interner::{Interner, Symbol};
+use crate::ast::{Field, Type};
+use inkwell::types::{BasicTypeEnum, StructType};
+use std::collections::HashMap;
+use std::rc::Rc;
+
+/// 構造体のフィールド
+#[derive(Debug)]
+pub struct FieldLayout<'ctx> {
+    pub name: String,
+    /// AST上の型
+    pub ty: Type,
+    pub llvm_type: BasicTypeEnum<'ctx>,
+}
+
+/// 構造体のレイアウト
+#[derive(Debug)]
+pub struct StructLayout<'ctx> {
+    pub llvm_type: StructType<'ctx>,
+    /// 宣言順のフィールド（インデックスはLLVMの構造体型のフィールドの位置と一致する）
+    pub fields: Vec<FieldLayout<'ctx>>,
+    /// フィールド名からインデックスへの対応
+    field_indices: HashMap<String, u32>,
+    /// 末尾のパディングを含むサイズ（バイト単位）
+    pub size: u64,
+    /// アライメント（バイト単位）
+    pub align: u64,
+}
+
+impl<'ctx> StructLayout<'ctx> {
+    pub fn new(llvm_type: StructType<'ctx>, fields: Vec<FieldLayout<'ctx>>, size: u64, align: u64) -> Self {
+        let field_indices = fields
+            .iter()
+            .enumerate()
+            .map(|(index, field)| (field.name.clone(), index as u32))
+            .collect();
+        Self { llvm_type, fields, field_indices, size, align }
+    }
+
+    /// フィールドのインデックスを取得
+    pub fn field_index(&self, name: &str) -> Option<u32> {
+        self.field_indices.get(name).copied()
+    }
+
+    /// 名前でフィールドを取得
+    pub fn field(&self, name: &str) -> Option<(u32, &FieldLayout<'ctx>)> {
+        let index = self.field_index(name)?;
+        Some((index, &self.fields[index as usize]))
+    }
+
+    /// インデックスでフィールドの型を取得
+    pub fn field_type(&self, index: u32) -> Option<&Type> {
+        self.fields.get(index as usize).map(|field| &field.ty)
+    }
+}
+
+/// Enumのバリアント
+#[derive(Debug)]
+pub struct VariantLayout<'ctx> {
+    pub name: String,
+    /// タグの値（宣言順に0から）
+    pub tag: u32,
+    /// 宣言順のフィールド
+    pub fields: Vec<Field>,
+    /// フィールドを宣言順に並べた構造体型（Enumの値の領域をこの型として読み書きする）
+    pub payload_type: StructType<'ctx>,
+}
+
+impl<'ctx> VariantLayout<'ctx> {
+    /// 名前でフィールドのインデックスを取得
+    pub fn field_index(&self, name: &str) -> Option<u32> {
+        self.fields.iter().position(|field| field.name == name).map(|index| index as u32)
+    }
+}
+
+/// Enumのレイアウト
+#[derive(Debug)]
+pub struct EnumLayout<'ctx> {
+    /// フィールドを持たないEnumはタグ(i32)、それ以外は { タグ(i32), 領域 }
+    ///
+    /// 領域はデータレイアウト上で最も大きいバリアントのフィールドが収まる大きさとアライメントです。
+    /// どのバリアントの値もこの型になるため、同じ変数への代入や値渡しは値全体のコピーで済みます。
+    pub llvm_type: BasicTypeEnum<'ctx>,
+    /// 宣言順のバリアント（インデックスはバリアントのIDで、タグの値と一致する）
+    pub variants: Vec<VariantLayout<'ctx>>,
+    /// バリアント名のIDからバリアントのIDへの対応
+    variant_ids: HashMap<Symbol, u32>,
+}
+
+impl<'ctx> EnumLayout<'ctx> {
+    /// バリアント名を名前表に登録してレイアウトを作成
+    pub fn new(llvm_type: BasicTypeEnum<'ctx>, variants: Vec<VariantLayout<'ctx>>, names: &mut Interner) -> Self {
+        let variant_ids = variants
+            .iter()
+            .enumerate()
+            .map(|(index, variant)| (names.intern(&variant.name), index as u32))
+            .collect();
+        Self { llvm_type, variants, variant_ids }
+    }
+
+    /// バリアント名のIDからバリアントのIDを取得
+    pub fn variant_id(&self, name: Symbol) -> Option<u32> {
+        self.variant_ids.get(&name).copied()
+    }
+
+    /// 宣言順のバリアント名（エラーメッセージ用）
+    pub fn variant_names(&self) -> String {
+        self.variants
+            .iter()
+            .map(|variant| variant.name.as_str())
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+/// 名前を解決したバリアント
+///
+/// 式やパターンのコード生成の最初に一度だけ名前から解決し、以降はこれを渡します。
+#[derive(Debug, Clone)]
+pub struct VariantRef<'ctx> {
+    pub layout: Rc<EnumLayout<'ctx>>,
+    pub id: u32,
+}
+
+impl<'ctx> VariantRef<'ctx> {
+    pub fn variant(&self) -> &VariantLayout<'ctx> {
+        &self.layout.variants[self.id as usize]
+    }
+}
@@ -3,12 +3,25 @@
 //! このモジュールはASTからLLVM IRを生成する責任を持ちます。
 
 mod code_generator;
+mod const_fold;
+mod defer;
 mod expr;
+mod inline;
+mod interner;
+mod layout;
+mod naming;
+mod options;
 mod runtime;
+mod stack;
+mod stats;
 mod stmt_codegen;
 mod symbol_table;
 mod tail_call;
 mod types;
 
 // 公開API
-pub use code_generator::CodeGenerator;
\ No newline at end of file
+pub use code_generator::CodeGenerator;
+pub use interner::{Interner, Symbol};
+pub use layout::{EnumLayout, FieldLayout, StructLayout, VariantLayout, VariantRef};
+pub use options::CodegenOptions;
+pub use stats::CodegenStats;
\ No newline at end of file
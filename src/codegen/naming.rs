@@ -0,0 +1,108 @@
+//! 生成するLLVM IRの値の名前
+//!
+//! IRの差分を読みやすく、テストの期待値を安定させるため、値には次の規則で名前を付けます。
+//!
+//! - 引数は `<引数名>.arg`、引数と `let` の変数のスタック領域は `<変数名>.addr`
+//! - 変数の読み出しは変数名、フィールドの読み出しは `<変数名>.<フィールド名>`
+//!   （フィールドのアドレスは `<変数名>.<フィールド名>.ptr`）
+//! - 文字列リテラルなどの定数のグローバル変数は `<関数名>.<種類>`。同じ関数の中で
+//!   2つ目以降は `<関数名>.<種類>.1` のように関数ごとの連番を付けます
+//! - それ以外の一時的な値は操作の種類の名前
+//!
+//! 同じ関数の中で名前が重複した場合は、LLVMが関数ごとの連番を付けます（`%x1` など）。
+//!
+//! どの名前も同じ関数の中の生成順だけで決まるため、他の関数を変更してもIRは変わりません。
+//! 名前を変える変更はゴールデンIRのテスト（`tests/codegen/golden`）の更新が必要です。
+//! `--no-names` を指定した場合は、関数の中の値の名前をすべて取り除いて出力します。
+
+use std::collections::HashMap;
+
+use crate::ast::Expression;
+use inkwell::module::Module;
+
+/// 関数の中で付けた定数のグローバル変数の名前
+#[derive(Debug, Default)]
+pub struct ValueNames {
+    function: String,
+    counts: HashMap<String, u32>,
+}
+
+impl ValueNames {
+    /// 関数のコンパイルを始める（連番は関数ごとに0から数える）
+    pub fn enter_function(&mut self, function: &str) {
+        self.function = function.to_string();
+        self.counts.clear();
+    }
+
+    /// 関数のコンパイルを終える（連番の表を解放する）
+    pub fn leave_function(&mut self) {
+        self.function.clear();
+        self.counts = HashMap::new();
+    }
+
+    /// 連番を数えている定数の種類の数
+    pub fn kind_count(&self) -> usize {
+        self.counts.len()
+    }
+
+    /// 種類 `kind` の定数のグローバル変数の名前
+    pub fn global(&mut self, kind: &str) -> String {
+        let base = if self.function.is_empty() {
+            kind.to_string()
+        } else {
+            format!("{}.{}", self.function, kind)
+        };
+        let count = self.counts.entry(kind.to_string()).or_insert(0);
+        let name = match *count {
+            0 => base,
+            n => format!("{}.{}", base, n),
+        };
+        *count += 1;
+        name
+    }
+}
+
+/// 引数の値の名前（変数の読み出しと区別する）
+pub fn argument_name(param: &str) -> String {
+    format!("{}.arg", param)
+}
+
+/// 変数のスタック領域の名前
+pub fn address_name(variable: &str) -> String {
+    format!("{}.addr", variable)
+}
+
+/// フィールドの読み出しの名前（変数から辿れない場合はフィールド名だけ）
+pub fn field_name(object: &Expression, field: &str) -> String {
+    match value_path(object) {
+        Some(path) => format!("{}.{}", path, field),
+        None => field.to_string(),
+    }
+}
+
+/// 変数とフィールドを辿る式の名前（`p.pos` など）
+fn value_path(expr: &Expression) -> Option<String> {
+    match expr {
+        Expression::Identifier(ident) => Some(ident.name.clone()),
+        Expression::Field(field) => value_path(&field.object).map(|path| format!("{}.{}", path, field.field)),
+        _ => None,
+    }
+}
+
+/// 関数の中の値（引数・基本ブロック・命令）の名前をすべて取り除く
+///
+/// 取り除いた値はLLVMが `%0` からの番号で出力します。グローバル変数と関数の名前は残します。
+pub fn strip_value_names(module: &Module) {
+    for function in module.get_functions() {
+        for param in function.get_param_iter() {
+            param.set_name("");
+        }
+        for block in function.get_basic_blocks() {
+            block.set_name("");
+            for instruction in block.get_instructions() {
+                // 値を持たない命令（storeなど）には名前がない
+                let _ = instruction.set_name("");
+            }
+        }
+    }
+}
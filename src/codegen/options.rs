@@ -0,0 +1,211 @@
+//! コード生成の設定
+//!
+//! 生成するモジュールのソースファイル名・ターゲット・再配置モデルなどをまとめます。
+//! 同じ設定からIRのモジュール情報とオブジェクトファイルを作るターゲットマシンを作るため、
+//! llcでオブジェクトファイルにする場合も `llc_args` で同じ設定を渡します。
+
+use crate::error::{CodegenError, YuniError, YuniResult};
+use inkwell::targets::{CodeModel, InitializationConfig, RelocMode, Target, TargetMachine, TargetTriple};
+use inkwell::OptimizationLevel;
+
+/// コード生成の設定
+#[derive(Debug, Clone)]
+pub struct CodegenOptions {
+    /// ソースファイルのパス（モジュール名とsource_filenameに使用）
+    pub source_path: String,
+    /// ターゲットトリプル（Noneの場合はホスト）
+    pub target_triple: Option<String>,
+    /// コードモデル
+    pub code_model: CodeModel,
+    /// 位置独立コードを生成する
+    ///
+    /// PIEを既定でリンクするLinuxディストリビューションでは、
+    /// 位置独立でないオブジェクトファイルは再配置のエラーでリンクできません。
+    pub pic: bool,
+    /// 最適化レベル
+    pub opt_level: OptimizationLevel,
+    /// 値が失われるキャストを実行時に検査する（`--checked-casts`）
+    pub checked_casts: bool,
+    /// `debug_assert` と `debug_assert_eq` を検査する（`-O0` または `--debug-assertions`）
+    pub debug_assertions: bool,
+    /// 関数の中の値の名前を取り除いて出力する（`--no-names`）
+    pub strip_names: bool,
+    /// 参照の参照先を読み出す前にnullを検査する（`--no-null-checks` で無効）
+    pub null_checks: bool,
+    /// 関数ごとにセクションを分け、リンク時に使われない関数を取り除けるようにする（`--no-gc-sections` で無効）
+    pub gc_sections: bool,
+    /// メインスレッドのスタックの大きさ（バイト数、`--stack-size`）。Noneの場合はOSの既定
+    pub stack_size: Option<u64>,
+}
+
+impl CodegenOptions {
+    /// ホスト向けの既定の設定を作成
+    pub fn new(source_path: impl Into<String>) -> Self {
+        Self {
+            source_path: source_path.into(),
+            target_triple: None,
+            code_model: CodeModel::Default,
+            pic: true,
+            opt_level: OptimizationLevel::Default,
+            checked_casts: false,
+            debug_assertions: false,
+            strip_names: false,
+            null_checks: true,
+            gc_sections: true,
+            stack_size: None,
+        }
+    }
+
+    /// ターゲットトリプルを設定（Noneの場合はホスト）
+    pub fn with_target_triple(mut self, triple: Option<String>) -> Self {
+        self.target_triple = triple;
+        self
+    }
+
+    /// コードモデルを設定
+    pub fn with_code_model(mut self, code_model: CodeModel) -> Self {
+        self.code_model = code_model;
+        self
+    }
+
+    /// 位置独立コードを生成するかを設定
+    pub fn with_pic(mut self, pic: bool) -> Self {
+        self.pic = pic;
+        self
+    }
+
+    /// 最適化レベルを設定
+    pub fn with_opt_level(mut self, opt_level: OptimizationLevel) -> Self {
+        self.opt_level = opt_level;
+        self
+    }
+
+    /// 値が失われるキャストを実行時に検査するかを設定
+    pub fn with_checked_casts(mut self, checked_casts: bool) -> Self {
+        self.checked_casts = checked_casts;
+        self
+    }
+
+    /// `debug_assert` と `debug_assert_eq` を検査するかを設定
+    ///
+    /// 検査しない場合、これらの呼び出しは引数も含めて何も生成しません。
+    pub fn with_debug_assertions(mut self, debug_assertions: bool) -> Self {
+        self.debug_assertions = debug_assertions;
+        self
+    }
+
+    /// 関数の中の値の名前を取り除いて出力するかを設定
+    pub fn with_strip_names(mut self, strip_names: bool) -> Self {
+        self.strip_names = strip_names;
+        self
+    }
+
+    /// 参照の参照先を読み出す前にnullを検査するかを設定
+    ///
+    /// 検査しない場合、nullの参照の読み出しはセグメンテーション違反になります。
+    pub fn with_null_checks(mut self, null_checks: bool) -> Self {
+        self.null_checks = null_checks;
+        self
+    }
+
+    /// 関数ごとにセクションを分けるかを設定
+    ///
+    /// 分けない場合、リンカは使われない関数をオブジェクトファイル単位でしか取り除けません。
+    pub fn with_gc_sections(mut self, gc_sections: bool) -> Self {
+        self.gc_sections = gc_sections;
+        self
+    }
+
+    /// メインスレッドのスタックの大きさを設定
+    ///
+    /// 実行ファイルのリンク時のリンカの引数と、ランタイムの初期化に渡す値になります。
+    pub fn with_stack_size(mut self, stack_size: Option<u64>) -> Self {
+        self.stack_size = stack_size;
+        self
+    }
+
+    /// ターゲットトリプル
+    pub fn triple(&self) -> TargetTriple {
+        match &self.target_triple {
+            Some(triple) => TargetTriple::create(triple),
+            None => TargetMachine::get_default_triple(),
+        }
+    }
+
+    /// 再配置モデル
+    pub fn reloc_mode(&self) -> RelocMode {
+        if self.pic {
+            RelocMode::PIC
+        } else {
+            RelocMode::Static
+        }
+    }
+
+    /// ターゲットのオブジェクトファイルがELF形式か
+    ///
+    /// Mach-O（macOS）は既定でシンボルごとに取り除けるため、関数ごとのセクションはELFでだけ必要です。
+    pub fn is_elf(&self) -> bool {
+        let triple = self.triple();
+        let triple = triple.as_str().to_string_lossy();
+        !["apple", "darwin", "windows", "uefi"].iter().any(|os| triple.contains(os))
+    }
+
+    /// 関数のプロローグでスタックのページを順に触る検査（stack probe）を生成できるか
+    ///
+    /// 大きなフレームを確保してもガードページを飛び越えず、スタックの溢れを確実に検出できます。
+    /// Windowsは既定で `__chkstk` による検査が生成されるため対象外です。
+    pub fn stack_probes(&self) -> bool {
+        let triple = self.triple();
+        let triple = triple.as_str().to_string_lossy();
+        let arch = triple.split('-').next().unwrap_or_default();
+        let supported = matches!(arch, "x86_64" | "i386" | "i486" | "i586" | "i686" | "aarch64" | "arm64");
+        supported && !triple.contains("windows")
+    }
+
+    /// 設定に従ったターゲットマシンを作成
+    pub fn target_machine(&self) -> YuniResult<TargetMachine> {
+        Target::initialize_all(&InitializationConfig::default());
+        let triple = self.triple();
+        let name = triple.as_str().to_string_lossy().into_owned();
+        let target = Target::from_triple(&triple).map_err(|e| {
+            YuniError::Codegen(CodegenError::Internal {
+                message: format!("Unknown target triple {}: {}", name, e),
+            })
+        })?;
+        target
+            .create_target_machine(&triple, "generic", "", self.opt_level, self.reloc_mode(), self.code_model)
+            .ok_or_else(|| {
+                YuniError::Codegen(CodegenError::Internal {
+                    message: format!("Failed to create target machine for {}", name),
+                })
+            })
+    }
+
+    /// LLVM IRをllcでコンパイルするときに同じ設定を渡す引数
+    ///
+    /// ターゲットトリプルはIRのモジュールに含まれるため渡しません。
+    pub fn llc_args(&self) -> Vec<String> {
+        let relocation_model = if self.pic { "pic" } else { "static" };
+        let mut args = vec![format!("-relocation-model={}", relocation_model)];
+        let code_model = match self.code_model {
+            CodeModel::Default | CodeModel::JITDefault => None,
+            CodeModel::Small => Some("small"),
+            CodeModel::Kernel => Some("kernel"),
+            CodeModel::Medium => Some("medium"),
+            CodeModel::Large => Some("large"),
+        };
+        if let Some(code_model) = code_model {
+            args.push(format!("-code-model={}", code_model));
+        }
+        if self.gc_sections {
+            args.extend(["-function-sections".to_string(), "-data-sections".to_string()]);
+        }
+        args
+    }
+}
+
+impl From<&str> for CodegenOptions {
+    fn from(source_path: &str) -> Self {
+        Self::new(source_path)
+    }
+}
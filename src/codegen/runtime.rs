@@ -2,7 +2,7 @@
 
 use inkwell::context::Context;
 use inkwell::module::{Linkage, Module};
-use inkwell::types::FunctionType;
+use inkwell::types::{BasicMetadataTypeEnum, FunctionType};
 use inkwell::values::FunctionValue;
 use inkwell::AddressSpace;
 use std::collections::HashMap;
@@ -40,11 +40,6 @@ impl<'ctx> RuntimeManager<'ctx> {
         let i64_type = self.context.i64_type();
         let void_type = self.context.void_type();
         
-        // printf
-        let printf_type = i32_type.fn_type(&[i8_ptr_type.into()], true);
-        let printf = module.add_function("printf", printf_type, Some(Linkage::External));
-        self.functions.insert("printf".to_string(), printf);
-        
         // malloc
         let malloc_type = i8_ptr_type.fn_type(&[i64_type.into()], false);
         let malloc = module.add_function("malloc", malloc_type, Some(Linkage::External));
@@ -84,6 +79,7 @@ impl<'ctx> RuntimeManager<'ctx> {
         let i8_ptr_type = self.context.ptr_type(AddressSpace::default());
         let i64_type = self.context.i64_type();
         let f64_type = self.context.f64_type();
+        let f32_type = self.context.f32_type();
         let bool_type = self.context.bool_type();
         let void_type = self.context.void_type();
         
@@ -96,6 +92,24 @@ impl<'ctx> RuntimeManager<'ctx> {
         );
         self.functions.insert("yuni_string_concat".to_string(), concat);
         
+        // 文字列の長さ（バイト数）
+        let str_len_type = i64_type.fn_type(&[i8_ptr_type.into()], false);
+        let str_len = module.add_function(
+            "yuni_str_len",
+            str_len_type,
+            Some(Linkage::External),
+        );
+        self.functions.insert("yuni_str_len".to_string(), str_len);
+        
+        // 長さを指定した文字列データを新しい文字列に複製（strからString）
+        let string_from_parts_type = i8_ptr_type.fn_type(&[i8_ptr_type.into(), i64_type.into()], false);
+        let string_from_parts = module.add_function(
+            "yuni_string_from_parts",
+            string_from_parts_type,
+            Some(Linkage::External),
+        );
+        self.functions.insert("yuni_string_from_parts".to_string(), string_from_parts);
+        
         // 型変換関数
         // int to string (汎用整数変換)
         let int_to_string_type = i8_ptr_type.fn_type(&[i64_type.into()], false);
@@ -115,6 +129,15 @@ impl<'ctx> RuntimeManager<'ctx> {
         );
         self.functions.insert("yuni_i64_to_string".to_string(), i64_to_string);
         
+        // u64 to string（符号なし整数はゼロ拡張して渡す）
+        let u64_to_string_type = i8_ptr_type.fn_type(&[i64_type.into()], false);
+        let u64_to_string = module.add_function(
+            "yuni_u64_to_string",
+            u64_to_string_type,
+            Some(Linkage::External),
+        );
+        self.functions.insert("yuni_u64_to_string".to_string(), u64_to_string);
+        
         // float to string (汎用浮動小数点変換)
         let float_to_string_type = i8_ptr_type.fn_type(&[f64_type.into()], false);
         let float_to_string = module.add_function(
@@ -133,6 +156,15 @@ impl<'ctx> RuntimeManager<'ctx> {
         );
         self.functions.insert("yuni_f64_to_string".to_string(), f64_to_string);
         
+        // f32 to string（f64に広げずにf32の値として出力する）
+        let f32_to_string_type = i8_ptr_type.fn_type(&[f32_type.into()], false);
+        let f32_to_string = module.add_function(
+            "yuni_f32_to_string",
+            f32_to_string_type,
+            Some(Linkage::External),
+        );
+        self.functions.insert("yuni_f32_to_string".to_string(), f32_to_string);
+        
         // bool to string
         let bool_to_string_type = i8_ptr_type.fn_type(&[bool_type.into()], false);
         let bool_to_string = module.add_function(
@@ -142,14 +174,62 @@ impl<'ctx> RuntimeManager<'ctx> {
         );
         self.functions.insert("yuni_bool_to_string".to_string(), bool_to_string);
         
-        // println
-        let println_type = void_type.fn_type(&[i8_ptr_type.into()], false);
-        let println = module.add_function(
-            "yuni_println",
-            println_type,
+        // コンソール出力（文字列をそのまま書き出す）
+        let output_type = void_type.fn_type(&[i8_ptr_type.into()], false);
+        for name in ["yuni_print", "yuni_println", "yuni_eprintln"] {
+            let output = module.add_function(name, output_type, Some(Linkage::External));
+            self.functions.insert(name.to_string(), output);
+        }
+        
+        // 標準入力から1行読み込む
+        let read_line_type = i8_ptr_type.fn_type(&[], false);
+        let read_line = module.add_function(
+            "yuni_read_line",
+            read_line_type,
+            Some(Linkage::External),
+        );
+        self.functions.insert("yuni_read_line".to_string(), read_line);
+        
+        // 文字列を数値に変換する（変換した値は出力先のポインタに書き込み、成否を返す）
+        let parse_type = bool_type.fn_type(&[i8_ptr_type.into(), i8_ptr_type.into()], false);
+        for name in ["yuni_parse_i64", "yuni_parse_f64"] {
+            let parse = module.add_function(name, parse_type, Some(Linkage::External));
+            self.functions.insert(name.to_string(), parse);
+        }
+        
+        // 時刻と乱数
+        // yuni_time_nanos() -> i64・yuni_time_unix_millis() -> i64
+        let time_type = i64_type.fn_type(&[], false);
+        for name in ["yuni_time_nanos", "yuni_time_unix_millis"] {
+            let time = module.add_function(name, time_type, Some(Linkage::External));
+            self.functions.insert(name.to_string(), time);
+        }
+        
+        // yuni_sleep_millis(ms: i64)
+        let sleep_millis_type = void_type.fn_type(&[i64_type.into()], false);
+        let sleep_millis = module.add_function(
+            "yuni_sleep_millis",
+            sleep_millis_type,
+            Some(Linkage::External),
+        );
+        self.functions.insert("yuni_sleep_millis".to_string(), sleep_millis);
+        
+        // yuni_random_seed(seed: u64)・yuni_random_u64() -> u64
+        let random_seed_type = void_type.fn_type(&[i64_type.into()], false);
+        let random_seed = module.add_function(
+            "yuni_random_seed",
+            random_seed_type,
+            Some(Linkage::External),
+        );
+        self.functions.insert("yuni_random_seed".to_string(), random_seed);
+        
+        let random_u64_type = i64_type.fn_type(&[], false);
+        let random_u64 = module.add_function(
+            "yuni_random_u64",
+            random_u64_type,
             Some(Linkage::External),
         );
-        self.functions.insert("yuni_println".to_string(), println);
+        self.functions.insert("yuni_random_u64".to_string(), random_u64);
         
         // 文字列比較
         let string_eq_type = bool_type.fn_type(&[i8_ptr_type.into(), i8_ptr_type.into()], false);
@@ -160,6 +240,49 @@ impl<'ctx> RuntimeManager<'ctx> {
         );
         self.functions.insert("yuni_string_eq".to_string(), string_eq);
         
+        // 部分文字列を含むか
+        let string_contains_type = bool_type.fn_type(&[i8_ptr_type.into(), i8_ptr_type.into()], false);
+        let string_contains = module.add_function(
+            "yuni_string_contains",
+            string_contains_type,
+            Some(Linkage::External),
+        );
+        self.functions.insert("yuni_string_contains".to_string(), string_contains);
+        
+        // 接頭辞・部分文字列の位置・分割・前後の空白の除去
+        let string_starts_with_type = bool_type.fn_type(&[i8_ptr_type.into(), i8_ptr_type.into()], false);
+        let string_starts_with = module.add_function(
+            "yuni_string_starts_with",
+            string_starts_with_type,
+            Some(Linkage::External),
+        );
+        self.functions.insert("yuni_string_starts_with".to_string(), string_starts_with);
+        
+        let string_find_type = i64_type.fn_type(&[i8_ptr_type.into(), i8_ptr_type.into()], false);
+        let string_find = module.add_function(
+            "yuni_string_find",
+            string_find_type,
+            Some(Linkage::External),
+        );
+        self.functions.insert("yuni_string_find".to_string(), string_find);
+        
+        // yuni_string_split(s, separator) -> *mut YuniVec（要素は新しく確保したString）
+        let string_split_type = i8_ptr_type.fn_type(&[i8_ptr_type.into(), i8_ptr_type.into()], false);
+        let string_split = module.add_function(
+            "yuni_string_split",
+            string_split_type,
+            Some(Linkage::External),
+        );
+        self.functions.insert("yuni_string_split".to_string(), string_split);
+        
+        let string_trim_type = i8_ptr_type.fn_type(&[i8_ptr_type.into()], false);
+        let string_trim = module.add_function(
+            "yuni_string_trim",
+            string_trim_type,
+            Some(Linkage::External),
+        );
+        self.functions.insert("yuni_string_trim".to_string(), string_trim);
+        
         // エラーハンドリング
         let panic_type = void_type.fn_type(&[i8_ptr_type.into()], false);
         let panic = module.add_function(
@@ -168,6 +291,38 @@ impl<'ctx> RuntimeManager<'ctx> {
             Some(Linkage::External),
         );
         self.functions.insert("yuni_panic".to_string(), panic);
+        let assert_eq_failed_type = void_type.fn_type(&[i8_ptr_type.into(), i8_ptr_type.into(), i8_ptr_type.into()], false);
+        let assert_eq_failed = module.add_function("yuni_assert_eq_failed", assert_eq_failed_type, Some(Linkage::External));
+        self.functions.insert("yuni_assert_eq_failed".to_string(), assert_eq_failed);
+
+        // 検査付きキャストの範囲外（整数は128ビットに拡張して下位・上位の64ビットで渡す）
+        let panic_cast_int_type = void_type.fn_type(
+            &[i64_type.into(), i64_type.into(), bool_type.into(), i8_ptr_type.into(), i8_ptr_type.into()],
+            false,
+        );
+        let panic_cast_int = module.add_function("yuni_panic_cast_int", panic_cast_int_type, Some(Linkage::External));
+        self.functions.insert("yuni_panic_cast_int".to_string(), panic_cast_int);
+        let panic_cast_float_type = void_type.fn_type(&[f64_type.into(), i8_ptr_type.into(), i8_ptr_type.into()], false);
+        let panic_cast_float = module.add_function("yuni_panic_cast_float", panic_cast_float_type, Some(Linkage::External));
+        self.functions.insert("yuni_panic_cast_float".to_string(), panic_cast_float);
+
+        // ランタイムの初期化（mainの先頭で呼び出す）
+        let runtime_init_type = void_type.fn_type(&[i64_type.into()], false);
+        let runtime_init = module.add_function("yuni_runtime_init", runtime_init_type, Some(Linkage::External));
+        self.functions.insert("yuni_runtime_init".to_string(), runtime_init);
+
+        // HashMapのインデックスアクセスでキーが見つからない
+        let panic_missing_key_type = void_type.fn_type(&[i8_ptr_type.into()], false);
+        let panic_missing_key = module.add_function("yuni_panic_missing_key", panic_missing_key_type, Some(Linkage::External));
+        self.functions.insert("yuni_panic_missing_key".to_string(), panic_missing_key);
+
+        // Vecのインデックスアクセスでインデックスが範囲外
+        let panic_index_type = void_type.fn_type(
+            &[i64_type.into(), i64_type.into(), bool_type.into(), i8_ptr_type.into()],
+            false,
+        );
+        let panic_index = module.add_function("yuni_panic_index", panic_index_type, Some(Linkage::External));
+        self.functions.insert("yuni_panic_index".to_string(), panic_index);
         
         // メモリ管理
         let alloc_string_type = i8_ptr_type.fn_type(&[i64_type.into()], false);
@@ -186,11 +341,64 @@ impl<'ctx> RuntimeManager<'ctx> {
         );
         self.functions.insert("yuni_free_string".to_string(), free_string);
         
+        // 文字列ビルダーランタイム関数
+        // yuni_sb_new() -> *mut YuniStringBuilder
+        let sb_ptr_type = self.context.ptr_type(AddressSpace::default());
+        let sb_new_type = sb_ptr_type.fn_type(&[], false);
+        let sb_new = module.add_function(
+            "yuni_sb_new",
+            sb_new_type,
+            Some(Linkage::External),
+        );
+        self.functions.insert("yuni_sb_new".to_string(), sb_new);
+        
+        // yuni_sb_append_str / int / uint / float / f32 / bool(sb, value)
+        let append_value_types: [(&str, BasicMetadataTypeEnum<'ctx>); 6] = [
+            ("yuni_sb_append_str", i8_ptr_type.into()),
+            ("yuni_sb_append_int", i64_type.into()),
+            ("yuni_sb_append_uint", i64_type.into()),
+            ("yuni_sb_append_float", f64_type.into()),
+            ("yuni_sb_append_f32", f32_type.into()),
+            ("yuni_sb_append_bool", bool_type.into()),
+        ];
+        for (name, value_type) in append_value_types {
+            let append_type = void_type.fn_type(&[sb_ptr_type.into(), value_type], false);
+            let append = module.add_function(name, append_type, Some(Linkage::External));
+            self.functions.insert(name.to_string(), append);
+        }
+        
+        // yuni_sb_len(sb) -> usize
+        let sb_len_type = i64_type.fn_type(&[sb_ptr_type.into()], false);
+        let sb_len = module.add_function(
+            "yuni_sb_len",
+            sb_len_type,
+            Some(Linkage::External),
+        );
+        self.functions.insert("yuni_sb_len".to_string(), sb_len);
+        
+        // yuni_sb_to_string(sb) -> *mut c_char（ビルダーは残る）
+        let sb_to_string_type = i8_ptr_type.fn_type(&[sb_ptr_type.into()], false);
+        let sb_to_string = module.add_function(
+            "yuni_sb_to_string",
+            sb_to_string_type,
+            Some(Linkage::External),
+        );
+        self.functions.insert("yuni_sb_to_string".to_string(), sb_to_string);
+        
+        // yuni_sb_finish(sb) -> *mut c_char（ビルダーを解放）
+        let sb_finish_type = i8_ptr_type.fn_type(&[sb_ptr_type.into()], false);
+        let sb_finish = module.add_function(
+            "yuni_sb_finish",
+            sb_finish_type,
+            Some(Linkage::External),
+        );
+        self.functions.insert("yuni_sb_finish".to_string(), sb_finish);
+        
         // Vecランタイム関数
-        // yuni_vec_new(element_size) -> *mut YuniVec
+        // yuni_vec_new(element_size, element_align) -> *mut YuniVec
         let _vec_type = self.context.opaque_struct_type("YuniVec");
         let vec_ptr_type = self.context.ptr_type(AddressSpace::default());
-        let vec_new_type = vec_ptr_type.fn_type(&[i64_type.into()], false);
+        let vec_new_type = vec_ptr_type.fn_type(&[i64_type.into(), i64_type.into()], false);
         let vec_new = module.add_function(
             "yuni_vec_new",
             vec_new_type,
@@ -225,6 +433,15 @@ impl<'ctx> RuntimeManager<'ctx> {
         );
         self.functions.insert("yuni_vec_len".to_string(), vec_len);
         
+        // yuni_vec_resize(vec, new_len, fill)
+        let vec_resize_type = void_type.fn_type(&[vec_ptr_type.into(), i64_type.into(), i8_ptr_type.into()], false);
+        let vec_resize = module.add_function(
+            "yuni_vec_resize",
+            vec_resize_type,
+            Some(Linkage::External),
+        );
+        self.functions.insert("yuni_vec_resize".to_string(), vec_resize);
+        
         // yuni_vec_free(vec)
         let vec_free_type = void_type.fn_type(&[vec_ptr_type.into()], false);
         let vec_free = module.add_function(
@@ -234,6 +451,70 @@ impl<'ctx> RuntimeManager<'ctx> {
         );
         self.functions.insert("yuni_vec_free".to_string(), vec_free);
         
+        // yuni_vec_from_bytes(data, len) -> *mut YuniVec（Vec<u8>を一度に作成）
+        let vec_from_bytes_type = vec_ptr_type.fn_type(&[i8_ptr_type.into(), i64_type.into()], false);
+        let vec_from_bytes = module.add_function(
+            "yuni_vec_from_bytes",
+            vec_from_bytes_type,
+            Some(Linkage::External),
+        );
+        self.functions.insert("yuni_vec_from_bytes".to_string(), vec_from_bytes);
+        
+        // yuni_vec_data(vec) -> *const u8
+        let vec_data_type = i8_ptr_type.fn_type(&[vec_ptr_type.into()], false);
+        let vec_data = module.add_function(
+            "yuni_vec_data",
+            vec_data_type,
+            Some(Linkage::External),
+        );
+        self.functions.insert("yuni_vec_data".to_string(), vec_data);
+        
+        // yuni_vec_sort(vec, compare)（compareは要素へのポインタを2つ受け取りi32を返す関数）
+        let vec_sort_type = void_type.fn_type(&[vec_ptr_type.into(), i8_ptr_type.into()], false);
+        let vec_sort = module.add_function(
+            "yuni_vec_sort",
+            vec_sort_type,
+            Some(Linkage::External),
+        );
+        self.functions.insert("yuni_vec_sort".to_string(), vec_sort);
+        
+        // yuni_vec_binary_search(vec, key, compare) -> i64（見つからなければ-1）
+        let vec_binary_search_type = i64_type.fn_type(&[vec_ptr_type.into(), i8_ptr_type.into(), i8_ptr_type.into()], false);
+        let vec_binary_search = module.add_function(
+            "yuni_vec_binary_search",
+            vec_binary_search_type,
+            Some(Linkage::External),
+        );
+        self.functions.insert("yuni_vec_binary_search".to_string(), vec_binary_search);
+        
+        // yuni_vec_contains(vec, key, compare) -> bool
+        let vec_contains_type = bool_type.fn_type(&[vec_ptr_type.into(), i8_ptr_type.into(), i8_ptr_type.into()], false);
+        let vec_contains = module.add_function(
+            "yuni_vec_contains",
+            vec_contains_type,
+            Some(Linkage::External),
+        );
+        self.functions.insert("yuni_vec_contains".to_string(), vec_contains);
+        
+        // バイナリファイルの読み書き（ポインタと長さで渡すためNULを含んでもよい）
+        // yuni_write_bytes(path, data, len) -> bool
+        let write_bytes_type = bool_type.fn_type(&[i8_ptr_type.into(), i8_ptr_type.into(), i64_type.into()], false);
+        let write_bytes = module.add_function(
+            "yuni_write_bytes",
+            write_bytes_type,
+            Some(Linkage::External),
+        );
+        self.functions.insert("yuni_write_bytes".to_string(), write_bytes);
+        
+        // yuni_read_bytes(path) -> *mut YuniVec
+        let read_bytes_type = vec_ptr_type.fn_type(&[i8_ptr_type.into()], false);
+        let read_bytes = module.add_function(
+            "yuni_read_bytes",
+            read_bytes_type,
+            Some(Linkage::External),
+        );
+        self.functions.insert("yuni_read_bytes".to_string(), read_bytes);
+        
         // HashMapランタイム関数
         // yuni_hashmap_new(key_size, value_size) -> *mut YuniHashMap
         let _hashmap_type = self.context.opaque_struct_type("YuniHashMap");
@@ -246,6 +527,15 @@ impl<'ctx> RuntimeManager<'ctx> {
         );
         self.functions.insert("yuni_hashmap_new".to_string(), hashmap_new);
         
+        // yuni_hashmap_new_str(value_size) -> *mut YuniHashMap（文字列のキー）
+        let hashmap_new_str_type = hashmap_ptr_type.fn_type(&[i64_type.into()], false);
+        let hashmap_new_str = module.add_function(
+            "yuni_hashmap_new_str",
+            hashmap_new_str_type,
+            Some(Linkage::External),
+        );
+        self.functions.insert("yuni_hashmap_new_str".to_string(), hashmap_new_str);
+        
         // yuni_hashmap_insert(map, key, value)
         let hashmap_insert_type = void_type.fn_type(&[hashmap_ptr_type.into(), i8_ptr_type.into(), i8_ptr_type.into()], false);
         let hashmap_insert = module.add_function(
@@ -264,23 +554,23 @@ impl<'ctx> RuntimeManager<'ctx> {
         );
         self.functions.insert("yuni_hashmap_get".to_string(), hashmap_get);
         
-        // yuni_hashmap_contains(map, key) -> bool
-        let hashmap_contains_type = bool_type.fn_type(&[hashmap_ptr_type.into(), i8_ptr_type.into()], false);
-        let hashmap_contains = module.add_function(
-            "yuni_hashmap_contains",
-            hashmap_contains_type,
+        // yuni_hashmap_contains_key(map, key) -> bool
+        let hashmap_contains_key_type = bool_type.fn_type(&[hashmap_ptr_type.into(), i8_ptr_type.into()], false);
+        let hashmap_contains_key = module.add_function(
+            "yuni_hashmap_contains_key",
+            hashmap_contains_key_type,
             Some(Linkage::External),
         );
-        self.functions.insert("yuni_hashmap_contains".to_string(), hashmap_contains);
+        self.functions.insert("yuni_hashmap_contains_key".to_string(), hashmap_contains_key);
         
-        // yuni_hashmap_size(map) -> usize
-        let hashmap_size_type = i64_type.fn_type(&[hashmap_ptr_type.into()], false);
-        let hashmap_size = module.add_function(
-            "yuni_hashmap_size",
-            hashmap_size_type,
+        // yuni_hashmap_len(map) -> usize
+        let hashmap_len_type = i64_type.fn_type(&[hashmap_ptr_type.into()], false);
+        let hashmap_len = module.add_function(
+            "yuni_hashmap_len",
+            hashmap_len_type,
             Some(Linkage::External),
         );
-        self.functions.insert("yuni_hashmap_size".to_string(), hashmap_size);
+        self.functions.insert("yuni_hashmap_len".to_string(), hashmap_len);
         
         // yuni_hashmap_free(map)
         let hashmap_free_type = void_type.fn_type(&[hashmap_ptr_type.into()], false);
@@ -0,0 +1,38 @@
+//! スタックの溢れの検出
+//!
+//! 定義した関数にはLLVMの `"probe-stack"="inline-asm"` 属性を付け、大きなフレームを確保する
+//! プロローグでもスタックのページを順に触るようにします。ガードページを飛び越えて
+//! 別の領域を壊さず、スタックの上限の付近で必ずセグメンテーション違反になります。
+//!
+//! `main` の先頭ではランタイムの `yuni_runtime_init` を呼び出し、その違反を
+//! 「stack overflow」のメッセージと専用の終了コードで報告するシグナルハンドラを登録します。
+
+use crate::error::{CodegenError, YuniError, YuniResult};
+use inkwell::attributes::AttributeLoc;
+use inkwell::values::FunctionValue;
+
+use super::code_generator::CodeGenerator;
+
+impl<'ctx> CodeGenerator<'ctx> {
+    /// 関数の宣言にスタックの検査の属性を付ける（対応していないターゲットでは何もしない）
+    pub(crate) fn add_stack_probe_attribute(&self, function: FunctionValue<'ctx>) {
+        if !self.options.stack_probes() {
+            return;
+        }
+        let attribute = self.context.create_string_attribute("probe-stack", "inline-asm");
+        function.add_attribute(AttributeLoc::Function, attribute);
+    }
+
+    /// `main` のエントリブロックでランタイムを初期化する
+    ///
+    /// `--stack-size` の指定がない場合はスタックの大きさとして0を渡します。
+    pub(crate) fn build_runtime_init(&mut self) -> YuniResult<()> {
+        let runtime_init = self.runtime_manager.get_function("yuni_runtime_init")
+            .ok_or_else(|| YuniError::Codegen(CodegenError::Internal {
+                message: "yuni_runtime_init not found".to_string(),
+            }))?;
+        let stack_size = self.context.i64_type().const_int(self.options.stack_size.unwrap_or(0), false);
+        self.builder.build_call(runtime_init, &[stack_size.into()], "")?;
+        Ok(())
+    }
+}
@@ -0,0 +1,30 @@
+//! コード生成の統計
+//!
+//! 関数ごとの作業用の状態（変数のスコープ・deferのスタック・定数の名前の連番）は、
+//! 関数のコンパイルを終えるたびに解放します。ここで数える値で、その状態がプログラムの
+//! 規模に比例して残っていないことを確認できます。
+
+use std::fmt;
+
+/// コード生成で保持したオブジェクトの数
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CodegenStats {
+    /// 本体をコンパイルした関数とメソッドの数
+    pub functions: usize,
+    /// 1つの関数のコンパイル中に同時に定義されていた変数の最大数
+    pub peak_variables: usize,
+    /// 1つの関数のコンパイル中のスコープの最大の深さ（グローバルスコープを含む）
+    pub peak_scopes: usize,
+    /// 最後の関数を終えた後に残っている作業用のオブジェクトの数
+    pub retained: usize,
+}
+
+impl fmt::Display for CodegenStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} functions, peak {} variables in {} scopes, {} retained",
+            self.functions, self.peak_variables, self.peak_scopes, self.retained
+        )
+    }
+}
@@ -2,15 +2,22 @@
 
 use crate::ast::*;
 use crate::error::{CodegenError, YuniError, YuniResult};
+use inkwell::basic_block::BasicBlock;
+use inkwell::types::BasicTypeEnum;
 use inkwell::values::BasicValueEnum;
+use std::rc::Rc;
 
 use super::code_generator::CodeGenerator;
+use super::expr::map_index::hashmap_types;
+use super::expr::vec_index::vec_element_type;
+use super::naming;
 use super::symbol_table::Symbol;
 
 impl<'ctx> CodeGenerator<'ctx> {
     /// ブロックをコンパイル
     pub fn compile_block(&mut self, block: &Block) -> YuniResult<()> {
         self.scope_manager.push_scope();
+        self.enter_defer_scope();
 
         for stmt in &block.statements {
             self.compile_statement(stmt)?;
@@ -21,6 +28,8 @@ impl<'ctx> CodeGenerator<'ctx> {
             }
         }
 
+        // ブロックの終端に到達する場合はdeferを実行
+        self.leave_defer_scope()?;
         self.scope_manager.pop_scope();
         Ok(())
     }
@@ -39,6 +48,11 @@ impl<'ctx> CodeGenerator<'ctx> {
             Statement::While(while_stmt) => self.compile_while_statement(while_stmt),
             Statement::For(for_stmt) => self.compile_for_statement(for_stmt),
             Statement::Block(block) => self.compile_block(block),
+            Statement::Defer(defer_stmt) => self.compile_defer_statement(defer_stmt),
+            // `compile_program` がトップレベルの関数に取り出すため、ここには現れない
+            Statement::Function(func) => Err(YuniError::Codegen(CodegenError::Internal {
+                message: format!("Local function `{}` was not hoisted", func.name),
+            })),
         }
     }
 
@@ -57,35 +71,49 @@ impl<'ctx> CodeGenerator<'ctx> {
                     }));
                 };
 
-                let alloca = self.create_entry_block_alloca(name, &ty)?;
+                let alloca = self.create_entry_block_alloca(&naming::address_name(name), &ty)?;
 
                 if let Some(init) = &let_stmt.init {
                     // 期待される型を渡して初期化式をコンパイル
                     let value = self.compile_expression_with_type(init, Some(&ty))?;
+                    // 初期化式がreturnで抜けた場合、以降には到達しない
+                    if self.current_block_has_terminator() {
+                        return Ok(());
+                    }
                     self.builder.build_store(alloca, value)?;
                 }
 
                 self.add_variable(name, alloca, ty, *is_mut)?;
             }
-            Pattern::Tuple(_patterns) => {
-                return Err(YuniError::Codegen(CodegenError::Unimplemented { 
-                    feature: "Tuple patterns not yet implemented".to_string(), 
-                    span: Span::dummy() 
-                }));
+            Pattern::Tuple(_) | Pattern::Struct(..) => {
+                let init = let_stmt.init.as_ref().ok_or_else(|| YuniError::Codegen(CodegenError::Unimplemented {
+                    feature: "Destructuring let statements without initializer".to_string(),
+                    span: let_stmt.span,
+                }))?;
+                let ty = match &let_stmt.ty {
+                    Some(ty) => ty.clone(),
+                    None => self.infer_type(init)?,
+                };
+
+                let value = self.compile_expression_with_type(init, Some(&ty))?;
+                if self.current_block_has_terminator() {
+                    return Ok(());
+                }
+                self.bind_let_pattern(&let_stmt.pattern, value, &ty)?;
             }
-            Pattern::Struct(_name, _fields) => {
+            Pattern::EnumVariant { .. } => {
                 return Err(YuniError::Codegen(CodegenError::Unimplemented { 
-                    feature: "Struct patterns not yet implemented".to_string(), 
+                    feature: "Enum variant patterns not yet implemented".to_string(), 
                     span: Span::dummy() 
                 }));
             }
-            Pattern::EnumVariant { .. } => {
+            Pattern::Binding(..) | Pattern::Or(_) => {
                 return Err(YuniError::Codegen(CodegenError::Unimplemented { 
-                    feature: "Enum variant patterns not yet implemented".to_string(), 
+                    feature: "Binding and or patterns in let statements not yet implemented".to_string(), 
                     span: Span::dummy() 
                 }));
             }
-            Pattern::Literal(_) | Pattern::Wildcard => {
+            Pattern::Literal(_) | Pattern::Range { .. } | Pattern::Wildcard => {
                 return Err(YuniError::Codegen(CodegenError::InvalidType {
                     message: "Literal and wildcard patterns are not allowed in let statements".to_string(),
                     span: Span::dummy()
@@ -96,9 +124,115 @@ impl<'ctx> CodeGenerator<'ctx> {
         Ok(())
     }
 
+    /// let文のパターンの変数に値を分解して束縛する
+    ///
+    /// 変数ごとにエントリブロックにスタック領域を確保します。
+    fn bind_let_pattern(&mut self, pattern: &Pattern, value: BasicValueEnum<'ctx>, ty: &Type) -> YuniResult<()> {
+        match pattern {
+            Pattern::Identifier(name, is_mut) => {
+                let alloca = self.create_entry_block_alloca(&naming::address_name(name), ty)?;
+                self.builder.build_store(alloca, value)?;
+                self.add_variable(name, alloca, ty.clone(), *is_mut)
+            }
+            Pattern::Wildcard => Ok(()),
+            Pattern::Tuple(patterns) => {
+                let Type::Tuple(element_types) = self.destructured_type(ty) else {
+                    return Err(YuniError::Codegen(CodegenError::InvalidType {
+                        message: format!("Cannot destructure {:?} as a tuple", ty),
+                        span: Span::dummy(),
+                    }));
+                };
+                for (i, (pattern, element_type)) in patterns.iter().zip(&element_types).enumerate() {
+                    if matches!(pattern, Pattern::Wildcard) {
+                        continue;
+                    }
+                    let name = component_name(pattern, &format!("elem{}", i));
+                    let element = self.extract_component(value, ty, i as u32, &name)?;
+                    self.bind_let_pattern(pattern, element, element_type)?;
+                }
+                Ok(())
+            }
+            Pattern::Struct(_, field_patterns) => {
+                // 単相化した構造体はパターンではなく型の名前でレイアウトを引く
+                let Type::UserDefined(struct_name) = self.destructured_type(ty) else {
+                    return Err(YuniError::Codegen(CodegenError::InvalidType {
+                        message: format!("Cannot destructure {:?} as a struct", ty),
+                        span: Span::dummy(),
+                    }));
+                };
+                let layout = Rc::clone(self.struct_layouts.get(&struct_name)
+                    .ok_or_else(|| YuniError::Codegen(CodegenError::Undefined {
+                        name: struct_name.clone(),
+                        span: Span::dummy(),
+                    }))?);
+                for (field_name, pattern) in field_patterns {
+                    if matches!(pattern, Pattern::Wildcard) {
+                        continue;
+                    }
+                    let (index, field) = layout.field(field_name)
+                        .ok_or_else(|| YuniError::Codegen(CodegenError::Undefined {
+                            name: format!("{}.{}", struct_name, field_name),
+                            span: Span::dummy(),
+                        }))?;
+                    let field_value = self.extract_component(value, ty, index, &component_name(pattern, field_name))?;
+                    self.bind_let_pattern(pattern, field_value, &field.ty)?;
+                }
+                Ok(())
+            }
+            _ => Err(YuniError::Codegen(CodegenError::Unimplemented {
+                feature: "Refutable patterns in let statements".to_string(),
+                span: Span::dummy(),
+            })),
+        }
+    }
+
+    /// 分解するタプルか構造体の型（エイリアスを解決し、参照を辿る）
+    fn destructured_type(&self, ty: &Type) -> Type {
+        match self.type_manager.resolve_alias(ty) {
+            Type::Reference(referent, _) => self.destructured_type(referent),
+            ty => ty.clone(),
+        }
+    }
+
+    /// タプルか構造体の `index` 番目の要素を取り出す
+    ///
+    /// 値はextractvalueで、参照はGEPで要素のアドレスを求めてから読み出します。
+    fn extract_component(
+        &self,
+        aggregate: BasicValueEnum<'ctx>,
+        ty: &Type,
+        index: u32,
+        name: &str,
+    ) -> YuniResult<BasicValueEnum<'ctx>> {
+        match (aggregate, self.type_manager.resolve_alias(ty)) {
+            (BasicValueEnum::StructValue(value), _) => Ok(self.builder.build_extract_value(value, index, name)?),
+            (BasicValueEnum::PointerValue(ptr), Type::Reference(referent, _)) => {
+                let BasicTypeEnum::StructType(struct_type) = self.type_manager.ast_type_to_llvm(referent)? else {
+                    return Err(YuniError::Codegen(CodegenError::InvalidType {
+                        message: format!("Cannot destructure {:?}", referent),
+                        span: Span::dummy(),
+                    }));
+                };
+                let field_type = struct_type.get_field_type_at_index(index)
+                    .ok_or_else(|| YuniError::Codegen(CodegenError::Internal {
+                        message: format!("Field {} not found in {:?}", index, referent),
+                    }))?;
+                let field_ptr = self.builder.build_struct_gep(struct_type, ptr, index, &format!("{}.ptr", name))?;
+                Ok(self.builder.build_load(field_type, field_ptr, name)?)
+            }
+            _ => Err(YuniError::Codegen(CodegenError::InvalidType {
+                message: format!("Cannot destructure {:?}", ty),
+                span: Span::dummy(),
+            })),
+        }
+    }
+
     /// 代入文をコンパイル
     pub fn compile_assignment(&mut self, assign: &AssignStatement) -> YuniResult<()> {
         let value = self.compile_expression(&assign.value)?;
+        if self.current_block_has_terminator() {
+            return Ok(());
+        }
 
         match &assign.target {
             Expression::Identifier(id) => {
@@ -138,11 +272,9 @@ impl<'ctx> CodeGenerator<'ctx> {
     /// return文をコンパイル
     pub fn compile_return(&mut self, ret: &ReturnStatement) -> YuniResult<()> {
         if let Some(value) = &ret.value {
-            // 現在の関数の戻り値型を期待される型として渡す
-            let expected_type = self.current_return_type.clone();
-            let return_value = self.compile_expression_with_type(value, expected_type.as_ref())?;
-            self.builder.build_return(Some(&return_value))?;
+            self.compile_return_value(value)?;
         } else {
+            self.emit_all_defers()?;
             self.builder.build_return(None)?;
         }
         Ok(())
@@ -152,6 +284,18 @@ impl<'ctx> CodeGenerator<'ctx> {
     pub fn compile_if_statement(&mut self, if_stmt: &IfStatement) -> YuniResult<()> {
         let condition = self.compile_expression(&if_stmt.condition)?;
 
+        // 条件が定数なら選ばれる分岐だけを生成する
+        if let BasicValueEnum::IntValue(int_val) = condition {
+            if let Some(taken) = Self::constant_condition(int_val) {
+                return match (taken, &if_stmt.else_branch) {
+                    (true, _) => self.compile_block(&if_stmt.then_branch),
+                    (false, Some(ElseBranch::Block(block))) => self.compile_block(block),
+                    (false, Some(ElseBranch::If(nested_if))) => self.compile_if_statement(nested_if),
+                    (false, None) => Ok(()),
+                };
+            }
+        }
+
         let function = self.current_function
             .ok_or_else(|| YuniError::Codegen(CodegenError::Internal { 
                 message: "No current function".to_string() 
@@ -208,37 +352,34 @@ impl<'ctx> CodeGenerator<'ctx> {
     }
 
     /// while文をコンパイル
+    ///
+    /// 条件ブロックは反復ごとに再び実行され、条件式はそのたびに評価し直されます。
     pub fn compile_while_statement(&mut self, while_stmt: &WhileStatement) -> YuniResult<()> {
         let function = self.current_function
             .ok_or_else(|| YuniError::Codegen(CodegenError::Internal { 
                 message: "No current function".to_string() 
             }))?;
 
-        let cond_block = self.context.append_basic_block(function, "while.cond");
-        let body_block = self.context.append_basic_block(function, "while.body");
-        let exit_block = self.context.append_basic_block(function, "while.exit");
+        self.loop_depth += 1;
+        let cond_block = self.context.append_basic_block(function, &self.loop_block_name("while", "cond"));
+        let body_block = self.context.append_basic_block(function, &self.loop_block_name("while", "body"));
+        let exit_block = self.context.append_basic_block(function, &self.loop_block_name("while", "exit"));
 
         // 条件ブロックへジャンプ
         self.builder.build_unconditional_branch(cond_block)?;
 
-        // 条件をコンパイル
-        self.builder.position_at_end(cond_block);
-        let condition = self.compile_expression(&while_stmt.condition)?;
-
-        match condition {
-            BasicValueEnum::IntValue(int_val) => {
-                self.builder.build_conditional_branch(int_val, body_block, exit_block)?;
-            }
-            _ => return Err(YuniError::Codegen(CodegenError::TypeError {
-                expected: "bool".to_string(),
-                actual: "non-bool".to_string(),
-                span: while_stmt.span,
-            })),
-        }
-
-        // ボディをコンパイル
-        self.builder.position_at_end(body_block);
-        self.compile_block(&while_stmt.body)?;
+        let result = self.compile_loop_body(
+            cond_block,
+            body_block,
+            exit_block,
+            Some(&while_stmt.condition),
+            &while_stmt.body,
+            while_stmt.span,
+        );
+        self.loop_depth -= 1;
+        result?;
+
+        // ボディの終端から条件ブロックへ戻る
         if !self.current_block_has_terminator() {
             self.builder.build_unconditional_branch(cond_block)?;
         }
@@ -250,10 +391,21 @@ impl<'ctx> CodeGenerator<'ctx> {
     }
 
     /// for文をコンパイル
+    ///
+    /// init文の変数はループ全体のスコープに属し、ループの後では参照できません。
+    /// ボディの終端からは更新ブロックを経由して条件ブロックに戻ります。
     pub fn compile_for_statement(&mut self, for_stmt: &ForStatement) -> YuniResult<()> {
         // ループ変数用の新しいスコープを作成
         self.scope_manager.push_scope();
+        self.loop_depth += 1;
+        let result = self.compile_for_loop(for_stmt);
+        self.loop_depth -= 1;
+        self.scope_manager.pop_scope();
+        result
+    }
 
+    /// for文のスコープ内をコンパイル
+    fn compile_for_loop(&mut self, for_stmt: &ForStatement) -> YuniResult<()> {
         // 初期化をコンパイル
         if let Some(init) = &for_stmt.init {
             self.compile_statement(init)?;
@@ -264,36 +416,22 @@ impl<'ctx> CodeGenerator<'ctx> {
                 message: "No current function".to_string() 
             }))?;
 
-        let cond_block = self.context.append_basic_block(function, "for.cond");
-        let body_block = self.context.append_basic_block(function, "for.body");
-        let update_block = self.context.append_basic_block(function, "for.update");
-        let exit_block = self.context.append_basic_block(function, "for.exit");
+        let cond_block = self.context.append_basic_block(function, &self.loop_block_name("for", "cond"));
+        let body_block = self.context.append_basic_block(function, &self.loop_block_name("for", "body"));
+        let update_block = self.context.append_basic_block(function, &self.loop_block_name("for", "update"));
+        let exit_block = self.context.append_basic_block(function, &self.loop_block_name("for", "exit"));
 
         // 条件ブロックへジャンプ
         self.builder.build_unconditional_branch(cond_block)?;
 
-        // 条件をコンパイル
-        self.builder.position_at_end(cond_block);
-        if let Some(condition) = &for_stmt.condition {
-            let cond_value = self.compile_expression(condition)?;
-            match cond_value {
-                BasicValueEnum::IntValue(int_val) => {
-                    self.builder.build_conditional_branch(int_val, body_block, exit_block)?;
-                }
-                _ => return Err(YuniError::Codegen(CodegenError::TypeError {
-                    expected: "bool".to_string(),
-                    actual: "non-bool".to_string(),
-                    span: for_stmt.span,
-                })),
-            }
-        } else {
-            // 条件がない場合は無限ループ
-            self.builder.build_unconditional_branch(body_block)?;
-        }
-
-        // ボディをコンパイル
-        self.builder.position_at_end(body_block);
-        self.compile_block(&for_stmt.body)?;
+        self.compile_loop_body(
+            cond_block,
+            body_block,
+            exit_block,
+            for_stmt.condition.as_ref(),
+            &for_stmt.body,
+            for_stmt.span,
+        )?;
         if !self.current_block_has_terminator() {
             self.builder.build_unconditional_branch(update_block)?;
         }
@@ -308,12 +446,49 @@ impl<'ctx> CodeGenerator<'ctx> {
         // 終了ブロックで継続
         self.builder.position_at_end(exit_block);
 
-        // ループスコープを終了
-        self.scope_manager.pop_scope();
-
         Ok(())
     }
 
+    /// ループの条件ブロックとボディをコンパイル
+    ///
+    /// 条件がない場合は無限ループになります。終了後のビルダーはボディの終端を指します。
+    fn compile_loop_body(
+        &mut self,
+        cond_block: BasicBlock<'ctx>,
+        body_block: BasicBlock<'ctx>,
+        exit_block: BasicBlock<'ctx>,
+        condition: Option<&Expression>,
+        body: &Block,
+        span: Span,
+    ) -> YuniResult<()> {
+        // 条件をコンパイル
+        self.builder.position_at_end(cond_block);
+        match condition {
+            Some(condition) => match self.compile_expression(condition)? {
+                BasicValueEnum::IntValue(int_val) => {
+                    self.builder.build_conditional_branch(int_val, body_block, exit_block)?;
+                }
+                _ => return Err(YuniError::Codegen(CodegenError::TypeError {
+                    expected: "bool".to_string(),
+                    actual: "non-bool".to_string(),
+                    span,
+                })),
+            },
+            None => {
+                self.builder.build_unconditional_branch(body_block)?;
+            }
+        }
+
+        // ボディをコンパイル（ボディの変数は反復ごとのスコープに属する）
+        self.builder.position_at_end(body_block);
+        self.compile_block(body)
+    }
+
+    /// ループの基本ブロック名（ネストの深さを含めて内側と外側のループを区別する）
+    fn loop_block_name(&self, kind: &str, part: &str) -> String {
+        format!("{}{}.{}", kind, self.loop_depth, part)
+    }
+
     /// 現在のブロックがターミネータを持っているかチェック
     pub fn current_block_has_terminator(&self) -> bool {
         let current_block = self.builder.get_insert_block().unwrap();
@@ -327,7 +502,7 @@ impl<'ctx> CodeGenerator<'ctx> {
 
     /// エントリブロックにallocaを作成
     pub fn create_entry_block_alloca(&self, name: &str, ty: &Type) -> YuniResult<inkwell::values::PointerValue<'ctx>> {
-        let builder = self.context.create_builder();
+        let builder = &self.alloca_builder;
         let function = self.current_function
             .ok_or_else(|| YuniError::Codegen(CodegenError::Internal {
                 message: "No current function".to_string()
@@ -383,28 +558,13 @@ impl<'ctx> CodeGenerator<'ctx> {
             }
         };
         
-        // 構造体情報を取得
-        let struct_info = self.struct_info.get(&struct_name)
-            .ok_or_else(|| YuniError::Codegen(CodegenError::Internal {
-                message: format!("Struct info not found for {}", struct_name),
-            }))?;
-        
-        // フィールドのインデックスを取得
-        let field_index = struct_info.get_field_index(&field_expr.field)
-            .ok_or_else(|| YuniError::Codegen(CodegenError::Undefined {
-                name: format!("{}.{}", struct_name, field_expr.field),
-                span: field_expr.span,
-            }))?;
+        // フィールドの位置を取得
+        let (struct_type, field_index, _) = self.struct_field_slot(&struct_name, &field_expr.field, field_expr.span)?;
         
         // オブジェクトがポインタであることを確認
         match object_value {
             BasicValueEnum::PointerValue(ptr_val) => {
                 // GEPを使ってフィールドのポインタを取得
-                let struct_type = self.type_manager.get_struct(&struct_name)
-                    .ok_or_else(|| YuniError::Codegen(CodegenError::Internal {
-                        message: format!("Struct type not found for {}", struct_name),
-                    }))?;
-                
                 let indices = [
                     self.context.i32_type().const_zero(),
                     self.context.i32_type().const_int(field_index as u64, false),
@@ -415,7 +575,7 @@ impl<'ctx> CodeGenerator<'ctx> {
                         struct_type,
                         ptr_val,
                         &indices,
-                        &format!("{}_ptr", field_expr.field),
+                        &format!("{}.ptr", naming::field_name(&field_expr.object, &field_expr.field)),
                     )?
                 };
                 
@@ -433,18 +593,33 @@ impl<'ctx> CodeGenerator<'ctx> {
     }
 
     /// インデックス代入をコンパイル
-    pub fn compile_index_assignment(&mut self, _index_expr: &IndexExpr, _value: BasicValueEnum<'ctx>) -> YuniResult<()> {
+    pub fn compile_index_assignment(&mut self, index_expr: &IndexExpr, value: BasicValueEnum<'ctx>) -> YuniResult<()> {
+        let object_type = self.expression_type(&index_expr.object)?;
+        if let Some((key_type, value_type)) = hashmap_types(&object_type) {
+            return self.compile_hashmap_index_assignment(index_expr, value, key_type, value_type);
+        }
+        if let Some(element_type) = vec_element_type(&object_type) {
+            return self.compile_vec_index_assignment(index_expr, value, element_type);
+        }
         Err(YuniError::Codegen(CodegenError::Unimplemented {
             feature: "Index assignment not yet implemented".to_string(),
-            span: Span::dummy(),
+            span: index_expr.span,
         }))
     }
 
     /// デリファレンス代入をコンパイル
-    pub fn compile_deref_assignment(&mut self, _deref_expr: &DereferenceExpr, _value: BasicValueEnum<'ctx>) -> YuniResult<()> {
+    pub fn compile_deref_assignment(&mut self, deref_expr: &DereferenceExpr, _value: BasicValueEnum<'ctx>) -> YuniResult<()> {
         Err(YuniError::Codegen(CodegenError::Unimplemented {
             feature: "Dereference assignment not yet implemented".to_string(),
-            span: Span::dummy(),
+            span: deref_expr.span,
         }))
     }
-}
\ No newline at end of file
+}
+
+/// 分解した要素の値の名前（変数に束縛する要素は変数名）
+fn component_name(pattern: &Pattern, fallback: &str) -> String {
+    match pattern {
+        Pattern::Identifier(name, _) => name.clone(),
+        _ => fallback.to_string(),
+    }
+}
@@ -47,50 +47,13 @@ impl<'ctx> Scope<'ctx> {
     }
 }
 
-/// 構造体のフィールド情報
-#[derive(Debug, Clone)]
-pub struct StructInfo {
-    /// フィールド名からインデックスへのマッピング
-    pub field_indices: HashMap<String, u32>,
-    /// フィールドの型情報（AST型を保持）
-    pub field_types: Vec<Type>,
-}
-
-impl Default for StructInfo {
-    fn default() -> Self {
-        Self::new()
-    }
-}
-
-impl StructInfo {
-    pub fn new() -> Self {
-        Self {
-            field_indices: HashMap::new(),
-            field_types: Vec::new(),
-        }
-    }
-    
-    /// フィールドを追加
-    pub fn add_field(&mut self, name: String, ty: Type) {
-        let index = self.field_types.len() as u32;
-        self.field_indices.insert(name, index);
-        self.field_types.push(ty);
-    }
-    
-    /// フィールドのインデックスを取得
-    pub fn get_field_index(&self, name: &str) -> Option<u32> {
-        self.field_indices.get(name).copied()
-    }
-    
-    /// フィールドの型を取得
-    pub fn get_field_type(&self, index: usize) -> Option<&Type> {
-        self.field_types.get(index)
-    }
-}
-
 /// スコープマネージャー
 pub struct ScopeManager<'ctx> {
     scopes: Vec<Scope<'ctx>>,
+    /// `reset` 以降のスコープの最大の深さ
+    peak_depth: usize,
+    /// `reset` 以降に同時に定義されていた変数の最大数
+    peak_symbols: usize,
 }
 
 impl<'ctx> Default for ScopeManager<'ctx> {
@@ -103,12 +66,15 @@ impl<'ctx> ScopeManager<'ctx> {
     pub fn new() -> Self {
         Self {
             scopes: vec![Scope::new()], // グローバルスコープ
+            peak_depth: 1,
+            peak_symbols: 0,
         }
     }
     
     /// 新しいスコープを開始
     pub fn push_scope(&mut self) {
         self.scopes.push(Scope::new());
+        self.peak_depth = self.peak_depth.max(self.scopes.len());
     }
     
     /// 現在のスコープを終了
@@ -125,6 +91,7 @@ impl<'ctx> ScopeManager<'ctx> {
         if let Some(scope) = self.scopes.last_mut() {
             scope.define(name, symbol);
         }
+        self.peak_symbols = self.peak_symbols.max(self.symbol_count());
     }
     
     /// 変数を定義（簡易版）
@@ -152,4 +119,45 @@ impl<'ctx> ScopeManager<'ctx> {
     pub fn lookup_in_current_scope(&self, name: &str) -> Option<&Symbol<'ctx>> {
         self.scopes.last()?.lookup(name)
     }
+
+    /// 現在のスコープの深さを取得
+    pub fn depth(&self) -> usize {
+        self.scopes.len()
+    }
+
+    /// すべてのスコープに定義されている変数の数
+    pub fn symbol_count(&self) -> usize {
+        self.scopes.iter().map(|scope| scope.symbols.len()).sum()
+    }
+
+    /// `reset` 以降のスコープの最大の深さ
+    pub fn peak_depth(&self) -> usize {
+        self.peak_depth
+    }
+
+    /// `reset` 以降に同時に定義されていた変数の最大数
+    pub fn peak_symbols(&self) -> usize {
+        self.peak_symbols
+    }
+
+    /// グローバルスコープより内側のスコープを破棄し、最大値の記録をやり直す
+    ///
+    /// エラーで関数のコンパイルを途中で抜けた場合も、次の関数に変数を持ち越さないようにします。
+    pub fn reset(&mut self) {
+        self.scopes.truncate(1);
+        self.peak_depth = self.scopes.len();
+        self.peak_symbols = self.symbol_count();
+    }
+
+    /// 指定した深さより内側のスコープを一時的に取り外す
+    ///
+    /// 取り外したスコープは `restore_scopes` で元に戻すこと。
+    pub fn detach_scopes_above(&mut self, depth: usize) -> Vec<Scope<'ctx>> {
+        self.scopes.split_off(depth.min(self.scopes.len()))
+    }
+
+    /// `detach_scopes_above` で取り外したスコープを元に戻す
+    pub fn restore_scopes(&mut self, scopes: Vec<Scope<'ctx>>) {
+        self.scopes.extend(scopes);
+    }
 }
\ No newline at end of file
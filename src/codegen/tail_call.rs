@@ -1,165 +1,153 @@
-//! 末尾呼び出し最適化のためのコード生成時解析
+//! 末尾呼び出し最適化のためのコード生成
 //!
-//! コード生成時に末尾位置を判定し、最適化を適用する
+//! 末尾位置の判定はセマンティック解析後のパス（`analyzer::tail_position`）が行い、
+//! `CallExpr::is_tail`・`MethodCallExpr::is_tail` に記録します。
+//! ここでは印の付いた呼び出しを末尾呼び出しとして出力できるかを判定し、
+//! 呼び出しの直後がretになるようreturnを分岐の中へ移します。
 
 use crate::ast::*;
-use std::collections::HashSet;
-
-/// 末尾位置コンテキスト
-#[derive(Default)]
-pub struct TailContext {
-    /// 現在の関数名
-    pub current_function: Option<String>,
-    /// 末尾位置にある呼び出しのセット（式のアドレスで識別）
-    pub tail_calls: HashSet<usize>,
-}
+use crate::error::{CodegenError, YuniError, YuniResult};
+use inkwell::values::BasicValueEnum;
+use inkwell::IntPredicate;
+
+use super::code_generator::CodeGenerator;
 
-impl TailContext {
-    /// 新しい末尾位置コンテキストを作成
-    pub fn new() -> Self {
-        Self::default()
+impl<'ctx> CodeGenerator<'ctx> {
+    /// 印の付いた呼び出しを末尾呼び出しとして出力できるか
+    ///
+    /// LLVMの`tail`は呼び出し先が呼び出し元のスタック領域に触れないことを表すため、
+    /// スタック上の値を指しうるポインタを渡す呼び出しには付けません。
+    /// また、呼び出しの後にdeferを展開する場合は末尾呼び出しになりません。
+    pub fn can_emit_tail_call(&mut self, args: &[(&Expression, BasicValueEnum<'ctx>)]) -> bool {
+        let has_pending_defers = self.defer_scopes.iter().any(|scope| !scope.is_empty());
+        !has_pending_defers && args.iter().all(|(arg, value)| self.is_frame_independent(arg, *value))
     }
 
-    /// 関数の末尾位置を解析
-    pub fn analyze_function(&mut self, func: &FunctionDecl) {
-        self.current_function = Some(func.name.clone());
-        self.tail_calls.clear();
-        
-        // 関数本体の最後の文を解析
-        if let Some(last_stmt) = func.body.statements.last() {
-            self.analyze_statement_tail_position(last_stmt, true);
+    /// 値が呼び出し元のスタック領域を指さないか
+    ///
+    /// 文字列・Vec・HashMap・StringBuilderのポインタはヒープ上の領域を指します。
+    fn is_frame_independent(&mut self, arg: &Expression, value: BasicValueEnum<'ctx>) -> bool {
+        if !value.is_pointer_value() {
+            return true;
+        }
+        match self.expression_type(arg) {
+            Ok(Type::String | Type::Str) => true,
+            Ok(Type::Generic(name, _)) => matches!(name.as_str(), "Vec" | "HashMap"),
+            Ok(Type::UserDefined(name)) => name == "StringBuilder",
+            _ => false,
         }
     }
 
-    /// 文の末尾位置を解析
-    fn analyze_statement_tail_position(&mut self, stmt: &Statement, is_tail: bool) {
-        match stmt {
-            Statement::Return(ret) => {
-                // return文の値は常に末尾位置
-                if let Some(expr) = &ret.value {
-                    self.analyze_expression_tail_position(expr, true);
+    /// returnの値をコンパイルして関数から戻る
+    ///
+    /// 値が末尾呼び出しを含むif式・ブロック式・match式の場合は、分岐を合流させてから
+    /// 戻るのではなく各分岐で戻ります。末尾呼び出しの直後がretになるため、
+    /// 最適化なしでもLLVMが呼び出し元のスタックフレームを再利用できます。
+    pub fn compile_return_value(&mut self, value: &Expression) -> YuniResult<()> {
+        if contains_tail_call(value) {
+            match value {
+                Expression::If(if_expr) if if_expr.else_branch.is_some() => {
+                    return self.compile_if_return(if_expr);
                 }
-            }
-            Statement::Expression(expr) => {
-                // 式文が関数の最後の文の場合、末尾位置
-                self.analyze_expression_tail_position(expr, is_tail);
-            }
-            Statement::If(if_stmt) => {
-                // 条件式は末尾位置ではない
-                self.analyze_expression_tail_position(&if_stmt.condition, false);
-                
-                // if文の各ブランチの最後が末尾位置
-                if let Some(last_then) = if_stmt.then_branch.statements.last() {
-                    self.analyze_statement_tail_position(last_then, is_tail);
+                Expression::Block(block_expr) if block_expr.last_expr.is_some() => {
+                    return self.compile_block_return(block_expr);
                 }
-                
-                if let Some(else_branch) = &if_stmt.else_branch {
-                    match else_branch {
-                        ElseBranch::Block(block) => {
-                            if let Some(last_else) = block.statements.last() {
-                                self.analyze_statement_tail_position(last_else, is_tail);
-                            }
-                        }
-                        ElseBranch::If(nested_if) => {
-                            self.analyze_statement_tail_position(
-                                &Statement::If((**nested_if).clone()),
-                                is_tail
-                            );
-                        }
-                    }
+                Expression::Match(match_expr) => {
+                    return self.compile_match_return(match_expr);
                 }
+                _ => {}
             }
-            Statement::Block(block) => {
-                // ブロックの最後の文が末尾位置
-                if let Some(last) = block.statements.last() {
-                    self.analyze_statement_tail_position(last, is_tail);
-                }
-            }
-            _ => {
-                // その他の文（変数宣言など）は末尾位置を含まない
-            }
         }
+
+        // 現在の関数の戻り値型を期待される型として渡す
+        let expected_type = self.current_return_type.clone();
+        let return_value = self.compile_expression_with_type(value, expected_type.as_ref())?;
+        // 値の式がすべての分岐でreturnした場合は、ここで戻る必要はない
+        if self.current_block_has_terminator() {
+            return Ok(());
+        }
+        // 戻り値を評価してからdeferを実行する
+        self.emit_all_defers()?;
+        self.builder.build_return(Some(&return_value))?;
+        Ok(())
     }
 
-    /// 式の末尾位置を解析
-    fn analyze_expression_tail_position(&mut self, expr: &Expression, is_tail: bool) {
-        match expr {
-            Expression::Call(call) => {
-                // 自己再帰呼び出しかチェック
-                if is_tail && self.is_self_recursive_call(call) {
-                    // 呼び出しのアドレスを記録
-                    self.tail_calls.insert(call as *const _ as usize);
-                }
-                
-                // 引数は末尾位置ではない
-                for arg in &call.args {
-                    self.analyze_expression_tail_position(arg, false);
-                }
-            }
-            Expression::Block(block_expr) => {
-                // ブロック式の最後の式が末尾位置
-                for stmt in &block_expr.statements {
-                    match stmt {
-                        Statement::Expression(expr) => {
-                            self.analyze_expression_tail_position(expr, false);
-                        }
-                        _ => self.analyze_statement_tail_position(stmt, false),
-                    }
-                }
-                
-                if let Some(last_expr) = &block_expr.last_expr {
-                    self.analyze_expression_tail_position(last_expr, is_tail);
-                }
-            }
-            Expression::If(if_expr) => {
-                // 条件式は末尾位置ではない
-                self.analyze_expression_tail_position(&if_expr.condition, false);
-                
-                // then/else式は末尾位置を継承
-                self.analyze_expression_tail_position(&if_expr.then_branch, is_tail);
-                if let Some(else_branch) = &if_expr.else_branch {
-                    self.analyze_expression_tail_position(else_branch, is_tail);
-                }
-            }
-            Expression::Match(match_expr) => {
-                // マッチ対象式は末尾位置ではない
-                self.analyze_expression_tail_position(&match_expr.expr, false);
-                
-                // 各アームの本体は末尾位置を継承
-                for arm in &match_expr.arms {
-                    if let Some(guard) = &arm.guard {
-                        self.analyze_expression_tail_position(guard, false);
-                    }
-                    self.analyze_expression_tail_position(&arm.expr, is_tail);
-                }
-            }
-            Expression::Binary(binary) => {
-                // 二項演算の両側は末尾位置ではない
-                self.analyze_expression_tail_position(&binary.left, false);
-                self.analyze_expression_tail_position(&binary.right, false);
-            }
-            Expression::Unary(unary) => {
-                // 単項演算のオペランドは末尾位置ではない
-                self.analyze_expression_tail_position(&unary.expr, false);
-            }
-            _ => {
-                // その他の式は末尾呼び出しを含まない
+    /// if式の各分岐で戻る
+    fn compile_if_return(&mut self, if_expr: &IfExpr) -> YuniResult<()> {
+        let condition = match self.compile_expression(&if_expr.condition)? {
+            BasicValueEnum::IntValue(int_val) if int_val.get_type().get_bit_width() == 1 => int_val,
+            BasicValueEnum::IntValue(int_val) => {
+                let zero = int_val.get_type().const_zero();
+                self.builder.build_int_compare(IntPredicate::NE, int_val, zero, "condition")?
             }
+            _ => return Err(YuniError::Codegen(CodegenError::TypeError {
+                expected: "bool".to_string(),
+                actual: "non-bool".to_string(),
+                span: if_expr.span,
+            })),
+        };
+
+        // 条件が定数なら選ばれる分岐だけを生成する
+        if let Some(taken) = Self::constant_condition(condition) {
+            return match (taken, &if_expr.else_branch) {
+                (true, _) => self.compile_return_value(&if_expr.then_branch),
+                (false, Some(else_branch)) => self.compile_return_value(else_branch),
+                (false, None) => Ok(()),
+            };
+        }
+
+        let function = self.builder.get_insert_block().unwrap().get_parent().unwrap();
+        let then_bb = self.context.append_basic_block(function, "then_return");
+        let else_bb = self.context.append_basic_block(function, "else_return");
+        self.builder.build_conditional_branch(condition, then_bb, else_bb)?;
+
+        self.builder.position_at_end(then_bb);
+        self.compile_return_value(&if_expr.then_branch)?;
+
+        self.builder.position_at_end(else_bb);
+        if let Some(else_branch) = &if_expr.else_branch {
+            self.compile_return_value(else_branch)?;
         }
+        Ok(())
     }
 
-    /// 呼び出しが自己再帰呼び出しかチェック
-    fn is_self_recursive_call(&self, call: &CallExpr) -> bool {
-        if let Some(current_func) = &self.current_function {
-            if let Expression::Identifier(id) = &*call.callee {
-                return id.name == *current_func;
+    /// ブロック式の最後の式で戻る
+    fn compile_block_return(&mut self, block_expr: &BlockExpr) -> YuniResult<()> {
+        self.scope_manager.push_scope();
+        self.enter_defer_scope();
+
+        for stmt in &block_expr.statements {
+            self.compile_statement(stmt)?;
+            if self.current_block_has_terminator() {
+                break;
+            }
+        }
+
+        // 文の途中で戻った場合、残りの式は到達しない
+        if !self.current_block_has_terminator() {
+            if let Some(last_expr) = &block_expr.last_expr {
+                self.compile_return_value(last_expr)?;
             }
         }
-        false
+
+        // 戻る経路のdeferはcompile_return_valueで展開済み
+        self.leave_defer_scope()?;
+        self.scope_manager.pop_scope();
+        Ok(())
     }
+}
 
-    /// 呼び出しが末尾位置にあるかチェック
-    pub fn is_tail_call(&self, call: &CallExpr) -> bool {
-        self.tail_calls.contains(&(call as *const _ as usize))
+/// 式の末尾位置（if・matchの分岐、ブロックの最後の式）に印の付いた呼び出しがあるか
+fn contains_tail_call(expr: &Expression) -> bool {
+    match expr {
+        Expression::Call(call) => call.is_tail,
+        Expression::MethodCall(method_call) => method_call.is_tail,
+        Expression::If(if_expr) => {
+            contains_tail_call(&if_expr.then_branch)
+                || if_expr.else_branch.as_deref().is_some_and(contains_tail_call)
+        }
+        Expression::Block(block_expr) => block_expr.last_expr.as_deref().is_some_and(contains_tail_call),
+        Expression::Match(match_expr) => match_expr.arms.iter().any(|arm| contains_tail_call(&arm.expr)),
+        _ => false,
     }
-}
\ No newline at end of file
+}
@@ -6,6 +6,7 @@ use inkwell::context::Context;
 use inkwell::types::{BasicMetadataTypeEnum, BasicType, BasicTypeEnum, FunctionType, StructType};
 use inkwell::values::BasicValueEnum;
 use inkwell::AddressSpace;
+use std::cell::RefCell;
 use std::collections::HashMap;
 
 /// 型変換マネージャー
@@ -13,10 +14,14 @@ pub struct TypeManager<'ctx> {
     context: &'ctx Context,
     /// 名前付き型のキャッシュ
     types: HashMap<String, StructType<'ctx>>,
-    /// Enum型のキャッシュ（Enumはi32として表現）
+    /// Enum型のキャッシュ（フィールドを持たないEnumはi32、持つEnumはタグ付きの構造体として表現）
     enum_types: HashMap<String, BasicTypeEnum<'ctx>>,
     /// 型エイリアス（型名 -> 基底型）
     type_aliases: HashMap<String, Type>,
+    /// 変換済みの型のキャッシュ（型エイリアスを解決した型 -> LLVM型）
+    ///
+    /// 変換は `&self` で行うため内部可変性で保持し、型を登録するたびに破棄します。
+    converted: RefCell<HashMap<Type, BasicTypeEnum<'ctx>>>,
 }
 
 impl<'ctx> TypeManager<'ctx> {
@@ -26,12 +31,24 @@ impl<'ctx> TypeManager<'ctx> {
             types: HashMap::new(),
             enum_types: HashMap::new(),
             type_aliases: HashMap::new(),
+            converted: RefCell::new(HashMap::new()),
         }
     }
     
+    /// 名前付きの構造体型を本体なしで宣言
+    ///
+    /// 参照を介して自身や互いを含む型のフィールドを変換できるよう、先にすべての型の名前を用意します。
+    /// 本体はフィールドの型を変換した後に `StructType::set_body` で設定します。
+    pub fn declare_struct(&mut self, name: &str) -> StructType<'ctx> {
+        let struct_type = self.context.opaque_struct_type(name);
+        self.register_struct(name.to_string(), struct_type);
+        struct_type
+    }
+    
     /// 構造体型を登録
     pub fn register_struct(&mut self, name: String, struct_type: StructType<'ctx>) {
         self.types.insert(name, struct_type);
+        self.converted.get_mut().clear();
     }
     
     /// 構造体型を取得
@@ -44,18 +61,55 @@ impl<'ctx> TypeManager<'ctx> {
         self.types.contains_key(name)
     }
     
-    /// Enum型を登録（i32として表現）
-    pub fn register_enum(&mut self, name: String, enum_type: inkwell::types::IntType<'ctx>) {
-        self.enum_types.insert(name, enum_type.into());
+    /// フィールドを持つEnum型を本体なしの名前付きの構造体型として宣言
+    pub fn declare_enum(&mut self, name: &str) -> StructType<'ctx> {
+        let enum_type = self.context.opaque_struct_type(name);
+        self.register_enum(name.to_string(), enum_type.into());
+        enum_type
+    }
+    
+    /// Enum型を取得
+    pub fn get_enum(&self, name: &str) -> Option<BasicTypeEnum<'ctx>> {
+        self.enum_types.get(name).copied()
+    }
+    
+    /// Enum型を登録
+    pub fn register_enum(&mut self, name: String, enum_type: BasicTypeEnum<'ctx>) {
+        self.enum_types.insert(name, enum_type);
+        self.converted.get_mut().clear();
     }
     
     /// 型エイリアスを登録
     pub fn register_type_alias(&mut self, name: String, underlying_type: Type) {
         self.type_aliases.insert(name, underlying_type);
+        self.converted.get_mut().clear();
+    }
+    
+    /// 型エイリアスを解決（エイリアスでなければそのまま返す）
+    pub fn resolve_alias<'a>(&'a self, ty: &'a Type) -> &'a Type {
+        match ty {
+            Type::UserDefined(name) => self.type_aliases.get(name).map_or(ty, |underlying| self.resolve_alias(underlying)),
+            _ => ty,
+        }
     }
     
     /// AST型からLLVM型への変換
+    ///
+    /// 型エイリアスを解決した型ごとに結果をキャッシュし、同じ型を何度も変換しません。
+    /// 構造体・列挙型は名前付きの型として登録済みのものを返すため、参照を介した再帰型でも変換が終わります。
     pub fn ast_type_to_llvm(&self, ty: &Type) -> YuniResult<BasicTypeEnum<'ctx>> {
+        let ty = self.resolve_alias(ty);
+        let cached = self.converted.borrow().get(ty).copied();
+        if let Some(llvm_type) = cached {
+            return Ok(llvm_type);
+        }
+        let llvm_type = self.convert_type(ty)?;
+        self.converted.borrow_mut().insert(ty.clone(), llvm_type);
+        Ok(llvm_type)
+    }
+    
+    /// 型エイリアスを解決したAST型をLLVM型に変換
+    fn convert_type(&self, ty: &Type) -> YuniResult<BasicTypeEnum<'ctx>> {
         match ty {
             Type::I8 => Ok(self.context.i8_type().into()),
             Type::I16 => Ok(self.context.i16_type().into()),
@@ -84,11 +138,6 @@ impl<'ctx> TypeManager<'ctx> {
                 Ok(self.context.struct_type(&field_types, false).into())
             }
             Type::UserDefined(name) => {
-                // まず型エイリアスを解決
-                if let Some(underlying_type) = self.type_aliases.get(name) {
-                    return self.ast_type_to_llvm(underlying_type);
-                }
-                
                 // 構造体型を探す
                 if let Some(struct_type) = self.types.get(name).copied() {
                     Ok(struct_type.into())
@@ -97,6 +146,10 @@ impl<'ctx> TypeManager<'ctx> {
                 else if let Some(enum_type) = self.enum_types.get(name).copied() {
                     Ok(enum_type)
                 }
+                // 組み込みのStringBuilderはランタイムのビルダーへのポインタ
+                else if name == "StringBuilder" {
+                    Ok(self.context.ptr_type(AddressSpace::default()).into())
+                }
                 else {
                     Err(YuniError::Codegen(CodegenError::Undefined {
                         name: name.clone(),
@@ -158,6 +211,9 @@ impl<'ctx> TypeManager<'ctx> {
                     span: crate::ast::Span::dummy(),
                 }))
             }
+            Type::Error => Err(YuniError::Codegen(CodegenError::Internal {
+                message: "Error type reached code generation".to_string(),
+            })),
         }
     }
     
@@ -246,6 +302,7 @@ impl<'ctx> TypeManager<'ctx> {
                 match llvm_type {
                     BasicTypeEnum::StructType(st) => Ok(st.const_zero().into()),
                     BasicTypeEnum::IntType(it) => Ok(it.const_zero().into()), // Enum型の場合
+                    BasicTypeEnum::PointerType(pt) => Ok(pt.const_null().into()), // StringBuilderの場合
                     _ => Err(YuniError::Codegen(CodegenError::Internal {
                         message: format!("Cannot create default value for type {:?}", ty),
                     })),
@@ -0,0 +1,121 @@
+//! LLVM IRとオブジェクトファイルの生成
+//!
+//! CLIの `build` と同じパイプライン（単相化とコード規模の警告を含む）で、ホスト向けの
+//! 位置独立コードを生成します。LLVMのコンテキストは関数の中で作って破棄するため、
+//! 結果はIRのテキストやオブジェクトファイルのバイト列として所有した値で返します。
+
+use inkwell::context::Context;
+
+use crate::codegen::CodeGenerator;
+use crate::compiler::{CompilationPipeline, CompilationState};
+
+use super::Diagnostics;
+
+/// 生成したLLVM IRモジュール（テキスト形式）
+#[derive(Debug, Clone)]
+pub struct IrModule {
+    ir: String,
+    diagnostics: Diagnostics,
+}
+
+impl IrModule {
+    /// LLVM IRのテキスト
+    pub fn as_str(&self) -> &str {
+        &self.ir
+    }
+
+    /// コンパイル中の警告（エラーは含まない）
+    pub fn diagnostics(&self) -> &Diagnostics {
+        &self.diagnostics
+    }
+}
+
+impl std::fmt::Display for IrModule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.ir)
+    }
+}
+
+/// 生成したオブジェクトファイル
+#[derive(Debug, Clone)]
+pub struct ObjectFile {
+    bytes: Vec<u8>,
+    diagnostics: Diagnostics,
+}
+
+impl ObjectFile {
+    /// オブジェクトファイルの内容
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// オブジェクトファイルの内容を取り出す
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.bytes
+    }
+
+    /// コンパイル中の警告（エラーは含まない）
+    pub fn diagnostics(&self) -> &Diagnostics {
+        &self.diagnostics
+    }
+}
+
+/// ソースコードをコンパイルし、LLVM IRのテキストを返す
+///
+/// `name` は診断とモジュールに記録するファイル名で、ファイルを読むことはありません。
+pub fn build_ir(name: &str, src: &str) -> Result<IrModule, Diagnostics> {
+    let context = Context::create();
+    compile_with(name, src, &context, |codegen| Ok(codegen.get_module().print_to_string().to_string()))
+        .map(|(ir, diagnostics)| IrModule { ir, diagnostics })
+}
+
+/// ソースコードをコンパイルし、ホスト向けのオブジェクトファイルの内容を返す
+///
+/// `name` は診断とモジュールに記録するファイル名で、ファイルを読み書きすることはありません。
+pub fn build_object(name: &str, src: &str) -> Result<ObjectFile, Diagnostics> {
+    let context = Context::create();
+    compile_with(name, src, &context, |codegen| codegen.object_code())
+        .map(|(bytes, diagnostics)| ObjectFile { bytes, diagnostics })
+}
+
+/// パイプラインでコード生成まで行い、生成したモジュールから結果を取り出す
+fn compile_with<'ctx, T>(
+    name: &str,
+    src: &str,
+    context: &'ctx Context,
+    output: impl FnOnce(&CodeGenerator<'ctx>) -> crate::error::YuniResult<T>,
+) -> Result<(T, Diagnostics), Diagnostics> {
+    let state = CompilationState::new_from_string(name, src.to_string())
+        .expect("creating a compilation state from a string does not fail");
+    let mut pipeline = CompilationPipeline::new(state, context);
+
+    let tokens = pipeline.tokenize();
+    let mut ast = pipeline.parse(tokens);
+    if let Some(ast) = &mut ast {
+        pipeline.resolve_names(ast);
+        pipeline.analyze(ast);
+    }
+    let ast = match ast {
+        Some(ast) if !pipeline.state().has_errors() => pipeline.monomorphize(ast),
+        _ => None,
+    };
+    let result = ast
+        .filter(|_| !pipeline.state().has_errors())
+        .map(|ast| pipeline.codegen(&ast).and_then(|codegen| output(&codegen)));
+
+    let mut state = pipeline.into_state();
+    let value = match result {
+        Some(Ok(value)) => Some(value),
+        Some(Err(e)) => {
+            state.add_error(e);
+            None
+        }
+        None => None,
+    };
+    let CompilationState { files, error_collector, .. } = state;
+    let diagnostics = Diagnostics::new(files, error_collector);
+    match value {
+        Some(value) => Ok((value, diagnostics)),
+        None => Err(diagnostics),
+    }
+}
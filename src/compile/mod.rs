@@ -0,0 +1,173 @@
+//! 安定した公開API（コンパイルの入口）
+//!
+//! ライブラリとしてYuniのソースコードを扱う場合の入口です。このモジュールの関数と型は
+//! 互換性を保つ対象で、引数・戻り値を変える場合は破壊的変更として扱います。
+//! それ以外のモジュール（`analyzer`・`codegen`・`compiler` など）はコンパイラの実装で、
+//! 予告なく変わることがあります。
+//!
+//! - [`check_source`] — 字句解析・構文解析・名前解決・セマンティック解析を行い、型付きのASTを返す
+//! - [`build_ir`] — LLVM IRのテキストを生成する（`full` フィーチャー）
+//! - [`build_object`] — ホスト向けのオブジェクトファイルを生成する（`full` フィーチャー）
+//!
+//! どの関数もソースコードの文字列を受け取り、ファイルを読み書きしません。エラーがあれば
+//! [`Diagnostics`] を返し、成功した場合も警告は結果の `diagnostics()` から取得できます。
+//!
+//! ```
+//! use yunilang::compile::{self, Diagnostics, TypedProgram};
+//!
+//! let source = "package main\n\nfn main() {\n    println(\"hello\");\n}\n";
+//! let checked: TypedProgram = compile::check_source("hello.yuni", source).unwrap();
+//! assert!(!checked.diagnostics().has_errors());
+//! assert_eq!(checked.program().items.len(), 1);
+//!
+//! let error: Diagnostics = compile::check_source("bad.yuni", "package main\n\nfn main() {\n    let x: i32 = \"s\";\n}\n")
+//!     .unwrap_err();
+//! assert!(error.has_errors());
+//! assert!(error.render().contains("bad.yuni"));
+//!
+//! # #[cfg(feature = "full")]
+//! # {
+//! use yunilang::compile::{IrModule, ObjectFile};
+//!
+//! let ir: IrModule = compile::build_ir("hello.yuni", source).unwrap();
+//! assert!(ir.as_str().contains("define"));
+//! let object: ObjectFile = compile::build_object("hello.yuni", source).unwrap();
+//! assert!(!object.as_bytes().is_empty());
+//! # }
+//! ```
+//!
+//! エラーの列挙型（[`YuniError`] など）は `#[non_exhaustive]` で、診断の種類を追加しても
+//! 破壊的変更になりません。ASTの列挙型（[`Expression`](crate::ast::Expression) など）は
+//! 言語の構文をそのまま表すため網羅的に照合できるようにしており、構文の追加は破壊的変更です。
+
+#[cfg(feature = "full")]
+mod llvm;
+
+use std::fmt;
+
+use codespan_reporting::diagnostic::Severity;
+use codespan_reporting::files::SimpleFiles;
+
+use crate::analyzer::TypeTable;
+use crate::ast::Program;
+use crate::error::{ErrorCollector, YuniError};
+use crate::frontend::{analyze_program, json_diagnostics, parse_source, JsonDiagnostic};
+
+#[cfg(feature = "full")]
+pub use llvm::{build_ir, build_object, IrModule, ObjectFile};
+
+/// 1つのソースファイルについての診断（エラーと警告）
+#[derive(Debug, Clone)]
+pub struct Diagnostics {
+    files: SimpleFiles<String, String>,
+    collector: ErrorCollector,
+}
+
+impl Diagnostics {
+    /// ソースファイルと蓄積した診断から作成
+    pub(crate) fn new(files: SimpleFiles<String, String>, collector: ErrorCollector) -> Self {
+        Self { files, collector }
+    }
+
+    /// エラーがあるか
+    pub fn has_errors(&self) -> bool {
+        self.collector.has_errors()
+    }
+
+    /// 診断がないか
+    pub fn is_empty(&self) -> bool {
+        !self.collector.has_errors() && !self.collector.has_warnings()
+    }
+
+    /// エラーの一覧（見つかった順）
+    pub fn errors(&self) -> impl Iterator<Item = &YuniError> {
+        self.collector.errors().iter().map(|error| &error.error)
+    }
+
+    /// 警告の一覧（見つかった順）
+    pub fn warnings(&self) -> impl Iterator<Item = &YuniError> {
+        self.collector.warnings().iter().map(|warning| &warning.error)
+    }
+
+    /// JSONに変換できる形式の診断の一覧（エラーが先）
+    pub fn to_json(&self) -> Vec<JsonDiagnostic> {
+        let source = self.files.get(0).map(|file| file.source().as_str()).unwrap_or_default();
+        json_diagnostics(&self.collector, &self.files, source)
+    }
+
+    /// 端末と同じ形式（色なし）で診断を文字列にする
+    pub fn render(&self) -> String {
+        let config = codespan_reporting::term::Config::default();
+        let render = |error: &crate::error::DiagnosticError, severity: Severity| {
+            let mut diagnostic = error.to_diagnostic_in(&self.files);
+            diagnostic.severity = severity;
+            codespan_reporting::term::emit_into_string(&config, &self.files, &diagnostic).unwrap_or_default()
+        };
+        self.collector
+            .errors()
+            .iter()
+            .map(|error| render(error, Severity::Error))
+            .chain(self.collector.warnings().iter().map(|warning| render(warning, Severity::Warning)))
+            .collect()
+    }
+}
+
+impl fmt::Display for Diagnostics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.render())
+    }
+}
+
+impl std::error::Error for Diagnostics {}
+
+/// 名前解決とセマンティック解析を終えたプログラム
+///
+/// 名前は正規の名前（引数の数でオーバーロードした関数は `add/2` など）に書き換わっています。
+#[derive(Debug, Clone)]
+pub struct TypedProgram {
+    program: Program,
+    types: TypeTable,
+    diagnostics: Diagnostics,
+}
+
+impl TypedProgram {
+    /// 名前解決したAST
+    pub fn program(&self) -> &Program {
+        &self.program
+    }
+
+    /// セマンティック解析で決定した式の型
+    pub fn types(&self) -> &TypeTable {
+        &self.types
+    }
+
+    /// 解析中の警告（エラーは含まない）
+    pub fn diagnostics(&self) -> &Diagnostics {
+        &self.diagnostics
+    }
+
+    /// ASTと式の型に分解する
+    pub fn into_parts(self) -> (Program, TypeTable) {
+        (self.program, self.types)
+    }
+}
+
+/// ソースコードを解析し、型付きのASTを返す
+///
+/// `name` は診断に表示するファイル名で、ファイルを読むことはありません。package宣言がなく
+/// 文から始まる入力はスクリプトとして解析します。エラーが1つでもあれば診断を返します。
+pub fn check_source(name: &str, src: &str) -> Result<TypedProgram, Diagnostics> {
+    let mut files = SimpleFiles::new();
+    let file_id = files.add(name.to_string(), src.to_string());
+    let mut collector = ErrorCollector::new();
+
+    let checked = parse_source(src, &mut collector, file_id).and_then(|mut program| {
+        let types = analyze_program(&mut program, &mut collector, file_id)?;
+        Some((program, types))
+    });
+    let diagnostics = Diagnostics::new(files, collector);
+    match checked {
+        Some((program, types)) if !diagnostics.has_errors() => Ok(TypedProgram { program, types, diagnostics }),
+        _ => Err(diagnostics),
+    }
+}
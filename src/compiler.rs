@@ -3,17 +3,25 @@
 //! このモジュールは、コンパイルパイプライン全体を管理し、
 //! 複数のエラーを蓄積しながら処理を進める機能を提供します。
 
-use crate::analyzer::{SemanticAnalyzer, monomorphize_program};
-use crate::codegen::CodeGenerator;
+use crate::analyzer::{
+    BorrowCheckMode, CallGraph, Lint, LintLevel, LintLevels, MonomorphizationStats, SemanticAnalyzer,
+    SourceMap, TypeTable, monomorphize_program_with_stats, prune_unreachable, resolve_names,
+};
+use crate::analyzer::monomorphization::REPORTED_ROOTS;
+use crate::assertion::SourceText;
+use crate::codegen::{CodeGenerator, CodegenOptions};
+use crate::deps::SOURCE_EXTENSION;
 use crate::error::{
-    ErrorCollector, LexerError, YuniError, YuniResult,
+    CodeSizeError, ErrorCollector, ParserError, YuniError, YuniResult,
 };
 use crate::lexer::{Lexer, Token};
 use crate::parser::Parser;
+use crate::source_file::{has_source_extension, read_source};
+use codespan_reporting::diagnostic::{Diagnostic, Severity};
 use codespan_reporting::files::SimpleFiles;
 use codespan_reporting::term::termcolor::{ColorChoice, StandardStream};
 use inkwell::context::Context;
-use std::fs;
+use std::collections::HashMap;
 use std::path::Path;
 
 /// コンパイル状態を管理する構造体
@@ -23,29 +31,29 @@ pub struct CompilationState {
     pub files: SimpleFiles<String, String>,
     pub file_id: usize,
     pub error_collector: ErrorCollector,
+    /// リントごとの警告の報告の水準
+    lints: LintLevels,
 }
 
 impl CompilationState {
     /// 新しいコンパイル状態を作成
+    ///
+    /// 読み込みの失敗はパスを含むエラーになります。拡張子が `.yuni` でないファイルは警告してから読みます。
     pub fn new<P: AsRef<Path>>(source_file: P) -> YuniResult<Self> {
-        let source_file_str = source_file.as_ref().display().to_string();
-        let source = fs::read_to_string(source_file.as_ref())
-            .map_err(|e| YuniError::Io(format!("Failed to read source file: {}", e)))?;
-
-        let mut files = SimpleFiles::new();
-        let file_id = files.add(source_file_str.clone(), source.clone());
-
-        Ok(Self {
-            source_file: source_file_str,
-            source,
-            files,
-            file_id,
-            error_collector: ErrorCollector::new(),
-        })
+        let path = source_file.as_ref();
+        let source = read_source(path)?;
+        let mut state = Self::new_from_string(&path.display().to_string(), source)?;
+        if !has_source_extension(path) {
+            state.add_warning(YuniError::Other(format!(
+                "{} does not have the .{} extension; compiling it as Yuni source anyway",
+                path.display(),
+                SOURCE_EXTENSION
+            )));
+        }
+        Ok(state)
     }
     
-    /// 文字列からコンパイル状態を作成（テスト用）
-    #[allow(dead_code)]
+    /// 文字列からコンパイル状態を作成（テストやJSON形式のASTの入力用）
     pub fn new_from_string(filename: &str, source: String) -> YuniResult<Self> {
         let mut files = SimpleFiles::new();
         let file_id = files.add(filename.to_string(), source.clone());
@@ -56,14 +64,46 @@ impl CompilationState {
             files,
             file_id,
             error_collector: ErrorCollector::new(),
+            lints: LintLevels::default(),
         })
     }
 
+    /// アサーションの失敗時のメッセージに引用するソースコード
+    pub fn source_text(&self) -> SourceText {
+        SourceText::new(&self.source_file, self.source.clone())
+    }
+
     /// エラーを追加
     pub fn add_error(&mut self, error: YuniError) {
         self.error_collector.add_error(error, self.file_id);
     }
 
+    /// 警告を追加
+    ///
+    /// リントの水準に従い、警告を捨てるかエラーとして追加することもあります。
+    pub fn add_warning(&mut self, warning: YuniError) {
+        match self.lints.level(Lint::of(&warning)) {
+            LintLevel::Allow => {}
+            LintLevel::Warn => self.error_collector.add_warning(warning, self.file_id),
+            LintLevel::Deny => self.error_collector.add_error(warning, self.file_id),
+        }
+    }
+
+    /// リントごとの警告の報告の水準を設定
+    ///
+    /// 読み込み時に追加した警告（入力ファイルの拡張子の警告）にも水準を適用し直します。
+    pub fn set_lints(&mut self, lints: LintLevels) {
+        self.lints = lints;
+        for warning in self.error_collector.take_warnings() {
+            self.add_warning(warning.error);
+        }
+    }
+
+    /// リントごとの警告の報告の水準
+    pub fn lints(&self) -> &LintLevels {
+        &self.lints
+    }
+
     /// 診断情報を報告
     pub fn report_diagnostics(&self) -> YuniResult<()> {
         let writer = StandardStream::stderr(ColorChoice::Always);
@@ -71,14 +111,21 @@ impl CompilationState {
 
         // エラーを報告
         for error in self.error_collector.errors() {
-            let diagnostic = error.to_diagnostic();
+            let diagnostic = self.within_source(error.to_diagnostic_in(&self.files));
             codespan_reporting::term::emit(&mut writer.lock(), &config, &self.files, &diagnostic)
                 .map_err(|e| YuniError::Io(format!("Failed to emit diagnostic: {}", e)))?;
         }
 
-        // 警告を報告
+        // 警告を報告（リントの警告には水準を変える方法を添える）
         for warning in self.error_collector.warnings() {
-            let diagnostic = warning.to_diagnostic();
+            let mut diagnostic = self.within_source(warning.to_diagnostic_in(&self.files));
+            diagnostic.severity = Severity::Warning;
+            if let Some(lint) = Lint::of(&warning.error) {
+                diagnostic.notes.push(format!(
+                    "リント {} の警告です（`--allow {}` で無視、`--deny {}` でエラーにできます）",
+                    lint, lint, lint
+                ));
+            }
             codespan_reporting::term::emit(&mut writer.lock(), &config, &self.files, &diagnostic)
                 .map_err(|e| YuniError::Io(format!("Failed to emit diagnostic: {}", e)))?;
         }
@@ -86,44 +133,164 @@ impl CompilationState {
         Ok(())
     }
 
+    /// ソースコードの範囲外を指すラベルを取り除く
+    ///
+    /// JSON形式のASTから読み込んだ場合はソースコードがないため、位置情報を表示できません。
+    fn within_source(&self, mut diagnostic: Diagnostic<usize>) -> Diagnostic<usize> {
+        diagnostic.labels.retain(|label| label.range.end <= self.source.len());
+        diagnostic
+    }
+
     /// エラーがあるかチェック
     pub fn has_errors(&self) -> bool {
         self.error_collector.has_errors()
     }
 
+    /// 警告があるかチェック
+    pub fn has_warnings(&self) -> bool {
+        self.error_collector.has_warnings()
+    }
+
     /// エラー数を取得
     pub fn error_count(&self) -> usize {
         self.error_collector.error_count()
     }
 }
 
+/// 生成するコードの規模のしきい値
+///
+/// 単相化でインスタンスが爆発的に増えたときに、コンパイル時間やメモリを
+/// 使い果たす前に気付けるようにします。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CodeSizeLimits {
+    /// インスタンス数がこれを超えると警告する
+    pub warn_instantiations: usize,
+    /// インスタンス数がこれを超えるとエラーで中断する
+    pub max_instantiations: Option<usize>,
+    /// LLVM IRモジュールで定義した関数の数がこれを超えると警告する
+    pub warn_functions: usize,
+    /// LLVM IRモジュールの命令数がこれを超えると警告する
+    pub warn_instructions: usize,
+}
+
+impl Default for CodeSizeLimits {
+    fn default() -> Self {
+        Self {
+            warn_instantiations: 1_000,
+            max_instantiations: None,
+            warn_functions: 5_000,
+            warn_instructions: 1_000_000,
+        }
+    }
+}
+
 /// コンパイルパイプライン
 pub struct CompilationPipeline<'ctx> {
     state: CompilationState,
     context: &'ctx Context,
-    verbose: bool,
+    /// セマンティック解析で決定した式の型（単相化とコード生成で使用）
+    type_table: TypeTable,
+    /// セマンティック解析で作った関数の呼び出しグラフ
+    call_graph: CallGraph,
+    /// 単相化の後、mainと公開された関数から到達できない関数を取り除く
+    prune_unreachable: bool,
+    /// 生成するコードの規模のしきい値
+    limits: CodeSizeLimits,
+    /// 単相化で生成したインスタンスの集計（コード生成の規模の内訳に使用）
+    stats: MonomorphizationStats,
+    /// 単相化で生成したアイテムの由来（コード生成のエラーの報告に使用）
+    source_map: SourceMap,
+    /// 入力を常にスクリプトとして解析する
+    script: bool,
+    /// 借用規則の違反の扱い
+    borrow_check: BorrowCheckMode,
+    /// コード生成の設定（ターゲット・再配置モデルなど）
+    codegen_options: CodegenOptions,
 }
 
 impl<'ctx> CompilationPipeline<'ctx> {
     /// 新しいコンパイルパイプラインを作成
-    pub fn new(state: CompilationState, context: &'ctx Context, verbose: bool) -> Self {
+    pub fn new(state: CompilationState, context: &'ctx Context) -> Self {
+        let codegen_options = CodegenOptions::new(state.source_file.clone());
         Self {
             state,
             context,
-            verbose,
+            type_table: TypeTable::new(),
+            call_graph: CallGraph::default(),
+            prune_unreachable: false,
+            limits: CodeSizeLimits::default(),
+            stats: MonomorphizationStats::default(),
+            source_map: SourceMap::default(),
+            script: false,
+            borrow_check: BorrowCheckMode::default(),
+            codegen_options,
         }
     }
     
+    /// 生成するコードの規模のしきい値を設定
+    pub fn with_code_size_limits(mut self, limits: CodeSizeLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// 入力をスクリプトとして解析するかを設定
+    ///
+    /// 設定しない場合も、package宣言がなく文から始まる入力はスクリプトとして解析します。
+    pub fn with_script(mut self, script: bool) -> Self {
+        self.script = script;
+        self
+    }
+    
+    /// 借用規則の違反の扱いを設定
+    ///
+    /// 設定しない場合は違反をエラーとして報告します。
+    pub fn with_borrow_check(mut self, mode: BorrowCheckMode) -> Self {
+        self.borrow_check = mode;
+        self
+    }
+
+    /// リントごとの警告の報告の水準を設定
+    ///
+    /// 設定しない場合はすべての警告を報告し、警告ではコンパイルは失敗しません。
+    pub fn with_lints(mut self, lints: LintLevels) -> Self {
+        self.state.set_lints(lints);
+        self
+    }
+
+    /// 到達できない関数をコード生成の前に取り除くかを設定
+    ///
+    /// 設定しない場合はすべての関数のコードを生成します（ライブラリとしての出力向け）。
+    pub fn with_prune_unreachable(mut self, prune: bool) -> Self {
+        self.prune_unreachable = prune;
+        self
+    }
+
+    /// コード生成の設定を指定
+    ///
+    /// 設定しない場合はホスト向けの位置独立コードを生成します。
+    pub fn with_codegen_options(mut self, options: CodegenOptions) -> Self {
+        self.codegen_options = options;
+        self
+    }
+
     /// コンパイル状態への参照を取得
     pub fn state(&self) -> &CompilationState {
         &self.state
     }
 
+    /// セマンティック解析で決定した式の型を取得
+    pub fn type_table(&self) -> &TypeTable {
+        &self.type_table
+    }
+
+    /// セマンティック解析で作った関数の呼び出しグラフを取得
+    pub fn call_graph(&self) -> &CallGraph {
+        &self.call_graph
+    }
+
     /// レキシカル解析を実行
     pub fn tokenize(&mut self) -> Vec<crate::lexer::TokenWithPosition> {
-        if self.verbose {
-            println!("ステップ: レキシカル解析を開始");
-        }
+        log::debug!(target: "yuni::pipeline", "starting lexical analysis");
 
         let lexer = Lexer::new(&self.state.source);
         let tokens: Vec<_> = lexer.collect_tokens();
@@ -131,15 +298,7 @@ impl<'ctx> CompilationPipeline<'ctx> {
         // レキサーエラーをチェック
         for token in &tokens {
             if matches!(token.token, Token::Error) {
-                // エラートークンの位置から実際の文字を取得
-                let error_text = self.state.source.get(token.span.clone())
-                    .unwrap_or("不明")
-                    .to_string();
-                
-                self.state.add_error(YuniError::Lexer(LexerError::UnrecognizedToken {
-                    token: error_text,
-                    span: token.span.clone().into(),
-                }));
+                self.state.add_error(YuniError::Lexer(token.lexer_error(&self.state.source)));
             }
         }
 
@@ -148,13 +307,25 @@ impl<'ctx> CompilationPipeline<'ctx> {
 
     /// 構文解析を実行
     pub fn parse(&mut self, tokens: Vec<crate::lexer::TokenWithPosition>) -> Option<crate::ast::Program> {
-        if self.verbose {
-            println!("ステップ: 構文解析を開始");
-        }
+        log::debug!(target: "yuni::pipeline", "starting parsing");
 
+        // セミコロンの書き忘れのように解析を続けられた構文エラーは、報告した上で後続の解析に進む
         let mut parser = Parser::new(tokens);
-        match parser.parse() {
-            Ok(program) => Some(program),
+        let result = if self.script || parser.starts_with_statement() {
+            parser.parse_script_recovering()
+        } else {
+            parser.parse_recovering()
+        };
+        for error in parser.recovered_errors() {
+            self.state.add_error(YuniError::Parser(error.clone()));
+        }
+        match result {
+            Ok(program) => {
+                for span in parser.misplaced_doc_comments() {
+                    self.state.add_warning(YuniError::Parser(ParserError::MisplacedDocComment { span }));
+                }
+                Some(program)
+            }
             Err(e) => {
                 self.state.add_error(YuniError::Parser(e));
                 None
@@ -162,29 +333,64 @@ impl<'ctx> CompilationPipeline<'ctx> {
         }
     }
 
+    /// 名前解決を実行
+    ///
+    /// 標準ライブラリのプレリュードとインポートに従って、型名・関数名を正規の名前に書き換えます。
+    pub fn resolve_names(&mut self, ast: &mut crate::ast::Program) {
+        let resolution = resolve_names(ast);
+        for warning in resolution.warnings {
+            self.state.add_warning(YuniError::Analyzer(warning));
+        }
+        for error in resolution.errors {
+            self.state.add_error(YuniError::Analyzer(error));
+        }
+    }
+
     /// セマンティック解析を実行
     pub fn analyze(&mut self, ast: &crate::ast::Program) -> bool {
-        if self.verbose {
-            println!("ステップ: セマンティック解析を開始");
+        log::debug!(target: "yuni::pipeline", "starting semantic analysis");
+
+        let mut analyzer = SemanticAnalyzer::new()
+            .with_borrow_check(self.borrow_check)
+            .with_lints(self.state.lints().clone());
+        let result = analyzer.analyze(ast);
+        for warning in std::mem::take(&mut analyzer.warnings) {
+            self.state.add_warning(YuniError::Analyzer(warning));
         }
-
-        let mut analyzer = SemanticAnalyzer::new();
-        if let Err(e) = analyzer.analyze(ast) {
-            self.state.add_error(YuniError::Analyzer(e));
+        // 解析は誤りの後も続けるため、記録したすべてのエラーを報告する
+        for error in std::mem::take(&mut analyzer.errors) {
+            self.state.add_error(YuniError::Analyzer(error));
+        }
+        if result.is_err() {
             false
         } else {
+            self.type_table = std::mem::take(&mut analyzer.type_table);
+            self.call_graph = std::mem::take(&mut analyzer.call_graph);
             true
         }
     }
     
     /// 単相化を実行
     pub fn monomorphize(&mut self, ast: crate::ast::Program) -> Option<crate::ast::Program> {
-        if self.verbose {
-            println!("ステップ: ジェネリクスの単相化を開始");
-        }
+        log::debug!(target: "yuni::pipeline", "starting monomorphization");
         
-        match monomorphize_program(ast) {
-            Ok(monomorphized_ast) => Some(monomorphized_ast),
+        match monomorphize_program_with_stats(ast, self.type_table.clone(), self.limits.max_instantiations) {
+            Ok((mut monomorphized_ast, source_map, stats)) => {
+                if self.prune_unreachable {
+                    let removed = prune_unreachable(&mut monomorphized_ast, &self.type_table);
+                    log::debug!(target: "yuni::pipeline", "removed {} unreachable functions: {:?}", removed.len(), removed);
+                }
+                if stats.instantiations > self.limits.warn_instantiations {
+                    self.state.add_warning(YuniError::CodeSize(CodeSizeError::TooManyInstantiations {
+                        count: stats.instantiations,
+                        threshold: self.limits.warn_instantiations,
+                        roots: stats.top_roots(REPORTED_ROOTS),
+                    }));
+                }
+                self.stats = stats;
+                self.source_map = source_map;
+                Some(monomorphized_ast)
+            }
             Err(e) => {
                 self.state.add_error(e);
                 None
@@ -194,15 +400,61 @@ impl<'ctx> CompilationPipeline<'ctx> {
 
     /// コード生成を実行
     pub fn codegen(&mut self, ast: &crate::ast::Program) -> YuniResult<CodeGenerator<'ctx>> {
-        if self.verbose {
-            println!("ステップ: コード生成を開始");
+        log::debug!(target: "yuni::pipeline", "starting code generation");
+
+        // 明示したターゲットが不明な場合は、デフォルトのデータレイアウトで生成せずにエラーにする
+        if self.codegen_options.target_triple.is_some() {
+            self.codegen_options.target_machine()?;
         }
 
-        let mut codegen = CodeGenerator::new(self.context, &self.state.source_file);
-        codegen.compile_program(ast)?;
+        let mut codegen = CodeGenerator::new(self.context, self.codegen_options.clone())
+            .with_source_map(self.source_map.clone())
+            .with_source_text(self.state.source_text());
+        codegen.compile_program_with_types(ast, self.type_table.clone())?;
+        self.check_module_size(&codegen);
+        codegen.run_inline_passes()?;
+        if self.codegen_options.strip_names {
+            codegen.strip_value_names();
+        }
         Ok(codegen)
     }
 
+    /// LLVM IRモジュールの規模を数え、しきい値を超えていれば警告する
+    fn check_module_size(&mut self, codegen: &CodeGenerator<'ctx>) {
+        let mut functions = 0;
+        let mut instructions = 0;
+        let mut per_root: HashMap<String, usize> = HashMap::new();
+
+        for function in codegen.get_module().get_functions() {
+            // 宣言のみの外部関数は数えない
+            if function.count_basic_blocks() == 0 {
+                continue;
+            }
+            let count: usize = function
+                .get_basic_blocks()
+                .iter()
+                .map(|block| block.get_instructions().count())
+                .sum();
+            let name = function.get_name().to_string_lossy();
+            functions += 1;
+            instructions += count;
+            *per_root.entry(self.stats.root_of(&name).to_string()).or_insert(0) += count;
+        }
+
+        if functions > self.limits.warn_functions || instructions > self.limits.warn_instructions {
+            let mut roots: Vec<(String, usize)> = per_root.into_iter().collect();
+            roots.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+            roots.truncate(REPORTED_ROOTS);
+            self.state.add_warning(YuniError::CodeSize(CodeSizeError::ModuleTooLarge {
+                functions,
+                instructions,
+                max_functions: self.limits.warn_functions,
+                max_instructions: self.limits.warn_instructions,
+                roots,
+            }));
+        }
+    }
+
     /// エラーレポートを生成
     pub fn report_errors(&self) -> YuniResult<()> {
         self.state.report_diagnostics()?;
@@ -225,14 +477,15 @@ impl<'ctx> CompilationPipeline<'ctx> {
         let tokens = self.tokenize();
         
         // エラーがあってもパースは続行（より多くのエラーを検出するため）
-        let ast = if !self.state.has_errors() {
+        let mut ast = if !self.state.has_errors() {
             self.parse(tokens)
         } else {
             None
         };
 
-        // ASTが取得できた場合のみセマンティック解析を実行
-        if let Some(ref ast) = ast {
+        // ASTが取得できた場合のみ名前解決とセマンティック解析を実行
+        if let Some(ast) = &mut ast {
+            self.resolve_names(ast);
             self.analyze(ast);
         }
 
@@ -265,4 +518,10 @@ impl<'ctx> CompilationPipeline<'ctx> {
     pub fn state_mut(&mut self) -> &mut CompilationState {
         &mut self.state
     }
+
+    /// パイプラインを終了し、蓄積した診断を含むコンパイル状態を取り出す
+    #[allow(dead_code)]
+    pub fn into_state(self) -> CompilationState {
+        self.state
+    }
 }
\ No newline at end of file
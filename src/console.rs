@@ -0,0 +1,91 @@
+//! コンソール出力の組み込み関数
+//!
+//! `print(message)` は改行なしで標準出力に、`println(message)` は改行付きで標準出力に、
+//! `eprintln(message)` は改行付きで標準エラー出力に文字列を1つ書き出します。
+//! 値を組み合わせる場合はテンプレート文字列（`` println(`${a} ${b}`) ``）を使います。
+//!
+//! 以前の複数の引数を取る形（`println("x = ", x)`）や文字列以外の値を1つ渡す形は、
+//! 各引数を順に補間したテンプレート文字列に脱糖します。出力する文字列は書式として
+//! 解釈しないため、`%s` などを含んでいてもそのまま出力されます。
+
+use crate::ast::{Expression, Span, TemplateStringLit, TemplateStringPart};
+
+/// コンソール出力の組み込み関数の種類
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputKind {
+    Print,
+    Println,
+    Eprintln,
+}
+
+impl OutputKind {
+    /// 組み込み関数の名前から種類を取得
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "print" => Some(Self::Print),
+            "println" => Some(Self::Println),
+            "eprintln" => Some(Self::Eprintln),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Print => "print",
+            Self::Println => "println",
+            Self::Eprintln => "eprintln",
+        }
+    }
+
+    /// 末尾に改行を出力するか
+    pub fn newline(self) -> bool {
+        !matches!(self, Self::Print)
+    }
+
+    /// 標準エラー出力に書き出すか
+    pub fn to_stderr(self) -> bool {
+        matches!(self, Self::Eprintln)
+    }
+
+    /// 文字列を出力するランタイム関数
+    #[cfg(feature = "full")]
+    pub fn runtime_function(self) -> &'static str {
+        match self {
+            Self::Print => "yuni_print",
+            Self::Println => "yuni_println",
+            Self::Eprintln => "yuni_eprintln",
+        }
+    }
+
+    /// 引数を省略できるか（改行だけを出力する）
+    pub fn allows_no_message(self) -> bool {
+        self.newline()
+    }
+
+    /// エラーの補足情報に表示するシグネチャ
+    pub fn signature(self) -> String {
+        format!("fn {}(message: String)", self.name())
+    }
+}
+
+/// 出力する文字列の式
+///
+/// 文字列リテラルかテンプレート文字列が1つだけ渡された場合はそのまま使い、
+/// それ以外は引数を順に連結するテンプレート文字列に脱糖します。引数がない場合は `None` です。
+pub fn output_message(args: &[Expression], span: Span) -> Option<Expression> {
+    match args {
+        [] => None,
+        [message @ (Expression::String(_) | Expression::TemplateString(_))] => Some(message.clone()),
+        _ => {
+            let mut parts = Vec::with_capacity(args.len());
+            for arg in args {
+                match arg {
+                    Expression::String(lit) => parts.push(TemplateStringPart::Text(lit.value.clone())),
+                    Expression::TemplateString(template) => parts.extend(template.parts.iter().cloned()),
+                    _ => parts.push(TemplateStringPart::Interpolation(arg.clone())),
+                }
+            }
+            Some(Expression::TemplateString(TemplateStringLit { parts, span }))
+        }
+    }
+}
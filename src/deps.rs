@@ -0,0 +1,213 @@
+//! インポートの依存関係グラフ
+//!
+//! エントリーポイントのファイルからインポートをたどり、依存するソースファイルを列挙します。
+//! 各ファイルはpackage宣言とインポートだけを解析し、コンパイルはしません。
+//! `yunilang compile --emit=deps` はこのグラフをMakefileの依存関係の行かJSONとして出力します。
+//!
+//! インポートのパスからファイルへの解決は [`resolve_import`] にまとめてあり、
+//! 複数ファイルのコンパイルでも同じ関数で解決します。
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+
+use crate::analyzer::prelude::{is_std_path, normalize_path};
+use crate::ast::Import;
+use crate::error::{YuniError, YuniResult};
+use crate::lexer::Lexer;
+use crate::parser::Parser;
+use crate::source_file::read_source;
+
+/// ソースファイルの拡張子
+pub const SOURCE_EXTENSION: &str = "yuni";
+
+/// インポートの解決先
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ImportTarget {
+    /// 標準ライブラリ（コンパイラに組み込まれているためファイルはない）
+    Std,
+    /// ソースファイル
+    File(PathBuf),
+    /// 対応するファイルが存在しない（`candidate` は探したパス）
+    Unresolved { candidate: PathBuf },
+}
+
+/// インポートのパスを解決する
+///
+/// `util` や `geometry.shapes`（`geometry/shapes`）のようなパスは、ルートディレクトリ
+/// （エントリーポイントのファイルがあるディレクトリ）からの相対パスに拡張子 `.yuni` を
+/// 付けたファイルを指します。どのファイルからインポートしても同じファイルに解決されます。
+///
+/// ルートディレクトリにないファイルは `module_dirs`（マニフェストの `[paths] modules`）から
+/// 順に探します。どこにもない場合、`candidate` はルートディレクトリで探したパスです。
+pub fn resolve_import(root: &Path, module_dirs: &[PathBuf], import_path: &str) -> ImportTarget {
+    let path = normalize_path(import_path);
+    if is_std_path(&path) {
+        return ImportTarget::Std;
+    }
+    let file_name = format!("{}.{}", path, SOURCE_EXTENSION);
+    let candidate = root.join(&file_name);
+    if candidate.is_file() {
+        return ImportTarget::File(candidate);
+    }
+    match module_dirs.iter().map(|dir| dir.join(&file_name)).find(|file| file.is_file()) {
+        Some(file) => ImportTarget::File(file),
+        None => ImportTarget::Unresolved { candidate },
+    }
+}
+
+/// エントリーポイントのファイルからルートディレクトリを求める
+pub fn root_dir(entry: &Path) -> &Path {
+    entry.parent().unwrap_or(Path::new(""))
+}
+
+/// ファイルのインポートを読む
+///
+/// package宣言とインポートだけを解析するため、本体に構文エラーがあっても読めます。
+pub fn read_imports(path: &Path) -> YuniResult<Vec<Import>> {
+    let source = read_source(path)?;
+    let tokens = Lexer::new(&source).collect_tokens();
+    Parser::new(tokens)
+        .parse_imports_only()
+        .map_err(|e| YuniError::Other(format!("Failed to parse imports in {}: {}", path.display(), e)))
+}
+
+/// 依存関係の辺（解決できたインポート1件）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DependencyEdge {
+    pub from: PathBuf,
+    pub to: PathBuf,
+    /// ソース上のインポートのパス
+    pub import: String,
+}
+
+/// 解決できなかったインポート
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct UnresolvedImport {
+    pub from: PathBuf,
+    /// ソース上のインポートのパス
+    pub import: String,
+    /// 探したファイルのパス
+    pub candidate: PathBuf,
+}
+
+/// インポートの依存関係グラフ
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DependencyGraph {
+    /// エントリーポイントのファイル
+    pub entry: PathBuf,
+    /// エントリーポイントから到達できるファイル（エントリーポイントから深さ優先でたどった順）
+    pub nodes: Vec<PathBuf>,
+    pub edges: Vec<DependencyEdge>,
+    pub unresolved: Vec<UnresolvedImport>,
+    /// インポートの循環（それぞれ始点のファイルで閉じる）
+    ///
+    /// 循環があってもグラフは作成します。
+    pub cycles: Vec<Vec<PathBuf>>,
+}
+
+impl DependencyGraph {
+    /// エントリーポイントのファイルからインポートをたどってグラフを作成
+    ///
+    /// インポートはルートディレクトリの次に `module_dirs` から探します。
+    pub fn build(entry: &Path, module_dirs: &[PathBuf]) -> YuniResult<Self> {
+        let mut builder = GraphBuilder {
+            root: root_dir(entry).to_path_buf(),
+            module_dirs: module_dirs.to_vec(),
+            graph: Self {
+                entry: entry.to_path_buf(),
+                nodes: Vec::new(),
+                edges: Vec::new(),
+                unresolved: Vec::new(),
+                cycles: Vec::new(),
+            },
+            visited: HashSet::new(),
+            stack: Vec::new(),
+        };
+        builder.visit(entry.to_path_buf())?;
+        Ok(builder.graph)
+    }
+
+    /// Makefileの依存関係の行（`target: main.yuni util.yuni`）に変換
+    ///
+    /// 解決できなかったインポートは含めません。
+    pub fn to_makefile(&self, target: &Path) -> String {
+        let mut line = escape_make(target);
+        line.push(':');
+        for node in &self.nodes {
+            line.push(' ');
+            line.push_str(&escape_make(node));
+        }
+        line.push('\n');
+        line
+    }
+
+    /// JSON文字列に変換
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("dependency graph is always serializable")
+    }
+}
+
+struct GraphBuilder {
+    root: PathBuf,
+    module_dirs: Vec<PathBuf>,
+    graph: DependencyGraph,
+    visited: HashSet<PathBuf>,
+    /// たどっている途中のファイル（循環の検出用）
+    stack: Vec<PathBuf>,
+}
+
+impl GraphBuilder {
+    fn visit(&mut self, file: PathBuf) -> YuniResult<()> {
+        self.visited.insert(file.clone());
+        self.graph.nodes.push(file.clone());
+        self.stack.push(file.clone());
+
+        for import in read_imports(&file)? {
+            match resolve_import(&self.root, &self.module_dirs, &import.path) {
+                ImportTarget::Std => {}
+                ImportTarget::Unresolved { candidate } => {
+                    self.graph.unresolved.push(UnresolvedImport {
+                        from: file.clone(),
+                        import: import.path,
+                        candidate,
+                    });
+                }
+                ImportTarget::File(dependency) => {
+                    self.graph.edges.push(DependencyEdge {
+                        from: file.clone(),
+                        to: dependency.clone(),
+                        import: import.path,
+                    });
+                    if let Some(start) = self.stack.iter().position(|f| *f == dependency) {
+                        let mut cycle = self.stack[start..].to_vec();
+                        cycle.push(dependency);
+                        self.graph.cycles.push(cycle);
+                    } else if !self.visited.contains(&dependency) {
+                        self.visit(dependency)?;
+                    }
+                }
+            }
+        }
+
+        self.stack.pop();
+        Ok(())
+    }
+}
+
+/// Makefileで特別な意味を持つ文字をエスケープする
+fn escape_make(path: &Path) -> String {
+    let mut escaped = String::new();
+    for c in path.display().to_string().chars() {
+        match c {
+            ' ' | '#' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            '$' => escaped.push_str("$$"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
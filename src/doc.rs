@@ -0,0 +1,68 @@
+//! ドキュメントの生成
+//!
+//! 公開されたアイテムを、シグネチャとドキュメントコメント（`///`）の本文とともに
+//! Markdownとして出力します。`yunilang doc` はこの出力をファイルに書き込みます。
+//!
+//! 型には可視性修飾子がないため、型定義はすべて出力します。関数とメソッドは `pub` のものだけです。
+
+use crate::ast::*;
+use crate::symbols::{extern_function_signature, function_signature, method_signature, receiver_type_name};
+
+/// プログラムの公開アイテムをMarkdownとして出力
+pub fn render_markdown(program: &Program) -> String {
+    let mut types = Vec::new();
+    let mut functions = Vec::new();
+    let mut methods = Vec::new();
+    for item in &program.items {
+        match item {
+            Item::TypeDef(type_def) => {
+                let name = match type_def {
+                    TypeDef::Struct(struct_def) => &struct_def.name,
+                    TypeDef::Enum(enum_def) => &enum_def.name,
+                    TypeDef::Alias(alias) => &alias.name,
+                };
+                types.push(entry(name.clone(), print_type_def(type_def), item.docs()));
+            }
+            Item::Function(func) if func.is_public => {
+                functions.push(entry(func.name.clone(), function_signature(func), item.docs()));
+            }
+            Item::ExternFunction(func) if func.is_public => {
+                functions.push(entry(func.name.clone(), extern_function_signature(func), item.docs()));
+            }
+            Item::Method(method) if method.is_public => {
+                let name = match receiver_type_name(&method.receiver.ty) {
+                    Some(receiver) => format!("{}::{}", receiver, method.name),
+                    None => method.name.clone(),
+                };
+                methods.push(entry(name, method_signature(method), item.docs()));
+            }
+            Item::Function(_) | Item::Method(_) | Item::ExternFunction(_) => {}
+        }
+    }
+
+    let mut out = format!("# Package `{}`\n", program.package.name);
+    for (heading, entries) in [("Types", types), ("Functions", functions), ("Methods", methods)] {
+        if entries.is_empty() {
+            continue;
+        }
+        out.push_str(&format!("\n## {}\n", heading));
+        for entry in entries {
+            out.push('\n');
+            out.push_str(&entry);
+        }
+    }
+    out
+}
+
+/// アイテム1件の見出し・シグネチャ・本文
+fn entry(name: String, signature: String, docs: &[String]) -> String {
+    let mut out = format!("### `{}`\n\n```yuni\n{}\n```\n", name, signature);
+    if !docs.is_empty() {
+        out.push('\n');
+        for line in docs {
+            out.push_str(line);
+            out.push('\n');
+        }
+    }
+    out
+}
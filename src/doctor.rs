@@ -0,0 +1,367 @@
+//! 実行ファイルの生成に使うツールチェーンの検査（`yunilang doctor`）
+//!
+//! llc・clang・rustcの有無とバージョン、一時ディレクトリへの書き込み、macOSではHomebrewのLLVMを検査し、
+//! 失敗した検査にはプラットフォームごとのインストールのコマンドを添えます。
+//! コンパイルのパイプラインも同じ検査を使い、必要なツールがなければコード生成の前にエラーにします。
+//! 外部コマンドの実行は [`CommandRunner`] で抽象化しているため、テストでは偽の出力に差し替えられます。
+
+use std::ffi::OsString;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use crate::error::{YuniError, YuniResult};
+use crate::toolchain::{Target, TargetOs};
+
+/// コンパイラがリンクしているLLVM（inkwell）のメジャーバージョン
+///
+/// 生成したLLVM IRを読むllcも同じメジャーバージョンでなければなりません。
+pub const REQUIRED_LLVM_MAJOR: u32 = 18;
+
+/// macOSでHomebrewがLLVM 18をインストールする場所
+const HOMEBREW_LLVM_DIRS: [&str; 2] = ["/opt/homebrew/opt/llvm@18/bin", "/usr/local/opt/llvm@18/bin"];
+
+/// 外部コマンドの実行
+pub trait CommandRunner {
+    /// `program` を `args` で実行し、成功した場合は標準出力と標準エラーをつなげた文字列を返す
+    ///
+    /// 起動できないか失敗した場合は `None` を返します。
+    fn run(&self, program: &Path, args: &[&str]) -> Option<String>;
+}
+
+/// 実際にプロセスを起動する [`CommandRunner`]
+pub struct SystemCommandRunner;
+
+impl CommandRunner for SystemCommandRunner {
+    fn run(&self, program: &Path, args: &[&str]) -> Option<String> {
+        let output = Command::new(program)
+            .args(args)
+            .stdin(Stdio::null())
+            .output()
+            .ok()
+            .filter(|output| output.status.success())?;
+        // 古いLLVMは `--version` を標準エラーに出力する
+        let mut text = String::from_utf8_lossy(&output.stdout).into_owned();
+        text.push_str(&String::from_utf8_lossy(&output.stderr));
+        Some(text)
+    }
+}
+
+/// 検査するツール
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tool {
+    /// LLVM IRをオブジェクトファイル・アセンブリにする
+    Llc,
+    /// リンクに使うCコンパイラのドライバ（WindowsのMSVC環境ではclang-clも使う）
+    Clang,
+    /// ランタイムを静的ライブラリにする
+    Rustc,
+}
+
+impl Tool {
+    pub fn name(self) -> &'static str {
+        match self {
+            Tool::Llc => "llc",
+            Tool::Clang => "clang",
+            Tool::Rustc => "rustc",
+        }
+    }
+}
+
+/// `major.minor.patch` 形式のバージョン
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Version {
+    pub major: u32,
+    pub minor: u32,
+    pub patch: u32,
+}
+
+impl Version {
+    /// `--version` の出力から `marker` の直後のバージョンを読む
+    ///
+    /// `llc --version` は `LLVM version 18.1.8`、`clang --version` は `clang version 18.1.8`、
+    /// `rustc --version` は `rustc 1.80.0` の形式で、前に配布元の名前が付くこともあります。
+    /// 各部分の数字の後の文字（`18.1.8git` の `git` など）は無視し、省略した部分は0とします。
+    pub fn parse_after(output: &str, marker: &str) -> Option<Version> {
+        let rest = output.lines().find_map(|line| line.split_once(marker).map(|(_, rest)| rest))?;
+        let text = rest.split_whitespace().next()?;
+        let mut parts = text.split('.').map(|part| {
+            let digits: String = part.chars().take_while(char::is_ascii_digit).collect();
+            digits.parse::<u32>().ok()
+        });
+        let major = parts.next()??;
+        let minor = parts.next().flatten().unwrap_or(0);
+        let patch = parts.next().flatten().unwrap_or(0);
+        Some(Version { major, minor, patch })
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+/// 検査の結果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CheckStatus {
+    Pass,
+    /// 問題になる可能性があるが、コンパイルは失敗しない
+    Warn,
+    Fail,
+}
+
+/// 1つの検査の結果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Check {
+    /// 検査の対象（`llc`・`temp dir` など）
+    pub name: &'static str,
+    pub status: CheckStatus,
+    /// 見つかったバージョンとパス、または失敗の理由
+    pub detail: String,
+    /// 失敗した場合の直し方
+    pub fix: Option<String>,
+}
+
+impl Check {
+    fn pass(name: &'static str, detail: String) -> Self {
+        Self { name, status: CheckStatus::Pass, detail, fix: None }
+    }
+
+    fn fail(name: &'static str, detail: String, fix: String) -> Self {
+        Self { name, status: CheckStatus::Fail, detail, fix: Some(fix) }
+    }
+
+    pub fn passed(&self) -> bool {
+        self.status != CheckStatus::Fail
+    }
+}
+
+/// ツールチェーンの検査
+pub struct Doctor<R> {
+    target: Target,
+    /// `PATH` 環境変数の値
+    path_var: Option<OsString>,
+    /// Windowsの `ProgramFiles` 環境変数の値
+    program_files: Option<OsString>,
+    /// 中間ファイルを置く一時ディレクトリ
+    temp_dir: PathBuf,
+    runner: R,
+}
+
+impl Doctor<SystemCommandRunner> {
+    /// コンパイラ自身の環境を検査する
+    pub fn from_env() -> Self {
+        Doctor::new(Target::host(), SystemCommandRunner)
+            .with_path_var(std::env::var_os("PATH"))
+            .with_program_files(std::env::var_os("ProgramFiles"))
+    }
+}
+
+impl<R: CommandRunner> Doctor<R> {
+    /// `target` のツールを `runner` で実行して検査する（PATHは空、一時ディレクトリはOSの既定）
+    pub fn new(target: Target, runner: R) -> Self {
+        Self {
+            target,
+            path_var: None,
+            program_files: None,
+            temp_dir: std::env::temp_dir(),
+            runner,
+        }
+    }
+
+    pub fn with_path_var(mut self, path_var: Option<OsString>) -> Self {
+        self.path_var = path_var;
+        self
+    }
+
+    pub fn with_program_files(mut self, program_files: Option<OsString>) -> Self {
+        self.program_files = program_files;
+        self
+    }
+
+    pub fn with_temp_dir(mut self, temp_dir: PathBuf) -> Self {
+        self.temp_dir = temp_dir;
+        self
+    }
+
+    /// すべての検査を実行する
+    pub fn checks(&self) -> Vec<Check> {
+        let mut checks: Vec<Check> = [Tool::Llc, Tool::Clang, Tool::Rustc]
+            .into_iter()
+            .map(|tool| self.check_tool(tool))
+            .collect();
+        checks.push(self.check_temp_dir());
+        if self.target.os == TargetOs::MacOs {
+            checks.push(self.check_homebrew_llvm());
+        }
+        checks
+    }
+
+    /// `tools` をすべて使えることを確かめ、使えないツールがあればエラーにする
+    ///
+    /// コンパイルのパイプラインがコード生成の前に呼び出します。
+    pub fn require(&self, tools: &[Tool]) -> YuniResult<()> {
+        let failures: Vec<String> = tools
+            .iter()
+            .map(|&tool| self.check_tool(tool))
+            .filter(|check| !check.passed())
+            .map(|check| match check.fix {
+                Some(fix) => format!("{}: {}; to fix: {}", check.name, check.detail, fix),
+                None => format!("{}: {}", check.name, check.detail),
+            })
+            .collect();
+        if failures.is_empty() {
+            return Ok(());
+        }
+        Err(YuniError::Other(format!(
+            "Missing tools needed for this output (run `yunilang doctor` for details):\n  {}",
+            failures.join("\n  ")
+        )))
+    }
+
+    /// ツールの有無とバージョンを検査する
+    pub fn check_tool(&self, tool: Tool) -> Check {
+        match tool {
+            Tool::Llc => self.check_llc(),
+            Tool::Clang => self.check_clang(),
+            Tool::Rustc => self.check_rustc(),
+        }
+    }
+
+    /// llcがあり、コンパイラと同じメジャーバージョンのLLVMであることを検査する
+    fn check_llc(&self) -> Check {
+        let name = Tool::Llc.name();
+        let Some(path) = self.target.find_tool(name, self.path_var.as_deref(), self.program_files.as_deref()) else {
+            return Check::fail(name, "not found".to_string(), self.llvm_install_command(Tool::Llc));
+        };
+        let Some(output) = self.runner.run(&path, &["--version"]) else {
+            return Check::fail(
+                name,
+                format!("{} --version failed", path.display()),
+                self.llvm_install_command(Tool::Llc),
+            );
+        };
+        match Version::parse_after(&output, "LLVM version ") {
+            Some(version) if version.major == REQUIRED_LLVM_MAJOR => {
+                Check::pass(name, format!("LLVM {} ({})", version, path.display()))
+            }
+            Some(version) => Check::fail(
+                name,
+                format!("LLVM {} at {}, but LLVM {} is required", version, path.display(), REQUIRED_LLVM_MAJOR),
+                format!(
+                    "{}, then put its bin directory before {} in PATH",
+                    self.llvm_install_command(Tool::Llc),
+                    path.parent().unwrap_or(Path::new(".")).display()
+                ),
+            ),
+            None => Check::fail(
+                name,
+                format!("could not read the LLVM version from {} --version", path.display()),
+                self.llvm_install_command(Tool::Llc),
+            ),
+        }
+    }
+
+    /// リンカ（clang・clang-cl）があることを検査する
+    ///
+    /// リンクはLLVMのバージョンに依存しないため、バージョンは表示するだけです。
+    fn check_clang(&self) -> Check {
+        let name = Tool::Clang.name();
+        let Some(linker) = self.target.find_linker(self.path_var.as_deref(), self.program_files.as_deref()) else {
+            return Check::fail(name, "not found".to_string(), self.llvm_install_command(Tool::Clang));
+        };
+        let path = linker.program;
+        match self.runner.run(&path, &["--version"]) {
+            Some(output) => match Version::parse_after(&output, "clang version ") {
+                Some(version) => Check::pass(name, format!("clang {} ({})", version, path.display())),
+                None => Check::pass(name, path.display().to_string()),
+            },
+            None => Check::fail(
+                name,
+                format!("{} --version failed", path.display()),
+                self.llvm_install_command(Tool::Clang),
+            ),
+        }
+    }
+
+    /// ランタイムのビルドに使うrustcがPATHにあることを検査する
+    fn check_rustc(&self) -> Check {
+        let name = Tool::Rustc.name();
+        // コンパイルのパイプラインと同じくPATHだけから探す
+        let file_name = self.target.tool_file_name(name);
+        let found = self
+            .path_var
+            .as_deref()
+            .into_iter()
+            .flat_map(std::env::split_paths)
+            .map(|dir| dir.join(&file_name))
+            .find(|path| path.is_file());
+        let Some(path) = found else {
+            return Check::fail(name, "not found in PATH".to_string(), self.rustup_install_command());
+        };
+        match self.runner.run(&path, &["--version"]).and_then(|output| Version::parse_after(&output, "rustc ")) {
+            Some(version) => Check::pass(name, format!("rustc {} ({})", version, path.display())),
+            None => Check::fail(
+                name,
+                format!("{} --version failed", path.display()),
+                self.rustup_install_command(),
+            ),
+        }
+    }
+
+    /// 一時ディレクトリにファイルを作れることを検査する
+    pub fn check_temp_dir(&self) -> Check {
+        let name = "temp dir";
+        match tempfile::Builder::new().prefix("yuni_doctor").tempfile_in(&self.temp_dir) {
+            Ok(_) => Check::pass(name, format!("{} is writable", self.temp_dir.display())),
+            Err(e) => Check::fail(
+                name,
+                format!("cannot create files in {}: {}", self.temp_dir.display(), e),
+                if self.target.is_windows() {
+                    "set TEMP to a writable directory".to_string()
+                } else {
+                    "set TMPDIR to a writable directory".to_string()
+                },
+            ),
+        }
+    }
+
+    /// macOSでHomebrewのLLVM 18があるかを検査する
+    ///
+    /// HomebrewのLLVMはPATHに追加されないため、llcとclangはこの場所からも探します。
+    /// ほかの方法でインストールしたLLVMも使えるため、見つからない場合は警告にします。
+    pub fn check_homebrew_llvm(&self) -> Check {
+        let name = "homebrew llvm";
+        match HOMEBREW_LLVM_DIRS.iter().map(Path::new).find(|dir| dir.is_dir()) {
+            Some(dir) => Check::pass(name, dir.display().to_string()),
+            None => Check {
+                name,
+                status: CheckStatus::Warn,
+                detail: format!("llvm@18 is not installed in {}", HOMEBREW_LLVM_DIRS.join(" or ")),
+                fix: Some("brew install llvm@18".to_string()),
+            },
+        }
+    }
+
+    /// LLVM 18のツールをインストールするコマンド
+    pub fn llvm_install_command(&self, tool: Tool) -> String {
+        match (self.target.os, tool) {
+            (TargetOs::MacOs, _) => "brew install llvm@18".to_string(),
+            (TargetOs::Linux, Tool::Clang) => "sudo apt install clang-18".to_string(),
+            (TargetOs::Linux, _) => "sudo apt install llvm-18".to_string(),
+            (TargetOs::Windows, _) => "winget install LLVM.LLVM --version 18.1.8".to_string(),
+            (TargetOs::Other, _) => "install LLVM 18 from https://releases.llvm.org".to_string(),
+        }
+    }
+
+    /// rustcをインストールするコマンド
+    pub fn rustup_install_command(&self) -> String {
+        if self.target.is_windows() {
+            "winget install Rustlang.Rustup".to_string()
+        } else {
+            "curl --proto '=https' --tlsv1.2 -sSf https://sh.rustup.rs | sh".to_string()
+        }
+    }
+}
+
@@ -5,10 +5,15 @@
 
 use crate::ast::Span;
 use codespan_reporting::diagnostic::{Diagnostic, Label};
+use codespan_reporting::files::Files;
 use thiserror::Error;
 
 /// Yuniコンパイラの統一エラー型
+///
+/// 診断の種類は今後も増えるため、この型と各フェーズのエラー型は `#[non_exhaustive]` です。
+/// クレートの外で照合する場合は `_` の分岐が必要です。
 #[derive(Error, Debug, Clone)]
+#[non_exhaustive]
 pub enum YuniError {
     /// レキサーエラー
     #[error("字句解析エラー")]
@@ -26,6 +31,25 @@ pub enum YuniError {
     #[error("コード生成エラー")]
     Codegen(#[from] CodegenError),
 
+    /// インタプリタ実行時エラー
+    #[error("実行時エラー")]
+    Runtime(#[from] RuntimeError),
+
+    /// 生成するコードの規模に関する診断
+    #[error("{0}")]
+    CodeSize(#[from] CodeSizeError),
+
+    /// 単相化で生成したインスタンスの中で発生したエラー
+    ///
+    /// `instance` は `max<T=f64>` 形式の元のジェネリック定義と型引数、
+    /// `requested_at` はインスタンス化を最初に要求した箇所です。
+    #[error("{error}（{instance} のインスタンス化の中で発生）")]
+    Instantiation {
+        error: Box<YuniError>,
+        instance: String,
+        requested_at: Span,
+    },
+
     /// ファイルI/Oエラー
     #[error("ファイル操作エラー: {0}")]
     Io(String),
@@ -37,6 +61,7 @@ pub enum YuniError {
 
 /// レキサーエラーの詳細
 #[derive(Error, Debug, Clone)]
+#[non_exhaustive]
 #[allow(dead_code)]
 pub enum LexerError {
     #[error("認識できないトークン: '{token}'")]
@@ -45,6 +70,9 @@ pub enum LexerError {
     #[error("未終了の文字列リテラル")]
     UnterminatedString { span: Span },
 
+    #[error("未終了のブロックコメント")]
+    UnterminatedComment { span: Span },
+
     #[error("不正な数値リテラル: {message}")]
     InvalidNumber { message: String, span: Span },
 
@@ -54,6 +82,7 @@ pub enum LexerError {
 
 /// パーサーエラーの詳細
 #[derive(Error, Debug, Clone)]
+#[non_exhaustive]
 #[allow(dead_code)]
 pub enum ParserError {
     #[error("予期しないトークン: {expected}を期待しましたが、{found}が見つかりました")]
@@ -70,11 +99,25 @@ pub enum ParserError {
     InvalidSyntax { message: String, span: Span },
 
     #[error("構文エラー: {message}")]
-    SyntaxError { message: String, span: Span },
+    SyntaxError { message: String, span: Span, context: Option<ParseContext> },
+
+    /// 警告: アイテムの直前にないドキュメントコメント
+    #[error("ドキュメントコメントの後にアイテムがありません")]
+    MisplacedDocComment { span: Span },
+}
+
+/// 構文エラーが起きたときに解析していた構文要素
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseContext {
+    /// 構文要素の説明（``struct `Point`'s fields`` など）
+    pub description: String,
+    /// 構文要素の開始位置
+    pub span: Span,
 }
 
 /// セマンティック解析エラーの詳細
 #[derive(Error, Debug, Clone)]
+#[non_exhaustive]
 pub enum AnalyzerError {
     #[error("未定義の変数: {name}")]
     UndefinedVariable { name: String, span: Span },
@@ -104,11 +147,17 @@ pub enum AnalyzerError {
     #[error("{name} の型を推論できません")]
     TypeInferenceError { name: String, span: Span },
 
+    #[error("{name} の型引数の数が一致しません: {expected}個を期待しましたが、{found}個が見つかりました")]
+    TypeArgumentCountMismatch { name: String, expected: usize, found: usize, span: Span },
+
+    #[error("{name} はジェネリックではないため、型引数を指定できません")]
+    UnexpectedTypeArguments { name: String, span: Span },
+
     #[error("不正な操作: {message}")]
     InvalidOperation { message: String, span: Span },
 
     #[error("不変変数 {name} を変更することはできません")]
-    ImmutableVariable { name: String, span: Span },
+    ImmutableVariable { name: String, span: Span, declared_at: Span },
 
     #[error("関数 {name} にreturn文がありません")]
     MissingReturn { name: String, span: Span },
@@ -120,33 +169,210 @@ pub enum AnalyzerError {
     NonExhaustiveMatch { span: Span },
 
     #[error("移動された値 {name} を使用しようとしました")]
-    UseAfterMove { name: String, span: Span },
+    UseAfterMove { name: String, span: Span, moved_at: Span },
 
     #[error("借用された値 {name} を移動しようとしました")]
-    MoveWhileBorrowed { name: String, span: Span },
+    MoveWhileBorrowed { name: String, span: Span, borrowed_at: Span },
+
+    #[error("借用された値 {name} を変更しようとしました")]
+    AssignWhileBorrowed { name: String, span: Span, borrowed_at: Span },
     
     #[error("複数の可変借用: {name}")]
-    MultipleMutableBorrows { name: String, span: Span },
+    MultipleMutableBorrows { name: String, span: Span, borrowed_at: Span },
     
-    #[error("不変借用中の可変借用: {name}")]
-    MutableBorrowConflict { name: String, span: Span },
+    #[error("可変借用と不変借用の競合: {name}")]
+    MutableBorrowConflict { name: String, span: Span, borrowed_at: Span },
     
     #[error("引数の数が一致しません: {expected}個を期待しましたが、{found}個が見つかりました")]
-    ArgumentCountMismatch { expected: usize, found: usize, span: Span },
+    ArgumentCountMismatch { expected: usize, found: usize, span: Span, callee: Option<Box<Callee>> },
+
+    #[error("引数 {param}（{}番目）の型の不一致: {expected}を期待しましたが、{found}が見つかりました", .index + 1)]
+    ArgumentTypeMismatch {
+        index: usize,
+        param: String,
+        expected: String,
+        found: String,
+        span: Span,
+        callee: Box<Callee>,
+        /// `index` 番目と入れ替えると型が一致する引数の位置（0始まり）
+        swap_with: Option<usize>,
+    },
     
+    #[error("{name} は値を返さないため、{usage}には使えません")]
+    VoidValue { name: String, usage: String, span: Span, callee: Option<Box<Callee>> },
+
     #[error("メソッド {method} が型 {ty} に見つかりません")]
-    MethodNotFound { method: String, ty: String, span: Span },
+    MethodNotFound { method: String, ty: String, available: Vec<String>, span: Span },
     
     #[error("一時的な値の参照を取得することはできません")]
-    #[allow(dead_code)]
     TemporaryReference { span: Span },
     
     #[error("到達不能コード")]
     UnreachableCode { span: Span },
+
+    #[error("{left} と {right} の演算には明示的なキャストが必要です")]
+    MixedNumericTypes { left: String, right: String, suggestion: String, span: Span },
+
+    #[error("deferブロックからreturnすることはできません")]
+    ReturnInDefer { span: Span },
+
+    #[error("論理否定 `!` は {ty} には使えません")]
+    LogicalNotOnInteger { ty: String, suggestion: String, span: Span },
+
+    #[error("{name} は標準ライブラリの {path} を隠しています")]
+    ShadowsPrelude { name: String, path: String, span: Span },
+
+    #[error("未定義の名前: {name}（no_preludeのため標準ライブラリの名前はインポートが必要です）")]
+    PreludeDisabled { name: String, path: String, span: Span },
+
+    #[error("標準ライブラリに {path} はありません")]
+    UnknownStdImport { path: String, span: Span },
+
+    #[error("{path} に {name} はありません")]
+    UnknownImportSymbol { name: String, path: String, available: Vec<String>, span: Span },
+
+    #[error("{path} からの選択インポートはまだ解決できません")]
+    UnresolvedImportSymbols { path: String, span: Span },
+
+    #[error("{name} は既にインポートされています")]
+    DuplicateImport { name: String, span: Span, previous: Span },
+
+    #[error("パターン {pattern} は前のアームで既に処理されています")]
+    DuplicateMatchArm { pattern: String, span: Span, previous: Span },
+
+    #[error("マップリテラルのキー {key} が重複しています")]
+    DuplicateMapKey { key: String, span: Span, previous: Span },
+
+    #[error("`in` の右辺に {ty} は使えません")]
+    InvalidMembershipContainer { ty: String, span: Span },
+
+    #[error("orパターンの選択肢によって束縛する変数が異なります: {}", names.join(", "))]
+    OrPatternBindingMismatch { names: Vec<String>, span: Span },
+
+    #[error("{name} の定義がインポートした {name} と衝突しています")]
+    ImportConflictsWithDefinition { name: String, span: Span, import_span: Span },
+
+    #[error("インポートした {name} は使われていません")]
+    UnusedImport { name: String, span: Span },
+
+    #[error("{name} の定義が {path} からインポートした {name} を隠しています")]
+    ShadowedImport { name: String, path: String, span: Span, import_span: Span },
+
+    #[error("関数 {name} には引数が{arity}個の定義がありません")]
+    NoOverloadForArity { name: String, arity: usize, arities: Vec<usize>, span: Span },
+
+    #[error("型 {name} の定義が自身を含んでいます")]
+    RecursiveType { name: String, span: Span },
+
+    #[error("再帰する {name} に inline(always) は指定できません")]
+    RecursiveInlineAlways { name: String, cycle: Vec<String>, span: Span },
+
+    #[error("ループの条件の変数 {} がループ本体で変更されていません", variables.join(", "))]
+    LoopConditionNotModified { variables: Vec<String>, span: Span },
+
+    #[error("returnのない無条件ループは終了しません")]
+    InfiniteLoop { span: Span },
+
+    #[error("定数 {value} は {target} の範囲外のため、キャストで値が変わります")]
+    LossyConstantCast { value: String, target: String, span: Span },
+
+    #[error("条件 {condition} は常に偽のため、この分岐は実行されません")]
+    BranchNeverTaken { condition: String, span: Span, sources: Vec<ConstantSource> },
+
+    #[error("ガード {guard} は常に偽のため、このアームは実行されません")]
+    MatchArmNeverTaken { guard: String, span: Span, sources: Vec<ConstantSource> },
+
+    #[error("比較 {comparison} は {ty} の値の範囲のため常に {result} です")]
+    ComparisonAlwaysConstant { comparison: String, result: bool, ty: String, range: String, span: Span },
+
+    #[error("bool値と {literal} の比較 {comparison} は冗長です")]
+    RedundantBoolComparison { comparison: String, literal: bool, suggestion: String, span: Span },
+
+    #[error("整数リテラル {literal} は {ty} の範囲外です")]
+    IntegerLiteralOutOfRange { literal: String, ty: String, min: String, max: String, span: Span },
+
+    #[error("ローカル変数 {name} への参照を返すことはできません")]
+    ReturnsLocalReference { name: String, span: Span, declared_at: Span },
+
+    #[error("関数 {name} の戻り値の参照の借用元をlives句で指定する必要があります")]
+    MissingLivesClause { name: String, suggestion: String, span: Span },
+
+    #[error("戻り値の参照は引数 {param} から借用されていますが、lives句に含まれていません")]
+    UnlistedReturnSource { param: String, suggestion: String, span: Span },
+
+    #[error("{borrowed} は破棄されているため、{borrowed} を借用している {name} は使えません")]
+    BorrowedValueDropped { name: String, borrowed: String, span: Span, dropped_at: Span },
+
+    #[error("{name} の引数は比較できる型である必要があります: {ty}")]
+    AssertNotComparable { name: String, ty: String, span: Span },
+
+    #[error("{ty} の値は文字列に補間できません")]
+    NotDisplayable { ty: String, span: Span },
+
+    #[error("外部関数 {name} の引数と戻り値にCと受け渡せない型 {ty} は使えません")]
+    NonFfiSafeType { name: String, ty: String, span: Span },
+
+    #[error("タプルパターンの要素数が一致しません: {ty} の要素は{expected}個ですが、パターンは{found}個です")]
+    TuplePatternArityMismatch { ty: String, expected: usize, found: usize, span: Span },
+
+    #[error("構造体 {ty} にフィールド {field} はありません")]
+    UnknownPatternField { ty: String, field: String, available: Vec<String>, span: Span },
+
+    #[error("列挙型 {ty} にバリアント {variant} はありません")]
+    UnknownPatternVariant { ty: String, variant: String, available: Vec<String>, span: Span },
+
+    #[error("バリアント {variant} にフィールド {field} はありません")]
+    UnknownVariantPatternField { variant: String, field: String, available: Vec<String>, span: Span },
+
+    #[error("型 {ty} に関連定数 {name} はありません")]
+    UnknownAssociatedConstant { ty: String, name: String, available: Vec<String>, span: Span },
+
+    #[error("let文では変数に分解できないパターン {pattern} は使えません")]
+    RefutableLetPattern { pattern: String, span: Span },
+
+    #[error("関数 {name} は未定義の関数 {} を呼び出すため、まだ実行できません", missing.join(", "))]
+    IncompleteFunction { name: String, missing: Vec<String>, span: Span },
+
+    #[error("ローカル関数 {function} は外側の変数 {name} を捕捉できません")]
+    LocalFunctionCapture { name: String, function: String, span: Span },
+
+    #[error("内部エラー: {message}")]
+    Internal { message: String, span: Span },
+}
+
+/// 呼び出された関数の情報
+///
+/// 引数のエラーで、関数の定義を開かなくてもシグネチャがわかるようにします。
+#[derive(Debug, Clone)]
+pub struct Callee {
+    /// シグネチャ（`fn add(a: i32, b: i32): i32`）
+    pub signature: String,
+    /// 各引数の宣言の位置（組み込み関数では空）
+    pub param_spans: Vec<Span>,
+}
+
+impl Callee {
+    /// 引数の宣言全体の位置
+    fn params_span(&self) -> Option<Span> {
+        let (first, last) = (self.param_spans.first()?, self.param_spans.last()?);
+        Some(Span::new(first.start, last.end))
+    }
+}
+
+/// 常に同じ値になる条件の根拠となった定数
+#[derive(Debug, Clone)]
+pub struct ConstantSource {
+    /// 変数名（リテラルのmatch対象では「match対象」）
+    pub name: String,
+    /// 値の表記（`false`・`3`）
+    pub value: String,
+    /// 値を決めた宣言や式の位置
+    pub span: Span,
 }
 
 /// コード生成エラーの詳細
 #[derive(Error, Debug, Clone)]
+#[non_exhaustive]
 #[allow(dead_code)]
 pub enum CodegenError {
     #[error("LLVM初期化エラー: {message}")]
@@ -166,11 +392,104 @@ pub enum CodegenError {
     
     #[error("未定義: {name}")]
     Undefined { name: String, span: Span },
+
+    #[error("未定義のバリアント: {enum_name}::{variant}（有効なバリアント: {valid}）")]
+    UndefinedVariant { enum_name: String, variant: String, valid: String, span: Span },
     
     #[error("コンパイル失敗: {message}")]
     CompilationFailed { message: String, span: Span },
 }
 
+/// インタプリタ実行時エラーの詳細
+#[derive(Error, Debug, Clone)]
+#[non_exhaustive]
+pub enum RuntimeError {
+    #[error("ゼロ除算")]
+    DivisionByZero { span: Span },
+
+    #[error("インデックスが範囲外です: 長さ {len}, インデックス {index}")]
+    IndexOutOfBounds { index: i128, len: usize, span: Span },
+
+    #[error("インデックスが範囲外のため代入できません: 長さ {len}, インデックス {index}（要素を追加するには push を、長さを変えるには resize を使ってください）")]
+    IndexAssignmentOutOfBounds { index: i128, len: usize, span: Span },
+
+    #[error("未定義: {name}")]
+    Undefined { name: String, span: Span },
+
+    #[error("不正な値: {message}")]
+    InvalidValue { message: String, span: Span },
+
+    #[error("インタプリタでは未対応の機能: {feature}")]
+    Unsupported { feature: String, span: Span },
+
+    #[error("キャストで値が失われます: {from} の値 {value} は {to} の範囲外です")]
+    CastOutOfRange { value: String, from: String, to: String, span: Span },
+
+    #[error("{message}")]
+    AssertionFailed { message: String, span: Span },
+
+    #[error("評価の手順数が上限の{limit}回を超えました")]
+    StepLimitExceeded { limit: u64, span: Span },
+}
+
+/// 生成するコードの規模に関する診断の詳細
+///
+/// `roots` はインスタンスを生成した起点のジェネリック定義と、その規模（多い順）です。
+#[derive(Error, Debug, Clone)]
+#[non_exhaustive]
+pub enum CodeSizeError {
+    #[error("ジェネリクスのインスタンスが多すぎます: {count}個（警告のしきい値 {threshold}個）")]
+    TooManyInstantiations {
+        count: usize,
+        threshold: usize,
+        roots: Vec<(String, usize)>,
+    },
+
+    #[error("ジェネリクスのインスタンスが上限の{limit}個を超えました: {}", format_roots(.roots))]
+    InstantiationLimitExceeded {
+        limit: usize,
+        roots: Vec<(String, usize)>,
+    },
+
+    #[error("LLVM IRモジュールが大きすぎます: 関数 {functions}個、命令 {instructions}個（警告のしきい値 関数 {max_functions}個、命令 {max_instructions}個）")]
+    ModuleTooLarge {
+        functions: usize,
+        instructions: usize,
+        max_functions: usize,
+        max_instructions: usize,
+        roots: Vec<(String, usize)>,
+    },
+}
+
+impl CodeSizeError {
+    /// 起点ごとの内訳の見出し
+    fn roots_heading(&self) -> &'static str {
+        match self {
+            CodeSizeError::TooManyInstantiations { .. }
+            | CodeSizeError::InstantiationLimitExceeded { .. } => "インスタンス数の多いジェネリック定義",
+            CodeSizeError::ModuleTooLarge { .. } => "命令数の多い起点",
+        }
+    }
+
+    /// 起点ごとの内訳
+    pub fn roots(&self) -> &[(String, usize)] {
+        match self {
+            CodeSizeError::TooManyInstantiations { roots, .. }
+            | CodeSizeError::InstantiationLimitExceeded { roots, .. }
+            | CodeSizeError::ModuleTooLarge { roots, .. } => roots,
+        }
+    }
+}
+
+/// 起点の一覧を「名前 (数)」の形式で連結する
+fn format_roots(roots: &[(String, usize)]) -> String {
+    roots
+        .iter()
+        .map(|(name, count)| format!("{} ({})", name, count))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
 /// エラー情報とソースコードの位置情報を含むエラー
 #[derive(Debug, Clone)]
 pub struct DiagnosticError {
@@ -197,6 +516,11 @@ impl DiagnosticError {
                     vec![Label::primary(self.file_id, span.start..span.end)
                         .with_message("文字列が閉じられていません")],
                 ),
+                LexerError::UnterminatedComment { span } => (
+                    "未終了のブロックコメント".to_string(),
+                    vec![Label::primary(self.file_id, span.start..span.end)
+                        .with_message("コメントが閉じられていません")],
+                ),
                 LexerError::InvalidNumber { message, span } => (
                     format!("不正な数値リテラル: {}", message),
                     vec![Label::primary(self.file_id, span.start..span.end)],
@@ -219,9 +543,19 @@ impl DiagnosticError {
                     format!("不正な構文: {}", message),
                     vec![Label::primary(self.file_id, span.start..span.end)],
                 ),
-                ParserError::SyntaxError { message, span } => (
+                ParserError::SyntaxError { message, span, context } => (
                     format!("構文エラー: {}", message),
-                    vec![Label::primary(self.file_id, span.start..span.end)],
+                    std::iter::once(Label::primary(self.file_id, span.start..span.end))
+                        .chain(context.iter().map(|context| {
+                            Label::secondary(self.file_id, context.span.start..context.span.end)
+                                .with_message(format!("while parsing {}, starting here", context.description))
+                        }))
+                        .collect(),
+                ),
+                ParserError::MisplacedDocComment { span } => (
+                    "ドキュメントコメントの後にアイテムがありません".to_string(),
+                    vec![Label::primary(self.file_id, span.start..span.end)
+                        .with_message("`///` は関数・型・メソッドの直前に書きます（通常のコメントは `//`）")],
                 ),
             },
             YuniError::Analyzer(e) => self.analyzer_error_to_diagnostic(e),
@@ -250,11 +584,67 @@ impl DiagnosticError {
                     format!("未定義: {}", name),
                     vec![Label::primary(self.file_id, span.start..span.end)],
                 ),
+                CodegenError::UndefinedVariant { enum_name, variant, span, .. } => (
+                    format!("未定義のバリアント: {}::{}", enum_name, variant),
+                    vec![Label::primary(self.file_id, span.start..span.end)
+                        .with_message(format!("{} にこのバリアントはありません", enum_name))],
+                ),
                 CodegenError::CompilationFailed { message, span } => (
                     format!("コンパイル失敗: {}", message),
                     vec![Label::primary(self.file_id, span.start..span.end)],
                 ),
             },
+            YuniError::Runtime(e) => match e {
+                RuntimeError::DivisionByZero { span } => (
+                    "ゼロ除算".to_string(),
+                    vec![Label::primary(self.file_id, span.start..span.end)
+                        .with_message("除数が0です")],
+                ),
+                RuntimeError::IndexOutOfBounds { index, len, span } => (
+                    format!("インデックスが範囲外です: 長さ {}, インデックス {}", len, index),
+                    vec![Label::primary(self.file_id, span.start..span.end)],
+                ),
+                RuntimeError::IndexAssignmentOutOfBounds { index, len, span } => (
+                    format!("インデックスが範囲外のため代入できません: 長さ {}, インデックス {}", len, index),
+                    vec![Label::primary(self.file_id, span.start..span.end)
+                        .with_message("要素を追加するには push を、長さを変えるには resize を使ってください")],
+                ),
+                RuntimeError::Undefined { name, span } => (
+                    format!("未定義: {}", name),
+                    vec![Label::primary(self.file_id, span.start..span.end)],
+                ),
+                RuntimeError::InvalidValue { message, span } => (
+                    format!("不正な値: {}", message),
+                    vec![Label::primary(self.file_id, span.start..span.end)],
+                ),
+                RuntimeError::Unsupported { feature, span } => (
+                    format!("インタプリタでは未対応の機能: {}", feature),
+                    vec![Label::primary(self.file_id, span.start..span.end)],
+                ),
+                RuntimeError::CastOutOfRange { value, from, to, span } => (
+                    format!("キャストで値が失われます: {} の値 {} は {} の範囲外です", from, value, to),
+                    vec![Label::primary(self.file_id, span.start..span.end)
+                        .with_message("--checked-casts により検査されたキャストです")],
+                ),
+                RuntimeError::AssertionFailed { message, span } => (
+                    message.clone(),
+                    vec![Label::primary(self.file_id, span.start..span.end)
+                        .with_message("このアサーションが失敗しました")],
+                ),
+                RuntimeError::StepLimitExceeded { limit, span } => (
+                    format!("評価の手順数が上限の{}回を超えました", limit),
+                    vec![Label::primary(self.file_id, span.start..span.end)
+                        .with_message("止まらないループか深い再帰の可能性があります")],
+                ),
+            },
+            YuniError::CodeSize(e) => (e.to_string(), vec![]),
+            YuniError::Instantiation { error, instance, requested_at } => {
+                let inner = DiagnosticError::new((**error).clone(), self.file_id).to_diagnostic();
+                let mut labels = inner.labels;
+                labels.push(Label::secondary(self.file_id, requested_at.start..requested_at.end)
+                    .with_message(format!("{} のインスタンス化はここで要求されています", instance)));
+                (inner.message, labels)
+            }
             YuniError::Io(message) => (
                 format!("ファイル操作エラー: {}", message),
                 vec![],
@@ -268,6 +658,60 @@ impl DiagnosticError {
         Diagnostic::error()
             .with_message(message)
             .with_labels(labels)
+            .with_notes(self.notes())
+    }
+
+    /// 補足情報
+    fn notes(&self) -> Vec<String> {
+        match &self.error {
+            YuniError::CodeSize(e) if !e.roots().is_empty() => {
+                let breakdown = e
+                    .roots()
+                    .iter()
+                    .map(|(name, count)| format!("  {}: {}", name, count))
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                vec![format!("{}:\n{}", e.roots_heading(), breakdown)]
+            }
+            YuniError::Instantiation { error, .. } => {
+                DiagnosticError::new((**error).clone(), self.file_id).notes()
+            }
+            YuniError::Analyzer(e) => analyzer_notes(e),
+            YuniError::Codegen(CodegenError::UndefinedVariant { valid, .. }) => {
+                vec![format!("有効なバリアント: {}", valid)]
+            }
+            _ => vec![],
+        }
+    }
+
+    /// ソースファイルの情報を使ってDiagnosticに変換
+    ///
+    /// 単相化で生成したインスタンスの中のエラーには、元のジェネリック定義と
+    /// インスタンス化を要求した箇所（`ファイル:行:列`）を補足情報として付けます。
+    pub fn to_diagnostic_in<'a, F>(&self, files: &'a F) -> Diagnostic<usize>
+    where
+        F: Files<'a, FileId = usize>,
+    {
+        let mut diagnostic = self.to_diagnostic();
+        if let YuniError::Instantiation { instance, requested_at, .. } = &self.error {
+            let requested = match (files.name(self.file_id), files.location(self.file_id, requested_at.start)) {
+                (Ok(name), Ok(location)) => {
+                    format!("{}:{}:{}", name, location.line_number, location.column_number)
+                }
+                _ => format!("{}..{}", requested_at.start, requested_at.end),
+            };
+            diagnostic.notes.push(format!("{} のインスタンス化（{} で要求）の中で発生しました", instance, requested));
+        }
+        diagnostic
+    }
+
+    /// 借用規則の違反のラベル（違反した箇所と、競合する借用・移動の箇所）
+    fn conflict_labels(&self, span: Span, message: &str, other: Span, other_message: &str) -> Vec<Label<usize>> {
+        let mut labels = vec![Label::primary(self.file_id, span.start..span.end).with_message(message)];
+        if other.start != other.end {
+            labels.push(Label::secondary(self.file_id, other.start..other.end).with_message(other_message));
+        }
+        labels
     }
 
     fn analyzer_error_to_diagnostic(&self, e: &AnalyzerError) -> (String, Vec<Label<usize>>) {
@@ -311,15 +755,29 @@ impl DiagnosticError {
                 vec![Label::primary(self.file_id, span.start..span.end)
                     .with_message("型注釈を追加してください")],
             ),
+            AnalyzerError::TypeArgumentCountMismatch { name, expected, found, span } => (
+                format!("{} の型引数の数が一致しません: {}個を期待しましたが、{}個が見つかりました", name, expected, found),
+                vec![Label::primary(self.file_id, span.start..span.end)
+                    .with_message(format!("型引数は{}個です", expected))],
+            ),
+            AnalyzerError::UnexpectedTypeArguments { name, span } => (
+                format!("{} はジェネリックではないため、型引数を指定できません", name),
+                vec![Label::primary(self.file_id, span.start..span.end)
+                    .with_message("型引数を削除してください")],
+            ),
             AnalyzerError::InvalidOperation { message, span } => (
                 format!("不正な操作: {}", message),
                 vec![Label::primary(self.file_id, span.start..span.end)],
             ),
-            AnalyzerError::ImmutableVariable { name, span } => (
-                format!("不変変数 {} を変更することはできません", name),
-                vec![Label::primary(self.file_id, span.start..span.end)
-                    .with_message("この変数はmutで宣言されていません")],
-            ),
+            AnalyzerError::ImmutableVariable { name, span, declared_at } => {
+                let mut labels = vec![Label::primary(self.file_id, span.start..span.end)
+                    .with_message("この変数はmutで宣言されていません")];
+                if declared_at.start != declared_at.end {
+                    labels.push(Label::secondary(self.file_id, declared_at.start..declared_at.end)
+                        .with_message("ここで宣言されています（変更するには `mut` を付けてください）"));
+                }
+                (format!("不変変数 {} を変更することはできません", name), labels)
+            }
             AnalyzerError::MissingReturn { name, span } => (
                 format!("関数 {} にreturn文がありません", name),
                 vec![Label::primary(self.file_id, span.start..span.end)
@@ -334,31 +792,52 @@ impl DiagnosticError {
                 vec![Label::primary(self.file_id, span.start..span.end)
                     .with_message("すべてのケースを処理する必要があります")],
             ),
-            AnalyzerError::UseAfterMove { name, span } => (
+            AnalyzerError::UseAfterMove { name, span, moved_at } => (
                 format!("移動された値 {} を使用しようとしました", name),
-                vec![Label::primary(self.file_id, span.start..span.end)
-                    .with_message("この値は既に移動されています")],
+                self.conflict_labels(*span, "移動した後に使用しています", *moved_at, "ここで移動されています"),
             ),
-            AnalyzerError::MoveWhileBorrowed { name, span } => (
+            AnalyzerError::MoveWhileBorrowed { name, span, borrowed_at } => (
                 format!("借用された値 {} を移動しようとしました", name),
-                vec![Label::primary(self.file_id, span.start..span.end)
-                    .with_message("この値は借用されています")],
+                self.conflict_labels(*span, "借用中に移動しています", *borrowed_at, "ここで借用されています"),
+            ),
+            AnalyzerError::AssignWhileBorrowed { name, span, borrowed_at } => (
+                format!("借用された値 {} を変更しようとしました", name),
+                self.conflict_labels(*span, "借用中に変更しています", *borrowed_at, "ここで借用されています"),
             ),
-            AnalyzerError::MultipleMutableBorrows { name, span } => (
+            AnalyzerError::MultipleMutableBorrows { name, span, borrowed_at } => (
                 format!("複数の可変借用: {}", name),
-                vec![Label::primary(self.file_id, span.start..span.end)
-                    .with_message("この値は既に可変借用されています")],
+                self.conflict_labels(*span, "2つ目の可変借用です", *borrowed_at, "最初の可変借用はここです"),
             ),
-            AnalyzerError::MutableBorrowConflict { name, span } => (
-                format!("不変借用中の可変借用: {}", name),
-                vec![Label::primary(self.file_id, span.start..span.end)
-                    .with_message("この値は不変借用されています")],
+            AnalyzerError::MutableBorrowConflict { name, span, borrowed_at } => (
+                format!("可変借用と不変借用の競合: {}", name),
+                self.conflict_labels(*span, "ここで借用しています", *borrowed_at, "競合する借用はここです"),
             ),
-            AnalyzerError::ArgumentCountMismatch { expected, found, span } => (
-                format!("引数の数が一致しません: {}個を期待しましたが、{}個が見つかりました", expected, found),
-                vec![Label::primary(self.file_id, span.start..span.end)],
+            AnalyzerError::ArgumentCountMismatch { expected, found, span, callee } => {
+                let mut labels = vec![Label::primary(self.file_id, span.start..span.end)];
+                if let Some(params_span) = callee.as_ref().and_then(|callee| callee.params_span()) {
+                    labels.push(Label::secondary(self.file_id, params_span.start..params_span.end)
+                        .with_message(format!("引数はここで{}個宣言されています", expected)));
+                }
+                (format!("引数の数が一致しません: {}個を期待しましたが、{}個が見つかりました", expected, found), labels)
+            }
+            AnalyzerError::ArgumentTypeMismatch { index, param, expected, found, span, callee, .. } => {
+                let mut labels = vec![Label::primary(self.file_id, span.start..span.end)
+                    .with_message(format!("この引数の型は {} です", found))];
+                if let Some(param_span) = callee.param_spans.get(*index) {
+                    labels.push(Label::secondary(self.file_id, param_span.start..param_span.end)
+                        .with_message(format!("引数 {} はここで {} として宣言されています", param, expected)));
+                }
+                (
+                    format!("引数 {}（{}番目）の型の不一致: {}を期待しましたが、{}が見つかりました", param, index + 1, expected, found),
+                    labels,
+                )
+            }
+            AnalyzerError::VoidValue { name, usage, span, .. } => (
+                format!("{} は値を返さないため、{}には使えません", name, usage),
+                vec![Label::primary(self.file_id, span.start..span.end)
+                    .with_message("この呼び出しは値を返しません")],
             ),
-            AnalyzerError::MethodNotFound { method, ty, span } => (
+            AnalyzerError::MethodNotFound { method, ty, span, .. } => (
                 format!("メソッド {} が型 {} に見つかりません", method, ty),
                 vec![Label::primary(self.file_id, span.start..span.end)
                     .with_message("このメソッドは定義されていません")],
@@ -373,12 +852,365 @@ impl DiagnosticError {
                 vec![Label::primary(self.file_id, span.start..span.end)
                     .with_message("このコードは実行されません")],
             ),
+            AnalyzerError::MixedNumericTypes { left, right, suggestion, span } => (
+                format!("{} と {} の演算には明示的なキャストが必要です", left, right),
+                vec![Label::primary(self.file_id, span.start..span.end)
+                    .with_message(suggestion.clone())],
+            ),
+            AnalyzerError::ReturnInDefer { span } => (
+                "deferブロックからreturnすることはできません".to_string(),
+                vec![Label::primary(self.file_id, span.start..span.end)
+                    .with_message("deferブロックは囲んでいるブロックの終了時に実行されます")],
+            ),
+            AnalyzerError::LogicalNotOnInteger { ty, suggestion, span } => (
+                format!("論理否定 `!` は {} には使えません", ty),
+                vec![Label::primary(self.file_id, span.start..span.end)
+                    .with_message(suggestion.clone())],
+            ),
+            AnalyzerError::ShadowsPrelude { name, path, span } => (
+                format!("{} は標準ライブラリの {} を隠しています", name, path),
+                vec![Label::primary(self.file_id, span.start..span.end)
+                    .with_message(format!("このファイルでは {} はこの定義を指します", name))],
+            ),
+            AnalyzerError::PreludeDisabled { name, path, span } => (
+                format!("未定義の名前: {}（no_preludeのため標準ライブラリの名前はインポートが必要です）", name),
+                vec![Label::primary(self.file_id, span.start..span.end)
+                    .with_message(format!("`import \"{}\"` を追加してください", path))],
+            ),
+            AnalyzerError::UnknownStdImport { path, span } => (
+                format!("標準ライブラリに {} はありません", path),
+                vec![Label::primary(self.file_id, span.start..span.end)
+                    .with_message("このインポートは解決できません")],
+            ),
+            AnalyzerError::UnknownImportSymbol { name, path, span, .. } => (
+                format!("{} に {} はありません", path, name),
+                vec![Label::primary(self.file_id, span.start..span.end)
+                    .with_message("このインポートは解決できません")],
+            ),
+            AnalyzerError::UnresolvedImportSymbols { path, span } => (
+                format!("{} からの選択インポートはまだ解決できません", path),
+                vec![Label::primary(self.file_id, span.start..span.end)
+                    .with_message("ファイルをまたぐ名前解決は未対応のため、要素が存在するかは確認されません")],
+            ),
+            AnalyzerError::DuplicateImport { name, span, previous } => (
+                format!("{} は既にインポートされています", name),
+                vec![
+                    Label::primary(self.file_id, span.start..span.end)
+                        .with_message("ここで再びインポートしています"),
+                    Label::secondary(self.file_id, previous.start..previous.end)
+                        .with_message(format!("{} はここでインポートされています", name)),
+                ],
+            ),
+            AnalyzerError::DuplicateMatchArm { pattern, span, previous } => (
+                format!("パターン {} は前のアームで既に処理されています", pattern),
+                vec![
+                    Label::primary(self.file_id, span.start..span.end)
+                        .with_message("このアームには到達しません"),
+                    Label::secondary(self.file_id, previous.start..previous.end)
+                        .with_message(format!("{} はここで処理されています", pattern)),
+                ],
+            ),
+            AnalyzerError::DuplicateMapKey { key, span, previous } => (
+                format!("マップリテラルのキー {} が重複しています", key),
+                vec![
+                    Label::primary(self.file_id, span.start..span.end)
+                        .with_message("同じキーを再び指定しています（後の値で上書きされます）"),
+                    Label::secondary(self.file_id, previous.start..previous.end)
+                        .with_message(format!("{} はここで指定されています", key)),
+                ],
+            ),
+            AnalyzerError::InvalidMembershipContainer { ty, span } => (
+                format!("`in` の右辺に {} は使えません", ty),
+                vec![Label::primary(self.file_id, span.start..span.end)
+                    .with_message("`in` に使えるのは Vec<T>（要素）・HashMap<K, V>（キー）・String（部分文字列）です")],
+            ),
+            AnalyzerError::OrPatternBindingMismatch { names, span } => (
+                format!("orパターンの選択肢によって束縛する変数が異なります: {}", names.join(", ")),
+                vec![Label::primary(self.file_id, span.start..span.end)
+                    .with_message(format!("{} を束縛しない選択肢か、型の異なる選択肢があります", names.join(", ")))],
+            ),
+            AnalyzerError::ImportConflictsWithDefinition { name, span, import_span } => (
+                format!("{} の定義がインポートした {} と衝突しています", name, name),
+                vec![
+                    Label::primary(self.file_id, span.start..span.end)
+                        .with_message("この定義"),
+                    Label::secondary(self.file_id, import_span.start..import_span.end)
+                        .with_message(format!("{} はここでインポートされています", name)),
+                ],
+            ),
+            AnalyzerError::UnusedImport { name, span } => (
+                format!("インポートした {} は使われていません", name),
+                vec![Label::primary(self.file_id, span.start..span.end)
+                    .with_message("このインポートを通して解決された名前はありません")],
+            ),
+            AnalyzerError::ShadowedImport { name, path, span, import_span } => (
+                format!("{} の定義が {} からインポートした {} を隠しています", name, path, name),
+                vec![
+                    Label::primary(self.file_id, span.start..span.end)
+                        .with_message(format!("このファイルでは {} はこの定義を指します", name)),
+                    Label::secondary(self.file_id, import_span.start..import_span.end)
+                        .with_message(format!("{} はここでインポートされています", name)),
+                ],
+            ),
+            AnalyzerError::NoOverloadForArity { name, arity, span, .. } => (
+                format!("関数 {} には引数が{}個の定義がありません", name, arity),
+                vec![Label::primary(self.file_id, span.start..span.end)
+                    .with_message("引数の数が一致するオーバーロードがありません")],
+            ),
+            AnalyzerError::RecursiveType { name, span } => (
+                format!("型 {} の定義が自身を含んでいます", name),
+                vec![Label::primary(self.file_id, span.start..span.end)
+                    .with_message("大きさが決まらないため、参照やVecなどを介して持つ必要があります")],
+            ),
+            AnalyzerError::RecursiveInlineAlways { name, cycle, span } => (
+                format!("再帰する {} に inline(always) は指定できません", name),
+                vec![Label::primary(self.file_id, span.start..span.end)
+                    .with_message(if cycle.len() == 2 {
+                        format!("{} が自身を呼び出しています", name)
+                    } else {
+                        format!("この呼び出しから {} に戻ります", name)
+                    })],
+            ),
+            AnalyzerError::LoopConditionNotModified { variables, span } => (
+                format!("ループの条件の変数 {} がループ本体で変更されていません", variables.join(", ")),
+                vec![Label::primary(self.file_id, span.start..span.end)
+                    .with_message("この条件は反復ごとに同じ値になるため、ループが終了しない可能性があります")],
+            ),
+            AnalyzerError::InfiniteLoop { span } => (
+                "returnのない無条件ループは終了しません".to_string(),
+                vec![Label::primary(self.file_id, span.start..span.end)
+                    .with_message("ループから抜けるreturnがありません")],
+            ),
+            AnalyzerError::LossyConstantCast { value, target, span } => (
+                format!("定数 {} は {} の範囲外のため、キャストで値が変わります", value, target),
+                vec![Label::primary(self.file_id, span.start..span.end)
+                    .with_message("このキャストは常に値を切り詰めます（--checked-casts では実行時にパニックします）")],
+            ),
+            AnalyzerError::BranchNeverTaken { condition, span, sources } => (
+                format!("条件 {} は常に偽のため、この分岐は実行されません", condition),
+                never_taken_labels(self.file_id, *span, "この条件は常に false です", sources),
+            ),
+            AnalyzerError::MatchArmNeverTaken { guard, span, sources } => (
+                format!("ガード {} は常に偽のため、このアームは実行されません", guard),
+                never_taken_labels(self.file_id, *span, "このガードは常に false です", sources),
+            ),
+            AnalyzerError::RedundantBoolComparison { comparison, literal, suggestion, span } => (
+                format!("bool値と {} の比較 {} は冗長です", literal, comparison),
+                vec![Label::primary(self.file_id, span.start..span.end)
+                    .with_message(format!("`{}` と書けます", suggestion))],
+            ),
+            AnalyzerError::ComparisonAlwaysConstant { comparison, result, ty, range, span } => (
+                format!("比較 {} は {} の値の範囲のため常に {} です", comparison, ty, result),
+                vec![Label::primary(self.file_id, span.start..span.end)
+                    .with_message(format!("{} の値の範囲は {} のため、この比較の結果は変わりません", ty, range))],
+            ),
+            AnalyzerError::IntegerLiteralOutOfRange { literal, ty, min, max, span } => (
+                format!("整数リテラル {} は {} の範囲外です", literal, ty),
+                vec![Label::primary(self.file_id, span.start..span.end)
+                    .with_message(format!("{} の範囲は {} から {} までです", ty, min, max))],
+            ),
+            AnalyzerError::ReturnsLocalReference { name, span, declared_at } => (
+                format!("ローカル変数 {} への参照を返すことはできません", name),
+                vec![
+                    Label::primary(self.file_id, span.start..span.end)
+                        .with_message(format!("この値は {} を借用しています", name)),
+                    Label::secondary(self.file_id, declared_at.start..declared_at.end)
+                        .with_message(format!("{} はここで宣言され、関数から戻るときに破棄されます", name)),
+                ],
+            ),
+            AnalyzerError::MissingLivesClause { name, span, .. } => (
+                format!("関数 {} の戻り値の参照の借用元をlives句で指定する必要があります", name),
+                vec![Label::primary(self.file_id, span.start..span.end)
+                    .with_message("参照を持つ引数が複数あるため、戻り値がどれを借用するか決まりません")],
+            ),
+            AnalyzerError::UnlistedReturnSource { param, span, .. } => (
+                format!("戻り値の参照は引数 {} から借用されていますが、lives句に含まれていません", param),
+                vec![Label::primary(self.file_id, span.start..span.end)
+                    .with_message(format!("この値は {} を借用しています", param))],
+            ),
+            AnalyzerError::BorrowedValueDropped { name, borrowed, span, dropped_at } => (
+                format!("{} は破棄されているため、{} を借用している {} は使えません", borrowed, borrowed, name),
+                vec![
+                    Label::primary(self.file_id, span.start..span.end)
+                        .with_message(format!("{} はここで使われています", name)),
+                    Label::secondary(self.file_id, dropped_at.start..dropped_at.end)
+                        .with_message(format!("{} はここで宣言され、このブロックの終わりで破棄されます", borrowed)),
+                ],
+            ),
+            AnalyzerError::AssertNotComparable { name, ty, span } => (
+                format!("{} の引数は比較できる型である必要があります: {}", name, ty),
+                vec![Label::primary(self.file_id, span.start..span.end)
+                    .with_message(format!("{} の値は比較できません", ty))],
+            ),
+            AnalyzerError::NotDisplayable { ty, span } => (
+                format!("{} の値は文字列に補間できません", ty),
+                vec![Label::primary(self.file_id, span.start..span.end)
+                    .with_message(format!("{} の値を明示的に文字列に変換してください", ty))],
+            ),
+            AnalyzerError::NonFfiSafeType { name, ty, span } => (
+                format!("外部関数 {} の引数と戻り値にCと受け渡せない型 {} は使えません", name, ty),
+                vec![Label::primary(self.file_id, span.start..span.end)
+                    .with_message(format!("{} はCの関数と受け渡せません", ty))],
+            ),
+            AnalyzerError::TuplePatternArityMismatch { ty, expected, found, span } => (
+                format!("タプルパターンの要素数が一致しません: {} の要素は{}個ですが、パターンは{}個です", ty, expected, found),
+                vec![Label::primary(self.file_id, span.start..span.end)
+                    .with_message(format!("{}個の要素のパターンが必要です", expected))],
+            ),
+            AnalyzerError::UnknownPatternField { ty, field, span, .. } => (
+                format!("構造体 {} にフィールド {} はありません", ty, field),
+                vec![Label::primary(self.file_id, span.start..span.end)
+                    .with_message(format!("{} は {} のフィールドではありません", field, ty))],
+            ),
+            AnalyzerError::UnknownPatternVariant { ty, variant, span, .. } => (
+                format!("列挙型 {} にバリアント {} はありません", ty, variant),
+                vec![Label::primary(self.file_id, span.start..span.end)
+                    .with_message(format!("{} は {} のバリアントではありません", variant, ty))],
+            ),
+            AnalyzerError::UnknownVariantPatternField { variant, field, span, .. } => (
+                format!("バリアント {} にフィールド {} はありません", variant, field),
+                vec![Label::primary(self.file_id, span.start..span.end)
+                    .with_message(format!("{} は {} のフィールドではありません", field, variant))],
+            ),
+            AnalyzerError::UnknownAssociatedConstant { ty, name, span, .. } => (
+                format!("型 {} に関連定数 {} はありません", ty, name),
+                vec![Label::primary(self.file_id, span.start..span.end)
+                    .with_message(format!("{} は {} の関連定数ではありません", name, ty))],
+            ),
+            AnalyzerError::RefutableLetPattern { pattern, span } => (
+                format!("let文では変数に分解できないパターン {} は使えません", pattern),
+                vec![Label::primary(self.file_id, span.start..span.end)
+                    .with_message("値によっては一致しないパターンです")],
+            ),
+            AnalyzerError::IncompleteFunction { name, missing, span } => (
+                format!("関数 {} は未定義の関数 {} を呼び出すため、まだ実行できません", name, missing.join(", ")),
+                vec![Label::primary(self.file_id, span.start..span.end)
+                    .with_message(format!("{} の定義を待っています", missing.join(", ")))],
+            ),
+            AnalyzerError::LocalFunctionCapture { name, function, span } => (
+                format!("ローカル関数 {} は外側の変数 {} を捕捉できません", function, name),
+                vec![Label::primary(self.file_id, span.start..span.end)
+                    .with_message(format!("{} は外側の関数の変数です", name))],
+            ),
+            AnalyzerError::Internal { message, span } => (
+                format!("内部エラー: {}", message),
+                vec![Label::primary(self.file_id, span.start..span.end)
+                    .with_message("コンパイラの不具合です。この箇所を含むプログラムを報告してください")],
+            ),
         }
     }
 }
 
+/// 常に偽になる条件のラベル（条件と、値の根拠となった定数）
+fn never_taken_labels(file_id: usize, span: Span, message: &str, sources: &[ConstantSource]) -> Vec<Label<usize>> {
+    let mut labels = vec![Label::primary(file_id, span.start..span.end).with_message(message)];
+    labels.extend(sources.iter().map(|source| {
+        Label::secondary(file_id, source.span.start..source.span.end)
+            .with_message(format!("{} は {} です", source.name, source.value))
+    }));
+    labels
+}
+
+/// セマンティック解析エラーの補足情報
+fn analyzer_notes(e: &AnalyzerError) -> Vec<String> {
+    match e {
+        AnalyzerError::ArgumentCountMismatch { callee: Some(callee), .. }
+        | AnalyzerError::VoidValue { callee: Some(callee), .. } => {
+            vec![format!("シグネチャ: {}", callee.signature)]
+        }
+        AnalyzerError::ArgumentTypeMismatch { index, callee, swap_with, .. } => {
+            let mut notes = vec![format!("シグネチャ: {}", callee.signature)];
+            if let Some(other) = swap_with {
+                notes.push(format!(
+                    "{}番目と{}番目の引数を入れ替えると型が一致します。引数の順序を確認してください",
+                    index.min(other) + 1,
+                    index.max(other) + 1
+                ));
+            }
+            notes
+        }
+        AnalyzerError::RecursiveInlineAlways { cycle, .. } => vec![
+            format!("呼び出しの循環: {}", cycle.join(" → ")),
+            "常に展開すると展開が終わらないため、展開を優先するだけの `inline` を指定してください".to_string(),
+        ],
+        AnalyzerError::MissingLivesClause { suggestion, .. }
+        | AnalyzerError::UnlistedReturnSource { suggestion, .. } => {
+            vec![format!("戻り値の型の後に `{}` を追加してください", suggestion)]
+        }
+        AnalyzerError::MethodNotFound { ty, available, .. } if !available.is_empty() => {
+            vec![format!("{} で使えるメソッド: {}", ty, available.join(", "))]
+        }
+        AnalyzerError::UnknownImportSymbol { path, available, .. } if !available.is_empty() => {
+            vec![format!("{} からインポートできる名前: {}", path, available.join(", "))]
+        }
+        AnalyzerError::ImportConflictsWithDefinition { .. } => {
+            vec!["別名を付けてインポートするか、定義の名前を変えてください".to_string()]
+        }
+        AnalyzerError::ShadowedImport { .. } => {
+            vec!["名前はユーザー定義、インポート、プレリュードの順に解決されます。インポートした方を使う場合は別名を付けて要素をインポートしてください".to_string()]
+        }
+        AnalyzerError::NoOverloadForArity { name, arities, .. } => {
+            let arities: Vec<String> = arities.iter().map(usize::to_string).collect();
+            vec![format!("{} の定義の引数の数: {}", name, arities.join(", "))]
+        }
+        AnalyzerError::DuplicateFunction { name, .. } if !name.contains("::") => {
+            vec!["関数は引数の数が異なる場合だけオーバーロードできます。引数の型だけが異なる関数は名前を変えてください".to_string()]
+        }
+        AnalyzerError::OrPatternBindingMismatch { .. } => {
+            vec!["どの選択肢に一致してもアームの式で同じ変数を使えるよう、すべての選択肢で同じ名前の変数を同じ型で束縛してください".to_string()]
+        }
+        AnalyzerError::BranchNeverTaken { .. } | AnalyzerError::MatchArmNeverTaken { .. } => {
+            vec!["値は変更されていない変数の初期値から求めました。意図した条件か確認してください".to_string()]
+        }
+        AnalyzerError::RedundantBoolComparison { comparison, suggestion, .. } => {
+            vec![format!("`{}` を `{}` に書き換えてください", comparison, suggestion)]
+        }
+        AnalyzerError::ComparisonAlwaysConstant { .. } => {
+            vec!["意図した比較であれば、`x as i32 < 0` のようにキャストして比較する型を明示してください".to_string()]
+        }
+        AnalyzerError::AssertNotComparable { .. } => {
+            vec!["比較できるのは数値・bool・文字列です".to_string()]
+        }
+        AnalyzerError::NotDisplayable { .. } => {
+            vec![
+                "補間できるのは64ビット以下の整数・f32・f64・bool・文字列と、バリアントがフィールドを持たない列挙型です".to_string(),
+                "構造体やコレクションは、フィールドや要素を補間するか文字列を返す関数で変換してください（`${p.x}`）".to_string(),
+            ]
+        }
+        AnalyzerError::UnknownPatternField { available, .. }
+        | AnalyzerError::UnknownVariantPatternField { available, .. }
+            if !available.is_empty() =>
+        {
+            vec![format!("使えるフィールド: {}", available.join(", "))]
+        }
+        AnalyzerError::UnknownPatternVariant { available, .. } => {
+            vec![format!("使えるバリアント: {}", available.join(", "))]
+        }
+        AnalyzerError::UnknownAssociatedConstant { available, .. } => {
+            if available.is_empty() {
+                vec!["関連定数を持つのは128ビットまでの整数型と f32・f64 です".to_string()]
+            } else {
+                vec![format!("使える関連定数: {}", available.join(", "))]
+            }
+        }
+        AnalyzerError::RefutableLetPattern { .. } => {
+            vec!["let文で使えるのは変数・`_`・タプル・構造体のパターンです。値によって分岐する場合はmatch式を使ってください".to_string()]
+        }
+        AnalyzerError::IncompleteFunction { missing, .. } => {
+            vec![format!("REPLでは {} を定義すると呼び出せるようになります", missing.join(", "))]
+        }
+        AnalyzerError::LocalFunctionCapture { name, .. } => {
+            vec![format!("ローカル関数は変数を捕捉できません。{} を引数として渡してください", name)]
+        }
+        AnalyzerError::NonFfiSafeType { .. } => {
+            vec!["外部関数で使える型は i8〜i64・u8〜u64・f32・f64・bool・String（Cの文字列へのポインタ）です".to_string()]
+        }
+        _ => vec![],
+    }
+}
+
 /// 複数のエラーを蓄積するためのコレクター
-#[derive(Debug, Default)]
+#[derive(Debug, Clone, Default)]
 pub struct ErrorCollector {
     errors: Vec<DiagnosticError>,
     warnings: Vec<DiagnosticError>,
@@ -394,8 +1226,7 @@ impl ErrorCollector {
         self.errors.push(DiagnosticError::new(error, file_id));
     }
 
-    /// 警告を追加（将来の拡張用）
-    #[allow(dead_code)]
+    /// 警告を追加
     pub fn add_warning(&mut self, error: YuniError, file_id: usize) {
         self.warnings.push(DiagnosticError::new(error, file_id));
     }
@@ -406,7 +1237,6 @@ impl ErrorCollector {
     }
 
     /// 警告があるかどうか
-    #[allow(dead_code)]
     pub fn has_warnings(&self) -> bool {
         !self.warnings.is_empty()
     }
@@ -432,6 +1262,11 @@ impl ErrorCollector {
         &self.warnings
     }
 
+    /// すべての警告を取り出す
+    pub fn take_warnings(&mut self) -> Vec<DiagnosticError> {
+        std::mem::take(&mut self.warnings)
+    }
+
     /// エラーと警告をクリア
     #[allow(dead_code)]
     pub fn clear(&mut self) {
@@ -466,6 +1301,7 @@ impl IntoYuniError for anyhow::Error {
     }
 }
 
+#[cfg(feature = "full")]
 impl From<inkwell::builder::BuilderError> for YuniError {
     fn from(e: inkwell::builder::BuilderError) -> Self {
         YuniError::Codegen(CodegenError::Internal {
@@ -0,0 +1,196 @@
+//! Webプレイグラウンドなどに組み込むためのフロントエンドAPI
+//!
+//! ソースコードの文字列を受け取って字句解析・構文解析・セマンティック解析を行い、
+//! 診断・シンボルの一覧・整形したASTを返します。ファイルシステム・プロセス・LLVMを
+//! 使わないため、`frontend` フィーチャーだけを有効にすればwasm32-unknown-unknown向けにも
+//! ビルドできます。
+
+use codespan_reporting::diagnostic::{Diagnostic, LabelStyle, Severity};
+use codespan_reporting::files::SimpleFiles;
+use serde::Serialize;
+
+use crate::analyzer::{resolve_names, SemanticAnalyzer, TypeTable};
+use crate::ast::{print_program, Program};
+use crate::error::{DiagnosticError, ErrorCollector, ParserError, YuniError};
+use crate::lexer::{Lexer, Token};
+use crate::parser::Parser;
+use crate::symbols::{LineIndex, Position, Symbol, SymbolIndex};
+
+/// 診断の重大度
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JsonSeverity {
+    Error,
+    Warning,
+    Note,
+}
+
+/// 診断のラベル（ソース上の範囲とその説明）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct JsonLabel {
+    /// 診断の主な原因の箇所か（補足の箇所はfalse）
+    pub primary: bool,
+    pub message: String,
+    pub start: Position,
+    pub end: Position,
+}
+
+/// JSONに変換できる診断情報
+///
+/// 端末以外で診断を表示するための形式です。位置は行・列とも1始まりで、列は文字単位で数えます。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct JsonDiagnostic {
+    pub severity: JsonSeverity,
+    pub message: String,
+    pub labels: Vec<JsonLabel>,
+    pub notes: Vec<String>,
+}
+
+impl JsonDiagnostic {
+    /// codespan-reportingのDiagnosticから変換
+    pub fn from_diagnostic(diagnostic: &Diagnostic<usize>, source: &str) -> Self {
+        let lines = LineIndex::new(source);
+        let severity = match diagnostic.severity {
+            Severity::Bug | Severity::Error => JsonSeverity::Error,
+            Severity::Warning => JsonSeverity::Warning,
+            Severity::Note | Severity::Help => JsonSeverity::Note,
+        };
+        let labels = diagnostic
+            .labels
+            .iter()
+            .map(|label| JsonLabel {
+                primary: label.style == LabelStyle::Primary,
+                message: label.message.clone(),
+                start: lines.position(label.range.start),
+                end: lines.position(label.range.end),
+            })
+            .collect();
+        Self {
+            severity,
+            message: diagnostic.message.clone(),
+            labels,
+            notes: diagnostic.notes.clone(),
+        }
+    }
+}
+
+/// ソースコードのチェック結果
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CheckResult {
+    pub file: String,
+    /// エラーがなかったか
+    pub success: bool,
+    pub diagnostics: Vec<JsonDiagnostic>,
+    /// トップレベルの定義（パースに失敗した場合は空）
+    pub symbols: Vec<Symbol>,
+    /// 整形したAST（パースに失敗した場合はなし）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ast: Option<String>,
+}
+
+impl CheckResult {
+    /// JSON文字列に変換
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("check result is always serializable")
+    }
+}
+
+/// ソースコードを解析し、診断とシンボルの一覧を返す
+///
+/// `name` は診断とシンボルインデックスに表示するファイル名で、ファイルを読むことはありません。
+pub fn check_source(name: &str, src: &str) -> CheckResult {
+    let mut files = SimpleFiles::new();
+    let file_id = files.add(name.to_string(), src.to_string());
+    let mut errors = ErrorCollector::new();
+
+    let mut symbols = Vec::new();
+    let mut ast = None;
+    if let Some(program) = parse_source(src, &mut errors, file_id) {
+        // シンボルと出力するASTはソース上の名前のまま、解析は名前解決した複製に行う
+        let mut resolved = program.clone();
+        let analyzed = analyze_program(&mut resolved, &mut errors, file_id).is_some();
+        symbols = SymbolIndex::build(name, src, &program, analyzed).symbols;
+        ast = Some(print_program(&program));
+    }
+
+    CheckResult {
+        file: name.to_string(),
+        success: !errors.has_errors(),
+        diagnostics: json_diagnostics(&errors, &files, src),
+        symbols,
+        ast,
+    }
+}
+
+/// 字句解析と構文解析を行う
+///
+/// 字句解析のエラーがあっても構文解析を続け、診断は `errors` に追加します。
+/// セミコロンの書き忘れのように解析を続けられた構文エラーは、報告した上でASTを返します。
+/// package宣言がなく文から始まる入力はスクリプトとして解析します。
+pub(crate) fn parse_source(src: &str, errors: &mut ErrorCollector, file_id: usize) -> Option<Program> {
+    let tokens = Lexer::new(src).collect_tokens();
+    for token in tokens.iter().filter(|t| matches!(t.token, Token::Error)) {
+        errors.add_error(YuniError::Lexer(token.lexer_error(src)), file_id);
+    }
+
+    let mut parser = Parser::new(tokens);
+    let parsed = if parser.starts_with_statement() { parser.parse_script_recovering() } else { parser.parse_recovering() };
+    for error in parser.recovered_errors() {
+        errors.add_error(YuniError::Parser(error.clone()), file_id);
+    }
+    match parsed {
+        Ok(program) => {
+            for span in parser.misplaced_doc_comments() {
+                errors.add_warning(YuniError::Parser(ParserError::MisplacedDocComment { span }), file_id);
+            }
+            Some(program)
+        }
+        Err(e) => {
+            errors.add_error(YuniError::Parser(e), file_id);
+            None
+        }
+    }
+}
+
+/// 名前解決とセマンティック解析を行い、解析で決定した式の型を返す
+///
+/// `program` の名前は正規の名前に書き換わります。解析に失敗した場合はNoneを返し、
+/// 診断は `errors` に追加します。
+pub(crate) fn analyze_program(program: &mut Program, errors: &mut ErrorCollector, file_id: usize) -> Option<TypeTable> {
+    let resolution = resolve_names(program);
+    for warning in resolution.warnings {
+        errors.add_warning(YuniError::Analyzer(warning), file_id);
+    }
+    for error in resolution.errors {
+        errors.add_error(YuniError::Analyzer(error), file_id);
+    }
+    let mut analyzer = SemanticAnalyzer::new();
+    let result = analyzer.analyze(program);
+    for warning in std::mem::take(&mut analyzer.warnings) {
+        errors.add_warning(YuniError::Analyzer(warning), file_id);
+    }
+    // 解析は誤りの後も続けるため、記録したすべてのエラーを報告する
+    for error in std::mem::take(&mut analyzer.errors) {
+        errors.add_error(YuniError::Analyzer(error), file_id);
+    }
+    result.ok().map(|()| analyzer.type_table)
+}
+
+/// 蓄積したエラーと警告をJSONに変換できる診断の一覧にする（エラーが先）
+pub(crate) fn json_diagnostics(
+    errors: &ErrorCollector,
+    files: &SimpleFiles<String, String>,
+    src: &str,
+) -> Vec<JsonDiagnostic> {
+    let to_json = |error: &DiagnosticError, severity: Severity| {
+        let mut diagnostic = error.to_diagnostic_in(files);
+        diagnostic.severity = severity;
+        JsonDiagnostic::from_diagnostic(&diagnostic, src)
+    };
+    errors
+        .errors()
+        .iter()
+        .map(|error| to_json(error, Severity::Error))
+        .chain(errors.warnings().iter().map(|warning| to_json(warning, Severity::Warning)))
+        .collect()
+}
@@ -0,0 +1,109 @@
+//! ファジング・プロパティテスト用のハーネス
+//!
+//! cargo-fuzz などのファザーやプロパティテストから任意の入力で呼び出すための
+//! エントリポイントです。不正な入力に対してコンパイラは構造化されたエラーを
+//! 返すべきであり、これらの関数の中で起きたパニックはすべてバグとして扱います。
+
+use serde_json::Value;
+
+use crate::analyzer::{monomorphize_program_with_types, resolve_names, SemanticAnalyzer};
+use crate::ast::{print_program, Program};
+use crate::error::{YuniError, YuniResult};
+use crate::lexer::{Lexer, Token};
+use crate::parser::{Parser, STACK_SIZE};
+
+/// パース → 出力 → 再パースでASTの構造が保たれることを検証
+///
+/// 最初のパースが成功した場合、プリティプリンタの出力を再びパースした結果が
+/// スパンを除いて元のASTと一致しなければパニックします。
+/// 最初のパースが失敗した入力は何も検証しません。
+pub fn parse_roundtrip(src: &str) {
+    with_stack(|| roundtrip(src))
+}
+
+/// 字句解析から単相化までのフロントエンドを実行
+///
+/// 各段階のエラーは構造化されたエラーとして返します。
+/// 入力にかかわらずパニックしてはいけません。
+pub fn analyze_no_panic(src: &str) -> YuniResult<()> {
+    with_stack(|| analyze(src))
+}
+
+/// コンパイラのコマンドを実行するスレッドと同じ大きさのスタックで実行
+///
+/// パーサーは入れ子の深さと連鎖の長さを制限していますが、制限内でも再帰は深くなります。
+/// 呼び出し元（テストスレッドなど）のスタックの大きさに結果が左右されないよう、
+/// 専用のスレッドで実行し、パニックは呼び出し元へそのまま伝えます。
+fn with_stack<T: Send>(f: impl FnOnce() -> T + Send) -> T {
+    std::thread::scope(|scope| {
+        let handle = std::thread::Builder::new()
+            .stack_size(STACK_SIZE)
+            .spawn_scoped(scope, f)
+            .expect("ハーネス用のスレッドを起動できる");
+        handle.join().unwrap_or_else(|payload| std::panic::resume_unwind(payload))
+    })
+}
+
+fn roundtrip(src: &str) {
+    let Ok(program) = parse(src) else {
+        return;
+    };
+
+    let printed = print_program(&program);
+    let reparsed = match parse(&printed) {
+        Ok(reparsed) => reparsed,
+        Err(e) => panic!(
+            "出力したソースコードを再パースできません: {}\n--- 入力 ---\n{}\n--- 出力 ---\n{}",
+            e, src, printed
+        ),
+    };
+
+    assert_eq!(
+        structure(&program),
+        structure(&reparsed),
+        "再パースしたASTの構造が一致しません\n--- 入力 ---\n{}\n--- 出力 ---\n{}",
+        src,
+        printed
+    );
+}
+
+fn analyze(src: &str) -> YuniResult<()> {
+    let tokens = Lexer::new(src).collect_tokens();
+    if let Some(error) = tokens.iter().find(|t| matches!(t.token, Token::Error)) {
+        return Err(YuniError::Lexer(error.lexer_error(src)));
+    }
+
+    let mut program = Parser::new(tokens).parse()?;
+    if let Some(error) = resolve_names(&mut program).errors.into_iter().next() {
+        return Err(YuniError::Analyzer(error));
+    }
+
+    let mut analyzer = SemanticAnalyzer::new();
+    analyzer.analyze(&program)?;
+
+    monomorphize_program_with_types(program, std::mem::take(&mut analyzer.type_table))?;
+    Ok(())
+}
+
+fn parse(src: &str) -> YuniResult<Program> {
+    let tokens = Lexer::new(src).collect_tokens();
+    Ok(Parser::new(tokens).parse()?)
+}
+
+/// スパンを取り除いたASTの構造
+fn structure(program: &Program) -> Value {
+    let mut value = serde_json::to_value(program).expect("ASTはJSONに変換できる");
+    strip_spans(&mut value);
+    value
+}
+
+fn strip_spans(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            map.remove("span");
+            map.values_mut().for_each(strip_spans);
+        }
+        Value::Array(items) => items.iter_mut().for_each(strip_spans),
+        _ => {}
+    }
+}
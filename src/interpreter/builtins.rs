@@ -0,0 +1,413 @@
+//! 組み込み関数と組み込みメソッドのRust実装
+
+use crate::assertion::{failure_message, with_values, AssertKind};
+use crate::ast::{BinaryOp, CallExpr, ExternFunctionDecl, Span, Type};
+use crate::console::{output_message, OutputKind};
+use crate::error::RuntimeError;
+use std::cell::RefCell;
+use std::cmp::Ordering;
+use std::io::Write;
+use std::sync::OnceLock;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use super::ops::binary_op;
+use super::value::Value;
+use super::{EvalResult, Interpreter};
+
+/// `time_nanos` の起点（最初に時刻を取得した時点）
+static CLOCK_ORIGIN: OnceLock<Instant> = OnceLock::new();
+
+/// シードから乱数の内部状態を作る（ランタイムの `random_state_from_seed` と同じ算法）
+pub(crate) fn random_state_from_seed(seed: u64) -> u64 {
+    let mut z = seed.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^= z >> 31;
+    if z == 0 {
+        0x9E37_79B9_7F4A_7C15
+    } else {
+        z
+    }
+}
+
+impl<'a> Interpreter<'a> {
+    /// アサーションを評価する
+    ///
+    /// インタプリタはデバッグ用の実行のため、`debug_assert` も常に検査します。
+    pub(crate) fn eval_assert(&mut self, kind: AssertKind, call: &CallExpr) -> EvalResult<Value> {
+        let failed = |this: &Self| failure_message(kind, &call.args, call.span, this.source.as_ref());
+        let message = if kind.is_eq() {
+            let (left, right) = self.eval_operands(&call.args[0], &call.args[1])?;
+            let equal = binary_op(&BinaryOp::Eq, left.clone(), right.clone(), call.span)?;
+            if equal == Value::Bool(true) {
+                return Ok(Value::Unit);
+            }
+            with_values(&failed(self), &left.to_string(), &right.to_string())
+        } else {
+            if self.eval_expression(&call.args[0])?.deref_all() == Value::Bool(true) {
+                return Ok(Value::Unit);
+            }
+            failed(self)
+        };
+        Err(RuntimeError::AssertionFailed { message, span: call.span }.into())
+    }
+
+    /// コンソール出力を評価する
+    ///
+    /// コード生成と同様に、引数を連結したテンプレート文字列を評価して出力します。
+    pub(crate) fn eval_output(&mut self, kind: OutputKind, call: &CallExpr) -> EvalResult<Value> {
+        let message = match output_message(&call.args, call.span) {
+            Some(message) => self.eval_expression(&message)?.to_string(),
+            None => String::new(),
+        };
+        let newline = if kind.newline() { "\n" } else { "" };
+        if kind.to_stderr() {
+            let _ = write!(std::io::stderr(), "{}{}", message, newline);
+        } else {
+            let _ = write!(self.output, "{}{}", message, newline);
+        }
+        Ok(Value::Unit)
+    }
+
+    /// 外部関数を呼び出す
+    ///
+    /// インタプリタからはCの関数を呼び出せないため、Cの標準ライブラリの一部の関数だけを
+    /// 同じ結果になるRustの実装で実行します。`getenv` はコード生成と同様に、
+    /// 環境変数が設定されていなければ空文字列を返します。
+    pub(crate) fn call_extern(
+        &mut self,
+        func: &ExternFunctionDecl,
+        args: Vec<Value>,
+        span: Span,
+    ) -> Result<Value, RuntimeError> {
+        let return_type = func.return_type.as_deref().cloned().unwrap_or(Type::Void);
+        let math: Option<fn(f64) -> f64> = match func.symbol() {
+            "sqrt" => Some(f64::sqrt),
+            "sin" => Some(f64::sin),
+            "cos" => Some(f64::cos),
+            "tan" => Some(f64::tan),
+            "exp" => Some(f64::exp),
+            "log" => Some(f64::ln),
+            "fabs" => Some(f64::abs),
+            "floor" => Some(f64::floor),
+            "ceil" => Some(f64::ceil),
+            _ => None,
+        };
+        if let (Some(math), [Value::Float(value, _)]) = (math, args.as_slice()) {
+            return Ok(Value::float(math(*value), return_type));
+        }
+        match (func.symbol(), args.as_slice()) {
+            ("pow", [Value::Float(base, _), Value::Float(exponent, _)]) => {
+                Ok(Value::float(base.powf(*exponent), return_type))
+            }
+            ("getenv", [Value::Str(name)]) => Ok(Value::Str(std::env::var(name).unwrap_or_default())),
+            (symbol, _) => Err(RuntimeError::Unsupported {
+                feature: format!("外部関数 {} の呼び出し", symbol),
+                span,
+            }),
+        }
+    }
+
+    /// 組み込み関数を呼び出す
+    pub(crate) fn call_builtin(&mut self, name: &str, args: Vec<Value>, span: Span) -> Result<Value, RuntimeError> {
+        match name {
+            "sqrt" => match args.as_slice() {
+                [Value::Float(value, _)] => Ok(Value::float(value.sqrt(), Type::F64)),
+                _ => Err(RuntimeError::InvalidValue {
+                    message: "sqrtはf64の引数を1つ取ります".to_string(),
+                    span,
+                }),
+            },
+            "read_line" => {
+                // ランタイムのyuni_read_lineと同様に末尾の改行を取り除く
+                let mut line = String::new();
+                let _ = std::io::stdin().read_line(&mut line);
+                if line.ends_with('\n') {
+                    line.pop();
+                    if line.ends_with('\r') {
+                        line.pop();
+                    }
+                }
+                Ok(Value::Str(line))
+            }
+            // ランタイムと同様に、読み込めない場合は空のVec・書き出せない場合はfalseを返す
+            "read_bytes" => match args.as_slice() {
+                [Value::Str(path)] => Ok(Value::bytes(&std::fs::read(path).unwrap_or_default())),
+                _ => Err(RuntimeError::InvalidValue {
+                    message: "read_bytesはStringの引数を1つ取ります".to_string(),
+                    span,
+                }),
+            },
+            "write_bytes" => match args.as_slice() {
+                [Value::Str(path), data] => match data.to_bytes() {
+                    Some(bytes) => Ok(Value::Bool(std::fs::write(path, bytes).is_ok())),
+                    None => Err(RuntimeError::InvalidValue {
+                        message: "write_bytesのデータはVec<u8>である必要があります".to_string(),
+                        span,
+                    }),
+                },
+                _ => Err(RuntimeError::InvalidValue {
+                    message: "write_bytesはStringとVec<u8>の引数を取ります".to_string(),
+                    span,
+                }),
+            },
+            // ランタイムのyuni_parse_i64・yuni_parse_f64と同様に、前後の空白を除いた文字列全体を変換し、
+            // 変換できない場合は `(false, 0)` を返す
+            "parse_int" => match args.as_slice() {
+                [Value::Str(text)] => {
+                    let parsed = text.trim().parse::<i64>().ok();
+                    Ok(Value::Tuple(vec![
+                        Value::Bool(parsed.is_some()),
+                        Value::int(parsed.unwrap_or(0) as i128, Type::I64),
+                    ]))
+                }
+                _ => Err(RuntimeError::InvalidValue {
+                    message: "parse_intはstrの引数を1つ取ります".to_string(),
+                    span,
+                }),
+            },
+            "parse_float" => match args.as_slice() {
+                [Value::Str(text)] => {
+                    let parsed = text.trim().parse::<f64>().ok().filter(|value| value.is_finite());
+                    Ok(Value::Tuple(vec![
+                        Value::Bool(parsed.is_some()),
+                        Value::float(parsed.unwrap_or(0.0), Type::F64),
+                    ]))
+                }
+                _ => Err(RuntimeError::InvalidValue {
+                    message: "parse_floatはstrの引数を1つ取ります".to_string(),
+                    span,
+                }),
+            },
+            // 時刻と乱数（乱数はランタイムと同じ算法のため、同じシードから同じ系列になる）
+            "time_nanos" => {
+                let origin = *CLOCK_ORIGIN.get_or_init(Instant::now);
+                Ok(Value::int(origin.elapsed().as_nanos().min(i64::MAX as u128) as i128, Type::I64))
+            }
+            "time_unix_millis" => {
+                let millis = match SystemTime::now().duration_since(UNIX_EPOCH) {
+                    Ok(elapsed) => elapsed.as_millis() as i128,
+                    Err(before) => -(before.duration().as_millis() as i128),
+                };
+                Ok(Value::int(millis, Type::I64))
+            }
+            "sleep_millis" => match args.as_slice() {
+                [Value::Int(ms, _)] => {
+                    if *ms > 0 {
+                        std::thread::sleep(Duration::from_millis(*ms as u64));
+                    }
+                    Ok(Value::Unit)
+                }
+                _ => Err(RuntimeError::InvalidValue {
+                    message: "sleep_millisはi64の引数を1つ取ります".to_string(),
+                    span,
+                }),
+            },
+            "random_seed" => match args.as_slice() {
+                [Value::Int(seed, _)] => {
+                    self.random_state = random_state_from_seed(*seed as u64);
+                    Ok(Value::Unit)
+                }
+                _ => Err(RuntimeError::InvalidValue {
+                    message: "random_seedはu64の引数を1つ取ります".to_string(),
+                    span,
+                }),
+            },
+            "random_u64" => {
+                let mut x = self.random_state;
+                x ^= x >> 12;
+                x ^= x << 25;
+                x ^= x >> 27;
+                self.random_state = x;
+                Ok(Value::int(x.wrapping_mul(0x2545_F491_4F6C_DD1D) as i128, Type::U64))
+            }
+            _ => Err(RuntimeError::Undefined {
+                name: name.to_string(),
+                span,
+            }),
+        }
+    }
+
+    /// Vec・HashMap・String・StringBuilder・数値の組み込みメソッドを呼び出す
+    pub(crate) fn call_builtin_method(
+        &mut self,
+        receiver: Value,
+        method: &str,
+        args: Vec<Value>,
+        span: Span,
+    ) -> Result<Value, RuntimeError> {
+        // 文字列のメソッドの引数はstrの位置に置くため、`&String` も渡せる
+        let args = match receiver {
+            Value::Str(_) => args.iter().map(Value::deref_all).collect(),
+            _ => args,
+        };
+        match (&receiver, method, args.as_slice()) {
+            (Value::Vec(elements), "push", [value]) => {
+                let mut elements = elements.borrow_mut();
+                // 既存要素の数値型に揃える
+                let value = match elements.first().and_then(|first| first.numeric_type()) {
+                    Some(ty) => value.clone().coerce_to(ty),
+                    None => value.clone(),
+                };
+                elements.push(value);
+                Ok(Value::Unit)
+            }
+            (Value::Vec(elements), "resize", [Value::Int(new_len, _), fill]) => {
+                let new_len = usize::try_from(*new_len).map_err(|_| RuntimeError::InvalidValue {
+                    message: format!("Vecの長さに負の値 {} は指定できません", new_len),
+                    span,
+                })?;
+                let mut elements = elements.borrow_mut();
+                // pushと同じく既存要素の数値型に揃える
+                let fill = match elements.first().and_then(|first| first.numeric_type()) {
+                    Some(ty) => fill.clone().coerce_to(ty),
+                    None => fill.clone(),
+                };
+                elements.resize(new_len, fill);
+                Ok(Value::Unit)
+            }
+            (Value::Vec(elements), "pop", []) => elements.borrow_mut().pop().ok_or_else(|| {
+                RuntimeError::InvalidValue {
+                    message: "空のVecからpopしました".to_string(),
+                    span,
+                }
+            }),
+            (Value::Vec(elements), "len", []) => Ok(Value::int(elements.borrow().len() as i128, Type::I64)),
+            (Value::Vec(elements), "get", [Value::Int(index, _)]) => {
+                let elements = elements.borrow();
+                let index = *index;
+                usize::try_from(index)
+                    .ok()
+                    .and_then(|i| elements.get(i).cloned())
+                    .ok_or(RuntimeError::IndexOutOfBounds {
+                        index,
+                        len: elements.len(),
+                        span,
+                    })
+            }
+            (Value::Vec(elements), "sort", []) => {
+                let values = std::mem::take(&mut *elements.borrow_mut());
+                let sorted = merge_sort_by(values, &mut |a, b| Ok::<_, RuntimeError>(compare_ordered(a, b)))?;
+                *elements.borrow_mut() = sorted;
+                Ok(Value::Unit)
+            }
+            (Value::Vec(elements), "binary_search", [key]) => {
+                // キー以上になる最初の要素を探し、等しくなければ-1（コード生成と同じ結果）
+                let elements = elements.borrow();
+                let index = elements.partition_point(|element| compare_ordered(element, key) == Ordering::Less);
+                let found = elements.get(index).is_some_and(|element| compare_ordered(element, key) == Ordering::Equal);
+                Ok(Value::int(if found { index as i128 } else { -1 }, Type::I64))
+            }
+            (Value::Map(pairs), "insert", [key, value]) => {
+                let mut pairs = pairs.borrow_mut();
+                match pairs.iter_mut().find(|(k, _)| k == key) {
+                    Some((_, existing)) => *existing = value.clone(),
+                    None => pairs.push((key.clone(), value.clone())),
+                }
+                Ok(Value::Unit)
+            }
+            (Value::Map(pairs), "get", [key]) => pairs
+                .borrow()
+                .iter()
+                .find(|(k, _)| k == key)
+                .map(|(_, v)| v.clone())
+                .ok_or_else(|| RuntimeError::InvalidValue {
+                    message: format!("キー {} が見つかりません", key),
+                    span,
+                }),
+            (Value::Map(pairs), "contains_key", [key]) => {
+                Ok(Value::Bool(pairs.borrow().iter().any(|(k, _)| k == key)))
+            }
+            (Value::Map(pairs), "len", []) => Ok(Value::int(pairs.borrow().len() as i128, Type::I64)),
+            (Value::Str(s), "len", []) => Ok(Value::int(s.len() as i128, Type::I64)),
+            (Value::Str(s), "as_str" | "to_string", []) => Ok(Value::Str(s.clone())),
+            // コード生成と同様に、位置はバイト単位で、空の区切りでは分割しない
+            (Value::Str(s), "starts_with", [Value::Str(prefix)]) => Ok(Value::Bool(s.starts_with(prefix.as_str()))),
+            (Value::Str(s), "find", [Value::Str(needle)]) => {
+                Ok(Value::int(s.find(needle.as_str()).map_or(-1, |index| index as i128), Type::I64))
+            }
+            (Value::Str(s), "split", [Value::Str(separator)]) => {
+                let parts: Vec<&str> = if separator.is_empty() { vec![s] } else { s.split(separator.as_str()).collect() };
+                Ok(Value::new_vec(parts.into_iter().map(|part| Value::Str(part.to_string())).collect()))
+            }
+            (Value::Str(s), "trim", []) => Ok(Value::Str(s.trim_matches(|c: char| c.is_ascii_whitespace()).to_string())),
+            (Value::StringBuilder(buf), "append", [value]) => {
+                buf.borrow_mut().push_str(&value.to_string());
+                Ok(Value::Unit)
+            }
+            (Value::StringBuilder(buf), "len", []) => Ok(Value::int(buf.borrow().len() as i128, Type::I64)),
+            (Value::StringBuilder(buf), "to_string", []) => Ok(Value::Str(buf.borrow().clone())),
+            (Value::Int(..) | Value::Float(..), "to_string", []) => Ok(Value::Str(receiver.to_string())),
+            // コード生成と同様に、最小値の絶対値は最小値のまま（型の幅でラップする）
+            (Value::Int(value, ty), "abs", []) => Ok(Value::int(value.wrapping_abs(), ty.clone())),
+            (Value::Float(value, ty), "abs", []) => Ok(Value::float(value.abs(), ty.clone())),
+            (Value::Int(value, ty), "min" | "max", [Value::Int(other, _)]) => {
+                let picked = if method == "min" { (*value).min(*other) } else { (*value).max(*other) };
+                Ok(Value::int(picked, ty.clone()))
+            }
+            (Value::Float(value, ty), "min" | "max", [Value::Float(other, _)]) => {
+                let picked = if method == "min" { value.min(*other) } else { value.max(*other) };
+                Ok(Value::float(picked, ty.clone()))
+            }
+            _ => Err(RuntimeError::Undefined {
+                name: format!("{}.{}", receiver.type_name(), method),
+                span,
+            }),
+        }
+    }
+
+    /// `v.sort_by(f)` を評価する（`f` はトップレベルの関数の名前）
+    pub(crate) fn sort_vec_by(&mut self, elements: &RefCell<Vec<Value>>, comparator: &str, span: Span) -> Result<Value, RuntimeError> {
+        let values = std::mem::take(&mut *elements.borrow_mut());
+        let sorted = merge_sort_by(values, &mut |a, b| {
+            match self.invoke_function(comparator, vec![a.clone(), b.clone()], span)? {
+                Value::Int(order, _) => Ok(order.cmp(&0)),
+                other => Err(RuntimeError::InvalidValue {
+                    message: format!("比較関数 {} の戻り値が整数ではありません: {}", comparator, other),
+                    span,
+                }),
+            }
+        })?;
+        *elements.borrow_mut() = sorted;
+        Ok(Value::Unit)
+    }
+}
+
+/// 組み込みの順序で2つの値を比較する
+///
+/// コード生成が合成する比較関数と同じく、浮動小数点数はNaNを最大とし、文字列はバイト列として比較します。
+fn compare_ordered(a: &Value, b: &Value) -> Ordering {
+    match (a, b) {
+        (Value::Int(a, _), Value::Int(b, _)) => a.cmp(b),
+        (Value::Float(a, _), Value::Float(b, _)) => a.partial_cmp(b).unwrap_or_else(|| a.is_nan().cmp(&b.is_nan())),
+        (Value::Str(a), Value::Str(b)) => a.as_bytes().cmp(b.as_bytes()),
+        _ => Ordering::Equal,
+    }
+}
+
+/// 値を安定にマージソートする
+///
+/// 比較関数のエラーはそのまま返します。比較関数が全順序でなくても停止します。
+fn merge_sort_by<E>(
+    mut values: Vec<Value>,
+    compare: &mut impl FnMut(&Value, &Value) -> Result<Ordering, E>,
+) -> Result<Vec<Value>, E> {
+    if values.len() < 2 {
+        return Ok(values);
+    }
+    let right = values.split_off(values.len() / 2);
+    let left = merge_sort_by(values, compare)?;
+    let right = merge_sort_by(right, compare)?;
+
+    let mut merged = Vec::with_capacity(left.len() + right.len());
+    let mut left = left.into_iter().peekable();
+    let mut right = right.into_iter().peekable();
+    while let (Some(l), Some(r)) = (left.peek(), right.peek()) {
+        // 等しい要素は左側を先に取り、元の順序を保つ
+        let next = if compare(l, r)? == Ordering::Greater { right.next() } else { left.next() };
+        merged.extend(next);
+    }
+    merged.extend(left);
+    merged.extend(right);
+    Ok(merged)
+}
@@ -0,0 +1,65 @@
+//! インタプリタの変数環境
+
+use super::value::Value;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+/// 変数の束縛
+///
+/// 意味解析器のSymbolと同様に可変性を保持し、値は参照から共有できるようセルに格納します。
+#[derive(Debug, Clone)]
+pub struct Binding {
+    pub cell: Rc<RefCell<Value>>,
+    pub is_mutable: bool,
+}
+
+/// スコープのスタックで構成される変数環境
+#[derive(Debug, Default)]
+pub struct Environment {
+    scopes: Vec<HashMap<String, Binding>>,
+}
+
+impl Environment {
+    pub fn new() -> Self {
+        Self {
+            scopes: vec![HashMap::new()],
+        }
+    }
+
+    /// 新しいスコープを開始
+    pub fn push_scope(&mut self) {
+        self.scopes.push(HashMap::new());
+    }
+
+    /// 現在のスコープを終了
+    pub fn pop_scope(&mut self) {
+        if self.scopes.len() > 1 {
+            self.scopes.pop();
+        }
+    }
+
+    /// 現在のスコープに変数を定義（同名の変数はシャドーイングされる）
+    pub fn define(&mut self, name: &str, value: Value, is_mutable: bool) {
+        let binding = Binding {
+            cell: Rc::new(RefCell::new(value)),
+            is_mutable,
+        };
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.insert(name.to_string(), binding);
+        }
+    }
+
+    /// 変数を検索（内側のスコープから順に）
+    pub fn lookup(&self, name: &str) -> Option<&Binding> {
+        self.scopes.iter().rev().find_map(|scope| scope.get(name))
+    }
+
+    /// 関数呼び出し用に環境を入れ替える
+    ///
+    /// 呼び出し先からは呼び出し元のローカル変数が見えないようにするため、
+    /// 新しい環境に差し替えて元の環境を返します。
+    pub fn replace_with_fresh(&mut self) -> Environment {
+        std::mem::replace(self, Environment::new())
+    }
+}
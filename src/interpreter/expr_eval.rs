@@ -0,0 +1,663 @@
+//! 式の評価
+
+use crate::analyzer::numeric::{
+    associated_constant, builtin_type_by_name, float_literal_type, integer_literal_type, is_untyped_integer_literal,
+    NumericConstant,
+};
+use crate::assertion::AssertKind;
+use crate::ast::*;
+use crate::console::OutputKind;
+use crate::error::RuntimeError;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use super::ops::{binary_op, cast_value, check_cast, unary_op};
+use super::value::{is_float_type, Value};
+use super::{EvalResult, Interpreter};
+
+/// 代入先の位置を表すステップ
+enum PlaceStep {
+    Field(String),
+    Index(Value),
+}
+
+impl<'a> Interpreter<'a> {
+    /// 式を評価（期待される型のコンテキストなし）
+    pub(crate) fn eval_expression(&mut self, expr: &Expression) -> EvalResult<Value> {
+        self.eval_expression_with_type(expr, None)
+    }
+
+    /// 式を評価（期待される型のコンテキスト付き）
+    ///
+    /// 期待される型は数値リテラルの型決定にのみ使用します（コード生成と同じ規則）。
+    pub(crate) fn eval_expression_with_type(&mut self, expr: &Expression, expected: Option<&Type>) -> EvalResult<Value> {
+        match expr {
+            Expression::Integer(lit) => match integer_literal_type(lit, self.literal_context(expr, expected)) {
+                ty if is_float_type(&ty) => Ok(Value::float(lit.value as f64, ty)),
+                ty => Ok(Value::int(lit.value, ty)),
+            },
+            Expression::Float(lit) => Ok(Value::float(lit.value, float_literal_type(lit, self.literal_context(expr, expected)))),
+            Expression::String(lit) => Ok(Value::Str(lit.value.clone())),
+            Expression::Bytes(lit) => Ok(Value::bytes(&lit.value)),
+            Expression::TemplateString(lit) => {
+                let mut result = String::new();
+                for part in &lit.parts {
+                    match part {
+                        TemplateStringPart::Text(text) => result.push_str(text),
+                        TemplateStringPart::Interpolation(expr) => {
+                            let value = self.eval_expression(expr)?;
+                            result.push_str(&value.to_string());
+                        }
+                    }
+                }
+                Ok(Value::Str(result))
+            }
+            Expression::Boolean(lit) => Ok(Value::Bool(lit.value)),
+            Expression::Identifier(id) => self.eval_variable(&id.name, id.span),
+            Expression::Path(path) => self.eval_path(path),
+            Expression::Binary(binary) => {
+                let (left, right) = self.eval_operands(&binary.left, &binary.right)?;
+                Ok(binary_op(&binary.op, left, right, binary.span)?)
+            }
+            Expression::Unary(unary) => {
+                let operand = self.eval_expression(&unary.expr)?.deref_all();
+                Ok(unary_op(&unary.op, operand, unary.span)?)
+            }
+            Expression::Call(call) => self.eval_call(call),
+            Expression::MethodCall(method_call) => self.eval_method_call(method_call),
+            Expression::Index(index) => self.eval_index(index),
+            Expression::Field(field) => self.eval_field(field),
+            Expression::Reference(ref_expr) => {
+                // 変数への参照は変数のセルを共有し、それ以外は一時値への参照を作る
+                if let Some(cell) = self.variable_cell(&ref_expr.expr) {
+                    return Ok(Value::Ref(cell));
+                }
+                let value = self.eval_expression(&ref_expr.expr)?;
+                Ok(Value::Ref(Rc::new(RefCell::new(value))))
+            }
+            Expression::Dereference(deref) => match self.eval_expression(&deref.expr)? {
+                Value::Ref(cell) => Ok(cell.borrow().clone()),
+                other => Err(RuntimeError::InvalidValue {
+                    message: format!("参照ではない値 {} を参照外ししました", other.type_name()),
+                    span: deref.span,
+                }
+                .into()),
+            },
+            Expression::StructLit(struct_lit) => self.eval_struct_literal(struct_lit),
+            Expression::EnumVariant(enum_variant) => self.eval_enum_variant(enum_variant),
+            Expression::Array(array) => {
+                let elements = array
+                    .elements
+                    .iter()
+                    .map(|e| self.eval_expression(e))
+                    .collect::<EvalResult<Vec<_>>>()?;
+                Ok(Value::new_vec(elements))
+            }
+            Expression::Tuple(tuple) => {
+                let elements = tuple
+                    .elements
+                    .iter()
+                    .map(|e| self.eval_expression(e))
+                    .collect::<EvalResult<Vec<_>>>()?;
+                Ok(Value::Tuple(elements))
+            }
+            Expression::Cast(cast) => {
+                let value = self.eval_expression(&cast.expr)?.deref_all();
+                if self.checked_casts {
+                    check_cast(&value, &cast.ty, cast.span)?;
+                }
+                Ok(cast_value(value, &cast.ty, cast.span)?)
+            }
+            Expression::Assignment(assign) => {
+                let value = self.eval_expression(&assign.value)?;
+                self.assign_to(&assign.target, value.clone(), assign.span)?;
+                // 代入式は代入された値を返す
+                Ok(value)
+            }
+            Expression::Match(match_expr) => self.eval_match(match_expr, expected),
+            Expression::If(if_expr) => {
+                if self.eval_condition(&if_expr.condition)? {
+                    self.eval_expression_with_type(&if_expr.then_branch, expected)
+                } else if let Some(else_branch) = &if_expr.else_branch {
+                    self.eval_expression_with_type(else_branch, expected)
+                } else {
+                    Ok(Value::Unit)
+                }
+            }
+            Expression::Block(block_expr) => {
+                self.env.push_scope();
+                self.defers.push(Vec::new());
+                // 最後の式の値を求めてからdeferを実行する
+                let result = self.eval_block_body(block_expr, expected);
+                let result = self.run_deferred(result);
+                self.env.pop_scope();
+                result
+            }
+            Expression::ListLiteral(list) => {
+                let element_type = list.type_name.as_ref().and_then(|(_, args)| args.first().cloned());
+                let elements = list
+                    .elements
+                    .iter()
+                    .map(|e| {
+                        let value = self.eval_expression_with_type(e, element_type.as_ref())?;
+                        Ok(match &element_type {
+                            Some(ty) => value.coerce_to(ty),
+                            None => value,
+                        })
+                    })
+                    .collect::<EvalResult<Vec<_>>>()?;
+                Ok(Value::new_vec(elements))
+            }
+            Expression::MapLiteral(map) => {
+                let (key_type, value_type) = match &map.type_name {
+                    Some((_, args)) if args.len() == 2 => (Some(args[0].clone()), Some(args[1].clone())),
+                    _ => (None, None),
+                };
+                let mut pairs: Vec<(Value, Value)> = Vec::new();
+                for (key_expr, value_expr) in &map.pairs {
+                    let key = self.eval_expression_with_type(key_expr, key_type.as_ref())?;
+                    let value = self.eval_expression_with_type(value_expr, value_type.as_ref())?;
+                    // 同じキーは後の値で上書きする
+                    match pairs.iter_mut().find(|(k, _)| *k == key) {
+                        Some((_, existing)) => *existing = value,
+                        None => pairs.push((key, value)),
+                    }
+                }
+                Ok(Value::new_map(pairs))
+            }
+        }
+    }
+
+    /// 数値リテラルの型を決める文脈
+    ///
+    /// 期待される型がなければ、セマンティック解析で決定したリテラルの型を使います。
+    fn literal_context<'t>(&'t self, expr: &Expression, expected: Option<&'t Type>) -> Option<&'t Type> {
+        expected.or_else(|| self.types.resolved(expr))
+    }
+
+    /// ブロック式の文と最後の式を評価
+    fn eval_block_body(&mut self, block_expr: &BlockExpr, expected: Option<&Type>) -> EvalResult<Value> {
+        for stmt in &block_expr.statements {
+            self.exec_statement(stmt)?;
+        }
+        match &block_expr.last_expr {
+            Some(last_expr) => self.eval_expression_with_type(last_expr, expected),
+            None => Ok(Value::Unit),
+        }
+    }
+
+    /// 変数の値を取得
+    fn eval_variable(&mut self, name: &str, span: Span) -> EvalResult<Value> {
+        match self.env.lookup(name) {
+            Some(binding) => Ok(binding.cell.borrow().clone()),
+            None => Err(RuntimeError::Undefined {
+                name: name.to_string(),
+                span,
+            }
+            .into()),
+        }
+    }
+
+    /// パス式を評価
+    fn eval_path(&mut self, path: &PathExpr) -> EvalResult<Value> {
+        // 組み込み型の関連定数（`i64::MAX`）
+        if let [type_name, name] = path.segments.as_slice() {
+            if let Some(ty) = builtin_type_by_name(type_name) {
+                return match associated_constant(&ty, name) {
+                    Some(NumericConstant::Integer(value)) => Ok(Value::int(value, ty)),
+                    Some(NumericConstant::Float(value)) => Ok(Value::float(value, ty)),
+                    None => Err(RuntimeError::Undefined {
+                        name: path.segments.join("::"),
+                        span: path.span,
+                    }
+                    .into()),
+                };
+            }
+        }
+
+        match path.segments.as_slice() {
+            [name] => self.eval_variable(name, path.span),
+            [enum_name, variant] => self.eval_enum_variant(&EnumVariantExpr {
+                enum_name: enum_name.clone(),
+                variant: variant.clone(),
+                fields: EnumVariantFields::Unit,
+                span: path.span,
+            }),
+            _ => Err(RuntimeError::Unsupported {
+                feature: format!("パス式 {}", path.segments.join("::")),
+                span: path.span,
+            }
+            .into()),
+        }
+    }
+
+    /// 式が変数を指す場合、そのセルを取得
+    fn variable_cell(&self, expr: &Expression) -> Option<Rc<RefCell<Value>>> {
+        let name = match expr {
+            Expression::Identifier(id) => &id.name,
+            Expression::Path(path) if path.segments.len() == 1 => &path.segments[0],
+            _ => return None,
+        };
+        self.env.lookup(name).map(|binding| binding.cell.clone())
+    }
+
+    /// 二項演算の両辺を評価
+    ///
+    /// 接尾辞のない整数リテラルはもう一方のオペランドの整数型を採用します。
+    pub(crate) fn eval_operands(&mut self, left: &Expression, right: &Expression) -> EvalResult<(Value, Value)> {
+        let mut left_value = self.eval_expression(left)?.deref_all();
+        let mut right_value = self.eval_expression(right)?.deref_all();
+        if let (true, Value::Int(_, ty)) = (is_untyped_integer_literal(left), &right_value) {
+            left_value = left_value.coerce_to(ty);
+        } else if let (true, Value::Int(_, ty)) = (is_untyped_integer_literal(right), &left_value) {
+            right_value = right_value.coerce_to(ty);
+        }
+        Ok((left_value, right_value))
+    }
+
+    /// 引数を評価（パラメータ型を期待される型として使用）
+    fn eval_args(&mut self, args: &[Expression], param_types: &[Type]) -> EvalResult<Vec<Value>> {
+        args.iter()
+            .enumerate()
+            .map(|(i, arg)| self.eval_expression_with_type(arg, param_types.get(i)))
+            .collect()
+    }
+
+    /// 関数呼び出しを評価
+    fn eval_call(&mut self, call: &CallExpr) -> EvalResult<Value> {
+        let name = match call.callee.as_ref() {
+            Expression::Identifier(id) => id.name.clone(),
+            Expression::Path(path) if path.segments.len() == 1 => path.segments[0].clone(),
+            _ => {
+                return Err(RuntimeError::Unsupported {
+                    feature: "関数名以外の呼び出し".to_string(),
+                    span: call.span,
+                }
+                .into())
+            }
+        };
+
+        if let Some(kind) = AssertKind::from_name(&name) {
+            return self.eval_assert(kind, call);
+        }
+        if let Some(kind) = OutputKind::from_name(&name) {
+            return self.eval_output(kind, call);
+        }
+
+        let param_types: Vec<Type> = self
+            .functions
+            .get(&name)
+            .map(|func| func.params.iter().map(|p| p.ty.clone()).collect())
+            .unwrap_or_default();
+        let args = self.eval_args(&call.args, &param_types)?;
+        Ok(self.invoke_function(&name, args, call.span)?)
+    }
+
+    /// メソッド呼び出しを評価
+    fn eval_method_call(&mut self, method_call: &MethodCallExpr) -> EvalResult<Value> {
+        let object = self.eval_expression(&method_call.object)?;
+        let target = object.deref_all();
+
+        let type_name = match &target {
+            Value::Struct(name, _) | Value::Enum(name, _, _) => Some(name.clone()),
+            _ => None,
+        };
+        let method = type_name.and_then(|name| self.methods.get(&(name, method_call.method.clone())).copied());
+
+        let method = match method {
+            Some(method) => method,
+            None => {
+                // sort_byの引数は関数の名前で、値としては評価しない
+                if let (Value::Vec(elements), "sort_by", [Expression::Identifier(comparator)]) =
+                    (&target, method_call.method.as_str(), method_call.args.as_slice())
+                {
+                    return Ok(self.sort_vec_by(elements, &comparator.name, method_call.span)?);
+                }
+                let args = self.eval_args(&method_call.args, &[])?;
+                return Ok(self.call_builtin_method(target, &method_call.method, args, method_call.span)?);
+            }
+        };
+
+        let param_types: Vec<Type> = method.params.iter().map(|p| p.ty.clone()).collect();
+        let args = self.eval_args(&method_call.args, &param_types)?;
+
+        let is_mut = match &method.receiver.ty {
+            Type::Reference(_, is_mut) => *is_mut,
+            // 値レシーバーはコピーを渡す
+            _ => return Ok(self.invoke_method(method, target, args, method_call.span)?),
+        };
+
+        if let Value::Ref(_) = object {
+            return Ok(self.invoke_method(method, object, args, method_call.span)?);
+        }
+        if let Some(cell) = self.variable_cell(&method_call.object) {
+            return Ok(self.invoke_method(method, Value::Ref(cell), args, method_call.span)?);
+        }
+
+        // フィールドや要素に対する参照レシーバーは一時セル経由で渡し、呼び出し後に書き戻す
+        let cell = Rc::new(RefCell::new(target));
+        let result = self.invoke_method(method, Value::Ref(cell.clone()), args, method_call.span)?;
+        if is_mut && matches!(method_call.object.as_ref(), Expression::Field(_) | Expression::Index(_)) {
+            let updated = cell.borrow().clone();
+            self.assign_to(&method_call.object, updated, method_call.span)?;
+        }
+        Ok(result)
+    }
+
+    /// インデックスアクセスを評価
+    fn eval_index(&mut self, index: &IndexExpr) -> EvalResult<Value> {
+        let object = self.eval_expression(&index.object)?.deref_all();
+        let key = self.eval_expression(&index.index)?.deref_all();
+
+        match (&object, &key) {
+            (Value::Vec(elements), Value::Int(i, _)) => {
+                let elements = elements.borrow();
+                let position = checked_index(*i, elements.len(), index.span)?;
+                Ok(elements[position].clone())
+            }
+            (Value::Tuple(elements), Value::Int(i, _)) => {
+                let position = checked_index(*i, elements.len(), index.span)?;
+                Ok(elements[position].clone())
+            }
+            (Value::Str(s), Value::Int(i, _)) => {
+                let position = checked_index(*i, s.len(), index.span)?;
+                Ok(Value::int(s.as_bytes()[position] as i128, Type::U8))
+            }
+            (Value::Map(pairs), _) => pairs
+                .borrow()
+                .iter()
+                .find(|(k, _)| *k == key)
+                .map(|(_, v)| v.clone())
+                .ok_or_else(|| {
+                    RuntimeError::InvalidValue {
+                        message: format!("キー {} が見つかりません", key),
+                        span: index.span,
+                    }
+                    .into()
+                }),
+            _ => Err(RuntimeError::InvalidValue {
+                message: format!("{} はインデックスアクセスできません", object.type_name()),
+                span: index.span,
+            }
+            .into()),
+        }
+    }
+
+    /// フィールドアクセスを評価
+    fn eval_field(&mut self, field: &FieldExpr) -> EvalResult<Value> {
+        let object = self.eval_expression(&field.object)?.deref_all();
+        field_of(&object, &field.field)
+            .ok_or_else(|| {
+                RuntimeError::Undefined {
+                    name: format!("{}.{}", object.type_name(), field.field),
+                    span: field.span,
+                }
+                .into()
+            })
+    }
+
+    /// 構造体リテラルを評価
+    fn eval_struct_literal(&mut self, struct_lit: &StructLiteral) -> EvalResult<Value> {
+        let name = struct_lit.name.clone().ok_or_else(|| RuntimeError::Unsupported {
+            feature: "型名のない構造体リテラル".to_string(),
+            span: struct_lit.span,
+        })?;
+        if name == "StringBuilder" {
+            return Ok(Value::StringBuilder(Rc::new(RefCell::new(String::new()))));
+        }
+        let struct_def = *self.structs.get(&name).ok_or_else(|| RuntimeError::Undefined {
+            name: name.clone(),
+            span: struct_lit.span,
+        })?;
+
+        // フィールドは宣言順に格納する
+        let mut fields = Vec::with_capacity(struct_def.fields.len());
+        for field in &struct_def.fields {
+            let init = struct_lit
+                .fields
+                .iter()
+                .find(|init| init.name == field.name)
+                .ok_or_else(|| RuntimeError::InvalidValue {
+                    message: format!("フィールド {}.{} が初期化されていません", name, field.name),
+                    span: struct_lit.span,
+                })?;
+            let value = self.eval_expression_with_type(&init.value, Some(&field.ty))?;
+            fields.push((field.name.clone(), value.coerce_to(&field.ty)));
+        }
+        Ok(Value::Struct(name, fields))
+    }
+
+    /// 列挙型バリアントを評価
+    fn eval_enum_variant(&mut self, enum_variant: &EnumVariantExpr) -> EvalResult<Value> {
+        let variant_def = self
+            .enums
+            .get(&enum_variant.enum_name)
+            .and_then(|def| def.variants.iter().find(|v| v.name == enum_variant.variant))
+            .ok_or_else(|| RuntimeError::Undefined {
+                name: format!("{}::{}", enum_variant.enum_name, enum_variant.variant),
+                span: enum_variant.span,
+            })?;
+        let field_defs: Vec<Field> = variant_def.fields.clone();
+
+        let values = match &enum_variant.fields {
+            EnumVariantFields::Unit => Vec::new(),
+            EnumVariantFields::Tuple(args) => {
+                let mut values = Vec::with_capacity(args.len());
+                for (i, arg) in args.iter().enumerate() {
+                    let ty = field_defs.get(i).map(|f| &f.ty);
+                    let value = self.eval_expression_with_type(arg, ty)?;
+                    values.push(match ty {
+                        Some(ty) => value.coerce_to(ty),
+                        None => value,
+                    });
+                }
+                values
+            }
+            EnumVariantFields::Struct(inits) => {
+                let mut values = Vec::with_capacity(field_defs.len());
+                for field in &field_defs {
+                    let init = inits.iter().find(|init| init.name == field.name).ok_or_else(|| {
+                        RuntimeError::InvalidValue {
+                            message: format!(
+                                "フィールド {}::{}.{} が初期化されていません",
+                                enum_variant.enum_name, enum_variant.variant, field.name
+                            ),
+                            span: enum_variant.span,
+                        }
+                    })?;
+                    let value = self.eval_expression_with_type(&init.value, Some(&field.ty))?;
+                    values.push(value.coerce_to(&field.ty));
+                }
+                values
+            }
+        };
+
+        Ok(Value::Enum(
+            enum_variant.enum_name.clone(),
+            enum_variant.variant.clone(),
+            values,
+        ))
+    }
+
+    /// match式を評価
+    fn eval_match(&mut self, match_expr: &MatchExpr, expected: Option<&Type>) -> EvalResult<Value> {
+        let scrutinee = self.eval_expression(&match_expr.expr)?;
+
+        for arm in &match_expr.arms {
+            self.env.push_scope();
+            let matched = self.bind_pattern(&arm.pattern, &scrutinee)?
+                && match &arm.guard {
+                    Some(guard) => self.eval_condition(guard)?,
+                    None => true,
+                };
+            if matched {
+                let value = self.eval_expression_with_type(&arm.expr, expected)?;
+                self.env.pop_scope();
+                return Ok(value);
+            }
+            self.env.pop_scope();
+        }
+
+        Err(RuntimeError::InvalidValue {
+            message: "どのmatchアームにも一致しませんでした".to_string(),
+            span: match_expr.span,
+        }
+        .into())
+    }
+
+    /// 代入先に値を書き込む
+    pub(crate) fn assign_to(&mut self, target: &Expression, value: Value, span: Span) -> EvalResult<()> {
+        // 初期化子なしで宣言された変数への最初の代入のみ、不変変数でも許可する
+        if let Expression::Identifier(ident) = target {
+            if let Some(binding) = self.env.lookup(&ident.name) {
+                if !binding.is_mutable && !matches!(*binding.cell.borrow(), Value::Unit) {
+                    return Err(RuntimeError::InvalidValue {
+                        message: format!("不変変数 {} に再代入できません", ident.name),
+                        span,
+                    }
+                    .into());
+                }
+            }
+        }
+
+        let (cell, steps) = self.resolve_place(target)?;
+        if steps.is_empty() {
+            let mut slot = cell.borrow_mut();
+            let value = match slot.numeric_type() {
+                Some(ty) => value.coerce_to(ty),
+                None => value,
+            };
+            *slot = value;
+            return Ok(());
+        }
+        let mut root = cell.borrow_mut();
+        Ok(write_place(&mut root, &steps, value, span)?)
+    }
+
+    /// 代入先の式をセルとアクセス経路に分解する
+    fn resolve_place(&mut self, target: &Expression) -> EvalResult<(Rc<RefCell<Value>>, Vec<PlaceStep>)> {
+        match target {
+            Expression::Identifier(_) | Expression::Path(_) => match self.variable_cell(target) {
+                Some(cell) => Ok((cell, Vec::new())),
+                None => Err(RuntimeError::Undefined {
+                    name: format!("{:?}", target),
+                    span: target.span(),
+                }
+                .into()),
+            },
+            Expression::Field(field) => {
+                let (cell, mut steps) = self.resolve_place(&field.object)?;
+                steps.push(PlaceStep::Field(field.field.clone()));
+                Ok((cell, steps))
+            }
+            Expression::Index(index) => {
+                let (cell, mut steps) = self.resolve_place(&index.object)?;
+                let key = self.eval_expression(&index.index)?.deref_all();
+                steps.push(PlaceStep::Index(key));
+                Ok((cell, steps))
+            }
+            Expression::Dereference(deref) => match self.eval_expression(&deref.expr)? {
+                Value::Ref(cell) => Ok((cell, Vec::new())),
+                other => Err(RuntimeError::InvalidValue {
+                    message: format!("参照ではない値 {} を参照外ししました", other.type_name()),
+                    span: deref.span,
+                }
+                .into()),
+            },
+            other => {
+                // 一時値への代入は観測できないため、一時セルに書き込む
+                let value = self.eval_expression(other)?;
+                Ok((Rc::new(RefCell::new(value)), Vec::new()))
+            }
+        }
+    }
+}
+
+/// 構造体やタプルのフィールドを取得
+fn field_of(object: &Value, field: &str) -> Option<Value> {
+    match object {
+        Value::Struct(_, fields) => fields.iter().find(|(name, _)| name == field).map(|(_, v)| v.clone()),
+        Value::Tuple(elements) => field.parse::<usize>().ok().and_then(|i| elements.get(i).cloned()),
+        _ => None,
+    }
+}
+
+/// インデックスの範囲をチェック
+fn checked_index(index: i128, len: usize, span: Span) -> Result<usize, RuntimeError> {
+    usize::try_from(index)
+        .ok()
+        .filter(|&i| i < len)
+        .ok_or(RuntimeError::IndexOutOfBounds { index, len, span })
+}
+
+/// アクセス経路を辿って値を書き込む
+fn write_place(target: &mut Value, steps: &[PlaceStep], value: Value, span: Span) -> Result<(), RuntimeError> {
+    // 参照を経由するフィールドアクセスは参照先に書き込む
+    if let Value::Ref(cell) = target {
+        if !steps.is_empty() {
+            let cell = cell.clone();
+            let mut inner = cell.borrow_mut();
+            return write_place(&mut inner, steps, value, span);
+        }
+    }
+
+    let (step, rest) = match steps.split_first() {
+        Some(split) => split,
+        None => {
+            *target = match target.numeric_type() {
+                Some(ty) => value.coerce_to(ty),
+                None => value,
+            };
+            return Ok(());
+        }
+    };
+
+    match (step, target) {
+        (PlaceStep::Field(name), Value::Struct(struct_name, fields)) => {
+            match fields.iter_mut().find(|(field, _)| field == name) {
+                Some((_, slot)) => write_place(slot, rest, value, span),
+                None => Err(RuntimeError::Undefined {
+                    name: format!("{}.{}", struct_name, name),
+                    span,
+                }),
+            }
+        }
+        (PlaceStep::Field(name), Value::Tuple(elements)) => {
+            let len = elements.len();
+            match name.parse::<usize>().ok().and_then(|i| elements.get_mut(i)) {
+                Some(slot) => write_place(slot, rest, value, span),
+                None => Err(RuntimeError::IndexOutOfBounds { index: -1, len, span }),
+            }
+        }
+        (PlaceStep::Index(Value::Int(i, _)), Value::Vec(elements)) => {
+            let elements = elements.clone();
+            let mut elements = elements.borrow_mut();
+            let len = elements.len();
+            let position = checked_index(*i, len, span).map_err(|error| match rest {
+                // 要素そのものへの代入は、要素を増やす方法を示す
+                [] => RuntimeError::IndexAssignmentOutOfBounds { index: *i, len, span },
+                _ => error,
+            })?;
+            write_place(&mut elements[position], rest, value, span)
+        }
+        (PlaceStep::Index(key), Value::Map(pairs)) => {
+            let pairs = pairs.clone();
+            let mut pairs = pairs.borrow_mut();
+            match pairs.iter_mut().position(|(k, _)| k == key) {
+                Some(position) => write_place(&mut pairs[position].1, rest, value, span),
+                None if rest.is_empty() => {
+                    pairs.push((key.clone(), value));
+                    Ok(())
+                }
+                None => Err(RuntimeError::InvalidValue {
+                    message: format!("キー {} が見つかりません", key),
+                    span,
+                }),
+            }
+        }
+        (_, target) => Err(RuntimeError::InvalidValue {
+            message: format!("{} の要素には代入できません", target.type_name()),
+            span,
+        }),
+    }
+}
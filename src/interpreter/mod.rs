@@ -0,0 +1,345 @@
+//! ツリーウォーキングインタプリタ
+//!
+//! このモジュールは意味解析（および単相化）済みのASTをLLVMを使わずに直接評価します。
+//! `yunilang run --interpret` による即時実行や `yunilang check --run-const` による
+//! 定数評価に使用され、整数のラップアラウンドやゼロ除算時の停止など
+//! コンパイル済みバックエンドと同じ意味論を持つように実装されています。
+
+mod builtins;
+mod environment;
+mod expr_eval;
+mod ops;
+mod pattern;
+mod stmt_eval;
+mod value;
+
+pub use value::Value;
+
+use crate::analyzer::TypeTable;
+use crate::assertion::SourceText;
+use crate::ast::*;
+use crate::error::{RuntimeError, YuniError, YuniResult};
+use environment::Environment;
+use std::collections::HashMap;
+use std::io::Write;
+
+/// 呼び出しの深さの上限
+const MAX_CALL_DEPTH: usize = 100_000;
+
+/// 定数評価での呼び出しの深さの上限
+const CONST_EVAL_MAX_CALL_DEPTH: usize = 1_000;
+
+/// 定数評価で1つの関数に使える手順数（ループの反復と関数呼び出しの回数）の上限
+pub const CONST_EVAL_STEP_LIMIT: u64 = 1_000_000;
+
+/// 評価を中断する理由
+///
+/// return文は式の途中（ブロック式やmatchのアーム内）からも関数を抜けるため、
+/// エラーと同じ経路で呼び出し元まで伝播させます。
+#[derive(Debug)]
+pub(crate) enum Unwind {
+    Return(Value),
+    Error(RuntimeError),
+}
+
+impl From<RuntimeError> for Unwind {
+    fn from(e: RuntimeError) -> Self {
+        Unwind::Error(e)
+    }
+}
+
+pub(crate) type EvalResult<T> = Result<T, Unwind>;
+
+/// インタプリタ本体
+pub struct Interpreter<'a> {
+    functions: HashMap<String, &'a FunctionDecl>,
+    externs: HashMap<String, &'a ExternFunctionDecl>,
+    // (レシーバー型名, メソッド名) -> メソッド宣言
+    methods: HashMap<(String, String), &'a MethodDecl>,
+    structs: HashMap<String, &'a StructDef>,
+    enums: HashMap<String, &'a EnumDef>,
+    env: Environment,
+    // ブロックごとに登録されたdeferの本体（内側のブロックが末尾）
+    defers: Vec<Vec<Block>>,
+    output: Box<dyn Write + 'a>,
+    call_depth: usize,
+    max_call_depth: usize,
+    // 実行した手順数とその上限（`None` なら無制限）
+    steps: u64,
+    step_limit: Option<u64>,
+    // 値が失われるキャストを検査する（`--checked-casts`）
+    checked_casts: bool,
+    // アサーションの失敗時のメッセージに引用するソースコード
+    source: Option<SourceText>,
+    // セマンティック解析で決定した式の型（文脈のない数値リテラルの型の決定に使用）
+    types: TypeTable,
+    // `random_u64` の内部状態（ランタイムと同じくシード0の状態から始める）
+    random_state: u64,
+}
+
+impl<'a> Interpreter<'a> {
+    /// 標準出力に書き込むインタプリタを作成
+    pub fn new(program: &'a Program) -> Self {
+        Self::with_output(program, Box::new(std::io::stdout()))
+    }
+
+    /// 出力先を指定してインタプリタを作成
+    pub fn with_output(program: &'a Program, output: Box<dyn Write + 'a>) -> Self {
+        let mut functions = HashMap::new();
+        let mut externs = HashMap::new();
+        let mut methods = HashMap::new();
+        let mut structs = HashMap::new();
+        let mut enums = HashMap::new();
+
+        for item in &program.items {
+            match item {
+                Item::Function(func) => {
+                    functions.insert(func.name.clone(), func);
+                }
+                Item::Method(method) => {
+                    if let Some(type_name) = receiver_type_name(&method.receiver.ty) {
+                        methods.insert((type_name, method.name.clone()), method);
+                    }
+                }
+                Item::TypeDef(TypeDef::Struct(struct_def)) => {
+                    structs.insert(struct_def.name.clone(), struct_def);
+                }
+                Item::TypeDef(TypeDef::Enum(enum_def)) => {
+                    enums.insert(enum_def.name.clone(), enum_def);
+                }
+                Item::TypeDef(TypeDef::Alias(_)) => {}
+                Item::ExternFunction(func) => {
+                    externs.insert(func.name.clone(), func);
+                }
+            }
+        }
+
+        Self {
+            functions,
+            externs,
+            methods,
+            structs,
+            enums,
+            env: Environment::new(),
+            defers: Vec::new(),
+            output,
+            call_depth: 0,
+            max_call_depth: MAX_CALL_DEPTH,
+            steps: 0,
+            step_limit: None,
+            checked_casts: false,
+            source: None,
+            types: TypeTable::new(),
+            random_state: builtins::random_state_from_seed(0),
+        }
+    }
+
+    /// 値が失われるキャストを実行時エラーにするかを設定
+    pub fn with_checked_casts(mut self, checked_casts: bool) -> Self {
+        self.checked_casts = checked_casts;
+        self
+    }
+
+    /// アサーションの失敗時のメッセージに引用するソースコードを設定
+    ///
+    /// 設定しない場合、メッセージの式はASTから出力し、位置を省きます。
+    pub fn with_source(mut self, source: SourceText) -> Self {
+        self.source = Some(source);
+        self
+    }
+
+    /// セマンティック解析で決定した式の型を設定
+    ///
+    /// matchのアームの値など、評価時に期待される型が分からない数値リテラルも
+    /// 解析やコード生成と同じ型で評価します。設定しない場合はリテラルの既定の型を使います。
+    pub fn with_type_table(mut self, types: TypeTable) -> Self {
+        self.types = types;
+        self
+    }
+
+    /// main関数を実行し、終了コードを返す
+    pub fn run_main(&mut self) -> YuniResult<i32> {
+        let result = self.call_function("main", Vec::new())?;
+        self.output.flush()?;
+        match result {
+            Value::Int(code, _) => Ok(code as i32),
+            _ => Ok(0),
+        }
+    }
+
+    /// 関数を名前で呼び出す
+    pub fn call_function(&mut self, name: &str, args: Vec<Value>) -> YuniResult<Value> {
+        self.invoke_function(name, args, Span::dummy())
+            .map_err(YuniError::Runtime)
+    }
+
+    /// ループの反復と関数呼び出しの回数の上限を設定
+    ///
+    /// 上限を超えると実行時エラーになります。設定しない場合は無制限です。
+    pub fn with_step_limit(mut self, limit: u64) -> Self {
+        self.step_limit = Some(limit);
+        self
+    }
+
+    /// 引数を取らず値を返すトップレベル関数を、main以外すべて評価する
+    ///
+    /// `check --run-const` で使用します。評価中の出力は破棄されます。
+    /// 止まらない関数でも検査が終わるように、関数ごとに手順数を
+    /// [`CONST_EVAL_STEP_LIMIT`] 回までに制限し、呼び出しの深さも浅く制限します。
+    /// 深さの上限までの再帰に備えて、`run --interpret` と同じく大きなスタックのスレッドで呼び出してください。
+    pub fn eval_const_functions(program: &'a Program, types: TypeTable) -> Vec<(String, YuniResult<Value>)> {
+        let mut interpreter = Self::with_output(program, Box::new(std::io::sink()))
+            .with_type_table(types)
+            .with_step_limit(CONST_EVAL_STEP_LIMIT);
+        interpreter.max_call_depth = CONST_EVAL_MAX_CALL_DEPTH;
+        program
+            .items
+            .iter()
+            .filter_map(|item| match item {
+                Item::Function(func)
+                    if func.name != "main"
+                        && func.params.is_empty()
+                        && func.type_params.is_empty()
+                        && func.return_type.as_deref().is_some_and(|ty| *ty != Type::Void) =>
+                {
+                    Some(func.name.clone())
+                }
+                _ => None,
+            })
+            .map(|name| {
+                interpreter.steps = 0;
+                let result = interpreter.call_function(&name, Vec::new());
+                (name, result)
+            })
+            .collect()
+    }
+
+    /// 手順を1つ消費する（ループの反復と関数呼び出しごと）
+    pub(crate) fn consume_step(&mut self, span: Span) -> Result<(), RuntimeError> {
+        match self.step_limit {
+            Some(limit) if self.steps >= limit => Err(RuntimeError::StepLimitExceeded { limit, span }),
+            _ => {
+                self.steps += 1;
+                Ok(())
+            }
+        }
+    }
+
+    /// ユーザー定義関数を呼び出す（組み込み関数へのフォールバック付き）
+    pub(crate) fn invoke_function(
+        &mut self,
+        name: &str,
+        args: Vec<Value>,
+        span: Span,
+    ) -> Result<Value, RuntimeError> {
+        let func = match self.functions.get(name) {
+            Some(func) => *func,
+            None => match self.externs.get(name) {
+                Some(func) => return self.call_extern(func, args, span),
+                None => return self.call_builtin(name, args, span),
+            },
+        };
+
+        if args.len() != func.params.len() {
+            return Err(RuntimeError::InvalidValue {
+                message: format!(
+                    "関数 {} の引数の数が一致しません: 期待値 {}, 実際 {}",
+                    name,
+                    func.params.len(),
+                    args.len()
+                ),
+                span,
+            });
+        }
+
+        let bindings = func
+            .params
+            .iter()
+            .zip(args)
+            .map(|(param, arg)| (param.name.clone(), arg.coerce_to(&param.ty), false))
+            .collect();
+
+        self.invoke_body(&func.body, bindings, func.return_type.as_deref(), span)
+    }
+
+    /// メソッドを呼び出す
+    pub(crate) fn invoke_method(
+        &mut self,
+        method: &'a MethodDecl,
+        receiver: Value,
+        args: Vec<Value>,
+        span: Span,
+    ) -> Result<Value, RuntimeError> {
+        if args.len() != method.params.len() {
+            return Err(RuntimeError::InvalidValue {
+                message: format!(
+                    "メソッド {} の引数の数が一致しません: 期待値 {}, 実際 {}",
+                    method.name,
+                    method.params.len(),
+                    args.len()
+                ),
+                span,
+            });
+        }
+
+        let receiver_name = method.receiver.name.clone().unwrap_or_else(|| "self".to_string());
+        let mut bindings = vec![(receiver_name, receiver, false)];
+        bindings.extend(
+            method
+                .params
+                .iter()
+                .zip(args)
+                .map(|(param, arg)| (param.name.clone(), arg.coerce_to(&param.ty), false)),
+        );
+
+        self.invoke_body(&method.body, bindings, method.return_type.as_deref(), span)
+    }
+
+    /// 関数本体を新しい環境で実行する
+    fn invoke_body(
+        &mut self,
+        body: &Block,
+        bindings: Vec<(String, Value, bool)>,
+        return_type: Option<&Type>,
+        span: Span,
+    ) -> Result<Value, RuntimeError> {
+        if self.call_depth >= self.max_call_depth {
+            return Err(RuntimeError::InvalidValue {
+                message: "呼び出しの深さが上限を超えました".to_string(),
+                span,
+            });
+        }
+        self.consume_step(span)?;
+
+        let saved_env = self.env.replace_with_fresh();
+        self.call_depth += 1;
+        for (name, value, is_mutable) in bindings {
+            self.env.define(&name, value, is_mutable);
+        }
+
+        let result = self.exec_block(body);
+
+        self.call_depth -= 1;
+        self.env = saved_env;
+
+        let value = match result {
+            Ok(()) => Value::Unit,
+            Err(Unwind::Return(value)) => value,
+            Err(Unwind::Error(e)) => return Err(e),
+        };
+
+        Ok(match return_type {
+            Some(ty) => value.coerce_to(ty),
+            None => value,
+        })
+    }
+}
+
+/// メソッドのレシーバー型から型名を取得
+fn receiver_type_name(ty: &Type) -> Option<String> {
+    match ty {
+        Type::UserDefined(name) => Some(name.clone()),
+        Type::Reference(inner, _) => receiver_type_name(inner),
+        _ => None,
+    }
+}
@@ -0,0 +1,222 @@
+//! 演算子とキャストの評価
+//!
+//! 整数演算はLLVMのadd/sub/mul（2の補数でラップ）、sdiv/srem（0方向への切り捨て）と
+//! 同じ結果になるように実装しています。
+
+use crate::analyzer::display_type;
+use crate::analyzer::numeric::{float_to_integer_bounds, integer_layout, integer_range, promote_numeric};
+use crate::ast::{BinaryOp, Span, Type, UnaryOp};
+use crate::error::RuntimeError;
+
+use super::value::{int_layout, is_float_type, is_integer_type, Value};
+
+/// 二項演算を評価
+pub fn binary_op(op: &BinaryOp, left: Value, right: Value, span: Span) -> Result<Value, RuntimeError> {
+    if *op == BinaryOp::In {
+        return contains(left, right, span);
+    }
+    match (left, right) {
+        (Value::Int(l, lt), Value::Int(r, rt)) => int_binary_op(op, l, lt, r, rt, span),
+        (Value::Float(l, lt), Value::Float(r, rt)) => {
+            // f64を優先する（コード生成のcoerce_float_typesと同じ）
+            let ty = if rt == Type::F64 { rt } else { lt };
+            let result = match op {
+                BinaryOp::Add => Value::float(l + r, ty),
+                BinaryOp::Subtract => Value::float(l - r, ty),
+                BinaryOp::Multiply => Value::float(l * r, ty),
+                BinaryOp::Divide => Value::float(l / r, ty),
+                BinaryOp::Modulo => Value::float(l % r, ty),
+                BinaryOp::Lt => Value::Bool(l < r),
+                BinaryOp::Gt => Value::Bool(l > r),
+                BinaryOp::Le => Value::Bool(l <= r),
+                BinaryOp::Ge => Value::Bool(l >= r),
+                BinaryOp::Eq => Value::Bool(l == r),
+                // IEEE 754に従い、NaNは自身を含むどの値とも等しくない（コード生成のfcmp une）
+                BinaryOp::Ne => Value::Bool(l != r),
+                _ => return Err(invalid_operands(op, "float", "float", span)),
+            };
+            Ok(result)
+        }
+        (Value::Bool(l), Value::Bool(r)) => {
+            // コード生成と同様に短絡評価は行わず、両辺を評価済みの値で計算する
+            let result = match op {
+                BinaryOp::And | BinaryOp::BitAnd => l & r,
+                BinaryOp::Or | BinaryOp::BitOr => l | r,
+                BinaryOp::BitXor | BinaryOp::Ne => l != r,
+                BinaryOp::Eq => l == r,
+                _ => return Err(invalid_operands(op, "bool", "bool", span)),
+            };
+            Ok(Value::Bool(result))
+        }
+        (Value::Str(l), Value::Str(r)) => {
+            let result = match op {
+                BinaryOp::Add => return Ok(Value::Str(l + &r)),
+                BinaryOp::Eq => l == r,
+                BinaryOp::Ne => l != r,
+                BinaryOp::Lt => l < r,
+                BinaryOp::Gt => l > r,
+                BinaryOp::Le => l <= r,
+                BinaryOp::Ge => l >= r,
+                _ => return Err(invalid_operands(op, "String", "String", span)),
+            };
+            Ok(Value::Bool(result))
+        }
+        (l, r) => match op {
+            BinaryOp::Eq => Ok(Value::Bool(l == r)),
+            BinaryOp::Ne => Ok(Value::Bool(l != r)),
+            _ => Err(invalid_operands(op, &l.type_name(), &r.type_name(), span)),
+        },
+    }
+}
+
+/// `in` 演算子を評価（Vecは要素、HashMapはキー、文字列は部分文字列を探す）
+///
+/// Vecの浮動小数点数はコード生成の比較関数と同じく、NaN同士を等しいとみなします。
+fn contains(needle: Value, container: Value, span: Span) -> Result<Value, RuntimeError> {
+    let found = match (container.deref_all(), needle.deref_all()) {
+        (Value::Vec(elements), needle) => elements.borrow().iter().any(|element| match (element, &needle) {
+            (Value::Float(a, _), Value::Float(b, _)) => a == b || (a.is_nan() && b.is_nan()),
+            (element, needle) => element == needle,
+        }),
+        (Value::Map(pairs), key) => pairs.borrow().iter().any(|(k, _)| *k == key),
+        (Value::Str(s), Value::Str(needle)) => s.contains(needle.as_str()),
+        (container, needle) => {
+            return Err(invalid_operands(&BinaryOp::In, &needle.type_name(), &container.type_name(), span))
+        }
+    };
+    Ok(Value::Bool(found))
+}
+
+/// 整数同士の二項演算を評価
+fn int_binary_op(
+    op: &BinaryOp,
+    l: i128,
+    lt: Type,
+    r: i128,
+    rt: Type,
+    span: Span,
+) -> Result<Value, RuntimeError> {
+    // 数値型の昇格規則で揃える（コード生成と同じ）
+    // 解析を経由しない符号の混在は、ビット幅の大きい方に揃えて左辺の符号で扱う
+    let (left_bits, left_signed) = int_layout(&lt);
+    let (right_bits, _) = int_layout(&rt);
+    let ty = promote_numeric(&lt, &rt).unwrap_or(if left_bits >= right_bits { lt } else { rt });
+    let signed = integer_layout(&ty).map_or(left_signed, |(_, signed)| signed);
+
+    let result = match op {
+        BinaryOp::Add => Value::int(l.wrapping_add(r), ty),
+        BinaryOp::Subtract => Value::int(l.wrapping_sub(r), ty),
+        BinaryOp::Multiply => Value::int(l.wrapping_mul(r), ty),
+        BinaryOp::Divide | BinaryOp::Modulo => {
+            if r == 0 {
+                return Err(RuntimeError::DivisionByZero { span });
+            }
+            let (bits, _) = int_layout(&ty);
+            let min = if bits >= 128 { i128::MIN } else { -(1i128 << (bits - 1)) };
+            if signed && l == min && r == -1 {
+                // x86のidivと同様にオーバーフローは停止扱い
+                return Err(RuntimeError::InvalidValue {
+                    message: "除算がオーバーフローしました".to_string(),
+                    span,
+                });
+            }
+            if matches!(op, BinaryOp::Divide) {
+                Value::int(l.wrapping_div(r), ty)
+            } else {
+                Value::int(l.wrapping_rem(r), ty)
+            }
+        }
+        BinaryOp::Lt => Value::Bool(l < r),
+        BinaryOp::Gt => Value::Bool(l > r),
+        BinaryOp::Le => Value::Bool(l <= r),
+        BinaryOp::Ge => Value::Bool(l >= r),
+        BinaryOp::Eq => Value::Bool(l == r),
+        BinaryOp::Ne => Value::Bool(l != r),
+        BinaryOp::And | BinaryOp::BitAnd => Value::int(l & r, ty),
+        BinaryOp::Or | BinaryOp::BitOr => Value::int(l | r, ty),
+        BinaryOp::BitXor => Value::int(l ^ r, ty),
+        BinaryOp::Shl | BinaryOp::Shr => {
+            let (bits, _) = int_layout(&ty);
+            if r < 0 || r >= bits as i128 {
+                return Err(RuntimeError::InvalidValue {
+                    message: format!("シフト量が範囲外です: {}", r),
+                    span,
+                });
+            }
+            if matches!(op, BinaryOp::Shl) {
+                Value::int(l.wrapping_shl(r as u32), ty)
+            } else {
+                Value::int(l >> r, ty)
+            }
+        }
+        // 整数はコンテナではない（`in` はbinary_opの入口で評価する）
+        BinaryOp::In => return Err(invalid_operands(op, &display_type(&ty), &display_type(&ty), span)),
+    };
+    Ok(result)
+}
+
+/// 単項演算を評価
+pub fn unary_op(op: &UnaryOp, operand: Value, span: Span) -> Result<Value, RuntimeError> {
+    match (op, operand) {
+        (UnaryOp::Not, Value::Bool(value)) => Ok(Value::Bool(!value)),
+        (UnaryOp::BitNot, Value::Int(value, ty)) => Ok(Value::int(!value, ty)),
+        (UnaryOp::Negate, Value::Int(value, ty)) => Ok(Value::int(value.wrapping_neg(), ty)),
+        (UnaryOp::Negate, Value::Float(value, ty)) => Ok(Value::float(-value, ty)),
+        (op, operand) => Err(RuntimeError::InvalidValue {
+            message: format!("単項演算 {:?} は {} に適用できません", op, operand.type_name()),
+            span,
+        }),
+    }
+}
+
+/// キャスト式を評価
+pub fn cast_value(value: Value, ty: &Type, span: Span) -> Result<Value, RuntimeError> {
+    match value {
+        Value::Int(v, _) if is_integer_type(ty) => Ok(Value::int(v, ty.clone())),
+        Value::Int(v, _) if is_float_type(ty) => Ok(Value::float(v as f64, ty.clone())),
+        Value::Float(v, _) if is_integer_type(ty) => Ok(Value::int(v as i128, ty.clone())),
+        Value::Float(v, _) if is_float_type(ty) => Ok(Value::float(v, ty.clone())),
+        Value::Bool(v) if is_integer_type(ty) => Ok(Value::int(v as i128, ty.clone())),
+        Value::Bool(_) if *ty == Type::Bool => Ok(value),
+        Value::Str(_) if matches!(ty, Type::String | Type::Str) => Ok(value),
+        other => Err(RuntimeError::InvalidValue {
+            message: format!("{} から {:?} へのキャストはサポートされていません", other.type_name(), ty),
+            span,
+        }),
+    }
+}
+
+/// 検査付きキャスト（`--checked-casts`）で、値が変換先の整数型の範囲内かを検査する
+///
+/// コンパイル済みバックエンドと同じく、浮動小数点数は切り捨てた値を検査し（NaNは範囲外）、
+/// 128ビットより広い整数型へのキャストは検査しません。
+pub fn check_cast(value: &Value, ty: &Type, span: Span) -> Result<(), RuntimeError> {
+    let (in_range, text, from) = match value {
+        Value::Int(v, from) => match integer_range(ty) {
+            Some((min, max)) => ((min..=max).contains(v), v.to_string(), from),
+            None => return Ok(()),
+        },
+        Value::Float(v, from) => match float_to_integer_bounds(ty) {
+            Some((min, max_exclusive)) => (v.trunc() >= min && v.trunc() < max_exclusive, v.to_string(), from),
+            None => return Ok(()),
+        },
+        _ => return Ok(()),
+    };
+    if in_range {
+        Ok(())
+    } else {
+        Err(RuntimeError::CastOutOfRange {
+            value: text,
+            from: display_type(from),
+            to: display_type(ty),
+            span,
+        })
+    }
+}
+
+fn invalid_operands(op: &BinaryOp, left: &str, right: &str, span: Span) -> RuntimeError {
+    RuntimeError::InvalidValue {
+        message: format!("演算 {:?} は {} と {} に適用できません", op, left, right),
+        span,
+    }
+}
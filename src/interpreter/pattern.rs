@@ -0,0 +1,123 @@
+//! パターンマッチングの評価
+
+use crate::ast::*;
+
+use super::value::Value;
+use super::{EvalResult, Interpreter};
+
+impl<'a> Interpreter<'a> {
+    /// 値をパターンに照合し、一致した場合は変数を現在のスコープに束縛する
+    ///
+    /// 参照は自動的に辿ってから照合します。
+    pub(crate) fn bind_pattern(&mut self, pattern: &Pattern, value: &Value) -> EvalResult<bool> {
+        let value = value.deref_all();
+        match pattern {
+            Pattern::Identifier(name, is_mut) => {
+                self.env.define(name, value, *is_mut);
+                Ok(true)
+            }
+            Pattern::Wildcard => Ok(true),
+            Pattern::Literal(literal) => Ok(match (literal, &value) {
+                (LiteralPattern::Integer(expected), Value::Int(actual, _)) => *expected as i128 == *actual,
+                (LiteralPattern::Float(expected), Value::Float(actual, _)) => expected == actual,
+                (LiteralPattern::String(expected), Value::Str(actual)) => expected == actual,
+                (LiteralPattern::Bool(expected), Value::Bool(actual)) => expected == actual,
+                _ => false,
+            }),
+            Pattern::Range { start, end, inclusive } => Ok(match &value {
+                Value::Int(actual, _) if *inclusive => (*start as i128..=*end as i128).contains(actual),
+                Value::Int(actual, _) => (*start as i128..*end as i128).contains(actual),
+                _ => false,
+            }),
+            Pattern::Tuple(patterns) => match &value {
+                Value::Tuple(elements) if elements.len() == patterns.len() => {
+                    self.bind_all(patterns.iter().zip(elements.iter()))
+                }
+                _ => Ok(false),
+            },
+            Pattern::Struct(name, field_patterns) => match &value {
+                Value::Struct(struct_name, fields) if struct_name == name => {
+                    for (field_name, field_pattern) in field_patterns {
+                        let field_value = match fields.iter().find(|(n, _)| n == field_name) {
+                            Some((_, v)) => v,
+                            None => return Ok(false),
+                        };
+                        if !self.bind_pattern(field_pattern, field_value)? {
+                            return Ok(false);
+                        }
+                    }
+                    Ok(true)
+                }
+                _ => Ok(false),
+            },
+            Pattern::EnumVariant { enum_name, variant, fields } => match &value {
+                Value::Enum(value_enum, value_variant, values)
+                    if value_enum == enum_name && value_variant == variant =>
+                {
+                    match fields {
+                        EnumVariantPatternFields::Unit => Ok(true),
+                        EnumVariantPatternFields::Tuple(patterns) => {
+                            if patterns.len() != values.len() {
+                                return Ok(false);
+                            }
+                            self.bind_all(patterns.iter().zip(values.iter()))
+                        }
+                        EnumVariantPatternFields::Struct(field_patterns) => {
+                            for (field_name, field_pattern) in field_patterns {
+                                let index = self.variant_field_index(enum_name, variant, field_name);
+                                let field_value = match index.and_then(|i| values.get(i)) {
+                                    Some(v) => v,
+                                    None => return Ok(false),
+                                };
+                                if !self.bind_pattern(field_pattern, field_value)? {
+                                    return Ok(false);
+                                }
+                            }
+                            Ok(true)
+                        }
+                    }
+                }
+                _ => Ok(false),
+            },
+            Pattern::Binding(name, pattern) => {
+                if !self.bind_pattern(pattern, &value)? {
+                    return Ok(false);
+                }
+                self.env.define(name, value, false);
+                Ok(true)
+            }
+            Pattern::Or(patterns) => {
+                // どの選択肢も同じ変数を束縛するため、一致しなかった選択肢が束縛した変数は
+                // 一致した選択肢で上書きされる
+                for pattern in patterns {
+                    if self.bind_pattern(pattern, &value)? {
+                        return Ok(true);
+                    }
+                }
+                Ok(false)
+            }
+        }
+    }
+
+    /// 複数のパターンと値の組をすべて照合する
+    fn bind_all<'p>(&mut self, pairs: impl Iterator<Item = (&'p Pattern, &'p Value)>) -> EvalResult<bool> {
+        for (pattern, value) in pairs {
+            if !self.bind_pattern(pattern, value)? {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
+    /// 列挙型バリアントのフィールド名から宣言順のインデックスを取得
+    pub(crate) fn variant_field_index(&self, enum_name: &str, variant: &str, field: &str) -> Option<usize> {
+        self.enums
+            .get(enum_name)?
+            .variants
+            .iter()
+            .find(|v| v.name == variant)?
+            .fields
+            .iter()
+            .position(|f| f.name == field)
+    }
+}
@@ -0,0 +1,161 @@
+//! 文の評価
+
+use crate::ast::*;
+use crate::error::RuntimeError;
+
+use super::value::Value;
+use super::{EvalResult, Interpreter, Unwind};
+
+impl<'a> Interpreter<'a> {
+    /// ブロックを新しいスコープで実行
+    pub(crate) fn exec_block(&mut self, block: &Block) -> EvalResult<()> {
+        self.env.push_scope();
+        self.defers.push(Vec::new());
+        let result = block.statements.iter().try_for_each(|stmt| self.exec_statement(stmt));
+        let result = self.run_deferred(result);
+        self.env.pop_scope();
+        result
+    }
+
+    /// 現在のブロックで登録されたdeferを登録と逆順に実行する
+    ///
+    /// 正常終了とreturnのときに実行し、実行時エラーのときは実行しません。
+    /// 本体はブロックのスコープが残っている間に実行されるため、ブロック内の変数を参照できます。
+    pub(crate) fn run_deferred<T>(&mut self, result: EvalResult<T>) -> EvalResult<T> {
+        let deferred = self.defers.pop().unwrap_or_default();
+        if matches!(result, Err(Unwind::Error(_))) {
+            return result;
+        }
+        for body in deferred.iter().rev() {
+            self.exec_block(body)?;
+        }
+        result
+    }
+
+    /// 文を実行
+    pub(crate) fn exec_statement(&mut self, stmt: &Statement) -> EvalResult<()> {
+        match stmt {
+            Statement::Let(let_stmt) => self.exec_let(let_stmt),
+            Statement::Assignment(assign) => {
+                let value = self.eval_expression(&assign.value)?;
+                self.assign_to(&assign.target, value, assign.span)?;
+                Ok(())
+            }
+            Statement::Return(ret) => {
+                let value = match &ret.value {
+                    Some(expr) => self.eval_expression(expr)?,
+                    None => Value::Unit,
+                };
+                Err(Unwind::Return(value))
+            }
+            Statement::If(if_stmt) => self.exec_if(if_stmt),
+            Statement::While(while_stmt) => {
+                while self.eval_condition(&while_stmt.condition)? {
+                    self.consume_step(while_stmt.span)?;
+                    self.exec_block(&while_stmt.body)?;
+                }
+                Ok(())
+            }
+            Statement::For(for_stmt) => self.exec_for(for_stmt),
+            Statement::Expression(expr) => {
+                self.eval_expression(expr)?;
+                Ok(())
+            }
+            Statement::Block(block) => self.exec_block(block),
+            Statement::Defer(defer_stmt) => {
+                if let Some(deferred) = self.defers.last_mut() {
+                    deferred.push(defer_stmt.body.clone());
+                }
+                Ok(())
+            }
+            // ローカル関数は単相化でトップレベルの関数に取り出している
+            Statement::Function(_) => Ok(()),
+        }
+    }
+
+    /// let文を実行
+    fn exec_let(&mut self, let_stmt: &LetStatement) -> EvalResult<()> {
+        let value = match &let_stmt.init {
+            Some(init) => {
+                let value = self.eval_expression_with_type(init, let_stmt.ty.as_ref())?;
+                match &let_stmt.ty {
+                    Some(ty) => value.coerce_to(ty),
+                    None => value,
+                }
+            }
+            None => Value::Unit,
+        };
+
+        match &let_stmt.pattern {
+            Pattern::Identifier(name, is_mut) => {
+                self.env.define(name, value, *is_mut);
+                Ok(())
+            }
+            pattern => {
+                if !self.bind_pattern(pattern, &value)? {
+                    return Err(RuntimeError::InvalidValue {
+                        message: "let文のパターンが値に一致しません".to_string(),
+                        span: let_stmt.span,
+                    }
+                    .into());
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// if文を実行
+    fn exec_if(&mut self, if_stmt: &IfStatement) -> EvalResult<()> {
+        if self.eval_condition(&if_stmt.condition)? {
+            self.exec_block(&if_stmt.then_branch)
+        } else {
+            match &if_stmt.else_branch {
+                Some(ElseBranch::Block(block)) => self.exec_block(block),
+                Some(ElseBranch::If(nested)) => self.exec_if(nested),
+                None => Ok(()),
+            }
+        }
+    }
+
+    /// for文を実行
+    fn exec_for(&mut self, for_stmt: &ForStatement) -> EvalResult<()> {
+        // ループ変数用のスコープ
+        self.env.push_scope();
+
+        let result = self.exec_for_loop(for_stmt);
+        self.env.pop_scope();
+        result
+    }
+
+    /// for文のinit文を実行し、条件が偽になるまで本体とupdate式を繰り返す
+    fn exec_for_loop(&mut self, for_stmt: &ForStatement) -> EvalResult<()> {
+        if let Some(init) = &for_stmt.init {
+            self.exec_statement(init)?;
+        }
+
+        loop {
+            if let Some(condition) = &for_stmt.condition {
+                if !self.eval_condition(condition)? {
+                    return Ok(());
+                }
+            }
+            self.consume_step(for_stmt.span)?;
+            self.exec_block(&for_stmt.body)?;
+            if let Some(update) = &for_stmt.update {
+                self.eval_expression(update)?;
+            }
+        }
+    }
+
+    /// 条件式をboolとして評価
+    pub(crate) fn eval_condition(&mut self, condition: &Expression) -> EvalResult<bool> {
+        match self.eval_expression(condition)?.deref_all() {
+            Value::Bool(value) => Ok(value),
+            other => Err(RuntimeError::InvalidValue {
+                message: format!("条件式がboolではありません: {}", other.type_name()),
+                span: condition.span(),
+            }
+            .into()),
+        }
+    }
+}
@@ -0,0 +1,271 @@
+//! インタプリタの実行時値
+
+use crate::ast::Type;
+use std::cell::RefCell;
+use std::fmt;
+use std::rc::Rc;
+
+/// 実行時の値
+///
+/// 整数と浮動小数点数は型情報を保持し、演算結果をその型の幅でラップします。
+/// Vec・HashMap・StringBuilder・参照はコンパイル済みバックエンドと同様に参照セマンティクスを持ちます。
+#[derive(Debug, Clone)]
+pub enum Value {
+    /// 整数値（値, 整数型）
+    Int(i128, Type),
+    /// 浮動小数点値（値, 浮動小数点型）
+    Float(f64, Type),
+    Bool(bool),
+    Str(String),
+    /// 構造体（構造体名, 宣言順のフィールド）
+    Struct(String, Vec<(String, Value)>),
+    /// 列挙型バリアント（列挙型名, バリアント名, 宣言順のフィールド値）
+    Enum(String, String, Vec<Value>),
+    Tuple(Vec<Value>),
+    Vec(Rc<RefCell<Vec<Value>>>),
+    /// HashMap（挿入順を保持するキーと値のペア）
+    Map(Rc<RefCell<Vec<(Value, Value)>>>),
+    /// 組み込みのStringBuilder（構築中の文字列）
+    StringBuilder(Rc<RefCell<String>>),
+    /// 変数や一時値への参照
+    Ref(Rc<RefCell<Value>>),
+    Unit,
+}
+
+impl Value {
+    /// 型付き整数値を作成（型の幅でラップする）
+    pub fn int(value: i128, ty: Type) -> Self {
+        Value::Int(wrap_int(value, &ty), ty)
+    }
+
+    /// 型付き浮動小数点値を作成（f32の場合は精度を落とす）
+    pub fn float(value: f64, ty: Type) -> Self {
+        Value::Float(round_float(value, &ty), ty)
+    }
+
+    /// 新しいVec値を作成
+    pub fn new_vec(elements: Vec<Value>) -> Self {
+        Value::Vec(Rc::new(RefCell::new(elements)))
+    }
+
+    /// バイト列から `Vec<u8>` の値を作成
+    pub fn bytes(bytes: &[u8]) -> Self {
+        Value::new_vec(bytes.iter().map(|&byte| Value::int(byte.into(), Type::U8)).collect())
+    }
+
+    /// `Vec<u8>` の値のバイト列（整数以外の要素を含む場合はNone）
+    pub fn to_bytes(&self) -> Option<Vec<u8>> {
+        match self.deref_all() {
+            Value::Vec(elements) => elements
+                .borrow()
+                .iter()
+                .map(|element| match element {
+                    Value::Int(value, _) => Some(*value as u8),
+                    _ => None,
+                })
+                .collect(),
+            _ => None,
+        }
+    }
+
+    /// 新しいHashMap値を作成
+    pub fn new_map(pairs: Vec<(Value, Value)>) -> Self {
+        Value::Map(Rc::new(RefCell::new(pairs)))
+    }
+
+    /// 参照を辿った先の値を取得
+    pub fn deref_all(&self) -> Value {
+        match self {
+            Value::Ref(cell) => cell.borrow().deref_all(),
+            other => other.clone(),
+        }
+    }
+
+    /// 値の型名（エラーメッセージ用）
+    pub fn type_name(&self) -> String {
+        match self {
+            Value::Int(_, ty) | Value::Float(_, ty) => format!("{:?}", ty),
+            Value::Bool(_) => "bool".to_string(),
+            Value::Str(_) => "String".to_string(),
+            Value::Struct(name, _) => name.clone(),
+            Value::Enum(name, _, _) => name.clone(),
+            Value::Tuple(_) => "tuple".to_string(),
+            Value::Vec(_) => "Vec".to_string(),
+            Value::Map(_) => "HashMap".to_string(),
+            Value::StringBuilder(_) => "StringBuilder".to_string(),
+            Value::Ref(cell) => format!("&{}", cell.borrow().type_name()),
+            Value::Unit => "()".to_string(),
+        }
+    }
+
+    /// 数値を指定された型に合わせる
+    ///
+    /// 整数同士・浮動小数点同士と、整数から浮動小数点型への場合のみ変換し、それ以外はそのまま返します。
+    /// 代入や引数渡しでの暗黙の幅変換をコンパイル済みバックエンドと揃えるために使用します。
+    /// 整数から浮動小数点型への変換は、期待される型が浮動小数点型の整数リテラル（`let x: f64 = 1;`）のためです。
+    /// `str` の位置に置いた文字列への参照は、参照先の文字列にします。
+    pub fn coerce_to(self, ty: &Type) -> Value {
+        match self {
+            Value::Ref(cell) if *ty == Type::Str && matches!(*cell.borrow(), Value::Str(_)) => cell.borrow().clone(),
+            Value::Int(value, _) if is_integer_type(ty) => Value::int(value, ty.clone()),
+            Value::Int(value, _) if is_float_type(ty) => Value::float(value as f64, ty.clone()),
+            Value::Float(value, _) if is_float_type(ty) => Value::float(value, ty.clone()),
+            other => other,
+        }
+    }
+
+    /// 値の数値型を取得（整数・浮動小数点以外はNone）
+    pub fn numeric_type(&self) -> Option<&Type> {
+        match self {
+            Value::Int(_, ty) | Value::Float(_, ty) => Some(ty),
+            _ => None,
+        }
+    }
+}
+
+impl PartialEq for Value {
+    /// 数値は型を無視して値で比較する（HashMapのキー検索などで使用）
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Value::Int(a, _), Value::Int(b, _)) => a == b,
+            (Value::Float(a, _), Value::Float(b, _)) => a == b,
+            (Value::Bool(a), Value::Bool(b)) => a == b,
+            (Value::Str(a), Value::Str(b)) => a == b,
+            (Value::Struct(a, af), Value::Struct(b, bf)) => a == b && af == bf,
+            (Value::Enum(a, av, af), Value::Enum(b, bv, bf)) => a == b && av == bv && af == bf,
+            (Value::Tuple(a), Value::Tuple(b)) => a == b,
+            (Value::Vec(a), Value::Vec(b)) => a == b,
+            (Value::Map(a), Value::Map(b)) => a == b,
+            (Value::StringBuilder(a), Value::StringBuilder(b)) => a == b,
+            (Value::Ref(a), Value::Ref(b)) => a == b,
+            (Value::Unit, Value::Unit) => true,
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Int(value, _) => write!(f, "{}", value),
+            // ランタイムのyuni_f32_to_string / yuni_f64_to_stringと同じ書式
+            Value::Float(value, Type::F8 | Type::F16 | Type::F32) => write!(f, "{}", *value as f32),
+            Value::Float(value, _) => write!(f, "{}", value),
+            Value::Bool(value) => write!(f, "{}", value),
+            Value::Str(value) => write!(f, "{}", value),
+            Value::Struct(name, fields) => {
+                write!(f, "{} {{ ", name)?;
+                for (i, (field, value)) in fields.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}: {}", field, value)?;
+                }
+                write!(f, " }}")
+            }
+            Value::Enum(name, variant, fields) => {
+                write!(f, "{}::{}", name, variant)?;
+                if !fields.is_empty() {
+                    write!(f, "(")?;
+                    for (i, value) in fields.iter().enumerate() {
+                        if i > 0 {
+                            write!(f, ", ")?;
+                        }
+                        write!(f, "{}", value)?;
+                    }
+                    write!(f, ")")?;
+                }
+                Ok(())
+            }
+            Value::Tuple(elements) => {
+                write!(f, "(")?;
+                for (i, value) in elements.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", value)?;
+                }
+                write!(f, ")")
+            }
+            Value::Vec(elements) => {
+                write!(f, "[")?;
+                for (i, value) in elements.borrow().iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", value)?;
+                }
+                write!(f, "]")
+            }
+            Value::Map(pairs) => {
+                write!(f, "{{")?;
+                for (i, (key, value)) in pairs.borrow().iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}: {}", key, value)?;
+                }
+                write!(f, "}}")
+            }
+            Value::StringBuilder(buf) => write!(f, "{}", buf.borrow()),
+            Value::Ref(cell) => write!(f, "{}", cell.borrow()),
+            Value::Unit => write!(f, "()"),
+        }
+    }
+}
+
+/// 整数型のビット幅と符号の有無を取得
+pub fn int_layout(ty: &Type) -> (u32, bool) {
+    match ty {
+        Type::I8 => (8, true),
+        Type::I16 => (16, true),
+        Type::I32 => (32, true),
+        Type::I64 => (64, true),
+        Type::I128 | Type::I256 => (128, true),
+        Type::U8 => (8, false),
+        Type::U16 => (16, false),
+        Type::U32 => (32, false),
+        Type::U64 => (64, false),
+        Type::U128 | Type::U256 => (128, false),
+        _ => (32, true),
+    }
+}
+
+/// 整数値を型の幅でラップアラウンドさせる
+///
+/// LLVMの整数演算（2の補数、オーバーフロー時は切り詰め）と同じ結果になります。
+pub fn wrap_int(value: i128, ty: &Type) -> i128 {
+    let (bits, signed) = int_layout(ty);
+    if bits >= 128 {
+        return value;
+    }
+    let modulus = 1i128 << bits;
+    let truncated = value & (modulus - 1);
+    if signed && truncated >= modulus >> 1 {
+        truncated - modulus
+    } else {
+        truncated
+    }
+}
+
+/// 浮動小数点値を型の精度に丸める
+pub fn round_float(value: f64, ty: &Type) -> f64 {
+    match ty {
+        Type::F8 | Type::F16 | Type::F32 => value as f32 as f64,
+        _ => value,
+    }
+}
+
+/// 整数型かどうか
+pub fn is_integer_type(ty: &Type) -> bool {
+    matches!(
+        ty,
+        Type::I8 | Type::I16 | Type::I32 | Type::I64 | Type::I128 | Type::I256
+            | Type::U8 | Type::U16 | Type::U32 | Type::U64 | Type::U128 | Type::U256
+    )
+}
+
+/// 浮動小数点型かどうか
+pub fn is_float_type(ty: &Type) -> bool {
+    matches!(ty, Type::F8 | Type::F16 | Type::F32 | Type::F64)
+}
@@ -79,6 +79,70 @@ pub fn unescape_string(s: &str) -> String {
     result
 }
 
+/// バイト列リテラルのエスケープシーケンスを処理
+///
+/// `\xNN` で任意のバイトを表せます。ASCII以外の文字と認識できないエスケープシーケンスは
+/// 受け付けません（`None` を返す）。
+pub fn unescape_bytes(s: &str) -> Option<Vec<u8>> {
+    let mut result = Vec::with_capacity(s.len());
+    let mut chars = s.chars();
+
+    while let Some(ch) = chars.next() {
+        let byte = match ch {
+            '\\' => match chars.next()? {
+                'n' => b'\n',
+                'r' => b'\r',
+                't' => b'\t',
+                '\\' => b'\\',
+                '"' => b'"',
+                '\'' => b'\'',
+                '0' => 0,
+                'x' => {
+                    let hex: String = chars.by_ref().take(2).collect();
+                    if hex.len() != 2 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+                        return None;
+                    }
+                    u8::from_str_radix(&hex, 16).ok()?
+                }
+                _ => return None,
+            },
+            c if c.is_ascii() => c as u8,
+            _ => return None,
+        };
+        result.push(byte);
+    }
+
+    Some(result)
+}
+
+/// 整数リテラルの表記を値に変換
+///
+/// `0x`・`0o`・`0b` の接頭辞で基数を指定でき、桁の間の `_` は無視します。
+pub fn parse_integer(s: &str) -> Option<i128> {
+    let (negative, digits) = match s.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, s),
+    };
+    let (radix, digits) = match digits.get(..2) {
+        Some("0x" | "0X") => (16, &digits[2..]),
+        Some("0o" | "0O") => (8, &digits[2..]),
+        Some("0b" | "0B") => (2, &digits[2..]),
+        _ => (10, digits),
+    };
+    let digits: String = digits.chars().filter(|&c| c != '_').collect();
+    if digits.is_empty() {
+        return None;
+    }
+    // 負の最小値を表せるよう、符号を付けてから変換する
+    let signed = if negative { format!("-{}", digits) } else { digits };
+    i128::from_str_radix(&signed, radix).ok()
+}
+
+/// 浮動小数点リテラルの表記を値に変換（桁の間の `_` は無視する）
+pub fn parse_float(s: &str) -> Option<f64> {
+    s.chars().filter(|&c| c != '_').collect::<String>().parse().ok()
+}
+
 /// 整数リテラルを解析（型サフィックス付き）
 #[allow(dead_code)]
 pub fn parse_integer_with_suffix(s: &str) -> Option<(i128, Option<String>)> {
@@ -133,6 +197,18 @@ mod tests {
         assert_eq!(unescape_string("\\u{1F600}"), "😀");
     }
 
+    #[test]
+    fn test_unescape_bytes() {
+        assert_eq!(unescape_bytes("abc"), Some(b"abc".to_vec()));
+        assert_eq!(unescape_bytes("\\x00\\xff\\n"), Some(vec![0, 0xff, b'\n']));
+        assert_eq!(unescape_bytes("\\0\\\"\\\\"), Some(vec![0, b'"', b'\\']));
+        assert_eq!(unescape_bytes("\\xg0"), None);
+        assert_eq!(unescape_bytes("\\x4"), None);
+        assert_eq!(unescape_bytes("\\x+f"), None);
+        assert_eq!(unescape_bytes("\\u{41}"), None);
+        assert_eq!(unescape_bytes("é"), None);
+    }
+
     #[test]
     fn test_parse_integer_with_suffix() {
         assert_eq!(parse_integer_with_suffix("42"), Some((42, None)));
@@ -141,6 +217,26 @@ mod tests {
         assert_eq!(parse_integer_with_suffix("-10i8"), Some((-10, Some("i8".to_string()))));
     }
 
+    #[test]
+    fn test_parse_integer() {
+        assert_eq!(parse_integer("42"), Some(42));
+        assert_eq!(parse_integer("1_000_000"), Some(1_000_000));
+        assert_eq!(parse_integer("0xFF"), Some(255));
+        assert_eq!(parse_integer("0x1f_ff"), Some(0x1fff));
+        assert_eq!(parse_integer("0o17"), Some(15));
+        assert_eq!(parse_integer("0b1010_1010"), Some(0b1010_1010));
+        assert_eq!(parse_integer("-0x80"), Some(-128));
+        assert_eq!(parse_integer("-170141183460469231731687303715884105728"), Some(i128::MIN));
+        assert_eq!(parse_integer("0x"), None);
+        assert_eq!(parse_integer("0b102"), None);
+    }
+
+    #[test]
+    fn test_parse_float() {
+        assert_eq!(parse_float("1_000.5"), Some(1000.5));
+        assert_eq!(parse_float("-0.25"), Some(-0.25));
+    }
+
     #[test]
     #[allow(clippy::approx_constant)]
     fn test_parse_float_with_suffix() {
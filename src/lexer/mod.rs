@@ -8,7 +8,9 @@ mod tokenizer;
 mod literal_parser;
 mod template_string;
 mod token;
+mod trivia;
 
 // 公開API
 pub use tokenizer::{Lexer, TokenWithPosition};
-pub use token::Token;
\ No newline at end of file
+pub use token::Token;
+pub use trivia::{Comment, CommentKind};
\ No newline at end of file
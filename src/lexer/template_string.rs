@@ -42,6 +42,7 @@ pub fn parse_template_string(input: &str, span: Span) -> Result<TemplateStringLi
                         return Err(ParserError::SyntaxError {
                             message: "Unterminated interpolation in template string".to_string(),
                             span,
+                            context: None,
                         });
                     }
                 }
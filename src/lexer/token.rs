@@ -3,9 +3,12 @@
 use logos::Logos;
 use std::fmt;
 
+use super::trivia::{self, Comment};
+
 /// Yuni言語のトークン型
 #[derive(Logos, Debug, PartialEq, Clone)]
 #[logos(skip r"[ \t\f]+")] // 空白文字をスキップ（改行以外）
+#[logos(extras = Vec<Comment>)] // 読み飛ばしたコメント（トリビア）
 pub enum Token {
     // キーワード
     #[token("package")]
@@ -32,16 +35,24 @@ pub enum Token {
     Else,
     #[token("for")]
     For,
+    #[token("in")]
+    In,
     #[token("while")]
     While,
     #[token("return")]
     Return,
+    #[token("defer")]
+    Defer,
     #[token("lives")]
     Lives,
     #[token("as")]
     As,
     #[token("impl")]
     Impl,
+    #[token("inline")]
+    Inline,
+    #[token("extern")]
+    Extern,
     #[token("self")]
     SelfValue,
     #[token("match")]
@@ -91,28 +102,41 @@ pub enum Token {
     #[regex(r"[a-zA-Z_][a-zA-Z0-9_]*", |lex| lex.slice().to_owned(), priority = 1)]
     Identifier(String),
 
-    // 数値リテラル（型サフィックス付き）
+    // 数値リテラル（値とソース上の表記。型サフィックスは別のトークン）
     #[regex(
-        r"-?[0-9]+",
-        |lex| lex.slice().parse::<i128>().ok()
+        r"-?(0[xX][0-9a-fA-F_]+|0[oO][0-7_]+|0[bB][01_]+|[0-9][0-9_]*)",
+        |lex| super::literal_parser::parse_integer(lex.slice()).map(|value| (value, lex.slice().to_owned()))
     )]
-    Integer(i128),
+    Integer((i128, String)),
 
-    #[regex(r"-?[0-9]+\.[0-9]+", |lex| lex.slice().parse::<f64>().ok())]
-    Float(f64),
+    #[regex(
+        r"-?[0-9][0-9_]*\.[0-9][0-9_]*",
+        |lex| super::literal_parser::parse_float(lex.slice()).map(|value| (value, lex.slice().to_owned()))
+    )]
+    Float((f64, String)),
 
-    // 文字列リテラル
+    // 文字列リテラル（閉じられていない場合は入力の終わりまでをエラートークンにする）
     #[regex(r#""([^"\\]|\\.)*""#, |lex| {
         let s = lex.slice();
         super::literal_parser::unescape_string(&s[1..s.len()-1])
     })]
+    #[regex(r#""([^"\\]|\\.)*\\?"#, |_| None::<String>)]
     String(String),
 
-    // テンプレート文字列リテラル（バッククォート）
+    // バイト列リテラル（`b"..."`。ASCII文字とエスケープシーケンスだけを含む）
+    #[regex(r#"b"([^"\\]|\\.)*""#, |lex| {
+        let s = lex.slice();
+        super::literal_parser::unescape_bytes(&s[2..s.len()-1])
+    })]
+    #[regex(r#"b"([^"\\]|\\.)*\\?"#, |_| None::<Vec<u8>>)]
+    Bytes(Vec<u8>),
+
+    // テンプレート文字列リテラル（バッククォート。中の `//` や `/*` はコメントにならない）
     #[regex(r"`([^`\\]|\\.)*`", |lex| {
         let s = lex.slice();
         s[1..s.len()-1].to_owned()
     })]
+    #[regex(r"`([^`\\]|\\.)*\\?", |_| None::<String>)]
     TemplateString(String),
 
     // 演算子
@@ -182,10 +206,18 @@ pub enum Token {
     ColonColon,
     #[token(":")]
     Colon,
+    #[token("..=")]
+    DotDotEq,
+    #[token("..")]
+    DotDot,
     #[token(".")]
     Dot,
     #[token("->")]
     Arrow,
+    #[token("@")]
+    At,
+    #[token("#")]
+    Hash,
 
     // 特殊トークン
     #[regex(r"\n")]
@@ -194,32 +226,13 @@ pub enum Token {
     // エラートークン
     Error,
 
-    // コメント
-    #[regex(r"//[^\n]*", logos::skip, allow_greedy = true)]
-    #[regex(r"/\*([^*]|\*[^/])*\*/", logos::skip)]
-    #[token("/*", |lex| {
-        // ネストしたブロックコメントを処理
-        let mut depth = 1;
-        let mut chars = lex.remainder().chars();
-        let mut prev = '\0';
-        
-        while depth > 0 {
-            match chars.next() {
-                Some('/') if prev == '*' => depth -= 1,
-                Some('*') if prev == '/' => depth += 1,
-                Some(c) => prev = c,
-                None => break,
-            }
-            lex.bump(prev.len_utf8());
-        }
-        
-        if depth == 0 {
-            logos::Skip
-        } else {
-            // 未完了のコメントはエラー
-            logos::Skip
-        }
-    })]
+    // ドキュメントコメント（`///` の後の本文）
+    #[regex(r"///[^\n]*", trivia::doc_comment, allow_greedy = true)]
+    DocComment(String),
+
+    // コメント（トリビアとして記録して読み飛ばす。ブロックコメントはネストできる）
+    #[regex(r"//[^\n]*", trivia::line_comment, allow_greedy = true)]
+    #[token("/*", trivia::block_comment)]
     _Comment,
 }
 
@@ -238,11 +251,15 @@ impl fmt::Display for Token {
             Token::If => write!(f, "if"),
             Token::Else => write!(f, "else"),
             Token::For => write!(f, "for"),
+            Token::In => write!(f, "in"),
             Token::While => write!(f, "while"),
             Token::Return => write!(f, "return"),
+            Token::Defer => write!(f, "defer"),
             Token::Lives => write!(f, "lives"),
             Token::As => write!(f, "as"),
             Token::Impl => write!(f, "impl"),
+            Token::Inline => write!(f, "inline"),
+            Token::Extern => write!(f, "extern"),
             Token::SelfValue => write!(f, "self"),
             Token::Match => write!(f, "match"),
             Token::I8 => write!(f, "i8"),
@@ -264,9 +281,10 @@ impl fmt::Display for Token {
             Token::True => write!(f, "true"),
             Token::False => write!(f, "false"),
             Token::Identifier(s) => write!(f, "{}", s),
-            Token::Integer(n) => write!(f, "{}", n),
-            Token::Float(n) => write!(f, "{}", n),
+            Token::Integer((_, raw)) => write!(f, "{}", raw),
+            Token::Float((_, raw)) => write!(f, "{}", raw),
             Token::String(s) => write!(f, "\"{}\"", s),
+            Token::Bytes(bytes) => write!(f, "b\"{}\"", bytes.escape_ascii()),
             Token::TemplateString(s) => write!(f, "`{}`", s),
             Token::Plus => write!(f, "+"),
             Token::Minus => write!(f, "-"),
@@ -300,10 +318,15 @@ impl fmt::Display for Token {
             Token::Semicolon => write!(f, ";"),
             Token::ColonColon => write!(f, "::"),
             Token::Colon => write!(f, ":"),
+            Token::DotDotEq => write!(f, "..="),
+            Token::DotDot => write!(f, ".."),
             Token::Dot => write!(f, "."),
             Token::Arrow => write!(f, "->"),
+            Token::At => write!(f, "@"),
+            Token::Hash => write!(f, "#"),
             Token::Newline => write!(f, "\\n"),
             Token::Error => write!(f, "error"),
+            Token::DocComment(_) => write!(f, "doc comment"),
             Token::_Comment => write!(f, "comment"),
         }
     }
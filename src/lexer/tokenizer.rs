@@ -2,8 +2,11 @@
 
 use logos::{Lexer as LogosLexer, Logos, Span};
 
+use crate::error::LexerError;
+
 use super::token::Token;
 use super::template_string::find_interpolations;
+use super::trivia::Comment;
 
 /// 位置情報付きトークン
 #[derive(Debug, Clone)]
@@ -12,6 +15,43 @@ pub struct TokenWithPosition {
     pub span: Span,
 }
 
+impl TokenWithPosition {
+    /// エラートークンの診断
+    ///
+    /// 閉じられていないブロックコメントと文字列は入力の終わりまでが1つのエラートークンになるため、
+    /// 開始位置の区切り記号（`/*`・`"`・`b"`・`` ` ``）を指すエラーにします。
+    pub fn lexer_error(&self, source: &str) -> LexerError {
+        let text = source.get(self.span.clone()).unwrap_or("不明");
+        let start = self.span.start;
+        if text.starts_with("/*") {
+            return LexerError::UnterminatedComment { span: (start..start + 2).into() };
+        }
+        // エラートークンはASCII以外の文字で始まることもあるため、バイト単位では切り出さない
+        let opening = [("b\"", '"'), ("\"", '"'), ("`", '`')]
+            .into_iter()
+            .find(|(opening, _)| text.starts_with(opening));
+        match opening {
+            Some((opening, quote)) if !is_closed(&text[opening.len()..], quote) => {
+                LexerError::UnterminatedString { span: (start..start + opening.len()).into() }
+            }
+            _ => LexerError::UnrecognizedToken { token: text.to_string(), span: self.span.clone().into() },
+        }
+    }
+}
+
+/// 開始の引用符の後の文字列が、エスケープされていない引用符で終わっているか
+fn is_closed(body: &str, quote: char) -> bool {
+    let mut chars = body.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            chars.next();
+        } else if c == quote {
+            return chars.as_str().is_empty();
+        }
+    }
+    false
+}
+
 /// Yuni言語のレキサー
 pub struct Lexer<'a> {
     inner: LogosLexer<'a, Token>,
@@ -43,12 +83,17 @@ impl<'a> Lexer<'a> {
     }
 
     /// すべてのトークンを収集
-    pub fn collect_tokens(mut self) -> Vec<TokenWithPosition> {
+    pub fn collect_tokens(self) -> Vec<TokenWithPosition> {
+        self.collect_tokens_with_trivia().0
+    }
+
+    /// すべてのトークンと、読み飛ばしたコメントを収集
+    pub fn collect_tokens_with_trivia(mut self) -> (Vec<TokenWithPosition>, Vec<Comment>) {
         let mut tokens = Vec::new();
         while let Some(token) = self.next_token() {
             tokens.push(token);
         }
-        tokens
+        (tokens, self.inner.extras)
     }
 }
 
@@ -119,7 +164,7 @@ mod tests {
         assert!(matches!(tokens[0].token, Token::Let));
         assert!(matches!(tokens[1].token, Token::Identifier(_)));
         assert!(matches!(tokens[2].token, Token::Assign));
-        assert!(matches!(tokens[3].token, Token::Integer(_)));
+        assert!(matches!(tokens[3].token, Token::Integer((_, _))));
         assert!(matches!(tokens[4].token, Token::Semicolon));
     }
 
@@ -155,10 +200,10 @@ mod tests {
         let tokens = tokenize(input);
         
         assert_eq!(tokens.len(), 4); // 数値4つ
-        assert!(matches!(tokens[0].token, Token::Integer(42)));
-        assert!(matches!(tokens[1].token, Token::Float(_)));
-        assert!(matches!(tokens[2].token, Token::Integer(100)));
-        assert!(matches!(tokens[3].token, Token::Float(_)));
+        assert!(matches!(tokens[0].token, Token::Integer((42, _))));
+        assert!(matches!(tokens[1].token, Token::Float((_, _))));
+        assert!(matches!(tokens[2].token, Token::Integer((100, _))));
+        assert!(matches!(tokens[3].token, Token::Float((_, _))));
     }
 
     #[test]
@@ -181,7 +226,7 @@ mod tests {
 
     #[test]
     fn test_error_tokens() {
-        let input = "let x = @#$;";
+        let input = "let x = ?\\$;";
         let lexer = Lexer::new(input);
         let tokens = lexer.collect_tokens();
         
@@ -194,6 +239,6 @@ mod tests {
         println!("Error count: {}", error_count);
         
         // 実際のトークン数とエラー数を確認
-        assert!(error_count == 3); // @, #, $ がそれぞれエラートークンになる
+        assert!(error_count == 3); // ?, \, $ がそれぞれエラートークンになる
     }
 }
\ No newline at end of file
@@ -0,0 +1,88 @@
+//! コメントのトリビア
+//!
+//! 通常のコメントはトークンとしては読み飛ばしますが、整形器がソースコードを書き戻せるよう、
+//! 種類・本文・位置をトリビアとして残します。ドキュメントコメント（`///`）はトリビアではなく
+//! `Token::DocComment` として出力します。
+//!
+//! ブロックコメントはネストでき、コメントアウトしたコードの中のコメントで終わりません。
+//! 文字列リテラルの中の `//` や `/*` は文字列のトークンの一部になるため、コメントとしては扱いません。
+
+use logos::{FilterResult, Lexer as LogosLexer, Skip, Span};
+
+use super::token::Token;
+
+/// コメントの種類
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CommentKind {
+    /// `// ...`
+    Line,
+    /// `/* ... */`
+    Block,
+}
+
+/// 読み飛ばしたコメント
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Comment {
+    pub kind: CommentKind,
+    /// 区切り記号を含むコメントの全体
+    pub text: String,
+    pub span: Span,
+}
+
+/// 行コメントをトリビアに記録して読み飛ばす
+pub(super) fn line_comment(lex: &mut LogosLexer<Token>) -> Skip {
+    record(lex, CommentKind::Line);
+    Skip
+}
+
+/// ネストしたブロックコメントを対応する `*/` まで読み、トリビアに記録して読み飛ばす
+///
+/// `/*` で深さを1つ増やし、`*/` で1つ減らします。閉じられていない場合は
+/// 入力の終わりまでをエラートークンにします。
+pub(super) fn block_comment(lex: &mut LogosLexer<Token>) -> FilterResult<(), ()> {
+    let rest = lex.remainder().as_bytes();
+    let mut depth = 1;
+    let mut i = 0;
+    while i < rest.len() {
+        match (rest[i], rest.get(i + 1)) {
+            (b'/', Some(b'*')) => {
+                depth += 1;
+                i += 2;
+            }
+            (b'*', Some(b'/')) => {
+                depth -= 1;
+                i += 2;
+                if depth == 0 {
+                    lex.bump(i);
+                    record(lex, CommentKind::Block);
+                    return FilterResult::Skip;
+                }
+            }
+            _ => i += 1,
+        }
+    }
+    lex.bump(rest.len());
+    FilterResult::Error(())
+}
+
+fn record(lex: &mut LogosLexer<Token>, kind: CommentKind) {
+    let comment = Comment {
+        kind,
+        text: lex.slice().to_string(),
+        span: lex.span(),
+    };
+    lex.extras.push(comment);
+}
+
+/// `///` のドキュメントコメントの本文（先頭の空白1つを除く）
+///
+/// `////` で始まるコメントは区切り線などの通常のコメントとして扱います。
+pub(super) fn doc_comment(lex: &mut LogosLexer<Token>) -> logos::Filter<String> {
+    let text = &lex.slice()[3..];
+    if text.starts_with('/') {
+        record(lex, CommentKind::Line);
+        return logos::Filter::Skip;
+    }
+    let text = text.strip_prefix(' ').unwrap_or(text).trim_end();
+    logos::Filter::Emit(text.to_string())
+}
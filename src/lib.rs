@@ -1,20 +1,54 @@
 //! Yuni Language Compiler Library
 //!
 //! This library provides the core functionality for the Yuni language compiler.
+//!
+//! The `full` feature (enabled by default) includes LLVM code generation and the
+//! native toolchain. With only the `frontend` feature, the crate provides lexing,
+//! parsing, semantic analysis and diagnostics without filesystem, process or LLVM
+//! usage, so it can be built for `wasm32-unknown-unknown`.
+//!
+//! The [`compile`] module is the supported entry point for embedding the compiler:
+//! its functions and types follow semver. The other modules expose compiler internals
+//! for tools and tests and may change between releases.
 
 pub mod analyzer;
+pub mod assertion;
 pub mod ast;
+#[cfg(feature = "full")]
 pub mod codegen;
+pub mod compile;
+#[cfg(feature = "full")]
 pub mod compiler;
+pub(crate) mod console;
+#[cfg(feature = "full")]
+pub mod deps;
+pub mod doc;
+#[cfg(feature = "full")]
+pub mod doctor;
 pub mod error;
+pub mod frontend;
+pub mod fuzz;
+pub mod interpreter;
 pub mod lexer;
+#[cfg(feature = "full")]
+pub mod manifest;
 pub mod parser;
+#[cfg(feature = "full")]
+pub mod repl;
+#[cfg(feature = "full")]
 pub mod runtime;
+#[cfg(feature = "full")]
+pub mod source_file;
+pub mod symbols;
+#[cfg(feature = "full")]
+pub mod toolchain;
 
 // Re-export commonly used types
 pub use analyzer::SemanticAnalyzer;
 pub use ast::{Expression, Program, Statement};
+#[cfg(feature = "full")]
 pub use codegen::CodeGenerator;
 pub use error::{ErrorCollector, YuniError, YuniResult};
+pub use frontend::{check_source, CheckResult};
 pub use lexer::{Lexer, Token, TokenWithPosition};
 pub use parser::{ParseError, ParseResult, Parser};
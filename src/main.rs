@@ -1,21 +1,42 @@
-use clap::{Parser, Subcommand, ValueEnum};
+use clap::{Args, Parser, Subcommand, ValueEnum};
 use colored::Colorize;
 use std::fs;
+use std::io::{self, IsTerminal, Write};
 use std::path::{Path, PathBuf};
-use std::process::Command;
+use std::process::{Child, Command, ExitStatus, Stdio};
 use tempfile::TempDir;
 
 mod analyzer;
+mod assertion;
 mod ast;
 mod codegen;
 mod compiler;
+mod console;
+mod deps;
+mod doc;
+mod doctor;
 mod error;
+mod interpreter;
 mod lexer;
+mod manifest;
 mod parser;
+mod repl;
 mod runtime;
-
-use crate::compiler::{CompilationPipeline, CompilationState};
+mod source_file;
+mod symbols;
+mod toolchain;
+
+use crate::analyzer::{BorrowCheckMode, LintLevel, LintLevels};
+use crate::ast::Program;
+use crate::codegen::CodegenOptions;
+use crate::compiler::{CodeSizeLimits, CompilationPipeline, CompilationState};
+use crate::deps::DependencyGraph;
+use crate::doctor::{CheckStatus, Doctor, Tool};
 use crate::error::{YuniError, YuniResult};
+use crate::manifest::Manifest;
+use crate::repl::ReplSession;
+use crate::symbols::SymbolIndex;
+use crate::toolchain::Target;
 
 #[derive(Parser)]
 #[command(name = "yunilang")]
@@ -24,9 +45,9 @@ struct Cli {
     #[command(subcommand)]
     command: Commands,
 
-    /// Enable verbose output
-    #[arg(short, long, global = true)]
-    verbose: bool,
+    /// Enable verbose output (-v logs compiler decisions at debug level, -vv at trace level)
+    #[arg(short, long, global = true, action = clap::ArgAction::Count)]
+    verbose: u8,
 }
 
 #[derive(Clone, Copy, Debug, ValueEnum)]
@@ -43,6 +64,191 @@ enum EmitType {
     /// Emit assembly (.s)
     #[value(name = "asm")]
     Asm,
+    /// Emit the files the input depends on through imports (see --deps-format)
+    #[value(name = "deps")]
+    Deps,
+    /// Emit the call graph of the functions and methods in DOT format
+    #[value(name = "callgraph")]
+    CallGraph,
+    /// Emit the parsed AST as a versioned JSON document for tools (before name resolution)
+    #[value(name = "ast-json")]
+    AstJson,
+}
+
+/// Output format of `--emit=deps`
+#[derive(Clone, Copy, Debug, Default, ValueEnum)]
+enum DepsFormat {
+    /// A Makefile rule `input.o: input.yuni dep.yuni ...`
+    #[default]
+    Make,
+    /// A JSON node/edge list including unresolved imports and import cycles
+    Json,
+}
+
+/// How to report borrow checker violations
+#[derive(Clone, Copy, Debug, Default, ValueEnum)]
+enum BorrowCheckArg {
+    /// Skip the borrow checker (type checking still runs)
+    Off,
+    /// Report conflicting borrows and uses after move as warnings
+    Warn,
+    /// Report conflicting borrows and uses after move as errors
+    #[default]
+    Error,
+}
+
+impl From<BorrowCheckArg> for BorrowCheckMode {
+    fn from(mode: BorrowCheckArg) -> Self {
+        match mode {
+            BorrowCheckArg::Off => Self::Off,
+            BorrowCheckArg::Warn => Self::Warn,
+            BorrowCheckArg::Error => Self::Error,
+        }
+    }
+}
+
+/// Lint levels for warnings
+#[derive(Args, Clone, Debug, Default)]
+struct LintArgs {
+    /// Ignore the lint NAME (`warnings` selects every lint; can be repeated)
+    #[arg(short = 'A', long = "allow", value_name = "NAME")]
+    allow: Vec<String>,
+
+    /// Report the lint NAME as a warning (the default for every lint)
+    #[arg(short = 'W', long = "warn", value_name = "NAME")]
+    warn: Vec<String>,
+
+    /// Report the lint NAME as an error so compilation fails (`--deny warnings` denies every lint)
+    #[arg(short = 'D', long = "deny", value_name = "NAME")]
+    deny: Vec<String>,
+}
+
+impl LintArgs {
+    /// Build the lint level table; a lint named in several flags takes the strictest level
+    fn levels(&self) -> YuniResult<LintLevels> {
+        let mut levels = LintLevels::default();
+        for (names, level) in [(&self.allow, LintLevel::Allow), (&self.warn, LintLevel::Warn), (&self.deny, LintLevel::Deny)] {
+            for name in names {
+                levels.set(name, level)?;
+            }
+        }
+        Ok(levels)
+    }
+}
+
+/// Thresholds for the size of the generated code
+#[derive(Args, Clone, Copy, Debug)]
+struct CodeSizeArgs {
+    /// Abort when monomorphization generates more than N generic instantiations
+    #[arg(long, value_name = "N")]
+    max_instantiations: Option<usize>,
+
+    /// Warn when monomorphization generates more than N generic instantiations
+    #[arg(long, value_name = "N", default_value_t = CodeSizeLimits::default().warn_instantiations)]
+    warn_instantiations: usize,
+
+    /// Warn when the LLVM IR module defines more than N functions
+    #[arg(long, value_name = "N", default_value_t = CodeSizeLimits::default().warn_functions)]
+    warn_functions: usize,
+
+    /// Warn when the LLVM IR module contains more than N instructions
+    #[arg(long = "warn-ir-instructions", value_name = "N", default_value_t = CodeSizeLimits::default().warn_instructions)]
+    warn_instructions: usize,
+}
+
+impl From<CodeSizeArgs> for CodeSizeLimits {
+    fn from(args: CodeSizeArgs) -> Self {
+        Self {
+            warn_instantiations: args.warn_instantiations,
+            max_instantiations: args.max_instantiations,
+            warn_functions: args.warn_functions,
+            warn_instructions: args.warn_instructions,
+        }
+    }
+}
+
+/// LLVMのコードモデル
+#[derive(Clone, Copy, Debug, Default, ValueEnum)]
+enum CodeModelArg {
+    /// Let LLVM choose for the target
+    #[default]
+    Default,
+    Small,
+    Kernel,
+    Medium,
+    Large,
+}
+
+impl From<CodeModelArg> for inkwell::targets::CodeModel {
+    fn from(model: CodeModelArg) -> Self {
+        match model {
+            CodeModelArg::Default => Self::Default,
+            CodeModelArg::Small => Self::Small,
+            CodeModelArg::Kernel => Self::Kernel,
+            CodeModelArg::Medium => Self::Medium,
+            CodeModelArg::Large => Self::Large,
+        }
+    }
+}
+
+/// Settings for the generated module and object code
+#[derive(Args, Clone, Debug, Default)]
+struct CodegenArgs {
+    /// LLVM target triple for the generated code (defaults to the host)
+    #[arg(long, value_name = "TRIPLE")]
+    target: Option<String>,
+
+    /// Code model for the generated code
+    #[arg(long, value_enum, default_value = "default")]
+    code_model: CodeModelArg,
+
+    /// Generate position-dependent code (by default code is position independent so it links as PIE)
+    #[arg(long)]
+    no_pic: bool,
+
+    /// Panic at runtime when a cast loses information (narrowing, sign change, or an out-of-range float)
+    #[arg(long)]
+    checked_casts: bool,
+
+    /// Check `debug_assert` and `debug_assert_eq` even when optimizing (they are always checked at -O0)
+    #[arg(long)]
+    debug_assertions: bool,
+
+    /// Strip the names of values inside functions so `--emit=llvm-ir` output only differs where the code does
+    #[arg(long)]
+    no_names: bool,
+
+    /// Skip the null checks before dereferencing references (for performance comparisons)
+    #[arg(long)]
+    no_null_checks: bool,
+
+    /// Keep unused functions in the executable instead of removing them at link time
+    #[arg(long)]
+    no_gc_sections: bool,
+
+    /// Size of the main thread's stack in bytes (defaults to the platform's default)
+    #[arg(long, value_name = "BYTES")]
+    stack_size: Option<u64>,
+
+    /// Pass an extra argument to the linker when linking an executable (can be repeated)
+    #[arg(long = "link-arg", value_name = "ARG", allow_hyphen_values = true)]
+    link_args: Vec<String>,
+}
+
+impl CodegenArgs {
+    fn into_options(self, source_path: String, opt_level: u8) -> CodegenOptions {
+        CodegenOptions::new(source_path)
+            .with_target_triple(self.target)
+            .with_code_model(self.code_model.into())
+            .with_pic(!self.no_pic)
+            .with_opt_level(inkwell_opt_level(opt_level))
+            .with_checked_casts(self.checked_casts)
+            .with_debug_assertions(self.debug_assertions || opt_level == 0)
+            .with_strip_names(self.no_names)
+            .with_null_checks(!self.no_null_checks)
+            .with_gc_sections(!self.no_gc_sections)
+            .with_stack_size(self.stack_size)
+    }
 }
 
 #[derive(Subcommand)]
@@ -52,7 +258,7 @@ enum Commands {
         /// The source file to compile
         input: PathBuf,
 
-        /// Output file (for executables or object files)
+        /// Output file (for executables or object files). Use `-` to write to stdout
         #[arg(short = 'o', long)]
         output: Option<PathBuf>,
 
@@ -60,11 +266,15 @@ enum Commands {
         #[arg(long = "emit", value_enum, default_value = "executable")]
         emit: EmitType,
 
+        /// Output format of `--emit=deps`
+        #[arg(long, value_enum, default_value = "make")]
+        deps_format: DepsFormat,
+
         /// Optimization level (0-3)
         #[arg(short = 'O', long, default_value = "2", value_parser = clap::value_parser!(u8).range(0..=3))]
         opt_level: u8,
 
-        /// Dump the AST to stdout
+        /// Dump the AST to stdout (for debugging; use --emit=ast-json for a stable format)
         #[arg(long)]
         dump_ast: bool,
 
@@ -72,78 +282,323 @@ enum Commands {
         #[arg(long)]
         dump_tokens: bool,
 
+        /// Read the input as a versioned JSON AST (as written by --emit=ast-json) instead of source code
+        #[arg(long, conflicts_with = "dump_tokens")]
+        from_ast: bool,
+
         /// Keep intermediate files (LLVM IR, object files)
         #[arg(long)]
         keep_temps: bool,
+
+        /// How to report borrow checker violations (`#[unsafe_no_borrow_check]` skips a single function)
+        #[arg(long, value_enum, default_value = "error")]
+        borrow_check: BorrowCheckArg,
+
+        /// Skip code generation for functions unreachable from main and public functions (default for executables)
+        #[arg(long, conflicts_with = "no_prune")]
+        prune_unreachable: bool,
+
+        /// Generate code for every function, even unreachable ones (default for --emit=llvm-ir, obj and asm)
+        #[arg(long)]
+        no_prune: bool,
+
+        #[command(flatten)]
+        lints: LintArgs,
+
+        #[command(flatten)]
+        codegen: CodegenArgs,
+
+        #[command(flatten)]
+        code_size: CodeSizeArgs,
+    },
+
+    /// Build the project described by the nearest yuni.toml (searched upward from the current directory)
+    Build {
+        /// Output file (defaults to the package name next to yuni.toml for executables). Use `-` to write to stdout
+        #[arg(short = 'o', long)]
+        output: Option<PathBuf>,
+
+        /// What to emit
+        #[arg(long = "emit", value_enum, default_value = "executable")]
+        emit: EmitType,
+
+        /// Output format of `--emit=deps`
+        #[arg(long, value_enum, default_value = "make")]
+        deps_format: DepsFormat,
+
+        /// Optimization level (0-3) [default: `[build] opt_level` in yuni.toml, or 2]
+        #[arg(short = 'O', long, value_parser = clap::value_parser!(u8).range(0..=3))]
+        opt_level: Option<u8>,
+
+        /// Keep intermediate files (LLVM IR, object files)
+        #[arg(long)]
+        keep_temps: bool,
+
+        /// How to report borrow checker violations (`#[unsafe_no_borrow_check]` skips a single function)
+        #[arg(long, value_enum, default_value = "error")]
+        borrow_check: BorrowCheckArg,
+
+        #[command(flatten)]
+        lints: LintArgs,
+
+        #[command(flatten)]
+        codegen: CodegenArgs,
+
+        #[command(flatten)]
+        code_size: CodeSizeArgs,
     },
 
     /// Run a Yuni source file
     Run {
-        /// The source file to run
-        input: PathBuf,
+        /// The source file to run (defaults to the entry point in the nearest yuni.toml)
+        input: Option<PathBuf>,
 
         /// Arguments to pass to the program
         args: Vec<String>,
 
-        /// Optimization level (0-3)
-        #[arg(short = 'O', long, default_value = "0", value_parser = clap::value_parser!(u8).range(0..=3))]
-        opt_level: u8,
+        /// Optimization level (0-3) [default: `[build] opt_level` in yuni.toml when running the project, or 0]
+        #[arg(short = 'O', long, value_parser = clap::value_parser!(u8).range(0..=3))]
+        opt_level: Option<u8>,
+
+        /// Run with the tree-walking interpreter instead of compiling with LLVM
+        #[arg(long, conflicts_with_all = ["args", "debug_assertions", "no_null_checks", "stack_size"])]
+        interpret: bool,
+
+        /// Treat the file as a script: top-level statements form an implicit main function
+        #[arg(long)]
+        script: bool,
+
+        /// Panic at runtime when a cast loses information (narrowing, sign change, or an out-of-range float)
+        #[arg(long)]
+        checked_casts: bool,
+
+        /// Check `debug_assert` and `debug_assert_eq` even when optimizing (they are always checked at -O0)
+        #[arg(long)]
+        debug_assertions: bool,
+
+        /// Skip the null checks before dereferencing references (for performance comparisons)
+        #[arg(long)]
+        no_null_checks: bool,
+
+        /// Size of the main thread's stack in bytes (defaults to the platform's default)
+        #[arg(long, value_name = "BYTES")]
+        stack_size: Option<u64>,
+
+        /// How to report borrow checker violations (`#[unsafe_no_borrow_check]` skips a single function)
+        #[arg(long, value_enum, default_value = "error")]
+        borrow_check: BorrowCheckArg,
+
+        #[command(flatten)]
+        code_size: CodeSizeArgs,
     },
 
 
     /// Check a Yuni source file for errors without compiling
     Check {
-        /// The source file to check
+        /// The source file to check (defaults to the entry point in the nearest yuni.toml)
+        input: Option<PathBuf>,
+
+        /// Evaluate parameterless functions with the interpreter and print their values
+        #[arg(long)]
+        run_const: bool,
+
+        /// Print the definitions in the file as a JSON symbol index
+        #[arg(long, conflicts_with = "run_const")]
+        json_symbols: bool,
+
+        /// How to report borrow checker violations (`#[unsafe_no_borrow_check]` skips a single function)
+        #[arg(long, value_enum, default_value = "error")]
+        borrow_check: BorrowCheckArg,
+
+        #[command(flatten)]
+        lints: LintArgs,
+    },
+
+    /// Generate Markdown documentation for the public items of a Yuni source file
+    Doc {
+        /// The source file to document
         input: PathBuf,
+
+        /// Output file (defaults to the input with a .md extension). Use `-` to write to stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
     },
+
+    /// Start an interactive session that compiles each input with the JIT (`:quit` or EOF to exit)
+    Repl,
+
+    /// Check that llc, clang and rustc needed for building executables are installed and usable
+    Doctor,
 }
 
-fn main() -> YuniResult<()> {
-    // Initialize logger before parsing CLI args
-    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
+/// Initialize the logger from the --verbose count
+///
+/// YUNI_LOG takes env_logger filter directives (e.g. `YUNI_LOG=yuni::mono=trace`)
+/// that override the level chosen by --verbose.
+fn init_logger(verbose: u8) {
+    let level = match verbose {
+        0 => log::LevelFilter::Info,
+        1 => log::LevelFilter::Debug,
+        _ => log::LevelFilter::Trace,
+    };
+    env_logger::Builder::new()
+        .filter_level(level)
+        .parse_env(env_logger::Env::new().filter("YUNI_LOG"))
+        .init();
+}
 
+fn main() -> YuniResult<()> {
     let cli = Cli::parse();
+    init_logger(cli.verbose);
+
+    // 長い式の連鎖などで深くなったASTを再帰的にたどれるよう、大きなスタックのスレッドで実行する
+    let result = std::thread::Builder::new()
+        .stack_size(parser::STACK_SIZE)
+        .spawn(move || execute(cli.command, cli.verbose))
+        .map_err(|e| YuniError::Other(format!("Failed to start compiler thread: {}", e)))
+        .and_then(|handle| handle.join().unwrap_or_else(|payload| std::panic::resume_unwind(payload)));
 
-    // Set log level based on verbose flag
-    if cli.verbose {
-        log::set_max_level(log::LevelFilter::Debug);
+    if let Err(e) = result {
+        eprintln!("{}: {}", "error".red().bold(), e);
+        // Debug情報を表示
+        eprintln!("{:?}", e);
+        std::process::exit(1);
     }
 
-    let result = match cli.command {
+    Ok(())
+}
+
+/// サブコマンドを実行
+fn execute(command: Commands, verbose: u8) -> YuniResult<()> {
+    match command {
         Commands::Compile {
             input,
             output,
             emit,
+            deps_format,
             opt_level,
             dump_ast,
             dump_tokens,
+            from_ast,
             keep_temps,
-        } => compile(CompileOptions {
+            borrow_check,
+            prune_unreachable,
+            no_prune,
+            lints,
+            codegen,
+            code_size,
+        } => lints.levels().and_then(|lints| compile(CompileOptions {
             input,
             output,
             emit,
+            deps_format,
             opt_level,
             dump_ast,
             dump_tokens,
+            from_ast,
+            script: false,
             keep_temps,
-            verbose: cli.verbose,
+            verbose: verbose > 0,
+            quiet: false,
+            borrow_check: borrow_check.into(),
+            prune: if prune_unreachable {
+                Some(true)
+            } else if no_prune {
+                Some(false)
+            } else {
+                None
+            },
+            lints,
+            codegen,
+            limits: code_size.into(),
+            module_dirs: Vec::new(),
+        })),
+        Commands::Build {
+            output,
+            emit,
+            deps_format,
+            opt_level,
+            keep_temps,
+            borrow_check,
+            lints,
+            codegen,
+            code_size,
+        } => lints.levels().and_then(|lints| {
+            let manifest = find_manifest()?;
+            let output = output.or_else(|| matches!(emit, EmitType::Executable).then(|| manifest.default_output()));
+            let codegen = CodegenArgs {
+                target: manifest.build.target(codegen.target.clone()),
+                link_args: manifest.build.linker_args(codegen.link_args.clone()),
+                ..codegen
+            };
+            compile(CompileOptions {
+                input: manifest.entry_path(),
+                output,
+                emit,
+                deps_format,
+                opt_level: manifest.build.opt_level(opt_level, 2),
+                dump_ast: false,
+                dump_tokens: false,
+                from_ast: false,
+                script: false,
+                keep_temps,
+                verbose: verbose > 0,
+                quiet: false,
+                borrow_check: borrow_check.into(),
+                prune: None,
+                lints,
+                codegen,
+                limits: code_size.into(),
+                module_dirs: manifest.module_dirs(),
+            })
         }),
         Commands::Run {
             input,
             args,
             opt_level,
-        } => run(input, args, opt_level),
-        Commands::Check { input } => check(input),
-    };
-
-    if let Err(e) = result {
-        eprintln!("{}: {}", "error".red().bold(), e);
-        // Debug情報を表示
-        eprintln!("{:?}", e);
-        std::process::exit(1);
+            interpret,
+            script,
+            checked_casts,
+            debug_assertions,
+            no_null_checks,
+            stack_size,
+            borrow_check,
+            code_size,
+        } => input_or_manifest(input).and_then(|(input, manifest)| {
+            // マニフェストのエントリーポイントを実行する場合はマニフェストのビルド設定を使う
+            let build = manifest.map(|manifest| manifest.build).unwrap_or_default();
+            if interpret {
+                run_interpreted(input, script, checked_casts, borrow_check.into(), code_size.into())
+            } else {
+                let codegen = CodegenArgs {
+                    checked_casts,
+                    debug_assertions,
+                    no_null_checks,
+                    stack_size,
+                    link_args: build.linker_args(Vec::new()),
+                    ..CodegenArgs::default()
+                };
+                run(input, args, build.opt_level(opt_level, 0), script, borrow_check.into(), codegen, code_size.into())
+            }
+        }),
+        Commands::Check {
+            input,
+            run_const,
+            json_symbols,
+            borrow_check,
+            lints,
+        } => lints.levels().and_then(|lints| {
+            let (input, _) = input_or_manifest(input)?;
+            if json_symbols {
+                check_symbols(input)
+            } else {
+                check(input, run_const, borrow_check.into(), lints)
+            }
+        }),
+        Commands::Doc { input, output } => doc(input, output),
+        Commands::Repl => repl(),
+        Commands::Doctor => doctor(),
     }
-
-    Ok(())
 }
 
 
@@ -151,11 +606,48 @@ struct CompileOptions {
     input: PathBuf,
     output: Option<PathBuf>,
     emit: EmitType,
+    /// `--emit=deps` の出力形式
+    deps_format: DepsFormat,
     opt_level: u8,
     dump_ast: bool,
     dump_tokens: bool,
+    /// 入力をソースコードではなくJSON形式のASTとして読む
+    from_ast: bool,
+    /// 入力をスクリプトとして解析する
+    script: bool,
     keep_temps: bool,
     verbose: bool,
+    /// 成功メッセージを出力しない（runサブコマンド用）
+    quiet: bool,
+    /// 借用規則の違反の扱い
+    borrow_check: BorrowCheckMode,
+    /// 到達できない関数を取り除くか（Noneは実行ファイルの場合だけ取り除く）
+    prune: Option<bool>,
+    /// リントごとの警告の報告の水準
+    lints: LintLevels,
+    /// 生成するモジュールとオブジェクトコードの設定
+    codegen: CodegenArgs,
+    /// 生成するコードの規模のしきい値
+    limits: CodeSizeLimits,
+    /// インポートを探す追加のディレクトリ（マニフェストの `[paths] modules`）
+    module_dirs: Vec<PathBuf>,
+}
+
+/// カレントディレクトリから親ディレクトリへ `yuni.toml` を探して読む
+fn find_manifest() -> YuniResult<Manifest> {
+    let cwd = std::env::current_dir()
+        .map_err(|e| YuniError::Io(format!("Failed to get the current directory: {}", e)))?;
+    let manifest = Manifest::discover(&cwd)?;
+    log::debug!("Using manifest {:?} for package {}", manifest.root.join(crate::manifest::MANIFEST_FILE), manifest.package.name);
+    Ok(manifest)
+}
+
+/// 入力ファイルの指定がなければマニフェストを探し、そのエントリーポイントを使う
+fn input_or_manifest(input: Option<PathBuf>) -> YuniResult<(PathBuf, Option<Manifest>)> {
+    match input {
+        Some(input) => Ok((input, None)),
+        None => find_manifest().map(|manifest| (manifest.entry_path(), Some(manifest))),
+    }
 }
 
 fn compile(opts: CompileOptions) -> YuniResult<()> {
@@ -163,43 +655,117 @@ fn compile(opts: CompileOptions) -> YuniResult<()> {
         input,
         output,
         emit,
+        deps_format,
         opt_level,
         dump_ast,
         dump_tokens,
+        from_ast,
+        script,
         keep_temps,
         verbose,
+        quiet,
+        borrow_check,
+        prune,
+        lints,
+        codegen: codegen_args,
+        limits,
+        module_dirs,
     } = opts;
+
+    if let EmitType::Deps = emit {
+        if from_ast {
+            return Err(YuniError::Other(
+                "--emit=deps reads imports from source files and cannot be combined with --from-ast".to_string(),
+            ));
+        }
+        return emit_deps(&input, output.as_deref(), deps_format, &module_dirs);
+    }
+
+    // `-o -` では出力を標準出力に流すため、情報メッセージはすべて標準エラーに出す
+    // （`--emit=ast-json` は出力先を指定しなければ標準出力に書き出す）
+    let to_stdout = match output.as_deref() {
+        Some(path) => path == Path::new(STDOUT_PATH),
+        None => matches!(emit, EmitType::AstJson),
+    };
+    macro_rules! status {
+        ($($arg:tt)*) => {
+            if to_stdout { eprintln!($($arg)*) } else { println!($($arg)*) }
+        };
+    }
+
+    if to_stdout && matches!(emit, EmitType::Obj | EmitType::Executable) && io::stdout().is_terminal() {
+        return Err(YuniError::Other(
+            "Refusing to write binary output to a terminal; redirect stdout or pass a file path to -o".to_string(),
+        ));
+    }
+
     if verbose {
-        println!("{}: Compiling {:?} with optimization level O{}", 
+        status!("{}: Compiling {:?} with optimization level O{}", 
                 "info".blue().bold(), input, opt_level);
     }
 
     // Initialize compilation state
-    let state = CompilationState::new(&input)?;
+    let state = if from_ast {
+        // ASTの入力にはソースコードがないため、診断はファイル名だけを表示する
+        CompilationState::new_from_string(&input.display().to_string(), String::new())?
+    } else {
+        CompilationState::new(&input)?
+    };
+    let link_args = codegen_args.link_args.clone();
+    let codegen_options = codegen_args.into_options(state.source_file.clone(), opt_level);
     let context = inkwell::context::Context::create();
-    let mut pipeline = CompilationPipeline::new(state, &context, verbose);
+    let mut pipeline = CompilationPipeline::new(state, &context)
+        .with_codegen_options(codegen_options)
+        .with_code_size_limits(limits)
+        .with_script(script)
+        .with_borrow_check(borrow_check)
+        .with_lints(lints)
+        .with_prune_unreachable(prune.unwrap_or(matches!(emit, EmitType::Executable)));
 
     // Run the compilation pipeline
-    let tokens = pipeline.tokenize();
+    let mut ast = if from_ast {
+        let json = source_file::read_source(&input)?;
+        Some(Program::from_json(&json)?)
+    } else {
+        let tokens = pipeline.tokenize();
 
-    if dump_tokens {
-        println!("{}", "=== Tokens ===".blue().bold());
-        for (i, token) in tokens.iter().enumerate() {
-            println!("{:4}: {:?}", i, token);
+        if dump_tokens {
+            status!("{}", "=== Tokens ===".blue().bold());
+            for (i, token) in tokens.iter().enumerate() {
+                status!("{:4}: {:?}", i, token);
+            }
+            status!("");
         }
-        println!();
-    }
 
-    let ast = pipeline.parse(tokens);
+        pipeline.parse(tokens)
+    };
+
+    // ASTのJSONは構文だけを表すため、名前解決の前に書き出す
+    if let EmitType::AstJson = emit {
+        if pipeline.state().has_errors() {
+            pipeline.report_errors()?;
+            return Err(YuniError::Other("Compilation failed".to_string()));
+        }
+        if pipeline.state().has_warnings() {
+            pipeline.report_errors()?;
+        }
+        let ast = ast.ok_or_else(|| YuniError::Other("No AST generated".to_string()))?;
+        let json = format!("{}\n", ast.to_ast_json(&pipeline.state().source_file));
+        return match output.as_deref() {
+            Some(path) if !to_stdout => fs::write(path, json)
+                .map_err(|e| YuniError::Io(format!("Failed to write {:?}: {}", path, e))),
+            _ => write_to_stdout(json.as_bytes()),
+        };
+    }
 
-    if let Some(ref ast) = ast {
+    if let Some(ast) = &mut ast {
         if dump_ast {
-            println!("{}", "=== AST ===".blue().bold());
-            println!("{}", serde_json::to_string_pretty(ast)
-                .map_err(|e| YuniError::Other(format!("Failed to serialize AST: {}", e)))?);
-            println!();
+            status!("{}", "=== AST ===".blue().bold());
+            status!("{}", ast.to_json());
+            status!("");
         }
 
+        pipeline.resolve_names(ast);
         pipeline.analyze(ast);
     }
 
@@ -209,6 +775,27 @@ fn compile(opts: CompileOptions) -> YuniResult<()> {
         return Err(YuniError::Other("Compilation failed".to_string()));
     }
 
+    // 呼び出しグラフは解析の結果だけで出力できるため、単相化とコード生成は行わない
+    if let EmitType::CallGraph = emit {
+        if pipeline.state().has_warnings() {
+            pipeline.report_errors()?;
+        }
+        let dot = pipeline.call_graph().to_dot();
+        return match output.as_deref() {
+            Some(path) if path != Path::new(STDOUT_PATH) => fs::write(path, dot)
+                .map_err(|e| YuniError::Io(format!("Failed to write {:?}: {}", path, e))),
+            _ => write_to_stdout(dot.as_bytes()),
+        };
+    }
+
+    // 解析のエラーを報告した後、必要なツールがなければ単相化とコード生成の前にエラーにする
+    let required_tools: &[Tool] = match emit {
+        EmitType::Executable => &[Tool::Llc, Tool::Clang, Tool::Rustc],
+        EmitType::Asm => &[Tool::Llc],
+        _ => &[],
+    };
+    Doctor::from_env().require(required_tools)?;
+
     // 単相化を実行
     let monomorphized_ast = if let Some(ast) = ast {
         pipeline.monomorphize(ast)
@@ -216,13 +803,35 @@ fn compile(opts: CompileOptions) -> YuniResult<()> {
         None
     };
 
+    // 単相化のエラー（インスタンス数の上限超過など）を報告
+    if pipeline.state().has_errors() {
+        pipeline.report_errors()?;
+        return Err(YuniError::Other("Compilation failed".to_string()));
+    }
+
     // コード生成
     let codegen = if let Some(ast) = monomorphized_ast {
-        pipeline.codegen(&ast)?
+        match pipeline.codegen(&ast) {
+            Ok(codegen) => codegen,
+            Err(e) => {
+                pipeline.state_mut().add_error(e);
+                pipeline.report_errors()?;
+                return Err(YuniError::Other("Compilation failed".to_string()));
+            }
+        }
     } else {
         return Err(YuniError::Other("No AST generated".to_string()));
     };
 
+    // 規模の警告を報告（`--deny code-size` ではエラーとして中断する）
+    if pipeline.state().has_errors() {
+        pipeline.report_errors()?;
+        return Err(YuniError::Other("Compilation failed".to_string()));
+    }
+    if pipeline.state().has_warnings() {
+        pipeline.report_errors()?;
+    }
+
     // Create temporary directory for intermediate files
     let temp_dir = if keep_temps {
         None
@@ -246,21 +855,31 @@ fn compile(opts: CompileOptions) -> YuniResult<()> {
                 path.set_extension("ll");
                 path
             });
-            if verbose { println!("{}: Writing LLVM IR to {:?}", "step".cyan().bold(), output_path); }
+            if to_stdout {
+                write_to_stdout(codegen.get_module().print_to_string().to_bytes())?;
+                status!("{}: Wrote LLVM IR to stdout", "success".green().bold());
+                return Ok(());
+            }
+            if verbose { status!("{}: Writing LLVM IR to {:?}", "step".cyan().bold(), output_path); }
             codegen.write_llvm_ir(&output_path)?;
-            println!("{}: Created LLVM IR file {:?}", "success".green().bold(), output_path);
+            status!("{}: Created LLVM IR file {:?}", "success".green().bold(), output_path);
             return Ok(());
         }
         EmitType::Obj => {
-            let output_path = output.unwrap_or_else(|| {
-                let mut path = input.clone();
-                path.set_extension("o");
-                path
-            });
-            if verbose { println!("{}: Writing object file to {:?}", "step".cyan().bold(), output_path); }
-            let opt = inkwell_opt_level(opt_level);
-            codegen.write_object_file(&output_path, opt)?;
-            println!("{}: Created object file {:?}", "success".green().bold(), output_path);
+            let output_path = output.unwrap_or_else(|| input.with_extension(Target::host().object_extension()));
+            if verbose { status!("{}: Writing object file to {:?}", "step".cyan().bold(), output_path); }
+            if to_stdout {
+                let temp_obj = get_temp_path(&format!("program.{}", Target::host().object_extension()));
+                codegen.write_object_file(&temp_obj)?;
+                copy_file_to_stdout(&temp_obj)?;
+                if temp_dir.is_some() {
+                    fs::remove_file(&temp_obj).ok();
+                }
+                status!("{}: Wrote object file to stdout", "success".green().bold());
+                return Ok(());
+            }
+            codegen.write_object_file(&output_path)?;
+            status!("{}: Created object file {:?}", "success".green().bold(), output_path);
             return Ok(());
         }
         EmitType::Asm => {
@@ -269,19 +888,21 @@ fn compile(opts: CompileOptions) -> YuniResult<()> {
                 path.set_extension("s");
                 path
             });
-            if verbose { println!("{}: Writing assembly to {:?}", "step".cyan().bold(), output_path); }
+            if verbose { status!("{}: Writing assembly to {:?}", "step".cyan().bold(), output_path); }
             
             // First write LLVM IR to a temp file
             let temp_ll = get_temp_path("temp.ll");
             codegen.write_llvm_ir(&temp_ll)?;
 
-            // Use llc to convert to assembly
+            // Use llc to convert to assembly (llc writes to our stdout when the output path is `-`)
             let llc_cmd = find_llc_command()?;
             let status = Command::new(&llc_cmd)
                 .arg(format!("-O{}", opt_level))
+                .args(codegen.options.llc_args())
                 .arg("-o")
                 .arg(&output_path)
                 .arg(&temp_ll)
+                .stdin(Stdio::null())
                 .status()
                 .map_err(|e| YuniError::Other(format!("Failed to run llc: {}", e)))?;
 
@@ -294,42 +915,45 @@ fn compile(opts: CompileOptions) -> YuniResult<()> {
                 fs::remove_file(&temp_ll).ok();
             }
             
-            println!("{}: Created assembly file {:?}", "success".green().bold(), output_path);
+            if to_stdout {
+                status!("{}: Wrote assembly to stdout", "success".green().bold());
+            } else {
+                status!("{}: Created assembly file {:?}", "success".green().bold(), output_path);
+            }
             return Ok(());
         }
         EmitType::Executable => {
             // This is the main case - build a complete executable
-            let executable_path = output.unwrap_or_else(|| {
-                let mut path = input.clone();
-                path.set_extension("");
-                if path.file_name().unwrap() == input.file_stem().unwrap() {
-                    // If input was "file.yuni", output should be "file", not "file."
-                    path
-                } else {
-                    path
-                }
-            });
+            let target = Target::host();
+            // Windowsのリンカは拡張子のない出力先に.exeを付けるため、あらかじめ付けておく
+            let executable_path = if to_stdout {
+                target.executable_path(&get_temp_path("program"))
+            } else {
+                output.map_or_else(|| target.default_executable_path(&input), |path| target.executable_path(&path))
+            };
 
             if verbose {
-                println!("{}: Building executable {:?}", "step".cyan().bold(), executable_path);
-                println!("{}: Step 1 - Generating LLVM IR", "substep".yellow());
+                status!("{}: Building executable {:?}", "step".cyan().bold(), executable_path);
+                status!("{}: Step 1 - Generating LLVM IR", "substep".yellow());
             }
 
             // Step 1: Generate LLVM IR for the main program
             let program_ll = get_temp_path("program.ll");
             codegen.write_llvm_ir(&program_ll)?;
 
-            if verbose { println!("{}: Step 2 - Compiling program to object file", "substep".yellow()); }
+            if verbose { status!("{}: Step 2 - Compiling program to object file", "substep".yellow()); }
 
             // Step 2: Compile LLVM IR to object file
-            let program_obj = get_temp_path("program.o");
+            let program_obj = get_temp_path(&format!("program.{}", target.object_extension()));
             let llc_cmd = find_llc_command()?;
             let status = Command::new(&llc_cmd)
                 .arg("-filetype=obj")
                 .arg(format!("-O{}", opt_level))
+                .args(codegen.options.llc_args())
                 .arg("-o")
                 .arg(&program_obj)
                 .arg(&program_ll)
+                .stdin(Stdio::null())
                 .status()
                 .map_err(|e| YuniError::Other(format!("Failed to run llc on program: {}", e)))?;
 
@@ -337,24 +961,27 @@ fn compile(opts: CompileOptions) -> YuniResult<()> {
                 return Err(YuniError::Other("Failed to compile program LLVM IR to object file".to_string()));
             }
 
-            if verbose { println!("{}: Step 3 - Compiling Rust runtime to object file", "substep".yellow()); }
+            if verbose { status!("{}: Step 3 - Compiling Rust runtime to object file", "substep".yellow()); }
 
             // Step 3: Compile Rust runtime to object file
-            let runtime_rs_path = Path::new("src/runtime/mod.rs");
+            // ビルドしたディレクトリのランタイムを使い、どのディレクトリからでもコンパイルできるようにする
+            let runtime_rs_path = &Path::new(env!("CARGO_MANIFEST_DIR")).join("src/runtime/mod.rs");
             
             if !runtime_rs_path.exists() {
                 return Err(YuniError::Other(format!("Runtime Rust file not found at {:?}", runtime_rs_path)));
             }
 
             // First compile the Rust runtime to a static library
-            let runtime_lib = get_temp_path("libyuniruntime.a");
+            let runtime_lib = get_temp_path(&target.static_library_name("yuniruntime"));
             let status = Command::new("rustc")
                 .arg("--crate-type=staticlib")
                 .arg("--crate-name=yuniruntime")
                 .arg(format!("-Copt-level={}", opt_level))
+                .args(target.runtime_rustc_args())
                 .arg("-o")
                 .arg(&runtime_lib)
                 .arg(runtime_rs_path)
+                .stdin(Stdio::null())
                 .status()
                 .map_err(|e| YuniError::Other(format!("Failed to compile Rust runtime: {}", e)))?;
 
@@ -362,31 +989,21 @@ fn compile(opts: CompileOptions) -> YuniResult<()> {
                 return Err(YuniError::Other("Failed to compile Rust runtime".to_string()));
             }
 
-            if verbose { println!("{}: Step 4 - Linking executable", "substep".yellow()); }
+            if verbose { status!("{}: Step 4 - Linking executable", "substep".yellow()); }
 
             // Step 4: Link everything together
-            let mut cmd = Command::new("clang");
-            cmd.arg("-o")
-                .arg(&executable_path)
-                .arg(&program_obj)
-                .arg(&runtime_lib)
-                .arg("-lm") // Math library
-                .arg("-lpthread"); // Thread library for Rust runtime
-            
-            // On macOS, we need to link against system libraries
-            #[cfg(target_os = "macos")]
-            {
-                cmd.arg("-framework").arg("System");
-                cmd.arg("-lc++");
-            }
-            
-            // On Linux, link against standard C++ library
-            #[cfg(target_os = "linux")]
-            {
-                cmd.arg("-lstdc++");
-            }
-
-            let status = cmd.status().map_err(|e| YuniError::Other(format!("Failed to link executable: {}", e)))?;
+            let linker = target
+                .find_linker(std::env::var_os("PATH").as_deref(), std::env::var_os("ProgramFiles").as_deref())
+                .ok_or_else(|| YuniError::Other("Could not find clang. Please install LLVM 18 or add clang to your PATH".to_string()))?;
+            let status = Command::new(&linker.program)
+                .args(target.link_args(&linker, &executable_path, &[&program_obj, &runtime_lib]))
+                .args(if codegen.options.gc_sections { target.gc_sections_args(&linker) } else { Vec::new() })
+                .args(codegen.options.stack_size.map(|size| target.stack_size_args(&linker, size)).unwrap_or_default())
+                .args(target.library_args(&linker, &codegen.link_libraries))
+                .args(&link_args)
+                .stdin(Stdio::null())
+                .status()
+                .map_err(|e| YuniError::Other(format!("Failed to link executable: {}", e)))?;
 
             if !status.success() {
                 return Err(YuniError::Other("Failed to link executable".to_string()));
@@ -402,17 +1019,81 @@ fn compile(opts: CompileOptions) -> YuniResult<()> {
                 fs::remove_file(&runtime_lib).ok();
             }
 
-            println!("{}: Created executable {:?}", "success".green().bold(), executable_path);
+            if to_stdout {
+                copy_file_to_stdout(&executable_path)?;
+                if temp_dir.is_none() {
+                    fs::remove_file(&executable_path).ok();
+                }
+                status!("{}: Wrote executable to stdout", "success".green().bold());
+            } else if !quiet {
+                status!("{}: Created executable {:?}", "success".green().bold(), executable_path);
+            }
             if keep_temps {
-                println!("{}: Intermediate files kept in {:?}", "info".blue(), 
+                status!("{}: Intermediate files kept in {:?}", "info".blue(), 
                     input.parent().unwrap_or(Path::new(".")));
             }
         }
+        EmitType::Deps => unreachable!("--emit=deps returns before compiling"),
+        EmitType::CallGraph => unreachable!("--emit=callgraph returns after analysis"),
+        EmitType::AstJson => unreachable!("--emit=ast-json returns after parsing"),
     }
 
     Ok(())
 }
 
+/// インポートの依存関係を出力する（`--emit=deps`）
+///
+/// 出力先を指定しないか `-o -` の場合は標準出力に書き出します。
+/// インポートの循環と、Makefile形式で省いた解決できないインポートは警告として報告します。
+fn emit_deps(input: &Path, output: Option<&Path>, format: DepsFormat, module_dirs: &[PathBuf]) -> YuniResult<()> {
+    let graph = DependencyGraph::build(input, module_dirs)?;
+
+    for cycle in &graph.cycles {
+        let files: Vec<String> = cycle.iter().map(|file| file.display().to_string()).collect();
+        eprintln!("{}: import cycle: {}", "warning".yellow().bold(), files.join(" -> "));
+    }
+
+    let text = match format {
+        DepsFormat::Make => {
+            for unresolved in &graph.unresolved {
+                eprintln!(
+                    "{}: unresolved import \"{}\" in {} (no file {})",
+                    "warning".yellow().bold(),
+                    unresolved.import,
+                    unresolved.from.display(),
+                    unresolved.candidate.display()
+                );
+            }
+            graph.to_makefile(&input.with_extension(Target::host().object_extension()))
+        }
+        DepsFormat::Json => format!("{}\n", graph.to_json()),
+    };
+
+    match output {
+        Some(path) if path != Path::new(STDOUT_PATH) => fs::write(path, text)
+            .map_err(|e| YuniError::Io(format!("Failed to write {:?}: {}", path, e))),
+        _ => write_to_stdout(text.as_bytes()),
+    }
+}
+
+/// 標準出力への書き出しを表す出力パス
+const STDOUT_PATH: &str = "-";
+
+/// バイト列を標準出力に書き出す
+fn write_to_stdout(bytes: &[u8]) -> YuniResult<()> {
+    let mut stdout = io::stdout().lock();
+    stdout
+        .write_all(bytes)
+        .and_then(|_| stdout.flush())
+        .map_err(|e| YuniError::Io(format!("Failed to write to stdout: {}", e)))
+}
+
+/// ファイルの内容を標準出力に書き出す
+fn copy_file_to_stdout(path: &Path) -> YuniResult<()> {
+    let bytes = fs::read(path).map_err(|e| YuniError::Io(format!("Failed to read {:?}: {}", path, e)))?;
+    write_to_stdout(&bytes)
+}
+
 fn inkwell_opt_level(level: u8) -> inkwell::OptimizationLevel {
     match level {
         0 => inkwell::OptimizationLevel::None,
@@ -423,114 +1104,333 @@ fn inkwell_opt_level(level: u8) -> inkwell::OptimizationLevel {
     }
 }
 
-fn find_llc_command() -> YuniResult<String> {
-    // Try to find llc command in system paths
-    if let Ok(output) = Command::new("which").arg("llc").output() {
-        if output.status.success() {
-            let path = String::from_utf8_lossy(&output.stdout).trim().to_string();
-            if !path.is_empty() {
-                return Ok(path);
-            }
-        }
-    }
+fn find_llc_command() -> YuniResult<PathBuf> {
+    Target::host()
+        .find_tool("llc", std::env::var_os("PATH").as_deref(), std::env::var_os("ProgramFiles").as_deref())
+        .ok_or_else(|| YuniError::Other("Could not find llc command. Please install LLVM 18 or add llc to your PATH".to_string()))
+}
 
-    // On macOS, try homebrew paths for LLVM 18
-    #[cfg(target_os = "macos")]
-    {
-        let homebrew_paths = [
-            "/opt/homebrew/opt/llvm@18/bin/llc",
-            "/usr/local/opt/llvm@18/bin/llc",  
-            "/opt/homebrew/Cellar/llvm@18/18.1.8/bin/llc",
-            "/opt/homebrew/bin/llc",
-        ];
-        
-        for path in &homebrew_paths {
-            if Path::new(path).exists() {
-                return Ok(path.to_string());
-            }
-        }
-    }
+/// `run` で使用する一時実行ファイル
+///
+/// ドロップ時にファイルを削除します。`std::process::exit` ではドロップが
+/// 実行されないため、終了前に明示的にドロップすること。
+struct TempExecutable {
+    path: PathBuf,
+}
 
-    // Try common Linux paths
-    #[cfg(target_os = "linux")]
-    {
-        let linux_paths = [
-            "/usr/bin/llc-18",
-            "/usr/bin/llc",
-            "/usr/local/bin/llc-18",
-            "/usr/local/bin/llc",
-        ];
-        
-        for path in &linux_paths {
-            if Path::new(path).exists() {
-                return Ok(path.to_string());
-            }
-        }
+impl Drop for TempExecutable {
+    fn drop(&mut self) {
+        fs::remove_file(&self.path).ok();
     }
-
-    Err(YuniError::Other("Could not find llc command. Please install LLVM 18 or add llc to your PATH".to_string()))
 }
 
-fn run(input: PathBuf, args: Vec<String>, opt_level: u8) -> YuniResult<()> {
+fn run(
+    input: PathBuf,
+    args: Vec<String>,
+    opt_level: u8,
+    script: bool,
+    borrow_check: BorrowCheckMode,
+    codegen: CodegenArgs,
+    limits: CodeSizeLimits,
+) -> YuniResult<()> {
     log::info!("Running {:?} with args: {:?}", input, args);
 
+    // コンパイル中に受け取ったシグナルでも一時実行ファイルを残さないよう、コード生成の前に登録する
+    let signals = SignalForwarder::install()?;
+
     // Create a temporary executable
-    let temp_dir = std::env::temp_dir();
-    let temp_exe = temp_dir.join(format!("yuni_run_{}", std::process::id()));
+    let temp_exe = TempExecutable {
+        path: Target::host().executable_path(&std::env::temp_dir().join(format!("yuni_run_{}", std::process::id()))),
+    };
 
-    // Compile to executable
+    // Compile to executable quietly so that only the program writes to stdout
     compile(CompileOptions {
         input,
-        output: Some(temp_exe.clone()),
+        output: Some(temp_exe.path.clone()),
         emit: EmitType::Executable,
+        deps_format: DepsFormat::default(),
         opt_level,
         dump_ast: false,
         dump_tokens: false,
+        from_ast: false,
+        script,
         keep_temps: false, // don't keep temps for run
         verbose: false, // not verbose
+        quiet: true,
+        borrow_check,
+        prune: None,
+        lints: LintLevels::default(),
+        codegen,
+        limits,
+        module_dirs: Vec::new(),
     })?;
 
-    // Run the executable
-    log::debug!("Executing {:?}", temp_exe);
-    let status = Command::new(&temp_exe)
+    // コンパイル中にシグナルを受け取った場合は、プログラムを実行せずにそのシグナルで終了した扱いにする
+    if let Some(signal) = signals.pending() {
+        drop(signals);
+        drop(temp_exe);
+        std::process::exit(128 + signal);
+    }
+
+    // Run the executable with the runner's stdio
+    log::debug!("Executing {:?}", temp_exe.path);
+    let mut child = Command::new(&temp_exe.path)
         .args(&args)
-        .status()
+        .stdin(Stdio::inherit())
+        .stdout(Stdio::inherit())
+        .stderr(Stdio::inherit())
+        .spawn()
         .map_err(|e| YuniError::Other(format!("Failed to execute compiled program: {}", e)))?;
 
-    // Clean up
-    fs::remove_file(&temp_exe).ok();
+    signals.forward_to(&child);
+    let status = child
+        .wait()
+        .map_err(|e| YuniError::Other(format!("Failed to wait for compiled program: {}", e)));
 
-    if !status.success() {
-        if let Some(code) = status.code() {
-            std::process::exit(code);
-        } else {
-            return Err(YuniError::Other("Program terminated by signal".to_string()));
-        }
+    // Clean up before any exit
+    drop(signals);
+    drop(temp_exe);
+
+    let code = exit_code_of(&status?);
+    if code != 0 {
+        std::process::exit(code);
     }
 
     Ok(())
 }
 
+/// runの間に受け取ったSIGINT・SIGTERM・SIGHUPを子プロセスに転送する
+///
+/// ランナー自身はシグナルで終了せず、一時実行ファイルの後片付けを行えるようにします。
+/// 子プロセスを起動する前（コンパイル中）に受け取ったシグナルは保留します。
+#[cfg(unix)]
+struct SignalForwarder {
+    state: std::sync::Arc<std::sync::Mutex<ForwardingState>>,
+    handle: signal_hook::iterator::Handle,
+    thread: Option<std::thread::JoinHandle<()>>,
+}
+
+/// 転送先の子プロセスと、子プロセスの起動前に受け取ったシグナル
+#[cfg(unix)]
+#[derive(Default)]
+struct ForwardingState {
+    child: Option<libc::pid_t>,
+    pending: Option<i32>,
+}
+
+#[cfg(unix)]
+impl SignalForwarder {
+    fn install() -> YuniResult<Self> {
+        use signal_hook::consts::{SIGHUP, SIGINT, SIGTERM};
+        use signal_hook::iterator::Signals;
+
+        let mut signals = Signals::new([SIGINT, SIGTERM, SIGHUP])
+            .map_err(|e| YuniError::Other(format!("Failed to install signal handlers: {}", e)))?;
+        let handle = signals.handle();
+        let state = std::sync::Arc::new(std::sync::Mutex::new(ForwardingState::default()));
+
+        let shared = state.clone();
+        let thread = std::thread::spawn(move || {
+            for signal in signals.forever() {
+                let mut state = shared.lock().unwrap();
+                match state.child {
+                    // SAFETY: killはシグナル番号とpidを受け取るだけで、メモリ安全性に影響しない
+                    Some(pid) => unsafe {
+                        libc::kill(pid, signal);
+                    },
+                    None => state.pending = Some(signal),
+                }
+            }
+        });
+        Ok(Self { state, handle, thread: Some(thread) })
+    }
+
+    /// 子プロセスの起動前に受け取ったシグナル
+    fn pending(&self) -> Option<i32> {
+        self.state.lock().unwrap().pending
+    }
+
+    /// 以降のシグナルを子プロセスに転送する
+    ///
+    /// 起動の直前に受け取って保留していたシグナルは、すぐに転送します。
+    fn forward_to(&self, child: &Child) {
+        let pid = child.id() as libc::pid_t;
+        let mut state = self.state.lock().unwrap();
+        state.child = Some(pid);
+        if let Some(signal) = state.pending.take() {
+            // SAFETY: killはシグナル番号とpidを受け取るだけで、メモリ安全性に影響しない
+            unsafe {
+                libc::kill(pid, signal);
+            }
+        }
+    }
+}
+
+#[cfg(unix)]
+impl Drop for SignalForwarder {
+    fn drop(&mut self) {
+        self.handle.close();
+        if let Some(thread) = self.thread.take() {
+            thread.join().ok();
+        }
+    }
+}
+
+#[cfg(not(unix))]
+struct SignalForwarder;
+
+#[cfg(not(unix))]
+impl SignalForwarder {
+    fn install() -> YuniResult<Self> {
+        Ok(Self)
+    }
+
+    fn pending(&self) -> Option<i32> {
+        None
+    }
+
+    fn forward_to(&self, _child: &Child) {}
+}
+
+/// 子プロセスの終了ステータスをランナーの終了コードに変換する
+///
+/// シグナルで終了した場合はシェルと同じく 128 + シグナル番号を返します。
+fn exit_code_of(status: &ExitStatus) -> i32 {
+    if let Some(code) = status.code() {
+        return code;
+    }
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::ExitStatusExt;
+        if let Some(signal) = status.signal() {
+            return 128 + signal;
+        }
+    }
+
+    1
+}
+
+/// インタプリタ実行用スレッドのスタックサイズ（深い再帰に対応するため）
+const INTERPRETER_STACK_SIZE: usize = 512 * 1024 * 1024;
+
+/// LLVMを使わずにインタプリタで実行する
+fn run_interpreted(
+    input: PathBuf,
+    script: bool,
+    checked_casts: bool,
+    borrow_check: BorrowCheckMode,
+    limits: CodeSizeLimits,
+) -> YuniResult<()> {
+    log::info!("Interpreting {:?}", input);
+
+    let state = CompilationState::new(&input)?;
+    let context = inkwell::context::Context::create();
+    let mut pipeline = CompilationPipeline::new(state, &context)
+        .with_code_size_limits(limits)
+        .with_script(script)
+        .with_borrow_check(borrow_check);
+
+    let tokens = pipeline.tokenize();
+    let mut ast = pipeline.parse(tokens);
+    if let Some(ast) = &mut ast {
+        pipeline.resolve_names(ast);
+        pipeline.analyze(ast);
+    }
+
+    // 単相化してからインタプリタに渡す（コード生成と同じ入力にする）
+    let ast = if pipeline.state().has_errors() {
+        None
+    } else {
+        ast.and_then(|ast| pipeline.monomorphize(ast))
+    };
+    let ast = match ast {
+        Some(ast) => ast,
+        None => {
+            pipeline.report_errors()?;
+            return Err(YuniError::Other("Compilation failed".to_string()));
+        }
+    };
+    if pipeline.state().has_warnings() {
+        pipeline.report_errors()?;
+    }
+
+    let source_text = pipeline.state().source_text();
+    let type_table = pipeline.type_table().clone();
+    let result = std::thread::scope(|scope| {
+        std::thread::Builder::new()
+            .stack_size(INTERPRETER_STACK_SIZE)
+            .spawn_scoped(scope, || {
+                interpreter::Interpreter::new(&ast)
+                    .with_checked_casts(checked_casts)
+                    .with_source(source_text)
+                    .with_type_table(type_table)
+                    .run_main()
+            })
+            .map_err(|e| YuniError::Other(format!("Failed to start interpreter thread: {}", e)))?
+            .join()
+            .map_err(|_| YuniError::Other("Interpreter thread panicked".to_string()))?
+    });
+
+    match result {
+        Ok(0) => Ok(()),
+        Ok(code) => std::process::exit(code),
+        Err(e) => {
+            pipeline.state_mut().add_error(e);
+            pipeline.report_errors()?;
+            Err(YuniError::Other("Program terminated by runtime error".to_string()))
+        }
+    }
+}
 
-fn check(input: PathBuf) -> YuniResult<()> {
+fn check(input: PathBuf, run_const: bool, borrow_check: BorrowCheckMode, lints: LintLevels) -> YuniResult<()> {
     log::info!("Checking {:?}", input);
 
     // コンパイルパイプラインを使用
     let state = CompilationState::new(&input)?;
     let context = inkwell::context::Context::create();
-    let mut pipeline = CompilationPipeline::new(state, &context, false);
+    let mut pipeline = CompilationPipeline::new(state, &context)
+        .with_borrow_check(borrow_check)
+        .with_lints(lints);
 
     // レキシカル解析
     let tokens = pipeline.tokenize();
     
     // 構文解析
-    let ast = pipeline.parse(tokens);
+    let mut ast = pipeline.parse(tokens);
     
-    // セマンティック解析
-    if let Some(ref ast) = ast {
+    // 名前解決とセマンティック解析
+    if let Some(ast) = &mut ast {
+        pipeline.resolve_names(ast);
         pipeline.analyze(ast);
     }
     
+    // 定数評価（引数なし関数をインタプリタで評価）
+    if run_const && !pipeline.state().has_errors() {
+        if let Some(ast) = ast.and_then(|ast| pipeline.monomorphize(ast)) {
+            let type_table = pipeline.type_table().clone();
+            let results = std::thread::scope(|scope| {
+                std::thread::Builder::new()
+                    .stack_size(INTERPRETER_STACK_SIZE)
+                    .spawn_scoped(scope, || {
+                        interpreter::Interpreter::eval_const_functions(&ast, type_table)
+                            .into_iter()
+                            .map(|(name, result)| (name, result.map(|value| value.to_string())))
+                            .collect::<Vec<_>>()
+                    })
+                    .map_err(|e| YuniError::Other(format!("Failed to start interpreter thread: {}", e)))?
+                    .join()
+                    .map_err(|_| YuniError::Other("Interpreter thread panicked".to_string()))
+            })?;
+            for (name, result) in results {
+                match result {
+                    Ok(value) => println!("{}: {}() = {}", "const".cyan().bold(), name, value),
+                    Err(e) => pipeline.state_mut().add_error(e),
+                }
+            }
+        }
+    }
+
     // エラーレポート
     pipeline.report_errors()?;
     
@@ -541,3 +1441,143 @@ fn check(input: PathBuf) -> YuniResult<()> {
         Err(YuniError::Other("Check failed".to_string()))
     }
 }
+
+/// 公開アイテムのドキュメントをMarkdownとして出力する
+///
+/// アイテムの直前にないドキュメントコメントは警告として報告します。
+fn doc(input: PathBuf, output: Option<PathBuf>) -> YuniResult<()> {
+    log::info!("Documenting {:?}", input);
+
+    let state = CompilationState::new(&input)?;
+    let context = inkwell::context::Context::create();
+    let mut pipeline = CompilationPipeline::new(state, &context);
+
+    let tokens = pipeline.tokenize();
+    let ast = pipeline.parse(tokens);
+    pipeline.report_errors()?;
+    let ast = ast.ok_or_else(|| YuniError::Other("Failed to parse source file".to_string()))?;
+
+    let markdown = doc::render_markdown(&ast);
+    let output = output.unwrap_or_else(|| input.with_extension("md"));
+    if output == Path::new(STDOUT_PATH) {
+        return write_to_stdout(markdown.as_bytes());
+    }
+    fs::write(&output, markdown)
+        .map_err(|e| YuniError::Io(format!("Failed to write {:?}: {}", output, e)))?;
+    println!("{}: Created documentation file {:?}", "success".green().bold(), output);
+    Ok(())
+}
+
+/// 標準入力から読んだ入力をJITで評価する対話的なセッション
+///
+/// 括弧が閉じるまでの行を1つの入力として評価し、式の値を表示します。エラーになった入力は
+/// 報告して読み飛ばし、セッションを続けます。
+/// ツールチェーンを検査し、検査ごとの結果と失敗した検査の直し方を表示する
+fn doctor() -> YuniResult<()> {
+    let checks = Doctor::from_env().checks();
+    for check in &checks {
+        let label = match check.status {
+            CheckStatus::Pass => format!("{:<4}", "ok").green().bold(),
+            CheckStatus::Warn => format!("{:<4}", "warn").yellow().bold(),
+            CheckStatus::Fail => format!("{:<4}", "fail").red().bold(),
+        };
+        println!("{} {:<14} {}", label, check.name, check.detail);
+        if let Some(fix) = check.fix.as_ref().filter(|_| check.status != CheckStatus::Pass) {
+            println!("     {:<14} {}", "", format!("fix: {}", fix).cyan());
+        }
+    }
+
+    let failed = checks.iter().filter(|check| !check.passed()).count();
+    if failed > 0 {
+        return Err(YuniError::Other(format!("{} of {} toolchain checks failed", failed, checks.len())));
+    }
+    println!("{}: The toolchain is ready to build executables", "success".green().bold());
+    Ok(())
+}
+
+fn repl() -> YuniResult<()> {
+    let context = inkwell::context::Context::create();
+    let mut session = ReplSession::new(&context);
+    let stdin = io::stdin();
+    let interactive = stdin.is_terminal();
+    let mut input = String::new();
+
+    loop {
+        if interactive {
+            print!("{}", if input.is_empty() { "yuni> " } else { "....> " });
+            io::stdout()
+                .flush()
+                .map_err(|e| YuniError::Io(format!("Failed to write the prompt: {}", e)))?;
+        }
+        let mut line = String::new();
+        let read = stdin
+            .read_line(&mut line)
+            .map_err(|e| YuniError::Io(format!("Failed to read input: {}", e)))?;
+        if read == 0 {
+            break;
+        }
+        if input.is_empty() && matches!(line.trim(), ":quit" | ":q") {
+            break;
+        }
+        input.push_str(&line);
+        if !repl::is_complete(&input) {
+            continue;
+        }
+        let source = std::mem::take(&mut input);
+        if source.trim().is_empty() {
+            continue;
+        }
+
+        match session.eval(&source) {
+            Ok(output) => {
+                for name in &output.defined {
+                    println!("{}: {}", "defined".green().bold(), name);
+                }
+                for (name, missing) in &output.pending {
+                    println!("{}: {} waits for {} to be defined", "pending".yellow().bold(), name, missing.join(", "));
+                }
+                if let Some(value) = output.value {
+                    println!("{}", value);
+                }
+            }
+            Err(error) => {
+                let mut state = CompilationState::new_from_string("<repl>", session.source().to_string())?;
+                state.add_error(error);
+                state.report_diagnostics()?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// 定義の一覧をJSONのシンボルインデックスとして出力する
+///
+/// セマンティック解析のエラーは標準エラーに報告し、パースできた定義はすべて出力します。
+fn check_symbols(input: PathBuf) -> YuniResult<()> {
+    log::info!("Indexing symbols in {:?}", input);
+
+    let state = CompilationState::new(&input)?;
+    let context = inkwell::context::Context::create();
+    let mut pipeline = CompilationPipeline::new(state, &context);
+
+    let tokens = pipeline.tokenize();
+    let ast = pipeline.parse(tokens);
+    let ast = match ast {
+        Some(ast) => ast,
+        None => {
+            pipeline.report_errors()?;
+            return Err(YuniError::Other("Failed to parse source file".to_string()));
+        }
+    };
+    // シンボルはソース上の名前で出力するため、名前解決は解析用の複製に行う
+    let mut resolved = ast.clone();
+    pipeline.resolve_names(&mut resolved);
+    let analyzed = pipeline.analyze(&resolved);
+
+    let state = pipeline.state();
+    let index = SymbolIndex::build(&state.source_file, &state.source, &ast, analyzed);
+    println!("{}", index.to_json());
+
+    pipeline.report_errors()?;
+    Ok(())
+}
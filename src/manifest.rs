@@ -0,0 +1,543 @@
+//! プロジェクトのマニフェスト（`yuni.toml`）
+//!
+//! `yunilang build` と、入力ファイルを指定しない `run`・`check` はカレントディレクトリから
+//! 親ディレクトリへ `yuni.toml` を探し、その内容でコンパイルします。
+//!
+//! ```toml
+//! [package]
+//! name = "hello"
+//! entry = "src/main.yuni"
+//!
+//! [build]
+//! opt_level = 2
+//! target = "x86_64-unknown-linux-gnu"
+//! linker_args = ["-static"]
+//!
+//! [paths]
+//! modules = ["lib"]
+//! ```
+//!
+//! パスはすべて `yuni.toml` のあるディレクトリからの相対パスです。コマンドラインの指定は
+//! マニフェストの値より優先します。依存パッケージの解決は行いません。
+//!
+//! 読めるのはTOMLのうちマニフェストに必要な部分（テーブル、文字列・整数・真偽値・配列の値、
+//! コメント）だけです。各キーの値はserdeでフィールドの型に変換し、エラーはキーの名前と行を示します。
+
+use std::collections::HashSet;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+use crate::error::{YuniError, YuniResult};
+
+/// マニフェストのファイル名
+pub const MANIFEST_FILE: &str = "yuni.toml";
+
+/// `[package]` の既定のエントリーポイント
+const DEFAULT_ENTRY: &str = "main.yuni";
+
+/// マニフェストの読み込みのエラー
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ManifestError {
+    /// エラーの行（1始まり）
+    pub line: usize,
+    /// 原因のキー（`build.opt_level` のようにテーブル名を含む）
+    pub key: Option<String>,
+    pub message: String,
+}
+
+impl ManifestError {
+    fn syntax(line: usize, message: impl Into<String>) -> Self {
+        Self { line, key: None, message: message.into() }
+    }
+
+    fn for_key(line: usize, key: &str, message: impl Into<String>) -> Self {
+        Self { line, key: Some(key.to_string()), message: message.into() }
+    }
+}
+
+impl fmt::Display for ManifestError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: ", self.line)?;
+        if let Some(key) = &self.key {
+            write!(f, "`{}`: ", key)?;
+        }
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for ManifestError {}
+
+/// `[package]` テーブル
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PackageManifest {
+    /// パッケージ名（実行ファイルの既定の名前）
+    pub name: String,
+    /// エントリーポイントのファイル
+    pub entry: PathBuf,
+}
+
+/// `[build]` テーブル
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BuildManifest {
+    /// 最適化レベル（0-3）
+    pub opt_level: Option<u8>,
+    /// 生成するコードのLLVMターゲットトリプル
+    pub target: Option<String>,
+    /// 実行ファイルのリンク時にリンカへ追加で渡す引数
+    pub linker_args: Vec<String>,
+}
+
+impl BuildManifest {
+    /// 最適化レベル（コマンドラインの指定、マニフェストの値、既定値の順に使う）
+    pub fn opt_level(&self, cli: Option<u8>, default: u8) -> u8 {
+        cli.or(self.opt_level).unwrap_or(default)
+    }
+
+    /// ターゲットトリプル（コマンドラインの指定を優先する）
+    pub fn target(&self, cli: Option<String>) -> Option<String> {
+        cli.or_else(|| self.target.clone())
+    }
+
+    /// リンカの追加の引数（コマンドラインで指定した場合はマニフェストの値を使わない）
+    pub fn linker_args(&self, cli: Vec<String>) -> Vec<String> {
+        if cli.is_empty() {
+            self.linker_args.clone()
+        } else {
+            cli
+        }
+    }
+}
+
+/// `[paths]` テーブル
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PathsManifest {
+    /// エントリーポイントのディレクトリの次にインポートを探すディレクトリ
+    pub modules: Vec<PathBuf>,
+}
+
+/// プロジェクトのマニフェスト
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Manifest {
+    /// `yuni.toml` のあるディレクトリ
+    pub root: PathBuf,
+    pub package: PackageManifest,
+    pub build: BuildManifest,
+    pub paths: PathsManifest,
+}
+
+impl Manifest {
+    /// `start` から親ディレクトリへ順に `yuni.toml` を探す
+    pub fn find(start: &Path) -> Option<PathBuf> {
+        start
+            .ancestors()
+            .map(|dir| dir.join(MANIFEST_FILE))
+            .find(|path| path.is_file())
+    }
+
+    /// `start` から `yuni.toml` を探して読む
+    pub fn discover(start: &Path) -> YuniResult<Self> {
+        let path = Self::find(start).ok_or_else(|| {
+            YuniError::Io(format!(
+                "Could not find {} in {} or any parent directory; pass a source file or create a manifest",
+                MANIFEST_FILE,
+                start.display()
+            ))
+        })?;
+        Self::load(&path)
+    }
+
+    /// マニフェストのファイルを読む
+    pub fn load(path: &Path) -> YuniResult<Self> {
+        let text = fs::read_to_string(path)
+            .map_err(|e| YuniError::Io(format!("Failed to read manifest {}: {}", path.display(), e)))?;
+        let root = path.parent().unwrap_or(Path::new("")).to_path_buf();
+        Self::parse(&text, root)
+            .map_err(|e| YuniError::Other(format!("Invalid manifest {}: {}", path.display(), e)))
+    }
+
+    /// マニフェストの内容を解析する（`root` は相対パスの基準のディレクトリ）
+    pub fn parse(text: &str, root: PathBuf) -> Result<Self, ManifestError> {
+        let mut builder = ManifestBuilder::default();
+        let mut section: Option<String> = None;
+        let mut seen_sections = HashSet::new();
+        let mut seen_keys = HashSet::new();
+
+        for item in TomlParser::new(text).parse()? {
+            match item {
+                TomlItem::Table { name, line } => {
+                    if !SECTIONS.contains(&name.as_str()) {
+                        return Err(ManifestError::for_key(
+                            line,
+                            &name,
+                            format!("unknown table (expected one of {})", quoted_list(SECTIONS)),
+                        ));
+                    }
+                    if !seen_sections.insert(name.clone()) {
+                        return Err(ManifestError::for_key(line, &name, "table is defined more than once"));
+                    }
+                    if name == "package" {
+                        builder.package_line = Some(line);
+                    }
+                    section = Some(name);
+                }
+                TomlItem::KeyValue { key, value, line } => {
+                    let Some(section) = &section else {
+                        return Err(ManifestError::for_key(line, &key, "key must be inside a table such as [package]"));
+                    };
+                    let full_key = format!("{}.{}", section, key);
+                    if !seen_keys.insert(full_key.clone()) {
+                        return Err(ManifestError::for_key(line, &full_key, "key is defined more than once"));
+                    }
+                    builder.set(section, &key, value).map_err(|message| ManifestError::for_key(line, &full_key, message))?;
+                }
+            }
+        }
+
+        builder.finish(root)
+    }
+
+    /// エントリーポイントのファイルのパス
+    pub fn entry_path(&self) -> PathBuf {
+        self.root.join(&self.package.entry)
+    }
+
+    /// インポートを探す追加のディレクトリ
+    pub fn module_dirs(&self) -> Vec<PathBuf> {
+        self.paths.modules.iter().map(|dir| self.root.join(dir)).collect()
+    }
+
+    /// 実行ファイルの既定の出力先（拡張子を除く）
+    pub fn default_output(&self) -> PathBuf {
+        self.root.join(&self.package.name)
+    }
+}
+
+/// マニフェストのテーブル
+const SECTIONS: &[&str] = &["package", "build", "paths"];
+
+/// テーブルごとのキー
+fn section_keys(section: &str) -> &'static [&'static str] {
+    match section {
+        "package" => &["name", "entry"],
+        "build" => &["opt_level", "target", "linker_args"],
+        "paths" => &["modules"],
+        _ => &[],
+    }
+}
+
+fn quoted_list(items: &[&str]) -> String {
+    items.iter().map(|item| format!("`{}`", item)).collect::<Vec<_>>().join(", ")
+}
+
+/// 値をフィールドの型に変換する
+fn from_value<T: DeserializeOwned>(value: Value) -> Result<T, String> {
+    serde_json::from_value(value).map_err(|e| e.to_string())
+}
+
+/// キーごとに値を集めるマニフェスト
+#[derive(Default)]
+struct ManifestBuilder {
+    /// `[package]` の行（必須のキーがない場合に示す）
+    package_line: Option<usize>,
+    name: Option<String>,
+    entry: Option<PathBuf>,
+    build: BuildManifest,
+    paths: PathsManifest,
+}
+
+impl ManifestBuilder {
+    fn set(&mut self, section: &str, key: &str, value: Value) -> Result<(), String> {
+        match (section, key) {
+            ("package", "name") => {
+                let name: String = from_value(value)?;
+                if name.is_empty() || name.contains(['/', '\\']) {
+                    return Err(format!("invalid package name {:?} (must be a non-empty file name)", name));
+                }
+                self.name = Some(name);
+            }
+            ("package", "entry") => self.entry = Some(from_value(value)?),
+            ("build", "opt_level") => {
+                let level: u8 = from_value(value)?;
+                if level > 3 {
+                    return Err(format!("optimization level must be between 0 and 3, found {}", level));
+                }
+                self.build.opt_level = Some(level);
+            }
+            ("build", "target") => self.build.target = Some(from_value(value)?),
+            ("build", "linker_args") => self.build.linker_args = from_value(value)?,
+            ("paths", "modules") => self.paths.modules = from_value(value)?,
+            _ => {
+                return Err(format!(
+                    "unknown key (expected one of {})",
+                    quoted_list(section_keys(section))
+                ))
+            }
+        }
+        Ok(())
+    }
+
+    fn finish(self, root: PathBuf) -> Result<Manifest, ManifestError> {
+        let Some(package_line) = self.package_line else {
+            return Err(ManifestError::for_key(1, "package", "missing [package] table"));
+        };
+        let name = self
+            .name
+            .ok_or_else(|| ManifestError::for_key(package_line, "package.name", "missing required key"))?;
+        Ok(Manifest {
+            root,
+            package: PackageManifest { name, entry: self.entry.unwrap_or_else(|| PathBuf::from(DEFAULT_ENTRY)) },
+            build: self.build,
+            paths: self.paths,
+        })
+    }
+}
+
+/// TOMLの行の要素
+enum TomlItem {
+    /// `[name]`
+    Table { name: String, line: usize },
+    /// `key = value`
+    KeyValue { key: String, value: Value, line: usize },
+}
+
+/// マニフェストに必要なTOMLのサブセットの構文解析器
+struct TomlParser {
+    chars: Vec<char>,
+    pos: usize,
+    line: usize,
+}
+
+impl TomlParser {
+    fn new(text: &str) -> Self {
+        Self { chars: text.chars().collect(), pos: 0, line: 1 }
+    }
+
+    fn parse(mut self) -> Result<Vec<TomlItem>, ManifestError> {
+        let mut items = Vec::new();
+        loop {
+            self.skip_blank_lines();
+            let Some(c) = self.peek() else {
+                return Ok(items);
+            };
+            let line = self.line;
+            if c == '[' {
+                self.pos += 1;
+                self.skip_spaces();
+                let name = self.bare_key()?;
+                self.skip_spaces();
+                self.expect(']')?;
+                items.push(TomlItem::Table { name, line });
+            } else {
+                let key = self.bare_key()?;
+                self.skip_spaces();
+                self.expect('=')?;
+                self.skip_spaces();
+                let value = self.value()?;
+                items.push(TomlItem::KeyValue { key, value, line });
+            }
+            self.end_of_line()?;
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += 1;
+        if c == '\n' {
+            self.line += 1;
+        }
+        Some(c)
+    }
+
+    fn error(&self, message: impl Into<String>) -> ManifestError {
+        ManifestError::syntax(self.line, message)
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), ManifestError> {
+        match self.peek() {
+            Some(c) if c == expected => {
+                self.pos += 1;
+                Ok(())
+            }
+            Some(c) => Err(self.error(format!("expected `{}`, found `{}`", expected, c))),
+            None => Err(self.error(format!("expected `{}`, found end of file", expected))),
+        }
+    }
+
+    fn skip_spaces(&mut self) {
+        while matches!(self.peek(), Some(' ' | '\t')) {
+            self.pos += 1;
+        }
+    }
+
+    fn skip_comment(&mut self) {
+        if self.peek() == Some('#') {
+            while !matches!(self.peek(), None | Some('\n')) {
+                self.pos += 1;
+            }
+        }
+    }
+
+    /// 空白・コメント・改行を読み飛ばす
+    fn skip_blank_lines(&mut self) {
+        loop {
+            self.skip_spaces();
+            self.skip_comment();
+            match self.peek() {
+                Some('\n') => {
+                    self.bump();
+                }
+                Some('\r') if self.chars.get(self.pos + 1) == Some(&'\n') => {
+                    self.pos += 1;
+                }
+                _ => return,
+            }
+        }
+    }
+
+    /// 値の後に行末（コメントを含む）があることを確かめる
+    fn end_of_line(&mut self) -> Result<(), ManifestError> {
+        self.skip_spaces();
+        self.skip_comment();
+        if self.peek() == Some('\r') {
+            self.pos += 1;
+        }
+        match self.peek() {
+            None => Ok(()),
+            Some('\n') => {
+                self.bump();
+                Ok(())
+            }
+            Some(c) => Err(self.error(format!("expected the end of the line, found `{}`", c))),
+        }
+    }
+
+    fn bare_key(&mut self) -> Result<String, ManifestError> {
+        let start = self.pos;
+        while matches!(self.peek(), Some(c) if c.is_ascii_alphanumeric() || c == '_' || c == '-') {
+            self.pos += 1;
+        }
+        if start == self.pos {
+            return Err(match self.peek() {
+                Some('"' | '\'' | '.') => self.error("quoted and dotted keys are not supported"),
+                Some(c) => self.error(format!("expected a key, found `{}`", c)),
+                None => self.error("expected a key, found end of file"),
+            });
+        }
+        let key: String = self.chars[start..self.pos].iter().collect();
+        if self.peek() == Some('.') {
+            return Err(self.error(format!("dotted keys are not supported (found `{}.`)", key)));
+        }
+        Ok(key)
+    }
+
+    fn value(&mut self) -> Result<Value, ManifestError> {
+        match self.peek() {
+            Some('"') => self.basic_string().map(Value::String),
+            Some('\'') => self.literal_string().map(Value::String),
+            Some('[') => self.array(),
+            Some(c) if c == '+' || c == '-' || c.is_ascii_digit() => self.integer(),
+            Some(c) if c.is_ascii_alphabetic() => {
+                let word = self.bare_key()?;
+                match word.as_str() {
+                    "true" => Ok(Value::Bool(true)),
+                    "false" => Ok(Value::Bool(false)),
+                    _ => Err(self.error(format!("invalid value `{}` (strings must be quoted)", word))),
+                }
+            }
+            Some('{') => Err(self.error("inline tables are not supported")),
+            Some(c) => Err(self.error(format!("expected a value, found `{}`", c))),
+            None => Err(self.error("expected a value, found end of file")),
+        }
+    }
+
+    fn basic_string(&mut self) -> Result<String, ManifestError> {
+        self.expect('"')?;
+        let mut text = String::new();
+        loop {
+            match self.peek() {
+                None | Some('\n') => return Err(self.error("unterminated string")),
+                Some('"') => {
+                    self.pos += 1;
+                    return Ok(text);
+                }
+                Some('\\') => {
+                    self.pos += 1;
+                    let escaped = match self.peek() {
+                        Some('"') => '"',
+                        Some('\\') => '\\',
+                        Some('n') => '\n',
+                        Some('t') => '\t',
+                        Some('r') => '\r',
+                        Some(c) => return Err(self.error(format!("unsupported escape sequence `\\{}`", c))),
+                        None => return Err(self.error("unterminated string")),
+                    };
+                    self.pos += 1;
+                    text.push(escaped);
+                }
+                Some(c) => {
+                    self.pos += 1;
+                    text.push(c);
+                }
+            }
+        }
+    }
+
+    fn literal_string(&mut self) -> Result<String, ManifestError> {
+        self.expect('\'')?;
+        let start = self.pos;
+        loop {
+            match self.peek() {
+                None | Some('\n') => return Err(self.error("unterminated string")),
+                Some('\'') => {
+                    let text = self.chars[start..self.pos].iter().collect();
+                    self.pos += 1;
+                    return Ok(text);
+                }
+                Some(_) => self.pos += 1,
+            }
+        }
+    }
+
+    fn integer(&mut self) -> Result<Value, ManifestError> {
+        let start = self.pos;
+        if matches!(self.peek(), Some('+' | '-')) {
+            self.pos += 1;
+        }
+        while matches!(self.peek(), Some(c) if c.is_ascii_alphanumeric() || c == '_' || c == '.') {
+            self.pos += 1;
+        }
+        let text: String = self.chars[start..self.pos].iter().filter(|c| **c != '_').collect();
+        text.parse::<i64>()
+            .map(Value::from)
+            .map_err(|_| self.error(format!("invalid integer `{}` (only decimal integers are supported)", text)))
+    }
+
+    /// 配列（複数行にまたがってもよい）
+    fn array(&mut self) -> Result<Value, ManifestError> {
+        self.expect('[')?;
+        let mut elements = Vec::new();
+        loop {
+            self.skip_blank_lines();
+            if self.peek() == Some(']') {
+                self.pos += 1;
+                return Ok(Value::Array(elements));
+            }
+            elements.push(self.value()?);
+            self.skip_blank_lines();
+            match self.peek() {
+                Some(',') => self.pos += 1,
+                Some(']') => {}
+                Some(c) => return Err(self.error(format!("expected `,` or `]` in array, found `{}`", c))),
+                None => return Err(self.error("unterminated array")),
+            }
+        }
+    }
+}
@@ -6,53 +6,138 @@ use crate::lexer::Token;
 use super::{ParseResult, Parser};
 
 impl Parser {
+    /// アイテムを解析（implブロックは含まれるメソッドの宣言に展開する）
+    ///
+    /// implブロック自体はアイテムではないため、その直前のドキュメントコメントはどこにも付きません。
+    pub(super) fn parse_items(&mut self) -> ParseResult<Vec<Item>> {
+        if self.check(&Token::Impl) && self.peek(1) != Some(&Token::Fn) {
+            let methods = self.parse_impl_block()?;
+            return Ok(methods.into_iter().map(Item::Method).collect());
+        }
+        Ok(vec![self.parse_item()?])
+    }
+
     /// アイテムを解析
     pub(super) fn parse_item(&mut self) -> ParseResult<Item> {
-        // 可視性修飾子をチェック
+        let docs = self.take_doc_comments();
+        let mut item = self.parse_item_without_docs()?;
+        *item.docs_mut() = docs;
+        Ok(item)
+    }
+
+    /// ドキュメントコメントを除いたアイテムを解析
+    fn parse_item_without_docs(&mut self) -> ParseResult<Item> {
+        let attributes_start = self.current_span().start;
+        let attributes = self.parse_attributes()?;
+
+        // 可視性修飾子をチェック（アイテムのスパンは修飾子から始まる）
+        let start = self.current_span().start;
         let is_public = if self.check(&Token::Pub) {
             self.advance();
             true
         } else {
             false
         };
+        let inline = self.parse_inline_hint()?;
+        if inline != InlineHint::None && !self.check(&Token::Fn) && !self.check(&Token::Impl) {
+            return Err(self.error("Only functions and methods can be marked inline".to_string()));
+        }
+        let is_function = self.check(&Token::Fn) || (self.check(&Token::Impl) && self.peek(1) == Some(&Token::Fn));
+        if !attributes.is_empty() && !is_function {
+            return Err(self.error_at(
+                "Only functions and methods can have attributes".to_string(),
+                self.span_from(attributes_start),
+            ));
+        }
 
         match self.current_token() {
             Some(Token::Type) => {
                 if is_public {
                     return Err(self.error("Type definitions cannot have visibility modifiers".to_string()));
                 }
-                let type_def = self.parse_type_def()?;
+                let type_def = self.parse_type_def(start)?;
                 Ok(Item::TypeDef(type_def))
             }
             Some(Token::Struct) => {
                 if is_public {
                     return Err(self.error("Struct definitions cannot have visibility modifiers".to_string()));
                 }
-                let struct_def = self.parse_struct_def()?;
+                let struct_def = self.parse_struct_def(start)?;
                 Ok(Item::TypeDef(TypeDef::Struct(struct_def)))
             }
             Some(Token::Enum) => {
                 if is_public {
                     return Err(self.error("Enum definitions cannot have visibility modifiers".to_string()));
                 }
-                let enum_def = self.parse_enum_def()?;
+                let enum_def = self.parse_enum_def(start)?;
                 Ok(Item::TypeDef(TypeDef::Enum(enum_def)))
             }
             Some(Token::Fn) => {
-                let func = self.parse_function_decl_with_visibility(is_public)?;
+                let mut func = self.parse_function_decl_with_visibility(start, is_public)?;
+                func.inline = inline;
+                func.attributes = attributes;
                 Ok(Item::Function(func))
             }
             Some(Token::Impl) => {
-                let method = self.parse_method_decl_with_visibility(is_public)?;
+                if is_public && self.peek(1) != Some(&Token::Fn) {
+                    return Err(self.error(
+                        "Impl blocks cannot have visibility modifiers; mark each method pub instead".to_string(),
+                    ));
+                }
+                if inline != InlineHint::None && self.peek(1) != Some(&Token::Fn) {
+                    return Err(self.error(
+                        "Impl blocks cannot be marked inline; mark each method inline instead".to_string(),
+                    ));
+                }
+                let mut method = self.parse_method_decl_with_visibility(start, is_public)?;
+                method.inline = inline;
+                method.attributes = attributes;
                 Ok(Item::Method(method))
             }
+            Some(Token::Extern) => {
+                let func = self.parse_extern_function_decl(start, is_public)?;
+                Ok(Item::ExternFunction(func))
+            }
             _ => Err(self.error("Expected item declaration".to_string())),
         }
     }
 
+    /// `inline` / `inline(always)` の指定を解析（指定がなければ `InlineHint::None`）
+    fn parse_inline_hint(&mut self) -> ParseResult<InlineHint> {
+        if !self.match_token(&Token::Inline) {
+            return Ok(InlineHint::None);
+        }
+        if !self.match_token(&Token::LeftParen) {
+            return Ok(InlineHint::Hint);
+        }
+        if self.expect_identifier()? != "always" {
+            return Err(self.error("Expected `always` in inline(...)".to_string()));
+        }
+        self.expect(Token::RightParen)?;
+        Ok(InlineHint::Always)
+    }
+
+    /// `#[名前]` の属性の並びを解析（なければ空）
+    fn parse_attributes(&mut self) -> ParseResult<Vec<Attribute>> {
+        let mut attributes = Vec::new();
+        while self.check(&Token::Hash) {
+            let start = self.current_span().start;
+            self.advance();
+            self.expect(Token::LeftBracket)?;
+            let name = self.expect_identifier()?;
+            self.expect(Token::RightBracket)?;
+            match Attribute::from_name(&name) {
+                Some(attribute) => attributes.push(attribute),
+                None => {
+                    return Err(self.error_at(format!("Unknown attribute `#[{}]`", name), self.span_from(start)));
+                }
+            }
+        }
+        Ok(attributes)
+    }
+
     /// 型定義を解析
-    fn parse_type_def(&mut self) -> ParseResult<TypeDef> {
-        let start = self.current_span().start;
+    fn parse_type_def(&mut self, start: usize) -> ParseResult<TypeDef> {
         self.expect(Token::Type)?;
         let name = self.expect_identifier()?;
         
@@ -66,12 +151,12 @@ impl Parser {
         match self.current_token() {
             Some(Token::Struct) => {
                 self.advance();
-                let struct_def = self.parse_struct_body(name, type_params)?;
+                let struct_def = self.parse_struct_body(start, name, type_params)?;
                 Ok(TypeDef::Struct(struct_def))
             }
             Some(Token::Enum) => {
                 self.advance();
-                let enum_def = self.parse_enum_body(name, type_params)?;
+                let enum_def = self.parse_enum_body(start, name, type_params)?;
                 Ok(TypeDef::Enum(enum_def))
             }
             _ => {
@@ -82,6 +167,7 @@ impl Parser {
                     name,
                     type_params,
                     underlying_type,
+                    docs: Vec::new(),
                     span,
                 }))
             }
@@ -89,112 +175,100 @@ impl Parser {
     }
 
     /// 構造体本体を解析
-    fn parse_struct_body(&mut self, name: String, type_params: Vec<TypeParam>) -> ParseResult<StructDef> {
-        let start = self.current_span().start;
-        self.expect(Token::LeftBrace)?;
-
-        let mut fields = Vec::new();
+    fn parse_struct_body(&mut self, start: usize, name: String, type_params: Vec<TypeParam>) -> ParseResult<StructDef> {
+        let fields = self.within(format!("struct `{}`'s fields", name), |parser| {
+            parser.expect(Token::LeftBrace)?;
 
-        while !self.check(&Token::RightBrace) && !self.is_at_end() {
-            let field_name = self.expect_identifier()?;
-            self.expect(Token::Colon)?;
-            let ty = self.parse_type()?;
-            let field_span = self.current_span();
+            let mut fields = Vec::new();
 
-            fields.push(Field {
-                name: field_name,
-                ty,
-                span: field_span.into(),
-            });
+            while !parser.check(&Token::RightBrace) && !parser.is_at_end() {
+                fields.push(parser.parse_field()?);
 
-            if !self.check(&Token::RightBrace) {
-                self.expect(Token::Comma)?;
+                if !parser.check(&Token::RightBrace) {
+                    parser.expect(Token::Comma)?;
+                }
             }
-        }
 
-        self.expect(Token::RightBrace)?;
+            parser.expect(Token::RightBrace)?;
+            Ok(fields)
+        })?;
         let span = self.span_from(start);
 
-        Ok(StructDef { name, type_params, fields, span })
+        Ok(StructDef { name, type_params, fields, docs: Vec::new(), span })
     }
 
     /// 列挙型本体を解析
-    fn parse_enum_body(&mut self, name: String, type_params: Vec<TypeParam>) -> ParseResult<EnumDef> {
-        let start = self.current_span().start;
-        self.expect(Token::LeftBrace)?;
+    fn parse_enum_body(&mut self, start: usize, name: String, type_params: Vec<TypeParam>) -> ParseResult<EnumDef> {
+        let variants = self.within(format!("enum `{}`'s variants", name), |parser| {
+            parser.expect(Token::LeftBrace)?;
 
-        let mut variants = Vec::new();
+            let mut variants = Vec::new();
 
-        while !self.check(&Token::RightBrace) && !self.is_at_end() {
-            let variant_name = self.expect_identifier()?;
-            let mut fields = Vec::new();
+            while !parser.check(&Token::RightBrace) && !parser.is_at_end() {
+                let variant_start = parser.current_span().start;
+                let variant_name = parser.expect_identifier()?;
+                let mut fields = Vec::new();
+
+                // タプルライクフィールド: Variant(field1: Type1, field2: Type2)
+                if parser.match_token(&Token::LeftParen) {
+                    while !parser.check(&Token::RightParen) && !parser.is_at_end() {
+                        fields.push(parser.parse_field()?);
 
-            // タプルライクフィールド: Variant(field1: Type1, field2: Type2)
-            if self.match_token(&Token::LeftParen) {
-                while !self.check(&Token::RightParen) && !self.is_at_end() {
-                    let field_name = self.expect_identifier()?;
-                    self.expect(Token::Colon)?;
-                    let ty = self.parse_type()?;
-                    let field_span = self.current_span();
-
-                    fields.push(Field {
-                        name: field_name,
-                        ty,
-                        span: field_span.into(),
-                    });
-
-                    if !self.check(&Token::RightParen) {
-                        self.expect(Token::Comma)?;
+                        if !parser.check(&Token::RightParen) {
+                            parser.expect(Token::Comma)?;
+                        }
                     }
+                    parser.expect(Token::RightParen)?;
                 }
-                self.expect(Token::RightParen)?;
-            }
-            // 構造体ライクフィールド: Variant { field1: Type1, field2: Type2 }
-            else if self.match_token(&Token::LeftBrace) {
-                while !self.check(&Token::RightBrace) && !self.is_at_end() {
-                    let field_name = self.expect_identifier()?;
-                    self.expect(Token::Colon)?;
-                    let ty = self.parse_type()?;
-                    let field_span = self.current_span();
-
-                    fields.push(Field {
-                        name: field_name,
-                        ty,
-                        span: field_span.into(),
-                    });
-
-                    if !self.check(&Token::RightBrace) {
-                        self.expect(Token::Comma)?;
+                // 構造体ライクフィールド: Variant { field1: Type1, field2: Type2 }
+                else if parser.match_token(&Token::LeftBrace) {
+                    while !parser.check(&Token::RightBrace) && !parser.is_at_end() {
+                        fields.push(parser.parse_field()?);
+
+                        if !parser.check(&Token::RightBrace) {
+                            parser.expect(Token::Comma)?;
+                        }
                     }
+                    parser.expect(Token::RightBrace)?;
                 }
-                self.expect(Token::RightBrace)?;
-            }
 
-            let variant_span = self.current_span();
-            variants.push(Variant {
-                name: variant_name,
-                fields,
-                span: variant_span.into(),
-            });
+                variants.push(Variant {
+                    name: variant_name,
+                    fields,
+                    span: parser.span_from(variant_start),
+                });
 
-            if !self.check(&Token::RightBrace) {
-                self.expect(Token::Comma)?;
+                if !parser.check(&Token::RightBrace) {
+                    parser.expect(Token::Comma)?;
+                }
             }
-        }
 
-        self.expect(Token::RightBrace)?;
+            parser.expect(Token::RightBrace)?;
+            Ok(variants)
+        })?;
         let span = self.span_from(start);
 
         Ok(EnumDef {
             name,
             type_params,
             variants,
+            docs: Vec::new(),
             span,
         })
     }
 
+    /// フィールド（`name: Type`）を解析
+    fn parse_field(&mut self) -> ParseResult<Field> {
+        let start = self.current_span().start;
+        let name = self.expect_identifier()?;
+        self.expect(Token::Colon)?;
+        let ty = self.parse_type()?;
+        let span = self.span_from(start);
+        Ok(Field { name, ty, span })
+    }
+
     /// 構造体定義を解析（`struct Name { ... }` 構文）
-    fn parse_struct_def(&mut self) -> ParseResult<StructDef> {
+    fn parse_struct_def(&mut self, start: usize) -> ParseResult<StructDef> {
         self.expect(Token::Struct)?;
         let name = self.expect_identifier()?;
         
@@ -205,11 +279,11 @@ impl Parser {
             Vec::new()
         };
         
-        self.parse_struct_body(name, type_params)
+        self.parse_struct_body(start, name, type_params)
     }
 
     /// 列挙型定義を解析（`enum Name { ... }` 構文）
-    fn parse_enum_def(&mut self) -> ParseResult<EnumDef> {
+    fn parse_enum_def(&mut self, start: usize) -> ParseResult<EnumDef> {
         self.expect(Token::Enum)?;
         let name = self.expect_identifier()?;
         
@@ -220,13 +294,11 @@ impl Parser {
             Vec::new()
         };
         
-        self.parse_enum_body(name, type_params)
+        self.parse_enum_body(start, name, type_params)
     }
 
     /// 関数宣言を解析（可視性修飾子付き）
-    fn parse_function_decl_with_visibility(&mut self, is_public: bool) -> ParseResult<FunctionDecl> {
-        let start = self.current_span().start;
-
+    fn parse_function_decl_with_visibility(&mut self, start: usize, is_public: bool) -> ParseResult<FunctionDecl> {
         self.expect(Token::Fn)?;
         let name = self.expect_identifier()?;
 
@@ -238,9 +310,12 @@ impl Parser {
         };
 
         // パラメータ
-        self.expect(Token::LeftParen)?;
-        let params = self.parse_parameters()?;
-        self.expect(Token::RightParen)?;
+        let params = self.within(format!("the parameters of `{}`", name), |parser| {
+            parser.expect(Token::LeftParen)?;
+            let params = parser.parse_parameters()?;
+            parser.expect(Token::RightParen)?;
+            Ok(params)
+        })?;
 
         // 戻り値型
         let return_type = if self.match_token(&Token::Colon) {
@@ -257,27 +332,148 @@ impl Parser {
         };
 
         // 関数本体
-        let body = self.parse_block()?;
+        let returns_value = return_type.as_deref().is_some_and(|ty| *ty != Type::Void);
+        let body = self.parse_function_body(returns_value)?;
 
         let span = self.span_from(start);
 
         Ok(FunctionDecl {
             is_public,
+            inline: InlineHint::None,
+            attributes: Vec::new(),
             name,
             type_params,
             params,
             return_type,
             lives_clause,
             body,
+            docs: Vec::new(),
             span,
         })
     }
 
-    /// メソッド宣言を解析（可視性修飾子付き）
-    fn parse_method_decl_with_visibility(&mut self, is_public: bool) -> ParseResult<MethodDecl> {
+    /// 文の位置の関数宣言（ローカル関数）を解析
+    ///
+    /// 可視性修飾子・`inline`・属性は付けられません。
+    pub(super) fn parse_local_function_decl(&mut self) -> ParseResult<FunctionDecl> {
+        let docs = self.take_doc_comments();
         let start = self.current_span().start;
+        let mut func = self.parse_function_decl_with_visibility(start, false)?;
+        func.docs = docs;
+        Ok(func)
+    }
+
+    /// 外部関数の宣言を解析
+    ///
+    /// `extern ["C"] [link("ライブラリ")] fn 名前(引数): 戻り値型;` の形で、本体の代わりに `;` で終わります。
+    /// 戻り値型の前には `:` の代わりにCの宣言に合わせた `->` も書けます。
+    fn parse_extern_function_decl(&mut self, start: usize, is_public: bool) -> ParseResult<ExternFunctionDecl> {
+        self.expect(Token::Extern)?;
+
+        // 呼び出し規約（オプション）
+        let abi = if matches!(self.current_token(), Some(Token::String(_))) {
+            let abi = self.expect_string()?;
+            if abi != "C" {
+                return Err(self.error(format!("Unsupported ABI \"{}\"; only \"C\" is supported", abi)));
+            }
+            abi
+        } else {
+            "C".to_string()
+        };
+
+        // リンクするライブラリ（オプション）
+        let link = if matches!(self.current_token(), Some(Token::Identifier(name)) if name == "link") {
+            self.advance();
+            self.expect(Token::LeftParen)?;
+            let library = self.expect_string()?;
+            self.expect(Token::RightParen)?;
+            Some(library)
+        } else {
+            None
+        };
+
+        if !self.check(&Token::Fn) {
+            return Err(self.error("Expected `fn` after extern".to_string()));
+        }
+        self.advance();
+        let name = self.expect_identifier()?;
+        if self.check(&Token::Lt) {
+            return Err(self.error("Extern functions cannot have type parameters".to_string()));
+        }
+
+        // パラメータ
+        let params = self.within(format!("the parameters of `{}`", name), |parser| {
+            parser.expect(Token::LeftParen)?;
+            let params = parser.parse_parameters()?;
+            parser.expect(Token::RightParen)?;
+            Ok(params)
+        })?;
+
+        // 戻り値型
+        let return_type = if self.match_token(&Token::Colon) || self.match_token(&Token::Arrow) {
+            Some(Box::new(self.parse_type()?))
+        } else {
+            None
+        };
 
+        if self.check(&Token::LeftBrace) {
+            return Err(self.error("Extern functions cannot have a body".to_string()));
+        }
+        self.expect(Token::Semicolon)?;
+        let span = self.span_from(start);
+
+        Ok(ExternFunctionDecl {
+            is_public,
+            abi,
+            link,
+            name,
+            params,
+            return_type,
+            docs: Vec::new(),
+            span,
+        })
+    }
+
+    /// メソッド宣言を解析（可視性修飾子付き）
+    fn parse_method_decl_with_visibility(&mut self, start: usize, is_public: bool) -> ParseResult<MethodDecl> {
         self.expect(Token::Impl)?;
+        self.parse_method(start, is_public, None)
+    }
+
+    /// implブロックを解析し、メソッドの宣言に展開する
+    ///
+    /// `impl Point { fn len(&self): f64 { ... } }` は `impl fn len(self: &Point): f64 { ... }` と同じです。
+    /// 同じ型のメソッドを `impl fn` とimplブロックの両方で定義できます。
+    fn parse_impl_block(&mut self) -> ParseResult<Vec<MethodDecl>> {
+        self.expect(Token::Impl)?;
+        let self_type = self.parse_type()?;
+        self.expect(Token::LeftBrace)?;
+
+        let mut methods = Vec::new();
+        while !self.check(&Token::RightBrace) && !self.is_at_end() {
+            let docs = self.take_doc_comments();
+            let attributes = self.parse_attributes()?;
+            let start = self.current_span().start;
+            let is_public = self.match_token(&Token::Pub);
+            let inline = self.parse_inline_hint()?;
+            if !self.check(&Token::Fn) {
+                return Err(self.error("Expected method declaration in impl block".to_string()));
+            }
+            let mut method = self.parse_method(start, is_public, Some(&self_type))?;
+            method.inline = inline;
+            method.attributes = attributes;
+            method.docs = docs;
+            methods.push(method);
+        }
+        self.expect(Token::RightBrace)?;
+
+        Ok(methods)
+    }
+
+    /// `fn` からメソッドの本体までを解析
+    ///
+    /// `self_type` はimplブロックの対象の型で、指定した場合はレシーバーに `&self` などの省略形を使えます。
+    fn parse_method(&mut self, start: usize, is_public: bool, self_type: Option<&Type>) -> ParseResult<MethodDecl> {
         self.expect(Token::Fn)?;
         let name = self.expect_identifier()?;
         
@@ -290,7 +486,10 @@ impl Parser {
 
         // レシーバー
         self.expect(Token::LeftParen)?;
-        let receiver = self.parse_receiver()?;
+        let receiver = match self_type {
+            Some(self_type) => self.parse_impl_receiver(self_type)?,
+            None => self.parse_receiver()?,
+        };
 
         // その他のパラメータ
         let params = if self.match_token(&Token::Comma) {
@@ -315,12 +514,15 @@ impl Parser {
         };
 
         // メソッド本体
-        let body = self.parse_block()?;
+        let returns_value = return_type.as_deref().is_some_and(|ty| *ty != Type::Void);
+        let body = self.parse_function_body(returns_value)?;
 
         let span = self.span_from(start);
 
         Ok(MethodDecl {
             is_public,
+            inline: InlineHint::None,
+            attributes: Vec::new(),
             name,
             type_params,
             receiver,
@@ -328,6 +530,7 @@ impl Parser {
             return_type,
             lives_clause,
             body,
+            docs: Vec::new(),
             span,
         })
     }
@@ -338,12 +541,13 @@ impl Parser {
 
         while !self.check(&Token::RightParen) && !self.is_at_end() {
             let param_start = self.current_span().start;
+            let is_mut = self.match_token(&Token::Mut);
             let name = self.expect_identifier()?;
             self.expect(Token::Colon)?;
             let ty = self.parse_type()?;
             let span = self.span_from(param_start);
 
-            params.push(Param { name, ty, span });
+            params.push(Param { name, ty, is_mut, span });
 
             if !self.check(&Token::RightParen) {
                 self.expect(Token::Comma)?;
@@ -384,6 +588,54 @@ impl Parser {
         })
     }
 
+    /// implブロック内のメソッドのレシーバーを解析
+    ///
+    /// `self`・`mut self`・`&self`・`&mut self` はimplブロックの型のレシーバーになります。
+    /// 型を明示したレシーバー（`self: &Point`）も書けますが、型はimplブロックの型と一致する必要があります。
+    fn parse_impl_receiver(&mut self, self_type: &Type) -> ParseResult<Receiver> {
+        let start = self.current_span().start;
+
+        let (ty, is_mut) = if self.match_token(&Token::Ampersand) {
+            let is_mut_ref = self.match_token(&Token::Mut);
+            self.expect(Token::SelfValue)?;
+            (Type::Reference(Box::new(self_type.clone()), is_mut_ref), false)
+        } else if self.check(&Token::SelfValue) && self.peek(1) != Some(&Token::Colon) {
+            self.advance();
+            (self_type.clone(), false)
+        } else if self.check(&Token::Mut)
+            && self.peek(1) == Some(&Token::SelfValue)
+            && self.peek(2) != Some(&Token::Colon)
+        {
+            self.advance();
+            self.advance();
+            (self_type.clone(), true)
+        } else {
+            let receiver = self.parse_receiver()?;
+            let target = match &receiver.ty {
+                Type::Reference(inner, _) => inner.as_ref(),
+                ty => ty,
+            };
+            if target != self_type {
+                return Err(self.error_at(
+                    format!(
+                        "Receiver type {} does not match the impl block type {}",
+                        print_type(&receiver.ty),
+                        print_type(self_type)
+                    ),
+                    receiver.span,
+                ));
+            }
+            return Ok(receiver);
+        };
+
+        Ok(Receiver {
+            name: None,
+            ty,
+            is_mut,
+            span: self.span_from(start),
+        })
+    }
+
     /// lives句を解析
     pub(super) fn parse_lives_clause(&mut self) -> ParseResult<LivesClause> {
         let start = self.current_span().start;
@@ -391,14 +643,19 @@ impl Parser {
 
         loop {
             let constraint_start = self.current_span().start;
-            let target = self.expect_identifier()?;
+            // `return` は戻り値を表す
+            let target = if self.match_token(&Token::Return) {
+                LivesConstraint::RETURN_TARGET.to_string()
+            } else {
+                self.expect_identifier()?
+            };
             self.expect(Token::Colon)?;
 
             let mut sources = Vec::new();
-            sources.push(self.expect_identifier()?);
+            sources.push(self.parse_lives_source()?);
 
             while self.match_token(&Token::Plus) {
-                sources.push(self.expect_identifier()?);
+                sources.push(self.parse_lives_source()?);
             }
 
             let constraint_span = self.span_from(constraint_start);
@@ -417,37 +674,104 @@ impl Parser {
         Ok(LivesClause { constraints, span })
     }
 
+    /// lives句の借用元（引数名または `self`）を解析
+    fn parse_lives_source(&mut self) -> ParseResult<String> {
+        if self.match_token(&Token::SelfValue) {
+            Ok("self".to_string())
+        } else {
+            self.expect_identifier()
+        }
+    }
+
     /// パッケージ宣言を解析
     pub(super) fn parse_package_decl(&mut self) -> ParseResult<PackageDecl> {
         let start = self.current_span().start;
         self.expect(Token::Package)?;
         let name = self.expect_identifier()?;
         // package宣言はセミコロンを要求しない
+
+        // `package main no_prelude` は標準ライブラリのプレリュードを読み込まない
+        let no_prelude = matches!(self.current_token(), Some(Token::Identifier(marker)) if marker == "no_prelude");
+        if no_prelude {
+            self.advance();
+        }
         
         let span = self.span_from(start);
-        Ok(PackageDecl { name, span })
+        Ok(PackageDecl { name, no_prelude, span })
     }
 
     /// インポートを解析
+    ///
+    /// パスは文字列（`"std/collections"`）かドット区切りの名前（`std.collections`）で書き、
+    /// 続く `{ a, b as c }` で要素を選択してインポートできます。
     pub(super) fn parse_imports(&mut self) -> ParseResult<Vec<Import>> {
         let mut imports = Vec::new();
 
         while self.match_token(&Token::Import) {
             let start = self.current_span().start;
-            let path = self.expect_string()?;
+            let path = self.parse_import_path()?;
             
             let alias = if self.match_token(&Token::As) {
                 Some(self.expect_identifier()?)
             } else {
                 None
             };
+
+            let symbols = if self.check(&Token::LeftBrace) {
+                if alias.is_some() {
+                    return Err(self.error("An import with a symbol list cannot have an alias".to_string()));
+                }
+                self.parse_import_symbols()?
+            } else {
+                Vec::new()
+            };
             
             // import文はセミコロンを要求しない
             let span = self.span_from(start);
             
-            imports.push(Import { path, alias, span });
+            imports.push(Import { path, alias, symbols, span });
         }
 
         Ok(imports)
     }
-}
\ No newline at end of file
+
+    /// インポートのパスを解析
+    fn parse_import_path(&mut self) -> ParseResult<String> {
+        if !self.check_identifier() {
+            return self.expect_string();
+        }
+        let mut path = self.expect_identifier()?;
+        while self.match_token(&Token::Dot) {
+            path.push('.');
+            path.push_str(&self.expect_identifier()?);
+        }
+        Ok(path)
+    }
+
+    /// 選択インポートの要素の一覧 `{ a, b as c }` を解析（末尾のカンマを許す）
+    fn parse_import_symbols(&mut self) -> ParseResult<Vec<ImportSymbol>> {
+        self.expect(Token::LeftBrace)?;
+        let mut symbols = Vec::new();
+
+        while !self.check(&Token::RightBrace) && !self.is_at_end() {
+            let start = self.current_span().start;
+            let name = self.expect_identifier()?;
+            let alias = if self.match_token(&Token::As) {
+                Some(self.expect_identifier()?)
+            } else {
+                None
+            };
+            symbols.push(ImportSymbol { name, alias, span: self.span_from(start) });
+
+            if !self.check(&Token::RightBrace) {
+                self.expect(Token::Comma)?;
+            }
+        }
+
+        self.expect(Token::RightBrace)?;
+        if symbols.is_empty() {
+            return Err(self.error("Expected at least one symbol in the import list".to_string()));
+        }
+        Ok(symbols)
+    }
+}
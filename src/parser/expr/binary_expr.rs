@@ -8,8 +8,9 @@ impl Parser {
     pub(crate) fn parse_or_expression(&mut self) -> ParseResult<Expression> {
         let mut left = self.parse_and_expression()?;
 
-        while self.match_token(&Token::OrOr) {
+        while self.match_operator(&[Token::OrOr]).is_some() {
             let op = BinaryOp::Or;
+            self.extend_chain()?;
             let right = self.parse_and_expression()?;
             let span = Span::new(left.span().start, right.span().end);
             left = Expression::Binary(BinaryExpr {
@@ -27,8 +28,9 @@ impl Parser {
     pub(crate) fn parse_and_expression(&mut self) -> ParseResult<Expression> {
         let mut left = self.parse_bitwise_or_expression()?;
 
-        while self.match_token(&Token::AndAnd) {
+        while self.match_operator(&[Token::AndAnd]).is_some() {
             let op = BinaryOp::And;
+            self.extend_chain()?;
             let right = self.parse_bitwise_or_expression()?;
             let span = Span::new(left.span().start, right.span().end);
             left = Expression::Binary(BinaryExpr {
@@ -46,8 +48,9 @@ impl Parser {
     pub(crate) fn parse_bitwise_or_expression(&mut self) -> ParseResult<Expression> {
         let mut left = self.parse_bitwise_xor_expression()?;
 
-        while self.match_token(&Token::Or) {
+        while self.match_operator(&[Token::Or]).is_some() {
             let op = BinaryOp::BitOr;
+            self.extend_chain()?;
             let right = self.parse_bitwise_xor_expression()?;
             let span = Span::new(left.span().start, right.span().end);
             left = Expression::Binary(BinaryExpr {
@@ -65,8 +68,9 @@ impl Parser {
     pub(crate) fn parse_bitwise_xor_expression(&mut self) -> ParseResult<Expression> {
         let mut left = self.parse_bitwise_and_expression()?;
 
-        while self.match_token(&Token::Caret) {
+        while self.match_operator(&[Token::Caret]).is_some() {
             let op = BinaryOp::BitXor;
+            self.extend_chain()?;
             let right = self.parse_bitwise_and_expression()?;
             let span = Span::new(left.span().start, right.span().end);
             left = Expression::Binary(BinaryExpr {
@@ -84,8 +88,9 @@ impl Parser {
     pub(crate) fn parse_bitwise_and_expression(&mut self) -> ParseResult<Expression> {
         let mut left = self.parse_equality_expression()?;
 
-        while self.match_token(&Token::Ampersand) {
+        while self.match_operator(&[Token::Ampersand]).is_some() {
             let op = BinaryOp::BitAnd;
+            self.extend_chain()?;
             let right = self.parse_equality_expression()?;
             let span = Span::new(left.span().start, right.span().end);
             left = Expression::Binary(BinaryExpr {
@@ -104,12 +109,13 @@ impl Parser {
         let start_pos = self.current_span().start;
         let mut left = self.parse_relational_expression()?;
 
-        while let Some(op) = self.match_tokens(&[Token::EqEq, Token::NotEq]) {
+        while let Some(op) = self.match_operator(&[Token::EqEq, Token::NotEq]) {
             let op = match op {
                 Token::EqEq => BinaryOp::Eq,
                 Token::NotEq => BinaryOp::Ne,
                 _ => unreachable!(),
             };
+            self.extend_chain()?;
             let right = self.parse_relational_expression()?;
             let span = self.span_from(start_pos);
             left = Expression::Binary(BinaryExpr {
@@ -123,18 +129,20 @@ impl Parser {
         Ok(left)
     }
 
-    /// 関係式を解析
+    /// 関係式を解析（`in` も比較と同じ優先順位）
     pub(crate) fn parse_relational_expression(&mut self) -> ParseResult<Expression> {
         let mut left = self.parse_shift_expression()?;
 
-        while let Some(op) = self.match_tokens(&[Token::Lt, Token::Gt, Token::LtEq, Token::GtEq]) {
+        while let Some(op) = self.match_operator(&[Token::Lt, Token::Gt, Token::LtEq, Token::GtEq, Token::In]) {
             let op = match op {
                 Token::Lt => BinaryOp::Lt,
                 Token::Gt => BinaryOp::Gt,
                 Token::LtEq => BinaryOp::Le,
                 Token::GtEq => BinaryOp::Ge,
+                Token::In => BinaryOp::In,
                 _ => unreachable!(),
             };
+            self.extend_chain()?;
             let right = self.parse_shift_expression()?;
             let span = Span::new(left.span().start, right.span().end);
             left = Expression::Binary(BinaryExpr {
@@ -152,12 +160,13 @@ impl Parser {
     pub(crate) fn parse_shift_expression(&mut self) -> ParseResult<Expression> {
         let mut left = self.parse_additive_expression()?;
 
-        while let Some(op) = self.match_tokens(&[Token::LtLt, Token::GtGt]) {
+        while let Some(op) = self.match_operator(&[Token::LtLt, Token::GtGt]) {
             let op = match op {
                 Token::LtLt => BinaryOp::Shl,
                 Token::GtGt => BinaryOp::Shr,
                 _ => unreachable!(),
             };
+            self.extend_chain()?;
             let right = self.parse_additive_expression()?;
             let span = Span::new(left.span().start, right.span().end);
             left = Expression::Binary(BinaryExpr {
@@ -175,12 +184,13 @@ impl Parser {
     pub(crate) fn parse_additive_expression(&mut self) -> ParseResult<Expression> {
         let mut left = self.parse_multiplicative_expression()?;
 
-        while let Some(op) = self.match_tokens(&[Token::Plus, Token::Minus]) {
+        while let Some(op) = self.match_operator(&[Token::Plus, Token::Minus]) {
             let op = match op {
                 Token::Plus => BinaryOp::Add,
                 Token::Minus => BinaryOp::Subtract,
                 _ => unreachable!(),
             };
+            self.extend_chain()?;
             let right = self.parse_multiplicative_expression()?;
             let span = Span::new(left.span().start, right.span().end);
             left = Expression::Binary(BinaryExpr {
@@ -198,13 +208,14 @@ impl Parser {
     pub(crate) fn parse_multiplicative_expression(&mut self) -> ParseResult<Expression> {
         let mut left = self.parse_cast_expression()?;
 
-        while let Some(op) = self.match_tokens(&[Token::Star, Token::Slash, Token::Percent]) {
+        while let Some(op) = self.match_operator(&[Token::Star, Token::Slash, Token::Percent]) {
             let op = match op {
                 Token::Star => BinaryOp::Multiply,
                 Token::Slash => BinaryOp::Divide,
                 Token::Percent => BinaryOp::Modulo,
                 _ => unreachable!(),
             };
+            self.extend_chain()?;
             let right = self.parse_cast_expression()?;
             let span = Span::new(left.span().start, right.span().end);
             left = Expression::Binary(BinaryExpr {
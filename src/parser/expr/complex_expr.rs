@@ -14,10 +14,11 @@ impl Parser {
             self.advance();
             let span = self.span_from(start);
             
-            // 空の構造体の場合（型引数がない場合は構造体の可能性が高い）
-            if type_args.is_empty() {
+            // 空の構造体の場合（型引数のあるHashMap以外は構造体）
+            if type_args.is_empty() || name != "HashMap" {
                 return Ok(Expression::StructLit(StructLiteral {
                     name: Some(name),
+                    type_args,
                     fields: vec![],
                     span,
                 }));
@@ -31,22 +32,17 @@ impl Parser {
             }));
         }
         
-        // 最初の要素を見て、初期化子の種類を判定
-        let is_named_field = if self.check_identifier() {
-            let saved_pos = self.current;
-            let _field_name = self.expect_identifier()?;
-            let result = self.check(&Token::Colon);
-            self.current = saved_pos; // 位置を戻す
-            result
-        } else {
-            false
-        };
+        // 最初の要素を見て、初期化子の種類を判定（`x` だけのフィールドは `x: x` の省略形）
+        let is_named_field = self.is_field_init_start()
+            || (self.check_identifier() && self.peek(1) == Some(&Token::RightBrace));
         
-        let is_key_value = if !is_named_field && matches!(self.current_token(), Some(Token::String(_))) {
-            let saved_pos = self.current;
+        // 名前付きフィールドでなければ、最初の式の後にコロンが続くかでマップリテラルと判定する
+        // （`HashMap<i32, String> { 1 + 1: "two" }` のように文字列以外のキーも指定できる）
+        let is_key_value = if !is_named_field {
+            let checkpoint = self.checkpoint();
             let _key = self.parse_expression_internal()?;
             let result = self.check(&Token::Colon);
-            self.current = saved_pos; // 位置を戻す
+            self.rewind(checkpoint); // 位置を戻す
             result
         } else {
             false
@@ -57,14 +53,7 @@ impl Parser {
             let mut fields = Vec::new();
             
             while !self.check(&Token::RightBrace) && !self.is_at_end() {
-                let field_name = self.expect_identifier()?;
-                self.expect(Token::Colon)?;
-                let value = self.parse_expression_internal()?;
-                
-                fields.push(StructFieldInit {
-                    name: field_name,
-                    value,
-                });
+                fields.push(self.parse_struct_field_init()?);
                 
                 if !self.check(&Token::RightBrace) {
                     self.expect(Token::Comma)?;
@@ -76,6 +65,7 @@ impl Parser {
             
             return Ok(Expression::StructLit(StructLiteral {
                 name: Some(name),
+                type_args,
                 fields,
                 span,
             }));
@@ -111,6 +101,26 @@ impl Parser {
         Err(self.error(format!("Invalid initializer syntax for type {}", name)))
     }
 
+    /// 現在位置が構造体リテラルのフィールドの初期化（`x: ...` または省略形の `x,`）で始まるか
+    pub(crate) fn is_field_init_start(&self) -> bool {
+        matches!(self.peek(0), Some(Token::Identifier(_)))
+            && matches!(self.peek(1), Some(Token::Colon | Token::Comma))
+    }
+
+    /// 構造体リテラルのフィールドの初期化を解析
+    ///
+    /// 値を省略した `x` は、同じ名前の変数で初期化する `x: x` の省略形です。
+    pub(crate) fn parse_struct_field_init(&mut self) -> ParseResult<StructFieldInit> {
+        let span = self.current_span();
+        let name = self.expect_identifier()?;
+        let value = if self.match_token(&Token::Colon) {
+            self.parse_expression_internal()?
+        } else {
+            Expression::Identifier(Identifier { name: name.clone(), span: span.into() })
+        };
+        Ok(StructFieldInit { name, value })
+    }
+
     /// 構造体リテラルを解析
     pub(crate) fn parse_struct_literal(&mut self, name: String) -> ParseResult<Expression> {
         let start = self.current_span().start - name.len();
@@ -142,6 +152,7 @@ impl Parser {
         
         Ok(Expression::StructLit(StructLiteral {
             name: Some(name),
+            type_args: Vec::new(),
             fields,
             span,
         }))
@@ -212,8 +223,8 @@ impl Parser {
                     span,
                 }));
             }
-            // ユニットバリアント: Enum::Variant
-            else {
+            // ユニットバリアント: Enum::Variant（組み込み型の関連定数 `i64::MAX` はパス式のまま残す）
+            else if !is_builtin_type_name(&enum_name) {
                 return Ok(Expression::EnumVariant(EnumVariantExpr {
                     enum_name,
                     variant: variant_name,
@@ -246,7 +257,7 @@ impl Parser {
         let else_branch = if self.match_token(&Token::Else) {
             if self.check(&Token::If) {
                 // else if の場合、再帰的にif式を解析
-                Some(Box::new(self.parse_if_expression()?))
+                Some(Box::new(self.chained(Self::parse_if_expression)?))
             } else {
                 // else ブロックの場合
                 let else_start = self.current_span().start;
@@ -296,4 +307,13 @@ impl Parser {
         }
         false
     }
-}
\ No newline at end of file
+}
+
+/// `i64::MAX` のように関連定数を参照できる組み込み型の名前かどうか
+fn is_builtin_type_name(name: &str) -> bool {
+    matches!(
+        name,
+        "i8" | "i16" | "i32" | "i64" | "i128" | "i256" | "u8" | "u16" | "u32" | "u64" | "u128" | "u256"
+            | "f8" | "f16" | "f32" | "f64" | "bool" | "str" | "String"
+    )
+}
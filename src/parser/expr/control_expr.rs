@@ -15,6 +15,7 @@ impl Parser {
         let mut arms = Vec::new();
         
         while !self.check(&Token::RightBrace) && !self.is_at_end() {
+            let arm_start = self.current_span().start;
             let pattern = self.parse_pattern(false)?; // false = not mutable pattern
             
             let guard = if self.match_token(&Token::If) {
@@ -22,6 +23,7 @@ impl Parser {
             } else {
                 None
             };
+            let arm_span = self.span_from(arm_start);
             
             self.expect(Token::FatArrow)?;
             
@@ -45,6 +47,7 @@ impl Parser {
                 pattern,
                 guard,
                 expr,
+                span: arm_span,
             });
             
             if !self.check(&Token::RightBrace) {
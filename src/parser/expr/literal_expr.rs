@@ -7,31 +7,56 @@ impl Parser {
     /// プライマリ式を解析
     pub(crate) fn parse_primary_expression(&mut self) -> ParseResult<Expression> {
         match self.current_token() {
-            Some(Token::Integer(value)) => self.parse_integer_literal(*value as i64),
-            Some(Token::Float(value)) => self.parse_float_literal(*value),
+            Some(Token::Integer((value, raw))) => self.parse_integer_literal(*value, raw.clone()),
+            Some(Token::Float((value, raw))) => self.parse_float_literal(*value, raw.clone()),
             Some(Token::String(value)) => self.parse_string_literal(value.clone()),
+            Some(Token::Bytes(value)) => self.parse_bytes_literal(value.clone()),
             Some(Token::TemplateString(value)) => self.parse_template_string(value.clone()),
             Some(Token::True) => self.parse_boolean_literal(true),
             Some(Token::False) => self.parse_boolean_literal(false),
+            // `_` は値を捨てるパターンで、値を持たないため式には書けない
+            Some(Token::Identifier(name)) if name == "_" => Err(self.error(
+                "`_` can only be used in patterns (e.g. `let _ = value;` or a `_ =>` match arm)".to_string(),
+            )),
             Some(Token::Identifier(name)) => self.parse_identifier_expression(name.clone()),
+            // 数値型の関連定数（`i64::MAX`）
+            Some(token @ (Token::I8 | Token::I16 | Token::I32 | Token::I64 | Token::I128 | Token::I256
+                | Token::U8 | Token::U16 | Token::U32 | Token::U64 | Token::U128 | Token::U256
+                | Token::F8 | Token::F16 | Token::F32 | Token::F64))
+                if self.peek(1) == Some(&Token::ColonColon) =>
+            {
+                let type_name = token.to_string();
+                let span = self.current_span();
+                self.advance();
+                self.parse_path_expression(type_name, span.into())
+            }
+            // メソッドの本体では名前を省略したレシーバーを `self` で参照する
+            Some(Token::SelfValue) => self.parse_identifier_expression("self".to_string()),
             Some(Token::LeftParen) => self.parse_parenthesized_or_tuple(),
             Some(Token::LeftBracket) => self.parse_array_literal(),
             Some(Token::Match) => self.parse_match_expression(),
             Some(Token::If) => self.parse_if_expression(),
             Some(Token::LeftBrace) => self.parse_block_expression_primary(),
-            _ => Err(self.error("Expected expression".to_string())),
+            _ => {
+                self.record_expected("expression".to_string());
+                Err(self.expected_error())
+            }
         }
     }
 
     /// 整数リテラルを解析
-    fn parse_integer_literal(&mut self, value: i64) -> ParseResult<Expression> {
+    fn parse_integer_literal(&mut self, value: i128, mut raw: String) -> ParseResult<Expression> {
         let span = self.current_span();
         self.advance();
 
         // 型サフィックスをチェック
         let suffix = self.parse_integer_suffix();
+        if let Some(suffix) = &suffix {
+            raw.push_str(suffix);
+        }
 
-        Ok(Expression::Integer(IntegerLit { value: value as i128, suffix, span: span.into() }))
+        // スパンは型サフィックスまでを含める
+        Ok(Expression::Integer(IntegerLit { value, suffix, raw, span: self.span_from(span.start) }))
     }
 
     /// 整数型サフィックスを解析
@@ -60,7 +85,7 @@ impl Parser {
     }
 
     /// 浮動小数点リテラルを解析
-    fn parse_float_literal(&mut self, value: f64) -> ParseResult<Expression> {
+    fn parse_float_literal(&mut self, value: f64, mut raw: String) -> ParseResult<Expression> {
         let span = self.current_span();
         self.advance();
 
@@ -75,8 +100,11 @@ impl Parser {
             Some(Token::F64) => { self.advance(); Some("f64".to_string()) },
             _ => None,
         };
+        if let Some(suffix) = &suffix {
+            raw.push_str(suffix);
+        }
 
-        Ok(Expression::Float(FloatLit { value, suffix, span: span.into() }))
+        Ok(Expression::Float(FloatLit { value, suffix, raw, span: self.span_from(span.start) }))
     }
 
     /// 文字列リテラルを解析
@@ -86,6 +114,13 @@ impl Parser {
         Ok(Expression::String(StringLit { value, span: span.into() }))
     }
 
+    /// バイト列リテラルを解析
+    fn parse_bytes_literal(&mut self, value: Vec<u8>) -> ParseResult<Expression> {
+        let span = self.current_span();
+        self.advance();
+        Ok(Expression::Bytes(BytesLit { value, span: span.into() }))
+    }
+
     /// ブール値リテラルを解析
     fn parse_boolean_literal(&mut self, value: bool) -> ParseResult<Expression> {
         let span = self.current_span();
@@ -99,6 +134,10 @@ impl Parser {
         self.advance();
         
         // パス（Enum::Variant など）を解析
+        // 型引数を明示した関数呼び出し（`make::<i64>()`）
+        if self.check(&Token::ColonColon) && self.peek(1) == Some(&Token::Lt) {
+            return self.parse_turbofish_call(name, span.into());
+        }
         if self.check(&Token::ColonColon) {
             return self.parse_path_expression(name, span.into());
         }
@@ -186,35 +225,19 @@ impl Parser {
                 // デフォルトで空の構造体リテラルとして扱う
                 return Ok(Expression::StructLit(StructLiteral {
                     name: None,
+                    type_args: Vec::new(),
                     fields: vec![],
                     span,
                 }));
             }
             
             // 最初の要素を見て判別
-            let is_struct_literal = if self.check_identifier() {
-                let saved_pos = self.current;
-                let _field = self.expect_identifier()?;
-                let result = self.check(&Token::Colon);
-                self.current = saved_pos; // 位置を戻す
-                result
-            } else {
-                false
-            };
-            
-            if is_struct_literal {
+            if self.is_field_init_start() {
                 // 匿名構造体リテラル
                 let mut fields = Vec::new();
                 
                 while !self.check(&Token::RightBrace) && !self.is_at_end() {
-                    let field_name = self.expect_identifier()?;
-                    self.expect(Token::Colon)?;
-                    let value = self.parse_expression_internal()?;
-                    
-                    fields.push(StructFieldInit {
-                        name: field_name,
-                        value,
-                    });
+                    fields.push(self.parse_struct_field_init()?);
                     
                     if !self.check(&Token::RightBrace) {
                         self.expect(Token::Comma)?;
@@ -226,6 +249,7 @@ impl Parser {
                 
                 Ok(Expression::StructLit(StructLiteral {
                     name: None,
+                    type_args: Vec::new(),
                     fields,
                     span,
                 }))
@@ -275,12 +299,14 @@ impl Parser {
         // テンプレート文字列をパース
         let mut parts = Vec::new();
         let mut current_text = String::new();
-        let mut chars = value.chars().peekable();
+        let mut chars = value.char_indices().peekable();
         
-        while let Some(ch) = chars.next() {
-            if ch == '$' && chars.peek() == Some(&'{') {
+        while let Some((_, ch)) = chars.next() {
+            if ch == '$' && chars.peek().map(|&(_, c)| c) == Some('{') {
                 // 補間式の開始
-                chars.next(); // '{'をスキップ
+                let (brace, _) = chars.next().unwrap(); // '{'をスキップ
+                // 補間式のソースコード上の位置（`` ` `` の次から数える）
+                let expr_offset = span.start + 1 + brace + 1;
                 
                 // 現在のテキストを保存
                 if !current_text.is_empty() {
@@ -293,7 +319,7 @@ impl Parser {
                 let mut brace_count = 1;
                 
                 while brace_count > 0 {
-                    match chars.next() {
+                    match chars.next().map(|(_, c)| c) {
                         Some('{') => {
                             brace_count += 1;
                             expr_str.push('{');
@@ -312,11 +338,11 @@ impl Parser {
                 }
                 
                 // 補間式をパース
-                let expr = self.parse_template_string_interpolation(&expr_str)?;
+                let expr = self.parse_template_string_interpolation(&expr_str, expr_offset)?;
                 parts.push(TemplateStringPart::Interpolation(expr));
             } else if ch == '\\' {
                 // エスケープシーケンス
-                match chars.next() {
+                match chars.next().map(|(_, c)| c) {
                     Some('n') => current_text.push('\n'),
                     Some('r') => current_text.push('\r'),
                     Some('t') => current_text.push('\t'),
@@ -357,9 +383,10 @@ impl Parser {
             return true;
         }
         
-        // 構造体リテラルパターン: { identifier : ...
+        // 構造体リテラルパターン: { identifier : ... または省略形の { identifier , ...
+        // （`{ x }` は変数の値を返すブロック式として扱う）
         if let Some(Token::Identifier(_)) = self.peek(1) {
-            if let Some(Token::Colon) = self.peek(2) {
+            if let Some(Token::Colon | Token::Comma) = self.peek(2) {
                 return true;
             }
         }
@@ -13,6 +13,7 @@ impl Parser {
             
             match self.current_token() {
                 Some(Token::LeftBracket) => {
+                    self.extend_chain()?;
                     self.advance();
                     let index = self.parse_expression_internal()?;
                     self.expect(Token::RightBracket)?;
@@ -24,20 +25,20 @@ impl Parser {
                     });
                 }
                 Some(Token::Dot) => {
+                    self.extend_chain()?;
                     self.advance();
                     let field = self.expect_identifier()?;
                     
                     // メソッド呼び出しかフィールドアクセスかを判定
                     if self.check(&Token::LeftParen) {
-                        self.advance();
-                        let args = self.parse_arguments()?;
-                        self.expect(Token::RightParen)?;
+                        let args = self.parse_call_arguments(format!("the arguments of method `{}`", field))?;
                         let span = self.span_from(start);
                         expr = Expression::MethodCall(MethodCallExpr {
                             object: Box::new(expr),
                             method: field,
                             args,
                             span,
+                            is_tail: false,
                         });
                     } else {
                         let span = self.span_from(start);
@@ -49,12 +50,16 @@ impl Parser {
                     }
                 }
                 Some(Token::LeftParen) => {
-                    self.advance();
-                    let args = self.parse_arguments()?;
-                    self.expect(Token::RightParen)?;
+                    self.extend_chain()?;
+                    let description = match &expr {
+                        Expression::Identifier(callee) => format!("the arguments of `{}`", callee.name),
+                        _ => "the call arguments".to_string(),
+                    };
+                    let args = self.parse_call_arguments(description)?;
                     let span = self.span_from(start);
                     expr = Expression::Call(CallExpr {
                         callee: Box::new(expr),
+                        type_args: Vec::new(),
                         args,
                         span,
                         is_tail: false,
@@ -109,6 +114,34 @@ impl Parser {
         Ok(expr)
     }
 
+    /// 型引数を明示した関数呼び出し（`make::<i64>()`）を解析
+    ///
+    /// `a < b > (c)` の比較と区別するため、呼び出しの型引数は `::<` で始めます。
+    /// 現在のトークンは関数名の後の `::` です。
+    pub(crate) fn parse_turbofish_call(&mut self, name: String, name_span: Span) -> ParseResult<Expression> {
+        self.advance(); // '::' をスキップ
+        let type_args = self.parse_type_arguments()?;
+        let args = self.parse_call_arguments(format!("the arguments of `{}`", name))?;
+        let span = self.span_from(name_span.start);
+        Ok(Expression::Call(CallExpr {
+            callee: Box::new(Expression::Identifier(Identifier { name, span: name_span })),
+            type_args,
+            args,
+            span,
+            is_tail: false,
+        }))
+    }
+
+    /// 括弧で囲んだ引数リストを解析
+    fn parse_call_arguments(&mut self, description: String) -> ParseResult<Vec<Expression>> {
+        self.within(description, |parser| {
+            parser.expect(Token::LeftParen)?;
+            let args = parser.parse_arguments()?;
+            parser.expect(Token::RightParen)?;
+            Ok(args)
+        })
+    }
+
     /// 引数リストを解析
     pub(crate) fn parse_arguments(&mut self) -> ParseResult<Vec<Expression>> {
         let mut args = Vec::new();
@@ -29,7 +29,7 @@ impl Parser {
         match self.current_token() {
             Some(Token::Bang) => {
                 self.advance();
-                let expr = self.parse_unary_expression()?;
+                let expr = self.nested(Self::parse_unary_expression)?;
                 let span = Span::new(start, expr.span().end);
                 Ok(Expression::Unary(UnaryExpr {
                     op: UnaryOp::Not,
@@ -39,7 +39,7 @@ impl Parser {
             }
             Some(Token::Minus) => {
                 self.advance();
-                let expr = self.parse_unary_expression()?;
+                let expr = self.nested(Self::parse_unary_expression)?;
                 let span = Span::new(start, expr.span().end);
                 Ok(Expression::Unary(UnaryExpr {
                     op: UnaryOp::Negate,
@@ -49,7 +49,7 @@ impl Parser {
             }
             Some(Token::Tilde) => {
                 self.advance();
-                let expr = self.parse_unary_expression()?;
+                let expr = self.nested(Self::parse_unary_expression)?;
                 let span = Span::new(start, expr.span().end);
                 Ok(Expression::Unary(UnaryExpr {
                     op: UnaryOp::BitNot,
@@ -60,7 +60,7 @@ impl Parser {
             Some(Token::Ampersand) => {
                 self.advance();
                 let is_mut = self.match_token(&Token::Mut);
-                let expr = self.parse_unary_expression()?;
+                let expr = self.nested(Self::parse_unary_expression)?;
                 let span = Span::new(start, expr.span().end);
                 Ok(Expression::Reference(ReferenceExpr {
                     expr: Box::new(expr),
@@ -70,7 +70,7 @@ impl Parser {
             }
             Some(Token::Star) => {
                 self.advance();
-                let expr = self.parse_unary_expression()?;
+                let expr = self.nested(Self::parse_unary_expression)?;
                 let span = Span::new(start, expr.span().end);
                 Ok(Expression::Dereference(DereferenceExpr {
                     expr: Box::new(expr),
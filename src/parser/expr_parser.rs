@@ -15,6 +15,6 @@ use super::{ParseResult, Parser};
 impl Parser {
     /// 式を解析（内部実装）
     pub(super) fn parse_expression_internal(&mut self) -> ParseResult<Expression> {
-        self.parse_or_expression()
+        self.nested(Self::parse_or_expression)
     }
 }
\ No newline at end of file
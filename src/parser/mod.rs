@@ -42,6 +42,13 @@ mod type_parser;
 // 公開API
 pub use parser_impl::Parser;
 
+/// 解析したASTを処理するスレッドのスタックの大きさ
+///
+/// 入れ子の深さと連鎖の長さを上限まで使ったASTでも、意味解析・単相化・コード生成の
+/// 再帰が（最適化なしのビルドでも）収まる大きさです。コンパイラのコマンドはこの大きさの
+/// スタックを持つスレッドで実行します。
+pub const STACK_SIZE: usize = 256 * 1024 * 1024;
+
 // 後方互換性のための型エイリアス
 use crate::error::ParserError;
 pub type ParseError = ParserError;
@@ -1,29 +1,141 @@
 //! メインパーサー構造とユーティリティ
 
+use std::cell::RefCell;
+
 use crate::ast::*;
-use crate::error::ParserError;
+use crate::error::{ParseContext, ParserError};
 use crate::lexer::{Token, TokenWithPosition};
 
 use super::{ParseError, ParseResult};
 
+/// 入れ子の深さの上限
+///
+/// 再帰下降解析と、解析したASTを再帰的にたどる後続の処理がスタックを使い果たさないよう、
+/// 式・文・型・パターンの入れ子をこの深さまでに制限します。
+/// 上限は後続の処理が [`super::STACK_SIZE`] のスタックで収まるように選んでいます。
+pub(super) const MAX_NESTING_DEPTH: usize = 128;
+
+/// 連鎖の長さの上限
+///
+/// 二項演算・後置演算の連鎖と `else if` の連なりは、括弧やブロックと違って平らに書けるため、
+/// 入れ子の深さとは別に数え、はるかに長いものまで受け付けます。
+/// 連鎖の長さだけASTは深くなるので、こちらも後続の処理がスタックに収まる長さに制限します。
+pub(super) const MAX_CHAIN_LENGTH: usize = 4096;
+
+/// トークン列から取り出したドキュメントコメント
+struct DocComment {
+    text: String,
+    span: logos::Span,
+    /// アイテムに付けたか
+    attached: bool,
+}
+
+/// 1つのトークンの位置で受け付けたものの一覧
+///
+/// 構文エラーで「期待したもの」として示します。照合する位置が変わったら（バックトラックした
+/// 場合も含めて）集め直します。
+#[derive(Default)]
+struct ExpectedSet {
+    position: usize,
+    items: Vec<String>,
+}
+
+/// バックトラックで戻る位置（`Parser::checkpoint`）
+pub(super) struct Checkpoint {
+    position: usize,
+    recovered: usize,
+}
+
 /// Yuniパーサー
 pub struct Parser {
     pub(super) tokens: Vec<TokenWithPosition>,
+    /// 各トークンの直前に改行があるか
+    after_newline: Vec<bool>,
     pub(super) current: usize,
+    /// 現在の入れ子の深さ
+    pub(super) depth: usize,
+    /// 現在の連鎖の長さ
+    pub(super) chain: usize,
+    /// ソース上の順のドキュメントコメント
+    doc_comments: Vec<DocComment>,
+    /// 現在の位置で照合して一致しなかったトークンの種類
+    expected: RefCell<ExpectedSet>,
+    /// 解析中の構文要素（外側から順）
+    contexts: Vec<ParseContext>,
+    /// 解析を続けられた構文エラー（書き忘れたセミコロンなど）
+    recovered: Vec<ParseError>,
 }
 
 impl Parser {
     pub fn new(tokens: Vec<TokenWithPosition>) -> Self {
-        // 改行トークンは意味を持たないのでフィルタリング
+        // 改行トークンは文法上の意味を持たないのでフィルタリングし、直後のトークンに印を付ける
+        // （セミコロンの書き忘れの検出だけに使う）
+        // ドキュメントコメントはトークン列から外し、アイテムの解析時に位置で対応付ける
+        let mut doc_comments = Vec::new();
+        let mut after_newline = Vec::new();
+        let mut newline_seen = false;
         let tokens: Vec<_> = tokens
             .into_iter()
-            .filter(|t| !matches!(t.token, Token::Newline))
+            .filter_map(|t| match t.token {
+                Token::Newline => {
+                    newline_seen = true;
+                    None
+                }
+                Token::DocComment(text) => {
+                    doc_comments.push(DocComment { text, span: t.span, attached: false });
+                    None
+                }
+                _ => {
+                    after_newline.push(std::mem::take(&mut newline_seen));
+                    Some(t)
+                }
+            })
             .collect();
-        Self { tokens, current: 0 }
+        Self {
+            tokens,
+            after_newline,
+            current: 0,
+            depth: 0,
+            chain: 0,
+            doc_comments,
+            expected: RefCell::default(),
+            contexts: Vec::new(),
+            recovered: Vec::new(),
+        }
+    }
+
+    /// 解析を続けられた構文エラー（ソース上の順）
+    ///
+    /// `parse` と `parse_script` は最初のエラーを返します。`parse_recovering` と
+    /// `parse_script_recovering` はエラーから回復したASTを返すため、この一覧で確認します。
+    pub fn recovered_errors(&self) -> &[ParseError] {
+        &self.recovered
+    }
+
+    /// どのアイテムにも付かなかったドキュメントコメントの位置
+    ///
+    /// 解析が終わった後に呼び出します。文や式の途中、ファイルの末尾、implブロックの直前の
+    /// ドキュメントコメントが該当します。
+    pub fn misplaced_doc_comments(&self) -> Vec<Span> {
+        self.doc_comments
+            .iter()
+            .filter(|doc| !doc.attached)
+            .map(|doc| doc.span.clone().into())
+            .collect()
     }
 
     /// 完全なプログラムを解析
+    ///
+    /// 解析を続けられた構文エラーがあっても、最初のエラーを返します。
     pub fn parse(&mut self) -> ParseResult<Program> {
+        let program = self.parse_recovering()?;
+        self.fail_on_recovered(program)
+    }
+
+    /// 完全なプログラムを解析し、解析を続けられた構文エラーがあってもASTを返す
+    ///
+    /// 続けられたエラーは `recovered_errors` で取得します。
+    pub fn parse_recovering(&mut self) -> ParseResult<Program> {
         // パッケージ宣言を解析
         let package = self.parse_package_decl()?;
 
@@ -33,30 +145,168 @@ impl Parser {
         // トップレベルアイテムを解析
         let mut items = Vec::new();
         while !self.is_at_end() {
-            items.push(self.parse_item()?);
+            items.extend(self.parse_items()?);
         }
 
-        let span = if let Some(first) = self.tokens.first() {
-            if let Some(last) = self.tokens.last() {
-                Span::new(first.span.start, last.span.end)
-            } else {
-                Span::new(first.span.start, first.span.end)
-            }
+        Ok(Program {
+            package,
+            imports,
+            items,
+            span: self.program_span(),
+        })
+    }
+
+    /// スクリプトとして解析
+    ///
+    /// package宣言（省略時は `package main`）とインポートの後に、文とアイテムを任意の順で書けます。
+    /// トップレベルの文は書かれた順に暗黙のmain関数の本体になり、アイテムはその外に出します。
+    /// 文の位置情報はソースコードのままなので、診断は元の位置を指します。
+    pub fn parse_script(&mut self) -> ParseResult<Program> {
+        let program = self.parse_script_recovering()?;
+        self.fail_on_recovered(program)
+    }
+
+    /// スクリプトとして解析し、解析を続けられた構文エラーがあってもASTを返す（`parse_recovering` を参照）
+    pub fn parse_script_recovering(&mut self) -> ParseResult<Program> {
+        let package = if self.check(&Token::Package) {
+            self.parse_package_decl()?
         } else {
-            Span::dummy()
+            PackageDecl {
+                name: "main".to_string(),
+                no_prelude: false,
+                span: Span::dummy(),
+            }
         };
+        let imports = self.parse_imports()?;
+
+        let mut items = Vec::new();
+        let mut statements = Vec::new();
+        let mut body_span: Option<Span> = None;
+        while !self.is_at_end() {
+            if self.at_item_start() {
+                items.extend(self.parse_items()?);
+                continue;
+            }
+            let start = self.current_span().start;
+            statements.push(self.parse_statement_internal()?);
+            let end = self.span_from(start).end;
+            body_span = Some(match body_span {
+                Some(span) => Span::new(span.start, end),
+                None => Span::new(start, end),
+            });
+        }
+
+        if let Some(body_span) = body_span {
+            let explicit_main = items.iter().find_map(|item| match item {
+                Item::Function(func) if func.name == "main" => Some(func.span),
+                _ => None,
+            });
+            if let Some(span) = explicit_main {
+                return Err(self.error_at("A script with top-level statements cannot also define fn main".to_string(), span));
+            }
+            items.push(Item::Function(FunctionDecl {
+                is_public: false,
+                inline: InlineHint::None,
+                attributes: Vec::new(),
+                name: "main".to_string(),
+                type_params: Vec::new(),
+                params: Vec::new(),
+                return_type: None,
+                lives_clause: None,
+                body: Block { statements, span: body_span },
+                docs: Vec::new(),
+                span: body_span,
+            }));
+        }
 
         Ok(Program {
             package,
             imports,
             items,
-            span,
+            span: self.program_span(),
         })
     }
 
+    /// 解析を続けられた構文エラーがあれば、最初のエラーを返す
+    fn fail_on_recovered(&self, program: Program) -> ParseResult<Program> {
+        match self.recovered.first() {
+            Some(error) => Err(error.clone()),
+            None => Ok(program),
+        }
+    }
+
+    /// ファイルの先頭のpackage宣言（省略可）とインポートだけを解析
+    ///
+    /// 依存関係の走査用で、以降のアイテムや文は解析しないため、本体に構文エラーがあっても
+    /// インポートを取得できます。
+    pub fn parse_imports_only(&mut self) -> ParseResult<Vec<Import>> {
+        if self.check(&Token::Package) {
+            self.parse_package_decl()?;
+        }
+        self.parse_imports()
+    }
+
+    /// トークン列がpackage宣言もアイテムもなく文から始まるか（スクリプトの判定用）
+    pub fn starts_with_statement(&self) -> bool {
+        !self.is_at_end() && !self.check(&Token::Package) && !self.check(&Token::Import) && !self.at_item_start()
+    }
+
+    /// 現在のトークンがアイテムの開始か
+    fn at_item_start(&self) -> bool {
+        matches!(
+            self.current_token(),
+            Some(
+                Token::Hash
+                    | Token::Pub
+                    | Token::Inline
+                    | Token::Extern
+                    | Token::Type
+                    | Token::Struct
+                    | Token::Enum
+                    | Token::Fn
+                    | Token::Impl
+            )
+        )
+    }
+
+    /// 全トークンを覆うスパン
+    fn program_span(&self) -> Span {
+        if let Some(first) = self.tokens.first() {
+            if let Some(last) = self.tokens.last() {
+                Span::new(first.span.start, last.span.end)
+            } else {
+                Span::new(first.span.start, first.span.end)
+            }
+        } else {
+            Span::dummy()
+        }
+    }
+
 
     // ==================== ユーティリティメソッド ====================
 
+    /// 現在のトークンの直前のドキュメントコメントを取り出す
+    ///
+    /// 前のトークンと現在のトークンの間にあるものをすべて取り出すため、
+    /// ドキュメントコメントとアイテムの間に空行や通常のコメントがあっても構いません。
+    pub(super) fn take_doc_comments(&mut self) -> Vec<String> {
+        let from = match self.current.checked_sub(1).and_then(|i| self.tokens.get(i)) {
+            Some(previous) => previous.span.end,
+            None => 0,
+        };
+        let to = self.current_span().start;
+        let first = self.doc_comments.partition_point(|doc| doc.span.start < from);
+        self.doc_comments[first..]
+            .iter_mut()
+            .take_while(|doc| doc.span.end <= to)
+            .filter(|doc| !doc.attached)
+            .map(|doc| {
+                doc.attached = true;
+                doc.text.clone()
+            })
+            .collect()
+    }
+
     /// 現在のトークンを取得
     pub(super) fn current_token(&self) -> Option<&Token> {
         self.tokens.get(self.current).map(|t| &t.token)
@@ -92,6 +342,17 @@ impl Parser {
         Span::new(start, end)
     }
 
+    /// 現在の位置を記録する（`rewind` で戻る）
+    pub(super) fn checkpoint(&self) -> Checkpoint {
+        Checkpoint { position: self.current, recovered: self.recovered.len() }
+    }
+
+    /// 記録した位置に戻り、その後に続けた構文エラーを取り消す
+    pub(super) fn rewind(&mut self, checkpoint: Checkpoint) {
+        self.current = checkpoint.position;
+        self.recovered.truncate(checkpoint.recovered);
+    }
+
     /// 次のトークンに進む
     pub(super) fn advance(&mut self) {
         if !self.is_at_end() {
@@ -105,11 +366,31 @@ impl Parser {
     }
 
     /// 特定のトークンをチェック（進まない）
+    ///
+    /// 一致しなければ、現在の位置で受け付けるものとして記録します。
     pub(super) fn check(&self, token_type: &Token) -> bool {
-        if let Some(token) = self.current_token() {
-            std::mem::discriminant(token) == std::mem::discriminant(token_type)
-        } else {
-            false
+        let matched = self.at(token_type);
+        if !matched {
+            self.record_expected(describe_expected(token_type));
+        }
+        matched
+    }
+
+    /// 現在のトークンが特定の種類か（受け付けるものとして記録しない）
+    fn at(&self, token_type: &Token) -> bool {
+        self.current_token()
+            .is_some_and(|token| std::mem::discriminant(token) == std::mem::discriminant(token_type))
+    }
+
+    /// 現在の位置で受け付けるもの（`` `,` `` や `expression` など）を記録
+    pub(super) fn record_expected(&self, item: String) {
+        let mut expected = self.expected.borrow_mut();
+        if expected.position != self.current {
+            expected.position = self.current;
+            expected.items.clear();
+        }
+        if !expected.items.contains(&item) {
+            expected.items.push(item);
         }
     }
 
@@ -123,22 +404,23 @@ impl Parser {
         }
     }
 
-    /// 複数のトークンタイプのいずれかにマッチしたら進む
-    pub(super) fn match_tokens(&mut self, token_types: &[Token]) -> Option<Token> {
-        for token_type in token_types {
-            if self.check(token_type) {
-                let token = self.current_token()?.clone();
-                self.advance();
-                return Some(token);
-            }
+    /// 二項演算子のいずれかにマッチしたら進む
+    ///
+    /// 一致しなければ、個々の演算子ではなく `an operator` を受け付けるものとして記録します。
+    pub(super) fn match_operator(&mut self, operators: &[Token]) -> Option<Token> {
+        if !operators.iter().any(|operator| self.at(operator)) {
+            self.record_expected("an operator".to_string());
+            return None;
         }
-        None
+        let token = self.current_token()?.clone();
+        self.advance();
+        Some(token)
     }
 
     /// 特定のトークンを期待
     pub(super) fn expect(&mut self, token_type: Token) -> ParseResult<()> {
         // >> を > として扱う特殊処理
-        if token_type == Token::Gt && self.check(&Token::GtGt) {
+        if token_type == Token::Gt && self.at(&Token::GtGt) {
             // >>の一部として>を消費したことにする
             // TODO: より適切な実装が必要
             self.advance();
@@ -149,14 +431,53 @@ impl Parser {
             self.advance();
             Ok(())
         } else {
-            let found = self
-                .current_token()
-                .map(|t| format!("{:?}", t))
-                .unwrap_or_else(|| "EOF".to_string());
-            Err(self.error(format!("Expected {:?}, found {}", token_type, found)))
+            Err(self.expected_error())
         }
     }
 
+    /// 文の終わりのセミコロンを期待
+    ///
+    /// セミコロンがなくても、次のトークンが行の先頭にあって文を始められる場合は、
+    /// 書き忘れとしてエラーを記録し、セミコロンがあったものとして解析を続けます。
+    /// エラーは直前の式の終わりを指します。セミコロンのあるコードの解析は変わりません。
+    pub(super) fn expect_statement_end(&mut self) -> ParseResult<()> {
+        if self.match_token(&Token::Semicolon) {
+            return Ok(());
+        }
+        if self.at_line_start() && self.at_statement_start() {
+            // 直前の式の終わり（現在のトークンは行の先頭にあるため、前のトークンが必ずある）
+            let end = self.tokens[self.current - 1].span.end;
+            let error = self.error_at("Missing `;` after expression".to_string(), Span::new(end, end));
+            self.recovered.push(error);
+            return Ok(());
+        }
+        Err(self.expected_error())
+    }
+
+    /// 現在のトークンの直前に改行があるか
+    fn at_line_start(&self) -> bool {
+        self.after_newline.get(self.current).copied().unwrap_or(false)
+    }
+
+    /// 現在のトークンが文の先頭に来るトークンか
+    fn at_statement_start(&self) -> bool {
+        matches!(
+            self.current_token(),
+            Some(
+                Token::Let
+                    | Token::Return
+                    | Token::If
+                    | Token::While
+                    | Token::For
+                    | Token::Defer
+                    | Token::Match
+                    | Token::Fn
+                    | Token::Identifier(_)
+                    | Token::SelfValue
+            )
+        )
+    }
+
     /// 識別子を期待
     pub(super) fn expect_identifier(&mut self) -> ParseResult<String> {
         match self.current_token() {
@@ -165,7 +486,10 @@ impl Parser {
                 self.advance();
                 Ok(name)
             }
-            _ => Err(self.error("Expected identifier".to_string())),
+            _ => {
+                self.record_expected("identifier".to_string());
+                Err(self.expected_error())
+            }
         }
     }
     
@@ -182,37 +506,151 @@ impl Parser {
                 self.advance();
                 Ok(value)
             }
-            _ => Err(self.error("Expected string literal".to_string())),
+            _ => {
+                self.record_expected("string literal".to_string());
+                Err(self.expected_error())
+            }
         }
     }
 
-    /// エラーを作成
+    /// 入れ子を1段深くして解析
+    ///
+    /// 解析が失敗しても深さと連鎖の長さは元に戻るため、バックトラックした後も正しい値が保たれます。
+    pub(super) fn nested<T>(&mut self, parse: impl FnOnce(&mut Self) -> ParseResult<T>) -> ParseResult<T> {
+        let (saved_depth, saved_chain) = (self.depth, self.chain);
+        if self.depth >= MAX_NESTING_DEPTH {
+            return Err(self.error(format!("Nesting depth exceeds the limit of {}", MAX_NESTING_DEPTH)));
+        }
+        self.depth += 1;
+        let result = parse(self);
+        (self.depth, self.chain) = (saved_depth, saved_chain);
+        result
+    }
+
+    /// 連鎖を1つ延ばして解析
+    ///
+    /// `else if` の連なりのように、入れ子ではなく連鎖として数える再帰に使います。
+    pub(super) fn chained<T>(&mut self, parse: impl FnOnce(&mut Self) -> ParseResult<T>) -> ParseResult<T> {
+        let saved_chain = self.chain;
+        self.extend_chain()?;
+        let result = parse(self);
+        self.chain = saved_chain;
+        result
+    }
+
+    /// 連鎖の長さを1つ増やす
+    ///
+    /// 二項演算や後置演算の連鎖のように、再帰せずにASTが深くなる箇所で呼び出します。
+    /// 増やした長さは、囲んでいる `nested` を抜けるときに元に戻ります。
+    pub(super) fn extend_chain(&mut self) -> ParseResult<()> {
+        if self.chain >= MAX_CHAIN_LENGTH {
+            return Err(self.error(format!("Chain length exceeds the limit of {}", MAX_CHAIN_LENGTH)));
+        }
+        self.chain += 1;
+        Ok(())
+    }
+
+    /// 構文要素の解析中であることを記録して解析
+    ///
+    /// 中で起きた構文エラーには、最も内側の構文要素の説明と開始位置（現在のトークン）が付きます。
+    pub(super) fn within<T>(
+        &mut self,
+        description: String,
+        parse: impl FnOnce(&mut Self) -> ParseResult<T>,
+    ) -> ParseResult<T> {
+        let span = self.current_span().into();
+        self.contexts.push(ParseContext { description, span });
+        let result = parse(self);
+        self.contexts.pop();
+        result
+    }
+
+    /// 現在のトークンを指すエラーを作成
     pub(super) fn error(&self, message: String) -> ParseError {
-        let span = self.current_span();
+        self.error_at(message, self.found_span())
+    }
+
+    /// 指定した位置を指すエラーを作成（解析中の構文要素を付ける）
+    pub(super) fn error_at(&self, message: String, span: Span) -> ParseError {
         ParserError::SyntaxError {
             message,
-            span: span.into(),
+            span,
+            context: self.contexts.last().cloned(),
         }
     }
 
+    /// 現在の位置で受け付けるものと、実際に見つかったトークンを示すエラーを作成
+    ///
+    /// 受け付けるものが複数あれば ``Expected one of `,`, `}`, found `fn` `` のようにすべて示します。
+    pub(super) fn expected_error(&self) -> ParseError {
+        let expected = self.expected.borrow();
+        let mut items: Vec<&str> = if expected.position == self.current {
+            expected.items.iter().map(String::as_str).collect()
+        } else {
+            Vec::new()
+        };
+        items.sort_unstable();
+        let found = self.found_description();
+        let message = match items.as_slice() {
+            [] => format!("Unexpected {}", found),
+            [item] => format!("Expected {}, found {}", item, found),
+            items => format!("Expected one of {}, found {}", items.join(", "), found),
+        };
+        self.error(message)
+    }
+
     /// 予期しないトークンエラーを作成
     #[allow(dead_code)]
     pub(super) fn unexpected_token(&self) -> ParseError {
-        let token = self
-            .current_token()
-            .map(|t| format!("{:?}", t))
-            .unwrap_or_else(|| "EOF".to_string());
-        self.error(format!("Unexpected token: {}", token))
+        self.error(format!("Unexpected token: {}", self.found_description()))
+    }
+
+    /// 見つかったトークンの説明（`` `fn` `` か `end of file`）
+    fn found_description(&self) -> String {
+        match self.current_token() {
+            Some(token) => format!("`{}`", token),
+            None => "end of file".to_string(),
+        }
+    }
+
+    /// 見つかったトークンの位置（終端では最後のトークンの直後）
+    fn found_span(&self) -> Span {
+        match (self.current_token_with_pos(), self.tokens.last()) {
+            (Some(token), _) => token.span.clone().into(),
+            (None, Some(last)) => Span::new(last.span.end, last.span.end),
+            (None, None) => Span::dummy(),
+        }
     }
 
     /// テンプレート文字列の補間式をパース
-    pub(super) fn parse_template_string_interpolation(&mut self, expr_str: &str) -> ParseResult<Expression> {
+    ///
+    /// `offset` は補間式のソースコード上の開始位置です。トークンの位置をずらし、
+    /// 補間式の各ノードのスパンがテンプレート文字列の中の位置を指すようにします。
+    pub(super) fn parse_template_string_interpolation(&mut self, expr_str: &str, offset: usize) -> ParseResult<Expression> {
         // 補間式のトークン化
         let lexer = crate::lexer::Lexer::new(expr_str);
-        let tokens = lexer.collect_tokens();
+        let mut tokens = lexer.collect_tokens();
+        for token in &mut tokens {
+            token.span = token.span.start + offset..token.span.end + offset;
+        }
         
         // 新しいパーサーで式を解析
         let mut parser = Parser::new(tokens);
+        parser.depth = self.depth;
+        parser.chain = self.chain;
         parser.parse_expression_internal()
     }
-}
\ No newline at end of file
+}
+
+/// 受け付けるトークンの種類の説明（記号とキーワードはそのまま、値を持つトークンは種類の名前）
+fn describe_expected(token: &Token) -> String {
+    match token {
+        Token::Identifier(_) => "identifier".to_string(),
+        Token::Integer(_) => "integer literal".to_string(),
+        Token::Float(_) => "float literal".to_string(),
+        Token::String(_) => "string literal".to_string(),
+        Token::Bytes(_) => "byte string literal".to_string(),
+        Token::TemplateString(_) => "template string".to_string(),
+        token => format!("`{}`", token),
+    }
+}
@@ -8,7 +8,32 @@ use super::{ParseResult, Parser};
 impl Parser {
     /// パターンを解析
     pub(super) fn parse_pattern(&mut self, is_mut: bool) -> ParseResult<Pattern> {
+        let first = self.parse_single_pattern(is_mut)?;
+        if !self.check(&Token::Or) {
+            return Ok(first);
+        }
+
+        // `|` で区切った選択肢はorパターンにする
+        let mut alternatives = vec![first];
+        while self.match_token(&Token::Or) {
+            alternatives.push(self.parse_single_pattern(is_mut)?);
+        }
+        Ok(Pattern::Or(alternatives))
+    }
+
+    /// orパターンの選択肢になる1つのパターンを解析（`@` は `|` より強く結合する）
+    fn parse_single_pattern(&mut self, is_mut: bool) -> ParseResult<Pattern> {
         match self.current_token() {
+            Some(Token::Mut) => {
+                // 要素ごとの可変な束縛: let (mut a, b) = ...
+                self.advance();
+                let name = self.expect_identifier()?;
+                // `mut _` も `let mut _ = ...` と同じく何も束縛しない
+                if name == "_" {
+                    return Ok(Pattern::Wildcard);
+                }
+                Ok(Pattern::Identifier(name, true))
+            }
             Some(Token::Identifier(name)) => {
                 let name = name.clone();
                 self.advance();
@@ -18,6 +43,12 @@ impl Parser {
                     return Ok(Pattern::Wildcard);
                 }
                 
+                // 一致した値に名前を付けるパターン: n @ 1..=9, all @ (A | B) など
+                if self.match_token(&Token::At) {
+                    let pattern = self.nested(|parser| parser.parse_single_pattern(false))?;
+                    return Ok(Pattern::Binding(name, Box::new(pattern)));
+                }
+                
                 // パス（Enum::Variant など）をチェック
                 if self.check(&Token::ColonColon) {
                     return self.parse_path_pattern(name);
@@ -32,7 +63,7 @@ impl Parser {
                     let mut patterns = Vec::new();
                     
                     while !self.check(&Token::RightParen) && !self.is_at_end() {
-                        let pattern = self.parse_pattern(false)?;
+                        let pattern = self.nested(|parser| parser.parse_pattern(false))?;
                         patterns.push(pattern);
                         
                         if !self.check(&Token::RightParen) {
@@ -54,12 +85,15 @@ impl Parser {
                     Ok(Pattern::Identifier(name, is_mut))
                 }
             }
-            Some(Token::Integer(n)) => {
+            Some(Token::Integer((n, _))) => {
                 let value = *n as i64;
                 self.advance();
+                if self.check(&Token::DotDotEq) || self.check(&Token::DotDot) {
+                    return self.parse_range_pattern(value);
+                }
                 Ok(Pattern::Literal(LiteralPattern::Integer(value)))
             }
-            Some(Token::Float(f)) => {
+            Some(Token::Float((f, _))) => {
                 let value = *f;
                 self.advance();
                 Ok(Pattern::Literal(LiteralPattern::Float(value)))
@@ -82,7 +116,7 @@ impl Parser {
                 let mut patterns = Vec::new();
                 
                 while !self.check(&Token::RightParen) && !self.is_at_end() {
-                    let pattern = self.parse_pattern(false)?;
+                    let pattern = self.nested(|parser| parser.parse_pattern(false))?;
                     patterns.push(pattern);
                     
                     if !self.check(&Token::RightParen) {
@@ -91,10 +125,40 @@ impl Parser {
                 }
                 
                 self.expect(Token::RightParen)?;
+                
+                // 括弧で囲んだorパターンは要素が1つのタプルではなく、選択肢のまとまりとする
+                if let [Pattern::Or(_)] = patterns.as_slice() {
+                    return Ok(patterns.remove(0));
+                }
                 Ok(Pattern::Tuple(patterns))
             }
-            _ => Err(self.error("Expected pattern".to_string())),
+            _ => {
+                self.record_expected("pattern".to_string());
+                Err(self.expected_error())
+            }
+        }
+    }
+
+    /// 整数の範囲パターン `start..=end` / `start..end` を解析（開始値は解析済み）
+    fn parse_range_pattern(&mut self, start: i64) -> ParseResult<Pattern> {
+        let inclusive = self.match_token(&Token::DotDotEq);
+        if !inclusive {
+            self.expect(Token::DotDot)?;
+        }
+        let end = match self.current_token() {
+            Some(Token::Integer((n, _))) => *n as i64,
+            _ => return Err(self.error("Expected integer at the end of range pattern".to_string())),
+        };
+        self.advance();
+        if start > end || (start == end && !inclusive) {
+            return Err(self.error(format!(
+                "Range pattern {}{}{} is empty",
+                start,
+                if inclusive { "..=" } else { ".." },
+                end
+            )));
         }
+        Ok(Pattern::Range { start, end, inclusive })
     }
 
     /// 構造体パターンを解析
@@ -103,13 +167,15 @@ impl Parser {
         let mut fields = Vec::new();
         
         while !self.check(&Token::RightBrace) && !self.is_at_end() {
+            // `mut x` はフィールド名と同じ名前の可変な変数にバインド
+            let is_mut = self.match_token(&Token::Mut);
             let field_name = self.expect_identifier()?;
             
-            let pattern = if self.match_token(&Token::Colon) {
-                self.parse_pattern(false)?
+            let pattern = if !is_mut && self.match_token(&Token::Colon) {
+                self.nested(|parser| parser.parse_pattern(false))?
             } else {
                 // フィールド名と同じ名前の変数にバインド
-                Pattern::Identifier(field_name.clone(), false)
+                Pattern::Identifier(field_name.clone(), is_mut)
             };
             
             fields.push((field_name, pattern));
@@ -146,7 +212,7 @@ impl Parser {
                     let field_name = self.expect_identifier()?;
                     
                     let pattern = if self.match_token(&Token::Colon) {
-                        self.parse_pattern(false)?
+                        self.nested(|parser| parser.parse_pattern(false))?
                     } else {
                         // フィールド名と同じ名前の変数にバインド
                         Pattern::Identifier(field_name.clone(), false)
@@ -173,7 +239,7 @@ impl Parser {
                 let mut patterns = Vec::new();
                 
                 while !self.check(&Token::RightParen) && !self.is_at_end() {
-                    let pattern = self.parse_pattern(false)?;
+                    let pattern = self.nested(|parser| parser.parse_pattern(false))?;
                     patterns.push(pattern);
                     
                     if !self.check(&Token::RightParen) {
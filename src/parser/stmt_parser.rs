@@ -14,6 +14,8 @@ impl Parser {
             Some(Token::If) => Ok(Statement::If(self.parse_if_statement()?)),
             Some(Token::While) => Ok(Statement::While(self.parse_while_statement()?)),
             Some(Token::For) => Ok(Statement::For(self.parse_for_statement()?)),
+            Some(Token::Defer) => Ok(Statement::Defer(self.parse_defer_statement()?)),
+            Some(Token::Fn) => Ok(Statement::Function(self.parse_local_function_decl()?)),
             Some(Token::LeftBrace) => Ok(Statement::Block(self.parse_block()?)),
             _ => {
                 // 式文または代入文として解析を試みる
@@ -23,7 +25,7 @@ impl Parser {
                 // 代入かどうかチェック
                 if self.match_token(&Token::Assign) {
                     let value = self.parse_expression_internal()?;
-                    self.expect(Token::Semicolon)?;
+                    self.expect_statement_end()?;
                     let span = self.span_from(start);
                     Ok(Statement::Assignment(AssignStatement {
                         target: expr,
@@ -31,11 +33,11 @@ impl Parser {
                         span,
                     }))
                 } else {
-                    // セミコロンがあるか、次がブロック終了なら省略可能
+                    // セミコロンがあるか、次がブロック終了（スクリプトでは入力の終わり）なら省略可能
                     if self.check(&Token::Semicolon) {
                         self.advance();
-                    } else if !self.check(&Token::RightBrace) {
-                        return Err(self.error("Expected semicolon".to_string()));
+                    } else if !self.check(&Token::RightBrace) && !self.is_at_end() {
+                        self.expect_statement_end()?;
                     }
                     Ok(Statement::Expression(expr))
                 }
@@ -46,31 +48,33 @@ impl Parser {
     /// let文を解析
     pub(super) fn parse_let_statement(&mut self) -> ParseResult<LetStatement> {
         let start = self.current_span().start;
-        self.expect(Token::Let)?;
+        self.within("`let` statement".to_string(), |parser| {
+            parser.expect(Token::Let)?;
 
-        let is_mut = self.match_token(&Token::Mut);
-        let pattern = self.parse_pattern(is_mut)?;
+            let is_mut = parser.match_token(&Token::Mut);
+            let pattern = parser.parse_pattern(is_mut)?;
 
-        let ty = if self.match_token(&Token::Colon) {
-            Some(self.parse_type()?)
-        } else {
-            None
-        };
-
-        let init = if self.match_token(&Token::Assign) {
-            Some(self.parse_expression_internal()?)
-        } else {
-            None
-        };
-
-        self.expect(Token::Semicolon)?;
-        let span = self.span_from(start);
+            let ty = if parser.match_token(&Token::Colon) {
+                Some(parser.parse_type()?)
+            } else {
+                None
+            };
 
-        Ok(LetStatement {
-            pattern,
-            ty,
-            init,
-            span,
+            let init = if parser.match_token(&Token::Assign) {
+                Some(parser.parse_expression_internal()?)
+            } else {
+                None
+            };
+
+            parser.expect_statement_end()?;
+            let span = parser.span_from(start);
+
+            Ok(LetStatement {
+                pattern,
+                ty,
+                init,
+                span,
+            })
         })
     }
 
@@ -85,7 +89,7 @@ impl Parser {
             Some(self.parse_expression_internal()?)
         };
 
-        self.expect(Token::Semicolon)?;
+        self.expect_statement_end()?;
         let span = self.span_from(start);
 
         Ok(ReturnStatement { value, span })
@@ -105,7 +109,7 @@ impl Parser {
 
         let else_branch = if self.match_token(&Token::Else) {
             if self.check(&Token::If) {
-                Some(ElseBranch::If(Box::new(self.parse_if_statement()?)))
+                Some(ElseBranch::If(Box::new(self.chained(Self::parse_if_statement)?)))
             } else {
                 Some(ElseBranch::Block(self.parse_block()?))
             }
@@ -181,11 +185,22 @@ impl Parser {
         };
         self.expect(Token::Semicolon)?;
 
-        // 更新部
+        // 更新部（`i = i + 1` のような代入も書ける）
         let update = if self.check(&Token::LeftBrace) {
             None
         } else {
-            Some(self.parse_expression_internal()?)
+            let expr = self.parse_expression_internal()?;
+            if self.match_token(&Token::Assign) {
+                let value = self.parse_expression_internal()?;
+                let span = Span::new(expr.span().start, value.span().end);
+                Some(Expression::Assignment(AssignmentExpr {
+                    target: Box::new(expr),
+                    value: Box::new(value),
+                    span,
+                }))
+            } else {
+                Some(expr)
+            }
         };
 
         // 本体
@@ -202,6 +217,17 @@ impl Parser {
         })
     }
 
+    /// defer文を解析
+    fn parse_defer_statement(&mut self) -> ParseResult<DeferStatement> {
+        let start = self.current_span().start;
+        self.expect(Token::Defer)?;
+
+        let body = self.parse_block()?;
+        let span = self.span_from(start);
+
+        Ok(DeferStatement { body, span })
+    }
+
     /// ブロックを解析
     pub(super) fn parse_block(&mut self) -> ParseResult<Block> {
         let start = self.current_span().start;
@@ -210,7 +236,7 @@ impl Parser {
         let mut statements = Vec::new();
 
         while !self.check(&Token::RightBrace) && !self.is_at_end() {
-            let stmt = self.parse_statement_internal()?;
+            let stmt = self.nested(Self::parse_statement_internal)?;
             statements.push(stmt);
         }
 
@@ -220,6 +246,70 @@ impl Parser {
         Ok(Block { statements, span })
     }
 
+    /// 関数本体を解析
+    ///
+    /// 戻り値型を持つ関数では、セミコロンのない最後の式を暗黙の戻り値として
+    /// `return` 文に変換します。セミコロンを付けた場合は通常の式文になります。
+    pub(super) fn parse_function_body(&mut self, returns_value: bool) -> ParseResult<Block> {
+        let start = self.current_span().start;
+        self.expect(Token::LeftBrace)?;
+
+        let mut statements = Vec::new();
+
+        while !self.check(&Token::RightBrace) && !self.is_at_end() {
+            if returns_value {
+                if let Some(value) = self.try_parse_tail_expression() {
+                    let span = value.span();
+                    statements.push(Statement::Return(ReturnStatement {
+                        value: Some(value),
+                        span,
+                    }));
+                    break;
+                }
+            }
+            statements.push(self.nested(Self::parse_statement_internal)?);
+        }
+
+        self.expect(Token::RightBrace)?;
+        let span = self.span_from(start);
+
+        Ok(Block { statements, span })
+    }
+
+    /// ブロック末尾の値を持つ式を解析
+    ///
+    /// 末尾の式でない場合（後続の文がある、値を持たないif式など）は
+    /// 位置を戻して `None` を返し、文として解析し直させます。
+    fn try_parse_tail_expression(&mut self) -> Option<Expression> {
+        if !self.is_expression_start() {
+            return None;
+        }
+
+        let checkpoint = self.checkpoint();
+        match self.parse_expression_internal() {
+            Ok(expr) if self.check(&Token::RightBrace) && Self::produces_value(&expr) => Some(expr),
+            _ => {
+                self.rewind(checkpoint); // 位置を戻す
+                None
+            }
+        }
+    }
+
+    /// 式が値を生成するかどうか
+    ///
+    /// すべての分岐が末尾の式を持つif式とブロック式のみが値を持ちます。
+    /// 分岐が `return` で終わるif文は、暗黙の戻り値ではなく文として扱います。
+    fn produces_value(expr: &Expression) -> bool {
+        match expr {
+            Expression::Block(block) => block.last_expr.is_some(),
+            Expression::If(if_expr) => {
+                Self::produces_value(&if_expr.then_branch)
+                    && if_expr.else_branch.as_deref().is_some_and(Self::produces_value)
+            }
+            _ => true,
+        }
+    }
+
     /// ブロック式を解析（最後の式を戻り値として扱う）
     pub(super) fn parse_block_expression(&mut self) -> ParseResult<(Vec<Statement>, Option<Box<Expression>>)> {
         self.expect(Token::LeftBrace)?;
@@ -240,12 +330,13 @@ impl Parser {
                     // ブロックの最後の式（セミコロンなし）は戻り値として扱う
                     last_expr = Some(Box::new(expr));
                 } else {
-                    // セミコロンが必要だがない場合はエラー
-                    return Err(self.error("Expected semicolon or end of block".to_string()));
+                    // セミコロンが必要（行をまたいで書き忘れた場合は記録して続ける）
+                    self.expect_statement_end()?;
+                    statements.push(Statement::Expression(expr));
                 }
             } else {
                 // 文として解析
-                let stmt = self.parse_statement_internal()?;
+                let stmt = self.nested(Self::parse_statement_internal)?;
                 statements.push(stmt);
             }
         }
@@ -258,7 +349,8 @@ impl Parser {
     /// 式の開始トークンかどうかをチェック
     fn is_expression_start(&self) -> bool {
         match self.current_token() {
-            Some(Token::Let) | Some(Token::Return) | Some(Token::While) | Some(Token::For) => false,
+            Some(Token::Let) | Some(Token::Return) | Some(Token::While) | Some(Token::For)
+            | Some(Token::Defer) | Some(Token::Fn) => false,
             Some(Token::If) => true, // if式は式として扱える
             Some(Token::LeftBrace) => true, // ブロック式
             _ => true, // その他は式として扱う
@@ -95,14 +95,14 @@ impl Parser {
             Some(Token::Ampersand) => {
                 self.advance();
                 let is_mut = self.match_token(&Token::Mut);
-                let inner_type = self.parse_type()?;
+                let inner_type = self.nested(Self::parse_type)?;
                 Ok(Type::Reference(Box::new(inner_type), is_mut))
             }
 
             // 配列型
             Some(Token::LeftBracket) => {
                 self.advance();
-                let element_type = self.parse_type()?;
+                let element_type = self.nested(Self::parse_type)?;
                 self.expect(Token::RightBracket)?;
                 Ok(Type::Array(Box::new(element_type)))
             }
@@ -113,7 +113,7 @@ impl Parser {
                 let mut types = Vec::new();
 
                 while !self.check(&Token::RightParen) && !self.is_at_end() {
-                    types.push(self.parse_type()?);
+                    types.push(self.nested(Self::parse_type)?);
                     if !self.check(&Token::RightParen) {
                         self.expect(Token::Comma)?;
                     }
@@ -130,7 +130,7 @@ impl Parser {
                 
                 let mut params = Vec::new();
                 while !self.check(&Token::RightParen) && !self.is_at_end() {
-                    params.push(self.parse_type()?);
+                    params.push(self.nested(Self::parse_type)?);
                     if !self.check(&Token::RightParen) {
                         self.expect(Token::Comma)?;
                     }
@@ -139,7 +139,7 @@ impl Parser {
                 self.expect(Token::RightParen)?;
                 
                 let return_type = if self.match_token(&Token::Arrow) {
-                    Box::new(self.parse_type()?)
+                    Box::new(self.nested(Self::parse_type)?)
                 } else {
                     Box::new(Type::Void)
                 };
@@ -169,7 +169,10 @@ impl Parser {
                 }
             }
 
-            _ => Err(self.error("Expected type".to_string())),
+            _ => {
+                self.record_expected("type".to_string());
+                Err(self.expected_error())
+            }
         }
     }
 
@@ -202,7 +205,7 @@ impl Parser {
         self.expect(Token::Lt)?;
 
         while !self.check(&Token::Gt) && !self.check(&Token::GtGt) && !self.is_at_end() {
-            args.push(self.parse_type()?);
+            args.push(self.nested(Self::parse_type)?);
 
             if !self.check(&Token::Gt) && !self.check(&Token::GtGt) {
                 self.expect(Token::Comma)?;
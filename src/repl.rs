@@ -0,0 +1,619 @@
+//! 対話的に定義と式を評価するREPL
+//!
+//! セッションは入力ごとに新しいLLVMモジュールを作り、JITでコンパイルします。関数は名前の登録簿で
+//! 管理し、別のモジュールの関数の呼び出しは本体のない宣言として生成して、実行エンジンの
+//! グローバルマッピングで登録簿のアドレスに結び付けます。古いモジュールのシンボル解決には頼りません。
+//!
+//! - 定義の時点で未定義の関数を呼び出す関数はコンパイルを保留し、待っている名前を記録します。
+//!   定義を受け付けるたびに保留中の関数を検査し直し、呼び出し先がそろった時点でコンパイルします。
+//!   エラーになるのは、保留中の関数を実際に呼び出したときだけです。
+//! - 関数を再定義すると、登録簿はその名前を新しい定義のアドレスに結び付け直します。呼び出し元の
+//!   関数も新しいモジュールで再コンパイルするため、以降の呼び出しはすべて最新の定義に届きます。
+//!   古い定義のコードを持つ実行エンジンは、セッションの終わりまで解放しません。
+//!
+//! バッチコンパイルは1つのプログラムの定義を宣言と本体の2回の走査で解決しますが、REPLでは
+//! 定義が1つずつ届くため、この解決を入力のたびに繰り返します。型の定義と外部関数の宣言は
+//! すべてのモジュールに含め、再定義した場合はすべての関数を再コンパイルします。
+//! ジェネリックな定義・メソッド・インポートはまだ扱いません。
+
+use std::collections::{HashMap, HashSet};
+use std::ffi::{c_char, CStr};
+
+use inkwell::context::Context;
+use inkwell::execution_engine::ExecutionEngine;
+use inkwell::OptimizationLevel;
+
+use crate::analyzer::{CallGraph, SemanticAnalyzer, TypeTable};
+use crate::ast::*;
+use crate::codegen::CodeGenerator;
+use crate::error::{AnalyzerError, CodegenError, YuniError, YuniResult};
+use crate::lexer::{Lexer, Token};
+use crate::parser::Parser;
+use crate::runtime;
+
+/// 1つの入力を評価した結果
+#[derive(Debug, Default)]
+pub struct ReplOutput {
+    /// 定義した関数と型の名前（入力の順）
+    pub defined: Vec<String>,
+    /// 未定義の関数を呼び出すためにまだ呼び出せない関数と、待っている関数の名前
+    pub pending: Vec<(String, Vec<String>)>,
+    /// 入力の最後の式の値（値を表示できない式と文で終わる入力はNone）
+    pub value: Option<String>,
+}
+
+/// 登録簿での関数の状態
+#[derive(Debug, Clone, PartialEq)]
+enum Binding {
+    /// コンパイル済みで、JITでコンパイルしたコードのアドレスに結び付いている
+    Compiled(usize),
+    /// 未定義の関数（`Vec` はその名前）を呼び出すため、コンパイルを保留している
+    Pending(Vec<String>),
+    /// 定義または呼び出し先が変わったため、コンパイルし直す必要がある
+    Stale,
+}
+
+/// 登録簿の関数
+#[derive(Debug, Clone)]
+struct ReplFunction {
+    decl: FunctionDecl,
+    binding: Binding,
+}
+
+/// REPLのセッション
+///
+/// 受け付けた入力をつなげたテキストを保持し、各入力の位置をこのテキスト上の位置にします。
+/// 式の型は位置で記録されるため、入力をまたいでも位置が重ならないようにしています。
+pub struct ReplSession<'ctx> {
+    context: &'ctx Context,
+    /// これまでの入力（診断の位置はこのテキストを指す）
+    source: String,
+    /// 型の定義と外部関数の宣言
+    declarations: Vec<Item>,
+    /// 関数の登録簿（最初に定義された順）
+    functions: Vec<ReplFunction>,
+    /// モジュールごとの実行エンジン（古い定義のコードを有効に保つため解放しない）
+    engines: Vec<ExecutionEngine<'ctx>>,
+    /// 式と文を包む関数の通し番号
+    evaluations: usize,
+}
+
+impl<'ctx> ReplSession<'ctx> {
+    /// 新しいセッションを作成
+    pub fn new(context: &'ctx Context) -> Self {
+        Self {
+            context,
+            source: String::new(),
+            declarations: Vec::new(),
+            functions: Vec::new(),
+            engines: Vec::new(),
+            evaluations: 0,
+        }
+    }
+
+    /// これまでの入力をつなげたテキスト
+    ///
+    /// `eval` が返したエラーの位置も、このテキスト上の位置です。
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// 入力を評価する
+    ///
+    /// 入力はスクリプトと同じく、アイテムの定義と文を並べたものです。定義を登録してから文を実行し、
+    /// 最後の文が値を持つ式であればその値を返します。エラーになった入力の定義は登録しません。
+    pub fn eval(&mut self, input: &str) -> YuniResult<ReplOutput> {
+        let start = self.source.len();
+        self.source.push_str(input);
+        self.source.push('\n');
+        let mut program = parse_input(&self.source, start)?;
+        if let Some(import) = program.imports.first() {
+            return Err(unsupported("インポート", import.span));
+        }
+        let statements = take_statements(&mut program.items);
+
+        let declarations = self.declarations.clone();
+        let functions = self.functions.clone();
+        let result = self.define(program.items).and_then(|mut output| {
+            if let Some(body) = statements {
+                output.value = self.evaluate(body)?;
+            }
+            Ok(output)
+        });
+        if result.is_err() {
+            self.declarations = declarations;
+            self.functions = functions;
+        }
+        result
+    }
+
+    /// アイテムを登録し、コンパイルできる関数をコンパイルする
+    fn define(&mut self, items: Vec<Item>) -> YuniResult<ReplOutput> {
+        let mut output = ReplOutput::default();
+        let mut changed = HashSet::new();
+        let mut redeclared = false;
+        for item in items {
+            match item {
+                Item::Function(func) => {
+                    if !func.type_params.is_empty() {
+                        return Err(unsupported("ジェネリック関数", func.span));
+                    }
+                    output.defined.push(func.name.clone());
+                    changed.insert(func.name.clone());
+                    match self.functions.iter_mut().find(|entry| entry.decl.name == func.name) {
+                        Some(entry) => entry.decl = func,
+                        None => self.functions.push(ReplFunction { decl: func, binding: Binding::Stale }),
+                    }
+                }
+                Item::Method(method) => return Err(unsupported("メソッド", method.span)),
+                declaration => {
+                    if let Some(span) = generic_declaration(&declaration) {
+                        return Err(unsupported("ジェネリックな型", span));
+                    }
+                    let name = declaration_name(&declaration).to_string();
+                    output.defined.push(name.clone());
+                    match self.declarations.iter_mut().find(|existing| declaration_name(existing) == name) {
+                        Some(existing) => {
+                            *existing = declaration;
+                            redeclared = true;
+                        }
+                        None => self.declarations.push(declaration),
+                    }
+                }
+            }
+        }
+
+        // 型を再定義した場合はすべての関数を、関数を再定義した場合はその呼び出し元をコンパイルし直す
+        // 保留中の関数は、呼び出し先がそろったかを検査し直す
+        if redeclared {
+            for entry in &mut self.functions {
+                entry.binding = Binding::Stale;
+            }
+        } else {
+            self.mark_callers_stale(changed);
+        }
+        self.compile_stale()?;
+
+        output.pending = self
+            .functions
+            .iter()
+            .filter(|entry| matches!(entry.binding, Binding::Pending(_)))
+            .map(|entry| (entry.decl.name.clone(), self.undefined_callees(&entry.decl.name)))
+            .collect();
+        Ok(output)
+    }
+
+    /// 関数 `changed` と、それを直接・間接に呼び出す関数、保留中の関数をコンパイルし直す対象にする
+    fn mark_callers_stale(&mut self, mut changed: HashSet<String>) {
+        let program = self.program(self.functions.iter().map(|entry| entry.decl.clone()));
+        let graph = CallGraph::build(&program, &TypeTable::new());
+        loop {
+            let callers: Vec<String> = graph
+                .edges
+                .iter()
+                .filter(|edge| changed.contains(&edge.callee) && !changed.contains(&edge.caller))
+                .map(|edge| edge.caller.clone())
+                .collect();
+            if callers.is_empty() {
+                break;
+            }
+            changed.extend(callers);
+        }
+        for entry in &mut self.functions {
+            if changed.contains(&entry.decl.name) || matches!(entry.binding, Binding::Pending(_)) {
+                entry.binding = Binding::Stale;
+            }
+        }
+    }
+
+    /// コンパイルし直す関数を解析し、呼び出し先がそろった関数をまとめてコンパイルする
+    ///
+    /// 未定義の関数を呼び出す関数を保留にして、残りの関数でエラーがなくなるまで解析を繰り返します。
+    /// 保留にした関数を呼び出す関数も、次の解析で保留になります。
+    fn compile_stale(&mut self) -> YuniResult<()> {
+        loop {
+            if !self.functions.iter().any(|entry| entry.binding == Binding::Stale) {
+                return Ok(());
+            }
+            let program = self.program(
+                self.functions
+                    .iter()
+                    .filter(|entry| !matches!(entry.binding, Binding::Pending(_)))
+                    .map(|entry| entry.decl.clone()),
+            );
+            let mut analyzer = SemanticAnalyzer::new();
+            if analyzer.analyze(&program).is_ok() {
+                return self.compile(&program, analyzer.type_table);
+            }
+
+            let mut missing: HashMap<usize, Vec<String>> = HashMap::new();
+            for error in &analyzer.errors {
+                if let AnalyzerError::UndefinedFunction { name, span } = error {
+                    if let Some(index) = self.function_at(*span) {
+                        missing.entry(index).or_default().push(name.clone());
+                    }
+                }
+            }
+            if missing.is_empty() {
+                return Err(YuniError::Analyzer(analyzer.errors.remove(0)));
+            }
+            for (index, names) in missing {
+                self.functions[index].binding = Binding::Pending(names);
+            }
+        }
+    }
+
+    /// 解析を終えたプログラムのうち、コンパイルし直す関数を新しいモジュールでコンパイルする
+    fn compile(&mut self, program: &Program, type_table: TypeTable) -> YuniResult<()> {
+        let engine = self.jit(program, type_table)?;
+        for entry in &mut self.functions {
+            if entry.binding == Binding::Stale {
+                let address = engine.get_function_address(&entry.decl.name).map_err(|e| {
+                    YuniError::Codegen(CodegenError::Internal {
+                        message: format!("Function {} was not compiled: {}", entry.decl.name, e),
+                    })
+                })?;
+                entry.binding = Binding::Compiled(address);
+            }
+        }
+        self.engines.push(engine);
+        Ok(())
+    }
+
+    /// 入力の文を関数に包んで実行し、最後の式の値を文字列にする
+    fn evaluate(&mut self, body: Block) -> YuniResult<Option<String>> {
+        self.evaluations += 1;
+        let mut wrapper = FunctionDecl {
+            is_public: false,
+            inline: InlineHint::None,
+            attributes: Vec::new(),
+            name: format!("__repl_eval_{}", self.evaluations),
+            type_params: Vec::new(),
+            params: Vec::new(),
+            return_type: None,
+            lives_clause: None,
+            span: body.span,
+            body,
+            docs: Vec::new(),
+        };
+
+        // 最後の式の値を表示できる場合は、その値を返す関数にする
+        let type_table = self.analyze_evaluation(&wrapper)?;
+        let value_type = match wrapper.body.statements.last() {
+            Some(Statement::Expression(expr)) => type_table.get(expr).filter(|ty| is_printable(ty)).cloned(),
+            _ => None,
+        };
+        if let Some(ty) = &value_type {
+            if let Some(Statement::Expression(expr)) = wrapper.body.statements.pop() {
+                let span = expr.span();
+                wrapper.body.statements.push(Statement::Return(ReturnStatement { value: Some(expr), span }));
+                wrapper.return_type = Some(Box::new(ty.clone()));
+            }
+        }
+
+        let type_table = self.analyze_evaluation(&wrapper)?;
+        let program = self.program(self.compiled_functions().chain(std::iter::once(wrapper.clone())));
+        let engine = self.jit(&program, type_table)?;
+        let address = engine.get_function_address(&wrapper.name).map_err(|e| {
+            YuniError::Codegen(CodegenError::Internal {
+                message: format!("Function {} was not compiled: {}", wrapper.name, e),
+            })
+        })?;
+        // SAFETY: 関数は引数を取らず、戻り値の型は `value_type` から生成したもの
+        let value = unsafe { call_printing(address, value_type.as_ref()) };
+        self.engines.push(engine);
+        Ok(value)
+    }
+
+    /// 入力の文を包んだ関数を、コンパイル済みの関数とともに解析する
+    ///
+    /// 保留中の関数の呼び出しは、待っている関数の名前を含むエラーにします。
+    fn analyze_evaluation(&self, wrapper: &FunctionDecl) -> YuniResult<TypeTable> {
+        let program = self.program(self.compiled_functions().chain(std::iter::once(wrapper.clone())));
+        let mut analyzer = SemanticAnalyzer::new();
+        match analyzer.analyze(&program) {
+            Ok(()) => Ok(analyzer.type_table),
+            Err(AnalyzerError::UndefinedFunction { name, span }) if self.is_pending(&name) => {
+                let missing = self.undefined_callees(&name);
+                Err(YuniError::Analyzer(AnalyzerError::IncompleteFunction { name, missing, span }))
+            }
+            Err(error) => Err(YuniError::Analyzer(error)),
+        }
+    }
+
+    /// モジュールを生成してJITでコンパイルし、別のモジュールの関数とランタイム関数を結び付ける
+    fn jit(&self, program: &Program, type_table: TypeTable) -> YuniResult<ExecutionEngine<'ctx>> {
+        let addresses: HashMap<&str, usize> = self
+            .functions
+            .iter()
+            .filter_map(|entry| match entry.binding {
+                Binding::Compiled(address) => Some((entry.decl.name.as_str(), address)),
+                _ => None,
+            })
+            .collect();
+
+        let mut codegen = CodeGenerator::new(self.context, "repl")
+            .with_functions_defined_elsewhere(addresses.keys().map(|name| name.to_string()));
+        codegen.compile_program_with_types(program, type_table)?;
+        codegen.get_module().verify().map_err(|e| {
+            YuniError::Codegen(CodegenError::Internal { message: format!("Module verification failed: {}", e) })
+        })?;
+
+        // 実行エンジンはモジュールを所有するため、コード生成器のモジュールの複製を渡す
+        let module = codegen.get_module().clone();
+        let engine = module.create_jit_execution_engine(OptimizationLevel::None).map_err(|e| {
+            YuniError::Codegen(CodegenError::Internal {
+                message: format!("Failed to create the JIT execution engine: {}", e),
+            })
+        })?;
+        for function in module.get_functions().filter(|function| function.count_basic_blocks() == 0) {
+            let name = function.get_name().to_string_lossy();
+            if let Some(address) = addresses.get(&*name).copied().or_else(|| runtime_symbol(&name)) {
+                engine.add_global_mapping(&function, address);
+            }
+        }
+        Ok(engine)
+    }
+
+    /// 型の定義・外部関数の宣言と関数 `functions` からなるプログラム
+    fn program(&self, functions: impl IntoIterator<Item = FunctionDecl>) -> Program {
+        let mut items = self.declarations.clone();
+        items.extend(functions.into_iter().map(Item::Function));
+        Program {
+            package: PackageDecl { name: "main".to_string(), no_prelude: false, span: Span::dummy() },
+            imports: Vec::new(),
+            items,
+            span: Span::dummy(),
+        }
+    }
+
+    /// コンパイル済みの関数の定義
+    fn compiled_functions(&self) -> impl Iterator<Item = FunctionDecl> + '_ {
+        self.functions
+            .iter()
+            .filter(|entry| matches!(entry.binding, Binding::Compiled(_)))
+            .map(|entry| entry.decl.clone())
+    }
+
+    /// 位置 `span` を本体に含む関数の登録簿での位置
+    fn function_at(&self, span: Span) -> Option<usize> {
+        self.functions
+            .iter()
+            .position(|entry| entry.decl.span.start <= span.start && span.end <= entry.decl.span.end)
+    }
+
+    fn is_pending(&self, name: &str) -> bool {
+        self.functions
+            .iter()
+            .any(|entry| entry.decl.name == name && matches!(entry.binding, Binding::Pending(_)))
+    }
+
+    /// 保留中の関数 `name` が直接・間接に待っている、どこにも定義のない関数の名前
+    fn undefined_callees(&self, name: &str) -> Vec<String> {
+        let mut undefined = Vec::new();
+        let mut visited = HashSet::new();
+        let mut pending = vec![name.to_string()];
+        while let Some(current) = pending.pop() {
+            if !visited.insert(current.clone()) {
+                continue;
+            }
+            match self.functions.iter().find(|entry| entry.decl.name == current) {
+                Some(ReplFunction { binding: Binding::Pending(missing), .. }) => pending.extend(missing.iter().cloned()),
+                Some(_) => {}
+                None if !undefined.contains(&current) => undefined.push(current),
+                None => {}
+            }
+        }
+        undefined
+    }
+}
+
+/// 入力が閉じていない括弧で終わっていないか（REPLが次の行を続けて読むかの判定用）
+pub fn is_complete(input: &str) -> bool {
+    let mut depth: i64 = 0;
+    for token in Lexer::new(input).collect_tokens() {
+        match token.token {
+            Token::LeftBrace | Token::LeftParen | Token::LeftBracket => depth += 1,
+            Token::RightBrace | Token::RightParen | Token::RightBracket => depth -= 1,
+            _ => {}
+        }
+    }
+    depth <= 0
+}
+
+/// セッションのテキスト `source` の位置 `start` から後の入力をスクリプトとして構文解析する
+fn parse_input(source: &str, start: usize) -> YuniResult<Program> {
+    let tokens: Vec<_> = Lexer::new(source)
+        .collect_tokens()
+        .into_iter()
+        .filter(|token| token.span.start >= start)
+        .collect();
+    if let Some(token) = tokens.iter().find(|token| matches!(token.token, Token::Error)) {
+        return Err(YuniError::Lexer(token.lexer_error(source)));
+    }
+    Ok(Parser::new(tokens).parse_script()?)
+}
+
+/// スクリプトの文を包んだ暗黙のmain関数をアイテムから取り出し、その本体を返す
+///
+/// 暗黙のmain関数は位置が本体と同じで、`fn main` と書いた定義とはそれで区別できます。
+fn take_statements(items: &mut Vec<Item>) -> Option<Block> {
+    let index = items
+        .iter()
+        .position(|item| matches!(item, Item::Function(func) if func.name == "main" && func.span == func.body.span))?;
+    match items.remove(index) {
+        Item::Function(func) => Some(func.body),
+        _ => None,
+    }
+}
+
+/// REPLでまだ扱えない入力のエラー
+fn unsupported(what: &str, span: Span) -> YuniError {
+    YuniError::Analyzer(AnalyzerError::InvalidOperation {
+        message: format!("REPLでは{}はまだ使えません", what),
+        span,
+    })
+}
+
+/// 型の定義と外部関数の宣言の名前
+fn declaration_name(item: &Item) -> &str {
+    match item {
+        Item::TypeDef(TypeDef::Struct(struct_def)) => &struct_def.name,
+        Item::TypeDef(TypeDef::Enum(enum_def)) => &enum_def.name,
+        Item::TypeDef(TypeDef::Alias(type_alias)) => &type_alias.name,
+        Item::ExternFunction(func) => &func.name,
+        Item::Function(func) => &func.name,
+        Item::Method(method) => &method.name,
+    }
+}
+
+/// 型パラメータを持つ型の定義の位置
+fn generic_declaration(item: &Item) -> Option<Span> {
+    match item {
+        Item::TypeDef(TypeDef::Struct(struct_def)) if !struct_def.type_params.is_empty() => Some(struct_def.span),
+        Item::TypeDef(TypeDef::Enum(enum_def)) if !enum_def.type_params.is_empty() => Some(enum_def.span),
+        Item::TypeDef(TypeDef::Alias(type_alias)) if !type_alias.type_params.is_empty() => Some(type_alias.span),
+        _ => None,
+    }
+}
+
+/// REPLが値を表示できる型
+fn is_printable(ty: &Type) -> bool {
+    matches!(
+        ty,
+        Type::I8
+            | Type::I16
+            | Type::I32
+            | Type::I64
+            | Type::U8
+            | Type::U16
+            | Type::U32
+            | Type::U64
+            | Type::F32
+            | Type::F64
+            | Type::Bool
+            | Type::Str
+            | Type::String
+    )
+}
+
+/// JITでコンパイルした引数のない関数を呼び出し、戻り値を文字列にする
+///
+/// # Safety
+/// `address` は引数を取らず、`ty` の型（Noneは値なし）を返す関数を指す必要がある。
+unsafe fn call_printing(address: usize, ty: Option<&Type>) -> Option<String> {
+    match ty {
+        None => {
+            call::<()>(address);
+            None
+        }
+        Some(Type::I8) => Some(call::<i8>(address).to_string()),
+        Some(Type::I16) => Some(call::<i16>(address).to_string()),
+        Some(Type::I32) => Some(call::<i32>(address).to_string()),
+        Some(Type::I64) => Some(call::<i64>(address).to_string()),
+        Some(Type::U8) => Some(call::<u8>(address).to_string()),
+        Some(Type::U16) => Some(call::<u16>(address).to_string()),
+        Some(Type::U32) => Some(call::<u32>(address).to_string()),
+        Some(Type::U64) => Some(call::<u64>(address).to_string()),
+        Some(Type::F32) => Some(call::<f32>(address).to_string()),
+        Some(Type::F64) => Some(call::<f64>(address).to_string()),
+        Some(Type::Bool) => Some(call::<bool>(address).to_string()),
+        Some(Type::Str | Type::String) => {
+            let text = call::<*const c_char>(address);
+            (!text.is_null()).then(|| format!("{:?}", CStr::from_ptr(text).to_string_lossy()))
+        }
+        Some(_) => None,
+    }
+}
+
+/// JITでコンパイルした引数のない関数をアドレスから呼び出す
+///
+/// # Safety
+/// `address` は引数を取らず、`R` の型を返す関数を指す必要がある。
+unsafe fn call<R>(address: usize) -> R {
+    let function = std::mem::transmute::<usize, unsafe extern "C" fn() -> R>(address);
+    function()
+}
+
+/// ランタイムの初期化の代わりに呼び出す関数
+///
+/// `yuni_runtime_init` はプロセスのシグナルハンドラとスタックのリミットを変えるため、
+/// コンパイラのプロセスの中で実行するJITのコードからは呼び出さない。
+extern "C" fn skip_runtime_init(_stack_size: u64) {}
+
+/// ランタイム関数の名前に対応する、コンパイラのプロセス内の実装のアドレス
+///
+/// JITでコンパイルしたコードのランタイム関数の呼び出しを、`crate::runtime` の実装に結び付けます。
+/// `malloc` などのCの標準関数は含めず、プロセスから解決させます。
+pub fn runtime_symbol(name: &str) -> Option<usize> {
+    let address: *const () = match name {
+        "yuni_runtime_init" => skip_runtime_init as *const (),
+        "yuni_alloc_string" => runtime::yuni_alloc_string as *const (),
+        "yuni_free_string" => runtime::yuni_free_string as *const (),
+        "yuni_string_concat" => runtime::yuni_string_concat as *const (),
+        "yuni_str_len" => runtime::yuni_str_len as *const (),
+        "yuni_string_from_parts" => runtime::yuni_string_from_parts as *const (),
+        "yuni_string_eq" => runtime::yuni_string_eq as *const (),
+        "yuni_string_contains" => runtime::yuni_string_contains as *const (),
+        "yuni_string_starts_with" => runtime::yuni_string_starts_with as *const (),
+        "yuni_string_find" => runtime::yuni_string_find as *const (),
+        "yuni_string_split" => runtime::yuni_string_split as *const (),
+        "yuni_string_trim" => runtime::yuni_string_trim as *const (),
+        "yuni_int_to_string" => runtime::yuni_int_to_string as *const (),
+        "yuni_i64_to_string" => runtime::yuni_i64_to_string as *const (),
+        "yuni_u64_to_string" => runtime::yuni_u64_to_string as *const (),
+        "yuni_float_to_string" => runtime::yuni_float_to_string as *const (),
+        "yuni_f64_to_string" => runtime::yuni_f64_to_string as *const (),
+        "yuni_f32_to_string" => runtime::yuni_f32_to_string as *const (),
+        "yuni_bool_to_string" => runtime::yuni_bool_to_string as *const (),
+        "yuni_print" => runtime::yuni_print as *const (),
+        "yuni_println" => runtime::yuni_println as *const (),
+        "yuni_eprintln" => runtime::yuni_eprintln as *const (),
+        "yuni_read_line" => runtime::yuni_read_line as *const (),
+        "yuni_parse_i64" => runtime::yuni_parse_i64 as *const (),
+        "yuni_parse_f64" => runtime::yuni_parse_f64 as *const (),
+        "yuni_write_bytes" => runtime::yuni_write_bytes as *const (),
+        "yuni_read_bytes" => runtime::yuni_read_bytes as *const (),
+        "yuni_time_nanos" => runtime::yuni_time_nanos as *const (),
+        "yuni_time_unix_millis" => runtime::yuni_time_unix_millis as *const (),
+        "yuni_sleep_millis" => runtime::yuni_sleep_millis as *const (),
+        "yuni_random_seed" => runtime::yuni_random_seed as *const (),
+        "yuni_random_u64" => runtime::yuni_random_u64 as *const (),
+        "yuni_sb_new" => runtime::yuni_sb_new as *const (),
+        "yuni_sb_append_str" => runtime::yuni_sb_append_str as *const (),
+        "yuni_sb_append_int" => runtime::yuni_sb_append_int as *const (),
+        "yuni_sb_append_uint" => runtime::yuni_sb_append_uint as *const (),
+        "yuni_sb_append_float" => runtime::yuni_sb_append_float as *const (),
+        "yuni_sb_append_f32" => runtime::yuni_sb_append_f32 as *const (),
+        "yuni_sb_append_bool" => runtime::yuni_sb_append_bool as *const (),
+        "yuni_sb_len" => runtime::yuni_sb_len as *const (),
+        "yuni_sb_to_string" => runtime::yuni_sb_to_string as *const (),
+        "yuni_sb_finish" => runtime::yuni_sb_finish as *const (),
+        "yuni_vec_new" => runtime::yuni_vec_new as *const (),
+        "yuni_vec_push" => runtime::yuni_vec_push as *const (),
+        "yuni_vec_get" => runtime::yuni_vec_get as *const (),
+        "yuni_vec_len" => runtime::yuni_vec_len as *const (),
+        "yuni_vec_resize" => runtime::yuni_vec_resize as *const (),
+        "yuni_vec_free" => runtime::yuni_vec_free as *const (),
+        "yuni_vec_from_bytes" => runtime::yuni_vec_from_bytes as *const (),
+        "yuni_vec_data" => runtime::yuni_vec_data as *const (),
+        "yuni_vec_sort" => runtime::yuni_vec_sort as *const (),
+        "yuni_vec_binary_search" => runtime::yuni_vec_binary_search as *const (),
+        "yuni_vec_contains" => runtime::yuni_vec_contains as *const (),
+        "yuni_hashmap_new" => runtime::yuni_hashmap_new as *const (),
+        "yuni_hashmap_new_str" => runtime::yuni_hashmap_new_str as *const (),
+        "yuni_hashmap_insert" => runtime::yuni_hashmap_insert as *const (),
+        "yuni_hashmap_get" => runtime::yuni_hashmap_get as *const (),
+        "yuni_hashmap_contains_key" => runtime::yuni_hashmap_contains_key as *const (),
+        "yuni_hashmap_len" => runtime::yuni_hashmap_len as *const (),
+        "yuni_hashmap_free" => runtime::yuni_hashmap_free as *const (),
+        "yuni_panic" => runtime::yuni_panic as *const (),
+        "yuni_panic_missing_key" => runtime::yuni_panic_missing_key as *const (),
+        "yuni_panic_index" => runtime::yuni_panic_index as *const (),
+        "yuni_assert_eq_failed" => runtime::yuni_assert_eq_failed as *const (),
+        "yuni_panic_cast_int" => runtime::yuni_panic_cast_int as *const (),
+        "yuni_panic_cast_float" => runtime::yuni_panic_cast_float as *const (),
+        _ => return None,
+    };
+    Some(address as usize)
+}
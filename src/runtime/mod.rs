@@ -3,6 +3,7 @@
 //! This module provides runtime functions and utilities that compiled Yuni programs can use.
 
 use std::ffi::{CStr, CString};
+use std::io::Write;
 use std::os::raw::{c_char, c_int, c_void};
 use std::ptr;
 use std::alloc::{alloc, dealloc, Layout};
@@ -127,6 +128,27 @@ pub unsafe extern "C" fn yuni_str_len(s: *const c_char) -> usize {
     c_str.to_bytes().len()
 }
 
+/// Copy `len` bytes of string data into a new owned string (`str` to `String`)
+///
+/// `len` is the byte length of the string data, so the NUL terminator is not read.
+/// The result is NUL-terminated like every Yuni string, so it can be passed to C functions.
+///
+/// # Safety
+/// `s`は`len`バイト以上の有効な文字列データを指すポインタである必要があります。
+/// 戻り値のポインタは呼び出し側が`yuni_free_string`で解放する必要があります。
+#[no_mangle]
+pub unsafe extern "C" fn yuni_string_from_parts(s: *const c_char, len: usize) -> *mut c_char {
+    let mut bytes = Vec::with_capacity(len + 1);
+    if !s.is_null() {
+        // SAFETY: 呼び出し側がlenバイトの有効なデータを提供することを前提とする
+        bytes.extend_from_slice(std::slice::from_raw_parts(s as *const u8, len));
+    }
+    bytes.push(0);
+    CString::from_vec_with_nul(bytes)
+        .map(CString::into_raw)
+        .unwrap_or(std::ptr::null_mut())
+}
+
 /// Convert integer to string (alias for compatibility)
 #[no_mangle]
 pub extern "C" fn yuni_int_to_string(n: i64) -> *mut c_char {
@@ -144,6 +166,16 @@ pub extern "C" fn yuni_i64_to_string(n: i64) -> *mut c_char {
     }
 }
 
+/// Convert unsigned integer to string
+///
+/// u8〜u64の値はゼロ拡張して渡されます。i64として変換すると2^63以上の値が負になるため分けています。
+#[no_mangle]
+pub extern "C" fn yuni_u64_to_string(n: u64) -> *mut c_char {
+    CString::new(n.to_string())
+        .map(CString::into_raw)
+        .unwrap_or(std::ptr::null_mut())
+}
+
 /// Convert float to string (alias for compatibility)
 #[no_mangle]
 pub extern "C" fn yuni_float_to_string(n: f64) -> *mut c_char {
@@ -151,6 +183,9 @@ pub extern "C" fn yuni_float_to_string(n: f64) -> *mut c_char {
 }
 
 /// Convert float to string
+///
+/// Uses Rust's `Display`: the shortest text that parses back to the same value,
+/// without an exponent (`1e21` -> `1000000000000000000000`, `-0.0` -> `-0`).
 #[no_mangle]
 pub extern "C" fn yuni_f64_to_string(n: f64) -> *mut c_char {
     let s = n.to_string();
@@ -161,6 +196,20 @@ pub extern "C" fn yuni_f64_to_string(n: f64) -> *mut c_char {
     }
 }
 
+/// Convert f32 to string
+///
+/// Formats the f32 value itself, so `0.1f32` prints as `0.1` rather than the
+/// digits of its f64 widening.
+#[no_mangle]
+pub extern "C" fn yuni_f32_to_string(n: f32) -> *mut c_char {
+    let s = n.to_string();
+    if let Ok(c_string) = CString::new(s) {
+        c_string.into_raw()
+    } else {
+        std::ptr::null_mut()
+    }
+}
+
 /// Convert boolean to string
 #[no_mangle]
 pub extern "C" fn yuni_bool_to_string(b: bool) -> *mut c_char {
@@ -217,13 +266,144 @@ pub unsafe extern "C" fn yuni_string_eq(s1: *const c_char, s2: *const c_char) ->
     c_str1 == c_str2
 }
 
-/// Print string with newline (wrapper for yuni_println_str)
-/// 
+/// Check whether `s` contains `needle` as a substring (the `in` operator on strings)
+///
+/// An empty needle is contained in every string. A null pointer is treated as an empty string.
+///
+/// # Safety
+/// `s`と`needle`は有効なnull終端C文字列を指すポインタである必要があります。
+#[no_mangle]
+pub unsafe extern "C" fn yuni_string_contains(s: *const c_char, needle: *const c_char) -> bool {
+    // SAFETY: 呼び出し側が有効なnull終端C文字列を提供することを前提とする
+    find_bytes(c_str_bytes(s), c_str_bytes(needle)).is_some()
+}
+
+/// Check whether `s` begins with `prefix` (the `starts_with` method on strings)
+///
+/// Every string starts with the empty prefix. A null pointer is treated as an empty string.
+///
+/// # Safety
+/// `s`と`prefix`は有効なnull終端C文字列を指すポインタである必要があります。
+#[no_mangle]
+pub unsafe extern "C" fn yuni_string_starts_with(s: *const c_char, prefix: *const c_char) -> bool {
+    // SAFETY: 呼び出し側が有効なnull終端C文字列を提供することを前提とする
+    c_str_bytes(s).starts_with(c_str_bytes(prefix))
+}
+
+/// Find the byte index of the first occurrence of `needle` in `s` (the `find` method on strings)
+///
+/// Returns -1 if `needle` does not occur. An empty needle is found at index 0.
+///
+/// # Safety
+/// `s`と`needle`は有効なnull終端C文字列を指すポインタである必要があります。
+#[no_mangle]
+pub unsafe extern "C" fn yuni_string_find(s: *const c_char, needle: *const c_char) -> i64 {
+    // SAFETY: 呼び出し側が有効なnull終端C文字列を提供することを前提とする
+    find_bytes(c_str_bytes(s), c_str_bytes(needle)).map_or(-1, |index| index as i64)
+}
+
+/// Split `s` at every occurrence of `separator` (the `split` method on strings)
+///
+/// Returns a `Vec<String>` whose elements are newly allocated strings owned by the Vec; each of them
+/// must be freed with `yuni_free_string` before the Vec itself. Adjacent separators yield empty
+/// elements, and an empty separator yields the whole string as the only element.
+///
+/// # Safety
+/// `s`と`separator`は有効なnull終端C文字列を指すポインタである必要があります。
+#[no_mangle]
+pub unsafe extern "C" fn yuni_string_split(s: *const c_char, separator: *const c_char) -> *mut YuniVec {
+    // SAFETY: 呼び出し側が有効なnull終端C文字列を提供することを前提とする
+    let (mut rest, separator) = (c_str_bytes(s), c_str_bytes(separator));
+    let vec = yuni_vec_new(std::mem::size_of::<*mut c_char>(), std::mem::align_of::<*mut c_char>());
+    let push = |part: &[u8]| {
+        let element = yuni_string_from_parts(part.as_ptr() as *const c_char, part.len());
+        yuni_vec_push(vec, &element as *const *mut c_char as *const c_void);
+    };
+    if !separator.is_empty() {
+        while let Some(index) = find_bytes(rest, separator) {
+            push(&rest[..index]);
+            rest = &rest[index + separator.len()..];
+        }
+    }
+    push(rest);
+    vec
+}
+
+/// Copy `s` without its leading and trailing ASCII whitespace (the `trim` method on strings)
+///
+/// # Safety
+/// `s`は有効なnull終端C文字列を指すポインタである必要があります。
+/// 戻り値のポインタは呼び出し側が`yuni_free_string`で解放する必要があります。
+#[no_mangle]
+pub unsafe extern "C" fn yuni_string_trim(s: *const c_char) -> *mut c_char {
+    // SAFETY: 呼び出し側が有効なnull終端C文字列を提供することを前提とする
+    let trimmed = c_str_bytes(s).trim_ascii();
+    yuni_string_from_parts(trimmed.as_ptr() as *const c_char, trimmed.len())
+}
+
+/// null終端C文字列のバイト列（nullポインタは空文字列）
+///
+/// # Safety
+/// `s`はnullか有効なnull終端C文字列を指すポインタである必要があります。
+unsafe fn c_str_bytes<'a>(s: *const c_char) -> &'a [u8] {
+    if s.is_null() {
+        &[]
+    } else {
+        CStr::from_ptr(s).to_bytes()
+    }
+}
+
+/// `haystack` の中で `needle` が最初に現れる位置（空の `needle` は0）
+fn find_bytes(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    if needle.is_empty() {
+        return Some(0);
+    }
+    haystack.windows(needle.len()).position(|window| window == needle)
+}
+
+/// Write a null-terminated string to a stream as raw bytes
+///
+/// The string is never interpreted as a format, so `%s` and the like are written literally.
+///
+/// # Safety
+/// `s`は有効なnull終端C文字列を指すポインタである必要があります。
+unsafe fn write_c_str(out: &mut dyn Write, s: *const c_char, newline: bool) {
+    if !s.is_null() {
+        // SAFETY: 呼び出し側が有効なnull終端C文字列を提供することを前提とする
+        let _ = out.write_all(CStr::from_ptr(s).to_bytes());
+    }
+    if newline {
+        let _ = out.write_all(b"\n");
+    }
+    // 改行のない出力も、プログラムの終了までに確実に書き出す
+    let _ = out.flush();
+}
+
+/// Print string without newline (the `print` builtin)
+///
+/// # Safety
+/// `s`は有効なnull終端C文字列を指すポインタである必要があります。
+#[no_mangle]
+pub unsafe extern "C" fn yuni_print(s: *const c_char) {
+    write_c_str(&mut std::io::stdout().lock(), s, false);
+}
+
+/// Print string with newline (the `println` builtin)
+///
 /// # Safety
 /// `s`は有効なnull終端C文字列を指すポインタである必要があります。
 #[no_mangle]
 pub unsafe extern "C" fn yuni_println(s: *const c_char) {
-    yuni_println_str(s);
+    write_c_str(&mut std::io::stdout().lock(), s, true);
+}
+
+/// Print string with newline to stderr (the `eprintln` builtin)
+///
+/// # Safety
+/// `s`は有効なnull終端C文字列を指すポインタである必要があります。
+#[no_mangle]
+pub unsafe extern "C" fn yuni_eprintln(s: *const c_char) {
+    write_c_str(&mut std::io::stderr().lock(), s, true);
 }
 
 /// Read line from stdin
@@ -251,34 +431,438 @@ pub extern "C" fn yuni_read_line() -> *mut c_char {
     std::ptr::null_mut()
 }
 
+/// Read the text of a C string trimmed of surrounding whitespace
+///
+/// Returns `None` for a null pointer or invalid UTF-8.
+///
+/// # Safety
+/// `s`はnullか、有効なnull終端C文字列を指すポインタである必要があります。
+unsafe fn trimmed_str<'a>(s: *const c_char) -> Option<&'a str> {
+    if s.is_null() {
+        return None;
+    }
+    // SAFETY: 呼び出し側が有効なnull終端C文字列を提供することを前提とする
+    CStr::from_ptr(s).to_str().ok().map(str::trim)
+}
+
+/// Parse a string as a signed 64-bit integer (the `parse_int` builtin)
+///
+/// 前後の空白を除いた文字列全体が10進数の整数（先頭の `+`・`-` は可）の場合だけ成功し、
+/// 値を `out` に書き込んでtrueを返します。空文字列・数字以外を含む文字列・i64の範囲を
+/// 超える値は失敗とし、`out` は変更しません。nullは失敗として扱います。
+///
+/// # Safety
+/// - sはnullか、有効なnull終端C文字列を指すポインタである必要があります
+/// - outは書き込み可能なi64を指すポインタである必要があります
+#[no_mangle]
+pub unsafe extern "C" fn yuni_parse_i64(s: *const c_char, out: *mut i64) -> bool {
+    match trimmed_str(s).and_then(|text| text.parse::<i64>().ok()) {
+        Some(value) => {
+            // SAFETY: 呼び出し側が書き込み可能なポインタを提供することを前提とする
+            *out = value;
+            true
+        }
+        None => false,
+    }
+}
+
+/// Parse a string as a 64-bit float (the `parse_float` builtin)
+///
+/// 前後の空白を除いた文字列全体が小数（`1.5`・`-2`・`3e8` など）の場合だけ成功し、
+/// 値を `out` に書き込んでtrueを返します。有限の値にならないもの（`inf`・`nan`・
+/// f64の範囲を超える `1e400` など）は失敗とし、`out` は変更しません。
+///
+/// # Safety
+/// - sはnullか、有効なnull終端C文字列を指すポインタである必要があります
+/// - outは書き込み可能なf64を指すポインタである必要があります
+#[no_mangle]
+pub unsafe extern "C" fn yuni_parse_f64(s: *const c_char, out: *mut f64) -> bool {
+    match trimmed_str(s).and_then(|text| text.parse::<f64>().ok()).filter(|value| value.is_finite()) {
+        Some(value) => {
+            // SAFETY: 呼び出し側が書き込み可能なポインタを提供することを前提とする
+            *out = value;
+            true
+        }
+        None => false,
+    }
+}
+
+/// Write a byte buffer to a file
+///
+/// 文字列と異なりNULで終端しないため、NULを含むバイナリデータも書き出せます。
+/// 書き出せた場合はtrueを返します。
+///
+/// # Safety
+/// - pathは有効なnull終端C文字列を指すポインタである必要があります
+/// - dataはlenバイトの有効なメモリを指している必要があります（lenが0の場合はnullでも構いません）
+#[no_mangle]
+pub unsafe extern "C" fn yuni_write_bytes(path: *const c_char, data: *const u8, len: usize) -> bool {
+    if path.is_null() || (data.is_null() && len > 0) {
+        return false;
+    }
+    let Ok(path) = CStr::from_ptr(path).to_str() else {
+        return false;
+    };
+    let bytes = if len == 0 { &[][..] } else { std::slice::from_raw_parts(data, len) };
+    std::fs::write(path, bytes).is_ok()
+}
+
+/// Read a whole file into a `Vec<u8>`
+///
+/// 読み込めない場合は空のVecを返します。
+///
+/// # Safety
+/// pathは有効なnull終端C文字列を指すポインタである必要があります
+#[no_mangle]
+pub unsafe extern "C" fn yuni_read_bytes(path: *const c_char) -> *mut YuniVec {
+    let bytes = if path.is_null() {
+        None
+    } else {
+        CStr::from_ptr(path).to_str().ok().and_then(|path| std::fs::read(path).ok())
+    };
+    YuniVec::from_bytes(&bytes.unwrap_or_default())
+}
+
 /// Exit the program
 #[no_mangle]
 pub extern "C" fn yuni_exit(code: c_int) {
     std::process::exit(code);
 }
 
+// ========== 時刻と乱数 ランタイム関数 ==========
+
+/// `yuni_time_nanos` の起点（最初に時刻を取得した時点）
+static CLOCK_ORIGIN: std::sync::OnceLock<std::time::Instant> = std::sync::OnceLock::new();
+
+/// Nanoseconds on a monotonic clock (the `time_nanos` builtin)
+///
+/// プロセスで最初に呼び出した時点からの経過時間を返します。値は減らないため、
+/// 2回の呼び出しの差を経過時間の計測に使えます。
+#[no_mangle]
+pub extern "C" fn yuni_time_nanos() -> i64 {
+    let origin = *CLOCK_ORIGIN.get_or_init(std::time::Instant::now);
+    origin.elapsed().as_nanos().min(i64::MAX as u128) as i64
+}
+
+/// Milliseconds since the Unix epoch (the `time_unix_millis` builtin)
+///
+/// システムの時計の時刻のため、時計の調整で戻ることがあります。経過時間の計測には
+/// `yuni_time_nanos` を使ってください。
+#[no_mangle]
+pub extern "C" fn yuni_time_unix_millis() -> i64 {
+    match std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH) {
+        Ok(elapsed) => elapsed.as_millis().min(i64::MAX as u128) as i64,
+        Err(before) => -(before.duration().as_millis().min(i64::MAX as u128) as i64),
+    }
+}
+
+/// Sleep the current thread (the `sleep_millis` builtin)
+///
+/// 0以下の値では何もしません。
+#[no_mangle]
+pub extern "C" fn yuni_sleep_millis(ms: i64) {
+    if ms > 0 {
+        std::thread::sleep(std::time::Duration::from_millis(ms as u64));
+    }
+}
+
+/// シードから乱数の内部状態を作る（SplitMix64で混ぜ、xorshiftで使えない0を避ける）
+///
+/// インタプリタの `random_seed` も同じ算法のため、変更する場合は両方を合わせてください。
+const fn random_state_from_seed(seed: u64) -> u64 {
+    let mut z = seed.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^= z >> 31;
+    if z == 0 {
+        0x9E37_79B9_7F4A_7C15
+    } else {
+        z
+    }
+}
+
+thread_local! {
+    /// 乱数の内部状態（シードを設定しなければ `yuni_random_seed(0)` と同じ系列になる）
+    static RANDOM_STATE: std::cell::Cell<u64> = const { std::cell::Cell::new(random_state_from_seed(0)) };
+}
+
+/// Seed the random number generator (the `random_seed` builtin)
+///
+/// 同じシードからは、プラットフォームに関係なく同じ系列の乱数が得られます。
+#[no_mangle]
+pub extern "C" fn yuni_random_seed(seed: u64) {
+    RANDOM_STATE.with(|state| state.set(random_state_from_seed(seed)));
+}
+
+/// Draw the next pseudo-random number (the `random_u64` builtin)
+///
+/// xorshift64* で内部状態を進めます。暗号用途には使えません。
+#[no_mangle]
+pub extern "C" fn yuni_random_u64() -> u64 {
+    RANDOM_STATE.with(|state| {
+        let mut x = state.get();
+        x ^= x >> 12;
+        x ^= x << 25;
+        x ^= x >> 27;
+        state.set(x);
+        x.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    })
+}
+
+// ========== 文字列ビルダー ランタイム関数 ==========
+
+/// 文字列ビルダーの表現
+///
+/// 連結のたびに蓄積済みの文字列全体をコピーする`yuni_string_concat`と異なり、
+/// 容量が足りなくなるとバッファを倍に伸ばすため、n バイトの構築は償却 O(n) で済みます。
+pub struct YuniStringBuilder {
+    buf: Vec<u8>,
+}
+
+impl YuniStringBuilder {
+    /// 最初に確保するバッファの大きさ
+    const INITIAL_CAPACITY: usize = 16;
+
+    fn append(&mut self, bytes: &[u8]) {
+        let required = self.buf.len() + bytes.len();
+        if required > self.buf.capacity() {
+            let new_capacity = required
+                .max(self.buf.capacity() * 2)
+                .max(Self::INITIAL_CAPACITY);
+            self.buf.reserve_exact(new_capacity - self.buf.len());
+        }
+        self.buf.extend_from_slice(bytes);
+    }
+
+    /// 内容をnull終端C文字列として複製
+    fn to_c_string(&self) -> *mut c_char {
+        match CString::new(self.buf.clone()) {
+            Ok(c_string) => c_string.into_raw(),
+            Err(_) => ptr::null_mut(),
+        }
+    }
+}
+
+/// 新しい文字列ビルダーを作成
+#[no_mangle]
+pub extern "C" fn yuni_sb_new() -> *mut YuniStringBuilder {
+    Box::into_raw(Box::new(YuniStringBuilder { buf: Vec::new() }))
+}
+
+/// 文字列を追加
+///
+/// # Safety
+/// - sbは有効なYuniStringBuilderポインタである必要があります
+/// - sは有効なnull終端C文字列を指すポインタである必要があります
+#[no_mangle]
+pub unsafe extern "C" fn yuni_sb_append_str(sb: *mut YuniStringBuilder, s: *const c_char) {
+    if sb.is_null() || s.is_null() {
+        return;
+    }
+    // SAFETY: 呼び出し側が有効なnull終端C文字列を提供することを前提とする
+    (*sb).append(CStr::from_ptr(s).to_bytes());
+}
+
+/// 整数を10進数で追加（`yuni_i64_to_string`と同じ書式）
+///
+/// # Safety
+/// sbは有効なYuniStringBuilderポインタである必要があります
+#[no_mangle]
+pub unsafe extern "C" fn yuni_sb_append_int(sb: *mut YuniStringBuilder, n: i64) {
+    if sb.is_null() {
+        return;
+    }
+    (*sb).append(n.to_string().as_bytes());
+}
+
+/// 符号なし整数を10進数で追加（`yuni_u64_to_string`と同じ書式）
+///
+/// # Safety
+/// sbは有効なYuniStringBuilderポインタである必要があります
+#[no_mangle]
+pub unsafe extern "C" fn yuni_sb_append_uint(sb: *mut YuniStringBuilder, n: u64) {
+    if sb.is_null() {
+        return;
+    }
+    (*sb).append(n.to_string().as_bytes());
+}
+
+/// 浮動小数点数を追加（`yuni_f64_to_string`と同じ書式）
+///
+/// # Safety
+/// sbは有効なYuniStringBuilderポインタである必要があります
+#[no_mangle]
+pub unsafe extern "C" fn yuni_sb_append_float(sb: *mut YuniStringBuilder, n: f64) {
+    if sb.is_null() {
+        return;
+    }
+    (*sb).append(n.to_string().as_bytes());
+}
+
+/// f32の浮動小数点数を追加（`yuni_f32_to_string`と同じ書式）
+///
+/// # Safety
+/// sbは有効なYuniStringBuilderポインタである必要があります
+#[no_mangle]
+pub unsafe extern "C" fn yuni_sb_append_f32(sb: *mut YuniStringBuilder, n: f32) {
+    if sb.is_null() {
+        return;
+    }
+    (*sb).append(n.to_string().as_bytes());
+}
+
+/// 真偽値を追加（`yuni_bool_to_string`と同じ書式）
+///
+/// # Safety
+/// sbは有効なYuniStringBuilderポインタである必要があります
+#[no_mangle]
+pub unsafe extern "C" fn yuni_sb_append_bool(sb: *mut YuniStringBuilder, b: bool) {
+    if sb.is_null() {
+        return;
+    }
+    (*sb).append(if b { b"true" } else { b"false" });
+}
+
+/// 構築中の文字列のバイト数を取得
+///
+/// # Safety
+/// sbは有効なYuniStringBuilderポインタである必要があります
+#[no_mangle]
+pub unsafe extern "C" fn yuni_sb_len(sb: *const YuniStringBuilder) -> usize {
+    if sb.is_null() {
+        return 0;
+    }
+    (*sb).buf.len()
+}
+
+/// 構築中の文字列を複製して取得（ビルダーは引き続き使用できる）
+///
+/// # Safety
+/// sbは有効なYuniStringBuilderポインタである必要があります
+#[no_mangle]
+pub unsafe extern "C" fn yuni_sb_to_string(sb: *const YuniStringBuilder) -> *mut c_char {
+    if sb.is_null() {
+        return ptr::null_mut();
+    }
+    (*sb).to_c_string()
+}
+
+/// 構築した文字列を取得し、ビルダーを解放
+///
+/// # Safety
+/// - sbは`yuni_sb_new`で作成した有効なポインタである必要があります
+/// - この呼び出しの後、sbを使用してはいけません
+#[no_mangle]
+pub unsafe extern "C" fn yuni_sb_finish(sb: *mut YuniStringBuilder) -> *mut c_char {
+    if sb.is_null() {
+        return ptr::null_mut();
+    }
+    let sb = Box::from_raw(sb);
+    // 追加できるのはnull終端C文字列と数値の書式だけなので、内部にnullは含まれない
+    match CString::new(sb.buf) {
+        Ok(c_string) => c_string.into_raw(),
+        Err(_) => ptr::null_mut(),
+    }
+}
+
 // ========== Vec ランタイム関数 ==========
 
 /// Vec構造体の表現
+///
+/// バッファは要素のアライメントで確保され、要素の間隔はサイズをアライメントの
+/// 倍数に切り上げた値になるため、すべての要素が整列したアドレスに配置されます。
 #[repr(C)]
 pub struct YuniVec {
     data: *mut c_void,
     len: usize,
     capacity: usize,
     element_size: usize,
+    element_align: usize,
+}
+
+impl YuniVec {
+    /// バッファ内の要素の間隔（アライメントの倍数）
+    fn stride(&self) -> usize {
+        self.element_size.div_ceil(self.element_align) * self.element_align
+    }
+
+    /// 容量分のバッファのレイアウト
+    fn buffer_layout(&self, capacity: usize) -> Option<Layout> {
+        let size = self.stride().checked_mul(capacity)?;
+        Layout::from_size_align(size, self.element_align).ok()
+    }
+
+    /// 容量を `min_capacity` 以上にする（確保に失敗した場合はfalse）
+    ///
+    /// 容量は少なくとも倍に増やし、空のVecでは4から始めます。
+    unsafe fn reserve(&mut self, min_capacity: usize) -> bool {
+        if min_capacity <= self.capacity {
+            return true;
+        }
+        let grown = if self.capacity == 0 { 4 } else { self.capacity.saturating_mul(2) };
+        let new_capacity = grown.max(min_capacity);
+        let new_layout = match self.buffer_layout(new_capacity) {
+            Some(layout) => layout,
+            None => return false,
+        };
+
+        let new_data = if self.data.is_null() {
+            alloc(new_layout)
+        } else {
+            // reallocは元のレイアウトのアライメントを維持する
+            let old_layout = self.buffer_layout(self.capacity).unwrap();
+            std::alloc::realloc(self.data as *mut u8, old_layout, new_layout.size())
+        };
+
+        if new_data.is_null() {
+            // アロケーション失敗
+            return false;
+        }
+
+        self.data = new_data as *mut c_void;
+        self.capacity = new_capacity;
+        true
+    }
+
+    /// バイト列をコピーした `Vec<u8>`（要素のサイズとアライメントが1のVec）を作成
+    fn from_bytes(bytes: &[u8]) -> *mut YuniVec {
+        let mut vec = Box::new(YuniVec {
+            data: ptr::null_mut(),
+            len: 0,
+            capacity: 0,
+            element_size: 1,
+            element_align: 1,
+        });
+        if let Some(layout) = vec.buffer_layout(bytes.len()).filter(|layout| layout.size() > 0) {
+            // SAFETY: レイアウトの大きさは0でなく、確保したバッファはbytesと同じ長さを持つ
+            unsafe {
+                let data = alloc(layout);
+                if !data.is_null() {
+                    ptr::copy_nonoverlapping(bytes.as_ptr(), data, bytes.len());
+                    vec.data = data as *mut c_void;
+                    vec.len = bytes.len();
+                    vec.capacity = bytes.len();
+                }
+            }
+        }
+        Box::into_raw(vec)
+    }
 }
 
 /// 新しいVecを作成
 /// 
 /// # Safety
-/// element_sizeは正の値である必要があります
+/// - element_sizeは正の値である必要があります
+/// - element_alignは2の累乗である必要があります（そうでない場合は次の2の累乗に切り上げます）
 #[no_mangle]
-pub unsafe extern "C" fn yuni_vec_new(element_size: usize) -> *mut YuniVec {
+pub unsafe extern "C" fn yuni_vec_new(element_size: usize, element_align: usize) -> *mut YuniVec {
+    let element_align = element_align.max(1).next_power_of_two();
     let vec = Box::new(YuniVec {
         data: ptr::null_mut(),
         len: 0,
         capacity: 0,
         element_size,
+        element_align,
     });
     Box::into_raw(vec)
 }
@@ -297,28 +881,12 @@ pub unsafe extern "C" fn yuni_vec_push(vec: *mut YuniVec, element: *const c_void
     let vec = &mut *vec;
     
     // 容量が足りない場合は再割り当て
-    if vec.len >= vec.capacity {
-        let new_capacity = if vec.capacity == 0 { 4 } else { vec.capacity * 2 };
-        let new_layout = Layout::array::<u8>(vec.element_size * new_capacity).unwrap();
-        
-        let new_data = if vec.data.is_null() {
-            alloc(new_layout)
-        } else {
-            let old_layout = Layout::array::<u8>(vec.element_size * vec.capacity).unwrap();
-            std::alloc::realloc(vec.data as *mut u8, old_layout, new_layout.size())
-        };
-        
-        if new_data.is_null() {
-            // アロケーション失敗
-            return;
-        }
-        
-        vec.data = new_data as *mut c_void;
-        vec.capacity = new_capacity;
+    if !vec.reserve(vec.len + 1) {
+        return;
     }
     
     // 要素をコピー
-    let dst = (vec.data as *mut u8).add(vec.len * vec.element_size);
+    let dst = (vec.data as *mut u8).add(vec.len * vec.stride());
     ptr::copy_nonoverlapping(element as *const u8, dst, vec.element_size);
     vec.len += 1;
 }
@@ -339,7 +907,7 @@ pub unsafe extern "C" fn yuni_vec_get(vec: *const YuniVec, index: usize) -> *con
         return ptr::null();
     }
     
-    (vec.data as *const u8).add(index * vec.element_size) as *const c_void
+    (vec.data as *const u8).add(index * vec.stride()) as *const c_void
 }
 
 /// Vecの長さを取得
@@ -354,6 +922,63 @@ pub unsafe extern "C" fn yuni_vec_len(vec: *const YuniVec) -> usize {
     (*vec).len
 }
 
+/// Vecの長さを `new_len` に変える
+///
+/// 縮める場合は末尾の要素を取り除き、伸ばす場合は増えた要素を `fill` の値で埋めます。
+/// 負の長さを指定するとパニックします。
+///
+/// # Safety
+/// - vecは有効なYuniVecポインタである必要があります
+/// - fillはelement_sizeバイトの有効なメモリを指している必要があります
+#[no_mangle]
+pub unsafe extern "C" fn yuni_vec_resize(vec: *mut YuniVec, new_len: i64, fill: *const c_void) {
+    if new_len < 0 {
+        panic!("Vecの長さに負の値 {} は指定できません", new_len);
+    }
+    if vec.is_null() || fill.is_null() {
+        return;
+    }
+
+    let vec = &mut *vec;
+    let new_len = new_len as usize;
+    if new_len > vec.len {
+        if !vec.reserve(new_len) {
+            return;
+        }
+        for i in vec.len..new_len {
+            let dst = (vec.data as *mut u8).add(i * vec.stride());
+            ptr::copy_nonoverlapping(fill as *const u8, dst, vec.element_size);
+        }
+    }
+    vec.len = new_len;
+}
+
+/// Create a `Vec<u8>` from a byte buffer
+///
+/// バイト列リテラルの値を作成します。NULを含むバイト列もそのままコピーします。
+///
+/// # Safety
+/// dataはlenバイトの有効なメモリを指している必要があります（lenが0の場合はnullでも構いません）
+#[no_mangle]
+pub unsafe extern "C" fn yuni_vec_from_bytes(data: *const u8, len: usize) -> *mut YuniVec {
+    if data.is_null() || len == 0 {
+        return YuniVec::from_bytes(&[]);
+    }
+    YuniVec::from_bytes(std::slice::from_raw_parts(data, len))
+}
+
+/// Get the element buffer of a Vec
+///
+/// # Safety
+/// vecは有効なYuniVecポインタである必要があります
+#[no_mangle]
+pub unsafe extern "C" fn yuni_vec_data(vec: *const YuniVec) -> *const u8 {
+    if vec.is_null() {
+        return ptr::null();
+    }
+    (*vec).data as *const u8
+}
+
 /// Vecを解放
 /// 
 /// # Safety
@@ -366,15 +991,137 @@ pub unsafe extern "C" fn yuni_vec_free(vec: *mut YuniVec) {
     
     let vec = Box::from_raw(vec);
     if !vec.data.is_null() && vec.capacity > 0 {
-        let layout = Layout::array::<u8>(vec.element_size * vec.capacity).unwrap();
+        let layout = vec.buffer_layout(vec.capacity).unwrap();
         dealloc(vec.data as *mut u8, layout);
     }
     // Boxがドロップされることで、YuniVec自体も解放される
 }
 
-// ========== HashMap ランタイム関数 ==========
+/// Vecの要素の比較関数（2つの要素へのポインタを受け取り、負・0・正で大小を返す）
+///
+/// コード生成が要素の型ごと（`sort_by` では比較関数ごと）に合成します。
+pub type YuniCompareFn = unsafe extern "C" fn(*const c_void, *const c_void) -> i32;
 
-/// HashMap構造体の表現（簡易実装）
+/// 比較関数で要素の添字を安定にマージソートする
+///
+/// 比較関数が全順序でなくても停止し、パニックしません。
+fn merge_sort_indices(len: usize, compare: &mut impl FnMut(usize, usize) -> i32) -> Vec<usize> {
+    let mut indices: Vec<usize> = (0..len).collect();
+    let mut buffer = indices.clone();
+    let mut width = 1;
+    while width < len {
+        for start in (0..len).step_by(2 * width) {
+            let mid = (start + width).min(len);
+            let end = (start + 2 * width).min(len);
+            let (mut left, mut right) = (start, mid);
+            for slot in &mut buffer[start..end] {
+                // 等しい要素は左側を先に取り、元の順序を保つ
+                if left < mid && (right == end || compare(indices[left], indices[right]) <= 0) {
+                    *slot = indices[left];
+                    left += 1;
+                } else {
+                    *slot = indices[right];
+                    right += 1;
+                }
+            }
+        }
+        std::mem::swap(&mut indices, &mut buffer);
+        width *= 2;
+    }
+    indices
+}
+
+/// Vecの要素を比較関数で昇順に並べ替える（安定ソート）
+///
+/// # Safety
+/// - vecは有効なYuniVecポインタである必要があります
+/// - compareはVecの要素へのポインタを2つ受け取る比較関数である必要があります
+#[no_mangle]
+pub unsafe extern "C" fn yuni_vec_sort(vec: *mut YuniVec, compare: YuniCompareFn) {
+    if vec.is_null() {
+        return;
+    }
+    let vec = &mut *vec;
+    if vec.len < 2 {
+        return;
+    }
+
+    let stride = vec.stride();
+    let data = vec.data as *mut u8;
+    let order = merge_sort_indices(vec.len, &mut |a, b| {
+        compare(data.add(a * stride) as *const c_void, data.add(b * stride) as *const c_void)
+    });
+
+    // 並べ替えた順に別のバッファへコピーしてから書き戻す
+    let Some(layout) = vec.buffer_layout(vec.len) else { return };
+    let sorted = alloc(layout);
+    if sorted.is_null() {
+        return;
+    }
+    for (i, &index) in order.iter().enumerate() {
+        ptr::copy_nonoverlapping(data.add(index * stride), sorted.add(i * stride), stride);
+    }
+    ptr::copy_nonoverlapping(sorted, data, layout.size());
+    dealloc(sorted, layout);
+}
+
+/// 昇順に並んだVecからキーと等しい要素を二分探索する
+///
+/// 等しい要素のうち最も前の添字を返し、見つからなければ-1を返します。
+///
+/// # Safety
+/// - vecは有効なYuniVecポインタである必要があります
+/// - keyは要素と同じ型の値を指している必要があります
+/// - compareはVecの要素へのポインタを2つ受け取る比較関数である必要があります
+#[no_mangle]
+pub unsafe extern "C" fn yuni_vec_binary_search(vec: *const YuniVec, key: *const c_void, compare: YuniCompareFn) -> i64 {
+    if vec.is_null() || key.is_null() {
+        return -1;
+    }
+    let vec = &*vec;
+    let stride = vec.stride();
+    let data = vec.data as *const u8;
+    let element = |index: usize| data.add(index * stride) as *const c_void;
+
+    // キー以上になる最初の要素を探す
+    let (mut low, mut high) = (0, vec.len);
+    while low < high {
+        let mid = low + (high - low) / 2;
+        if compare(element(mid), key) < 0 {
+            low = mid + 1;
+        } else {
+            high = mid;
+        }
+    }
+    if low < vec.len && compare(element(low), key) == 0 {
+        low as i64
+    } else {
+        -1
+    }
+}
+
+/// Vecにキーと等しい要素が含まれるかを調べる（`in` 演算子）
+///
+/// `compare` が0を返す要素があればtrueを返します。比較関数は `yuni_vec_sort` と同じです。
+///
+/// # Safety
+/// - vecは有効なYuniVecポインタである必要があります
+/// - keyは要素と同じ型の値を指している必要があります
+/// - compareはVecの要素へのポインタを2つ受け取る比較関数である必要があります
+#[no_mangle]
+pub unsafe extern "C" fn yuni_vec_contains(vec: *const YuniVec, key: *const c_void, compare: YuniCompareFn) -> bool {
+    if vec.is_null() || key.is_null() {
+        return false;
+    }
+    let vec = &*vec;
+    let stride = vec.stride();
+    let data = vec.data as *const u8;
+    (0..vec.len).any(|index| compare(data.add(index * stride) as *const c_void, key) == 0)
+}
+
+// ========== HashMap ランタイム関数 ==========
+
+/// HashMap構造体の表現（簡易実装）
 #[repr(C)]
 pub struct YuniHashMap {
     buckets: *mut *mut YuniHashMapBucket,
@@ -382,6 +1129,8 @@ pub struct YuniHashMap {
     size: usize,
     key_size: usize,
     value_size: usize,
+    /// キーがC文字列へのポインタで、文字列の内容で比較するか
+    string_keys: bool,
 }
 
 #[repr(C)]
@@ -391,6 +1140,36 @@ struct YuniHashMapBucket {
     next: *mut YuniHashMapBucket,
 }
 
+impl YuniHashMap {
+    /// キーを比較するバイト列（文字列のキーは文字列の内容）
+    unsafe fn key_bytes<'a>(&self, key: *const c_void) -> &'a [u8] {
+        if self.string_keys {
+            let s = *(key as *const *const c_char);
+            if s.is_null() {
+                return &[];
+            }
+            CStr::from_ptr(s).to_bytes()
+        } else {
+            std::slice::from_raw_parts(key as *const u8, self.key_size)
+        }
+    }
+
+    /// キーに一致する要素を探す（見つからなければnull）
+    unsafe fn find(&self, key: *const c_void) -> *mut YuniHashMapBucket {
+        let key_bytes = self.key_bytes(key);
+        let bucket_index = hash_bytes(key_bytes) % self.bucket_count;
+
+        let mut current = *self.buckets.add(bucket_index);
+        while !current.is_null() {
+            if self.key_bytes((*current).key) == key_bytes {
+                return current;
+            }
+            current = (*current).next;
+        }
+        ptr::null_mut()
+    }
+}
+
 /// 新しいHashMapを作成
 /// 
 /// # Safety
@@ -412,13 +1191,26 @@ pub unsafe extern "C" fn yuni_hashmap_new(key_size: usize, value_size: usize) ->
         size: 0,
         key_size,
         value_size,
+        string_keys: false,
     });
     Box::into_raw(hashmap)
 }
 
+/// 文字列をキーとする新しいHashMapを作成
+///
+/// キーはポインタではなく文字列の内容で比較し、挿入時に複製して保持します。
+///
+/// # Safety
+/// value_sizeは正の値である必要があります
+#[no_mangle]
+pub unsafe extern "C" fn yuni_hashmap_new_str(value_size: usize) -> *mut YuniHashMap {
+    let hashmap = yuni_hashmap_new(std::mem::size_of::<*const c_char>(), value_size);
+    (*hashmap).string_keys = true;
+    hashmap
+}
+
 /// 簡易ハッシュ関数
-unsafe fn hash_bytes(data: *const c_void, size: usize) -> usize {
-    let bytes = std::slice::from_raw_parts(data as *const u8, size);
+fn hash_bytes(bytes: &[u8]) -> usize {
     let mut hash = 0usize;
     for &byte in bytes {
         hash = hash.wrapping_mul(31).wrapping_add(byte as usize);
@@ -427,6 +1219,8 @@ unsafe fn hash_bytes(data: *const c_void, size: usize) -> usize {
 }
 
 /// HashMapに要素を挿入
+///
+/// 既に同じキーがある場合は値を上書きし、要素数は変わりません。
 /// 
 /// # Safety
 /// - hashmapは有効なYuniHashMapポインタである必要があります
@@ -438,15 +1232,26 @@ pub unsafe extern "C" fn yuni_hashmap_insert(hashmap: *mut YuniHashMap, key: *co
     }
     
     let hashmap = &mut *hashmap;
-    let hash = hash_bytes(key, hashmap.key_size);
-    let bucket_index = hash % hashmap.bucket_count;
+    let existing = hashmap.find(key);
+    if !existing.is_null() {
+        ptr::copy_nonoverlapping(value as *const u8, (*existing).value as *mut u8, hashmap.value_size);
+        return;
+    }
+
+    let bucket_index = hash_bytes(hashmap.key_bytes(key)) % hashmap.bucket_count;
     
     // 新しいバケットエントリを作成
     let new_bucket = alloc(Layout::new::<YuniHashMapBucket>()) as *mut YuniHashMapBucket;
     let key_mem = alloc(Layout::from_size_align(hashmap.key_size, 8).unwrap());
     let value_mem = alloc(Layout::from_size_align(hashmap.value_size, 8).unwrap());
     
-    ptr::copy_nonoverlapping(key as *const u8, key_mem, hashmap.key_size);
+    if hashmap.string_keys {
+        // 呼び出し側の文字列が解放されてもキーが残るよう複製する
+        let copy = CString::new(hashmap.key_bytes(key)).unwrap_or_default().into_raw();
+        *(key_mem as *mut *mut c_char) = copy;
+    } else {
+        ptr::copy_nonoverlapping(key as *const u8, key_mem, hashmap.key_size);
+    }
     ptr::copy_nonoverlapping(value as *const u8, value_mem, hashmap.value_size);
     
     (*new_bucket).key = key_mem as *mut c_void;
@@ -458,6 +1263,8 @@ pub unsafe extern "C" fn yuni_hashmap_insert(hashmap: *mut YuniHashMap, key: *co
 }
 
 /// HashMapから要素を取得
+///
+/// 要素の値へのポインタを返します。値をその場で変更でき、キーがなければnullを返します。
 /// 
 /// # Safety
 /// - hashmapは有効なYuniHashMapポインタである必要があります
@@ -468,24 +1275,22 @@ pub unsafe extern "C" fn yuni_hashmap_get(hashmap: *const YuniHashMap, key: *con
         return ptr::null();
     }
     
-    let hashmap = &*hashmap;
-    let hash = hash_bytes(key, hashmap.key_size);
-    let bucket_index = hash % hashmap.bucket_count;
-    
-    let mut current = *hashmap.buckets.add(bucket_index);
-    while !current.is_null() {
-        // キーを比較
-        let key_bytes = std::slice::from_raw_parts(key as *const u8, hashmap.key_size);
-        let bucket_key_bytes = std::slice::from_raw_parts((*current).key as *const u8, hashmap.key_size);
-        
-        if key_bytes == bucket_key_bytes {
-            return (*current).value;
-        }
-        
-        current = (*current).next;
+    let bucket = (*hashmap).find(key);
+    if bucket.is_null() {
+        ptr::null()
+    } else {
+        (*bucket).value
     }
-    
-    ptr::null()
+}
+
+/// HashMapにキーが含まれるかを調べる（`in` 演算子）
+///
+/// # Safety
+/// - hashmapは有効なYuniHashMapポインタである必要があります
+/// - keyは適切なサイズの有効なメモリを指している必要があります
+#[no_mangle]
+pub unsafe extern "C" fn yuni_hashmap_contains_key(hashmap: *const YuniHashMap, key: *const c_void) -> bool {
+    !yuni_hashmap_get(hashmap, key).is_null()
 }
 
 /// HashMapのサイズを取得
@@ -518,7 +1323,10 @@ pub unsafe extern "C" fn yuni_hashmap_free(hashmap: *mut YuniHashMap) {
         while !current.is_null() {
             let next = (*current).next;
             
-            // キーと値のメモリを解放
+            // キーと値のメモリを解放（文字列のキーは複製した文字列も解放する）
+            if hashmap.string_keys {
+                drop(CString::from_raw(*((*current).key as *mut *mut c_char)));
+            }
             dealloc((*current).key as *mut u8, Layout::from_size_align(hashmap.key_size, 8).unwrap());
             dealloc((*current).value as *mut u8, Layout::from_size_align(hashmap.value_size, 8).unwrap());
             
@@ -536,6 +1344,44 @@ pub unsafe extern "C" fn yuni_hashmap_free(hashmap: *mut YuniHashMap) {
     // YuniHashMap自体はBoxがドロップされることで解放される
 }
 
+/// HashMapのインデックスアクセスでキーが見つからなかった場合のパニック
+///
+/// インタプリタと同じ文言になります。
+///
+/// # Safety
+/// `key`は有効なnull終端C文字列を指すポインタである必要があります。
+#[no_mangle]
+pub unsafe extern "C" fn yuni_panic_missing_key(key: *const c_char) {
+    // SAFETY: 呼び出し側が有効なnull終端C文字列を提供することを前提とする
+    let key = CStr::from_ptr(key).to_string_lossy();
+    panic!("キー {} が見つかりません", key);
+}
+
+/// Vecのインデックスアクセスでインデックスが範囲外だった場合のパニック
+///
+/// `is_write` は代入（`v[i] = x`）かどうか、`location` はソースコードの位置（不明な場合は空文字列）です。
+/// インタプリタの `RuntimeError::IndexOutOfBounds`・`IndexAssignmentOutOfBounds` と同じ文言になります。
+///
+/// # Safety
+/// `location`は有効なnull終端C文字列を指すポインタである必要があります。
+#[no_mangle]
+pub unsafe extern "C" fn yuni_panic_index(index: i64, len: i64, is_write: bool, location: *const c_char) {
+    let message = if is_write {
+        format!(
+            "インデックスが範囲外のため代入できません: 長さ {}, インデックス {}（要素を追加するには push を、長さを変えるには resize を使ってください）",
+            len, index
+        )
+    } else {
+        format!("インデックスが範囲外です: 長さ {}, インデックス {}", len, index)
+    };
+    // SAFETY: 呼び出し側が有効なnull終端C文字列を提供することを前提とする
+    let location = CStr::from_ptr(location).to_string_lossy();
+    if location.is_empty() {
+        panic!("{}", message);
+    }
+    panic!("{} (at {})", message, location);
+}
+
 /// Panic handler
 /// 
 /// # Safety
@@ -552,6 +1398,254 @@ pub unsafe extern "C" fn yuni_panic(msg: *const c_char) {
     panic!("Unknown panic");
 }
 
+/// `assert_eq` が失敗した場合のパニック
+///
+/// `msg` は式と位置を含むメッセージ、`left` と `right` は文字列に変換した両辺の値です。
+/// インタプリタの `RuntimeError::AssertionFailed` と同じ文言になります。
+///
+/// # Safety
+/// 各引数は有効なnull終端C文字列を指すポインタである必要があります。
+#[no_mangle]
+pub unsafe extern "C" fn yuni_assert_eq_failed(msg: *const c_char, left: *const c_char, right: *const c_char) {
+    // SAFETY: 呼び出し側が有効なnull終端C文字列を提供することを前提とする
+    let msg = CStr::from_ptr(msg).to_string_lossy();
+    let left = CStr::from_ptr(left).to_string_lossy();
+    let right = CStr::from_ptr(right).to_string_lossy();
+    panic!("{}", crate::assertion::with_values(&msg, &left, &right));
+}
+
+/// 検査付きキャスト（`--checked-casts`）で整数が変換先の範囲外だった場合のパニック
+///
+/// 値は128ビットに拡張して上位・下位の64ビットに分けて渡されます。
+///
+/// # Safety
+/// `from`と`to`は有効なnull終端C文字列を指すポインタである必要があります。
+#[no_mangle]
+pub unsafe extern "C" fn yuni_panic_cast_int(low: u64, high: u64, is_signed: bool, from: *const c_char, to: *const c_char) {
+    let bits = ((high as u128) << 64) | low as u128;
+    let value = if is_signed { (bits as i128).to_string() } else { bits.to_string() };
+    panic_cast(&value, from, to)
+}
+
+/// 検査付きキャストで浮動小数点数がNaNまたは変換先の整数型の範囲外だった場合のパニック
+///
+/// # Safety
+/// `from`と`to`は有効なnull終端C文字列を指すポインタである必要があります。
+#[no_mangle]
+pub unsafe extern "C" fn yuni_panic_cast_float(value: f64, from: *const c_char, to: *const c_char) {
+    panic_cast(&value.to_string(), from, to)
+}
+
+/// キャストのパニックメッセージ（インタプリタの `RuntimeError::CastOutOfRange` と同じ文言）
+unsafe fn panic_cast(value: &str, from: *const c_char, to: *const c_char) -> ! {
+    // SAFETY: 呼び出し側が有効なnull終端C文字列を提供することを前提とする
+    let from = CStr::from_ptr(from).to_string_lossy();
+    let to = CStr::from_ptr(to).to_string_lossy();
+    panic!("キャストで値が失われます: {} の値 {} は {} の範囲外です", from, value, to);
+}
+
+// ========== スタックの溢れの検出 ==========
+
+/// スタックが溢れた場合の終了コード
+///
+/// シグナルによる異常終了（139など）やパニック（134）と区別できる値にしています。
+pub const STACK_OVERFLOW_EXIT_CODE: i32 = 253;
+
+/// スタックが溢れた場合に標準エラー出力に書き出すメッセージ
+const STACK_OVERFLOW_MESSAGE: &[u8] =
+    "stack overflow — consider increasing --stack-size or converting recursion to iteration\n".as_bytes();
+
+/// スタックの上限の外側でもスタックの溢れとみなす範囲
+///
+/// Linuxはスタックの下に1MiBの空白（stack_guard_gap）を置くため、大きなフレームの確保で
+/// 上限を越えた場合もこの範囲に収まります。
+const STACK_GUARD_SLACK: usize = 1 << 20;
+
+/// スタックの大きさが無制限の場合に溢れを検出する範囲
+const UNLIMITED_STACK: usize = 1 << 30;
+
+/// シグナルハンドラ専用のスタックの大きさ
+const SIGNAL_STACK_SIZE: usize = 64 * 1024;
+
+/// メインスレッドのスタックの先頭（`yuni_runtime_init` の呼び出し時のスタックの位置）
+static STACK_TOP: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
+
+/// メインスレッドのスタックの大きさの上限
+static STACK_LIMIT: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
+
+/// 違反したアドレスがスタックの上限の付近か
+///
+/// スタックは下位のアドレスに向かって伸びるため、先頭から上限の大きさだけ下がった位置の
+/// 付近への違反をスタックの溢れとみなします。nullの参照などスタックから離れた違反は含みません。
+fn is_stack_overflow(fault_addr: usize, stack_top: usize, stack_limit: usize) -> bool {
+    stack_top != 0
+        && fault_addr < stack_top
+        && stack_top - fault_addr <= stack_limit.saturating_add(STACK_GUARD_SLACK)
+}
+
+/// Initialize the runtime at the start of `main`
+///
+/// `stack_size` はリンク時に `--stack-size` で指定したバイト数（指定がない場合は0）です。
+/// Linuxのメインスレッドのスタックはリンカの指定ではなく `RLIMIT_STACK` で決まるため、
+/// ソフトリミットが小さければ引き上げます。
+/// その後、スタックの溢れによるSIGSEGV・SIGBUSを報告するシグナルハンドラを専用のスタックに登録します。
+#[no_mangle]
+pub extern "C" fn yuni_runtime_init(stack_size: u64) {
+    let marker = 0u8;
+    let stack_top = &marker as *const u8 as usize;
+    let limit = signals::raise_stack_limit(stack_size as usize).max(stack_size as usize);
+    STACK_TOP.store(stack_top, std::sync::atomic::Ordering::Relaxed);
+    STACK_LIMIT.store(limit, std::sync::atomic::Ordering::Relaxed);
+    signals::install_stack_overflow_handler();
+}
+
+/// シグナルハンドラの登録（libcの構造体はOSごとの配置で宣言する）
+#[cfg(all(any(target_os = "linux", target_os = "macos"), target_pointer_width = "64"))]
+mod signals {
+    use super::*;
+    use std::sync::atomic::Ordering;
+
+    #[cfg(target_os = "linux")]
+    mod sys {
+        use std::os::raw::c_int;
+
+        pub const SIGBUS: c_int = 7;
+        pub const SA_SIGINFO: c_int = 0x4;
+        pub const SA_ONSTACK: c_int = 0x0800_0000;
+        pub const SA_RESETHAND: c_int = 0x8000_0000_u32 as c_int;
+        /// `siginfo_t` の `si_addr` の位置（`si_signo`・`si_errno`・`si_code` と詰め物の後）
+        pub const SI_ADDR_OFFSET: usize = 16;
+
+        #[repr(C)]
+        pub struct SigAction {
+            pub sa_sigaction: usize,
+            pub sa_mask: [u64; 16],
+            pub sa_flags: c_int,
+            pub sa_restorer: usize,
+        }
+
+        #[repr(C)]
+        pub struct StackT {
+            pub ss_sp: *mut u8,
+            pub ss_flags: c_int,
+            pub ss_size: usize,
+        }
+    }
+
+    #[cfg(target_os = "macos")]
+    mod sys {
+        use std::os::raw::c_int;
+
+        pub const SIGBUS: c_int = 10;
+        pub const SA_SIGINFO: c_int = 0x40;
+        pub const SA_ONSTACK: c_int = 0x1;
+        pub const SA_RESETHAND: c_int = 0x4;
+        /// `siginfo_t` の `si_addr` の位置（`si_signo` から `si_status` までの6つのintの後）
+        pub const SI_ADDR_OFFSET: usize = 24;
+
+        #[repr(C)]
+        pub struct SigAction {
+            pub sa_sigaction: usize,
+            pub sa_mask: u32,
+            pub sa_flags: c_int,
+        }
+
+        #[repr(C)]
+        pub struct StackT {
+            pub ss_sp: *mut u8,
+            pub ss_size: usize,
+            pub ss_flags: c_int,
+        }
+    }
+
+    use sys::*;
+
+    const SIGSEGV: c_int = 11;
+    const RLIMIT_STACK: c_int = 3;
+
+    #[repr(C)]
+    struct RLimit {
+        rlim_cur: u64,
+        rlim_max: u64,
+    }
+
+    extern "C" {
+        fn sigaction(signum: c_int, act: *const SigAction, old: *mut SigAction) -> c_int;
+        fn sigaltstack(stack: *const StackT, old: *mut StackT) -> c_int;
+        fn getrlimit(resource: c_int, rlim: *mut RLimit) -> c_int;
+        fn setrlimit(resource: c_int, rlim: *const RLimit) -> c_int;
+        fn write(fd: c_int, buf: *const c_void, count: usize) -> isize;
+        fn _exit(status: c_int) -> !;
+    }
+
+    /// スタックのソフトリミットを `stack_size` まで引き上げ、引き上げた後の上限を返す
+    ///
+    /// ハードリミットを越える分は引き上げません。
+    pub fn raise_stack_limit(stack_size: usize) -> usize {
+        let mut limit = RLimit { rlim_cur: 0, rlim_max: 0 };
+        // SAFETY: `limit` は書き込み可能なrlimit構造体
+        if unsafe { getrlimit(RLIMIT_STACK, &mut limit) } != 0 {
+            return 0;
+        }
+        if (stack_size as u64) > limit.rlim_cur {
+            let raised = RLimit { rlim_cur: (stack_size as u64).min(limit.rlim_max), rlim_max: limit.rlim_max };
+            // SAFETY: `raised` は有効なrlimit構造体
+            if unsafe { setrlimit(RLIMIT_STACK, &raised) } == 0 {
+                limit.rlim_cur = raised.rlim_cur;
+            }
+        }
+        usize::try_from(limit.rlim_cur).unwrap_or(usize::MAX).min(UNLIMITED_STACK)
+    }
+
+    /// SIGSEGVとSIGBUSのハンドラを専用のスタックに登録する
+    ///
+    /// ハンドラは一度呼ばれると既定の動作に戻るため、スタックの溢れ以外の違反では
+    /// ハンドラから戻った後に同じ命令で既定どおりシグナルにより終了します。
+    pub fn install_stack_overflow_handler() {
+        let signal_stack = Box::leak(vec![0u8; SIGNAL_STACK_SIZE].into_boxed_slice());
+        let stack = StackT { ss_sp: signal_stack.as_mut_ptr(), ss_flags: 0, ss_size: SIGNAL_STACK_SIZE };
+        // SAFETY: 0で初期化したsigaction構造体はマスクが空の有効な値
+        let mut action: SigAction = unsafe { std::mem::zeroed() };
+        action.sa_sigaction = handle_fault as extern "C" fn(c_int, *const u8, *mut c_void) as usize;
+        action.sa_flags = SA_SIGINFO | SA_ONSTACK | SA_RESETHAND;
+        // SAFETY: 専用のスタックはプログラムの終了まで解放しない
+        unsafe {
+            if sigaltstack(&stack, ptr::null_mut()) != 0 {
+                return;
+            }
+            sigaction(SIGSEGV, &action, ptr::null_mut());
+            sigaction(SIGBUS, &action, ptr::null_mut());
+        }
+    }
+
+    /// SIGSEGV・SIGBUSのハンドラ
+    ///
+    /// シグナルハンドラの中では非同期シグナル安全な `write` と `_exit` だけを使います。
+    extern "C" fn handle_fault(_signum: c_int, info: *const u8, _context: *mut c_void) {
+        // SAFETY: SA_SIGINFOで登録したハンドラには有効なsiginfo_tが渡される
+        let fault_addr = unsafe { (info.add(SI_ADDR_OFFSET) as *const usize).read_unaligned() };
+        let stack_top = STACK_TOP.load(Ordering::Relaxed);
+        let stack_limit = STACK_LIMIT.load(Ordering::Relaxed);
+        if is_stack_overflow(fault_addr, stack_top, stack_limit) {
+            // SAFETY: writeと_exitは非同期シグナル安全
+            unsafe {
+                write(2, STACK_OVERFLOW_MESSAGE.as_ptr() as *const c_void, STACK_OVERFLOW_MESSAGE.len());
+                _exit(STACK_OVERFLOW_EXIT_CODE);
+            }
+        }
+    }
+}
+
+/// シグナルハンドラを登録できないターゲットでは何もしない
+#[cfg(not(all(any(target_os = "linux", target_os = "macos"), target_pointer_width = "64")))]
+mod signals {
+    pub fn raise_stack_limit(_stack_size: usize) -> usize {
+        0
+    }
+
+    pub fn install_stack_overflow_handler() {}
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -564,6 +1658,18 @@ mod tests {
         assert_eq!(len, 12);
     }
 
+    #[test]
+    fn test_string_from_parts_copies_prefix() {
+        let s = CString::new("Hello, Yuni!").unwrap();
+        unsafe {
+            let whole = yuni_string_from_parts(s.as_ptr(), yuni_str_len(s.as_ptr()));
+            assert_ne!(whole as *const c_char, s.as_ptr(), "the data should be copied");
+            assert_eq!(CString::from_raw(whole).to_str().unwrap(), "Hello, Yuni!");
+            assert_eq!(CString::from_raw(yuni_string_from_parts(s.as_ptr(), 5)).to_str().unwrap(), "Hello");
+            assert_eq!(CString::from_raw(yuni_string_from_parts(std::ptr::null(), 0)).to_str().unwrap(), "");
+        }
+    }
+
     #[test]
     fn test_int_to_str() {
         let ptr = yuni_i64_to_string(42);
@@ -576,4 +1682,585 @@ mod tests {
             let _ = CString::from_raw(ptr);
         }
     }
+
+    /// ビルダーが返したC文字列をRustの文字列として取り出して解放する
+    unsafe fn take_c_string(ptr: *mut c_char) -> String {
+        assert!(!ptr.is_null());
+        CString::from_raw(ptr).into_string().unwrap()
+    }
+
+    #[test]
+    fn test_string_builder_appends_all_kinds() {
+        unsafe {
+            let sb = yuni_sb_new();
+            let text = CString::new("x = ").unwrap();
+            yuni_sb_append_str(sb, text.as_ptr());
+            yuni_sb_append_int(sb, -42);
+            yuni_sb_append_str(sb, CString::new(", y = ").unwrap().as_ptr());
+            yuni_sb_append_float(sb, 2.5);
+            yuni_sb_append_str(sb, CString::new(", ok = ").unwrap().as_ptr());
+            yuni_sb_append_bool(sb, true);
+            assert_eq!(take_c_string(yuni_sb_finish(sb)), "x = -42, y = 2.5, ok = true");
+        }
+    }
+
+    #[test]
+    fn test_string_builder_matches_to_string_functions() {
+        // テンプレート文字列を連結で組み立てた場合と同じ書式になる
+        unsafe {
+            for (int, uint, float) in [(0, 0, 0.0), (i64::MIN, u64::MAX, 1e21), (7, 1 << 63, -0.1)] {
+                let sb = yuni_sb_new();
+                yuni_sb_append_int(sb, int);
+                yuni_sb_append_uint(sb, uint);
+                yuni_sb_append_float(sb, float);
+                let expected = take_c_string(yuni_i64_to_string(int))
+                    + &take_c_string(yuni_u64_to_string(uint))
+                    + &take_c_string(yuni_f64_to_string(float));
+                assert_eq!(take_c_string(yuni_sb_finish(sb)), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn test_float_to_string_is_shortest_round_trip() {
+        // 指数表記を使わず、同じ値に読み戻せる最短の桁数で出力する
+        unsafe {
+            let cases = [
+                (0.1, "0.1"),
+                (1.0, "1"),
+                (1e21, "1000000000000000000000"),
+                (-0.0, "-0"),
+                (f64::NAN, "NaN"),
+                (f64::INFINITY, "inf"),
+                (f64::NEG_INFINITY, "-inf"),
+            ];
+            for (value, expected) in cases {
+                assert_eq!(take_c_string(yuni_f64_to_string(value)), expected);
+            }
+            assert_eq!(take_c_string(yuni_f64_to_string(f64::MAX)).len(), 309);
+            assert_eq!(take_c_string(yuni_f32_to_string(0.1)), "0.1");
+            assert_eq!(take_c_string(yuni_f32_to_string(f32::MAX)), "340282350000000000000000000000000000000");
+
+            let sb = yuni_sb_new();
+            yuni_sb_append_f32(sb, 0.1);
+            yuni_sb_append_float(sb, 0.1);
+            assert_eq!(take_c_string(yuni_sb_finish(sb)), "0.10.1");
+        }
+    }
+
+    #[test]
+    fn test_string_builder_preserves_interior_data_across_growth() {
+        // 再割り当てをまたいでも、既に追加したマルチバイト文字や書式指定子に見える文字列が壊れない
+        let piece = CString::new("añ🦀%s{}\\n\t").unwrap();
+        let piece_str = piece.to_str().unwrap();
+        unsafe {
+            let sb = yuni_sb_new();
+            for i in 0..1000 {
+                yuni_sb_append_str(sb, piece.as_ptr());
+                yuni_sb_append_int(sb, i);
+            }
+            assert_eq!(yuni_sb_len(sb), (0..1000).map(|i: i64| piece_str.len() + i.to_string().len()).sum::<usize>());
+
+            let snapshot = take_c_string(yuni_sb_to_string(sb));
+            let expected: String = (0..1000).map(|i| format!("{}{}", piece_str, i)).collect();
+            assert_eq!(snapshot, expected);
+
+            // 複製を取った後も追加を続けられる
+            yuni_sb_append_str(sb, CString::new("end").unwrap().as_ptr());
+            assert_eq!(take_c_string(yuni_sb_finish(sb)), expected + "end");
+        }
+    }
+
+    #[test]
+    fn test_string_builder_grows_geometrically() {
+        // 1バイトずつ追加しても、再割り当ての回数は長さの対数程度に収まる
+        unsafe {
+            let sb = yuni_sb_new();
+            let byte = CString::new("a").unwrap();
+            let mut capacity = (*sb).buf.capacity();
+            let mut reallocations = 0;
+            for _ in 0..100_000 {
+                yuni_sb_append_str(sb, byte.as_ptr());
+                let new_capacity = (*sb).buf.capacity();
+                if new_capacity != capacity {
+                    assert!(new_capacity >= capacity * 2, "capacity grew from {} to {}", capacity, new_capacity);
+                    capacity = new_capacity;
+                    reallocations += 1;
+                }
+            }
+            assert!(reallocations <= 14, "{} reallocations for 100000 bytes", reallocations);
+            assert_eq!(yuni_sb_len(sb), 100_000);
+            let result = take_c_string(yuni_sb_finish(sb));
+            assert!(result.len() == 100_000 && result.bytes().all(|b| b == b'a'));
+        }
+    }
+
+    #[test]
+    fn test_string_builder_empty_and_null() {
+        unsafe {
+            assert_eq!(take_c_string(yuni_sb_finish(yuni_sb_new())), "");
+            // nullのビルダーや文字列は無視する
+            yuni_sb_append_str(ptr::null_mut(), CString::new("x").unwrap().as_ptr());
+            let sb = yuni_sb_new();
+            yuni_sb_append_str(sb, ptr::null());
+            assert_eq!(yuni_sb_len(sb), 0);
+            assert!(yuni_sb_finish(ptr::null_mut()).is_null());
+            let _ = take_c_string(yuni_sb_finish(sb));
+        }
+    }
+
+    #[test]
+    fn test_bytes_round_trip_through_file() {
+        let path = std::env::temp_dir().join(format!("yuni_runtime_bytes_{}.bin", std::process::id()));
+        let c_path = CString::new(path.to_str().unwrap()).unwrap();
+        let bytes = [b'a', 0, 0xff, b'\n', 0];
+        unsafe {
+            let vec = yuni_vec_from_bytes(bytes.as_ptr(), bytes.len());
+            assert_eq!(yuni_vec_len(vec), bytes.len());
+            assert!(yuni_write_bytes(c_path.as_ptr(), yuni_vec_data(vec), yuni_vec_len(vec)));
+            yuni_vec_free(vec);
+
+            let read = yuni_read_bytes(c_path.as_ptr());
+            assert_eq!(std::slice::from_raw_parts(yuni_vec_data(read), yuni_vec_len(read)), &bytes);
+            yuni_vec_free(read);
+        }
+        let _ = std::fs::remove_file(&path);
+    }
+
+    /// 16バイト境界に整列する構造体（f64を含む構造体と同じ整列要求のパターン）
+    #[repr(C, align(16))]
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    struct Aligned {
+        weight: f64,
+        count: i32,
+    }
+
+    #[test]
+    fn test_vec_preserves_element_alignment() {
+        let size = std::mem::size_of::<Aligned>();
+        let align = std::mem::align_of::<Aligned>();
+        unsafe {
+            let vec = yuni_vec_new(size, align);
+            // 再割り当てをまたぐ数の要素を追加する
+            for i in 0..10 {
+                let element = Aligned { weight: i as f64 * 0.5, count: i };
+                yuni_vec_push(vec, &element as *const Aligned as *const c_void);
+            }
+            assert_eq!(yuni_vec_len(vec), 10);
+
+            for i in 0..10 {
+                let element = yuni_vec_get(vec, i as usize) as *const Aligned;
+                assert_eq!(element as usize % align, 0, "element {} is misaligned", i);
+                assert_eq!(*element, Aligned { weight: i as f64 * 0.5, count: i });
+            }
+            yuni_vec_free(vec);
+        }
+    }
+
+    #[test]
+    fn test_vec_rounds_stride_up_to_alignment() {
+        // サイズがアライメントの倍数でない場合も要素の整列が保たれる
+        unsafe {
+            let vec = yuni_vec_new(12, 8);
+            let bytes = [0xABu8; 12];
+            for _ in 0..5 {
+                yuni_vec_push(vec, bytes.as_ptr() as *const c_void);
+            }
+            for i in 0..5 {
+                let element = yuni_vec_get(vec, i) as *const u8;
+                assert_eq!(element as usize % 8, 0, "element {} is misaligned", i);
+                assert_eq!(std::slice::from_raw_parts(element, 12), &bytes);
+            }
+            yuni_vec_free(vec);
+        }
+    }
+
+    unsafe extern "C" fn compare_i64(a: *const c_void, b: *const c_void) -> i32 {
+        let (a, b) = (*(a as *const i64), *(b as *const i64));
+        (a > b) as i32 - (a < b) as i32
+    }
+
+    unsafe extern "C" fn compare_c_str(a: *const c_void, b: *const c_void) -> i32 {
+        let a = CStr::from_ptr(*(a as *const *const c_char));
+        let b = CStr::from_ptr(*(b as *const *const c_char));
+        a.cmp(b) as i32
+    }
+
+    /// 値を並べたVecを作成する
+    unsafe fn vec_of<T>(values: &[T]) -> *mut YuniVec {
+        let vec = yuni_vec_new(std::mem::size_of::<T>(), std::mem::align_of::<T>());
+        for value in values {
+            yuni_vec_push(vec, value as *const T as *const c_void);
+        }
+        vec
+    }
+
+    /// Vecの要素を取り出す
+    unsafe fn vec_values<T: Copy>(vec: *const YuniVec) -> Vec<T> {
+        (0..yuni_vec_len(vec)).map(|i| *(yuni_vec_get(vec, i) as *const T)).collect()
+    }
+
+    #[test]
+    fn test_vec_resize() {
+        unsafe {
+            let vec = vec_of(&[1i64, 2, 3, 4, 5]);
+            let zero = 0i64;
+            // 縮めると末尾の要素を取り除く
+            yuni_vec_resize(vec, 2, &zero as *const i64 as *const c_void);
+            assert_eq!(vec_values::<i64>(vec), [1, 2]);
+
+            // 伸ばすと増えた要素を埋める（再割り当てをまたぐ長さ）
+            let fill = -7i64;
+            yuni_vec_resize(vec, 9, &fill as *const i64 as *const c_void);
+            assert_eq!(vec_values::<i64>(vec), [1, 2, -7, -7, -7, -7, -7, -7, -7]);
+
+            // 縮めた後に伸ばしても、取り除いた要素は戻らない
+            yuni_vec_resize(vec, 1, &zero as *const i64 as *const c_void);
+            yuni_vec_resize(vec, 3, &zero as *const i64 as *const c_void);
+            assert_eq!(vec_values::<i64>(vec), [1, 0, 0]);
+            yuni_vec_push(vec, &fill as *const i64 as *const c_void);
+            assert_eq!(vec_values::<i64>(vec), [1, 0, 0, -7]);
+            yuni_vec_free(vec);
+        }
+    }
+
+    #[test]
+    fn test_vec_resize_empty_vec_keeps_alignment() {
+        unsafe {
+            let vec = yuni_vec_new(std::mem::size_of::<Aligned>(), std::mem::align_of::<Aligned>());
+            let fill = Aligned { weight: 1.5, count: 3 };
+            yuni_vec_resize(vec, 6, &fill as *const Aligned as *const c_void);
+            assert_eq!(yuni_vec_len(vec), 6);
+            for i in 0..6 {
+                let element = yuni_vec_get(vec, i) as *const Aligned;
+                assert_eq!(element as usize % std::mem::align_of::<Aligned>(), 0);
+                assert_eq!(*element, Aligned { weight: 1.5, count: 3 });
+            }
+            yuni_vec_free(vec);
+        }
+    }
+
+    #[test]
+    fn test_vec_sort_i64() {
+        // 疑似乱数の値（重複と負の値を含む）を並べ替える
+        let mut state = 0x2545F4914F6CDD1Du64;
+        let values: Vec<i64> = (0..1000)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                (state % 2000) as i64 - 1000
+            })
+            .collect();
+        unsafe {
+            let vec = vec_of(&values);
+            yuni_vec_sort(vec, compare_i64);
+            let mut expected = values.clone();
+            expected.sort();
+            assert_eq!(vec_values::<i64>(vec), expected);
+            yuni_vec_free(vec);
+        }
+    }
+
+    #[test]
+    fn test_vec_sort_strings() {
+        let strings: Vec<CString> = ["pear", "apple", "Banana", "apricot", ""]
+            .iter()
+            .map(|s| CString::new(*s).unwrap())
+            .collect();
+        let pointers: Vec<*const c_char> = strings.iter().map(|s| s.as_ptr()).collect();
+        unsafe {
+            let vec = vec_of(&pointers);
+            yuni_vec_sort(vec, compare_c_str);
+            let sorted: Vec<&str> = vec_values::<*const c_char>(vec)
+                .into_iter()
+                .map(|p| CStr::from_ptr(p).to_str().unwrap())
+                .collect();
+            assert_eq!(sorted, ["", "Banana", "apple", "apricot", "pear"]);
+            yuni_vec_free(vec);
+        }
+    }
+
+    #[test]
+    fn test_vec_binary_search() {
+        unsafe {
+            let vec = vec_of(&[1i64, 3, 3, 3, 8, 13]);
+            let search = |key: i64| yuni_vec_binary_search(vec, &key as *const i64 as *const c_void, compare_i64);
+            assert_eq!(search(1), 0);
+            assert_eq!(search(3), 1);
+            assert_eq!(search(13), 5);
+            assert_eq!(search(0), -1);
+            assert_eq!(search(5), -1);
+            assert_eq!(search(14), -1);
+            yuni_vec_free(vec);
+
+            let empty = vec_of::<i64>(&[]);
+            assert_eq!(yuni_vec_binary_search(empty, &1i64 as *const i64 as *const c_void, compare_i64), -1);
+            yuni_vec_free(empty);
+        }
+    }
+
+    #[test]
+    fn test_vec_contains() {
+        unsafe {
+            let vec = vec_of(&[5i64, -2, 9]);
+            let contains = |key: i64| yuni_vec_contains(vec, &key as *const i64 as *const c_void, compare_i64);
+            assert!(contains(5));
+            assert!(contains(-2));
+            assert!(contains(9));
+            assert!(!contains(0));
+            yuni_vec_free(vec);
+
+            let empty = vec_of::<i64>(&[]);
+            assert!(!yuni_vec_contains(empty, &1i64 as *const i64 as *const c_void, compare_i64));
+            yuni_vec_free(empty);
+        }
+    }
+
+    #[test]
+    fn test_hashmap_contains_key() {
+        unsafe {
+            let map = yuni_hashmap_new(8, 4);
+            let (key, value) = (7i64, 1i32);
+            yuni_hashmap_insert(map, &key as *const i64 as *const c_void, &value as *const i32 as *const c_void);
+            assert!(yuni_hashmap_contains_key(map, &key as *const i64 as *const c_void));
+            let missing = 8i64;
+            assert!(!yuni_hashmap_contains_key(map, &missing as *const i64 as *const c_void));
+            yuni_hashmap_free(map);
+        }
+    }
+
+    #[test]
+    fn test_string_contains() {
+        let haystack = CString::new("hello, world").unwrap();
+        let contains = |needle: &str| {
+            let needle = CString::new(needle).unwrap();
+            unsafe { yuni_string_contains(haystack.as_ptr(), needle.as_ptr()) }
+        };
+        assert!(contains("world"));
+        assert!(contains("hello, world"));
+        assert!(contains(""));
+        assert!(!contains("World"));
+        assert!(!contains("hello, world!"));
+    }
+
+    #[test]
+    fn test_string_starts_with() {
+        let s = CString::new("// comment").unwrap();
+        let starts_with = |prefix: &str| {
+            let prefix = CString::new(prefix).unwrap();
+            unsafe { yuni_string_starts_with(s.as_ptr(), prefix.as_ptr()) }
+        };
+        assert!(starts_with("//"));
+        assert!(starts_with("// comment"));
+        // 空の接頭辞はどの文字列にもある
+        assert!(starts_with(""));
+        assert!(!starts_with("comment"));
+        assert!(!starts_with("// comment!"));
+        assert!(unsafe { yuni_string_starts_with(ptr::null(), ptr::null()) });
+    }
+
+    #[test]
+    fn test_string_find() {
+        let s = CString::new("a,b,,c").unwrap();
+        let find = |needle: &str| {
+            let needle = CString::new(needle).unwrap();
+            unsafe { yuni_string_find(s.as_ptr(), needle.as_ptr()) }
+        };
+        assert_eq!(find(","), 1);
+        assert_eq!(find(",,"), 3);
+        assert_eq!(find("c"), 5);
+        // 空の文字列は先頭に見つかる
+        assert_eq!(find(""), 0);
+        // 見つからなければ-1
+        assert_eq!(find(";"), -1);
+        assert_eq!(find("a,b,,c,"), -1);
+    }
+
+    /// 文字列を分割し、要素の文字列を解放して内容を返す
+    fn split(s: &str, separator: &str) -> Vec<String> {
+        let (s, separator) = (CString::new(s).unwrap(), CString::new(separator).unwrap());
+        unsafe {
+            let vec = yuni_string_split(s.as_ptr(), separator.as_ptr());
+            let parts = vec_values::<*mut c_char>(vec)
+                .into_iter()
+                .map(|part| {
+                    let text = CStr::from_ptr(part).to_str().unwrap().to_string();
+                    yuni_free_string(part);
+                    text
+                })
+                .collect();
+            yuni_vec_free(vec);
+            parts
+        }
+    }
+
+    #[test]
+    fn test_string_split() {
+        assert_eq!(split("1,22,333", ","), ["1", "22", "333"]);
+        // 連続する区切りと端の区切りは空の要素になる
+        assert_eq!(split(",a,,b,", ","), ["", "a", "", "b", ""]);
+        assert_eq!(split("a::b", "::"), ["a", "b"]);
+        assert_eq!(split("", ","), [""]);
+        assert_eq!(split("no separator", ","), ["no separator"]);
+        // 空の区切りでは分割しない
+        assert_eq!(split("abc", ""), ["abc"]);
+    }
+
+    #[test]
+    fn test_string_trim() {
+        for (input, expected) in [("  x y \t\n", "x y"), ("x", "x"), (" \n ", ""), ("", "")] {
+            let input = CString::new(input).unwrap();
+            unsafe {
+                let trimmed = yuni_string_trim(input.as_ptr());
+                assert_eq!(CStr::from_ptr(trimmed).to_str().unwrap(), expected);
+                yuni_free_string(trimmed);
+            }
+        }
+    }
+
+    #[test]
+    fn test_hashmap_insert_overwrites_existing_key() {
+        // 同じキーへの挿入は値を上書きし、要素数は増えない
+        unsafe {
+            let map = yuni_hashmap_new(8, 4);
+            let key = 7i64;
+            for value in [1i32, 2, 3] {
+                yuni_hashmap_insert(map, &key as *const i64 as *const c_void, &value as *const i32 as *const c_void);
+            }
+            assert_eq!(yuni_hashmap_len(map), 1);
+            let value = yuni_hashmap_get(map, &key as *const i64 as *const c_void) as *const i32;
+            assert_eq!(*value, 3);
+
+            let missing = 8i64;
+            assert!(yuni_hashmap_get(map, &missing as *const i64 as *const c_void).is_null());
+            yuni_hashmap_free(map);
+        }
+    }
+
+    #[test]
+    fn test_hashmap_string_keys_compare_by_content() {
+        // 文字列のキーはポインタではなく内容で比較し、挿入時に複製される
+        unsafe {
+            let map = yuni_hashmap_new_str(4);
+            let first = CString::new("alice").unwrap();
+            let first_ptr = first.as_ptr();
+            let value = 10i32;
+            yuni_hashmap_insert(map, &first_ptr as *const *const c_char as *const c_void, &value as *const i32 as *const c_void);
+            drop(first);
+
+            let second = CString::new("alice").unwrap();
+            let second_ptr = second.as_ptr();
+            let found = yuni_hashmap_get(map, &second_ptr as *const *const c_char as *const c_void) as *mut i32;
+            assert!(!found.is_null());
+            // 返されたポインタを通して値をその場で変更できる
+            *found += 1;
+            let found = yuni_hashmap_get(map, &second_ptr as *const *const c_char as *const c_void) as *const i32;
+            assert_eq!(*found, 11);
+            assert_eq!(yuni_hashmap_len(map), 1);
+            yuni_hashmap_free(map);
+        }
+    }
+
+    /// 文字列を整数に変換し、失敗した場合はNoneを返す
+    fn parse_i64(text: &str) -> Option<i64> {
+        let s = CString::new(text).unwrap();
+        let mut out = -1i64;
+        unsafe { yuni_parse_i64(s.as_ptr(), &mut out) }.then_some(out)
+    }
+
+    /// 文字列を小数に変換し、失敗した場合はNoneを返す
+    fn parse_f64(text: &str) -> Option<f64> {
+        let s = CString::new(text).unwrap();
+        let mut out = -1.0f64;
+        unsafe { yuni_parse_f64(s.as_ptr(), &mut out) }.then_some(out)
+    }
+
+    #[test]
+    fn test_random_sequence_is_deterministic() {
+        yuni_random_seed(42);
+        let first: Vec<u64> = (0..4).map(|_| yuni_random_u64()).collect();
+        yuni_random_seed(42);
+        let second: Vec<u64> = (0..4).map(|_| yuni_random_u64()).collect();
+        assert_eq!(first, second);
+        assert_eq!(
+            first,
+            [0x31b0_ece7_c4f6_97a2, 0x9008_a3b1_cb68_6f03, 0x7c71_73ab_d97b_e16f, 0x4567_2c8c_8d6b_8c4f]
+        );
+
+        // シード0は初期状態と同じで、内部状態が0にならない
+        yuni_random_seed(0);
+        assert_ne!(yuni_random_u64(), 0);
+    }
+
+    #[test]
+    fn test_time_nanos_is_monotonic_across_sleep() {
+        let before = yuni_time_nanos();
+        yuni_sleep_millis(2);
+        let after = yuni_time_nanos();
+        assert!(after - before >= 2_000_000, "{} -> {}", before, after);
+        assert!(yuni_time_unix_millis() > 1_600_000_000_000);
+        // 0以下の値では待たない
+        yuni_sleep_millis(-1);
+    }
+
+    #[test]
+    fn test_parse_i64() {
+        assert_eq!(parse_i64("42"), Some(42));
+        assert_eq!(parse_i64("-17"), Some(-17));
+        assert_eq!(parse_i64("+5"), Some(5));
+        // 前後の空白は無視する
+        assert_eq!(parse_i64("  12\t\r\n"), Some(12));
+        assert_eq!(parse_i64("9223372036854775807"), Some(i64::MAX));
+        assert_eq!(parse_i64("-9223372036854775808"), Some(i64::MIN));
+    }
+
+    #[test]
+    fn test_parse_i64_rejects_invalid_input() {
+        // 文字列全体が整数でなければ失敗し、出力先は変更しない
+        assert_eq!(parse_i64("12abc"), None);
+        assert_eq!(parse_i64("1 2"), None);
+        assert_eq!(parse_i64("1.5"), None);
+        assert_eq!(parse_i64(""), None);
+        assert_eq!(parse_i64("   "), None);
+        assert_eq!(parse_i64("-"), None);
+        // 桁あふれは丸めずに失敗とする
+        assert_eq!(parse_i64("9223372036854775808"), None);
+        assert_eq!(parse_i64("-9223372036854775809"), None);
+
+        let mut out = 7i64;
+        assert!(!unsafe { yuni_parse_i64(std::ptr::null(), &mut out) });
+        assert_eq!(out, 7);
+    }
+
+    #[test]
+    fn test_parse_f64() {
+        assert_eq!(parse_f64("1.5"), Some(1.5));
+        assert_eq!(parse_f64(" -2 "), Some(-2.0));
+        assert_eq!(parse_f64("3e8"), Some(3e8));
+        assert_eq!(parse_f64(".25"), Some(0.25));
+
+        assert_eq!(parse_f64("1.5x"), None);
+        assert_eq!(parse_f64(""), None);
+        assert_eq!(parse_f64("inf"), None);
+        assert_eq!(parse_f64("NaN"), None);
+        // f64の範囲を超える値は無限大にせず失敗とする
+        assert_eq!(parse_f64("1e400"), None);
+    }
+
+    #[test]
+    fn test_is_stack_overflow() {
+        let top = 0x7fff_0000_0000;
+        let limit = 8 << 20;
+        // 上限の付近（上限のすぐ外側も含む）への違反はスタックの溢れ
+        assert!(is_stack_overflow(top - limit, top, limit));
+        assert!(is_stack_overflow(top - limit - 4096, top, limit));
+        assert!(is_stack_overflow(top - 4096, top, limit));
+        // nullの参照やスタックから離れたアドレスは含まない
+        assert!(!is_stack_overflow(0, top, limit));
+        assert!(!is_stack_overflow(top - limit - (2 << 20), top, limit));
+        assert!(!is_stack_overflow(top + 4096, top, limit));
+        // 初期化されていない場合は判定しない
+        assert!(!is_stack_overflow(0x1000, 0, limit));
+    }
 }
@@ -0,0 +1,53 @@
+//! ソースファイルの読み込み
+//!
+//! コマンドラインから指定されたファイルを読み、存在しない・権限がない・ディレクトリである・
+//! UTF-8でないといった失敗を、パスを含む `YuniError::Io` として報告します。
+//! すべてのサブコマンドは `CompilationState::new` を通してここでファイルを読みます。
+//!
+//! 先頭のBOMは字句解析の前に取り除くため、診断の位置はBOMを除いたソースコードの位置です。
+
+use std::fs;
+use std::io::ErrorKind;
+use std::path::Path;
+
+use crate::deps::SOURCE_EXTENSION;
+use crate::error::{YuniError, YuniResult};
+
+/// UTF-8のBOM
+const UTF8_BOM: &[u8] = b"\xEF\xBB\xBF";
+
+/// ソースファイルを読み、先頭のBOMを取り除いた内容を返す
+pub fn read_source(path: &Path) -> YuniResult<String> {
+    if path.is_dir() {
+        return Err(YuniError::Io(format!(
+            "{} is a directory, not a source file",
+            path.display()
+        )));
+    }
+    let bytes = fs::read(path).map_err(|e| {
+        YuniError::Io(match e.kind() {
+            ErrorKind::NotFound => format!("Source file not found: {}", path.display()),
+            ErrorKind::PermissionDenied => {
+                format!("Permission denied reading source file: {}", path.display())
+            }
+            _ => format!("Failed to read source file {}: {}", path.display(), e),
+        })
+    })?;
+
+    let (bom_len, content) = match bytes.strip_prefix(UTF8_BOM) {
+        Some(content) => (UTF8_BOM.len(), content.to_vec()),
+        None => (0, bytes),
+    };
+    String::from_utf8(content).map_err(|e| {
+        YuniError::Io(format!(
+            "Source file {} is not valid UTF-8: invalid byte sequence at byte offset {}; check that the file is saved with UTF-8 encoding",
+            path.display(),
+            bom_len + e.utf8_error().valid_up_to()
+        ))
+    })
+}
+
+/// 拡張子が `.yuni` かどうか（拡張子が違っていてもコンパイルはする）
+pub fn has_source_extension(path: &Path) -> bool {
+    path.extension().is_some_and(|extension| extension == SOURCE_EXTENSION)
+}
@@ -0,0 +1,250 @@
+//! エディタ連携用のシンボルインデックス
+//!
+//! パース済みのプログラムを走査し、トップレベルの定義（関数・メソッド・型と
+//! そのフィールド・バリアント）を種類・名前・シグネチャ・ソース上の位置とともに列挙します。
+//! `yunilang check --json-symbols` はこの一覧をJSONとして出力します。
+
+use serde::Serialize;
+
+use crate::analyzer::display_type;
+use crate::ast::*;
+
+/// シンボルの種類
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SymbolKind {
+    Function,
+    Method,
+    Struct,
+    Enum,
+    TypeAlias,
+    Field,
+    Variant,
+}
+
+/// ソース上の位置（行・列とも1始まり、列は文字単位）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Position {
+    pub line: usize,
+    pub column: usize,
+}
+
+/// シンボル1件
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Symbol {
+    pub kind: SymbolKind,
+    pub name: String,
+    /// 表示用のシグネチャ
+    pub detail: String,
+    /// メソッドのレシーバー型、フィールド・バリアントの属する型
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub container: Option<String>,
+    pub start: Position,
+    pub end: Position,
+}
+
+/// ファイル1つ分のシンボルインデックス
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SymbolIndex {
+    pub file: String,
+    /// セマンティック解析がエラーなく完了したか
+    ///
+    /// 解析に失敗した場合も、パースできた定義はすべて列挙します。
+    pub analyzed: bool,
+    pub symbols: Vec<Symbol>,
+}
+
+impl SymbolIndex {
+    /// プログラムの定義を列挙してインデックスを作成
+    pub fn build(file: &str, source: &str, program: &Program, analyzed: bool) -> Self {
+        let lines = LineIndex::new(source);
+        let mut symbols = Vec::new();
+        for item in &program.items {
+            collect_item(item, &lines, &mut symbols);
+        }
+        Self {
+            file: file.to_string(),
+            analyzed,
+            symbols,
+        }
+    }
+
+    /// JSON文字列に変換
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("symbol index is always serializable")
+    }
+}
+
+fn collect_item(item: &Item, lines: &LineIndex, symbols: &mut Vec<Symbol>) {
+    match item {
+        Item::Function(func) => {
+            let detail = function_signature(func);
+            symbols.push(lines.symbol(SymbolKind::Function, &func.name, detail, None, func.span));
+        }
+        Item::Method(method) => {
+            let detail = method_signature(method);
+            let container = receiver_type_name(&method.receiver.ty);
+            symbols.push(lines.symbol(SymbolKind::Method, &method.name, detail, container, method.span));
+        }
+        Item::TypeDef(TypeDef::Struct(struct_def)) => {
+            let detail = format!("type {}{} struct", struct_def.name, type_params(&struct_def.type_params));
+            symbols.push(lines.symbol(SymbolKind::Struct, &struct_def.name, detail, None, struct_def.span));
+            for field in &struct_def.fields {
+                let detail = format!("{}: {}", field.name, display_type(&field.ty));
+                let container = Some(struct_def.name.clone());
+                symbols.push(lines.symbol(SymbolKind::Field, &field.name, detail, container, field.span));
+            }
+        }
+        Item::TypeDef(TypeDef::Enum(enum_def)) => {
+            let detail = format!("type {}{} enum", enum_def.name, type_params(&enum_def.type_params));
+            symbols.push(lines.symbol(SymbolKind::Enum, &enum_def.name, detail, None, enum_def.span));
+            for variant in &enum_def.variants {
+                let detail = if variant.fields.is_empty() {
+                    variant.name.clone()
+                } else {
+                    let fields = join(variant.fields.iter().map(|f| format!("{}: {}", f.name, display_type(&f.ty))));
+                    format!("{} {{ {} }}", variant.name, fields)
+                };
+                let container = Some(enum_def.name.clone());
+                symbols.push(lines.symbol(SymbolKind::Variant, &variant.name, detail, container, variant.span));
+            }
+        }
+        Item::TypeDef(TypeDef::Alias(alias)) => {
+            let detail = format!(
+                "type {}{} {}",
+                alias.name,
+                type_params(&alias.type_params),
+                display_type(&alias.underlying_type)
+            );
+            symbols.push(lines.symbol(SymbolKind::TypeAlias, &alias.name, detail, None, alias.span));
+        }
+        Item::ExternFunction(func) => {
+            let detail = extern_function_signature(func);
+            symbols.push(lines.symbol(SymbolKind::Function, &func.name, detail, None, func.span));
+        }
+    }
+}
+
+/// 関数のシグネチャ（`pub fn add(a: i32, b: i32): i32`）
+pub(crate) fn function_signature(func: &FunctionDecl) -> String {
+    format!(
+        "{}{}fn {}{}({}){}",
+        visibility(func.is_public),
+        func.inline.prefix(),
+        func.name,
+        type_params(&func.type_params),
+        join(func.params.iter().map(param)),
+        return_type(&func.return_type)
+    )
+}
+
+/// 外部関数のシグネチャ（`extern fn sqrt(x: f64): f64`）
+pub(crate) fn extern_function_signature(func: &ExternFunctionDecl) -> String {
+    format!(
+        "{}extern fn {}({}){}",
+        visibility(func.is_public),
+        func.name,
+        join(func.params.iter().map(param)),
+        return_type(&func.return_type)
+    )
+}
+
+/// メソッドのシグネチャ（`pub impl fn len(self: &Point): f64`）
+pub(crate) fn method_signature(method: &MethodDecl) -> String {
+    let receiver = &method.receiver;
+    let mut params = vec![format!(
+        "{}{}: {}",
+        if receiver.is_mut { "mut " } else { "" },
+        receiver.name.as_deref().unwrap_or("self"),
+        display_type(&receiver.ty)
+    )];
+    params.extend(method.params.iter().map(param));
+    format!(
+        "{}{}impl fn {}{}({}){}",
+        visibility(method.is_public),
+        method.inline.prefix(),
+        method.name,
+        type_params(&method.type_params),
+        params.join(", "),
+        return_type(&method.return_type)
+    )
+}
+
+/// メソッドのレシーバー型の名前（参照とジェネリクスの型引数は取り除く）
+pub(crate) fn receiver_type_name(ty: &Type) -> Option<String> {
+    match ty {
+        Type::Reference(inner, _) => receiver_type_name(inner),
+        Type::UserDefined(name) | Type::Generic(name, _) => Some(name.clone()),
+        other => Some(display_type(other)),
+    }
+}
+
+fn visibility(is_public: bool) -> &'static str {
+    if is_public {
+        "pub "
+    } else {
+        ""
+    }
+}
+
+fn type_params(params: &[TypeParam]) -> String {
+    if params.is_empty() {
+        String::new()
+    } else {
+        format!("<{}>", join(params.iter().map(|p| p.name.clone())))
+    }
+}
+
+fn param(param: &Param) -> String {
+    let prefix = if param.is_mut { "mut " } else { "" };
+    format!("{}{}: {}", prefix, param.name, display_type(&param.ty))
+}
+
+fn return_type(ty: &Option<Box<Type>>) -> String {
+    ty.as_ref().map(|ty| format!(": {}", display_type(ty))).unwrap_or_default()
+}
+
+fn join(items: impl Iterator<Item = String>) -> String {
+    items.collect::<Vec<_>>().join(", ")
+}
+
+/// バイトオフセットから行・列への変換表
+pub(crate) struct LineIndex<'a> {
+    source: &'a str,
+    /// 各行の先頭のバイトオフセット
+    line_starts: Vec<usize>,
+}
+
+impl<'a> LineIndex<'a> {
+    pub(crate) fn new(source: &'a str) -> Self {
+        let line_starts = std::iter::once(0)
+            .chain(source.match_indices('\n').map(|(i, _)| i + 1))
+            .collect();
+        Self { source, line_starts }
+    }
+
+    pub(crate) fn position(&self, offset: usize) -> Position {
+        let offset = offset.min(self.source.len());
+        let line = self.line_starts.partition_point(|&start| start <= offset) - 1;
+        let line_start = self.line_starts[line];
+        let column = self
+            .source
+            .get(line_start..offset)
+            .map_or(offset - line_start, |text| text.chars().count());
+        Position {
+            line: line + 1,
+            column: column + 1,
+        }
+    }
+
+    fn symbol(&self, kind: SymbolKind, name: &str, detail: String, container: Option<String>, span: Span) -> Symbol {
+        Symbol {
+            kind,
+            name: name.to_string(),
+            detail,
+            container,
+            start: self.position(span.start),
+            end: self.position(span.end),
+        }
+    }
+}
@@ -0,0 +1,289 @@
+//! 実行ファイルの生成に使う外部ツールチェーン
+//!
+//! 実行ファイルはllcでプログラムをオブジェクトファイルにし、rustcでランタイムを
+//! 静的ライブラリにして、Cコンパイラのドライバでリンクして作ります。
+//! ファイル名の規則・ツールの場所・リンクの引数はターゲットごとに異なるため、
+//! ここでターゲットを表す型にまとめます。
+
+use std::ffi::{OsStr, OsString};
+use std::path::{Path, PathBuf};
+
+/// ターゲットのOS
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TargetOs {
+    Linux,
+    MacOs,
+    Windows,
+    Other,
+}
+
+/// ターゲットのABI（WindowsではMSVCとMinGWで必要なライブラリが異なる）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TargetEnv {
+    Gnu,
+    Msvc,
+}
+
+/// Windowsでランタイム（Rustの標準ライブラリ）が必要とするシステムライブラリ
+const WINDOWS_SYSTEM_LIBS: [&str; 7] = ["kernel32", "advapi32", "bcrypt", "ntdll", "userenv", "ws2_32", "dbghelp"];
+
+/// リンクの対象
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Target {
+    pub os: TargetOs,
+    pub env: TargetEnv,
+}
+
+impl Target {
+    /// コンパイラ自身が動いているターゲット
+    pub fn host() -> Self {
+        let os = if cfg!(target_os = "windows") {
+            TargetOs::Windows
+        } else if cfg!(target_os = "macos") {
+            TargetOs::MacOs
+        } else if cfg!(target_os = "linux") {
+            TargetOs::Linux
+        } else {
+            TargetOs::Other
+        };
+        let env = if cfg!(target_env = "msvc") { TargetEnv::Msvc } else { TargetEnv::Gnu };
+        Self { os, env }
+    }
+
+    pub fn is_windows(&self) -> bool {
+        self.os == TargetOs::Windows
+    }
+
+    /// 実行ファイルの拡張子（拡張子がない場合は空文字列）
+    pub fn executable_extension(&self) -> &'static str {
+        if self.is_windows() {
+            "exe"
+        } else {
+            ""
+        }
+    }
+
+    /// オブジェクトファイルの拡張子
+    pub fn object_extension(&self) -> &'static str {
+        if self.is_windows() && self.env == TargetEnv::Msvc {
+            "obj"
+        } else {
+            "o"
+        }
+    }
+
+    /// 静的ライブラリのファイル名
+    pub fn static_library_name(&self, name: &str) -> String {
+        if self.is_windows() && self.env == TargetEnv::Msvc {
+            format!("{}.lib", name)
+        } else {
+            format!("lib{}.a", name)
+        }
+    }
+
+    /// 出力先の指定がないときの実行ファイルのパス（`file.yuni` → `file` / `file.exe`）
+    pub fn default_executable_path(&self, input: &Path) -> PathBuf {
+        input.with_extension(self.executable_extension())
+    }
+
+    /// 拡張子のないパスに実行ファイルの拡張子を付ける
+    pub fn executable_path(&self, path: &Path) -> PathBuf {
+        if self.is_windows() && path.extension().is_none() {
+            path.with_extension(self.executable_extension())
+        } else {
+            path.to_path_buf()
+        }
+    }
+
+    /// ツールの実行ファイル名（Windowsでは `.exe` を付ける）
+    pub fn tool_file_name(&self, tool: &str) -> String {
+        if self.is_windows() {
+            format!("{}.exe", tool)
+        } else {
+            tool.to_string()
+        }
+    }
+
+    /// PATHのほかに探すLLVMのインストール先の候補
+    ///
+    /// `program_files` にはWindowsの `ProgramFiles` 環境変数の値を渡します。
+    pub fn tool_install_candidates(&self, tool: &str, program_files: Option<&OsStr>) -> Vec<PathBuf> {
+        match self.os {
+            TargetOs::MacOs => [
+                "/opt/homebrew/opt/llvm@18/bin",
+                "/usr/local/opt/llvm@18/bin",
+                "/opt/homebrew/Cellar/llvm@18/18.1.8/bin",
+                "/opt/homebrew/bin",
+            ]
+            .iter()
+            .map(|dir| Path::new(dir).join(tool))
+            .collect(),
+            TargetOs::Linux => ["/usr/bin", "/usr/local/bin"]
+                .iter()
+                .flat_map(|dir| [Path::new(dir).join(format!("{}-18", tool)), Path::new(dir).join(tool)])
+                .collect(),
+            TargetOs::Windows => {
+                let mut dirs: Vec<PathBuf> = program_files
+                    .map(|dir| Path::new(dir).join("LLVM").join("bin"))
+                    .into_iter()
+                    .collect();
+                let default_dir = PathBuf::from(r"C:\Program Files\LLVM\bin");
+                if !dirs.contains(&default_dir) {
+                    dirs.push(default_dir);
+                }
+                dirs.into_iter().map(|dir| dir.join(self.tool_file_name(tool))).collect()
+            }
+            TargetOs::Other => Vec::new(),
+        }
+    }
+
+    /// ツールをPATHとインストール先の候補から探す
+    ///
+    /// `path_var` と `program_files` にはそれぞれ `PATH`・`ProgramFiles` 環境変数の値を渡します。
+    pub fn find_tool(&self, tool: &str, path_var: Option<&OsStr>, program_files: Option<&OsStr>) -> Option<PathBuf> {
+        let file_name = self.tool_file_name(tool);
+        let in_path = path_var
+            .into_iter()
+            .flat_map(std::env::split_paths)
+            .map(|dir| dir.join(&file_name));
+        in_path
+            .chain(self.tool_install_candidates(tool, program_files))
+            .find(|path| path.is_file())
+    }
+
+    /// ランタイムを静的ライブラリにするときのrustcの追加引数
+    ///
+    /// MSVCのclang・clang-clは既定で静的CRT（libcmt）をリンクするため、ランタイムもそれに合わせます。
+    pub fn runtime_rustc_args(&self) -> Vec<String> {
+        if self.is_windows() && self.env == TargetEnv::Msvc {
+            vec!["-Ctarget-feature=+crt-static".to_string()]
+        } else {
+            Vec::new()
+        }
+    }
+
+    /// リンカを探す
+    ///
+    /// clangを優先し、WindowsではMSVC互換のドライバであるclang-clも探します。
+    pub fn find_linker(&self, path_var: Option<&OsStr>, program_files: Option<&OsStr>) -> Option<Linker> {
+        let mut names = vec!["clang"];
+        if self.is_windows() && self.env == TargetEnv::Msvc {
+            names.push("clang-cl");
+        }
+        names
+            .into_iter()
+            .find_map(|name| self.find_tool(name, path_var, program_files))
+            .map(Linker::from_program)
+    }
+
+    /// リンカに渡す引数
+    pub fn link_args(&self, linker: &Linker, output: &Path, inputs: &[&Path]) -> Vec<OsString> {
+        let mut args: Vec<OsString> = Vec::new();
+        match linker.flavor {
+            LinkerFlavor::ClangCl => {
+                args.push("/nologo".into());
+                let mut out = OsString::from("/Fe");
+                out.push(output);
+                args.push(out);
+                args.extend(inputs.iter().map(|input| input.as_os_str().to_os_string()));
+                args.push("/link".into());
+                args.extend(WINDOWS_SYSTEM_LIBS.iter().map(|lib| OsString::from(format!("{}.lib", lib))));
+            }
+            LinkerFlavor::Gcc => {
+                args.push("-o".into());
+                args.push(output.as_os_str().to_os_string());
+                args.extend(inputs.iter().map(|input| input.as_os_str().to_os_string()));
+                match self.os {
+                    TargetOs::Windows => {
+                        args.extend(WINDOWS_SYSTEM_LIBS.iter().map(|lib| OsString::from(format!("-l{}", lib))));
+                    }
+                    TargetOs::MacOs => {
+                        args.extend(["-lm", "-lpthread", "-framework", "System", "-lc++"].map(OsString::from));
+                    }
+                    TargetOs::Linux => {
+                        args.extend(["-lm", "-lpthread", "-lstdc++"].map(OsString::from));
+                    }
+                    TargetOs::Other => {
+                        args.extend(["-lm", "-lpthread"].map(OsString::from));
+                    }
+                }
+            }
+        }
+        args
+    }
+
+    /// 使われない関数をリンク時に取り除く引数
+    ///
+    /// 関数ごとのセクション（Mach-Oではシンボル）のうち、`main` から辿れないものを取り除きます。
+    /// リンカ引数の後に追加します。clang-clでは `/link` の後に置くリンカの設定になります。
+    pub fn gc_sections_args(&self, linker: &Linker) -> Vec<OsString> {
+        let arg = match (linker.flavor, self.os) {
+            (LinkerFlavor::ClangCl, _) => "/OPT:REF",
+            (LinkerFlavor::Gcc, TargetOs::MacOs) => "-Wl,-dead_strip",
+            (LinkerFlavor::Gcc, TargetOs::Linux | TargetOs::Windows) => "-Wl,--gc-sections",
+            (LinkerFlavor::Gcc, TargetOs::Other) => return Vec::new(),
+        };
+        vec![OsString::from(arg)]
+    }
+
+    /// メインスレッドのスタックの大きさを `stack_size` バイトにする引数（`--stack-size`）
+    ///
+    /// リンカ引数の後に追加します。macOSのld64はページの大きさ（16KiB）の倍数を16進数で受け付けるため切り上げます。
+    /// Linuxのメインスレッドのスタックは実行時の `RLIMIT_STACK` で決まるため、
+    /// 実行ファイルの `PT_GNU_STACK` に記録するほか、ランタイムの初期化でリミットを引き上げます。
+    pub fn stack_size_args(&self, linker: &Linker, stack_size: u64) -> Vec<OsString> {
+        let arg = match (linker.flavor, self.os) {
+            (LinkerFlavor::ClangCl, _) => format!("/STACK:{}", stack_size),
+            (LinkerFlavor::Gcc, TargetOs::MacOs) => format!("-Wl,-stack_size,{:#x}", stack_size.div_ceil(0x4000) * 0x4000),
+            (LinkerFlavor::Gcc, TargetOs::Linux) => format!("-Wl,-z,stack-size={}", stack_size),
+            (LinkerFlavor::Gcc, TargetOs::Windows) => format!("-Wl,--stack,{}", stack_size),
+            (LinkerFlavor::Gcc, TargetOs::Other) => return Vec::new(),
+        };
+        vec![OsString::from(arg)]
+    }
+
+    /// 外部関数の宣言で指定したライブラリをリンクする引数（`link("m")` は `-lm`）
+    ///
+    /// リンカ引数の末尾に追加します。clang-clでは `/link` の後に置くライブラリのファイル名になります。
+    pub fn library_args(&self, linker: &Linker, libraries: &[String]) -> Vec<OsString> {
+        libraries
+            .iter()
+            .map(|library| match linker.flavor {
+                LinkerFlavor::ClangCl => OsString::from(format!("{}.lib", library)),
+                LinkerFlavor::Gcc => OsString::from(format!("-l{}", library)),
+            })
+            .collect()
+    }
+}
+
+/// リンカのコマンドライン引数の形式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LinkerFlavor {
+    /// clang・gccと同じ形式（`-o out -lfoo`）
+    Gcc,
+    /// clang-cl・clと同じMSVC形式（`/Feout foo.lib`）
+    ClangCl,
+}
+
+/// リンクに使うCコンパイラのドライバ
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Linker {
+    pub program: PathBuf,
+    pub flavor: LinkerFlavor,
+}
+
+impl Linker {
+    /// 実行ファイル名から引数の形式を判定する
+    pub fn from_program(program: PathBuf) -> Self {
+        let stem = program
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_ascii_lowercase())
+            .unwrap_or_default();
+        let flavor = if stem == "clang-cl" || stem == "cl" {
+            LinkerFlavor::ClangCl
+        } else {
+            LinkerFlavor::Gcc
+        };
+        Self { program, flavor }
+    }
+}
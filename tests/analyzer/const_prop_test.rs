@@ -0,0 +1,292 @@
+//! 定数の伝播による常に偽になる条件・型の範囲により結果の変わらない比較の警告と、マップリテラルのキーの重複のテスト
+
+use super::*;
+
+#[test]
+fn test_debug_flag_branch_never_taken() {
+    // falseで初期化したフラグとの && は右辺を呼ばずに偽になる
+    let source = r#"
+    package main
+
+    fn expensive_check(): bool {
+        return true;
+    }
+
+    fn main() {
+        let DEBUG: bool = false;
+        if DEBUG && expensive_check() {
+            println("debug");
+        }
+    }
+    "#;
+
+    let warnings = analyze_warnings(source);
+    assert_eq!(warnings.len(), 1, "{:?}", warnings);
+    assert!(matches!(
+        &warnings[0],
+        AnalyzerError::BranchNeverTaken { span, sources, .. }
+            if &source[span.start..span.end] == "DEBUG && expensive_check()"
+                && sources.len() == 1
+                && sources[0].name == "DEBUG"
+                && sources[0].value == "false"
+                && &source[sources[0].span.start..sources[0].span.end] == "let DEBUG: bool = false;"
+    ), "{:?}", warnings[0]);
+}
+
+#[test]
+fn test_literal_scrutinee_impossible_guard() {
+    // match対象が3のとき、n > 10 のガードは常に偽
+    let source = r#"
+    package main
+
+    fn main() {
+        let label = match 3 {
+            n if n > 10 => "big",
+            _ => "small",
+        };
+        println(label);
+    }
+    "#;
+
+    let warnings = analyze_warnings(source);
+    assert_eq!(warnings.len(), 1, "{:?}", warnings);
+    assert!(matches!(
+        &warnings[0],
+        AnalyzerError::MatchArmNeverTaken { span, sources, .. }
+            if &source[span.start..span.end] == "n > 10"
+                && sources.len() == 1
+                && sources[0].value == "3"
+                && &source[sources[0].span.start..sources[0].span.end] == "3"
+    ), "{:?}", warnings[0]);
+}
+
+#[test]
+fn test_propagated_arithmetic() {
+    // 定数の変数から計算した値も伝播する
+    let source = r#"
+    package main
+
+    fn main() {
+        let limit = 4;
+        let doubled = limit * 2;
+        if doubled > 100 {
+            println("never");
+        }
+    }
+    "#;
+
+    let warnings = analyze_warnings(source);
+    assert_eq!(warnings.len(), 1, "{:?}", warnings);
+    assert!(matches!(
+        &warnings[0],
+        AnalyzerError::BranchNeverTaken { sources, .. } if sources[0].name == "doubled" && sources[0].value == "8"
+    ), "{:?}", warnings[0]);
+}
+
+#[test]
+fn test_mutation_invalidates_propagation() {
+    // 代入・&mut の借用・ループでの変更の後は値を追跡しない
+    let source = r#"
+    package main
+
+    fn enable(flag: &mut bool) {
+        *flag = true;
+    }
+
+    fn main() {
+        let mut assigned = false;
+        assigned = true;
+        if assigned {
+            println("assigned");
+        }
+
+        let mut borrowed = false;
+        enable(&mut borrowed);
+        if borrowed {
+            println("borrowed");
+        }
+
+        let mut i = 0;
+        while i < 10 {
+            if i == 5 {
+                println("five");
+            }
+            i = i + 1;
+        }
+
+        let mut n = 3;
+        n = 20;
+        let label = match n {
+            m if m > 10 => "big",
+            _ => "small",
+        };
+        println(label);
+    }
+    "#;
+
+    let warnings = analyze_warnings(source);
+    assert!(warnings.is_empty(), "{:?}", warnings);
+}
+
+#[test]
+fn test_literal_conditions_and_unknown_values_are_silent() {
+    // リテラルだけの条件と、値のわからない変数を含む条件は警告しない
+    let source = r#"
+    package main
+
+    fn main() {
+        if false {
+            println("disabled");
+        }
+        let n = 3;
+        match n * 1000000000 {
+            m if m > 10 => println("overflow"),
+            _ => println("small"),
+        }
+    }
+    "#;
+
+    let warnings = analyze_warnings(source);
+    assert!(warnings.is_empty(), "{:?}", warnings);
+}
+
+#[test]
+fn test_duplicate_string_map_keys() {
+    // 同じ文字列リテラルのキーは、両方の位置を示すエラーになる
+    let source = r#"
+    package main
+
+    fn main() {
+        let prices = HashMap<String, i32> { "apple": 100, "pear": 80, "apple": 120 };
+    }
+    "#;
+
+    let first = source.find("\"apple\"").unwrap();
+    let second = first + 1 + source[first + 1..].find("\"apple\"").unwrap();
+    assert_specific_error(source, |e| {
+        matches!(e, AnalyzerError::DuplicateMapKey { key, span, previous }
+            if key == "\"apple\"" && span.start == second && previous.start == first)
+    });
+}
+
+#[test]
+fn test_duplicate_folded_map_keys() {
+    // リテラルだけから計算できるキーは、計算した値で比較する
+    let source = r#"
+    package main
+
+    fn main() {
+        let names = HashMap<i32, String> { 1 + 1: "two", 2: "deux" };
+    }
+    "#;
+
+    assert_specific_error(source, |e| {
+        matches!(e, AnalyzerError::DuplicateMapKey { key, span, previous }
+            if key == "2" && &source[span.start..span.end] == "2" && &source[previous.start..previous.end] == "1 + 1")
+    });
+}
+
+#[test]
+fn test_distinct_and_non_constant_map_keys() {
+    // 異なるキーと、変数を含むキーはエラーにならない
+    let source = r#"
+    package main
+
+    fn main() {
+        let n: i32 = 2;
+        let names = HashMap<i32, String> { 1: "one", 2: "two", n: "n", n: "again" };
+        let prices = HashMap<String, i32> { "apple": 100, "pear": 80 };
+    }
+    "#;
+
+    let warnings = analyze_warnings(source);
+    assert!(warnings.is_empty(), "{:?}", warnings);
+}
+
+/// 警告のメッセージと、警告の位置のソースコード
+fn warning_texts(source: &str) -> Vec<(String, &str)> {
+    analyze_warnings(source)
+        .iter()
+        .map(|warning| match warning {
+            AnalyzerError::ComparisonAlwaysConstant { span, .. } => (warning.to_string(), &source[span.start..span.end]),
+            other => panic!("Unexpected warning: {:?}", other),
+        })
+        .collect()
+}
+
+#[test]
+fn test_unsigned_comparison_with_zero() {
+    // 符号なし整数と0以下の定数の比較は結果が変わらない
+    let source = r#"
+    package main
+
+    fn check(u8_value: u8, x: u32): bool {
+        let negative = u8_value < 0;
+        let non_negative = x >= 0;
+        return negative || non_negative;
+    }
+
+    fn main() {
+    }
+    "#;
+
+    assert_eq!(
+        warning_texts(source),
+        [
+            ("比較 u8_value < 0 は u8 の値の範囲のため常に false です".to_string(), "u8_value < 0"),
+            ("比較 x >= 0 は u32 の値の範囲のため常に true です".to_string(), "x >= 0"),
+        ]
+    );
+}
+
+#[test]
+fn test_comparison_with_constant_out_of_type_range() {
+    // 型の範囲外の定数との比較（接尾辞のない範囲外のリテラルはそれ自体がエラー）
+    let source = r#"
+    package main
+
+    fn check(byte: u8, small: i8): bool {
+        let limit: u16 = 300;
+        let above = byte > limit;
+        let wide = byte > 300u16;
+        let below = -200i16 < small;
+        return above || wide || below;
+    }
+
+    fn main() {
+    }
+    "#;
+
+    let warnings = warning_texts(source);
+    assert_eq!(warnings.len(), 3, "{:?}", warnings);
+    assert_eq!(warnings[0].0, "比較 byte > limit は u8 の値の範囲のため常に false です");
+    assert_eq!(warnings[1], ("比較 byte > 300u16 は u8 の値の範囲のため常に false です".to_string(), "byte > 300u16"));
+    assert_eq!(warnings[2].0, "比較 -200i16 < small は i8 の値の範囲のため常に true です");
+    assert!(matches!(
+        &analyze_warnings(source)[0],
+        AnalyzerError::ComparisonAlwaysConstant { comparison, range, .. } if comparison == "byte > limit" && range == "0..=255"
+    ));
+}
+
+#[test]
+fn test_comparison_within_type_range_is_silent() {
+    // 範囲内の定数との比較、符号付き整数と0の比較、キャストした値の比較は警告しない
+    let source = r#"
+    package main
+
+    fn check(byte: u8, small: i8): bool {
+        let a = byte < 10;
+        let b = byte <= 0;
+        let c = byte == 255;
+        let d = small < 0;
+        let e = byte as i32 < 0;
+        return a || b || c || d || e;
+    }
+
+    fn main() {
+    }
+    "#;
+
+    let warnings = analyze_warnings(source);
+    assert!(warnings.is_empty(), "{:?}", warnings);
+}
@@ -180,4 +180,395 @@ fn test_unreachable_code_in_block() {
     assert_specific_error(source, |e| {
         matches!(e, AnalyzerError::UnreachableCode { .. })
     });
-}
\ No newline at end of file
+}
+#[test]
+fn test_defer_reads_outer_variables() {
+    // deferブロックは外側の変数を参照できる
+    let source = r#"
+    package main
+    
+    fn test(x: i32): i32 {
+        let label = "done";
+        defer {
+            println(label, x);
+        }
+        return x + 1;
+    }
+    
+    fn main() {
+    }
+    "#;
+    
+    assert_analysis_success(source);
+}
+
+#[test]
+fn test_return_in_defer_error() {
+    // deferブロックから関数を抜けることはできない
+    let source = r#"
+    package main
+    
+    fn test(x: i32): i32 {
+        defer {
+            if x > 0 {
+                return;
+            }
+        }
+        return x;
+    }
+    
+    fn main() {
+    }
+    "#;
+    
+    assert_specific_error(source, |e| {
+        matches!(e, AnalyzerError::ReturnInDefer { .. })
+    });
+}
+
+#[test]
+fn test_trailing_expression_implicit_return() {
+    // セミコロンのない最後の式は戻り値になる
+    let source = r#"
+    package main
+    
+    fn add(a: i32, b: i32): i32 {
+        a + b
+    }
+    
+    fn abs(x: i32): i32 {
+        if x < 0 {
+            -x
+        } else if x == 0 {
+            0
+        } else {
+            x
+        }
+    }
+    
+    fn main() {
+        let sum: i32 = add(1, 2);
+        let value: i32 = abs(-3);
+    }
+    "#;
+    
+    assert_analysis_success(source);
+}
+
+#[test]
+fn test_trailing_expression_with_semicolon_missing_return() {
+    // セミコロンを付けると式文になり、戻り値にはならない
+    let source = r#"
+    package main
+    
+    fn add(a: i32, b: i32): i32 {
+        a + b;
+    }
+    
+    fn main() {
+    }
+    "#;
+    
+    assert_specific_error(source, |e| {
+        matches!(e, AnalyzerError::MissingReturn { name, .. } if name == "add")
+    });
+}
+
+#[test]
+fn test_trailing_expression_type_mismatch() {
+    // 戻り値型と一致しない最後の式は、その式の位置でエラーになる
+    let source = r#"
+    package main
+    
+    fn flag(x: i32): bool {
+        x + 1
+    }
+    
+    fn main() {
+    }
+    "#;
+    
+    let start = source.find("x + 1").unwrap();
+    assert_specific_error(source, |e| {
+        matches!(e, AnalyzerError::TypeMismatch { span, .. }
+            if span.start == start && span.end == start + "x + 1".len())
+    });
+}
+
+#[test]
+fn test_duplicate_literal_match_arm() {
+    // 前のアームと同じリテラルのアームは、両方の位置を示すエラーになる
+    let source = r#"
+    package main
+
+    fn main() {
+        let code: i32 = 404;
+        let x = match code {
+            200 => 1,
+            404 => 2,
+            404 => 3,
+            _ => 0,
+        };
+    }
+    "#;
+
+    let (first, second) = {
+        let first = source.find("404 =>").unwrap();
+        let second = first + 1 + source[first + 1..].find("404 =>").unwrap();
+        (first, second)
+    };
+    assert_specific_error(source, |e| {
+        matches!(e, AnalyzerError::DuplicateMatchArm { pattern, span, previous }
+            if pattern == "404" && span.start == second && previous.start == first)
+    });
+
+    // 前の範囲に含まれるリテラルも重複になる
+    let source = r#"
+    package main
+
+    fn main() {
+        let code: i32 = 404;
+        let x = match code {
+            400..500 => 1,
+            404 => 2,
+            _ => 0,
+        };
+    }
+    "#;
+    assert_specific_error(source, |e| matches!(e, AnalyzerError::DuplicateMatchArm { .. }));
+
+    // ガードのあるアームは値が同じでも重複ではない
+    let source = r#"
+    package main
+
+    fn main() {
+        let code: i32 = 404;
+        let flag = true;
+        let x = match code {
+            404 if flag => 1,
+            404 => 2,
+            _ => 0,
+        };
+    }
+    "#;
+    assert_analysis_success(source);
+}
+
+#[test]
+fn test_bool_match_exhaustive_without_wildcard() {
+    // trueとfalseの両方のアームがあれば、ワイルドカードがなくても網羅的
+    let source = r#"
+    package main
+
+    fn main() {
+        let flag = 3 > 2;
+        let x = match flag {
+            true => 1,
+            false => 0,
+        };
+        let y = match flag {
+            false | true => 2,
+        };
+    }
+    "#;
+    assert_analysis_success(source);
+
+    // falseのアームがなければ網羅的ではない（ガードのあるアームは数えない）
+    let source = r#"
+    package main
+
+    fn main() {
+        let flag = 3 > 2;
+        let x = match flag {
+            true => 1,
+        };
+    }
+    "#;
+    assert_specific_error(source, |e| matches!(e, AnalyzerError::NonExhaustiveMatch { .. }));
+
+    let source = r#"
+    package main
+
+    fn main() {
+        let flag = 3 > 2;
+        let n: i32 = 5;
+        let x = match flag {
+            true => 1,
+            false if n > 0 => 0,
+        };
+    }
+    "#;
+    assert_specific_error(source, |e| matches!(e, AnalyzerError::NonExhaustiveMatch { .. }));
+
+    // 同じ値のアームは重複になる
+    let source = r#"
+    package main
+
+    fn main() {
+        let flag = 3 > 2;
+        let x = match flag {
+            true => 1,
+            false => 0,
+            true => 2,
+        };
+    }
+    "#;
+    assert_specific_error(source, |e| {
+        matches!(e, AnalyzerError::DuplicateMatchArm { pattern, .. } if pattern == "true")
+    });
+}
+
+#[test]
+fn test_redundant_bool_comparison_lint() {
+    // boolの値とリテラルの比較は、比較しない書き方を提案する警告になる
+    let source = r#"
+    package main
+
+    fn report(flag: bool, ready: bool, n: i32) {
+        if flag == true {
+            println("a");
+        }
+        if ready != false {
+            println("b");
+        }
+        if false == flag {
+            println("c");
+        }
+        if (flag || ready) != true {
+            println("d");
+        }
+        if n == 1 && flag {
+            println("e");
+        }
+    }
+
+    fn main() {
+        report(true, false, 1);
+    }
+    "#;
+
+    let tokens: Vec<_> = Lexer::new(source).collect_tokens();
+    let ast = Parser::new(tokens).parse().expect("Parsing should succeed");
+    let mut analyzer = SemanticAnalyzer::new();
+    analyzer.analyze(&ast).expect("Analysis should succeed");
+    let suggestions: Vec<(&str, &str)> = analyzer
+        .warnings
+        .iter()
+        .map(|warning| match warning {
+            AnalyzerError::RedundantBoolComparison { suggestion, span, .. } => {
+                (&source[span.start..span.end], suggestion.as_str())
+            }
+            other => panic!("Unexpected warning: {:?}", other),
+        })
+        .collect();
+    assert_eq!(
+        suggestions,
+        [
+            ("flag == true", "flag"),
+            ("ready != false", "ready"),
+            ("false == flag", "!flag"),
+            ("(flag || ready) != true", "!(flag || ready)"),
+        ]
+    );
+}
+
+#[test]
+fn test_integer_match_requires_wildcard() {
+    // 整数のmatchはすべての値を列挙できないため、ワイルドカードのアームが必要
+    let source = r#"
+    package main
+
+    fn main() {
+        let code: i32 = 200;
+        let x = match code {
+            200 => 1,
+            300..=399 => 2,
+        };
+    }
+    "#;
+    assert_specific_error(source, |e| matches!(e, AnalyzerError::NonExhaustiveMatch { .. }));
+
+    // ガードのある識別子のアームはすべての値を処理しない
+    let source = r#"
+    package main
+
+    fn main() {
+        let code: i32 = 200;
+        let x = match code {
+            200 => 1,
+            n if n > 200 => 2,
+        };
+    }
+    "#;
+    assert_specific_error(source, |e| matches!(e, AnalyzerError::NonExhaustiveMatch { .. }));
+}
+
+#[test]
+fn test_literal_pattern_type_mismatch() {
+    // リテラルのパターンはmatch対象と同じ種類の型でなければならない
+    let source = r#"
+    package main
+
+    fn main() {
+        let name = "yuni";
+        let x = match name {
+            1 => 1,
+            _ => 0,
+        };
+    }
+    "#;
+    assert_specific_error(source, |e| matches!(e, AnalyzerError::TypeMismatch { .. }));
+}
+
+#[test]
+fn test_diverging_branch_takes_other_branch_type() {
+    // returnで抜ける分岐は値を生まないため、if式・match式はもう一方の分岐の型になる
+    let source = r#"
+    package main
+
+    fn pick(c: bool): i32 {
+        let x = if c { return 1; } else { 2 };
+        let y: i32 = match x {
+            0 => { return 0; },
+            n => n * 2,
+        };
+        return x + y;
+    }
+
+    fn main() {
+        let v = pick(true);
+    }
+    "#;
+    assert_analysis_success(source);
+
+    // 値を生む分岐の型がほかの分岐と合わなければ従来どおりエラー
+    let mismatched = r#"
+    package main
+
+    fn pick(c: bool): i32 {
+        let x: String = if c { return 1; } else { 2 };
+        return 0;
+    }
+
+    fn main() {
+    }
+    "#;
+    assert_specific_error(mismatched, |e| matches!(e, AnalyzerError::TypeMismatch { .. }));
+}
+
+#[test]
+fn test_code_after_fully_diverging_expression_is_unreachable() {
+    // すべての分岐でreturnするif式の後の文には到達しない
+    let source = r#"
+    package main
+
+    fn sign(x: i32): i32 {
+        if x < 0 { return -1; } else { return 1; }
+        return 0;
+    }
+
+    fn main() {
+    }
+    "#;
+    assert_specific_error(source, |e| matches!(e, AnalyzerError::UnreachableCode { .. }));
+}
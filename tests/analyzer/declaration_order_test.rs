@@ -0,0 +1,164 @@
+//! 宣言の順序に依存しない名前解決のテスト
+//!
+//! 参照する側を先に、参照される側を後に宣言しても解析が成功することを確認する。
+
+use super::*;
+
+#[test]
+fn test_function_before_parameter_struct() {
+    let source = r#"
+    package main
+
+    fn area(r: Rect): i32 {
+        return r.w * r.h;
+    }
+
+    type Rect struct {
+        w: i32,
+        h: i32,
+    }
+
+    fn main() {
+        let a: i32 = area(Rect { w: 2, h: 3 });
+    }
+    "#;
+
+    assert_analysis_success(source);
+}
+
+#[test]
+fn test_struct_field_referencing_later_types() {
+    // 構造体・列挙型・型エイリアス・ジェネリック構造体を後で定義する
+    let source = r#"
+    package main
+
+    type Line struct {
+        from: Point,
+        to: Point,
+        style: Style,
+        width: Width,
+        label: Labeled<i32>,
+    }
+
+    type Point struct {
+        x: i32,
+        y: i32,
+    }
+
+    type Style enum {
+        Solid,
+        Dashed,
+    }
+
+    type Width Pixels
+    type Pixels i32
+
+    type Labeled<T> struct {
+        value: T,
+    }
+
+    fn main() {
+        let line = Line {
+            from: Point { x: 0, y: 0 },
+            to: Point { x: 3, y: 4 },
+            style: Style::Solid,
+            width: 2,
+            label: Labeled<i32> { value: 7 },
+        };
+        let x: i32 = line.to.x + line.label.value;
+    }
+    "#;
+
+    assert_analysis_success(source);
+}
+
+#[test]
+fn test_method_used_before_its_declaration() {
+    // メソッドを呼び出す関数、メソッド、レシーバーの型の順に宣言する
+    let source = r#"
+    package main
+
+    fn main() {
+        let c = Counter { count: 41 };
+        let n: i32 = c.next();
+    }
+
+    impl fn next(c: &Counter): i32 {
+        return c.count + 1;
+    }
+
+    type Counter struct {
+        count: i32,
+    }
+    "#;
+
+    assert_analysis_success(source);
+}
+
+#[test]
+fn test_generic_instantiated_before_its_definition() {
+    let source = r#"
+    package main
+
+    fn main() {
+        let b = Holder<i32> { value: 1 };
+        let v: i32 = unwrap(b);
+    }
+
+    fn unwrap<T>(holder: Holder<T>): T {
+        return holder.value;
+    }
+
+    type Holder<T> struct {
+        value: T,
+    }
+    "#;
+
+    assert_analysis_success(source);
+}
+
+#[test]
+fn test_recursive_types() {
+    // Vecを介した自己参照は大きさが決まる
+    let source = r#"
+    package main
+
+    type Tree struct {
+        children: Vec<Tree>,
+    }
+
+    fn main() {
+        let t = Tree { children: Vec<Tree>[] };
+    }
+    "#;
+    assert_analysis_success(source);
+
+    // 値として互いを含む構造体は大きさが決まらない
+    let source = r#"
+    package main
+
+    type Outer struct {
+        inner: Inner,
+    }
+
+    type Inner struct {
+        outer: Outer,
+    }
+
+    fn main() {
+    }
+    "#;
+    assert_specific_error(source, |error| matches!(error, AnalyzerError::RecursiveType { name, .. } if name == "Outer"));
+
+    // 循環するエイリアスは展開が終わらない
+    let source = r#"
+    package main
+
+    type Meters Length
+    type Length &Meters
+
+    fn main() {
+    }
+    "#;
+    assert_specific_error(source, |error| matches!(error, AnalyzerError::RecursiveType { name, .. } if name == "Meters"));
+}
@@ -0,0 +1,209 @@
+//! let文のタプルと構造体の分解のテスト
+
+use super::*;
+
+#[test]
+fn test_destructure_tuple_from_function() {
+    // タプルを返す関数の戻り値を要素ごとの変数に分解する
+    let source = r#"
+    package main
+
+    fn divmod(a: i32, b: i32): (i32, i64) {
+        return (a / b, (a % b) as i64);
+    }
+
+    fn main() {
+        let (q, r) = divmod(7, 2);
+        let x: i32 = q;
+        let y: i64 = r;
+    }
+    "#;
+
+    assert_analysis_success(source);
+
+    // 要素の型は初期化式のタプルの型から決まる
+    let mismatch = r#"
+    package main
+
+    fn pair(): (i32, String) {
+        return (1, "one");
+    }
+
+    fn main() {
+        let (n, name) = pair();
+        let s: i32 = name;
+    }
+    "#;
+
+    assert_specific_error(mismatch, |e| matches!(e, AnalyzerError::TypeMismatch { .. }));
+}
+
+#[test]
+fn test_destructure_struct_with_missing_field() {
+    // 構造体にないフィールドを分解するとフィールド名と使えるフィールドを報告する
+    let source = r#"
+    package main
+
+    type Point struct {
+        x: i32,
+        y: i32,
+    }
+
+    fn main() {
+        let p = Point { x: 1, y: 2 };
+        let Point { x, z } = p;
+    }
+    "#;
+
+    let result = analyze_source(source);
+    let Err(YuniError::Analyzer(AnalyzerError::UnknownPatternField { ty, field, available, span })) = result else {
+        panic!("Expected UnknownPatternField, got {:?}", result);
+    };
+    assert_eq!(ty, "Point");
+    assert_eq!(field, "z");
+    assert_eq!(available, vec!["x", "y"]);
+    assert_eq!(&source[span.start..span.end], "let Point { x, z } = p;");
+
+    // フィールドをすべて分解すれば、各変数はフィールドの型になる
+    let valid = r#"
+    package main
+
+    type Point struct {
+        x: i32,
+        y: f64,
+    }
+
+    fn main() {
+        let Point { x, y: height } = Point { x: 1, y: 2.0 };
+        let a: i32 = x;
+        let b: f64 = height;
+    }
+    "#;
+
+    assert_analysis_success(valid);
+}
+
+#[test]
+fn test_underscore_ignores_values() {
+    // `_` は値を捨てるだけで束縛しないため、同じスコープや同じ関数の引数に何度でも書ける
+    let source = r#"
+    package main
+
+    fn compute(): i32 {
+        return 42;
+    }
+
+    fn second(_: i32, value: i64, _: &String): i64 {
+        return value;
+    }
+
+    fn main() {
+        let _ = compute();
+        let _ = compute();
+        let (first, _, third) = (1, "skipped", 3.5);
+        let (_, mut count) = (0, 10);
+        count = count + first;
+        let name: String = "yuni";
+        let n: i64 = second(1, 2, &name);
+        let label = match n {
+            1 => "one",
+            _ => "other",
+        };
+    }
+    "#;
+
+    let tokens: Vec<_> = Lexer::new(source).collect_tokens();
+    let ast = Parser::new(tokens).parse().expect("Parsing should succeed");
+    let mut analyzer = SemanticAnalyzer::new();
+    analyzer.analyze(&ast).expect("Analysis should succeed");
+    assert!(analyzer.warnings.is_empty(), "{:?}", analyzer.warnings);
+
+    // `_` で始まる名前は通常の変数で、引数の名前の重複は従来どおりエラー
+    let duplicate = r#"
+    package main
+
+    fn pick(_a: i32, _a: i32): i32 {
+        return 0;
+    }
+
+    fn main() {
+    }
+    "#;
+
+    assert_specific_error(duplicate, |e| matches!(e, AnalyzerError::DuplicateVariable { name, .. } if name == "_a"));
+}
+
+#[test]
+fn test_destructure_nested_tuple() {
+    // 入れ子のパターンは内側のタプルも分解し、`_` の要素は束縛しない
+    let source = r#"
+    package main
+
+    fn main() {
+        let (a, (b, _)) = (1, (2.5, "ignored"));
+        let x: i32 = a;
+        let y: f64 = b;
+    }
+    "#;
+
+    assert_analysis_success(source);
+
+    let arity = r#"
+    package main
+
+    fn main() {
+        let (a, (b, c, d)) = (1, (2.5, "two"));
+    }
+    "#;
+
+    assert_specific_error(arity, |e| matches!(
+        e,
+        AnalyzerError::TuplePatternArityMismatch { expected: 2, found: 3, .. }
+    ));
+}
+
+#[test]
+fn test_mutable_component() {
+    // mutを付けた要素だけを変更できる
+    let source = r#"
+    package main
+
+    fn main() {
+        let (mut count, limit) = (0, 10);
+        count = count + limit;
+    }
+    "#;
+
+    assert_analysis_success(source);
+
+    let immutable = r#"
+    package main
+
+    fn main() {
+        let (mut count, limit) = (0, 10);
+        limit = 20;
+    }
+    "#;
+
+    assert_specific_error(immutable, |e| matches!(
+        e,
+        AnalyzerError::ImmutableVariable { name, .. } if name == "limit"
+    ));
+}
+
+#[test]
+fn test_refutable_let_pattern() {
+    // 値によっては一致しないパターンはlet文では使えない
+    let source = r#"
+    package main
+
+    fn main() {
+        let (a, 1) = (0, 1);
+    }
+    "#;
+
+    assert_specific_error(source, |e| matches!(
+        e,
+        AnalyzerError::RefutableLetPattern { pattern, .. } if pattern == "1"
+    ));
+}
@@ -0,0 +1,196 @@
+//! 誤りの後も解析を続けるエラー回復のテスト
+//!
+//! 1つの関数に互いに関係のない誤りがあっても、それぞれを1回ずつ報告し、
+//! 誤った変数を後で使った箇所でエラーを重ねて報告しないことを検証する。
+
+use super::*;
+
+/// エラーが指すソースコードの範囲
+fn error_span(error: &AnalyzerError) -> Span {
+    match error {
+        AnalyzerError::TypeMismatch { span, .. }
+        | AnalyzerError::UndefinedVariable { span, .. }
+        | AnalyzerError::UndefinedType { span, .. }
+        | AnalyzerError::ArgumentTypeMismatch { span, .. }
+        | AnalyzerError::ImmutableVariable { span, .. } => *span,
+        other => panic!("Unexpected error: {:?}", other),
+    }
+}
+
+#[test]
+fn test_five_unrelated_mistakes_in_one_function() {
+    let source = r#"
+    package main
+
+    struct Point {
+        x: i32,
+        y: i32,
+    }
+
+    enum Shape {
+        Circle(radius: i32),
+        Square(side: i32),
+    }
+
+    fn describe(n: i32): String {
+        return `value ${n}`;
+    }
+
+    fn area(shape: Shape): i32 {
+        return match shape {
+            Shape::Circle(r) => 3 * r * r,
+            Shape::Square(side) => side * side,
+        };
+    }
+
+    fn main() {
+        let origin = Point { x: 0, y: 0 };
+        let mut total: i32 = 0;
+        let mut count: i32 = 0;
+
+        // 1. 型の異なる初期化式
+        let limit: i32 = "ten";
+
+        let mut i: i32 = 0;
+        while i < limit {
+            total = total + i;
+            i = i + 1;
+        }
+
+        // 2. 未定義の変数
+        let ratio = undefined_scale * 2;
+
+        let scaled = ratio + total;
+        let doubled = scaled * 2;
+        println(doubled);
+        println(`ratio: ${ratio}`);
+
+        for let mut j: i32 = 0; j < 10; j = j + 1 {
+            count = count + 1;
+            if j % 2 == 0 {
+                total = total + j;
+            } else {
+                total = total - 1;
+            }
+        }
+
+        // 3. boolではない条件
+        if count + 1 {
+            let inside = count * 2;
+            total = total + inside;
+        }
+
+        let shapes = Vec<Shape>[];
+        let circle = Shape::Circle(2);
+        let square = Shape::Square(limit);
+        let areas = area(circle) + area(square);
+        println(areas);
+
+        // 4. 型の異なる引数
+        let label = describe(true);
+
+        println(label);
+        let message = `${label} and ${ratio}`;
+        println(message);
+
+        let corner = Point { x: limit, y: ratio };
+        let sum = corner.x + corner.y;
+        println(sum);
+
+        let kind = match ratio {
+            0 => "zero",
+            _ => "other",
+        };
+        println(kind);
+
+        // 5. 不変の変数のフィールドへの代入
+        origin.x = 5;
+
+        let mut k: i32 = 0;
+        while k < count {
+            k = k + 1;
+            total = total + k;
+        }
+        if total > limit {
+            println("large");
+        } else {
+            println("small");
+        }
+
+        let final_value = total + count + sum;
+        println(final_value);
+        println(origin.y);
+        println(shapes.len());
+    }
+    "#;
+
+    let mut errors = analyze_errors(source);
+    errors.sort_by_key(|error| error_span(error).start);
+    let texts: Vec<&str> = errors
+        .iter()
+        .map(|error| {
+            let span = error_span(error);
+            &source[span.start..span.end]
+        })
+        .collect();
+
+    // 誤った変数（limit・ratio・label）を後で使った箇所はエラーにならない
+    assert_eq!(
+        texts,
+        [
+            "let limit: i32 = \"ten\";",
+            "undefined_scale",
+            "count + 1",
+            "true",
+            "origin.x = 5;",
+        ],
+        "{:#?}",
+        errors
+    );
+    assert!(matches!(errors[0], AnalyzerError::TypeMismatch { .. }), "{:?}", errors[0]);
+    assert!(matches!(errors[1], AnalyzerError::UndefinedVariable { .. }), "{:?}", errors[1]);
+    assert!(matches!(errors[2], AnalyzerError::TypeMismatch { .. }), "{:?}", errors[2]);
+    assert!(matches!(errors[3], AnalyzerError::ArgumentTypeMismatch { .. }), "{:?}", errors[3]);
+    assert!(matches!(errors[4], AnalyzerError::ImmutableVariable { .. }), "{:?}", errors[4]);
+}
+
+#[test]
+fn test_errors_in_nested_blocks_and_signatures() {
+    // ブロック式・matchのアーム・ループの本体の誤りも、ほかの文の解析を止めない
+    let source = r#"
+    package main
+
+    fn scale(shape: Shap, factor: i32): i32 {
+        return factor * 2;
+    }
+
+    fn main() {
+        let value = {
+            let a: i32 = missing_a;
+            a + 1
+        };
+        let picked = match value {
+            0 => missing_b,
+            _ => value + 1,
+        };
+        while value > picked {
+            println(missing_c);
+        }
+        let scaled = scale(1, value);
+        println(scaled + picked);
+    }
+    "#;
+
+    let errors = analyze_errors(source);
+    let texts: Vec<&str> = errors
+        .iter()
+        .map(|error| {
+            let span = error_span(error);
+            &source[span.start..span.end]
+        })
+        .collect();
+
+    // 定義されていない引数の型はシグネチャの収集で1回だけ報告し、呼び出し側は解析を続ける
+    assert_eq!(texts, ["shape: Shap", "missing_a", "missing_b", "missing_c"], "{:#?}", errors);
+    assert!(matches!(&errors[0], AnalyzerError::UndefinedType { name, .. } if name == "Shap"), "{:?}", errors[0]);
+}
@@ -35,6 +35,90 @@ fn test_immutable_assignment_error() {
     });
 }
 
+#[test]
+fn test_immutable_parameter_assignment_error() {
+    // mutのない引数への再代入エラーのテスト
+    let source = r#"
+    package main
+    
+    fn f(count: i32) {
+        count = count - 1;
+    }
+    
+    fn main() {
+        f(1);
+    }
+    "#;
+    
+    assert_specific_error(source, |e| {
+        // 代入と引数の宣言の両方を指す
+        matches!(e, AnalyzerError::ImmutableVariable { name, span, declared_at }
+            if name == "count" && span.start > declared_at.end && declared_at.start < declared_at.end)
+    });
+}
+
+#[test]
+fn test_mutable_parameter_assignment() {
+    // mut付きの引数には再代入できる
+    let source = r#"
+    package main
+    
+    fn countdown(mut count: i32): i32 {
+        while count > 0 {
+            count = count - 1;
+        }
+        return count;
+    }
+    
+    fn main() {
+        let n: i32 = countdown(3);
+    }
+    "#;
+    
+    assert_analysis_success(source);
+}
+
+#[test]
+fn test_mutable_reference_parameter_rebinding() {
+    // 参照引数のmutは束縛の再代入を許可する（参照先の可変性とは無関係）
+    let source = r#"
+    package main
+    
+    fn pick(mut r: &i32, other: &i32): i32 {
+        r = other;
+        return *r;
+    }
+    
+    fn main() {
+        let a: i32 = 1;
+        let b: i32 = 2;
+        let c: i32 = pick(&a, &b);
+    }
+    "#;
+    
+    assert_analysis_success(source);
+
+    // mutがなければ参照引数の束縛も再代入できない
+    let source = r#"
+    package main
+    
+    fn pick(r: &i32, other: &i32): i32 {
+        r = other;
+        return *r;
+    }
+    
+    fn main() {
+        let a: i32 = 1;
+        let b: i32 = 2;
+        let c: i32 = pick(&a, &b);
+    }
+    "#;
+    
+    assert_specific_error(source, |e| {
+        matches!(e, AnalyzerError::ImmutableVariable { name, .. } if name == "r")
+    });
+}
+
 #[test]
 fn test_return_type_mismatch_error() {
     // 戻り値型不一致エラーのテスト
@@ -125,4 +209,74 @@ fn test_logical_operation_type_error() {
     assert_specific_error(source, |e| {
         matches!(e, AnalyzerError::TypeMismatch { .. })
     });
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_logical_not_requires_bool() {
+    // boolの論理否定はif文の条件に使える
+    let source = r#"
+    package main
+    
+    fn main() {
+        let flag = false;
+        if !flag {
+            let inverted: i32 = ~7;
+        }
+    }
+    "#;
+    
+    assert_analysis_success(source);
+
+    // 整数の論理否定は0との比較かビット反転への書き換えを提案する
+    let source = r#"
+    package main
+    
+    fn main() {
+        let count = 3;
+        if !count {
+            let x = 1;
+        }
+    }
+    "#;
+    
+    assert_specific_error(source, |e| {
+        matches!(e, AnalyzerError::LogicalNotOnInteger { ty, suggestion, .. }
+            if ty == "i32" && suggestion.contains("`count == 0`") && suggestion.contains("`~count`"))
+    });
+}
+
+#[test]
+fn test_bitwise_not_requires_integer() {
+    // ビット反転は整数にのみ適用できる
+    let source = r#"
+    package main
+    
+    fn main() {
+        let flag = true;
+        let result = ~flag;
+    }
+    "#;
+    
+    assert_specific_error(source, |e| {
+        matches!(e, AnalyzerError::TypeMismatch { .. })
+    });
+}
+
+#[test]
+fn test_print_requires_message() {
+    // 改行を出力しないprintは、出力する文字列を省略できない
+    let source = r#"
+    package main
+
+    fn main() {
+        print();
+        println();
+        eprintln("done");
+    }
+    "#;
+
+    assert_specific_error(source, |e| {
+        matches!(e, AnalyzerError::ArgumentCountMismatch { expected: 1, found: 0, callee: Some(callee), .. }
+            if callee.signature == "fn print(message: String)")
+    });
+}
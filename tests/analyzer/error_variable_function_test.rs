@@ -110,7 +110,7 @@ fn test_argument_count_mismatch_error() {
 
 #[test]
 fn test_function_overloading_error() {
-    // 関数オーバーロードエラーのテスト（Yuniでは未サポート）
+    // 引数の数が同じ関数のオーバーロードはエラー（オーバーロードは引数の数だけ）
     let source = r#"
     package main
     
@@ -129,4 +129,385 @@ fn test_function_overloading_error() {
     assert_specific_error(source, |e| {
         matches!(e, AnalyzerError::DuplicateFunction { .. })
     });
-}
\ No newline at end of file
+}
+/// 解析エラーを端末向けに描画する
+fn render_error(source: &str) -> String {
+    use codespan_reporting::files::SimpleFiles;
+    use codespan_reporting::term::{self, termcolor::Buffer};
+    use yunilang::error::DiagnosticError;
+
+    let mut files = SimpleFiles::new();
+    let file_id = files.add("test.yuni".to_string(), source.to_string());
+    let error = analyze_source(source).expect_err("Analysis should fail");
+    let diagnostic = DiagnosticError::new(error, file_id).to_diagnostic();
+    let mut buffer = Buffer::no_color();
+    term::emit(&mut buffer, &term::Config::default(), &files, &diagnostic).unwrap();
+    String::from_utf8(buffer.into_inner()).unwrap()
+}
+
+#[test]
+fn test_argument_count_mismatch_shows_signature() {
+    let source = r#"
+    package main
+
+    fn add(a: i32, b: i32): i32 {
+        return a + b;
+    }
+
+    fn main() {
+        let result = add(5);
+    }
+    "#;
+
+    let rendered = render_error(source);
+    assert!(rendered.contains("シグネチャ: fn add(a: i32, b: i32): i32"), "{}", rendered);
+    assert!(rendered.contains("引数はここで2個宣言されています"), "{}", rendered);
+}
+
+#[test]
+fn test_argument_type_mismatch_suggests_swap() {
+    // 引数の順序を取り違えた呼び出し
+    let source = r#"
+    package main
+
+    fn repeat(text: String, count: i32): String {
+        return text;
+    }
+
+    fn main() {
+        let s = repeat(3, "ab");
+    }
+    "#;
+
+    assert_specific_error(source, |e| {
+        matches!(e, AnalyzerError::ArgumentTypeMismatch { index: 0, param, swap_with: Some(1), .. } if param == "text")
+    });
+    let rendered = render_error(source);
+    assert!(rendered.contains("引数 text（1番目）の型の不一致"), "{}", rendered);
+    assert!(rendered.contains("シグネチャ: fn repeat(text: String, count: i32): String"), "{}", rendered);
+    assert!(rendered.contains("引数 text はここで String として宣言されています"), "{}", rendered);
+    assert!(rendered.contains("1番目と2番目の引数を入れ替えると型が一致します"), "{}", rendered);
+}
+
+#[test]
+fn test_argument_type_mismatch_without_swap() {
+    let source = r#"
+    package main
+
+    fn scale(x: f64, factor: f64): f64 {
+        return x * factor;
+    }
+
+    fn main() {
+        let y = scale(1.0, true);
+    }
+    "#;
+
+    assert_specific_error(source, |e| {
+        matches!(e, AnalyzerError::ArgumentTypeMismatch { index: 1, swap_with: None, .. })
+    });
+    let rendered = render_error(source);
+    assert!(rendered.contains("シグネチャ: fn scale(x: f64, factor: f64): f64"), "{}", rendered);
+    assert!(!rendered.contains("入れ替える"), "{}", rendered);
+}
+
+#[test]
+fn test_void_call_in_let_is_rejected() {
+    let source = r#"
+    package main
+
+    fn main() {
+        let x = println("hi");
+    }
+    "#;
+
+    assert_specific_error(source, |e| {
+        matches!(e, AnalyzerError::VoidValue { name, usage, .. } if name == "println" && usage == "let文の初期化式")
+    });
+    let rendered = render_error(source);
+    assert!(rendered.contains("println は値を返さないため、let文の初期化式には使えません"), "{}", rendered);
+    assert!(rendered.contains("シグネチャ: fn println(message: String)"), "{}", rendered);
+}
+
+#[test]
+fn test_void_call_in_if_condition_is_rejected() {
+    // 「boolを期待しましたが、voidが見つかりました」ではなく、値を返さないことを示す
+    let source = r#"
+    package main
+
+    fn do_something() {
+    }
+
+    fn main() {
+        if do_something() {
+            println("done");
+        }
+    }
+    "#;
+
+    assert_specific_error(source, |e| {
+        matches!(e, AnalyzerError::VoidValue { name, usage, span, .. }
+            if name == "do_something" && usage == "条件" && &source[span.start..span.end] == "do_something()")
+    });
+    let rendered = render_error(source);
+    assert!(rendered.contains("シグネチャ: fn do_something()"), "{}", rendered);
+}
+
+#[test]
+fn test_void_call_as_statement_is_allowed() {
+    // 式文と、戻り値型のない関数の最後の文としての呼び出しは使える
+    let source = r#"
+    package main
+
+    fn do_something() {
+        println("working");
+    }
+
+    fn run() {
+        do_something()
+    }
+
+    fn main() {
+        do_something();
+        run();
+    }
+    "#;
+
+    assert_analysis_success(source);
+}
+
+#[test]
+fn test_assert_builtins_accept_conditions_and_comparable_values() {
+    let source = r#"
+    package main
+
+    fn main() {
+        let count: i64 = 3;
+        let name = "yuni";
+        assert(count > 0);
+        debug_assert(name == "yuni");
+        assert_eq(count, 3);
+        assert_eq(name, "yuni");
+        debug_assert_eq(count * 2, 6);
+    }
+    "#;
+
+    assert_analysis_success(source);
+}
+
+#[test]
+fn test_assert_condition_must_be_bool() {
+    let source = r#"
+    package main
+
+    fn main() {
+        let count = 3;
+        assert(count);
+    }
+    "#;
+
+    assert_specific_error(source, |e| {
+        matches!(e, AnalyzerError::TypeMismatch { expected, found, .. } if expected == "bool" && found == "i32")
+    });
+}
+
+#[test]
+fn test_assert_eq_rejects_values_that_cannot_be_compared() {
+    let source = r#"
+    package main
+
+    type Point struct {
+        x: i32,
+        y: i32
+    }
+
+    fn main() {
+        let a = Point { x: 1, y: 2 };
+        let b = Point { x: 1, y: 2 };
+        assert_eq(a, b);
+    }
+    "#;
+
+    assert_specific_error(source, |e| {
+        matches!(e, AnalyzerError::AssertNotComparable { name, ty, .. } if name == "assert_eq" && ty == "Point")
+    });
+}
+
+#[test]
+fn test_template_string_rejects_values_that_cannot_be_displayed() {
+    let source = r#"
+    package main
+
+    type Point struct {
+        x: i32,
+        y: i32
+    }
+
+    fn main() {
+        let p = Point { x: 1, y: 2 };
+        let text = `p = ${p}`;
+    }
+    "#;
+
+    assert_specific_error(source, |e| {
+        matches!(e, AnalyzerError::NotDisplayable { ty, .. } if ty == "Point")
+    });
+    // 補間式の位置を指し、明示的な変換を促す
+    let rendered = render_error(source);
+    assert!(rendered.contains("Point の値は文字列に補間できません"), "{}", rendered);
+    assert!(rendered.contains("Point の値を明示的に文字列に変換してください"), "{}", rendered);
+    assert!(rendered.contains("`${p.x}`"), "{}", rendered);
+    let error = analyze_source(source).expect_err("Analysis should fail");
+    let YuniError::Analyzer(AnalyzerError::NotDisplayable { span, .. }) = error else {
+        panic!("Expected NotDisplayable, got {:?}", error);
+    };
+    assert_eq!(&source[span.start..span.end], "p");
+
+    // printlnに渡した値もテンプレート文字列と同じく確認する
+    let source = r#"
+    package main
+
+    fn main() {
+        let values: Vec<i32> = Vec<i32>[1, 2, 3];
+        println("values = ", values);
+    }
+    "#;
+    assert_specific_error(source, |e| {
+        matches!(e, AnalyzerError::NotDisplayable { ty, .. } if ty == "Vec<i32>")
+    });
+}
+
+#[test]
+fn test_template_string_accepts_displayable_values() {
+    let source = r#"
+    package main
+
+    type Color enum {
+        Red,
+        Green
+    }
+
+    fn main() {
+        let small: u8 = 255;
+        let big: u64 = 18446744073709551615;
+        let ratio: f32 = 0.5;
+        let name = "yuni";
+        let name_ref = &name;
+        let color = Color::Green;
+        println(`${small} ${big} ${ratio} ${true} ${name} ${name_ref} ${color}`);
+        println("color = ", color);
+    }
+    "#;
+
+    assert_analysis_success(source);
+}
+
+#[test]
+fn test_assert_eq_rejects_mismatched_types() {
+    let source = r#"
+    package main
+
+    fn main() {
+        assert_eq(1, "one");
+    }
+    "#;
+
+    assert_specific_error(source, |e| matches!(e, AnalyzerError::TypeMismatch { .. }));
+    let rendered = render_error(r#"
+    package main
+
+    fn main() {
+        assert_eq(1);
+    }
+    "#);
+    assert!(rendered.contains("シグネチャ: fn assert_eq(left: T, right: T)"), "{}", rendered);
+}
+
+#[test]
+fn test_inline_always_on_recursive_function_error() {
+    // 自身を呼び出す関数は常には展開できない
+    let source = r#"
+    package main
+
+    inline(always) fn factorial(n: i32): i32 {
+        if n <= 1 {
+            return 1;
+        }
+        return n * factorial(n - 1);
+    }
+
+    fn main() {
+        let x = factorial(5);
+    }
+    "#;
+
+    assert_specific_error(source, |e| {
+        matches!(e, AnalyzerError::RecursiveInlineAlways { name, cycle, .. }
+            if name == "factorial" && cycle == &["factorial", "factorial"])
+    });
+    let rendered = render_error(source);
+    assert!(rendered.contains("再帰する factorial に inline(always) は指定できません"), "{}", rendered);
+    assert!(rendered.contains("factorial が自身を呼び出しています"), "{}", rendered);
+}
+
+#[test]
+fn test_inline_always_on_mutually_recursive_functions_error() {
+    // 他の関数やメソッドを経由した再帰も検出する
+    let source = r#"
+    package main
+
+    type Counter struct {
+        count: i32
+    }
+
+    impl Counter {
+        fn step(&self, n: i32): bool {
+            return is_even(n - 1);
+        }
+    }
+
+    inline(always) fn is_even(n: i32): bool {
+        if n == 0 {
+            return true;
+        }
+        let c = Counter { count: 0 };
+        return c.step(n);
+    }
+
+    fn main() {
+        let x = is_even(4);
+    }
+    "#;
+
+    assert_specific_error(source, |e| {
+        matches!(e, AnalyzerError::RecursiveInlineAlways { name, cycle, .. }
+            if name == "is_even" && cycle == &["is_even", "Counter::step", "is_even"])
+    });
+    let rendered = render_error(source);
+    assert!(rendered.contains("呼び出しの循環: is_even → Counter::step → is_even"), "{}", rendered);
+}
+
+#[test]
+fn test_inline_on_recursive_function_is_allowed() {
+    // 展開を優先するだけの inline は再帰する関数にも付けられる
+    let source = r#"
+    package main
+
+    inline fn factorial(n: i32): i32 {
+        if n <= 1 {
+            return 1;
+        }
+        return n * factorial(n - 1);
+    }
+
+    inline(always) fn twice(n: i32): i32 {
+        return factorial(n) * 2;
+    }
+
+    fn main() {
+        let x = twice(5);
+    }
+    "#;
+
+    assert_analysis_success(source);
+}
@@ -0,0 +1,106 @@
+//! 外部関数の宣言のテスト
+
+use super::*;
+use yunilang::analyzer::resolve_names;
+
+#[test]
+fn test_call_extern_function() {
+    // 外部関数は宣言したシグネチャで呼び出せる
+    let source = r#"
+    package main
+
+    type CInt i32
+
+    extern fn abs(x: CInt): CInt;
+    extern "C" fn getenv(name: String) -> String;
+    extern fn srand(seed: u32);
+
+    fn main() {
+        let n: i32 = abs(-3);
+        let home: String = getenv("HOME");
+        srand(42);
+    }
+    "#;
+
+    assert_analysis_success(source);
+}
+
+#[test]
+fn test_extern_function_argument_type_mismatch() {
+    // 宣言と異なる型の引数はエラー
+    let source = r#"
+    package main
+
+    extern fn abs(x: i32): i32;
+
+    fn main() {
+        let n = abs("three");
+    }
+    "#;
+
+    assert_specific_error(source, |e| matches!(e, AnalyzerError::ArgumentTypeMismatch { .. }));
+}
+
+#[test]
+fn test_non_ffi_safe_types() {
+    // Cと受け渡せない型を使う引数と戻り値をそれぞれ報告する
+    let source = r#"
+    package main
+
+    type Point struct {
+        x: i32,
+        y: i32,
+    }
+
+    extern fn take(values: Vec<i32>, offset: i64, p: &Point): Point;
+
+    fn main() {
+    }
+    "#;
+
+    let errors = analyze_errors(source);
+    let types: Vec<&str> = errors
+        .iter()
+        .map(|e| match e {
+            AnalyzerError::NonFfiSafeType { name, ty, span } => {
+                assert_eq!(name, "take");
+                assert!(source[span.start..span.end].contains(ty.trim_start_matches('&')), "{:?}", e);
+                ty.as_str()
+            }
+            other => panic!("Expected NonFfiSafeType, got {:?}", other),
+        })
+        .collect();
+    assert_eq!(types.len(), 3, "{:?}", errors);
+    assert!(types[0].contains("Vec"), "{:?}", types);
+    assert!(types[1].contains("Point") && types[2].contains("Point"), "{:?}", types);
+}
+
+#[test]
+fn test_extern_shadowing_prelude_keeps_symbol() {
+    // プレリュードの名前を隠す外部関数は名前が書き換わっても、Cのシンボル名は宣言時の名前のまま
+    let source = r#"
+    package main
+
+    extern fn sqrt(x: f64) -> f64;
+
+    fn main() {
+        let root: f64 = sqrt(2.0);
+    }
+    "#;
+
+    let tokens: Vec<_> = Lexer::new(source).collect_tokens();
+    let mut ast = Parser::new(tokens).parse().expect("Parsing should succeed");
+    let resolution = resolve_names(&mut ast);
+    assert!(resolution.errors.is_empty(), "{:?}", resolution.errors);
+    assert!(matches!(
+        &resolution.warnings[..],
+        [AnalyzerError::ShadowsPrelude { name, .. }] if name == "sqrt"
+    ), "{:?}", resolution.warnings);
+
+    let Item::ExternFunction(func) = &ast.items[0] else {
+        panic!("Expected extern function, got {:?}", ast.items[0]);
+    };
+    assert_eq!(func.name, "main.sqrt");
+    assert_eq!(func.symbol(), "sqrt");
+    assert!(SemanticAnalyzer::new().analyze(&ast).is_ok());
+}
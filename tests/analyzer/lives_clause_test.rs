@@ -0,0 +1,153 @@
+//! lives句と、返す参照の借用元の検査のテスト
+
+use super::*;
+
+#[test]
+fn test_lives_clause_names_returned_parameter() {
+    // lives句に指定した引数から借用した参照は返せる
+    let source = r#"
+    package main
+
+    type Pair struct {
+        left: i32,
+        right: i32
+    }
+
+    fn first(a: &i32, b: &i32): &i32 lives return: a {
+        return a;
+    }
+
+    fn either(a: &i32, b: &i32, pick_a: bool): &i32 lives return: a + b {
+        if pick_a {
+            return a;
+        }
+        return b;
+    }
+
+    // 参照を持つ引数が1つだけならlives句は省略できる
+    fn left_of(pair: &Pair): &i32 {
+        return &pair.left;
+    }
+
+    fn main() {
+        let x = 1;
+        let y = 2;
+        let pair = Pair { left: 3, right: 4 };
+        println(*first(&x, &y), *either(&x, &y, false), *left_of(&pair));
+    }
+    "#;
+
+    let errors = analyze_errors(source);
+    assert!(errors.is_empty(), "{:?}", errors);
+}
+
+#[test]
+fn test_returning_local_reference_is_rejected() {
+    let source = r#"
+    package main
+
+    fn dangling(): &i32 {
+        let local = 42;
+        return &local;
+    }
+
+    fn main() {
+    }
+    "#;
+
+    let errors = analyze_errors(source);
+    assert_eq!(errors.len(), 1, "{:?}", errors);
+    assert!(matches!(
+        &errors[0],
+        AnalyzerError::ReturnsLocalReference { name, span, declared_at }
+            if name == "local"
+                && &source[span.start..span.end] == "&local"
+                && source[declared_at.start..declared_at.end].starts_with("let local")
+    ), "{:?}", errors[0]);
+}
+
+#[test]
+fn test_missing_lives_clause_suggests_borrowed_parameter() {
+    // 参照を持つ引数が複数ある場合はlives句が必要で、本体から借用元を提案する
+    let source = r#"
+    package main
+
+    fn second(a: &i32, b: &i32): &i32 {
+        return b;
+    }
+
+    fn main() {
+    }
+    "#;
+
+    let errors = analyze_errors(source);
+    assert_eq!(errors.len(), 1, "{:?}", errors);
+    assert!(matches!(
+        &errors[0],
+        AnalyzerError::MissingLivesClause { name, suggestion, .. }
+            if name == "second" && suggestion == "lives return: b"
+    ), "{:?}", errors[0]);
+}
+
+#[test]
+fn test_returning_unlisted_parameter_is_rejected() {
+    let source = r#"
+    package main
+
+    fn first(a: &i32, b: &i32): &i32 lives return: a {
+        return b;
+    }
+
+    fn main() {
+    }
+    "#;
+
+    let errors = analyze_errors(source);
+    assert_eq!(errors.len(), 1, "{:?}", errors);
+    assert!(matches!(
+        &errors[0],
+        AnalyzerError::UnlistedReturnSource { param, suggestion, .. }
+            if param == "b" && suggestion == "lives return: a + b"
+    ), "{:?}", errors[0]);
+}
+
+#[test]
+fn test_call_result_cannot_outlive_its_source() {
+    // 呼び出しの結果はlives句に指定した引数の借用元を引き継ぐ
+    let program = |args: &str| {
+        format!(
+            r#"
+    package main
+
+    fn first(a: &i32, b: &i32): &i32 lives return: a {{
+        return a;
+    }}
+
+    fn main() {{
+        let outer = 1;
+        let mut r = &outer;
+        {{
+            let inner = 2;
+            r = first({});
+        }}
+        println(*r);
+    }}
+    "#,
+            args
+        )
+    };
+
+    // 結果はouterだけを借用している
+    let errors = analyze_errors(&program("&outer, &inner"));
+    assert!(errors.is_empty(), "{:?}", errors);
+
+    // 結果が借用しているinnerはブロックの終わりで破棄される
+    let source = program("&inner, &outer");
+    let errors = analyze_errors(&source);
+    assert_eq!(errors.len(), 1, "{:?}", errors);
+    assert!(matches!(
+        &errors[0],
+        AnalyzerError::BorrowedValueDropped { name, borrowed, span, .. }
+            if name == "r" && borrowed == "inner" && source[span.start..].starts_with("r);")
+    ), "{:?}", errors[0]);
+}
@@ -0,0 +1,135 @@
+//! 関数の中で定義した関数（ローカル関数）のテスト
+
+use super::*;
+use yunilang::analyzer::resolve_names;
+use yunilang::error::DiagnosticError;
+
+/// 名前解決してからセマンティック解析を実行するヘルパー関数
+fn resolve_and_analyze(source: &str) -> Result<(), AnalyzerError> {
+    let tokens: Vec<_> = Lexer::new(source).collect_tokens();
+    let mut ast = Parser::new(tokens).parse().expect("Parsing should succeed");
+    let resolution = resolve_names(&mut ast);
+    assert!(resolution.errors.is_empty(), "{:?}", resolution.errors);
+    SemanticAnalyzer::new().analyze(&ast)
+}
+
+#[test]
+fn test_local_function() {
+    let source = r#"
+    package main
+
+    fn outer(): i32 {
+        fn helper(x: i32): i32 {
+            x * 2
+        }
+        let a = helper(3);
+        {
+            // 入れ子のブロックからも呼び出せる
+            let b = helper(a);
+            println(b);
+        }
+        return helper(a);
+    }
+
+    fn main() {
+        println(outer());
+    }
+    "#;
+
+    assert_analysis_success(source);
+}
+
+#[test]
+fn test_local_function_cannot_capture_variables() {
+    let source = r#"
+    package main
+
+    fn outer(factor: i32): i32 {
+        let offset = 1;
+        fn scale(x: i32): i32 {
+            return x * factor;
+        }
+        return scale(2) + offset;
+    }
+
+    fn main() {
+        println(outer(3));
+    }
+    "#;
+
+    let tokens: Vec<_> = Lexer::new(source).collect_tokens();
+    let ast = Parser::new(tokens).parse().expect("Parsing should succeed");
+    let mut analyzer = SemanticAnalyzer::new();
+    assert!(analyzer.analyze(&ast).is_err());
+
+    // 未定義の変数としては報告しない
+    assert!(
+        !analyzer.errors.iter().any(|error| matches!(error, AnalyzerError::UndefinedVariable { .. })),
+        "{:?}",
+        analyzer.errors
+    );
+    let AnalyzerError::LocalFunctionCapture { name, function, span } = &analyzer.errors[0] else {
+        panic!("Expected LocalFunctionCapture, got {:?}", analyzer.errors[0]);
+    };
+    assert_eq!(name, "factor");
+    assert_eq!(function, "scale");
+    assert_eq!(&source[span.start..span.end], "factor");
+
+    let notes = DiagnosticError::new(YuniError::Analyzer(analyzer.errors[0].clone()), 0).to_diagnostic().notes;
+    assert_eq!(notes, vec!["ローカル関数は変数を捕捉できません。factor を引数として渡してください".to_string()]);
+}
+
+#[test]
+fn test_local_function_is_scoped_to_enclosing_block() {
+    // 囲んでいる関数の外からは呼び出せない
+    assert_specific_error(
+        r#"
+        package main
+
+        fn outer() {
+            fn helper(x: i32): i32 {
+                x * 2
+            }
+            println(helper(1));
+        }
+
+        fn main() {
+            println(helper(2));
+        }
+        "#,
+        |error| matches!(error, AnalyzerError::UndefinedFunction { name, .. } if name == "helper"),
+    );
+
+    // 定義より前の文とブロックの外からは呼び出せない
+    for call_site in ["println(helper(1));\n{ fn helper(x: i32): i32 { x } }", "{ fn helper(x: i32): i32 { x } }\nprintln(helper(1));"] {
+        let source = format!("package main\n\nfn main() {{\n{}\n}}\n", call_site);
+        assert_specific_error(&source, |error| {
+            matches!(error, AnalyzerError::UndefinedFunction { name, .. } if name == "helper")
+        });
+    }
+}
+
+#[test]
+fn test_local_function_shadows_outer_function() {
+    // 同じ名前のトップレベルの関数やプレリュードの関数よりローカル関数を優先する
+    let source = r#"
+    package main
+
+    fn helper(x: String): String {
+        return x;
+    }
+
+    fn main() {
+        fn helper(x: i32): i32 {
+            x + 1
+        }
+        fn sqrt(x: i32): i32 {
+            x / 2
+        }
+        let value: i32 = helper(1) + sqrt(8);
+        println(value);
+    }
+    "#;
+
+    resolve_and_analyze(source).expect("Analysis should succeed");
+}
@@ -0,0 +1,138 @@
+//! ループの進行の検査（条件の変数の変更忘れ）のテスト
+
+use super::*;
+
+#[test]
+fn test_forgotten_increment_warns() {
+    // iを増やし忘れたループは終了しない
+    let source = r#"
+    package main
+
+    fn sum(n: i32): i32 {
+        let mut sum = 0;
+        let mut i = 0;
+        while i < n {
+            sum = sum + i;
+        }
+        return sum;
+    }
+
+    fn main() {
+    }
+    "#;
+
+    let warnings = analyze_warnings(source);
+    assert_eq!(warnings.len(), 1, "{:?}", warnings);
+    assert!(matches!(
+        &warnings[0],
+        AnalyzerError::LoopConditionNotModified { variables, span }
+            if variables == &["i".to_string(), "n".to_string()] && &source[span.start..span.end] == "i < n"
+    ), "{:?}", warnings[0]);
+}
+
+#[test]
+fn test_incremented_loops_are_silent() {
+    // 条件の変数のどれかを変更していれば警告しない
+    let source = r#"
+    package main
+
+    fn sum(n: i32): i32 {
+        let mut sum = 0;
+        let mut i = 0;
+        while i < n {
+            sum = sum + i;
+            i = i + 1;
+        }
+        for let mut j = 0; j < n; j = j + 1 {
+            sum = sum + j;
+        }
+        return sum;
+    }
+
+    fn find(n: i32): i32 {
+        let i = 0;
+        while i < n {
+            return i;
+        }
+        return n;
+    }
+
+    fn main() {
+    }
+    "#;
+
+    let warnings = analyze_warnings(source);
+    assert!(warnings.is_empty(), "{:?}", warnings);
+}
+
+#[test]
+fn test_mutable_borrow_suppresses_warning() {
+    // &mutで渡した変数は呼び出し先で変更され得る
+    let source = r#"
+    package main
+
+    type Counter struct {
+        value: i32,
+    }
+
+    fn bump(x: &mut i32) {
+        *x = *x + 1;
+    }
+
+    fn advance(mut counter: &mut Counter) {
+        counter.value = counter.value + 1;
+    }
+
+    fn count(n: i32): i32 {
+        let mut i = 0;
+        while i < n {
+            bump(&mut i);
+        }
+        return i;
+    }
+
+    fn count_through(mut counter: &mut Counter, n: i32) {
+        while counter.value < n {
+            advance(counter);
+        }
+    }
+
+    fn main() {
+    }
+    "#;
+
+    let warnings = analyze_warnings(source);
+    assert!(warnings.is_empty(), "{:?}", warnings);
+}
+
+#[test]
+fn test_unconditional_loop_without_return_warns() {
+    let source = r#"
+    package main
+
+    fn first_positive(x: i32): i32 {
+        while true {
+            if x > 0 {
+                return x;
+            }
+        }
+        return 0;
+    }
+
+    fn spin() {
+        let mut n = 0;
+        while true {
+            n = n + 1;
+        }
+    }
+
+    fn main() {
+    }
+    "#;
+
+    let warnings = analyze_warnings(source);
+    assert!(matches!(
+        warnings.as_slice(),
+        [AnalyzerError::InfiniteLoop { span }] if &source[span.start..span.end] == "true"
+    ), "{:?}", warnings);
+}
@@ -183,4 +183,533 @@ fn test_static_method_registration() {
     "#;
     
     assert_analysis_success(source);
-}
\ No newline at end of file
+}
+#[test]
+fn test_method_call_declared_later() {
+    // 呼び出し箇所より後に定義されたメソッドの解決テスト
+    let source = r#"
+    package main
+    
+    fn main() {
+        let c = Counter { count: 1 };
+        let n: i32 = c.get();
+    }
+    
+    impl fn get(c: &Counter): i32 {
+        return c.count;
+    }
+    
+    struct Counter {
+        count: i32,
+    }
+    "#;
+    
+    assert_analysis_success(source);
+}
+
+#[test]
+fn test_method_call_through_reference() {
+    // 参照経由でのメソッド呼び出しのテスト
+    let source = r#"
+    package main
+    
+    struct Counter {
+        count: i32,
+    }
+    
+    impl fn get(c: &Counter): i32 {
+        return c.count;
+    }
+    
+    impl fn doubled(c: &Counter): i32 {
+        return c.get() * 2;
+    }
+    
+    fn main() {
+        let c = Counter { count: 1 };
+        let n: i32 = c.doubled();
+    }
+    "#;
+    
+    assert_analysis_success(source);
+}
+
+#[test]
+fn test_method_call_argument_count_mismatch() {
+    // メソッド呼び出しの引数数エラーのテスト（MethodNotFoundにならないこと）
+    let source = r#"
+    package main
+    
+    struct Counter {
+        count: i32,
+    }
+    
+    impl fn add(c: &Counter, n: i32): i32 {
+        return c.count + n;
+    }
+    
+    fn main() {
+        let c = Counter { count: 1 };
+        let n: i32 = c.add(1, 2);
+    }
+    "#;
+    
+    assert_specific_error(source, |e| {
+        matches!(e, AnalyzerError::ArgumentCountMismatch { expected: 1, found: 2, .. })
+    });
+}
+
+#[test]
+fn test_method_duplicate_across_receiver_kinds_error() {
+    // 値レシーバーと参照レシーバーで同名メソッドを定義した場合の重複エラーテスト
+    let source = r#"
+    package main
+    
+    struct Counter {
+        count: i32,
+    }
+    
+    impl fn get(c: Counter): i32 {
+        return c.count;
+    }
+    
+    impl fn get(c: &Counter): i32 {
+        return c.count;
+    }
+    
+    fn main() {
+    }
+    "#;
+    
+    assert_specific_error(source, |e| {
+        matches!(e, AnalyzerError::DuplicateFunction { .. })
+    });
+}
+
+#[test]
+fn test_string_builder_methods() {
+    // 組み込みのStringBuilderのメソッド呼び出しのテスト
+    let source = r#"
+    package main
+    
+    fn fill(sb: &StringBuilder) {
+        sb.append("n=");
+        sb.append(42);
+        sb.append(1.5);
+        sb.append(true);
+    }
+    
+    fn main() {
+        let sb: StringBuilder = StringBuilder{};
+        fill(&sb);
+        let len: i64 = sb.len();
+        let s: String = sb.to_string();
+    }
+    "#;
+    
+    assert_analysis_success(source);
+}
+
+#[test]
+fn test_string_builder_method_errors() {
+    // StringBuilderに存在しないメソッドや、文字列にできない値の追加はエラー
+    let unknown_method = r#"
+    package main
+    
+    fn main() {
+        let sb = StringBuilder{};
+        sb.clear();
+    }
+    "#;
+    assert_specific_error(unknown_method, |e| {
+        matches!(e, AnalyzerError::MethodNotFound { .. })
+    });
+    
+    let unprintable = r#"
+    package main
+    
+    struct Point {
+        x: i32,
+    }
+    
+    fn main() {
+        let sb = StringBuilder{};
+        sb.append(Point { x: 1 });
+    }
+    "#;
+    assert_specific_error(unprintable, |e| {
+        matches!(e, AnalyzerError::NotDisplayable { ty, .. } if ty == "Point")
+    });
+    
+    let too_many_args = r#"
+    package main
+    
+    fn main() {
+        let sb = StringBuilder{};
+        sb.append("a", "b");
+    }
+    "#;
+    assert_specific_error(too_many_args, |e| {
+        matches!(e, AnalyzerError::ArgumentCountMismatch { expected: 1, found: 2, .. })
+    });
+}
+
+#[test]
+fn test_impl_block_methods_resolve() {
+    // implブロックで定義したメソッドを、定義より前の呼び出しや `impl fn` のメソッドから呼び出す
+    let source = r#"
+    package main
+
+    fn main() {
+        let mut p = Point { x: 3.0, y: 4.0 };
+        let scaled: f64 = p.scale(2.0);
+        let total: f64 = p.sum();
+        let twice: f64 = p.doubled();
+    }
+
+    impl Point {
+        fn sum(&self): f64 {
+            return self.x + self.y;
+        }
+
+        fn scale(&mut self, k: f64): f64 {
+            return self.sum() * k;
+        }
+    }
+
+    impl fn doubled(p: &Point): f64 {
+        return p.sum() * 2.0;
+    }
+
+    type Point struct {
+        x: f64,
+        y: f64
+    }
+    "#;
+
+    assert_analysis_success(source);
+}
+
+#[test]
+fn test_impl_block_duplicate_with_impl_fn_error() {
+    // implブロックと `impl fn` で同名のメソッドを定義すると重複エラー
+    let source = r#"
+    package main
+
+    type Counter struct {
+        count: i32
+    }
+
+    impl Counter {
+        fn get(&self): i32 {
+            return self.count;
+        }
+    }
+
+    impl fn get(c: &Counter): i32 {
+        return c.count;
+    }
+
+    fn main() {
+    }
+    "#;
+
+    assert_specific_error(source, |e| {
+        matches!(e, AnalyzerError::DuplicateFunction { name, .. } if name == "Counter::get")
+    });
+}
+
+#[test]
+fn test_builtin_methods() {
+    // 文字列・Vec・HashMap・数値型の組み込みメソッド
+    let source = r#"
+    package main
+    
+    fn count(v: &Vec<i32>): i64 {
+        return v.len();
+    }
+    
+    fn main() {
+        let s = "hello";
+        let v = Vec<i32>[1, 2, 3];
+        let m = HashMap<String, i32> { "a": 1 };
+        let total: i64 = s.len() + v.len() + m.len() + count(&v);
+        let n: i32 = -5;
+        let text: String = n.to_string();
+        let positive: i32 = n.abs();
+        let low: i32 = n.min(3);
+        let high: i32 = n.max(3);
+        let big: u64 = 7u64;
+        let small: u64 = big.min(2u64);
+        let x: f64 = -1.5;
+        let y: f64 = x.abs().max(0.5);
+        let label: String = x.to_string();
+    }
+    "#;
+    
+    assert_analysis_success(source);
+}
+
+#[test]
+fn test_builtin_method_errors() {
+    // 引数の型はレシーバーと同じ型
+    let wrong_type = r#"
+    package main
+    
+    fn main() {
+        let n: i32 = 5;
+        let m = n.min(1.5);
+    }
+    "#;
+    assert_specific_error(wrong_type, |e| {
+        matches!(e, AnalyzerError::TypeMismatch { .. })
+    });
+    
+    let wrong_count = r#"
+    package main
+    
+    fn main() {
+        let n: i32 = 5;
+        let m = n.max();
+    }
+    "#;
+    assert_specific_error(wrong_count, |e| {
+        matches!(e, AnalyzerError::ArgumentCountMismatch { expected: 1, found: 0, .. })
+    });
+}
+
+#[test]
+fn test_unknown_builtin_method_lists_available_methods() {
+    // 組み込み型に存在しないメソッドは、その型で使えるメソッドを示す
+    let unsigned = r#"
+    package main
+    
+    fn main() {
+        let n: u32 = 5u32;
+        let m = n.abs();
+    }
+    "#;
+    assert_specific_error(unsigned, |e| match e {
+        AnalyzerError::MethodNotFound { method, ty, available, .. } => {
+            method == "abs" && ty == "u32" && available == &["to_string", "min", "max"]
+        }
+        _ => false,
+    });
+    
+    let vec = r#"
+    package main
+    
+    fn main() {
+        let v = Vec<i32>[1, 2];
+        let n = v.size();
+    }
+    "#;
+    assert_specific_error(vec, |e| match e {
+        AnalyzerError::MethodNotFound { ty, available, .. } => {
+            ty == "Vec<i32>" && available == &["len", "push", "resize", "sort", "sort_by", "binary_search"]
+        }
+        _ => false,
+    });
+    
+    let source = r#"
+    package main
+    
+    fn main() {
+        let s = "hello";
+        let n = s.length();
+    }
+    "#;
+    let error = analyze_source(source).expect_err("Analysis should fail");
+    let diagnostic = yunilang::error::DiagnosticError::new(error, 0).to_diagnostic();
+    assert_eq!(diagnostic.notes, vec!["str で使えるメソッド: len, to_string, starts_with, find, split, trim".to_string()]);
+}
+
+#[test]
+fn test_vec_resize() {
+    // resizeは長さ（i64）と、伸ばした要素を埋める要素の型の値を受け取る
+    let source = r#"
+    package main
+    
+    fn main() {
+        let mut values: Vec<u8> = [1, 2];
+        let n: i32 = 4;
+        values.resize(n as i64, 0);
+        values.resize(8, 255);
+    }
+    "#;
+    assert_analysis_success(source);
+    
+    let wrong_fill = r#"
+    package main
+    
+    fn main() {
+        let mut values: Vec<i64> = [1, 2];
+        values.resize(4, "zero");
+    }
+    "#;
+    assert_specific_error(wrong_fill, |e| {
+        matches!(e, AnalyzerError::TypeMismatch { .. })
+    });
+}
+
+#[test]
+fn test_string_search_methods() {
+    // 文字列の検索・分割のメソッドは、str・String・&Stringのどれでも引数に取れる
+    let source = r#"
+    package main
+    
+    fn fields(line: &String): Vec<String> {
+        return line.split(",");
+    }
+    
+    fn main() {
+        let line: String = " a,b ".trim();
+        let comma = ",";
+        let is_comment: bool = line.starts_with("//");
+        let position: i64 = line.find(comma);
+        let parts: Vec<String> = fields(&line);
+        let first: String = parts[0].trim();
+        let again: bool = "a,b".starts_with(&line);
+    }
+    "#;
+    assert_analysis_success(source);
+    
+    let wrong_type = r#"
+    package main
+    
+    fn main() {
+        let position = "a,b".find(1);
+    }
+    "#;
+    assert_specific_error(wrong_type, |e| {
+        matches!(e, AnalyzerError::TypeMismatch { .. })
+    });
+}
+
+#[test]
+fn test_vec_sort_and_binary_search() {
+    // sortとbinary_searchは順序のある要素の型、sort_byは比較関数の名前を受け取る
+    let source = r#"
+    package main
+    
+    struct Point {
+        x: i32,
+    }
+    
+    fn by_x(a: Point, b: Point): i32 {
+        return a.x - b.x;
+    }
+    
+    fn main() {
+        let mut numbers: Vec<i64> = [5, 3, 9];
+        numbers.sort();
+        let found: i64 = numbers.binary_search(9);
+        let mut names = Vec<String>["pear", "apple"];
+        names.sort();
+        let mut weights: Vec<f64> = [2.5, 1.0];
+        weights.sort();
+        let mut points = Vec<Point>[];
+        points.sort_by(by_x);
+    }
+    "#;
+    assert_analysis_success(source);
+    
+    // 順序のない要素の型のVecにはsortとbinary_searchがない
+    let unordered = r#"
+    package main
+    
+    fn main() {
+        let mut flags = Vec<bool>[true, false];
+        flags.sort();
+    }
+    "#;
+    assert_specific_error(unordered, |e| match e {
+        AnalyzerError::MethodNotFound { ty, available, .. } => ty == "Vec<bool>" && available == &["len", "push", "resize", "sort_by"],
+        _ => false,
+    });
+    
+    // 比較関数の引数は要素の型で、戻り値はi32
+    let wrong_signature = r#"
+    package main
+    
+    fn by_value(a: i32, b: i32): i32 {
+        return a - b;
+    }
+    
+    fn main() {
+        let mut numbers: Vec<i64> = [5, 3];
+        numbers.sort_by(by_value);
+    }
+    "#;
+    assert_specific_error(wrong_signature, |e| match e {
+        AnalyzerError::TypeMismatch { expected, found, .. } => {
+            expected == "fn(i64, i64) -> i32" && found == "fn(i32, i32) -> i32"
+        }
+        _ => false,
+    });
+    
+    // 関数の名前以外は受け取らない
+    let not_function = r#"
+    package main
+    
+    fn main() {
+        let mut numbers: Vec<i64> = [5, 3];
+        let order = 1;
+        numbers.sort_by(order);
+    }
+    "#;
+    assert_specific_error(not_function, |e| matches!(e, AnalyzerError::InvalidOperation { .. }));
+}
+
+#[test]
+fn test_hashmap_index_and_vec_push() {
+    // HashMapのインデックスはキーの型、インデックスアクセスの値と代入する値は値の型
+    let source = r#"
+    package main
+    
+    fn main() {
+        let mut scores = HashMap<String, i32> { "alice": 1 };
+        scores["bob"] = 2;
+        scores["alice"] = scores["alice"] + 10;
+        let total: i32 = scores["alice"] + scores["bob"];
+        let mut groups = HashMap<String, Vec<i32> > { "a": Vec<i32>[] };
+        groups["a"].push(1);
+        let first: i32 = groups["a"][0];
+    }
+    "#;
+    assert_analysis_success(source);
+    
+    let wrong_key = r#"
+    package main
+    
+    fn main() {
+        let scores = HashMap<String, i32> { "alice": 1 };
+        let n = scores[0];
+    }
+    "#;
+    assert_specific_error(wrong_key, |e| match e {
+        AnalyzerError::TypeMismatch { expected, found, .. } => expected == "String" && found.starts_with('i'),
+        _ => false,
+    });
+    
+    let wrong_value = r#"
+    package main
+    
+    fn main() {
+        let mut scores = HashMap<String, i32> { "alice": 1 };
+        scores["bob"] = "two";
+    }
+    "#;
+    assert_specific_error(wrong_value, |e| matches!(e, AnalyzerError::TypeMismatch { .. }));
+    
+    // pushの引数はVecの要素の型
+    let wrong_element = r#"
+    package main
+    
+    fn main() {
+        let v = Vec<i32>[1];
+        v.push("two");
+    }
+    "#;
+    assert_specific_error(wrong_element, |e| matches!(e, AnalyzerError::TypeMismatch { .. }));
+}
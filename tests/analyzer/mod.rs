@@ -42,6 +42,25 @@ where F: Fn(&AnalyzerError) -> bool {
     }
 }
 
+/// 解析に成功することを確認し、警告を返すヘルパー関数
+pub fn analyze_warnings(source: &str) -> Vec<AnalyzerError> {
+    let tokens: Vec<_> = Lexer::new(source).collect_tokens();
+    let ast = Parser::new(tokens).parse().expect("Parsing should succeed");
+    let mut analyzer = SemanticAnalyzer::new();
+    let result = analyzer.analyze(&ast);
+    assert!(result.is_ok(), "Analysis should succeed: {:?}", result);
+    analyzer.warnings
+}
+
+/// セマンティック解析を実行し、報告したエラーをすべて返すヘルパー関数
+pub fn analyze_errors(source: &str) -> Vec<AnalyzerError> {
+    let tokens: Vec<_> = Lexer::new(source).collect_tokens();
+    let ast = Parser::new(tokens).parse().expect("Parsing should succeed");
+    let mut analyzer = SemanticAnalyzer::new();
+    let _ = analyzer.analyze(&ast);
+    analyzer.errors
+}
+
 // サブモジュールの宣言
 #[cfg(test)]
 mod type_checking_test;
@@ -58,4 +77,27 @@ mod error_type_test;
 #[cfg(test)]
 mod method_test;
 #[cfg(test)]
-mod advanced_test;
\ No newline at end of file
+mod advanced_test;
+#[cfg(test)]
+mod tail_position_test;
+#[cfg(test)]
+mod prelude_test;
+#[cfg(test)]
+mod loop_progress_test;
+#[cfg(test)]
+mod const_prop_test;
+
+#[cfg(test)]
+mod declaration_order_test;
+#[cfg(test)]
+mod extern_test;
+#[cfg(test)]
+mod destructuring_test;
+#[cfg(test)]
+mod overload_test;
+#[cfg(test)]
+mod error_recovery_test;
+#[cfg(test)]
+mod lives_clause_test;
+#[cfg(test)]
+mod local_function_test;
@@ -0,0 +1,155 @@
+//! 引数の数による関数のオーバーロードのテスト
+
+use super::*;
+use yunilang::analyzer::{resolve_names, NameResolution};
+
+/// 名前解決してからセマンティック解析を実行するヘルパー関数
+fn resolve_and_analyze(source: &str) -> (Program, NameResolution, Result<(), AnalyzerError>) {
+    let tokens: Vec<_> = Lexer::new(source).collect_tokens();
+    let mut ast = Parser::new(tokens).parse().expect("Parsing should succeed");
+    let resolution = resolve_names(&mut ast);
+    let result = SemanticAnalyzer::new().analyze(&ast);
+    (ast, resolution, result)
+}
+
+/// 関数 `function` の中で呼び出している関数の名前
+fn callees(program: &Program, function: &str) -> Vec<String> {
+    let func = program.items.iter().find_map(|item| match item {
+        Item::Function(func) if func.name == function => Some(func),
+        _ => None,
+    }).expect("function should exist");
+    func.body.statements.iter().filter_map(|stmt| match stmt {
+        Statement::Let(LetStatement { init: Some(Expression::Call(call)), .. }) => match call.callee.as_ref() {
+            Expression::Identifier(ident) => Some(ident.name.clone()),
+            _ => None,
+        },
+        _ => None,
+    }).collect()
+}
+
+#[test]
+fn test_overload_by_arity() {
+    // 引数の数が異なる定義は、呼び出しの引数の数で選ばれる
+    let source = r#"
+    package main
+
+    fn area(side: f64): f64 {
+        return side * side;
+    }
+
+    fn area(width: f64, height: f64): f64 {
+        return width * height;
+    }
+
+    fn main() {
+        let square: f64 = area(3.0);
+        let rect: f64 = area(2.0, 5.0);
+    }
+    "#;
+
+    let (program, resolution, result) = resolve_and_analyze(source);
+    assert!(resolution.errors.is_empty(), "{:?}", resolution.errors);
+    assert!(result.is_ok(), "Analysis should succeed: {:?}", result);
+
+    // 定義は引数の数ごとの名前に改名される
+    let names: Vec<&str> = program.items.iter().filter_map(|item| match item {
+        Item::Function(func) => Some(func.name.as_str()),
+        _ => None,
+    }).collect();
+    assert_eq!(names, vec!["area/1", "area/2", "main"]);
+    assert_eq!(callees(&program, "main"), vec!["area/1", "area/2"]);
+}
+
+#[test]
+fn test_overload_with_different_return_types() {
+    // 戻り値の型は定義ごとに検査される
+    let source = r#"
+    package main
+
+    fn describe(n: i32): String {
+        return "one";
+    }
+
+    fn describe(a: i32, b: i32): i32 {
+        return a + b;
+    }
+
+    fn main() {
+        let text: String = describe(1);
+        let sum: String = describe(1, 2);
+    }
+    "#;
+
+    let (_, resolution, result) = resolve_and_analyze(source);
+    assert!(resolution.errors.is_empty(), "{:?}", resolution.errors);
+    assert!(matches!(
+        result,
+        Err(AnalyzerError::TypeMismatch { ref expected, ref found, .. }) if expected == "String" && found == "i32"
+    ), "{:?}", result);
+}
+
+#[test]
+fn test_overload_without_matching_arity() {
+    // 引数の数が一致する定義がなければ、定義されている引数の数を示す
+    let source = r#"
+    package main
+
+    fn area(side: f64): f64 {
+        return side * side;
+    }
+
+    fn area(width: f64, height: f64): f64 {
+        return width * height;
+    }
+
+    fn main() {
+        let volume: f64 = area(1.0, 2.0, 3.0);
+    }
+    "#;
+
+    let (_, resolution, _) = resolve_and_analyze(source);
+    assert_eq!(resolution.errors.len(), 1, "{:?}", resolution.errors);
+    let AnalyzerError::NoOverloadForArity { name, arity, arities, span } = &resolution.errors[0] else {
+        panic!("Expected NoOverloadForArity, got {:?}", resolution.errors[0]);
+    };
+    assert_eq!(name, "area");
+    assert_eq!(*arity, 3);
+    assert_eq!(arities, &vec![1, 2]);
+    assert_eq!(&source[span.start..span.end], "area");
+
+    let notes = yunilang::error::DiagnosticError::new(YuniError::Analyzer(resolution.errors[0].clone()), 0)
+        .to_diagnostic()
+        .notes;
+    assert_eq!(notes, vec!["area の定義の引数の数: 1, 2".to_string()]);
+}
+
+#[test]
+fn test_same_arity_overload_is_rejected() {
+    // 引数の型だけが異なる定義はオーバーロードできず、名前を変えるよう示す
+    let source = r#"
+    package main
+
+    fn show(x: i32) {
+        println(x);
+    }
+
+    fn show(x: String) {
+        println(x);
+    }
+
+    fn main() {
+        show(1);
+    }
+    "#;
+
+    let (_, resolution, result) = resolve_and_analyze(source);
+    assert!(resolution.errors.is_empty(), "{:?}", resolution.errors);
+    let Err(error @ AnalyzerError::DuplicateFunction { .. }) = result else {
+        panic!("Expected DuplicateFunction, got {:?}", result);
+    };
+    let notes = yunilang::error::DiagnosticError::new(YuniError::Analyzer(error), 0).to_diagnostic().notes;
+    assert_eq!(
+        notes,
+        vec!["関数は引数の数が異なる場合だけオーバーロードできます。引数の型だけが異なる関数は名前を変えてください".to_string()]
+    );
+}
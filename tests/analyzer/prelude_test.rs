@@ -0,0 +1,375 @@
+//! 標準ライブラリのプレリュードと名前解決のテスト
+
+use super::*;
+use yunilang::analyzer::{resolve_names, NameResolution};
+
+/// 名前解決してからセマンティック解析を実行するヘルパー関数
+fn resolve_and_analyze(source: &str) -> (Program, NameResolution, Result<(), AnalyzerError>) {
+    let tokens: Vec<_> = Lexer::new(source).collect_tokens();
+    let mut ast = Parser::new(tokens).parse().expect("Parsing should succeed");
+    let resolution = resolve_names(&mut ast);
+    let result = SemanticAnalyzer::new().analyze(&ast);
+    (ast, resolution, result)
+}
+
+fn let_type<'a>(program: &'a Program, function: &str, index: usize) -> &'a Type {
+    let func = program.items.iter().find_map(|item| match item {
+        Item::Function(func) if func.name == function => Some(func),
+        _ => None,
+    }).expect("function should exist");
+    match &func.body.statements[index] {
+        Statement::Let(let_stmt) => let_stmt.ty.as_ref().expect("let should have a type annotation"),
+        other => panic!("Expected let statement, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_user_defined_vec_shadows_prelude() {
+    // ユーザー定義のVecは警告付きでビルトインのVecを隠す
+    let source = r#"
+    package main
+
+    import "std/collections/Vec" as List
+
+    struct Vec {
+        len: i32,
+    }
+
+    fn size(v: Vec): i32 {
+        return v.len;
+    }
+
+    fn main() {
+        let v: Vec = Vec { len: 3 };
+        let n: i32 = size(v);
+        let xs: List<i32> = List<i32>[1, 2, 3];
+    }
+    "#;
+
+    let (program, resolution, result) = resolve_and_analyze(source);
+    assert!(resolution.errors.is_empty(), "{:?}", resolution.errors);
+    assert!(result.is_ok(), "Analysis should succeed: {:?}", result);
+
+    assert_eq!(resolution.warnings.len(), 1, "{:?}", resolution.warnings);
+    assert!(matches!(
+        &resolution.warnings[0],
+        AnalyzerError::ShadowsPrelude { name, path, .. } if name == "Vec" && path == "std/collections/Vec"
+    ), "{:?}", resolution.warnings[0]);
+
+    // ユーザー定義はビルトインと区別できる名前になり、別名のインポートはビルトインを指す
+    assert!(matches!(&program.items[0], Item::TypeDef(TypeDef::Struct(s)) if s.name == "main.Vec"));
+    assert_eq!(let_type(&program, "main", 0), &Type::UserDefined("main.Vec".to_string()));
+    assert_eq!(let_type(&program, "main", 2), &Type::Generic("Vec".to_string(), vec![Type::I32]));
+}
+
+#[test]
+fn test_aliased_hashmap_import_in_type_annotation() {
+    // 別名でインポートしたHashMapを型注釈と初期化子に使う
+    let source = r#"
+    package main
+
+    import "std.collections.HashMap" as Map
+
+    fn main() {
+        let prices: Map<String, i32> = Map<String, i32>{ "apple": 100 };
+    }
+    "#;
+
+    let (program, resolution, result) = resolve_and_analyze(source);
+    assert!(resolution.errors.is_empty(), "{:?}", resolution.errors);
+    assert!(resolution.warnings.is_empty(), "{:?}", resolution.warnings);
+    assert!(result.is_ok(), "Analysis should succeed: {:?}", result);
+    assert_eq!(
+        let_type(&program, "main", 0),
+        &Type::Generic("HashMap".to_string(), vec![Type::String, Type::I32])
+    );
+}
+
+#[test]
+fn test_no_prelude_requires_imports() {
+    // no_preludeのファイルではインポートしていないビルトインは未定義
+    let source = r#"
+    package main no_prelude
+
+    fn main() {
+        let xs: Vec<i32> = Vec<i32>[1, 2];
+    }
+    "#;
+
+    let (program, resolution, _) = resolve_and_analyze(source);
+    assert!(program.package.no_prelude);
+    assert!(!resolution.errors.is_empty());
+    assert!(resolution.errors.iter().all(|error| matches!(
+        error,
+        AnalyzerError::PreludeDisabled { name, path, .. } if name == "Vec" && path == "std/collections/Vec"
+    )), "{:?}", resolution.errors);
+
+    // モジュールをインポートすれば使える
+    let source = r#"
+    package main no_prelude
+
+    import "std/collections"
+
+    fn main() {
+        let xs: Vec<i32> = Vec<i32>[1, 2];
+    }
+    "#;
+
+    let (_, resolution, result) = resolve_and_analyze(source);
+    assert!(resolution.errors.is_empty(), "{:?}", resolution.errors);
+    assert!(result.is_ok(), "Analysis should succeed: {:?}", result);
+
+    // 標準ライブラリにないパスはエラー
+    let source = r#"
+    package main
+
+    import "std/collections/Deque"
+
+    fn main() {
+    }
+    "#;
+
+    let (_, resolution, _) = resolve_and_analyze(source);
+    assert!(matches!(
+        resolution.errors.as_slice(),
+        [AnalyzerError::UnknownStdImport { path, .. }] if path == "std/collections/Deque"
+    ), "{:?}", resolution.errors);
+}
+
+#[test]
+fn test_selective_import_binds_listed_symbols() {
+    // 選択した要素だけが（別名があれば別名で）使える
+    let source = r#"
+    package main no_prelude
+
+    import "std/collections" { Vec, HashMap as Map }
+
+    fn main() {
+        let xs: Vec<i32> = Vec<i32>[1, 2];
+        let prices: Map<String, i32> = Map<String, i32>{ "apple": 100 };
+    }
+    "#;
+
+    let (program, resolution, result) = resolve_and_analyze(source);
+    assert!(resolution.errors.is_empty(), "{:?}", resolution.errors);
+    assert!(result.is_ok(), "Analysis should succeed: {:?}", result);
+    assert_eq!(
+        let_type(&program, "main", 1),
+        &Type::Generic("HashMap".to_string(), vec![Type::String, Type::I32])
+    );
+
+    // 選択しなかった要素はno_preludeでは使えない
+    let source = r#"
+    package main no_prelude
+
+    import std.collections { Vec }
+
+    fn main() {
+        let prices: HashMap<String, i32> = HashMap<String, i32>{ "apple": 100 };
+    }
+    "#;
+
+    let (_, resolution, _) = resolve_and_analyze(source);
+    assert!(!resolution.errors.is_empty());
+    assert!(resolution.errors.iter().all(|error| matches!(
+        error,
+        AnalyzerError::PreludeDisabled { name, .. } if name == "HashMap"
+    )), "{:?}", resolution.errors);
+}
+
+#[test]
+fn test_selective_import_unknown_symbol() {
+    // モジュールにない要素はエラーで、インポートできる名前を示す
+    let source = r#"
+    package main
+
+    import "std/collections" { Vec, Deque }
+
+    fn main() {
+    }
+    "#;
+
+    let (_, resolution, _) = resolve_and_analyze(source);
+    match resolution.errors.as_slice() {
+        [error @ AnalyzerError::UnknownImportSymbol { name, path, span, .. }] => {
+            assert_eq!(name, "Deque");
+            assert_eq!(path, "std/collections");
+            assert_eq!(&source[span.start..span.end], "Deque");
+            let notes = yunilang::error::DiagnosticError::new(YuniError::Analyzer(error.clone()), 0).to_diagnostic().notes;
+            assert_eq!(notes, ["std/collections からインポートできる名前: Vec, HashMap"]);
+        }
+        other => panic!("Expected UnknownImportSymbol, got {:?}", other),
+    }
+
+    // 標準ライブラリ以外のモジュールは未解決として警告する
+    let source = r#"
+    package main
+
+    import foo.bar { baz }
+
+    fn main() {
+    }
+    "#;
+
+    let (_, resolution, _) = resolve_and_analyze(source);
+    assert!(resolution.errors.is_empty(), "{:?}", resolution.errors);
+    assert!(matches!(
+        resolution.warnings.as_slice(),
+        [AnalyzerError::UnresolvedImportSymbols { path, .. }] if path == "foo.bar"
+    ), "{:?}", resolution.warnings);
+}
+
+#[test]
+fn test_selective_import_conflicts() {
+    // 同じ名前を2回インポートするとエラーで、両方の位置を示す
+    let source = r#"
+    package main
+
+    import "std/collections/HashMap" as Map
+    import "std/collections" { Vec as Map }
+
+    fn main() {
+    }
+    "#;
+
+    let (_, resolution, _) = resolve_and_analyze(source);
+    match resolution.errors.as_slice() {
+        [AnalyzerError::DuplicateImport { name, span, previous }] => {
+            assert_eq!(name, "Map");
+            assert_eq!(&source[span.start..span.end], "Vec as Map");
+            assert_eq!(&source[previous.start..previous.end], "\"std/collections/HashMap\" as Map");
+        }
+        other => panic!("Expected DuplicateImport, got {:?}", other),
+    }
+
+    // インポートした名前と同じ名前の定義はエラーで、定義とインポートの位置を示す
+    let source = r#"
+    package main
+
+    import "std/collections" { Vec, HashMap }
+
+    struct HashMap {
+        size: i32,
+    }
+
+    fn main() {
+    }
+    "#;
+
+    let (_, resolution, _) = resolve_and_analyze(source);
+    match resolution.errors.as_slice() {
+        [AnalyzerError::ImportConflictsWithDefinition { name, span, import_span }] => {
+            assert_eq!(name, "HashMap");
+            assert!(source[span.start..span.end].starts_with("struct HashMap"));
+            assert_eq!(&source[import_span.start..import_span.end], "HashMap");
+        }
+        other => panic!("Expected ImportConflictsWithDefinition, got {:?}", other),
+    }
+    // 衝突したインポートは未使用としては報告しない
+    assert!(matches!(
+        resolution.warnings.as_slice(),
+        [AnalyzerError::UnusedImport { name, .. }] if name == "Vec"
+    ), "{:?}", resolution.warnings);
+
+    // 別名でインポートすれば同じ名前を定義できる
+    let source = r#"
+    package main
+
+    import "std/collections" { HashMap as Map }
+
+    struct HashMap {
+        size: i32,
+    }
+
+    fn main() {
+        let m: Map<String, i32> = Map<String, i32>{ "a": 1 };
+    }
+    "#;
+
+    let (_, resolution, result) = resolve_and_analyze(source);
+    assert!(resolution.errors.is_empty(), "{:?}", resolution.errors);
+    assert!(result.is_ok(), "Analysis should succeed: {:?}", result);
+}
+
+#[test]
+fn test_unused_import_warns() {
+    // 一度も使われないインポートはインポート文の位置で警告し、選択インポートは要素ごとに報告する
+    let source = r#"
+    package main
+
+    import "std/math/sqrt"
+    import "std/collections" { Vec, HashMap as Map }
+
+    fn main() {
+        let xs: Vec<i32> = Vec<i32>[1, 2];
+    }
+    "#;
+
+    let (_, resolution, result) = resolve_and_analyze(source);
+    assert!(resolution.errors.is_empty(), "{:?}", resolution.errors);
+    assert!(result.is_ok(), "Analysis should succeed: {:?}", result);
+    let unused: Vec<(&str, &str)> = resolution
+        .warnings
+        .iter()
+        .map(|warning| match warning {
+            AnalyzerError::UnusedImport { name, span } => (name.as_str(), &source[span.start..span.end]),
+            other => panic!("Expected UnusedImport, got {:?}", other),
+        })
+        .collect();
+    assert_eq!(unused, [("sqrt", "\"std/math/sqrt\""), ("Map", "HashMap as Map")]);
+}
+
+#[test]
+fn test_import_used_through_qualified_path() {
+    // パスの先頭（`Opt::Some`）として使われたインポートは使用済み
+    let source = r#"
+    package main
+
+    import "std/option/Option" as Opt
+    import "std/io"
+
+    fn main() {
+        let value = Opt::Some(1);
+        println("done");
+    }
+    "#;
+
+    let (_, resolution, _) = resolve_and_analyze(source);
+    assert!(resolution.errors.is_empty(), "{:?}", resolution.errors);
+    assert!(resolution.warnings.is_empty(), "{:?}", resolution.warnings);
+}
+
+#[test]
+fn test_definition_shadows_module_import() {
+    // モジュールのインポートの要素と同じ名前の定義はユーザー定義が優先され、両方の位置を警告する
+    let source = r#"
+    package main
+
+    import "std/collections"
+
+    struct Vec {
+        len: i32,
+    }
+
+    fn main() {
+        let v = Vec { len: 3 };
+        let m = HashMap<String, i32> { "a": 1 };
+    }
+    "#;
+
+    let (program, resolution, result) = resolve_and_analyze(source);
+    assert!(resolution.errors.is_empty(), "{:?}", resolution.errors);
+    assert!(result.is_ok(), "Analysis should succeed: {:?}", result);
+    match resolution.warnings.as_slice() {
+        [warning @ AnalyzerError::ShadowedImport { name, path, span, import_span }] => {
+            assert_eq!(name, "Vec");
+            assert_eq!(path, "std/collections");
+            assert!(source[span.start..span.end].starts_with("struct Vec"));
+            assert_eq!(&source[import_span.start..import_span.end], "\"std/collections\"");
+            let diagnostic = yunilang::error::DiagnosticError::new(YuniError::Analyzer(warning.clone()), 0).to_diagnostic();
+            assert_eq!(diagnostic.labels[0].message, "このファイルでは Vec はこの定義を指します");
+        }
+        other => panic!("Expected ShadowedImport, got {:?}", other),
+    }
+    assert!(matches!(&program.items[0], Item::TypeDef(TypeDef::Struct(s)) if s.name == "main.Vec"));
+}
@@ -79,4 +79,50 @@ fn test_nested_scope_resolution() {
     "#;
     
     assert_analysis_success(source);
-}
\ No newline at end of file
+}
+#[test]
+fn test_loop_variables_are_scoped_to_the_loop() {
+    // for文のinit変数はループの後では見えない
+    let source = r#"
+    package main
+
+    fn main() {
+        for let mut i: i32 = 0; i < 3; i = i + 1 {
+            let doubled = i * 2;
+        }
+        let after = i;
+    }
+    "#;
+    assert_specific_error(source, |e| matches!(e, AnalyzerError::UndefinedVariable { name, .. } if name == "i"));
+
+    // ループ本体で宣言した変数もループの後では見えない
+    let source = r#"
+    package main
+
+    fn main() {
+        let mut n: i32 = 0;
+        while n < 3 {
+            let inner: i32 = n;
+            n = n + 1;
+        }
+        let after = inner;
+    }
+    "#;
+    assert_specific_error(source, |e| matches!(e, AnalyzerError::UndefinedVariable { name, .. } if name == "inner"));
+
+    // 同じ名前のループ変数を続けて宣言できる
+    let source = r#"
+    package main
+
+    fn main() {
+        let mut total: i32 = 0;
+        for let mut i: i32 = 0; i < 3; i = i + 1 {
+            total = total + i;
+        }
+        for let mut i: i32 = 10; i > 7; i = i - 1 {
+            total = total + i;
+        }
+    }
+    "#;
+    assert_analysis_success(source);
+}
@@ -64,4 +64,518 @@ fn test_enum_type_checking() {
     "#;
     
     assert_analysis_success(source);
-}
\ No newline at end of file
+}
+#[test]
+fn test_match_bound_payload_field_access() {
+    // matchで束縛したペイロードの型がフィールドアクセスに伝わることのテスト
+    let source = r#"
+    package main
+    
+    type Item struct {
+        name: String,
+        price: i32
+    }
+    
+    type Lookup enum {
+        Found(item: Item),
+        Named { item: Item, count: i32 },
+        Missing
+    }
+    
+    fn price_of(lookup: Lookup): i32 {
+        return match lookup {
+            Lookup::Found(item) if item.price > 100 => item.price - 10,
+            Lookup::Found(item) => item.price,
+            Lookup::Named { item: found, count: n } => found.price * n,
+            Lookup::Missing => 0,
+        };
+    }
+    
+    fn main() {
+        let name: String = match Lookup::Found(Item { name: "apple", price: 120 }) {
+            Lookup::Found(item) => item.name,
+            _ => "none",
+        };
+    }
+    "#;
+    
+    assert_analysis_success(source);
+}
+
+#[test]
+fn test_match_binding_is_scoped_to_arm() {
+    // matchで束縛した変数はarmの外から参照できない
+    let source = r#"
+    package main
+    
+    type Lookup enum {
+        Found(value: i32),
+        Missing
+    }
+    
+    fn main() {
+        let x = match Lookup::Found(1) {
+            Lookup::Found(value) => value,
+            Lookup::Missing => 0,
+        };
+        let y = value;
+    }
+    "#;
+    
+    assert_specific_error(source, |e| {
+        matches!(e, AnalyzerError::UndefinedVariable { name, .. } if name == "value")
+    });
+}
+
+#[test]
+fn test_match_bound_payload_unknown_field() {
+    // 束縛したペイロードの型に存在しないフィールドはエラー
+    let source = r#"
+    package main
+    
+    type Item struct {
+        price: i32
+    }
+    
+    type Lookup enum {
+        Found(item: Item),
+        Missing
+    }
+    
+    fn main() {
+        let x = match Lookup::Found(Item { price: 1 }) {
+            Lookup::Found(item) => item.weight,
+            Lookup::Missing => 0,
+        };
+    }
+    "#;
+    
+    assert_specific_error(source, |e| {
+        matches!(e, AnalyzerError::UndefinedVariable { name, .. } if name == "Item.weight")
+    });
+}
+
+#[test]
+fn test_match_variant_pattern_arity() {
+    // バリアントパターンのフィールド数はバリアントの定義と一致しなければならない
+    let source = r#"
+    package main
+    
+    type Lookup enum {
+        Found(value: i32),
+        Missing
+    }
+    
+    fn main() {
+        let x = match Lookup::Found(1) {
+            Lookup::Found(a, b) => a,
+            Lookup::Missing => 0,
+        };
+    }
+    "#;
+    
+    assert_specific_error(source, |e| {
+        matches!(e, AnalyzerError::ArgumentCountMismatch { expected: 1, found: 2, .. })
+    });
+}
+
+#[test]
+fn test_variant_pattern_errors_point_at_the_arm() {
+    // バリアントパターンの誤りは、ソースの先頭ではなくパターンを含むアームを指す
+    type Check = fn(&AnalyzerError) -> bool;
+    let cases: [(&str, &str, Check); 5] = [
+        ("Lookup::Found(a, b) => a,", "Lookup::Found(a, b)", |e| {
+            matches!(e, AnalyzerError::ArgumentCountMismatch { expected: 1, found: 2, .. })
+        }),
+        ("Lookup::Gone => 0,", "Lookup::Gone", |e| {
+            matches!(e, AnalyzerError::UnknownPatternVariant { ty, variant, available, .. }
+                if ty == "Lookup" && variant == "Gone" && available == &["Found", "Missing"])
+        }),
+        ("Lookup::Found { weight: w } => w,", "Lookup::Found { weight: w }", |e| {
+            matches!(e, AnalyzerError::UnknownVariantPatternField { variant, field, available, .. }
+                if variant == "Lookup::Found" && field == "weight" && available == &["value"])
+        }),
+        ("Other::Found(a) => a,", "Other::Found(a)", |e| {
+            matches!(e, AnalyzerError::TypeMismatch { expected, found, .. } if expected == "Other" && found == "Lookup")
+        }),
+        ("_ => match 1 { Lookup::Found(a) => a, _ => 0 },", "Lookup::Found(a)", |e| {
+            matches!(e, AnalyzerError::TypeMismatch { expected, .. } if expected == "Lookup")
+        }),
+    ];
+    for (arm, pattern, check) in cases {
+        let source = format!(
+            "package main\n\ntype Lookup enum {{\n    Found(value: i32),\n    Missing\n}}\n\n\
+             type Other enum {{\n    Found(value: i32)\n}}\n\n\
+             fn main() {{\n    let x = match Lookup::Found(1) {{\n        {}\n        _ => 0,\n    }};\n}}\n",
+            arm
+        );
+        let Err(YuniError::Analyzer(error)) = analyze_source(&source) else {
+            panic!("Analysis should fail: {}", arm);
+        };
+        assert!(check(&error), "{}: {:?}", arm, error);
+        let span = match &error {
+            AnalyzerError::ArgumentCountMismatch { span, .. }
+            | AnalyzerError::UnknownPatternVariant { span, .. }
+            | AnalyzerError::UnknownVariantPatternField { span, .. }
+            | AnalyzerError::TypeMismatch { span, .. } => *span,
+            other => panic!("Unexpected error: {:?}", other),
+        };
+        assert!(source[span.start..span.end].starts_with(pattern), "{}: {:?}", arm, &source[span.start..span.end]);
+    }
+}
+
+#[test]
+fn test_bare_variant_in_match_arms_and_call_args() {
+    // 期待される型が列挙型なら、列挙型名を省略してバリアントを書ける
+    let source = r#"
+    package main
+    
+    type Color enum {
+        Red,
+        Green,
+        Blue
+    }
+    
+    type Shape enum {
+        Circle(r: i32),
+        Dot
+    }
+    
+    fn code(color: Color): i32 {
+        return match color {
+            Red => 1,
+            Green => 2,
+            Blue => 3,
+        };
+    }
+    
+    fn area(shape: Shape): i32 {
+        return match shape {
+            Circle(r) => r * r,
+            Dot => 0,
+        };
+    }
+    
+    fn main() {
+        let c: Color = Green;
+        let mut d: Color = Red;
+        d = Blue;
+        let x = code(Red) + code(c) + code(d) + area(Dot);
+    }
+    "#;
+    
+    assert_analysis_success(source);
+}
+
+#[test]
+fn test_bare_variant_match_is_checked_for_exhaustiveness() {
+    // 列挙型名を省略したバリアントのパターンは、すべてに一致する変数の束縛として扱わない
+    let source = r#"
+    package main
+    
+    type Color enum {
+        Red,
+        Green,
+        Blue
+    }
+    
+    fn main() {
+        let x = match Color::Red {
+            Red => 1,
+            Green => 2,
+        };
+    }
+    "#;
+    
+    assert_specific_error(source, |e| matches!(e, AnalyzerError::NonExhaustiveMatch { .. }));
+}
+
+#[test]
+fn test_variable_takes_precedence_over_bare_variant() {
+    // 同名の変数があれば、期待される型が列挙型でも変数として解決する
+    let source = r#"
+    package main
+    
+    type Color enum {
+        Red,
+        Green
+    }
+    
+    fn main() {
+        let Red: i32 = 1;
+        let c: Color = Red;
+    }
+    "#;
+    
+    assert_specific_error(source, |e| matches!(e, AnalyzerError::TypeMismatch { .. }));
+}
+
+#[test]
+fn test_bare_variant_without_expected_type() {
+    // 期待される型がない場合は列挙型を決められないため、列挙型名を付けるよう求める
+    let source = r#"
+    package main
+    
+    type Color enum {
+        Red,
+        Green
+    }
+    
+    fn main() {
+        let c = Red;
+    }
+    "#;
+    
+    assert_specific_error(source, |e| {
+        matches!(e, AnalyzerError::InvalidOperation { message, .. } if message.contains("Color::Red"))
+    });
+}
+
+#[test]
+fn test_or_pattern_over_enum_variants() {
+    // orパターンは選択肢のいずれかに一致し、網羅性には選択肢をそれぞれ数える
+    let source = r#"
+    package main
+    
+    type Shape enum {
+        Circle(r: i32),
+        Square(side: i32),
+        Triangle(base: i32),
+        Dot
+    }
+    
+    fn size(shape: Shape): i32 {
+        return match shape {
+            Shape::Circle(n) | Shape::Square(n) | Shape::Triangle(n) => n,
+            Dot => 0,
+        };
+    }
+    
+    fn main() {
+        let x = size(Shape::Square(3));
+    }
+    "#;
+    
+    assert_analysis_success(source);
+    
+    // 選択肢に含まれないバリアントが残っていれば網羅的ではない
+    let source = r#"
+    package main
+    
+    type Shape enum {
+        Circle(r: i32),
+        Square(side: i32),
+        Triangle(base: i32),
+        Dot
+    }
+    
+    fn main() {
+        let x = match Shape::Dot {
+            Shape::Circle(n) | Shape::Square(n) | Shape::Triangle(n) => n,
+        };
+    }
+    "#;
+    
+    assert_specific_error(source, |e| matches!(e, AnalyzerError::NonExhaustiveMatch { .. }));
+}
+
+#[test]
+fn test_or_pattern_binding_mismatch() {
+    // すべての選択肢が同じ名前の変数を同じ型で束縛しなければ、異なる変数を挙げてエラーにする
+    let source = r#"
+    package main
+    
+    type Value enum {
+        Int(n: i32),
+        Text(s: String),
+        Pair(a: i32, b: i32)
+    }
+    
+    fn main() {
+        let x = match Value::Int(1) {
+            Value::Int(n) | Value::Pair(a, n) => n,
+            Value::Text(s) => 0,
+        };
+    }
+    "#;
+    
+    let arm = "Value::Int(n) | Value::Pair(a, n)";
+    let start = source.find(arm).unwrap();
+    assert_specific_error(source, |e| {
+        matches!(e, AnalyzerError::OrPatternBindingMismatch { names, span }
+            if names == &["a"] && span.start == start && span.end == start + arm.len())
+    });
+    
+    // 同じ名前でも型が異なる束縛はエラー
+    let source = r#"
+    package main
+    
+    type Value enum {
+        Int(n: i32),
+        Text(s: String),
+        Pair(a: i32, b: i32)
+    }
+    
+    fn main() {
+        let x = match Value::Int(1) {
+            Value::Int(v) | Value::Text(v) => 1,
+            Value::Pair(a, b) => 0,
+        };
+    }
+    "#;
+    
+    assert_specific_error(source, |e| {
+        matches!(e, AnalyzerError::OrPatternBindingMismatch { names, .. } if names == &["v"])
+    });
+}
+
+#[test]
+fn test_binding_pattern() {
+    // `name @ pat` はpatに一致した値全体を名前で参照でき、網羅性と重複はpatで判定する
+    let source = r#"
+    package main
+    
+    type Shape enum {
+        Circle(r: i32),
+        Dot
+    }
+    
+    fn describe(shape: Shape, code: i32): i32 {
+        let digit: i32 = match code {
+            d @ 0..=9 => d * 2,
+            n @ (10 | 20) => n,
+            _ => 0,
+        };
+        return match shape {
+            whole @ Shape::Circle(r) => r + digit,
+            other @ _ => digit,
+        };
+    }
+    
+    fn main() {
+        let x = describe(Shape::Circle(2), 5);
+    }
+    "#;
+    
+    assert_analysis_success(source);
+    
+    let source = r#"
+    package main
+    
+    fn main() {
+        let x = match 4 {
+            1..=5 => 1,
+            n @ 4 => n,
+            _ => 0,
+        };
+    }
+    "#;
+    
+    assert_specific_error(source, |e| {
+        matches!(e, AnalyzerError::DuplicateMatchArm { pattern, .. } if pattern == "4")
+    });
+}
+
+#[test]
+fn test_anonymous_struct_literal_from_context() {
+    // 型名のない構造体リテラルは、戻り値・引数・型注釈の型の構造体になる
+    let source = r#"
+    package main
+    
+    struct Point {
+        x: f64,
+        y: f64,
+    }
+    
+    struct Pair<T> {
+        first: T,
+        second: T,
+    }
+    
+    fn origin(): Point {
+        return { x: 0.0, y: 0.0 };
+    }
+    
+    fn norm2(p: Point): f64 {
+        return p.x * p.x + p.y * p.y;
+    }
+    
+    fn main() {
+        let n = norm2({ x: 3.0, y: 4.0 });
+        let p: Point = { y: 1.0, x: 2.0 };
+        let pair: Pair<i64> = { first: 1, second: 2 };
+        let o = origin();
+    }
+    "#;
+    
+    assert_analysis_success(source);
+}
+
+#[test]
+fn test_struct_field_init_shorthand() {
+    // `{ x, y }` は同じ名前の変数でフィールドを初期化する
+    let source = r#"
+    package main
+    
+    struct Point {
+        x: f64,
+        y: f64,
+    }
+    
+    fn make(x: f64, y: f64): Point {
+        return Point { x, y };
+    }
+    
+    fn mirror(x: f64, y: f64): Point {
+        return { x: y, y: x };
+    }
+    
+    fn main() {
+        let x = 1.0;
+        let y = 2.0;
+        let p: Point = { x, y };
+        let q = Point { y, x: 3.0 };
+    }
+    "#;
+    
+    assert_analysis_success(source);
+    
+    // 省略形のフィールドも値の型を検査する
+    let source = r#"
+    package main
+    
+    struct Point {
+        x: f64,
+        y: f64,
+    }
+    
+    fn main() {
+        let x = "one";
+        let y = 2.0;
+        let p = Point { x, y };
+    }
+    "#;
+    
+    assert_specific_error(source, |e| matches!(e, AnalyzerError::TypeMismatch { .. }));
+}
+
+#[test]
+fn test_anonymous_struct_literal_without_context() {
+    // 期待される型がない場合は構造体を決められないため、型を注釈するよう求める
+    let source = r#"
+    package main
+    
+    struct Point {
+        x: f64,
+        y: f64,
+    }
+    
+    fn main() {
+        let p = { x: 1.0, y: 2.0 };
+    }
+    "#;
+    
+    assert_specific_error(source, |e| {
+        matches!(e, AnalyzerError::InvalidOperation { message, .. } if message.contains("型を注釈"))
+    });
+}
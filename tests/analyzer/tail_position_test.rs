@@ -0,0 +1,268 @@
+//! 末尾位置解析のテスト
+
+use super::*;
+use yunilang::analyzer::mark_tail_calls;
+
+/// ソースを解析して末尾位置の印を付ける
+fn marked_program(source: &str) -> Program {
+    let mut program = assert_analysis_success(source);
+    mark_tail_calls(&mut program);
+    program
+}
+
+/// 関数・メソッド本体の呼び出しを出現順に (呼び出し先, is_tail) で集める
+fn tail_flags(program: &Program, name: &str) -> Vec<(String, bool)> {
+    let body = program
+        .items
+        .iter()
+        .find_map(|item| match item {
+            Item::Function(func) if func.name == name => Some(&func.body),
+            Item::Method(method) if method.name == name => Some(&method.body),
+            _ => None,
+        })
+        .expect("function should exist");
+    let mut flags = Vec::new();
+    collect_block(body, &mut flags);
+    flags
+}
+
+fn collect_block(block: &Block, flags: &mut Vec<(String, bool)>) {
+    for stmt in &block.statements {
+        collect_statement(stmt, flags);
+    }
+}
+
+fn collect_statement(stmt: &Statement, flags: &mut Vec<(String, bool)>) {
+    match stmt {
+        Statement::Let(let_stmt) => {
+            if let Some(init) = &let_stmt.init {
+                collect_expr(init, flags);
+            }
+        }
+        Statement::Return(ret) => {
+            if let Some(value) = &ret.value {
+                collect_expr(value, flags);
+            }
+        }
+        Statement::Expression(expr) => collect_expr(expr, flags),
+        Statement::If(if_stmt) => {
+            collect_expr(&if_stmt.condition, flags);
+            collect_block(&if_stmt.then_branch, flags);
+            if let Some(ElseBranch::Block(block)) = &if_stmt.else_branch {
+                collect_block(block, flags);
+            }
+        }
+        Statement::While(while_stmt) => {
+            collect_expr(&while_stmt.condition, flags);
+            collect_block(&while_stmt.body, flags);
+        }
+        Statement::Block(block) => collect_block(block, flags),
+        Statement::Defer(defer_stmt) => collect_block(&defer_stmt.body, flags),
+        _ => {}
+    }
+}
+
+fn collect_expr(expr: &Expression, flags: &mut Vec<(String, bool)>) {
+    match expr {
+        Expression::Call(call) => {
+            if let Expression::Identifier(id) = call.callee.as_ref() {
+                flags.push((id.name.clone(), call.is_tail));
+            }
+            call.args.iter().for_each(|arg| collect_expr(arg, flags));
+        }
+        Expression::MethodCall(method_call) => {
+            flags.push((method_call.method.clone(), method_call.is_tail));
+            collect_expr(&method_call.object, flags);
+            method_call.args.iter().for_each(|arg| collect_expr(arg, flags));
+        }
+        Expression::If(if_expr) => {
+            collect_expr(&if_expr.condition, flags);
+            collect_expr(&if_expr.then_branch, flags);
+            if let Some(else_branch) = &if_expr.else_branch {
+                collect_expr(else_branch, flags);
+            }
+        }
+        Expression::Match(match_expr) => {
+            collect_expr(&match_expr.expr, flags);
+            for arm in &match_expr.arms {
+                if let Some(guard) = &arm.guard {
+                    collect_expr(guard, flags);
+                }
+                collect_expr(&arm.expr, flags);
+            }
+        }
+        Expression::Block(block_expr) => {
+            for stmt in &block_expr.statements {
+                collect_statement(stmt, flags);
+            }
+            if let Some(last_expr) = &block_expr.last_expr {
+                collect_expr(last_expr, flags);
+            }
+        }
+        Expression::Binary(binary) => {
+            collect_expr(&binary.left, flags);
+            collect_expr(&binary.right, flags);
+        }
+        Expression::Unary(unary) => collect_expr(&unary.expr, flags),
+        _ => {}
+    }
+}
+
+fn flags(items: &[(&str, bool)]) -> Vec<(String, bool)> {
+    items.iter().map(|(name, tail)| (name.to_string(), *tail)).collect()
+}
+
+#[test]
+fn test_tail_calls_through_if_expression_branches() {
+    // 暗黙の戻り値のif式の各分岐は末尾位置
+    let program = marked_program(r#"
+    package main
+
+    fn is_even(n: i64): bool {
+        if n == 0 { true } else { is_odd(n - 1) }
+    }
+
+    fn is_odd(n: i64): bool {
+        if n == 0 { false } else { is_even(n - 1) }
+    }
+
+    fn main() {
+        println(is_even(10));
+    }
+    "#);
+
+    assert_eq!(tail_flags(&program, "is_even"), flags(&[("is_odd", true)]));
+    assert_eq!(tail_flags(&program, "is_odd"), flags(&[("is_even", true)]));
+    // 関数本体の最後の式文も末尾位置（引数の呼び出しは末尾位置ではない）
+    assert_eq!(tail_flags(&program, "main"), flags(&[("println", true), ("is_even", false)]));
+}
+
+#[test]
+fn test_tail_calls_in_match_arms_and_blocks() {
+    // matchの各アームの本体とブロック式の最後の式は末尾位置、ガードとマッチ対象は末尾位置ではない
+    let program = marked_program(r#"
+    package main
+
+    fn done(): i32 {
+        return 0;
+    }
+
+    fn limit(): i32 {
+        return 100;
+    }
+
+    fn count(n: i32): i32 {
+        return match n {
+            0 => done(),
+            x if x > limit() => {
+                let m = count(x - 1);
+                count(m)
+            },
+            _ => count(n - 1),
+        };
+    }
+
+    fn main() {
+    }
+    "#);
+
+    assert_eq!(
+        tail_flags(&program, "count"),
+        flags(&[("done", true), ("limit", false), ("count", false), ("count", true), ("count", true)])
+    );
+}
+
+#[test]
+fn test_non_tail_positions_are_not_marked() {
+    // 演算の被演算子・引数・変数の初期化・ループ内の式文は末尾位置ではない
+    let program = marked_program(r#"
+    package main
+
+    fn helper(n: i32): i32 {
+        return n;
+    }
+
+    fn factorial(n: i32): i32 {
+        if n <= 1 {
+            return 1;
+        }
+        return n * factorial(n - 1);
+    }
+
+    fn nested(n: i32): i32 {
+        let x = helper(n);
+        return helper(helper(x));
+    }
+
+    fn looping(n: i32): i32 {
+        while n > 0 {
+            helper(n);
+            return helper(n - 1);
+        }
+        return 0;
+    }
+
+    fn main() {
+    }
+    "#);
+
+    assert_eq!(tail_flags(&program, "factorial"), flags(&[("factorial", false)]));
+    assert_eq!(
+        tail_flags(&program, "nested"),
+        flags(&[("helper", false), ("helper", true), ("helper", false)])
+    );
+    // ループの中でもreturnの値は末尾位置
+    assert_eq!(tail_flags(&program, "looping"), flags(&[("helper", false), ("helper", true)]));
+}
+
+#[test]
+fn test_method_calls_in_tail_position() {
+    // メソッド呼び出しにも印が付き、レシーバーや引数は末尾位置ではない
+    let program = marked_program(r#"
+    package main
+
+    struct Counter {
+        count: i32,
+    }
+
+    impl fn value(c: &Counter): i32 {
+        return c.count;
+    }
+
+    impl fn step(c: &Counter, n: i32): i32 {
+        if n == 0 { c.value() } else { c.step(n - c.value()) }
+    }
+
+    fn main() {
+    }
+    "#);
+
+    assert_eq!(
+        tail_flags(&program, "step"),
+        flags(&[("value", true), ("step", true), ("value", false)])
+    );
+}
+
+#[test]
+fn test_functions_with_defer_have_no_tail_calls() {
+    // deferは戻る直前に実行されるため、呼び出しは末尾位置にならない
+    let program = marked_program(r#"
+    package main
+
+    fn helper(n: i32): i32 {
+        return n;
+    }
+
+    fn deferred(n: i32): i32 {
+        defer {
+            println("done");
+        }
+        return helper(n);
+    }
+
+    fn main() {
+    }
+    "#);
+
+    assert_eq!(tail_flags(&program, "deferred"), flags(&[("println", false), ("helper", false)]));
+}
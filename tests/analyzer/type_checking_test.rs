@@ -130,6 +130,61 @@ fn test_logical_type_checking() {
     assert_analysis_success(source);
 }
 
+#[test]
+fn test_membership_type_checking() {
+    // `in` はVecの要素・HashMapのキー・部分文字列を調べ、結果はbool
+    let source = r#"
+    package main
+    
+    fn main() {
+        let numbers: Vec<i64> = [1, 2, 3];
+        let ages = HashMap<String, i32> { "alice": 30 };
+        let text = "hello world";
+        let flags = Vec<bool>[true];
+        
+        if 2 in numbers {
+            println("found");
+        }
+        if "alice" in ages && !("bob" in ages) {
+            println("alice only");
+        }
+        let has_world: bool = "world" in text;
+        let has_false = false in flags;
+        let through_ref = 3 in &numbers;
+    }
+    "#;
+    
+    assert_analysis_success(source);
+    
+    // 左辺は要素・キーの型
+    let wrong_needle = r#"
+    package main
+    
+    fn main() {
+        let numbers: Vec<i64> = [1, 2, 3];
+        let found = "two" in numbers;
+    }
+    "#;
+    assert_specific_error(wrong_needle, |e| match e {
+        AnalyzerError::TypeMismatch { expected, .. } => expected == "i64",
+        _ => false,
+    });
+    
+    // コンテナでない右辺は使えるコンテナを示すエラー
+    let not_container = r#"
+    package main
+    
+    fn main() {
+        let count = 10;
+        let found = 1 in count;
+    }
+    "#;
+    assert_specific_error(not_container, |e| match e {
+        AnalyzerError::InvalidMembershipContainer { ty, .. } => ty == "i32",
+        _ => false,
+    });
+}
+
 #[test]
 fn test_complex_expression_type_checking() {
     // 複雑な式の型チェックのテスト
@@ -154,4 +209,422 @@ fn test_complex_expression_type_checking() {
     "#;
     
     assert_analysis_success(source);
-}
\ No newline at end of file
+}
+#[test]
+fn test_numeric_promotion_rules() {
+    // 数値型の昇格規則のテスト
+    let allowed = [
+        ("i32", "i64"),
+        ("i8", "i32"),
+        ("u8", "u32"),
+        ("u16", "u64"),
+        ("f32", "f64"),
+    ];
+    for (left, right) in allowed {
+        let source = format!(
+            r#"
+    package main
+    
+    fn calc(a: {left}, b: {right}): {right} {{
+        let less = a < b;
+        return a + b;
+    }}
+    
+    fn main() {{
+    }}
+    "#
+        );
+        assert_analysis_success(&source);
+    }
+
+    // 符号の混在には明示的なキャストが必要
+    for (left, right) in [("i32", "u32"), ("u8", "i64")] {
+        let source = format!(
+            r#"
+    package main
+    
+    fn calc(a: {left}, b: {right}) {{
+        let c = a + b;
+    }}
+    
+    fn main() {{
+    }}
+    "#
+        );
+        assert_specific_error(&source, |e| {
+            matches!(e, AnalyzerError::MixedNumericTypes { .. })
+        });
+    }
+
+    // 整数と浮動小数点の混在も暗黙には変換しない
+    let source = r#"
+    package main
+    
+    fn calc(a: i64, b: f64) {
+        let c = a * b;
+    }
+    
+    fn main() {
+    }
+    "#;
+    assert_analysis_error(source);
+}
+
+#[test]
+fn test_untyped_literal_adopts_operand_type() {
+    // 接尾辞のない整数リテラルはもう一方のオペランドの型を採用する
+    let source = r#"
+    package main
+    
+    fn main() {
+        let a: i64 = 10i64;
+        let b: i64 = a + 1;
+        let c: u8 = 200u8;
+        let d: u8 = 3 * c;
+        let e: bool = c > 100;
+    }
+    "#;
+    
+    assert_analysis_success(source);
+}
+
+#[test]
+fn test_numeric_literal_adopts_expected_type() {
+    // 接尾辞のない数値リテラルは、フィールド・引数・型引数から決まる期待される型を採用する
+    let source = r#"
+    package main
+    
+    struct Circle {
+        radius: f64,
+    }
+    
+    fn halve(x: f32): f32 {
+        return x / 2.0f32;
+    }
+    
+    fn pick<T>(a: T, b: T): T {
+        return a;
+    }
+    
+    fn main() {
+        let c = Circle { radius: 3 };
+        let h: f32 = halve(5);
+        let q: f32 = halve(0.75);
+        let w: i64 = pick(5000000000i64, 7);
+        let f: f64 = 1;
+    }
+    "#;
+    
+    assert_analysis_success(source);
+}
+
+#[test]
+fn test_literal_context_flows_into_elements_and_arms() {
+    // 要素・引数・matchのアーム・ブロックの最後の式にも期待される型が伝わり、接尾辞は不要
+    let source = r#"
+    package main
+    
+    fn widen(x: u16): u16 {
+        return x;
+    }
+    
+    fn main() {
+        let bytes: Vec<u8> = [1, 2, 255];
+        let w = widen(60000);
+        let x: i32 = 2;
+        let big: i64 = match x {
+            1 => 10,
+            _ => 5000000000,
+        };
+        let nested: i64 = if x > 1 { 3000000000 } else { { 1 } };
+    }
+    "#;
+    
+    let tokens: Vec<_> = Lexer::new(source).collect_tokens();
+    let ast = Parser::new(tokens).parse().expect("Parsing should succeed");
+    let mut analyzer = SemanticAnalyzer::new();
+    analyzer.analyze(&ast).expect("Analysis should succeed");
+    
+    let main = ast.items.iter().find_map(|item| match item {
+        Item::Function(func) if func.name == "main" => Some(func),
+        _ => None,
+    }).unwrap();
+    let init = |index: usize| match &main.body.statements[index] {
+        Statement::Let(let_stmt) => let_stmt.init.as_ref().unwrap(),
+        other => panic!("Expected let statement, got {:?}", other),
+    };
+    
+    let Expression::ListLiteral(list) = init(0) else {
+        panic!("Expected list literal");
+    };
+    assert!(list.elements.iter().all(|elem| analyzer.type_table.get(elem) == Some(&Type::U8)));
+    let Expression::Call(call) = init(1) else {
+        panic!("Expected call expression");
+    };
+    assert_eq!(analyzer.type_table.get(&call.args[0]), Some(&Type::U16));
+    let Expression::Match(match_expr) = init(3) else {
+        panic!("Expected match expression");
+    };
+    assert!(match_expr.arms.iter().all(|arm| analyzer.type_table.get(&arm.expr) == Some(&Type::I64)));
+}
+
+#[test]
+fn test_type_table_records_expression_types() {
+    // 解析で決定した式の型が型テーブルに記録される
+    let source = r#"
+    package main
+    
+    fn wide(x: i64): i64 {
+        return x;
+    }
+    
+    fn main() {
+        let y: i64 = wide(3000000000);
+    }
+    "#;
+    
+    let tokens: Vec<_> = Lexer::new(source).collect_tokens();
+    let ast = Parser::new(tokens).parse().expect("Parsing should succeed");
+    let mut analyzer = SemanticAnalyzer::new();
+    analyzer.analyze(&ast).expect("Analysis should succeed");
+    
+    let main = ast.items.iter().find_map(|item| match item {
+        Item::Function(func) if func.name == "main" => Some(func),
+        _ => None,
+    }).unwrap();
+    let Statement::Let(let_stmt) = &main.body.statements[0] else {
+        panic!("Expected let statement");
+    };
+    let call = let_stmt.init.as_ref().unwrap();
+    let Expression::Call(call_expr) = call else {
+        panic!("Expected call expression");
+    };
+    
+    // 引数のリテラルは仮引数の型を採用する
+    assert_eq!(analyzer.type_table.get(&call_expr.args[0]), Some(&Type::I64));
+    assert_eq!(analyzer.type_table.get(call), Some(&Type::I64));
+}
+
+#[test]
+fn test_constant_cast_out_of_range_warns() {
+    // 定数のオペランドが範囲外のキャストは常に値が変わるため警告する
+    let source = r#"
+    package main
+
+    fn main() {
+        let a = 1000 as u8;
+        let b = -1 as u32;
+        let c = 300.5 as u8;
+        let ok = 255 as u8;
+        let truncated = 3.9 as i32;
+    }
+    "#;
+
+    let tokens: Vec<_> = Lexer::new(source).collect_tokens();
+    let ast = Parser::new(tokens).parse().expect("Parsing should succeed");
+    let mut analyzer = SemanticAnalyzer::new();
+    assert!(analyzer.analyze(&ast).is_ok(), "Lossy casts should only be warnings");
+
+    let warned: Vec<_> = analyzer
+        .warnings
+        .iter()
+        .map(|warning| match warning {
+            AnalyzerError::LossyConstantCast { value, target, span } => {
+                (value.as_str(), target.as_str(), &source[span.start..span.end])
+            }
+            other => panic!("Unexpected warning: {:?}", other),
+        })
+        .collect();
+    assert_eq!(
+        warned,
+        [("1000", "u8", "1000 as u8"), ("-1", "u32", "-1 as u32"), ("300.5", "u8", "300.5 as u8")]
+    );
+}
+
+#[test]
+fn test_lossy_cast_reports_literal_as_written() {
+    // 警告には値ではなくソースコードに書かれたリテラルの表記を使う
+    let source = r#"
+    package main
+
+    fn main() {
+        let a = 0x1FF as u8;
+        let b = 1_000 as u8;
+    }
+    "#;
+
+    let tokens: Vec<_> = Lexer::new(source).collect_tokens();
+    let ast = Parser::new(tokens).parse().expect("Parsing should succeed");
+    let mut analyzer = SemanticAnalyzer::new();
+    assert!(analyzer.analyze(&ast).is_ok(), "Lossy casts should only be warnings");
+
+    let values: Vec<_> = analyzer
+        .warnings
+        .iter()
+        .map(|warning| match warning {
+            AnalyzerError::LossyConstantCast { value, .. } => value.as_str(),
+            other => panic!("Unexpected warning: {:?}", other),
+        })
+        .collect();
+    assert_eq!(values, ["0x1FF", "1_000"]);
+}
+
+#[test]
+fn test_integer_literal_out_of_range() {
+    // 型の範囲外の整数リテラルはエラーで、メッセージには書かれた表記を使う
+    let cases = [
+        ("let x: u8 = 0x1FF;", "0x1FF", "u8", "0x1FF"),
+        ("let x = 300u8;", "300u8", "u8", "300u8"),
+        ("let x: i8 = -129;", "-129", "i8", "-129"),
+    ];
+    for (statement, expected_literal, expected_type, expected_span) in cases {
+        let source = format!("package main\n\nfn main() {{\n    {}\n}}\n", statement);
+        let tokens: Vec<_> = Lexer::new(&source).collect_tokens();
+        let ast = Parser::new(tokens).parse().expect("Parsing should succeed");
+        let mut analyzer = SemanticAnalyzer::new();
+        let error = analyzer.analyze(&ast).expect_err("Out-of-range literal should be rejected");
+        match &error {
+            AnalyzerError::IntegerLiteralOutOfRange { literal, ty, span, .. } => {
+                assert_eq!(literal, expected_literal);
+                assert_eq!(ty, expected_type);
+                assert_eq!(&source[span.start..span.end], expected_span);
+            }
+            other => panic!("Expected IntegerLiteralOutOfRange, got {:?}", other),
+        }
+        assert!(!error.to_string().contains("511"), "{}", error);
+    }
+
+    // 範囲内のリテラルはそのまま受け付ける
+    let source = "package main\n\nfn main() {\n    let a: u8 = 0xFF;\n    let b: i8 = -128;\n    let c = 0b1010_1010u8;\n}\n";
+    let tokens: Vec<_> = Lexer::new(source).collect_tokens();
+    let ast = Parser::new(tokens).parse().expect("Parsing should succeed");
+    assert!(SemanticAnalyzer::new().analyze(&ast).is_ok());
+}
+
+#[test]
+fn test_str_and_string_types() {
+    // 文字列リテラルはstr、連結はString、Stringへの参照はstrの仮引数に渡せる
+    let source = r#"
+    package main
+    
+    fn shout(word: str): String {
+        return word + "!";
+    }
+    
+    fn main() {
+        let view = "hi";
+        let owned = view.to_string();
+        let joined = view + view;
+        let borrowed = shout(&owned);
+        let back: str = owned.as_str();
+    }
+    "#;
+    
+    let tokens: Vec<_> = Lexer::new(source).collect_tokens();
+    let ast = Parser::new(tokens).parse().expect("Parsing should succeed");
+    let mut analyzer = SemanticAnalyzer::new();
+    analyzer.analyze(&ast).expect("Analysis should succeed");
+    
+    let main = ast.items.iter().find_map(|item| match item {
+        Item::Function(func) if func.name == "main" => Some(func),
+        _ => None,
+    }).unwrap();
+    let init_types: Vec<Option<&Type>> = main.body.statements.iter().map(|statement| match statement {
+        Statement::Let(let_stmt) => analyzer.type_table.get(let_stmt.init.as_ref().unwrap()),
+        _ => None,
+    }).collect();
+    assert_eq!(
+        init_types,
+        vec![Some(&Type::Str), Some(&Type::String), Some(&Type::String), Some(&Type::String), Some(&Type::Str)]
+    );
+    
+    // strに as_str はない（すでに文字列データを指している）
+    let view_as_str = r#"
+    package main
+    
+    fn main() {
+        let view = "hi";
+        let again = view.as_str();
+    }
+    "#;
+    assert_specific_error(view_as_str, |e| match e {
+        AnalyzerError::MethodNotFound { method, ty, .. } => method == "as_str" && ty == "str",
+        _ => false,
+    });
+
+    // strの値はStringの位置に置けない（to_stringで複製する）。Stringの位置のリテラルは複製したStringになる
+    let view_as_string = r#"
+    package main
+    
+    fn main() {
+        let view = "hi";
+        let owned: String = view;
+    }
+    "#;
+    assert_specific_error(view_as_string, |e| match e {
+        AnalyzerError::TypeMismatch { expected, found, .. } => expected == "String" && found == "str",
+        _ => false,
+    });
+    
+    let literal_as_string = r#"
+    package main
+    
+    fn main() {
+        let owned: String = "hi";
+        let copied: String = owned.as_str().to_string();
+    }
+    "#;
+    let ast = Parser::new(Lexer::new(literal_as_string).collect_tokens()).parse().expect("Parsing should succeed");
+    let mut analyzer = SemanticAnalyzer::new();
+    analyzer.analyze(&ast).expect("Analysis should succeed");
+    let main = ast.items.iter().find_map(|item| match item {
+        Item::Function(func) if func.name == "main" => Some(func),
+        _ => None,
+    }).unwrap();
+    let Statement::Let(first) = &main.body.statements[0] else { panic!("let文のはず") };
+    assert_eq!(analyzer.type_table.get(first.init.as_ref().unwrap()), Some(&Type::String));
+}
+
+#[test]
+fn test_numeric_associated_constants() {
+    // 関連定数はその数値型の値になる
+    let source = r#"
+    package main
+
+    fn main() {
+        let big: i64 = i64::MAX;
+        let byte: u8 = u8::MIN;
+        let tiny: f32 = f32::EPSILON;
+        let limit = f64::INFINITY;
+        let half: f64 = limit / 2.0;
+    }
+    "#;
+    assert_analysis_success(source);
+
+    let narrowing = r#"
+    package main
+
+    fn main() {
+        let x: i32 = i64::MAX;
+    }
+    "#;
+    assert_specific_error(narrowing, |e| matches!(e, AnalyzerError::TypeMismatch { .. }));
+
+    // 未知の定数名と、関連定数を持たない型は使える定数の一覧とともに報告する
+    let cases = [
+        ("let x = i32::MAXIMUM;", "i32", "MAXIMUM", vec!["MIN", "MAX"], "i32::MAXIMUM"),
+        ("let x = f64::NaN;", "f64", "NaN", vec!["MIN", "MAX", "INFINITY", "NEG_INFINITY", "NAN", "EPSILON"], "f64::NaN"),
+        ("let x = bool::MAX;", "bool", "MAX", vec![], "bool::MAX"),
+    ];
+    for (statement, expected_type, expected_name, expected_available, expected_span) in cases {
+        let source = format!("package main\n\nfn main() {{\n    {}\n}}\n", statement);
+        let result = analyze_source(&source);
+        let Err(YuniError::Analyzer(AnalyzerError::UnknownAssociatedConstant { ty, name, available, span })) = result else {
+            panic!("Expected UnknownAssociatedConstant, got {:?}", result);
+        };
+        assert_eq!(ty, expected_type);
+        assert_eq!(name, expected_name);
+        assert_eq!(available, expected_available);
+        assert_eq!(&source[span.start..span.end], expected_span);
+    }
+}
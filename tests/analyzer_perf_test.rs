@@ -0,0 +1,98 @@
+//! セマンティック解析の性能テスト
+//!
+//! 関数が1万個あるファイルの解析でメモリを確保した回数を数え、型情報やシグネチャを
+//! 参照のたびに複製していないことを確認する。時間がかかるため通常は無視する。
+//!
+//! ```sh
+//! cargo test --release --test analyzer_perf_test -- --ignored --nocapture
+//! ```
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::fmt::Write;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Instant;
+
+use yunilang::analyzer::SemanticAnalyzer;
+use yunilang::lexer::Lexer;
+use yunilang::parser::Parser;
+
+/// メモリを確保した回数を数えるアロケータ
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        unsafe { System.realloc(ptr, layout, new_size) }
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+const FUNCTIONS: usize = 10_000;
+const FIELDS: usize = 16;
+const METHODS: usize = 16;
+
+/// フィールドとメソッドの多い構造体と列挙型を使う関数を並べたプログラム
+fn generate_program() -> String {
+    let mut source = String::from("package main\n\nstruct Record {\n");
+    for i in 0..FIELDS {
+        writeln!(source, "    field{}: i64,", i).unwrap();
+    }
+    source.push_str("}\n\nenum Shape {\n");
+    for i in 0..FIELDS {
+        writeln!(source, "    Variant{} {{ value: i64 }},", i).unwrap();
+    }
+    source.push_str("}\n\n");
+    for i in 0..METHODS {
+        writeln!(source, "impl fn get{}(r: &Record): i64 {{\n    return r.field{};\n}}\n", i, i % FIELDS).unwrap();
+    }
+    for i in 0..FUNCTIONS {
+        writeln!(
+            source,
+            "fn func{i}(r: &Record, n: i64): i64 {{\n    let s = Shape::Variant{v} {{ value: n }};\n    let a = r.get{m}() + r.get{m2}();\n    return a + n;\n}}\n",
+            i = i,
+            v = i % FIELDS,
+            m = i % METHODS,
+            m2 = (i + 1) % METHODS,
+        )
+        .unwrap();
+    }
+    source.push_str("fn main() {\n}\n");
+    source
+}
+
+#[test]
+#[ignore] // 時間がかかるため通常は無視
+fn test_analysis_allocations_per_function() {
+    let source = generate_program();
+    let tokens = Lexer::new(&source).collect_tokens();
+    let program = Parser::new(tokens).parse().expect("Parsing should succeed");
+
+    let mut analyzer = SemanticAnalyzer::new();
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    let started = Instant::now();
+    analyzer.analyze(&program).expect("Analysis should succeed");
+    let elapsed = started.elapsed();
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed) - before;
+
+    let per_function = allocations / FUNCTIONS;
+    println!(
+        "{} functions: {} allocations ({} per function) in {:?}",
+        FUNCTIONS, allocations, per_function, elapsed
+    );
+    // 呼び出しのたびに型情報（フィールドとメソッドの一覧）を複製すると、1関数あたりの
+    // 確保の回数がフィールドとメソッドの数に比例して増える
+    assert!(per_function < 150, "too many allocations per function: {}", per_function);
+}
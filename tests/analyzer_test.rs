@@ -12,6 +12,7 @@
 //! - error_type_test: 型エラー
 //! - method_test: メソッド関連
 //! - advanced_test: 高度なテスト（循環依存等）
+//! - tail_position_test: 末尾位置の呼び出しの印付け
 
 #[cfg(test)]
 mod analyzer;
\ No newline at end of file
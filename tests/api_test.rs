@@ -0,0 +1,111 @@
+//! 安定した公開API（`yunilang::compile`）のテスト
+//!
+//! 関数のシグネチャと型が実装するトレイトをコンパイル時に固定し、公開APIを誤って
+//! 変更した場合にこのテストのビルドが失敗するようにする。
+
+use std::error::Error;
+use std::fmt::{Debug, Display};
+
+use yunilang::ast::{Item, LetStatement, Statement, Type};
+use yunilang::compile::{self, Diagnostics, TypedProgram};
+use yunilang::frontend::JsonSeverity;
+use yunilang::YuniError;
+
+/// 結果の型はスレッド間で受け渡しでき、複製・表示できる
+fn assert_result_type<T: Clone + Debug + Send + Sync + 'static>() {}
+
+/// 診断はエラーとして `?` で伝播できる
+fn assert_error_type<T: Error + Display + Send + Sync + 'static>() {}
+
+#[test]
+fn test_facade_signatures() {
+    let _: fn(&str, &str) -> Result<TypedProgram, Diagnostics> = compile::check_source;
+    assert_result_type::<TypedProgram>();
+    assert_result_type::<Diagnostics>();
+    assert_error_type::<Diagnostics>();
+
+    #[cfg(feature = "full")]
+    {
+        use yunilang::compile::{IrModule, ObjectFile};
+
+        let _: fn(&str, &str) -> Result<IrModule, Diagnostics> = compile::build_ir;
+        let _: fn(&str, &str) -> Result<ObjectFile, Diagnostics> = compile::build_object;
+        assert_result_type::<IrModule>();
+        assert_result_type::<ObjectFile>();
+    }
+}
+
+#[test]
+fn test_check_source_returns_typed_program() {
+    let source = "package main\n\nfn main() {\n    let n = 40 + 2;\n    println(n);\n}\n";
+    let checked = compile::check_source("typed.yuni", source).expect("check should succeed");
+    assert!(checked.diagnostics().is_empty());
+
+    // 式の型はASTの式から引ける
+    let Item::Function(main) = &checked.program().items[0] else {
+        panic!("main should be a function");
+    };
+    let Statement::Let(LetStatement { init: Some(init), .. }) = &main.body.statements[0] else {
+        panic!("the first statement should be a let");
+    };
+    assert_eq!(checked.types().get(init), Some(&Type::I32));
+
+    let (program, _) = checked.clone().into_parts();
+    assert_eq!(program.package.name, "main");
+}
+
+#[test]
+fn test_check_source_keeps_warnings() {
+    // 警告だけであれば成功し、警告は結果から取得できる
+    let source = "package main\n\nfn main() {\n    /// not an item\n    let x = 1;\n}\n";
+    let checked = compile::check_source("doc.yuni", source).expect("warnings should not fail the check");
+    let diagnostics = checked.diagnostics();
+    assert!(!diagnostics.has_errors());
+    assert_eq!(diagnostics.warnings().count(), 1);
+    assert_eq!(diagnostics.to_json()[0].severity, JsonSeverity::Warning);
+}
+
+#[test]
+fn test_check_source_reports_errors() {
+    let source = "package main\n\nfn main() {\n    let x: i32 = undefined_name;\n}\n";
+    let diagnostics = compile::check_source("bad.yuni", source).expect_err("check should fail");
+
+    assert!(diagnostics.has_errors());
+    let errors: Vec<&YuniError> = diagnostics.errors().collect();
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].to_string(), "意味解析エラー");
+
+    let json = diagnostics.to_json();
+    assert_eq!(json[0].severity, JsonSeverity::Error);
+    assert_eq!(json[0].message, "未定義の変数: undefined_name");
+
+    // 端末と同じ形式で、ファイル名と位置を含めて表示する
+    let rendered = diagnostics.to_string();
+    assert!(rendered.contains("未定義の変数: undefined_name"), "{}", rendered);
+    assert!(rendered.contains("bad.yuni:4:18"), "{}", rendered);
+}
+
+#[test]
+fn test_check_source_reports_parse_errors() {
+    let diagnostics = compile::check_source("broken.yuni", "package main\n\nfn main( {\n").expect_err("parse should fail");
+    assert!(matches!(diagnostics.errors().next(), Some(YuniError::Parser(_))));
+}
+
+#[cfg(feature = "full")]
+#[test]
+fn test_build_ir_and_object() {
+    const HELLO: &str = "package main\n\nfn main() {\n    println(\"hello\");\n}\n";
+
+    let ir = compile::build_ir("hello.yuni", HELLO).expect("build should succeed");
+    assert!(ir.as_str().contains("@main"), "{}", ir);
+    assert!(ir.diagnostics().is_empty());
+
+    let object = compile::build_object("hello.yuni", HELLO).expect("build should succeed");
+    assert!(!object.as_bytes().is_empty());
+    assert_eq!(object.clone().into_bytes(), object.as_bytes());
+
+    // 解析エラーはコード生成の前に診断として返す
+    let diagnostics = compile::build_ir("bad.yuni", "package main\n\nfn main() {\n    let x: i32 = \"s\";\n}\n")
+        .expect_err("build should fail");
+    assert!(diagnostics.has_errors());
+}
@@ -0,0 +1,93 @@
+//! JSON形式のASTの読み書きのテスト
+//!
+//! `Program::to_json` と `Program::from_json` の往復、および
+//! `yunilang compile --from-ast` の入力モードを検証する。
+
+use std::io::Write;
+use std::process::Command;
+
+use inkwell::context::Context;
+use tempfile::NamedTempFile;
+use yunilang::ast::{Item, Program};
+use yunilang::compiler::{CompilationPipeline, CompilationState};
+use yunilang::lexer::Lexer;
+use yunilang::parser::Parser;
+use yunilang::YuniError;
+
+const FIXTURE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/ast_roundtrip.yuni");
+
+fn parse_fixture() -> Program {
+    let source = std::fs::read_to_string(FIXTURE).expect("fixture should be readable");
+    let tokens: Vec<_> = Lexer::new(&source).collect_tokens();
+    Parser::new(tokens).parse().expect("fixture should parse")
+}
+
+/// 名前解決からコード生成までを実行してLLVM IRを返す
+fn compile_to_ir(mut ast: Program) -> String {
+    let state = CompilationState::new_from_string("ast_roundtrip.yuni", String::new()).unwrap();
+    let context = Context::create();
+    let mut pipeline = CompilationPipeline::new(state, &context);
+    pipeline.resolve_names(&mut ast);
+    pipeline.analyze(&ast);
+    assert!(!pipeline.state().has_errors(), "fixture should analyze without errors");
+    let ast = pipeline.monomorphize(ast).expect("fixture should monomorphize");
+    let codegen = pipeline.codegen(&ast).expect("fixture should compile");
+    codegen.get_module().print_to_string().to_string()
+}
+
+#[test]
+fn test_program_json_roundtrip_compiles_to_identical_ir() {
+    let original = parse_fixture();
+    let json = original.to_json();
+    let restored = Program::from_json(&json).expect("JSON should deserialize");
+
+    assert_eq!(original, restored);
+    assert_eq!(compile_to_ir(original), compile_to_ir(restored));
+}
+
+#[test]
+fn test_program_json_rejects_other_versions() {
+    let json = parse_fixture().to_json();
+    let mut value: serde_json::Value = serde_json::from_str(&json).unwrap();
+
+    value["format_version"] = serde_json::json!(999);
+    let result = Program::from_json(&value.to_string());
+    assert!(matches!(&result, Err(YuniError::Other(message)) if message.contains("version 999")), "{:?}", result);
+
+    // バージョンのないJSON（`serde_json::to_string(&program)` の出力など）も受け付けない
+    value.as_object_mut().unwrap().remove("format_version");
+    let result = Program::from_json(&value.to_string());
+    assert!(matches!(&result, Err(YuniError::Other(message)) if message.contains("format_version")), "{:?}", result);
+}
+
+#[test]
+fn test_compile_from_ast_cli() {
+    let mut json_file = NamedTempFile::with_suffix(".json").expect("Failed to create temp file");
+    json_file.write_all(parse_fixture().to_json().as_bytes()).unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_yunilang"))
+        .args(["compile", "--from-ast", "--emit=llvm-ir", "-o", "-"])
+        .arg(json_file.path())
+        .output()
+        .expect("Failed to execute compiler");
+
+    assert!(output.status.success(), "Compilation should succeed: {}", String::from_utf8_lossy(&output.stderr));
+    let ir = String::from_utf8(output.stdout).expect("IR should be valid UTF-8");
+    assert!(ir.starts_with("; ModuleID"), "Stdout should start with the IR module header: {}", ir);
+    assert!(ir.contains("define i32 @sum_even"), "IR should contain the fixture functions: {}", ir);
+
+    // ソースコードがなくても解析エラーを報告できる
+    let mut broken = parse_fixture();
+    broken.items.retain(|item| !matches!(item, Item::Function(f) if f.name == "sum_even"));
+    let mut json_file = NamedTempFile::with_suffix(".json").expect("Failed to create temp file");
+    json_file.write_all(broken.to_json().as_bytes()).unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_yunilang"))
+        .args(["compile", "--from-ast", "--emit=llvm-ir", "-o", "-"])
+        .arg(json_file.path())
+        .output()
+        .expect("Failed to execute compiler");
+
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("sum_even"));
+}
@@ -550,6 +550,19 @@ mod tests {
         assert_eq!(original_ast, deserialized_ast);
     }
 
+    #[test]
+    fn test_numeric_literal_without_raw_text() {
+        // 表記を持たない古いJSONのリテラルは値と型サフィックスから表記を作る
+        let int: IntegerLit = serde_json::from_str(r#"{"value":255,"suffix":"u8","span":{"start":0,"end":5}}"#)
+            .expect("Deserialization should succeed");
+        assert_eq!(int.raw, "");
+        assert_eq!(int.text(), "255u8");
+
+        let float: FloatLit = serde_json::from_str(r#"{"value":2.0,"suffix":null,"span":{"start":0,"end":3}}"#)
+            .expect("Deserialization should succeed");
+        assert_eq!(float.text(), "2.0");
+    }
+
     #[test]
     fn test_ast_pretty_print() {
         // ASTの可読形式出力テスト
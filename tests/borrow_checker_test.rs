@@ -1,8 +1,10 @@
 //! 借用チェッカーのテスト
 
-use yunilang::analyzer::SemanticAnalyzer;
+use yunilang::analyzer::{BorrowCheckMode, SemanticAnalyzer};
+use yunilang::error::{AnalyzerError, DiagnosticError};
 use yunilang::lexer::Lexer;
 use yunilang::parser::Parser;
+use yunilang::YuniError;
 
 #[test]
 fn test_use_after_move() {
@@ -124,4 +126,144 @@ fn test_move_while_borrowed() {
     
     // 借用チェックエラーが発生することを確認
     assert!(result.is_err(), "借用中の移動がエラーにならなかった");
-}
\ No newline at end of file
+}
+/// 借用チェックの設定を指定して解析する
+fn analyze_with(source: &str, mode: BorrowCheckMode) -> (SemanticAnalyzer, Result<(), AnalyzerError>) {
+    let tokens: Vec<_> = Lexer::new(source).collect_tokens();
+    let program = Parser::new(tokens).parse().expect("パースに失敗");
+    let mut analyzer = SemanticAnalyzer::new().with_borrow_check(mode);
+    let result = analyzer.analyze(&program);
+    (analyzer, result)
+}
+
+/// 診断のラベルが指すソースコードとメッセージ
+fn labels(source: &str, error: &AnalyzerError) -> Vec<(String, String)> {
+    DiagnosticError::new(YuniError::Analyzer(error.clone()), 0)
+        .to_diagnostic()
+        .labels
+        .iter()
+        .map(|label| (source[label.range.clone()].to_string(), label.message.clone()))
+        .collect()
+}
+
+const TWO_MUTABLE_BORROWS: &str = r#"
+package test
+
+fn borrow_twice() {
+    let mut x = 42;
+    let y = &mut x;
+    let z = &mut x;
+}
+"#;
+
+#[test]
+fn test_borrow_conflict_reports_both_spans() {
+    let (analyzer, result) = analyze_with(TWO_MUTABLE_BORROWS, BorrowCheckMode::Error);
+    assert!(result.is_err());
+    let error = &analyzer.errors[0];
+    assert_eq!(error.to_string(), "複数の可変借用: x");
+    assert_eq!(
+        labels(TWO_MUTABLE_BORROWS, error),
+        [
+            ("&mut x".to_string(), "2つ目の可変借用です".to_string()),
+            ("&mut x".to_string(), "最初の可変借用はここです".to_string()),
+        ]
+    );
+
+    // 移動後の使用は移動した箇所を、借用中の変更は借用した箇所を示す
+    let source = "package test\n\nfn f() {\n    let s = \"hi\";\n    let t = s;\n    let u = s;\n}\n";
+    let (analyzer, _) = analyze_with(source, BorrowCheckMode::Error);
+    assert!(matches!(&analyzer.errors[..], [AnalyzerError::UseAfterMove { .. }]), "{:?}", analyzer.errors);
+    assert_eq!(labels(source, &analyzer.errors[0])[1], ("s".to_string(), "ここで移動されています".to_string()));
+
+    let source = "package test\n\nfn f() {\n    let mut n = 1;\n    let r = &n;\n    n = 2;\n}\n";
+    let (analyzer, _) = analyze_with(source, BorrowCheckMode::Error);
+    assert_eq!(analyzer.errors.len(), 1, "{:?}", analyzer.errors);
+    assert_eq!(analyzer.errors[0].to_string(), "借用された値 n を変更しようとしました");
+    assert_eq!(labels(source, &analyzer.errors[0])[1], ("&n".to_string(), "ここで借用されています".to_string()));
+}
+
+#[test]
+fn test_warn_mode_reports_borrow_conflicts_as_warnings() {
+    let (analyzer, result) = analyze_with(TWO_MUTABLE_BORROWS, BorrowCheckMode::Warn);
+    assert!(result.is_ok(), "警告モードでエラーになった: {:?}", result);
+    match analyzer.warnings.as_slice() {
+        [warning @ AnalyzerError::MultipleMutableBorrows { .. }] => {
+            assert_eq!(warning.to_string(), "複数の可変借用: x");
+            assert_eq!(labels(TWO_MUTABLE_BORROWS, warning).len(), 2);
+        }
+        other => panic!("複数の可変借用の警告を期待しましたが {:?}", other),
+    }
+
+    // 型エラーは警告モードでもエラーのまま
+    let source = "package test\n\nfn f() {\n    let mut x = 42;\n    let y = &mut x;\n    let z = &mut x;\n    let s: String = 1;\n}\n";
+    let (analyzer, result) = analyze_with(source, BorrowCheckMode::Warn);
+    assert!(result.is_err());
+    assert!(matches!(&analyzer.errors[..], [AnalyzerError::TypeMismatch { .. }]), "{:?}", analyzer.errors);
+
+    // 無効にすると借用チェックの診断を出さない
+    let (analyzer, result) = analyze_with(TWO_MUTABLE_BORROWS, BorrowCheckMode::Off);
+    assert!(result.is_ok(), "{:?}", result);
+    assert!(analyzer.warnings.is_empty(), "{:?}", analyzer.warnings);
+}
+
+#[test]
+fn test_unsafe_no_borrow_check_attribute() {
+    // 属性を付けた関数だけ借用チェックを行わない
+    let source = r#"
+package test
+
+#[unsafe_no_borrow_check]
+fn unchecked() {
+    let mut x = 42;
+    let y = &mut x;
+    let z = &mut x;
+}
+
+fn checked() {
+    let mut a = 1;
+    let b = &mut a;
+    let c = &mut a;
+}
+"#;
+    let (analyzer, result) = analyze_with(source, BorrowCheckMode::Error);
+    assert!(result.is_err());
+    match &analyzer.errors[..] {
+        [AnalyzerError::MultipleMutableBorrows { name, .. }] => assert_eq!(name, "a"),
+        other => panic!("checked の複数の可変借用だけを期待しましたが {:?}", other),
+    }
+
+    // 型検査は属性を付けた関数でも行う
+    let source = "package test\n\n#[unsafe_no_borrow_check]\nfn f() {\n    let s: String = 1;\n}\n";
+    let (_, result) = analyze_with(source, BorrowCheckMode::Error);
+    assert!(matches!(result, Err(AnalyzerError::TypeMismatch { .. })), "{:?}", result);
+}
+
+#[test]
+fn test_borrows_do_not_leak_between_functions() {
+    // 別の関数の同じ名前の変数の借用とは競合しない
+    let source = r#"
+package test
+
+fn first() {
+    let mut x = 1;
+    let y = &mut x;
+}
+
+fn second() {
+    let mut x = 2;
+    let y = &mut x;
+    let z = &x;
+}
+"#;
+    let (analyzer, _) = analyze_with(source, BorrowCheckMode::Error);
+    match &analyzer.errors[..] {
+        [AnalyzerError::MutableBorrowConflict { span, borrowed_at, .. }] => {
+            // 競合は second の中の2つの借用の間で報告する
+            assert_eq!(&source[span.start..span.end], "&x");
+            assert_eq!(&source[borrowed_at.start..borrowed_at.end], "&mut x");
+            assert!(borrowed_at.start > source.find("fn second").unwrap());
+        }
+        other => panic!("second の借用の競合だけを期待しましたが {:?}", other),
+    }
+}
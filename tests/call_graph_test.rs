@@ -0,0 +1,171 @@
+//! 呼び出しグラフと到達できない関数の除去のテスト
+
+use yunilang::analyzer::{monomorphize_program_with_types, prune_unreachable, CallGraph, CallKind};
+use yunilang::ast::{Item, Program};
+use yunilang::compile;
+
+/// ソースコードを解析し、プログラムと呼び出しグラフを返す
+fn call_graph(source: &str) -> (Program, CallGraph) {
+    let checked = compile::check_source("graph.yuni", source).expect("check should succeed");
+    let graph = CallGraph::build(checked.program(), checked.types());
+    (checked.program().clone(), graph)
+}
+
+/// 呼び出しグラフの辺（呼び出し元・呼び出し先・種類）
+fn edges(graph: &CallGraph) -> Vec<(&str, &str, CallKind)> {
+    graph.edges.iter().map(|edge| (edge.caller.as_str(), edge.callee.as_str(), edge.kind)).collect()
+}
+
+/// プログラムに残っている関数とメソッドの名前
+fn function_names(program: &Program) -> Vec<String> {
+    program.items.iter()
+        .filter_map(|item| match item {
+            Item::Function(func) => Some(func.name.clone()),
+            Item::Method(method) => Some(method.name.clone()),
+            _ => None,
+        })
+        .collect()
+}
+
+const THREE_FUNCTIONS: &str = r#"
+package main
+
+fn helper(x: i32): i32 {
+    return x + 1;
+}
+
+fn unused(x: i32): i32 {
+    return x * 2;
+}
+
+fn main() {
+    println(helper(1));
+}
+"#;
+
+#[test]
+fn test_call_edges() {
+    // 組み込み関数の呼び出しは辺にならない
+    let (_, graph) = call_graph(THREE_FUNCTIONS);
+    assert_eq!(graph.nodes, ["helper", "unused", "main"]);
+    assert_eq!(edges(&graph), [("main", "helper", CallKind::Direct)]);
+    assert_eq!(graph.roots, ["main"]);
+}
+
+#[test]
+fn test_method_calls_and_function_pointers() {
+    let source = r#"
+package main
+
+struct Point {
+    x: i64,
+    y: i64,
+}
+
+impl Point {
+    fn sum(&self): i64 {
+        return self.x + self.y;
+    }
+}
+
+fn descending(a: i64, b: i64): i32 {
+    return (b - a) as i32;
+}
+
+fn main() {
+    let p = Point { x: 1, y: 2 };
+    let mut values: Vec<i64> = [p.sum(), 3];
+    values.sort_by(descending);
+}
+"#;
+    let (_, graph) = call_graph(source);
+    assert_eq!(
+        edges(&graph),
+        [("main", "Point::sum", CallKind::Direct), ("main", "descending", CallKind::AddressTaken)]
+    );
+    // アドレスを取られた関数は関数ポインタとして呼び出されうるため残す
+    assert!(graph.roots.contains(&"descending".to_string()));
+}
+
+#[test]
+fn test_dot_output() {
+    let (_, graph) = call_graph(THREE_FUNCTIONS);
+    let dot = graph.to_dot();
+    assert!(dot.starts_with("digraph callgraph {\n"), "{}", dot);
+    assert!(dot.contains("    \"main\" -> \"helper\";\n"), "{}", dot);
+    // mainから到達できない関数は灰色で表示する
+    assert!(dot.contains("    \"unused\" [color=gray, fontcolor=gray];\n"), "{}", dot);
+    assert!(dot.contains("    \"helper\";\n"), "{}", dot);
+    assert!(dot.ends_with("}\n"), "{}", dot);
+}
+
+#[test]
+fn test_prune_unreachable() {
+    // 公開された関数とアドレスを取られた関数は呼び出されなくても残す
+    let source = r#"
+package main
+
+fn leaf(): i32 {
+    return 1;
+}
+
+fn helper(): i32 {
+    return leaf();
+}
+
+fn only_from_unused(): i32 {
+    return 2;
+}
+
+fn unused(): i32 {
+    return only_from_unused();
+}
+
+pub fn exported(): i32 {
+    return 3;
+}
+
+fn compare(a: i64, b: i64): i32 {
+    return (a - b) as i32;
+}
+
+fn main() {
+    let mut values: Vec<i64> = [2, 1];
+    values.sort_by(compare);
+    println(helper());
+}
+"#;
+    let checked = compile::check_source("prune.yuni", source).expect("check should succeed");
+    let (mut program, types) = checked.into_parts();
+    let removed = prune_unreachable(&mut program, &types);
+    assert_eq!(removed, ["only_from_unused", "unused"]);
+    assert_eq!(function_names(&program), ["leaf", "helper", "exported", "compare", "main"]);
+}
+
+#[test]
+fn test_prune_keeps_monomorphized_instances() {
+    // 単相化したジェネリックな関数のインスタンスも、呼び出されていれば残る
+    let source = r#"
+package main
+
+fn identity<T>(x: T): T {
+    return x;
+}
+
+fn first<T>(x: T, y: T): T {
+    return identity(x);
+}
+
+fn main() {
+    println(first(5, 6), identity("!"));
+}
+"#;
+    let checked = compile::check_source("generic.yuni", source).expect("check should succeed");
+    let (program, types) = checked.into_parts();
+    let (mut monomorphized, _) = monomorphize_program_with_types(program, types.clone()).unwrap();
+    let before = function_names(&monomorphized);
+    assert!(before.len() > 3, "{:?}", before);
+    let removed = prune_unreachable(&mut monomorphized, &types);
+    assert!(removed.is_empty(), "{:?}", removed);
+    assert_eq!(function_names(&monomorphized), before);
+}
@@ -50,11 +50,11 @@ fn test_complex_program_codegen() {
     assert_valid_ir(&ir);
     
     // 複雑なプログラムの主要コンポーネントが含まれていることを確認
-    // LLVM IRでは構造体名は保持されないので、構造体の型（double）をチェック
-    assert!(ir.contains("{ double }"), "Should contain struct type definition");
+    // 構造体は名前付きの型として定義される
+    assert!(ir.contains("%Calculator = type { double }"), "Should contain struct type definition");
     assert!(ir.matches("define").count() >= 3, "Should contain multiple function definitions");
     assert!(ir.contains("call"), "Should contain function calls");
-    assert!(ir.contains("printf"), "Should contain println implementation");
+    assert!(ir.contains("yuni_println"), "Should contain println implementation");
 }
 
 #[test]
@@ -4,74 +4,96 @@ use super::*;
 
 #[test]
 fn test_arithmetic_expressions_codegen() {
-    // 算術式のコード生成テスト
+    // 算術式を実行し、符号付きの除算・剰余が0に向かって丸められることを確認する
     let source = r#"
     package main
     
-    fn main() {
-        let a = 10;
-        let b = 20;
-        let sum = a + b;
-        let diff = a - b;
-        let prod = a * b;
-        let quot = a / b;
-        let rem = a % b;
+    fn add(a: i32, b: i32): i32 {
+        return a + b;
     }
-    "#;
     
-    let ir = assert_compile_success(source, "arithmetic");
-    assert_valid_ir(&ir);
+    fn sub(a: i32, b: i32): i32 {
+        return a - b;
+    }
+    
+    fn mul(a: i32, b: i32): i32 {
+        return a * b;
+    }
+    
+    fn div(a: i32, b: i32): i32 {
+        return a / b;
+    }
+    
+    fn rem(a: i32, b: i32): i32 {
+        return a % b;
+    }
+    
+    fn combined(a: i32, b: i32, c: i32): i32 {
+        return a + b * c - (a - c) / b;
+    }
+    "#;
     
-    // 算術演算のLLVM命令が含まれていることを確認
-    assert!(ir.contains("add"), "Should contain add instruction");
-    assert!(ir.contains("sub"), "Should contain sub instruction");
-    assert!(ir.contains("mul"), "Should contain mul instruction");
-    assert!(ir.contains("div") || ir.contains("sdiv"), "Should contain division instruction");
-    assert!(ir.contains("rem") || ir.contains("srem"), "Should contain remainder instruction");
+    with_jit(source, |engine| {
+        assert_eq!(call::<i32, i32>(engine, "add", &[10, 20]), 30);
+        assert_eq!(call::<i32, i32>(engine, "sub", &[10, 20]), -10);
+        assert_eq!(call::<i32, i32>(engine, "mul", &[-6, 7]), -42);
+        assert_eq!(call::<i32, i32>(engine, "div", &[-7, 2]), -3);
+        assert_eq!(call::<i32, i32>(engine, "rem", &[-7, 2]), -1);
+        assert_eq!(call::<i32, i32>(engine, "rem", &[7, -2]), 1);
+        // 乗除算は加減算より先に評価される
+        assert_eq!(call::<i32, i32>(engine, "combined", &[10, 3, 4]), 20);
+    });
 }
 
 #[test]
 fn test_floating_point_operations_codegen() {
-    // 浮動小数点演算のコード生成テスト
+    // 浮動小数点演算と比較の実行テスト
     let source = r#"
     package main
     
-    fn main() {
-        let x: f64 = 3.14;
-        let y: f64 = 2.71;
+    fn mix(x: f64, y: f64): f64 {
         let sum = x + y;
         let prod = x * y;
-        let is_greater = x > y;
+        return sum * 10.0 + prod;
     }
-    "#;
     
-    let ir = assert_compile_success(source, "float_ops");
-    assert_valid_ir(&ir);
+    fn distance(x: f64, y: f64): f64 {
+        if x > y {
+            return x - y;
+        }
+        return y - x;
+    }
+    "#;
     
-    // 浮動小数点演算のLLVM命令が含まれていることを確認
-    assert!(ir.contains("fadd"), "Should contain floating point addition");
-    assert!(ir.contains("fmul"), "Should contain floating point multiplication");
-    assert!(ir.contains("fcmp"), "Should contain floating point comparison");
+    with_jit(source, |engine| {
+        assert_eq!(call::<f64, f64>(engine, "mix", &[1.5, 2.0]), 38.0);
+        assert_eq!(call::<f64, f64>(engine, "distance", &[3.25, 1.0]), 2.25);
+        assert_eq!(call::<f64, f64>(engine, "distance", &[-1.0, 1.5]), 2.5);
+    });
 }
 
 #[test]
 fn test_type_casting_codegen() {
-    // 型キャストのコード生成テスト
+    // 型キャストの実行テスト（浮動小数点数から整数へのキャストは0に向かって切り捨てる）
     let source = r#"
     package main
     
-    fn main() {
-        let int_val: i32 = 42;
+    fn halve(int_val: i32): i32 {
         let float_val: f64 = int_val as f64;
-        let back_to_int: i32 = float_val as i32;
+        let back_to_int: i32 = (float_val / 2.0) as i32;
+        return back_to_int;
     }
-    "#;
     
-    let ir = assert_compile_success(source, "casting");
-    assert_valid_ir(&ir);
+    fn widen(int_val: i32): f64 {
+        return int_val as f64 + 0.5;
+    }
+    "#;
     
-    // 型変換のLLVM命令が含まれていることを確認
-    assert!(ir.contains("sitofp") || ir.contains("fptosi"), "Should contain type conversion instructions");
+    with_jit(source, |engine| {
+        assert_eq!(call::<i32, i32>(engine, "halve", &[7]), 3);
+        assert_eq!(call::<i32, i32>(engine, "halve", &[-7]), -3);
+        assert_eq!(call::<i32, f64>(engine, "widen", &[-42]), -41.5);
+    });
 }
 
 #[test]
@@ -80,15 +102,16 @@ fn test_integer_literal_type_inference() {
     let source = r#"
     package main
     
-    fn main() {
+    fn narrow(): i32 {
         let a = 42;         // i32として推論
-        let b = 42i64;      // i64として明示
         let c = 255u8;      // u8として明示
         let d = -128i8;     // i8として明示
-        
-        // 演算での型の整合性確認
-        let sum = a + a;    // i32 + i32
-        let sum2 = b + b;   // i64 + i64
+        return a + a + c as i32 + d as i32;
+    }
+    
+    fn wide(): i64 {
+        let b = 3000000000i64;  // i64として明示
+        return b + b;           // i32に切り詰められない
     }
     "#;
     
@@ -99,32 +122,170 @@ fn test_integer_literal_type_inference() {
     assert!(ir.contains("i32"), "Should contain i32 type");
     assert!(ir.contains("i64"), "Should contain i64 type");
     assert!(ir.contains("i8"), "Should contain i8 type");
+    
+    assert_eq!(run_i32(source, "narrow", &[]), 211);
+    assert_eq!(run_i64(source, "wide", &[]), 6000000000);
 }
 
 #[test]
 fn test_signed_unsigned_integers() {
-    // 符号付き/符号なし整数のテスト
+    // 符号付き/符号なし整数の除算・剰余の実行テスト
+    let source = r#"
+    package main
+    
+    fn s_div(signed: i32): i32 {
+        return signed / 4;
+    }
+    
+    fn s_rem(signed: i32): i32 {
+        return signed % 4;
+    }
+    
+    fn u_div(unsigned: u32): u32 {
+        return unsigned / 3u32;
+    }
+    
+    fn u_rem(unsigned: u32): u32 {
+        return unsigned % 7u32;
+    }
+    "#;
+    
+    with_jit(source, |engine| {
+        assert_eq!(call::<i32, i32>(engine, "s_div", &[-42]), -10);
+        assert_eq!(call::<i32, i32>(engine, "s_rem", &[-42]), -2);
+        // 最上位ビットが立った値も符号なしとして除算する
+        assert_eq!(call::<u32, u32>(engine, "u_div", &[4000000000]), 1333333333);
+        assert_eq!(call::<u32, u32>(engine, "u_rem", &[4000000000]), 4000000000 % 7);
+    });
+}
+
+#[test]
+fn test_bitwise_operators_codegen() {
+    // ビット演算子の実行テスト（右シフトは符号付きなら算術シフト、符号なしなら論理シフト）
+    let source = r#"
+    package main
+
+    fn s_and(a: i32, b: i32): i32 {
+        return a & b;
+    }
+
+    fn s_or(a: i32, b: i32): i32 {
+        return a | b;
+    }
+
+    fn s_xor(a: i32, b: i32): i32 {
+        return a ^ b;
+    }
+
+    fn s_shl(a: i32, b: i32): i32 {
+        return a << b;
+    }
+
+    fn s_shr(a: i32, b: i32): i32 {
+        return a >> b;
+    }
+
+    fn u_shr(a: u32, b: u32): u32 {
+        return a >> b;
+    }
+
+    fn folded(): i32 {
+        return (1 << 4) | (255 & 15) ^ (-16 >> 2);
+    }
+    "#;
+
+    let ir = assert_compile_success(source, "bitwise");
+    assert_valid_ir(&ir);
+    assert!(function_ir(&ir, "s_shr").contains("ashr i32"), "{}", ir);
+    assert!(function_ir(&ir, "u_shr").contains("lshr i32"), "{}", ir);
+    // 定数でないシフト量は範囲を検査する
+    assert!(function_ir(&ir, "s_shl").contains("shift.fail"), "{}", ir);
+    assert!(!function_ir(&ir, "s_and").contains("shift.fail"), "{}", ir);
+
+    with_jit(source, |engine| {
+        assert_eq!(call::<i32, i32>(engine, "s_and", &[-16, 7]), 0);
+        assert_eq!(call::<i32, i32>(engine, "s_or", &[-16, 3]), -13);
+        assert_eq!(call::<i32, i32>(engine, "s_xor", &[6, 3]), 5);
+        assert_eq!(call::<i32, i32>(engine, "s_shl", &[-16, 2]), -64);
+        assert_eq!(call::<i32, i32>(engine, "s_shl", &[1, 31]), i32::MIN);
+        assert_eq!(call::<i32, i32>(engine, "s_shr", &[-16, 2]), -4);
+        assert_eq!(call::<u32, u32>(engine, "u_shr", &[0x8000_0000, 31]), 1);
+        assert_eq!(call::<i32, i32>(engine, "folded", &[]), 16 | (15 ^ -4));
+    });
+}
+#[test]
+fn test_numeric_promotion_codegen() {
+    // 数値型の昇格規則に従った拡張命令の生成テスト
     let source = r#"
     package main
     
     fn main() {
-        let signed: i32 = -42;
-        let unsigned: u32 = 42u32;
-        
-        // 符号付き演算
-        let s_div = signed / 2;
-        let s_rem = signed % 3;
-        
-        // 符号なし演算
-        let u_div = unsigned / 2u32;
-        let u_rem = unsigned % 3u32;
+        let a: i64 = 10i64;
+        let b: i64 = a + 1;      // リテラルはi64として生成される
+        let c: u8 = 200u8;
+        let d: u8 = c / 3;       // リテラルはu8として生成される
     }
     "#;
     
-    let ir = assert_compile_success(source, "signed_unsigned");
+    let ir = assert_compile_success(source, "promotion_literals");
     assert_valid_ir(&ir);
     
-    // 符号付き/符号なし演算が含まれていることを確認
-    assert!(ir.contains("sdiv") || ir.contains("udiv"), "Should contain signed/unsigned division");
-    assert!(ir.contains("srem") || ir.contains("urem"), "Should contain signed/unsigned remainder");
-}
\ No newline at end of file
+    // 解析で型が揃っているオペランドには拡張命令が不要
+    assert!(!ir.contains("sext"), "Should not contain sext: {}", ir);
+    assert!(!ir.contains("zext"), "Should not contain zext: {}", ir);
+    assert!(ir.contains("udiv i8"), "Should divide as u8: {}", ir);
+
+    let source = r#"
+    package main
+    
+    fn widen(small: u8, large: u32): u32 {
+        return small + large;    // u8はu32にゼロ拡張される
+    }
+    
+    fn extend(x: i32): i64 {
+        return x + 1i64;         // i32はi64に符号拡張される
+    }
+    
+    fn widen_max(): u32 {
+        return widen(255u8, 4000000000u32);
+    }
+    "#;
+    
+    let ir = assert_compile_success(source, "promotion_widening");
+    assert_valid_ir(&ir);
+    assert!(ir.contains("zext i8"), "u8 should be zero-extended: {}", ir);
+    assert!(ir.contains("sext i32"), "i32 should be sign-extended: {}", ir);
+    
+    with_jit(source, |engine| {
+        assert_eq!(call::<i32, i64>(engine, "extend", &[-1]), 0);
+        assert_eq!(call::<i32, i64>(engine, "extend", &[i32::MIN]), i32::MIN as i64 + 1);
+        assert_eq!(call::<u32, u32>(engine, "widen_max", &[]), 4000000255);
+    });
+}
+
+#[test]
+fn test_call_argument_literal_uses_parameter_type() {
+    // 呼び出し引数のリテラルは解析で決定した仮引数の型で生成される
+    let source = r#"
+    package main
+    
+    fn wide(x: i64): i64 {
+        return x;
+    }
+    
+    fn main() {
+        let y: i64 = wide(3000000000);
+    }
+    
+    fn call_wide(): i64 {
+        return wide(3000000000);
+    }
+    "#;
+    
+    let ir = assert_compile_success(source, "call_literal_type");
+    assert_valid_ir(&ir);
+    
+    // i32に切り詰められずにi64の定数として渡され、符号拡張されない
+    assert_golden_ir(&ir, "call_literal_type", &["wide", "main"]);
+    assert_eq!(run_i64(source, "call_wide", &[]), 3000000000);
+}
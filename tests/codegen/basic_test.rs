@@ -34,11 +34,37 @@ fn test_hello_world_codegen() {
     let ir = assert_compile_success(source, "hello");
     assert_valid_ir(&ir);
     
-    // printf呼び出しが含まれていることを確認
-    assert!(ir.contains("printf"), "Should contain printf call for println");
+    // printlnはランタイムの出力関数で出力し、printfは使わない
+    assert!(ir.contains("yuni_println"), "Should contain yuni_println call for println");
+    assert!(!ir.contains("printf"), "println should not be lowered to printf");
     assert!(ir.contains("Hello, World!"), "Should contain the string literal");
 }
 
+#[test]
+fn test_console_output_codegen() {
+    // print・println・eprintln はそれぞれのランタイム関数に文字列を1つ渡し、
+    // 書式指定子を含む文字列もprintfの書式として使わない
+    let source = r#"
+    package main
+    
+    fn main() {
+        let user: String = "%s%s%s";
+        print(user);
+        println("value: ", 42, " ", user);
+        eprintln(`error: ${user}`);
+    }
+    "#;
+    
+    let ir = assert_compile_success(source, "console_output");
+    assert_valid_ir(&ir);
+    
+    assert!(ir.contains("@yuni_print("), "Should call yuni_print");
+    assert!(ir.contains("@yuni_println("), "Should call yuni_println");
+    assert!(ir.contains("@yuni_eprintln("), "Should call yuni_eprintln");
+    assert!(ir.contains("%s%s%s"), "Should contain the string literal");
+    assert!(!ir.contains("printf"), "Console output should not use printf");
+}
+
 #[test]
 fn test_ir_validation() {
     // 生成されるLLVM IRの妥当性を確認
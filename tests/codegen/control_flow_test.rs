@@ -21,37 +21,45 @@ fn test_conditional_statements_codegen() {
     }
     "#;
     
-    let ir = assert_compile_success(source, "conditionals");
-    assert_valid_ir(&ir);
-    
-    // 条件分岐のLLVM命令が含まれていることを確認
-    assert!(ir.contains("icmp"), "Should contain integer comparison");
-    assert!(ir.contains("br"), "Should contain branch instructions");
-    assert!(ir.contains("label"), "Should contain basic block labels");
+    with_jit(source, |engine| {
+        assert_eq!(call::<i32, i32>(engine, "abs", &[-42]), 42);
+        assert_eq!(call::<i32, i32>(engine, "abs", &[42]), 42);
+        assert_eq!(call::<i32, i32>(engine, "abs", &[0]), 0);
+    });
 }
 
 #[test]
 fn test_loops_codegen() {
-    // ループのコード生成テスト
+    // ループの実行テスト
     let source = r#"
     package main
     
-    fn main() {
+    fn sum_below(n: i32): i32 {
         let mut i = 0;
-        while i < 10 {
+        let mut total = 0;
+        while i < n {
             println(i);
+            total = total + i;
             i = i + 1;
         }
+        return total;
     }
-    "#;
     
-    let ir = assert_compile_success(source, "loops");
-    assert_valid_ir(&ir);
+    fn sum_range(n: i32): i32 {
+        let mut total = 0;
+        for let mut i = 0; i <= n; i = i + 1 {
+            total = total + i;
+        }
+        return total;
+    }
+    "#;
     
-    // ループのLLVM構造が含まれていることを確認
-    assert!(ir.contains("br"), "Should contain branch instructions for loop");
-    assert!(ir.contains("icmp"), "Should contain comparison for loop condition");
-    assert!(ir.matches("label").count() >= 2, "Should contain multiple basic blocks for loop");
+    with_jit(source, |engine| {
+        assert_eq!(call::<i32, i32>(engine, "sum_below", &[10]), 45);
+        // 条件が最初から偽ならループの本体は実行されない
+        assert_eq!(call::<i32, i32>(engine, "sum_below", &[0]), 0);
+        assert_eq!(call::<i32, i32>(engine, "sum_range", &[10]), 55);
+    });
 }
 
 #[test]
@@ -82,8 +90,149 @@ fn test_multiple_return_paths_codegen() {
     
     // 複数の戻り値パスが含まれていることを確認
     assert!(ir.matches("ret i32").count() >= 3, "Should contain multiple return statements");
-    assert!(ir.contains("icmp"), "Should contain comparisons");
-    assert!(ir.contains("br"), "Should contain branches");
+    
+    with_jit(source, |engine| {
+        assert_eq!(call::<i32, i32>(engine, "classify", &[10]), 1);
+        assert_eq!(call::<i32, i32>(engine, "classify", &[-10]), -1);
+        assert_eq!(call::<i32, i32>(engine, "classify", &[0]), 0);
+    });
+}
+
+#[test]
+fn test_defer_codegen() {
+    // defer文のコード生成テスト
+    let source = r#"
+    package main
+    
+    fn counter(flag: bool): i32 {
+        let mut count: i32 = 1;
+        defer {
+            println("first");
+        }
+        defer {
+            count = count + 10;
+            println("second");
+        }
+        if flag {
+            return count;
+        }
+        return count + 1;
+    }
+    
+    fn main() {
+        let a = counter(true);
+        let b = counter(false);
+    }
+    "#;
+    
+    let ir = assert_compile_success(source, "defer");
+    assert_valid_ir(&ir);
+    
+    // 両方のreturnの前でdeferが展開されていることを確認
+    assert_eq!(ir.matches("ret i32").count(), 2, "Should contain both return paths");
+    assert_eq!(ir.matches("%println_call").count(), 4, "Each return path should run both defers: {}", ir);
+    
+    // 戻り値はdeferより前に読み込まれる
+    let counter_body = &ir[ir.find("define i32 @counter").expect("counter should be defined")..];
+    let first_load = counter_body.find("load i32").expect("return value should be loaded");
+    let first_print = counter_body.find("%println_call").expect("defer should print");
+    assert!(first_load < first_print, "Return value should be evaluated before defers");
+    
+    // deferでの変更は戻り値に影響しない
+    with_jit(source, |engine| {
+        assert_eq!(call::<bool, i32>(engine, "counter", &[true]), 1);
+        assert_eq!(call::<bool, i32>(engine, "counter", &[false]), 2);
+    });
+}
+
+#[test]
+fn test_implicit_return_codegen() {
+    // 最後の式が関数の戻り値として返されるコード生成テスト
+    let source = r#"
+    package main
+    
+    fn add(a: i32, b: i32): i32 {
+        a + b
+    }
+    
+    fn abs(x: i32): i32 {
+        if x < 0 {
+            -x
+        } else {
+            x
+        }
+    }
+    
+    fn main() {
+        let sum = add(1, 2);
+        let value = abs(-3);
+    }
+    "#;
+    
+    let ir = assert_compile_success(source, "implicit_return");
+    assert_valid_ir(&ir);
+    
+    let add_body = &ir[ir.find("define i32 @add").expect("add should be defined")..];
+    let add_body = &add_body[..add_body.find("\n}").unwrap()];
+    assert!(add_body.contains("add i32"), "Should compute the sum: {}", add_body);
+    assert!(add_body.contains("ret i32 %"), "Should return the trailing expression: {}", add_body);
+    
+    // if式の値がphiで合流して返される
+    let abs_body = &ir[ir.find("define i32 @abs").expect("abs should be defined")..];
+    let abs_body = &abs_body[..abs_body.find("\n}").unwrap()];
+    assert!(abs_body.contains("phi i32"), "Should merge branch values: {}", abs_body);
+    assert!(abs_body.contains("ret i32 %"), "Should return the if value: {}", abs_body);
+    
+    with_jit(source, |engine| {
+        assert_eq!(call::<i32, i32>(engine, "add", &[1, 2]), 3);
+        assert_eq!(call::<i32, i32>(engine, "abs", &[-3]), 3);
+        assert_eq!(call::<i32, i32>(engine, "abs", &[5]), 5);
+    });
+}
+
+#[test]
+fn test_constant_condition_and_literal_arithmetic_codegen() {
+    // 定数の条件とリテラル同士の演算は最適化なしでも畳み込まれる
+    let source = r#"
+    package main
+    
+    fn a(): i32 {
+        return 1;
+    }
+    
+    fn b(): i32 {
+        return 2;
+    }
+    
+    fn pick(): i32 {
+        let value = if true { a() } else { b() };
+        return value;
+    }
+    
+    fn main() {
+        let picked = pick();
+        let answer = 7 * 6 - 2;
+        let small = 3 < 4;
+    }
+    "#;
+    
+    let ir = assert_compile_success(source, "constant_condition");
+    assert_valid_ir(&ir);
+    
+    // 選ばれない分岐の呼び出しも、合流のphiも生成されない
+    let pick_body = &ir[ir.find("define i32 @pick").expect("pick should be defined")..];
+    let pick_body = &pick_body[..pick_body.find("\n}").unwrap()];
+    assert!(pick_body.contains("call i32 @a()"), "Should call the taken branch: {}", pick_body);
+    assert!(!pick_body.contains("@b("), "Should not call the untaken branch: {}", pick_body);
+    assert!(!pick_body.contains("phi"), "Should not merge branches: {}", pick_body);
+    
+    let main_body = &ir[ir.find("@main(").expect("main should be defined")..];
+    let main_body = &main_body[..main_body.find("\n}").unwrap()];
+    assert!(main_body.contains("store i32 40"), "Should fold literal arithmetic: {}", main_body);
+    assert!(!main_body.contains("mul i32"), "Should not emit folded instructions: {}", main_body);
+    assert!(!main_body.contains("icmp"), "Should fold literal comparisons: {}", main_body);
+    
+    assert_eq!(run_i32(source, "pick", &[]), 1);
 }
 
 #[test]
@@ -105,11 +254,11 @@ fn test_recursive_functions_codegen() {
     }
     "#;
     
-    let ir = assert_compile_success(source, "recursive");
-    assert_valid_ir(&ir);
-    
-    // 再帰呼び出しが含まれていることを確認
-    assert!(ir.contains("call"), "Should contain recursive function call");
+    with_jit(source, |engine| {
+        assert_eq!(call::<i32, i32>(engine, "factorial", &[1]), 1);
+        assert_eq!(call::<i32, i32>(engine, "factorial", &[5]), 120);
+        assert_eq!(call::<i32, i32>(engine, "factorial", &[10]), 3628800);
+    });
 }
 
 #[test]
@@ -128,10 +277,181 @@ fn test_function_calls_codegen() {
     }
     "#;
     
-    let ir = assert_compile_success(source, "functions");
+    with_jit(source, |engine| {
+        assert_eq!(call::<i32, i32>(engine, "add", &[5, 3]), 8);
+        // mainは呼び出した結果を文字列にして出力する
+        call::<i32, ()>(engine, "main", &[]);
+    });
+}
+#[test]
+fn test_diverging_branches_codegen() {
+    // returnで抜ける分岐はphiに合流せず、すべての分岐が戻る場合は合流ブロックを作らない
+    let source = r#"
+    package main
+
+    fn clamp(x: i32): i32 {
+        let y = if x < 0 { return 0; } else { x * 2 };
+        return y + 1;
+    }
+
+    fn parse_digit(c: i32): i32 {
+        let d = match c {
+            0 => { return -1; },
+            n => n - 48,
+        };
+        return d;
+    }
+
+    fn sign(x: i32): i32 {
+        if x < 0 { return -1; } else { return 1; }
+    }
+
+    fn main() {
+        let a = clamp(5);
+        let b = parse_digit(55);
+        let c = sign(-3);
+    }
+    "#;
+
+    let tokens = Lexer::new(source).collect_tokens();
+    let ast = Parser::new(tokens).parse().expect("Parsing should succeed");
+    let mut analyzer = SemanticAnalyzer::new();
+    analyzer.analyze(&ast).expect("Analysis should succeed");
+
+    let context = Context::create();
+    let mut codegen = CodeGenerator::new(&context, "diverging_branches");
+    codegen.compile_program_with_types(&ast, analyzer.type_table).expect("Compilation should succeed");
+    let ir = codegen.get_module().print_to_string().to_string();
+    codegen.get_module().verify().unwrap_or_else(|e| panic!("Module verification failed: {}\n{}", e, ir));
+
+    let clamp_body = &ir[ir.find("define i32 @clamp").expect("clamp should be defined")..];
+    let clamp_body = &clamp_body[..clamp_body.find("\n}").unwrap()];
+    assert!(clamp_body.contains("ret i32 0"), "Should return early from the then branch: {}", clamp_body);
+    assert!(clamp_body.contains("mul i32"), "Should compute the else value: {}", clamp_body);
+
+    let sign_body = &ir[ir.find("define i32 @sign").expect("sign should be defined")..];
+    let sign_body = &sign_body[..sign_body.find("\n}").unwrap()];
+    assert!(!sign_body.contains("phi"), "Should not merge diverging branches: {}", sign_body);
+
+    with_jit(source, |engine| {
+        assert_eq!(call::<i32, i32>(engine, "clamp", &[5]), 11);
+        assert_eq!(call::<i32, i32>(engine, "clamp", &[-5]), 0);
+        assert_eq!(call::<i32, i32>(engine, "parse_digit", &[55]), 7);
+        assert_eq!(call::<i32, i32>(engine, "parse_digit", &[0]), -1);
+        assert_eq!(call::<i32, i32>(engine, "sign", &[-3]), -1);
+        assert_eq!(call::<i32, i32>(engine, "sign", &[3]), 1);
+    });
+}
+
+#[test]
+fn test_or_and_binding_patterns_codegen() {
+    // 整数のorパターンは選択肢ごとにswitchのcaseになり、ペイロードを束縛するorパターンは
+    // 選択肢を順に照合してから、一致した選択肢の値を共有する変数に格納する
+    let source = r#"
+    package main
+
+    type Shape enum {
+        Circle(r: i32),
+        Square(side: i32),
+        Triangle(base: i32),
+        Dot
+    }
+
+    fn size(shape: Shape): i32 {
+        return match shape {
+            Shape::Circle(n) | Shape::Square(n) | Shape::Triangle(n) => n,
+            Shape::Dot => 0,
+        };
+    }
+
+    fn classify(code: i32): i32 {
+        return match code {
+            1 | 2 | 3 => 1,
+            d @ 4..=9 => d * 10,
+            _ => 0,
+        };
+    }
+
+    fn sizes(): i32 {
+        return size(Shape::Circle(1)) + size(Shape::Square(20)) + size(Shape::Triangle(300)) + size(Shape::Dot);
+    }
+
+    fn main() {
+        let a = size(Shape::Square(2));
+        let b = classify(7);
+    }
+    "#;
+
+    let tokens = Lexer::new(source).collect_tokens();
+    let ast = Parser::new(tokens).parse().expect("Parsing should succeed");
+    let mut analyzer = SemanticAnalyzer::new();
+    analyzer.analyze(&ast).expect("Analysis should succeed");
+
+    let context = Context::create();
+    let mut codegen = CodeGenerator::new(&context, "or_patterns");
+    codegen.compile_program_with_types(&ast, analyzer.type_table).expect("Compilation should succeed");
+    let ir = codegen.get_module().print_to_string().to_string();
+    codegen.get_module().verify().unwrap_or_else(|e| panic!("Module verification failed: {}\n{}", e, ir));
+
+    let size = function_ir(&ir, "size");
+    assert!(size.contains("or_match_2"), "Should test every alternative: {}", size);
+    assert!(size.contains("or_bind_done:"), "Should bind the matching alternative: {}", size);
+
+    let classify = function_ir(&ir, "classify");
+    assert!(classify.contains("switch i32"), "Should switch on the alternatives: {}", classify);
+    for value in ["i32 1, ", "i32 2, ", "i32 3, ", "i32 4, ", "i32 9, "] {
+        assert!(classify.contains(value), "Should have a case for {}: {}", value, classify);
+    }
+    assert!(classify.contains("%d.addr = alloca i32"), "Should bind the matched value: {}", classify);
+
+    with_jit(source, |engine| {
+        assert_eq!(call::<i32, i32>(engine, "sizes", &[]), 321);
+        assert_eq!(call::<i32, i32>(engine, "classify", &[2]), 1);
+        assert_eq!(call::<i32, i32>(engine, "classify", &[7]), 70);
+        assert_eq!(call::<i32, i32>(engine, "classify", &[10]), 0);
+    });
+}
+
+#[test]
+fn test_bool_match_lowers_to_conditional_branch() {
+    // boolのmatchはswitchを使わず、値で2つのアームへ分岐する
+    let source = r#"
+    package main
+
+    fn sign_bonus(n: i32): i32 {
+        let positive = n > 0;
+        return match positive {
+            true => n + 100,
+            false => n - 100,
+        };
+    }
+
+    fn guarded(n: i32): i32 {
+        let even = n % 2 == 0;
+        return match even {
+            true if n > 10 => 2,
+            false => 1,
+            _ => 0,
+        };
+    }
+
+    fn main() {
+        let a = sign_bonus(5);
+        let b = guarded(4);
+    }
+    "#;
+
+    let ir = compile_to_ir(source, "bool_match").expect("Compilation should succeed");
     assert_valid_ir(&ir);
-    
-    // 関数定義と呼び出しが含まれていることを確認
-    assert!(ir.contains("define") && ir.matches("define").count() >= 2, "Should contain multiple function definitions");
-    assert!(ir.contains("call"), "Should contain function call");
-}
\ No newline at end of file
+    let sign_bonus = function_ir(&ir, "sign_bonus");
+    assert!(!sign_bonus.contains("switch"), "Should not switch on a bool: {}", sign_bonus);
+    assert!(sign_bonus.contains("br i1 %"), "Should branch on the bool: {}", sign_bonus);
+
+    with_jit(source, |engine| {
+        assert_eq!(call::<i32, i32>(engine, "sign_bonus", &[5]), 105);
+        assert_eq!(call::<i32, i32>(engine, "sign_bonus", &[-5]), -105);
+        assert_eq!(call::<i32, i32>(engine, "guarded", &[12]), 2);
+        assert_eq!(call::<i32, i32>(engine, "guarded", &[4]), 0);
+        assert_eq!(call::<i32, i32>(engine, "guarded", &[3]), 1);
+    });
+}
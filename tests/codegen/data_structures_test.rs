@@ -19,25 +19,302 @@ fn test_struct_operations_codegen() {
         return dx * dx + dy * dy;
     }
     
-    fn main() {
+    fn main(): f64 {
         let origin = Point { x: 0.0, y: 0.0 };
         let point = Point { x: 3.0, y: 4.0 };
         let dist_sq = distance_squared(origin, point);
+        return dist_sq;
     }
     "#;
     
     let ir = assert_compile_success(source, "structs");
     assert_valid_ir(&ir);
     
-    // デバッグ用にIRを出力
-    println!("Generated IR:\n{}", ir);
-    
-    // 構造体型の定義とフィールドアクセスが含まれていることを確認
-    // LLVMのバージョンによっては型定義が異なる形式で出力される可能性がある
-    assert!(ir.contains("%Point") || ir.contains("struct") || ir.contains("{ double, double }"), 
-            "Should contain struct type definitions or struct literals");
-    assert!(ir.contains("extractvalue") || ir.contains("getelementptr"), 
-            "Should contain struct field access");
+    // 構造体の値からフィールドを取り出し、変数名・フィールド名の付いた値になる
+    assert_golden_ir(&ir, "distance_squared", &["distance_squared"]);
+    assert_eq!(run_f64(source, "main", &[]), 25.0);
+}
+
+#[test]
+fn test_struct_field_of_later_struct_codegen() {
+    // 後で定義される構造体・列挙型をフィールドに持つ構造体
+    let source = r#"
+    package main
+
+    type Segment struct {
+        from: Point,
+        to: Point,
+        kind: Kind,
+    }
+
+    type Kind enum {
+        Line,
+        Arrow { head: f64 },
+    }
+
+    type Point struct {
+        x: f64,
+        y: f64,
+    }
+
+    fn length_squared(s: Segment): f64 {
+        let dx = s.to.x - s.from.x;
+        let dy = s.to.y - s.from.y;
+        return dx * dx + dy * dy;
+    }
+
+    fn main(): f64 {
+        let s = Segment { from: Point { x: 1.0, y: 2.0 }, to: Point { x: 4.0, y: 6.0 }, kind: Kind::Line };
+        let len = length_squared(s);
+        return len;
+    }
+    "#;
+
+    let ir = assert_compile_success(source, "later_struct");
+    assert_valid_ir(&ir);
+    assert!(ir.contains("define double @length_squared"), "IR should contain length_squared: {}", ir);
+    assert_eq!(run_f64(source, "main", &[]), 25.0);
+}
+
+#[test]
+fn test_enum_variants_share_one_layout() {
+    // ペイロードのないバリアントと型の異なるペイロードを持つバリアントを、
+    // 同じ可変変数にループの中で代入し直しても、毎回正しくmatchできる
+    let source = r#"
+    package main
+
+    type Slot enum {
+        Empty,
+        Single(v: i64),
+        Pair(a: i32, b: f64),
+    }
+
+    fn describe(slot: Slot): i64 {
+        return match slot {
+            Slot::Empty => 1000,
+            Slot::Single(v) => v,
+            Slot::Pair(a, b) => (a as i64) * 100 + (b * 2.0) as i64,
+        };
+    }
+
+    fn cycle(): i64 {
+        let mut slot = Slot::Empty;
+        let mut total: i64 = 0;
+        for let mut i = 0; i < 6; i = i + 1 {
+            if i % 3 == 0 {
+                slot = Slot::Single((i as i64) * 10);
+            } else if i % 3 == 1 {
+                slot = Slot::Pair(i, 1.5);
+            } else {
+                slot = Slot::Empty;
+            }
+            total = total + describe(slot);
+        }
+        return total;
+    }
+
+    fn main() {
+    }
+    "#;
+
+    // どのバリアントもタグと、最も大きいバリアント（Pair: 16バイト）が収まる領域の1つの型になる
+    let ir = assert_compile_success(source, "enum_layout");
+    assert_valid_ir(&ir);
+    assert!(ir.contains("%Slot = type { i32, [2 x i64] }"), "The enum should be a tagged union: {}", ir);
+    let cycle = function_ir(&ir, "cycle");
+    assert!(cycle.contains("alloca %Slot"), "The variable should use the tagged union: {}", cycle);
+    assert!(ir.contains("define i64 @describe(%Slot "), "Enums should be passed by value: {}", ir);
+
+    assert_eq!(run_i64(source, "cycle", &[]), 2536);
+}
+
+#[test]
+fn test_self_referential_types_codegen() {
+    // 参照を介して自身を含む構造体・列挙型は、名前付きの型として宣言してから本体を設定する
+    let source = r#"
+    package main
+
+    type Node struct {
+        value: i64,
+        next: &Node,
+    }
+
+    type Tree enum {
+        Leaf(value: i64),
+        Branch(left: &Tree, right: &Tree),
+    }
+
+    fn third(node: &Node): i64 {
+        return node.next.next.value;
+    }
+
+    fn leaf_value(tree: Tree): i64 {
+        return match tree {
+            Tree::Leaf(value) => value,
+            Tree::Branch(left, right) => 0,
+        };
+    }
+
+    fn main() {
+    }
+    "#;
+
+    let ir = assert_compile_success(source, "self_referential");
+    assert!(ir.contains("%Node = type { i64, ptr }"), "Node should be a named struct: {}", ir);
+    assert!(ir.contains("%Tree = type { i32, [2 x i64] }"), "Tree should be a named tagged union: {}", ir);
+
+    #[repr(C)]
+    struct Node {
+        value: i64,
+        next: *const Node,
+    }
+    let last = Node { value: 3, next: std::ptr::null() };
+    let middle = Node { value: 2, next: &last };
+    let first = Node { value: 1, next: &middle };
+    let value: i64 = with_jit(source, |engine| call(engine, "third", &[&first as *const Node]));
+    assert_eq!(value, 3);
+}
+
+#[test]
+fn test_mutually_recursive_structs_codegen() {
+    // 参照を介して互いを含む構造体（後で定義される型を先に参照する）
+    let source = r#"
+    package main
+
+    type Left struct {
+        value: i64,
+        right: &Right,
+    }
+
+    type Right struct {
+        scale: i64,
+        left: &Left,
+    }
+
+    fn bounce(left: &Left): i64 {
+        return left.right.left.value * left.right.scale;
+    }
+
+    fn main() {
+    }
+    "#;
+
+    let ir = assert_compile_success(source, "mutual_recursion");
+    assert!(ir.contains("%Left = type { i64, ptr }"), "Left should be a named struct: {}", ir);
+    assert!(ir.contains("%Right = type { i64, ptr }"), "Right should be a named struct: {}", ir);
+
+    #[repr(C)]
+    struct Left {
+        value: i64,
+        right: *const Right,
+    }
+    #[repr(C)]
+    struct Right {
+        scale: i64,
+        left: *const Left,
+    }
+    let mut left = Left { value: 7, right: std::ptr::null() };
+    let right = Right { scale: 6, left: &left };
+    left.right = &right;
+    let value: i64 = with_jit(source, |engine| call(engine, "bounce", &[&left as *const Left]));
+    assert_eq!(value, 42);
+}
+
+#[test]
+fn test_type_conversion_is_cached() {
+    // 変換した型はキャッシュから返すため、深くネストした型を何度変換しても時間がかからない
+    use yunilang::ast::Type;
+
+    const DEPTH: usize = 100;
+    const CONVERSIONS: usize = 10_000;
+
+    let mut ty = Type::Generic("Vec".to_string(), vec![Type::I64]);
+    for _ in 0..DEPTH {
+        ty = Type::Tuple(vec![Type::I64, Type::Generic("Vec".to_string(), vec![Type::F64]), ty]);
+    }
+
+    let context = Context::create();
+    let codegen = CodeGenerator::new(&context, "type_cache");
+    let expected = codegen.type_manager.ast_type_to_llvm(&ty).expect("Conversion should succeed");
+
+    let start = std::time::Instant::now();
+    for _ in 0..CONVERSIONS {
+        assert_eq!(codegen.type_manager.ast_type_to_llvm(&ty).expect("Conversion should succeed"), expected);
+    }
+    let elapsed = start.elapsed();
+    assert!(
+        elapsed < std::time::Duration::from_secs(1),
+        "Converting a type nested {} levels {} times took too long: {:?}",
+        DEPTH,
+        CONVERSIONS,
+        elapsed
+    );
+}
+
+#[test]
+fn test_undefined_enum_variant_lists_valid_variants() {
+    // セマンティック解析を通さず、コード生成で存在しないバリアントを解決する
+    let source = r#"
+    package main
+
+    type Color enum {
+        Red,
+        Green,
+        Blue,
+    }
+
+    fn main() {
+        let c = Color::Purple;
+    }
+    "#;
+
+    let tokens = Lexer::new(source).collect_tokens();
+    let ast = Parser::new(tokens).parse().expect("Parsing should succeed");
+    let context = Context::create();
+    let mut codegen = CodeGenerator::new(&context, "undefined_variant");
+    let error = codegen.compile_program(&ast).expect_err("Compilation should fail");
+
+    match error {
+        yunilang::error::YuniError::Codegen(yunilang::error::CodegenError::UndefinedVariant { enum_name, variant, valid, .. }) => {
+            assert_eq!(enum_name, "Color");
+            assert_eq!(variant, "Purple");
+            assert_eq!(valid, "Red, Green, Blue");
+        }
+        other => panic!("Expected UndefinedVariant error, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_many_struct_literals_compile_quickly() {
+    // 構造体のレイアウトは型の宣言時に一度だけ計算し、リテラルやフィールドアクセスごとには複製しない
+    const FUNCTIONS: usize = 50;
+    const LITERALS_PER_FUNCTION: usize = 100;
+
+    let mut source = String::from(
+        "package main\n\ntype Point struct {\n    x: i32,\n    y: i32,\n    z: f64,\n}\n\n",
+    );
+    for f in 0..FUNCTIONS {
+        source.push_str(&format!("fn sum_{}(): i32 {{\n    let mut total = 0;\n", f));
+        for i in 0..LITERALS_PER_FUNCTION {
+            source.push_str(&format!(
+                "    let p{i} = Point {{ x: {i}, y: {f}, z: 0.5 }};\n    total = total + p{i}.x + p{i}.y;\n",
+            ));
+        }
+        source.push_str("    return total;\n}\n\n");
+    }
+    source.push_str("fn main() {\n}\n");
+
+    let start = std::time::Instant::now();
+    let ir = assert_compile_success(&source, "many_struct_literals");
+    let elapsed = start.elapsed();
+
+    assert!(ir.contains(&format!("define i32 @sum_{}", FUNCTIONS - 1)), "IR should contain every function");
+    assert!(
+        elapsed < std::time::Duration::from_secs(30),
+        "Compiling {} struct literals took too long: {:?}",
+        FUNCTIONS * LITERALS_PER_FUNCTION,
+        elapsed
+    );
 }
 
 #[test]
@@ -45,7 +322,7 @@ fn test_tuple_expressions() {
     let source = r#"
     package test
 
-    fn main() {
+    fn main(): i32 {
         // 空のタプル
         let empty = ();
         
@@ -58,16 +335,23 @@ fn test_tuple_expressions() {
         
         // ネストしたタプル
         let nested = ((1, 2), (3, 4));
+        
+        let (a,) = single;
+        let (b, c) = pair;
+        let (_, d, flag) = triple;
+        let ((e, f), (g, h)) = nested;
+        if !flag {
+            return -1;
+        }
+        return a + b + (c * 2.0) as i32 + d + e * 1000 + f * 100 + g * 10 + h;
     }
     "#;
     
     let ir = compile_to_ir(source, "test_tuples").unwrap();
     assert_valid_ir(&ir);
     
-    // タプルが構造体として実装されていることを確認
-    assert!(ir.contains("alloca"), "IR should contain alloca for tuples");
-    assert!(ir.contains("getelementptr"), "IR should contain GEP for tuple field access");
-    assert!(ir.contains("store"), "IR should contain store instructions for tuple elements");
+    // 要素を順に取り出し、ネストしたタプルの要素も入れ替わらない
+    assert_eq!(run_i32(source, "main", &[]), 42 + 1 + 5 + 42 + 1234);
 }
 
 #[test]
@@ -75,7 +359,7 @@ fn test_array_expressions() {
     let source = r#"
     package test
 
-    fn main() {
+    fn main(): f64 {
         // 固定サイズ配列
         let arr1 = [1, 2, 3, 4, 5];
         // let arr2 = [0; 10];  // TODO: 配列の繰り返し初期化構文は未実装
@@ -87,16 +371,17 @@ fn test_array_expressions() {
         // 配列の要素アクセス
         let first = arr1[0];
         let last = arr1[4];
+        if bool_arr[1] {
+            return -1.0;
+        }
+        return (first * 10 + last) as f64 + float_arr[2] / 4.0;
     }
     "#;
     
     let ir = compile_to_ir(source, "test_arrays").unwrap();
     assert_valid_ir(&ir);
     
-    // 配列の初期化と要素アクセスが含まれていることを確認
-    assert!(ir.contains("alloca") || ir.contains("malloc"), "IR should contain memory allocation for arrays");
-    assert!(ir.contains("store"), "IR should contain store instructions for array initialization");
-    assert!(ir.contains("getelementptr"), "IR should contain GEP for array indexing");
+    assert_eq!(run_f64(source, "main", &[]), 15.75);
 }
 
 #[test]
@@ -134,4 +419,249 @@ fn test_index_access() {
     // 配列の初期化を確認
     assert!(ir.contains("malloc"), "Should allocate array on heap");
     assert!(ir.contains("store i32 30"), "Should store array element 30");
-}
\ No newline at end of file
+    
+    with_jit(source, |engine| {
+        assert_eq!(call::<i32, i32>(engine, "test_array_index", &[]), 30);
+        assert_eq!(call::<i32, i32>(engine, "test_array_index_ref", &[]), 200);
+        assert_eq!(call::<i32, i32>(engine, "test_array_index_expr", &[]), 20);
+    });
+}
+#[test]
+fn test_bytes_literal_codegen() {
+    // バイト列リテラルは定数のグローバル変数から長さを指定してVecを作り、NULで切れない
+    let source = r#"
+    package main
+
+    fn main() {
+        let frame = b"A\x00\xff";
+        let first: u8 = frame[0];
+        let ok = write_bytes("frame.bin", frame);
+        let data = read_bytes("frame.bin");
+        let n = data.len();
+    }
+
+    fn checksum(): i64 {
+        let frame = b"A\x00\xff";
+        let last: u8 = frame[2];
+        return frame.len() * 1000 + last as i64;
+    }
+    "#;
+
+    let result = compile_to_ir(source, "test_bytes");
+    assert!(result.is_ok(), "Compilation should succeed: {:?}", result.unwrap_err());
+    let ir = result.unwrap();
+
+    assert!(ir.contains(r#"c"A\00\FF""#), "Should emit the bytes as a constant without a terminator:\n{}", ir);
+    assert!(ir.contains("@yuni_vec_from_bytes(ptr @main.bytes, i64 3)"), "Should build the Vec in one call:\n{}", ir);
+    assert!(ir.contains("@yuni_vec_data") && ir.contains("@yuni_write_bytes"), "Should pass the buffer and length:\n{}", ir);
+    assert!(ir.contains("@yuni_read_bytes"), "Should read the file through the runtime:\n{}", ir);
+
+    // NULの後ろの要素も長さに含まれる
+    assert_eq!(run_i64(source, "checksum", &[]), 3255);
+}
+
+#[test]
+fn test_destructuring_let_codegen() {
+    // タプルの値はextractvalue、構造体の参照はGEPとloadで要素を取り出し、変数ごとにスタック領域を確保する
+    let source = r#"
+    package main
+
+    type Point struct {
+        x: i32,
+        y: i32,
+    }
+
+    fn divmod(a: i32, b: i32): (i32, i32) {
+        return (a / b, a % b);
+    }
+
+    fn sum(p: &Point): i32 {
+        let Point { x, y } = p;
+        return x + y;
+    }
+
+    fn main(): i32 {
+        let (mut q, r) = divmod(17, 5);
+        q = q + r;
+        let (a, (b, _)) = (1, (2.5, 3));
+        let p = Point { x: a, y: q };
+        let total = sum(&p);
+        return total;
+    }
+    "#;
+
+    let ir = assert_compile_success(source, "destructuring");
+
+    let main = function_ir(&ir, "main");
+    assert!(main.contains("%q.addr = alloca i32"), "{}", main);
+    assert!(main.contains("%q = extractvalue"), "{}", main);
+    assert!(main.contains("%r = extractvalue"), "{}", main);
+    assert!(main.contains("%elem1 = extractvalue"), "{}", main);
+    assert!(main.contains("%b = extractvalue { double, i32 } %elem1, 0"), "{}", main);
+    assert!(main.contains("%b.addr = alloca double"), "{}", main);
+
+    let sum = function_ir(&ir, "sum");
+    assert!(sum.contains("%x.ptr = getelementptr"), "{}", sum);
+    assert!(sum.contains("%x = load i32, ptr %x.ptr"), "{}", sum);
+    assert!(sum.contains("%y = load i32, ptr %y.ptr"), "{}", sum);
+
+    assert_eq!(run_i32(source, "main", &[]), 6);
+}
+
+#[test]
+fn test_hashmap_index_codegen() {
+    // 文字列のキーは内容で比較するHashMapを作り、読み出しはキーがなければパニックし、代入は挿入する
+    let source = r#"
+    package main
+
+    fn main(): i32 {
+        let mut scores = HashMap<String, i32> { "alice": 1 };
+        scores["alice"] = scores["alice"] + 1;
+        let mut groups = HashMap<String, Vec<i32> > { "a": Vec<i32>[] };
+        groups["a"].push(2);
+        return scores["alice"] * 10 + groups["a"][0];
+    }
+    "#;
+
+    let result = compile_to_ir(source, "test_hashmap_index");
+    assert!(result.is_ok(), "Compilation should succeed: {:?}", result.unwrap_err());
+    let ir = result.unwrap();
+    assert_valid_ir(&ir);
+
+    assert!(ir.contains("@yuni_hashmap_new_str(i64 4)"), "String keys should be compared by content:\n{}", ir);
+    assert!(ir.contains("@yuni_hashmap_get"), "Index reads should look up the value:\n{}", ir);
+    assert!(ir.contains("map.missing:") && ir.contains("@yuni_panic_missing_key"), "Missing keys should panic:\n{}", ir);
+    assert!(ir.matches("@yuni_hashmap_insert(").count() >= 3, "Index writes should insert:\n{}", ir);
+    assert!(ir.contains("@yuni_vec_push"), "push should mutate the Vec stored in the map:\n{}", ir);
+
+    // 文字列のキーを別の文字列リテラルで引いても同じ値になる
+    assert_eq!(run_i32(source, "main", &[]), 22);
+}
+
+#[test]
+fn test_vec_and_string_operations_execute() {
+    // Vec・文字列のランタイム関数をJITから呼び出して実行する
+    let source = r#"
+    package main
+
+    fn vec_total(): i64 {
+        let mut values = Vec<i32>[5, 3, 8];
+        values.push(1);
+        let mut total: i64 = values.len() * 100;
+        for let mut i: i64 = 0; i < values.len(); i = i + 1 {
+            total = total + values[i] as i64;
+        }
+        return total;
+    }
+
+    fn string_length(): i64 {
+        let greeting = "Hello, " + "World";
+        if greeting == "Hello, World" {
+            return greeting.len();
+        }
+        return -1;
+    }
+    "#;
+
+    with_jit(source, |engine| {
+        assert_eq!(call::<i64, i64>(engine, "vec_total", &[]), 417);
+        assert_eq!(call::<i64, i64>(engine, "string_length", &[]), 12);
+    });
+}
+
+#[test]
+fn test_vec_index_assignment_and_resize_execute() {
+    // 範囲内の代入は要素を書き換え、resizeで伸ばした要素は埋める値で初期化される
+    let source = r#"
+    package main
+
+    fn resized_total(): i64 {
+        let mut values: Vec<i64> = [1, 2, 3];
+        values[0] = 10;
+        values.resize(6, 7);
+        values[5] = 100;
+        let mut total: i64 = 0;
+        for let mut i: i64 = 0; i < values.len(); i = i + 1 {
+            total = total + values[i];
+        }
+        values.resize(2, 0);
+        return total * 10 + values.len();
+    }
+    "#;
+
+    with_jit(source, |engine| {
+        // (10 + 2 + 3 + 7 + 7 + 100) * 10 + 2
+        assert_eq!(call::<i64, i64>(engine, "resized_total", &[]), 1292);
+    });
+}
+
+#[test]
+fn test_split_csv_line_and_sum_fields_execute() {
+    // CSV風の行を分割し、数値に変換できた欄を合計する
+    let source = r#"
+    package main
+
+    fn sum_fields(line: str): i64 {
+        let fields = line.split(",");
+        let mut total: i64 = 0;
+        for let mut i: i64 = 0; i < fields.len(); i = i + 1 {
+            let (ok, value) = parse_int(fields[i].trim());
+            if ok {
+                total = total + value;
+            }
+        }
+        return total;
+    }
+
+    fn csv_total(): i64 {
+        let line = "-- 10, 20,,x, -3 ";
+        if !line.starts_with("--") {
+            return -1;
+        }
+        let start = line.find(" ");
+        if start != 2 || line.find(";") != -1 {
+            return -2;
+        }
+        let count = line.split(",").len();
+        return sum_fields(line) + sum_fields("1,2,3") * 100 + count * 1000;
+    }
+    "#;
+
+    with_jit(source, |engine| {
+        // 先頭の欄は "-- 10" のため変換できず、(20 - 3) + 600 + 5000
+        assert_eq!(call::<i64, i64>(engine, "csv_total", &[]), 5617);
+    });
+}
+
+#[test]
+fn test_anonymous_struct_literals_execute() {
+    // 構造体名のないリテラルは戻り値・引数・型注釈の型の構造体になり、`{ x, y }` は同名の変数で初期化する
+    let source = r#"
+    package main
+
+    struct Point {
+        x: i64,
+        y: i64,
+    }
+
+    fn make(x: i64, y: i64): Point {
+        return { x, y };
+    }
+
+    fn weigh(p: Point): i64 {
+        return p.x * 10 + p.y;
+    }
+
+    fn total(): i64 {
+        let x: i64 = 4;
+        let y: i64 = 5;
+        let shorthand = Point { y, x };
+        let annotated: Point = { x: 6, y: 7 };
+        return weigh(make(1, 2)) + weigh({ x: 2, y: 3 }) * 100 + weigh(shorthand) * 10000 + weigh(annotated) * 1000000;
+    }
+    "#;
+
+    with_jit(source, |engine| {
+        assert_eq!(call::<i64, i64>(engine, "total", &[]), 67_452_312);
+    });
+}
@@ -0,0 +1,16 @@
+define i64 @wide(i64 %x.arg) #0 {
+entry:
+  %x.addr = alloca i64, align 8
+  store i64 %x.arg, ptr %x.addr, align 8
+  %x = load i64, ptr %x.addr, align 8
+  ret i64 %x
+}
+
+define void @main() #0 {
+entry:
+  %y.addr = alloca i64, align 8
+  call void @yuni_runtime_init(i64 0)
+  %call_result = call i64 @wide(i64 3000000000)
+  store i64 %call_result, ptr %y.addr, align 8
+  ret void
+}
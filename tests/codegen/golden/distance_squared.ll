@@ -0,0 +1,29 @@
+define double @distance_squared(%Point %p1.arg, %Point %p2.arg) #0 {
+entry:
+  %dy.addr = alloca double, align 8
+  %dx.addr = alloca double, align 8
+  %p2.addr = alloca %Point, align 8
+  %p1.addr = alloca %Point, align 8
+  store %Point %p1.arg, ptr %p1.addr, align 8
+  store %Point %p2.arg, ptr %p2.addr, align 8
+  %p1 = load %Point, ptr %p1.addr, align 8
+  %p1.x = extractvalue %Point %p1, 0
+  %p2 = load %Point, ptr %p2.addr, align 8
+  %p2.x = extractvalue %Point %p2, 0
+  %fsub = fsub double %p1.x, %p2.x
+  store double %fsub, ptr %dx.addr, align 8
+  %p11 = load %Point, ptr %p1.addr, align 8
+  %p1.y = extractvalue %Point %p11, 1
+  %p22 = load %Point, ptr %p2.addr, align 8
+  %p2.y = extractvalue %Point %p22, 1
+  %fsub3 = fsub double %p1.y, %p2.y
+  store double %fsub3, ptr %dy.addr, align 8
+  %dx = load double, ptr %dx.addr, align 8
+  %dx4 = load double, ptr %dx.addr, align 8
+  %fmul = fmul double %dx, %dx4
+  %dy = load double, ptr %dy.addr, align 8
+  %dy5 = load double, ptr %dy.addr, align 8
+  %fmul6 = fmul double %dy, %dy5
+  %fadd = fadd double %fmul, %fmul6
+  ret double %fadd
+}
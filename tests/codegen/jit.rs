@@ -0,0 +1,96 @@
+//! JITで関数を実行するコード生成テストの共通モジュール
+//!
+//! `compile_to_ir` と同じ手順で生成したモジュールをLLVMの実行エンジンでコンパイルし、
+//! 関数を呼び出して戻り値を検証する。llcやリンカを使わないため、ツールチェーンのない環境でも実行できる。
+//!
+//! コンパイルしたプログラムが呼び出すランタイム関数は、REPLと同じ `runtime_symbol` で
+//! テストのプロセスにリンクされた `yunilang::runtime` の実装に対応付ける。`malloc` などのCの標準関数は
+//! プロセスから解決される。
+
+use super::*;
+
+use std::ffi::{c_char, CStr};
+
+use yunilang::repl::runtime_symbol;
+
+use inkwell::execution_engine::{ExecutionEngine, JitFunction, UnsafeFunctionPointer};
+use inkwell::OptimizationLevel;
+
+/// ソースコードをコンパイルし、JITの実行エンジンを `f` に渡すヘルパー関数
+///
+/// 1つのモジュールの複数の関数を呼び出す場合に使う。
+pub fn with_jit<R>(source: &str, f: impl FnOnce(&ExecutionEngine<'_>) -> R) -> R {
+    let tokens = Lexer::new(source).collect_tokens();
+    let ast = Parser::new(tokens).parse().expect("Parsing should succeed");
+    let mut analyzer = SemanticAnalyzer::new();
+    analyzer.analyze(&ast).expect("Analysis should succeed");
+
+    let context = Context::create();
+    let mut codegen = CodeGenerator::new(&context, "jit");
+    codegen.compile_program_with_types(&ast, analyzer.type_table).expect("Compilation should succeed");
+    codegen.get_module().verify().unwrap_or_else(|e| {
+        panic!("Module verification failed: {}\n{}", e, codegen.get_module().print_to_string())
+    });
+
+    // 実行エンジンはモジュールを所有するため、コード生成器のモジュールの複製を渡す
+    let module = codegen.get_module().clone();
+    let engine = module
+        .create_jit_execution_engine(OptimizationLevel::None)
+        .unwrap_or_else(|e| panic!("Failed to create the JIT execution engine: {}", e));
+    for function in module.get_functions().filter(|function| function.count_basic_blocks() == 0) {
+        if let Some(address) = runtime_symbol(&function.get_name().to_string_lossy()) {
+            engine.add_global_mapping(&function, address);
+        }
+    }
+    f(&engine)
+}
+
+/// JITでコンパイルした関数 `function` を呼び出すヘルパー関数
+///
+/// 引数はすべて `T` 型で3つまでとし、戻り値を `R` 型として受け取る。
+/// 型の異なる引数を渡す場合は、ソースコードに引数なしで呼び出せる関数を用意する。
+pub fn call<T: Copy, R>(engine: &ExecutionEngine<'_>, function: &str, args: &[T]) -> R {
+    // SAFETY: 呼び出し側がソースコードの関数の仮引数と戻り値の型を `T`・`R` と一致させる
+    unsafe {
+        match *args {
+            [] => lookup::<unsafe extern "C" fn() -> R>(engine, function).call(),
+            [a] => lookup::<unsafe extern "C" fn(T) -> R>(engine, function).call(a),
+            [a, b] => lookup::<unsafe extern "C" fn(T, T) -> R>(engine, function).call(a, b),
+            [a, b, c] => lookup::<unsafe extern "C" fn(T, T, T) -> R>(engine, function).call(a, b, c),
+            _ => panic!("Too many arguments for {}: {}", function, args.len()),
+        }
+    }
+}
+
+/// JITでコンパイルした引数のない関数 `function` を呼び出し、戻り値の文字列を取り出すヘルパー関数
+pub fn call_string(engine: &ExecutionEngine<'_>, function: &str) -> String {
+    let text: *const c_char = call::<i64, _>(engine, function, &[]);
+    assert!(!text.is_null(), "{} returned a null string", function);
+    // SAFETY: Yuniの文字列はnull終端のC文字列として返される
+    unsafe { CStr::from_ptr(text) }.to_string_lossy().into_owned()
+}
+
+/// ソースコードをコンパイルし、i32を返す関数を実行するヘルパー関数
+pub fn run_i32(source: &str, function: &str, args: &[i32]) -> i32 {
+    with_jit(source, |engine| call(engine, function, args))
+}
+
+/// ソースコードをコンパイルし、i64を返す関数を実行するヘルパー関数
+pub fn run_i64(source: &str, function: &str, args: &[i64]) -> i64 {
+    with_jit(source, |engine| call(engine, function, args))
+}
+
+/// ソースコードをコンパイルし、f64を返す関数を実行するヘルパー関数
+pub fn run_f64(source: &str, function: &str, args: &[f64]) -> f64 {
+    with_jit(source, |engine| call(engine, function, args))
+}
+
+/// JITでコンパイルした関数 `function` を型 `F` の関数ポインタとして取り出す
+///
+/// # Safety
+/// `F` は関数 `function` の型と一致する必要がある。
+unsafe fn lookup<'ctx, F: UnsafeFunctionPointer>(engine: &ExecutionEngine<'ctx>, function: &str) -> JitFunction<'ctx, F> {
+    engine
+        .get_function(function)
+        .unwrap_or_else(|e| panic!("Function {} should be compiled: {:?}", function, e))
+}
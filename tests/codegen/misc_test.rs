@@ -26,6 +26,94 @@ fn test_string_operations_codegen() {
     assert!(ir.contains("World"), "Should contain string literal 'World'");
 }
 
+#[test]
+fn test_template_string_interpolation_execute() {
+    // 補間できる型ごとに、インタプリタと同じ書式で文字列にする
+    let source = r#"
+    package main
+
+    type Color enum {
+        Red,
+        Green,
+        Blue
+    }
+
+    type Count u32
+
+    fn signed_integers(): String {
+        let a: i8 = -128;
+        let b: i16 = -300;
+        let c: i32 = -70000;
+        let d: i64 = -5000000000;
+        return `${a} ${b} ${c} ${d}`;
+    }
+
+    fn unsigned_integers(): String {
+        let a: u8 = 255;
+        let b: u16 = 65535;
+        let raw: u32 = 4294967295;
+        let c: Count = raw;
+        let d: u64 = 18446744073709551615;
+        return `${a} ${b} ${c} ${d}`;
+    }
+
+    fn floats(): String {
+        let x: f32 = 0.1;
+        let y: f64 = 2.5;
+        return `${x} ${y}`;
+    }
+
+    fn strings_and_bools(): String {
+        let name = "yuni";
+        let name_ref = &name;
+        let ok = 3 > 2;
+        return `${ok} ${name} ${name_ref} ${!ok}`;
+    }
+
+    fn enums(): String {
+        let color = Color::Blue;
+        let colors = Vec<Color>[Color::Red, Color::Green];
+        return `${color} ${colors[0]} ${colors[1]}`;
+    }
+
+    fn short_template(): String {
+        let max: u64 = 18446744073709551615;
+        return `max=${max}`;
+    }
+
+    fn short_enum_template(): String {
+        let color = Color::Green;
+        return `${color}`;
+    }
+
+    fn unsigned_to_string(): String {
+        let value: u32 = 4294967295;
+        return value.to_string();
+    }
+
+    fn builder_append(): String {
+        let sb = StringBuilder{};
+        let max: u64 = 18446744073709551615;
+        sb.append(Color::Red);
+        sb.append(" ");
+        sb.append(max);
+        return sb.to_string();
+    }
+    "#;
+
+    with_jit(source, |engine| {
+        assert_eq!(call_string(engine, "signed_integers"), "-128 -300 -70000 -5000000000");
+        assert_eq!(call_string(engine, "unsigned_integers"), "255 65535 4294967295 18446744073709551615");
+        assert_eq!(call_string(engine, "floats"), "0.1 2.5");
+        assert_eq!(call_string(engine, "strings_and_bools"), "true yuni yuni false");
+        assert_eq!(call_string(engine, "enums"), "Color::Blue Color::Red Color::Green");
+        assert_eq!(call_string(engine, "short_template"), "max=18446744073709551615");
+        assert_eq!(call_string(engine, "short_enum_template"), "Color::Green");
+        assert_eq!(call_string(engine, "unsigned_to_string"), "4294967295");
+        assert_eq!(call_string(engine, "builder_append"), "Color::Red 18446744073709551615");
+    });
+}
+
 #[test]
 fn test_boolean_operations_codegen() {
     // ブール演算のコード生成テスト
@@ -99,4 +187,252 @@ fn test_error_handling_codegen() {
     for (i, source) in invalid_sources.iter().enumerate() {
         assert_compile_error(source, &format!("error_test_{}", i));
     }
-}
\ No newline at end of file
+}
+/// IRの関数定義に付いた属性グループの中身（`define ... @name(...) #0` の `#0` の内容）
+fn function_attributes(ir: &str, name: &str) -> String {
+    let define = ir
+        .lines()
+        .find(|line| line.starts_with("define") && line.contains(&format!("@{}(", name)))
+        .unwrap_or_else(|| panic!("IR should define {}: {}", name, ir));
+    let Some(group) = define.split_whitespace().find(|word| word.starts_with('#')) else {
+        return String::new();
+    };
+    let prefix = format!("attributes {} = ", group);
+    ir.lines()
+        .find_map(|line| line.strip_prefix(&prefix))
+        .unwrap_or_else(|| panic!("IR should contain attribute group {}: {}", group, ir))
+        .to_string()
+}
+
+#[test]
+fn test_inline_attributes() {
+    // `inline` は inlinehint、`inline(always)` は alwaysinline になる
+    let source = r#"
+    package main
+
+    type Point struct {
+        x: i32,
+    }
+
+    inline fn double(x: i32): i32 {
+        return x * 2;
+    }
+
+    inline(always) fn square(x: i32): i32 {
+        return x * x;
+    }
+
+    fn plain(x: i32): i32 {
+        return x + 1;
+    }
+
+    impl Point {
+        inline(always) fn get_x(&self): i32 {
+            return self.x;
+        }
+    }
+
+    fn main() {
+        let p = Point { x: 3 };
+        let total = double(1) + square(2) + plain(3) + p.get_x();
+    }
+    "#;
+
+    let ir = assert_compile_success(source, "inline_attributes");
+    assert!(function_attributes(&ir, "double").contains("inlinehint"), "{}", ir);
+    assert!(function_attributes(&ir, "square").contains("alwaysinline"), "{}", ir);
+    assert!(function_attributes(&ir, "Point_get_x").contains("alwaysinline"), "{}", ir);
+    let plain = function_attributes(&ir, "plain");
+    assert!(!plain.contains("inlinehint") && !plain.contains("alwaysinline"), "{}", ir);
+}
+
+#[test]
+fn test_small_monomorphized_functions_get_inline_hint() {
+    // 単相化で生成した小さい関数には指定がなくても inlinehint が付く
+    let source = r#"
+    package main
+
+    fn identity<T>(x: T): T {
+        return x;
+    }
+
+    fn busy<T>(x: T): T {
+        let a = x;
+        let b = a;
+        let c = b;
+        let d = c;
+        let e = d;
+        let f = e;
+        let g = f;
+        let h = g;
+        return h;
+    }
+
+    fn main() {
+        let a = identity(1);
+        let b = busy(2);
+    }
+    "#;
+
+    let tokens = Lexer::new(source).collect_tokens();
+    let ast = Parser::new(tokens).parse().expect("Parsing should succeed");
+    let mut analyzer = SemanticAnalyzer::new();
+    analyzer.analyze(&ast).expect("Analysis should succeed");
+    let (ast, source_map) = yunilang::analyzer::monomorphize_program_with_types(ast, analyzer.type_table)
+        .expect("Monomorphization should succeed");
+
+    let context = Context::create();
+    let mut codegen = CodeGenerator::new(&context, "small_instances").with_source_map(source_map);
+    codegen.compile_program(&ast).expect("Compilation should succeed");
+    let ir = codegen.get_module().print_to_string().to_string();
+
+    assert!(function_attributes(&ir, "identity_i32").contains("inlinehint"), "{}", ir);
+    assert!(!function_attributes(&ir, "busy_i32").contains("inlinehint"), "{}", ir);
+}
+
+#[test]
+fn test_extern_function_declarations() {
+    // 外部関数はCのシンボル名で宣言し、ランタイムが宣言済みの関数はその宣言を使う
+    let source = r#"
+    package main
+
+    extern fn cbrt(x: f64) -> f64;
+    extern "C" fn getenv(name: String) -> String;
+    extern fn strlen(s: String): u64;
+
+    fn main() {
+        let root = cbrt(27.0);
+        let home = getenv("HOME");
+        let len = strlen("yuni");
+    }
+    "#;
+
+    let ir = assert_compile_success(source, "extern_functions");
+    assert!(ir.contains("declare double @cbrt(double)"), "{}", ir);
+    assert!(ir.contains("declare ptr @getenv(ptr)"), "{}", ir);
+    assert_eq!(ir.matches("declare i64 @strlen(ptr)").count(), 1, "{}", ir);
+
+    let main = function_ir(&ir, "main");
+    assert!(main.contains("call double @cbrt(double 2.700000e+01)"), "{}", main);
+    assert!(main.contains("call i64 @strlen("), "{}", main);
+    // getenvが返したNULLは空文字列に置き換える
+    assert!(main.contains("icmp eq ptr %call_result"), "{}", main);
+    assert!(main.contains("select i1 %is_null"), "{}", main);
+}
+
+#[test]
+fn test_extern_function_conflicting_runtime_declaration() {
+    // ランタイムの宣言と型が異なる外部関数はコンパイルできない
+    let source = r#"
+    package main
+
+    extern fn strlen(s: String): i32;
+
+    fn main() {
+        let len = strlen("yuni");
+    }
+    "#;
+
+    assert_compile_error(source, "extern_conflict");
+}
+
+#[test]
+fn test_random_sequence_from_seed_execute() {
+    // 同じシードからはプラットフォームに関係なく同じ系列の乱数になる
+    let source = r#"
+    package main
+
+    fn draw(seed: u64): u64 {
+        random_seed(seed);
+        return random_u64();
+    }
+
+    fn sequence(): String {
+        random_seed(42);
+        let a = random_u64();
+        let b = random_u64();
+        let c = random_u64();
+        return `${a} ${b} ${c}`;
+    }
+
+    fn main() {
+    }
+    "#;
+
+    with_jit(source, |engine| {
+        assert_eq!(
+            call_string(engine, "sequence"),
+            "3580622183945639842 10378725325292465923 8967075514996744559"
+        );
+        // シードを設定し直すと系列の最初に戻る
+        assert_eq!(call::<u64, u64>(engine, "draw", &[42]), 3580622183945639842);
+        assert_eq!(call::<u64, u64>(engine, "draw", &[7]), 1507201545562260538);
+        assert_eq!(call::<u64, u64>(engine, "draw", &[42]), 3580622183945639842);
+    });
+}
+
+#[test]
+fn test_time_nanos_is_monotonic_across_sleep_execute() {
+    let source = r#"
+    package main
+
+    fn elapsed_across_sleep(ms: i64): i64 {
+        let start = time_nanos();
+        sleep_millis(ms);
+        let end = time_nanos();
+        return end - start;
+    }
+
+    fn unix_millis(): i64 {
+        return time_unix_millis();
+    }
+
+    fn main() {
+    }
+    "#;
+
+    with_jit(source, |engine| {
+        let elapsed = call::<i64, i64>(engine, "elapsed_across_sleep", &[5]);
+        assert!(elapsed >= 5_000_000, "{}", elapsed);
+        assert!(call::<i64, i64>(engine, "elapsed_across_sleep", &[0]) >= 0);
+        // 2020年以降の時刻
+        assert!(call::<i64, i64>(engine, "unix_millis", &[]) > 1_577_836_800_000);
+    });
+}
+
+#[test]
+fn test_local_functions_hoisted_with_unique_names() {
+    // 別々の関数で定義した同じ名前のローカル関数は、囲んでいる関数の名前を付けたトップレベルの関数になる
+    let source = r#"
+    package main
+
+    fn first(x: i32): i32 {
+        fn helper(x: i32): i32 {
+            x * 2
+        }
+        return helper(x);
+    }
+
+    fn second(x: i32): i32 {
+        fn helper(x: i32): i32 {
+            x * 3
+        }
+        return helper(x);
+    }
+
+    fn main() {
+    }
+    "#;
+
+    // LLVMは `::` を含む名前を引用符で囲む
+    let ir = assert_compile_success(source, "local_functions");
+    assert!(function_ir(&ir, "first").contains("call i32 @\"first::helper\"("), "{}", ir);
+    assert!(function_ir(&ir, "second").contains("call i32 @\"second::helper\"("), "{}", ir);
+    function_ir(&ir, "\"first::helper\"");
+    function_ir(&ir, "\"second::helper\"");
+
+    with_jit(source, |engine| {
+        assert_eq!(call::<i32, i32>(engine, "first", &[5]), 10);
+        assert_eq!(call::<i32, i32>(engine, "second", &[5]), 15);
+    });
+}
@@ -12,6 +12,9 @@ use inkwell::context::Context;
 use std::fs;
 use std::process::Command;
 
+mod jit;
+pub use jit::{call, call_string, run_f64, run_i32, run_i64, with_jit};
+
 /// ソースコードを完全にコンパイルしてLLVM IRを生成するヘルパー関数
 pub fn compile_to_ir(source: &str, module_name: &str) -> Result<String, Box<dyn std::error::Error>> {
     // 字句解析
@@ -29,7 +32,7 @@ pub fn compile_to_ir(source: &str, module_name: &str) -> Result<String, Box<dyn
     // コード生成
     let context = Context::create();
     let mut codegen = CodeGenerator::new(&context, module_name);
-    codegen.compile_program(&ast)?;
+    codegen.compile_program_with_types(&ast, analyzer.type_table)?;
     
     // LLVM IRを文字列として取得
     Ok(codegen.get_module().print_to_string().to_string())
@@ -47,6 +50,44 @@ pub fn assert_valid_ir(ir: &str) {
     assert!(ret_count >= define_count, "Each function should have at least one return");
 }
 
+/// IRから関数 `name` の定義（`define` から閉じ括弧まで）を取り出すヘルパー関数
+pub fn function_ir(ir: &str, name: &str) -> String {
+    let signature = format!("@{}(", name);
+    let lines = ir.lines().skip_while(|line| !(line.starts_with("define") && line.contains(&signature)));
+    let mut body = String::new();
+    for line in lines {
+        body.push_str(line);
+        body.push('\n');
+        if line == "}" {
+            return body;
+        }
+    }
+    panic!("Function {} should be defined:\n{}", name, ir);
+}
+
+/// 関数の定義をゴールデンファイル `tests/codegen/golden/<golden>.ll` と比較するヘルパー関数
+///
+/// 値の名前は関数ごとに決まるため、モジュールの他の部分に左右されずに比較できる。
+/// 環境変数 `UPDATE_GOLDEN=1` を設定すると、比較せずにゴールデンファイルを書き換える。
+pub fn assert_golden_ir(ir: &str, golden: &str, functions: &[&str]) {
+    let actual = functions
+        .iter()
+        .map(|name| function_ir(ir, name))
+        .collect::<Vec<_>>()
+        .join("\n");
+    let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/codegen/golden")
+        .join(format!("{}.ll", golden));
+
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        fs::write(&path, &actual).expect("Failed to write golden file");
+        return;
+    }
+    let expected = fs::read_to_string(&path)
+        .unwrap_or_else(|e| panic!("Failed to read {}: {}", path.display(), e));
+    assert_eq!(actual, expected, "IR differs from {} (UPDATE_GOLDEN=1 to update)", path.display());
+}
+
 /// コンパイルに成功することを確認するヘルパー関数
 pub fn assert_compile_success(source: &str, module_name: &str) -> String {
     compile_to_ir(source, module_name).expect("Compilation should succeed")
@@ -71,4 +112,6 @@ mod data_structures_test;
 #[cfg(test)]
 mod misc_test;
 #[cfg(test)]
-mod advanced_test;
\ No newline at end of file
+mod advanced_test;
+#[cfg(test)]
+mod naming_test;
\ No newline at end of file
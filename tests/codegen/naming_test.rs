@@ -0,0 +1,128 @@
+//! 生成するLLVM IRの値の名前のテスト
+
+use super::*;
+
+use inkwell::memory_buffer::MemoryBuffer;
+
+#[test]
+fn test_value_names_are_stable_per_function() {
+    // 他の関数に文字列リテラルを足しても、mainのIRと定数の名前は変わらない
+    let before = r#"
+    package main
+
+    fn greet() {
+        println("hello");
+    }
+
+    fn main() {
+        greet();
+        println("done");
+    }
+    "#;
+    let after = r#"
+    package main
+
+    fn greet() {
+        println("hello");
+        println("again");
+    }
+
+    fn main() {
+        greet();
+        println("done");
+    }
+    "#;
+
+    let before_ir = assert_compile_success(before, "names_before");
+    let after_ir = assert_compile_success(after, "names_after");
+    assert_eq!(function_ir(&before_ir, "main"), function_ir(&after_ir, "main"));
+
+    assert!(after_ir.contains("@greet.str = "), "Should name constants after the function:\n{}", after_ir);
+    assert!(after_ir.contains("@greet.str.1 = "), "Should number constants per function:\n{}", after_ir);
+    assert!(function_ir(&after_ir, "main").contains("@main.str"), "main should use its own constant:\n{}", after_ir);
+}
+
+#[test]
+fn test_field_loads_named_after_path() {
+    // フィールドの読み出しは変数からのパスで、参照を通した読み出しはアドレスも名前が付く
+    let source = r#"
+    package main
+
+    type Point struct {
+        x: f64,
+        y: f64,
+    }
+
+    type Segment struct {
+        from: Point,
+        to: Point,
+    }
+
+    fn dx(s: Segment): f64 {
+        return s.to.x - s.from.x;
+    }
+
+    fn get_x(p: &Point): f64 {
+        return p.x;
+    }
+
+    fn main() {
+        let s = Segment { from: Point { x: 0.0, y: 0.0 }, to: Point { x: 3.0, y: 4.0 } };
+        let d = dx(s);
+    }
+    "#;
+
+    let ir = assert_compile_success(source, "field_names");
+    let dx = function_ir(&ir, "dx");
+    assert!(dx.contains("%s.to = extractvalue"), "{}", dx);
+    assert!(dx.contains("%s.to.x = extractvalue"), "{}", dx);
+    assert!(dx.contains("%s.from.x = extractvalue"), "{}", dx);
+
+    let get_x = function_ir(&ir, "get_x");
+    assert!(get_x.contains("%p.arg"), "{}", get_x);
+    assert!(get_x.contains("%p.addr = alloca ptr"), "{}", get_x);
+    assert!(get_x.contains("%p.x.ptr = getelementptr"), "{}", get_x);
+    assert!(get_x.contains("%p.x = load double, ptr %p.x.ptr"), "{}", get_x);
+}
+
+#[test]
+fn test_strip_value_names() {
+    // --no-names では関数の中の値の名前を取り除き、グローバル変数と関数の名前は残す
+    let source = r#"
+    package main
+
+    fn add(a: i32, b: i32): i32 {
+        let sum = a + b;
+        return sum;
+    }
+
+    fn main() {
+        let total = add(1, 2);
+        if total > 2 {
+            println("big");
+        }
+    }
+    "#;
+
+    let tokens: Vec<_> = Lexer::new(source).collect_tokens();
+    let ast = Parser::new(tokens).parse().expect("Parsing should succeed");
+    let mut analyzer = SemanticAnalyzer::new();
+    analyzer.analyze(&ast).expect("Analysis should succeed");
+
+    let context = Context::create();
+    let mut codegen = CodeGenerator::new(&context, "no_names");
+    codegen.compile_program_with_types(&ast, analyzer.type_table).expect("Codegen should succeed");
+    codegen.strip_value_names();
+    let ir = codegen.get_module().print_to_string().to_string();
+
+    for name in ["%a", "%b", "%sum", "%total", ".addr", "%add", "%call_result", "if.then", "entry:"] {
+        assert!(!ir.contains(name), "{} should be stripped:\n{}", name, ir);
+    }
+    assert!(ir.contains("define i32 @add(i32 %0, i32 %1)"), "Arguments should be numbered:\n{}", ir);
+    assert!(ir.contains("@main.str"), "Global names should be kept:\n{}", ir);
+
+    // 名前のないIRもそのまま読み込める
+    let buffer = MemoryBuffer::create_from_memory_range_copy(ir.as_bytes(), "no_names.ll");
+    let module = context.create_module_from_ir(buffer).expect("Stripped IR should parse");
+    assert!(module.verify().is_ok(), "Stripped IR should verify");
+}
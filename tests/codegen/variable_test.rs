@@ -2,6 +2,9 @@
 
 use super::*;
 
+use inkwell::OptimizationLevel;
+use yunilang::codegen::CodegenOptions;
+
 #[test]
 fn test_variable_operations_codegen() {
     // 変数操作のコード生成テスト
@@ -128,4 +131,111 @@ fn test_reference_expressions() {
     assert!(ir.contains("alloca"), "Should contain stack allocation");
     assert!(ir.contains("load"), "Should contain load for dereferencing");
     assert!(ir.contains("store"), "Should contain store for assignment through reference");
-}
\ No newline at end of file
+}
+
+/// 参照を取る・`mut` で再代入するパラメータを含むプログラム
+const PARAMETERS: &str = r#"
+    package main
+
+    fn read(r: &i64): i64 {
+        return *r;
+    }
+
+    fn bump(r: &mut i64) {
+        *r = *r + 1;
+    }
+
+    fn forward(n: i64): i64 {
+        return read(&n) * 10;
+    }
+
+    fn countdown(mut n: i64): i64 {
+        let mut steps: i64 = 0;
+        while n > 0 {
+            n = n - 3;
+            steps = steps + 1;
+        }
+        return steps * 100 + n;
+    }
+
+    fn settle(mut n: i64): i64 {
+        bump(&mut n);
+        bump(&mut n);
+        return n;
+    }
+
+    fn sum(a: i64, b: i64): i64 {
+        return a + b;
+    }
+    "#;
+
+#[test]
+fn test_parameters_are_addressable_and_mutable_execute() {
+    // パラメータはローカル変数と同じスタック領域に置かれ、参照を渡したり `mut` で再代入したりできる
+    with_jit(PARAMETERS, |engine| {
+        assert_eq!(call::<i64, i64>(engine, "forward", &[7]), 70);
+        assert_eq!(call::<i64, i64>(engine, "settle", &[5]), 7);
+        // 10 → 7 → 4 → 1 → -2 の4回で抜ける
+        assert_eq!(call::<i64, i64>(engine, "countdown", &[10]), 398);
+    });
+}
+
+#[test]
+fn test_parameter_allocas_are_promoted_when_optimizing() {
+    // -O0 ではパラメータごとに名前付きのallocaを作り、-O2 ではアドレスを取らないものが取り除かれる
+    let compile = |opt_level: OptimizationLevel| {
+        let tokens = Lexer::new(PARAMETERS).collect_tokens();
+        let ast = Parser::new(tokens).parse().expect("Parsing should succeed");
+        let mut analyzer = SemanticAnalyzer::new();
+        analyzer.analyze(&ast).expect("Analysis should succeed");
+        let context = Context::create();
+        let options = CodegenOptions::new("parameters").with_opt_level(opt_level);
+        let mut codegen = CodeGenerator::new(&context, options);
+        codegen.compile_program_with_types(&ast, analyzer.type_table).expect("Compilation should succeed");
+        codegen.run_inline_passes().expect("Optimization passes should succeed");
+        codegen.get_module().print_to_string().to_string()
+    };
+
+    let debug = compile(OptimizationLevel::None);
+    let sum = function_ir(&debug, "sum");
+    assert!(sum.contains("%a.addr = alloca i64"), "{}", sum);
+    assert!(sum.contains("store i64 %a.arg, ptr %a.addr"), "{}", sum);
+    let countdown = function_ir(&debug, "countdown");
+    assert!(countdown.contains("%n.addr = alloca i64"), "{}", countdown);
+
+    let optimized = compile(OptimizationLevel::Default);
+    for name in ["sum", "countdown"] {
+        let body = function_ir(&optimized, name);
+        assert!(!body.contains("alloca"), "{} should not keep allocas at -O2: {}", name, body);
+    }
+}
+
+#[test]
+fn test_underscore_parameters_and_bindings_execute() {
+    // `_` のパラメータは引数を受け取るだけで、スタック領域を作らない
+    let source = r#"
+    package main
+
+    fn middle(_: i64, value: i64, _: i64): i64 {
+        return value;
+    }
+
+    fn discard(): i64 {
+        let _ = middle(1, 2, 3);
+        let (a, _, c) = (4, 5, 6);
+        let kind = match a {
+            0 => 0,
+            _ => 100,
+        };
+        return middle(7, 8, 9) * 1000 + kind + a * 10 + c;
+    }
+    "#;
+
+    with_jit(source, |engine| {
+        assert_eq!(call::<i64, i64>(engine, "discard", &[]), 8146);
+    });
+    let ir = compile_to_ir(source, "underscore").expect("Compilation should succeed");
+    let middle = function_ir(&ir, "middle");
+    assert!(middle.contains("%value.addr = alloca i64"), "{}", middle);
+    assert!(!middle.contains("%_.addr"), "{}", middle);
+}
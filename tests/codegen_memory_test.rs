@@ -0,0 +1,92 @@
+//! コード生成のメモリ使用量のテスト
+//!
+//! 関数が5千個あるファイルをコンパイルし、コード生成の後に残るメモリ（Rust側で確保したもの）が
+//! 関数の数に対して一定の範囲に収まること、関数ごとの作業用の状態を関数のコンパイルを
+//! 終えるたびに解放していることを確認する。
+//!
+//! ```sh
+//! cargo test --release --test codegen_memory_test -- --nocapture
+//! ```
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::fmt::Write;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use inkwell::context::Context;
+use yunilang::analyzer::SemanticAnalyzer;
+use yunilang::codegen::CodeGenerator;
+use yunilang::lexer::Lexer;
+use yunilang::parser::Parser;
+
+/// 確保中のバイト数を数えるアロケータ
+struct CountingAllocator;
+
+static LIVE_BYTES: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        LIVE_BYTES.fetch_add(layout.size(), Ordering::Relaxed);
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        LIVE_BYTES.fetch_sub(layout.size(), Ordering::Relaxed);
+        unsafe { System.dealloc(ptr, layout) }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        LIVE_BYTES.fetch_add(new_size, Ordering::Relaxed);
+        LIVE_BYTES.fetch_sub(layout.size(), Ordering::Relaxed);
+        unsafe { System.realloc(ptr, layout, new_size) }
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+const FUNCTIONS: usize = 5_000;
+
+/// 変数・ループ・条件分岐・文字列リテラルを使う関数を並べたプログラム
+fn generate_program() -> String {
+    let mut source = String::from("package main\n\n");
+    for i in 0..FUNCTIONS {
+        writeln!(
+            source,
+            "fn func{i}(n: i64): i64 {{\n    let a = n + {i};\n    let mut total: i64 = 0;\n    let mut j: i64 = 0;\n    while j < 3 {{\n        let step = a * j;\n        total = total + step;\n        j = j + 1;\n    }}\n    if total > 100 {{\n        println(\"large\");\n    }}\n    return total;\n}}\n",
+            i = i,
+        )
+        .unwrap();
+    }
+    source.push_str("fn main() {\n}\n");
+    source
+}
+
+#[test]
+fn test_codegen_memory_per_function() {
+    let source = generate_program();
+    let tokens = Lexer::new(&source).collect_tokens();
+    let program = Parser::new(tokens).parse().expect("Parsing should succeed");
+    let mut analyzer = SemanticAnalyzer::new();
+    analyzer.analyze(&program).expect("Analysis should succeed");
+
+    let context = Context::create();
+    let mut codegen = CodeGenerator::new(&context, "memory");
+    let before = LIVE_BYTES.load(Ordering::Relaxed);
+    codegen
+        .compile_program_with_types(&program, analyzer.type_table)
+        .expect("Codegen should succeed");
+    let retained = LIVE_BYTES.load(Ordering::Relaxed).saturating_sub(before);
+
+    let stats = codegen.stats();
+    let per_function = retained / FUNCTIONS;
+    println!("{}: {} bytes retained ({} per function)", stats, retained, per_function);
+
+    // 関数の数に比例して残るのは関数の宣言（名前とシグネチャ）だけ
+    assert!(per_function < 1024, "too much memory retained per function: {}", per_function);
+
+    // 作業用の状態の最大値は1つの関数の大きさで決まり、関数の数に比例しない
+    assert_eq!(stats.functions, FUNCTIONS + 1);
+    assert!(stats.peak_variables < 10, "{}", stats);
+    assert!(stats.peak_scopes < 10, "{}", stats);
+    assert_eq!(stats.retained, 0, "{}", stats);
+}
@@ -11,6 +11,11 @@
 //! - data_structures_test: データ構造（構造体、配列、タプル）
 //! - misc_test: その他の機能（文字列、ブール演算、最適化）
 //! - advanced_test: 高度な機能（複雑なプログラム、実行可能ファイル生成）
+//! - naming_test: 生成するIRの値の名前
+//!
+//! 一部のテストは期待するIRを `codegen/golden` に置いて比較する（`UPDATE_GOLDEN=1` で更新）。
+//! 算術演算・制御フロー・データ構造のテストは、生成したモジュールをJITで実行して関数の戻り値を検証する
+//! （`codegen/jit.rs`）。
 
 #[cfg(test)]
 mod codegen;
\ No newline at end of file
@@ -0,0 +1,118 @@
+//! インポートの依存関係グラフ（`compile --emit=deps`）のテスト
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use serde_json::Value;
+use yunilang::deps::{resolve_import, DependencyGraph, ImportTarget};
+
+const FIXTURE_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/deps");
+
+fn fixture(path: &str) -> PathBuf {
+    Path::new(FIXTURE_DIR).join(path)
+}
+
+/// フィクスチャのディレクトリで `compile --emit=deps` を実行し、標準出力と標準エラーを得る
+fn emit_deps(args: &[&str]) -> (String, String) {
+    let output = Command::new(env!("CARGO_BIN_EXE_yunilang"))
+        .args(["compile", "--emit=deps", "main.yuni"])
+        .args(args)
+        .current_dir(FIXTURE_DIR)
+        .output()
+        .expect("コンパイラの起動に失敗しました");
+    let stderr = String::from_utf8_lossy(&output.stderr).into_owned();
+    assert!(output.status.success(), "{}", stderr);
+    (String::from_utf8_lossy(&output.stdout).into_owned(), stderr)
+}
+
+#[test]
+fn test_resolve_import() {
+    let root = Path::new(FIXTURE_DIR);
+    assert_eq!(resolve_import(root, &[], "util"), ImportTarget::File(fixture("util.yuni")));
+    // `.` と `/` のどちらで区切っても同じファイルに解決する
+    assert_eq!(resolve_import(root, &[], "geometry.shapes"), ImportTarget::File(fixture("geometry/shapes.yuni")));
+    assert_eq!(resolve_import(root, &[], "geometry/shapes"), ImportTarget::File(fixture("geometry/shapes.yuni")));
+    assert_eq!(
+        resolve_import(root, &[], "missing"),
+        ImportTarget::Unresolved { candidate: fixture("missing.yuni") }
+    );
+    // 標準ライブラリはファイルに解決しない
+    assert_eq!(resolve_import(root, &[], "std.collections"), ImportTarget::Std);
+    assert_eq!(resolve_import(root, &[], "std/io/println"), ImportTarget::Std);
+}
+
+#[test]
+fn test_dependency_graph() {
+    let graph = DependencyGraph::build(&fixture("main.yuni"), &[]).expect("graph should be built");
+
+    assert_eq!(
+        graph.nodes,
+        vec![fixture("main.yuni"), fixture("util.yuni"), fixture("geometry/shapes.yuni")]
+    );
+    let edges: Vec<(&Path, &Path, &str)> = graph
+        .edges
+        .iter()
+        .map(|edge| (edge.from.as_path(), edge.to.as_path(), edge.import.as_str()))
+        .collect();
+    assert_eq!(
+        edges,
+        vec![
+            (fixture("main.yuni").as_path(), fixture("util.yuni").as_path(), "util"),
+            (fixture("util.yuni").as_path(), fixture("geometry/shapes.yuni").as_path(), "geometry.shapes"),
+            (fixture("geometry/shapes.yuni").as_path(), fixture("util.yuni").as_path(), "util"),
+        ]
+    );
+
+    assert_eq!(graph.unresolved.len(), 1);
+    assert_eq!(graph.unresolved[0].from, fixture("main.yuni"));
+    assert_eq!(graph.unresolved[0].import, "missing");
+
+    // 循環は報告するがグラフの作成は続ける
+    assert_eq!(
+        graph.cycles,
+        vec![vec![fixture("util.yuni"), fixture("geometry/shapes.yuni"), fixture("util.yuni")]]
+    );
+}
+
+#[test]
+fn test_makefile_escapes_special_characters() {
+    let graph = DependencyGraph {
+        entry: PathBuf::from("my app.yuni"),
+        nodes: vec![PathBuf::from("my app.yuni"), PathBuf::from("lib#1.yuni"), PathBuf::from("$x.yuni")],
+        edges: Vec::new(),
+        unresolved: Vec::new(),
+        cycles: Vec::new(),
+    };
+    assert_eq!(
+        graph.to_makefile(Path::new("my app.o")),
+        "my\\ app.o: my\\ app.yuni lib\\#1.yuni $$x.yuni\n"
+    );
+}
+
+#[test]
+fn test_emit_deps_makefile() {
+    let (stdout, stderr) = emit_deps(&[]);
+    let object = format!("main.{}", yunilang::toolchain::Target::host().object_extension());
+    assert_eq!(stdout, format!("{}: main.yuni util.yuni geometry/shapes.yuni\n", object));
+    assert!(stderr.contains("import cycle: util.yuni -> geometry/shapes.yuni -> util.yuni"), "{}", stderr);
+    assert!(stderr.contains("unresolved import \"missing\" in main.yuni"), "{}", stderr);
+}
+
+#[test]
+fn test_emit_deps_json() {
+    let (stdout, _) = emit_deps(&["--deps-format=json"]);
+    let graph: Value = serde_json::from_str(&stdout).expect("stdout should be a JSON document");
+
+    assert_eq!(graph["entry"], "main.yuni");
+    assert_eq!(graph["nodes"], serde_json::json!(["main.yuni", "util.yuni", "geometry/shapes.yuni"]));
+    assert_eq!(graph["edges"].as_array().unwrap().len(), 3);
+    assert_eq!(
+        graph["edges"][1],
+        serde_json::json!({"from": "util.yuni", "to": "geometry/shapes.yuni", "import": "geometry.shapes"})
+    );
+    assert_eq!(
+        graph["unresolved"],
+        serde_json::json!([{"from": "main.yuni", "import": "missing", "candidate": "missing.yuni"}])
+    );
+    assert_eq!(graph["cycles"], serde_json::json!([["util.yuni", "geometry/shapes.yuni", "util.yuni"]]));
+}
@@ -0,0 +1,31 @@
+//! ドキュメント生成（`yunilang doc`）のテスト
+
+use yunilang::ast::print_program;
+use yunilang::doc::render_markdown;
+use yunilang::lexer::Lexer;
+use yunilang::parser::Parser;
+use yunilang::Program;
+
+const FIXTURE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/doc.yuni");
+const EXPECTED: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/doc.md");
+
+fn parse(source: &str) -> Program {
+    let tokens = Lexer::new(source).collect_tokens();
+    Parser::new(tokens).parse().expect("source should parse")
+}
+
+#[test]
+fn test_render_markdown_matches_fixture() {
+    let source = std::fs::read_to_string(FIXTURE).unwrap();
+    let expected = std::fs::read_to_string(EXPECTED).unwrap();
+    assert_eq!(render_markdown(&parse(&source)), expected);
+}
+
+#[test]
+fn test_printed_program_keeps_doc_comments() {
+    let source = std::fs::read_to_string(FIXTURE).unwrap();
+    let program = parse(&source);
+    let reparsed = parse(&print_program(&program));
+    let docs = |program: &Program| program.items.iter().map(|item| item.docs().to_vec()).collect::<Vec<_>>();
+    assert_eq!(docs(&reparsed), docs(&program));
+}
@@ -0,0 +1,210 @@
+//! ツールチェーンの検査（`yunilang doctor`）のテスト
+//!
+//! ツールの実行ファイルの代わりに空のファイルを置き、`--version` の出力を偽の出力に差し替えて、
+//! 検査の合否とバージョンの読み取りを検証する。
+
+use std::collections::HashMap;
+use std::ffi::OsString;
+use std::fs;
+use std::path::Path;
+
+use tempfile::TempDir;
+use yunilang::doctor::{CheckStatus, CommandRunner, Doctor, Tool, Version};
+use yunilang::error::YuniError;
+use yunilang::toolchain::{Target, TargetEnv, TargetOs};
+
+/// インストール先の候補を持たないターゲット（PATHに置いたツールだけが見つかる）
+const OTHER: Target = Target { os: TargetOs::Other, env: TargetEnv::Gnu };
+const LINUX: Target = Target { os: TargetOs::Linux, env: TargetEnv::Gnu };
+const MACOS: Target = Target { os: TargetOs::MacOs, env: TargetEnv::Gnu };
+const WINDOWS_MSVC: Target = Target { os: TargetOs::Windows, env: TargetEnv::Msvc };
+
+const LLC_18: &str = "Ubuntu LLVM version 18.1.3\n  Optimized build.\n  Default target: x86_64-pc-linux-gnu\n";
+const CLANG_18: &str = "Ubuntu clang version 18.1.3 (1ubuntu1)\nTarget: x86_64-pc-linux-gnu\n";
+const RUSTC: &str = "rustc 1.80.0 (051478957 2024-07-21)\n";
+
+/// ツールのファイル名ごとに `--version` の出力を返す（登録していないツールは起動に失敗する）
+#[derive(Default)]
+struct FakeRunner {
+    outputs: HashMap<String, String>,
+}
+
+impl FakeRunner {
+    fn with(mut self, file_name: &str, output: &str) -> Self {
+        self.outputs.insert(file_name.to_string(), output.to_string());
+        self
+    }
+}
+
+impl CommandRunner for FakeRunner {
+    fn run(&self, program: &Path, args: &[&str]) -> Option<String> {
+        assert_eq!(args, ["--version"]);
+        let file_name = program.file_name()?.to_string_lossy();
+        self.outputs.get(file_name.as_ref()).cloned()
+    }
+}
+
+/// `tools` の空のファイルを置いたディレクトリと、そこだけを指すPATH
+fn bin_dir(tools: &[&str]) -> (TempDir, Option<OsString>) {
+    let dir = tempfile::tempdir().unwrap();
+    for tool in tools {
+        fs::write(dir.path().join(tool), "").unwrap();
+    }
+    let path_var = Some(dir.path().as_os_str().to_os_string());
+    (dir, path_var)
+}
+
+fn all_tools_runner() -> FakeRunner {
+    FakeRunner::default().with("llc", LLC_18).with("clang", CLANG_18).with("rustc", RUSTC)
+}
+
+#[test]
+fn test_version_parsing() {
+    assert_eq!(Version::parse_after(LLC_18, "LLVM version "), Some(Version { major: 18, minor: 1, patch: 3 }));
+    // 公式のビルドは `LLVM (http://llvm.org/):` の次の行にバージョンがある
+    let official = "LLVM (http://llvm.org/):\n  LLVM version 18.1.8git\n  Optimized build.\n";
+    assert_eq!(Version::parse_after(official, "LLVM version "), Some(Version { major: 18, minor: 1, patch: 8 }));
+    assert_eq!(
+        Version::parse_after("Apple clang version 15.0.0 (clang-1500.3.9.4)", "clang version "),
+        Some(Version { major: 15, minor: 0, patch: 0 })
+    );
+    assert_eq!(Version::parse_after(RUSTC, "rustc "), Some(Version { major: 1, minor: 80, patch: 0 }));
+    assert_eq!(Version::parse_after("Homebrew LLVM version 17", "LLVM version ").map(|v| v.to_string()), Some("17.0.0".to_string()));
+
+    assert_eq!(Version::parse_after("llc: unknown option", "LLVM version "), None);
+    assert_eq!(Version::parse_after("LLVM version unknown", "LLVM version "), None);
+}
+
+#[test]
+fn test_all_checks_pass() {
+    let (dir, path_var) = bin_dir(&["llc", "clang", "rustc"]);
+    let doctor = Doctor::new(OTHER, all_tools_runner()).with_path_var(path_var);
+
+    let checks = doctor.checks();
+    let names: Vec<_> = checks.iter().map(|check| check.name).collect();
+    assert_eq!(names, ["llc", "clang", "rustc", "temp dir"]);
+    assert!(checks.iter().all(|check| check.status == CheckStatus::Pass), "{:?}", checks);
+    let llc_path = dir.path().join("llc");
+    assert_eq!(checks[0].detail, format!("LLVM 18.1.3 ({})", llc_path.display()));
+    assert!(checks[2].detail.starts_with("rustc 1.80.0"), "{}", checks[2].detail);
+
+    assert!(doctor.require(&[Tool::Llc, Tool::Clang, Tool::Rustc]).is_ok());
+}
+
+#[test]
+fn test_missing_tools_fail_with_install_command() {
+    let (_dir, path_var) = bin_dir(&["clang"]);
+    let doctor = Doctor::new(OTHER, all_tools_runner()).with_path_var(path_var);
+
+    let llc = doctor.check_tool(Tool::Llc);
+    assert_eq!(llc.status, CheckStatus::Fail);
+    assert_eq!(llc.detail, "not found");
+    assert_eq!(llc.fix.as_deref(), Some("install LLVM 18 from https://releases.llvm.org"));
+    assert_eq!(doctor.check_tool(Tool::Clang).status, CheckStatus::Pass);
+    assert_eq!(doctor.check_tool(Tool::Rustc).detail, "not found in PATH");
+
+    // 必要なツールだけを検査し、失敗したツールと直し方をまとめてエラーにする
+    assert!(doctor.require(&[Tool::Clang]).is_ok());
+    match doctor.require(&[Tool::Llc, Tool::Clang, Tool::Rustc]) {
+        Err(YuniError::Other(message)) => {
+            assert!(message.contains("llc: not found; to fix: install LLVM 18"), "{}", message);
+            assert!(message.contains("rustc: not found in PATH; to fix: curl"), "{}", message);
+            assert!(!message.contains("clang:"), "{}", message);
+            assert!(message.contains("yunilang doctor"), "{}", message);
+        }
+        other => panic!("Expected missing tools error, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_llc_must_match_linked_llvm_major_version() {
+    let (dir, path_var) = bin_dir(&["llc", "clang", "rustc"]);
+    let runner = all_tools_runner()
+        .with("llc", "Homebrew LLVM version 17.0.6\n")
+        .with("clang", "Apple clang version 15.0.0 (clang-1500.3.9.4)\n");
+    let doctor = Doctor::new(OTHER, runner).with_path_var(path_var);
+
+    let llc = doctor.check_tool(Tool::Llc);
+    assert_eq!(llc.status, CheckStatus::Fail);
+    assert!(llc.detail.starts_with("LLVM 17.0.6 at "), "{}", llc.detail);
+    assert!(llc.detail.ends_with("but LLVM 18 is required"), "{}", llc.detail);
+    let fix = llc.fix.unwrap();
+    assert!(fix.contains(&format!("before {} in PATH", dir.path().display())), "{}", fix);
+
+    // リンクはLLVMのバージョンに依存しないため、clangのバージョンは問わない
+    let clang = doctor.check_tool(Tool::Clang);
+    assert_eq!(clang.status, CheckStatus::Pass);
+    assert!(clang.detail.starts_with("clang 15.0.0"), "{}", clang.detail);
+}
+
+#[test]
+fn test_unusable_tools_fail() {
+    // 実行できないllcと、バージョンを読めないllcはどちらも失敗
+    let (_dir, path_var) = bin_dir(&["llc", "rustc"]);
+    let doctor = Doctor::new(OTHER, FakeRunner::default()).with_path_var(path_var.clone());
+    let llc = doctor.check_tool(Tool::Llc);
+    assert_eq!(llc.status, CheckStatus::Fail);
+    assert!(llc.detail.ends_with("--version failed"), "{}", llc.detail);
+    assert_eq!(doctor.check_tool(Tool::Rustc).status, CheckStatus::Fail);
+
+    let doctor = Doctor::new(OTHER, FakeRunner::default().with("llc", "llc: error\n")).with_path_var(path_var);
+    let llc = doctor.check_tool(Tool::Llc);
+    assert_eq!(llc.status, CheckStatus::Fail);
+    assert!(llc.detail.starts_with("could not read the LLVM version"), "{}", llc.detail);
+}
+
+#[test]
+fn test_windows_finds_clang_cl_and_exe_names() {
+    let (_dir, path_var) = bin_dir(&["llc.exe", "clang-cl.exe", "rustc.exe"]);
+    let runner = FakeRunner::default()
+        .with("llc.exe", LLC_18)
+        .with("clang-cl.exe", "clang version 18.1.8\nTarget: x86_64-pc-windows-msvc\n")
+        .with("rustc.exe", RUSTC);
+    let doctor = Doctor::new(WINDOWS_MSVC, runner).with_path_var(path_var);
+
+    let clang = doctor.check_tool(Tool::Clang);
+    assert_eq!(clang.status, CheckStatus::Pass);
+    assert!(clang.detail.contains("clang-cl.exe"), "{}", clang.detail);
+    assert_eq!(doctor.check_tool(Tool::Rustc).status, CheckStatus::Pass);
+}
+
+#[test]
+fn test_install_commands_per_platform() {
+    let linux = Doctor::new(LINUX, FakeRunner::default());
+    assert_eq!(linux.llvm_install_command(Tool::Llc), "sudo apt install llvm-18");
+    assert_eq!(linux.llvm_install_command(Tool::Clang), "sudo apt install clang-18");
+    assert!(linux.rustup_install_command().contains("https://sh.rustup.rs"));
+
+    let macos = Doctor::new(MACOS, FakeRunner::default());
+    assert_eq!(macos.llvm_install_command(Tool::Clang), "brew install llvm@18");
+
+    let windows = Doctor::new(WINDOWS_MSVC, FakeRunner::default());
+    assert_eq!(windows.llvm_install_command(Tool::Llc), "winget install LLVM.LLVM --version 18.1.8");
+    assert_eq!(windows.rustup_install_command(), "winget install Rustlang.Rustup");
+}
+
+#[test]
+fn test_macos_checks_homebrew_llvm() {
+    let doctor = Doctor::new(MACOS, FakeRunner::default());
+    let names: Vec<_> = doctor.checks().iter().map(|check| check.name).collect();
+    assert_eq!(names, ["llc", "clang", "rustc", "temp dir", "homebrew llvm"]);
+
+    // ほかの方法でインストールしたLLVMも使えるため、Homebrewになくても失敗にはしない
+    let homebrew = doctor.check_homebrew_llvm();
+    assert!(homebrew.passed());
+    if homebrew.status == CheckStatus::Warn {
+        assert_eq!(homebrew.fix.as_deref(), Some("brew install llvm@18"));
+    }
+}
+
+#[test]
+fn test_temp_dir_must_be_writable() {
+    let dir = tempfile::tempdir().unwrap();
+    let doctor = Doctor::new(LINUX, FakeRunner::default()).with_temp_dir(dir.path().to_path_buf());
+    assert_eq!(doctor.check_temp_dir().status, CheckStatus::Pass);
+
+    let missing = Doctor::new(LINUX, FakeRunner::default()).with_temp_dir(dir.path().join("missing"));
+    let check = missing.check_temp_dir();
+    assert_eq!(check.status, CheckStatus::Fail);
+    assert_eq!(check.fix.as_deref(), Some("set TMPDIR to a writable directory"));
+}
@@ -0,0 +1,184 @@
+//! ツール向けのASTのJSON（`yunilang compile --emit=ast-json`）のテスト
+//!
+//! すべての文と式を含むフィクスチャの出力をゴールデンファイルと比較し、
+//! ASTの形が変わったのに `AST_FORMAT_VERSION` を上げていなければ失敗させる。
+//! 環境変数 `UPDATE_GOLDEN=1` を設定すると、比較せずにゴールデンファイルを書き換える。
+
+use std::collections::BTreeSet;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use serde_json::Value;
+use tempfile::NamedTempFile;
+use yunilang::ast::{Program, AST_FORMAT_VERSION, AST_SPAN_ENCODING};
+use yunilang::lexer::Lexer;
+use yunilang::parser::Parser;
+
+const FIXTURE: &str = "all_syntax.yuni";
+
+fn fixture_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/ast_json")
+}
+
+fn fixture_json() -> String {
+    let source = fs::read_to_string(fixture_dir().join(FIXTURE)).expect("fixture should be readable");
+    let tokens: Vec<_> = Lexer::new(&source).collect_tokens();
+    let program = Parser::new(tokens).parse().expect("fixture should parse");
+    format!("{}\n", program.to_ast_json(FIXTURE))
+}
+
+/// `actual` をゴールデンファイル `tests/fixtures/ast_json/<name>` と比較する
+fn assert_golden(actual: &str, name: &str) {
+    let path = fixture_dir().join(name);
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        fs::write(&path, actual).expect("Failed to write golden file");
+        return;
+    }
+    let expected = fs::read_to_string(&path)
+        .unwrap_or_else(|e| panic!("Failed to read {}: {}", path.display(), e));
+    assert_eq!(actual, expected, "AST JSON differs from {} (UPDATE_GOLDEN=1 to update)", path.display());
+}
+
+/// JSONに現れる列挙型のバリアント（`{"Let": ...}` のように大文字で始まる唯一のキー）
+fn variant_names(value: &Value, names: &mut BTreeSet<String>) {
+    match value {
+        Value::Object(map) => {
+            if map.len() == 1 {
+                let key = map.keys().next().unwrap();
+                if key.starts_with(char::is_uppercase) {
+                    names.insert(key.clone());
+                }
+            }
+            map.values().for_each(|v| variant_names(v, names));
+        }
+        Value::Array(items) => items.iter().for_each(|v| variant_names(v, names)),
+        _ => {}
+    }
+}
+
+/// JSONの形（オブジェクトごとのキーと値の種類の組）を1行ずつ並べたもの
+///
+/// 値そのものやスパンの位置は含まないため、フィクスチャを書き換えてもASTの定義が同じなら変わらない。
+fn shape(value: &Value, lines: &mut BTreeSet<String>) {
+    match value {
+        Value::Object(map) => {
+            let fields: Vec<String> = map
+                .iter()
+                .map(|(key, v)| {
+                    let kind = match v {
+                        Value::Null => "null",
+                        Value::Bool(_) => "bool",
+                        Value::Number(_) => "number",
+                        Value::String(_) => "string",
+                        Value::Array(_) => "array",
+                        Value::Object(_) => "object",
+                    };
+                    format!("{}: {}", key, kind)
+                })
+                .collect();
+            lines.insert(format!("{{ {} }}", fields.join(", ")));
+            map.values().for_each(|v| shape(v, lines));
+        }
+        Value::Array(items) => items.iter().for_each(|v| shape(v, lines)),
+        _ => {}
+    }
+}
+
+#[test]
+fn test_ast_json_matches_golden() {
+    let json = fixture_json();
+    assert_golden(&json, "all_syntax.json");
+
+    let value: Value = serde_json::from_str(&json).unwrap();
+    assert_eq!(value["format_version"], AST_FORMAT_VERSION);
+    assert_eq!(value["source_file"], FIXTURE);
+    assert_eq!(value["span_encoding"], AST_SPAN_ENCODING);
+
+    // フィクスチャはパーサーが生成するすべての文と式を含む（`Expression::Array` は生成しない）
+    let mut names = BTreeSet::new();
+    variant_names(&value, &mut names);
+    let statements = ["Let", "Assignment", "Return", "If", "While", "For", "Expression", "Block", "Defer", "Function"];
+    let expressions = [
+        "Integer", "Float", "String", "Bytes", "TemplateString", "Boolean", "Identifier", "Path", "Binary",
+        "Unary", "Call", "MethodCall", "Index", "Field", "Reference", "Dereference", "StructLit", "EnumVariant",
+        "Tuple", "Cast", "Assignment", "Match", "If", "Block", "ListLiteral", "MapLiteral",
+    ];
+    for name in statements.iter().chain(&expressions) {
+        assert!(names.contains(*name), "the fixture should contain `{}`: {:?}", name, names);
+    }
+
+    // 出力はそのまま `--from-ast` の入力として読める
+    let restored = Program::from_json(&json).expect("emitted JSON should deserialize");
+    assert_eq!(restored.items.len(), 7);
+}
+
+#[test]
+fn test_ast_shape_change_requires_version_bump() {
+    // ASTの形はバージョンごとに記録する。形を変えたら `AST_FORMAT_VERSION` を上げ、
+    // 新しいバージョンの形を `UPDATE_GOLDEN=1` で記録する（古いバージョンの記録は残す）
+    let value: Value = serde_json::from_str(&fixture_json()).unwrap();
+    let mut lines = BTreeSet::new();
+    shape(&value, &mut lines);
+    let actual: String = lines.into_iter().map(|line| line + "\n").collect();
+
+    let path = fixture_dir().join(format!("shape_v{}.txt", AST_FORMAT_VERSION));
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        fs::write(&path, &actual).expect("Failed to write golden file");
+        return;
+    }
+    let recorded = fs::read_to_string(&path)
+        .unwrap_or_else(|e| panic!("Failed to read {} (record it with UPDATE_GOLDEN=1): {}", path.display(), e));
+    assert!(
+        recorded == actual,
+        "the AST shape changed but AST_FORMAT_VERSION is still {}; bump it and record shape_v{}.txt with UPDATE_GOLDEN=1",
+        AST_FORMAT_VERSION,
+        AST_FORMAT_VERSION + 1
+    );
+}
+
+#[test]
+fn test_emit_ast_json_cli() {
+    // 名前解決をしないため、未定義の名前があっても構文が正しければ出力する
+    let mut source = NamedTempFile::with_suffix(".yuni").expect("Failed to create temp file");
+    source.write_all(b"package main\n\nfn main() {\n    println(undefined_name);\n}\n").unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_yunilang"))
+        .args(["compile", "--emit=ast-json", "-v", "-o", "-"])
+        .arg(source.path())
+        .output()
+        .expect("Failed to execute compiler");
+    assert!(output.status.success(), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+
+    // 標準出力はJSON文書だけ（情報メッセージは標準エラーに出す）
+    let value: Value = serde_json::from_slice(&output.stdout).expect("stdout should be a single JSON document");
+    assert_eq!(value["format_version"], AST_FORMAT_VERSION);
+    assert_eq!(value["source_file"], source.path().display().to_string());
+    assert_eq!(value["span_encoding"], AST_SPAN_ENCODING);
+    let call = &value["items"][0]["Function"]["body"]["statements"][0]["Expression"]["Call"];
+    assert_eq!(call["args"][0]["Identifier"]["name"], "undefined_name");
+
+    // 出力先のファイルにも同じ内容を書き出す
+    let out = tempfile::tempdir().unwrap();
+    let path = out.path().join("ast.json");
+    let status = Command::new(env!("CARGO_BIN_EXE_yunilang"))
+        .args(["compile", "--emit=ast-json", "-o"])
+        .arg(&path)
+        .arg(source.path())
+        .status()
+        .expect("Failed to execute compiler");
+    assert!(status.success());
+    assert_eq!(fs::read(&path).unwrap(), output.stdout);
+
+    // 構文エラーでは何も出力しない
+    let mut broken = NamedTempFile::with_suffix(".yuni").expect("Failed to create temp file");
+    broken.write_all(b"package main\n\nfn main() {\n    let x = ;\n}\n").unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_yunilang"))
+        .args(["compile", "--emit=ast-json", "-o", "-"])
+        .arg(broken.path())
+        .output()
+        .expect("Failed to execute compiler");
+    assert!(!output.status.success());
+    assert!(output.stdout.is_empty(), "stdout: {}", String::from_utf8_lossy(&output.stdout));
+}
@@ -1,7 +1,8 @@
 //! 統一的なエラーハンドリングのテスト
 
-use yunilang::compiler::{CompilationPipeline, CompilationState};
-use yunilang::error::{LexerError, ParserError, YuniError};
+use codespan_reporting::term::{self, termcolor::Buffer};
+use yunilang::compiler::{CodeSizeLimits, CompilationPipeline, CompilationState};
+use yunilang::error::{CodeSizeError, LexerError, ParserError, YuniError};
 
 #[test]
 fn test_lexer_error_handling() {
@@ -14,7 +15,7 @@ fn main() {
 
     let state = CompilationState::new_from_string("test.yuni", source.to_string()).unwrap();
     let context = inkwell::context::Context::create();
-    let mut pipeline = CompilationPipeline::new(state, &context, false);
+    let mut pipeline = CompilationPipeline::new(state, &context);
 
     // レキシカル解析を実行
     let _tokens = pipeline.tokenize();
@@ -35,7 +36,7 @@ fn main() {
 
     let state = CompilationState::new_from_string("test.yuni", source.to_string()).unwrap();
     let context = inkwell::context::Context::create();
-    let mut pipeline = CompilationPipeline::new(state, &context, false);
+    let mut pipeline = CompilationPipeline::new(state, &context);
 
     // レキシカル解析と構文解析を実行
     let tokens = pipeline.tokenize();
@@ -59,7 +60,7 @@ fn main() {
 
     let state = CompilationState::new_from_string("test.yuni", source.to_string()).unwrap();
     let context = inkwell::context::Context::create();
-    let mut pipeline = CompilationPipeline::new(state, &context, false);
+    let mut pipeline = CompilationPipeline::new(state, &context);
 
     // レキシカル解析を実行
     let _tokens = pipeline.tokenize();
@@ -82,7 +83,7 @@ fn main() {
 
     let state = CompilationState::new_from_string("test.yuni", source.to_string()).unwrap();
     let context = inkwell::context::Context::create();
-    let mut pipeline = CompilationPipeline::new(state, &context, false);
+    let mut pipeline = CompilationPipeline::new(state, &context);
 
     // パイプライン全体を実行
     let _result = pipeline.run();
@@ -112,3 +113,234 @@ fn test_error_type_conversion() {
     let error_message = parser_error.to_string();
     assert!(error_message.contains("構文解析エラー"));
 }
+
+#[test]
+fn test_code_size_warnings_name_generic_root() {
+    let source = r#"
+package test
+
+struct Wrap<T> {
+    v: T,
+}
+
+fn leaf<T>(x: T): T {
+    return x;
+}
+
+fn grow<T>(x: T): Wrap<T> {
+    let a: T = leaf(x);
+    let w: Wrap<T> = Wrap { v: a };
+    let b: Wrap<T> = leaf(w);
+    return b;
+}
+
+fn other<T>(x: T): T {
+    return x;
+}
+
+fn main() {
+    let a = grow(1);
+    let b = grow(true);
+    let c = grow(2.5);
+    let d = grow("s");
+    let e = other(1);
+    println(e);
+}
+"#;
+
+    let state = CompilationState::new_from_string("test.yuni", source.to_string()).unwrap();
+    let context = inkwell::context::Context::create();
+    let mut pipeline = CompilationPipeline::new(state, &context).with_code_size_limits(CodeSizeLimits {
+        warn_instantiations: 5,
+        max_instantiations: None,
+        warn_functions: 5,
+        warn_instructions: 10,
+    });
+
+    let tokens = pipeline.tokenize();
+    let ast = pipeline.parse(tokens).unwrap();
+    assert!(pipeline.analyze(&ast));
+    let ast = pipeline.monomorphize(ast).unwrap();
+    pipeline.codegen(&ast).unwrap();
+
+    // 単相化とコード生成の規模の警告が、どちらも grow を最大の起点として挙げる
+    assert!(!pipeline.state().has_errors());
+    let warnings = pipeline.state().error_collector.warnings();
+    assert_eq!(warnings.len(), 2);
+    for warning in warnings {
+        match &warning.error {
+            YuniError::CodeSize(e @ CodeSizeError::TooManyInstantiations { count: 17, .. })
+            | YuniError::CodeSize(e @ CodeSizeError::ModuleTooLarge { .. }) => {
+                assert_eq!(e.roots()[0].0, "grow");
+            }
+            other => panic!("規模の警告を期待しましたが {:?} でした", other),
+        }
+    }
+}
+
+#[test]
+fn test_instantiation_limit_is_reported_as_error() {
+    let source = r#"
+package test
+
+struct Wrap<T> {
+    v: T,
+}
+
+fn leaf<T>(x: T): T {
+    return x;
+}
+
+fn grow<T>(x: T): Wrap<T> {
+    let a: T = leaf(x);
+    let w: Wrap<T> = Wrap { v: a };
+    let b: Wrap<T> = leaf(w);
+    return b;
+}
+
+fn other<T>(x: T): T {
+    return x;
+}
+
+fn main() {
+    let a = grow(1);
+    let b = grow(true);
+    let c = grow(2.5);
+    let d = grow("s");
+    let e = other(1);
+    println(e);
+}
+"#;
+
+    let state = CompilationState::new_from_string("test.yuni", source.to_string()).unwrap();
+    let context = inkwell::context::Context::create();
+    let mut pipeline = CompilationPipeline::new(state, &context).with_code_size_limits(CodeSizeLimits {
+        max_instantiations: Some(3),
+        ..CodeSizeLimits::default()
+    });
+
+    let tokens = pipeline.tokenize();
+    let ast = pipeline.parse(tokens).unwrap();
+    assert!(pipeline.analyze(&ast));
+
+    // 上限を超えた時点で単相化を中断し、エラーとして収集する
+    assert!(pipeline.monomorphize(ast).is_none());
+    assert_eq!(pipeline.state().error_count(), 1);
+    let message = pipeline.state().error_collector.errors()[0].error.to_string();
+    assert!(message.contains("grow"), "{}", message);
+}
+
+#[test]
+fn test_codegen_error_in_instantiation_names_generic_origin() {
+    // 参照外しへの代入はコード生成が未対応のため、store_f64 の本体でエラーになる
+    let source = r#"package test
+
+fn store<T>(r: &mut T, x: T) {
+    *r = x;
+}
+
+fn main() {
+    let mut a = 1.5;
+    store(&mut a, 2.5);
+    println(a);
+}
+"#;
+
+    let state = CompilationState::new_from_string("test.yuni", source.to_string()).unwrap();
+    let context = inkwell::context::Context::create();
+    let mut pipeline = CompilationPipeline::new(state, &context);
+
+    let tokens = pipeline.tokenize();
+    let ast = pipeline.parse(tokens).unwrap();
+    assert!(pipeline.analyze(&ast));
+    let ast = pipeline.monomorphize(ast).unwrap();
+    let error = pipeline.codegen(&ast).err().expect("コード生成エラーを期待しました");
+    assert!(matches!(&error, YuniError::Instantiation { instance, .. } if instance == "store<T=f64>"));
+    pipeline.state_mut().add_error(error);
+
+    let state = pipeline.state();
+    let mut buffer = Buffer::no_color();
+    for error in state.error_collector.errors() {
+        term::emit(&mut buffer, &term::Config::default(), &state.files, &error.to_diagnostic_in(&state.files)).unwrap();
+    }
+    let rendered = String::from_utf8(buffer.into_inner()).unwrap();
+
+    // ジェネリック定義の中の位置と、インスタンス化を要求した呼び出し箇所の両方を示す
+    assert!(rendered.contains("test.yuni:4:5"), "{}", rendered);
+    assert!(rendered.contains("store<T=f64> のインスタンス化（test.yuni:9:5 で要求）"), "{}", rendered);
+}
+
+#[test]
+fn test_script_error_reports_original_line() {
+    // スクリプトの暗黙のmainの中のエラーは元のソースの行を指す
+    let source = "let x = 1;\nprintln(x);\nlet y: i32 = missing + 1;\n";
+
+    let state = CompilationState::new_from_string("script.yuni", source.to_string()).unwrap();
+    let context = inkwell::context::Context::create();
+    let mut pipeline = CompilationPipeline::new(state, &context).with_script(true);
+
+    let tokens = pipeline.tokenize();
+    let mut ast = pipeline.parse(tokens).expect("スクリプトの構文解析に失敗しました");
+    pipeline.resolve_names(&mut ast);
+    assert!(!pipeline.analyze(&ast));
+
+    let state = pipeline.state();
+    let mut buffer = Buffer::no_color();
+    for error in state.error_collector.errors() {
+        term::emit(&mut buffer, &term::Config::default(), &state.files, &error.to_diagnostic_in(&state.files)).unwrap();
+    }
+    let rendered = String::from_utf8(buffer.into_inner()).unwrap();
+    assert!(rendered.contains("script.yuni:3:14"), "{}", rendered);
+}
+
+#[test]
+fn test_source_file_errors_are_io_errors() {
+    // 読み込みの失敗は診断ではなく、パスを含む `YuniError::Io` になる
+    let dir = tempfile::TempDir::new().unwrap();
+    let missing = dir.path().join("missing.yuni");
+    match CompilationState::new(&missing) {
+        Err(YuniError::Io(message)) => assert!(message.contains(&missing.display().to_string()), "{}", message),
+        other => panic!("Expected an Io error, got {:?}", other.err()),
+    }
+    assert!(matches!(CompilationState::new(dir.path()), Err(YuniError::Io(_))));
+}
+
+#[test]
+fn test_bom_prefixed_source_reports_correct_positions() {
+    // BOMを取り除いてから字句解析するため、診断の位置はずれない
+    let dir = tempfile::TempDir::new().unwrap();
+    let path = dir.path().join("bom.yuni");
+    std::fs::write(&path, "\u{FEFF}package main\n\nfn main() {\n    let y: i32 = missing;\n}\n").unwrap();
+
+    let state = CompilationState::new(&path).expect("BOM-prefixed UTF-8 should be accepted");
+    assert!(state.source.starts_with("package"));
+    assert!(!state.has_warnings());
+    let context = inkwell::context::Context::create();
+    let mut pipeline = CompilationPipeline::new(state, &context);
+    let tokens = pipeline.tokenize();
+    let mut ast = pipeline.parse(tokens).expect("構文解析に失敗しました");
+    pipeline.resolve_names(&mut ast);
+    assert!(!pipeline.analyze(&ast));
+
+    let state = pipeline.state();
+    let mut buffer = Buffer::no_color();
+    for error in state.error_collector.errors() {
+        term::emit(&mut buffer, &term::Config::default(), &state.files, &error.to_diagnostic_in(&state.files)).unwrap();
+    }
+    let rendered = String::from_utf8(buffer.into_inner()).unwrap();
+    assert!(rendered.contains("bom.yuni:4:18"), "{}", rendered);
+}
+
+#[test]
+fn test_non_yuni_extension_warns() {
+    // 拡張子が .yuni でなくても読み込むが、警告する
+    let dir = tempfile::TempDir::new().unwrap();
+    let path = dir.path().join("program.txt");
+    std::fs::write(&path, "package main\n\nfn main() {\n}\n").unwrap();
+
+    let state = CompilationState::new(&path).expect("Other extensions should be accepted");
+    assert!(!state.has_errors());
+    let warnings: Vec<String> = state.error_collector.warnings().iter().map(|w| w.error.to_string()).collect();
+    assert_eq!(warnings.len(), 1);
+    assert!(warnings[0].contains("does not have the .yuni extension"), "{:?}", warnings);
+}
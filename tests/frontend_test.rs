@@ -0,0 +1,107 @@
+//! フロントエンドAPI（`check_source`）とフロントエンドのみのビルドのテスト
+
+use std::path::Path;
+use std::process::Command;
+
+use serde_json::Value;
+use yunilang::frontend::{check_source, JsonSeverity};
+
+#[test]
+fn test_check_source_reports_diagnostics_with_positions() {
+    let result = check_source(
+        "playground.yuni",
+        "package main\n\nfn main() {\n    let x: i32 = undefined_name;\n}\n",
+    );
+
+    assert!(!result.success);
+    assert_eq!(result.diagnostics.len(), 1);
+    let diagnostic = &result.diagnostics[0];
+    assert_eq!(diagnostic.severity, JsonSeverity::Error);
+    assert_eq!(diagnostic.message, "未定義の変数: undefined_name");
+
+    let label = &diagnostic.labels[0];
+    assert!(label.primary);
+    assert_eq!(label.message, "この変数は定義されていません");
+    assert_eq!((label.start.line, label.start.column), (4, 18));
+    assert_eq!((label.end.line, label.end.column), (4, 32));
+
+    // 解析エラーがあってもパースできた定義とASTは返す
+    assert_eq!(result.symbols.len(), 1);
+    assert_eq!(result.symbols[0].name, "main");
+    assert!(result.ast.as_deref().unwrap().contains("fn main()"));
+}
+
+#[test]
+fn test_check_source_warns_about_misplaced_doc_comments() {
+    let result = check_source(
+        "doc.yuni",
+        "package main\n\nfn main() {\n    /// not an item\n    let x = 1;\n}\n",
+    );
+
+    assert!(result.success);
+    assert_eq!(result.diagnostics.len(), 1);
+    let diagnostic = &result.diagnostics[0];
+    assert_eq!(diagnostic.severity, JsonSeverity::Warning);
+    assert_eq!(diagnostic.message, "ドキュメントコメントの後にアイテムがありません");
+    let label = &diagnostic.labels[0];
+    assert_eq!((label.start.line, label.start.column), (4, 5));
+    assert_eq!((label.end.line, label.end.column), (4, 20));
+}
+
+#[test]
+fn test_check_source_serializes_to_json() {
+    let result = check_source("ok.yuni", "package main\n\nfn answer(): i32 {\n    return 42;\n}\n");
+    assert!(result.success);
+
+    let json: Value = serde_json::from_str(&result.to_json()).expect("check result should be valid JSON");
+    assert_eq!(json["file"], "ok.yuni");
+    assert_eq!(json["success"], true);
+    assert_eq!(json["diagnostics"].as_array().unwrap().len(), 0);
+    assert_eq!(json["symbols"][0]["detail"], "fn answer(): i32");
+
+    // 構文エラーではASTを返さない
+    let result = check_source("broken.yuni", "package main\n\nfn main( {\n}\n");
+    assert!(!result.success);
+    assert!(result.ast.is_none());
+    let json: Value = serde_json::from_str(&result.to_json()).unwrap();
+    assert!(json.get("ast").is_none());
+    assert_eq!(json["diagnostics"][0]["severity"], "error");
+    assert_eq!(json["diagnostics"][0]["labels"][0]["start"]["line"], 3);
+}
+
+/// wasm32-unknown-unknownターゲットがインストールされているかどうか
+fn wasm_target_installed() -> bool {
+    Command::new("rustup")
+        .args(["target", "list", "--installed"])
+        .output()
+        .map(|output| String::from_utf8_lossy(&output.stdout).lines().any(|line| line == "wasm32-unknown-unknown"))
+        .unwrap_or(false)
+}
+
+#[test]
+fn test_frontend_builds_for_wasm() {
+    if !wasm_target_installed() {
+        eprintln!("wasm32-unknown-unknownターゲットがないためフロントエンドのビルド確認をスキップします");
+        return;
+    }
+
+    let output = Command::new(env!("CARGO"))
+        .args([
+            "check",
+            "--lib",
+            "--no-default-features",
+            "--features",
+            "frontend",
+            "--target",
+            "wasm32-unknown-unknown",
+        ])
+        .env("CARGO_TARGET_DIR", Path::new(env!("CARGO_TARGET_TMPDIR")).join("wasm-frontend"))
+        .current_dir(env!("CARGO_MANIFEST_DIR"))
+        .output()
+        .expect("cargoの起動に失敗しました");
+    assert!(
+        output.status.success(),
+        "フロントエンドのみのビルドに失敗しました: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
@@ -0,0 +1,97 @@
+//! ファジングハーネスのテスト
+//!
+//! 過去にパニックやスタックオーバーフローを起こした入力や、壊れた入力を
+//! ハーネスに通し、構造化されたエラーで終わることを確認する。
+
+use yunilang::fuzz::{analyze_no_panic, parse_roundtrip};
+
+/// 両方のハーネスに入力を通す
+fn check(source: &str) {
+    parse_roundtrip(source);
+    let _ = analyze_no_panic(source);
+}
+
+fn in_main(body: &str) -> String {
+    format!("package main\nfn main() {{\n{}\n}}\n", body)
+}
+
+#[test]
+fn test_empty_and_garbage_input() {
+    check("");
+    check("package");
+    check("@@@ ### $$$");
+    check("package main\n\u{0}\u{7f}");
+    check("}");
+    check("package main\nfn main() {}\n}");
+}
+
+#[test]
+fn test_broken_template_strings() {
+    check(&in_main("let s = `abc"));
+    check(&in_main("let s = `${`;"));
+    check(&in_main("let s = `${}`;"));
+    check(&in_main("let s = `${1 +}`;"));
+    check(&in_main("let s = `${\"}\"}`;"));
+    check(&in_main("let s = `a${`b${1}`}c`;"));
+}
+
+#[test]
+fn test_deep_nesting_is_rejected_without_overflow() {
+    let n = 1000;
+    let cases = [
+        format!("let x = {}1{};", "(".repeat(n), ")".repeat(n)),
+        format!("let x = {}1;", "- ".repeat(n)),
+        format!("{}{}", "{ ".repeat(n), "} ".repeat(n)),
+        format!("let x: {}i32{} = 1;", "[".repeat(n), "]".repeat(n)),
+        format!("let {}x{} = 1;", "(".repeat(n), ")".repeat(n)),
+        format!("let x = `${{{}1{}}}`;", "(".repeat(n), ")".repeat(n)),
+    ];
+
+    for body in cases {
+        let source = in_main(&body);
+        check(&source);
+        assert!(analyze_no_panic(&source).is_err());
+    }
+}
+
+#[test]
+fn test_long_chains_are_accepted() {
+    let n = 500;
+    let cases = [
+        format!("let x = 1{};", " + 1".repeat(n)),
+        format!("if true {{ }} {}", "else if true { } ".repeat(n)),
+        format!("let x = if true {{ 1 }} {}else {{ 0 }};", "else if true { 2 } ".repeat(n)),
+        format!("let x = (1{});", ".abs()".repeat(n)),
+    ];
+
+    for body in cases {
+        let source = in_main(&body);
+        check(&source);
+        assert!(analyze_no_panic(&source).is_ok());
+    }
+
+    // 連鎖にも（入れ子よりはるかに大きい）上限がある
+    let source = in_main(&format!("let x = 1{};", " + 1".repeat(10_000)));
+    check(&source);
+    assert!(analyze_no_panic(&source).is_err());
+}
+
+#[test]
+fn test_roundtrip_preserves_tricky_syntax() {
+    check(&in_main("let v: Vec<Vec<i32> > = Vec<Vec<i32> >{};"));
+    check(&in_main("let x = -5 - -(3 + 2) * !true as i32;"));
+    check(&in_main("let s = `{} ${\"{\" + \"}\"} \\` \\$ ${1.0}`;"));
+    check(&in_main("let r = & &x;\nlet t = (1,);\nlet u = ();"));
+    check(&in_main("let y = if a < b { 1 } else { { 2 } };\n({ 3 });"));
+}
+
+#[test]
+fn test_examples_roundtrip_and_analyze() {
+    let dir = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("examples");
+    for entry in std::fs::read_dir(dir).unwrap() {
+        let path = entry.unwrap().path();
+        if path.extension().is_some_and(|ext| ext == "yuni") {
+            check(&std::fs::read_to_string(&path).unwrap());
+        }
+    }
+}
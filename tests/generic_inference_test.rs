@@ -3,6 +3,7 @@
 use yunilang::parser::Parser;
 use yunilang::lexer::Lexer;
 use yunilang::analyzer::SemanticAnalyzer;
+use yunilang::error::AnalyzerError;
 
 #[test]
 fn test_generic_function_call_inference() {
@@ -105,7 +106,7 @@ fn main() {
     let x: i32 = int_box.value;  // フィールドアクセスで正しい型を取得
     
     let str_box = Box { value: "hello" };
-    let s: String = str_box.value;  // フィールドアクセスで正しい型を取得
+    let s: str = str_box.value;  // フィールドアクセスで正しい型を取得
 }
 "#;
 
@@ -141,4 +142,107 @@ fn main() {
     let mut analyzer = SemanticAnalyzer::new();
     let result = analyzer.analyze(&program);
     assert!(result.is_err(), "Expected type error but analysis succeeded");
-}
\ No newline at end of file
+}
+/// プログラムを解析し、セマンティック解析の結果を返すヘルパー関数
+fn analyze(input: &str) -> Result<(), AnalyzerError> {
+    let tokens = Lexer::new(input).collect_tokens();
+    let program = Parser::new(tokens).parse().expect("Failed to parse");
+    SemanticAnalyzer::new().analyze(&program)
+}
+
+#[test]
+fn test_explicit_type_arguments() {
+    // 引数を取らないジェネリック関数も、型引数を明示すれば呼び出せる
+    let input = r#"
+package test
+
+struct Pair<T, U> {
+    first: T,
+    second: U,
+}
+
+fn empty<T>(): i64 {
+    return 0;
+}
+
+fn main() {
+    let a: i64 = empty::<bool>();
+    let p = Pair<i64, f64> { first: 1, second: 2 };
+    let b: f64 = p.second;
+}
+"#;
+
+    let result = analyze(input);
+    assert!(result.is_ok(), "Analysis failed: {:?}", result);
+}
+
+#[test]
+fn test_explicit_type_arguments_conflict_with_arguments() {
+    // 明示した型引数は推論より優先し、合わない引数は型エラーになる
+    let input = r#"
+package test
+
+fn identity<T>(x: T): T {
+    return x;
+}
+
+fn main() {
+    let a = identity::<bool>(42);
+}
+"#;
+
+    assert!(analyze(input).is_err(), "Expected type error but analysis succeeded");
+}
+
+#[test]
+fn test_type_argument_count_mismatch() {
+    let input = r#"
+package test
+
+fn identity<T>(x: T): T {
+    return x;
+}
+
+fn main() {
+    let a = identity::<i32, i64>(1);
+}
+"#;
+
+    match analyze(input) {
+        Err(AnalyzerError::TypeArgumentCountMismatch { name, expected, found, .. }) => {
+            assert_eq!(name, "identity");
+            assert_eq!((expected, found), (1, 2));
+        }
+        other => panic!("Expected TypeArgumentCountMismatch, got: {:?}", other),
+    }
+}
+
+#[test]
+fn test_type_arguments_on_non_generic() {
+    let input = r#"
+package test
+
+struct Point {
+    x: i32,
+}
+
+fn one(): i32 {
+    return 1;
+}
+
+fn main() {
+    let a = one::<i32>();
+}
+"#;
+
+    match analyze(input) {
+        Err(AnalyzerError::UnexpectedTypeArguments { name, .. }) => assert_eq!(name, "one"),
+        other => panic!("Expected UnexpectedTypeArguments, got: {:?}", other),
+    }
+
+    let input = input.replace("one::<i32>()", "Point<i32> { x: 1 }");
+    match analyze(&input) {
+        Err(AnalyzerError::UnexpectedTypeArguments { name, .. }) => assert_eq!(name, "Point"),
+        other => panic!("Expected UnexpectedTypeArguments, got: {:?}", other),
+    }
+}
@@ -46,10 +46,12 @@ fn test_full_compilation(source: &str, expected_success: bool) -> Result<String,
 
 #[cfg(test)]
 mod tests {
-    use std::process::Command;
+    use std::process::{Command, Stdio};
     use std::fs;
+    use std::io::{BufRead, BufReader, Write};
     use std::path::Path;
     use std::env;
+    use std::time::{Duration, Instant};
     
     
     
@@ -95,7 +97,7 @@ mod tests {
         
         let ir = result.unwrap();
         assert!(ir.contains("Hello, World!"), "IR should contain the string literal");
-        assert!(ir.contains("printf"), "IR should contain printf call");
+        assert!(ir.contains("yuni_println"), "IR should contain yuni_println call");
     }
 
     #[test]
@@ -157,9 +159,9 @@ mod tests {
         assert!(result.is_ok(), "Struct program compilation should succeed");
         
         let ir = result.unwrap();
-        // LLVM IRでは構造体は { type, type } の形式で表現される
-        assert!(ir.contains("{ double, double }"), "IR should contain struct type definition");
-        assert!(ir.contains("getelementptr") || ir.contains("extractvalue") || ir.contains("load { double"), "IR should contain struct field access");
+        // LLVM IRでは構造体は名前付きの型 %Point = type { type, type } として定義される
+        assert!(ir.contains("%Point = type { double, double }"), "IR should contain struct type definition");
+        assert!(ir.contains("getelementptr") || ir.contains("extractvalue") || ir.contains("load %Point"), "IR should contain struct field access");
     }
 
     #[test]
@@ -404,7 +406,7 @@ mod tests {
         assert!(result.is_ok(), "Complex program compilation should succeed");
         
         let ir = result.unwrap();
-        assert!(ir.contains("{ double, i32 }"), "IR should contain Calculator struct type");
+        assert!(ir.contains("%Calculator = type { double, i32 }"), "IR should contain Calculator struct type");
         assert!(ir.matches("define").count() >= 3, "IR should contain multiple functions");
     }
 
@@ -474,6 +476,1200 @@ mod tests {
         assert!(compile_output.status.success(), "File compilation should succeed");
     }
 
+    #[test]
+    #[ignore] // llc・clangが必要なため通常は無視
+    fn test_run_pipes_stdin_to_program() {
+        // runサブコマンドで標準入力がプログラムに渡されることのテスト
+        let source = r#"
+        package main
+        
+        fn main() {
+            let first = read_line();
+            let second = read_line();
+            println(first);
+            println(second);
+        }
+        "#;
+        
+        let temp_file = create_test_file(source, "echo.yuni").expect("Failed to create temp file");
+        let compiler_path = get_compiler_path();
+        
+        if !Path::new(&compiler_path).exists() {
+            eprintln!("Compiler binary not found, skipping run stdin test");
+            return;
+        }
+        
+        let mut child = Command::new(&compiler_path)
+            .arg("run")
+            .arg(temp_file.path())
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .expect("Failed to execute compiler");
+        
+        child
+            .stdin
+            .take()
+            .expect("stdin should be piped")
+            .write_all(b"first line\nsecond line\n")
+            .expect("Failed to write stdin");
+        
+        let output = child.wait_with_output().expect("Failed to wait for compiler");
+        assert!(output.status.success(), "Program should exit successfully");
+        // コンパイル時のメッセージは標準出力に混ざらない
+        assert_eq!(String::from_utf8_lossy(&output.stdout), "first line\nsecond line\n");
+    }
+
+    #[test]
+    #[ignore] // llc・clangが必要なため通常は無視
+    fn test_run_sums_parsed_stdin_lines() {
+        // 標準入力の各行を整数に変換して合計する（数値でない行か入力の終わりで止まる）
+        let source = r#"
+        package main
+        
+        fn main() {
+            let mut total: i64 = 0;
+            let mut done = false;
+            while !done {
+                let (ok, value) = parse_int(read_line());
+                if ok {
+                    total = total + value;
+                } else {
+                    done = true;
+                }
+            }
+            println(total);
+        }
+        "#;
+        
+        let temp_file = create_test_file(source, "sum.yuni").expect("Failed to create temp file");
+        let compiler_path = get_compiler_path();
+        
+        if !Path::new(&compiler_path).exists() {
+            eprintln!("Compiler binary not found, skipping parse stdin test");
+            return;
+        }
+        
+        let mut child = Command::new(&compiler_path)
+            .arg("run")
+            .arg(temp_file.path())
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .expect("Failed to execute compiler");
+        
+        child
+            .stdin
+            .take()
+            .expect("stdin should be piped")
+            .write_all(b"10\n  32 \n-5\n")
+            .expect("Failed to write stdin");
+        
+        let output = child.wait_with_output().expect("Failed to wait for compiler");
+        assert!(output.status.success(), "Program should exit successfully");
+        assert_eq!(String::from_utf8_lossy(&output.stdout), "37\n");
+    }
+
+    #[test]
+    #[ignore] // llc・clangが必要なため通常は無視
+    fn test_run_vec_of_structs() {
+        // f64を含む構造体をVecに格納し、読み出したフィールドの合計が正しいことのテスト
+        let source = r#"
+        package main
+
+        type Sample struct {
+            weight: f64,
+            count: i32
+        }
+
+        fn main() {
+            let samples = Vec<Sample>[
+                Sample { weight: 1.5, count: 2 },
+                Sample { weight: 2.25, count: 3 },
+                Sample { weight: 4.0, count: 5 }
+            ];
+            let mut total: f64 = 0.0;
+            let mut count: i32 = 0;
+            let mut i: i32 = 0;
+            while i < 3 {
+                let sample: Sample = samples[i];
+                total = total + sample.weight;
+                count = count + sample.count;
+                i = i + 1;
+            }
+            println(total, " ", count);
+        }
+        "#;
+
+        let temp_file = create_test_file(source, "vec_structs.yuni").expect("Failed to create temp file");
+        let compiler_path = get_compiler_path();
+
+        if !Path::new(&compiler_path).exists() {
+            eprintln!("Compiler binary not found, skipping vec of structs test");
+            return;
+        }
+
+        let output = Command::new(&compiler_path)
+            .arg("run")
+            .arg(temp_file.path())
+            .output()
+            .expect("Failed to execute compiler");
+
+        assert!(output.status.success(), "Program should exit successfully: {}", String::from_utf8_lossy(&output.stderr));
+        assert_eq!(String::from_utf8_lossy(&output.stdout), "7.75 10\n");
+    }
+
+    #[test]
+    #[ignore] // llc・clangが必要なため通常は無視
+    fn test_run_extern_sqrt() {
+        // libmのsqrtを外部関数として宣言して呼び出す
+        let source = r#"
+        package main
+
+        extern "C" link("m") fn sqrt(x: f64) -> f64;
+
+        fn main() {
+            println(sqrt(2.25), " ", sqrt(30.25));
+        }
+        "#;
+
+        let temp_file = create_test_file(source, "extern_sqrt.yuni").expect("Failed to create temp file");
+        let compiler_path = get_compiler_path();
+
+        if !Path::new(&compiler_path).exists() {
+            eprintln!("Compiler binary not found, skipping extern sqrt test");
+            return;
+        }
+
+        let output = Command::new(&compiler_path)
+            .arg("run")
+            .arg(temp_file.path())
+            .output()
+            .expect("Failed to execute compiler");
+
+        assert!(output.status.success(), "Program should exit successfully: {}", String::from_utf8_lossy(&output.stderr));
+        assert_eq!(String::from_utf8_lossy(&output.stdout), "1.5 5.5\n");
+    }
+
+    #[test]
+    #[ignore] // llc・clangが必要なため通常は無視
+    fn test_run_extern_getenv() {
+        // getenvが返すNULL（設定されていない環境変数）は空文字列になる
+        let source = r#"
+        package main
+
+        extern fn getenv(name: String) -> String;
+
+        fn main() {
+            let greeting = getenv("YUNI_EXTERN_GREETING");
+            let missing = getenv("YUNI_EXTERN_MISSING");
+            println(greeting, " [", missing, "]");
+        }
+        "#;
+
+        let temp_file = create_test_file(source, "extern_getenv.yuni").expect("Failed to create temp file");
+        let compiler_path = get_compiler_path();
+
+        if !Path::new(&compiler_path).exists() {
+            eprintln!("Compiler binary not found, skipping extern getenv test");
+            return;
+        }
+
+        let output = Command::new(&compiler_path)
+            .arg("run")
+            .arg(temp_file.path())
+            .env("YUNI_EXTERN_GREETING", "hello")
+            .env_remove("YUNI_EXTERN_MISSING")
+            .output()
+            .expect("Failed to execute compiler");
+
+        assert!(output.status.success(), "Program should exit successfully: {}", String::from_utf8_lossy(&output.stderr));
+        assert_eq!(String::from_utf8_lossy(&output.stdout), "hello []\n");
+    }
+
+    #[test]
+    #[ignore] // llc・clangが必要なため通常は無視
+    fn test_run_or_and_binding_patterns() {
+        // orパターンの選択肢ごとに異なる位置のペイロードを束縛し、@ で一致した値全体を使う
+        let source = r#"
+        package main
+
+        type Shape enum {
+            Circle(r: i32),
+            Rect(w: i32, h: i32),
+            Dot
+        }
+
+        fn width(shape: Shape): i32 {
+            return match shape {
+                Shape::Circle(n) | Shape::Rect(n, _) => n,
+                Shape::Dot => 0,
+            };
+        }
+
+        fn classify(code: i32): i32 {
+            return match code {
+                d @ 0..=9 => d * 10,
+                n @ (100 | 200) => n + 1,
+                _ => -1,
+            };
+        }
+
+        fn main() {
+            println(width(Shape::Circle(1)), width(Shape::Rect(2, 5)), width(Shape::Dot));
+            println(classify(7), classify(200), classify(50));
+        }
+        "#;
+
+        let temp_file = create_test_file(source, "or_patterns.yuni").expect("Failed to create temp file");
+        let compiler_path = get_compiler_path();
+
+        if !Path::new(&compiler_path).exists() {
+            eprintln!("Compiler binary not found, skipping or pattern test");
+            return;
+        }
+
+        let output = Command::new(&compiler_path)
+            .arg("run")
+            .arg(temp_file.path())
+            .output()
+            .expect("Failed to execute compiler");
+
+        assert!(output.status.success(), "Program should exit successfully: {}", String::from_utf8_lossy(&output.stderr));
+        assert_eq!(String::from_utf8_lossy(&output.stdout), "120\n70201-1\n");
+    }
+
+    #[test]
+    #[ignore] // llc・clangが必要なため通常は無視
+    fn test_run_destructuring_let() {
+        // let文で分解した要素をネイティブコードでも同じ値で使える
+        let source = r#"
+        package main
+
+        type Point struct {
+            x: i32,
+            y: i32
+        }
+
+        fn divmod(a: i32, b: i32): (i32, i32) {
+            return (a / b, a % b);
+        }
+
+        fn sum(p: &Point): i32 {
+            let Point { x, y } = p;
+            return x + y;
+        }
+
+        fn main() {
+            let (q, r) = divmod(17, 5);
+            let (a, (b, _)) = (1, (2, 3));
+            let (mut total, step) = (10, 7);
+            total = total + step;
+            let p = Point { x: a, y: b };
+            println(q, r, sum(&p), total);
+        }
+        "#;
+
+        let temp_file = create_test_file(source, "destructuring.yuni").expect("Failed to create temp file");
+        let compiler_path = get_compiler_path();
+
+        if !Path::new(&compiler_path).exists() {
+            eprintln!("Compiler binary not found, skipping destructuring test");
+            return;
+        }
+
+        let output = Command::new(&compiler_path)
+            .arg("run")
+            .arg(temp_file.path())
+            .output()
+            .expect("Failed to execute compiler");
+
+        assert!(output.status.success(), "Program should exit successfully: {}", String::from_utf8_lossy(&output.stderr));
+        assert_eq!(String::from_utf8_lossy(&output.stdout), "32317\n");
+    }
+
+    #[test]
+    #[ignore] // llc・clangが必要なため通常は無視
+    fn test_run_float_formatting() {
+        // ネイティブコードでもprintlnとテンプレート文字列が同じ最短の桁数の書式で浮動小数点数を出力する
+        let source = format!(
+            r#"
+        package main
+
+        fn show(x: f64) {{
+            println(x);
+            println(`[${{x}}]`);
+        }}
+
+        fn main() {{
+            let zero = 0.0;
+            show(0.1);
+            show(1000000000000000000000.0);
+            show(-0.0);
+            show({max}.0);
+            show(zero / zero);
+            show(1.0 / zero);
+            show(-1.0 / zero);
+            let single: f32 = 0.1;
+            println(single);
+            println(`[${{single}}]`);
+        }}
+        "#,
+            max = f64::MAX
+        );
+
+        let temp_file = create_test_file(&source, "float_format.yuni").expect("Failed to create temp file");
+        let compiler_path = get_compiler_path();
+
+        if !Path::new(&compiler_path).exists() {
+            eprintln!("Compiler binary not found, skipping float formatting test");
+            return;
+        }
+
+        let output = Command::new(&compiler_path)
+            .arg("run")
+            .arg(temp_file.path())
+            .output()
+            .expect("Failed to execute compiler");
+
+        assert!(output.status.success(), "Program should exit successfully: {}", String::from_utf8_lossy(&output.stderr));
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let lines: Vec<&str> = stdout.lines().collect();
+        let expected = ["0.1", "1000000000000000000000", "-0", &f64::MAX.to_string(), "NaN", "inf", "-inf", "0.1"];
+        assert_eq!(lines.len(), expected.len() * 2, "{}", stdout);
+        for (pair, expected) in lines.chunks(2).zip(expected) {
+            assert_eq!(pair[0], expected);
+            assert_eq!(pair[1], format!("[{}]", expected));
+        }
+    }
+
+    /// 引数の数でオーバーロードした関数を呼び出すプログラム
+    const OVERLOADS: &str = r#"
+    package main
+
+    fn area(side: f64): f64 {
+        return side * side;
+    }
+
+    fn area(width: f64, height: f64): f64 {
+        return width * height;
+    }
+
+    fn main() {
+        println(area(3.0), " ", area(2.0, 5.0));
+    }
+    "#;
+
+    #[test]
+    fn test_overloads_are_emitted_separately() {
+        // 引数の数ごとの定義は別の関数として生成され、呼び出しはそれぞれの関数を呼ぶ
+        let temp_file = create_test_file(OVERLOADS, "overloads.yuni").expect("Failed to create temp file");
+        let output = Command::new(env!("CARGO_BIN_EXE_yunilang"))
+            .args(["compile", "--emit=llvm-ir", "-o", "-"])
+            .arg(temp_file.path())
+            .output()
+            .expect("Failed to execute compiler");
+        assert!(output.status.success(), "Compilation should succeed: {}", String::from_utf8_lossy(&output.stderr));
+        let ir = String::from_utf8(output.stdout).expect("IR should be valid UTF-8");
+
+        assert!(ir.contains("define double @\"area/1\"(double"), "{}", ir);
+        assert!(ir.contains("define double @\"area/2\"(double"), "{}", ir);
+        assert!(ir.contains("call double @\"area/1\"("), "{}", ir);
+        assert!(ir.contains("call double @\"area/2\"("), "{}", ir);
+    }
+
+    #[test]
+    fn test_run_overloads_with_interpreter() {
+        // インタプリタでも呼び出しの引数の数で定義が選ばれる
+        let temp_file = create_test_file(OVERLOADS, "overloads.yuni").expect("Failed to create temp file");
+        let output = Command::new(env!("CARGO_BIN_EXE_yunilang"))
+            .args(["run", "--interpret"])
+            .arg(temp_file.path())
+            .output()
+            .expect("Failed to execute compiler");
+
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+        assert_eq!(String::from_utf8_lossy(&output.stdout), "9 10\n");
+    }
+
+    #[test]
+    fn test_run_interpreter_rejects_compiled_only_options() {
+        // インタプリタが扱えないオプションは黙って無視せずにエラーにする
+        let temp_file = create_test_file(OVERLOADS, "overloads.yuni").expect("Failed to create temp file");
+        for options in [&["--stack-size", "1048576"][..], &["--no-null-checks"], &["--debug-assertions"], &["arg"]] {
+            let output = Command::new(env!("CARGO_BIN_EXE_yunilang"))
+                .args(["run", "--interpret"])
+                .arg(temp_file.path())
+                .args(options)
+                .output()
+                .expect("Failed to execute compiler");
+
+            assert!(!output.status.success(), "{:?}", options);
+            assert!(String::from_utf8_lossy(&output.stderr).contains("cannot be used with"), "{:?}", options);
+        }
+    }
+
+    #[test]
+    fn test_vec_sort_synthesizes_comparators() {
+        // 要素の型ごと・比較関数ごとに比較関数を1つだけ合成してランタイムに渡す
+        let source = r#"
+        package main
+
+        fn descending(a: i64, b: i64): i32 {
+            return (b - a) as i32;
+        }
+
+        fn main() {
+            let mut values: Vec<i64> = [3, 1, 2];
+            values.sort();
+            let found = values.binary_search(2);
+            values.sort_by(descending);
+            let mut words = Vec<String>["b", "a"];
+            words.sort();
+            println(values[0], " ", found, " ", words[0]);
+        }
+        "#;
+        let temp_file = create_test_file(source, "vec_sort.yuni").expect("Failed to create temp file");
+        let output = Command::new(env!("CARGO_BIN_EXE_yunilang"))
+            .args(["compile", "--emit=llvm-ir", "-o", "-"])
+            .arg(temp_file.path())
+            .output()
+            .expect("Failed to execute compiler");
+        assert!(output.status.success(), "Compilation should succeed: {}", String::from_utf8_lossy(&output.stderr));
+        let ir = String::from_utf8(output.stdout).expect("IR should be valid UTF-8");
+
+        assert_eq!(ir.matches("define internal i32 @yuni.cmp.i64(ptr").count(), 1, "{}", ir);
+        assert!(ir.contains("define internal i32 @yuni.cmp_by.descending(ptr"), "{}", ir);
+        assert!(ir.contains("define internal i32 @yuni.cmp.String(ptr"), "{}", ir);
+        assert!(ir.contains("call i64 @yuni_vec_binary_search("), "{}", ir);
+        assert_eq!(ir.matches("call void @yuni_vec_sort(").count(), 3, "{}", ir);
+    }
+
+    #[test]
+    fn test_membership_calls_runtime() {
+        // `in` はコンテナの種類ごとのランタイム関数を呼び出し、Vecには要素の比較関数を渡す
+        let source = r#"
+        package main
+
+        fn main() {
+            let values: Vec<i64> = [3, 1, 2];
+            let ages = HashMap<String, i32> { "alice": 30 };
+            let text = "hello";
+            if 2 in values && !("bob" in ages) && "ell" in text {
+                println("ok");
+            }
+        }
+        "#;
+        let temp_file = create_test_file(source, "membership.yuni").expect("Failed to create temp file");
+        let output = Command::new(env!("CARGO_BIN_EXE_yunilang"))
+            .args(["compile", "--emit=llvm-ir", "-o", "-"])
+            .arg(temp_file.path())
+            .output()
+            .expect("Failed to execute compiler");
+        assert!(output.status.success(), "Compilation should succeed: {}", String::from_utf8_lossy(&output.stderr));
+        let ir = String::from_utf8(output.stdout).expect("IR should be valid UTF-8");
+
+        assert!(ir.contains("define internal i32 @yuni.cmp.i64(ptr"), "{}", ir);
+        assert!(ir.contains("call i1 @yuni_vec_contains("), "{}", ir);
+        assert!(ir.contains("call i1 @yuni_hashmap_contains_key("), "{}", ir);
+        assert!(ir.contains("call i1 @yuni_string_contains("), "{}", ir);
+    }
+
+    #[test]
+    fn test_str_parameter_borrows_literal() {
+        // strの仮引数には文字列リテラルの定数をそのまま渡し、連結も確保もしない
+        let source = r#"
+        package main
+
+        fn greet(name: str): i64 {
+            return name.len();
+        }
+
+        fn main() {
+            let n = greet("yuni");
+            println(n);
+        }
+        "#;
+        let temp_file = create_test_file(source, "str_param.yuni").expect("Failed to create temp file");
+        let output = Command::new(env!("CARGO_BIN_EXE_yunilang"))
+            .args(["compile", "--emit=llvm-ir", "-o", "-", "-O", "0"])
+            .arg(temp_file.path())
+            .output()
+            .expect("Failed to execute compiler");
+        assert!(output.status.success(), "Compilation should succeed: {}", String::from_utf8_lossy(&output.stderr));
+        let ir = String::from_utf8(output.stdout).expect("IR should be valid UTF-8");
+
+        let main_start = ir.find("@main(").expect("main should be defined");
+        let main_body = &ir[main_start..main_start + ir[main_start..].find("\n}").unwrap()];
+        assert!(main_body.contains("(ptr @main.str)"), "{}", main_body);
+        for allocation in ["@yuni_string_concat", "@yuni_string_from_parts", "@malloc"] {
+            assert!(!main_body.contains(allocation), "{} should not be called: {}", allocation, main_body);
+        }
+    }
+
+    const UNCALLED_FUNCTION: &str = r#"
+    package main
+
+    fn helper(x: i64): i64 {
+        return x + 1;
+    }
+
+    fn never_called(x: i64): i64 {
+        return x * 2;
+    }
+
+    fn main() {
+        println(helper(41));
+    }
+    "#;
+
+    /// `compile --emit=llvm-ir -o -` に追加の引数を渡してIRを得る
+    fn llvm_ir_with(source_file: &Path, args: &[&str]) -> String {
+        let output = Command::new(env!("CARGO_BIN_EXE_yunilang"))
+            .args(["compile", "--emit=llvm-ir", "-o", "-"])
+            .args(args)
+            .arg(source_file)
+            .output()
+            .expect("Failed to execute compiler");
+        assert!(output.status.success(), "Compilation should succeed: {}", String::from_utf8_lossy(&output.stderr));
+        String::from_utf8(output.stdout).expect("IR should be valid UTF-8")
+    }
+
+    #[test]
+    fn test_prune_unreachable_functions() {
+        // mainから到達できない関数は、取り除く指定のときだけコードを生成しない
+        let temp_file = create_test_file(UNCALLED_FUNCTION, "prune.yuni").expect("Failed to create temp file");
+
+        let pruned = llvm_ir_with(temp_file.path(), &["--prune-unreachable"]);
+        assert!(pruned.contains("@helper("), "{}", pruned);
+        assert!(!pruned.contains("never_called"), "{}", pruned);
+
+        // IRの出力は既定ではすべての関数を生成する
+        for args in [&["--no-prune"][..], &[][..]] {
+            let ir = llvm_ir_with(temp_file.path(), args);
+            assert!(ir.contains("define i64 @never_called("), "{:?}: {}", args, ir);
+        }
+    }
+
+    #[test]
+    fn test_emit_callgraph() {
+        let temp_file = create_test_file(UNCALLED_FUNCTION, "callgraph.yuni").expect("Failed to create temp file");
+        let output = Command::new(env!("CARGO_BIN_EXE_yunilang"))
+            .args(["compile", "--emit=callgraph", "-o", "-"])
+            .arg(temp_file.path())
+            .output()
+            .expect("Failed to execute compiler");
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+        let dot = String::from_utf8(output.stdout).expect("DOT should be valid UTF-8");
+        assert!(dot.starts_with("digraph callgraph {"), "{}", dot);
+        assert!(dot.contains("\"main\" -> \"helper\";"), "{}", dot);
+        assert!(dot.contains("\"never_called\" [color=gray, fontcolor=gray];"), "{}", dot);
+        assert!(!dot.contains("-> \"never_called\""), "{}", dot);
+    }
+
+    #[test]
+    fn test_string_literal_in_string_position_is_copied() {
+        // Stringの位置に置いたリテラルは静的な文字列データを複製する
+        let source = r#"
+        package main
+
+        fn main() {
+            let owned: String = "yuni";
+            println(owned);
+        }
+        "#;
+        let temp_file = create_test_file(source, "string_literal.yuni").expect("Failed to create temp file");
+        let output = Command::new(env!("CARGO_BIN_EXE_yunilang"))
+            .args(["compile", "--emit=llvm-ir", "-o", "-", "-O", "0"])
+            .arg(temp_file.path())
+            .output()
+            .expect("Failed to execute compiler");
+        assert!(output.status.success(), "Compilation should succeed: {}", String::from_utf8_lossy(&output.stderr));
+        let ir = String::from_utf8(output.stdout).expect("IR should be valid UTF-8");
+
+        let main_start = ir.find("@main(").expect("main should be defined");
+        let main_body = &ir[main_start..main_start + ir[main_start..].find("\n}").unwrap()];
+        assert!(main_body.contains("@yuni_string_from_parts(ptr @main.str, i64 4)"), "{}", main_body);
+    }
+
+    #[test]
+    fn test_run_script_with_interpreter() {
+        // `run --script` はトップレベルの文を暗黙のmainとして実行する
+        let source = "let greeting = \"hello\";\nlet n = 2;\nprintln(greeting, \" \", n + 1);\n";
+
+        let temp_file = create_test_file(source, "script.yuni").expect("Failed to create temp file");
+        let output = Command::new(env!("CARGO_BIN_EXE_yunilang"))
+            .args(["run", "--interpret", "--script"])
+            .arg(temp_file.path())
+            .output()
+            .expect("Failed to execute compiler");
+
+        assert!(output.status.success(), "Script should run: {}", String::from_utf8_lossy(&output.stderr));
+        assert_eq!(String::from_utf8_lossy(&output.stdout), "hello 3\n");
+    }
+
+    #[test]
+    #[ignore] // llc・clangが必要なため通常は無視
+    fn test_run_mutual_tail_recursion_without_stack_overflow() {
+        // if式の分岐から相互に呼び合う関数が、最適化なしでもスタックを消費しないことのテスト
+        let source = r#"
+        package main
+
+        fn is_even(n: i32): bool {
+            if n == 0 { true } else { is_odd(n - 1) }
+        }
+
+        fn is_odd(n: i32): bool {
+            if n == 0 { false } else { is_even(n - 1) }
+        }
+
+        fn main() {
+            println(is_even(5000000), " ", is_odd(3000001));
+        }
+        "#;
+
+        let temp_file = create_test_file(source, "mutual_tail.yuni").expect("Failed to create temp file");
+        let compiler_path = get_compiler_path();
+
+        if !Path::new(&compiler_path).exists() {
+            eprintln!("Compiler binary not found, skipping mutual tail recursion test");
+            return;
+        }
+
+        let output = Command::new(&compiler_path)
+            .arg("run")
+            .arg("-O0")
+            .arg(temp_file.path())
+            .output()
+            .expect("Failed to execute compiler");
+
+        assert!(output.status.success(), "Program should exit successfully: {}", String::from_utf8_lossy(&output.stderr));
+        assert_eq!(String::from_utf8_lossy(&output.stdout), "true true\n");
+    }
+
+    #[test]
+    #[ignore] // llc・clangが必要なため通常は無視
+    fn test_run_string_builder_large_loop() {
+        // StringBuilderへの追加を繰り返しても、連結のように二乗の時間がかからないことのテスト
+        let source = r#"
+        package main
+
+        fn main() {
+            let sb = StringBuilder{};
+            let mut i: i32 = 0;
+            while i < 200000 {
+                sb.append(`item ${i} ${i % 2 == 0};`);
+                i = i + 1;
+            }
+            let s = sb.to_string();
+            println(sb.len());
+        }
+        "#;
+
+        let temp_file = create_test_file(source, "string_builder.yuni").expect("Failed to create temp file");
+        let compiler_path = get_compiler_path();
+
+        if !Path::new(&compiler_path).exists() {
+            eprintln!("Compiler binary not found, skipping string builder test");
+            return;
+        }
+
+        let start = Instant::now();
+        let output = Command::new(&compiler_path)
+            .arg("run")
+            .arg(temp_file.path())
+            .output()
+            .expect("Failed to execute compiler");
+        let elapsed = start.elapsed();
+
+        assert!(output.status.success(), "Program should exit successfully: {}", String::from_utf8_lossy(&output.stderr));
+        // "item " + 数字 + " " + true/false + ";" の長さの合計
+        let expected_len: usize = (0..200000)
+            .map(|i: usize| format!("item {} {};", i, i % 2 == 0).len())
+            .sum();
+        assert_eq!(String::from_utf8_lossy(&output.stdout), format!("{}\n", expected_len));
+        // 二乗の連結では数GBのコピーになるため、コンパイル時間を含めても十分に余裕のある上限
+        assert!(elapsed < Duration::from_secs(60), "String building took too long: {:?}", elapsed);
+    }
+
+    #[test]
+    #[cfg(unix)]
+    #[ignore] // llc・clangが必要なため通常は無視
+    fn test_run_sigint_cleans_up_temp_executable() {
+        // runサブコマンドがSIGINTを転送し、一時実行ファイルを削除することのテスト
+        let source = r#"
+        package main
+        
+        fn main() {
+            println("ready");
+            while true {
+            }
+        }
+        "#;
+        
+        let temp_file = create_test_file(source, "loop.yuni").expect("Failed to create temp file");
+        let compiler_path = get_compiler_path();
+        
+        if !Path::new(&compiler_path).exists() {
+            eprintln!("Compiler binary not found, skipping run signal test");
+            return;
+        }
+        
+        let mut child = Command::new(&compiler_path)
+            .arg("run")
+            .arg(temp_file.path())
+            .stdout(Stdio::piped())
+            .spawn()
+            .expect("Failed to execute compiler");
+        
+        // プログラムが起動して準備完了の行を出力するまで待つ（コンパイルが終わっている）
+        let temp_exe = env::temp_dir().join(format!("yuni_run_{}", child.id()));
+        let mut stdout = BufReader::new(child.stdout.take().expect("stdout should be piped"));
+        let mut line = String::new();
+        stdout.read_line(&mut line).expect("Failed to read program output");
+        assert_eq!(line, "ready\n", "Program should start before the signal is sent");
+        assert!(temp_exe.exists(), "Temporary executable should exist while the program runs");
+        
+        let kill_status = Command::new("kill")
+            .args(["-INT", &child.id().to_string()])
+            .status()
+            .expect("Failed to send SIGINT");
+        assert!(kill_status.success(), "kill should succeed");
+        
+        let status = child.wait().expect("Failed to wait for compiler");
+        // シグナルによる終了は 128 + シグナル番号で報告される
+        assert_eq!(status.code(), Some(130), "Runner should report SIGINT death as 130");
+        assert!(!temp_exe.exists(), "Temporary executable should be removed");
+    }
+
+    #[test]
+    fn test_emit_llvm_ir_to_stdout() {
+        // `-o -` でLLVM IRが標準出力に書き出されることのテスト
+        let source = r#"
+        package main
+        
+        fn main() {
+            println("streamed");
+        }
+        "#;
+        
+        let temp_file = create_test_file(source, "stream.yuni").expect("Failed to create temp file");
+        let output = Command::new(env!("CARGO_BIN_EXE_yunilang"))
+            .args(["compile", "--emit=llvm-ir", "-o", "-"])
+            .arg(temp_file.path())
+            .output()
+            .expect("Failed to execute compiler");
+        
+        assert!(output.status.success(), "Compilation should succeed: {}", String::from_utf8_lossy(&output.stderr));
+        let ir = String::from_utf8(output.stdout).expect("IR should be valid UTF-8");
+        assert!(ir.starts_with("; ModuleID"), "Stdout should start with the IR module header: {}", ir);
+        assert!(ir.contains("define"), "IR should contain function definitions");
+        // ステータスメッセージ（色付き）は標準エラーに出力される
+        assert!(!ir.contains('\x1b'), "Stdout should not contain ANSI escapes");
+        assert!(!ir.contains("success"), "Stdout should not contain status lines");
+        assert!(String::from_utf8_lossy(&output.stderr).contains("stdout"), "Status should go to stderr");
+        
+        // LLVMのIRパーサーで解析できることを確認する
+        let context = inkwell::context::Context::create();
+        let buffer = inkwell::memory_buffer::MemoryBuffer::create_from_memory_range_copy(ir.as_bytes(), "stdout.ll");
+        assert!(context.create_module_from_ir(buffer).is_ok(), "Stdout should parse as LLVM IR");
+    }
+
+    #[test]
+    fn test_emit_llvm_ir_module_options() {
+        // コード生成の設定がモジュールのsource_filename・ターゲット・フラグに反映されることのテスト
+        let source = r#"
+        package main
+
+        fn main() {
+            println("configured");
+        }
+        "#;
+
+        let temp_file = create_test_file(source, "configured.yuni").expect("Failed to create temp file");
+        let emit_ir = |extra_args: &[&str]| {
+            let output = Command::new(env!("CARGO_BIN_EXE_yunilang"))
+                .args(["compile", "--emit=llvm-ir", "-o", "-", "--target", "x86_64-unknown-linux-gnu"])
+                .args(extra_args)
+                .arg(temp_file.path())
+                .output()
+                .expect("Failed to execute compiler");
+            assert!(output.status.success(), "Compilation should succeed: {}", String::from_utf8_lossy(&output.stderr));
+            String::from_utf8(output.stdout).expect("IR should be valid UTF-8")
+        };
+
+        let ir = emit_ir(&[]);
+        let source_filename = format!("source_filename = \"{}\"", temp_file.path().display());
+        assert!(ir.contains(&source_filename), "IR should record the source file: {}", ir);
+        assert!(ir.contains("target triple = \"x86_64-unknown-linux-gnu\""), "IR should use the requested triple: {}", ir);
+        assert!(ir.contains("target datalayout = \"e-m:e"), "IR should use the target's data layout: {}", ir);
+        assert!(ir.contains("\"PIC Level\", i32 2") && ir.contains("\"PIE Level\", i32 2"), "IR should be position independent by default: {}", ir);
+
+        let ir = emit_ir(&["--no-pic"]);
+        assert!(!ir.contains("PIC Level"), "--no-pic should omit the PIC module flags: {}", ir);
+
+        // 不明なターゲットはエラーになる
+        let output = Command::new(env!("CARGO_BIN_EXE_yunilang"))
+            .args(["compile", "--emit=llvm-ir", "-o", "-", "--target", "nonexistent-unknown-nowhere"])
+            .arg(temp_file.path())
+            .output()
+            .expect("Failed to execute compiler");
+        assert!(!output.status.success(), "Unknown targets should be rejected");
+        assert!(String::from_utf8_lossy(&output.stderr).contains("nonexistent-unknown-nowhere"));
+    }
+
+    #[test]
+    fn test_emit_llvm_ir_no_names() {
+        // --no-names は関数の中の値の名前を取り除いて出力することのテスト
+        let source = r#"
+        package main
+
+        fn main() {
+            let greeting: String = "named";
+            println(greeting);
+        }
+        "#;
+
+        let temp_file = create_test_file(source, "no_names.yuni").expect("Failed to create temp file");
+        let emit_ir = |extra_args: &[&str]| {
+            let output = Command::new(env!("CARGO_BIN_EXE_yunilang"))
+                .args(["compile", "--emit=llvm-ir", "-o", "-"])
+                .args(extra_args)
+                .arg(temp_file.path())
+                .output()
+                .expect("Failed to execute compiler");
+            assert!(output.status.success(), "Compilation should succeed: {}", String::from_utf8_lossy(&output.stderr));
+            String::from_utf8(output.stdout).expect("IR should be valid UTF-8")
+        };
+
+        // -O1以上ではallocaがレジスタに戻るため、変数の名前は最適化なしで確認する
+        let ir = emit_ir(&["-O", "0"]);
+        assert!(ir.contains("%greeting.addr = alloca ptr"), "Variables should be named: {}", ir);
+        let ir = emit_ir(&["-O", "0", "--no-names"]);
+        assert!(!ir.contains("%greeting"), "Value names should be stripped: {}", ir);
+        assert!(ir.contains("@main.str"), "Global names should be kept: {}", ir);
+    }
+
+    /// 実行時に範囲外になるキャストを含むプログラム
+    const NARROWING_CAST: &str = r#"
+    package main
+
+    fn narrow(x: i64): i32 {
+        return x as i32;
+    }
+
+    fn main() {
+        println(narrow(200));
+        println(narrow(5000000000));
+    }
+    "#;
+
+    #[test]
+    fn test_checked_casts_emit_range_check() {
+        // --checked-casts のときだけ、切り詰めの前に範囲外のパニックへの分岐が生成される
+        let temp_file = create_test_file(NARROWING_CAST, "narrow.yuni").expect("Failed to create temp file");
+        let emit_ir = |extra_args: &[&str]| {
+            let output = Command::new(env!("CARGO_BIN_EXE_yunilang"))
+                .args(["compile", "--emit=llvm-ir", "-o", "-"])
+                .args(extra_args)
+                .arg(temp_file.path())
+                .output()
+                .expect("Failed to execute compiler");
+            assert!(output.status.success(), "Compilation should succeed: {}", String::from_utf8_lossy(&output.stderr));
+            String::from_utf8(output.stdout).expect("IR should be valid UTF-8")
+        };
+
+        let checked = emit_ir(&["--checked-casts"]);
+        assert!(checked.contains("call void @yuni_panic_cast_int"), "Checked casts should call the panic path: {}", checked);
+        let unchecked = emit_ir(&[]);
+        assert!(!unchecked.contains("call void @yuni_panic_cast_int"), "Casts should be unchecked by default: {}", unchecked);
+    }
+
+    #[test]
+    #[ignore] // llc・clangが必要なため通常は無視
+    fn test_run_checked_cast_panics() {
+        // 範囲外のキャストは値と型を示してパニックし、フラグがなければ切り詰める
+        let temp_file = create_test_file(NARROWING_CAST, "narrow.yuni").expect("Failed to create temp file");
+        let run = |extra_args: &[&str]| {
+            Command::new(env!("CARGO_BIN_EXE_yunilang"))
+                .arg("run")
+                .args(extra_args)
+                .arg(temp_file.path())
+                .output()
+                .expect("Failed to execute compiler")
+        };
+
+        let output = run(&["--checked-casts"]);
+        assert!(!output.status.success(), "The failing cast should abort the program");
+        assert_eq!(String::from_utf8_lossy(&output.stdout), "200\n");
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains("キャストで値が失われます: i64 の値 5000000000 は i32 の範囲外です"), "{}", stderr);
+
+        let output = run(&[]);
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+        assert_eq!(String::from_utf8_lossy(&output.stdout), "200\n705032704\n");
+    }
+
+    const ASSERTIONS: &str = r#"
+    package main
+
+    fn main() {
+        let total = 1 + 2;
+        debug_assert(total > 0);
+        println(total);
+        assert_eq(total, 5);
+    }
+    "#;
+
+    #[test]
+    fn test_debug_assert_compiles_to_nothing_when_optimizing() {
+        // -O2 では debug_assert のメッセージの文字列定数も生成しない
+        let temp_file = create_test_file(ASSERTIONS, "assert.yuni").expect("Failed to create temp file");
+        let emit_ir = |extra_args: &[&str]| {
+            let output = Command::new(env!("CARGO_BIN_EXE_yunilang"))
+                .args(["compile", "--emit=llvm-ir", "-o", "-"])
+                .args(extra_args)
+                .arg(temp_file.path())
+                .output()
+                .expect("Failed to execute compiler");
+            assert!(output.status.success(), "Compilation should succeed: {}", String::from_utf8_lossy(&output.stderr));
+            String::from_utf8(output.stdout).expect("IR should be valid UTF-8")
+        };
+
+        let optimized = emit_ir(&["-O", "2"]);
+        assert!(!optimized.contains("assertion failed: total > 0"), "debug_assert should be removed: {}", optimized);
+        assert!(optimized.contains("assertion failed: total == 5"), "assert_eq should be kept: {}", optimized);
+        let debug = emit_ir(&["-O", "0"]);
+        assert!(debug.contains("assertion failed: total > 0"), "debug_assert should be checked at -O0: {}", debug);
+        let forced = emit_ir(&["-O", "2", "--debug-assertions"]);
+        assert!(forced.contains("assertion failed: total > 0"), "--debug-assertions should keep the check: {}", forced);
+    }
+
+    #[test]
+    fn test_getter_in_loop_is_inlined_when_optimizing() {
+        // -O1以上ではインライナーが小さいゲッターの呼び出しを展開する
+        let source = r#"
+        package main
+
+        type Point struct {
+            x: i32,
+        }
+
+        impl fn get_x(self: &Point): i32 {
+            return self.x;
+        }
+
+        fn main() {
+            let p = Point { x: 3 };
+            let mut total = 0;
+            let mut i = 0;
+            while i < 10 {
+                total = total + p.get_x();
+                i = i + 1;
+            }
+            println(total);
+        }
+        "#;
+
+        let temp_file = create_test_file(source, "getter.yuni").expect("Failed to create temp file");
+        let emit_ir = |opt_level: &str| {
+            let output = Command::new(env!("CARGO_BIN_EXE_yunilang"))
+                .args(["compile", "--emit=llvm-ir", "-o", "-", "-O", opt_level])
+                .arg(temp_file.path())
+                .output()
+                .expect("Failed to execute compiler");
+            assert!(output.status.success(), "Compilation should succeed: {}", String::from_utf8_lossy(&output.stderr));
+            String::from_utf8(output.stdout).expect("IR should be valid UTF-8")
+        };
+
+        let optimized = emit_ir("2");
+        assert!(!optimized.contains("call i32 @Point_get_x"), "The getter should be inlined at -O2: {}", optimized);
+        let debug = emit_ir("0");
+        assert!(debug.contains("call i32 @Point_get_x"), "The getter should be called at -O0: {}", debug);
+    }
+
+    #[test]
+    #[ignore] // llc・clangが必要なため通常は無視
+    fn test_run_assert_eq_failure() {
+        // 失敗したアサーションは式・位置・両辺の値を示して異常終了する
+        let temp_file = create_test_file(ASSERTIONS, "assert.yuni").expect("Failed to create temp file");
+        let output = Command::new(env!("CARGO_BIN_EXE_yunilang"))
+            .arg("run")
+            .arg(temp_file.path())
+            .output()
+            .expect("Failed to execute compiler");
+
+        assert!(!output.status.success(), "The failing assertion should abort the program");
+        assert_eq!(String::from_utf8_lossy(&output.stdout), "3\n");
+        let file_name = temp_file.path().file_name().unwrap().to_string_lossy();
+        let expected = format!("assertion failed: total == 5 (at {}:8): left = 3, right = 5", file_name);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains(&expected), "{}", stderr);
+    }
+
+    /// 見つからないキーの値への参照（null）を読み出すプログラム
+    const NULL_DEREF: &str = r#"
+    package main
+
+    fn main() {
+        let ages = HashMap<String, i32> { "alice": 30 };
+        let found = &ages["alice"];
+        println(*found);
+        let missing = &ages["bob"];
+        println(*missing);
+    }
+    "#;
+
+    #[test]
+    fn test_null_checks_removed_by_flag() {
+        // 参照の読み出しの前のnullの検査は既定で生成され、--no-null-checks で取り除かれる
+        let temp_file = create_test_file(NULL_DEREF, "null_deref.yuni").expect("Failed to create temp file");
+        let emit_ir = |extra_args: &[&str]| {
+            let output = Command::new(env!("CARGO_BIN_EXE_yunilang"))
+                .args(["compile", "--emit=llvm-ir", "-o", "-"])
+                .args(extra_args)
+                .arg(temp_file.path())
+                .output()
+                .expect("Failed to execute compiler");
+            assert!(output.status.success(), "Compilation should succeed: {}", String::from_utf8_lossy(&output.stderr));
+            String::from_utf8(output.stdout).expect("IR should be valid UTF-8")
+        };
+
+        let checked = emit_ir(&[]);
+        assert!(checked.contains("dereferenced null at "), "Dereferences should be checked by default: {}", checked);
+        assert!(checked.contains("null.fail"), "{}", checked);
+        let unchecked = emit_ir(&["--no-null-checks"]);
+        assert!(!unchecked.contains("dereferenced null"), "--no-null-checks should remove the check: {}", unchecked);
+        assert!(!unchecked.contains("null.fail"), "{}", unchecked);
+    }
+
+    #[test]
+    #[ignore] // llc・clangが必要なため通常は無視
+    fn test_run_null_dereference_panics() {
+        // nullの参照の読み出しはシグナルではなく、位置を示したパニックで異常終了する
+        let temp_file = create_test_file(NULL_DEREF, "null_deref.yuni").expect("Failed to create temp file");
+        let output = Command::new(env!("CARGO_BIN_EXE_yunilang"))
+            .arg("run")
+            .arg(temp_file.path())
+            .output()
+            .expect("Failed to execute compiler");
+
+        assert!(!output.status.success(), "The null dereference should abort the program");
+        assert_ne!(output.status.code(), Some(139), "The program should not die with SIGSEGV");
+        assert_eq!(String::from_utf8_lossy(&output.stdout), "30\n");
+        let file_name = temp_file.path().file_name().unwrap().to_string_lossy();
+        let expected = format!("dereferenced null at {}:9:17", file_name);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains(&expected), "{}", stderr);
+    }
+
+    #[test]
+    #[ignore] // llc・clangが必要なため通常は無視
+    fn test_run_vec_index_assignment_out_of_bounds_panics() {
+        // 長さと同じインデックスへの代入は要素を追加せず、長さ・インデックス・位置を示してパニックする
+        let source = r#"
+    package main
+
+    fn main() {
+        let mut v: Vec<i64> = [1, 2, 3];
+        v.resize(4, 0);
+        v[3] = 4;
+        println(v[3]);
+        v[4] = 5;
+    }
+    "#;
+        let temp_file = create_test_file(source, "vec_index.yuni").expect("Failed to create temp file");
+        let output = Command::new(env!("CARGO_BIN_EXE_yunilang"))
+            .arg("run")
+            .arg(temp_file.path())
+            .output()
+            .expect("Failed to execute compiler");
+
+        assert!(!output.status.success(), "The out-of-bounds assignment should abort the program");
+        assert_eq!(String::from_utf8_lossy(&output.stdout), "4\n");
+        let file_name = temp_file.path().file_name().unwrap().to_string_lossy();
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains("インデックスが範囲外のため代入できません: 長さ 4, インデックス 4"), "{}", stderr);
+        assert!(stderr.contains(&format!("(at {}:9:9)", file_name)), "{}", stderr);
+    }
+
+    #[test]
+    #[ignore] // llc・clangが必要なため通常は無視
+    fn test_run_stack_overflow_is_reported() {
+        // 終わらない再帰はシグナルによる異常終了ではなく、メッセージと専用の終了コードで報告される
+        let source = r#"
+        package main
+
+        fn depth(n: i64): i64 {
+            return depth(n + 1) + 1;
+        }
+
+        fn main() {
+            println(depth(0));
+        }
+        "#;
+        let temp_file = create_test_file(source, "overflow.yuni").expect("Failed to create temp file");
+        let output = Command::new(env!("CARGO_BIN_EXE_yunilang"))
+            .args(["run", "-O", "0", "--stack-size", "16777216"])
+            .arg(temp_file.path())
+            .output()
+            .expect("Failed to execute compiler");
+
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert_eq!(output.status.code(), Some(253), "The program should exit with the stack overflow code: {}", stderr);
+        assert!(
+            stderr.contains("stack overflow — consider increasing --stack-size or converting recursion to iteration"),
+            "{}",
+            stderr
+        );
+    }
+
+    #[test]
+    #[ignore] // llc・clangが必要なため通常は無視
+    fn test_gc_sections_shrinks_executable() {
+        // printlnだけを使うプログラムでは、使われないランタイム関数をリンク時に取り除く
+        let source = r#"
+        package main
+
+        fn main() {
+            println("hello");
+        }
+        "#;
+        let temp_file = create_test_file(source, "hello.yuni").expect("Failed to create temp file");
+        let out_dir = tempfile::tempdir().expect("Failed to create temp dir");
+        let build = |name: &str, extra_args: &[&str]| {
+            let executable = out_dir.path().join(name);
+            let output = Command::new(env!("CARGO_BIN_EXE_yunilang"))
+                .arg("compile")
+                .args(extra_args)
+                .arg("-o")
+                .arg(&executable)
+                .arg(temp_file.path())
+                .output()
+                .expect("Failed to execute compiler");
+            assert!(output.status.success(), "Compilation should succeed: {}", String::from_utf8_lossy(&output.stderr));
+            executable
+        };
+
+        let collected = build("hello_gc", &[]);
+        let kept = build("hello_nogc", &["--no-gc-sections"]);
+        let collected_size = fs::metadata(&collected).unwrap().len();
+        let kept_size = fs::metadata(&kept).unwrap().len();
+        assert!(
+            collected_size * 5 <= kept_size * 4,
+            "The executable should be at least 20% smaller: {} bytes with gc-sections, {} bytes without",
+            collected_size,
+            kept_size
+        );
+
+        for executable in [&collected, &kept] {
+            let output = Command::new(executable).output().expect("Failed to run the executable");
+            assert!(output.status.success());
+            assert_eq!(String::from_utf8_lossy(&output.stdout), "hello\n");
+        }
+    }
+
     #[test]
     fn test_memory_safety_integration() {
         // メモリ安全性の統合テスト
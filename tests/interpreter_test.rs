@@ -0,0 +1,1837 @@
+//! インタプリタのテスト
+//!
+//! ツリーウォーキングインタプリタの実行結果を検証する。
+//! 差分テストではコンパイル済みバックエンドと標準出力・終了ステータスが一致することを確認する。
+
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+use yunilang::analyzer::{monomorphize_program_with_types, SemanticAnalyzer, TypeTable};
+use yunilang::assertion::SourceText;
+use yunilang::ast::Program;
+use yunilang::error::{RuntimeError, YuniError};
+use yunilang::interpreter::{Interpreter, Value, CONST_EVAL_STEP_LIMIT};
+use yunilang::lexer::Lexer;
+use yunilang::parser::Parser;
+use yunilang::toolchain::Target;
+
+/// ソースコードを解析・単相化してASTと式の型を返すヘルパー関数
+fn prepare(source: &str) -> (Program, TypeTable) {
+    let lexer = Lexer::new(source);
+    let tokens: Vec<_> = lexer.collect_tokens();
+    let mut parser = Parser::new(tokens);
+    let ast = parser.parse().expect("構文解析に失敗しました");
+    let mut analyzer = SemanticAnalyzer::new();
+    analyzer.analyze(&ast).expect("セマンティック解析に失敗しました");
+    let types = analyzer.type_table.clone();
+    (monomorphize_program_with_types(ast, analyzer.type_table).expect("単相化に失敗しました").0, types)
+}
+
+/// インタプリタでmainを実行し、標準出力と実行結果を返すヘルパー関数
+fn interpret(source: &str) -> (String, Result<i32, YuniError>) {
+    let (ast, types) = prepare(source);
+    let mut output = Vec::new();
+    let result = Interpreter::with_output(&ast, Box::new(&mut output)).with_type_table(types).run_main();
+    (String::from_utf8(output).expect("出力がUTF-8ではありません"), result)
+}
+
+/// スクリプトとして解析してインタプリタで実行し、標準出力と実行結果を返すヘルパー関数
+fn interpret_script(source: &str) -> (String, Result<i32, YuniError>) {
+    let tokens: Vec<_> = Lexer::new(source).collect_tokens();
+    let ast = Parser::new(tokens).parse_script().expect("構文解析に失敗しました");
+    let mut analyzer = SemanticAnalyzer::new();
+    analyzer.analyze(&ast).expect("セマンティック解析に失敗しました");
+    let types = analyzer.type_table.clone();
+    let ast = monomorphize_program_with_types(ast, analyzer.type_table).expect("単相化に失敗しました").0;
+    let mut output = Vec::new();
+    let result = Interpreter::with_output(&ast, Box::new(&mut output)).with_type_table(types).run_main();
+    (String::from_utf8(output).expect("出力がUTF-8ではありません"), result)
+}
+
+/// 接尾辞のない数値リテラルが要素・引数・matchのアームの型を採用するプログラム
+const LITERAL_CONTEXT: &str = r#"
+package main
+
+fn scale(x: u16): u16 {
+    return x * 2;
+}
+
+fn pick(x: i32): i64 {
+    let big: i64 = match x {
+        1 => 10,
+        _ => 5000000000,
+    };
+    return big;
+}
+
+fn main() {
+    let bytes: Vec<u8> = [1, 2, 255];
+    println(bytes[1] + bytes[2]);
+    println(scale(40000));
+    let x: i32 = 2;
+    let n = match x {
+        1 => 10i64,
+        _ => 5000000000,
+    };
+    println(pick(2), " ", n);
+}
+"#;
+
+/// Vecを並べ替えて二分探索するプログラム
+const VEC_SORT: &str = r#"
+package main
+
+fn descending(a: i64, b: i64): i32 {
+    if a > b {
+        return -1;
+    }
+    if a < b {
+        return 1;
+    }
+    return 0;
+}
+
+fn main() {
+    // 線形合同法の疑似乱数（重複と負の値を含む）
+    let mut values = Vec<i64>[];
+    let mut seed: i64 = 12345;
+    let mut i = 0;
+    while i < 200 {
+        seed = (seed * 1103515245 + 12345) % 2147483648;
+        values.push(seed % 1000 - 500);
+        i = i + 1;
+    }
+    values.sort();
+    let mut ascending = true;
+    let mut j: i64 = 1;
+    while j < values.len() {
+        if values[j - 1] > values[j] {
+            ascending = false;
+        }
+        j = j + 1;
+    }
+    println(ascending, " ", values[0], " ", values[199]);
+    values.sort_by(descending);
+    let mut descending_order = true;
+    j = 1;
+    while j < values.len() {
+        if values[j - 1] < values[j] {
+            descending_order = false;
+        }
+        j = j + 1;
+    }
+    println(descending_order, " ", values[0], " ", values[199]);
+
+    let mut words = Vec<String>["pear", "apple", "fig", "Banana"];
+    words.sort();
+    println(words[0], " ", words[1], " ", words[2], " ", words[3]);
+
+    let primes: Vec<i64> = [2, 3, 5, 7, 11];
+    println(primes.binary_search(7), " ", primes.binary_search(8), " ", primes.binary_search(1));
+}
+"#;
+
+/// `in` でVecの要素・HashMapのキー・部分文字列を調べるプログラム
+const MEMBERSHIP: &str = r#"
+package main
+
+fn has_key(table: &HashMap<String, i32>, key: String): bool {
+    return key in table;
+}
+
+fn main() {
+    let primes: Vec<i64> = [2, 3, 5, 7];
+    if 5 in primes {
+        println("5 is prime");
+    }
+    if !(4 in primes) {
+        println("4 is not prime");
+    }
+
+    let ages = HashMap<String, i32> { "alice": 30, "bob": 25 };
+    println("bob" in ages, " ", "carol" in ages, " ", has_key(&ages, "alice"));
+
+    let text = "hello world";
+    println("world" in text, " ", "World" in text, " ", "" in text);
+
+    let weights: Vec<f64> = [1.5, 0.0 / 0.0];
+    let flags = Vec<bool>[false];
+    println(1.5 in weights, " ", 0.0 / 0.0 in weights, " ", true in flags);
+}
+"#;
+
+/// strとStringを受け渡し、連結・比較するプログラム
+const STRING_VIEWS: &str = r#"
+package main
+
+fn shout(word: str): String {
+    return word + "!";
+}
+
+fn main() {
+    let owned = "yuni".to_string();
+    let view: str = owned.as_str();
+    println(shout("hi"), " ", shout(&owned), " ", shout(view));
+
+    let left: str = "ab";
+    let right: str = "cd";
+    let joined = left + right;
+    println(joined, " ", joined.len());
+    println(joined == "abcd", " ", left == owned, " ", "ab" == "ab".to_string(), " ", left != owned);
+}
+"#;
+
+/// 関数の中で定義した関数（ローカル関数）を呼び出すプログラム
+const LOCAL_FUNCTIONS: &str = r#"
+package main
+
+fn helper(x: i32): i32 {
+    return x + 100;
+}
+
+fn first(x: i32): i32 {
+    fn helper(x: i32): i32 {
+        x * 2
+    }
+    return helper(x);
+}
+
+fn second(x: i32): i32 {
+    fn helper(x: i32): i32 {
+        x * 3
+    }
+    fn factorial(n: i32): i32 {
+        if n <= 1 {
+            return 1;
+        }
+        return n * factorial(n - 1);
+    }
+    return helper(x) + factorial(4);
+}
+
+fn main() {
+    println(helper(1), " ", first(5), " ", second(5));
+    {
+        fn square(x: i32): i32 {
+            x * x
+        }
+        println(square(7));
+    }
+}
+"#;
+
+/// Vecの要素から3段のフィールドアクセスを辿るプログラム
+const VEC_ELEMENT_FIELD_CHAIN: &str = r#"
+package main
+
+type Address struct {
+    zip: i32
+}
+
+type Customer struct {
+    id: i32,
+    address: Address
+}
+
+type Order struct {
+    customer: Customer,
+    total: f64
+}
+
+fn main() {
+    let orders = Vec<Order>[
+        Order { customer: Customer { id: 7, address: Address { zip: 100 } }, total: 1.5 },
+        Order { customer: Customer { id: 8, address: Address { zip: 200 } }, total: 2.5 }
+    ];
+    let i: i32 = 1;
+    println(orders[i].customer.address.zip, " ", orders[0].customer.id, " ", orders[i].total);
+}
+"#;
+
+/// matchで束縛したEnumのペイロードのフィールドを読むプログラム
+const MATCH_BOUND_PAYLOAD_FIELD: &str = r#"
+package main
+
+type Item struct {
+    name: String,
+    price: i32
+}
+
+type Lookup enum {
+    Found(item: Item),
+    Missing
+}
+
+fn find(i: i32): Lookup {
+    if i == 0 {
+        return Lookup::Found(Item { name: "apple", price: 120 });
+    }
+    if i == 1 {
+        return Lookup::Found(Item { name: "pear", price: 80 });
+    }
+    return Lookup::Missing;
+}
+
+fn label(i: i32): String {
+    return match find(i) {
+        Lookup::Found(item) if item.price > 100 => "expensive",
+        Lookup::Found(item) => item.name,
+        Lookup::Missing => "none",
+    };
+}
+
+fn main() {
+    let price = match find(0) {
+        Lookup::Found(item) => item.price,
+        Lookup::Missing => 0,
+    };
+    println(price, " ", label(0), " ", label(1), " ", label(2));
+}
+"#;
+
+/// 列挙型名を省略したバリアントを使うプログラム（同名の変数はバリアントより優先される）
+const BARE_ENUM_VARIANTS: &str = r#"
+package main
+
+type Color enum {
+    Red,
+    Green,
+    Blue
+}
+
+type Shape enum {
+    Circle(r: i32),
+    Dot
+}
+
+fn code(c: Color): i32 {
+    return match c {
+        Red => 1,
+        Green => 2,
+        Blue => 3,
+    };
+}
+
+fn area(s: Shape): i32 {
+    return match s {
+        Circle(r) => r * r,
+        Dot => 0,
+    };
+}
+
+fn shadowed(): i32 {
+    let Red: Color = Color::Green;
+    return code(Red);
+}
+
+fn main() {
+    let c: Color = Green;
+    let mut d: Color = Red;
+    d = Blue;
+    println(code(c), code(d), shadowed(), " ", area(Shape::Circle(3)), " ", area(Dot));
+}
+"#;
+
+const STRING_BUILDER: &str = r#"
+package main
+
+fn fill(sb: &StringBuilder, n: i32) {
+    let mut i: i32 = 0;
+    while i < n {
+        sb.append(i);
+        sb.append(",");
+        i = i + 1;
+    }
+}
+
+fn main() {
+    let sb = StringBuilder{};
+    fill(&sb, 5);
+    sb.append(true);
+    sb.append(" ");
+    sb.append(2.5);
+    println(sb.to_string(), " ", sb.len());
+    let name = "yuni";
+    let n: i64 = 3;
+    println(`${name}: n=${n}, half=${0.5}, ok=${n > 2}`);
+}
+"#;
+
+/// ネストしたforループで掛け算の表をVecに書き込み、whileループで読み出すプログラム
+const NESTED_LOOPS: &str = r#"
+package main
+
+fn main() {
+    let mut table = Vec<i32>[0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+    for let mut i: i32 = 0; i < 3; i = i + 1 {
+        for let mut j: i32 = 0; j < 4; j = j + 1 {
+            let product: i32 = (i + 1) * (j + 1);
+            table[i * 4 + j] = product;
+        }
+    }
+
+    let mut row: i32 = 0;
+    while row < 3 {
+        let base: i32 = row * 4;
+        println(table[base], " ", table[base + 1], " ", table[base + 2], " ", table[base + 3]);
+        row = row + 1;
+    }
+
+    // 条件は本体で更新した変数を毎回読み直す
+    let mut remaining: i32 = 10;
+    let mut steps: i32 = 0;
+    while remaining > 0 {
+        let step: i32 = 3;
+        remaining = remaining - step;
+        steps = steps + 1;
+    }
+    println("steps=", steps, " remaining=", remaining);
+}
+"#;
+
+/// 論理否定・ビット反転・符号反転を使うプログラム
+const UNARY_OPERATORS: &str = r#"
+package main
+
+fn main() {
+    let flags: i32 = 5;
+    let mask: u8 = 0u8;
+    let small: u8 = 3u8;
+    let done: bool = false;
+    if !done {
+        println(~flags, " ", ~mask, " ", -small, " ", ~~flags, " ", !(flags > 3));
+    }
+}
+"#;
+
+const NUMERIC_CONSTANTS: &str = r#"
+package main
+
+fn saturating_add(a: i32, b: i32): i32 {
+    if b > 0 && a > i32::MAX - b {
+        return i32::MAX;
+    }
+    if b < 0 && a < i32::MIN - b {
+        return i32::MIN;
+    }
+    return a + b;
+}
+
+fn main() {
+    println(saturating_add(2147483600, 100), " ", saturating_add(-2147483600, -100), " ", saturating_add(40, 2));
+    let nan: f64 = f64::NAN;
+    println(f64::INFINITY, " ", f64::NEG_INFINITY, " ", nan);
+    println(nan != nan, " ", nan == nan, " ", nan < 1.0, " ", nan >= 1.0);
+    println(u8::MAX, " ", i64::MIN, " ", f64::EPSILON > 0.0, " ", f32::MAX);
+}
+"#;
+
+const CONTEXTUAL_NUMERIC_LITERALS: &str = r#"
+package main
+
+struct Circle {
+    radius: f64,
+}
+
+fn halve(x: f32): f32 {
+    return x / 2.0f32;
+}
+
+fn main() {
+    let c = Circle { radius: 3 };
+    println(c.radius / 2.0, " ", halve(5), " ", halve(0.75));
+}
+"#;
+
+/// 組み込み型のメソッド（len・to_string・abs・min・max）を呼び出すプログラム
+const BUILTIN_METHODS: &str = r#"
+package main
+
+fn count(v: &Vec<i32>): i64 {
+    return v.len();
+}
+
+fn main() {
+    let s = "hello";
+    let v = Vec<i32>[3, 1, 4, 1, 5];
+    let m = HashMap<String, i32> { "a": 1, "b": 2 };
+    println(s.len(), " ", v.len(), " ", m.len(), " ", count(&v));
+    let n: i32 = -42;
+    let label: String = n.to_string();
+    println(label, " ", n.abs(), " ", n.min(7), " ", n.max(7));
+    let big: u8 = 200u8;
+    println(big.to_string(), " ", big.min(100u8), " ", big.max(100u8).to_string());
+    let x: f64 = -2.5;
+    println(x.to_string(), " ", x.abs(), " ", x.min(1.5), " ", x.max(1.5));
+}
+"#;
+
+/// HashMapのインデックスで読み書きし、値のVecをその場で変更するプログラム
+const HASHMAP_INDEX: &str = r#"
+package main
+
+fn main() {
+    let mut scores = HashMap<String, i32> { "alice": 1 };
+    scores["bob"] = 5;
+    scores["alice"] = scores["alice"] + 10;
+    println(scores["alice"], " ", scores["bob"], " ", scores.len());
+    let mut groups = HashMap<String, Vec<i32> > { "a": Vec<i32>[] };
+    groups["a"].push(1);
+    groups["a"].push(2);
+    println(groups["a"].len(), " ", groups["a"][1]);
+    println(scores["carol"]);
+}
+"#;
+
+/// 整数・真偽値・文字列のリテラルと範囲でmatchするプログラム
+const LITERAL_MATCH: &str = r#"
+package main
+
+fn status_text(code: i32, cached: bool): String {
+    return match code {
+        200 => "OK",
+        204 => "No Content",
+        304 if cached => "Not Modified",
+        301..=308 => "Redirect",
+        404 => "Not Found",
+        400..500 => "Client Error",
+        500..=599 => "Server Error",
+        _ => "Unknown",
+    };
+}
+
+fn run(command: String, n: i32): i32 {
+    return match command {
+        "inc" => n + 1,
+        "dec" => n - 1,
+        "double" => n * 2,
+        _ => n,
+    };
+}
+
+fn flag(b: bool): i32 {
+    return match b {
+        true => 1,
+        false => 0,
+    };
+}
+
+fn main() {
+    println(status_text(200, false), ",", status_text(204, false), ",", status_text(304, true));
+    println(status_text(304, false), ",", status_text(404, false), ",", status_text(418, false));
+    println(status_text(503, false), ",", status_text(600, false), ",", status_text(-1, false));
+    println(run("inc", 10), " ", run("dec", 10), " ", run("double", 10), " ", run("noop", 10));
+    println(flag(true), flag(false));
+}
+"#;
+
+/// 分岐の途中でreturnするif式・match式・ブロック式の値を使うプログラム
+const EARLY_RETURN_BRANCHES: &str = r#"
+package main
+
+fn halve_even(n: i32): i32 {
+    let half = if n % 2 == 1 { return -1; } else { n / 2 };
+    return half * 10;
+}
+
+fn digit_value(c: i32): i32 {
+    let value = match c {
+        48..=57 => c - 48,
+        _ => { return -1; },
+    };
+    return value + 100;
+}
+
+fn first_positive(a: i32, b: i32): i32 {
+    let found = {
+        if a > 0 {
+            return a;
+        };
+        b
+    };
+    return found * 2;
+}
+
+fn sign(x: i32): i32 {
+    if x < 0 { return -1; } else { return 1; }
+}
+
+fn main() {
+    println(`${halve_even(8)} ${halve_even(7)}`);
+    println(`${digit_value(55)} ${digit_value(65)}`);
+    println(`${first_positive(3, 4)} ${first_positive(-3, 4)}`);
+    println(`${sign(-5)} ${sign(5)}`);
+}
+"#;
+
+/// バイト列リテラルの要素を添字で読んで合計するプログラム
+const BYTES_LITERAL: &str = r#"
+package main
+
+fn checksum(data: Vec<u8>): i64 {
+    let mut sum: i64 = 0;
+    for let mut i: i64 = 0; i < data.len(); i = i + 1 {
+        sum = sum + data[i] as i64;
+    }
+    return sum;
+}
+
+fn main() {
+    let frame = b"A\x00\xff\n\"";
+    let last: u8 = frame[4];
+    println(`${frame.len()} ${frame[0]} ${frame[1]} ${frame[2]} ${last}`);
+    println(`${checksum(frame)} ${checksum(b"")}`);
+}
+"#;
+
+/// print・println・eprintln で書式指定子を含む文字列を出力するプログラム
+const CONSOLE_OUTPUT: &str = r#"
+package main
+
+fn main() {
+    let user: String = "%s%s%s%n";
+    print("a");
+    print(`b${1 + 1}`);
+    println("");
+    println(user);
+    println("user: ", user, " ", 42);
+    eprintln(`error: ${user}`);
+    println();
+}
+"#;
+
+/// 差分テストで使用するプログラム
+///
+/// どちらのバックエンドでもサポートされている機能のみを使用すること。
+const DIFFERENTIAL_PROGRAMS: &[(&str, &str)] = &[
+    (
+        "arithmetic",
+        r#"
+package main
+
+fn main() {
+    let a: i32 = 17;
+    let b: i32 = 5;
+    println(a + b, " ", a - b, " ", a * b, " ", a / b, " ", a % b);
+    println(-17 / 5, " ", -17 % 5);
+    println(1 << 4, " ", 255 & 15, " ", 8 | 3, " ", 6 ^ 3);
+}
+"#,
+    ),
+    (
+        "bitwise",
+        r#"
+package main
+
+fn main() {
+    let x: i32 = -16;
+    let s: i32 = 2;
+    println(x >> s, " ", x << s, " ", x & 7, " ", x | 3, " ", x ^ -1);
+    let u: u8 = 240u8;
+    let n: u8 = 4u8;
+    println(u >> n, " ", u << n, " ", u & 60u8, " ", u | 15u8, " ", u ^ 255u8);
+    let big: i64 = 1;
+    println(big << 40, " ", (big << 63) >> 63);
+}
+"#,
+    ),
+    (
+        "wraparound",
+        r#"
+package main
+
+fn main() {
+    let x: i8 = 100i8;
+    let y: i8 = x + x;
+    println(y);
+    let big: i32 = 2147483647;
+    println(big + 1);
+    let u: u8 = 250u8;
+    println(u + 10u8);
+}
+"#,
+    ),
+    (
+        "control_flow",
+        r#"
+package main
+
+fn fib(n: i32): i32 {
+    if n < 2 {
+        return n;
+    }
+    return fib(n - 1) + fib(n - 2);
+}
+
+fn main() {
+    let mut i: i32 = 0;
+    let mut sum: i32 = 0;
+    while i < 10 {
+        sum = sum + i;
+        i = i + 1;
+    }
+    println("sum=", sum);
+    println("fib=", fib(15));
+    println(sum > 40, " ", sum == 0);
+}
+"#,
+    ),
+    (
+        "structs",
+        r#"
+package main
+
+type Point struct {
+    x: i32,
+    y: i32
+}
+
+fn manhattan(p: Point): i32 {
+    return p.x + p.y;
+}
+
+fn main() {
+    let mut p: Point = Point { x: 3, y: 4 };
+    p.x = 10;
+    println(p.x, ",", p.y);
+    println(manhattan(p));
+}
+"#,
+    ),
+    (
+        "floats",
+        r#"
+package main
+
+fn main() {
+    let a: f64 = 1.5;
+    let b: f64 = 0.25;
+    println(a + b, " ", a * b, " ", a / b);
+}
+"#,
+    ),
+    (
+        "division_by_zero",
+        r#"
+package main
+
+fn main() {
+    let zero: i32 = 0;
+    println("before");
+    println(10 / zero);
+}
+"#,
+    ),
+    (
+        "defer",
+        r#"
+package main
+
+fn compute(x: i32): i32 {
+    let mut result: i32 = x;
+    defer {
+        println("cleanup ", result);
+    }
+    if x > 10 {
+        defer {
+            println("early");
+        }
+        return x;
+    }
+    result = result * 2;
+    return result;
+}
+
+fn main() {
+    defer {
+        println("main done");
+    }
+    println(compute(3));
+    println(compute(20));
+}
+"#,
+    ),
+    (
+        "implicit_return",
+        r#"
+package main
+
+fn add(a: i32, b: i32): i32 {
+    a + b
+}
+
+fn abs(x: i32): i32 {
+    if x < 0 {
+        -x
+    } else {
+        x
+    }
+}
+
+fn main() {
+    println(add(2, 3), " ", abs(-7), " ", abs(4));
+}
+"#,
+    ),
+    ("vec_element_field_chain", VEC_ELEMENT_FIELD_CHAIN),
+    ("match_bound_payload_field", MATCH_BOUND_PAYLOAD_FIELD),
+    ("bare_enum_variants", BARE_ENUM_VARIANTS),
+    ("string_builder", STRING_BUILDER),
+    ("nested_loops", NESTED_LOOPS),
+    ("unary_operators", UNARY_OPERATORS),
+    ("contextual_numeric_literals", CONTEXTUAL_NUMERIC_LITERALS),
+    ("numeric_constants", NUMERIC_CONSTANTS),
+    ("builtin_methods", BUILTIN_METHODS),
+    ("literal_match", LITERAL_MATCH),
+    ("console_output", CONSOLE_OUTPUT),
+    ("early_return_branches", EARLY_RETURN_BRANCHES),
+    ("bytes_literal", BYTES_LITERAL),
+    ("hashmap_index", HASHMAP_INDEX),
+    (
+        "overload_by_arity",
+        r#"
+package main
+
+fn area(side: f64): f64 {
+    return side * side;
+}
+
+fn area(width: f64, height: f64): f64 {
+    return width * height;
+}
+
+fn main() {
+    println(area(3.0), " ", area(2.0, 5.0));
+}
+"#,
+    ),
+    ("literal_context", LITERAL_CONTEXT),
+    ("vec_sort", VEC_SORT),
+    ("membership", MEMBERSHIP),
+    ("string_views", STRING_VIEWS),
+    ("local_functions", LOCAL_FUNCTIONS),
+];
+
+#[test]
+fn test_println_concatenates_arguments() {
+    let (output, result) = interpret(
+        r#"
+package main
+
+fn main() {
+    println("Value is ", 42, " and ", true);
+    println();
+}
+"#,
+    );
+    assert_eq!(result.unwrap(), 0);
+    assert_eq!(output, "Value is 42 and true\n\n");
+}
+
+#[test]
+fn test_integer_overflow_wraps_to_type_width() {
+    let (output, result) = interpret(
+        r#"
+package main
+
+fn main() {
+    let x: i8 = 127i8;
+    println(x + 1i8);
+    let y: u16 = 0u16;
+    println(y - 1u16);
+    let z: i64 = 9223372036854775807i64;
+    println(z + 1i64);
+}
+"#,
+    );
+    assert_eq!(result.unwrap(), 0);
+    assert_eq!(output, "-128\n65535\n-9223372036854775808\n");
+}
+
+#[test]
+fn test_division_truncates_toward_zero() {
+    let (output, result) = interpret(
+        r#"
+package main
+
+fn main() {
+    println(-7 / 2, " ", -7 % 2, " ", 7 / -2, " ", 7 % -2);
+}
+"#,
+    );
+    assert_eq!(result.unwrap(), 0);
+    assert_eq!(output, "-3 -1 -3 1\n");
+}
+
+#[test]
+fn test_division_by_zero_is_runtime_error() {
+    let (output, result) = interpret(
+        r#"
+package main
+
+fn main() {
+    let zero: i32 = 0;
+    println("before");
+    println(1 / zero);
+    println("after");
+}
+"#,
+    );
+    // エラー発生前の出力は保持される
+    assert_eq!(output, "before\n");
+    assert!(matches!(
+        result,
+        Err(YuniError::Runtime(RuntimeError::DivisionByZero { .. }))
+    ));
+}
+
+#[test]
+fn test_hashmap_index_read_write() {
+    // 代入は既存のキーの値を上書きし、ないキーを追加する。値のVecはその場で変更される
+    let (output, result) = interpret(HASHMAP_INDEX);
+    assert_eq!(output, "11 5 2\n2 2\n");
+    // ないキーの読み出しはキーを示すエラー
+    match result {
+        Err(YuniError::Runtime(RuntimeError::InvalidValue { message, .. })) => {
+            assert_eq!(message, "キー carol が見つかりません");
+        }
+        other => panic!("Expected missing key error, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_vec_index_assignment_and_resize() {
+    let (output, result) = interpret(
+        r#"
+package main
+
+fn main() {
+    let mut v: Vec<i64> = [1, 2, 3];
+    v[2] = 30;
+    v.resize(5, 7);
+    v[4] = 40;
+    println(v[2], " ", v[3], " ", v[4], " ", v.len());
+    v.resize(1, 0);
+    println(v.len());
+    v[1] = 2;
+}
+"#,
+    );
+    assert_eq!(output, "30 7 40 5\n1\n");
+    // 長さと同じインデックスへの代入は要素を追加せずにエラーになる
+    assert!(matches!(
+        result,
+        Err(YuniError::Runtime(RuntimeError::IndexAssignmentOutOfBounds { index: 1, len: 1, .. }))
+    ), "{:?}", result);
+}
+
+#[test]
+fn test_struct_field_assignment_and_copy() {
+    let (output, result) = interpret(
+        r#"
+package main
+
+type Counter struct {
+    count: i32,
+    step: i32
+}
+
+fn advance(c: Counter): i32 {
+    return c.count + c.step;
+}
+
+fn main() {
+    let mut c: Counter = Counter { count: 1, step: 2 };
+    c.count = c.count * 10;
+    println(c.count, " ", advance(c));
+}
+"#,
+    );
+    assert_eq!(result.unwrap(), 0);
+    assert_eq!(output, "10 12\n");
+}
+
+#[test]
+fn test_match_on_enum_variants() {
+    let (output, result) = interpret(
+        r#"
+package main
+
+type Color enum {
+    Red,
+    Green,
+    Blue
+}
+
+fn code(c: Color): i32 {
+    return match c {
+        Color::Red => 1,
+        Color::Green => 2,
+        Color::Blue => 3,
+    };
+}
+
+fn main() {
+    println(code(Color::Red), code(Color::Green), code(Color::Blue));
+}
+"#,
+    );
+    assert_eq!(result.unwrap(), 0);
+    assert_eq!(output, "123\n");
+}
+
+#[test]
+fn test_or_and_binding_patterns() {
+    // orパターンは選択肢のいずれかに一致したアームを実行し、@ は一致した値全体を束縛する
+    let (output, result) = interpret(
+        r#"
+package main
+
+type Shape enum {
+    Circle(r: i32),
+    Square(side: i32),
+    Triangle(base: i32),
+    Dot
+}
+
+fn size(shape: Shape): i32 {
+    return match shape {
+        Shape::Circle(n) | Shape::Square(n) | Shape::Triangle(n) => n,
+        Shape::Dot => 0,
+    };
+}
+
+fn classify(code: i32): i32 {
+    return match code {
+        d @ 0..=9 => d * 10,
+        n @ (100 | 200) => n + 1,
+        _ => -1,
+    };
+}
+
+fn main() {
+    println(size(Shape::Circle(1)), size(Shape::Square(2)), size(Shape::Triangle(3)), size(Shape::Dot));
+    println(classify(7), classify(200), classify(50));
+}
+"#,
+    );
+    assert_eq!(result.unwrap(), 0);
+    assert_eq!(output, "1230\n70201-1\n");
+}
+
+#[test]
+fn test_destructuring_let() {
+    // let文でタプルと構造体を分解し、mutを付けた要素を変更する
+    let (output, result) = interpret(
+        r#"
+package main
+
+type Point struct {
+    x: i32,
+    y: i32
+}
+
+fn divmod(a: i32, b: i32): (i32, i32) {
+    return (a / b, a % b);
+}
+
+fn main() {
+    let (q, r) = divmod(17, 5);
+    let (a, (b, _)) = (1, (2, 3));
+    let Point { x, y: height } = Point { x: 4, y: 5 };
+    let (mut total, step) = (10, 7);
+    total = total + step;
+    println(q, r, a, b, x, height, total);
+}
+"#,
+    );
+    assert_eq!(result.unwrap(), 0);
+    assert_eq!(output, "32124517\n");
+}
+
+#[test]
+fn test_underscore_parameters_and_bindings() {
+    // `_` の引数・let・要素・matchの腕は値を捨てる
+    let (output, result) = interpret(
+        r#"
+package main
+
+fn middle(_: i32, value: i32, _: i32): i32 {
+    return value;
+}
+
+fn main() {
+    let _ = middle(1, 2, 3);
+    let (a, _, c) = (4, 5, 6);
+    let kind = match a {
+        0 => "zero",
+        _ => "other",
+    };
+    println(middle(7, 8, 9), " ", a, c, " ", kind);
+}
+"#,
+    );
+    assert_eq!(result.unwrap(), 0);
+    assert_eq!(output, "8 46 other\n");
+}
+
+#[test]
+fn test_float_formatting() {
+    // 浮動小数点数はprintlnでもテンプレート文字列でも、同じ値に読み戻せる最短の桁数で出力する
+    let source = format!(
+        r#"
+package main
+
+fn show(x: f64) {{
+    println(x);
+    println(`[${{x}}]`);
+}}
+
+fn main() {{
+    let zero = 0.0;
+    show(0.1);
+    show(1000000000000000000000.0);
+    show(-0.0);
+    show({max}.0);
+    show(zero / zero);
+    show(1.0 / zero);
+    show(-1.0 / zero);
+    let single: f32 = 0.1;
+    println(single);
+    println(`[${{single}}]`);
+}}
+"#,
+        max = f64::MAX
+    );
+    let (output, result) = interpret(&source);
+    assert_eq!(result.unwrap(), 0);
+
+    let lines: Vec<&str> = output.lines().collect();
+    let expected = ["0.1", "1000000000000000000000", "-0", &f64::MAX.to_string(), "NaN", "inf", "-inf", "0.1"];
+    assert_eq!(lines.len(), expected.len() * 2, "{}", output);
+    for (pair, expected) in lines.chunks(2).zip(expected) {
+        assert_eq!(pair[0], expected);
+        assert_eq!(pair[1], format!("[{}]", expected));
+    }
+}
+
+#[test]
+fn test_immutable_variable_cannot_be_reassigned() {
+    // セマンティック解析を経由せずにインタプリタ単体の検査を確認する
+    let lexer = Lexer::new(
+        r#"
+package main
+
+fn main() {
+    let x: i32 = 1;
+    x = 2;
+}
+"#,
+    );
+    let tokens: Vec<_> = lexer.collect_tokens();
+    let ast = Parser::new(tokens).parse().expect("構文解析に失敗しました");
+    let result = Interpreter::with_output(&ast, Box::new(std::io::sink())).run_main();
+    assert!(matches!(
+        result,
+        Err(YuniError::Runtime(RuntimeError::InvalidValue { .. }))
+    ));
+}
+
+#[test]
+fn test_main_return_value_is_exit_code() {
+    let (_, result) = interpret(
+        r#"
+package main
+
+fn main(): i32 {
+    return 3;
+}
+"#,
+    );
+    assert_eq!(result.unwrap(), 3);
+}
+
+#[test]
+fn test_literals_take_decided_type() {
+    // 期待される型のないmatchのアームも、解析で決まった型（i64）で評価する
+    let (output, result) = interpret(LITERAL_CONTEXT);
+    assert_eq!(result.unwrap(), 0);
+    assert_eq!(output, "1\n14464\n5000000000 5000000000\n");
+}
+
+#[test]
+fn test_membership_operator() {
+    let (output, result) = interpret(MEMBERSHIP);
+    assert_eq!(result.unwrap(), 0);
+    assert_eq!(
+        output,
+        "5 is prime\n4 is not prime\ntrue false true\ntrue false true\ntrue true false\n"
+    );
+}
+
+#[test]
+fn test_str_and_string_views() {
+    let (output, result) = interpret(STRING_VIEWS);
+    assert_eq!(result.unwrap(), 0);
+    assert_eq!(output, "hi! yuni! yuni!\nabcd 4\ntrue false true true\n");
+}
+
+#[test]
+fn test_local_functions() {
+    // 同じ名前のトップレベルの関数よりローカル関数を優先し、別々の関数のローカル関数は衝突しない
+    let (output, result) = interpret(LOCAL_FUNCTIONS);
+    assert_eq!(result.unwrap(), 0);
+    assert_eq!(output, "101 10 39\n49\n");
+}
+
+#[test]
+fn test_vec_sort_and_binary_search() {
+    let (output, result) = interpret(VEC_SORT);
+    assert_eq!(result.unwrap(), 0);
+    let lines: Vec<&str> = output.lines().collect();
+    assert!(lines[0].starts_with("true "), "{}", output);
+    assert!(lines[1].starts_with("true "), "{}", output);
+    assert_eq!(lines[2], "Banana apple fig pear");
+    assert_eq!(lines[3], "3 -1 -1");
+}
+
+#[test]
+fn test_eval_const_functions() {
+    let (ast, types) = prepare(
+        r#"
+package main
+
+fn answer(): i32 {
+    return 6 * 7;
+}
+
+fn greeting(): String {
+    return "hello";
+}
+
+fn add(a: i32, b: i32): i32 {
+    return a + b;
+}
+
+fn main() {
+}
+"#,
+    );
+    let results = Interpreter::eval_const_functions(&ast, types);
+
+    // 引数を取る関数とvoid関数は評価対象外
+    let names: Vec<&str> = results.iter().map(|(name, _)| name.as_str()).collect();
+    assert_eq!(names, vec!["answer", "greeting"]);
+
+    let answer = results[0].1.as_ref().expect("answerの評価に失敗しました");
+    assert_eq!(*answer, Value::Int(42, yunilang::ast::Type::I32));
+    let greeting = results[1].1.as_ref().expect("greetingの評価に失敗しました");
+    assert_eq!(greeting.to_string(), "hello");
+}
+
+#[test]
+fn test_eval_const_functions_stops_runaway_functions() {
+    // 止まらないループと終わらない再帰は手順数と呼び出しの深さの上限でエラーにし、mainは評価しない
+    let (ast, types) = prepare(
+        r#"
+package main
+
+fn spin(): i32 {
+    let mut i: i32 = 0;
+    while i >= 0 {
+        i = i + 1;
+        i = i - 1;
+    }
+    return i;
+}
+
+fn forever(): i32 {
+    return forever();
+}
+
+fn answer(): i32 {
+    return 42;
+}
+
+fn main(): i32 {
+    return spin();
+}
+"#,
+    );
+    // 再帰の上限に達するまでのスタックを確保する（`check --run-const` と同じ）
+    let results = std::thread::Builder::new()
+        .stack_size(256 * 1024 * 1024)
+        .spawn(move || {
+            Interpreter::eval_const_functions(&ast, types)
+                .into_iter()
+                .map(|(name, result)| (name, result.map(|value| value.to_string())))
+                .collect::<Vec<_>>()
+        })
+        .unwrap()
+        .join()
+        .unwrap();
+
+    let names: Vec<&str> = results.iter().map(|(name, _)| name.as_str()).collect();
+    assert_eq!(names, vec!["spin", "forever", "answer"]);
+    assert!(
+        matches!(results[0].1, Err(YuniError::Runtime(RuntimeError::StepLimitExceeded { limit: CONST_EVAL_STEP_LIMIT, .. }))),
+        "{:?}",
+        results[0].1
+    );
+    assert!(matches!(results[1].1, Err(YuniError::Runtime(_))), "{:?}", results[1].1);
+    // 上限は関数ごとに数える
+    assert_eq!(results[2].1.as_ref().expect("answerの評価に失敗しました"), "42");
+}
+
+#[test]
+fn test_defers_run_in_reverse_order() {
+    let (output, result) = interpret(
+        r#"
+package main
+
+fn main() {
+    defer {
+        println("first");
+    }
+    defer {
+        println("second");
+    }
+    println("body");
+}
+"#,
+    );
+    assert_eq!(result.unwrap(), 0);
+    assert_eq!(output, "body\nsecond\nfirst\n");
+}
+
+#[test]
+fn test_defer_runs_before_early_return() {
+    let (output, result) = interpret(
+        r#"
+package main
+
+fn check(x: i32) {
+    defer {
+        println("outer");
+    }
+    if x > 0 {
+        defer {
+            println("inner");
+        }
+        println("early");
+        return;
+    }
+    println("late");
+}
+
+fn main() {
+    check(1);
+    check(0);
+}
+"#,
+    );
+    assert_eq!(result.unwrap(), 0);
+    assert_eq!(output, "early\ninner\nouter\nlate\nouter\n");
+}
+
+#[test]
+fn test_return_value_is_evaluated_before_defers() {
+    let (output, result) = interpret(
+        r#"
+package main
+
+fn counter(): i32 {
+    let mut count: i32 = 1;
+    defer {
+        count = count + 10;
+        println("deferred ", count);
+    }
+    return count;
+}
+
+fn main() {
+    println("returned ", counter());
+}
+"#,
+    );
+    assert_eq!(result.unwrap(), 0);
+    assert_eq!(output, "deferred 11\nreturned 1\n");
+}
+
+#[test]
+fn test_trailing_expression_is_return_value() {
+    let (output, result) = interpret(
+        r#"
+package main
+
+fn square(x: i32): i32 {
+    x * x
+}
+
+fn classify(x: i32): i32 {
+    if x < 0 {
+        -1
+    } else if x == 0 {
+        0
+    } else {
+        1
+    }
+}
+
+fn main() {
+    println(square(4), " ", classify(-5), " ", classify(0), " ", classify(9));
+}
+"#,
+    );
+    assert_eq!(result.unwrap(), 0);
+    assert_eq!(output, "16 -1 0 1\n");
+}
+
+#[test]
+fn test_field_chain_through_vec_element() {
+    let (output, result) = interpret(VEC_ELEMENT_FIELD_CHAIN);
+    assert_eq!(result.unwrap(), 0);
+    assert_eq!(output, "200 7 2.5\n");
+}
+
+#[test]
+fn test_field_access_on_match_bound_payload() {
+    let (output, result) = interpret(MATCH_BOUND_PAYLOAD_FIELD);
+    assert_eq!(result.unwrap(), 0);
+    assert_eq!(output, "120 expensive pear none\n");
+}
+
+#[test]
+fn test_bare_enum_variants_resolve_from_context() {
+    let (output, result) = interpret(BARE_ENUM_VARIANTS);
+    assert_eq!(result.unwrap(), 0);
+    assert_eq!(output, "232 9 0\n");
+}
+
+#[test]
+fn test_string_builder_and_template_strings() {
+    let (output, result) = interpret(STRING_BUILDER);
+    assert_eq!(result.unwrap(), 0);
+    assert_eq!(output, "0,1,2,3,4,true 2.5 18\nyuni: n=3, half=0.5, ok=true\n");
+}
+
+#[test]
+fn test_nested_loops_and_mutated_conditions() {
+    let (output, result) = interpret(NESTED_LOOPS);
+    assert_eq!(result.unwrap(), 0);
+    assert_eq!(output, "1 2 3 4\n2 4 6 8\n3 6 9 12\nsteps=4 remaining=-2\n");
+}
+
+#[test]
+fn test_bitwise_not_and_unsigned_negation() {
+    // ビット反転は補数を、符号なし整数の符号反転は型の幅でラップアラウンドした値を返す
+    let (output, result) = interpret(UNARY_OPERATORS);
+    assert_eq!(result.unwrap(), 0);
+    assert_eq!(output, "-6 255 253 5 false\n");
+}
+
+#[test]
+fn test_numeric_associated_constants() {
+    // 関連定数で飽和加算を書け、NaNはIEEE 754に従い自身とも等しくない
+    let (output, result) = interpret(NUMERIC_CONSTANTS);
+    assert_eq!(result.unwrap(), 0);
+    assert_eq!(
+        output,
+        "2147483647 -2147483648 42\ninf -inf NaN\ntrue false false false\n255 -9223372036854775808 true 340282350000000000000000000000000000000\n"
+    );
+}
+
+#[test]
+fn test_numeric_literals_take_field_and_parameter_types() {
+    // 接尾辞のない数値リテラルは、フィールドや引数の型を期待される型として型が決まる
+    let (output, result) = interpret(CONTEXTUAL_NUMERIC_LITERALS);
+    assert_eq!(result.unwrap(), 0);
+    assert_eq!(output, "1.5 2.5 0.375\n");
+}
+
+#[test]
+fn test_builtin_methods() {
+    let (output, result) = interpret(BUILTIN_METHODS);
+    assert_eq!(result.unwrap(), 0);
+    assert_eq!(output, "5 5 2 5\n-42 42 -42 7\n200 100 200\n-2.5 2.5 -2.5 1.5\n");
+}
+
+#[test]
+fn test_literal_match() {
+    // ガードが偽の場合は後ろの範囲のアームへ進む
+    let (output, result) = interpret(LITERAL_MATCH);
+    assert_eq!(result.unwrap(), 0);
+    assert_eq!(
+        output,
+        "OK,No Content,Not Modified\nRedirect,Not Found,Client Error\nServer Error,Unknown,Unknown\n11 9 20 10\n10\n"
+    );
+}
+
+#[test]
+fn test_early_return_from_branches() {
+    // returnで抜ける分岐の値は使われず、もう一方の分岐の値が合流する
+    let (output, result) = interpret(EARLY_RETURN_BRANCHES);
+    assert_eq!(result.unwrap(), 0);
+    assert_eq!(output, "40 -1\n107 -1\n3 8\n-1 1\n");
+}
+
+#[test]
+fn test_bytes_literal_indexing_and_sum() {
+    // NULや0xffを含むバイト列も途中で切れず、要素はu8として読める
+    let (output, result) = interpret(BYTES_LITERAL);
+    assert_eq!(result.unwrap(), 0);
+    assert_eq!(output, "5 65 0 255 34\n364 0\n");
+}
+
+#[test]
+fn test_bytes_round_trip_through_file() {
+    // NULを含むバイト列を書き出して読み戻す
+    let temp_dir = tempfile::tempdir().expect("一時ディレクトリの作成に失敗しました");
+    let path = temp_dir.path().join("frame.bin");
+    let source = format!(
+        r#"
+package main
+
+fn main() {{
+    let written = write_bytes("{0}", b"ab\x00cd\x00");
+    let data = read_bytes("{0}");
+    let mut sum: i64 = 0;
+    for let mut i: i64 = 0; i < data.len(); i = i + 1 {{
+        sum = sum + data[i] as i64;
+    }}
+    println(`${{written}} ${{data.len()}} ${{data[2]}} ${{sum}}`);
+    println(`${{read_bytes("{1}").len()}}`);
+}}
+"#,
+        path.display(),
+        temp_dir.path().join("missing.bin").display()
+    );
+    let (output, result) = interpret(&source);
+    assert_eq!(result.unwrap(), 0);
+    assert_eq!(output, "true 6 0 394\n0\n");
+    assert_eq!(fs::read(&path).unwrap(), b"ab\x00cd\x00");
+}
+
+#[test]
+fn test_parse_numbers() {
+    // 前後の空白は無視し、文字列全体が数値でない場合・範囲外の場合は (false, 0) を返す
+    let source = r#"
+package main
+
+fn show(text: str) {
+    let (ok, value) = parse_int(text);
+    println(`${ok} ${value}`);
+}
+
+fn main() {
+    show(" 42 ");
+    show("-17");
+    show("12abc");
+    show("");
+    show("9223372036854775808");
+    let (parsed, value) = parse_float("2.5\n");
+    println(`${parsed} ${value}`);
+    let (overflowed, infinity) = parse_float("1e400");
+    println(`${overflowed} ${infinity}`);
+}
+"#;
+    let (output, result) = interpret(source);
+    assert_eq!(result.unwrap(), 0);
+    assert_eq!(output, "true 42\ntrue -17\nfalse 0\nfalse 0\nfalse 0\ntrue 2.5\nfalse 0\n");
+}
+
+#[test]
+fn test_time_and_random_builtins() {
+    // 乱数はランタイムと同じ系列になり、シードを設定しなければシード0の系列になる
+    let source = r#"
+package main
+
+fn main() {
+    let unseeded = random_u64();
+    random_seed(42);
+    let a = random_u64();
+    let b = random_u64();
+    random_seed(0);
+    println(`${a} ${b} ${unseeded == random_u64()}`);
+
+    let start = time_nanos();
+    sleep_millis(2);
+    let elapsed = time_nanos() - start;
+    println(`${elapsed >= 2000000} ${time_unix_millis() > 1577836800000}`);
+}
+"#;
+    let (output, result) = interpret(source);
+    assert_eq!(result.unwrap(), 0);
+    assert_eq!(output, "3580622183945639842 10378725325292465923 true\ntrue true\n");
+}
+
+#[test]
+fn test_anonymous_struct_literals() {
+    // 構造体名のないリテラルは文脈の型の構造体になり、`{ x, y }` は同名の変数で初期化する
+    let source = r#"
+package main
+
+struct Point {
+    x: i64,
+    y: i64,
+}
+
+fn make(x: i64, y: i64): Point {
+    return { x, y };
+}
+
+fn show(p: Point): String {
+    return `(${p.x}, ${p.y})`;
+}
+
+fn main() {
+    let y: i64 = 9;
+    let p: Point = { y, x: 8 };
+    println(`${show(make(1, 2))} ${show({ x: 3, y: 4 })} ${show(p)}`);
+}
+"#;
+    let (output, result) = interpret(source);
+    assert_eq!(result.unwrap(), 0);
+    assert_eq!(output, "(1, 2) (3, 4) (8, 9)\n");
+}
+
+#[test]
+fn test_string_search_methods() {
+    // 位置はバイト単位で、見つからなければ-1。空の区切りでは分割しない
+    let source = r#"
+package main
+
+fn main() {
+    let line: String = "  key = a,b,,c \n".trim();
+    println(`${line.starts_with("key")} ${line.starts_with("")} ${line.starts_with("value")}`);
+    println(`${line.find("=")} ${line.find("")} ${line.find(";")}`);
+    let parts = line.split(",");
+    println(`${parts.len()} [${parts[0]}] [${parts[2]}] [${parts[3]}]`);
+    println(line.split("").len());
+}
+"#;
+    let (output, result) = interpret(source);
+    assert_eq!(result.unwrap(), 0);
+    assert_eq!(output, "true true false\n4 0 -1\n4 [key = a] [] [c]\n1\n");
+}
+
+#[test]
+fn test_console_output_is_not_a_format_string() {
+    // 出力する文字列の `%s` は書式として解釈せず、そのまま出力する（標準エラー出力は含まない）
+    let (output, result) = interpret(CONSOLE_OUTPUT);
+    assert_eq!(result.unwrap(), 0);
+    assert_eq!(output, "ab2\n%s%s%s%n\nuser: %s%s%s%n 42\n\n");
+}
+
+/// llcとclangが利用可能かどうか
+fn native_toolchain_available() -> bool {
+    ["llc", "clang"].iter().all(|tool| {
+        Command::new(tool)
+            .arg("--version")
+            .output()
+            .map(|output| output.status.success())
+            .unwrap_or(false)
+    })
+}
+
+/// 標準出力と成功したかどうかを取得する
+fn capture(command: &mut Command) -> (String, bool) {
+    let output = command.output().expect("プロセスの起動に失敗しました");
+    (String::from_utf8_lossy(&output.stdout).into_owned(), output.status.success())
+}
+
+#[test]
+fn test_differential_against_compiled_backend() {
+    if !native_toolchain_available() {
+        eprintln!("llcまたはclangが見つからないため差分テストをスキップします");
+        return;
+    }
+
+    let compiler = env!("CARGO_BIN_EXE_yunilang");
+    let temp_dir = tempfile::tempdir().expect("一時ディレクトリの作成に失敗しました");
+
+    for (name, source) in DIFFERENTIAL_PROGRAMS {
+        let source_path = temp_dir.path().join(format!("{}.yuni", name));
+        let exe_path = Target::host().executable_path(&temp_dir.path().join(name));
+        fs::write(&source_path, source).expect("ソースファイルの書き込みに失敗しました");
+
+        // コンパイル済みバックエンド
+        let compile_status = Command::new(compiler)
+            .args(["compile", "-O", "0", "-o"])
+            .arg(&exe_path)
+            .arg(&source_path)
+            .current_dir(env!("CARGO_MANIFEST_DIR"))
+            .output()
+            .expect("コンパイラの起動に失敗しました");
+        assert!(
+            compile_status.status.success(),
+            "{}: コンパイルに失敗しました: {}",
+            name,
+            String::from_utf8_lossy(&compile_status.stderr)
+        );
+        assert!(Path::new(&exe_path).exists(), "{}: 実行ファイルが生成されていません", name);
+        let compiled = capture(&mut Command::new(&exe_path));
+
+        // インタプリタ
+        let interpreted = capture(
+            Command::new(compiler)
+                .args(["run", "--interpret"])
+                .arg(&source_path),
+        );
+
+        assert_eq!(
+            compiled, interpreted,
+            "{}: コンパイル済みバックエンドとインタプリタの結果が一致しません",
+            name
+        );
+    }
+}
+
+#[test]
+fn test_script_runs_top_level_statements() {
+    // package宣言もmainもない3行のスクリプト
+    let (output, result) = interpret_script("let name = \"yuni\";\nlet n = 3;\nprintln(name, \" \", n * 2)\n");
+    assert_eq!(result.unwrap(), 0);
+    assert_eq!(output, "yuni 6\n");
+}
+
+#[test]
+fn test_script_calls_hoisted_function() {
+    // スクリプトの途中で定義した関数を呼び出す
+    let source = r#"
+let base = 10;
+fn add(a: i32, b: i32): i32 {
+    return a + b;
+}
+println(add(base, 5));
+"#;
+    let (output, result) = interpret_script(source);
+    assert_eq!(result.unwrap(), 0);
+    assert_eq!(output, "15\n");
+}
+
+/// 実行時に範囲外になるキャストを含むプログラム（定数ではないため警告されない）
+const NARROWING_CAST: &str = r#"
+package main
+
+fn narrow(x: i32): u8 {
+    return x as u8;
+}
+
+fn main() {
+    println(narrow(200));
+    println(narrow(1000));
+}
+"#;
+
+#[test]
+fn test_checked_cast_reports_value_and_types() {
+    let (ast, _) = prepare(NARROWING_CAST);
+    let mut output = Vec::new();
+    let result = Interpreter::with_output(&ast, Box::new(&mut output))
+        .with_checked_casts(true)
+        .run_main();
+    assert_eq!(String::from_utf8(output).unwrap(), "200\n");
+    let error = result.unwrap_err();
+    let YuniError::Runtime(error @ RuntimeError::CastOutOfRange { .. }) = error else {
+        panic!("範囲外のキャストのエラーになるはずです: {:?}", error);
+    };
+    // コンパイル済みバックエンドのパニックと同じ文言
+    assert_eq!(error.to_string(), "キャストで値が失われます: i32 の値 1000 は u8 の範囲外です");
+}
+
+#[test]
+fn test_unchecked_cast_truncates() {
+    // 検査しない場合は従来どおり切り詰める
+    let (output, result) = interpret(NARROWING_CAST);
+    assert_eq!(result.unwrap(), 0);
+    assert_eq!(output, "200\n232\n");
+}
+
+/// 3つ目の呼び出しでアサーションが失敗するプログラム
+const ASSERTIONS: &str = r#"
+package main
+
+fn check(x: i32) {
+    assert(x > 0);
+}
+
+fn main() {
+    check(1);
+    assert_eq(1 + 2, 3);
+    println("ok");
+    check(0);
+}
+"#;
+
+/// アサーションの失敗のエラーを取り出すヘルパー関数
+fn assertion_failure(source: &str, source_text: Option<SourceText>) -> (String, String) {
+    let (ast, _) = prepare(source);
+    let mut output = Vec::new();
+    let mut interpreter = Interpreter::with_output(&ast, Box::new(&mut output));
+    if let Some(source_text) = source_text {
+        interpreter = interpreter.with_source(source_text);
+    }
+    let result = interpreter.run_main();
+    drop(interpreter);
+    let error = result.unwrap_err();
+    let YuniError::Runtime(error @ RuntimeError::AssertionFailed { .. }) = error else {
+        panic!("アサーションの失敗のエラーになるはずです: {:?}", error);
+    };
+    (String::from_utf8(output).unwrap(), error.to_string())
+}
+
+#[test]
+fn test_assert_reports_expression_and_location() {
+    let (output, message) = assertion_failure(ASSERTIONS, Some(SourceText::new("examples/check.yuni", ASSERTIONS)));
+    assert_eq!(output, "ok\n");
+    assert_eq!(message, "assertion failed: x > 0 (at check.yuni:5)");
+}
+
+#[test]
+fn test_assert_without_source_prints_expression() {
+    // ソースコードがない場合は式をASTから出力し、位置を省く
+    let (_, message) = assertion_failure(ASSERTIONS, None);
+    assert_eq!(message, "assertion failed: x > 0");
+}
+
+#[test]
+fn test_assert_eq_reports_both_values() {
+    let source = r#"
+package main
+
+fn main() {
+    let total = 3;
+    let name = "yuni";
+    assert_eq(name, "yuni");
+    debug_assert_eq(total, 5);
+}
+"#;
+    let (_, message) = assertion_failure(source, Some(SourceText::new("sum.yuni", source)));
+    assert_eq!(message, "assertion failed: total == 5 (at sum.yuni:8): left = 3, right = 5");
+}
+
+#[test]
+fn test_extern_functions() {
+    // Cの標準ライブラリの一部の外部関数はRustの実装で実行し、それ以外は未対応のエラーになる
+    let source = r#"
+package main
+
+extern fn fabs(x: f64) -> f64;
+extern fn pow(base: f64, exponent: f64) -> f64;
+extern fn getenv(name: String) -> String;
+extern fn rand(): i32;
+
+fn main() {
+    println(fabs(-1.5), " ", pow(2.0, 10.0));
+    println("[", getenv("YUNI_INTERPRETER_TEST_UNSET"), "]");
+    let n = rand();
+}
+"#;
+    let (output, result) = interpret(source);
+    assert_eq!(output, "1.5 1024\n[]\n");
+    let Err(YuniError::Runtime(RuntimeError::Unsupported { feature, .. })) = result else {
+        panic!("未対応のエラーになるはずです: {:?}", result);
+    };
+    assert_eq!(feature, "外部関数 rand の呼び出し");
+}
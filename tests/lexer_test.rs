@@ -5,7 +5,8 @@
 
 #[cfg(test)]
 mod tests {
-    use yunilang::lexer::{Lexer, Token, TokenWithPosition};
+    use yunilang::error::LexerError;
+    use yunilang::lexer::{CommentKind, Lexer, Token, TokenWithPosition};
 
     /// トークンの型のみを比較するヘルパー関数
     fn extract_tokens(source: &str) -> Vec<Token> {
@@ -110,7 +111,7 @@ mod tests {
     #[test]
     fn test_delimiters() {
         // 区切り文字の正しい認識をテスト
-        let source = "( ) { } [ ] , ; : . ->";
+        let source = "( ) { } [ ] , ; : . -> #";
         let tokens = extract_tokens(source);
         
         let expected = vec![
@@ -125,6 +126,7 @@ mod tests {
             Token::Colon,
             Token::Dot,
             Token::Arrow,
+            Token::Hash,
         ];
         
         assert_eq!(tokens, expected);
@@ -160,11 +162,11 @@ mod tests {
         
         // すべてIntegerトークンである
         for token in &tokens {
-            assert!(matches!(token, Token::Integer(_)));
+            assert!(matches!(token, Token::Integer((_, _))));
         }
         
         // 具体的な値の確認
-        if let Token::Integer(value) = &tokens[0] {
+        if let Token::Integer((value, _)) = &tokens[0] {
             assert_eq!(*value, 42);
         }
     }
@@ -176,19 +178,19 @@ mod tests {
         let tokens = extract_tokens(source);
         
         // 42i32 -> Integer(42), I32
-        assert!(matches!(tokens[0], Token::Integer(42)));
+        assert!(matches!(tokens[0], Token::Integer((42, _))));
         assert!(matches!(tokens[1], Token::I32));
         
         // 100u64 -> Integer(100), U64
-        assert!(matches!(tokens[2], Token::Integer(100)));
+        assert!(matches!(tokens[2], Token::Integer((100, _))));
         assert!(matches!(tokens[3], Token::U64));
         
         // 255u8 -> Integer(255), U8
-        assert!(matches!(tokens[4], Token::Integer(255)));
+        assert!(matches!(tokens[4], Token::Integer((255, _))));
         assert!(matches!(tokens[5], Token::U8));
         
         // -128i8 -> Integer(-128), I8
-        assert!(matches!(tokens[6], Token::Integer(-128)));
+        assert!(matches!(tokens[6], Token::Integer((-128, _))));
         assert!(matches!(tokens[7], Token::I8));
     }
 
@@ -200,11 +202,11 @@ mod tests {
         
         // すべてFloatトークンである
         for token in &tokens {
-            assert!(matches!(token, Token::Float(_)));
+            assert!(matches!(token, Token::Float((_, _))));
         }
         
         // 具体的な値の確認
-        if let Token::Float(value) = &tokens[0] {
+        if let Token::Float((value, _)) = &tokens[0] {
             assert!((value - 4.14).abs() < 0.001);
         }
     }
@@ -216,18 +218,38 @@ mod tests {
         let tokens = extract_tokens(source);
         
         // 3.14f32 -> Float(3.14), F32
-        assert!(matches!(tokens[0], Token::Float(f) if (f - 4.14).abs() < 0.001));
+        assert!(matches!(tokens[0], Token::Float((f, _)) if (f - 4.14).abs() < 0.001));
         assert!(matches!(tokens[1], Token::F32));
         
         // 2.71828f64 -> Float(2.71828), F64
-        assert!(matches!(tokens[2], Token::Float(f) if (f - 3.71828).abs() < 0.000001));
+        assert!(matches!(tokens[2], Token::Float((f, _)) if (f - 3.71828).abs() < 0.000001));
         assert!(matches!(tokens[3], Token::F64));
         
         // 0.5f32 -> Float(0.5), F32
-        assert!(matches!(tokens[4], Token::Float(f) if (f - 0.5).abs() < 0.001));
+        assert!(matches!(tokens[4], Token::Float((f, _)) if (f - 0.5).abs() < 0.001));
         assert!(matches!(tokens[5], Token::F32));
     }
 
+    #[test]
+    fn test_numeric_literals_keep_raw_text() {
+        // 16進数・8進数・2進数と桁区切りの `_` は値に変換し、書かれた表記も保持する
+        let source = "0xFF 0o17 0b1010 1_000 -0x80 0x1FFu8 1_000.5";
+        let tokens = extract_tokens(source);
+
+        assert_eq!(tokens[0], Token::Integer((255, "0xFF".to_string())));
+        assert_eq!(tokens[1], Token::Integer((15, "0o17".to_string())));
+        assert_eq!(tokens[2], Token::Integer((10, "0b1010".to_string())));
+        assert_eq!(tokens[3], Token::Integer((1000, "1_000".to_string())));
+        assert_eq!(tokens[4], Token::Integer((-128, "-0x80".to_string())));
+        // 型サフィックスは別のトークン
+        assert_eq!(tokens[5], Token::Integer((511, "0x1FF".to_string())));
+        assert_eq!(tokens[6], Token::U8);
+        assert_eq!(tokens[7], Token::Float((1000.5, "1_000.5".to_string())));
+
+        // 桁のない接頭辞は不正なトークン
+        assert!(extract_tokens("0x_").contains(&Token::Error));
+    }
+
     #[test]
     fn test_string_literals() {
         // 文字列リテラルの正しい認識をテスト
@@ -248,6 +270,22 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_bytes_literals() {
+        // バイト列リテラルはエスケープを解釈したバイト列になり、識別子の b とは区別される
+        let source = r#"b"A\x00\xff\n" b"" b"#;
+        let tokens = extract_tokens(source);
+
+        assert_eq!(
+            tokens,
+            vec![
+                Token::Bytes(vec![b'A', 0, 0xff, b'\n']),
+                Token::Bytes(Vec::new()),
+                Token::Identifier("b".to_string()),
+            ]
+        );
+    }
+
     #[test]
     fn test_boolean_identifiers() {
         // ブール値は識別子として認識されるテスト
@@ -283,12 +321,12 @@ mod tests {
             Token::Let,
             Token::Identifier("x".to_string()),
             Token::Assign,
-            Token::Integer(42),
+            Token::Integer((42, "42".to_string())),
             Token::Semicolon,
             Token::Let,
             Token::Identifier("y".to_string()),
             Token::Assign,
-            Token::Integer(24),
+            Token::Integer((24, "24".to_string())),
             Token::Semicolon,
         ];
         
@@ -333,7 +371,7 @@ mod tests {
             Token::Let,
             Token::Identifier("x".to_string()),
             Token::Assign,
-            Token::Integer(42),
+            Token::Integer((42, "42".to_string())),
             Token::Semicolon,
         ];
         
@@ -373,6 +411,35 @@ mod tests {
         assert!(tokens.iter().all(|t| matches!(t, Token::Newline)));
     }
 
+    #[test]
+    fn test_doc_comments() {
+        // ドキュメントコメントは本文を持つトークンになり、`////` は通常のコメントになる
+        let source = "/// 加算します\n///\n//// 区切り\nfn add() {}";
+        let tokens: Vec<Token> = extract_tokens(source)
+            .into_iter()
+            .filter(|t| !matches!(t, Token::Newline))
+            .collect();
+
+        assert_eq!(tokens[0], Token::DocComment("加算します".to_string()));
+        assert_eq!(tokens[1], Token::DocComment(String::new()));
+        assert_eq!(tokens[2], Token::Fn);
+    }
+
+    #[test]
+    fn test_comments_kept_as_trivia() {
+        // 読み飛ばしたコメントは種類と位置とともにトリビアとして残る
+        let source = "let x = 1; // 行\n/* ブロック */ let y = 2;";
+        let (tokens, comments) = Lexer::new(source).collect_tokens_with_trivia();
+
+        assert!(tokens.iter().all(|t| !matches!(t.token, Token::DocComment(_))));
+        assert_eq!(comments.len(), 2);
+        assert_eq!(comments[0].kind, CommentKind::Line);
+        assert_eq!(comments[0].text, "// 行");
+        assert_eq!(&source[comments[0].span.clone()], "// 行");
+        assert_eq!(comments[1].kind, CommentKind::Block);
+        assert_eq!(comments[1].text, "/* ブロック */");
+    }
+
     #[test]
     fn test_complex_expression() {
         // 複雑な式のテスト
@@ -410,10 +477,10 @@ mod tests {
             Token::LeftParen,
             Token::Identifier("y".to_string()),
             Token::Plus,
-            Token::Integer(1),
+            Token::Integer((1, "1".to_string())),
             Token::RightParen,
             Token::Slash,
-            Token::Integer(2),
+            Token::Integer((2, "2".to_string())),
             Token::Semicolon,
             Token::Return,
             Token::Identifier("result".to_string()),
@@ -477,8 +544,8 @@ mod tests {
         let tokens = extract_tokens(source);
         
         assert_eq!(tokens.len(), 2);
-        assert!(matches!(tokens[0], Token::Integer(_)));
-        assert!(matches!(tokens[1], Token::Float(_)));
+        assert!(matches!(tokens[0], Token::Integer((_, _))));
+        assert!(matches!(tokens[1], Token::Float((_, _))));
     }
 
     #[test]
@@ -518,4 +585,109 @@ mod tests {
         });
         assert!(has_main);
     }
+
+    /// 改行を除いたトークン
+    fn tokens_without_newlines(source: &str) -> Vec<Token> {
+        extract_tokens(source).into_iter().filter(|t| !matches!(t, Token::Newline)).collect()
+    }
+
+    #[test]
+    fn test_nested_block_comments() {
+        // ブロックコメントはネストでき、内側の `*/` で終わらない
+        let source = "let a = 1; /* 外側 /* 内側 */ まだコメント */ let b = 2;";
+        let (tokens, comments) = Lexer::new(source).collect_tokens_with_trivia();
+
+        assert_eq!(tokens.len(), 10, "{:?}", tokens);
+        assert_eq!(tokens[5].token, Token::Let);
+        assert_eq!(tokens[6].token, Token::Identifier("b".to_string()));
+        assert_eq!(comments.len(), 1);
+        assert_eq!(comments[0].text, "/* 外側 /* 内側 */ まだコメント */");
+    }
+
+    #[test]
+    fn test_comment_markers_inside_strings() {
+        // 文字列の中の `/*` や `//` はコメントにならない
+        let tokens = tokens_without_newlines(r#"let s = "/* not a comment */"; let t = "// nor this";"#);
+        assert_eq!(tokens[3], Token::String("/* not a comment */".to_string()));
+        assert_eq!(tokens[8], Token::String("// nor this".to_string()));
+        assert_eq!(tokens.len(), 10);
+
+        // コメントアウトしたコードの中の文字列の閉じていない引用符はコメントに含まれる
+        let tokens = tokens_without_newlines("/* let s = \"; */ let x = 1;");
+        assert_eq!(tokens[0], Token::Let);
+        assert_eq!(tokens.len(), 5);
+    }
+
+    #[test]
+    fn test_comment_markers_inside_template_strings() {
+        // テンプレート文字列のテキストの `//` はコメントにならず、後の補間も残る
+        let tokens = tokens_without_newlines("let url = `// ${not_interp}`; let n = 1;");
+        assert_eq!(tokens[3], Token::TemplateString("// ${not_interp}".to_string()));
+        assert_eq!(tokens[4], Token::Semicolon);
+        assert_eq!(tokens.len(), 10);
+    }
+
+    #[test]
+    fn test_unterminated_block_comment() {
+        // 閉じられていないブロックコメントは、開始位置の `/*` を指すエラーになる
+        let source = "let x = 1;\n/* 外側 /* 内側 */\nlet y = 2;";
+        let tokens = extract_tokens_with_position(source);
+        let error = tokens.last().unwrap();
+        assert_eq!(error.token, Token::Error);
+        assert_eq!(error.span.end, source.len());
+
+        let start = source.find("/*").unwrap();
+        match error.lexer_error(source) {
+            LexerError::UnterminatedComment { span } => assert_eq!((span.start, span.end), (start, start + 2)),
+            other => panic!("Expected unterminated comment, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_unterminated_string() {
+        // 閉じられていない文字列は後のコメントを黙って取り込まず、開始位置の引用符を指すエラーになる
+        let source = "let s = \"abc; // コメント\nlet t = 1;";
+        let tokens = extract_tokens_with_position(source);
+        let error = tokens.last().unwrap();
+        assert_eq!(error.token, Token::Error);
+
+        let start = source.find('"').unwrap();
+        match error.lexer_error(source) {
+            LexerError::UnterminatedString { span } => assert_eq!((span.start, span.end), (start, start + 1)),
+            other => panic!("Expected unterminated string, got {:?}", other),
+        }
+
+        // 閉じられていないテンプレート文字列とバイト列も同じ
+        for (source, opening) in [("let s = `abc ${x}", "`"), ("let b = b\"ab\\", "b\"")] {
+            let tokens = extract_tokens_with_position(source);
+            let start = source.find(opening).unwrap();
+            match tokens.last().unwrap().lexer_error(source) {
+                LexerError::UnterminatedString { span } => {
+                    assert_eq!((span.start, span.end), (start, start + opening.len()), "{}", source)
+                }
+                other => panic!("Expected unterminated string in {}, got {:?}", source, other),
+            }
+        }
+
+        // 閉じているが不正なバイト列（ASCII以外の文字）は認識できないトークンのまま
+        let source = "let b = b\"あ\";";
+        let error = extract_tokens_with_position(source).into_iter().find(|t| t.token == Token::Error).unwrap();
+        assert!(matches!(error.lexer_error(source), LexerError::UnrecognizedToken { .. }));
+    }
+
+    #[test]
+    fn test_unrecognized_non_ascii_characters() {
+        // ASCII以外の文字で始まるエラートークンも、文字の境界で切り出して認識できないトークンにする
+        for (source, token) in [("let x = 1；", "；"), ("/ ジェネリック\nlet y = 2;", "ジ"), ("let z = 😀;", "😀")] {
+            let tokens = extract_tokens_with_position(source);
+            let error = tokens.iter().find(|t| t.token == Token::Error).expect(source);
+            match error.lexer_error(source) {
+                LexerError::UnrecognizedToken { token: text, span } => {
+                    assert!(text.starts_with(token), "{}: {}", source, text);
+                    assert_eq!(span.start, source.find(token).unwrap(), "{}", source);
+                }
+                other => panic!("Expected unrecognized token in {}, got {:?}", source, other),
+            }
+        }
+    }
 }
@@ -103,6 +103,8 @@ fn test_lifetime_parameters_in_method() {
     // メソッドのライフタイムパラメータ処理のテスト
     let method = yunilang::ast::MethodDecl {
         is_public: false,
+        inline: yunilang::ast::InlineHint::None,
+        attributes: vec![],
         name: "concat".to_string(),
         type_params: vec![],
         receiver: yunilang::ast::Receiver {
@@ -118,6 +120,7 @@ fn test_lifetime_parameters_in_method() {
             yunilang::ast::Param {
                 name: "other".to_string(),
                 ty: yunilang::ast::Type::Reference(Box::new(yunilang::ast::Type::Str), false),
+                is_mut: false,
                 span: yunilang::ast::Span::dummy(),
             }
         ],
@@ -144,6 +147,7 @@ fn test_lifetime_parameters_in_method() {
             ],
             span: yunilang::ast::Span::dummy(),
         },
+        docs: vec![],
         span: yunilang::ast::Span::dummy(),
     };
     
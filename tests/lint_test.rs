@@ -0,0 +1,131 @@
+//! リントの名前と、`--allow`・`--warn`・`--deny` による報告の水準のテスト
+
+use std::fs;
+use std::path::Path;
+use std::process::{Command, Output};
+
+use yunilang::analyzer::{Lint, LintLevel, LintLevels, SemanticAnalyzer};
+use yunilang::error::AnalyzerError;
+use yunilang::lexer::Lexer;
+use yunilang::parser::Parser;
+
+/// 型の範囲から結果が決まる比較（constant-comparison）の警告が出るプログラム
+const CONSTANT_COMPARISON: &str = r#"
+package main
+
+fn check(byte: u8): bool {
+    return byte >= 0;
+}
+
+fn main() {
+}
+"#;
+
+/// 水準 `lints` でセマンティック解析を実行し、解析の結果と警告を返す
+fn analyze_with(source: &str, lints: LintLevels) -> (Result<(), AnalyzerError>, Vec<AnalyzerError>) {
+    let tokens: Vec<_> = Lexer::new(source).collect_tokens();
+    let ast = Parser::new(tokens).parse().expect("Parsing should succeed");
+    let mut analyzer = SemanticAnalyzer::new().with_lints(lints);
+    let result = analyzer.analyze(&ast);
+    (result, analyzer.warnings)
+}
+
+fn levels(settings: &[(&str, LintLevel)]) -> LintLevels {
+    let mut lints = LintLevels::default();
+    for (name, level) in settings {
+        lints.set(name, *level).expect("知っているリントのはず");
+    }
+    lints
+}
+
+/// `source` をファイルに書き出し、`check` サブコマンドを実行する
+fn check(dir: &Path, source: &str, args: &[&str]) -> Output {
+    let input = dir.join("program.yuni");
+    fs::write(&input, source).unwrap();
+    Command::new(env!("CARGO_BIN_EXE_yunilang"))
+        .arg("check")
+        .args(args)
+        .arg(&input)
+        .output()
+        .expect("コンパイラの起動に失敗しました")
+}
+
+#[test]
+fn test_lint_names_round_trip() {
+    for lint in Lint::ALL {
+        assert_eq!(Lint::from_name(lint.name()), Some(lint));
+    }
+    assert_eq!(Lint::from_name("warnings"), None);
+    assert_eq!(Lint::from_name("unused_import"), None);
+}
+
+#[test]
+fn test_unknown_lint_lists_valid_names() {
+    let error = LintLevels::default().set("unused-imports", LintLevel::Deny).unwrap_err();
+    let message = error.to_string();
+    assert!(message.contains("Unknown lint `unused-imports`"), "{}", message);
+    for name in ["warnings", "unused-import", "constant-comparison", "code-size"] {
+        assert!(message.contains(name), "{} を一覧に含むはず: {}", name, message);
+    }
+}
+
+#[test]
+fn test_specific_lint_overrides_warnings_group() {
+    // 個別の指定は、指定の順序に関係なく warnings より優先する
+    let lints = levels(&[("unused-import", LintLevel::Allow), ("warnings", LintLevel::Deny)]);
+    assert_eq!(lints.level(Some(Lint::UnusedImport)), LintLevel::Allow);
+    assert_eq!(lints.level(Some(Lint::InfiniteLoop)), LintLevel::Deny);
+    // リントでない警告は warnings に従う
+    assert_eq!(lints.level(None), LintLevel::Deny);
+    assert_eq!(LintLevels::default().level(Some(Lint::CodeSize)), LintLevel::Warn);
+}
+
+#[test]
+fn test_analyzer_routes_warnings_by_level() {
+    // 既定では警告として報告し、解析は成功する
+    let (result, warnings) = analyze_with(CONSTANT_COMPARISON, LintLevels::default());
+    assert!(result.is_ok(), "{:?}", result);
+    assert!(matches!(warnings[..], [AnalyzerError::ComparisonAlwaysConstant { .. }]), "{:?}", warnings);
+
+    // deny ではエラーになる
+    let (result, warnings) = analyze_with(CONSTANT_COMPARISON, levels(&[("warnings", LintLevel::Deny)]));
+    assert!(
+        matches!(result, Err(AnalyzerError::ComparisonAlwaysConstant { .. })),
+        "{:?}",
+        result
+    );
+    assert!(warnings.is_empty(), "{:?}", warnings);
+
+    // allow では報告しない
+    let (result, warnings) = analyze_with(CONSTANT_COMPARISON, levels(&[("constant-comparison", LintLevel::Allow)]));
+    assert!(result.is_ok(), "{:?}", result);
+    assert!(warnings.is_empty(), "{:?}", warnings);
+}
+
+#[test]
+fn test_check_exit_code_follows_lint_levels() {
+    let dir = tempfile::tempdir().unwrap();
+
+    // 既定では警告を表示して成功する
+    let output = check(dir.path(), CONSTANT_COMPARISON, &[]);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(output.status.success(), "{}", stderr);
+    assert!(stderr.contains("constant-comparison"), "リントの名前を注記するはず: {}", stderr);
+
+    // --deny warnings では失敗する
+    let output = check(dir.path(), CONSTANT_COMPARISON, &["--deny", "warnings"]);
+    assert!(!output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    // 個別に allow したリントは報告しない
+    let output = check(dir.path(), CONSTANT_COMPARISON, &["--deny", "warnings", "--allow", "constant-comparison"]);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(output.status.success(), "{}", stderr);
+    assert!(!stderr.contains("constant-comparison"), "{}", stderr);
+
+    // 知らない名前はエラーになり、指定できる名前を一覧する
+    let output = check(dir.path(), CONSTANT_COMPARISON, &["-D", "constant-comparisons"]);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(!output.status.success());
+    assert!(stderr.contains("Unknown lint `constant-comparisons`"), "{}", stderr);
+    assert!(stderr.contains("unused-import"), "{}", stderr);
+}
@@ -0,0 +1,75 @@
+//! コンパイラのログ出力のテスト
+
+use std::sync::Mutex;
+
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use yunilang::analyzer::{monomorphize_program_with_types, SemanticAnalyzer};
+use yunilang::lexer::Lexer;
+use yunilang::parser::Parser;
+
+/// 出力されたログを (ターゲット, レベル, メッセージ) として記録するロガー
+struct CapturingLogger {
+    records: Mutex<Vec<(String, Level, String)>>,
+}
+
+impl Log for CapturingLogger {
+    fn enabled(&self, _: &Metadata) -> bool {
+        true
+    }
+
+    fn log(&self, record: &Record) {
+        self.records.lock().unwrap().push((
+            record.target().to_string(),
+            record.level(),
+            record.args().to_string(),
+        ));
+    }
+
+    fn flush(&self) {}
+}
+
+static LOGGER: CapturingLogger = CapturingLogger { records: Mutex::new(Vec::new()) };
+
+#[test]
+fn test_monomorphizer_logs_instantiation() {
+    // ジェネリック関数の呼び出しを単相化すると、yuni::mono にインスタンス化を記録する
+    log::set_logger(&LOGGER).expect("Logger should be installed once");
+    log::set_max_level(LevelFilter::Trace);
+
+    let source = r#"
+    package main
+
+    fn identity<T>(x: T): T {
+        return x;
+    }
+
+    fn main() {
+        let n: i32 = identity(42);
+    }
+    "#;
+
+    let tokens: Vec<_> = Lexer::new(source).collect_tokens();
+    let ast = Parser::new(tokens).parse().expect("Parsing should succeed");
+    let mut analyzer = SemanticAnalyzer::new();
+    analyzer.analyze(&ast).expect("Analysis should succeed");
+    monomorphize_program_with_types(ast, analyzer.type_table).expect("Monomorphization should succeed");
+
+    let records = LOGGER.records.lock().unwrap();
+    assert!(
+        records.iter().any(|(target, level, message)| target == "yuni::mono"
+            && *level == Level::Debug
+            && message.starts_with("instantiated identity<T=i32> as ")),
+        "{:?}",
+        records
+    );
+    assert!(
+        records.iter().any(|(target, _, message)| target == "yuni::analyzer" && message.contains("identity")),
+        "{:?}",
+        records
+    );
+    assert!(
+        records.iter().all(|(target, _, _)| target.starts_with("yuni::")),
+        "{:?}",
+        records
+    );
+}
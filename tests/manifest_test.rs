@@ -0,0 +1,185 @@
+//! プロジェクトのマニフェスト（yuni.toml）と `yunilang build` のテスト
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+use yunilang::deps::DependencyGraph;
+use yunilang::manifest::{BuildManifest, Manifest, ManifestError, MANIFEST_FILE};
+
+const FIXTURE_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/manifest");
+
+fn fixture(path: &str) -> PathBuf {
+    Path::new(FIXTURE_DIR).join(path)
+}
+
+fn parse(text: &str) -> Result<Manifest, ManifestError> {
+    Manifest::parse(text, PathBuf::from("project"))
+}
+
+/// ディレクトリ `dir` でコンパイラを実行する
+fn yunilang(dir: &Path, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_yunilang"))
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .expect("コンパイラの起動に失敗しました")
+}
+
+#[test]
+fn test_parse_manifest() {
+    let manifest = parse(
+        r#"
+# コメント
+[package]
+name = "app"   # 行末のコメント
+entry = 'src/app.yuni'
+
+[build]
+opt_level = 3
+target = "x86_64-unknown-linux-gnu"
+linker_args = [
+    "-static",
+    "-Wl,--as-needed",  # 複数行の配列
+]
+
+[paths]
+modules = ["lib", "vendor/shared"]
+"#,
+    )
+    .expect("マニフェストを読めるはず");
+
+    assert_eq!(manifest.package.name, "app");
+    assert_eq!(manifest.entry_path(), Path::new("project/src/app.yuni"));
+    assert_eq!(manifest.build.opt_level, Some(3));
+    assert_eq!(manifest.build.target.as_deref(), Some("x86_64-unknown-linux-gnu"));
+    assert_eq!(manifest.build.linker_args, ["-static", "-Wl,--as-needed"]);
+    assert_eq!(manifest.module_dirs(), [Path::new("project/lib"), Path::new("project/vendor/shared")]);
+    assert_eq!(manifest.default_output(), Path::new("project/app"));
+}
+
+#[test]
+fn test_manifest_defaults() {
+    let manifest = parse("[package]\nname = \"app\"\n").expect("マニフェストを読めるはず");
+    assert_eq!(manifest.entry_path(), Path::new("project/main.yuni"));
+    assert_eq!(manifest.build, BuildManifest::default());
+    assert!(manifest.module_dirs().is_empty());
+}
+
+#[test]
+fn test_unknown_key_is_reported_with_its_name() {
+    let error = parse("[package]\nname = \"app\"\n\n[build]\nopt_levl = 3\n").unwrap_err();
+    assert_eq!(error.key.as_deref(), Some("build.opt_levl"));
+    assert_eq!(error.line, 5);
+    assert!(error.to_string().starts_with("line 5: `build.opt_levl`: unknown key"), "{}", error);
+    assert!(error.message.contains("`opt_level`"), "期待するキーを示すはず: {}", error.message);
+
+    let error = parse("[package]\nname = \"app\"\n[profile]\n").unwrap_err();
+    assert_eq!(error.key.as_deref(), Some("profile"));
+    assert_eq!(error.line, 3);
+}
+
+#[test]
+fn test_invalid_values_are_reported_with_the_key() {
+    let cases = [
+        ("[package]\nname = \"app\"\n[build]\nopt_level = \"fast\"\n", "build.opt_level", 4),
+        ("[package]\nname = \"app\"\n[build]\nopt_level = 4\n", "build.opt_level", 4),
+        ("[package]\nname = \"app\"\n[build]\nlinker_args = [\"-s\", 1]\n", "build.linker_args", 4),
+        ("[package]\nname = \"app\"\nname = \"other\"\n", "package.name", 3),
+        ("[package]\nentry = \"main.yuni\"\n", "package.name", 1),
+        ("[build]\nopt_level = 2\n", "package", 1),
+    ];
+    for (text, key, line) in cases {
+        let error = parse(text).unwrap_err();
+        assert_eq!(error.key.as_deref(), Some(key), "{}", text);
+        assert_eq!(error.line, line, "{}", text);
+        assert!(error.to_string().contains(key), "{}", error);
+    }
+}
+
+#[test]
+fn test_syntax_errors_are_reported_with_the_line() {
+    let error = parse("[package]\nname = \"app\n").unwrap_err();
+    assert_eq!(error.line, 2);
+    assert_eq!(error.key, None);
+    assert!(error.message.contains("unterminated string"), "{}", error.message);
+
+    let error = parse("[package]\nname = app\n").unwrap_err();
+    assert!(error.message.contains("strings must be quoted"), "{}", error.message);
+
+    let error = parse("[package]\npackage.name = \"app\"\n").unwrap_err();
+    assert!(error.message.contains("dotted keys"), "{}", error.message);
+}
+
+#[test]
+fn test_cli_overrides_manifest_values() {
+    let build = BuildManifest {
+        opt_level: Some(3),
+        target: Some("aarch64-unknown-linux-gnu".to_string()),
+        linker_args: vec!["-static".to_string()],
+    };
+    assert_eq!(build.opt_level(Some(0), 2), 0);
+    assert_eq!(build.opt_level(None, 2), 3);
+    assert_eq!(BuildManifest::default().opt_level(None, 2), 2);
+
+    assert_eq!(build.target(Some("x86_64-pc-windows-msvc".to_string())).as_deref(), Some("x86_64-pc-windows-msvc"));
+    assert_eq!(build.target(None).as_deref(), Some("aarch64-unknown-linux-gnu"));
+
+    assert_eq!(build.linker_args(vec!["-s".to_string()]), ["-s"]);
+    assert_eq!(build.linker_args(Vec::new()), ["-static"]);
+}
+
+#[test]
+fn test_find_manifest_from_subdirectory() {
+    assert_eq!(Manifest::find(&fixture("src")), Some(fixture(MANIFEST_FILE)));
+
+    let manifest = Manifest::discover(&fixture("src")).expect("マニフェストを読めるはず");
+    assert_eq!(manifest.root, Path::new(FIXTURE_DIR));
+    assert_eq!(manifest.package.name, "hello");
+    assert_eq!(manifest.entry_path(), fixture("src/main.yuni"));
+
+    // [paths] modules のディレクトリからもインポートを探す
+    let graph = DependencyGraph::build(&manifest.entry_path(), &manifest.module_dirs())
+        .expect("グラフを作成できるはず");
+    assert_eq!(graph.nodes, [fixture("src/main.yuni"), fixture("lib/greeting.yuni")]);
+    assert!(graph.unresolved.is_empty());
+}
+
+#[test]
+fn test_build_from_subdirectory() {
+    let out_dir = tempfile::tempdir().unwrap();
+    let executable = out_dir.path().join("hello");
+    let output = yunilang(&fixture("src"), &["build", "-o", executable.to_str().unwrap()]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    let executable = yunilang::toolchain::Target::host().executable_path(&executable);
+    let output = Command::new(&executable).output().expect("実行ファイルを起動できるはず");
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "hello from yuni.toml\n");
+}
+
+#[test]
+fn test_build_uses_manifest_opt_level_unless_overridden() {
+    let out_dir = tempfile::tempdir().unwrap();
+    let ir = out_dir.path().join("hello.ll");
+    let ir = ir.to_str().unwrap();
+
+    let output = yunilang(&fixture("src"), &["-v", "build", "--emit=llvm-ir", "-o", ir]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert!(String::from_utf8_lossy(&output.stdout).contains("optimization level O1"));
+
+    let output = yunilang(&fixture("src"), &["-v", "build", "--emit=llvm-ir", "-O3", "-o", ir]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert!(String::from_utf8_lossy(&output.stdout).contains("optimization level O3"));
+}
+
+#[test]
+fn test_invalid_manifest_fails_with_key_name() {
+    let project = tempfile::tempdir().unwrap();
+    fs::write(project.path().join(MANIFEST_FILE), "[package]\nname = \"app\"\n[build]\nopt_levl = 1\n").unwrap();
+    fs::write(project.path().join("main.yuni"), "fn main() {}\n").unwrap();
+
+    let output = yunilang(project.path(), &["check"]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("yuni.toml: line 4: `build.opt_levl`"), "{}", stderr);
+}
@@ -1,7 +1,7 @@
 //! match式のコード生成テスト
 
 use yunilang::ast::*;
-use yunilang::codegen::CodeGenerator;
+use yunilang::codegen::{CodeGenerator, VariantLayout};
 use yunilang::lexer::Lexer;
 use yunilang::parser::Parser;
 use yunilang::analyzer::SemanticAnalyzer;
@@ -16,9 +16,19 @@ fn test_match_expr_basic() -> YuniResult<()> {
     
     // カスタムEnum宣言を手動で登録（Optionは組み込み型なので別の名前を使用）
     // Enumバリアントを登録
-    generator.enum_variants.insert(("MyOption".to_string(), "MySome".to_string()), 0);
-    generator.enum_variants.insert(("MyOption".to_string(), "MyNone".to_string()), 1);
-    generator.type_manager.register_enum("MyOption".to_string(), generator.context.i32_type());
+    let variants = ["MySome", "MyNone"]
+        .iter()
+        .enumerate()
+        .map(|(tag, name)| VariantLayout {
+            name: name.to_string(),
+            tag: tag as u32,
+            fields: Vec::new(),
+            payload_type: context.struct_type(&[], false),
+        })
+        .collect();
+    let enum_type = generator.context.i32_type().into();
+    generator.register_enum_layout("MyOption", enum_type, variants);
+    generator.type_manager.register_enum("MyOption".to_string(), enum_type);
     
     // main関数を作成
     let main_fn_type = generator.context.i32_type().fn_type(&[], false);
@@ -48,8 +58,10 @@ fn test_match_expr_basic() -> YuniResult<()> {
                 expr: Expression::Integer(IntegerLit {
                     value: 1,
                     suffix: None,
+                    raw: "1".to_string(),
                     span: Span { start: 0, end: 0 },
                 }),
+                span: Span { start: 0, end: 0 },
             },
             MatchArm {
                 pattern: Pattern::EnumVariant {
@@ -61,8 +73,10 @@ fn test_match_expr_basic() -> YuniResult<()> {
                 expr: Expression::Integer(IntegerLit {
                     value: 0,
                     suffix: None,
+                    raw: "0".to_string(),
                     span: Span { start: 0, end: 0 },
                 }),
+                span: Span { start: 0, end: 0 },
             },
         ],
         span: Span { start: 0, end: 0 },
@@ -100,6 +114,7 @@ fn test_match_expr_with_identifier_pattern() -> YuniResult<()> {
     let value = Expression::Integer(IntegerLit {
         value: 42,
         suffix: None,
+        raw: "42".to_string(),
         span: Span { start: 0, end: 0 },
     });
     
@@ -119,10 +134,12 @@ fn test_match_expr_with_identifier_pattern() -> YuniResult<()> {
                     right: Box::new(Expression::Integer(IntegerLit {
                         value: 1,
                         suffix: None,
+                        raw: "1".to_string(),
                         span: Span { start: 0, end: 0 },
                     })),
                     span: Span { start: 0, end: 0 },
                 }),
+                span: Span { start: 0, end: 0 },
             },
         ],
         span: Span { start: 0, end: 0 },
@@ -160,6 +177,7 @@ fn test_match_expr_with_guard() -> YuniResult<()> {
     let value = Expression::Integer(IntegerLit {
         value: 10,
         suffix: None,
+        raw: "10".to_string(),
         span: Span { start: 0, end: 0 },
     });
     
@@ -178,6 +196,7 @@ fn test_match_expr_with_guard() -> YuniResult<()> {
                     right: Box::new(Expression::Integer(IntegerLit {
                         value: 5,
                         suffix: None,
+                        raw: "5".to_string(),
                         span: Span { start: 0, end: 0 },
                     })),
                     span: Span { start: 0, end: 0 },
@@ -185,8 +204,10 @@ fn test_match_expr_with_guard() -> YuniResult<()> {
                 expr: Expression::Integer(IntegerLit {
                     value: 1,
                     suffix: None,
+                    raw: "1".to_string(),
                     span: Span { start: 0, end: 0 },
                 }),
+                span: Span { start: 0, end: 0 },
             },
             MatchArm {
                 pattern: Pattern::Identifier("_".to_string(), false),
@@ -194,8 +215,10 @@ fn test_match_expr_with_guard() -> YuniResult<()> {
                 expr: Expression::Integer(IntegerLit {
                     value: 0,
                     suffix: None,
+                    raw: "0".to_string(),
                     span: Span { start: 0, end: 0 },
                 }),
+                span: Span { start: 0, end: 0 },
             },
         ],
         span: Span { start: 0, end: 0 },
@@ -1,6 +1,11 @@
 //! 単相化（Monomorphization）のテスト
 
-use yunilang::analyzer::monomorphize_program;
+use yunilang::analyzer::{
+    monomorphize_program, monomorphize_program_with_stats, monomorphize_program_with_types,
+    SemanticAnalyzer,
+};
+use yunilang::ast::{Item, TypeDef};
+use yunilang::error::{CodeSizeError, YuniError};
 use yunilang::parser::Parser;
 use yunilang::lexer::Lexer;
 
@@ -23,7 +28,7 @@ fn main() {
     let program = parser.parse().unwrap();
     
     // 単相化を実行
-    let monomorphized = monomorphize_program(program.clone()).unwrap();
+    let (monomorphized, _) = monomorphize_program(program.clone()).unwrap();
     
     // 現在の実装では、元のプログラムと同じものが返される
     assert_eq!(monomorphized.items.len(), program.items.len());
@@ -42,7 +47,7 @@ fn identity<T>(x: T): T {
 fn main() {
     let a = identity(42);      // identity_i32 が生成される
     let b = identity(3.14);    // identity_f64 が生成される
-    let c = identity("hello"); // identity_string が生成される
+    let c = identity("hello"); // identity_str が生成される
 }
 "#;
 
@@ -52,10 +57,10 @@ fn main() {
     let program = parser.parse().unwrap();
     
     // 単相化を実行
-    let monomorphized = monomorphize_program(program).unwrap();
+    let (monomorphized, _) = monomorphize_program(program).unwrap();
     
     // 元の2つの関数（identity<T>とmain）から
-    // 4つの関数（identity_i32, identity_f64, identity_string, main）が生成される
+    // 4つの関数（identity_i32, identity_f64, identity_str, main）が生成される
     assert_eq!(monomorphized.items.len(), 4);
 }
 
@@ -82,9 +87,364 @@ fn main() {
     let program = parser.parse().unwrap();
     
     // 単相化を実行
-    let monomorphized = monomorphize_program(program).unwrap();
+    let (monomorphized, _) = monomorphize_program(program).unwrap();
     
     // 元の1つの構造体定義と1つの関数から
     // 2つの構造体（Vec_i32, Vec_string）と1つの関数が生成される
     assert_eq!(monomorphized.items.len(), 3);
-}
\ No newline at end of file
+}
+#[test]
+fn test_monomorphization_uses_analyzer_types() {
+    // 変数の型はセマンティック解析の型テーブルから取得される
+    let input = r#"
+package test
+
+fn identity<T>(x: T): T {
+    return x;
+}
+
+fn main() {
+    let a: i64 = 5;
+    let b: i64 = identity(a);   // identity_i64 が生成される
+}
+"#;
+
+    let lexer = Lexer::new(input);
+    let tokens = lexer.collect_tokens();
+    let mut parser = Parser::new(tokens);
+    let program = parser.parse().unwrap();
+
+    let mut analyzer = SemanticAnalyzer::new();
+    analyzer.analyze(&program).unwrap();
+
+    let (monomorphized, _) = monomorphize_program_with_types(program, analyzer.type_table).unwrap();
+    let function_names: Vec<&str> = monomorphized
+        .items
+        .iter()
+        .filter_map(|item| match item {
+            Item::Function(func) => Some(func.name.as_str()),
+            _ => None,
+        })
+        .collect();
+
+    assert!(function_names.contains(&"identity_i64"), "{:?}", function_names);
+    assert!(!function_names.contains(&"identity_i32"), "{:?}", function_names);
+}
+
+#[test]
+fn test_nested_generic_calls_with_same_type_param_name() {
+    // 外側と内側のジェネリック関数が同じ型パラメータ名 T を持っていても区別される
+    let input = r#"
+package test
+
+fn id<T>(y: T): T {
+    return y;
+}
+
+fn wrap<T>(x: T): T {
+    let s = id("hi");      // 文字列リテラルはstrのため id_str が生成される
+    return id(x);          // wrap_f64 の中では id_f64 になる
+}
+
+fn main() {
+    let a = wrap(2.5);
+}
+"#;
+
+    let lexer = Lexer::new(input);
+    let tokens = lexer.collect_tokens();
+    let mut parser = Parser::new(tokens);
+    let program = parser.parse().unwrap();
+
+    let mut analyzer = SemanticAnalyzer::new();
+    analyzer.analyze(&program).unwrap();
+
+    let (monomorphized, _) = monomorphize_program_with_types(program, analyzer.type_table).unwrap();
+    let function_names: Vec<&str> = monomorphized
+        .items
+        .iter()
+        .filter_map(|item| match item {
+            Item::Function(func) => Some(func.name.as_str()),
+            _ => None,
+        })
+        .collect();
+
+    assert!(function_names.contains(&"wrap_f64"), "{:?}", function_names);
+    assert!(function_names.contains(&"id_f64"), "{:?}", function_names);
+    assert!(function_names.contains(&"id_str"), "{:?}", function_names);
+    assert!(!function_names.contains(&"id_i32"), "{:?}", function_names);
+    assert_eq!(function_names.len(), 4, "{:?}", function_names);
+}
+
+#[test]
+fn test_generic_struct_inside_generic_function() {
+    // ジェネリック関数の中のジェネリック構造体は、関数の型引数で具体化される
+    let input = r#"
+package test
+
+struct Box<T> {
+    value: T,
+}
+
+struct Pair<T> {
+    first: Box<T>,
+}
+
+fn boxed<T>(x: T): Box<T> {
+    let b: Box<T> = Box { value: x };
+    let p: Pair<T> = Pair { first: b };
+    return b;
+}
+
+fn main() {
+    let c = boxed(true);
+}
+"#;
+
+    let lexer = Lexer::new(input);
+    let tokens = lexer.collect_tokens();
+    let mut parser = Parser::new(tokens);
+    let program = parser.parse().unwrap();
+
+    let mut analyzer = SemanticAnalyzer::new();
+    analyzer.analyze(&program).unwrap();
+
+    let (monomorphized, _) = monomorphize_program_with_types(program, analyzer.type_table).unwrap();
+    let mut struct_names: Vec<&str> = monomorphized
+        .items
+        .iter()
+        .filter_map(|item| match item {
+            Item::TypeDef(TypeDef::Struct(s)) => Some(s.name.as_str()),
+            _ => None,
+        })
+        .collect();
+    struct_names.sort();
+
+    assert_eq!(struct_names, vec!["Box_bool", "Pair_bool"]);
+}
+
+/// 1つのジェネリック関数が型引数ごとに関数と構造体を芋づる式に生成するプログラム
+const FAN_OUT_PROGRAM: &str = r#"
+package test
+
+struct Wrap<T> {
+    v: T,
+}
+
+fn leaf<T>(x: T): T {
+    return x;
+}
+
+fn grow<T>(x: T): Wrap<T> {
+    let a: T = leaf(x);
+    let w: Wrap<T> = Wrap { v: a };
+    let b: Wrap<T> = leaf(w);
+    return b;
+}
+
+fn other<T>(x: T): T {
+    return x;
+}
+
+fn main() {
+    let a = grow(1);
+    let b = grow(true);
+    let c = grow(2.5);
+    let d = grow("s");
+    let e = other(1);
+    println(e);
+}
+"#;
+
+fn analyze_fan_out() -> (yunilang::ast::Program, SemanticAnalyzer) {
+    let tokens = Lexer::new(FAN_OUT_PROGRAM).collect_tokens();
+    let program = Parser::new(tokens).parse().unwrap();
+    let mut analyzer = SemanticAnalyzer::new();
+    analyzer.analyze(&program).unwrap();
+    (program, analyzer)
+}
+
+#[test]
+fn test_instantiations_are_attributed_to_requesting_root() {
+    let (program, analyzer) = analyze_fan_out();
+
+    let (_, _, stats) = monomorphize_program_with_stats(program, analyzer.type_table, None).unwrap();
+
+    // grow<T> 4個 + Wrap<T> 4個 + leaf<T> 8個（T と Wrap<T>）はすべて grow が起点
+    assert_eq!(stats.instantiations, 17);
+    assert_eq!(
+        stats.top_roots(5),
+        vec![("grow".to_string(), 16), ("other".to_string(), 1)]
+    );
+    assert_eq!(stats.root_of("leaf_Wrap_f64"), "grow");
+    assert_eq!(stats.root_of("other_i32"), "other");
+    assert_eq!(stats.root_of("main"), "main");
+}
+
+#[test]
+fn test_instantiation_limit_aborts_with_offenders() {
+    let (program, analyzer) = analyze_fan_out();
+
+    let result = monomorphize_program_with_stats(program, analyzer.type_table, Some(10));
+
+    match result {
+        Err(YuniError::CodeSize(CodeSizeError::InstantiationLimitExceeded { limit, roots })) => {
+            assert_eq!(limit, 10);
+            assert_eq!(roots[0].0, "grow");
+        }
+        other => panic!("インスタンス数の上限エラーを期待しましたが {:?} でした", other.map(|(_, _, stats)| stats)),
+    }
+}
+
+#[test]
+fn test_source_map_records_generic_origin_and_call_site() {
+    let input = r#"
+package test
+
+fn second<T>(a: T, b: T): T {
+    return b;
+}
+
+fn main() {
+    let x = second(1, 2);
+    let y = second(1.5, 0.5);
+}
+"#;
+
+    let tokens = Lexer::new(input).collect_tokens();
+    let program = Parser::new(tokens).parse().unwrap();
+    let mut analyzer = SemanticAnalyzer::new();
+    analyzer.analyze(&program).unwrap();
+
+    let (_, source_map) = monomorphize_program_with_types(program, analyzer.type_table).unwrap();
+
+    assert_eq!(source_map.len(), 2);
+    let origin = source_map.origin("second_f64").unwrap();
+    assert_eq!(origin.generic_name, "second");
+    assert_eq!(origin.to_string(), "second<T=f64>");
+    let requested = &input[origin.requested_at.start..origin.requested_at.end];
+    assert_eq!(requested, "second(1.5, 0.5)");
+
+    // 単相化で生成していないアイテムはそのままの名前になる
+    assert_eq!(source_map.display_name("second_i32"), "second<T=i32>");
+    assert_eq!(source_map.display_name("main"), "main");
+    assert!(source_map.origin("main").is_none());
+}
+
+#[test]
+fn test_same_instantiation_from_multiple_functions() {
+    // 同じインスタンスを別々の関数・式の位置から要求しても生成は1つだけで、
+    // すべての呼び出しが生成されたインスタンスを指す
+    let input = r#"
+package test
+
+type Point struct {
+    x: i64,
+}
+
+impl fn shift(p: &Point, dx: i64): i64 {
+    return p.x + dx;
+}
+
+fn identity<T>(x: T): T {
+    return x;
+}
+
+fn from_variable(): i64 {
+    let x: i64 = 5;
+    return identity(x);
+}
+
+fn from_unary(): i64 {
+    return -identity(7i64);
+}
+
+fn from_method_arg(p: Point): i64 {
+    if p.x > 0 {
+        return 0;
+    } else if identity(p.x) < 0 {
+        return p.shift(identity(1i64));
+    }
+    return identity(identity(p.x));
+}
+
+fn main() {
+    let a = identity(5i64);
+    let b = from_variable() + from_unary() + from_method_arg(Point { x: a });
+}
+"#;
+
+    let lexer = Lexer::new(input);
+    let tokens = lexer.collect_tokens();
+    let mut parser = Parser::new(tokens);
+    let program = parser.parse().unwrap();
+
+    let mut analyzer = SemanticAnalyzer::new();
+    analyzer.analyze(&program).unwrap();
+
+    let (monomorphized, _) = monomorphize_program_with_types(program, analyzer.type_table).unwrap();
+    let function_names: Vec<&str> = monomorphized
+        .items
+        .iter()
+        .filter_map(|item| match item {
+            Item::Function(func) => Some(func.name.as_str()),
+            _ => None,
+        })
+        .collect();
+
+    let instances: Vec<&&str> = function_names.iter().filter(|name| name.starts_with("identity")).collect();
+    assert_eq!(instances, vec![&"identity_i64"], "{:?}", function_names);
+
+    // 呼び出し先が置き換え漏れのジェネリック関数のまま残っていない
+    let dump = format!("{:?}", monomorphized);
+    assert!(!dump.contains("name: \"identity\""), "{}", dump);
+}
+
+#[test]
+fn test_explicit_type_arguments_select_instance() {
+    // 引数から推論できない型引数も、明示すればその型で具体化される
+    let input = r#"
+package test
+
+struct Pair<T, U> {
+    first: T,
+    second: U,
+}
+
+fn empty<T>(): i64 {
+    return 0;
+}
+
+fn wrap<T>(): i64 {
+    return empty::<T>();
+}
+
+fn main() {
+    let a = empty::<i64>();
+    let b = wrap::<u8>();
+    let p = Pair<i64, f64> { first: 1, second: 2 };
+}
+"#;
+
+    let lexer = Lexer::new(input);
+    let tokens = lexer.collect_tokens();
+    let mut parser = Parser::new(tokens);
+    let program = parser.parse().unwrap();
+
+    let mut analyzer = SemanticAnalyzer::new();
+    analyzer.analyze(&program).unwrap();
+
+    let (monomorphized, _) = monomorphize_program_with_types(program, analyzer.type_table).unwrap();
+    let mut names: Vec<&str> = monomorphized
+        .items
+        .iter()
+        .filter_map(|item| match item {
+            Item::Function(func) => Some(func.name.as_str()),
+            Item::TypeDef(TypeDef::Struct(s)) => Some(s.name.as_str()),
+            _ => None,
+        })
+        .collect();
+    names.sort();
+
+    assert_eq!(names, vec!["Pair_i64_f64", "empty_i64", "empty_u8", "main", "wrap_u8"]);
+}
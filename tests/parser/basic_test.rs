@@ -79,6 +79,63 @@ fn test_imports() {
     assert!(ast.imports[2].alias.is_none());
 }
 
+#[test]
+fn test_selective_imports() {
+    // 要素を選択するインポート（別名と末尾のカンマを含む）
+    let source = r#"
+    package main
+
+    import "std/collections" { Vec, HashMap as Map, }
+    import std.io {
+        println,
+    }
+    import foo.bar { baz, qux as q }
+
+    fn main() {
+    }
+    "#;
+
+    let ast = assert_parse_success(source);
+    assert_eq!(ast.imports.len(), 3);
+
+    let names = |import: &Import| -> Vec<(String, Option<String>)> {
+        import.symbols.iter().map(|symbol| (symbol.name.clone(), symbol.alias.clone())).collect()
+    };
+
+    assert_eq!(ast.imports[0].path, "std/collections");
+    assert!(ast.imports[0].alias.is_none());
+    assert_eq!(
+        names(&ast.imports[0]),
+        [("Vec".to_string(), None), ("HashMap".to_string(), Some("Map".to_string()))]
+    );
+    assert_eq!(ast.imports[0].symbols[1].local_name(), "Map");
+    let span = ast.imports[0].symbols[1].span;
+    assert_eq!(&source[span.start..span.end], "HashMap as Map");
+
+    assert_eq!(ast.imports[1].path, "std.io");
+    assert_eq!(names(&ast.imports[1]), [("println".to_string(), None)]);
+
+    assert_eq!(ast.imports[2].path, "foo.bar");
+    assert_eq!(
+        names(&ast.imports[2]),
+        [("baz".to_string(), None), ("qux".to_string(), Some("q".to_string()))]
+    );
+
+    // 印字して解析し直しても同じ
+    let printed = print_program(&ast);
+    assert!(printed.contains("import \"std/collections\" { Vec, HashMap as Map }"), "{}", printed);
+    let reparsed = assert_parse_success(&printed);
+    assert_eq!(names(&reparsed.imports[2]), names(&ast.imports[2]));
+}
+
+#[test]
+fn test_invalid_selective_imports() {
+    // 空の一覧、別名との併用、カンマのない一覧はエラー
+    assert_parse_error("package main\nimport \"std/io\" { }\nfn main() {}");
+    assert_parse_error("package main\nimport \"std/io\" as io { println }\nfn main() {}");
+    assert_parse_error("package main\nimport \"std/io\" { println read_line }\nfn main() {}");
+}
+
 #[test]
 fn test_complex_program() {
     // 複雑なプログラム全体のテスト
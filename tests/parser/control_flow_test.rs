@@ -106,4 +106,80 @@ fn test_for_loops() {
         assert!(has_while);
         // for文は現在コメントアウトされているため、チェックをスキップ
     }
-}
\ No newline at end of file
+}
+#[test]
+fn test_range_patterns() {
+    // `..=` は終端を含み、`..` は終端を含まない整数の範囲パターン
+    let source = r#"
+    package main
+
+    fn main() {
+        let x = match code {
+            1..=5 => 1,
+            10..20 => 2,
+            _ => 0,
+        };
+    }
+    "#;
+
+    let ast = assert_parse_success(source);
+    let Item::Function(ref func) = ast.items[0] else { panic!("expected function") };
+    let Statement::Let(ref let_stmt) = func.body.statements[0] else { panic!("expected let") };
+    let Some(Expression::Match(ref match_expr)) = let_stmt.init else { panic!("expected match") };
+    assert_eq!(match_expr.arms[0].pattern, Pattern::Range { start: 1, end: 5, inclusive: true });
+    assert_eq!(match_expr.arms[1].pattern, Pattern::Range { start: 10, end: 20, inclusive: false });
+    assert_eq!(&source[match_expr.arms[1].span.start..match_expr.arms[1].span.end], "10..20");
+
+    // 空の範囲は構文エラー
+    assert_parse_error("package main\nfn main() { let x = match 1 { 5..=1 => 1, _ => 0 }; }");
+    assert_parse_error("package main\nfn main() { let x = match 1 { 3..3 => 1, _ => 0 }; }");
+}
+
+#[test]
+fn test_or_and_binding_patterns() {
+    // `|` で区切った選択肢はorパターン、`name @ pat` は一致した値に名前を付けるパターン。
+    // `@` は `|` より強く結合し、括弧で囲んだorパターンはタプルではなく選択肢のまとまりになる
+    let source = r#"
+    package main
+
+    fn main() {
+        let x = match code {
+            1 | 2 | 3 => 1,
+            n @ 10..=19 | n @ 30..40 => n,
+            digit @ (4 | 5) => digit,
+            Shape::Circle(r) | Shape::Square(r) => r,
+            _ => 0,
+        };
+    }
+    "#;
+
+    let ast = assert_parse_success(source);
+    let Item::Function(ref func) = ast.items[0] else { panic!("expected function") };
+    let Statement::Let(ref let_stmt) = func.body.statements[0] else { panic!("expected let") };
+    let Some(Expression::Match(ref match_expr)) = let_stmt.init else { panic!("expected match") };
+
+    let literal = |value| Pattern::Literal(LiteralPattern::Integer(value));
+    assert_eq!(match_expr.arms[0].pattern, Pattern::Or(vec![literal(1), literal(2), literal(3)]));
+    assert_eq!(
+        match_expr.arms[1].pattern,
+        Pattern::Or(vec![
+            Pattern::Binding("n".to_string(), Box::new(Pattern::Range { start: 10, end: 19, inclusive: true })),
+            Pattern::Binding("n".to_string(), Box::new(Pattern::Range { start: 30, end: 40, inclusive: false })),
+        ])
+    );
+    assert_eq!(
+        match_expr.arms[2].pattern,
+        Pattern::Binding("digit".to_string(), Box::new(Pattern::Or(vec![literal(4), literal(5)])))
+    );
+    assert!(matches!(&match_expr.arms[3].pattern, Pattern::Or(alternatives) if alternatives.len() == 2));
+
+    // 出力したパターンは同じ形で読み直せる
+    let printed: Vec<String> = match_expr.arms.iter().map(|arm| print_pattern(&arm.pattern)).collect();
+    assert_eq!(printed[0], "1 | 2 | 3");
+    assert_eq!(printed[1], "n @ 10..=19 | n @ 30..40");
+    assert_eq!(printed[2], "digit @ (4 | 5)");
+    assert_eq!(printed[3], "Shape::Circle(r) | Shape::Square(r)");
+
+    assert_parse_error("package main\nfn main() { let x = match 1 { 1 | => 1, _ => 0 }; }");
+    assert_parse_error("package main\nfn main() { let x = match 1 { n @ => 1, _ => 0 }; }");
+}
@@ -0,0 +1,137 @@
+//! ドキュメントコメントのパーサーテスト
+
+use super::*;
+
+/// ソースを解析し、ASTとどのアイテムにも付かなかったドキュメントコメントの位置を返す
+fn parse_with_misplaced(source: &str) -> (Program, Vec<Span>) {
+    let tokens = Lexer::new(source).collect_tokens();
+    let mut parser = Parser::new(tokens);
+    let program = parser.parse().expect("Parsing should succeed");
+    (program, parser.misplaced_doc_comments())
+}
+
+#[test]
+fn test_doc_comments_attach_to_following_item() {
+    let source = r#"
+    package main
+
+    /// 2つの数値を加算します
+    ///
+    /// 桁あふれは検査しません
+    pub fn add(a: i32, b: i32): i32 {
+        return a + b;
+    }
+
+    fn undocumented() {
+    }
+
+    /// 平面上の点
+    type Point struct {
+        x: f64,
+        y: f64,
+    }
+
+    /// 色
+    type Color enum {
+        Red,
+        Green,
+    }
+
+    /// 距離
+    type Distance f64
+    "#;
+
+    let (ast, misplaced) = parse_with_misplaced(source);
+    assert!(misplaced.is_empty(), "{:?}", misplaced);
+    let docs: Vec<&[String]> = ast.items.iter().map(|item| item.docs()).collect();
+    assert_eq!(docs[0], ["2つの数値を加算します", "", "桁あふれは検査しません"]);
+    assert!(docs[1].is_empty());
+    assert_eq!(docs[2], ["平面上の点"]);
+    assert_eq!(docs[3], ["色"]);
+    assert_eq!(docs[4], ["距離"]);
+}
+
+#[test]
+fn test_doc_comments_attach_across_blank_lines_and_comments() {
+    let source = r#"
+    package main
+
+    /// 初期値を返します
+
+    // 実装メモ（ドキュメントには含めない）
+    /* ブロックコメント */
+    fn initial(): i32 {
+        return 0;
+    }
+    "#;
+
+    let (ast, misplaced) = parse_with_misplaced(source);
+    assert!(misplaced.is_empty(), "{:?}", misplaced);
+    assert_eq!(ast.items[0].docs(), ["初期値を返します"]);
+}
+
+#[test]
+fn test_doc_comments_attach_to_methods() {
+    let source = r#"
+    package main
+
+    type Counter struct {
+        count: i32,
+    }
+
+    /// 現在の値
+    impl fn get(self: &Counter): i32 {
+        return self.count;
+    }
+
+    impl Counter {
+        /// 値が0か
+        pub fn is_zero(&self): bool {
+            return self.count == 0;
+        }
+
+        fn twice(&self): i32 {
+            return self.count * 2;
+        }
+    }
+    "#;
+
+    let (ast, misplaced) = parse_with_misplaced(source);
+    assert!(misplaced.is_empty(), "{:?}", misplaced);
+    let methods: Vec<(&str, &[String])> = ast
+        .items
+        .iter()
+        .filter_map(|item| match item {
+            Item::Method(method) => Some((method.name.as_str(), method.docs.as_slice())),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(methods.len(), 3);
+    assert_eq!(methods[0], ("get", &["現在の値".to_string()][..]));
+    assert_eq!(methods[1], ("is_zero", &["値が0か".to_string()][..]));
+    assert_eq!(methods[2], ("twice", &[][..]));
+}
+
+#[test]
+fn test_misplaced_doc_comments_are_reported() {
+    let source = r#"
+    package main
+
+    fn main() {
+        /// 文の前には書けない
+        let x = 1;
+    }
+
+    fn next() {
+    }
+
+    /// 後にアイテムがない
+    "#;
+
+    let (ast, misplaced) = parse_with_misplaced(source);
+    // 関数の本体の中のドキュメントコメントは次のアイテムにも付かない
+    assert!(ast.items.iter().all(|item| item.docs().is_empty()));
+    assert_eq!(misplaced.len(), 2);
+    assert_eq!(&source[misplaced[0].start..misplaced[0].end], "/// 文の前には書けない");
+    assert_eq!(&source[misplaced[1].start..misplaced[1].end], "/// 後にアイテムがない");
+}
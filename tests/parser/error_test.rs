@@ -1,6 +1,7 @@
 //! エラーケースのパーサーテスト
 
 use super::*;
+use yunilang::error::{DiagnosticError, ParseContext, YuniError};
 
 #[test]
 fn test_missing_package() {
@@ -111,4 +112,122 @@ fn test_invalid_if_syntax() {
     "#;
     
     assert_parse_error(source);
-}
\ No newline at end of file
+}
+/// 構文エラーのメッセージ・位置・解析中の構文要素を取得するヘルパー関数
+fn syntax_error(source: &str) -> (String, Span, Option<ParseContext>) {
+    match parse_source(source) {
+        Err(ParseError::SyntaxError { message, span, context }) => (message, span, context),
+        other => panic!("Expected a syntax error, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_struct_missing_comma_lists_expected_tokens() {
+    // フィールドの後に受け付けるトークンをすべて示し、見つかったトークンを指す
+    let source = "package main\n\nstruct Point {\n    x: i32\n    y: i32,\n}\n";
+    let (message, span, context) = syntax_error(source);
+    assert_eq!(message, "Expected one of `,`, `}`, found `y`");
+    assert_eq!(&source[span.start..span.end], "y");
+
+    // 解析中の構文要素は開始位置（`{`）とともに示す
+    let context = context.expect("the error should name the struct being parsed");
+    assert_eq!(context.description, "struct `Point`'s fields");
+    assert_eq!(&source[context.span.start..context.span.end], "{");
+
+    let diagnostic = DiagnosticError::new(
+        YuniError::Parser(ParseError::SyntaxError { message, span, context: Some(context) }),
+        0,
+    )
+    .to_diagnostic();
+    assert_eq!(diagnostic.labels.len(), 2);
+    assert_eq!(diagnostic.labels[1].message, "while parsing struct `Point`'s fields, starting here");
+}
+
+#[test]
+fn test_call_missing_closing_paren_lists_expected_tokens() {
+    // 二項演算子は個々に列挙せず `an operator` にまとめる
+    let source = "package main\n\nfn main() {\n    let total = add(1, 2;\n}\n";
+    let (message, span, context) = syntax_error(source);
+    assert_eq!(message, "Expected one of `)`, `,`, `as`, an operator, found `;`");
+    assert_eq!(&source[span.start..span.end], ";");
+
+    // 最も内側の構文要素（letではなく呼び出し）を示す
+    let context = context.expect("the error should name the call being parsed");
+    assert_eq!(context.description, "the arguments of `add`");
+    assert_eq!(&source[context.span.start..context.span.end], "(");
+}
+
+#[test]
+fn test_unexpected_end_of_file_points_at_last_token() {
+    // 入力の終わりでは最後のトークンの直後を指す
+    let source = "package main\n\nfn main() {\n    let x = 1;\n";
+    let (message, span, _) = syntax_error(source);
+    assert_eq!(message, "Expected `}`, found end of file");
+    assert_eq!(span.start, source.trim_end().len());
+}
+
+#[test]
+fn test_underscore_in_expression_position() {
+    // `_` はパターンでだけ使え、式の位置では使い方を示すエラーになる
+    let source = "package main\n\nfn main() {\n    let x = _;\n}\n";
+    let (message, span, _) = syntax_error(source);
+    assert_eq!(message, "`_` can only be used in patterns (e.g. `let _ = value;` or a `_ =>` match arm)");
+    assert_eq!(&source[span.start..span.end], "_");
+
+    let (message, _, _) = syntax_error("package main\n\nfn main() {\n    _ = compute();\n}\n");
+    assert!(message.starts_with("`_` can only be used in patterns"), "{}", message);
+
+    // 先頭が `_` の名前は通常の識別子
+    assert_parse_success("package main\n\nfn main() {\n    let _unused = 1;\n    let y = _unused;\n}\n");
+}
+
+/// 解析を続けて、ASTと続けられた構文エラーを返す
+fn parse_recovering(source: &str) -> (Program, Vec<ParseError>) {
+    let mut parser = Parser::new(Lexer::new(source).collect_tokens());
+    let program = parser.parse_recovering().expect("Parsing should recover");
+    (program, parser.recovered_errors().to_vec())
+}
+
+#[test]
+fn test_missing_semicolon_between_lines_is_recovered() {
+    // 改行の後に文が始まれば、セミコロンを書き忘れたものとして解析を続ける
+    let source = "package main\n\nfn main() {\n    let a = 1\n    let b = 2;\n    println(a + b);\n}\n";
+    let (program, errors) = parse_recovering(source);
+    assert_eq!(errors.len(), 1, "{:?}", errors);
+    match &errors[0] {
+        ParseError::SyntaxError { message, span, .. } => {
+            assert_eq!(message, "Missing `;` after expression");
+            // 1つ目の式の終わりを指す
+            assert_eq!(span.start, source.find("1\n").unwrap() + 1);
+            assert_eq!(span.end, span.start);
+        }
+        other => panic!("Expected a syntax error, got {:?}", other),
+    }
+
+    let Item::Function(main) = &program.items[0] else {
+        panic!("Expected a function, got {:?}", program.items[0]);
+    };
+    assert_eq!(main.body.statements.len(), 3);
+    assert!(matches!(&main.body.statements[0], Statement::Let(_)));
+    assert!(matches!(&main.body.statements[1], Statement::Let(_)));
+
+    // 通常の解析では最初のエラーを返す
+    let (message, _, _) = syntax_error(source);
+    assert_eq!(message, "Missing `;` after expression");
+}
+
+#[test]
+fn test_expression_continued_on_next_line_is_not_missing_semicolon() {
+    // 次の行に続く二項演算は1つの式として解析する
+    let source = "package main\n\nfn main() {\n    let a = 1;\n    let total = a\n        + 2;\n    total = total\n        * 3;\n}\n";
+    let (program, errors) = parse_recovering(source);
+    assert!(errors.is_empty(), "{:?}", errors);
+    let Item::Function(main) = &program.items[0] else {
+        panic!("Expected a function, got {:?}", program.items[0]);
+    };
+    assert_eq!(main.body.statements.len(), 3);
+
+    // 同じ行に続く文はこれまでどおり解析エラー
+    let (message, _, _) = syntax_error("package main\n\nfn main() {\n    let a = 1 let b = 2;\n}\n");
+    assert!(message.starts_with("Expected"), "{}", message);
+}
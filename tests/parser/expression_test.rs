@@ -73,6 +73,116 @@ fn test_operator_precedence() {
     }
 }
 
+#[test]
+fn test_membership_operator_precedence() {
+    // `in` は比較演算子と同じ優先順位（算術演算より弱く、`&&` より強い）
+    let source = r#"
+    package main
+    
+    fn main() {
+        let a = x + 1 in values && flag;
+        let b = !(name in table);
+    }
+    "#;
+    
+    let ast = assert_parse_success(source);
+    let Item::Function(ref func) = ast.items[0] else {
+        panic!("main should be a function");
+    };
+    let Statement::Let(LetStatement { init: Some(Expression::Binary(and)), .. }) = &func.body.statements[0] else {
+        panic!("the first statement should bind a binary expression");
+    };
+    assert_eq!(and.op, BinaryOp::And);
+    let Expression::Binary(membership) = and.left.as_ref() else {
+        panic!("the left operand of && should be the membership test");
+    };
+    assert_eq!(membership.op, BinaryOp::In);
+    assert!(matches!(membership.left.as_ref(), Expression::Binary(add) if add.op == BinaryOp::Add));
+    
+    let Statement::Let(LetStatement { init: Some(Expression::Unary(not)), .. }) = &func.body.statements[1] else {
+        panic!("the second statement should bind a negation");
+    };
+    assert_eq!(not.op, UnaryOp::Not);
+    assert!(matches!(not.expr.as_ref(), Expression::Binary(membership) if membership.op == BinaryOp::In));
+}
+
+#[test]
+fn test_explicit_type_arguments() {
+    // 呼び出しの型引数は `::<` で始まり、`a < b > (c)` は比較のまま
+    let source = r#"
+    package main
+    
+    fn main() {
+        let a = make::<i64>();
+        let b = a < b > (c);
+        let p = Pair<i32, f64> { first: 1, second: 2.0 };
+    }
+    "#;
+    
+    let ast = assert_parse_success(source);
+    let Item::Function(ref func) = ast.items[0] else {
+        panic!("main should be a function");
+    };
+    let Statement::Let(LetStatement { init: Some(Expression::Call(call)), .. }) = &func.body.statements[0] else {
+        panic!("the first statement should bind a call");
+    };
+    assert!(matches!(call.callee.as_ref(), Expression::Identifier(id) if id.name == "make"));
+    assert_eq!(call.type_args, vec![Type::I64]);
+    assert!(call.args.is_empty());
+    
+    let Statement::Let(LetStatement { init: Some(Expression::Binary(greater)), .. }) = &func.body.statements[1] else {
+        panic!("the second statement should bind a comparison");
+    };
+    assert_eq!(greater.op, BinaryOp::Gt);
+    assert!(matches!(greater.left.as_ref(), Expression::Binary(less) if less.op == BinaryOp::Lt));
+    assert!(matches!(greater.right.as_ref(), Expression::Identifier(id) if id.name == "c"));
+    
+    let Statement::Let(LetStatement { init: Some(Expression::StructLit(lit)), .. }) = &func.body.statements[2] else {
+        panic!("the third statement should bind a struct literal");
+    };
+    assert_eq!(lit.name.as_deref(), Some("Pair"));
+    assert_eq!(lit.type_args, vec![Type::I32, Type::F64]);
+}
+
+#[test]
+fn test_struct_field_init_shorthand() {
+    // `{ x, y }` の `x` は `x: x` の省略形で、`{ x }` はブロックのまま
+    let source = r#"
+    package main
+    
+    fn main() {
+        let p = Point { x, y: 2 };
+        let q: Point = { x, y };
+        let r = { x };
+    }
+    "#;
+    
+    let ast = assert_parse_success(source);
+    let Item::Function(ref func) = ast.items[0] else {
+        panic!("main should be a function");
+    };
+    let Statement::Let(LetStatement { init: Some(Expression::StructLit(named)), .. }) = &func.body.statements[0] else {
+        panic!("the first statement should bind a struct literal");
+    };
+    assert_eq!(named.name.as_deref(), Some("Point"));
+    assert_eq!(named.fields[0].name, "x");
+    assert!(matches!(&named.fields[0].value, Expression::Identifier(id) if id.name == "x"));
+    assert!(matches!(&named.fields[1].value, Expression::Integer(_)));
+    
+    let Statement::Let(LetStatement { init: Some(Expression::StructLit(anonymous)), .. }) = &func.body.statements[1] else {
+        panic!("the second statement should bind an anonymous struct literal");
+    };
+    assert_eq!(anonymous.name, None);
+    let names: Vec<_> = anonymous.fields.iter().map(|field| field.name.as_str()).collect();
+    assert_eq!(names, ["x", "y"]);
+    assert!(matches!(&anonymous.fields[1].value, Expression::Identifier(id) if id.name == "y"));
+    
+    assert!(matches!(
+        &func.body.statements[2],
+        Statement::Let(LetStatement { init: Some(Expression::Block(_)), .. })
+    ));
+}
+
 #[test]
 fn test_nested_expressions() {
     // ネストした式の解析テスト
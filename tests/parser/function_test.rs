@@ -32,6 +32,30 @@ fn test_function_with_parameters() {
     }
 }
 
+#[test]
+fn test_mutable_function_parameters() {
+    // mut修飾子付きのパラメータ
+    let source = r#"
+    package main
+    
+    fn f(mut count: i32, limit: i32, mut r: &i32) {
+    }
+    "#;
+    
+    let ast = assert_parse_success(source);
+    
+    if let Item::Function(ref func) = ast.items[0] {
+        assert_eq!(func.params[0].name, "count");
+        assert!(func.params[0].is_mut);
+        assert!(!func.params[1].is_mut);
+        assert_eq!(func.params[2].name, "r");
+        assert!(func.params[2].is_mut);
+        assert!(matches!(func.params[2].ty, Type::Reference(_, false)));
+    } else {
+        panic!("Expected function");
+    }
+}
+
 #[test]
 fn test_empty_function_parameters() {
     // 空の関数パラメータリストは有効
@@ -76,4 +100,348 @@ fn test_multiple_functions() {
     for item in &ast.items {
         assert!(matches!(item, Item::Function(_)));
     }
-}
\ No newline at end of file
+}
+#[test]
+fn test_trailing_expression_in_function_body() {
+    // 戻り値型を持つ関数の最後の式は暗黙のreturn文になる
+    let source = r#"
+    package main
+    
+    fn add(a: i32, b: i32): i32 {
+        a + b
+    }
+    
+    fn add_stmt(a: i32, b: i32): i32 {
+        a + b;
+    }
+    
+    fn sign(x: i32): i32 {
+        if x < 0 {
+            return -1;
+        } else {
+            return 1;
+        }
+    }
+    
+    fn greet() {
+        println("hello")
+    }
+    "#;
+    
+    let ast = assert_parse_success(source);
+    let bodies: Vec<&Block> = ast.items.iter().filter_map(|item| match item {
+        Item::Function(func) => Some(&func.body),
+        _ => None,
+    }).collect();
+    
+    assert!(matches!(&bodies[0].statements[..], [Statement::Return(ReturnStatement { value: Some(Expression::Binary(_)), .. })]));
+    // セミコロン付きは式文のまま
+    assert!(matches!(&bodies[1].statements[..], [Statement::Expression(_)]));
+    // 分岐がreturnで終わるifは文のまま
+    assert!(matches!(&bodies[2].statements[..], [Statement::If(_)]));
+    // 戻り値型のない関数では式文のまま
+    assert!(matches!(&bodies[3].statements[..], [Statement::Expression(_)]));
+}
+
+#[test]
+fn test_impl_block_desugars_to_methods() {
+    // implブロックのメソッドはレシーバーの省略形を含めて `impl fn` と同じ宣言になる
+    let source = r#"
+package main
+
+type Point struct {
+    x: f64,
+    y: f64
+}
+
+impl Point {
+    fn len(&self): f64 {
+        return self.x + self.y;
+    }
+
+    pub fn scale(&mut self, k: f64) {
+        self.x = self.x * k;
+    }
+
+    fn into_x(self): f64 {
+        return self.x;
+    }
+
+    fn reset(mut self): Point {
+        self.x = 0.0;
+        return self;
+    }
+}
+"#;
+
+    let ast = assert_parse_success(source);
+    assert_eq!(ast.items.len(), 5);
+    let point = Type::UserDefined("Point".to_string());
+    let expected = [
+        ("len", Type::Reference(Box::new(point.clone()), false), false, false),
+        ("scale", Type::Reference(Box::new(point.clone()), true), false, true),
+        ("into_x", point.clone(), false, false),
+        ("reset", point.clone(), true, false),
+    ];
+    for (item, (name, ty, is_mut, is_public)) in ast.items[1..].iter().zip(expected) {
+        let Item::Method(method) = item else {
+            panic!("Expected method, got {:?}", item);
+        };
+        assert_eq!(method.name, name);
+        assert_eq!(method.receiver.name, None);
+        assert_eq!(method.receiver.ty, ty);
+        assert_eq!(method.receiver.is_mut, is_mut);
+        assert_eq!(method.is_public, is_public);
+        // メソッドのスパンは `fn`（または `pub`）から本体の終わりまで
+        let text = &source[method.span.start..method.span.end];
+        assert!(text.starts_with("fn ") || text.starts_with("pub fn "), "{}", text);
+        assert!(text.ends_with('}'), "{}", text);
+    }
+
+    let Item::Method(scale) = &ast.items[2] else { unreachable!() };
+    assert_eq!(scale.params.len(), 1);
+    assert_eq!(scale.params[0].name, "k");
+    assert_eq!(&source[scale.receiver.span.start..scale.receiver.span.end], "&mut self");
+}
+
+#[test]
+fn test_impl_block_mixes_with_impl_fn() {
+    // 同じ型に `impl fn` とimplブロックの両方でメソッドを定義できる
+    let source = r#"
+package main
+
+impl Counter {
+    fn get(&self): i32 {
+        return self.count;
+    }
+}
+
+impl fn reset(c: &mut Counter) {
+    c.count = 0;
+}
+
+impl Counter {
+    fn named(counter: &Counter): i32 {
+        return counter.count;
+    }
+}
+
+type Counter struct {
+    count: i32
+}
+"#;
+
+    let ast = assert_parse_success(source);
+    let names: Vec<&str> = ast
+        .items
+        .iter()
+        .filter_map(|item| match item {
+            Item::Method(method) => Some(method.name.as_str()),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(names, ["get", "reset", "named"]);
+}
+
+#[test]
+fn test_impl_block_errors() {
+    // implブロックには可視性修飾子を付けられない
+    assert_parse_error("package main\npub impl Point {\n}");
+    // implブロックにはメソッドしか書けない
+    assert_parse_error("package main\nimpl Point {\n    type Inner struct { x: i32 }\n}");
+    // 型を明示したレシーバーはimplブロックの型と一致する必要がある
+    assert_parse_error("package main\nimpl Point {\n    fn f(self: &Other) {\n    }\n}");
+    // 閉じ括弧がない
+    assert_parse_error("package main\nimpl Point {\n    fn f(&self) {\n    }\n");
+}
+
+#[test]
+fn test_inline_modifiers() {
+    let source = r#"
+package main
+
+inline fn double(x: i32): i32 {
+    return x * 2;
+}
+
+pub inline(always) fn square(x: i32): i32 {
+    return x * x;
+}
+
+inline impl fn get(self: &Counter): i32 {
+    return self.count;
+}
+
+impl Counter {
+    pub inline(always) fn reset(&mut self) {
+        self.count = 0;
+    }
+
+    fn plain(&self): i32 {
+        return self.count;
+    }
+}
+"#;
+
+    let ast = assert_parse_success(source);
+    let hints: Vec<(&str, InlineHint)> = ast
+        .items
+        .iter()
+        .filter_map(|item| match item {
+            Item::Function(func) => Some((func.name.as_str(), func.inline)),
+            Item::Method(method) => Some((method.name.as_str(), method.inline)),
+            Item::TypeDef(_) | Item::ExternFunction(_) => None,
+        })
+        .collect();
+    assert_eq!(
+        hints,
+        [
+            ("double", InlineHint::Hint),
+            ("square", InlineHint::Always),
+            ("get", InlineHint::Hint),
+            ("reset", InlineHint::Always),
+            ("plain", InlineHint::None),
+        ]
+    );
+
+    // 整形して読み直しても指定は変わらない
+    let reparsed = assert_parse_success(&print_program(&ast));
+    assert_eq!(reparsed.items.len(), ast.items.len());
+    for (original, printed) in ast.items.iter().zip(&reparsed.items) {
+        match (original, printed) {
+            (Item::Function(a), Item::Function(b)) => assert_eq!(a.inline, b.inline),
+            (Item::Method(a), Item::Method(b)) => assert_eq!(a.inline, b.inline),
+            _ => panic!("Item kinds should match"),
+        }
+    }
+}
+
+#[test]
+fn test_inline_modifier_errors() {
+    // inlineは関数とメソッドにだけ付けられる
+    assert_parse_error("package main\ninline type Point struct { x: i32 }");
+    assert_parse_error("package main\ninline impl Point {\n}");
+    // inline(...) に書けるのは always だけ
+    assert_parse_error("package main\ninline(never) fn f() {\n}");
+    assert_parse_error("package main\ninline() fn f() {\n}");
+}
+
+#[test]
+fn test_function_attributes() {
+    let source = r#"
+package main
+
+/// 借用チェックを行わない
+#[unsafe_no_borrow_check]
+pub fn raw(x: i32): i32 {
+    return x;
+}
+
+#[unsafe_no_borrow_check]
+impl fn get(self: &Counter): i32 {
+    return self.count;
+}
+
+impl Counter {
+    #[unsafe_no_borrow_check]
+    pub inline fn reset(&mut self) {
+        self.count = 0;
+    }
+}
+
+fn plain() {
+}
+"#;
+
+    let ast = assert_parse_success(source);
+    let attributes: Vec<(&str, &[Attribute])> = ast
+        .items
+        .iter()
+        .filter_map(|item| match item {
+            Item::Function(func) => Some((func.name.as_str(), func.attributes.as_slice())),
+            Item::Method(method) => Some((method.name.as_str(), method.attributes.as_slice())),
+            Item::TypeDef(_) | Item::ExternFunction(_) => None,
+        })
+        .collect();
+    let no_borrow_check: &[Attribute] = &[Attribute::UnsafeNoBorrowCheck];
+    assert_eq!(
+        attributes,
+        [("raw", no_borrow_check), ("get", no_borrow_check), ("reset", no_borrow_check), ("plain", &[][..])]
+    );
+    // 属性の前のドキュメントコメントはその関数に付く
+    assert_eq!(ast.items[0].docs(), ["借用チェックを行わない"]);
+
+    // 整形して読み直しても属性は変わらない
+    let reparsed = assert_parse_success(&print_program(&ast));
+    assert_eq!(reparsed.items.len(), ast.items.len());
+    for (original, printed) in ast.items.iter().zip(&reparsed.items) {
+        match (original, printed) {
+            (Item::Function(a), Item::Function(b)) => assert_eq!(a.attributes, b.attributes),
+            (Item::Method(a), Item::Method(b)) => assert_eq!(a.attributes, b.attributes),
+            _ => panic!("Item kinds should match"),
+        }
+    }
+}
+
+#[test]
+fn test_function_attribute_errors() {
+    // 属性は関数とメソッドにだけ付けられる
+    assert_parse_error("package main\n#[unsafe_no_borrow_check]\nstruct Point { x: i32 }");
+    assert_parse_error("package main\n#[unsafe_no_borrow_check]\nimpl Point {\n}");
+    // 知らない属性は書けない
+    assert_parse_error("package main\n#[no_such_attribute]\nfn f() {\n}");
+    assert_parse_error("package main\n#unsafe_no_borrow_check\nfn f() {\n}");
+}
+
+#[test]
+fn test_extern_function_declaration() {
+    // 外部関数は本体の代わりに `;` で終わり、戻り値型は `:` と `->` のどちらでも書ける
+    let source = r#"
+package main
+
+extern fn sqrt(x: f64) -> f64;
+
+/// zlibのバージョン
+extern "C" link("z") fn zlibVersion(): String;
+
+pub extern fn srand(seed: u32);
+"#;
+
+    let ast = assert_parse_success(source);
+    let externs: Vec<&ExternFunctionDecl> = ast
+        .items
+        .iter()
+        .filter_map(|item| match item {
+            Item::ExternFunction(func) => Some(func),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(externs.len(), 3);
+
+    assert_eq!(externs[0].name, "sqrt");
+    assert_eq!(externs[0].abi, "C");
+    assert_eq!(externs[0].params.len(), 1);
+    assert_eq!(externs[0].return_type.as_deref(), Some(&Type::F64));
+
+    assert_eq!(externs[1].link.as_deref(), Some("z"));
+    assert_eq!(externs[1].return_type.as_deref(), Some(&Type::String));
+    assert_eq!(externs[1].docs, ["zlibのバージョン"]);
+
+    assert!(externs[2].is_public);
+    assert_eq!(externs[2].return_type, None);
+
+    // 整形して読み直しても同じ宣言になる
+    let printed = print_program(&ast);
+    assert!(printed.contains("extern link(\"z\") fn zlibVersion(): String;"), "{}", printed);
+    assert_eq!(print_program(&assert_parse_success(&printed)), printed);
+}
+
+#[test]
+fn test_extern_function_errors() {
+    // 外部関数は本体・型パラメータを持てず、呼び出し規約は "C" だけ
+    assert_parse_error("package main\nextern fn f(x: i32): i32 {\n    return x;\n}");
+    assert_parse_error("package main\nextern fn f<T>(x: T);");
+    assert_parse_error("package main\nextern \"Rust\" fn f();");
+    assert_parse_error("package main\nextern fn f()");
+    assert_parse_error("package main\ninline extern fn f();");
+}
@@ -115,4 +115,70 @@ fn test_float_literal_with_suffix() {
     } else {
         panic!("Expected function");
     }
-}
\ No newline at end of file
+}
+#[test]
+fn test_numeric_literal_raw_text() {
+    // 16進数・桁区切り・接尾辞付きのリテラルは、値とともに書かれた表記を保持する
+    let source = r#"
+    package main
+    
+    fn main() {
+        let a = 0xFF;
+        let b = 1_000_000;
+        let c = 0x1FFu16;
+        let d = 0b1010_0101u8;
+        let e = 1_000.25f32;
+    }
+    "#;
+    
+    let ast = assert_parse_success(source);
+    let Item::Function(ref func) = ast.items[0] else {
+        panic!("Expected function");
+    };
+    let literals: Vec<(String, String)> = func.body.statements.iter().map(|stmt| match stmt {
+        Statement::Let(LetStatement { init: Some(Expression::Integer(lit)), .. }) => (lit.value.to_string(), lit.raw.clone()),
+        Statement::Let(LetStatement { init: Some(Expression::Float(lit)), .. }) => (lit.value.to_string(), lit.raw.clone()),
+        _ => panic!("Expected numeric literal"),
+    }).collect();
+    let expected = [
+        ("255", "0xFF"),
+        ("1000000", "1_000_000"),
+        ("511", "0x1FFu16"),
+        ("165", "0b1010_0101u8"),
+        ("1000.25", "1_000.25f32"),
+    ];
+    assert_eq!(literals, expected.map(|(value, raw)| (value.to_string(), raw.to_string())));
+    
+    // 整形しても書かれた表記のまま出力する
+    let printed = print_program(&ast);
+    for (_, raw) in expected {
+        assert!(printed.contains(raw), "{} should be printed as written:\n{}", raw, printed);
+    }
+}
+
+#[test]
+fn test_bytes_literal() {
+    // バイト列リテラルはNULや0xffを含むバイト列になり、整形しても同じ値に戻る
+    let source = r#"
+    package main
+    
+    fn main() {
+        let frame = b"GIF\x00\xff\n\"{}`";
+        let empty = b"";
+    }
+    "#;
+    
+    let ast = assert_parse_success(source);
+    let Item::Function(ref func) = ast.items[0] else {
+        panic!("Expected function");
+    };
+    let values: Vec<Vec<u8>> = func.body.statements.iter().map(|stmt| match stmt {
+        Statement::Let(LetStatement { init: Some(Expression::Bytes(lit)), .. }) => lit.value.clone(),
+        _ => panic!("Expected bytes literal"),
+    }).collect();
+    assert_eq!(values, [b"GIF\x00\xff\n\"{}`".to_vec(), Vec::new()]);
+    
+    let printed = print_program(&ast);
+    assert!(printed.contains(r#"b"GIF\x00\xff\n\"{}\x60""#), "{}", printed);
+    assert_eq!(print_program(&assert_parse_success(&printed)), printed);
+}
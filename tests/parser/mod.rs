@@ -46,4 +46,8 @@ mod template_string_advanced_test;
 #[cfg(test)]
 mod error_test;
 #[cfg(test)]
-mod visibility_test;
\ No newline at end of file
+mod visibility_test;
+#[cfg(test)]
+mod script_test;
+#[cfg(test)]
+mod doc_comment_test;
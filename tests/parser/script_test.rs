@@ -0,0 +1,55 @@
+//! スクリプトモードのパーサーテスト
+
+use super::*;
+
+/// ソースコードをスクリプトとして解析するヘルパー関数
+fn parse_script(source: &str) -> Result<Program, ParseError> {
+    let tokens: Vec<_> = Lexer::new(source).collect_tokens();
+    Parser::new(tokens).parse_script()
+}
+
+#[test]
+fn test_script_wraps_statements_into_main() {
+    // トップレベルの文は暗黙のmainになり、関数はその外に出る
+    let source = "let x = 1;\nfn double(n: i32): i32 {\n    return n * 2;\n}\nprintln(double(x))\n";
+
+    let program = parse_script(source).expect("Parsing should succeed");
+    assert_eq!(program.package.name, "main");
+    assert_eq!(program.items.len(), 2);
+    assert!(matches!(&program.items[0], Item::Function(func) if func.name == "double"));
+
+    let Item::Function(main) = &program.items[1] else {
+        panic!("Expected implicit main, got {:?}", program.items[1]);
+    };
+    assert_eq!(main.name, "main");
+    assert!(main.params.is_empty() && main.return_type.is_none());
+    assert_eq!(main.body.statements.len(), 2);
+    // 文の位置はソースコードのまま
+    let Statement::Let(let_stmt) = &main.body.statements[0] else {
+        panic!("Expected let statement");
+    };
+    assert_eq!(&source[let_stmt.span.start..let_stmt.span.end], "let x = 1;");
+    assert_eq!(&source[main.span.start..main.span.end], source.trim_end());
+}
+
+#[test]
+fn test_script_detection_and_explicit_main() {
+    // package宣言がなく文から始まる入力はスクリプト
+    let tokens: Vec<_> = Lexer::new("let x = 1;").collect_tokens();
+    assert!(Parser::new(tokens).starts_with_statement());
+    let tokens: Vec<_> = Lexer::new("fn main() {}").collect_tokens();
+    assert!(!Parser::new(tokens).starts_with_statement());
+    let tokens: Vec<_> = Lexer::new("package main\nlet x = 1;").collect_tokens();
+    assert!(!Parser::new(tokens).starts_with_statement());
+
+    // アイテムだけのスクリプトはそのまま
+    let program = parse_script("package main\nfn main() {\n}\n").expect("Parsing should succeed");
+    assert_eq!(program.items.len(), 1);
+
+    // トップレベルの文とmainは両立しない
+    let result = parse_script("println(1);\nfn main() {\n}\n");
+    assert!(matches!(
+        result,
+        Err(ParseError::SyntaxError { ref message, .. }) if message.contains("fn main")
+    ), "{:?}", result);
+}
@@ -85,6 +85,102 @@ fn test_return_statements() {
     }
 }
 
+#[test]
+fn test_defer_statements() {
+    // defer文の解析テスト
+    let source = r#"
+    package main
+    
+    fn main() {
+        defer {
+            println("first");
+        }
+        defer { println("second"); }
+    }
+    "#;
+    
+    let ast = assert_parse_success(source);
+    
+    if let Item::Function(func) = &ast.items[0] {
+        assert_eq!(func.body.statements.len(), 2);
+        for stmt in &func.body.statements {
+            if let Statement::Defer(defer_stmt) = stmt {
+                assert_eq!(defer_stmt.body.statements.len(), 1);
+            } else {
+                panic!("Expected defer statement, got {:?}", stmt);
+            }
+        }
+    } else {
+        panic!("Expected function");
+    }
+}
+
+#[test]
+fn test_local_function_statements() {
+    // 文の位置の関数宣言（ローカル関数）の解析テスト
+    let source = r#"
+    package main
+
+    fn main() {
+        /// 2倍にする
+        fn double(x: i32): i32 {
+            x * 2
+        }
+        println(double(21));
+    }
+    "#;
+
+    let ast = assert_parse_success(source);
+
+    let Item::Function(func) = &ast.items[0] else {
+        panic!("Expected function");
+    };
+    assert_eq!(func.body.statements.len(), 2);
+    let Statement::Function(local) = &func.body.statements[0] else {
+        panic!("Expected local function, got {:?}", func.body.statements[0]);
+    };
+    assert_eq!(local.name, "double");
+    assert_eq!(local.params.len(), 1);
+    assert_eq!(local.docs, vec!["2倍にする".to_string()]);
+
+    // 出力したソースコードを再び解析すると同じ形になる
+    let printed = print_program(&ast);
+    assert!(printed.contains("    /// 2倍にする\n    fn double(x: i32): i32 {\n        return x * 2;\n    }"), "{}", printed);
+    assert_eq!(assert_parse_success(&printed).items.len(), 1);
+}
+
+#[test]
+fn test_destructuring_let_statements() {
+    // タプルと構造体のパターンのlet文と、要素ごとのmutの解析テスト
+    let source = r#"
+    package main
+    
+    fn main() {
+        let (mut a, (b, _)) = get();
+        let Point { mut x, y: height } = p;
+    }
+    "#;
+    
+    let ast = assert_parse_success(source);
+    
+    let Item::Function(func) = &ast.items[0] else {
+        panic!("Expected function");
+    };
+    let patterns: Vec<&Pattern> = func.body.statements.iter().map(|stmt| match stmt {
+        Statement::Let(let_stmt) => &let_stmt.pattern,
+        other => panic!("Expected let statement, got {:?}", other),
+    }).collect();
+    assert_eq!(patterns[0], &Pattern::Tuple(vec![
+        Pattern::Identifier("a".to_string(), true),
+        Pattern::Tuple(vec![Pattern::Identifier("b".to_string(), false), Pattern::Wildcard]),
+    ]));
+    assert_eq!(patterns[1], &Pattern::Struct("Point".to_string(), vec![
+        ("x".to_string(), Pattern::Identifier("x".to_string(), true)),
+        ("y".to_string(), Pattern::Identifier("height".to_string(), false)),
+    ]));
+    assert_eq!(print_pattern(patterns[0]), "(mut a, (b, _))");
+}
+
 #[test]
 fn test_function_calls() {
     // 関数呼び出しの解析テスト
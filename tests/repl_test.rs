@@ -0,0 +1,143 @@
+//! REPLのセッションのテスト
+//!
+//! 入力を順に評価するスクリプトでセッションを動かし、前方参照・未定義の関数の呼び出し・
+//! 再定義の扱いを検証する。
+
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+use inkwell::context::Context;
+use yunilang::error::{AnalyzerError, YuniError};
+use yunilang::repl::{ReplOutput, ReplSession};
+
+/// 入力を評価し、成功することを確認するヘルパー関数
+fn eval(session: &mut ReplSession<'_>, input: &str) -> ReplOutput {
+    session
+        .eval(input)
+        .unwrap_or_else(|e| panic!("{:?} should be evaluated: {:?}", input, e))
+}
+
+/// 入力の式の値を返すヘルパー関数
+fn value(session: &mut ReplSession<'_>, input: &str) -> String {
+    eval(session, input).value.unwrap_or_else(|| panic!("{:?} should have a value", input))
+}
+
+#[test]
+fn test_forward_reference_then_definition_then_call() {
+    let context = Context::create();
+    let mut session = ReplSession::new(&context);
+
+    // gを定義する前にgを呼び出すfを定義できる
+    let output = eval(&mut session, "fn f(): i32 { return g() + 1; }");
+    assert_eq!(output.defined, ["f"]);
+    assert_eq!(output.pending, [("f".to_string(), vec!["g".to_string()])]);
+
+    // gを定義するとfもコンパイルされる
+    let output = eval(&mut session, "fn g(): i32 { return 41; }");
+    assert_eq!(output.defined, ["g"]);
+    assert!(output.pending.is_empty(), "{:?}", output.pending);
+
+    assert_eq!(value(&mut session, "f()"), "42");
+}
+
+#[test]
+fn test_call_while_callee_is_missing() {
+    let context = Context::create();
+    let mut session = ReplSession::new(&context);
+
+    eval(&mut session, "fn twice(): i32 { return helper() * 2; }");
+    eval(&mut session, "fn outer(): i32 { return twice() + 1; }");
+
+    // 保留中の関数の呼び出しは、待っている関数の名前を含むエラーになる
+    match session.eval("outer()") {
+        Err(YuniError::Analyzer(AnalyzerError::IncompleteFunction { name, missing, .. })) => {
+            assert_eq!(name, "outer");
+            assert_eq!(missing, ["helper"]);
+        }
+        other => panic!("Expected IncompleteFunction, got: {:?}", other),
+    }
+
+    // エラーの後もセッションは続き、定義がそろえば呼び出せる
+    eval(&mut session, "fn helper(): i32 { return 10; }");
+    assert_eq!(value(&mut session, "outer()"), "21");
+}
+
+#[test]
+fn test_redefinition_changes_next_call() {
+    let context = Context::create();
+    let mut session = ReplSession::new(&context);
+
+    eval(&mut session, "fn base(): i64 { return 1; }");
+    eval(&mut session, "fn scaled(n: i64): i64 { return base() * n; }");
+    assert_eq!(value(&mut session, "scaled(5)"), "5");
+
+    // 再定義すると、以前にコンパイルした呼び出し元からも新しい定義が呼ばれる
+    let output = eval(&mut session, "fn base(): i64 { return 3; }");
+    assert_eq!(output.defined, ["base"]);
+    assert_eq!(value(&mut session, "scaled(5)"), "15");
+    assert_eq!(value(&mut session, "base()"), "3");
+}
+
+#[test]
+fn test_rejected_redefinition_keeps_previous_definition() {
+    let context = Context::create();
+    let mut session = ReplSession::new(&context);
+
+    eval(&mut session, "fn answer(): i32 { return 42; }");
+    let error = session.eval("fn answer(): i32 { return \"forty-two\"; }");
+    assert!(error.is_err(), "{:?}", error.map(|output| output.defined));
+    assert_eq!(value(&mut session, "answer()"), "42");
+}
+
+#[test]
+fn test_mutual_recursion_across_inputs() {
+    let context = Context::create();
+    let mut session = ReplSession::new(&context);
+
+    let output = eval(&mut session, "fn is_even(n: i32): bool { if n == 0 { return true; } return is_odd(n - 1); }");
+    assert_eq!(output.pending, [("is_even".to_string(), vec!["is_odd".to_string()])]);
+    let output = eval(&mut session, "fn is_odd(n: i32): bool { if n == 0 { return false; } return is_even(n - 1); }");
+    assert!(output.pending.is_empty(), "{:?}", output.pending);
+
+    assert_eq!(value(&mut session, "is_even(10)"), "true");
+    assert_eq!(value(&mut session, "let n = 7; is_odd(n)"), "true");
+}
+
+#[test]
+fn test_types_and_statements() {
+    let context = Context::create();
+    let mut session = ReplSession::new(&context);
+
+    eval(&mut session, "struct Point { x: f64, y: f64 }");
+    eval(&mut session, "fn norm2(p: Point): f64 { return p.x * p.x + p.y * p.y; }");
+    assert_eq!(value(&mut session, "norm2(Point { x: 3.0, y: 4.0 })"), "25");
+
+    // 値を持たない入力と、文字列の値
+    assert_eq!(eval(&mut session, "let unused = 1;").value, None);
+    assert_eq!(value(&mut session, "\"yuni\""), "\"yuni\"");
+}
+
+#[test]
+fn test_repl_command_runs_scripted_session() {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_yunilang"))
+        .arg("repl")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("コンパイラの起動に失敗しました");
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(b"fn f(): i32 {\n    return g() * 2;\n}\nf()\nfn g(): i32 { return 21; }\nf()\n:quit\n")
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+
+    assert!(output.status.success(), "{}", stderr);
+    assert!(stdout.contains("f waits for g to be defined"), "{}", stdout);
+    assert!(stderr.contains("関数 f は未定義の関数 g を呼び出すため、まだ実行できません"), "{}", stderr);
+    assert_eq!(stdout.lines().last(), Some("42"), "{}", stdout);
+}
@@ -0,0 +1,94 @@
+//! ソースファイルの読み込みのテスト
+//!
+//! 読み込みに失敗した場合の種類ごとのエラーメッセージと、BOM付きのファイルの扱いを確認する。
+
+use std::fs;
+use std::path::Path;
+
+use tempfile::TempDir;
+use yunilang::error::YuniError;
+use yunilang::lexer::{Lexer, Token};
+use yunilang::source_file::{has_source_extension, read_source};
+
+/// 読み込みのエラーメッセージ（`YuniError::Io` 以外ならパニック）
+fn read_error(path: &Path) -> String {
+    match read_source(path) {
+        Err(YuniError::Io(message)) => message,
+        other => panic!("Expected an Io error, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_missing_file() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("does_not_exist.yuni");
+    let message = read_error(&path);
+    assert!(message.contains("Source file not found"), "{}", message);
+    assert!(message.contains(&path.display().to_string()), "{}", message);
+}
+
+#[test]
+fn test_directory_input() {
+    let dir = TempDir::new().unwrap();
+    let message = read_error(dir.path());
+    assert!(message.contains("is a directory, not a source file"), "{}", message);
+    assert!(message.contains(&dir.path().display().to_string()), "{}", message);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_unreadable_file() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("secret.yuni");
+    fs::write(&path, "package main\n").unwrap();
+    fs::set_permissions(&path, fs::Permissions::from_mode(0o000)).unwrap();
+    // rootなど権限を無視できるユーザーでは読めてしまうため確認できない
+    if fs::read(&path).is_ok() {
+        return;
+    }
+    let message = read_error(&path);
+    assert!(message.contains("Permission denied"), "{}", message);
+    assert!(message.contains(&path.display().to_string()), "{}", message);
+}
+
+#[test]
+fn test_invalid_utf8() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("latin1.yuni");
+    // "caf\xE9" はLatin-1のé（UTF-8では不正なバイト列）
+    fs::write(&path, b"package main\n// caf\xE9\n").unwrap();
+    let message = read_error(&path);
+    assert!(message.contains("is not valid UTF-8"), "{}", message);
+    assert!(message.contains("byte offset 19"), "{}", message);
+    assert!(message.contains("UTF-8 encoding"), "{}", message);
+    assert!(message.contains(&path.display().to_string()), "{}", message);
+
+    // BOMの後の不正なバイト列の位置はファイルの先頭から数える
+    fs::write(&path, b"\xEF\xBB\xBFpackage main\n// caf\xE9\n").unwrap();
+    assert!(read_error(&path).contains("byte offset 22"));
+}
+
+#[test]
+fn test_bom_is_stripped() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("bom.yuni");
+    fs::write(&path, b"\xEF\xBB\xBFpackage main\n").unwrap();
+    let source = read_source(&path).expect("BOM-prefixed UTF-8 should be accepted");
+    assert_eq!(source, "package main\n");
+
+    // 位置はBOMを除いたソースコードの先頭から数える
+    let tokens = Lexer::new(&source).collect_tokens();
+    assert_eq!(tokens[0].token, Token::Package);
+    assert_eq!(tokens[0].span.start, 0);
+    assert_eq!(tokens[1].span.start, 8);
+}
+
+#[test]
+fn test_source_extension() {
+    assert!(has_source_extension(Path::new("main.yuni")));
+    assert!(has_source_extension(Path::new("dir/main.yuni")));
+    assert!(!has_source_extension(Path::new("main.rs")));
+    assert!(!has_source_extension(Path::new("main")));
+}
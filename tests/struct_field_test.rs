@@ -277,4 +277,112 @@ fn main() {
     
     // モジュールが正しく生成されたことを確認
     assert!(module.verify().is_ok());
-}
\ No newline at end of file
+}
+/// Vecの要素から辿る多段のフィールドアクセスのテスト
+#[test]
+fn test_field_chain_through_vec_element() {
+    let input = r#"
+package test
+
+type Address struct {
+    zip: i32,
+}
+
+type Customer struct {
+    id: i32,
+    address: Address,
+}
+
+type Order struct {
+    customer: Customer,
+    total: f64,
+}
+
+fn main() {
+    let orders = Vec<Order>[
+        Order { customer: Customer { id: 7, address: Address { zip: 100 } }, total: 1.5 },
+        Order { customer: Customer { id: 8, address: Address { zip: 200 } }, total: 2.5 }
+    ];
+    let i: i32 = 1;
+    let zip: i32 = orders[i].customer.address.zip;
+    let total: f64 = orders[i].total;
+    println(zip, " ", total);
+}
+"#;
+
+    // レクサー
+    let lexer = Lexer::new(input);
+    let tokens: Vec<TokenWithPosition> = lexer.collect_tokens();
+    
+    // パーサー
+    let mut parser = Parser::new(tokens);
+    let ast = parser.parse().expect("Parsing failed");
+    
+    // 解析
+    let mut analyzer = SemanticAnalyzer::new();
+    analyzer.analyze(&ast).expect("Analysis failed");
+    
+    // コード生成
+    let context = Context::create();
+    let mut codegen = CodeGenerator::new(&context, "test");
+    codegen.compile_program(&ast).expect("Code generation failed");
+    let module = codegen.get_module();
+    
+    // モジュールが正しく生成されたことを確認
+    assert!(module.verify().is_ok());
+}
+
+/// matchで束縛したEnumのペイロードに対するフィールドアクセスのテスト
+#[test]
+fn test_field_access_on_match_bound_payload() {
+    let input = r#"
+package test
+
+type Item struct {
+    name: String,
+    price: i32,
+}
+
+type Lookup enum {
+    Found(item: Item),
+    Named { item: Item, count: i32 },
+    Missing
+}
+
+fn total(lookup: Lookup): i32 {
+    return match lookup {
+        Lookup::Found(item) if item.price > 100 => item.price - 10,
+        Lookup::Found(item) => item.price,
+        Lookup::Named { item: found, count: n } => found.price * n,
+        Lookup::Missing => 0,
+    };
+}
+
+fn main() {
+    let found = Lookup::Found(Item { name: "apple", price: 120 });
+    let named = Lookup::Named { item: Item { name: "pear", price: 80 }, count: 2 };
+    println(total(found), " ", total(named), " ", total(Lookup::Missing));
+}
+"#;
+
+    // レクサー
+    let lexer = Lexer::new(input);
+    let tokens: Vec<TokenWithPosition> = lexer.collect_tokens();
+    
+    // パーサー
+    let mut parser = Parser::new(tokens);
+    let ast = parser.parse().expect("Parsing failed");
+    
+    // 解析
+    let mut analyzer = SemanticAnalyzer::new();
+    analyzer.analyze(&ast).expect("Analysis failed");
+    
+    // コード生成
+    let context = Context::create();
+    let mut codegen = CodeGenerator::new(&context, "test");
+    codegen.compile_program(&ast).expect("Code generation failed");
+    let module = codegen.get_module();
+    
+    // モジュールが正しく生成されたことを確認
+    assert!(module.verify().is_ok());
+}
@@ -0,0 +1,139 @@
+//! シンボルインデックス（`check --json-symbols`）のテスト
+
+use std::process::Command;
+
+use serde_json::Value;
+use yunilang::analyzer::SemanticAnalyzer;
+use yunilang::lexer::Lexer;
+use yunilang::parser::Parser;
+use yunilang::symbols::SymbolIndex;
+
+const FIXTURE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/symbols.yuni");
+
+/// ソースをパース・解析してシンボルインデックスのJSONを得る
+fn index_json(source: &str) -> Value {
+    let tokens = Lexer::new(source).collect_tokens();
+    let program = Parser::new(tokens).parse().expect("source should parse");
+    let analyzed = SemanticAnalyzer::new().analyze(&program).is_ok();
+    let json = SymbolIndex::build("test.yuni", source, &program, analyzed).to_json();
+    serde_json::from_str(&json).expect("symbol index should be valid JSON")
+}
+
+/// 種類と名前でシンボルを探す
+fn find<'a>(index: &'a Value, kind: &str, name: &str) -> &'a Value {
+    index["symbols"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|s| s["kind"] == kind && s["name"] == name)
+        .unwrap_or_else(|| panic!("{} {} should be indexed", kind, name))
+}
+
+/// 開始・終了位置を (行, 列, 行, 列) で取り出す
+fn range(symbol: &Value) -> (u64, u64, u64, u64) {
+    let pos = |key: &str| (symbol[key]["line"].as_u64().unwrap(), symbol[key]["column"].as_u64().unwrap());
+    let (start_line, start_column) = pos("start");
+    let (end_line, end_column) = pos("end");
+    (start_line, start_column, end_line, end_column)
+}
+
+#[test]
+fn test_symbol_index_of_fixture() {
+    let source = std::fs::read_to_string(FIXTURE).unwrap();
+    let index = index_json(&source);
+
+    assert_eq!(index["analyzed"], true);
+    let entries: Vec<(&str, &str)> = index["symbols"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|s| (s["kind"].as_str().unwrap(), s["name"].as_str().unwrap()))
+        .collect();
+    assert_eq!(
+        entries,
+        vec![
+            ("struct", "Point"),
+            ("field", "x"),
+            ("field", "y"),
+            ("enum", "Shape"),
+            ("variant", "Circle"),
+            ("variant", "Square"),
+            ("variant", "Empty"),
+            ("type_alias", "Meters"),
+            ("method", "norm"),
+            ("method", "scale"),
+            ("function", "area"),
+            ("function", "identity"),
+            ("function", "main"),
+        ]
+    );
+
+    let point = find(&index, "struct", "Point");
+    assert_eq!(point["detail"], "type Point struct");
+    assert_eq!(range(point), (3, 1, 6, 2));
+
+    let field = find(&index, "field", "y");
+    assert_eq!(field["detail"], "y: f64");
+    assert_eq!(field["container"], "Point");
+    assert_eq!(range(field), (5, 5, 5, 11));
+
+    let variant = find(&index, "variant", "Circle");
+    assert_eq!(variant["detail"], "Circle { radius: f64 }");
+    assert_eq!(variant["container"], "Shape");
+    assert_eq!(range(variant), (9, 5, 9, 27));
+    assert_eq!(range(find(&index, "variant", "Empty")), (11, 5, 11, 10));
+
+    assert_eq!(find(&index, "type_alias", "Meters")["detail"], "type Meters f64");
+
+    // メソッドはレシーバーの型に属する
+    let scale = find(&index, "method", "scale");
+    assert_eq!(scale["detail"], "impl fn scale(mut p: &mut Point, factor: f64)");
+    assert_eq!(scale["container"], "Point");
+    assert_eq!(range(scale), (20, 1, 23, 2));
+    assert_eq!(find(&index, "method", "norm")["detail"], "impl fn norm(p: &Point): f64");
+
+    // 可視性修飾子も関数の範囲に含む
+    let area = find(&index, "function", "area");
+    assert_eq!(area["detail"], "pub fn area(s: Shape): f64");
+    assert_eq!(range(area), (25, 1, 31, 2));
+    assert!(area.get("container").is_none());
+
+    assert_eq!(find(&index, "function", "identity")["detail"], "fn identity<T>(value: T): T");
+    assert_eq!(find(&index, "function", "main")["detail"], "fn main()");
+}
+
+#[test]
+fn test_symbol_index_with_analysis_errors() {
+    // 解析エラーがあってもパースできた定義はすべて列挙する
+    let index = index_json(
+        "package main\n\nfn broken(): i32 {\n    return undefined_name;\n}\n\nfn main() {\n}\n",
+    );
+
+    assert_eq!(index["analyzed"], false);
+    let broken = find(&index, "function", "broken");
+    assert_eq!(broken["detail"], "fn broken(): i32");
+    assert_eq!(range(broken), (3, 1, 5, 2));
+    assert_eq!(range(find(&index, "function", "main")), (7, 1, 8, 2));
+}
+
+#[test]
+fn test_symbol_columns_count_characters() {
+    // 列はバイトではなく文字単位で数える
+    let index = index_json("package main\n\n/* 日本語 */ fn main() {\n}\n");
+    assert_eq!(range(find(&index, "function", "main")), (3, 11, 4, 2));
+}
+
+#[test]
+fn test_check_json_symbols_command() {
+    let output = Command::new(env!("CARGO_BIN_EXE_yunilang"))
+        .args(["check", "--json-symbols", FIXTURE])
+        .output()
+        .expect("コンパイラの起動に失敗しました");
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    let index: Value = serde_json::from_slice(&output.stdout).expect("stdout should be a JSON document");
+    assert_eq!(index["file"], FIXTURE);
+    assert_eq!(index["analyzed"], true);
+    assert_eq!(range(find(&index, "method", "norm")), (16, 1, 18, 2));
+    assert_eq!(index["symbols"].as_array().unwrap().len(), 13);
+}
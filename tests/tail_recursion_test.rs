@@ -2,7 +2,7 @@
 
 #[cfg(test)]
 mod tests {
-    use yunilang::analyzer::SemanticAnalyzer;
+    use yunilang::analyzer::{mark_tail_calls, SemanticAnalyzer};
     use yunilang::codegen::CodeGenerator;
     use yunilang::lexer::Lexer;
     use yunilang::parser::Parser;
@@ -17,12 +17,15 @@ mod tests {
         
         // 構文解析
         let mut parser = Parser::new(tokens);
-        let ast = parser.parse()?;
+        let mut ast = parser.parse()?;
         
         // セマンティック解析
         let mut analyzer = SemanticAnalyzer::new();
         analyzer.analyze(&ast)?;
         
+        // 末尾位置の呼び出しに印を付ける
+        mark_tail_calls(&mut ast);
+        
         // コード生成
         let context = Context::create();
         let mut codegen = CodeGenerator::new(&context, "test_module");
@@ -135,13 +138,79 @@ mod tests {
         
         let ir = result.unwrap();
         
-        // 相互再帰は自己呼び出しではないため、末尾呼び出し最適化されない
-        assert!(!ir.contains("tail call"), 
-                "Mutual recursion should not be optimized as tail call");
+        // 自己呼び出しでなくても、末尾位置の呼び出しは末尾呼び出しになる
+        assert!(ir.contains("tail call i1 @is_odd"), "is_even should tail call is_odd:\n{}", ir);
+        assert!(ir.contains("tail call i1 @is_even"), "is_odd should tail call is_even:\n{}", ir);
+    }
+
+    #[test]
+    fn test_tail_call_in_if_expression_returns_directly() {
+        let source = r#"
+            package test
+            
+            fn is_even(n: i32) : bool {
+                if n == 0 { true } else { is_odd(n - 1) }
+            }
+            
+            fn is_odd(n: i32) : bool {
+                if n == 0 { false } else { is_even(n - 1) }
+            }
+        "#;
+
+        let ir = compile_and_check_tail_call(source).expect("Compilation should succeed");
+
+        // 分岐を合流させず、末尾呼び出しの直後で戻る
+        let lines: Vec<&str> = ir.lines().map(str::trim).collect();
+        for callee in ["is_odd", "is_even"] {
+            let call = lines
+                .iter()
+                .position(|line| line.contains(&format!("tail call i1 @{}", callee)))
+                .unwrap_or_else(|| panic!("Should tail call {}:\n{}", callee, ir));
+            assert!(lines[call + 1].starts_with("ret i1"), "Tail call should be followed by ret:\n{}", ir);
+        }
+        assert!(!ir.contains("phi"), "Branches should return without merging:\n{}", ir);
+    }
+
+    #[test]
+    fn test_tail_method_call() {
+        let source = r#"
+            package test
+            
+            struct Counter {
+                count: i32,
+            }
+            
+            impl fn down(c: Counter, n: i32) : i32 {
+                if n == 0 { c.count } else { c.down(n - 1) }
+            }
+        "#;
+
+        let ir = compile_and_check_tail_call(source).expect("Compilation should succeed");
+        assert!(ir.contains("tail call"), "Method call in tail position should be a tail call:\n{}", ir);
+    }
+
+    #[test]
+    fn test_call_passing_local_reference_is_not_tail_call() {
+        let source = r#"
+            package test
+            
+            fn read(x: &i32) : i32 {
+                return *x;
+            }
+            
+            fn caller(n: i32) : i32 {
+                let local = n + 1;
+                return read(&local);
+            }
+        "#;
+
+        let ir = compile_and_check_tail_call(source).expect("Compilation should succeed");
+
+        // 呼び出し元のスタック上の変数を指すポインタを渡すため、末尾呼び出しにしない
+        assert!(!ir.contains("tail call"), "Call passing a stack reference must not be a tail call:\n{}", ir);
     }
 
     #[test]
-    #[ignore = "Parser does not yet support match expressions syntax"]
     fn test_tail_call_in_match() {
         let source = r#"
             package test
@@ -0,0 +1,144 @@
+//! ツールチェーン（ターゲットごとのファイル名・ツール探索・リンク引数）のテスト
+
+use std::ffi::{OsStr, OsString};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use yunilang::toolchain::{Linker, LinkerFlavor, Target, TargetEnv, TargetOs};
+
+const LINUX: Target = Target { os: TargetOs::Linux, env: TargetEnv::Gnu };
+const MACOS: Target = Target { os: TargetOs::MacOs, env: TargetEnv::Gnu };
+const WINDOWS_MSVC: Target = Target { os: TargetOs::Windows, env: TargetEnv::Msvc };
+const WINDOWS_GNU: Target = Target { os: TargetOs::Windows, env: TargetEnv::Gnu };
+
+fn strings(args: Vec<OsString>) -> Vec<String> {
+    args.into_iter().map(|arg| arg.to_string_lossy().into_owned()).collect()
+}
+
+#[test]
+fn test_executable_paths() {
+    let input = Path::new("dir/program.yuni");
+    assert_eq!(LINUX.default_executable_path(input), PathBuf::from("dir/program"));
+    assert_eq!(MACOS.default_executable_path(input), PathBuf::from("dir/program"));
+    assert_eq!(WINDOWS_MSVC.default_executable_path(input), PathBuf::from("dir/program.exe"));
+    assert_eq!(WINDOWS_GNU.default_executable_path(input), PathBuf::from("dir/program.exe"));
+
+    // 拡張子のないパスにだけ.exeを付ける
+    assert_eq!(WINDOWS_MSVC.executable_path(Path::new("tmp/yuni_run_1")), PathBuf::from("tmp/yuni_run_1.exe"));
+    assert_eq!(WINDOWS_MSVC.executable_path(Path::new("tmp/app.bin")), PathBuf::from("tmp/app.bin"));
+    assert_eq!(LINUX.executable_path(Path::new("tmp/yuni_run_1")), PathBuf::from("tmp/yuni_run_1"));
+}
+
+#[test]
+fn test_intermediate_file_names() {
+    assert_eq!(LINUX.object_extension(), "o");
+    assert_eq!(WINDOWS_GNU.object_extension(), "o");
+    assert_eq!(WINDOWS_MSVC.object_extension(), "obj");
+
+    assert_eq!(LINUX.static_library_name("yuniruntime"), "libyuniruntime.a");
+    assert_eq!(WINDOWS_GNU.static_library_name("yuniruntime"), "libyuniruntime.a");
+    assert_eq!(WINDOWS_MSVC.static_library_name("yuniruntime"), "yuniruntime.lib");
+
+    // MSVCではclangの既定に合わせて静的CRTでランタイムをビルドする
+    assert!(LINUX.runtime_rustc_args().is_empty());
+    assert!(WINDOWS_GNU.runtime_rustc_args().is_empty());
+    assert_eq!(WINDOWS_MSVC.runtime_rustc_args(), vec!["-Ctarget-feature=+crt-static"]);
+}
+
+#[test]
+fn test_tool_install_candidates() {
+    assert_eq!(WINDOWS_MSVC.tool_file_name("llc"), "llc.exe");
+    assert_eq!(LINUX.tool_file_name("llc"), "llc");
+
+    let candidates = WINDOWS_MSVC.tool_install_candidates("llc", Some(OsStr::new(r"D:\Apps")));
+    assert_eq!(
+        candidates,
+        vec![
+            Path::new(r"D:\Apps").join("LLVM").join("bin").join("llc.exe"),
+            PathBuf::from(r"C:\Program Files\LLVM\bin").join("llc.exe"),
+        ]
+    );
+    assert_eq!(WINDOWS_MSVC.tool_install_candidates("llc", None).len(), 1);
+
+    let candidates = LINUX.tool_install_candidates("llc", None);
+    assert_eq!(candidates[0], PathBuf::from("/usr/bin/llc-18"));
+    assert!(candidates.contains(&PathBuf::from("/usr/local/bin/llc")));
+}
+
+#[test]
+fn test_find_tool_in_path() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("llc.exe"), "").unwrap();
+    fs::write(dir.path().join("clang-cl.exe"), "").unwrap();
+    let path_var = dir.path().as_os_str();
+
+    assert_eq!(WINDOWS_MSVC.find_tool("llc", Some(path_var), None), Some(dir.path().join("llc.exe")));
+
+    // clangがなければclang-clをMSVC形式のリンカとして使う
+    let linker = WINDOWS_MSVC.find_linker(Some(path_var), None).expect("clang-cl should be found");
+    assert_eq!(linker.program, dir.path().join("clang-cl.exe"));
+    assert_eq!(linker.flavor, LinkerFlavor::ClangCl);
+
+    fs::write(dir.path().join("clang.exe"), "").unwrap();
+    let linker = WINDOWS_MSVC.find_linker(Some(path_var), None).expect("clang should be found");
+    assert_eq!(linker.flavor, LinkerFlavor::Gcc);
+}
+
+#[test]
+fn test_link_args() {
+    let output = Path::new("out/program.exe");
+    let inputs = [Path::new("program.obj"), Path::new("yuniruntime.lib")];
+
+    let clang_cl = Linker::from_program(PathBuf::from("clang-cl.exe"));
+    assert_eq!(clang_cl.flavor, LinkerFlavor::ClangCl);
+    let args = strings(WINDOWS_MSVC.link_args(&clang_cl, output, &inputs));
+    assert_eq!(&args[..4], ["/nologo", "/Feout/program.exe", "program.obj", "yuniruntime.lib"]);
+    assert_eq!(args[4], "/link");
+    assert!(args.contains(&"kernel32.lib".to_string()));
+    assert!(args.contains(&"ws2_32.lib".to_string()));
+
+    let clang = Linker::from_program(PathBuf::from("clang"));
+    let args = strings(WINDOWS_GNU.link_args(&clang, output, &inputs));
+    assert_eq!(&args[..4], ["-o", "out/program.exe", "program.obj", "yuniruntime.lib"]);
+    assert!(args.contains(&"-lkernel32".to_string()));
+    assert!(!args.contains(&"-lpthread".to_string()));
+
+    let args = strings(LINUX.link_args(&clang, Path::new("program"), &[Path::new("program.o")]));
+    assert_eq!(args, ["-o", "program", "program.o", "-lm", "-lpthread", "-lstdc++"]);
+    let args = strings(MACOS.link_args(&clang, Path::new("program"), &[Path::new("program.o")]));
+    assert!(args.ends_with(&["-framework".to_string(), "System".to_string(), "-lc++".to_string()]));
+
+    // 外部関数の宣言で指定したライブラリ
+    let libraries = ["z".to_string(), "sqlite3".to_string()];
+    assert_eq!(strings(LINUX.library_args(&clang, &libraries)), ["-lz", "-lsqlite3"]);
+    assert_eq!(strings(WINDOWS_MSVC.library_args(&clang_cl, &libraries)), ["z.lib", "sqlite3.lib"]);
+}
+
+#[test]
+fn test_gc_sections_args() {
+    let clang = Linker::from_program(PathBuf::from("clang"));
+    let clang_cl = Linker::from_program(PathBuf::from("clang-cl.exe"));
+    assert_eq!(strings(LINUX.gc_sections_args(&clang)), ["-Wl,--gc-sections"]);
+    assert_eq!(strings(WINDOWS_GNU.gc_sections_args(&clang)), ["-Wl,--gc-sections"]);
+    assert_eq!(strings(MACOS.gc_sections_args(&clang)), ["-Wl,-dead_strip"]);
+    assert_eq!(strings(WINDOWS_MSVC.gc_sections_args(&clang_cl)), ["/OPT:REF"]);
+
+    // リンカが分からないターゲットでは何も渡さない
+    let other = Target { os: TargetOs::Other, env: TargetEnv::Gnu };
+    assert!(other.gc_sections_args(&clang).is_empty());
+}
+
+#[test]
+fn test_stack_size_args() {
+    let clang = Linker::from_program(PathBuf::from("clang"));
+    let clang_cl = Linker::from_program(PathBuf::from("clang-cl.exe"));
+    assert_eq!(strings(LINUX.stack_size_args(&clang, 67108864)), ["-Wl,-z,stack-size=67108864"]);
+    assert_eq!(strings(WINDOWS_GNU.stack_size_args(&clang, 67108864)), ["-Wl,--stack,67108864"]);
+    assert_eq!(strings(WINDOWS_MSVC.stack_size_args(&clang_cl, 67108864)), ["/STACK:67108864"]);
+    assert_eq!(strings(MACOS.stack_size_args(&clang, 67108864)), ["-Wl,-stack_size,0x4000000"]);
+    // ld64はページの大きさの倍数しか受け付けないため切り上げる
+    assert_eq!(strings(MACOS.stack_size_args(&clang, 100000)), ["-Wl,-stack_size,0x1c000"]);
+
+    let other = Target { os: TargetOs::Other, env: TargetEnv::Gnu };
+    assert!(other.stack_size_args(&clang, 67108864).is_empty());
+}